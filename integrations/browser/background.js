@@ -0,0 +1,58 @@
+// "Set as wallpaper" for images, sent to Aether-Desk over native messaging,
+// or over the local API when the native messaging host isn't registered.
+const HOST = "dev.aether_desk";
+const MENU_ID = "aether-desk-set-wallpaper";
+
+const api = globalThis.browser ?? globalThis.chrome;
+
+api.runtime.onInstalled.addListener(() => {
+  api.contextMenus.create({
+    id: MENU_ID,
+    title: "Set as wallpaper",
+    contexts: ["image"],
+  });
+});
+
+api.contextMenus.onClicked.addListener(async (info, tab) => {
+  if (info.menuItemId !== MENU_ID || !info.srcUrl) {
+    return;
+  }
+  const message = { action: "set_image", url: info.srcUrl, page: info.pageUrl ?? tab?.url };
+  try {
+    const reply = await send(message);
+    if (!reply.ok) {
+      throw new Error(reply.error ?? "Aether-Desk refused the image");
+    }
+    notify("Wallpaper set", reply.wallpaper?.name ?? info.srcUrl);
+  } catch (error) {
+    notify("Couldn't set the wallpaper", String(error.message ?? error));
+  }
+});
+
+// Native messaging first; the local API needs its token from the options page
+async function send(message) {
+  try {
+    return await api.runtime.sendNativeMessage(HOST, message);
+  } catch (nativeError) {
+    const { token, port } = await api.storage.local.get({ token: "", port: 47932 });
+    if (!token) {
+      throw new Error(`Native messaging failed (${nativeError.message}). Run "aether-desk browser install" or set the API token in the options.`);
+    }
+    const response = await fetch(`http://127.0.0.1:${port}/api/browser/image`, {
+      method: "POST",
+      headers: { "Authorization": `Bearer ${token}`, "Content-Type": "application/json" },
+      body: JSON.stringify({ url: message.url, page: message.page }),
+    });
+    const body = await response.json();
+    return response.ok ? { ok: true, wallpaper: body } : { ok: false, error: body.error };
+  }
+}
+
+function notify(title, message) {
+  api.notifications.create({
+    type: "basic",
+    iconUrl: "icon.png",
+    title,
+    message,
+  });
+}
@@ -0,0 +1,13 @@
+const api = globalThis.browser ?? globalThis.chrome;
+const token = document.getElementById("token");
+const port = document.getElementById("port");
+
+api.storage.local.get({ token: "", port: 47932 }).then((saved) => {
+  token.value = saved.token;
+  port.value = saved.port;
+});
+
+document.getElementById("save").addEventListener("click", async () => {
+  await api.storage.local.set({ token: token.value.trim(), port: Number(port.value) || 47932 });
+  document.getElementById("status").textContent = "Saved";
+});
@@ -0,0 +1,89 @@
+"""Aether-Desk desktop wallpapers from Home Assistant.
+
+Talks to the local API of Aether-Desk: its scenes become Home Assistant
+scenes, and the push_image service shows any image URL on the desktop.
+"""
+from __future__ import annotations
+
+from urllib.parse import quote
+
+import aiohttp
+import voluptuous as vol
+
+from homeassistant.const import CONF_HOST, CONF_PORT, CONF_TOKEN, Platform
+from homeassistant.core import HomeAssistant, ServiceCall
+from homeassistant.exceptions import HomeAssistantError
+from homeassistant.helpers import config_validation as cv, discovery
+from homeassistant.helpers.aiohttp_client import async_get_clientsession
+from homeassistant.helpers.typing import ConfigType
+
+DOMAIN = "aether_desk"
+DEFAULT_PORT = 47932
+TIMEOUT = aiohttp.ClientTimeout(total=60)
+
+CONFIG_SCHEMA = vol.Schema(
+    {
+        DOMAIN: vol.Schema(
+            {
+                vol.Required(CONF_HOST): cv.string,
+                vol.Optional(CONF_PORT, default=DEFAULT_PORT): cv.port,
+                vol.Required(CONF_TOKEN): cv.string,
+            }
+        )
+    },
+    extra=vol.ALLOW_EXTRA,
+)
+
+
+class AetherDesk:
+    """Client of one desktop's local API."""
+
+    def __init__(self, hass: HomeAssistant, host: str, port: int, token: str) -> None:
+        self._hass = hass
+        self._base = f"http://{host}:{port}/api/homeassistant"
+        self._token = token
+
+    async def request(self, method: str, path: str = "", payload: dict | None = None) -> dict:
+        """Call an endpoint and return its JSON answer."""
+        session = async_get_clientsession(self._hass)
+        try:
+            async with session.request(
+                method,
+                self._base + path,
+                json=payload,
+                headers={"Authorization": f"Bearer {self._token}"},
+                timeout=TIMEOUT,
+            ) as response:
+                body = await response.json(content_type=None)
+        except (aiohttp.ClientError, TimeoutError) as error:
+            raise HomeAssistantError(f"Aether-Desk is not reachable: {error}") from error
+        if response.status != 200:
+            raise HomeAssistantError(body.get("error", f"Aether-Desk answered {response.status}"))
+        return body
+
+    async def activate_scene(self, scene: str) -> None:
+        """Activate a scene by name."""
+        await self.request("POST", "/scenes/" + quote(scene, safe=""))
+
+    async def push_image(self, url: str) -> None:
+        """Show an image on the desktop."""
+        await self.request("POST", "/image", {"url": url})
+
+
+async def async_setup(hass: HomeAssistant, config: ConfigType) -> bool:
+    """Set up the client, the services and the scenes."""
+    conf = config[DOMAIN]
+    client = AetherDesk(hass, conf[CONF_HOST], conf[CONF_PORT], conf[CONF_TOKEN])
+    hass.data[DOMAIN] = client
+
+    async def push_image(call: ServiceCall) -> None:
+        await client.push_image(call.data["url"])
+
+    async def activate_scene(call: ServiceCall) -> None:
+        await client.activate_scene(call.data["scene"])
+
+    hass.services.async_register(DOMAIN, "push_image", push_image, schema=vol.Schema({vol.Required("url"): cv.url}))
+    hass.services.async_register(DOMAIN, "activate_scene", activate_scene, schema=vol.Schema({vol.Required("scene"): cv.string}))
+
+    hass.async_create_task(discovery.async_load_platform(hass, Platform.SCENE, DOMAIN, {}, config))
+    return True
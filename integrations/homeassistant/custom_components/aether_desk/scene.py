@@ -0,0 +1,43 @@
+"""Aether-Desk scenes as Home Assistant scenes."""
+from __future__ import annotations
+
+from typing import Any
+
+from homeassistant.components.scene import Scene
+from homeassistant.core import HomeAssistant
+from homeassistant.exceptions import PlatformNotReady
+from homeassistant.helpers.entity_platform import AddEntitiesCallback
+from homeassistant.helpers.typing import ConfigType, DiscoveryInfoType
+
+from . import DOMAIN, AetherDesk
+
+
+async def async_setup_platform(
+    hass: HomeAssistant,
+    config: ConfigType,
+    async_add_entities: AddEntitiesCallback,
+    discovery_info: DiscoveryInfoType | None = None,
+) -> None:
+    """Add a scene for every scene set up in Aether-Desk."""
+    if discovery_info is None:
+        return
+    client: AetherDesk = hass.data[DOMAIN]
+    try:
+        info = await client.request("GET")
+    except Exception as error:
+        raise PlatformNotReady(str(error)) from error
+    async_add_entities(AetherDeskScene(client, name) for name in info["scenes"])
+
+
+class AetherDeskScene(Scene):
+    """Scene set up in Aether-Desk."""
+
+    def __init__(self, client: AetherDesk, name: str) -> None:
+        self._client = client
+        self._scene = name
+        self._attr_name = f"Aether-Desk {name}"
+        self._attr_unique_id = f"{DOMAIN}_{name}"
+
+    async def async_activate(self, **kwargs: Any) -> None:
+        """Activate the scene."""
+        await self._client.activate_scene(self._scene)
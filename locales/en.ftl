@@ -0,0 +1,818 @@
+# English UI messages
+# Placeholders use Fluent syntax: { $name }
+
+accent-sync = Match the desktop accent color
+accent-sync-hint = Sets the Windows, KDE Plasma or GNOME accent color to the most vivid color of each static wallpaper (GNOME gets its closest named accent). Turning it off puts back the accent you had before.
+active-processes = Active Processes: { $count }
+adaptive-quality = Adaptive Quality
+adaptive-quality-hint = Video wallpapers switch to the lower frame rate and resolution after 15 seconds above the CPU limit and back to full quality after 30 seconds below the restore level. Other wallpaper types keep their quality.
+add-change-hook = Add Command
+add-peer = Add Computer
+add-scene = Add Scene
+add-schedule-item = Add Schedule Item
+add-user = Add User
+add-wallpaper = Add Wallpaper
+add-webhook = Add Webhook
+add-widget = Add Widget
+adjust-blur = Blur
+adjust-brightness = Brightness
+adjust-contrast = Contrast
+adjust-hint = Kept for this image without changing the file; shown in the preview and the next time it's applied
+adjust-reset = Reset
+adjust-saturation = Saturation
+adjust-vignette = Vignette
+adjustments = Adjustments
+all-monitors = All monitors
+allowed-content = Allowed content:
+animated-image = Animated Image
+animated-image-hint = Animated GIF, APNG and WebP files play at their own frame timing; the last frame stays on screen once the loops are done.
+animated-image-loop-forever = Loop forever
+animated-image-loops = Loops
+animated-image-speed = Speed
+animated-static = Animated Static
+api-allow-remote = Allow access from other devices on the network
+api-enabled = Enable the local HTTP API
+api-hint = Only reachable from this computer, e.g. { $url }. Send the token as "Authorization: Bearer <token>".
+api-key = API Key:
+api-keys-stored-in = API keys are stored in: { $backend }
+api-port = Port:
+api-remote-hint = Open { $url } on a phone on the same network to control the wallpaper. Anyone on the network who has the token can use the API.
+api-token = Token:
+apply = Apply
+apply-newest-download = Apply the newest download as wallpaper
+apply-selected = Apply Selected
+apply-to-workspace = Apply to This Workspace
+apply-to-workspace-hint = Show this wallpaper whenever the current workspace or virtual desktop is active. Other workspaces keep the usual wallpaper.
+applying-wallpaper = Applying wallpaper…
+aspect-ratio-tolerance = Aspect ratio tolerance
+attach = Attach
+audio = Audio
+audio-overlay = Audio Overlay
+audio-overlay-bar-count = Bars
+audio-overlay-bar-height = Bar height
+audio-overlay-bars = Spectrum bars
+audio-overlay-color = Bar color
+audio-overlay-device = Recording device
+audio-overlay-device-default = Default output
+audio-overlay-device-hint = A PulseAudio source on Linux, or a DirectShow audio device such as "Stereo Mix" on Windows. Leave empty for the default.
+audio-overlay-enabled = Draw what's playing over the wallpaper
+audio-overlay-hint = Spectrum bars and a pulsing vignette follow the system's audio, over a static wallpaper filling every monitor or over a video wallpaper.
+audio-overlay-sensitivity = Sensitivity
+audio-overlay-vignette = Pulsing vignette
+auto-change = Auto-Change
+auto-change-enabled = Change the wallpaper on a timer
+auto-change-folder = Folder:
+auto-change-hint = Shows the next wallpaper of the folder every interval while the background service runs. The wait starts over whenever the wallpaper changes, and wallpapers from elsewhere, like scheduled ones, are kept until one from the folder is back.
+auto-change-interval = Change every
+auto-change-match-resolution = Prefer images matching the monitor
+auto-change-match-resolution-hint = Pick images with the monitor's aspect ratio and at least about its size when the folder has any
+auto-change-next = Next Wallpaper Now
+auto-change-no-folder = None yet; pick one to start changing
+auto-change-palette = Keep auto-change within:
+auto-change-palette-hint = Pick the next static wallpaper from images whose dominant colors belong to this family, for a coherent desktop. Other images are only used when the folder has none.
+auto-change-tolerance = Tolerance
+auto-download = Auto-Download
+auto-download-enabled = Automatically download wallpapers
+auto-pause = Auto-Pause
+auto-tagging = Automatic Tagging
+auto-tagging-enabled = Tag wallpapers added to the gallery
+auto-tagging-hint = A local CLIP model labels images (nature, city, abstract, dark, minimal, …) for gallery search and "More Like This". Needs a build with the auto-tagging feature and a model folder.
+background-color = Background Color:
+background-service = Background Service
+battery-saver = Show a still frame on battery power
+battery-saver-active = Battery saver: showing a still frame
+battery-saver-hint = Animated wallpapers are replaced by a frame captured from them and start again when the charger is plugged in. At 100% this happens whenever the battery is in use.
+battery-threshold = Only below battery charge
+battery-widget-interval = Widget updates on battery
+benchmark = Benchmark
+benchmark-average-lowest = { $average } average, { $lowest } lowest
+benchmark-average-peak = { $average } average, { $peak } peak
+benchmark-bottleneck-cpu = The system CPU was busy while the wallpaper ran
+benchmark-bottleneck-fps = The app's frame rate dropped while the wallpaper ran
+benchmark-bottleneck-gpu = The GPU was busy while the wallpaper ran
+benchmark-bottleneck-limit = The player went over the resource limits: { $reason }
+benchmark-duration = Duration
+benchmark-fair = Fair: uses a noticeable share of this machine
+benchmark-fps = App frame rate
+benchmark-good = Good: runs without a noticeable cost
+benchmark-gpu = GPU load
+benchmark-hint = Run the wallpaper on the desktop for a while, measure what it costs, then go back
+benchmark-not-reported = Not reported
+benchmark-player-cpu = Player CPU
+benchmark-player-memory = Player memory (peak)
+benchmark-poor = Poor: too heavy for this machine
+benchmark-rating = Suitability
+benchmark-report = Benchmark Report
+benchmark-running = Benchmarking { $wallpaper }
+benchmark-seconds = { $seconds } s
+benchmark-system-cpu = System CPU
+benchmark-throttled = The wallpaper was paused or lowered during the run, so it may cost more than measured
+benchmark-wallpaper = Wallpaper
+bind-monitors = Use With These Monitors
+bind-monitors-hint = Switch to this profile whenever the monitors connected now are connected again, e.g. when docking
+bottom-left = Bottom Left
+bottom-right = Bottom Right
+browse = Browse...
+browser-extension = Browser Extension
+browser-extension-hint = The extension in integrations/browser adds "Set as wallpaper" to the menu of images on the web. Register it here so the browser can reach Aether-Desk; Firefox needs nothing else.
+browser-extension-ids = Chrome/Edge extension IDs:
+browser-extension-ids-hint = From the browser's extensions page
+browser-extension-install = Register with Browsers
+browser-extension-installed = Registered with { $count ->
+    [one] one browser location
+   *[other] { $count } browser locations
+}
+browser-extension-reinstall = Register Again
+browser-extension-uninstall = Unregister
+builtin-no-file = Drawn by Aether-Desk, no file needed
+cache-max-age = Remove unused after (days, 0 = never):
+cache-max-size = Max size (MB, 0 = unlimited):
+cache-protected-hint = Favorites, scheduled and current wallpapers are never removed.
+cache-usage = Using { $mb } MB in { $files } files
+calendar = Calendar
+calendar-days-ahead = Days ahead
+calendar-hint = Upcoming events from subscribed calendars appear in the Calendar widget and can trigger scheduled wallpapers.
+calendar-refresh = Refresh every
+calendar-urls = Calendar addresses
+calendar-urls-hint = One ICS, webcal or CalDAV export address per line
+cancel = Cancel
+change-hooks = Change Hooks
+change-hooks-hint = Commands run after every wallpaper change, with AETHER_WALLPAPER_PATH, AETHER_WALLPAPER_TYPE and AETHER_WALLPAPER_MONITOR set (e.g. wal -i "$AETHER_WALLPAPER_PATH").
+chat-bot = Chat Bot
+chat-bot-enabled = Control the wallpaper from a Telegram or Discord bot
+chat-bot-hint = Message the bot an image to show it, or a command such as next, pause, resume, undo or status. Only the users listed below are answered.
+chat-bot-platform = Platform
+chat-bot-token = Bot token
+chat-bot-user-hint = User ID, logged when someone not allowed messages the bot
+chat-bot-users = Allowed user IDs
+check-error = Error
+check-fix = Fix: { $fix }
+check-ok = OK
+check-warning = Warning
+clean-up-now = Clean Up Now
+clear-cache = Clear Cache
+clock = Clock
+close-preview = Close Preview
+color-palette = Color Palette
+color-temperature = Color Temperature
+color-temperature-add = Add Time
+color-temperature-enabled = Tint static wallpapers by the time of day
+color-temperature-hint = Like a night light, static wallpapers turn warmer in the evening. 6500 K leaves the image as it is; lower is warmer, higher cooler. Temperatures are blended between the times and saved with each profile.
+color-temperature-now = Now: { $kelvin } K
+color-temperature-update = Redraw every
+colors-like = Wallpapers with colors like { $name }
+config-history = Configuration History
+config-history-close = Close
+config-history-empty = No versions yet
+config-history-enabled = Keep a history of the configuration
+config-history-hint = Every save of the configuration, schedule and widget layout is kept in a git repository in the configuration folder.
+config-history-refresh = Refresh
+config-history-restore = Restore This Version
+config-history-restored = Restored { $file } from { $version }
+config-history-view = View this file as it was
+config-history-viewing = { $file } at { $version }
+config-reloaded = Configuration reloaded after it was edited
+confirm = Confirm
+content-filter = Content Filter
+copy = Copy
+copy-log = Copy Shown Lines
+copy-remote-link = Copy Link with Token
+cpu-limit = CPU limit (100% = one core)
+cpu-usage = CPU Usage: { $percent }%
+crash-report = Aether-Desk Crashed
+crash-report-hint = Aether-Desk closed unexpectedly last time. A report with the error, recent log lines and a summary of your settings was saved. Attaching it to a bug report helps fix the problem.
+crop-portrait = Crop for portrait monitors
+crop-portrait-hint = Fill portrait monitors with landscape images by cropping around their most detailed part, instead of showing them letterboxed.
+current-wallpaper = Current wallpaper: { $name }
+custom = Custom
+custom-widget-unsupported = Custom widget settings are not supported in this version.
+daemon-hint = Wallpapers keep running after this window is closed.
+daemon-running = Running (PID { $pid }, up { $minutes } min)
+dark = Dark
+date-format = Date Format:
+delete = Delete
+description = Description:
+desktop-notifications = Show errors as desktop notifications
+detach = Detach
+detach-hint = Open in a separate window
+detached-window = Shown in a separate window.
+detail = Detail:
+details-author = Author: { $author }
+details-builtin-settings = Its settings are on the Wallpaper tab
+details-description = Description: { $description }
+details-name = Name: { $name }
+details-path = Path: { $path }
+details-tags = Tags: { $tags }
+details-type = Type: { $type }
+details-url = URL: { $url }
+details-usage = Average use while running: { $usage }
+details-usage-static = Resource use: none once set
+details-usage-unknown = Resource use: not measured yet
+details-version = Version: { $version }
+diagnostics = Diagnostics
+diagnostics-configuration = Configuration
+diagnostics-desktop = Desktop
+diagnostics-hint = Checks the external tools, desktop integration, permissions and configuration files Aether-Desk needs. The same checks run with `aether-desk doctor`.
+diagnostics-permissions = Permissions
+diagnostics-tools = Tools
+dismiss = Dismiss
+download-cache = Download Cache
+download-now = Download Now
+edit = Edit
+edit-schedule-item = Edit Schedule Item
+edit-widget = Edit Widget
+enabled = Enabled
+enforce-resource-limits = Pause or stop players that use too much
+error = Error: { $error }
+error-add-schedule-item = Failed to add schedule item
+error-add-widget = Failed to add widget
+error-apply-wallpaper = Failed to apply wallpaper
+error-browser-extension = Browser extension registration failed
+error-clean-up-cache = Failed to clean up download cache
+error-clear-cache = Failed to clear download cache
+error-config-history = Configuration history failed
+error-delete-profile = Failed to delete profile
+error-delete-theme = Failed to delete theme
+error-disable-plugin = Failed to disable plugin
+error-download-wallpapers = Failed to download wallpapers
+error-edited-config = The edited configuration can't be read; keeping the previous settings
+error-enable-plugin = Failed to enable plugin
+error-export-pack = Failed to export the wallpaper pack
+error-export-theme = Failed to export theme
+error-generate-wallpaper = Failed to generate wallpapers
+error-import = Failed to import from {$tool}
+error-import-theme = Failed to import theme
+error-install-pack = Failed to install the wallpaper pack
+error-load-configuration = Failed to load configuration
+error-load-plugins = Failed to load plugins
+error-load-schedule = Failed to load schedule
+error-load-widgets = Failed to load widgets
+error-next-wallpaper = Failed to change to the next wallpaper
+error-no-monitors = The connected monitors can't be detected
+error-no-path = Select a file for the { $type } wallpaper first
+error-no-url = Enter a URL for the web wallpaper first
+error-no-workspace = The active workspace can't be detected on this desktop
+error-open-crash-report = Failed to open the crash report
+error-read-api-token = Failed to read API token
+error-read-log = Failed to read the log: { $error }
+error-reload-config = Background service could not reload the configuration
+error-reload-schedule = Background service could not reload the schedule
+error-remove-schedule-item = Failed to remove schedule item
+error-remove-widget = Failed to remove widget
+error-resolve-orphans = Failed to handle the players from the previous run
+error-revert-wallpaper = Failed to revert the wallpaper
+error-save-config = Failed to save config
+error-save-generated = Failed to save the generated wallpaper
+error-save-profile = Failed to save profile
+error-save-schedule = Failed to save schedule
+error-save-theme = Failed to save theme
+error-save-widgets = Failed to save widgets
+error-set-lock-screen = Failed to set lock screen wallpaper
+error-start-daemon = Failed to start daemon
+error-start-widget-manager = Failed to start widget manager
+error-stop-daemon = Failed to stop daemon
+error-stop-wallpaper = Failed to stop wallpaper
+error-store-api-key = Failed to store API key
+error-store-api-token = Failed to store API token
+error-store-bot-token = Failed to store bot token
+error-store-calendar = Failed to store the calendar addresses
+error-store-sync-key = Failed to store sync key
+error-switch-profile = Failed to switch profile
+error-undo-wallpaper = Failed to undo the wallpaper change
+error-update-autostart = Failed to update start with system
+error-update-live-earth = Failed to update Live Earth
+error-update-schedule-item = Failed to update schedule item
+error-update-widget = Failed to update widget
+error-update-widget-position = Failed to update widget position
+error-upload-qr = Couldn't make the QR code
+event = Event:
+export = Export...
+export-pack = Export Pack…
+export-pack-hint = Save the { $count } library wallpapers shown, with their schedule items, as one file to share
+export-palette = Export the palette of static wallpapers
+export-palette-hint = Writes pywal-compatible colors, colors.json, colors.sh, colors.Xresources and colors.css before the change hooks run, so terminal and bar themes can follow the wallpaper.
+favorite = Favorite
+filter-animations = Animations
+filter-images = Images
+filter-shaders = Shaders
+filter-themes = Themes
+filter-videos = Videos
+filter-windows-themes = Windows themes
+fit-center = Center
+fit-crop = Crop
+fit-fill = Fill
+fit-fit = Fit
+fit-mode = Fit Mode:
+fit-stretch = Stretch
+fit-tile = Tile
+focus-cover-above = Turn on above
+focus-effect = Effect
+focus-effect-blur = Blur
+focus-effect-dim = Dim
+focus-effect-dim-and-blur = Dim and blur
+focus-fade = Fade
+focus-mode = Focus Mode
+focus-mode-enabled = Tone down the wallpaper while windows cover the desktop
+focus-mode-hint = A static wallpaper fades to a dimmed or blurred copy once windows cover this much of the desktop, and back when less than the lower share is covered. Spanned wallpapers and monitors with images of their own are left as they are.
+focus-pause-animated = Pause animated wallpapers
+focus-strength = Strength
+focus-uncover-below = Turn off below
+font-size = Font Size:
+frame-rate-fixed = Fixed
+frame-rate-half-refresh = Half monitor refresh
+frame-rate-hint = Video and shader wallpapers are drawn at no more than this frame rate, which saves power on laptops. Applies from the next wallpaper change.
+frame-rate-limit = Frame Rate Limit
+frame-rate-match-refresh = Match monitor refresh
+frame-rate-own-limit = Use its own limit
+frame-rate-unlimited = Unlimited
+gallery-all = All
+gallery-builtin = Built-in
+gallery-library = Library
+general = General
+generate = Generate
+generate-apply = Save and Apply
+generate-candidates = Images:
+generate-for-monitor = For monitor:
+generate-hint = Describe a wallpaper and an image generation service creates a few to pick from.
+generate-negative-prompt = Leave out:
+generate-prompt = A misty pine forest at dawn, soft light
+generate-save = Save to Library
+generate-saved-size = Saved at { $width }x{ $height }
+generate-wallpaper = Generate a Wallpaper
+generated-saved = Generated wallpaper saved to the library
+generating = Generating, this can take a few minutes…
+generation-api-key = API key:
+generation-endpoint = Address:
+generation-max-edge = Largest edge generated:
+generation-model = Model:
+generation-model-comfyui-hint = ComfyUI needs the checkpoint file to load, e.g. sd_xl_base_1.0.safetensors
+generation-provider = Service:
+generation-provider-hosted = Hosted API (OpenAI compatible)
+generation-service = Generation Service
+generation-steps = Steps:
+gpu = Graphics Card
+gpu-default = System default
+gpu-discrete = Discrete GPU (better quality)
+gpu-hint = Applies to players started from the next wallpaper change.
+gpu-integrated = Integrated GPU (saves battery)
+gpu-preference = Render shader and web wallpapers on
+gpu-single = Only one GPU was found, so this setting has no effect here.
+ha-scene-hide-widgets = Hide
+ha-scene-keep-wallpaper = Keep Wallpaper
+ha-scene-profile = Profile:
+ha-scene-show-widgets = Show
+ha-scene-unchanged = Unchanged
+ha-scene-wallpaper = Wallpaper:
+ha-scene-widgets = Widgets:
+ha-scenes = Home Assistant Scenes
+ha-scenes-hint = Scenes show up in Home Assistant through the aether_desk integration. Each switches a profile, applies a wallpaper and shows or hides the widgets, leaving unchanged what isn't set.
+hemisphere = Hemisphere:
+hide-upload-qr = Hide QR Code
+homepage = Homepage
+hours = hours
+idle-minutes = Away after
+import = Import...
+import-folder = Wallpaper folder…
+import-from = Import from:
+import-windows-theme = Windows theme…
+imported-from = Imported from { $tool }: { $count ->
+    [one] one schedule item
+   *[other] { $count } schedule items
+} added
+install-pack = Install Pack…
+interval = Interval:
+interval-minutes = Interval (minutes):
+keep = Keep
+keep-orphans = Keep them
+keep-orphans-hint = Keep them playing; they stop with the background service
+ken-burns-duration = Each move
+ken-burns-hint = Slowly pans and zooms across the image, easing from one part of it to the next. Auto-change turns a folder of photos into a gentle ambient wallpaper.
+ken-burns-zoom = Zoom
+lan-sync = LAN Sync
+lan-sync-enabled = Sync the wallpaper with other computers on the network
+lan-sync-hint = Computers with the same sync key find each other and show the same wallpaper. Files are sent to the others when the wallpaper changes.
+lan-sync-key = Sync key
+lan-sync-key-hint = Set the same key on every computer. It's sent over the network unencrypted, so don't reuse a password.
+lan-sync-name = Computer name
+lan-sync-peers = Computers that can't be found automatically (host:port)
+lan-sync-receive = Show changes from others
+lan-sync-send = Send my changes
+language = Language:
+large = Large
+last-run = Last run: { $time }
+last-update = Last update: { $time }
+level-error = Error
+level-info = Info
+level-warning = Warning
+light = Light
+limit-action = When over a limit
+limit-action-kill = Stop the player
+limit-action-pause = Pause the player
+live-earth = Live Earth
+live-earth-enabled = Use live satellite imagery as wallpaper
+load = Load
+loading = Loading…
+local-api = Local API
+location = Location:
+lock-screen-set = Lock screen wallpaper set
+log-empty = No log lines to show
+log-file = File: { $path }
+log-level = Log level:
+log-level-debug = Debug
+log-level-error = Errors
+log-level-hint = Messages less severe than this aren't written to the log files. Raise it to Debug when reporting a problem.
+log-level-info = Info
+log-level-trace = Trace
+log-level-warn = Warnings
+log-search = Filter text
+log-show-all = Everything
+log-show-debug = Debug and above
+log-show-error = Errors only
+log-show-info = Info and above
+log-show-warn = Warnings and errors
+log-source-app = Window
+log-source-daemon = Background service
+logs = Logs
+loosen-content-filter = Loosen the content filter? Up to { $purity } content may be downloaded.
+lower-above-cpu = Lower above CPU
+lower-quality-when-busy = Lower video quality while the system is busy
+match-monitor-aspect-ratio = Match monitor aspect ratio
+medium = Medium
+memory-limit = Memory limit
+memory-used = Memory Used: { $mb } MB
+minimum-size = Minimum size (x monitor resolution)
+minutes = minutes
+mirror-monitors = Same Wallpaper Everywhere
+mirror-monitors-hint = Show every wallpaper alike on all monitors, e.g. for presentations. The monitors' own wallpapers come back when you turn it off.
+monitor-image = Shows {$path}
+monitor-image-clear = Clear
+monitor-image-wallpaper = Shows the static wallpaper
+monitor-images-clear-all = Clear All
+monitor-images-disconnected = { $count ->
+    [one] One disconnected monitor keeps its image
+   *[other] { $count } disconnected monitors keep their image
+}
+monitor-layout = Monitor Layout
+monitor-layout-dragging = Drop {$name} on a monitor
+monitor-layout-hint = Drag an image onto a monitor to show it there instead of the static wallpaper. Right-click a monitor to clear it.
+monitor-layout-no-images = Add static images to the gallery or your favorites to drag them here.
+monitor-layout-none = No monitors were detected.
+monitor-profile = {$monitors}: {$name}
+monitor-profiles = Profiles for monitor setups:
+monitor-wallpaper = {$monitor}: {$name} ({$type})
+monitor-wallpapers = Wallpapers on single monitors
+more-like = Wallpapers like { $name }
+more-like-this = More Like This
+motion-effect = Motion
+motion-fps = Frames per second
+motion-ken-burns = Ken Burns
+motion-parallax = Parallax
+name = Name:
+no-file-selected = No file selected
+no-monitors-detected = No monitors detected; all resolutions will be accepted.
+no-plugins-installed = No plugins installed. Plugins will be available in a future release.
+no-profiles = No profiles yet.
+no-schedule-items = No schedule items. Add a new schedule item to automatically change wallpapers.
+no-wallpaper-running = No wallpaper running
+no-widgets-installed = No widgets installed. Add a new widget to display information on your desktop.
+not-running = Not running
+notes = Notes
+notes-content = Notes Content:
+nothing-to-import = Nothing to import from {$tool}
+nothing-to-redo = No wallpaper change to redo
+nothing-to-undo = No wallpaper change to undo
+notification-details = Details
+notification-hint = Fix: { $hint }
+notifications = Notifications
+notifications-clear = Clear All
+notifications-empty = No notifications
+notifications-unread = Notifications ({ $count })
+now-playing = Now Playing
+now-playing-blur = Background blur
+now-playing-cover = Show the cover in the middle
+now-playing-dim = Darken background
+now-playing-hint = Shows the album art of the track playing in your media player, through playerctl (MPRIS) on Linux and the media controls on Windows. Changes apply the next time the wallpaper is applied.
+now-playing-poll = Check the player every
+online-sources = Online Sources
+open-crash-report = Open Report
+orphans-found = Wallpaper players left running by a previous run: { $count }
+pack-exported = Exported { $count ->
+    [one] one wallpaper
+   *[other] { $count } wallpapers
+} to { $name }
+pack-installed = Installed { $name }: { $count } wallpapers, { $schedule } schedule items added
+palette-family-any = Any colors
+palette-family-blue = Blues
+palette-family-cyan = Cyans
+palette-family-dark = Dark
+palette-family-green = Greens
+palette-family-neutral = Neutral grays
+palette-family-orange = Oranges
+palette-family-pink = Pinks
+palette-family-purple = Purples
+palette-family-red = Reds
+palette-family-yellow = Yellows
+palette-folder = Palette folder:
+parallax-depth-map = Move by depth
+parallax-depth-map-hint = Nearer parts move further. Uses <name>.depth.png next to the image, or estimates the depth from the image itself.
+parallax-hint = Animated static wallpapers are still images moved subtly as the mouse moves, drawn by Aether-Desk.
+parallax-strength = Strength
+particle-add-color = Add a color
+particle-background = Background:
+particle-colors = Colors:
+particle-colors-hint = Right-click a color to remove it
+particle-count = Particles
+particle-gravity = Gravity
+particle-gravity-hint = Positive values pull the particles down, negative values let them float up
+particle-hint = Changes apply the next time the particle wallpaper is applied.
+particle-links = Link distance
+particle-links-hint = Draws lines between particles closer than this share of the screen height; 0 draws none
+particle-mouse = Mouse:
+particle-mouse-attract = Attract
+particle-mouse-none = Ignore
+particle-mouse-radius = Mouse reach
+particle-mouse-repel = Repel
+particle-mouse-strength = Mouse strength
+particle-preset = Preset:
+particle-preset-bubbles = Bubbles
+particle-preset-constellation = Constellation
+particle-preset-custom = Custom
+particle-preset-fireflies = Fireflies
+particle-preset-snowfall = Snowfall
+particle-size = Size
+particle-speed = Speed
+particles = Particles
+pause-on-fullscreen = Pause animated wallpapers while a fullscreen app is focused
+pause-on-fullscreen-hint = Frees the GPU for games and videos. Detected on Windows, Hyprland, Sway and X11; other Wayland desktops don't report fullscreen windows.
+pause-on-idle = Pause animated wallpapers and widgets while you're away
+pause-on-idle-hint = Saves power when nobody is looking; any key press or mouse movement resumes them. Detected on Windows, GNOME, KDE and X11 (with xprintidle).
+per-provider-purity = Per provider (cannot exceed the global setting):
+per-run = Per run:
+performance = Performance
+performance-collecting = Collecting metrics…
+performance-cpu = System CPU
+performance-degraded = The app is running slowly or using a lot of memory, or the system is busy. Open the Performance panel below for details.
+performance-fps = Frame rate
+performance-frame-time = Frame time
+performance-load-time = { $ms } ms
+performance-load-times = Wallpaper load times
+performance-memory = App memory
+placement = Position
+placement-crop-hint = Drag the rectangle to choose what this monitor shows; drag its corner to resize.
+placement-no-monitors = No monitors were detected.
+placement-reset-crop = Reset Crop
+placement-span-crop = A spanned image shows a centered crop of the whole desktop.
+plugin-author = Author: { $author }
+plugin-description = Description: { $description }
+plugin-license = License: { $license }
+plugin-settings-coming-soon = Plugin settings will be available in a future release.
+plugins = Plugins
+portrait-only = Portrait only
+portrait-only-hint = Show only images taller than they are wide, for rotated monitors.
+position = Position:
+preview = Preview
+preview-failed = Preview failed: { $error }
+preview-unavailable = No preview is available for this wallpaper.
+procedural = Procedural
+procedural-colors = Colors:
+procedural-density = Density
+procedural-density-hint = How many rain columns, stars, living cells, growing spots or drifting particles there are, or how fine the plasma's waves are
+procedural-flow-field = Flow field
+procedural-game-of-life = Game of Life
+procedural-hint = Changes apply the next time a procedural wallpaper is applied.
+procedural-matrix-rain = Matrix rain
+procedural-new-seed = New
+procedural-pattern = Pattern:
+procedural-pick-pattern = Pick a pattern
+procedural-plasma = Plasma
+procedural-reaction-diffusion = Reaction-diffusion
+procedural-scheme-classic = Classic
+procedural-scheme-mono = Monochrome
+procedural-scheme-neon = Neon
+procedural-scheme-ocean = Ocean
+procedural-scheme-sunset = Sunset
+procedural-seed = Seed:
+procedural-seed-hint = The same seed grows the same pattern every time; 0 grows a different one every time
+procedural-speed = Speed
+procedural-starfield = Starfield
+process-paused = Paused (over limit)
+profile-active = { $name } (active)
+profiles = Profiles
+profiles-hint = A profile keeps the wallpaper settings, schedule and widgets, e.g. "Work" or "Gaming". Switching saves your changes into the active profile first. Favorites are shared by all profiles.
+purity-nsfw = NSFW
+purity-sfw = SFW
+purity-sketchy = Sketchy
+redo = Redo
+redo-hint = Go forward to the wallpaper you undid (Ctrl+Shift+Z)
+reduced-fps = Lowered frame rate
+reduced-scale = Lowered resolution scale
+refresh-gallery = Refresh Gallery
+refresh-interval-minutes = Refresh Interval (minutes):
+refresh-log = Refresh
+regenerate-token = Regenerate
+resolution-filter = Resolution Filter
+resolution-filter-enabled = Only download wallpapers that fit my monitors
+resource-limits = Resource Limits
+resource-limits-hint = The limits apply to each wallpaper player, such as mpv for videos. A player that stays over a limit for 15 seconds is paused or stopped and a warning is shown.
+resource-monitoring = Resource Monitoring
+resource-usage = Resource Usage
+restart-crashed-players = Restart wallpaper players that crash
+restore-below-cpu = Restore below CPU
+revert = Revert
+run-diagnostics = Run Checks
+running-wallpaper-usage = Running { $name }: { $usage }
+satellite-goes-east = Americas / Atlantic (GOES-East)
+satellite-goes-west = Pacific (GOES-West)
+satellite-himawari = Asia / Oceania (Himawari)
+save = Save
+save-as-profile = Save Current as Profile
+save-key = Save Key
+saved-themes = Saved Themes
+schedule-reloaded = Schedule reloaded after it was edited
+search-gallery = Search:
+search-gallery-hint = Name or tag, e.g. nature dark
+search-query = Search Query:
+secret-backend-encrypted-file = Encrypted file (keyring unavailable)
+secret-backend-keyring = OS keyring
+security = Security
+selected-wallpaper-details = Selected Wallpaper Details
+set-lock-screen = Set as Lock Screen
+settings = Settings
+shader = Shader
+show-all = Show All
+show-token = Show Token
+show-upload-qr = Show Upload QR Code
+show-week-numbers = Show Week Numbers:
+similar-colors = Similar Colors
+similar-colors-hint = Show the gallery's wallpapers with the closest dominant colors
+size = Size:
+slideshow = Slideshow
+slideshow-hint = Pick a folder; its images are shown one after another, changing with the transition. Changes apply the next time a slideshow is applied.
+slideshow-interval = Each image
+slideshow-order = Order:
+slideshow-order-modified = Oldest first
+slideshow-order-name = By name
+slideshow-order-shuffle = Shuffle
+small = Small
+smart-crop = Smart crop
+smart-crop-hint = Crop images that don't match the monitor around their most detailed part instead of their middle.
+source = Source:
+span-monitors = Span all monitors
+span-monitors-hint = Show one wallpaper across all monitors as a single desktop. Videos and shaders play in a window per monitor, kept in step.
+start-service = Start Service
+start-with-system = Start with system
+start-with-system-hint = Aether-Desk starts minimized when launched at login.
+static = Static
+status = Status
+status-backend = Backend: { $backend }
+status-monitors = Monitors
+status-no-events = No events yet
+status-no-processes = No helper processes running
+status-pid = PID { $pid }
+status-processes = Helper Processes
+status-recent-events = Recent Events
+status-scheduler-running = Running, { $enabled } of { $total } items enabled
+status-scheduler-stopped = Stopped
+stop = Stop
+stop-orphans = Stop them
+stop-service = Stop Service
+stored-securely = Stored securely
+switch-profile = Switch
+sync-lock-screen = Keep the lock screen in sync
+sync-lock-screen-hint = Also show static wallpapers set on the whole desktop on the lock screen (GNOME, and Windows with administrator rights)
+system-default = System default
+system-monitor = System Monitor
+system-theme = System
+tab-gallery = Gallery
+tab-generate = Generate
+tab-logs = Logs
+tab-plugins = Plugins
+tab-scheduler = Scheduler
+tab-settings = Settings
+tab-status = Status
+tab-wallpaper = Wallpaper
+tab-widgets = Widgets
+tag-library = Tag Library Now
+tagging-model-folder = Model folder:
+tagging-threshold = Least likelihood of a tag
+theme = Theme
+theme-accent = Accent
+theme-background = Background
+theme-label = Theme:
+theme-none-selected = Select a theme
+theme-panel = Panels
+theme-preview = Preview
+theme-preview-text = The quick brown fox jumps over the lazy dog.
+theme-save-as = Save As
+theme-text = Text
+theme-unsaved = Previewing unsaved theme changes
+theme-widget = Widgets
+theming-dry-run = Dry Run
+theming-dry-run-failed = Dry run failed: { $error }
+theming-dry-run-hint = Shows what recoloring the system for the last static wallpaper would write and run, without doing it.
+theming-no-files = No files are written; the tool writes its own.
+theming-none = Palette files only
+theming-runs = Runs: { $command }
+theming-templates = Templates
+theming-templates-builtin = Fill in templates
+theming-tool = Recolor with
+theming-tool-hint = Fill in pywal-style templates ({"{"}color0{"}"}, {"{"}background{"}"}, ...) from the templates folder into the palette folder, or let pywal or wallust recolor terminals, bars and GTK themes with their own templates.
+theming-writes = Writes { $path }
+time = Time:
+time-format = Time Format:
+top-left = Top Left
+top-right = Top Right
+transition = Transition
+transition-crossfade = Crossfade
+transition-duration = Duration
+transition-hint = How the desktop changes from one static wallpaper to the next. swww animates it itself; other desktops are shown a few frames in between.
+transition-morph = Morph
+transition-none = None
+transition-pixelate = Pixelate
+transition-slide = Slide
+transition-wipe = Wipe
+trigger = Trigger:
+trigger-calendar = Calendar event
+trigger-calendar-any = Any busy event
+trigger-calendar-title = Title contains
+trigger-interval = Interval
+trigger-system-event = System Event
+trigger-time = Time
+trigger-type = Trigger Type:
+try-on-desktop = Try on Desktop
+try-on-desktop-hint = Show the wallpaper on the desktop for { $seconds } seconds, then go back
+try-on-remaining = Reverting in { $seconds } s
+undo = Undo
+undo-hint = Go back to the wallpaper before the last change (Ctrl+Z)
+update-every-minutes = Update every (minutes):
+update-interval-seconds = Update Interval (seconds):
+update-now = Update Now
+update-profile = Update
+update-profile-hint = Replace this profile with the current settings
+upload-qr-hint = Scan with a phone on the same network to send a photo straight to the wallpaper. The code holds the API token, so don't share it.
+use-default-folder = Use Default
+use-pywal-folder = Use pywal Folder
+video = Video
+virtual-camera = Virtual Camera
+virtual-camera-device = Device
+virtual-camera-device-hint = The v4l2loopback device, created with: sudo modprobe v4l2loopback video_nr=10
+virtual-camera-enabled = Send the wallpaper to a virtual camera
+virtual-camera-hint = Streams the wallpaper through ffmpeg, to use it as a webcam or as an OBS scene background.
+virtual-camera-loopback = Webcam (v4l2loopback)
+virtual-camera-output = Output
+virtual-camera-size = Size
+virtual-camera-stream = OBS stream
+virtual-camera-stream-url = Stream address
+virtual-camera-stream-url-hint = Play this address in an OBS Media Source, with "Local File" unchecked
+wallhaven-api-key = Wallhaven API Key:
+wallpaper = Wallpaper
+wallpaper-auto-paused = Paused while a fullscreen app is focused
+wallpaper-focus-mode = Toned down while windows cover the desktop
+wallpaper-gallery = Wallpaper Gallery
+wallpaper-held = Paused on request
+wallpaper-idle-paused = Paused while you're away
+wallpaper-monitor = Monitor:
+wallpaper-monitor-hint = Show static, video and shader wallpapers on one monitor while the others keep theirs. Monitors without a wallpaper of their own stay empty.
+wallpaper-pack = Wallpaper pack
+wallpaper-path = Wallpaper Path:
+wallpaper-quality-reduced = Wallpaper quality lowered while the system is busy
+wallpaper-scheduler = Wallpaper Scheduler
+wallpaper-since = Shown since { $time }
+wallpaper-since-player = Shown since { $time }, player process { $pid }
+wallpaper-type = Wallpaper Type:
+wallpaper-usage = { $memory } MB memory, { $cpu }% CPU
+wallpaper-usage-gpu = { $memory } MB memory, { $cpu }% CPU, { $gpu }% GPU
+wallpaper-weather-effect = Weather drawn over the wallpaper: { $effect }
+watchdog = Crash Recovery
+watchdog-hint = A crashed video player is restarted after 2, 4, 8… seconds. When it keeps crashing, the last static wallpaper is shown instead.
+watchdog-max-restarts = Restarts before giving up
+weather = Weather
+weather-api-key = OpenWeatherMap API Key:
+weather-effects = Weather Effects
+weather-effects-enabled = Draw the weather over static wallpapers
+weather-effects-hint = Rain, snow or fog at the location is drawn over a static wallpaper filling every monitor, and taken away once the weather clears.
+weather-fog = Fog
+weather-intensity = Intensity
+weather-location = Location:
+weather-preview = Preview:
+weather-preview-off = Real weather
+weather-rain = Rain
+weather-refresh = Weather update interval
+weather-snow = Snow
+web = Web
+web-url = Web URL:
+webhook-apply-wallpaper = Apply wallpaper
+webhook-next-wallpaper = Next wallpaper
+webhook-start-scheduler = Start scheduler
+webhook-stop-scheduler = Stop scheduler
+webhook-stop-wallpaper = Stop wallpaper
+webhook-switch-profile = Switch profile
+webhooks = Webhooks
+webhooks-hint = Each webhook runs an action when called at its URL. Senders that can't set headers may append ?token=<token>.
+widget-preview = Widget Preview
+widget-type = Widget Type:
+widgets = Widgets
+widgets-reloaded = Widgets reloaded after they were edited
+workspace-wallpaper = {$workspace}: {$name}
+workspace-wallpapers = Wallpapers of workspaces
+zoom = Zoom
@@ -0,0 +1,817 @@
+# Spanish UI messages
+
+accent-sync = Igualar el color de acento del escritorio
+accent-sync-hint = Usa el color más vivo de cada fondo estático como color de acento de Windows, KDE Plasma o GNOME (GNOME recibe su acento con nombre más cercano). Al desactivarlo se recupera el acento que tenías antes.
+active-processes = Procesos activos: { $count }
+adaptive-quality = Calidad adaptativa
+adaptive-quality-hint = Los fondos de vídeo pasan a la tasa de fotogramas y resolución reducidas tras 15 segundos por encima del límite de CPU y vuelven a la calidad completa tras 30 segundos por debajo del nivel de restauración. Los demás tipos de fondo mantienen su calidad.
+add-change-hook = Añadir comando
+add-peer = Añadir equipo
+add-scene = Añadir escena
+add-schedule-item = Añadir programación
+add-user = Añadir usuario
+add-wallpaper = Añadir fondo
+add-webhook = Añadir webhook
+add-widget = Añadir widget
+adjust-blur = Desenfoque
+adjust-brightness = Brillo
+adjust-contrast = Contraste
+adjust-hint = Se guardan para esta imagen sin cambiar el archivo; se ven en la vista previa y la próxima vez que se aplique
+adjust-reset = Restablecer
+adjust-saturation = Saturación
+adjust-vignette = Viñeta
+adjustments = Ajustes
+all-monitors = Todos los monitores
+allowed-content = Contenido permitido:
+animated-image = Imagen animada
+animated-image-hint = Los GIF, APNG y WebP animados se reproducen con sus propios tiempos de fotograma; el último fotograma queda en pantalla al terminar las repeticiones.
+animated-image-loop-forever = Repetir sin fin
+animated-image-loops = Repeticiones
+animated-image-speed = Velocidad
+animated-static = Estático animado
+api-allow-remote = Permitir el acceso desde otros dispositivos de la red
+api-enabled = Activar la API HTTP local
+api-hint = Solo accesible desde este equipo, p. ej. { $url }. Envía el token como "Authorization: Bearer <token>".
+api-key = Clave de API:
+api-keys-stored-in = Las claves de API se guardan en: { $backend }
+api-port = Puerto:
+api-remote-hint = Abre { $url } en un teléfono de la misma red para controlar el fondo. Cualquiera en la red que tenga el token puede usar la API.
+api-token = Token:
+apply = Aplicar
+apply-newest-download = Aplicar la descarga más reciente como fondo
+apply-selected = Aplicar selección
+apply-to-workspace = Aplicar a este espacio de trabajo
+apply-to-workspace-hint = Muestra este fondo siempre que el espacio de trabajo o escritorio virtual actual esté activo. Los demás espacios mantienen el fondo habitual.
+applying-wallpaper = Aplicando fondo de pantalla…
+aspect-ratio-tolerance = Tolerancia de relación de aspecto
+attach = Acoplar
+audio = Audio
+audio-overlay = Superposición de audio
+audio-overlay-bar-count = Barras
+audio-overlay-bar-height = Altura de las barras
+audio-overlay-bars = Barras del espectro
+audio-overlay-color = Color de las barras
+audio-overlay-device = Dispositivo de grabación
+audio-overlay-device-default = Salida predeterminada
+audio-overlay-device-hint = Una fuente de PulseAudio en Linux, o un dispositivo de audio DirectShow como "Stereo Mix" en Windows. Déjalo vacío para el predeterminado.
+audio-overlay-enabled = Dibujar lo que suena sobre el fondo
+audio-overlay-hint = Las barras del espectro y una viñeta pulsante siguen el audio del sistema, sobre un fondo estático que cubre todos los monitores o sobre un fondo de vídeo.
+audio-overlay-sensitivity = Sensibilidad
+audio-overlay-vignette = Viñeta pulsante
+auto-change = Cambio automático
+auto-change-enabled = Cambiar el fondo con un temporizador
+auto-change-folder = Carpeta:
+auto-change-hint = Muestra el siguiente fondo de la carpeta en cada intervalo mientras el servicio en segundo plano está activo. La espera empieza de nuevo cada vez que cambia el fondo, y los fondos de otros sitios, como los programados, se mantienen hasta que vuelve uno de la carpeta.
+auto-change-interval = Cambiar cada
+auto-change-match-resolution = Preferir imágenes que encajen con el monitor
+auto-change-match-resolution-hint = Elige imágenes con la proporción del monitor y al menos su tamaño aproximado cuando la carpeta tiene alguna
+auto-change-next = Siguiente fondo ahora
+auto-change-no-folder = Ninguna todavía; elige una para empezar
+auto-change-palette = Mantener el cambio automático en:
+auto-change-palette-hint = Elige el siguiente fondo estático entre las imágenes cuyos colores dominantes pertenecen a esta familia, para un escritorio coherente. Las demás imágenes solo se usan si la carpeta no tiene ninguna.
+auto-change-tolerance = Tolerancia
+auto-download = Descarga automática
+auto-download-enabled = Descargar fondos automáticamente
+auto-pause = Pausa automática
+auto-tagging = Etiquetado automático
+auto-tagging-enabled = Etiquetar los fondos añadidos a la galería
+auto-tagging-hint = Un modelo CLIP local etiqueta las imágenes (naturaleza, ciudad, abstracto, oscuro, minimalista, …) para la búsqueda en la galería y "Más como este". Necesita una compilación con la función auto-tagging y una carpeta con el modelo.
+background-color = Color de fondo:
+background-service = Servicio en segundo plano
+battery-saver = Mostrar una imagen fija con batería
+battery-saver-active = Ahorro de batería: mostrando una imagen fija
+battery-saver-hint = Los fondos animados se sustituyen por un fotograma capturado de ellos y vuelven a iniciarse al conectar el cargador. Con el 100% ocurre siempre que se usa la batería.
+battery-threshold = Solo por debajo de la carga
+battery-widget-interval = Actualización de widgets con batería
+benchmark = Medir rendimiento
+benchmark-average-lowest = { $average } de media, { $lowest } como mínimo
+benchmark-average-peak = { $average } de media, { $peak } como máximo
+benchmark-bottleneck-cpu = La CPU del sistema estuvo ocupada mientras se ejecutaba el fondo
+benchmark-bottleneck-fps = Los fotogramas de la aplicación bajaron mientras se ejecutaba el fondo
+benchmark-bottleneck-gpu = La GPU estuvo ocupada mientras se ejecutaba el fondo
+benchmark-bottleneck-limit = El reproductor superó los límites de recursos: { $reason }
+benchmark-duration = Duración
+benchmark-fair = Aceptable: usa una parte apreciable de este equipo
+benchmark-fps = Fotogramas de la aplicación
+benchmark-good = Buena: funciona sin un coste apreciable
+benchmark-gpu = Carga de la GPU
+benchmark-hint = Ejecuta el fondo en el escritorio un rato, mide lo que consume y luego vuelve al anterior
+benchmark-not-reported = No disponible
+benchmark-player-cpu = CPU del reproductor
+benchmark-player-memory = Memoria del reproductor (máxima)
+benchmark-poor = Mala: demasiado pesado para este equipo
+benchmark-rating = Idoneidad
+benchmark-report = Informe de rendimiento
+benchmark-running = Midiendo { $wallpaper }
+benchmark-seconds = { $seconds } s
+benchmark-system-cpu = CPU del sistema
+benchmark-throttled = El fondo se pausó o se redujo durante la prueba, así que puede consumir más de lo medido
+benchmark-wallpaper = Fondo
+bind-monitors = Usar con estos monitores
+bind-monitors-hint = Cambia a este perfil cada vez que vuelvas a conectar los monitores conectados ahora, p. ej. al acoplar el portátil
+bottom-left = Abajo a la izquierda
+bottom-right = Abajo a la derecha
+browse = Examinar...
+browser-extension = Extensión del navegador
+browser-extension-hint = La extensión de integrations/browser añade "Usar como fondo de pantalla" al menú de las imágenes de la web. Regístrala aquí para que el navegador pueda comunicarse con Aether-Desk; Firefox no necesita nada más.
+browser-extension-ids = IDs de extensión de Chrome/Edge:
+browser-extension-ids-hint = De la página de extensiones del navegador
+browser-extension-install = Registrar en los navegadores
+browser-extension-installed = Registrado en { $count ->
+    [one] una ubicación de navegador
+   *[other] { $count } ubicaciones de navegador
+}
+browser-extension-reinstall = Registrar de nuevo
+browser-extension-uninstall = Anular registro
+builtin-no-file = Dibujado por Aether-Desk, sin archivo
+cache-max-age = Eliminar sin usar tras (días, 0 = nunca):
+cache-max-size = Tamaño máximo (MB, 0 = ilimitado):
+cache-protected-hint = Los favoritos, los fondos programados y el actual nunca se eliminan.
+cache-usage = Usando { $mb } MB en { $files } archivos
+calendar = Calendario
+calendar-days-ahead = Días por delante
+calendar-hint = Los próximos eventos de los calendarios suscritos aparecen en el widget Calendario y pueden activar fondos programados.
+calendar-refresh = Actualizar cada
+calendar-urls = Direcciones de calendario
+calendar-urls-hint = Una dirección ICS, webcal o de exportación CalDAV por línea
+cancel = Cancelar
+change-hooks = Comandos al cambiar
+change-hooks-hint = Comandos que se ejecutan tras cada cambio de fondo, con AETHER_WALLPAPER_PATH, AETHER_WALLPAPER_TYPE y AETHER_WALLPAPER_MONITOR definidas (p. ej. wal -i "$AETHER_WALLPAPER_PATH").
+chat-bot = Bot de chat
+chat-bot-enabled = Controlar el fondo desde un bot de Telegram o Discord
+chat-bot-hint = Envía al bot una imagen para mostrarla, o una orden como next, pause, resume, undo o status. Solo se responde a los usuarios de la lista.
+chat-bot-platform = Plataforma
+chat-bot-token = Token del bot
+chat-bot-user-hint = ID de usuario, que se registra cuando alguien no permitido escribe al bot
+chat-bot-users = ID de usuarios permitidos
+check-error = Error
+check-fix = Solución: { $fix }
+check-ok = OK
+check-warning = Aviso
+clean-up-now = Limpiar ahora
+clear-cache = Vaciar caché
+clock = Reloj
+close-preview = Cerrar vista previa
+color-palette = Paleta de colores
+color-temperature = Temperatura de color
+color-temperature-add = Añadir hora
+color-temperature-enabled = Teñir los fondos estáticos según la hora del día
+color-temperature-hint = Como una luz nocturna, los fondos estáticos se vuelven más cálidos por la tarde. 6500 K deja la imagen como está; menos es más cálido, más es más frío. Las temperaturas se mezclan entre las horas y se guardan con cada perfil.
+color-temperature-now = Ahora: { $kelvin } K
+color-temperature-update = Redibujar cada
+colors-like = Fondos con colores como { $name }
+config-history = Historial de configuración
+config-history-close = Cerrar
+config-history-empty = Aún no hay versiones
+config-history-enabled = Guardar un historial de la configuración
+config-history-hint = Cada guardado de la configuración, la programación y la disposición de widgets se conserva en un repositorio git en la carpeta de configuración.
+config-history-refresh = Actualizar
+config-history-restore = Restaurar esta versión
+config-history-restored = { $file } restaurado desde { $version }
+config-history-view = Ver este archivo tal como era
+config-history-viewing = { $file } en { $version }
+config-reloaded = Configuración recargada tras editarla
+confirm = Confirmar
+content-filter = Filtro de contenido
+copy = Copiar
+copy-log = Copiar líneas mostradas
+copy-remote-link = Copiar enlace con token
+cpu-limit = Límite de CPU (100% = un núcleo)
+cpu-usage = Uso de CPU: { $percent }%
+crash-report = Aether-Desk se cerró inesperadamente
+crash-report-hint = Aether-Desk se cerró inesperadamente la última vez. Se guardó un informe con el error, las últimas líneas del registro y un resumen de tu configuración. Adjuntarlo a un informe de errores ayuda a solucionar el problema.
+crop-portrait = Recortar para monitores verticales
+crop-portrait-hint = Llena los monitores verticales con imágenes horizontales recortando alrededor de su parte con más detalle, en lugar de mostrarlas con franjas.
+current-wallpaper = Fondo actual: { $name }
+custom = Personalizado
+custom-widget-unsupported = Los ajustes de widgets personalizados no están disponibles en esta versión.
+daemon-hint = Los fondos siguen funcionando después de cerrar esta ventana.
+daemon-running = En ejecución (PID { $pid }, activo { $minutes } min)
+dark = Oscuro
+date-format = Formato de fecha:
+delete = Eliminar
+description = Descripción:
+desktop-notifications = Mostrar los errores como notificaciones del escritorio
+detach = Desacoplar
+detach-hint = Abrir en una ventana separada
+detached-window = Se muestra en una ventana separada.
+detail = Detalle:
+details-author = Autor: { $author }
+details-builtin-settings = Su configuración está en la pestaña Fondo
+details-description = Descripción: { $description }
+details-name = Nombre: { $name }
+details-path = Ruta: { $path }
+details-tags = Etiquetas: { $tags }
+details-type = Tipo: { $type }
+details-url = URL: { $url }
+details-usage = Uso medio en ejecución: { $usage }
+details-usage-static = Uso de recursos: ninguno una vez aplicado
+details-usage-unknown = Uso de recursos: aún sin medir
+details-version = Versión: { $version }
+diagnostics = Diagnóstico
+diagnostics-configuration = Configuración
+diagnostics-desktop = Escritorio
+diagnostics-hint = Comprueba las herramientas externas, la integración con el escritorio, los permisos y los archivos de configuración que necesita Aether-Desk. Las mismas comprobaciones se ejecutan con `aether-desk doctor`.
+diagnostics-permissions = Permisos
+diagnostics-tools = Herramientas
+dismiss = Descartar
+download-cache = Caché de descargas
+download-now = Descargar ahora
+edit = Editar
+edit-schedule-item = Editar programación
+edit-widget = Editar widget
+enabled = Activado
+enforce-resource-limits = Pausar o detener los reproductores que consumen demasiado
+error = Error: { $error }
+error-add-schedule-item = No se pudo añadir el elemento de la programación
+error-add-widget = No se pudo añadir el widget
+error-apply-wallpaper = No se pudo aplicar el fondo de pantalla
+error-browser-extension = Falló el registro de la extensión del navegador
+error-clean-up-cache = No se pudo limpiar la caché de descargas
+error-clear-cache = No se pudo vaciar la caché de descargas
+error-config-history = Falló el historial de configuración
+error-delete-profile = Error al eliminar el perfil
+error-delete-theme = No se pudo eliminar el tema
+error-disable-plugin = No se pudo desactivar el plugin
+error-download-wallpapers = No se pudieron descargar fondos de pantalla
+error-edited-config = No se puede leer la configuración editada; se mantienen los ajustes anteriores
+error-enable-plugin = No se pudo activar el plugin
+error-export-pack = No se pudo exportar el paquete de fondos
+error-export-theme = No se pudo exportar el tema
+error-generate-wallpaper = No se pudieron generar fondos
+error-import = No se pudo importar de {$tool}
+error-import-theme = No se pudo importar el tema
+error-install-pack = No se pudo instalar el paquete de fondos
+error-load-configuration = No se pudo cargar la configuración
+error-load-plugins = No se pudieron cargar los plugins
+error-load-schedule = No se pudo cargar la programación
+error-load-widgets = No se pudieron cargar los widgets
+error-next-wallpaper = No se pudo cambiar al siguiente fondo
+error-no-monitors = No se pueden detectar los monitores conectados
+error-no-path = Selecciona primero un archivo para el fondo { $type }
+error-no-url = Introduce primero una URL para el fondo web
+error-no-workspace = No se puede detectar el espacio de trabajo activo en este escritorio
+error-open-crash-report = No se pudo abrir el informe de fallo
+error-read-api-token = No se pudo leer el token de la API
+error-read-log = No se pudo leer el registro: { $error }
+error-reload-config = El servicio en segundo plano no pudo recargar la configuración
+error-reload-schedule = El servicio en segundo plano no pudo recargar la programación
+error-remove-schedule-item = No se pudo eliminar el elemento de la programación
+error-remove-widget = No se pudo eliminar el widget
+error-resolve-orphans = No se pudieron gestionar los reproductores de la ejecución anterior
+error-revert-wallpaper = No se pudo restaurar el fondo de pantalla
+error-save-config = No se pudo guardar la configuración
+error-save-generated = No se pudo guardar el fondo generado
+error-save-profile = Error al guardar el perfil
+error-save-schedule = No se pudo guardar la programación
+error-save-theme = No se pudo guardar el tema
+error-save-widgets = No se pudieron guardar los widgets
+error-set-lock-screen = No se pudo establecer el fondo de la pantalla de bloqueo
+error-start-daemon = No se pudo iniciar el servicio en segundo plano
+error-start-widget-manager = No se pudo iniciar el gestor de widgets
+error-stop-daemon = No se pudo detener el servicio en segundo plano
+error-stop-wallpaper = No se pudo detener el fondo de pantalla
+error-store-api-key = No se pudo guardar la clave de API
+error-store-api-token = No se pudo guardar el token de la API
+error-store-bot-token = No se pudo guardar el token del bot
+error-store-calendar = No se pudieron guardar las direcciones del calendario
+error-store-sync-key = No se pudo guardar la clave de sincronización
+error-switch-profile = Error al cambiar de perfil
+error-undo-wallpaper = No se pudo deshacer el cambio de fondo
+error-update-autostart = No se pudo actualizar el inicio con el sistema
+error-update-live-earth = No se pudo actualizar Live Earth
+error-update-schedule-item = No se pudo actualizar el elemento de la programación
+error-update-widget = No se pudo actualizar el widget
+error-update-widget-position = No se pudo actualizar la posición del widget
+error-upload-qr = No se pudo crear el código QR
+event = Evento:
+export = Exportar...
+export-pack = Exportar paquete…
+export-pack-hint = Guarda los { $count } fondos de la biblioteca mostrados, con sus programaciones, en un archivo para compartir
+export-palette = Exportar la paleta de los fondos estáticos
+export-palette-hint = Escribe colors, colors.json, colors.sh, colors.Xresources y colors.css compatibles con pywal antes de los comandos al cambiar, para que los temas de la terminal y la barra sigan al fondo.
+favorite = Favorito
+filter-animations = Animaciones
+filter-images = Imágenes
+filter-shaders = Shaders
+filter-themes = Temas
+filter-videos = Vídeos
+filter-windows-themes = Temas de Windows
+fit-center = Centrar
+fit-crop = Recortar
+fit-fill = Rellenar
+fit-fit = Ajustar
+fit-mode = Modo de ajuste:
+fit-stretch = Estirar
+fit-tile = Mosaico
+focus-cover-above = Activar por encima de
+focus-effect = Efecto
+focus-effect-blur = Desenfocar
+focus-effect-dim = Oscurecer
+focus-effect-dim-and-blur = Oscurecer y desenfocar
+focus-fade = Fundido
+focus-mode = Modo concentración
+focus-mode-enabled = Atenuar el fondo mientras las ventanas cubren el escritorio
+focus-mode-hint = Un fondo estático pasa a una copia oscurecida o desenfocada cuando las ventanas cubren esta parte del escritorio, y vuelve cuando se cubre menos que la parte inferior. Los fondos extendidos y los monitores con imagen propia no cambian.
+focus-pause-animated = Pausar los fondos animados
+focus-strength = Intensidad
+focus-uncover-below = Desactivar por debajo de
+font-size = Tamaño de fuente:
+frame-rate-fixed = Fijo
+frame-rate-half-refresh = Mitad de la frecuencia del monitor
+frame-rate-hint = Los fondos de vídeo y shader se dibujan como máximo a esta velocidad, lo que ahorra batería en portátiles. Se aplica desde el siguiente cambio de fondo.
+frame-rate-limit = Límite de fotogramas
+frame-rate-match-refresh = Igual a la frecuencia del monitor
+frame-rate-own-limit = Usar su propio límite
+frame-rate-unlimited = Sin límite
+gallery-all = Todos
+gallery-builtin = Integrados
+gallery-library = Biblioteca
+general = General
+generate = Generar
+generate-apply = Guardar y aplicar
+generate-candidates = Imágenes:
+generate-for-monitor = Para el monitor:
+generate-hint = Describe un fondo y un servicio de generación de imágenes creará varios para que elijas.
+generate-negative-prompt = Evitar:
+generate-prompt = Un bosque de pinos con niebla al amanecer, luz suave
+generate-save = Guardar en la biblioteca
+generate-saved-size = Se guarda a { $width }x{ $height }
+generate-wallpaper = Generar un fondo
+generated-saved = Fondo generado guardado en la biblioteca
+generating = Generando, puede tardar unos minutos…
+generation-api-key = Clave de API:
+generation-endpoint = Dirección:
+generation-max-edge = Lado más largo generado:
+generation-model = Modelo:
+generation-model-comfyui-hint = ComfyUI necesita el archivo del checkpoint que cargar, p. ej. sd_xl_base_1.0.safetensors
+generation-provider = Servicio:
+generation-provider-hosted = API alojada (compatible con OpenAI)
+generation-service = Servicio de generación
+generation-steps = Pasos:
+gpu = Tarjeta gráfica
+gpu-default = Predeterminada del sistema
+gpu-discrete = GPU dedicada (mejor calidad)
+gpu-hint = Se aplica a los reproductores iniciados desde el siguiente cambio de fondo.
+gpu-integrated = GPU integrada (ahorra batería)
+gpu-preference = Dibujar fondos shader y web en
+gpu-single = Solo se encontró una GPU, así que este ajuste no tiene efecto aquí.
+ha-scene-hide-widgets = Ocultar
+ha-scene-keep-wallpaper = Mantener fondo
+ha-scene-profile = Perfil:
+ha-scene-show-widgets = Mostrar
+ha-scene-unchanged = Sin cambios
+ha-scene-wallpaper = Fondo:
+ha-scene-widgets = Widgets:
+ha-scenes = Escenas de Home Assistant
+ha-scenes-hint = Las escenas aparecen en Home Assistant mediante la integración aether_desk. Cada una cambia de perfil, aplica un fondo y muestra u oculta los widgets, sin cambiar lo que no esté definido.
+hemisphere = Hemisferio:
+hide-upload-qr = Ocultar código QR
+homepage = Página web
+hours = horas
+idle-minutes = Ausente tras
+import = Importar...
+import-folder = Carpeta de fondos…
+import-from = Importar de:
+import-windows-theme = Tema de Windows…
+imported-from = Importado de { $tool }: { $count ->
+    [one] una programación añadida
+   *[other] { $count } programaciones añadidas
+}
+install-pack = Instalar paquete…
+interval = Intervalo:
+interval-minutes = Intervalo (minutos):
+keep = Conservar
+keep-orphans = Conservarlos
+keep-orphans-hint = Siguen reproduciéndose y se detienen con el servicio en segundo plano
+ken-burns-duration = Cada movimiento
+ken-burns-hint = Recorre la imagen y se acerca lentamente, pasando suavemente de una parte a otra. Con el cambio automático, una carpeta de fotos se convierte en un fondo ambiental tranquilo.
+ken-burns-zoom = Zoom
+lan-sync = Sincronización en red
+lan-sync-enabled = Sincronizar el fondo con otros equipos de la red
+lan-sync-hint = Los equipos con la misma clave se encuentran entre sí y muestran el mismo fondo. Los archivos se envían a los demás cuando cambia el fondo.
+lan-sync-key = Clave de sincronización
+lan-sync-key-hint = Usa la misma clave en todos los equipos. Se envía por la red sin cifrar, así que no reutilices una contraseña.
+lan-sync-name = Nombre del equipo
+lan-sync-peers = Equipos que no se encuentran automáticamente (host:puerto)
+lan-sync-receive = Mostrar los cambios de otros
+lan-sync-send = Enviar mis cambios
+language = Idioma:
+large = Grande
+last-run = Última ejecución: { $time }
+last-update = Última actualización: { $time }
+level-error = Error
+level-info = Info
+level-warning = Aviso
+light = Claro
+limit-action = Al superar un límite
+limit-action-kill = Detener el reproductor
+limit-action-pause = Pausar el reproductor
+live-earth = Tierra en vivo
+live-earth-enabled = Usar imágenes de satélite en vivo como fondo
+load = Cargar
+loading = Cargando…
+local-api = API local
+location = Ubicación:
+lock-screen-set = Fondo de la pantalla de bloqueo establecido
+log-empty = No hay líneas de registro que mostrar
+log-file = Archivo: { $path }
+log-level = Nivel de registro:
+log-level-debug = Depuración
+log-level-error = Errores
+log-level-hint = Los mensajes menos graves que este nivel no se escriben en los archivos de registro. Súbelo a Depuración al informar de un problema.
+log-level-info = Información
+log-level-trace = Traza
+log-level-warn = Advertencias
+log-search = Filtrar texto
+log-show-all = Todo
+log-show-debug = Depuración y superior
+log-show-error = Solo errores
+log-show-info = Información y superior
+log-show-warn = Advertencias y errores
+log-source-app = Ventana
+log-source-daemon = Servicio en segundo plano
+logs = Registros
+loosen-content-filter = ¿Relajar el filtro de contenido? Se podrá descargar contenido hasta { $purity }.
+lower-above-cpu = Bajar por encima de CPU
+lower-quality-when-busy = Bajar la calidad del vídeo cuando el sistema está ocupado
+match-monitor-aspect-ratio = Coincidir con la relación de aspecto del monitor
+medium = Mediano
+memory-limit = Límite de memoria
+memory-used = Memoria usada: { $mb } MB
+minimum-size = Tamaño mínimo (x resolución del monitor)
+minutes = minutos
+mirror-monitors = El mismo fondo en todas partes
+mirror-monitors-hint = Muestra cada fondo igual en todos los monitores, p. ej. para presentaciones. Los fondos propios de cada monitor vuelven al desactivarlo.
+monitor-image = Muestra {$path}
+monitor-image-clear = Quitar
+monitor-image-wallpaper = Muestra el fondo estático
+monitor-images-clear-all = Quitar todas
+monitor-images-disconnected = { $count ->
+    [one] Un monitor desconectado conserva su imagen
+   *[other] { $count } monitores desconectados conservan su imagen
+}
+monitor-layout = Disposición de monitores
+monitor-layout-dragging = Suelta {$name} sobre un monitor
+monitor-layout-hint = Arrastra una imagen sobre un monitor para mostrarla allí en lugar del fondo estático. Haz clic derecho en un monitor para quitarla.
+monitor-layout-no-images = Añade imágenes estáticas a la galería o a tus favoritos para arrastrarlas aquí.
+monitor-layout-none = No se detectaron monitores.
+monitor-profile = {$monitors}: {$name}
+monitor-profiles = Perfiles por configuración de monitores:
+monitor-wallpaper = {$monitor}: {$name} ({$type})
+monitor-wallpapers = Fondos en monitores concretos
+more-like = Fondos como { $name }
+more-like-this = Más como este
+motion-effect = Movimiento
+motion-fps = Fotogramas por segundo
+motion-ken-burns = Ken Burns
+motion-parallax = Paralaje
+name = Nombre:
+no-file-selected = Ningún archivo seleccionado
+no-monitors-detected = No se detectaron monitores; se aceptarán todas las resoluciones.
+no-plugins-installed = No hay complementos instalados. Los complementos estarán disponibles en una versión futura.
+no-profiles = Aún no hay perfiles.
+no-schedule-items = No hay programaciones. Añade una para cambiar los fondos automáticamente.
+no-wallpaper-running = Ningún fondo en ejecución
+no-widgets-installed = No hay widgets instalados. Añade un widget para mostrar información en tu escritorio.
+not-running = Detenido
+notes = Notas
+notes-content = Contenido de las notas:
+nothing-to-import = Nada que importar de {$tool}
+nothing-to-redo = No hay ningún cambio de fondo que rehacer
+nothing-to-undo = No hay ningún cambio de fondo que deshacer
+notification-details = Detalles
+notification-hint = Solución: { $hint }
+notifications = Notificaciones
+notifications-clear = Borrar todo
+notifications-empty = No hay notificaciones
+notifications-unread = Notificaciones ({ $count })
+now-playing = Reproduciendo ahora
+now-playing-blur = Desenfoque del fondo
+now-playing-cover = Mostrar la portada en el centro
+now-playing-dim = Oscurecer el fondo
+now-playing-hint = Muestra la portada del álbum que suena en tu reproductor, mediante playerctl (MPRIS) en Linux y los controles multimedia en Windows. Los cambios se aplican la próxima vez que se aplique el fondo.
+now-playing-poll = Consultar el reproductor cada
+online-sources = Fuentes en línea
+open-crash-report = Abrir informe
+orphans-found = Reproductores de fondos que dejó en marcha una ejecución anterior: { $count }
+pack-exported = { $count ->
+    [one] Un fondo exportado
+   *[other] { $count } fondos exportados
+} a { $name }
+pack-installed = { $name } instalado: { $count } fondos, { $schedule } programaciones añadidas
+palette-family-any = Cualquier color
+palette-family-blue = Azules
+palette-family-cyan = Cianes
+palette-family-dark = Oscuros
+palette-family-green = Verdes
+palette-family-neutral = Grises neutros
+palette-family-orange = Naranjas
+palette-family-pink = Rosas
+palette-family-purple = Morados
+palette-family-red = Rojos
+palette-family-yellow = Amarillos
+palette-folder = Carpeta de la paleta:
+parallax-depth-map = Mover según la profundidad
+parallax-depth-map-hint = Las partes más cercanas se mueven más. Usa <nombre>.depth.png junto a la imagen, o estima la profundidad a partir de la propia imagen.
+parallax-hint = Los fondos estáticos animados son imágenes fijas que se mueven sutilmente con el ratón, dibujadas por Aether-Desk.
+parallax-strength = Intensidad
+particle-add-color = Añadir un color
+particle-background = Fondo:
+particle-colors = Colores:
+particle-colors-hint = Haz clic derecho en un color para quitarlo
+particle-count = Partículas
+particle-gravity = Gravedad
+particle-gravity-hint = Los valores positivos atraen las partículas hacia abajo; los negativos las hacen flotar hacia arriba
+particle-hint = Los cambios se aplican la próxima vez que se aplique el fondo de partículas.
+particle-links = Distancia de enlace
+particle-links-hint = Dibuja líneas entre partículas más cercanas que esta parte de la altura de la pantalla; 0 no dibuja ninguna
+particle-mouse = Ratón:
+particle-mouse-attract = Atraer
+particle-mouse-none = Ignorar
+particle-mouse-radius = Alcance del ratón
+particle-mouse-repel = Repeler
+particle-mouse-strength = Fuerza del ratón
+particle-preset = Preajuste:
+particle-preset-bubbles = Burbujas
+particle-preset-constellation = Constelación
+particle-preset-custom = Personalizado
+particle-preset-fireflies = Luciérnagas
+particle-preset-snowfall = Nevada
+particle-size = Tamaño
+particle-speed = Velocidad
+particles = Partículas
+pause-on-fullscreen = Pausar los fondos animados mientras una aplicación a pantalla completa tiene el foco
+pause-on-fullscreen-hint = Libera la GPU para juegos y vídeos. Se detecta en Windows, Hyprland, Sway y X11; otros escritorios Wayland no informan de las ventanas a pantalla completa.
+pause-on-idle = Pausar fondos animados y widgets mientras no estás
+pause-on-idle-hint = Ahorra energía cuando nadie mira; cualquier tecla o movimiento del ratón los reanuda. Se detecta en Windows, GNOME, KDE y X11 (con xprintidle).
+per-provider-purity = Por proveedor (no puede superar el ajuste global):
+per-run = Por ejecución:
+performance = Rendimiento
+performance-collecting = Recopilando métricas…
+performance-cpu = CPU del sistema
+performance-degraded = La aplicación va lenta o usa mucha memoria, o el sistema está ocupado. Abre el panel de Rendimiento de abajo para ver los detalles.
+performance-fps = Fotogramas por segundo
+performance-frame-time = Tiempo por fotograma
+performance-load-time = { $ms } ms
+performance-load-times = Tiempos de carga de fondos
+performance-memory = Memoria de la aplicación
+placement = Posición
+placement-crop-hint = Arrastra el rectángulo para elegir qué muestra este monitor; arrastra su esquina para cambiar el tamaño.
+placement-no-monitors = No se detectaron monitores.
+placement-reset-crop = Restablecer recorte
+placement-span-crop = Una imagen extendida muestra un recorte centrado de todo el escritorio.
+plugin-author = Autor: { $author }
+plugin-description = Descripción: { $description }
+plugin-license = Licencia: { $license }
+plugin-settings-coming-soon = Los ajustes de complementos estarán disponibles en una versión futura.
+plugins = Complementos
+portrait-only = Solo verticales
+portrait-only-hint = Muestra solo las imágenes más altas que anchas, para monitores girados.
+position = Posición:
+preview = Vista previa
+preview-failed = Error en la vista previa: { $error }
+preview-unavailable = No hay vista previa disponible para este fondo de pantalla.
+procedural = Procedural
+procedural-colors = Colores:
+procedural-density = Densidad
+procedural-density-hint = Cuántas columnas de lluvia, estrellas, células vivas, manchas o partículas hay, o lo finas que son las ondas del plasma
+procedural-flow-field = Campo de flujo
+procedural-game-of-life = Juego de la vida
+procedural-hint = Los cambios se aplican la próxima vez que se aplique un fondo procedural.
+procedural-matrix-rain = Lluvia de Matrix
+procedural-new-seed = Nueva
+procedural-pattern = Patrón:
+procedural-pick-pattern = Elige un patrón
+procedural-plasma = Plasma
+procedural-reaction-diffusion = Reacción-difusión
+procedural-scheme-classic = Clásico
+procedural-scheme-mono = Monocromo
+procedural-scheme-neon = Neón
+procedural-scheme-ocean = Océano
+procedural-scheme-sunset = Atardecer
+procedural-seed = Semilla:
+procedural-seed-hint = La misma semilla hace crecer el mismo patrón cada vez; 0 hace crecer uno distinto cada vez
+procedural-speed = Velocidad
+procedural-starfield = Campo de estrellas
+process-paused = En pausa (límite superado)
+profile-active = { $name } (activo)
+profiles = Perfiles
+profiles-hint = Un perfil guarda los ajustes del fondo, la programación y los widgets, p. ej. "Trabajo" o "Juegos". Al cambiar, tus cambios se guardan antes en el perfil activo. Los favoritos se comparten entre todos los perfiles.
+purity-nsfw = NSFW
+purity-sfw = Apto (SFW)
+purity-sketchy = Dudoso
+redo = Rehacer
+redo-hint = Volver al fondo que deshiciste (Ctrl+Mayús+Z)
+reduced-fps = Fotogramas por segundo reducidos
+reduced-scale = Escala de resolución reducida
+refresh-gallery = Actualizar galería
+refresh-interval-minutes = Intervalo de actualización (minutos):
+refresh-log = Actualizar
+regenerate-token = Regenerar
+resolution-filter = Filtro de resolución
+resolution-filter-enabled = Descargar solo fondos que se ajusten a mis monitores
+resource-limits = Límites de recursos
+resource-limits-hint = Los límites se aplican a cada reproductor de fondos, como mpv para los vídeos. Un reproductor que supere un límite durante 15 segundos se pausa o se detiene y se muestra un aviso.
+resource-monitoring = Supervisión de recursos
+resource-usage = Uso de recursos
+restart-crashed-players = Reiniciar los reproductores de fondos que fallen
+restore-below-cpu = Restaurar por debajo de CPU
+revert = Revertir
+run-diagnostics = Ejecutar comprobaciones
+running-wallpaper-usage = En ejecución { $name }: { $usage }
+satellite-goes-east = América / Atlántico (GOES-East)
+satellite-goes-west = Pacífico (GOES-West)
+satellite-himawari = Asia / Oceanía (Himawari)
+save = Guardar
+save-as-profile = Guardar actual como perfil
+save-key = Guardar clave
+saved-themes = Temas guardados
+schedule-reloaded = Programación recargada tras editarla
+search-gallery = Buscar:
+search-gallery-hint = Nombre o etiqueta, p. ej. naturaleza oscuro
+search-query = Búsqueda:
+secret-backend-encrypted-file = Archivo cifrado (llavero no disponible)
+secret-backend-keyring = Llavero del sistema
+security = Seguridad
+selected-wallpaper-details = Detalles del fondo seleccionado
+set-lock-screen = Usar en la pantalla de bloqueo
+settings = Ajustes
+shader = Shader
+show-all = Mostrar todos
+show-token = Mostrar token
+show-upload-qr = Mostrar código QR de subida
+show-week-numbers = Mostrar números de semana:
+similar-colors = Colores similares
+similar-colors-hint = Muestra los fondos de la galería con los colores dominantes más parecidos
+size = Tamaño:
+slideshow = Presentación
+slideshow-hint = Elige una carpeta; sus imágenes se muestran una tras otra, cambiando con la transición. Los cambios se aplican la próxima vez que se aplique una presentación.
+slideshow-interval = Cada imagen
+slideshow-order = Orden:
+slideshow-order-modified = Más antiguas primero
+slideshow-order-name = Por nombre
+slideshow-order-shuffle = Aleatorio
+small = Pequeño
+smart-crop = Recorte inteligente
+smart-crop-hint = Recorta las imágenes que no encajan con el monitor alrededor de su parte con más detalle en lugar del centro.
+source = Fuente:
+span-monitors = Extender por todos los monitores
+span-monitors-hint = Muestra un solo fondo en todos los monitores como un único escritorio. Los vídeos y shaders se reproducen en una ventana por monitor, sincronizadas.
+start-service = Iniciar servicio
+start-with-system = Iniciar con el sistema
+start-with-system-hint = Aether-Desk se inicia minimizado al iniciar sesión.
+static = Estático
+status = Estado
+status-backend = Backend: { $backend }
+status-monitors = Monitores
+status-no-events = Aún no hay eventos
+status-no-processes = No hay procesos auxiliares en ejecución
+status-pid = PID { $pid }
+status-processes = Procesos auxiliares
+status-recent-events = Eventos recientes
+status-scheduler-running = En ejecución, { $enabled } de { $total } elementos activados
+status-scheduler-stopped = Detenido
+stop = Detener
+stop-orphans = Detenerlos
+stop-service = Detener servicio
+stored-securely = Guardada de forma segura
+switch-profile = Cambiar
+sync-lock-screen = Sincronizar la pantalla de bloqueo
+sync-lock-screen-hint = Muestra también en la pantalla de bloqueo los fondos estáticos que pongas en todo el escritorio (GNOME, y Windows con permisos de administrador)
+system-default = Predeterminado del sistema
+system-monitor = Monitor del sistema
+system-theme = Sistema
+tab-gallery = Galería
+tab-generate = Generar
+tab-logs = Registros
+tab-plugins = Complementos
+tab-scheduler = Programador
+tab-settings = Ajustes
+tab-status = Estado
+tab-wallpaper = Fondo
+tab-widgets = Widgets
+tag-library = Etiquetar la biblioteca ahora
+tagging-model-folder = Carpeta del modelo:
+tagging-threshold = Probabilidad mínima de una etiqueta
+theme = Tema
+theme-accent = Acento
+theme-background = Fondo
+theme-label = Tema:
+theme-none-selected = Selecciona un tema
+theme-panel = Paneles
+theme-preview = Vista previa
+theme-preview-text = El veloz murciélago hindú comía feliz cardillo y kiwi.
+theme-save-as = Guardar como
+theme-text = Texto
+theme-unsaved = Vista previa de cambios sin guardar
+theme-widget = Controles
+theming-dry-run = Simulación
+theming-dry-run-failed = La simulación falló: { $error }
+theming-dry-run-hint = Muestra lo que escribiría y ejecutaría recolorear el sistema con el último fondo estático, sin hacerlo.
+theming-no-files = No se escriben archivos; la herramienta escribe los suyos.
+theming-none = Solo los archivos de la paleta
+theming-runs = Ejecuta: { $command }
+theming-templates = Plantillas
+theming-templates-builtin = Rellenar plantillas
+theming-tool = Recolorear con
+theming-tool-hint = Rellena las plantillas al estilo de pywal ({"{"}color0{"}"}, {"{"}background{"}"}, ...) de la carpeta de plantillas en la carpeta de la paleta, o deja que pywal o wallust recoloreen la terminal, las barras y los temas GTK con sus propias plantillas.
+theming-writes = Escribe { $path }
+time = Hora:
+time-format = Formato de hora:
+top-left = Arriba a la izquierda
+top-right = Arriba a la derecha
+transition = Transición
+transition-crossfade = Fundido
+transition-duration = Duración
+transition-hint = Cómo cambia el escritorio de un fondo estático al siguiente. swww lo anima por sí mismo; en otros escritorios se muestran unos fotogramas intermedios.
+transition-morph = Transformar
+transition-none = Ninguna
+transition-pixelate = Pixelar
+transition-slide = Deslizar
+transition-wipe = Barrido
+trigger = Disparador:
+trigger-calendar = Evento de calendario
+trigger-calendar-any = Cualquier evento ocupado
+trigger-calendar-title = El título contiene
+trigger-interval = Intervalo
+trigger-system-event = Evento del sistema
+trigger-time = Hora
+trigger-type = Tipo de disparador:
+try-on-desktop = Probar en el escritorio
+try-on-desktop-hint = Muestra el fondo en el escritorio durante { $seconds } segundos y luego vuelve al anterior
+try-on-remaining = Se restaurará en { $seconds } s
+undo = Deshacer
+undo-hint = Volver al fondo anterior al último cambio (Ctrl+Z)
+update-every-minutes = Actualizar cada (minutos):
+update-interval-seconds = Intervalo de actualización (segundos):
+update-now = Actualizar ahora
+update-profile = Actualizar
+update-profile-hint = Reemplazar este perfil con los ajustes actuales
+upload-qr-hint = Escanéalo con un teléfono en la misma red para enviar una foto directamente al fondo de pantalla. El código contiene el token de la API, así que no lo compartas.
+use-default-folder = Usar la predeterminada
+use-pywal-folder = Usar carpeta de pywal
+video = Vídeo
+virtual-camera = Cámara virtual
+virtual-camera-device = Dispositivo
+virtual-camera-device-hint = El dispositivo v4l2loopback, creado con: sudo modprobe v4l2loopback video_nr=10
+virtual-camera-enabled = Enviar el fondo a una cámara virtual
+virtual-camera-hint = Transmite el fondo con ffmpeg, para usarlo como webcam o como fondo de escena en OBS.
+virtual-camera-loopback = Webcam (v4l2loopback)
+virtual-camera-output = Salida
+virtual-camera-size = Tamaño
+virtual-camera-stream = Transmisión para OBS
+virtual-camera-stream-url = Dirección de la transmisión
+virtual-camera-stream-url-hint = Reproduce esta dirección en una fuente multimedia de OBS, con «Archivo local» desmarcado
+wallhaven-api-key = Clave de API de Wallhaven:
+wallpaper = Fondo de pantalla
+wallpaper-auto-paused = En pausa mientras una aplicación a pantalla completa tiene el foco
+wallpaper-focus-mode = Atenuado mientras las ventanas cubren el escritorio
+wallpaper-gallery = Galería de fondos
+wallpaper-held = En pausa a petición
+wallpaper-idle-paused = En pausa mientras no estás
+wallpaper-monitor = Monitor:
+wallpaper-monitor-hint = Muestra fondos estáticos, de vídeo y shaders en un monitor mientras los demás conservan el suyo. Los monitores sin fondo propio quedan vacíos.
+wallpaper-pack = Paquete de fondos
+wallpaper-path = Ruta del fondo:
+wallpaper-quality-reduced = Calidad del fondo reducida mientras el sistema está ocupado
+wallpaper-scheduler = Programador de fondos
+wallpaper-since = Mostrado desde las { $time }
+wallpaper-since-player = Mostrado desde las { $time }, proceso del reproductor { $pid }
+wallpaper-type = Tipo de fondo:
+wallpaper-usage = { $memory } MB de memoria, { $cpu } % de CPU
+wallpaper-usage-gpu = { $memory } MB de memoria, { $cpu } % de CPU, { $gpu } % de GPU
+wallpaper-weather-effect = Tiempo dibujado sobre el fondo: { $effect }
+watchdog = Recuperación de fallos
+watchdog-hint = Un reproductor de vídeo que falla se reinicia tras 2, 4, 8… segundos. Si sigue fallando, se muestra el último fondo estático.
+watchdog-max-restarts = Reinicios antes de rendirse
+weather = Clima
+weather-api-key = Clave API de OpenWeatherMap:
+weather-effects = Efectos del tiempo
+weather-effects-enabled = Dibujar el tiempo sobre los fondos estáticos
+weather-effects-hint = La lluvia, la nieve o la niebla de la ubicación se dibujan sobre un fondo estático que cubre todos los monitores, y desaparecen cuando el tiempo mejora.
+weather-fog = Niebla
+weather-intensity = Intensidad
+weather-location = Ubicación:
+weather-preview = Vista previa:
+weather-preview-off = Tiempo real
+weather-rain = Lluvia
+weather-refresh = Intervalo de actualización del tiempo
+weather-snow = Nieve
+web = Web
+web-url = URL web:
+webhook-apply-wallpaper = Aplicar fondo
+webhook-next-wallpaper = Siguiente fondo
+webhook-start-scheduler = Iniciar programador
+webhook-stop-scheduler = Detener programador
+webhook-stop-wallpaper = Detener fondo
+webhook-switch-profile = Cambiar de perfil
+webhooks = Webhooks
+webhooks-hint = Cada webhook ejecuta una acción al llamar a su URL. Los emisores que no pueden enviar cabeceras pueden añadir ?token=<token>.
+widget-preview = Vista previa del widget
+widget-type = Tipo de widget:
+widgets = Widgets
+widgets-reloaded = Widgets recargados tras editarlos
+workspace-wallpaper = {$workspace}: {$name}
+workspace-wallpapers = Fondos de los espacios de trabajo
+zoom = Zoom
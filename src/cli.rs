@@ -0,0 +1,1042 @@
+//! Command-line interface
+//!
+//! Subcommands talk to the daemon, so they work from scripts and window
+//! manager keybindings without opening the settings window. Shell
+//! completions and man pages are generated from the same definitions.
+use crate::core::config::{ProceduralPattern, WallpaperType};
+use crate::core::migrate::{self, Migration, Tool};
+use crate::core::{AppError, AppResult, Config, ProfileStore, TriggerType, WallpaperInfo};
+use crate::daemon::{browser, DaemonClient, DaemonStatus, Request};
+use crate::platform::doctor::{self, Check, CheckCategory, CheckStatus};
+use crate::platform::native_messaging;
+use crate::platform::topology;
+use chrono::Local;
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Command-line arguments
+#[derive(Debug, Parser)]
+#[command(
+    name = "aether-desk",
+    version,
+    about = "Dynamic wallpapers for your desktop"
+)]
+pub struct Cli {
+    /// Run the background daemon
+    #[arg(long, hide = true)]
+    pub daemon: bool,
+
+    /// Run the wallpaper engine, scheduler and widgets without a window
+    ///
+    /// Control it with the subcommands or the daemon's IPC.
+    #[arg(long, visible_alias = "no-gui", conflicts_with = "daemon")]
+    pub headless: bool,
+
+    /// Start the settings window minimized to the tray
+    #[arg(long, hide = true)]
+    pub minimized: bool,
+
+    /// Log how long each phase of opening the settings window takes
+    #[arg(long)]
+    pub profile_startup: bool,
+
+    /// Subcommand; opens the settings window when omitted
+    #[command(subcommand)]
+    pub command: Option<CliCommand>,
+}
+
+/// Subcommands
+#[derive(Debug, Subcommand)]
+pub enum CliCommand {
+    /// Set the wallpaper
+    Set {
+        /// Wallpaper type (guessed from the target when omitted)
+        #[arg(long = "type", short = 't', value_enum)]
+        wallpaper_type: Option<CliWallpaperType>,
+
+        /// Wallpaper file, folder for slideshows, URL for web wallpapers, or the name of a built-in one (`particles`,
+        /// `now-playing`, `matrix-rain`, `starfield`, `plasma`, `game-of-life`, `reaction-diffusion`, `flow-field`)
+        target: String,
+
+        /// Show the wallpaper on this monitor only (ID or name), beside the others' wallpapers
+        #[arg(long)]
+        monitor: Option<String>,
+    },
+
+    /// Switch to the next wallpaper in the current folder
+    Next,
+
+    /// Show the same wallpaper on every monitor, or go back to each monitor's own
+    Mirror {
+        /// Whether to mirror
+        #[arg(value_enum)]
+        state: Switch,
+    },
+
+    /// Set the image shown on the lock screen
+    LockScreen {
+        /// Image file
+        image: PathBuf,
+    },
+
+    /// Stop the current wallpaper
+    Stop {
+        /// Stop only the wallpaper on this monitor (ID or name)
+        #[arg(long)]
+        monitor: Option<String>,
+    },
+
+    /// Go back to the wallpaper before the last change
+    Undo,
+
+    /// Go forward to the last undone wallpaper
+    Redo,
+
+    /// Show the daemon status
+    Status {
+        /// Print the status as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// List, save, switch and delete profiles
+    Profile {
+        #[command(subcommand)]
+        command: ProfileCommand,
+    },
+
+    /// Check tools, desktop integration, permissions and configuration
+    Doctor {
+        /// Print the results as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Import the rotation, schedule and favorites of another wallpaper tool
+    ///
+    /// Komorebi and Variety are read from where they keep their settings
+    /// unless a file is given. A folder is scheduled by its GNOME timed
+    /// background, or by images named after times like 07-30.jpg or parts of
+    /// the day like evening.png, and rotated through otherwise.
+    Import {
+        /// Tool to import from
+        #[arg(value_enum)]
+        tool: ImportTool,
+
+        /// Settings file, the folder, or the Windows theme to import
+        path: Option<PathBuf>,
+
+        /// Show what would be imported without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Connect the browser extension's "Set as wallpaper" menu to the app
+    Browser {
+        #[command(subcommand)]
+        command: BrowserCommand,
+    },
+
+    /// Talk to the browser extension over native messaging; started by the browser
+    #[command(hide = true)]
+    NativeHost {
+        /// Extension origin and other arguments the browser passes
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// Print a shell completion script
+    ///
+    /// For example `aether-desk completions bash > /usr/share/bash-completion/completions/aether-desk`.
+    Completions {
+        /// Shell to complete for
+        shell: Shell,
+    },
+
+    /// Generate man pages
+    ///
+    /// Prints the main page, or writes a page per subcommand with --out-dir.
+    Man {
+        /// Directory for aether-desk.1 and aether-desk-<command>.1
+        #[arg(long, value_name = "DIR")]
+        out_dir: Option<PathBuf>,
+    },
+}
+
+/// Profile subcommands
+#[derive(Debug, Subcommand)]
+pub enum ProfileCommand {
+    /// List the profiles, marking the active one
+    List,
+
+    /// Switch to a profile
+    Switch {
+        /// Profile name
+        name: String,
+    },
+
+    /// Save the current wallpaper, schedule and widgets as a profile
+    Save {
+        /// Profile name
+        name: String,
+    },
+
+    /// Delete a profile
+    Delete {
+        /// Profile name
+        name: String,
+    },
+
+    /// Switch to a profile whenever the monitors connected now are connected again
+    Bind {
+        /// Profile name
+        name: String,
+    },
+
+    /// Stop switching profiles when the monitors connected now are connected
+    Unbind,
+}
+
+/// Browser extension subcommands
+#[derive(Debug, Subcommand)]
+pub enum BrowserCommand {
+    /// Register the native messaging host with the installed browsers
+    ///
+    /// Firefox needs nothing else. Chrome, Edge and other Chromium-based
+    /// browsers only let the extension IDs given here in, as shown on their
+    /// extensions page once the extension is loaded.
+    Install {
+        /// ID of the extension in a Chromium-based browser; may be repeated
+        #[arg(long = "extension-id", value_name = "ID")]
+        extension_ids: Vec<String>,
+    },
+
+    /// Remove the native messaging host from every browser
+    Uninstall,
+}
+
+/// On or off, as written on the command line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Switch {
+    /// Turn it on
+    On,
+    /// Turn it off
+    Off,
+}
+
+/// Tool to import from, as written on the command line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ImportTool {
+    Komorebi,
+    Variety,
+    Folder,
+    WindowsTheme,
+}
+
+impl From<ImportTool> for Tool {
+    fn from(tool: ImportTool) -> Self {
+        match tool {
+            ImportTool::Komorebi => Tool::Komorebi,
+            ImportTool::Variety => Tool::Variety,
+            ImportTool::Folder => Tool::Folder,
+            ImportTool::WindowsTheme => Tool::WindowsTheme,
+        }
+    }
+}
+
+/// Wallpaper type as written on the command line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CliWallpaperType {
+    Static,
+    AnimatedStatic,
+    AnimatedImage,
+    Video,
+    Web,
+    Shader,
+    Audio,
+    Particles,
+    Procedural,
+    Slideshow,
+    NowPlaying,
+}
+
+impl From<CliWallpaperType> for WallpaperType {
+    fn from(wallpaper_type: CliWallpaperType) -> Self {
+        match wallpaper_type {
+            CliWallpaperType::Static => WallpaperType::Static,
+            CliWallpaperType::AnimatedStatic => WallpaperType::AnimatedStatic,
+            CliWallpaperType::AnimatedImage => WallpaperType::AnimatedImage,
+            CliWallpaperType::Video => WallpaperType::Video,
+            CliWallpaperType::Web => WallpaperType::Web,
+            CliWallpaperType::Shader => WallpaperType::Shader,
+            CliWallpaperType::Audio => WallpaperType::Audio,
+            CliWallpaperType::Particles => WallpaperType::Particles,
+            CliWallpaperType::Procedural => WallpaperType::Procedural,
+            CliWallpaperType::Slideshow => WallpaperType::Slideshow,
+            CliWallpaperType::NowPlaying => WallpaperType::NowPlaying,
+        }
+    }
+}
+
+/// Run a subcommand
+pub fn run(command: CliCommand) -> AppResult<()> {
+    let daemon = DaemonClient::new();
+
+    match command {
+        CliCommand::Set {
+            wallpaper_type,
+            target,
+            monitor,
+        } => {
+            let wallpaper =
+                wallpaper_from_target(wallpaper_type.map(Into::into), &target)?.on_monitor(monitor);
+            daemon.ensure_running()?;
+            daemon.send(&Request::ApplyWallpaper {
+                wallpaper: wallpaper.clone(),
+            })?;
+            remember(Some(&wallpaper))?;
+            println!(
+                "Set {} wallpaper: {}",
+                wallpaper.r#type.as_str().to_lowercase(),
+                target
+            );
+        }
+        CliCommand::Next => {
+            daemon.ensure_running()?;
+            let response = daemon.send(&Request::NextWallpaper)?;
+            let wallpaper = response.status.and_then(|status| status.current_wallpaper);
+            remember(wallpaper.as_ref())?;
+            if let Some(message) = response.message {
+                println!("{}", message);
+            }
+        }
+        CliCommand::Mirror { state } => {
+            // The daemon re-arranges the wallpapers when it reloads the configuration
+            let mut config = Config::load().map_err(|e| AppError::ConfigError(e.to_string()))?;
+            config.wallpaper.placement.mirror = state == Switch::On;
+            config
+                .save()
+                .map_err(|e| AppError::ConfigError(e.to_string()))?;
+            reload_daemon_config(&daemon)?;
+            match state {
+                Switch::On => println!("Showing the same wallpaper on every monitor"),
+                Switch::Off => println!("Showing each monitor's own wallpaper"),
+            }
+        }
+        CliCommand::LockScreen { image } => {
+            if !image.is_file() {
+                return Err(AppError::WallpaperError(format!(
+                    "No image at {}",
+                    image.display()
+                )));
+            }
+            daemon.ensure_running()?;
+            daemon.send(&Request::SetLockScreen {
+                path: image.canonicalize()?,
+            })?;
+            println!("Set lock screen wallpaper: {}", image.display());
+        }
+        CliCommand::Stop {
+            monitor: Some(monitor),
+        } => {
+            if !daemon.is_running() {
+                println!("Daemon is not running");
+                return Ok(());
+            }
+            daemon.send(&Request::StopMonitorWallpaper {
+                monitor: monitor.clone(),
+            })?;
+            let mut config = Config::load().map_err(|e| AppError::ConfigError(e.to_string()))?;
+            config
+                .wallpaper
+                .monitor_wallpapers
+                .retain(|id, _| *id != monitor);
+            config
+                .save()
+                .map_err(|e| AppError::ConfigError(e.to_string()))?;
+            println!("Stopped wallpaper on {}", monitor);
+        }
+        CliCommand::Stop { monitor: None } => {
+            if !daemon.is_running() {
+                println!("Daemon is not running");
+                return Ok(());
+            }
+            daemon.send(&Request::StopWallpaper)?;
+            remember(None)?;
+            println!("Stopped wallpaper");
+        }
+        CliCommand::Undo | CliCommand::Redo => {
+            if !daemon.is_running() {
+                println!("Daemon is not running");
+                return Ok(());
+            }
+            let request = match command {
+                CliCommand::Undo => Request::UndoWallpaper,
+                _ => Request::RedoWallpaper,
+            };
+            let response = daemon.send(&request)?;
+            if let Some(wallpaper) = response.status.and_then(|status| status.current_wallpaper) {
+                remember(Some(&wallpaper))?;
+            }
+            if let Some(message) = response.message {
+                println!("{}", message);
+            }
+        }
+        CliCommand::Status { json } => {
+            if !daemon.is_running() {
+                if json {
+                    println!("null");
+                } else {
+                    println!("Daemon is not running");
+                }
+                return Ok(());
+            }
+            let status = daemon.status()?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&status)?);
+            } else {
+                print_status(&status);
+            }
+        }
+        CliCommand::Profile { command } => run_profile(command, &daemon)?,
+        CliCommand::Doctor { json } => {
+            let checks = doctor::run();
+            if json {
+                println!("{}", serde_json::to_string_pretty(&checks)?);
+            } else {
+                print_checks(&checks);
+            }
+
+            let errors = checks
+                .iter()
+                .filter(|check| check.status == CheckStatus::Error)
+                .count();
+            if errors > 0 {
+                return Err(AppError::Other(format!(
+                    "{} problem(s) need fixing",
+                    errors
+                )));
+            }
+        }
+        CliCommand::Import {
+            tool,
+            path,
+            dry_run,
+        } => {
+            let migration = migrate::read(tool.into(), path.as_deref())?;
+            print_migration(&migration);
+            if dry_run || migration.is_empty() {
+                return Ok(());
+            }
+
+            let mut config = Config::load().map_err(|e| AppError::ConfigError(e.to_string()))?;
+            let added = migration.merge_into(&mut config)?;
+            config
+                .save()
+                .map_err(|e| AppError::ConfigError(e.to_string()))?;
+            reload_daemon_config(&daemon)?;
+            if added > 0 && daemon.is_running() {
+                daemon.send(&Request::ReloadSchedule)?;
+            }
+            if let Some(wallpaper) = migration.wallpaper {
+                daemon.ensure_running()?;
+                daemon.send(&Request::ApplyWallpaper { wallpaper })?;
+            }
+            println!(
+                "Imported from {}; {} schedule item(s) added",
+                migration.source, added
+            );
+        }
+        CliCommand::Browser {
+            command: BrowserCommand::Install { extension_ids },
+        } => {
+            for path in native_messaging::install(&extension_ids)? {
+                println!("Registered {}", path.display());
+            }
+            if extension_ids.is_empty() {
+                println!("Chromium-based browsers were skipped; pass --extension-id to register them too");
+            }
+        }
+        CliCommand::Browser {
+            command: BrowserCommand::Uninstall,
+        } => {
+            native_messaging::uninstall()?;
+            println!("Removed the native messaging host");
+        }
+        CliCommand::NativeHost { .. } => browser::run_native_host()?,
+        CliCommand::Completions { shell } => {
+            let mut command = Cli::command();
+            let name = command.get_name().to_string();
+            clap_complete::generate(shell, &mut command, name, &mut io::stdout());
+        }
+        CliCommand::Man { out_dir } => match out_dir {
+            Some(dir) => {
+                for path in write_man_pages(&dir)? {
+                    println!("Wrote {}", path.display());
+                }
+            }
+            None => clap_mangen::Man::new(Cli::command()).render(&mut io::stdout())?,
+        },
+    }
+
+    Ok(())
+}
+
+/// Run a profile subcommand
+fn run_profile(command: ProfileCommand, daemon: &DaemonClient) -> AppResult<()> {
+    let mut config = Config::load().map_err(|e| AppError::ConfigError(e.to_string()))?;
+    let store = ProfileStore::open(&config);
+
+    match command {
+        ProfileCommand::List => {
+            let names = store.list()?;
+            if names.is_empty() {
+                println!("No profiles; create one with `aether-desk profile save <name>`");
+            }
+            for name in names {
+                let marker = if config.app.active_profile.as_deref() == Some(name.as_str()) {
+                    "*"
+                } else {
+                    " "
+                };
+                println!("{} {}", marker, name);
+            }
+            for (topology, name) in &config.app.topology_profiles {
+                println!("Monitors {}: {}", topology, name);
+            }
+        }
+        ProfileCommand::Switch { name } => {
+            // The daemon applies the profile's wallpaper, schedule and widgets
+            daemon.ensure_running()?;
+            let response = daemon.send(&Request::SwitchProfile { name })?;
+            if let Some(message) = response.message {
+                println!("{}", message);
+            }
+        }
+        ProfileCommand::Save { name } => {
+            store.save(&name, &config)?;
+            config.app.active_profile = Some(name.clone());
+            config
+                .save()
+                .map_err(|e| AppError::ConfigError(e.to_string()))?;
+            reload_daemon_config(daemon)?;
+            println!("Saved profile {}", name);
+        }
+        ProfileCommand::Delete { name } => {
+            store.delete(&name, &mut config)?;
+            config
+                .save()
+                .map_err(|e| AppError::ConfigError(e.to_string()))?;
+            reload_daemon_config(daemon)?;
+            println!("Deleted profile {}", name);
+        }
+        ProfileCommand::Bind { name } => {
+            if !store.exists(&name) {
+                return Err(AppError::ConfigError(format!("No profile named {}", name)));
+            }
+            let topology = current_topology()?;
+            config
+                .app
+                .topology_profiles
+                .insert(topology.clone(), name.clone());
+            config
+                .save()
+                .map_err(|e| AppError::ConfigError(e.to_string()))?;
+            reload_daemon_config(daemon)?;
+            println!("Profile {} is used with monitors {}", name, topology);
+        }
+        ProfileCommand::Unbind => {
+            let topology = current_topology()?;
+            if config.app.topology_profiles.remove(&topology).is_none() {
+                println!("No profile is used with monitors {}", topology);
+                return Ok(());
+            }
+            config
+                .save()
+                .map_err(|e| AppError::ConfigError(e.to_string()))?;
+            reload_daemon_config(daemon)?;
+            println!("No longer switching profiles for monitors {}", topology);
+        }
+    }
+
+    Ok(())
+}
+
+/// Get the connected monitor setup, failing when monitors can't be detected
+fn current_topology() -> AppResult<String> {
+    topology::current_topology()
+        .ok_or_else(|| AppError::Other("The connected monitors can't be detected".to_string()))
+}
+
+/// Have a running daemon pick up the saved configuration
+fn reload_daemon_config(daemon: &DaemonClient) -> AppResult<()> {
+    if daemon.is_running() {
+        daemon.send(&Request::ReloadConfig)?;
+    }
+    Ok(())
+}
+
+/// Write the man pages of the CLI and its visible subcommands into a directory
+fn write_man_pages(dir: &Path) -> AppResult<Vec<PathBuf>> {
+    fs::create_dir_all(dir)?;
+
+    let mut command = Cli::command();
+    command.build();
+    let name = command.get_name().to_string();
+
+    let mut pages = vec![(name.clone(), command.clone())];
+    pages.extend(
+        command
+            .get_subcommands()
+            .filter(|subcommand| !subcommand.is_hide_set() && subcommand.get_name() != "help")
+            .map(|subcommand| {
+                (
+                    format!("{}-{}", name, subcommand.get_name()),
+                    subcommand.clone(),
+                )
+            }),
+    );
+
+    let mut written = Vec::new();
+    for (title, page) in pages {
+        let path = dir.join(format!("{}.1", title));
+        let mut file = fs::File::create(&path)?;
+        clap_mangen::Man::new(page).title(title).render(&mut file)?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Build the wallpaper for a `set` target
+///
+/// `--type` decides how the target is read; built-in names and URLs are only
+/// recognized when no type was given.
+fn wallpaper_from_target(
+    wallpaper_type: Option<WallpaperType>,
+    target: &str,
+) -> AppResult<WallpaperInfo> {
+    match wallpaper_type {
+        Some(WallpaperType::Web) => {
+            return Ok(WallpaperInfo::new(
+                WallpaperType::Web,
+                None,
+                Some(target.to_string()),
+            ))
+        }
+        Some(WallpaperType::Particles) => {
+            return Ok(WallpaperInfo::new(WallpaperType::Particles, None, None))
+        }
+        Some(WallpaperType::NowPlaying) => {
+            return Ok(WallpaperInfo::new(WallpaperType::NowPlaying, None, None))
+        }
+        Some(WallpaperType::Procedural) => {
+            return ProceduralPattern::from_id(&target.to_ascii_lowercase())
+                .map(WallpaperInfo::procedural)
+                .ok_or_else(|| {
+                    let patterns: Vec<&str> = ProceduralPattern::ALL
+                        .iter()
+                        .map(|pattern| pattern.id())
+                        .collect();
+                    AppError::WallpaperError(format!(
+                        "Unknown procedural wallpaper {}; pick one of {}",
+                        target,
+                        patterns.join(", ")
+                    ))
+                });
+        }
+        Some(_) => {}
+        None => {
+            if target.starts_with("http://") || target.starts_with("https://") {
+                return Ok(WallpaperInfo::new(
+                    WallpaperType::Web,
+                    None,
+                    Some(target.to_string()),
+                ));
+            }
+            if target.eq_ignore_ascii_case(WallpaperType::Particles.as_str()) {
+                return Ok(WallpaperInfo::new(WallpaperType::Particles, None, None));
+            }
+            if target.eq_ignore_ascii_case("now-playing") {
+                return Ok(WallpaperInfo::new(WallpaperType::NowPlaying, None, None));
+            }
+            if let Some(pattern) = ProceduralPattern::from_id(&target.to_ascii_lowercase()) {
+                return Ok(WallpaperInfo::procedural(pattern));
+            }
+        }
+    }
+
+    let path = PathBuf::from(target)
+        .canonicalize()
+        .map_err(|e| AppError::WallpaperError(format!("Failed to open {}: {}", target, e)))?;
+    let wallpaper_type = wallpaper_type
+        .or_else(|| WallpaperType::from_path(&path))
+        .ok_or_else(|| {
+            AppError::WallpaperError(format!(
+                "Can't tell the wallpaper type of {}; pass --type",
+                path.display()
+            ))
+        })?;
+
+    Ok(WallpaperInfo::new(wallpaper_type, Some(path), None))
+}
+
+/// Save the current wallpaper so the daemon restores it on the next start
+fn remember(wallpaper: Option<&WallpaperInfo>) -> AppResult<()> {
+    let mut config = Config::load().map_err(|e| AppError::ConfigError(e.to_string()))?;
+    match wallpaper {
+        Some(wallpaper) => wallpaper.remember(&mut config.wallpaper),
+        None => {
+            config.wallpaper.current_path = None;
+            config.wallpaper.monitor_wallpapers.clear();
+        }
+    }
+    config
+        .save()
+        .map_err(|e| AppError::ConfigError(e.to_string()))
+}
+
+/// Print what's imported from another tool for humans
+fn print_migration(migration: &Migration) {
+    if migration.is_empty() {
+        println!("Nothing to import from {}", migration.source);
+    }
+    if let Some(rotation) = &migration.rotation {
+        let folder = rotation
+            .folder
+            .as_ref()
+            .map_or("the current folder".to_string(), |folder| {
+                folder.display().to_string()
+            });
+        let state = if rotation.enabled { "on" } else { "off" };
+        println!(
+            "Rotation:   every {} min through {} ({})",
+            rotation.interval, folder, state
+        );
+    }
+    for item in &migration.schedule {
+        if let (TriggerType::Time(time), Some(path)) = (&item.trigger, &item.wallpaper.path) {
+            println!("Schedule:   {} {}", time.format("%H:%M"), path.display());
+        }
+    }
+    if let Some(wallpaper) = &migration.wallpaper {
+        println!("Wallpaper:  {}", wallpaper.name);
+    }
+    if let Some((mode, span)) = migration.placement {
+        println!(
+            "Fit:        {:?}{}",
+            mode,
+            if span { ", spanning all monitors" } else { "" }
+        );
+    }
+    if !migration.favorites.is_empty() {
+        println!("Favorites:  {}", migration.favorites.len());
+    }
+    if !migration.files.is_empty() {
+        println!(
+            "Images:     {} to copy into the library",
+            migration.files.len()
+        );
+    }
+    for note in &migration.notes {
+        println!("Note:       {}", note);
+    }
+}
+
+/// Print the daemon status for humans
+fn print_status(status: &DaemonStatus) {
+    println!(
+        "Daemon:     running (PID {}, up {} min)",
+        status.pid,
+        status.uptime_secs / 60
+    );
+    println!("Backend:    {}", status.backend);
+    match &status.current_wallpaper {
+        Some(wallpaper) => {
+            let source = wallpaper
+                .path
+                .as_ref()
+                .map(|p| p.display().to_string())
+                .or_else(|| wallpaper.url.clone())
+                .unwrap_or_default();
+            let paused = if status.saving_power && wallpaper.r#type != WallpaperType::Static {
+                ", battery saver"
+            } else if status.held {
+                ", paused"
+            } else if status.user_away {
+                ", paused while you're away"
+            } else if status.auto_paused {
+                ", paused for a fullscreen app"
+            } else if status.focused {
+                ", toned down by focus mode"
+            } else if status.quality_reduced && wallpaper.r#type != WallpaperType::Static {
+                ", lowered quality"
+            } else {
+                ""
+            };
+            println!(
+                "Wallpaper:  {} ({}{})",
+                source,
+                wallpaper.r#type.as_str().to_lowercase(),
+                paused
+            );
+            if let Some(current) = &status.current {
+                let player = current
+                    .pid
+                    .map(|pid| format!(", player PID {}", pid))
+                    .unwrap_or_default();
+                println!(
+                    "            since {}{}",
+                    current
+                        .started_at
+                        .with_timezone(&Local)
+                        .format("%Y-%m-%d %H:%M"),
+                    player
+                );
+                if !current.monitors.is_empty() {
+                    println!("            on {}", current.monitors.join(", "));
+                }
+            }
+        }
+        None => println!("Wallpaper:  none"),
+    }
+    for state in &status.monitor_wallpapers {
+        let monitor = state.wallpaper.monitor.as_deref().unwrap_or_default();
+        let source = state.source().unwrap_or_default();
+        println!(
+            "Monitor:    {}: {} ({})",
+            monitor,
+            source,
+            state.wallpaper_type().as_str().to_lowercase()
+        );
+    }
+    if let Some(workspace) = &status.workspace {
+        println!("Workspace:  {}", workspace.name);
+    }
+    if let Some(effect) = status.weather_effect {
+        println!("Weather:    {}", format!("{:?}", effect).to_lowercase());
+    }
+    println!(
+        "Scheduler:  {} ({} of {} items enabled)",
+        if status.scheduler_running {
+            "running"
+        } else {
+            "stopped"
+        },
+        status.enabled_schedule_items,
+        status.schedule_items,
+    );
+    for process in &status.processes {
+        let paused = if process.paused {
+            ", paused over its resource limits"
+        } else {
+            ""
+        };
+        println!(
+            "Process:    {} (PID {}, {}{})",
+            process.name, process.pid, process.role, paused
+        );
+    }
+    for orphan in &status.orphans {
+        println!(
+            "Orphan:     {} (PID {}, left running by a previous run)",
+            orphan.name, orphan.pid
+        );
+    }
+}
+
+/// Print diagnostics grouped by category, with the fixes for problems
+fn print_checks(checks: &[Check]) {
+    for category in CheckCategory::ALL {
+        let group: Vec<&Check> = checks
+            .iter()
+            .filter(|check| check.category == category)
+            .collect();
+        if group.is_empty() {
+            continue;
+        }
+
+        println!("{}", category.as_str());
+        for check in group {
+            let marker = match check.status {
+                CheckStatus::Ok => "  ok ",
+                CheckStatus::Warning => " warn",
+                CheckStatus::Error => " FAIL",
+            };
+            println!("{}  {}: {}", marker, check.name, check.detail);
+            if let Some(fix) = &check.fix {
+                println!("         fix: {}", fix);
+            }
+        }
+        println!();
+    }
+
+    let count = |status| checks.iter().filter(|check| check.status == status).count();
+    println!(
+        "{} ok, {} warnings, {} errors",
+        count(CheckStatus::Ok),
+        count(CheckStatus::Warning),
+        count(CheckStatus::Error)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_commands() {
+        let cli =
+            Cli::try_parse_from(["aether-desk", "set", "--type", "video", "clip.mp4"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(CliCommand::Set { wallpaper_type: Some(CliWallpaperType::Video), ref target, .. }) if target == "clip.mp4"
+        ));
+
+        let cli = Cli::try_parse_from(["aether-desk", "--daemon"]).unwrap();
+        assert!(cli.daemon && cli.command.is_none());
+
+        let cli = Cli::try_parse_from(["aether-desk", "--no-gui"]).unwrap();
+        assert!(cli.headless);
+
+        let cli = Cli::try_parse_from(["aether-desk", "status", "--json"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(CliCommand::Status { json: true })
+        ));
+
+        let cli = Cli::try_parse_from(["aether-desk", "profile", "switch", "Gaming"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(CliCommand::Profile { command: ProfileCommand::Switch { ref name } }) if name == "Gaming"
+        ));
+
+        let cli = Cli::try_parse_from([
+            "aether-desk",
+            "import",
+            "windows-theme",
+            "Lakes.themepack",
+            "--dry-run",
+        ])
+        .unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(CliCommand::Import {
+                tool: ImportTool::WindowsTheme,
+                dry_run: true,
+                ..
+            })
+        ));
+
+        let cli = Cli::try_parse_from(["aether-desk", "mirror", "on"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(CliCommand::Mirror { state: Switch::On })
+        ));
+
+        // Browsers pass their own arguments to the native messaging host
+        let cli = Cli::try_parse_from([
+            "aether-desk",
+            "native-host",
+            "chrome-extension://abc/",
+            "--parent-window=0",
+        ])
+        .unwrap();
+        assert!(
+            matches!(cli.command, Some(CliCommand::NativeHost { ref args }) if args.len() == 2)
+        );
+
+        let cli = Cli::try_parse_from(["aether-desk", "completions", "zsh"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(CliCommand::Completions { shell: Shell::Zsh })
+        ));
+        assert!(Cli::try_parse_from(["aether-desk", "completions", "tcsh"]).is_err());
+    }
+
+    #[test]
+    fn test_completions() {
+        let mut script = Vec::new();
+        clap_complete::generate(Shell::Bash, &mut Cli::command(), "aether-desk", &mut script);
+        let script = String::from_utf8(script).unwrap();
+        assert!(script.contains("completions"));
+        assert!(script.contains("--json"));
+
+        // Internal flags stay out of the completions
+        assert!(!script.contains("--daemon"));
+    }
+
+    #[test]
+    fn test_write_man_pages() {
+        let dir = tempfile::tempdir().unwrap();
+        let written = write_man_pages(dir.path()).unwrap();
+
+        for name in [
+            "aether-desk.1",
+            "aether-desk-set.1",
+            "aether-desk-status.1",
+            "aether-desk-man.1",
+        ] {
+            assert!(written.contains(&dir.path().join(name)), "missing {}", name);
+        }
+        assert!(!dir.path().join("aether-desk-help.1").exists());
+
+        let page = fs::read_to_string(dir.path().join("aether-desk-set.1")).unwrap();
+        assert!(page.contains("Set the wallpaper"));
+    }
+
+    #[test]
+    fn test_wallpaper_from_target() {
+        let wallpaper = wallpaper_from_target(None, "https://example.com").unwrap();
+        assert_eq!(wallpaper.r#type, WallpaperType::Web);
+        assert_eq!(wallpaper.url.as_deref(), Some("https://example.com"));
+
+        assert!(wallpaper_from_target(None, "/nonexistent/wallpaper.png").is_err());
+
+        let particles = wallpaper_from_target(None, "particles").unwrap();
+        assert_eq!(particles.r#type, WallpaperType::Particles);
+        assert_eq!(
+            (particles.path, particles.name.as_str()),
+            (None, "Particles")
+        );
+        assert_eq!(
+            wallpaper_from_target(None, "now-playing").unwrap().r#type,
+            WallpaperType::NowPlaying
+        );
+
+        let plasma = wallpaper_from_target(None, "Plasma").unwrap();
+        assert_eq!(plasma.r#type, WallpaperType::Procedural);
+        assert_eq!(
+            (plasma.url.as_deref(), plasma.name.as_str()),
+            (Some("plasma"), "Plasma")
+        );
+        assert!(wallpaper_from_target(Some(WallpaperType::Procedural), "lava-lamp").is_err());
+
+        // A type given with --type wins over built-in names
+        assert_eq!(
+            wallpaper_from_target(Some(WallpaperType::Procedural), "Plasma")
+                .unwrap()
+                .r#type,
+            WallpaperType::Procedural
+        );
+        let err = wallpaper_from_target(Some(WallpaperType::Static), "particles").unwrap_err();
+        assert!(
+            err.to_string().contains("Failed to open particles"),
+            "{}",
+            err
+        );
+        assert!(wallpaper_from_target(Some(WallpaperType::Video), "plasma").is_err());
+        let dir = tempfile::tempdir().unwrap();
+        let named = dir.path().join("now-playing");
+        fs::write(&named, b"").unwrap();
+        let wallpaper =
+            wallpaper_from_target(Some(WallpaperType::Static), &named.display().to_string())
+                .unwrap();
+        assert_eq!(wallpaper.r#type, WallpaperType::Static);
+
+        let slideshow = wallpaper_from_target(None, &dir.path().display().to_string()).unwrap();
+        assert_eq!(slideshow.r#type, WallpaperType::Slideshow);
+    }
+}
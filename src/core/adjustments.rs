@@ -0,0 +1,329 @@
+//! Brightness, contrast, saturation, blur and vignette of each wallpaper
+//!
+//! Adjustments never change the file. Wallpapers drawn in-process apply them
+//! to the frames they render; static images reach the backend as an adjusted
+//! copy, kept in the `adjusted` folder and reused until the image or its
+//! adjustments change. Adjustments are kept in a file shared by every
+//! process, read again before each lookup so the daemon sees the changes made
+//! in the details panel.
+use crate::core::image_pipeline::{Encoding, ImagePipeline};
+use crate::core::{image_format, AppError, AppResult, Config};
+use image::{Rgba, RgbaImage};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Strongest blur, in pixels of a 1920 pixel wide image
+pub const MAX_BLUR: f32 = 32.0;
+
+/// Most adjusted copies kept; the oldest are removed first
+const MAX_ADJUSTED: usize = 32;
+
+/// Distance from the center where the vignette starts, as a share of the way to a corner
+const VIGNETTE_START: f32 = 0.4;
+
+/// How an image is adjusted; the default leaves it as it is
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Adjustments {
+    /// Brightness added (-1.0 - 1.0)
+    pub brightness: f32,
+
+    /// Contrast change (-1.0 - 1.0); -1.0 is flat gray
+    pub contrast: f32,
+
+    /// Saturation change (-1.0 - 1.0); -1.0 is grayscale
+    pub saturation: f32,
+
+    /// Blur in pixels of a 1920 pixel wide image (0.0 - `MAX_BLUR`)
+    pub blur: f32,
+
+    /// Darkening of the corners (0.0 - 1.0)
+    pub vignette: f32,
+}
+
+impl Adjustments {
+    /// Check whether the image is left as it is
+    pub fn is_neutral(&self) -> bool {
+        self.clamped() == Self::default()
+    }
+
+    /// Bring each adjustment into its range
+    pub fn clamped(self) -> Self {
+        Self {
+            brightness: self.brightness.clamp(-1.0, 1.0),
+            contrast: self.contrast.clamp(-1.0, 1.0),
+            saturation: self.saturation.clamp(-1.0, 1.0),
+            blur: self.blur.clamp(0.0, MAX_BLUR),
+            vignette: self.vignette.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Adjust an image in place
+    pub fn apply(&self, image: &mut RgbaImage) {
+        let adjustments = self.clamped();
+        if adjustments.is_neutral() {
+            return;
+        }
+        if adjustments.blur > 0.0 {
+            let sigma = adjustments.blur * image.width() as f32 / 1920.0;
+            *image = ImagePipeline::new(std::mem::take(image))
+                .blur(sigma)
+                .into_rgba8();
+        }
+
+        let (width, height) = image.dimensions();
+        let center = (width as f32 / 2.0, height as f32 / 2.0);
+        let corner = center.0.hypot(center.1).max(1.0);
+        let brightness = adjustments.brightness * 255.0;
+        let contrast = 1.0 + adjustments.contrast;
+        let saturation = 1.0 + adjustments.saturation;
+        for (x, y, pixel) in image.enumerate_pixels_mut() {
+            let Rgba([r, g, b, a]) = *pixel;
+            let shade = if adjustments.vignette > 0.0 {
+                let distance =
+                    (x as f32 + 0.5 - center.0).hypot(y as f32 + 0.5 - center.1) / corner;
+                1.0 - adjustments.vignette * smoothstep(VIGNETTE_START, 1.0, distance)
+            } else {
+                1.0
+            };
+            let luma = 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
+            let adjust = |channel: u8| {
+                let saturated = luma + (channel as f32 - luma) * saturation;
+                let contrasted = (saturated - 128.0) * contrast + 128.0;
+                ((contrasted + brightness) * shade)
+                    .round()
+                    .clamp(0.0, 255.0) as u8
+            };
+            *pixel = Rgba([adjust(r), adjust(g), adjust(b), a]);
+        }
+    }
+
+    /// Hash the adjustments for naming adjusted copies
+    fn key(&self) -> u64 {
+        let adjustments = self.clamped();
+        let mut hasher = DefaultHasher::new();
+        for value in [
+            adjustments.brightness,
+            adjustments.contrast,
+            adjustments.saturation,
+            adjustments.blur,
+            adjustments.vignette,
+        ] {
+            value.to_bits().hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+}
+
+/// Smooth step from 0 at `edge0` to 1 at `edge1`
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Adjustments of the wallpapers, by path
+#[derive(Debug, Default)]
+pub struct AdjustmentStore {
+    /// File the adjustments are kept in (None = memory only)
+    path: Option<PathBuf>,
+
+    /// Adjustments by wallpaper path; wallpapers left as they are have none
+    entries: Mutex<BTreeMap<PathBuf, Adjustments>>,
+}
+
+impl AdjustmentStore {
+    /// Create a store kept in memory only
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open the store kept in a file
+    pub fn open(path: PathBuf) -> Self {
+        let entries = read(&path);
+        Self {
+            path: Some(path),
+            entries: Mutex::new(entries),
+        }
+    }
+
+    /// Get the process-wide store
+    pub fn shared() -> Arc<AdjustmentStore> {
+        static SHARED: OnceLock<Arc<AdjustmentStore>> = OnceLock::new();
+        SHARED
+            .get_or_init(|| match Config::get_config_dir() {
+                Ok(dir) => Arc::new(AdjustmentStore::open(dir.join("adjustments.json"))),
+                Err(_) => Arc::new(AdjustmentStore::new()),
+            })
+            .clone()
+    }
+
+    /// Get the adjustments of a wallpaper
+    pub fn get(&self, path: &Path) -> Adjustments {
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(file) = &self.path {
+            *entries = read(file);
+        }
+        entries.get(path).copied().unwrap_or_default()
+    }
+
+    /// Record the adjustments of a wallpaper, forgetting them when they're neutral
+    pub fn set(&self, path: &Path, adjustments: Adjustments) {
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(file) = &self.path {
+            *entries = read(file);
+        }
+        if adjustments.is_neutral() {
+            entries.remove(path);
+        } else {
+            entries.insert(path.to_path_buf(), adjustments.clamped());
+        }
+        if let Some(file) = &self.path {
+            let result = serde_json::to_string(&*entries)
+                .map_err(|e| e.to_string())
+                .and_then(|json| fs::write(file, json).map_err(|e| e.to_string()));
+            if let Err(e) = result {
+                warn!("Failed to save adjustments to {}: {}", file.display(), e);
+            }
+        }
+    }
+}
+
+/// Read the adjustments kept in a file
+fn read(path: &Path) -> BTreeMap<PathBuf, Adjustments> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Get a file showing an image with its adjustments, or the image itself when it has none
+pub async fn adjusted(path: &Path) -> AppResult<PathBuf> {
+    let adjustments = AdjustmentStore::shared().get(path);
+    if adjustments.is_neutral() {
+        return Ok(path.to_path_buf());
+    }
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        adjusted_copy(&path, &adjustments, &Config::get_adjusted_dir())
+    })
+    .await
+    .map_err(|e| AppError::WallpaperError(format!("Failed to adjust image: {}", e)))?
+}
+
+/// Save an adjusted copy of an image as PNG in `dir`, reusing an earlier copy with the same adjustments
+pub fn adjusted_copy(path: &Path, adjustments: &Adjustments, dir: &Path) -> AppResult<PathBuf> {
+    let name = image_format::converted_name(path)?;
+    let copy = dir.join(format!(
+        "{}-{:016x}.png",
+        name.trim_end_matches(".png"),
+        adjustments.key()
+    ));
+    if copy.exists() {
+        debug!("Reusing the adjusted copy of {}", path.display());
+        return Ok(copy);
+    }
+
+    let mut image = ImagePipeline::open(path)?.into_rgba8();
+    adjustments.apply(&mut image);
+    fs::create_dir_all(dir)?;
+    ImagePipeline::new(image).save(&copy, Encoding::Png)?;
+    info!("Saved an adjusted copy of {}", path.display());
+    image_format::prune(dir, MAX_ADJUSTED);
+    Ok(copy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_apply() {
+        let gray = RgbaImage::from_pixel(8, 8, Rgba([100, 100, 100, 255]));
+
+        let mut unchanged = gray.clone();
+        Adjustments::default().apply(&mut unchanged);
+        assert_eq!(unchanged, gray);
+
+        let mut brighter = gray.clone();
+        Adjustments {
+            brightness: 0.2,
+            ..Default::default()
+        }
+        .apply(&mut brighter);
+        assert_eq!(brighter.get_pixel(0, 0), &Rgba([151, 151, 151, 255]));
+
+        // Full desaturation leaves the luma of each pixel
+        let mut red = RgbaImage::from_pixel(2, 2, Rgba([200, 0, 0, 128]));
+        Adjustments {
+            saturation: -1.0,
+            ..Default::default()
+        }
+        .apply(&mut red);
+        assert_eq!(red.get_pixel(0, 0), &Rgba([60, 60, 60, 128]));
+
+        // The vignette darkens the corners, not the middle
+        let mut vignetted = gray.clone();
+        Adjustments {
+            vignette: 1.0,
+            ..Default::default()
+        }
+        .apply(&mut vignetted);
+        assert_eq!(vignetted.get_pixel(4, 4), &Rgba([100, 100, 100, 255]));
+        assert!(vignetted.get_pixel(0, 0)[0] < 50);
+    }
+
+    #[test]
+    fn test_store() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("adjustments.json");
+        let store = AdjustmentStore::open(file.clone());
+        let darker = Adjustments {
+            brightness: -0.3,
+            ..Default::default()
+        };
+        store.set(Path::new("forest.png"), darker);
+
+        // Other processes see the change
+        assert_eq!(
+            AdjustmentStore::open(file.clone()).get(Path::new("forest.png")),
+            darker
+        );
+        assert!(store.get(Path::new("sea.png")).is_neutral());
+
+        store.set(Path::new("forest.png"), Adjustments::default());
+        assert_eq!(fs::read_to_string(&file).unwrap(), "{}");
+    }
+
+    #[test]
+    fn test_adjusted_copy() {
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("forest.png");
+        RgbaImage::from_pixel(4, 4, Rgba([100, 100, 100, 255]))
+            .save(&source)
+            .unwrap();
+        let copies = dir.path().join("adjusted");
+
+        let darker = Adjustments {
+            brightness: -0.2,
+            ..Default::default()
+        };
+        let copy = adjusted_copy(&source, &darker, &copies).unwrap();
+        assert!(image::open(&copy).unwrap().to_rgba8().get_pixel(0, 0)[0] < 100);
+        assert_eq!(adjusted_copy(&source, &darker, &copies).unwrap(), copy);
+
+        // Other adjustments get a copy of their own
+        let blurred = Adjustments {
+            blur: 4.0,
+            ..Default::default()
+        };
+        assert_ne!(adjusted_copy(&source, &blurred, &copies).unwrap(), copy);
+        assert_eq!(fs::read_dir(&copies).unwrap().count(), 2);
+    }
+}
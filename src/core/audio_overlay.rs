@@ -0,0 +1,298 @@
+//! Audio overlay: spectrum bars and a pulsing vignette over the wallpaper
+//!
+//! What's playing on the system is recorded and drawn over the wallpaper in
+//! time with it. A static wallpaper filling every monitor is drawn in-process
+//! with the effects on top, like the weather effects, which go first while
+//! they are drawing. Video wallpapers get the effects as an overlay their
+//! player shows over the video. Other wallpapers are left as they are.
+use crate::core::config::{AudioOverlayConfig, PlacementConfig, WeatherEffect};
+use crate::core::scheduler::CurrentWallpaper;
+use crate::core::{runtime, AppError, AppResult, WallpaperType};
+use crate::experiments::effects::audio::{self as effects, AudioVisuals};
+use crate::platform::audio::AudioCapture;
+use crate::platform::WallpaperManager;
+use crate::wallpapers::frame_player::{self, FramePlayer};
+use log::{debug, info, warn};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Time between checks of the wallpaper while nothing is drawn frame by frame
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Time before starting a recording again after it failed
+const RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Wallpaper the effects are drawn over
+#[derive(Debug, Clone, PartialEq)]
+enum Target {
+    /// Static image, drawn in-process with the effects
+    Static(PathBuf),
+
+    /// Video, whose player shows the effects
+    Video,
+}
+
+/// What the effects on screen are drawn from
+#[derive(Debug, Clone, PartialEq)]
+struct Drawn {
+    /// Wallpaper drawn over
+    target: Target,
+
+    /// Effects drawn
+    settings: AudioOverlayConfig,
+
+    /// How static images are fitted
+    placement: PlacementConfig,
+
+    /// Recordings started so far, as a new one has new samples
+    recording: u32,
+}
+
+/// Draws audio-reactive effects over static and video wallpapers
+pub struct AudioOverlay {
+    /// Audio overlay configuration
+    config: Arc<Mutex<AudioOverlayConfig>>,
+
+    /// How static wallpapers are fitted
+    placement: Arc<Mutex<PlacementConfig>>,
+
+    /// Wallpaper to draw over
+    current_wallpaper: CurrentWallpaper,
+
+    /// Platform-specific wallpaper manager, which shows the frames
+    wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>,
+
+    /// Weather drawn over static wallpapers, which goes first
+    weather_effect: Arc<Mutex<Option<WeatherEffect>>>,
+
+    /// Drawing thread handle
+    draw_thread: Option<thread::JoinHandle<()>>,
+
+    /// Whether the overlay is running
+    is_running: Arc<Mutex<bool>>,
+}
+
+#[allow(dead_code)]
+impl AudioOverlay {
+    /// Create a new audio overlay for the scheduler's wallpaper
+    pub fn new(
+        config: AudioOverlayConfig,
+        current_wallpaper: CurrentWallpaper,
+        wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>,
+        weather_effect: Arc<Mutex<Option<WeatherEffect>>>,
+    ) -> Self {
+        Self {
+            config: Arc::new(Mutex::new(config)),
+            placement: Arc::new(Mutex::new(PlacementConfig::default())),
+            current_wallpaper,
+            wallpaper_manager,
+            weather_effect,
+            draw_thread: None,
+            is_running: Arc::new(Mutex::new(false)),
+        }
+    }
+
+    /// Update the audio overlay configuration
+    pub fn update_config(&self, config: AudioOverlayConfig) {
+        *self.config.lock().unwrap() = config;
+    }
+
+    /// Set how static wallpapers are fitted
+    pub fn set_placement(&self, placement: PlacementConfig) {
+        *self.placement.lock().unwrap() = placement;
+    }
+
+    /// Start the drawing thread
+    pub fn start(&mut self) -> AppResult<()> {
+        if *self.is_running.lock().unwrap() {
+            debug!("Audio overlay is already running");
+            return Ok(());
+        }
+
+        *self.is_running.lock().unwrap() = true;
+
+        let config = self.config.clone();
+        let placement = self.placement.clone();
+        let current_wallpaper = self.current_wallpaper.clone();
+        let wallpaper_manager = self.wallpaper_manager.clone();
+        let weather_effect = self.weather_effect.clone();
+        let is_running = self.is_running.clone();
+
+        self.draw_thread = Some(thread::spawn(move || {
+            let player = FramePlayer::new(wallpaper_manager.clone());
+            let mut capture: Option<(AudioCapture, String)> = None;
+            let mut failed: Option<(Instant, String)> = None;
+            let mut recording = 0;
+            let mut drawn: Option<Drawn> = None;
+            let mut visuals: Option<AudioVisuals> = None;
+            let mut last_frame = Instant::now();
+
+            while *is_running.lock().unwrap() {
+                let started = Instant::now();
+                let config = config.lock().unwrap().clone();
+                let placement = placement.lock().unwrap().clone();
+
+                // Recording only while enabled, started again when it stopped or the device changed
+                if !config.enabled {
+                    capture = None;
+                } else if !capture.as_mut().map_or(false, |(capture, device)| {
+                    *device == config.device && capture.is_running()
+                }) {
+                    capture = None;
+                    let retry = failed.as_ref().map_or(true, |(at, device)| {
+                        at.elapsed() >= RETRY_INTERVAL || *device != config.device
+                    });
+                    if retry {
+                        match AudioCapture::start(&config.device) {
+                            Ok(started) => {
+                                info!("Recording audio for the overlay");
+                                capture = Some((started, config.device.clone()));
+                                failed = None;
+                                recording += 1;
+                            }
+                            Err(e) => {
+                                warn!("Failed to record audio for the overlay: {}", e);
+                                failed = Some((Instant::now(), config.device.clone()));
+                            }
+                        }
+                    }
+                }
+
+                let target =
+                    current_wallpaper.lock().unwrap().get(None).and_then(
+                        |wallpaper| match wallpaper.get_type() {
+                            WallpaperType::Static => wallpaper
+                                .get_path()
+                                .map(|path| Target::Static(path.to_path_buf())),
+                            WallpaperType::Video => Some(Target::Video),
+                            _ => None,
+                        },
+                    );
+                let target = target.filter(|target| match target {
+                    Target::Static(_) => {
+                        !placement.span
+                            && placement.monitor_images.is_empty()
+                            && weather_effect.lock().unwrap().is_none()
+                    }
+                    Target::Video => true,
+                });
+                let wanted = target.filter(|_| capture.is_some()).map(|target| Drawn {
+                    target,
+                    settings: config.clone(),
+                    placement: placement.clone(),
+                    recording,
+                });
+
+                if wanted != drawn {
+                    player.stop();
+                    visuals = None;
+                    match drawn.as_ref().map(|drawn| &drawn.target) {
+                        Some(Target::Static(path)) => restore(&current_wallpaper, path),
+                        Some(Target::Video) => remove_overlay(&current_wallpaper),
+                        None => {}
+                    }
+                    if let (Some(wanted), Some((capture, _))) = (&wanted, &capture) {
+                        match &wanted.target {
+                            Target::Static(path) => match effects::over_wallpaper(
+                                path,
+                                &wanted.placement,
+                                wanted.settings.clone(),
+                                capture.samples(),
+                            ) {
+                                Ok(overlay) => {
+                                    info!("Drawing the audio overlay over the wallpaper");
+                                    player.start(Box::new(overlay), wanted.settings.fps);
+                                }
+                                Err(e) => warn!(
+                                    "Failed to draw the audio overlay over the wallpaper: {}",
+                                    e
+                                ),
+                            },
+                            Target::Video => {
+                                info!("Showing the audio overlay over the video");
+                                visuals = Some(AudioVisuals::new(wanted.settings.clone()));
+                            }
+                        }
+                    }
+                    drawn = wanted;
+                }
+
+                // Video overlays are drawn here, frame by frame
+                let interval = match (&mut visuals, &capture) {
+                    (Some(visuals), Some((capture, _))) => {
+                        visuals.update(
+                            &capture.samples().latest(),
+                            last_frame.elapsed().as_secs_f32(),
+                        );
+                        last_frame = Instant::now();
+                        let (width, height) = frame_player::frame_size();
+                        let overlay = visuals.draw_alone(width, height);
+                        if let Some(wallpaper) = current_wallpaper.lock().unwrap().get(None) {
+                            if let Err(e) = runtime::block_on(wallpaper.set_overlay(Some(&overlay)))
+                            {
+                                debug!("Failed to show the audio overlay: {}", e);
+                            }
+                        }
+                        Duration::from_secs(1) / config.fps.max(1)
+                    }
+                    _ => CHECK_INTERVAL,
+                };
+
+                thread::sleep(interval.saturating_sub(started.elapsed()));
+            }
+
+            player.stop();
+            match drawn.map(|drawn| drawn.target) {
+                Some(Target::Static(path)) => restore(&current_wallpaper, &path),
+                Some(Target::Video) => remove_overlay(&current_wallpaper),
+                None => {}
+            }
+        }));
+
+        info!("Audio overlay started");
+        Ok(())
+    }
+
+    /// Stop the drawing thread, bringing back the wallpaper without the effects
+    pub fn stop(&mut self) -> AppResult<()> {
+        if !*self.is_running.lock().unwrap() {
+            debug!("Audio overlay is not running");
+            return Ok(());
+        }
+
+        *self.is_running.lock().unwrap() = false;
+
+        if let Some(thread) = self.draw_thread.take() {
+            thread.join().map_err(|e| {
+                AppError::Other(format!("Failed to join audio overlay thread: {:?}", e))
+            })?;
+        }
+
+        info!("Audio overlay stopped");
+        Ok(())
+    }
+}
+
+/// Show a static wallpaper drawn over without the effects, unless another one replaced it meanwhile
+fn restore(current_wallpaper: &CurrentWallpaper, path: &Path) {
+    if let Some(wallpaper) = current_wallpaper.lock().unwrap().get(None) {
+        if wallpaper.get_type() == WallpaperType::Static && wallpaper.get_path() == Some(path) {
+            if let Err(e) = runtime::block_on(wallpaper.redraw()) {
+                warn!("Failed to bring back the wallpaper: {}", e);
+            }
+        }
+    }
+}
+
+/// Take the overlay off a video wallpaper
+fn remove_overlay(current_wallpaper: &CurrentWallpaper) {
+    if let Some(wallpaper) = current_wallpaper.lock().unwrap().get(None) {
+        if wallpaper.get_type() == WallpaperType::Video {
+            if let Err(e) = runtime::block_on(wallpaper.set_overlay(None)) {
+                debug!("Failed to remove the audio overlay: {}", e);
+            }
+        }
+    }
+}
@@ -0,0 +1,218 @@
+//! Changing the wallpaper on a timer
+//!
+//! While auto-change is on, the next wallpaper of the auto-change folder is
+//! shown every `interval` minutes. The wait counts from when the wallpaper on
+//! screen was applied, whoever applied it, so a wallpaper picked by the user or
+//! the scheduler gets a full interval before it's changed. Wallpapers from
+//! outside the folder, like a scheduled one or a calendar event's, are left
+//! alone until something brings back one of the folder's; the rotation then
+//! carries on from there. Nothing changes until a folder is set.
+use crate::core::config::AutoChangeConfig;
+use crate::core::{AppError, AppResult, CurrentWallpaperState, CurrentWallpaperStore};
+use chrono::{DateTime, Duration, Utc};
+use log::{debug, info};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration as StdDuration;
+
+/// Time between checks for a due change
+const CHECK_INTERVAL: StdDuration = StdDuration::from_secs(1);
+
+/// Changes the wallpaper to the next one of a folder on a timer
+pub struct AutoChange {
+    /// Auto-change configuration
+    config: Arc<Mutex<AutoChangeConfig>>,
+
+    /// Check thread handle
+    check_thread: Option<thread::JoinHandle<()>>,
+
+    /// Whether the checks are running
+    is_running: Arc<Mutex<bool>>,
+}
+
+#[allow(dead_code)]
+impl AutoChange {
+    /// Create a new auto-change service
+    pub fn new(config: AutoChangeConfig) -> Self {
+        Self {
+            config: Arc::new(Mutex::new(config)),
+            check_thread: None,
+            is_running: Arc::new(Mutex::new(false)),
+        }
+    }
+
+    /// Update the auto-change configuration
+    pub fn update_config(&self, config: AutoChangeConfig) {
+        *self.config.lock().unwrap() = config;
+    }
+
+    /// Check whether the checks are running
+    pub fn is_running(&self) -> bool {
+        *self.is_running.lock().unwrap()
+    }
+
+    /// Start checking, calling `on_due` whenever the next wallpaper is due
+    ///
+    /// `on_due` should change the wallpaper; a change that fails is tried again
+    /// once another interval has passed.
+    pub fn start(&mut self, on_due: impl Fn() + Send + 'static) -> AppResult<()> {
+        if *self.is_running.lock().unwrap() {
+            debug!("Auto-change is already running");
+            return Ok(());
+        }
+
+        *self.is_running.lock().unwrap() = true;
+
+        let config = self.config.clone();
+        let is_running = self.is_running.clone();
+
+        self.check_thread = Some(thread::spawn(move || {
+            // Counts in for the first change when nothing is on screen yet
+            let mut last_change = Utc::now();
+
+            while *is_running.lock().unwrap() {
+                let auto_change = config.lock().unwrap().clone();
+                let current = CurrentWallpaperStore::shared().get();
+                if is_due(&auto_change, current.as_ref(), last_change, Utc::now()) {
+                    debug!("Changing to the next wallpaper of the auto-change folder");
+                    last_change = Utc::now();
+                    on_due();
+                }
+
+                thread::sleep(CHECK_INTERVAL);
+            }
+        }));
+
+        info!("Auto-change started");
+        Ok(())
+    }
+
+    /// Stop checking
+    pub fn stop(&mut self) -> AppResult<()> {
+        if !*self.is_running.lock().unwrap() {
+            debug!("Auto-change is not running");
+            return Ok(());
+        }
+
+        *self.is_running.lock().unwrap() = false;
+
+        if let Some(thread) = self.check_thread.take() {
+            thread.join().map_err(|e| {
+                AppError::Other(format!("Failed to join auto-change thread: {:?}", e))
+            })?;
+        }
+
+        info!("Auto-change stopped");
+        Ok(())
+    }
+}
+
+/// Check whether the next wallpaper of the folder is due
+///
+/// `last_change` is when auto-change last changed the wallpaper, or started.
+fn is_due(
+    config: &AutoChangeConfig,
+    current: Option<&CurrentWallpaperState>,
+    last_change: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> bool {
+    let Some(folder) = config.folder.as_deref().filter(|_| config.enabled) else {
+        return false;
+    };
+    let since = match current {
+        // Someone else chose a wallpaper from elsewhere, which stays
+        Some(current)
+            if current.wallpaper.path.as_deref().and_then(Path::parent)
+                != Some(Path::new(folder)) =>
+        {
+            return false
+        }
+        Some(current) => current.started_at.max(last_change),
+        None => last_change,
+    };
+    now.signed_duration_since(since) >= Duration::minutes(config.interval.max(1) as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{WallpaperInfo, WallpaperType};
+
+    fn showing(path: &str, started_at: DateTime<Utc>) -> CurrentWallpaperState {
+        let wallpaper = WallpaperInfo::new(WallpaperType::Static, Some(path.into()), None);
+        CurrentWallpaperState {
+            started_at,
+            ..CurrentWallpaperState::new(wallpaper, Vec::new(), None)
+        }
+    }
+
+    #[test]
+    fn test_is_due() {
+        let config = AutoChangeConfig {
+            enabled: true,
+            interval: 30,
+            folder: Some("/walls".to_string()),
+            match_resolution: false,
+            resolution_tolerance: 0.1,
+            palette_family: None,
+        };
+        let now = Utc::now();
+        let long_ago = now - Duration::hours(2);
+
+        // Counted from the latest change, whoever made it
+        assert!(is_due(
+            &config,
+            Some(&showing("/walls/a.png", now - Duration::minutes(30))),
+            long_ago,
+            now
+        ));
+        assert!(!is_due(
+            &config,
+            Some(&showing("/walls/a.png", now - Duration::minutes(10))),
+            long_ago,
+            now
+        ));
+        assert!(!is_due(
+            &config,
+            Some(&showing("/walls/a.png", long_ago)),
+            now - Duration::minutes(5),
+            now
+        ));
+        assert!(is_due(&config, None, long_ago, now));
+        assert!(!is_due(&config, None, now, now));
+
+        // Wallpapers from elsewhere are kept
+        assert!(!is_due(
+            &config,
+            Some(&showing("/schedule/night.png", long_ago)),
+            long_ago,
+            now
+        ));
+        assert!(!is_due(
+            &config,
+            Some(&showing("/walls/sub/b.png", long_ago)),
+            long_ago,
+            now
+        ));
+
+        assert!(!is_due(
+            &AutoChangeConfig {
+                enabled: false,
+                ..config.clone()
+            },
+            None,
+            long_ago,
+            now
+        ));
+        assert!(!is_due(
+            &AutoChangeConfig {
+                folder: None,
+                ..config
+            },
+            Some(&showing("/walls/a.png", long_ago)),
+            long_ago,
+            now
+        ));
+    }
+}
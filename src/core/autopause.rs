@@ -0,0 +1,312 @@
+//! Automatic pausing of animated wallpapers
+//!
+//! Animated wallpapers are paused while a fullscreen application such as a
+//! game is focused, so they don't cost it frames, and resume when it closes
+//! or loses focus. Wallpapers whose player can't pause keep running.
+//!
+//! On battery power an animated wallpaper is swapped for a still frame
+//! captured from it, and started again once the machine is back on AC.
+//!
+//! When enabled, wallpapers are also paused while nobody has touched the
+//! keyboard or mouse for a while. Input is then polled more often, so the
+//! wallpaper resumes as soon as the user is back.
+//!
+//! Wallpapers can also be held paused on request, e.g. from the chat bot,
+//! until they're resumed the same way.
+use crate::core::config::AutoPauseConfig;
+use crate::core::scheduler::CurrentWallpaper;
+use crate::core::{runtime, AppError, AppResult, Config, WallpaperType};
+use crate::platform::{fullscreen, idle, power, WallpaperManager};
+use crate::wallpapers::Wallpaper;
+use log::{debug, info, warn};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Time between checks for a fullscreen application
+const CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Time between checks for input while the user is away
+pub const IDLE_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Animated wallpaper replaced by the battery saver
+struct Swapped {
+    /// Path of the animated wallpaper
+    path: Option<PathBuf>,
+
+    /// Whether it was stopped; wallpapers without a frame to show keep running
+    stopped: bool,
+}
+
+/// Pauses the running wallpaper while a fullscreen application is focused
+/// and swaps it for a still frame on battery power
+pub struct AutoPause {
+    /// Auto-pause configuration
+    config: Arc<Mutex<AutoPauseConfig>>,
+
+    /// Wallpaper to pause
+    current_wallpaper: CurrentWallpaper,
+
+    /// Platform-specific wallpaper manager, which shows the still frames
+    wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>,
+
+    /// Check thread handle
+    check_thread: Option<thread::JoinHandle<()>>,
+
+    /// Whether the checks are running
+    is_running: Arc<Mutex<bool>>,
+
+    /// Whether the wallpaper is paused right now
+    is_paused: Arc<Mutex<bool>>,
+
+    /// Whether the battery saver is active right now
+    saving_power: Arc<Mutex<bool>>,
+
+    /// Whether the user is away right now
+    user_away: Arc<Mutex<bool>>,
+
+    /// Whether the wallpaper is held paused on request
+    held: Arc<Mutex<bool>>,
+}
+
+#[allow(dead_code)]
+impl AutoPause {
+    /// Create a new auto-pause service for the scheduler's wallpaper
+    pub fn new(
+        config: AutoPauseConfig,
+        current_wallpaper: CurrentWallpaper,
+        wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>,
+    ) -> Self {
+        Self {
+            config: Arc::new(Mutex::new(config)),
+            current_wallpaper,
+            wallpaper_manager,
+            check_thread: None,
+            is_running: Arc::new(Mutex::new(false)),
+            is_paused: Arc::new(Mutex::new(false)),
+            saving_power: Arc::new(Mutex::new(false)),
+            user_away: Arc::new(Mutex::new(false)),
+            held: Arc::new(Mutex::new(false)),
+        }
+    }
+
+    /// Update the auto-pause configuration
+    pub fn update_config(&self, config: AutoPauseConfig) {
+        *self.config.lock().unwrap() = config;
+    }
+
+    /// Check whether the wallpaper is paused because of a fullscreen application or the user being away
+    pub fn is_paused(&self) -> bool {
+        *self.is_paused.lock().unwrap()
+    }
+
+    /// Check whether the battery saver is active
+    pub fn is_saving_power(&self) -> bool {
+        *self.saving_power.lock().unwrap()
+    }
+
+    /// Check whether the wallpaper is paused because the user is away
+    pub fn is_user_away(&self) -> bool {
+        *self.user_away.lock().unwrap()
+    }
+
+    /// Hold the wallpaper paused, or let it resume
+    ///
+    /// Takes effect on the next check, within a second.
+    pub fn hold(&self, held: bool) {
+        *self.held.lock().unwrap() = held;
+    }
+
+    /// Check whether the wallpaper is held paused on request
+    pub fn is_held(&self) -> bool {
+        *self.held.lock().unwrap()
+    }
+
+    /// Start the check thread
+    pub fn start(&mut self) -> AppResult<()> {
+        if *self.is_running.lock().unwrap() {
+            debug!("Auto-pause is already running");
+            return Ok(());
+        }
+
+        *self.is_running.lock().unwrap() = true;
+
+        let config = self.config.clone();
+        let current_wallpaper = self.current_wallpaper.clone();
+        let wallpaper_manager = self.wallpaper_manager.clone();
+        let is_running = self.is_running.clone();
+        let is_paused = self.is_paused.clone();
+        let saving_power = self.saving_power.clone();
+        let user_away = self.user_away.clone();
+        let held = self.held.clone();
+
+        self.check_thread = Some(thread::spawn(move || {
+            let mut last_check: Option<Instant> = None;
+            let mut swapped: Option<Swapped> = None;
+            let mut was_held = false;
+
+            while *is_running.lock().unwrap() {
+                // Input while away, and holding or letting go, act right away
+                let away = *user_away.lock().unwrap();
+                if away && !is_user_away(&config.lock().unwrap()) {
+                    last_check = None;
+                }
+                let hold = *held.lock().unwrap();
+                if hold != std::mem::replace(&mut was_held, hold) {
+                    last_check = None;
+                }
+
+                if last_check.map_or(true, |last| last.elapsed() >= CHECK_INTERVAL) {
+                    last_check = Some(Instant::now());
+                    let config = config.lock().unwrap().clone();
+
+                    let save = config.on_battery
+                        && power::power_status()
+                            .map_or(false, |status| status.should_save(config.battery_threshold));
+                    if save != std::mem::replace(&mut *saving_power.lock().unwrap(), save) {
+                        info!(
+                            "{}",
+                            if save {
+                                "On battery power, saving power"
+                            } else {
+                                "Stopped saving power"
+                            }
+                        );
+                    }
+
+                    // Only a wallpaper on every monitor can be swapped for a still frame
+                    if let Some(wallpaper) = current_wallpaper.lock().unwrap().get(None) {
+                        let animated = wallpaper.get_type() != WallpaperType::Static;
+                        let path = wallpaper.get_path().map(Path::to_path_buf);
+                        let current = swapped
+                            .as_ref()
+                            .map_or(false, |swapped| swapped.path == path);
+
+                        if save && animated && !current {
+                            // Also swaps a wallpaper applied while saving power
+                            let stopped = match runtime::block_on(show_still_frame(
+                                wallpaper.as_ref(),
+                                wallpaper_manager.as_ref(),
+                            )) {
+                                Ok(()) => true,
+                                Err(e) => {
+                                    warn!("Failed to swap wallpaper for a still frame: {}", e);
+                                    false
+                                }
+                            };
+                            swapped = Some(Swapped { path, stopped });
+                        } else if !save && swapped.is_some() {
+                            if animated
+                                && current
+                                && swapped.as_ref().map_or(false, |swapped| swapped.stopped)
+                            {
+                                info!("Restarting animated wallpaper");
+                                if let Err(e) = runtime::block_on(wallpaper.start()) {
+                                    warn!("Failed to restart wallpaper: {}", e);
+                                }
+                            }
+                            swapped = None;
+                        }
+                    }
+
+                    // A stopped wallpaper mustn't be resumed, which would start it again
+                    let stopped = swapped.as_ref().map_or(false, |swapped| swapped.stopped);
+                    let away = is_user_away(&config);
+                    if away != std::mem::replace(&mut *user_away.lock().unwrap(), away) {
+                        info!("{}", if away { "User is away" } else { "User is back" });
+                    }
+                    let pause = !stopped
+                        && (hold
+                            || (config.on_fullscreen && fullscreen::fullscreen_app_focused())
+                            || away);
+                    let was_paused = std::mem::replace(&mut *is_paused.lock().unwrap(), pause);
+                    if pause != was_paused {
+                        info!(
+                            "{} animated wallpaper",
+                            if pause { "Pausing" } else { "Resuming" }
+                        );
+                    }
+
+                    // Pausing is repeated so a wallpaper applied in the meantime is paused too
+                    if (pause || was_paused) && !stopped {
+                        for (_, wallpaper) in current_wallpaper.lock().unwrap().iter() {
+                            if wallpaper.get_type() != WallpaperType::Static {
+                                let result = runtime::block_on(async {
+                                    if pause {
+                                        wallpaper.pause().await
+                                    } else {
+                                        wallpaper.resume().await
+                                    }
+                                });
+                                if let Err(e) = result {
+                                    debug!(
+                                        "Failed to {} wallpaper: {}",
+                                        if pause { "pause" } else { "resume" },
+                                        e
+                                    );
+                                }
+                            }
+                        }
+                    }
+                }
+
+                let away = *user_away.lock().unwrap();
+                thread::sleep(if away {
+                    IDLE_POLL_INTERVAL
+                } else {
+                    Duration::from_secs(1)
+                });
+            }
+        }));
+
+        info!("Auto-pause started");
+        Ok(())
+    }
+
+    /// Stop the check thread
+    pub fn stop(&mut self) -> AppResult<()> {
+        if !*self.is_running.lock().unwrap() {
+            debug!("Auto-pause is not running");
+            return Ok(());
+        }
+
+        *self.is_running.lock().unwrap() = false;
+
+        if let Some(thread) = self.check_thread.take() {
+            thread.join().map_err(|e| {
+                AppError::Other(format!("Failed to join auto-pause thread: {:?}", e))
+            })?;
+        }
+
+        info!("Auto-pause stopped");
+        Ok(())
+    }
+}
+
+/// Check whether the user has been away long enough to pause for
+///
+/// Always false when idle pausing is off or the platform doesn't report input.
+pub fn is_user_away(config: &AutoPauseConfig) -> bool {
+    config.on_idle
+        && idle::idle_time().map_or(false, |idle| {
+            idle >= Duration::from_secs(config.idle_minutes.max(1) * 60)
+        })
+}
+
+/// Stop an animated wallpaper and show a frame captured from it instead
+async fn show_still_frame(
+    wallpaper: &(dyn Wallpaper + Send + Sync),
+    wallpaper_manager: &(dyn WallpaperManager + Send + Sync),
+) -> AppResult<()> {
+    let name = wallpaper
+        .get_path()
+        .and_then(Path::file_stem)
+        .map_or_else(|| "wallpaper".into(), |stem| stem.to_string_lossy());
+    let frame = Config::get_frames_dir().join(format!("{}.png", name));
+    wallpaper.capture_frame(&frame).await?;
+    wallpaper.stop().await?;
+    wallpaper_manager.set_static_wallpaper(&frame).await?;
+    info!("Showing a still frame to save power");
+    Ok(())
+}
@@ -0,0 +1,546 @@
+//! Wallpaper benchmark
+//!
+//! Runs a wallpaper for a while, samples the system, the wallpaper players and
+//! the GPU every second and rates how well the machine copes with it. External
+//! players don't report their frame rate, so the app's own frame rate while the
+//! wallpaper runs stands in for how smooth the desktop stays.
+use crate::core::config::ResourceLimitsConfig;
+use crate::core::performance::PerformanceMonitor;
+use crate::core::resource_manager::{ResourceLimits, ResourceManager, ResourceUsage};
+use crate::core::{runtime, ProcessInfo};
+use log::debug;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How long a benchmark runs unless told otherwise
+pub const DEFAULT_DURATION: Duration = Duration::from_secs(30);
+
+/// Time between samples
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Average system CPU usage above which a wallpaper is too heavy, in percent
+const CPU_POOR: f32 = 70.0;
+
+/// Average system CPU usage above which a wallpaper is noticeable, in percent
+const CPU_FAIR: f32 = 40.0;
+
+/// Average GPU load above which a wallpaper is too heavy, in percent
+const GPU_POOR: f32 = 85.0;
+
+/// Average GPU load above which a wallpaper is noticeable, in percent
+const GPU_FAIR: f32 = 50.0;
+
+/// Frame rate below which the desktop stutters
+const FPS_POOR: f32 = 30.0;
+
+/// Frame rate below which the desktop is less smooth than usual
+const FPS_FAIR: f32 = 50.0;
+
+/// How well a wallpaper suits this machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Suitability {
+    /// Runs without a noticeable cost
+    Good,
+
+    /// Runs, but uses a noticeable share of the machine
+    Fair,
+
+    /// Too heavy for this machine
+    Poor,
+}
+
+/// What held a wallpaper's rating back
+#[derive(Debug, Clone, PartialEq)]
+pub enum Bottleneck {
+    /// A player went over the configured resource limits
+    ResourceLimit(String),
+
+    /// System CPU usage
+    Cpu,
+
+    /// GPU load
+    Gpu,
+
+    /// The app's frame rate
+    FrameRate,
+}
+
+/// One benchmark sample
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Sample {
+    /// System CPU usage in percent, once the monitor has it
+    system_cpu: Option<f32>,
+
+    /// CPU usage of the wallpaper players in percent (100 = one core)
+    player_cpu: f32,
+
+    /// Memory of the wallpaper players in bytes
+    player_memory: u64,
+
+    /// GPU load in percent, if the GPU reports it
+    gpu_busy: Option<f32>,
+
+    /// App frame rate, once the monitor has one
+    fps: Option<f32>,
+}
+
+/// What a background sample found out about the wallpaper
+#[derive(Debug, Clone)]
+pub struct Probe {
+    /// Wallpaper players
+    pub processes: Vec<ProcessInfo>,
+
+    /// Whether the daemon currently pauses, freezes or lowers the wallpaper
+    pub throttled: bool,
+
+    /// GPU load in percent, if the GPU reports it
+    pub gpu_busy: Option<f32>,
+}
+
+/// Benchmark results
+#[derive(Debug, Clone)]
+pub struct BenchmarkReport {
+    /// Wallpaper name
+    pub wallpaper: String,
+
+    /// How long the wallpaper ran
+    pub duration: Duration,
+
+    /// Average and peak system CPU usage in percent
+    pub system_cpu: Option<(f32, f32)>,
+
+    /// Average and peak CPU usage of the players in percent
+    pub player_cpu: (f32, f32),
+
+    /// Peak memory of the players in bytes
+    pub player_memory: u64,
+
+    /// Average and peak GPU load in percent, if the GPU reports it
+    pub gpu_busy: Option<(f32, f32)>,
+
+    /// Average and lowest app frame rate
+    pub fps: Option<(f32, f32)>,
+
+    /// Whether the daemon paused, froze or lowered the wallpaper during the run
+    pub throttled: bool,
+
+    /// Rating for this machine
+    pub rating: Suitability,
+
+    /// What held the rating back
+    pub bottlenecks: Vec<Bottleneck>,
+}
+
+/// Running benchmark
+pub struct Benchmark {
+    /// Wallpaper name
+    wallpaper: String,
+
+    /// How long to run
+    duration: Duration,
+
+    /// Start time
+    started: Instant,
+
+    /// System CPU usage and frame rate
+    monitor: PerformanceMonitor,
+
+    /// Player usage, checked against the resource limits
+    resources: Arc<ResourceManager>,
+
+    /// Samples taken so far
+    samples: Vec<Sample>,
+
+    /// Time of the last sample
+    last_sample: Option<Instant>,
+
+    /// Whether the daemon throttled the wallpaper
+    throttled: bool,
+
+    /// Sample being taken in the background
+    pending: Option<Receiver<(Probe, ResourceUsage)>>,
+}
+
+#[allow(dead_code)]
+impl Benchmark {
+    /// Start a benchmark of a wallpaper that was just applied
+    pub fn new(wallpaper: &str, duration: Duration, limits: &ResourceLimitsConfig) -> Self {
+        Self {
+            wallpaper: wallpaper.to_string(),
+            duration,
+            started: Instant::now(),
+            monitor: PerformanceMonitor::new(),
+            resources: Arc::new(ResourceManager::new(ResourceLimits::from(limits))),
+            samples: Vec::new(),
+            last_sample: None,
+            throttled: false,
+            pending: None,
+        }
+    }
+
+    /// Record the time it took to build a frame
+    pub fn end_frame(&mut self, frame_time: Duration) {
+        self.monitor.update_frame_timing(frame_time);
+    }
+
+    /// Check whether the next sample is due
+    pub fn is_sample_due(&self) -> bool {
+        self.pending.is_none()
+            && self
+                .last_sample
+                .map_or(true, |last| last.elapsed() >= SAMPLE_INTERVAL)
+    }
+
+    /// Sample the wallpaper players and the system
+    ///
+    /// `throttled` tells whether the daemon currently pauses, freezes or
+    /// lowers the wallpaper.
+    pub async fn sample(
+        &mut self,
+        processes: &[ProcessInfo],
+        throttled: bool,
+        gpu_busy: Option<f32>,
+    ) {
+        self.last_sample = Some(Instant::now());
+        let players = track_players(&self.resources, processes).await;
+        self.record(players, throttled, gpu_busy);
+    }
+
+    /// Start a sample in the background
+    ///
+    /// `probe` runs on a blocking worker, as asking the daemon for its players
+    /// or the GPU for its load can take a while. The result is added by
+    /// `collect_sample` once it's ready.
+    pub fn start_sample(&mut self, probe: impl FnOnce() -> Probe + Send + 'static) {
+        self.last_sample = Some(Instant::now());
+        let resources = self.resources.clone();
+        let (sender, receiver) = mpsc::channel();
+        self.pending = Some(receiver);
+        runtime::shared().spawn(async move {
+            let Ok(probe) = tokio::task::spawn_blocking(probe).await else {
+                return;
+            };
+            let players = track_players(&resources, &probe.processes).await;
+            let _ = sender.send((probe, players));
+        });
+    }
+
+    /// Add the sample started in the background, if it's ready
+    pub fn collect_sample(&mut self) {
+        let Some(pending) = &self.pending else {
+            return;
+        };
+        match pending.try_recv() {
+            Ok((probe, players)) => {
+                self.pending = None;
+                self.record(players, probe.throttled, probe.gpu_busy);
+            }
+            Err(TryRecvError::Empty) => {}
+            Err(TryRecvError::Disconnected) => self.pending = None,
+        }
+    }
+
+    /// Add a sample of the players' usage and the system
+    fn record(&mut self, players: ResourceUsage, throttled: bool, gpu_busy: Option<f32>) {
+        self.throttled |= throttled;
+        let metrics = self.monitor.get_current_metrics();
+        self.samples.push(Sample {
+            system_cpu: metrics.map(|metrics| metrics.cpu_usage),
+            player_cpu: players.cpu_usage,
+            player_memory: players.memory_used,
+            gpu_busy,
+            fps: metrics.map(|metrics| metrics.fps),
+        });
+    }
+
+    /// Get the share of the run that is done, from 0 to 1
+    pub fn progress(&self) -> f32 {
+        (self.started.elapsed().as_secs_f32() / self.duration.as_secs_f32()).min(1.0)
+    }
+
+    /// Check whether the run is over
+    pub fn is_finished(&self) -> bool {
+        self.started.elapsed() >= self.duration
+    }
+
+    /// Get the wallpaper name
+    pub fn wallpaper(&self) -> &str {
+        &self.wallpaper
+    }
+
+    /// Summarize the samples and rate the wallpaper
+    pub async fn report(&self) -> BenchmarkReport {
+        // The first CPU sample has nothing to compare against
+        let samples = if self.samples.len() > 1 {
+            &self.samples[1..]
+        } else {
+            &self.samples[..]
+        };
+
+        let peak_players = ResourceUsage {
+            memory_used: samples
+                .iter()
+                .map(|sample| sample.player_memory)
+                .max()
+                .unwrap_or(0),
+            cpu_usage: samples
+                .iter()
+                .map(|sample| sample.player_cpu)
+                .fold(0.0, f32::max),
+            gpu_memory_used: 0,
+            active_processes: 1,
+        };
+        let over_limit = self.resources.check_usage(&peak_players).await;
+
+        let system_cpu = average_and_peak(samples.iter().filter_map(|sample| sample.system_cpu));
+        let player_cpu = average_and_peak(samples.iter().map(|sample| sample.player_cpu));
+        let gpu_busy = average_and_peak(samples.iter().filter_map(|sample| sample.gpu_busy));
+        let fps = average_and_lowest(samples.iter().filter_map(|sample| sample.fps));
+        let average = |values: Option<(f32, f32)>| values.map(|(average, _)| average);
+        let (rating, bottlenecks) = rate(
+            average(system_cpu),
+            average(gpu_busy),
+            average(fps),
+            over_limit,
+        );
+
+        BenchmarkReport {
+            wallpaper: self.wallpaper.clone(),
+            duration: self.started.elapsed().min(self.duration),
+            system_cpu,
+            player_cpu: player_cpu.unwrap_or_default(),
+            player_memory: peak_players.memory_used,
+            gpu_busy,
+            fps,
+            throttled: self.throttled,
+            rating,
+            bottlenecks,
+        }
+    }
+}
+
+/// Track the wallpaper players' usage and get their total
+async fn track_players(resources: &ResourceManager, processes: &[ProcessInfo]) -> ResourceUsage {
+    for id in resources.resource_ids().await {
+        if !processes
+            .iter()
+            .any(|process| process.pid.to_string() == id)
+        {
+            let _ = resources.unregister_resource(&id).await;
+        }
+    }
+    for process in processes {
+        let id = process.pid.to_string();
+        // Registering checks the limits, updating records any usage
+        if resources.get_resource_usage(&id).await.is_none() {
+            let empty = ResourceUsage {
+                memory_used: 0,
+                cpu_usage: 0.0,
+                gpu_memory_used: 0,
+                active_processes: 1,
+            };
+            if let Err(e) = resources.register_resource(id.clone(), empty).await {
+                debug!(
+                    "Benchmark can't track {} ({}): {}",
+                    process.name, process.pid, e
+                );
+                continue;
+            }
+        }
+        let usage = ResourceUsage {
+            memory_used: process.memory_bytes,
+            cpu_usage: process.cpu_usage,
+            gpu_memory_used: 0,
+            active_processes: 1,
+        };
+        let _ = resources.update_resource(&id, usage).await;
+    }
+
+    resources.get_usage().await
+}
+
+/// Rate a wallpaper from its average usage
+///
+/// The worst metric decides; every metric that lowered the rating is returned.
+fn rate(
+    system_cpu: Option<f32>,
+    gpu_busy: Option<f32>,
+    fps: Option<f32>,
+    over_limit: Option<String>,
+) -> (Suitability, Vec<Bottleneck>) {
+    let mut checks = Vec::new();
+    if let Some(reason) = over_limit {
+        checks.push((Suitability::Poor, Bottleneck::ResourceLimit(reason)));
+    }
+    if let Some(cpu) = system_cpu {
+        checks.push((level(cpu > CPU_POOR, cpu > CPU_FAIR), Bottleneck::Cpu));
+    }
+    if let Some(gpu) = gpu_busy {
+        checks.push((level(gpu > GPU_POOR, gpu > GPU_FAIR), Bottleneck::Gpu));
+    }
+    if let Some(fps) = fps {
+        checks.push((level(fps < FPS_POOR, fps < FPS_FAIR), Bottleneck::FrameRate));
+    }
+
+    let rating = checks
+        .iter()
+        .map(|(rating, _)| *rating)
+        .max()
+        .unwrap_or(Suitability::Good);
+    let bottlenecks = checks
+        .into_iter()
+        .filter(|(rating, _)| *rating != Suitability::Good)
+        .map(|(_, bottleneck)| bottleneck)
+        .collect();
+    (rating, bottlenecks)
+}
+
+/// Pick a rating from whether the poor and fair thresholds were crossed
+fn level(poor: bool, fair: bool) -> Suitability {
+    if poor {
+        Suitability::Poor
+    } else if fair {
+        Suitability::Fair
+    } else {
+        Suitability::Good
+    }
+}
+
+/// Get the average and the highest value
+fn average_and_peak(values: impl Iterator<Item = f32>) -> Option<(f32, f32)> {
+    let values: Vec<f32> = values.collect();
+    if values.is_empty() {
+        return None;
+    }
+    Some((
+        values.iter().sum::<f32>() / values.len() as f32,
+        values.iter().copied().fold(f32::MIN, f32::max),
+    ))
+}
+
+/// Get the average and the lowest value
+fn average_and_lowest(values: impl Iterator<Item = f32>) -> Option<(f32, f32)> {
+    let values: Vec<f32> = values.collect();
+    if values.is_empty() {
+        return None;
+    }
+    Some((
+        values.iter().sum::<f32>() / values.len() as f32,
+        values.iter().copied().fold(f32::MAX, f32::min),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate() {
+        assert_eq!(
+            rate(Some(10.0), Some(20.0), Some(60.0), None),
+            (Suitability::Good, Vec::new())
+        );
+        assert_eq!(
+            rate(None, None, None, None),
+            (Suitability::Good, Vec::new())
+        );
+        assert_eq!(
+            rate(Some(50.0), None, Some(60.0), None),
+            (Suitability::Fair, vec![Bottleneck::Cpu])
+        );
+        assert_eq!(
+            rate(Some(50.0), Some(90.0), Some(45.0), None),
+            (
+                Suitability::Poor,
+                vec![Bottleneck::Cpu, Bottleneck::Gpu, Bottleneck::FrameRate]
+            )
+        );
+
+        let (rating, bottlenecks) = rate(Some(10.0), None, None, Some("uses too much".to_string()));
+        assert_eq!(rating, Suitability::Poor);
+        assert_eq!(
+            bottlenecks,
+            vec![Bottleneck::ResourceLimit("uses too much".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_report_uses_player_samples() {
+        let limits = ResourceLimitsConfig {
+            max_memory_mb: 100,
+            ..ResourceLimitsConfig::default()
+        };
+        let mut benchmark = Benchmark::new("ocean.mp4", Duration::from_secs(5), &limits);
+        let player = |memory_mb: u64, cpu_usage: f32| ProcessInfo {
+            pid: 42,
+            name: "mpv".to_string(),
+            role: "video wallpaper".to_string(),
+            started: String::new(),
+            memory_bytes: memory_mb * 1024 * 1024,
+            cpu_usage,
+            paused: false,
+        };
+
+        benchmark
+            .sample(&[player(10, 90.0)], false, Some(5.0))
+            .await;
+        benchmark
+            .sample(&[player(50, 20.0)], false, Some(30.0))
+            .await;
+        benchmark
+            .sample(&[player(200, 10.0)], true, Some(60.0))
+            .await;
+
+        let report = benchmark.report().await;
+        assert_eq!(report.wallpaper, "ocean.mp4");
+        // No frames were drawn
+        assert_eq!(report.fps, None);
+        // The first sample is skipped
+        assert_eq!(report.player_cpu, (15.0, 20.0));
+        assert_eq!(report.gpu_busy, Some((45.0, 60.0)));
+        assert_eq!(report.player_memory, 200 * 1024 * 1024);
+        assert!(report.throttled);
+        assert_eq!(report.rating, Suitability::Poor);
+        assert!(matches!(
+            report.bottlenecks.first(),
+            Some(Bottleneck::ResourceLimit(_))
+        ));
+    }
+
+    #[test]
+    fn test_background_sample() {
+        let mut benchmark = Benchmark::new(
+            "ocean.mp4",
+            Duration::from_secs(5),
+            &ResourceLimitsConfig::default(),
+        );
+        let player = ProcessInfo {
+            pid: 42,
+            name: "mpv".to_string(),
+            role: "video wallpaper".to_string(),
+            started: String::new(),
+            memory_bytes: 64 * 1024 * 1024,
+            cpu_usage: 25.0,
+            paused: false,
+        };
+        benchmark.start_sample(move || Probe {
+            processes: vec![player],
+            throttled: true,
+            gpu_busy: Some(40.0),
+        });
+        assert!(!benchmark.is_sample_due());
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while benchmark.pending.is_some() && Instant::now() < deadline {
+            benchmark.collect_sample();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        assert_eq!(benchmark.samples.len(), 1);
+        assert_eq!(benchmark.samples[0].player_cpu, 25.0);
+        assert_eq!(benchmark.samples[0].player_memory, 64 * 1024 * 1024);
+        assert_eq!(benchmark.samples[0].gpu_busy, Some(40.0));
+        assert!(benchmark.throttled);
+    }
+}
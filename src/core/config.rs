@@ -1,21 +1,49 @@
+use crate::core::format;
+use crate::wallpapers::animated_image;
 use anyhow::Result;
+use chrono::NaiveTime;
 use dirs::config_dir;
 use log::{debug, info};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Application configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     /// Current wallpaper settings
     pub wallpaper: WallpaperConfig,
-    
+
     /// Application settings
     pub app: AppConfig,
-    
+
     /// Plugin settings
     pub plugins: PluginConfig,
+
+    /// Online source settings
+    #[serde(default)]
+    pub sources: SourcesConfig,
+
+    /// Local HTTP API settings
+    #[serde(default)]
+    pub api: ApiConfig,
+
+    /// Image generation settings
+    #[serde(default)]
+    pub ai: AiConfig,
+
+    /// Automatic tagging of imported wallpapers
+    #[serde(default)]
+    pub tagging: TaggingConfig,
+
+    /// Wallpaper sync with other computers on the LAN
+    #[serde(default)]
+    pub sync: SyncConfig,
+
+    /// Remote control from a Telegram or Discord bot
+    #[serde(default)]
+    pub bot: BotConfig,
 }
 
 /// Wallpaper configuration
@@ -23,96 +51,1957 @@ pub struct Config {
 pub struct WallpaperConfig {
     /// Current wallpaper path
     pub current_path: Option<String>,
-    
+
     /// Wallpaper type
     pub wallpaper_type: WallpaperType,
-    
+
     /// Auto-change settings
     pub auto_change: AutoChangeConfig,
+
+    /// Favorited wallpaper paths
+    #[serde(default)]
+    pub favorites: Vec<String>,
+
+    /// Last static wallpaper, shown when an animated wallpaper keeps crashing
+    #[serde(default)]
+    pub last_static: Option<String>,
+
+    /// How static wallpapers are fitted to the monitors
+    #[serde(default)]
+    pub placement: PlacementConfig,
+
+    /// Shell commands run after every wallpaper change
+    #[serde(default)]
+    pub change_hooks: Vec<String>,
+
+    /// Color palette export for static wallpapers
+    #[serde(default)]
+    pub palette: PaletteConfig,
+
+    /// Desktop accent color following static wallpapers
+    #[serde(default)]
+    pub accent_sync: AccentSyncConfig,
+
+    /// Frame rate limits of animated wallpapers
+    #[serde(default)]
+    pub frame_rate: FrameRateConfig,
+
+    /// Wallpapers applied to single monitors, by monitor ID, shown instead of the current one
+    #[serde(default)]
+    pub monitor_wallpapers: BTreeMap<String, crate::core::WallpaperInfo>,
+
+    /// Wallpapers shown while a workspace or virtual desktop is active, by workspace ID
+    #[serde(default)]
+    pub workspace_wallpapers: BTreeMap<String, WorkspaceWallpaper>,
+
+    /// Whether static wallpapers set on the whole desktop are also set on the lock screen
+    #[serde(default)]
+    pub sync_lock_screen: bool,
+
+    /// Animation between one static wallpaper and the next
+    #[serde(default)]
+    pub transition: TransitionConfig,
+
+    /// Motion of animated static wallpapers
+    #[serde(default)]
+    pub animation: AnimatedStaticConfig,
+
+    /// Speed and loops of animated image wallpapers
+    #[serde(default)]
+    pub animated_image: AnimatedImageConfig,
+
+    /// Look and behavior of the particle wallpaper
+    #[serde(default)]
+    pub particles: ParticlesConfig,
+
+    /// Settings of the procedural wallpapers
+    #[serde(default)]
+    pub procedural: ProceduralConfig,
+
+    /// Settings of slideshow wallpapers
+    #[serde(default)]
+    pub slideshow: SlideshowConfig,
+
+    /// Settings of the now-playing album art wallpaper
+    #[serde(default)]
+    pub now_playing: NowPlayingConfig,
+
+    /// Time-of-day tint of static wallpapers
+    #[serde(default)]
+    pub color_temperature: ColorTemperatureConfig,
+
+    /// Effects drawn over static wallpapers to match the weather outside
+    #[serde(default)]
+    pub weather_effects: WeatherEffectsConfig,
+
+    /// Audio-reactive effects drawn over static and video wallpapers
+    #[serde(default)]
+    pub audio_overlay: AudioOverlayConfig,
+
+    /// Copy of the wallpaper sent to a virtual camera or OBS
+    #[serde(default)]
+    pub virtual_camera: VirtualCameraConfig,
 }
 
-/// Wallpaper type
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub enum WallpaperType {
-    /// Static image
-    Static,
-    
-    /// Video
-    Video,
-    
-    /// Web-based
-    Web,
-    
-    /// Shader-based
-    Shader,
-    
-    /// Audio-reactive
-    Audio,
+/// Wallpaper assigned to a workspace
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceWallpaper {
+    /// Workspace name when it was assigned, to show in lists
+    pub workspace: String,
+
+    /// Wallpaper shown on the workspace
+    pub wallpaper: crate::core::WallpaperInfo,
 }
 
-/// Auto-change configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AutoChangeConfig {
-    /// Whether auto-change is enabled
+/// Wallpaper placement on the monitors
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlacementConfig {
+    /// Fit mode
+    pub mode: FitMode,
+
+    /// Whether one wallpaper spans all monitors as a single canvas
+    ///
+    /// Applies to static, video and shader wallpapers; the fit mode then
+    /// fits static images to the canvas rather than to each monitor.
+    #[serde(default)]
+    pub span: bool,
+
+    /// Crop rectangle per monitor name, for the crop mode
+    ///
+    /// Monitors without one use a centered crop.
+    #[serde(default)]
+    pub crops: HashMap<String, CropRect>,
+
+    /// Image per monitor ID, shown on that monitor instead of the static wallpaper
+    #[serde(default)]
+    pub monitor_images: HashMap<String, String>,
+
+    /// Whether the fill mode crops around the most detailed part of the image
+    /// instead of its middle
+    #[serde(default)]
+    pub smart_crop: bool,
+
+    /// Whether portrait monitors show landscape images with the fit mode like
+    /// any other, instead of filled with a crop around their most detailed part
+    #[serde(default)]
+    pub letterbox_portrait: bool,
+
+    /// Whether every wallpaper is shown alike on all monitors, ignoring the
+    /// monitors' own wallpapers and images and spanning
+    #[serde(default)]
+    pub mirror: bool,
+}
+
+/// Color palette export settings
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PaletteConfig {
+    /// Whether to write the palette after each static wallpaper
     pub enabled: bool,
-    
-    /// Change interval in minutes
-    pub interval: u32,
-    
-    /// Folder to pick wallpapers from
-    pub folder: Option<String>,
+
+    /// Directory for the palette files (pywal's cache when unset)
+    #[serde(default)]
+    pub output_dir: Option<String>,
+
+    /// What recolors terminals, bars and GTK themes from the palette
+    #[serde(default)]
+    pub theming: ThemingTool,
+
+    /// Folder of pywal-style templates filled in with the palette (pywal's
+    /// templates folder when unset)
+    #[serde(default)]
+    pub templates_dir: Option<String>,
 }
 
-/// Application configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AppConfig {
-    /// Whether to start with system
-    pub start_with_system: bool,
-    
-    /// Whether to show in system tray
-    pub show_in_tray: bool,
-    
-    /// Whether to minimize to tray
-    pub minimize_to_tray: bool,
-    
-    /// Theme configuration
-    pub theme: ThemeConfig,
+/// Desktop accent color settings
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AccentSyncConfig {
+    /// Whether the accent follows each static wallpaper; turning it off puts
+    /// back the accent the desktop had before
+    pub enabled: bool,
+}
+
+/// What recolors the system after the palette is extracted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThemingTool {
+    /// Only the palette files are written
+    #[default]
+    None,
+
+    /// Aether-Desk fills in the templates with its palette
+    Templates,
+
+    /// `wal` picks the palette, writes its cache and fills in its templates
+    Pywal,
+
+    /// `wallust` picks the palette and fills in its templates
+    Wallust,
+}
+
+/// Colors a wallpaper is mostly made of
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PaletteFamily {
+    /// Mostly reds
+    Red,
+
+    /// Mostly oranges
+    Orange,
+
+    /// Mostly yellows
+    Yellow,
+
+    /// Mostly greens
+    Green,
+
+    /// Mostly cyans
+    Cyan,
+
+    /// Mostly blues
+    Blue,
+
+    /// Mostly purples
+    Purple,
+
+    /// Mostly pinks
+    Pink,
+
+    /// Grays, whites and washed-out colors
+    Neutral,
+
+    /// Mostly black or very dark colors
+    Dark,
+}
+
+/// Animation between static wallpapers
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransitionConfig {
+    /// Effect, or `None` to switch at once
+    pub effect: TransitionEffect,
+
+    /// Length of the animation in milliseconds
+    pub duration_ms: u32,
+}
+
+impl Default for TransitionConfig {
+    fn default() -> Self {
+        Self {
+            effect: TransitionEffect::None,
+            duration_ms: 800,
+        }
+    }
+}
+
+/// Animation from one wallpaper to the next
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransitionEffect {
+    /// Switch at once
+    #[default]
+    None,
+
+    /// Fade the new wallpaper in over the old one
+    Crossfade,
+
+    /// Push the old wallpaper out to the left
+    Slide,
+
+    /// Reveal the new wallpaper from left to right
+    Wipe,
+
+    /// Dissolve the old wallpaper into blocks and sharpen the new one
+    Pixelate,
+
+    /// Melt the old wallpaper into a soft blur that settles into the new one
+    Morph,
+}
+
+/// Motion given to a still image
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MotionEffect {
+    /// Shift the image with the mouse, nearer parts further than distant ones
+    #[default]
+    Parallax,
+
+    /// Slowly pan and zoom across the image
+    KenBurns,
+}
+
+/// Settings of animated static wallpapers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnimatedStaticConfig {
+    /// Motion given to the image
+    pub effect: MotionEffect,
+
+    /// Farthest the nearest parts move, as a fraction of the image width
+    pub parallax_strength: f32,
+
+    /// Whether parts of the image move by their depth, from a `.depth.png`
+    /// next to the image or estimated from it, instead of all together
+    pub depth_map: bool,
+
+    /// Minutes each Ken Burns pan and zoom takes
+    pub ken_burns_minutes: u32,
+
+    /// How far Ken Burns zooms in, 1.2 showing 1/1.2 of the image
+    pub ken_burns_zoom: f32,
+
+    /// Most frames rendered per second
+    pub fps: u32,
+}
+
+impl Default for AnimatedStaticConfig {
+    fn default() -> Self {
+        Self {
+            effect: MotionEffect::Parallax,
+            parallax_strength: 0.02,
+            depth_map: true,
+            ken_burns_minutes: 3,
+            ken_burns_zoom: 1.2,
+            fps: 15,
+        }
+    }
+}
+
+/// Settings of animated image wallpapers (GIF, APNG and WebP)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnimatedImageConfig {
+    /// Playback speed, 1.0 keeping the file's own frame timing
+    pub speed: f32,
+
+    /// Times the animation plays before resting on its last frame, 0 for forever
+    pub loops: u32,
+
+    /// Most frames shown per second
+    pub fps: u32,
+}
+
+impl Default for AnimatedImageConfig {
+    fn default() -> Self {
+        Self {
+            speed: 1.0,
+            loops: 0,
+            fps: 30,
+        }
+    }
+}
+
+/// How particles react to the mouse
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MouseReaction {
+    /// Particles ignore the mouse
+    None,
+
+    /// Particles are drawn towards the mouse
+    Attract,
+
+    /// Particles are pushed away from the mouse
+    Repel,
+}
+
+/// Settings of the particle wallpaper
+///
+/// Sizes and speeds are in pixels of a 1080 pixel high frame, so the
+/// wallpaper looks the same at any resolution.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ParticlesConfig {
+    /// Number of particles
+    pub count: u32,
+
+    /// Colors particles are given, as hex codes like "#8be9fd"
+    pub colors: Vec<String>,
+
+    /// Background color, as a hex code
+    pub background: String,
+
+    /// Particle radius
+    pub size: f32,
+
+    /// Speed particles drift at, in pixels per second
+    pub speed: f32,
+
+    /// Downward pull in pixels per second squared; negative floats particles up
+    pub gravity: f32,
+
+    /// How particles react to the mouse
+    pub mouse: MouseReaction,
+
+    /// How strongly the mouse pulls or pushes
+    pub mouse_strength: f32,
+
+    /// Reach of the mouse, as a fraction of the frame height
+    pub mouse_radius: f32,
+
+    /// Longest line drawn between neighboring particles, as a fraction of the
+    /// frame height; 0 draws no lines
+    pub link_distance: f32,
+
+    /// Most frames rendered per second
+    pub fps: u32,
+}
+
+impl Default for ParticlesConfig {
+    fn default() -> Self {
+        Self {
+            count: 120,
+            colors: vec![
+                "#8be9fd".to_string(),
+                "#bd93f9".to_string(),
+                "#ff79c6".to_string(),
+            ],
+            background: "#0b0d17".to_string(),
+            size: 3.0,
+            speed: 40.0,
+            gravity: 0.0,
+            mouse: MouseReaction::Repel,
+            mouse_strength: 1.0,
+            mouse_radius: 0.15,
+            link_distance: 0.12,
+            fps: 24,
+        }
+    }
+}
+
+/// Pattern drawn by a procedural wallpaper
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProceduralPattern {
+    /// Columns of glyphs raining down the screen
+    MatrixRain,
+
+    /// Flying through a field of stars
+    Starfield,
+
+    /// Flowing waves of color
+    Plasma,
+
+    /// Conway's Game of Life, cells living and dying on a grid
+    GameOfLife,
+
+    /// Gray-Scott reaction-diffusion, spots and stripes growing like coral
+    ReactionDiffusion,
+
+    /// Particles drifting along a slowly turning field, leaving fading trails
+    FlowField,
+}
+
+impl ProceduralPattern {
+    /// Every pattern, in the order shown
+    pub const ALL: [ProceduralPattern; 6] = [
+        ProceduralPattern::MatrixRain,
+        ProceduralPattern::Starfield,
+        ProceduralPattern::Plasma,
+        ProceduralPattern::GameOfLife,
+        ProceduralPattern::ReactionDiffusion,
+        ProceduralPattern::FlowField,
+    ];
+
+    /// Get the identifier saved as the source of wallpapers with this pattern
+    pub fn id(self) -> &'static str {
+        match self {
+            ProceduralPattern::MatrixRain => "matrix-rain",
+            ProceduralPattern::Starfield => "starfield",
+            ProceduralPattern::Plasma => "plasma",
+            ProceduralPattern::GameOfLife => "game-of-life",
+            ProceduralPattern::ReactionDiffusion => "reaction-diffusion",
+            ProceduralPattern::FlowField => "flow-field",
+        }
+    }
+
+    /// Get the pattern with this identifier
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|pattern| pattern.id() == id)
+    }
+
+    /// Get the name of the pattern
+    pub fn name(self) -> &'static str {
+        match self {
+            ProceduralPattern::MatrixRain => "Matrix Rain",
+            ProceduralPattern::Starfield => "Starfield",
+            ProceduralPattern::Plasma => "Plasma",
+            ProceduralPattern::GameOfLife => "Game of Life",
+            ProceduralPattern::ReactionDiffusion => "Reaction-Diffusion",
+            ProceduralPattern::FlowField => "Flow Field",
+        }
+    }
+}
+
+/// Colors a procedural wallpaper is drawn in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColorScheme {
+    /// The pattern's own colors: green glyphs, white stars, a rainbow plasma
+    Classic,
+
+    /// Deep blues to aqua
+    Ocean,
+
+    /// Purple to red to gold
+    Sunset,
+
+    /// Magenta and cyan on near black
+    Neon,
+
+    /// Black to white
+    Mono,
+}
+
+impl ColorScheme {
+    /// Every scheme, in the order shown
+    pub const ALL: [ColorScheme; 5] = [
+        ColorScheme::Classic,
+        ColorScheme::Ocean,
+        ColorScheme::Sunset,
+        ColorScheme::Neon,
+        ColorScheme::Mono,
+    ];
+}
+
+/// Settings of one procedural pattern
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PatternSettings {
+    /// How fast the pattern moves, 1.0 being its normal speed
+    pub speed: f32,
+
+    /// Colors the pattern is drawn in
+    pub scheme: ColorScheme,
+
+    /// How full the pattern is, from 0.0 to 1.0: rain columns, stars, how
+    /// fine the plasma's waves are, living cells, growing spots or particles
+    pub density: f32,
+
+    /// Seed of the pattern's randomness, so it grows the same way every
+    /// time; 0 for a different pattern every time
+    pub seed: u64,
+}
+
+impl Default for PatternSettings {
+    fn default() -> Self {
+        Self {
+            speed: 1.0,
+            scheme: ColorScheme::Classic,
+            density: 0.5,
+            seed: 0,
+        }
+    }
+}
+
+/// Settings of the procedural wallpapers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProceduralConfig {
+    /// Matrix rain settings
+    pub matrix_rain: PatternSettings,
+
+    /// Starfield settings
+    pub starfield: PatternSettings,
+
+    /// Plasma settings
+    pub plasma: PatternSettings,
+
+    /// Game of Life settings
+    pub game_of_life: PatternSettings,
+
+    /// Reaction-diffusion settings
+    pub reaction_diffusion: PatternSettings,
+
+    /// Flow field settings
+    pub flow_field: PatternSettings,
+
+    /// Most frames rendered per second
+    pub fps: u32,
+}
+
+impl Default for ProceduralConfig {
+    fn default() -> Self {
+        Self {
+            matrix_rain: PatternSettings::default(),
+            starfield: PatternSettings::default(),
+            plasma: PatternSettings::default(),
+            game_of_life: PatternSettings::default(),
+            reaction_diffusion: PatternSettings::default(),
+            flow_field: PatternSettings::default(),
+            fps: 20,
+        }
+    }
+}
+
+impl ProceduralConfig {
+    /// Get the settings of a pattern
+    pub fn settings(&self, pattern: ProceduralPattern) -> &PatternSettings {
+        match pattern {
+            ProceduralPattern::MatrixRain => &self.matrix_rain,
+            ProceduralPattern::Starfield => &self.starfield,
+            ProceduralPattern::Plasma => &self.plasma,
+            ProceduralPattern::GameOfLife => &self.game_of_life,
+            ProceduralPattern::ReactionDiffusion => &self.reaction_diffusion,
+            ProceduralPattern::FlowField => &self.flow_field,
+        }
+    }
+
+    /// Get the settings of a pattern to change them
+    pub fn settings_mut(&mut self, pattern: ProceduralPattern) -> &mut PatternSettings {
+        match pattern {
+            ProceduralPattern::MatrixRain => &mut self.matrix_rain,
+            ProceduralPattern::Starfield => &mut self.starfield,
+            ProceduralPattern::Plasma => &mut self.plasma,
+            ProceduralPattern::GameOfLife => &mut self.game_of_life,
+            ProceduralPattern::ReactionDiffusion => &mut self.reaction_diffusion,
+            ProceduralPattern::FlowField => &mut self.flow_field,
+        }
+    }
+}
+
+/// Order the images of a slideshow are shown in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SlideshowOrder {
+    /// By file name
+    #[default]
+    Name,
+
+    /// Oldest changed first
+    Modified,
+
+    /// Random, shuffled again after every round
+    Shuffle,
+}
+
+/// Settings of slideshow wallpapers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SlideshowConfig {
+    /// Seconds each image is shown
+    pub interval_secs: u32,
+
+    /// Animation from one image to the next
+    pub transition: TransitionConfig,
+
+    /// Order the images are shown in
+    pub order: SlideshowOrder,
+
+    /// Most frames rendered per second while changing images
+    pub fps: u32,
+}
+
+impl Default for SlideshowConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: 300,
+            transition: TransitionConfig {
+                effect: TransitionEffect::Crossfade,
+                duration_ms: 2000,
+            },
+            order: SlideshowOrder::Name,
+            fps: 24,
+        }
+    }
+}
+
+/// Settings of the now-playing album art wallpaper
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NowPlayingConfig {
+    /// Blur radius of the background, in pixels of the monitor
+    pub blur: u32,
+
+    /// How much the background is darkened (0.0 - 1.0)
+    pub dim: f32,
+
+    /// Whether the sharp cover is shown in the middle
+    pub show_cover: bool,
+
+    /// Seconds between checks of the media player
+    pub poll_secs: u32,
+
+    /// Animation from one cover to the next
+    pub transition: TransitionConfig,
+
+    /// Most frames rendered per second while changing covers
+    pub fps: u32,
+}
+
+impl Default for NowPlayingConfig {
+    fn default() -> Self {
+        Self {
+            blur: 48,
+            dim: 0.35,
+            show_cover: true,
+            poll_secs: 2,
+            transition: TransitionConfig {
+                effect: TransitionEffect::Crossfade,
+                duration_ms: 1000,
+            },
+            fps: 24,
+        }
+    }
+}
+
+/// Color temperature reached at a time of day
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TemperaturePoint {
+    /// Time of day
+    pub time: NaiveTime,
+
+    /// Color temperature in kelvin; 6500 leaves the image as it is, lower is warmer
+    pub kelvin: u32,
+}
+
+/// Time-of-day tint of static wallpapers, like a night light
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColorTemperatureConfig {
+    /// Whether static wallpapers are tinted by the time of day
+    pub enabled: bool,
+
+    /// Temperatures through the day, blended in between and around midnight
+    pub curve: Vec<TemperaturePoint>,
+
+    /// Minutes between redraws of the wallpaper as the temperature changes
+    pub update_minutes: u32,
+}
+
+impl Default for ColorTemperatureConfig {
+    fn default() -> Self {
+        let point = |hour, kelvin| TemperaturePoint {
+            time: NaiveTime::from_hms_opt(hour, 0, 0).unwrap(),
+            kelvin,
+        };
+        Self {
+            enabled: false,
+            curve: vec![
+                point(6, 3400),
+                point(8, 6500),
+                point(18, 6500),
+                point(21, 3400),
+            ],
+            update_minutes: 10,
+        }
+    }
+}
+
+/// Effect drawn over the wallpaper for the weather
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WeatherEffect {
+    /// Streaks of falling rain, also for drizzle and thunderstorms
+    Rain,
+
+    /// Drifting snowflakes
+    Snow,
+
+    /// A slowly moving haze, also for mist, smoke and dust
+    Fog,
+}
+
+/// Effects drawn over static wallpapers to match the weather outside
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WeatherEffectsConfig {
+    /// Whether the weather is drawn over static wallpapers
+    pub enabled: bool,
+
+    /// City the weather is looked up for, e.g. "Oslo,NO"
+    pub location: String,
+
+    /// How dense the rain and snow and how thick the fog, from 0.0 to 1.0
+    pub intensity: f32,
+
+    /// Minutes between weather updates
+    pub refresh_minutes: u32,
+
+    /// Most frames rendered per second
+    pub fps: u32,
+
+    /// Effect shown whatever the weather, to try the effects out
+    pub preview: Option<WeatherEffect>,
+}
+
+impl Default for WeatherEffectsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            location: String::new(),
+            intensity: 0.6,
+            refresh_minutes: 15,
+            fps: 12,
+            preview: None,
+        }
+    }
+}
+
+/// Audio-reactive effects drawn over static and video wallpapers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioOverlayConfig {
+    /// Whether the effects are drawn
+    pub enabled: bool,
+
+    /// Whether spectrum bars rise from the bottom edge
+    pub bars: bool,
+
+    /// Number of spectrum bars
+    pub bar_count: u32,
+
+    /// Height of the tallest bars, as a share of the screen height
+    pub bar_height: f32,
+
+    /// Color of the bars as hex, e.g. "#8be9fd"
+    pub color: String,
+
+    /// Whether the edges darken with the beat
+    pub vignette: bool,
+
+    /// How strongly the effects react to the sound, 1.0 being normal
+    pub sensitivity: f32,
+
+    /// Most frames rendered per second
+    pub fps: u32,
+
+    /// Device recorded, the default output when empty
+    pub device: String,
+}
+
+impl Default for AudioOverlayConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bars: true,
+            bar_count: 48,
+            bar_height: 0.2,
+            color: "#8be9fd".to_string(),
+            vignette: true,
+            sensitivity: 1.0,
+            fps: 30,
+            device: String::new(),
+        }
+    }
+}
+
+/// Where the virtual camera sends the wallpaper
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CameraOutput {
+    /// A v4l2loopback device, seen as a webcam (Linux)
+    #[default]
+    Loopback,
+
+    /// An MPEG-TS stream OBS's Media Source can play
+    Stream,
+}
+
+/// Copy of the wallpaper sent to a virtual camera or OBS
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VirtualCameraConfig {
+    /// Whether the wallpaper is sent
+    pub enabled: bool,
+
+    /// Where it's sent
+    pub output: CameraOutput,
+
+    /// v4l2loopback device written to
+    pub device: String,
+
+    /// Address the stream is sent to
+    pub stream_url: String,
+
+    /// Width of the picture sent
+    pub width: u32,
+
+    /// Height of the picture sent
+    pub height: u32,
+
+    /// Frames sent per second
+    pub fps: u32,
+}
+
+impl Default for VirtualCameraConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            output: CameraOutput::Loopback,
+            device: "/dev/video10".to_string(),
+            stream_url: "udp://127.0.0.1:47940".to_string(),
+            width: 1280,
+            height: 720,
+            fps: 30,
+        }
+    }
+}
+
+/// Frame rate limits of video and shader wallpapers
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FrameRateConfig {
+    /// Limit for wallpapers without their own
+    pub limit: FrameRateLimit,
+
+    /// Limit per wallpaper path
+    pub overrides: HashMap<String, FrameRateLimit>,
+}
+
+impl FrameRateConfig {
+    /// Get the limit of a wallpaper
+    pub fn limit_for(&self, path: &Path) -> FrameRateLimit {
+        self.overrides
+            .get(path.to_string_lossy().as_ref())
+            .copied()
+            .unwrap_or(self.limit)
+    }
+
+    /// Set or clear (`None`) the limit of a wallpaper
+    pub fn set_override(&mut self, path: &Path, limit: Option<FrameRateLimit>) {
+        let key = path.to_string_lossy().to_string();
+        match limit {
+            Some(limit) => {
+                self.overrides.insert(key, limit);
+            }
+            None => {
+                self.overrides.remove(&key);
+            }
+        }
+    }
+}
+
+/// Highest frame rate of an animated wallpaper
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FrameRateLimit {
+    /// As fast as the player renders
+    #[default]
+    Unlimited,
+
+    /// Fixed frames per second
+    Fixed(u32),
+
+    /// The monitor refresh rate
+    MatchRefresh,
+
+    /// Half the monitor refresh rate
+    HalfRefresh,
+}
+
+impl FrameRateLimit {
+    /// Get the frames per second, or `None` without a limit
+    ///
+    /// `refresh_rate` is only asked for by the refresh-based limits.
+    pub fn fps(&self, refresh_rate: impl FnOnce() -> f32) -> Option<u32> {
+        match self {
+            FrameRateLimit::Unlimited => None,
+            FrameRateLimit::Fixed(fps) => Some((*fps).max(1)),
+            FrameRateLimit::MatchRefresh => Some(refresh_rate().round().max(1.0) as u32),
+            FrameRateLimit::HalfRefresh => Some((refresh_rate() / 2.0).round().max(1.0) as u32),
+        }
+    }
+}
+
+/// How an image is fitted to a monitor
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FitMode {
+    /// Scale to cover the monitor, cutting off the overflow
+    #[default]
+    Fill,
+
+    /// Scale to fit inside the monitor, leaving bars
+    Fit,
+
+    /// Scale to the monitor size, ignoring the aspect ratio
+    Stretch,
+
+    /// Show at the original size in the middle
+    Center,
+
+    /// Repeat at the original size
+    Tile,
+
+    /// Show a chosen part of the image per monitor
+    Crop,
+}
+
+/// Part of an image, as fractions of its width and height
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CropRect {
+    /// Left edge (0.0 - 1.0)
+    pub x: f32,
+
+    /// Top edge (0.0 - 1.0)
+    pub y: f32,
+
+    /// Width (0.0 - 1.0)
+    pub width: f32,
+
+    /// Height (0.0 - 1.0)
+    pub height: f32,
+}
+
+/// Wallpaper type
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum WallpaperType {
+    /// Static image
+    Static,
+
+    /// Static image brought to life by a motion effect
+    AnimatedStatic,
+
+    /// GIF, APNG or WebP played frame by frame
+    AnimatedImage,
+
+    /// Video
+    Video,
+
+    /// Web-based
+    Web,
+
+    /// Shader-based
+    Shader,
+
+    /// Audio-reactive
+    Audio,
+
+    /// Particle system drawn in-process, without a file
+    Particles,
+
+    /// Pattern drawn in-process, without a file; the URL names the pattern
+    Procedural,
+
+    /// Images of a folder shown one after another; the path is the folder
+    Slideshow,
+
+    /// Album art of the track playing, drawn in-process without a file
+    NowPlaying,
+}
+
+impl WallpaperType {
+    /// Get a string representation of the wallpaper type
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WallpaperType::Static => "Static",
+            WallpaperType::AnimatedStatic => "Animated Static",
+            WallpaperType::AnimatedImage => "Animated Image",
+            WallpaperType::Video => "Video",
+            WallpaperType::Web => "Web",
+            WallpaperType::Shader => "Shader",
+            WallpaperType::Audio => "Audio",
+            WallpaperType::Particles => "Particles",
+            WallpaperType::Procedural => "Procedural",
+            WallpaperType::Slideshow => "Slideshow",
+            WallpaperType::NowPlaying => "Now Playing",
+        }
+    }
+
+    /// Get the lowercase file extensions of this wallpaper type
+    pub fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            WallpaperType::Static | WallpaperType::AnimatedStatic => &[
+                "png",
+                "jpg",
+                "jpeg",
+                "bmp",
+                "gif",
+                #[cfg(feature = "heic")]
+                "heic",
+                #[cfg(feature = "heic")]
+                "heif",
+                #[cfg(feature = "avif")]
+                "avif",
+                #[cfg(feature = "jxl")]
+                "jxl",
+            ],
+            WallpaperType::AnimatedImage => &["gif", "png", "apng", "webp"],
+            WallpaperType::Video => &["mp4", "webm", "avi", "mkv", "mov", "wmv"],
+            // Web wallpapers are URLs, built-in ones need no file and slideshows are folders
+            WallpaperType::Web
+            | WallpaperType::Particles
+            | WallpaperType::Procedural
+            | WallpaperType::Slideshow
+            | WallpaperType::NowPlaying => &[],
+            WallpaperType::Shader | WallpaperType::Audio => &["glsl", "frag", "vert", "shader"],
+        }
+    }
+
+    /// Check whether a file has one of this type's extensions
+    pub fn accepts(&self, path: &Path) -> bool {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .map_or(false, |ext| {
+                self.extensions().contains(&ext.to_lowercase().as_str())
+            })
+    }
+
+    /// Check whether wallpapers of this type are drawn without a file or URL
+    pub fn is_builtin(&self) -> bool {
+        matches!(
+            self,
+            WallpaperType::Particles | WallpaperType::Procedural | WallpaperType::NowPlaying
+        )
+    }
+
+    /// Check whether wallpapers of this type can be shown on a single monitor
+    pub fn runs_per_monitor(&self) -> bool {
+        matches!(
+            self,
+            WallpaperType::Static | WallpaperType::Video | WallpaperType::Shader
+        )
+    }
+
+    /// Guess the wallpaper type from a file extension
+    ///
+    /// Shader files are reported as shaders, not audio wallpapers, images
+    /// as static wallpapers unless they move and folders as slideshows.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        if path.is_dir() {
+            return Some(WallpaperType::Slideshow);
+        }
+        let wallpaper_type = [
+            WallpaperType::Static,
+            WallpaperType::AnimatedImage,
+            WallpaperType::Video,
+            WallpaperType::Shader,
+        ]
+        .into_iter()
+        .find(|wallpaper_type| wallpaper_type.accepts(path))?;
+        if wallpaper_type == WallpaperType::Static && animated_image::is_animated(path) {
+            return Some(WallpaperType::AnimatedImage);
+        }
+        Some(wallpaper_type)
+    }
+}
+
+/// Auto-change configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AutoChangeConfig {
+    /// Whether auto-change is enabled
+    pub enabled: bool,
+
+    /// Change interval in minutes
+    pub interval: u32,
+
+    /// Folder to pick wallpapers from
+    pub folder: Option<String>,
+
+    /// Whether to prefer images matching the monitor's resolution and aspect ratio
+    #[serde(default)]
+    pub match_resolution: bool,
+
+    /// How far an image's aspect ratio and size may differ from the monitor's
+    /// and still match, as a fraction
+    #[serde(default = "default_resolution_tolerance")]
+    pub resolution_tolerance: f32,
+
+    /// Colors every picked image should share, so the desktop stays coherent
+    #[serde(default)]
+    pub palette_family: Option<PaletteFamily>,
+}
+
+/// Resolution tolerance of configs saved before it existed
+fn default_resolution_tolerance() -> f32 {
+    0.1
+}
+
+/// Application configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppConfig {
+    /// Whether to start with system
+    pub start_with_system: bool,
+
+    /// Whether to show in system tray
+    pub show_in_tray: bool,
+
+    /// Whether to minimize to tray
+    pub minimize_to_tray: bool,
+
+    /// Theme configuration
+    pub theme: ThemeConfig,
+
+    /// UI language code (None = system language)
+    #[serde(default)]
+    pub language: Option<String>,
+
+    /// Whether errors are also shown as desktop notifications
+    #[serde(default)]
+    pub desktop_notifications: bool,
+
+    /// Whether the gallery opens in its own window
+    #[serde(default)]
+    pub detach_gallery: bool,
+
+    /// Whether the widget preview opens in its own window
+    #[serde(default)]
+    pub detach_widget_preview: bool,
+
+    /// Name of the active profile
+    #[serde(default)]
+    pub active_profile: Option<String>,
+
+    /// Profiles switched to when a monitor setup is connected, by setup
+    #[serde(default)]
+    pub topology_profiles: BTreeMap<String, String>,
+
+    /// Limits on the resources of wallpaper players
+    #[serde(default)]
+    pub resource_limits: ResourceLimitsConfig,
+
+    /// When animated wallpapers are paused automatically
+    #[serde(default)]
+    pub auto_pause: AutoPauseConfig,
+
+    /// Dimming the wallpaper while windows cover the desktop
+    #[serde(default)]
+    pub focus_mode: FocusModeConfig,
+
+    /// Lowering animated wallpaper quality while the system is busy
+    #[serde(default)]
+    pub adaptive_quality: AdaptiveQualityConfig,
+
+    /// GPU that renders shader and web wallpapers
+    #[serde(default)]
+    pub gpu_preference: GpuPreference,
+
+    /// Restarting crashed wallpaper players
+    #[serde(default)]
+    pub watchdog: WatchdogConfig,
+
+    /// Least severe messages written to the log
+    #[serde(default)]
+    pub log_level: LogLevel,
+
+    /// Whether saves of the configuration, schedule and widgets are kept in a git history
+    #[serde(default)]
+    pub config_history: bool,
+}
+
+/// Theme configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThemeConfig {
+    /// Theme type
+    pub theme: Theme,
+    /// Custom accent color (if custom theme)
+    pub accent_color: Option<String>,
+    /// Custom background color (if custom theme)
+    pub background_color: Option<String>,
+    /// Custom panel and window color (if custom theme)
+    #[serde(default)]
+    pub panel_color: Option<String>,
+    /// Custom text color (if custom theme)
+    #[serde(default)]
+    pub text_color: Option<String>,
+    /// Custom widget chrome color (if custom theme)
+    #[serde(default)]
+    pub widget_color: Option<String>,
+}
+
+/// Theme type
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Theme {
+    Light,
+    Dark,
+    Custom,
+    /// Follow the OS light/dark preference
+    System,
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self {
+            theme: Theme::Dark,
+            accent_color: None,
+            background_color: None,
+            panel_color: None,
+            text_color: None,
+            widget_color: None,
+        }
+    }
+}
+
+/// When animated wallpapers are paused automatically
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoPauseConfig {
+    /// Pause while a fullscreen application (e.g. a game) is focused
+    pub on_fullscreen: bool,
+
+    /// Show a still frame instead of an animated wallpaper on battery power
+    pub on_battery: bool,
+
+    /// Battery charge in percent at or below which power is saved (100 = always on battery)
+    pub battery_threshold: u8,
+
+    /// Seconds between widget updates while saving power
+    pub battery_widget_interval: u64,
+
+    /// Pause animated wallpapers and widget updates while nobody uses the computer
+    pub on_idle: bool,
+
+    /// Minutes without keyboard or mouse input before the user counts as away
+    pub idle_minutes: u64,
+}
+
+impl Default for AutoPauseConfig {
+    fn default() -> Self {
+        Self {
+            on_fullscreen: true,
+            on_battery: true,
+            battery_threshold: 100,
+            battery_widget_interval: 10,
+            on_idle: false,
+            idle_minutes: 5,
+        }
+    }
+}
+
+/// How focus mode tones down a static wallpaper
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FocusEffect {
+    /// Darken the image
+    #[default]
+    Dim,
+
+    /// Blur the image
+    Blur,
+
+    /// Darken and blur the image
+    DimAndBlur,
+}
+
+/// Dimming the wallpaper while windows cover the desktop
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FocusModeConfig {
+    /// Whether the wallpaper is toned down while windows cover the desktop
+    pub enabled: bool,
+
+    /// How static wallpapers are toned down
+    pub effect: FocusEffect,
+
+    /// How strongly, from 0.0 (not at all) to 1.0
+    pub strength: f32,
+
+    /// Percent of the desktop windows have to cover to turn focus mode on
+    pub cover_above: u8,
+
+    /// Percent of the desktop below which it turns off again
+    pub uncover_below: u8,
+
+    /// Whether animated wallpapers are paused while focus mode is on
+    pub pause_animated: bool,
+
+    /// Milliseconds the fade in and out takes
+    pub fade_ms: u32,
+}
+
+impl Default for FocusModeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            effect: FocusEffect::Dim,
+            strength: 0.5,
+            cover_above: 70,
+            uncover_below: 50,
+            pause_animated: true,
+            fade_ms: 800,
+        }
+    }
+}
+
+/// Lowering animated wallpaper quality while the system is busy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AdaptiveQualityConfig {
+    /// Whether quality is lowered automatically
+    pub enabled: bool,
+
+    /// System CPU usage in percent above which quality is lowered
+    pub lower_above_cpu: f32,
+
+    /// System CPU usage in percent below which full quality returns
+    pub restore_below_cpu: f32,
+
+    /// Frame rate of a lowered video
+    pub reduced_fps: u32,
+
+    /// Resolution scale of a lowered video (0.5 = half width and height)
+    pub reduced_scale: f32,
+}
+
+impl Default for AdaptiveQualityConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            lower_above_cpu: 85.0,
+            restore_below_cpu: 60.0,
+            reduced_fps: 15,
+            reduced_scale: 0.5,
+        }
+    }
+}
+
+/// Restarting wallpaper players that crash
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchdogConfig {
+    /// Whether crashed players are restarted
+    pub enabled: bool,
+
+    /// Restarts before falling back to the last static wallpaper
+    pub max_restarts: u32,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_restarts: 3,
+        }
+    }
+}
+
+/// GPU that renders shader and web wallpapers on hybrid-graphics machines
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GpuPreference {
+    /// Whatever the system picks
+    #[default]
+    Default,
+
+    /// The integrated GPU, to save battery
+    Integrated,
+
+    /// The discrete GPU, for quality
+    Discrete,
+}
+
+/// Least severe messages written to the log
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum LogLevel {
+    /// Errors only
+    Error,
+
+    /// Errors and warnings
+    Warn,
+
+    /// What the app is doing
+    #[default]
+    Info,
+
+    /// Details for troubleshooting
+    Debug,
+
+    /// Everything
+    Trace,
+}
+
+impl LogLevel {
+    /// Get the matching filter of the `log` crate
+    pub fn filter(self) -> log::LevelFilter {
+        match self {
+            LogLevel::Error => log::LevelFilter::Error,
+            LogLevel::Warn => log::LevelFilter::Warn,
+            LogLevel::Info => log::LevelFilter::Info,
+            LogLevel::Debug => log::LevelFilter::Debug,
+            LogLevel::Trace => log::LevelFilter::Trace,
+        }
+    }
+}
+
+/// Limits on the memory and CPU used by wallpaper players
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResourceLimitsConfig {
+    /// Whether players over a limit are paused or killed
+    pub enabled: bool,
+
+    /// Maximum resident memory in MB
+    pub max_memory_mb: u64,
+
+    /// Maximum CPU usage percentage (100 = one core)
+    pub max_cpu: f32,
+
+    /// What happens to a player that stays over a limit
+    pub action: LimitAction,
+}
+
+impl Default for ResourceLimitsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_memory_mb: 512,
+            max_cpu: 80.0,
+            action: LimitAction::Pause,
+        }
+    }
+}
+
+/// What happens to a player over its resource limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LimitAction {
+    /// Suspend the player until the wallpaper is changed
+    Pause,
+
+    /// Kill the player
+    Kill,
+}
+
+/// Local HTTP API configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiConfig {
+    /// Whether the API server is enabled
+    pub enabled: bool,
+
+    /// Port
+    pub port: u16,
+
+    /// Listen on every interface so phones on the LAN can reach the remote page
+    pub allow_remote: bool,
+
+    /// Incoming webhooks by name
+    pub webhooks: BTreeMap<String, WebhookAction>,
+
+    /// Scenes offered to Home Assistant by name
+    pub scenes: BTreeMap<String, SceneConfig>,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 47932,
+            allow_remote: false,
+            webhooks: BTreeMap::new(),
+            scenes: BTreeMap::new(),
+        }
+    }
+}
+
+/// Image generation configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AiConfig {
+    /// Service generating the images
+    pub provider: AiProvider,
+
+    /// Address of the service (empty = the provider's usual address)
+    pub endpoint: String,
+
+    /// Model or checkpoint to generate with (empty = the service's default)
+    pub model: String,
+
+    /// What the images should not show
+    pub negative_prompt: String,
+
+    /// Number of candidates generated at once
+    pub candidates: u32,
+
+    /// Sampling steps for local services
+    pub steps: u32,
+
+    /// Longest edge generated, in pixels; saved images are scaled up to the monitor
+    pub max_edge: u32,
+}
+
+impl Default for AiConfig {
+    fn default() -> Self {
+        Self {
+            provider: AiProvider::default(),
+            endpoint: String::new(),
+            model: String::new(),
+            negative_prompt: String::new(),
+            candidates: 4,
+            steps: 25,
+            max_edge: 1024,
+        }
+    }
+}
+
+impl AiConfig {
+    /// Get the address of the service
+    pub fn endpoint(&self) -> &str {
+        match self.endpoint.trim_end_matches('/') {
+            "" => self.provider.default_endpoint(),
+            endpoint => endpoint,
+        }
+    }
+}
+
+/// Automatic tagging configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TaggingConfig {
+    /// Whether imported wallpapers are tagged
+    pub enabled: bool,
+
+    /// Folder with the classifier model (None = `models/clip` in the configuration folder)
+    pub model_dir: Option<String>,
+
+    /// Least likelihood (0.0 - 1.0) of a label for it to become a tag
+    pub threshold: f32,
+}
+
+impl Default for TaggingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            model_dir: None,
+            threshold: 0.15,
+        }
+    }
+}
+
+impl TaggingConfig {
+    /// Get the folder with the classifier model
+    pub fn model_dir(&self) -> PathBuf {
+        match &self.model_dir {
+            Some(dir) => PathBuf::from(dir),
+            None => Config::get_config_dir()
+                .unwrap_or_else(|_| PathBuf::from("config"))
+                .join("models")
+                .join("clip"),
+        }
+    }
+}
+
+/// LAN wallpaper sync configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncConfig {
+    /// Whether this computer takes part in the sync
+    pub enabled: bool,
+
+    /// Name other computers see (empty = the computer's name)
+    pub name: String,
+
+    /// Port wallpapers are received on
+    pub port: u16,
+
+    /// Send this computer's wallpaper changes to the others
+    pub send: bool,
+
+    /// Show the wallpapers the others send
+    pub receive: bool,
+
+    /// Computers to sync with that can't be discovered, as `host:port`
+    pub peers: Vec<String>,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            name: String::new(),
+            port: 47941,
+            send: true,
+            receive: true,
+            peers: Vec::new(),
+        }
+    }
+}
+
+/// Chat service the bot bridge talks to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BotPlatform {
+    /// Telegram bot, polled for updates
+    #[default]
+    Telegram,
+
+    /// Discord bot, polling its direct messages with the allowed users
+    Discord,
+}
+
+impl BotPlatform {
+    /// Get the name the platform is shown with
+    pub fn name(self) -> &'static str {
+        match self {
+            BotPlatform::Telegram => "Telegram",
+            BotPlatform::Discord => "Discord",
+        }
+    }
+}
+
+/// Chat bot remote control configuration
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BotConfig {
+    /// Whether the bot bridge runs
+    pub enabled: bool,
+
+    /// Chat service the bot is on
+    pub platform: BotPlatform,
+
+    /// IDs of the users the bot obeys; messages from anyone else are ignored
+    pub allowed_users: Vec<String>,
+}
+
+/// Image generation service
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AiProvider {
+    /// Stable Diffusion WebUI (AUTOMATIC1111 or Forge) started with `--api`
+    #[default]
+    StableDiffusion,
+
+    /// ComfyUI
+    ComfyUi,
+
+    /// Hosted API compatible with OpenAI image generation
+    Hosted,
+}
+
+impl AiProvider {
+    /// Get the address the service usually listens on
+    pub fn default_endpoint(self) -> &'static str {
+        match self {
+            AiProvider::StableDiffusion => "http://127.0.0.1:7860",
+            AiProvider::ComfyUi => "http://127.0.0.1:8188",
+            AiProvider::Hosted => "https://api.openai.com",
+        }
+    }
+}
+
+/// What a Home Assistant scene does; parts left unset are unchanged
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SceneConfig {
+    /// Profile switched to first
+    pub profile: Option<String>,
+
+    /// Wallpaper file applied
+    pub wallpaper: Option<String>,
+
+    /// Whether the widgets are shown or hidden
+    pub widgets: Option<bool>,
+}
+
+/// What an incoming webhook does
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum WebhookAction {
+    /// Apply a wallpaper file
+    ApplyWallpaper {
+        /// Wallpaper file
+        path: String,
+
+        /// Wallpaper type (guessed from the file extension when omitted)
+        #[serde(default, rename = "type")]
+        wallpaper_type: Option<WallpaperType>,
+    },
+
+    /// Switch to the next wallpaper in the folder
+    NextWallpaper,
+
+    /// Stop the current wallpaper
+    StopWallpaper,
+
+    /// Start the scheduler
+    StartScheduler,
+
+    /// Stop the scheduler
+    StopScheduler,
+
+    /// Switch to a profile
+    SwitchProfile {
+        /// Profile name
+        profile: String,
+    },
+}
+
+/// Plugin configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginConfig {
+    /// Enabled plugins
+    pub enabled: Vec<String>,
+}
+
+/// Online source configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SourcesConfig {
+    /// Auto-download settings
+    #[serde(default)]
+    pub auto_download: AutoDownloadConfig,
+
+    /// Resolution filter for downloaded wallpapers
+    #[serde(default)]
+    pub resolution_filter: ResolutionFilterConfig,
+
+    /// Content filter for search results and downloads
+    #[serde(default)]
+    pub content_filter: ContentFilterConfig,
+
+    /// Download cache limits
+    #[serde(default)]
+    pub cache: DownloadCacheConfig,
+
+    /// Live Earth satellite wallpaper
+    #[serde(default)]
+    pub live_earth: LiveEarthConfig,
+
+    /// Calendar subscriptions
+    #[serde(default)]
+    pub calendar: CalendarConfig,
+}
+
+/// Auto-download configuration
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AutoDownloadConfig {
+    /// Whether auto-download is enabled
+    pub enabled: bool,
+
+    /// Source to download from
+    pub source: String,
+
+    /// Search query
+    pub query: String,
+
+    /// Download interval in minutes
+    pub interval: u32,
+
+    /// Number of wallpapers to download per run
+    pub per_run: u32,
+
+    /// Whether to apply the newest download as the wallpaper
+    pub set_as_wallpaper: bool,
+}
+
+impl Default for AutoDownloadConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            source: "wallhaven".to_string(),
+            query: String::new(),
+            interval: 60,
+            per_run: 1,
+            set_as_wallpaper: false,
+        }
+    }
+}
+
+/// Resolution filter configuration
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ResolutionFilterConfig {
+    /// Whether to filter by monitor resolution
+    pub enabled: bool,
+
+    /// Whether the aspect ratio must match a monitor
+    pub match_aspect_ratio: bool,
+
+    /// Allowed relative aspect ratio difference (0.05 = 5%)
+    pub aspect_tolerance: f32,
+
+    /// Minimum image size relative to the monitor (1.0 = native resolution)
+    pub min_scale: f32,
+}
+
+impl Default for ResolutionFilterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            match_aspect_ratio: true,
+            aspect_tolerance: 0.05,
+            min_scale: 1.0,
+        }
+    }
+}
+
+/// Download cache configuration
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DownloadCacheConfig {
+    /// Maximum cache size in megabytes (0 = unlimited)
+    pub max_size_mb: u64,
+
+    /// Maximum age of unused downloads in days (0 = unlimited)
+    pub max_age_days: u32,
+}
+
+impl Default for DownloadCacheConfig {
+    fn default() -> Self {
+        Self {
+            max_size_mb: 2048,
+            max_age_days: 30,
+        }
+    }
+}
+
+/// Satellite used for Live Earth imagery
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Satellite {
+    /// Himawari (Asia and Oceania)
+    #[default]
+    Himawari,
+
+    /// GOES-East (Americas and Atlantic)
+    GoesEast,
+
+    /// GOES-West (Pacific and western Americas)
+    GoesWest,
+}
+
+/// Live Earth configuration
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LiveEarthConfig {
+    /// Whether Live Earth is enabled
+    pub enabled: bool,
+
+    /// Satellite, which determines the visible hemisphere
+    pub satellite: Satellite,
+
+    /// Zoom into the center of the disk (1.0 = full disk)
+    pub zoom: f32,
+
+    /// Image detail level (1, 2, 4 or 8)
+    pub detail: u32,
+
+    /// Update interval in minutes
+    pub interval: u32,
 }
 
-/// Theme configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ThemeConfig {
-    /// Theme type
-    pub theme: Theme,
-    /// Custom accent color (if custom theme)
-    pub accent_color: Option<String>,
-    /// Custom background color (if custom theme)
-    pub background_color: Option<String>,
+impl Default for LiveEarthConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            satellite: Satellite::Himawari,
+            zoom: 1.0,
+            detail: 2,
+            interval: 30,
+        }
+    }
 }
 
-/// Theme type
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-pub enum Theme {
-    Light,
-    Dark,
-    Custom,
+/// Calendar subscription configuration
+///
+/// The subscribed addresses are kept in the secret store.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct CalendarConfig {
+    /// Time between fetches of the calendars, in minutes
+    pub refresh_minutes: u32,
+
+    /// Number of days ahead events are listed for
+    pub days_ahead: u32,
 }
 
-impl Default for ThemeConfig {
+impl Default for CalendarConfig {
     fn default() -> Self {
         Self {
-            theme: Theme::Dark,
-            accent_color: None,
-            background_color: None,
+            refresh_minutes: 15,
+            days_ahead: 7,
         }
     }
 }
 
-/// Plugin configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PluginConfig {
-    /// Enabled plugins
-    pub enabled: Vec<String>,
+/// Content purity level, ordered from most to least restrictive
+#[derive(
+    Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+pub enum Purity {
+    #[default]
+    Sfw,
+    Sketchy,
+    Nsfw,
+}
+
+/// Content filter configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ContentFilterConfig {
+    /// Highest purity allowed from any source
+    pub max_purity: Purity,
+
+    /// Per-provider purity, capped by `max_purity`
+    #[serde(default)]
+    pub providers: HashMap<String, Purity>,
 }
 
 impl Default for Config {
@@ -125,17 +2014,61 @@ impl Default for Config {
                     enabled: false,
                     interval: 30,
                     folder: None,
+                    match_resolution: false,
+                    resolution_tolerance: default_resolution_tolerance(),
+                    palette_family: None,
                 },
+                favorites: Vec::new(),
+                last_static: None,
+                placement: PlacementConfig::default(),
+                change_hooks: Vec::new(),
+                palette: PaletteConfig::default(),
+                accent_sync: AccentSyncConfig::default(),
+                frame_rate: FrameRateConfig::default(),
+                monitor_wallpapers: BTreeMap::new(),
+                workspace_wallpapers: BTreeMap::new(),
+                sync_lock_screen: false,
+                transition: TransitionConfig::default(),
+                animation: AnimatedStaticConfig::default(),
+                animated_image: AnimatedImageConfig::default(),
+                particles: ParticlesConfig::default(),
+                procedural: ProceduralConfig::default(),
+                slideshow: SlideshowConfig::default(),
+                now_playing: NowPlayingConfig::default(),
+                color_temperature: ColorTemperatureConfig::default(),
+                weather_effects: WeatherEffectsConfig::default(),
+                audio_overlay: AudioOverlayConfig::default(),
+                virtual_camera: VirtualCameraConfig::default(),
             },
             app: AppConfig {
                 start_with_system: false,
                 show_in_tray: true,
                 minimize_to_tray: true,
                 theme: ThemeConfig::default(),
+                language: None,
+                desktop_notifications: false,
+                detach_gallery: false,
+                detach_widget_preview: false,
+                active_profile: None,
+                topology_profiles: BTreeMap::new(),
+                resource_limits: ResourceLimitsConfig::default(),
+                auto_pause: AutoPauseConfig::default(),
+                focus_mode: FocusModeConfig::default(),
+                adaptive_quality: AdaptiveQualityConfig::default(),
+                gpu_preference: GpuPreference::default(),
+                watchdog: WatchdogConfig::default(),
+                log_level: LogLevel::default(),
+                config_history: false,
             },
             plugins: PluginConfig {
                 enabled: Vec::new(),
             },
+            sources: SourcesConfig::default(),
+            api: ApiConfig::default(),
+            ai: AiConfig::default(),
+            tagging: TaggingConfig::default(),
+            sync: SyncConfig::default(),
+            bot: BotConfig::default(),
         }
     }
 }
@@ -143,50 +2076,45 @@ impl Default for Config {
 impl Config {
     /// Get the configuration directory
     pub fn get_config_dir() -> Result<PathBuf> {
-        let mut config_dir = config_dir().ok_or_else(|| {
-            anyhow::anyhow!("Could not find configuration directory")
-        })?;
-        
+        let mut config_dir = config_dir()
+            .ok_or_else(|| anyhow::anyhow!("Could not find configuration directory"))?;
+
         config_dir.push("aether-desk");
-        
+
         if !config_dir.exists() {
             fs::create_dir_all(&config_dir)?;
         }
-        
+
         Ok(config_dir)
     }
-    
-    /// Get the configuration file path
+
+    /// Get the configuration file path, `config.toml` when it exists
     pub fn get_config_path() -> Result<PathBuf> {
-        let mut config_path = Self::get_config_dir()?;
-        config_path.push("config.json");
-        Ok(config_path)
+        Ok(format::config_file(&Self::get_config_dir()?, "config"))
     }
-    
-    /// Get the schedule file path
+
+    /// Get the schedule file path, `schedule.toml` when it exists
     pub fn get_schedule_file(&self) -> PathBuf {
-        let mut config_dir = Self::get_config_dir().unwrap_or_else(|_| {
+        let config_dir = Self::get_config_dir().unwrap_or_else(|_| {
             let mut dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
             dir.push("config");
             dir
         });
-        
-        config_dir.push("schedule.json");
-        config_dir
+
+        format::config_file(&config_dir, "schedule")
     }
-    
-    /// Get the widgets file path
+
+    /// Get the widgets file path, `widgets.toml` when it exists
     pub fn get_widgets_file(&self) -> PathBuf {
-        let mut config_dir = Self::get_config_dir().unwrap_or_else(|_| {
+        let config_dir = Self::get_config_dir().unwrap_or_else(|_| {
             let mut dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
             dir.push("config");
             dir
         });
-        
-        config_dir.push("widgets.json");
-        config_dir
+
+        format::config_file(&config_dir, "widgets")
     }
-    
+
     /// Get the plugin directory path
     pub fn get_plugin_dir(&self) -> PathBuf {
         let mut config_dir = Self::get_config_dir().unwrap_or_else(|_| {
@@ -194,42 +2122,315 @@ impl Config {
             dir.push("config");
             dir
         });
-        
+
         config_dir.push("plugins");
-        
+
         // Create plugins directory if it doesn't exist
         if !config_dir.exists() {
             let _ = std::fs::create_dir_all(&config_dir);
         }
-        
+
+        config_dir
+    }
+
+    /// Get the directory for Live Earth images
+    pub fn get_live_earth_dir(&self) -> PathBuf {
+        let mut config_dir = Self::get_config_dir().unwrap_or_else(|_| {
+            let mut dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+            dir.push("config");
+            dir
+        });
+
+        config_dir.push("live_earth");
+
+        // Create Live Earth directory if it doesn't exist
+        if !config_dir.exists() {
+            let _ = std::fs::create_dir_all(&config_dir);
+        }
+
+        config_dir
+    }
+
+    /// Get the directory for saved custom themes
+    pub fn get_themes_dir(&self) -> PathBuf {
+        let mut config_dir = Self::get_config_dir().unwrap_or_else(|_| {
+            let mut dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+            dir.push("config");
+            dir
+        });
+
+        config_dir.push("themes");
+
+        // Create themes directory if it doesn't exist
+        if !config_dir.exists() {
+            let _ = std::fs::create_dir_all(&config_dir);
+        }
+
+        config_dir
+    }
+
+    /// Get the directory for images rendered for a fit mode
+    pub fn get_placement_dir() -> PathBuf {
+        let mut config_dir = Self::get_config_dir().unwrap_or_else(|_| {
+            let mut dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+            dir.push("config");
+            dir
+        });
+
+        config_dir.push("placement");
+
+        // Create placement directory if it doesn't exist
+        if !config_dir.exists() {
+            let _ = std::fs::create_dir_all(&config_dir);
+        }
+
+        config_dir
+    }
+
+    /// Get the directory for images converted to a format the desktop can show
+    pub fn get_converted_dir() -> PathBuf {
+        let mut config_dir = Self::get_config_dir().unwrap_or_else(|_| {
+            let mut dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+            dir.push("config");
+            dir
+        });
+
+        config_dir.push("converted");
+
+        // Create converted directory if it doesn't exist
+        if !config_dir.exists() {
+            let _ = std::fs::create_dir_all(&config_dir);
+        }
+
+        config_dir
+    }
+
+    /// Get the directory for copies of images with their adjustments applied
+    pub fn get_adjusted_dir() -> PathBuf {
+        let mut config_dir = Self::get_config_dir().unwrap_or_else(|_| {
+            let mut dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+            dir.push("config");
+            dir
+        });
+
+        config_dir.push("adjusted");
+
+        // Create adjusted directory if it doesn't exist
+        if !config_dir.exists() {
+            let _ = std::fs::create_dir_all(&config_dir);
+        }
+
+        config_dir
+    }
+
+    /// Get the directory for wallpapers imported from other tools' themes
+    pub fn get_imported_dir() -> PathBuf {
+        let mut config_dir = Self::get_config_dir().unwrap_or_else(|_| {
+            let mut dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+            dir.push("config");
+            dir
+        });
+
+        config_dir.push("imported");
+
+        // Create imported directory if it doesn't exist
+        if !config_dir.exists() {
+            let _ = std::fs::create_dir_all(&config_dir);
+        }
+
+        config_dir
+    }
+
+    /// Get the directory for generated wallpapers saved to the library
+    pub fn get_generated_dir() -> PathBuf {
+        let mut config_dir = Self::get_config_dir().unwrap_or_else(|_| {
+            let mut dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+            dir.push("config");
+            dir
+        });
+
+        config_dir.push("generated");
+
+        // Create generated directory if it doesn't exist
+        if !config_dir.exists() {
+            let _ = std::fs::create_dir_all(&config_dir);
+        }
+
+        config_dir
+    }
+
+    /// Get the directory wallpaper packs are installed in
+    pub fn get_packs_dir() -> PathBuf {
+        let mut config_dir = Self::get_config_dir().unwrap_or_else(|_| {
+            let mut dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+            dir.push("config");
+            dir
+        });
+
+        config_dir.push("packs");
+
+        // Create packs directory if it doesn't exist
+        if !config_dir.exists() {
+            let _ = std::fs::create_dir_all(&config_dir);
+        }
+
+        config_dir
+    }
+
+    /// Get the directory for frames captured from animated wallpapers
+    pub fn get_frames_dir() -> PathBuf {
+        let mut config_dir = Self::get_config_dir().unwrap_or_else(|_| {
+            let mut dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+            dir.push("config");
+            dir
+        });
+
+        config_dir.push("frames");
+
+        // Create frames directory if it doesn't exist
+        if !config_dir.exists() {
+            let _ = std::fs::create_dir_all(&config_dir);
+        }
+
+        config_dir
+    }
+
+    /// Get the PID file listing the players the daemon runs
+    pub fn get_players_file() -> PathBuf {
+        let mut config_dir = Self::get_config_dir().unwrap_or_else(|_| {
+            let mut dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+            dir.push("config");
+            dir
+        });
+
+        config_dir.push("players.json");
+        config_dir
+    }
+
+    /// Get the directory holding the log files
+    pub fn get_logs_dir() -> PathBuf {
+        let mut config_dir = Self::get_config_dir().unwrap_or_else(|_| {
+            let mut dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+            dir.push("config");
+            dir
+        });
+
+        config_dir.push("logs");
+
+        // Create logs directory if it doesn't exist
+        if !config_dir.exists() {
+            let _ = std::fs::create_dir_all(&config_dir);
+        }
+
+        config_dir
+    }
+
+    /// Get the directory holding crash reports
+    pub fn get_crashes_dir() -> PathBuf {
+        let mut config_dir = Self::get_config_dir().unwrap_or_else(|_| {
+            let mut dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+            dir.push("config");
+            dir
+        });
+
+        config_dir.push("crashes");
+        config_dir
+    }
+
+    /// Get the file noting the desktop's own wallpaper while the daemon runs
+    pub fn get_system_wallpaper_file() -> PathBuf {
+        let mut config_dir = Self::get_config_dir().unwrap_or_else(|_| {
+            let mut dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+            dir.push("config");
+            dir
+        });
+
+        config_dir.push("system_wallpaper.txt");
+        config_dir
+    }
+
+    /// Get the directory holding the profiles
+    pub fn get_profiles_dir() -> PathBuf {
+        let mut config_dir = Self::get_config_dir().unwrap_or_else(|_| {
+            let mut dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+            dir.push("config");
+            dir
+        });
+
+        config_dir.push("profiles");
+        config_dir
+    }
+
+    /// Get the directory for downloaded wallpapers
+    pub fn get_downloads_dir(&self) -> PathBuf {
+        let mut config_dir = Self::get_config_dir().unwrap_or_else(|_| {
+            let mut dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+            dir.push("config");
+            dir
+        });
+
+        config_dir.push("downloads");
+
+        // Create downloads directory if it doesn't exist
+        if !config_dir.exists() {
+            let _ = std::fs::create_dir_all(&config_dir);
+        }
+
         config_dir
     }
-    
+
     /// Load configuration from file
     pub fn load() -> Result<Self> {
         let config_path = Self::get_config_path()?;
-        
+
         if !config_path.exists() {
             info!("Configuration file not found, creating default");
             let config = Self::default();
             config.save()?;
             return Ok(config);
         }
-        
-        let config_str = fs::read_to_string(config_path)?;
-        let config: Self = serde_json::from_str(&config_str)?;
-        
+
+        let config: Self = format::load(&config_path)?;
+
         debug!("Configuration loaded");
         Ok(config)
     }
-    
+
     /// Save configuration to file
     pub fn save(&self) -> Result<()> {
         let config_path = Self::get_config_path()?;
-        let config_str = serde_json::to_string_pretty(self)?;
-        fs::write(config_path, config_str)?;
-        
+        format::save(&config_path, self)?;
+
         debug!("Configuration saved");
         Ok(())
     }
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_rate_limit() {
+        let refresh = || 144.0;
+        assert_eq!(FrameRateLimit::Unlimited.fps(refresh), None);
+        assert_eq!(FrameRateLimit::Fixed(30).fps(refresh), Some(30));
+        assert_eq!(FrameRateLimit::Fixed(0).fps(refresh), Some(1));
+        assert_eq!(FrameRateLimit::MatchRefresh.fps(refresh), Some(144));
+        assert_eq!(FrameRateLimit::HalfRefresh.fps(|| 59.95), Some(30));
+
+        let mut config = FrameRateConfig {
+            limit: FrameRateLimit::Fixed(30),
+            ..Default::default()
+        };
+        let path = Path::new("/videos/waves.mp4");
+        config.set_override(path, Some(FrameRateLimit::HalfRefresh));
+        assert_eq!(config.limit_for(path), FrameRateLimit::HalfRefresh);
+        assert_eq!(
+            config.limit_for(Path::new("/videos/other.mp4")),
+            FrameRateLimit::Fixed(30)
+        );
+        config.set_override(path, None);
+        assert_eq!(config.limit_for(path), FrameRateLimit::Fixed(30));
+    }
+}
@@ -0,0 +1,407 @@
+//! History of the configuration files, kept in git
+//!
+//! When turned on, every save of the configuration, schedule or widget
+//! layout is committed to a git repository in the `history` folder of the
+//! configuration directory. Saves are gathered for a moment first, as sliders
+//! save on every step they move. The repository lives apart from the files it
+//! tracks, so a dotfiles repository holding the configuration directory isn't
+//! disturbed, and only those three files are ever added: secrets, caches and
+//! rendered images stay out. The repository is written through libgit2, so
+//! the `git` command isn't needed.
+use crate::core::{format, AppError, AppResult, Config, ConfigFile};
+use chrono::{DateTime, Local, TimeZone};
+use git2::{Commit, Oid, Repository, Signature, Sort, Tree};
+use log::{debug, info, warn};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, Once, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Time saves are gathered for before they're committed together
+const SETTLE_TIME: Duration = Duration::from_secs(1);
+
+/// Whether saves are committed
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Version of the configuration files
+#[derive(Debug, Clone, PartialEq)]
+pub struct Version {
+    /// Commit ID
+    pub id: String,
+
+    /// When it was saved
+    pub time: DateTime<Local>,
+
+    /// What was done, like "Save schedule.json"
+    pub message: String,
+
+    /// Names of the files it changed
+    pub files: Vec<String>,
+}
+
+impl Version {
+    /// Get the first characters of the ID, enough to tell versions apart
+    pub fn short_id(&self) -> &str {
+        &self.id[..self.id.len().min(7)]
+    }
+}
+
+/// Saves waiting to be committed
+#[derive(Default)]
+struct Pending {
+    /// Names of the saved files
+    files: BTreeSet<String>,
+
+    /// Last save
+    last: Option<Instant>,
+}
+
+/// Turn committing saves on or off
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::SeqCst);
+}
+
+/// Check whether saves are committed
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::SeqCst)
+}
+
+/// Note a saved file, committing it shortly if it's a configuration file
+///
+/// Files outside the configuration directory, like profile copies, are left
+/// out.
+pub fn record(path: &Path) {
+    if !is_enabled() || ConfigFile::of(path).is_none() {
+        return;
+    }
+    let Ok(dir) = Config::get_config_dir() else {
+        return;
+    };
+    let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+        return;
+    };
+    if parent != dir {
+        return;
+    }
+
+    {
+        let mut pending = pending().lock().unwrap();
+        pending.files.insert(name.to_string_lossy().to_string());
+        pending.last = Some(Instant::now());
+    }
+
+    static WORKER: Once = Once::new();
+    WORKER.call_once(|| {
+        thread::spawn(move || loop {
+            thread::sleep(SETTLE_TIME / 2);
+            let files: Vec<String> = {
+                let mut pending = pending().lock().unwrap();
+                if pending
+                    .last
+                    .map_or(true, |last| last.elapsed() < SETTLE_TIME)
+                {
+                    continue;
+                }
+                pending.last = None;
+                std::mem::take(&mut pending.files).into_iter().collect()
+            };
+            let message = format!("Save {}", files.join(", "));
+            if let Err(e) = ConfigHistory::open(&dir).commit(&files, &message) {
+                warn!("Failed to record the configuration history: {}", e);
+            }
+        });
+    });
+}
+
+/// Get the saves waiting to be committed
+fn pending() -> &'static Mutex<Pending> {
+    static PENDING: OnceLock<Mutex<Pending>> = OnceLock::new();
+    PENDING.get_or_init(|| Mutex::new(Pending::default()))
+}
+
+/// Git repository holding the history of a configuration directory
+pub struct ConfigHistory {
+    /// Configuration directory, the repository's work tree
+    dir: PathBuf,
+
+    /// Repository
+    git_dir: PathBuf,
+}
+
+impl ConfigHistory {
+    /// Open the history of the configuration files in `dir`
+    pub fn open(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            git_dir: dir.join("history"),
+        }
+    }
+
+    /// Open the history of the configuration directory in use
+    pub fn current() -> AppResult<Self> {
+        let dir = Config::get_config_dir().map_err(|e| AppError::ConfigError(e.to_string()))?;
+        Ok(Self::open(&dir))
+    }
+
+    /// Commit the current content of files, creating the repository first if needed
+    ///
+    /// Files that no longer exist are removed from the next version. Returns
+    /// false when none of them changed since the last version.
+    pub fn commit(&self, files: &[String], message: &str) -> AppResult<bool> {
+        if files.is_empty() {
+            return Ok(false);
+        }
+        let repo = if self.git_dir.join("HEAD").exists() {
+            Repository::open(&self.git_dir).map_err(git_error)?
+        } else {
+            let repo = Repository::init_bare(&self.git_dir).map_err(git_error)?;
+            info!(
+                "Created configuration history in {}",
+                self.git_dir.display()
+            );
+            repo
+        };
+
+        let parent = head_commit(&repo)?;
+        let parent_tree = parent
+            .as_ref()
+            .map(Commit::tree)
+            .transpose()
+            .map_err(git_error)?;
+        let mut builder = repo.treebuilder(parent_tree.as_ref()).map_err(git_error)?;
+        for file in files {
+            check_file(file)?;
+            match fs::read(self.dir.join(file)) {
+                Ok(content) => {
+                    let blob = repo.blob(&content).map_err(git_error)?;
+                    builder.insert(file, blob, 0o100644).map_err(git_error)?;
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    if builder.get(file).map_err(git_error)?.is_some() {
+                        builder.remove(file).map_err(git_error)?;
+                    }
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+        let tree_id = builder.write().map_err(git_error)?;
+        if parent_tree.as_ref().map(Tree::id) == Some(tree_id) {
+            debug!(
+                "Configuration history: nothing changed in {}",
+                files.join(", ")
+            );
+            return Ok(false);
+        }
+
+        // The history is the app's own, whatever the user's git settings say
+        let signature =
+            Signature::now("Aether-Desk", "aether-desk@localhost").map_err(git_error)?;
+        let tree = repo.find_tree(tree_id).map_err(git_error)?;
+        let parents: Vec<&Commit> = parent.iter().collect();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents,
+        )
+        .map_err(git_error)?;
+        debug!("Configuration history: {}", message);
+        Ok(true)
+    }
+
+    /// Commit every configuration file as it is, e.g. when the history is turned on
+    pub fn snapshot(&self, message: &str) -> AppResult<bool> {
+        let files: Vec<String> = ConfigFile::ALL
+            .into_iter()
+            .map(|file| format::config_file(&self.dir, file.name()))
+            .filter(|path| path.exists())
+            .filter_map(|path| Some(path.file_name()?.to_string_lossy().to_string()))
+            .collect();
+        self.commit(&files, message)
+    }
+
+    /// List the latest versions, newest first
+    pub fn versions(&self, limit: usize) -> AppResult<Vec<Version>> {
+        let Some(repo) = self.repository()? else {
+            return Ok(Vec::new());
+        };
+        // A repository without commits yet has no versions
+        if head_commit(&repo)?.is_none() {
+            return Ok(Vec::new());
+        }
+
+        let mut walk = repo.revwalk().map_err(git_error)?;
+        walk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)
+            .map_err(git_error)?;
+        walk.push_head().map_err(git_error)?;
+        walk.take(limit)
+            .map(|id| version(&repo, id.map_err(git_error)?))
+            .collect()
+    }
+
+    /// Get the content of a file in a version
+    pub fn show(&self, id: &str, file: &str) -> AppResult<String> {
+        check_file(file)?;
+        let repo = self.repository()?.ok_or_else(|| {
+            AppError::ConfigError("There is no configuration history yet".to_string())
+        })?;
+        let oid = Oid::from_str(id).map_err(git_error)?;
+        let tree = repo
+            .find_commit(oid)
+            .and_then(|commit| commit.tree())
+            .map_err(git_error)?;
+        let entry = tree.get_name(file).ok_or_else(|| {
+            AppError::ConfigError(format!(
+                "{} isn't in version {}",
+                file,
+                &id[..id.len().min(7)]
+            ))
+        })?;
+        let blob = entry
+            .to_object(&repo)
+            .and_then(|object| object.peel_to_blob())
+            .map_err(git_error)?;
+        Ok(String::from_utf8_lossy(blob.content()).into_owned())
+    }
+
+    /// Put back a file as it was in a version, recording that as a new version
+    ///
+    /// The file is written like an edit by hand, so the running app and
+    /// service reload it.
+    pub fn restore(&self, version: &Version, file: &str) -> AppResult<()> {
+        let content = self.show(&version.id, file)?;
+        fs::write(self.dir.join(file), content)?;
+        self.commit(
+            &[file.to_string()],
+            &format!("Restore {} from {}", file, version.short_id()),
+        )?;
+        info!("Restored {} from version {}", file, version.short_id());
+        Ok(())
+    }
+
+    /// Open the repository, or None when no version was recorded yet
+    fn repository(&self) -> AppResult<Option<Repository>> {
+        if !self.git_dir.join("HEAD").exists() {
+            return Ok(None);
+        }
+        Repository::open(&self.git_dir).map(Some).map_err(git_error)
+    }
+}
+
+/// Get the latest commit, or None in a repository without commits
+fn head_commit(repo: &Repository) -> AppResult<Option<Commit<'_>>> {
+    match repo.head() {
+        Ok(head) => head.peel_to_commit().map(Some).map_err(git_error),
+        Err(e)
+            if matches!(
+                e.code(),
+                git2::ErrorCode::UnbornBranch | git2::ErrorCode::NotFound
+            ) =>
+        {
+            Ok(None)
+        }
+        Err(e) => Err(git_error(e)),
+    }
+}
+
+/// Describe a commit as a version, with the files it changed
+fn version(repo: &Repository, id: Oid) -> AppResult<Version> {
+    let commit = repo.find_commit(id).map_err(git_error)?;
+    let tree = commit.tree().map_err(git_error)?;
+    let parent_tree = commit
+        .parents()
+        .next()
+        .map(|parent| parent.tree())
+        .transpose()
+        .map_err(git_error)?;
+    let diff = repo
+        .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)
+        .map_err(git_error)?;
+    let files = diff
+        .deltas()
+        .filter_map(|delta| {
+            delta
+                .new_file()
+                .path()
+                .or_else(|| delta.old_file().path())
+                .map(|path| path.to_string_lossy().to_string())
+        })
+        .collect();
+
+    Ok(Version {
+        id: id.to_string(),
+        time: Local
+            .timestamp_opt(commit.time().seconds(), 0)
+            .single()
+            .unwrap_or_else(Local::now),
+        message: commit.summary().unwrap_or_default().to_string(),
+        files,
+    })
+}
+
+/// Turn a libgit2 error into a configuration error
+fn git_error(e: git2::Error) -> AppError {
+    AppError::ConfigError(format!("Configuration history: {}", e.message()))
+}
+
+/// Check that a name is one of the tracked files, not a path elsewhere
+fn check_file(file: &str) -> AppResult<()> {
+    let path = Path::new(file);
+    if path.components().count() != 1 || ConfigFile::of(path).is_none() {
+        return Err(AppError::ConfigError(format!(
+            "{} isn't a configuration file",
+            file
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commit_and_restore() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = ConfigHistory::open(dir.path());
+        let schedule = dir.path().join("schedule.json");
+        assert!(history.versions(10).unwrap().is_empty());
+
+        fs::write(&schedule, "[1]").unwrap();
+        fs::write(dir.path().join("secrets.json"), "{}").unwrap();
+        assert!(history
+            .commit(&["schedule.json".to_string()], "Save schedule.json")
+            .unwrap());
+        assert!(!history
+            .commit(&["schedule.json".to_string()], "Save schedule.json")
+            .unwrap());
+        fs::write(&schedule, "[2]").unwrap();
+        assert!(history
+            .commit(&["schedule.json".to_string()], "Save schedule.json")
+            .unwrap());
+
+        let versions = history.versions(10).unwrap();
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions[0].message, "Save schedule.json");
+        assert_eq!(versions[0].files, ["schedule.json"]);
+        assert_eq!(
+            history.show(&versions[1].id, "schedule.json").unwrap(),
+            "[1]"
+        );
+        assert!(history.show(&versions[1].id, "secrets.json").is_err());
+        assert!(history.show(&versions[1].id, "../schedule.json").is_err());
+
+        history.restore(&versions[1], "schedule.json").unwrap();
+        assert_eq!(fs::read_to_string(&schedule).unwrap(), "[1]");
+        let versions = history.versions(10).unwrap();
+        assert_eq!(versions.len(), 3);
+        assert!(versions[0]
+            .message
+            .starts_with("Restore schedule.json from"));
+    }
+}
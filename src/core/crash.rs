@@ -0,0 +1,245 @@
+//! Crash reports
+//!
+//! A panic writes a report with the message, a backtrace, the latest log lines
+//! and a summary of the configuration to the config directory. The window
+//! offers to open the newest report it hasn't shown yet on the next launch.
+use crate::core::{format, logging, Config};
+use chrono::Local;
+use std::backtrace::Backtrace;
+use std::fs;
+use std::io;
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::thread;
+
+/// Number of reports kept
+const KEPT_REPORTS: usize = 10;
+
+/// File naming the newest report the user was shown
+const SEEN_FILE: &str = "seen.txt";
+
+/// Install the panic hook, keeping the default one that prints the panic
+pub fn install() {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        let location = info
+            .location()
+            .map_or_else(String::new, |location| location.to_string());
+        let thread = thread::current().name().unwrap_or("unnamed").to_string();
+
+        // Logging could wait on a lock the panicking thread holds, so only stderr is used
+        let text = report_text(
+            &message,
+            &location,
+            &thread,
+            &Backtrace::force_capture().to_string(),
+            &logging::recent_lines(),
+            &config_summary(),
+        );
+        match save_report(&Config::get_crashes_dir(), &text) {
+            Ok(path) => eprintln!("Crash report written to {}", path.display()),
+            Err(e) => eprintln!("Failed to write crash report: {}", e),
+        }
+        previous(info);
+    }));
+}
+
+/// Get the newest report the user hasn't been shown
+pub fn latest_unseen() -> Option<PathBuf> {
+    latest_unseen_in(&Config::get_crashes_dir())
+}
+
+/// Note that the user was shown a report, and every older one
+pub fn mark_seen(report: &Path) -> io::Result<()> {
+    let name = report
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let dir = report
+        .parent()
+        .map_or_else(Config::get_crashes_dir, Path::to_path_buf);
+    fs::write(dir.join(SEEN_FILE), name)
+}
+
+/// Describe the configuration, leaving out keys, tokens and file paths
+fn config_summary() -> String {
+    let mut lines = vec![
+        format!("Version: {}", env!("CARGO_PKG_VERSION")),
+        format!("OS: {} ({})", std::env::consts::OS, std::env::consts::ARCH),
+    ];
+    if let Ok(desktop) = std::env::var("XDG_CURRENT_DESKTOP") {
+        lines.push(format!("Desktop: {}", desktop));
+    }
+
+    // Loaded without Config::load, which would create a missing file
+    let config = Config::get_config_path()
+        .ok()
+        .filter(|path| path.exists())
+        .and_then(|path| format::load::<Config>(&path).ok());
+    match config {
+        Some(config) => {
+            lines.push(format!(
+                "Wallpaper type: {}",
+                config.wallpaper.wallpaper_type.as_str()
+            ));
+            lines.push(format!(
+                "Auto change: {}",
+                config.wallpaper.auto_change.enabled
+            ));
+            lines.push(format!("Theme: {:?}", config.app.theme.theme));
+            lines.push(format!(
+                "Language: {}",
+                config.app.language.as_deref().unwrap_or("system")
+            ));
+            lines.push(format!(
+                "Profile: {}",
+                if config.app.active_profile.is_some() {
+                    "custom"
+                } else {
+                    "default"
+                }
+            ));
+            lines.push(format!("GPU preference: {:?}", config.app.gpu_preference));
+            lines.push(format!("Log level: {:?}", config.app.log_level));
+            lines.push(format!("Plugins enabled: {}", config.plugins.enabled.len()));
+            lines.push(format!("Local API: {}", config.api.enabled));
+        }
+        None => lines.push("Configuration: not readable".to_string()),
+    }
+    lines.join("\n")
+}
+
+/// Write the text of a report
+fn report_text(
+    message: &str,
+    location: &str,
+    thread: &str,
+    backtrace: &str,
+    log_lines: &[String],
+    summary: &str,
+) -> String {
+    let mut text = format!(
+        "Aether-Desk crash report\n{}\n\nPanic in thread '{}' at {}:\n{}\n\n== Configuration ==\n{}\n\n== Recent log ==\n",
+        Local::now().format("%Y-%m-%d %H:%M:%S %z"),
+        thread,
+        location,
+        message,
+        summary,
+    );
+    if log_lines.is_empty() {
+        text.push_str("(nothing logged)\n");
+    }
+    for line in log_lines {
+        text.push_str(line);
+        text.push('\n');
+    }
+    text.push_str("\n== Backtrace ==\n");
+    text.push_str(backtrace);
+    text.push('\n');
+    text
+}
+
+/// Save a report in `dir`, removing the oldest ones past the limit
+fn save_report(dir: &Path, text: &str) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!(
+        "crash-{}.txt",
+        Local::now().format("%Y%m%d-%H%M%S-%3f")
+    ));
+    fs::write(&path, text)?;
+
+    let reports = reports_in(dir);
+    for old in reports
+        .iter()
+        .take(reports.len().saturating_sub(KEPT_REPORTS))
+    {
+        let _ = fs::remove_file(old);
+    }
+    Ok(path)
+}
+
+/// Get the reports in `dir`, oldest first
+fn reports_in(dir: &Path) -> Vec<PathBuf> {
+    let mut reports: Vec<PathBuf> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            name.starts_with("crash-") && name.ends_with(".txt")
+        })
+        .collect();
+    // The names hold the time, so they sort by age
+    reports.sort();
+    reports
+}
+
+/// Get the newest report in `dir` newer than the last one shown
+fn latest_unseen_in(dir: &Path) -> Option<PathBuf> {
+    let latest = reports_in(dir).pop()?;
+    let seen = fs::read_to_string(dir.join(SEEN_FILE)).unwrap_or_default();
+    let name = latest.file_name()?.to_string_lossy().to_string();
+    (name.as_str() > seen.trim()).then_some(latest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_text() {
+        let lines = vec!["2024-05-01 12:00:00.000 INFO  aether_desk: Started".to_string()];
+        let text = report_text(
+            "index out of bounds",
+            "src/core/scheduler.rs:10:5",
+            "main",
+            "0: main",
+            &lines,
+            "Version: 1.0",
+        );
+        assert!(text.contains(
+            "Panic in thread 'main' at src/core/scheduler.rs:10:5:\nindex out of bounds"
+        ));
+        assert!(
+            text.contains("== Recent log ==\n2024-05-01 12:00:00.000 INFO  aether_desk: Started\n")
+        );
+        assert!(text.contains("== Configuration ==\nVersion: 1.0"));
+        assert!(text.ends_with("0: main\n"));
+    }
+
+    #[test]
+    fn test_unseen_and_pruned_reports() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(latest_unseen_in(dir.path()).is_none());
+
+        let first = save_report(dir.path(), "first").unwrap();
+        assert_eq!(latest_unseen_in(dir.path()), Some(first.clone()));
+        mark_seen(&first).unwrap();
+        assert!(latest_unseen_in(dir.path()).is_none());
+
+        for index in 0..KEPT_REPORTS {
+            fs::write(
+                dir.path()
+                    .join(format!("crash-00000000-000000-{:03}.txt", index)),
+                "older",
+            )
+            .unwrap();
+        }
+        // Report names have millisecond precision
+        thread::sleep(std::time::Duration::from_millis(5));
+        let last = save_report(dir.path(), "last").unwrap();
+        assert_eq!(reports_in(dir.path()).len(), KEPT_REPORTS);
+        assert!(first.exists());
+        assert!(!dir.path().join("crash-00000000-000000-000.txt").exists());
+        assert_eq!(latest_unseen_in(dir.path()), Some(last));
+    }
+}
@@ -0,0 +1,217 @@
+//! State of the wallpaper on screen
+//!
+//! Whoever changes the wallpaper records it here, so the UI, the scheduler and
+//! the daemon report the same wallpaper whichever backend shows it. Either one
+//! wallpaper covers every monitor, or monitors each show their own.
+use crate::core::{WallpaperInfo, WallpaperType};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Wallpaper on screen
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurrentWallpaperState {
+    /// Type, file and URL of the wallpaper
+    pub wallpaper: WallpaperInfo,
+
+    /// Monitors given their own image, empty when every monitor shows the same one
+    #[serde(default)]
+    pub monitors: Vec<String>,
+
+    /// When the wallpaper was applied
+    pub started_at: DateTime<Utc>,
+
+    /// Process ID of the player, for wallpapers that have one
+    #[serde(default)]
+    pub pid: Option<u32>,
+}
+
+impl CurrentWallpaperState {
+    /// Describe a wallpaper applied now
+    pub fn new(wallpaper: WallpaperInfo, monitors: Vec<String>, pid: Option<u32>) -> Self {
+        Self {
+            wallpaper,
+            monitors,
+            started_at: Utc::now(),
+            pid,
+        }
+    }
+
+    /// Get the wallpaper type
+    pub fn wallpaper_type(&self) -> &WallpaperType {
+        &self.wallpaper.r#type
+    }
+
+    /// Get the file or URL the wallpaper shows
+    pub fn source(&self) -> Option<String> {
+        self.wallpaper
+            .path
+            .as_ref()
+            .map(|path| path.display().to_string())
+            .or_else(|| self.wallpaper.url.clone())
+    }
+}
+
+/// Holder of the current wallpaper state
+#[derive(Debug, Default)]
+pub struct CurrentWallpaperStore {
+    /// Wallpaper on every monitor, if any
+    state: Mutex<Option<CurrentWallpaperState>>,
+
+    /// Wallpapers on single monitors, by monitor ID
+    monitors: Mutex<BTreeMap<String, CurrentWallpaperState>>,
+}
+
+#[allow(dead_code)]
+impl CurrentWallpaperStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the process-wide store
+    pub fn shared() -> Arc<CurrentWallpaperStore> {
+        static SHARED: OnceLock<Arc<CurrentWallpaperStore>> = OnceLock::new();
+        SHARED
+            .get_or_init(|| Arc::new(CurrentWallpaperStore::new()))
+            .clone()
+    }
+
+    /// Record a newly applied wallpaper
+    ///
+    /// A wallpaper for one monitor replaces the one covering every monitor,
+    /// and the other way around.
+    pub fn set(&self, state: CurrentWallpaperState) {
+        match state.wallpaper.monitor.clone() {
+            Some(monitor) => {
+                *self.state.lock().unwrap() = None;
+                self.monitors.lock().unwrap().insert(monitor, state);
+            }
+            None => {
+                self.monitors.lock().unwrap().clear();
+                *self.state.lock().unwrap() = Some(state);
+            }
+        }
+    }
+
+    /// Record that no wallpaper is shown
+    pub fn clear(&self) {
+        *self.state.lock().unwrap() = None;
+        self.monitors.lock().unwrap().clear();
+    }
+
+    /// Record that a monitor no longer shows a wallpaper of its own
+    pub fn clear_on(&self, monitor: &str) {
+        self.monitors.lock().unwrap().remove(monitor);
+    }
+
+    /// Record the process of a restarted player
+    pub fn set_pid(&self, pid: Option<u32>) {
+        if let Some(state) = self.state.lock().unwrap().as_mut() {
+            state.pid = pid;
+        }
+    }
+
+    /// Record the process of a restarted player on a single monitor
+    pub fn set_pid_on(&self, monitor: &str, pid: Option<u32>) {
+        if let Some(state) = self.monitors.lock().unwrap().get_mut(monitor) {
+            state.pid = pid;
+        }
+    }
+
+    /// Get the current wallpaper, the first monitor's when monitors show their own
+    pub fn get(&self) -> Option<CurrentWallpaperState> {
+        self.state
+            .lock()
+            .unwrap()
+            .clone()
+            .or_else(|| self.monitors.lock().unwrap().values().next().cloned())
+    }
+
+    /// Get the wallpapers shown on single monitors, ordered by monitor ID
+    pub fn per_monitor(&self) -> Vec<CurrentWallpaperState> {
+        self.monitors.lock().unwrap().values().cloned().collect()
+    }
+
+    /// Get the file of the current wallpaper
+    pub fn path(&self) -> Option<PathBuf> {
+        self.get().and_then(|state| state.wallpaper.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_tracks_current_wallpaper() {
+        let store = CurrentWallpaperStore::new();
+        assert!(store.get().is_none());
+
+        let wallpaper =
+            WallpaperInfo::new(WallpaperType::Video, Some("/walls/sea.mp4".into()), None);
+        store.set(CurrentWallpaperState::new(wallpaper, Vec::new(), Some(42)));
+        assert_eq!(store.path(), Some(PathBuf::from("/walls/sea.mp4")));
+        assert_eq!(store.get().unwrap().pid, Some(42));
+
+        store.set_pid(None);
+        assert_eq!(store.get().unwrap().pid, None);
+        store.clear();
+        assert!(store.get().is_none());
+        store.set_pid(Some(7));
+        assert!(store.get().is_none());
+    }
+
+    #[test]
+    fn test_store_tracks_wallpapers_per_monitor() {
+        let store = CurrentWallpaperStore::new();
+        let video = WallpaperInfo::new(WallpaperType::Video, Some("/walls/sea.mp4".into()), None);
+        let shader = WallpaperInfo::new(
+            WallpaperType::Shader,
+            Some("/walls/waves.frag".into()),
+            None,
+        );
+        store.set(CurrentWallpaperState::new(video.clone(), Vec::new(), None));
+
+        // Monitors showing their own wallpaper replace the one on every monitor
+        store.set(CurrentWallpaperState::new(
+            shader.on_monitor(Some("DP-2".to_string())),
+            Vec::new(),
+            None,
+        ));
+        store.set(CurrentWallpaperState::new(
+            video.clone().on_monitor(Some("DP-1".to_string())),
+            Vec::new(),
+            Some(42),
+        ));
+        let monitors: Vec<_> = store
+            .per_monitor()
+            .into_iter()
+            .map(|state| state.wallpaper.monitor.unwrap())
+            .collect();
+        assert_eq!(monitors, vec!["DP-1", "DP-2"]);
+        assert_eq!(store.path(), Some(PathBuf::from("/walls/sea.mp4")));
+
+        store.set_pid_on("DP-1", Some(7));
+        assert_eq!(store.get().unwrap().pid, Some(7));
+        store.clear_on("DP-1");
+        assert_eq!(store.path(), Some(PathBuf::from("/walls/waves.frag")));
+
+        store.set(CurrentWallpaperState::new(video, Vec::new(), None));
+        assert!(store.per_monitor().is_empty());
+    }
+
+    #[test]
+    fn test_source_prefers_path() {
+        let web = WallpaperInfo::new(
+            WallpaperType::Web,
+            None,
+            Some("https://example.com".to_string()),
+        );
+        let state = CurrentWallpaperState::new(web, Vec::new(), None);
+        assert_eq!(state.source().as_deref(), Some("https://example.com"));
+        assert_eq!(state.wallpaper_type(), &WallpaperType::Web);
+    }
+}
@@ -7,31 +7,85 @@ pub enum AppError {
     /// Configuration error
     #[error("Configuration error: {0}")]
     ConfigError(String),
-    
+
     /// Platform error
     #[error("Platform error: {0}")]
     PlatformError(String),
-    
+
     /// Wallpaper error
     #[error("Wallpaper error: {0}")]
     WallpaperError(String),
-    
+
     /// Plugin error
     #[error("Plugin error: {0}")]
     PluginError(String),
-    
+
+    /// Network error
+    #[error("Network error: {0}")]
+    NetworkError(String),
+
+    /// Secret storage error
+    #[error("Secret storage error: {0}")]
+    SecretError(String),
+
     /// IO error
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
-    
+
     /// Serialization error
     #[error("Serialization error: {0}")]
     SerializationError(#[from] serde_json::Error),
-    
+
     /// Unsupported platform
     #[error("Unsupported platform")]
     UnsupportedPlatform,
-    
+
+    /// A program the feature needs isn't installed
+    #[error("{tool} is not installed")]
+    MissingExternalTool {
+        /// Program name, e.g. "mpv"
+        tool: String,
+        /// How to install it
+        install_hint: String,
+    },
+
+    /// The desktop can't be driven the way the feature needs
+    #[error("{backend} is not available: {reason}")]
+    BackendUnavailable {
+        /// Backend, e.g. "Hyprland" or "GNOME"
+        backend: String,
+        /// What is missing
+        reason: String,
+    },
+
+    /// A helper process exited while it should have kept running
+    #[error("{process} exited {}", exit_text(.code))]
+    ProcessDied {
+        /// Program name
+        process: String,
+        /// Exit code, None when killed by a signal
+        code: Option<i32>,
+    },
+
+    /// Error with what was being done when it happened
+    #[error("{context}: {source}")]
+    Context {
+        /// What was being done
+        context: String,
+        /// Underlying error
+        #[source]
+        source: Box<AppError>,
+    },
+
+    /// Error reported by the daemon
+    #[error("{message}")]
+    Remote {
+        /// Error message
+        message: String,
+        /// Suggested fix sent along
+        hint: Option<String>,
+    },
+
     /// Other error
     #[error("Other error: {0}")]
     Other(String),
@@ -49,6 +103,120 @@ impl From<&str> for AppError {
     }
 }
 
+impl AppError {
+    /// Report a program that isn't installed, with how to install it
+    pub fn missing_tool(tool: &str) -> Self {
+        AppError::MissingExternalTool {
+            tool: tool.to_string(),
+            install_hint: install_hint(tool),
+        }
+    }
+
+    /// Turn a failure to start a program into a missing-tool error when it isn't installed
+    pub fn from_spawn(tool: &str, error: std::io::Error) -> Self {
+        if error.kind() == std::io::ErrorKind::NotFound {
+            Self::missing_tool(tool)
+        } else {
+            AppError::IoError(error).context(format!("Failed to run {}", tool))
+        }
+    }
+
+    /// Add what was being done when the error happened
+    pub fn context(self, context: impl Into<String>) -> Self {
+        AppError::Context {
+            context: context.into(),
+            source: Box::new(self),
+        }
+    }
+
+    /// Get a fix to suggest to the user, if one is known
+    pub fn hint(&self) -> Option<String> {
+        match self {
+            AppError::MissingExternalTool { install_hint, .. } => Some(install_hint.clone()),
+            AppError::BackendUnavailable { .. } => {
+                Some("Run `aether-desk doctor` to see which desktop tools are missing".to_string())
+            },
+            AppError::ProcessDied { process, .. } => {
+                Some(format!("Run {} on its own to see why it stops, or turn on Settings → Watchdog to restart it", process))
+            },
+            AppError::NetworkError(_) => Some("Check your internet connection and try again".to_string()),
+            AppError::UnsupportedPlatform => Some("Aether-Desk runs on Windows and Linux".to_string()),
+            AppError::Context { source, .. } => source.hint(),
+            AppError::Remote { hint, .. } => hint.clone(),
+            _ => None,
+        }
+    }
+}
+
+/// Add context to errors as they travel up, like `anyhow::Context`
+pub trait ErrorContext<T> {
+    /// Add what was being done when the error happened
+    fn context(self, context: impl Into<String>) -> AppResult<T>;
+
+    /// Add context built only when there is an error
+    fn with_context<C: Into<String>>(self, context: impl FnOnce() -> C) -> AppResult<T>;
+}
+
+impl<T, E: Into<AppError>> ErrorContext<T> for Result<T, E> {
+    fn context(self, context: impl Into<String>) -> AppResult<T> {
+        self.map_err(|e| e.into().context(context))
+    }
+
+    fn with_context<C: Into<String>>(self, context: impl FnOnce() -> C) -> AppResult<T> {
+        self.map_err(|e| e.into().context(context()))
+    }
+}
+
+/// Suggest how to install an external program on this platform
+pub fn install_hint(tool: &str) -> String {
+    let (package, url) = match tool {
+        "mpv" => ("mpv", "https://mpv.io/installation/"),
+        "vlc" => ("VLC", "https://www.videolan.org/vlc/"),
+        "firefox" => ("Firefox", "https://www.mozilla.org/firefox/"),
+        "curl" => ("curl", "https://curl.se/download.html"),
+        "cabextract" => ("cabextract", "https://www.cabextract.org.uk/"),
+        "secret-tool" => (
+            "libsecret-tools (secret-tool)",
+            "https://wiki.gnome.org/Projects/Libsecret",
+        ),
+        "notify-send" => (
+            "libnotify (notify-send)",
+            "https://gitlab.gnome.org/GNOME/libnotify",
+        ),
+        "xdg-open" => (
+            "xdg-utils (xdg-open)",
+            "https://www.freedesktop.org/wiki/Software/xdg-utils/",
+        ),
+        "xrandr" => ("xrandr", "https://www.x.org/wiki/Projects/XRandR/"),
+        "wlr-randr" => ("wlr-randr", "https://sr.ht/~emersion/wlr-randr/"),
+        "shadertoy" => {
+            return "Install a `shadertoy` player and make sure it is on your PATH".to_string()
+        }
+        "hyprctl" => return "hyprctl ships with Hyprland; check your PATH".to_string(),
+        "hyprpaper" => {
+            return "Install hyprpaper and add `exec-once = hyprpaper` to hyprland.conf".to_string()
+        }
+        _ => return format!("Install {} and make sure it is on your PATH", tool),
+    };
+
+    if cfg!(target_os = "windows") {
+        format!("Install {} from {} and add it to your PATH", package, url)
+    } else {
+        format!(
+            "Install {} with your package manager (see {})",
+            package, url
+        )
+    }
+}
+
+/// Describe how a process exited
+fn exit_text(code: &Option<i32>) -> String {
+    match code {
+        Some(code) => format!("with code {}", code),
+        None => "after being killed".to_string(),
+    }
+}
+
 #[cfg(windows)]
 impl From<windows::core::Error> for AppError {
     fn from(e: windows::core::Error) -> Self {
@@ -59,3 +227,44 @@ impl From<windows::core::Error> for AppError {
 /// Result type for the application
 #[allow(dead_code)]
 pub type AppResult<T> = Result<T, AppError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_tool_from_spawn() {
+        let error = AppError::from_spawn("mpv", std::io::Error::from(std::io::ErrorKind::NotFound));
+        assert_eq!(error.to_string(), "mpv is not installed");
+        assert!(error.hint().unwrap().contains("https://mpv.io"));
+
+        let error = AppError::from_spawn(
+            "mpv",
+            std::io::Error::from(std::io::ErrorKind::PermissionDenied),
+        );
+        assert!(error.to_string().starts_with("Failed to run mpv: "));
+        assert_eq!(error.hint(), None);
+    }
+
+    #[test]
+    fn test_context_keeps_hint() {
+        let result: AppResult<()> = Err(AppError::missing_tool("vlc"));
+        let error = result.context("Video wallpaper sea.mp4").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Video wallpaper sea.mp4: vlc is not installed"
+        );
+        assert!(error.hint().is_some());
+
+        let error = AppError::ProcessDied {
+            process: "mpv".to_string(),
+            code: Some(2),
+        };
+        assert_eq!(error.to_string(), "mpv exited with code 2");
+        let error = AppError::ProcessDied {
+            process: "mpv".to_string(),
+            code: None,
+        };
+        assert_eq!(error.to_string(), "mpv exited after being killed");
+    }
+}
@@ -0,0 +1,153 @@
+//! In-process event log
+//!
+//! Background services record notable successes and failures here so that
+//! frontends can surface them instead of leaving them in the log file only.
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Number of events kept in memory
+const MAX_EVENTS: usize = 200;
+
+/// Event severity
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum EventLevel {
+    /// Informational event
+    Info,
+
+    /// Something went wrong but the service keeps working
+    Warning,
+
+    /// An operation failed
+    Error,
+}
+
+/// Recorded event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Event {
+    /// Monotonic event ID
+    pub id: u64,
+
+    /// Time the event was recorded (RFC 3339)
+    pub time: String,
+
+    /// Event severity
+    pub level: EventLevel,
+
+    /// Component that recorded the event (e.g. "scheduler")
+    pub source: String,
+
+    /// Short, user-facing summary
+    pub message: String,
+
+    /// Underlying error or extra details
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+}
+
+/// Bounded log of recent events
+#[derive(Debug, Default)]
+pub struct EventLog {
+    /// Recent events, oldest first
+    events: Mutex<VecDeque<Event>>,
+}
+
+#[allow(dead_code)]
+impl EventLog {
+    /// Create an empty event log
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the process-wide event log
+    pub fn shared() -> Arc<EventLog> {
+        static SHARED: OnceLock<Arc<EventLog>> = OnceLock::new();
+        SHARED.get_or_init(|| Arc::new(EventLog::new())).clone()
+    }
+
+    /// Record an event
+    pub fn record(
+        &self,
+        level: EventLevel,
+        source: &str,
+        message: impl Into<String>,
+        details: Option<String>,
+    ) -> u64 {
+        let mut events = self.events.lock().unwrap();
+        let id = events.back().map_or(1, |last| last.id + 1);
+        events.push_back(Event {
+            id,
+            time: Local::now().to_rfc3339(),
+            level,
+            source: source.to_string(),
+            message: message.into(),
+            details,
+        });
+
+        while events.len() > MAX_EVENTS {
+            events.pop_front();
+        }
+
+        id
+    }
+
+    /// Record an error with its details
+    pub fn error(&self, source: &str, message: impl Into<String>, details: impl ToString) -> u64 {
+        self.record(
+            EventLevel::Error,
+            source,
+            message,
+            Some(details.to_string()),
+        )
+    }
+
+    /// Record an informational event
+    pub fn info(&self, source: &str, message: impl Into<String>) -> u64 {
+        self.record(EventLevel::Info, source, message, None)
+    }
+
+    /// Get the events recorded after the given ID
+    pub fn since(&self, id: u64) -> Vec<Event> {
+        self.events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|event| event.id > id)
+            .cloned()
+            .collect()
+    }
+
+    /// Get the most recent events, newest last
+    pub fn recent(&self, count: usize) -> Vec<Event> {
+        let events = self.events.lock().unwrap();
+        events
+            .iter()
+            .skip(events.len().saturating_sub(count))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_log_is_bounded_and_ordered() {
+        let log = EventLog::new();
+        for i in 0..MAX_EVENTS + 5 {
+            log.info("test", format!("event {}", i));
+        }
+
+        let recent = log.recent(3);
+        assert_eq!(recent.len(), 3);
+        assert_eq!(recent[2].message, format!("event {}", MAX_EVENTS + 4));
+
+        let last_id = recent[2].id;
+        assert!(log.since(last_id).is_empty());
+        assert_eq!(log.since(last_id - 1).len(), 1);
+        assert_eq!(log.since(0).len(), MAX_EVENTS);
+    }
+}
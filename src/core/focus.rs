@@ -0,0 +1,270 @@
+//! Focus mode: toning down the wallpaper while windows cover the desktop
+//!
+//! Every second the share of the desktop covered by application windows is
+//! checked. Above the configured share a static wallpaper fades to a dimmed
+//! or blurred copy and animated wallpapers are paused; once it drops below
+//! the lower share, the wallpaper fades back. The gap between the two keeps
+//! a window resized around one share from flickering the desktop. Spanned
+//! wallpapers and monitors with images of their own are left as they are.
+use crate::core::config::{FocusModeConfig, PlacementConfig};
+use crate::core::scheduler::CurrentWallpaper;
+use crate::core::{runtime, AppError, AppResult, WallpaperType};
+use crate::experiments::effects::focus;
+use crate::platform::{coverage, WallpaperManager};
+use log::{debug, info, warn};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Time between checks of the windows
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Static wallpaper shown toned down
+struct Toned {
+    /// Image of the wallpaper
+    path: PathBuf,
+
+    /// Toned down copy on screen, None when toning down failed
+    copy: Option<PathBuf>,
+}
+
+/// Tones down the wallpaper while windows cover the desktop
+pub struct FocusMode {
+    /// Focus mode configuration
+    config: Arc<Mutex<FocusModeConfig>>,
+
+    /// How static wallpapers are fitted
+    placement: Arc<Mutex<PlacementConfig>>,
+
+    /// Wallpaper to tone down
+    current_wallpaper: CurrentWallpaper,
+
+    /// Platform-specific wallpaper manager, which shows the toned down copies
+    wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>,
+
+    /// Check thread handle
+    check_thread: Option<thread::JoinHandle<()>>,
+
+    /// Whether the checks are running
+    is_running: Arc<Mutex<bool>>,
+
+    /// Whether windows cover the desktop right now
+    is_focused: Arc<Mutex<bool>>,
+}
+
+#[allow(dead_code)]
+impl FocusMode {
+    /// Create a new focus mode service for the scheduler's wallpaper
+    pub fn new(
+        config: FocusModeConfig,
+        current_wallpaper: CurrentWallpaper,
+        wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>,
+    ) -> Self {
+        Self {
+            config: Arc::new(Mutex::new(config)),
+            placement: Arc::new(Mutex::new(PlacementConfig::default())),
+            current_wallpaper,
+            wallpaper_manager,
+            check_thread: None,
+            is_running: Arc::new(Mutex::new(false)),
+            is_focused: Arc::new(Mutex::new(false)),
+        }
+    }
+
+    /// Update the focus mode configuration
+    pub fn update_config(&self, config: FocusModeConfig) {
+        *self.config.lock().unwrap() = config;
+    }
+
+    /// Set how static wallpapers are fitted
+    pub fn set_placement(&self, placement: PlacementConfig) {
+        *self.placement.lock().unwrap() = placement;
+    }
+
+    /// Check whether the wallpaper is toned down because windows cover the desktop
+    pub fn is_focused(&self) -> bool {
+        *self.is_focused.lock().unwrap()
+    }
+
+    /// Start the check thread
+    pub fn start(&mut self) -> AppResult<()> {
+        if *self.is_running.lock().unwrap() {
+            debug!("Focus mode is already running");
+            return Ok(());
+        }
+
+        *self.is_running.lock().unwrap() = true;
+
+        let config = self.config.clone();
+        let placement = self.placement.clone();
+        let current_wallpaper = self.current_wallpaper.clone();
+        let wallpaper_manager = self.wallpaper_manager.clone();
+        let is_running = self.is_running.clone();
+        let is_focused = self.is_focused.clone();
+
+        self.check_thread = Some(thread::spawn(move || {
+            let mut toned: Option<Toned> = None;
+            let mut paused = false;
+
+            while *is_running.lock().unwrap() {
+                let config = config.lock().unwrap().clone();
+                let placement = placement.lock().unwrap().clone();
+
+                let was_focused = *is_focused.lock().unwrap();
+                let threshold = if was_focused {
+                    config.uncover_below
+                } else {
+                    config.cover_above
+                };
+                let focused = config.enabled
+                    && coverage::desktop_coverage()
+                        .map_or(false, |share| share * 100.0 >= threshold as f32);
+                if focused != was_focused {
+                    info!(
+                        "{}",
+                        if focused {
+                            "Windows cover the desktop, toning down the wallpaper"
+                        } else {
+                            "Desktop visible again"
+                        }
+                    );
+                    *is_focused.lock().unwrap() = focused;
+                }
+
+                // Only a static wallpaper filling every monitor alike is toned down
+                let shown = current_wallpaper
+                    .lock()
+                    .unwrap()
+                    .get(None)
+                    .filter(|wallpaper| wallpaper.get_type() == WallpaperType::Static)
+                    .and_then(|wallpaper| wallpaper.get_path().map(Path::to_path_buf))
+                    .filter(|_| !placement.span && placement.monitor_images.is_empty());
+
+                if focused
+                    && shown.is_some()
+                    && toned.as_ref().map(|toned| &toned.path) != shown.as_ref()
+                {
+                    let path = shown.clone().unwrap();
+                    let copy = match runtime::block_on(focus::fade_in(
+                        &wallpaper_manager,
+                        &path,
+                        &placement,
+                        &config,
+                    )) {
+                        Ok(copy) => Some(copy),
+                        Err(e) => {
+                            warn!("Failed to tone down the wallpaper: {}", e);
+                            None
+                        }
+                    };
+                    toned = Some(Toned { path, copy });
+                } else if !focused {
+                    if let Some(toned) = toned.take() {
+                        restore(
+                            &wallpaper_manager,
+                            &toned,
+                            shown.as_deref(),
+                            &placement,
+                            &config,
+                        );
+                    }
+                }
+
+                // Pausing is repeated so a wallpaper applied in the meantime is paused too
+                let pause = focused && config.pause_animated;
+                if pause || paused {
+                    for (_, wallpaper) in current_wallpaper.lock().unwrap().iter() {
+                        if wallpaper.get_type() != WallpaperType::Static {
+                            let result = runtime::block_on(async {
+                                if pause {
+                                    wallpaper.pause().await
+                                } else {
+                                    wallpaper.resume().await
+                                }
+                            });
+                            if let Err(e) = result {
+                                debug!(
+                                    "Failed to {} wallpaper: {}",
+                                    if pause { "pause" } else { "resume" },
+                                    e
+                                );
+                            }
+                        }
+                    }
+                }
+                paused = pause;
+
+                thread::sleep(CHECK_INTERVAL);
+            }
+
+            if let Some(toned) = toned {
+                let shown = current_wallpaper
+                    .lock()
+                    .unwrap()
+                    .get(None)
+                    .and_then(|wallpaper| wallpaper.get_path().map(Path::to_path_buf));
+                let config = FocusModeConfig {
+                    fade_ms: 0,
+                    ..config.lock().unwrap().clone()
+                };
+                restore(
+                    &wallpaper_manager,
+                    &toned,
+                    shown.as_deref(),
+                    &placement.lock().unwrap().clone(),
+                    &config,
+                );
+            }
+        }));
+
+        info!("Focus mode started");
+        Ok(())
+    }
+
+    /// Stop the check thread, bringing back a toned down wallpaper
+    pub fn stop(&mut self) -> AppResult<()> {
+        if !*self.is_running.lock().unwrap() {
+            debug!("Focus mode is not running");
+            return Ok(());
+        }
+
+        *self.is_running.lock().unwrap() = false;
+
+        if let Some(thread) = self.check_thread.take() {
+            thread.join().map_err(|e| {
+                AppError::Other(format!("Failed to join focus mode thread: {:?}", e))
+            })?;
+        }
+        *self.is_focused.lock().unwrap() = false;
+
+        info!("Focus mode stopped");
+        Ok(())
+    }
+}
+
+/// Fade a toned down wallpaper back, unless another one replaced it meanwhile
+fn restore(
+    wallpaper_manager: &Arc<dyn WallpaperManager + Send + Sync>,
+    toned: &Toned,
+    shown: Option<&Path>,
+    placement: &PlacementConfig,
+    config: &FocusModeConfig,
+) {
+    let Some(copy) = toned
+        .copy
+        .as_ref()
+        .filter(|_| shown == Some(toned.path.as_path()))
+    else {
+        return;
+    };
+    if let Err(e) = runtime::block_on(focus::fade_out(
+        wallpaper_manager,
+        copy,
+        &toned.path,
+        placement,
+        config,
+    )) {
+        warn!("Failed to bring back the wallpaper: {}", e);
+    }
+}
@@ -0,0 +1,418 @@
+//! Configuration file formats
+//!
+//! The configuration, schedule and widget files are JSON by default. Renaming
+//! one to `.toml` switches it to TOML, which is easier to edit by hand; the
+//! comments in a TOML file are kept when the app saves it again.
+use crate::core::{config_history, watcher};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use toml_edit::{DocumentMut, Item, RawString, Table};
+
+/// Key the entries of a file holding a list are kept under in TOML
+const LIST_KEY: &str = "items";
+
+/// Error reading or writing a configuration file
+#[derive(Error, Debug)]
+pub enum FormatError {
+    /// Invalid JSON
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    /// Invalid TOML
+    #[error(transparent)]
+    Toml(#[from] toml_edit::de::Error),
+
+    /// The file can't be read or written
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    /// The value has no TOML form
+    #[error("{0} can't be written as TOML")]
+    Unsupported(String),
+}
+
+/// Format of a configuration file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    /// JSON
+    Json,
+    /// TOML
+    Toml,
+}
+
+impl FileFormat {
+    /// Get the format of a file from its extension
+    pub fn of(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => FileFormat::Toml,
+            _ => FileFormat::Json,
+        }
+    }
+
+    /// Get the file extension
+    pub fn extension(self) -> &'static str {
+        match self {
+            FileFormat::Json => "json",
+            FileFormat::Toml => "toml",
+        }
+    }
+
+    /// Parse a file's content
+    pub fn parse<T: DeserializeOwned>(self, text: &str) -> Result<T, FormatError> {
+        match self {
+            FileFormat::Json => Ok(serde_json::from_str(text)?),
+            FileFormat::Toml => {
+                let value = match toml_edit::de::from_str::<Value>(text)? {
+                    // TOML documents are tables, so lists are kept under a key
+                    Value::Object(mut map)
+                        if map.len() == 1 && map.get(LIST_KEY).map_or(false, Value::is_array) =>
+                    {
+                        map.remove(LIST_KEY).unwrap_or_default()
+                    }
+                    value => value,
+                };
+                Ok(serde_json::from_value(value)?)
+            }
+        }
+    }
+
+    /// Write a value as file content, keeping the comments of the previous content
+    pub fn render<T: Serialize>(
+        self,
+        value: &T,
+        previous: Option<&str>,
+    ) -> Result<String, FormatError> {
+        match self {
+            FileFormat::Json => Ok(serde_json::to_string_pretty(value)?),
+            FileFormat::Toml => {
+                let root = match serde_json::to_value(value)? {
+                    Value::Object(map) => map,
+                    value => Map::from_iter([(LIST_KEY.to_string(), value)]),
+                };
+                let previous = previous.and_then(|text| text.parse::<DocumentMut>().ok());
+
+                let mut out = String::new();
+                write_table(
+                    &mut out,
+                    &[],
+                    &root,
+                    previous.as_ref().map(|document| document.as_table()),
+                )?;
+                if let Some(trailing) = previous
+                    .as_ref()
+                    .and_then(|document| comment(document.trailing()))
+                {
+                    out.push_str(trailing);
+                }
+                Ok(out)
+            }
+        }
+    }
+}
+
+/// Get the file to use for `name` in `dir`, the TOML one when it exists
+pub fn config_file(dir: &Path, name: &str) -> PathBuf {
+    let toml = dir.join(format!("{}.toml", name));
+    if toml.exists() {
+        toml
+    } else {
+        dir.join(format!("{}.json", name))
+    }
+}
+
+/// Read a configuration file in the format of its extension
+pub fn load<T: DeserializeOwned>(path: &Path) -> Result<T, FormatError> {
+    FileFormat::of(path).parse(&fs::read_to_string(path)?)
+}
+
+/// Write a configuration file in the format of its extension
+///
+/// The content is written to a temporary file that then replaces the old one,
+/// so a crash while saving can't leave a truncated file behind.
+pub fn save<T: Serialize>(path: &Path, value: &T) -> Result<(), FormatError> {
+    let previous = fs::read_to_string(path).ok();
+    let content = FileFormat::of(path).render(value, previous.as_deref())?;
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let mut file = tempfile::Builder::new()
+        .prefix(".config-")
+        .tempfile_in(dir)?;
+    file.write_all(content.as_bytes())?;
+    file.as_file().sync_all()?;
+    file.persist(path).map_err(|e| e.error)?;
+    watcher::note_own_write(path, &content);
+    config_history::record(path);
+    Ok(())
+}
+
+/// Copy a configuration file, converting it when the extensions differ
+pub fn copy(from: &Path, to: &Path) -> Result<(), FormatError> {
+    if FileFormat::of(from) == FileFormat::of(to) {
+        fs::copy(from, to)?;
+        return Ok(());
+    }
+    save(to, &load::<Value>(from)?)
+}
+
+/// Write the entries of a table, then its sub-tables
+fn write_table(
+    out: &mut String,
+    path: &[String],
+    map: &Map<String, Value>,
+    previous: Option<&Table>,
+) -> Result<(), FormatError> {
+    for (key, value) in map {
+        if value.is_null() || value.is_object() || is_table_array(value) {
+            continue;
+        }
+
+        let entry = previous.and_then(|table| table.get_key_value(key));
+        if let Some(prefix) = entry
+            .and_then(|(key, _)| key.leaf_decor().prefix())
+            .and_then(comment)
+        {
+            out.push_str(prefix);
+        }
+        let text = inline(value)
+            .ok_or_else(|| FormatError::Unsupported(format!("Empty entry in `{}`", key)))?;
+        out.push_str(&format!("{} = {}", key_text(key), text));
+        let suffix = entry
+            .and_then(|(_, item)| item.as_value())
+            .and_then(|value| value.decor().suffix());
+        if let Some(suffix) = suffix.and_then(comment) {
+            out.push_str(suffix);
+        }
+        out.push('\n');
+    }
+
+    for (key, value) in map {
+        let mut child_path = path.to_vec();
+        child_path.push(key_text(key));
+        let item = previous.and_then(|table| table.get(key));
+
+        match value {
+            Value::Object(child) => {
+                let table = item.and_then(Item::as_table);
+                write_header(out, &format!("[{}]", child_path.join(".")), table);
+                write_table(out, &child_path, child, table)?;
+            }
+            Value::Array(entries) if is_table_array(value) => {
+                let tables = item.and_then(Item::as_array_of_tables);
+                for (index, entry) in entries.iter().filter_map(Value::as_object).enumerate() {
+                    let table = tables.and_then(|tables| tables.get(index));
+                    write_header(out, &format!("[[{}]]", child_path.join(".")), table);
+                    write_table(out, &child_path, entry, table)?;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Write a table header with the comments it had before
+fn write_header(out: &mut String, header: &str, previous: Option<&Table>) {
+    match previous
+        .and_then(|table| table.decor().prefix())
+        .and_then(comment)
+    {
+        Some(prefix) => out.push_str(prefix),
+        None if !out.is_empty() => out.push('\n'),
+        None => {}
+    }
+    out.push_str(header);
+    out.push('\n');
+}
+
+/// Get whitespace from the previous document if it holds a comment
+fn comment(raw: &RawString) -> Option<&str> {
+    raw.as_str().filter(|text| text.contains('#'))
+}
+
+/// Check whether an array is written as `[[name]]` tables
+fn is_table_array(value: &Value) -> bool {
+    matches!(value, Value::Array(entries) if !entries.is_empty() && entries.iter().all(Value::is_object))
+}
+
+/// Write a key, quoting it unless it's a bare key
+fn key_text(key: &str) -> String {
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if bare {
+        key.to_string()
+    } else {
+        quote(key)
+    }
+}
+
+/// Write a value on one line
+///
+/// Returns `None` for null, which TOML has no form for. Keys holding null are
+/// left out, but an array can't leave out an entry without moving the others.
+fn inline(value: &Value) -> Option<String> {
+    let text = match value {
+        Value::Null => return None,
+        Value::Bool(value) => value.to_string(),
+        Value::Number(number) => match number.as_f64() {
+            Some(float) if number.is_f64() => float_text(float),
+            _ => number.to_string(),
+        },
+        Value::String(text) => quote(text),
+        Value::Array(entries) => {
+            format!(
+                "[{}]",
+                entries
+                    .iter()
+                    .map(inline)
+                    .collect::<Option<Vec<_>>>()?
+                    .join(", ")
+            )
+        }
+        Value::Object(map) => {
+            let entries = map
+                .iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| Some(format!("{} = {}", key_text(key), inline(value)?)))
+                .collect::<Option<Vec<_>>>()?;
+            if entries.is_empty() {
+                "{}".to_string()
+            } else {
+                format!("{{ {} }}", entries.join(", "))
+            }
+        }
+    };
+    Some(text)
+}
+
+/// Write a float, which TOML requires to have a fraction or exponent
+fn float_text(value: f64) -> String {
+    if value.is_nan() {
+        "nan".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "inf" } else { "-inf" }.to_string()
+    } else {
+        // Debug formatting always keeps the fraction ("1.0")
+        format!("{:?}", value)
+    }
+}
+
+/// Write a basic string
+fn quote(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04X}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Entry {
+        name: String,
+        weight: f32,
+        tags: Vec<String>,
+        note: Option<String>,
+        nested: BTreeMap<String, u32>,
+    }
+
+    fn entry(name: &str) -> Entry {
+        Entry {
+            name: name.to_string(),
+            weight: 1.0,
+            tags: vec!["a \"quoted\" tag".to_string()],
+            note: None,
+            nested: BTreeMap::from([("one two".to_string(), 1)]),
+        }
+    }
+
+    #[test]
+    fn test_format_of() {
+        assert_eq!(FileFormat::of(Path::new("config.toml")), FileFormat::Toml);
+        assert_eq!(FileFormat::of(Path::new("config.TOML")), FileFormat::Toml);
+        assert_eq!(FileFormat::of(Path::new("config.json")), FileFormat::Json);
+        assert_eq!(FileFormat::of(Path::new("config")), FileFormat::Json);
+    }
+
+    #[test]
+    fn test_toml_round_trip() {
+        let items = vec![entry("first"), entry("second")];
+        let text = FileFormat::Toml.render(&items, None).unwrap();
+        assert!(text.contains("[[items]]"));
+        assert_eq!(FileFormat::Toml.parse::<Vec<Entry>>(&text).unwrap(), items);
+
+        let map = BTreeMap::from([("clock".to_string(), entry("clock"))]);
+        let text = FileFormat::Toml.render(&map, None).unwrap();
+        assert!(text.contains("[clock.nested]"));
+        assert_eq!(
+            FileFormat::Toml
+                .parse::<BTreeMap<String, Entry>>(&text)
+                .unwrap(),
+            map
+        );
+    }
+
+    #[test]
+    fn test_toml_keeps_comments() {
+        let previous = "# Widgets on the desktop\n\n# The big clock\n[clock]\nname = \"clock\" # shown on top\n# Heavier sinks\nweight = 2.0\ntags = []\n\n[clock.nested]\n\n# end\n";
+        let map = BTreeMap::from([("clock".to_string(), entry("clock"))]);
+        let text = FileFormat::Toml.render(&map, Some(previous)).unwrap();
+        assert!(text.contains("# The big clock\n[clock]"));
+        assert!(text.contains("name = \"clock\" # shown on top\n"));
+        assert!(text.contains("# Heavier sinks\nweight = 1.0\n"));
+        assert!(text.ends_with("# end\n"));
+        assert_eq!(
+            FileFormat::Toml
+                .parse::<BTreeMap<String, Entry>>(&text)
+                .unwrap(),
+            map
+        );
+    }
+
+    #[test]
+    fn test_toml_rejects_null_in_array() {
+        let map = BTreeMap::from([("slots".to_string(), vec![Some(1), None, Some(3)])]);
+        assert!(matches!(
+            FileFormat::Toml.render(&map, None),
+            Err(FormatError::Unsupported(_))
+        ));
+        assert!(FileFormat::Json.render(&map, None).is_ok());
+    }
+
+    #[test]
+    fn test_copy_converts() {
+        let dir = tempfile::tempdir().unwrap();
+        let json = dir.path().join("schedule.json");
+        let toml = dir.path().join("schedule.toml");
+        let items = vec![entry("only")];
+        save(&json, &items).unwrap();
+        assert_eq!(config_file(dir.path(), "schedule"), json);
+
+        copy(&json, &toml).unwrap();
+        assert_eq!(config_file(dir.path(), "schedule"), toml);
+        assert_eq!(load::<Vec<Entry>>(&toml).unwrap(), items);
+    }
+}
@@ -0,0 +1,201 @@
+//! Undo and redo for wallpaper changes
+//!
+//! Every wallpaper change, whether made by the user, the scheduler or an
+//! online source, is recorded here, so an unwanted change can be undone.
+use crate::core::{AppResult, WallpaperInfo};
+use log::debug;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread::{self, ThreadId};
+
+/// Number of earlier wallpapers kept
+const MAX_UNDO: usize = 20;
+
+/// Recent wallpapers, before and after the current one
+#[derive(Debug, Default)]
+struct Stacks {
+    /// Earlier wallpapers, oldest first
+    undo: VecDeque<WallpaperInfo>,
+
+    /// Current wallpaper
+    current: Option<WallpaperInfo>,
+
+    /// Undone wallpapers, most recently undone last
+    redo: Vec<WallpaperInfo>,
+}
+
+/// History of wallpaper changes
+#[derive(Debug, Default)]
+pub struct WallpaperHistory {
+    /// Recent wallpapers
+    stacks: Mutex<Stacks>,
+
+    /// Thread applying an undone or redone wallpaper, whose change isn't recorded
+    replaying: Mutex<Option<ThreadId>>,
+}
+
+#[allow(dead_code)]
+impl WallpaperHistory {
+    /// Create an empty history
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the process-wide history
+    pub fn shared() -> Arc<WallpaperHistory> {
+        static SHARED: OnceLock<Arc<WallpaperHistory>> = OnceLock::new();
+        SHARED
+            .get_or_init(|| Arc::new(WallpaperHistory::new()))
+            .clone()
+    }
+
+    /// Record a wallpaper change
+    ///
+    /// A new change can't be redone past, so it clears the redo stack.
+    pub fn record(&self, wallpaper: &WallpaperInfo) {
+        if *self.replaying.lock().unwrap() == Some(thread::current().id()) {
+            return;
+        }
+
+        let mut stacks = self.stacks.lock().unwrap();
+        if let Some(previous) = stacks.current.replace(wallpaper.clone()) {
+            stacks.undo.push_back(previous);
+            if stacks.undo.len() > MAX_UNDO {
+                stacks.undo.pop_front();
+            }
+        }
+        stacks.redo.clear();
+    }
+
+    /// Check whether there is a wallpaper to go back to
+    pub fn can_undo(&self) -> bool {
+        !self.stacks.lock().unwrap().undo.is_empty()
+    }
+
+    /// Check whether there is an undone wallpaper to go forward to
+    pub fn can_redo(&self) -> bool {
+        !self.stacks.lock().unwrap().redo.is_empty()
+    }
+
+    /// Go back to the previous wallpaper with `apply`
+    ///
+    /// Returns None when there is nothing to undo. The history only moves
+    /// when `apply` succeeds.
+    pub fn undo(
+        &self,
+        apply: impl FnOnce(&WallpaperInfo) -> AppResult<()>,
+    ) -> AppResult<Option<WallpaperInfo>> {
+        let Some(target) = self.stacks.lock().unwrap().undo.back().cloned() else {
+            return Ok(None);
+        };
+        self.replay(&target, apply)?;
+
+        let mut stacks = self.stacks.lock().unwrap();
+        stacks.undo.pop_back();
+        if let Some(current) = stacks.current.replace(target.clone()) {
+            stacks.redo.push(current);
+        }
+        debug!("Undid wallpaper change back to {}", target.name);
+        Ok(Some(target))
+    }
+
+    /// Go forward to the last undone wallpaper with `apply`
+    ///
+    /// Returns None when there is nothing to redo.
+    pub fn redo(
+        &self,
+        apply: impl FnOnce(&WallpaperInfo) -> AppResult<()>,
+    ) -> AppResult<Option<WallpaperInfo>> {
+        let Some(target) = self.stacks.lock().unwrap().redo.last().cloned() else {
+            return Ok(None);
+        };
+        self.replay(&target, apply)?;
+
+        let mut stacks = self.stacks.lock().unwrap();
+        stacks.redo.pop();
+        if let Some(current) = stacks.current.replace(target.clone()) {
+            stacks.undo.push_back(current);
+        }
+        debug!("Redid wallpaper change to {}", target.name);
+        Ok(Some(target))
+    }
+
+    /// Apply a wallpaper from the history without recording it again
+    fn replay(
+        &self,
+        wallpaper: &WallpaperInfo,
+        apply: impl FnOnce(&WallpaperInfo) -> AppResult<()>,
+    ) -> AppResult<()> {
+        *self.replaying.lock().unwrap() = Some(thread::current().id());
+        let result = apply(wallpaper);
+        *self.replaying.lock().unwrap() = None;
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{AppError, WallpaperType};
+
+    fn wallpaper(name: &str) -> WallpaperInfo {
+        WallpaperInfo::new(
+            WallpaperType::Static,
+            Some(format!("/walls/{}.png", name).into()),
+            None,
+        )
+    }
+
+    /// Apply like the scheduler, which records every change
+    fn apply(history: &WallpaperHistory) -> impl Fn(&WallpaperInfo) -> AppResult<()> + '_ {
+        move |wallpaper| {
+            history.record(wallpaper);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_undo_and_redo() {
+        let history = WallpaperHistory::new();
+        assert!(history.undo(apply(&history)).unwrap().is_none());
+
+        for name in ["a", "b", "c"] {
+            history.record(&wallpaper(name));
+        }
+        assert_eq!(history.undo(apply(&history)).unwrap().unwrap().name, "b");
+        assert_eq!(history.undo(apply(&history)).unwrap().unwrap().name, "a");
+        assert!(!history.can_undo());
+        assert_eq!(history.redo(apply(&history)).unwrap().unwrap().name, "b");
+        assert!(history.can_redo());
+
+        // A new change drops what was undone
+        history.record(&wallpaper("d"));
+        assert!(!history.can_redo());
+        assert_eq!(history.undo(apply(&history)).unwrap().unwrap().name, "b");
+    }
+
+    #[test]
+    fn test_failed_undo_keeps_history() {
+        let history = WallpaperHistory::new();
+        history.record(&wallpaper("a"));
+        history.record(&wallpaper("b"));
+        assert!(history
+            .undo(|_| Err(AppError::Other("broken".to_string())))
+            .is_err());
+        assert!(history.can_undo());
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let history = WallpaperHistory::new();
+        for index in 0..MAX_UNDO + 5 {
+            history.record(&wallpaper(&index.to_string()));
+        }
+        let mut undone = 0;
+        while history.undo(apply(&history)).unwrap().is_some() {
+            undone += 1;
+        }
+        assert_eq!(undone, MAX_UNDO);
+    }
+}
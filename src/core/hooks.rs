@@ -0,0 +1,253 @@
+//! User commands run after every wallpaper change
+//!
+//! Each command runs through the shell with the new wallpaper in
+//! `AETHER_WALLPAPER_PATH`, `AETHER_WALLPAPER_TYPE` and
+//! `AETHER_WALLPAPER_MONITOR`, so tools like pywal can follow the wallpaper
+//! without a plugin. When palette export is on, the palette of a static
+//! wallpaper is written and the system recolored first, so the commands can
+//! read the palette and reload what uses it. The desktop's accent color can
+//! follow static wallpapers too.
+use crate::core::config::{AccentSyncConfig, PaletteConfig};
+use crate::core::{
+    palette, theming, AppResult, EventLevel, EventLog, PaletteStore, WallpaperInfo, WallpaperType,
+};
+use crate::platform::{accent, display, MonitorInfo};
+use log::{debug, info, warn};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+
+/// Runs the configured change hooks
+#[derive(Debug, Default)]
+pub struct ChangeHooks {
+    /// Shell commands to run
+    commands: Mutex<Vec<String>>,
+
+    /// Palette export settings
+    palette: Mutex<PaletteConfig>,
+
+    /// Accent color settings
+    accent: Mutex<AccentSyncConfig>,
+}
+
+impl ChangeHooks {
+    /// Get the process-wide change hooks
+    pub fn shared() -> Arc<ChangeHooks> {
+        static SHARED: OnceLock<Arc<ChangeHooks>> = OnceLock::new();
+        SHARED
+            .get_or_init(|| Arc::new(ChangeHooks::default()))
+            .clone()
+    }
+
+    /// Replace the commands
+    pub fn set_commands(&self, commands: Vec<String>) {
+        *self.commands.lock().unwrap() = commands
+            .into_iter()
+            .filter(|command| !command.trim().is_empty())
+            .collect();
+    }
+
+    /// Replace the palette export settings
+    pub fn set_palette(&self, config: PaletteConfig) {
+        *self.palette.lock().unwrap() = config;
+    }
+
+    /// Replace the accent color settings, putting back the desktop's own accent when it stops following
+    pub fn set_accent(&self, config: AccentSyncConfig) {
+        if !config.enabled && accent::is_changed() {
+            thread::spawn(|| {
+                if let Err(e) = accent::restore() {
+                    warn!("Failed to restore the desktop accent: {}", e);
+                    EventLog::shared().error("accent", "Failed to restore the desktop accent", e);
+                }
+            });
+        }
+        *self.accent.lock().unwrap() = config;
+    }
+
+    /// Export the palette and run the commands for a new wallpaper in the background
+    pub fn run(&self, wallpaper: &WallpaperInfo) {
+        let commands = self.commands.lock().unwrap().clone();
+        let palette = self.palette.lock().unwrap().clone();
+        let follow_accent = self.accent.lock().unwrap().enabled;
+        let image = match (&wallpaper.r#type, &wallpaper.path) {
+            (WallpaperType::Static, Some(path)) if palette.enabled || follow_accent => {
+                Some(path.clone())
+            }
+            _ => None,
+        };
+        if commands.is_empty() && image.is_none() {
+            return;
+        }
+
+        let wallpaper = wallpaper.clone();
+        thread::spawn(move || {
+            if let Some(image) = image.as_ref().filter(|_| palette.enabled) {
+                if let Err(e) =
+                    theming::plan(image, &palette).and_then(|plan| theming::apply(&plan))
+                {
+                    warn!("Failed to export color palette: {}", e);
+                    EventLog::shared().error("palette", "Failed to export color palette", e);
+                }
+            }
+            if let Some(image) = image.as_ref().filter(|_| follow_accent) {
+                if let Some(colors) = PaletteStore::shared().colors(image) {
+                    if let Err(e) = accent::apply(palette::accent_color(&colors)) {
+                        warn!("Failed to set the desktop accent: {}", e);
+                        EventLog::shared().error("accent", "Failed to set the desktop accent", e);
+                    }
+                }
+            }
+
+            let monitors = display::monitors().unwrap_or_default();
+            let env = hook_env(&wallpaper, &monitors);
+
+            for command in &commands {
+                match run_command(command, &env) {
+                    Ok(status) if status.success() => debug!("Change hook finished: {}", command),
+                    Ok(status) => {
+                        warn!("Change hook {} exited with {}", command, status);
+                        EventLog::shared().record(
+                            EventLevel::Warning,
+                            "hooks",
+                            format!("Change hook failed: {}", command),
+                            Some(status.to_string()),
+                        );
+                    }
+                    Err(e) => {
+                        warn!("Failed to run change hook {}: {}", command, e);
+                        EventLog::shared().error(
+                            "hooks",
+                            format!("Failed to run change hook: {}", command),
+                            e,
+                        );
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// Environment passed to the hooks
+///
+/// The monitor is the one the wallpaper was applied to, or every connected
+/// monitor for wallpapers covering the whole desktop.
+fn hook_env(wallpaper: &WallpaperInfo, monitors: &[MonitorInfo]) -> Vec<(&'static str, String)> {
+    let target = wallpaper
+        .path
+        .as_ref()
+        .map(|p| p.to_string_lossy().to_string())
+        .or_else(|| wallpaper.url.clone())
+        .unwrap_or_default();
+    let monitor = match &wallpaper.monitor {
+        Some(id) => monitors
+            .iter()
+            .find(|monitor| &monitor.id == id)
+            .map_or_else(|| id.clone(), |monitor| monitor.name.clone()),
+        None => monitors
+            .iter()
+            .map(|monitor| monitor.name.as_str())
+            .collect::<Vec<_>>()
+            .join(","),
+    };
+    vec![
+        ("AETHER_WALLPAPER_PATH", target),
+        (
+            "AETHER_WALLPAPER_TYPE",
+            wallpaper.r#type.as_str().to_lowercase(),
+        ),
+        ("AETHER_WALLPAPER_MONITOR", monitor),
+    ]
+}
+
+/// Run one command through the shell and wait for it
+fn run_command(command: &str, env: &[(&str, String)]) -> AppResult<ExitStatus> {
+    info!("Running change hook: {}", command);
+
+    #[cfg(windows)]
+    let mut process = {
+        let mut process = Command::new("cmd");
+        process.args(["/C", command]);
+        process
+    };
+
+    #[cfg(not(windows))]
+    let mut process = {
+        let mut process = Command::new("sh");
+        process.args(["-c", command]);
+        process
+    };
+
+    process
+        .envs(env.iter().map(|(name, value)| (*name, value)))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    Ok(process.status()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::WallpaperType;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_hook_env() {
+        let monitors = vec![
+            MonitorInfo {
+                id: "DEL U2720Q 1234".to_string(),
+                ..MonitorInfo::new("DP-1", 3840, 2160)
+            },
+            MonitorInfo::new("HDMI-A-1", 1920, 1080),
+        ];
+        let wallpaper = WallpaperInfo::new(
+            WallpaperType::Video,
+            Some(PathBuf::from("/tmp/waves.mp4")),
+            None,
+        );
+        let env = hook_env(&wallpaper, &monitors);
+        assert_eq!(
+            env[0],
+            ("AETHER_WALLPAPER_PATH", "/tmp/waves.mp4".to_string())
+        );
+        assert_eq!(env[1], ("AETHER_WALLPAPER_TYPE", "video".to_string()));
+        assert_eq!(
+            env[2],
+            ("AETHER_WALLPAPER_MONITOR", "DP-1,HDMI-A-1".to_string())
+        );
+
+        // A wallpaper on one monitor names only that one
+        let on_monitor = wallpaper
+            .clone()
+            .on_monitor(Some("DEL U2720Q 1234".to_string()));
+        assert_eq!(hook_env(&on_monitor, &monitors)[2].1, "DP-1");
+        let disconnected = wallpaper.on_monitor(Some("eDP-1".to_string()));
+        assert_eq!(hook_env(&disconnected, &monitors)[2].1, "eDP-1");
+
+        let web = WallpaperInfo::new(
+            WallpaperType::Web,
+            None,
+            Some("https://example.com".to_string()),
+        );
+        assert_eq!(hook_env(&web, &[])[0].1, "https://example.com");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_command() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("type");
+        let env = vec![("AETHER_WALLPAPER_TYPE", "static".to_string())];
+
+        let status = run_command(
+            &format!("echo \"$AETHER_WALLPAPER_TYPE\" > '{}'", output.display()),
+            &env,
+        )
+        .unwrap();
+        assert!(status.success());
+        assert_eq!(std::fs::read_to_string(&output).unwrap().trim(), "static");
+
+        assert!(!run_command("exit 3", &env).unwrap().success());
+    }
+}
@@ -0,0 +1,915 @@
+//! Shared HTTP client for online providers
+//!
+//! Requests go through `curl` and are cached on disk using ETag/Last-Modified
+//! validators. Each provider has its own rate limit, and connection failures
+//! put the client into an offline state so callers fall back to cached data
+//! instead of retrying on every update. Services on this machine are left
+//! out of the offline state, as one not running says nothing about the network.
+use crate::core::{AppError, AppResult, Config};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Initial delay before retrying after the network goes down
+const OFFLINE_INITIAL_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Maximum delay between connectivity retries
+const OFFLINE_MAX_BACKOFF: Duration = Duration::from_secs(600);
+
+/// curl exit codes that indicate the network is unreachable
+const CURL_OFFLINE_CODES: &[i32] = &[5, 6, 7, 28, 35, 56];
+
+/// Request rate limit
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimit {
+    /// Maximum number of requests per window
+    pub max_requests: u32,
+
+    /// Window length
+    pub window: Duration,
+}
+
+#[allow(dead_code)]
+impl RateLimit {
+    /// Limit to a number of requests per minute
+    pub fn per_minute(max_requests: u32) -> Self {
+        Self {
+            max_requests,
+            window: Duration::from_secs(60),
+        }
+    }
+
+    /// Limit to a number of requests per hour
+    pub fn per_hour(max_requests: u32) -> Self {
+        Self {
+            max_requests,
+            window: Duration::from_secs(3600),
+        }
+    }
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self::per_minute(30)
+    }
+}
+
+/// HTTP response
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    /// Status code (200 for responses served from cache)
+    pub status: u16,
+
+    /// Response body
+    pub body: Vec<u8>,
+
+    /// Whether the body came from the local cache
+    pub from_cache: bool,
+}
+
+#[allow(dead_code)]
+impl HttpResponse {
+    /// Get the body as text
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).to_string()
+    }
+
+    /// Parse the body as JSON
+    pub fn json(&self) -> AppResult<serde_json::Value> {
+        Ok(serde_json::from_slice(&self.body)?)
+    }
+}
+
+/// Cached response metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    /// Request URL
+    url: String,
+
+    /// ETag validator
+    etag: Option<String>,
+
+    /// Last-Modified validator
+    last_modified: Option<String>,
+
+    /// When the response was stored
+    fetched_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Per-provider rate limiter state
+#[derive(Debug, Default)]
+struct RateLimiter {
+    /// Configured limit
+    limit: RateLimit,
+
+    /// Recent request times
+    requests: VecDeque<Instant>,
+}
+
+impl RateLimiter {
+    /// Try to take a request slot
+    fn try_acquire(&mut self, now: Instant) -> bool {
+        while let Some(oldest) = self.requests.front() {
+            if now.duration_since(*oldest) >= self.limit.window {
+                self.requests.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        if self.requests.len() >= self.limit.max_requests as usize {
+            return false;
+        }
+
+        self.requests.push_back(now);
+        true
+    }
+}
+
+/// Network connectivity state
+#[derive(Debug)]
+struct ConnectivityState {
+    /// Don't attempt requests before this time
+    offline_until: Option<Instant>,
+
+    /// Current retry backoff
+    backoff: Duration,
+}
+
+/// Shared HTTP client with caching, rate limiting and offline detection
+pub struct HttpClient {
+    /// Response cache directory
+    cache_dir: PathBuf,
+
+    /// Request timeout
+    timeout: Duration,
+
+    /// Per-provider rate limiters
+    rate_limiters: Mutex<HashMap<String, RateLimiter>>,
+
+    /// Connectivity state
+    connectivity: Mutex<ConnectivityState>,
+}
+
+#[allow(dead_code)]
+impl HttpClient {
+    /// Create a new HTTP client caching responses in the given directory
+    pub fn new(cache_dir: PathBuf) -> Self {
+        Self {
+            cache_dir,
+            timeout: Duration::from_secs(20),
+            rate_limiters: Mutex::new(HashMap::new()),
+            connectivity: Mutex::new(ConnectivityState {
+                offline_until: None,
+                backoff: OFFLINE_INITIAL_BACKOFF,
+            }),
+        }
+    }
+
+    /// Get the process-wide client
+    ///
+    /// Rate limits and the offline state only work if every provider shares
+    /// the same client.
+    pub fn shared() -> Arc<HttpClient> {
+        static SHARED: OnceLock<Arc<HttpClient>> = OnceLock::new();
+        SHARED
+            .get_or_init(|| {
+                let mut cache_dir = Config::get_config_dir().unwrap_or_else(|_| {
+                    let mut dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+                    dir.push("config");
+                    dir
+                });
+                cache_dir.push("http_cache");
+                Arc::new(HttpClient::new(cache_dir))
+            })
+            .clone()
+    }
+
+    /// Set the rate limit for a provider
+    pub fn set_rate_limit(&self, provider: &str, limit: RateLimit) {
+        let mut limiters = self.rate_limiters.lock().unwrap();
+        let limiter = limiters.entry(provider.to_string()).or_default();
+        limiter.limit = limit;
+    }
+
+    /// Check whether the network is currently considered offline
+    pub fn is_offline(&self) -> bool {
+        let connectivity = self.connectivity.lock().unwrap();
+        connectivity
+            .offline_until
+            .map_or(false, |until| Instant::now() < until)
+    }
+
+    /// Perform a cached GET request
+    ///
+    /// When offline or rate limited, a cached response is returned if one
+    /// exists, even if it is stale.
+    pub fn get(&self, provider: &str, url: &str) -> AppResult<HttpResponse> {
+        self.get_with_headers(provider, url, &[])
+    }
+
+    /// Perform a cached GET request with extra headers, such as an API key
+    ///
+    /// Responses are cached per URL and header set, but the headers
+    /// themselves are never written to the cache.
+    pub fn get_with_headers(
+        &self,
+        provider: &str,
+        url: &str,
+        headers: &[String],
+    ) -> AppResult<HttpResponse> {
+        let key = cache_key(url, headers);
+        let entry = self.load_cache_entry(&key, url);
+
+        if !is_local(url) && self.is_offline() {
+            debug!("Offline, serving {} from cache", redact_url(url));
+            return self
+                .cached_response(&key, &entry)
+                .ok_or_else(|| AppError::NetworkError("Network is offline".to_string()));
+        }
+
+        if !self.acquire(provider) {
+            debug!(
+                "Rate limit reached for {}, serving {} from cache",
+                provider,
+                redact_url(url)
+            );
+            return self.cached_response(&key, &entry).ok_or_else(|| {
+                AppError::NetworkError(format!("Rate limit reached for {}", provider))
+            });
+        }
+
+        std::fs::create_dir_all(&self.cache_dir)?;
+        let body_path = self.body_path(&key);
+
+        let mut header_lines = headers.to_vec();
+        if let Some(entry) = &entry {
+            if body_path.exists() {
+                if let Some(etag) = &entry.etag {
+                    header_lines.push(format!("If-None-Match: {}", etag));
+                }
+                if let Some(last_modified) = &entry.last_modified {
+                    header_lines.push(format!("If-Modified-Since: {}", last_modified));
+                }
+            }
+        }
+
+        // Each fetch gets its own files, so concurrent fetches of the same
+        // URL don't read each other's half-written responses
+        let headers_file = private_file(
+            &self.cache_dir,
+            ".headers",
+            header_lines.join("\n").as_bytes(),
+        )?;
+        let url_file = private_file(&self.cache_dir, ".url", curl_config_url(url).as_bytes())?;
+        let header_dump = private_file(&self.cache_dir, ".response-headers", &[])?;
+        let response_file = private_file(&self.cache_dir, ".response", &[])?;
+
+        let mut command = Command::new("curl");
+        command
+            .args(["-sS", "-L", "--compressed"])
+            .arg("--max-time")
+            .arg(self.timeout.as_secs().to_string())
+            .arg("-A")
+            .arg(concat!("aether-desk/", env!("CARGO_PKG_VERSION")))
+            .arg("-H")
+            .arg(format!("@{}", headers_file.path().display()))
+            .arg("-K")
+            .arg(url_file.path())
+            .arg("-D")
+            .arg(header_dump.path())
+            .arg("-o")
+            .arg(response_file.path())
+            .args(["-w", "%{http_code}"]);
+
+        let status = self.run_curl(command, url)?;
+        let response_headers = std::fs::read_to_string(header_dump.path()).unwrap_or_default();
+
+        if status == 304 {
+            debug!("{} not modified, serving from cache", redact_url(url));
+            return self.cached_response(&key, &entry).ok_or_else(|| {
+                AppError::NetworkError(format!("Cache entry missing for {}", redact_url(url)))
+            });
+        }
+
+        let body = std::fs::read(response_file.path()).unwrap_or_default();
+
+        if !(200..300).contains(&status) {
+            return Ok(HttpResponse {
+                status,
+                body,
+                from_cache: false,
+            });
+        }
+
+        // Only cache responses that can be revalidated
+        let (etag, last_modified) = parse_validators(&response_headers);
+        if etag.is_some() || last_modified.is_some() {
+            response_file
+                .persist(&body_path)
+                .map_err(|e| AppError::IoError(e.error))?;
+            self.save_cache_entry(
+                &key,
+                &CacheEntry {
+                    url: redact_url(url),
+                    etag,
+                    last_modified,
+                    fetched_at: chrono::Utc::now(),
+                },
+            )?;
+        } else {
+            let _ = std::fs::remove_file(&body_path);
+            let _ = std::fs::remove_file(self.meta_path(&key));
+        }
+
+        Ok(HttpResponse {
+            status,
+            body,
+            from_cache: false,
+        })
+    }
+
+    /// Download a URL to a file without caching
+    ///
+    /// The URL is passed to curl through a file, like in `request`.
+    pub fn download(&self, provider: &str, url: &str, dest: &Path) -> AppResult<()> {
+        if !is_local(url) && self.is_offline() {
+            return Err(AppError::NetworkError("Network is offline".to_string()));
+        }
+
+        if !self.acquire(provider) {
+            return Err(AppError::NetworkError(format!(
+                "Rate limit reached for {}",
+                provider
+            )));
+        }
+
+        let dest_dir = match dest.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        std::fs::create_dir_all(dest_dir)?;
+        std::fs::create_dir_all(&self.cache_dir)?;
+
+        let url_file = private_file(&self.cache_dir, ".url", curl_config_url(url).as_bytes())?;
+        // Unique per download and removed on failure, then renamed into place
+        let part_file = tempfile::Builder::new()
+            .prefix(".download-")
+            .suffix(".part")
+            .tempfile_in(dest_dir)?;
+
+        let mut command = Command::new("curl");
+        command
+            .args(["-sS", "-L"])
+            .arg("--max-time")
+            .arg((self.timeout.as_secs() * 6).to_string())
+            .arg("-A")
+            .arg(concat!("aether-desk/", env!("CARGO_PKG_VERSION")))
+            .arg("-K")
+            .arg(url_file.path())
+            .arg("-o")
+            .arg(part_file.path())
+            .args(["-w", "%{http_code}"]);
+
+        let status = self.run_curl(command, url)?;
+        if !(200..300).contains(&status) {
+            return Err(AppError::NetworkError(format!(
+                "Download of {} failed with status {}",
+                redact_url(url),
+                status
+            )));
+        }
+
+        part_file
+            .persist(dest)
+            .map_err(|e| AppError::IoError(e.error))?;
+        info!("Downloaded {} to {}", redact_url(url), dest.display());
+        Ok(())
+    }
+
+    /// POST a JSON body without caching, waiting up to `timeout` for the answer
+    ///
+    /// Headers are passed to curl through a file, keeping API keys out of the
+    /// process list.
+    pub fn post_json(
+        &self,
+        provider: &str,
+        url: &str,
+        body: &serde_json::Value,
+        headers: &[String],
+        timeout: Duration,
+    ) -> AppResult<HttpResponse> {
+        self.request(provider, "POST", url, Some(body), headers, timeout)
+    }
+
+    /// Send a request without caching, waiting up to `timeout` for the answer
+    ///
+    /// The URL and headers are passed to curl through files, so tokens in
+    /// either stay out of the process list.
+    pub fn request(
+        &self,
+        provider: &str,
+        method: &str,
+        url: &str,
+        body: Option<&serde_json::Value>,
+        headers: &[String],
+        timeout: Duration,
+    ) -> AppResult<HttpResponse> {
+        if !is_local(url) && self.is_offline() {
+            return Err(AppError::NetworkError("Network is offline".to_string()));
+        }
+
+        if !self.acquire(provider) {
+            return Err(AppError::NetworkError(format!(
+                "Rate limit reached for {}",
+                provider
+            )));
+        }
+
+        std::fs::create_dir_all(&self.cache_dir)?;
+        let mut header_lines = Vec::new();
+        let body_file = match body {
+            Some(body) => {
+                header_lines.push("Content-Type: application/json".to_string());
+                Some(private_file(
+                    &self.cache_dir,
+                    ".body",
+                    &serde_json::to_vec(body)?,
+                )?)
+            }
+            None => None,
+        };
+        header_lines.extend(headers.iter().cloned());
+        let headers_file = private_file(
+            &self.cache_dir,
+            ".headers",
+            header_lines.join("\n").as_bytes(),
+        )?;
+        let url_file = private_file(&self.cache_dir, ".url", curl_config_url(url).as_bytes())?;
+        let response_file = private_file(&self.cache_dir, ".response", &[])?;
+
+        let mut command = Command::new("curl");
+        command
+            .args(["-sS", "-L", "-X", method])
+            .arg("--max-time")
+            .arg(timeout.as_secs().to_string())
+            .arg("-A")
+            .arg(concat!("aether-desk/", env!("CARGO_PKG_VERSION")))
+            .arg("-H")
+            .arg(format!("@{}", headers_file.path().display()))
+            .arg("-K")
+            .arg(url_file.path())
+            .arg("-o")
+            .arg(response_file.path())
+            .args(["-w", "%{http_code}"]);
+        if let Some(body_file) = &body_file {
+            command
+                .arg("--data-binary")
+                .arg(format!("@{}", body_file.path().display()));
+        }
+
+        let status = self.run_curl(command, url)?;
+        let body = std::fs::read(response_file.path()).unwrap_or_default();
+
+        Ok(HttpResponse {
+            status,
+            body,
+            from_cache: false,
+        })
+    }
+
+    /// Take a rate limit slot for a provider
+    fn acquire(&self, provider: &str) -> bool {
+        let mut limiters = self.rate_limiters.lock().unwrap();
+        limiters
+            .entry(provider.to_string())
+            .or_default()
+            .try_acquire(Instant::now())
+    }
+
+    /// Run curl for a URL and return the HTTP status code, tracking connectivity
+    fn run_curl(&self, mut command: Command, url: &str) -> AppResult<u16> {
+        let output = command
+            .output()
+            .map_err(|e| AppError::NetworkError(format!("Failed to execute curl: {}", e)))?;
+
+        let code = output.status.code().unwrap_or(-1);
+        if !is_local(url) {
+            if CURL_OFFLINE_CODES.contains(&code) {
+                self.mark_offline();
+                return Err(AppError::NetworkError(format!(
+                    "Network unreachable: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                )));
+            }
+            self.mark_online();
+        }
+
+        if !output.status.success() {
+            return Err(AppError::NetworkError(format!(
+                "curl failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .parse()
+            .map_err(|_| AppError::NetworkError("Invalid HTTP status from curl".to_string()))
+    }
+
+    /// Enter the offline state, backing off exponentially
+    fn mark_offline(&self) {
+        let mut connectivity = self.connectivity.lock().unwrap();
+        if connectivity.offline_until.is_none() {
+            warn!("Network appears to be offline, using cached data");
+        } else {
+            connectivity.backoff = (connectivity.backoff * 2).min(OFFLINE_MAX_BACKOFF);
+        }
+        connectivity.offline_until = Some(Instant::now() + connectivity.backoff);
+    }
+
+    /// Leave the offline state
+    fn mark_online(&self) {
+        let mut connectivity = self.connectivity.lock().unwrap();
+        if connectivity.offline_until.take().is_some() {
+            info!("Network connection restored");
+        }
+        connectivity.backoff = OFFLINE_INITIAL_BACKOFF;
+    }
+
+    /// Get the cache body path for a cache key
+    fn body_path(&self, key: &str) -> PathBuf {
+        self.cache_dir.join(format!("{:016x}.body", fnv1a(key)))
+    }
+
+    /// Get the cache metadata path for a cache key
+    fn meta_path(&self, key: &str) -> PathBuf {
+        self.cache_dir.join(format!("{:016x}.json", fnv1a(key)))
+    }
+
+    /// Load cache metadata for a URL
+    fn load_cache_entry(&self, key: &str, url: &str) -> Option<CacheEntry> {
+        let content = std::fs::read_to_string(self.meta_path(key)).ok()?;
+        let entry: CacheEntry = serde_json::from_str(&content).ok()?;
+        // Guard against hash collisions, and skip entries written before
+        // URLs were redacted so they get replaced
+        if entry.url == redact_url(url) {
+            Some(entry)
+        } else {
+            None
+        }
+    }
+
+    /// Save cache metadata
+    fn save_cache_entry(&self, key: &str, entry: &CacheEntry) -> AppResult<()> {
+        std::fs::write(self.meta_path(key), serde_json::to_string_pretty(entry)?)?;
+        Ok(())
+    }
+
+    /// Build a response from the cache
+    fn cached_response(&self, key: &str, entry: &Option<CacheEntry>) -> Option<HttpResponse> {
+        entry.as_ref()?;
+        let body = std::fs::read(self.body_path(key)).ok()?;
+        Some(HttpResponse {
+            status: 200,
+            body,
+            from_cache: true,
+        })
+    }
+}
+
+/// Extract ETag and Last-Modified from a curl header dump
+///
+/// With redirects curl writes one header block per response, so the last
+/// occurrence wins.
+fn parse_validators(headers: &str) -> (Option<String>, Option<String>) {
+    let mut etag = None;
+    let mut last_modified = None;
+
+    for line in headers.lines() {
+        if let Some((name, value)) = line.split_once(':') {
+            let value = value.trim().to_string();
+            match name.trim().to_ascii_lowercase().as_str() {
+                "etag" => etag = Some(value),
+                "last-modified" => last_modified = Some(value),
+                _ => {}
+            }
+        } else if line.starts_with("HTTP/") {
+            etag = None;
+            last_modified = None;
+        }
+    }
+
+    (etag, last_modified)
+}
+
+/// Check whether a URL points at a service on this machine
+fn is_local(url: &str) -> bool {
+    let host = url
+        .split_once("://")
+        .map_or(url, |(_, rest)| rest)
+        .split('/')
+        .next()
+        .unwrap_or_default();
+    let host = match host.strip_prefix('[') {
+        Some(ipv6) => ipv6.split(']').next().unwrap_or_default(),
+        None => host.split(':').next().unwrap_or_default(),
+    };
+    matches!(host, "localhost" | "::1") || host.starts_with("127.")
+}
+
+/// Create a file readable by the user only, holding the given content
+///
+/// It is uniquely named and removed when dropped, whichever way the
+/// request ends.
+fn private_file(
+    dir: &Path,
+    suffix: &str,
+    content: &[u8],
+) -> std::io::Result<tempfile::NamedTempFile> {
+    let mut file = tempfile::Builder::new()
+        .prefix("request-")
+        .suffix(suffix)
+        .tempfile_in(dir)?;
+    file.write_all(content)?;
+    Ok(file)
+}
+
+/// Get the key a response is cached under
+///
+/// Headers are part of it, as an API key can change what a URL returns.
+fn cache_key(url: &str, headers: &[String]) -> String {
+    if headers.is_empty() {
+        url.to_string()
+    } else {
+        format!("{}\n{}", url, headers.join("\n"))
+    }
+}
+
+/// Strip the query string from a URL before logging or caching it
+///
+/// Query strings can hold API keys.
+pub fn redact_url(url: &str) -> String {
+    let end = url.find(['?', '#']).unwrap_or(url.len());
+    if end < url.len() {
+        format!("{}?…", &url[..end])
+    } else {
+        url.to_string()
+    }
+}
+
+/// Write a URL as a curl config file line
+fn curl_config_url(url: &str) -> String {
+    format!(
+        "url = \"{}\"\n",
+        url.replace('\\', "\\\\").replace('"', "\\\"")
+    )
+}
+
+/// Percent-encode a query string component
+pub fn encode_query_component(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char);
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// FNV-1a hash, used for stable cache file names
+fn fnv1a(value: &str) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in value.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limiter_window() {
+        let mut limiter = RateLimiter {
+            limit: RateLimit {
+                max_requests: 2,
+                window: Duration::from_secs(10),
+            },
+            requests: VecDeque::new(),
+        };
+        let start = Instant::now();
+
+        assert!(limiter.try_acquire(start));
+        assert!(limiter.try_acquire(start + Duration::from_secs(1)));
+        assert!(!limiter.try_acquire(start + Duration::from_secs(2)));
+
+        // The first request falls out of the window
+        assert!(limiter.try_acquire(start + Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn test_parse_validators_uses_last_response() {
+        let headers = "HTTP/1.1 301 Moved\r\nETag: \"old\"\r\nLocation: /new\r\n\r\nHTTP/1.1 200 OK\r\netag: \"abc\"\r\nLast-Modified: Wed, 21 Oct 2015 07:28:00 GMT\r\n\r\n";
+        let (etag, last_modified) = parse_validators(headers);
+
+        assert_eq!(etag.as_deref(), Some("\"abc\""));
+        assert_eq!(
+            last_modified.as_deref(),
+            Some("Wed, 21 Oct 2015 07:28:00 GMT")
+        );
+    }
+
+    #[test]
+    fn test_is_local() {
+        assert!(is_local("http://127.0.0.1:7860/sdapi/v1/txt2img"));
+        assert!(is_local("http://localhost:8188/prompt"));
+        assert!(is_local("http://[::1]:8188/prompt"));
+        assert!(!is_local("https://api.openai.com/v1/images/generations"));
+        assert!(!is_local("https://localhost.example.com/"));
+    }
+
+    #[test]
+    fn test_encode_query_component() {
+        assert_eq!(encode_query_component("New York, US"), "New%20York%2C%20US");
+    }
+
+    /// Serve each request with its Authorization header as the body, slowly so requests overlap
+    fn echo_server(requests: usize) -> String {
+        use std::io::{BufRead, BufReader};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/echo", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming().take(requests).flatten() {
+                std::thread::spawn(move || {
+                    let mut reader = BufReader::new(&stream);
+                    let mut token = String::new();
+                    let mut line = String::new();
+                    while reader.read_line(&mut line).unwrap_or(0) > 0 && line != "\r\n" {
+                        if let Some(value) = line.strip_prefix("Authorization: ") {
+                            token = value.trim().to_string();
+                        }
+                        line.clear();
+                    }
+                    std::thread::sleep(Duration::from_millis(200));
+                    let _ = write!(
+                        &stream,
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        token.len(),
+                        token
+                    );
+                });
+            }
+        });
+        url
+    }
+
+    #[test]
+    fn test_request_files_are_private_and_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let client = Arc::new(HttpClient::new(dir.path().to_path_buf()));
+        let url = echo_server(4);
+
+        // Concurrent requests to the same URL each get their own files
+        let requests: Vec<_> = (0..4)
+            .map(|i| {
+                let client = client.clone();
+                let url = url.clone();
+                std::thread::spawn(move || {
+                    let header = format!("Authorization: Bearer token-{}", i);
+                    let response = client
+                        .request(
+                            "test",
+                            "GET",
+                            &url,
+                            None,
+                            &[header],
+                            Duration::from_secs(10),
+                        )
+                        .unwrap();
+                    assert_eq!(
+                        String::from_utf8(response.body).unwrap(),
+                        format!("Bearer token-{}", i)
+                    );
+                })
+            })
+            .collect();
+
+        // The headers are only ever readable by the user while curl runs
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let deadline = Instant::now() + Duration::from_secs(5);
+            let mut seen = false;
+            while !seen && Instant::now() < deadline {
+                for entry in std::fs::read_dir(dir.path()).unwrap().flatten() {
+                    if let Ok(metadata) = entry.metadata() {
+                        assert_eq!(
+                            metadata.permissions().mode() & 0o077,
+                            0,
+                            "{}",
+                            entry.path().display()
+                        );
+                        seen = true;
+                    }
+                }
+            }
+            assert!(seen);
+        }
+
+        for request in requests {
+            request.join().unwrap();
+        }
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+
+        // Failed requests leave nothing behind either
+        let closed = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let header = "Authorization: Bearer secret".to_string();
+        assert!(client
+            .request(
+                "test",
+                "GET",
+                &format!("http://{}/", closed),
+                None,
+                &[header],
+                Duration::from_secs(5)
+            )
+            .is_err());
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_get_keeps_keys_out_of_the_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let client = Arc::new(HttpClient::new(dir.path().to_path_buf()));
+        let url = format!("{}?appid=secret", echo_server(4));
+
+        // Concurrent fetches of the same URL don't share temporary files
+        let fetches: Vec<_> = (0..4)
+            .map(|i| {
+                let client = client.clone();
+                let url = url.clone();
+                std::thread::spawn(move || {
+                    let header = format!("Authorization: Bearer token-{}", i);
+                    let response = client.get_with_headers("test", &url, &[header]).unwrap();
+                    assert_eq!(
+                        String::from_utf8(response.body).unwrap(),
+                        format!("Bearer token-{}", i)
+                    );
+                })
+            })
+            .collect();
+        for fetch in fetches {
+            fetch.join().unwrap();
+        }
+
+        // Nothing was cacheable, and nothing was left behind
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_redact_url() {
+        assert_eq!(
+            redact_url("https://example.com/a?q=x&appid=secret"),
+            "https://example.com/a?…"
+        );
+        assert_eq!(
+            redact_url("https://example.com/a#frag"),
+            "https://example.com/a?…"
+        );
+        assert_eq!(redact_url("https://example.com/a"), "https://example.com/a");
+    }
+
+    #[test]
+    fn test_cache_key_includes_headers() {
+        let url = "https://example.com/";
+        assert_eq!(cache_key(url, &[]), url);
+        assert_ne!(
+            cache_key(url, &["X-API-Key: a".to_string()]),
+            cache_key(url, &["X-API-Key: b".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_curl_config_url() {
+        assert_eq!(
+            curl_config_url("https://example.com/a?b=1"),
+            "url = \"https://example.com/a?b=1\"\n"
+        );
+        assert_eq!(curl_config_url("x\"y\\z"), "url = \"x\\\"y\\\\z\"\n");
+    }
+}
@@ -0,0 +1,321 @@
+//! HEIC, AVIF and JPEG XL images, and images the backend can't read
+//!
+//! The image crate reads the common formats itself; builds with the `heic`,
+//! `avif` and `jxl` features also decode HEIC/HEIF (through libheif), AVIF
+//! (through dav1d) and JPEG XL. Desktops can't be relied on to read these, so
+//! they're converted to PNG before reaching the backend, as are images in
+//! formats the backend doesn't list. Conversions are kept in the `converted`
+//! folder and reused until the original changes.
+use crate::core::image_pipeline::{Encoding, ImagePipeline};
+use crate::core::{AppError, AppResult, Config};
+use image::DynamicImage;
+use log::{debug, info};
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Most conversions kept; the oldest are removed first
+const MAX_CONVERTED: usize = 64;
+
+/// Image format decoded by an optional feature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtraFormat {
+    /// HEIC/HEIF, as saved by phones
+    Heif,
+    /// AVIF
+    Avif,
+    /// JPEG XL
+    Jxl,
+}
+
+impl ExtraFormat {
+    /// Get the format of a file from its extension, if it's one of these
+    pub fn of(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "heic" | "heif" => Some(ExtraFormat::Heif),
+            "avif" => Some(ExtraFormat::Avif),
+            "jxl" => Some(ExtraFormat::Jxl),
+            _ => None,
+        }
+    }
+
+    /// Get the name of the format
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExtraFormat::Heif => "HEIC",
+            ExtraFormat::Avif => "AVIF",
+            ExtraFormat::Jxl => "JPEG XL",
+        }
+    }
+
+    /// Get the feature building in the format's decoder
+    pub fn feature(&self) -> &'static str {
+        match self {
+            ExtraFormat::Heif => "heic",
+            ExtraFormat::Avif => "avif",
+            ExtraFormat::Jxl => "jxl",
+        }
+    }
+
+    /// Check whether this build decodes the format
+    pub fn is_supported(&self) -> bool {
+        match self {
+            ExtraFormat::Heif => cfg!(feature = "heic"),
+            ExtraFormat::Avif => cfg!(feature = "avif"),
+            ExtraFormat::Jxl => cfg!(feature = "jxl"),
+        }
+    }
+}
+
+/// Check that this build can decode an image's format
+pub fn check_supported(path: &Path) -> AppResult<()> {
+    match ExtraFormat::of(path) {
+        Some(format) if !format.is_supported() => Err(AppError::BackendUnavailable {
+            backend: format!("{} images", format.name()),
+            reason: format!(
+                "this build can't decode them (build with --features {})",
+                format.feature()
+            ),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Open an image, decoding the extra formats this build supports
+pub fn open(path: &Path) -> AppResult<DynamicImage> {
+    check_supported(path)?;
+    let open_error = |e: &dyn std::fmt::Display| {
+        AppError::WallpaperError(format!("Failed to open {}: {}", path.display(), e))
+    };
+    match ExtraFormat::of(path) {
+        None => image::open(path).map_err(|e| open_error(&e)),
+        Some(ExtraFormat::Heif) => decode_heif(path).map_err(|e| open_error(&e)),
+        // The image crate reads AVIF once its decoder is built in
+        Some(ExtraFormat::Avif) => image::open(path).map_err(|e| open_error(&e)),
+        Some(ExtraFormat::Jxl) => decode_jxl(path).map_err(|e| open_error(&e)),
+    }
+}
+
+/// Check whether a backend reading `formats` can be given an image as it is
+pub fn is_readable(path: &Path, formats: &[&str]) -> bool {
+    ExtraFormat::of(path).is_none()
+        && path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map_or(false, |extension| {
+                formats.contains(&extension.to_lowercase().as_str())
+            })
+}
+
+/// Get a file a backend reading `formats` can show for an image, converting it to PNG otherwise
+///
+/// `formats` are lowercase extensions, as listed by `WallpaperManager::image_formats`.
+pub async fn compatible(path: &Path, formats: &[&str]) -> AppResult<PathBuf> {
+    if is_readable(path, formats) {
+        return Ok(path.to_path_buf());
+    }
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || convert(&path, &Config::get_converted_dir()))
+        .await
+        .map_err(|e| AppError::WallpaperError(format!("Failed to convert image: {}", e)))?
+}
+
+/// Convert an image to PNG in `dir`, reusing an earlier conversion of the same file
+pub fn convert(path: &Path, dir: &Path) -> AppResult<PathBuf> {
+    let converted = dir.join(converted_name(path)?);
+    if converted.exists() {
+        debug!("Reusing the conversion of {}", path.display());
+        return Ok(converted);
+    }
+
+    let image = ImagePipeline::open(path)?;
+    fs::create_dir_all(dir)?;
+    image.save(&converted, Encoding::Png)?;
+    info!("Converted {} to PNG for the desktop", path.display());
+    prune(dir, MAX_CONVERTED);
+    Ok(converted)
+}
+
+/// Name the conversion of a file after its name, size and modification time
+pub(crate) fn converted_name(path: &Path) -> AppResult<String> {
+    let metadata = fs::metadata(path)?;
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |modified| modified.as_secs());
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    metadata.len().hash(&mut hasher);
+    modified.hash(&mut hasher);
+
+    let stem = path
+        .file_stem()
+        .map_or_else(|| "image".into(), |stem| stem.to_string_lossy());
+    Ok(format!("{}-{:016x}.png", stem, hasher.finish()))
+}
+
+/// Remove the oldest files in `dir` beyond `keep`
+pub(crate) fn prune(dir: &Path, keep: usize) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut files: Vec<_> = entries
+        .flatten()
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .collect();
+    if files.len() <= keep {
+        return;
+    }
+    files.sort();
+    for (_, path) in &files[..files.len() - keep] {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Decode the primary image of a HEIC/HEIF file
+#[cfg(feature = "heic")]
+fn decode_heif(path: &Path) -> Result<DynamicImage, String> {
+    use libheif_rs::{ColorSpace, HeifContext, LibHeif, RgbChroma};
+
+    let name = path.to_str().ok_or("the path isn't valid UTF-8")?;
+    let context = HeifContext::read_from_file(name).map_err(|e| e.to_string())?;
+    let handle = context.primary_image_handle().map_err(|e| e.to_string())?;
+    let decoded = LibHeif::new()
+        .decode(&handle, ColorSpace::Rgb(RgbChroma::Rgba), None)
+        .map_err(|e| e.to_string())?;
+    let plane = decoded
+        .planes()
+        .interleaved
+        .ok_or("libheif returned no pixels")?;
+
+    // Rows may be padded past the image's width
+    let row_bytes = plane.width as usize * 4;
+    let pixels: Vec<u8> = plane
+        .data
+        .chunks(plane.stride)
+        .take(plane.height as usize)
+        .flat_map(|row| &row[..row_bytes])
+        .copied()
+        .collect();
+    image::RgbaImage::from_raw(plane.width, plane.height, pixels)
+        .map(DynamicImage::ImageRgba8)
+        .ok_or_else(|| "libheif returned too few pixels".to_string())
+}
+
+/// Decode the primary image of a HEIC/HEIF file
+#[cfg(not(feature = "heic"))]
+fn decode_heif(_path: &Path) -> Result<DynamicImage, String> {
+    Err("HEIC support isn't built in".to_string())
+}
+
+/// Decode the first frame of a JPEG XL file
+#[cfg(feature = "jxl")]
+fn decode_jxl(path: &Path) -> Result<DynamicImage, String> {
+    use jxl_oxide::JxlImage;
+
+    let image = JxlImage::builder().open(path).map_err(|e| e.to_string())?;
+    let render = image.render_frame(0).map_err(|e| e.to_string())?;
+    let frame = render.image_all_channels();
+    let (width, height, channels) = (
+        frame.width() as u32,
+        frame.height() as u32,
+        frame.channels(),
+    );
+    let samples: Vec<u8> = frame
+        .buf()
+        .iter()
+        .map(|&sample| (sample.clamp(0.0, 1.0) * 255.0).round() as u8)
+        .collect();
+
+    let image = match channels {
+        1 => image::GrayImage::from_raw(width, height, samples).map(DynamicImage::ImageLuma8),
+        2 => image::GrayAlphaImage::from_raw(width, height, samples).map(DynamicImage::ImageLumaA8),
+        3 => image::RgbImage::from_raw(width, height, samples).map(DynamicImage::ImageRgb8),
+        // Extra channels, such as depth, follow the color and alpha
+        _ => {
+            let rgba = samples
+                .chunks(channels)
+                .flat_map(|pixel| pixel[..4].iter().copied())
+                .collect();
+            image::RgbaImage::from_raw(width, height, rgba).map(DynamicImage::ImageRgba8)
+        }
+    };
+    image.ok_or_else(|| "jxl-oxide returned too few pixels".to_string())
+}
+
+/// Decode the first frame of a JPEG XL file
+#[cfg(not(feature = "jxl"))]
+fn decode_jxl(_path: &Path) -> Result<DynamicImage, String> {
+    Err("JPEG XL support isn't built in".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_extra_format() {
+        assert_eq!(
+            ExtraFormat::of(Path::new("IMG_0042.HEIC")),
+            Some(ExtraFormat::Heif)
+        );
+        assert_eq!(
+            ExtraFormat::of(Path::new("a.heif")),
+            Some(ExtraFormat::Heif)
+        );
+        assert_eq!(
+            ExtraFormat::of(Path::new("a.avif")),
+            Some(ExtraFormat::Avif)
+        );
+        assert_eq!(ExtraFormat::of(Path::new("a.jxl")), Some(ExtraFormat::Jxl));
+        assert_eq!(ExtraFormat::of(Path::new("a.png")), None);
+        assert_eq!(ExtraFormat::of(Path::new("heic")), None);
+    }
+
+    #[test]
+    fn test_is_readable() {
+        let formats = ["png", "jpg", "jpeg"];
+        assert!(is_readable(Path::new("a.JPG"), &formats));
+        assert!(!is_readable(Path::new("a.bmp"), &formats));
+        assert!(!is_readable(Path::new("a"), &formats));
+        // The extra formats are converted even for backends claiming to read them
+        assert!(!is_readable(Path::new("a.heic"), &["heic"]));
+    }
+
+    #[test]
+    fn test_convert() {
+        let dir = tempdir().unwrap();
+        let converted_dir = dir.path().join("converted");
+
+        // Without the feature, the file is refused rather than handed on
+        let path = dir.path().join("photo.jxl");
+        fs::write(&path, b"not really").unwrap();
+        if !ExtraFormat::Jxl.is_supported() {
+            assert!(matches!(
+                convert(&path, &converted_dir),
+                Err(AppError::BackendUnavailable { .. })
+            ));
+        }
+
+        // A conversion is named after the file and changes with it
+        let first = converted_name(&path).unwrap();
+        assert!(first.starts_with("photo-") && first.ends_with(".png"));
+        fs::write(&path, b"something else").unwrap();
+        assert_ne!(converted_name(&path).unwrap(), first);
+    }
+
+    #[test]
+    fn test_prune() {
+        let dir = tempdir().unwrap();
+        for index in 0..5 {
+            fs::write(dir.path().join(format!("{}.png", index)), b"").unwrap();
+        }
+        prune(dir.path(), 3);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 3);
+    }
+}
@@ -0,0 +1,294 @@
+//! Image processing pipeline
+//!
+//! Wallpapers are cropped, resized and encoded here with the image crate, so
+//! span parts, per-monitor renders, thumbnails and conversions come out the
+//! same on every desktop instead of depending on what the desktop's tools do.
+//! Steps are chained on an `ImagePipeline` and the result is taken as an
+//! image or encoded to a file or bytes.
+use crate::core::config::CropRect;
+use crate::core::{image_format, AppError, AppResult};
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageOutputFormat, RgbaImage};
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{BufWriter, Cursor};
+use std::path::Path;
+
+/// Filter used when resizing; thumbnails use the faster one of `thumbnail`
+const FILTER: FilterType = FilterType::Lanczos3;
+
+/// Most shrinking applied before blurring, which keeps strong blurs fast
+const MAX_BLUR_DOWNSCALE: f32 = 4.0;
+
+/// Encoding of a pipeline's output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// Lossless PNG, read by every backend
+    Png,
+    /// JPEG at a quality from 1 to 100; transparency is dropped
+    Jpeg(u8),
+}
+
+impl Encoding {
+    fn format(self) -> ImageOutputFormat {
+        match self {
+            Encoding::Png => ImageOutputFormat::Png,
+            Encoding::Jpeg(quality) => ImageOutputFormat::Jpeg(quality.clamp(1, 100)),
+        }
+    }
+}
+
+/// Image being prepared, with the steps applied so far
+///
+/// A pipeline started from a borrowed image copies it only when a step
+/// changes it, so a large canvas can be cut up without copying all of it for
+/// each part.
+pub struct ImagePipeline<'a> {
+    image: Cow<'a, DynamicImage>,
+}
+
+impl<'a> ImagePipeline<'a> {
+    /// Start from a decoded image
+    pub fn new(image: impl Into<DynamicImage>) -> Self {
+        Self {
+            image: Cow::Owned(image.into()),
+        }
+    }
+
+    /// Start from a borrowed image
+    pub fn of(image: &'a DynamicImage) -> Self {
+        Self {
+            image: Cow::Borrowed(image),
+        }
+    }
+
+    /// Start from an image file, decoding the extra formats this build supports
+    pub fn open(path: &Path) -> AppResult<Self> {
+        image_format::open(path).map(Self::new)
+    }
+
+    /// Get the current size of the image
+    pub fn size(&self) -> (u32, u32) {
+        (self.image.width(), self.image.height())
+    }
+
+    /// Keep a part of the image given as fractions of its size
+    pub fn crop(self, crop: CropRect) -> Self {
+        let crop = crop.clamped();
+        let (width, height) = self.size();
+        let x = (crop.x * width as f32).round() as u32;
+        let y = (crop.y * height as f32).round() as u32;
+        let crop_width = (crop.width * width as f32).round() as u32;
+        let crop_height = (crop.height * height as f32).round() as u32;
+        self.region(x, y, crop_width, crop_height)
+    }
+
+    /// Keep a part of the image given in pixels, kept inside the image
+    pub fn region(self, x: u32, y: u32, width: u32, height: u32) -> Self {
+        if (x, y, width, height) == (0, 0, self.image.width(), self.image.height()) {
+            return self;
+        }
+        let (x, y, width, height) = inside(&self.image, x, y, width, height);
+        Self::new(self.image.crop_imm(x, y, width, height))
+    }
+
+    /// Resize to exactly `width` x `height`, ignoring the aspect ratio
+    pub fn resize(self, width: u32, height: u32) -> Self {
+        let (width, height) = (width.max(1), height.max(1));
+        if self.size() == (width, height) {
+            return self;
+        }
+        Self::new(self.image.resize_exact(width, height, FILTER))
+    }
+
+    /// Scale to fit inside `width` x `height`, keeping the aspect ratio
+    pub fn fit(self, width: u32, height: u32) -> Self {
+        Self::new(self.image.resize(width.max(1), height.max(1), FILTER))
+    }
+
+    /// Scale and crop the middle to fill `width` x `height` exactly
+    pub fn fill(self, width: u32, height: u32) -> Self {
+        Self::new(
+            self.image
+                .resize_to_fill(width.max(1), height.max(1), FILTER),
+        )
+    }
+
+    /// Shrink until the image just covers `width` x `height`, keeping the aspect ratio
+    ///
+    /// Smaller images are left alone.
+    pub fn cover(self, width: u32, height: u32) -> Self {
+        let (image_width, image_height) = self.size();
+        let scale = (width as f64 / image_width as f64).max(height as f64 / image_height as f64);
+        if scale >= 1.0 {
+            return self;
+        }
+        let new_width = (image_width as f64 * scale).round() as u32;
+        let new_height = (image_height as f64 * scale).round() as u32;
+        self.resize(new_width, new_height)
+    }
+
+    /// Blur by `sigma` pixels
+    ///
+    /// Strong blurs are done on a smaller copy, which looks the same and takes
+    /// a fraction of the time.
+    pub fn blur(self, sigma: f32) -> Self {
+        if sigma <= 0.0 {
+            return self;
+        }
+        let (width, height) = self.size();
+        let downscale = (sigma / 2.0).clamp(1.0, MAX_BLUR_DOWNSCALE);
+        let small = self.image.resize_exact(
+            ((width as f32 / downscale) as u32).max(1),
+            ((height as f32 / downscale) as u32).max(1),
+            FilterType::Triangle,
+        );
+        Self::new(
+            small
+                .blur(sigma / downscale)
+                .resize_exact(width, height, FilterType::Triangle),
+        )
+    }
+
+    /// Shrink quickly to fit inside a square of `max_edge` pixels
+    pub fn thumbnail(self, max_edge: u32) -> Self {
+        Self::new(self.image.thumbnail(max_edge, max_edge))
+    }
+
+    /// Get the resulting image
+    pub fn into_image(self) -> DynamicImage {
+        self.image.into_owned()
+    }
+
+    /// Get the resulting image as 8-bit RGBA
+    pub fn into_rgba8(self) -> RgbaImage {
+        match self.image {
+            Cow::Owned(DynamicImage::ImageRgba8(image)) => image,
+            image => image.to_rgba8(),
+        }
+    }
+
+    /// Encode the image into memory
+    pub fn encode(&self, encoding: Encoding) -> AppResult<Vec<u8>> {
+        let mut bytes = Cursor::new(Vec::new());
+        self.encodable(encoding)
+            .write_to(&mut bytes, encoding.format())
+            .map_err(|e| AppError::WallpaperError(format!("Failed to encode image: {}", e)))?;
+        Ok(bytes.into_inner())
+    }
+
+    /// Encode the image into a file, whatever its extension
+    pub fn save(&self, path: &Path, encoding: Encoding) -> AppResult<()> {
+        let save_error = |e: &dyn std::fmt::Display| {
+            AppError::WallpaperError(format!("Failed to save {}: {}", path.display(), e))
+        };
+        let mut writer = BufWriter::new(File::create(path).map_err(|e| save_error(&e))?);
+        self.encodable(encoding)
+            .write_to(&mut writer, encoding.format())
+            .map_err(|e| save_error(&e))
+    }
+
+    /// Get the image in a color type the encoding takes
+    fn encodable(&self, encoding: Encoding) -> Cow<'_, DynamicImage> {
+        match (encoding, &*self.image) {
+            (Encoding::Jpeg(_), DynamicImage::ImageRgb8(_) | DynamicImage::ImageLuma8(_))
+            | (Encoding::Png, _) => Cow::Borrowed(&*self.image),
+            (Encoding::Jpeg(_), image) => Cow::Owned(DynamicImage::ImageRgb8(image.to_rgb8())),
+        }
+    }
+}
+
+/// Move a pixel rectangle inside an image, at least one pixel in size
+fn inside(image: &DynamicImage, x: u32, y: u32, width: u32, height: u32) -> (u32, u32, u32, u32) {
+    let x = x.min(image.width().saturating_sub(1));
+    let y = y.min(image.height().saturating_sub(1));
+    (
+        x,
+        y,
+        width.clamp(1, image.width() - x),
+        height.clamp(1, image.height() - y),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+    use tempfile::tempdir;
+
+    /// 4x2 image: left half red, right half blue
+    fn test_image() -> ImagePipeline<'static> {
+        ImagePipeline::new(RgbaImage::from_fn(4, 2, |x, _| {
+            if x < 2 {
+                Rgba([255, 0, 0, 255])
+            } else {
+                Rgba([0, 0, 255, 255])
+            }
+        }))
+    }
+
+    #[test]
+    fn test_crop_and_resize() {
+        let right = test_image().crop(CropRect {
+            x: 0.5,
+            y: 0.0,
+            width: 0.5,
+            height: 1.0,
+        });
+        assert_eq!(right.size(), (2, 2));
+        let right = right.resize(6, 3).into_rgba8();
+        assert_eq!(right.dimensions(), (6, 3));
+        assert!(right.pixels().all(|pixel| pixel[2] > pixel[0]));
+
+        // A borrowed canvas is cut up without being changed
+        let canvas = test_image().into_image();
+        assert_eq!(
+            ImagePipeline::of(&canvas)
+                .region(2, 0, 2, 2)
+                .into_rgba8()
+                .get_pixel(0, 0),
+            &Rgba([0, 0, 255, 255])
+        );
+        assert_eq!(canvas.width(), 4);
+
+        // Regions past the edge are kept inside the image
+        assert_eq!(test_image().region(3, 1, 10, 10).size(), (1, 1));
+        assert_eq!(test_image().fit(8, 8).size(), (8, 4));
+        assert_eq!(test_image().fill(8, 8).size(), (8, 8));
+        assert_eq!(test_image().thumbnail(2).size(), (2, 1));
+        assert_eq!(test_image().blur(8.0).size(), (4, 2));
+    }
+
+    #[test]
+    fn test_cover() {
+        let photo = ImagePipeline::new(RgbaImage::new(4000, 3000));
+        assert_eq!(photo.cover(1920, 1080).size(), (1920, 1440));
+        let small = ImagePipeline::new(RgbaImage::new(800, 600));
+        assert_eq!(small.cover(1920, 1080).size(), (800, 600));
+    }
+
+    #[test]
+    fn test_encode() {
+        let dir = tempdir().unwrap();
+        let png = test_image().encode(Encoding::Png).unwrap();
+        assert_eq!(
+            image::load_from_memory(&png)
+                .unwrap()
+                .to_rgba8()
+                .get_pixel(3, 0),
+            &Rgba([0, 0, 255, 255])
+        );
+
+        // JPEG drops the alpha channel rather than failing, whatever the file is named
+        let path = dir.path().join("photo.png");
+        test_image().save(&path, Encoding::Jpeg(90)).unwrap();
+        assert_eq!(
+            image::io::Reader::open(&path)
+                .unwrap()
+                .with_guessed_format()
+                .unwrap()
+                .format(),
+            Some(image::ImageFormat::Jpeg)
+        );
+    }
+}
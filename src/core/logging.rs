@@ -0,0 +1,340 @@
+//! Log files
+//!
+//! Log messages go to stderr and to a file in the config directory, which is
+//! rotated once it grows too large. The window and the daemon write their own
+//! files so they never rotate each other's. `RUST_LOG` can still turn on more
+//! messages than the configured level.
+use crate::core::config::LogLevel;
+use crate::core::Config;
+use chrono::Local;
+use env_logger::filter::{Builder, Filter};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+/// Size at which a log file is rotated
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+/// Number of rotated files kept besides the current one
+const KEPT_FILES: usize = 3;
+
+/// Number of recent lines kept in memory for crash reports
+const RECENT_LINES: usize = 100;
+
+/// Configured level, as a `LevelFilter` discriminant
+static LEVEL: AtomicUsize = AtomicUsize::new(LevelFilter::Info as usize);
+
+/// Installed logger, for changing the level later
+static LOGGER: OnceLock<&'static Logger> = OnceLock::new();
+
+/// Log file of a part of the app
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFile {
+    /// The settings window
+    App,
+
+    /// The background daemon, or the headless engine
+    Daemon,
+}
+
+impl LogFile {
+    /// Get the file name
+    pub fn file_name(self) -> &'static str {
+        match self {
+            LogFile::App => "aether-desk.log",
+            LogFile::Daemon => "daemon.log",
+        }
+    }
+
+    /// Get the path of the current file
+    pub fn path(self) -> PathBuf {
+        Config::get_logs_dir().join(self.file_name())
+    }
+}
+
+/// Line of a log file
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogLine {
+    /// Level of the message the line belongs to
+    pub level: Option<Level>,
+
+    /// Line text
+    pub text: String,
+}
+
+/// File that is moved aside and started again once it reaches a size
+struct RotatingFile {
+    /// Path of the current file
+    path: PathBuf,
+
+    /// Size at which the file is rotated
+    max_size: u64,
+
+    /// Open file
+    file: File,
+
+    /// Bytes in the open file
+    size: u64,
+}
+
+impl RotatingFile {
+    /// Open a file for appending
+    fn open(path: &Path, max_size: u64) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path: path.to_path_buf(),
+            max_size,
+            file,
+            size,
+        })
+    }
+
+    /// Append a line, rotating first when it wouldn't fit
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        if self.size > 0 && self.size + line.len() as u64 + 1 > self.max_size {
+            self.rotate()?;
+        }
+        writeln!(self.file, "{}", line)?;
+        self.size += line.len() as u64 + 1;
+        Ok(())
+    }
+
+    /// Shift the older files up by one and start an empty current file
+    fn rotate(&mut self) -> io::Result<()> {
+        for index in (1..KEPT_FILES).rev() {
+            let from = rotated_path(&self.path, index);
+            if from.exists() {
+                fs::rename(&from, rotated_path(&self.path, index + 1))?;
+            }
+        }
+        fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        self.file = File::create(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+/// Logger writing to stderr and a log file
+struct Logger {
+    /// Extra messages turned on with `RUST_LOG` or by the caller
+    extra: Filter,
+
+    /// Log file, if this process keeps one
+    file: Option<Mutex<RotatingFile>>,
+
+    /// Latest lines, oldest first
+    recent: Mutex<VecDeque<String>>,
+}
+
+impl Logger {
+    /// Get the configured level
+    fn level() -> LevelFilter {
+        match LEVEL.load(Ordering::Relaxed) {
+            0 => LevelFilter::Off,
+            1 => LevelFilter::Error,
+            2 => LevelFilter::Warn,
+            3 => LevelFilter::Info,
+            4 => LevelFilter::Debug,
+            _ => LevelFilter::Trace,
+        }
+    }
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Self::level() || self.extra.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let line = format!(
+            "{} {:<5} {}: {}",
+            Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+            record.level(),
+            record.target(),
+            record.args(),
+        );
+        let _ = writeln!(io::stderr(), "{}", line);
+        if let Some(file) = &self.file {
+            // Nowhere left to report a failure to write the log
+            let _ = file.lock().unwrap().write_line(&line);
+        }
+
+        let mut recent = self.recent.lock().unwrap();
+        if recent.len() == RECENT_LINES {
+            recent.pop_front();
+        }
+        recent.push_back(line);
+    }
+
+    fn flush(&self) {
+        if let Some(file) = &self.file {
+            let _ = file.lock().unwrap().file.flush();
+        }
+    }
+}
+
+/// Install the logger
+///
+/// `targets` are logged at info level whatever the configured level.
+/// Without `file`, messages only go to stderr, as for CLI commands.
+pub fn init(file: Option<LogFile>, level: LogLevel, targets: &[&str]) {
+    let mut extra = Builder::from_env("RUST_LOG");
+    for target in targets {
+        extra.filter_module(target, LevelFilter::Info);
+    }
+    let extra = extra.build();
+
+    let file = file.and_then(
+        |file| match RotatingFile::open(&file.path(), MAX_FILE_SIZE) {
+            Ok(file) => Some(Mutex::new(file)),
+            Err(e) => {
+                eprintln!("Failed to open log file {}: {}", file.path().display(), e);
+                None
+            }
+        },
+    );
+
+    let recent = Mutex::new(VecDeque::with_capacity(RECENT_LINES));
+    let logger: &'static Logger = Box::leak(Box::new(Logger {
+        extra,
+        file,
+        recent,
+    }));
+    if log::set_logger(logger).is_ok() {
+        let _ = LOGGER.set(logger);
+        set_level(level);
+    }
+}
+
+/// Change the configured level of the installed logger
+pub fn set_level(level: LogLevel) {
+    LEVEL.store(level.filter() as usize, Ordering::Relaxed);
+    let extra = LOGGER
+        .get()
+        .map_or(LevelFilter::Off, |logger| logger.extra.filter());
+    log::set_max_level(level.filter().max(extra));
+}
+
+/// Get the latest lines logged by this process
+///
+/// Doesn't wait for a logging thread, so it is safe to call while panicking.
+pub fn recent_lines() -> Vec<String> {
+    let Some(logger) = LOGGER.get() else {
+        return Vec::new();
+    };
+    match logger.recent.try_lock() {
+        Ok(recent) => recent.iter().cloned().collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Read the last `max_lines` lines of a log, including its latest rotated file
+pub fn read_tail(file: LogFile, max_lines: usize) -> io::Result<Vec<LogLine>> {
+    let path = file.path();
+    let mut lines = Vec::new();
+    for path in [rotated_path(&path, 1), path] {
+        match File::open(&path) {
+            Ok(file) => {
+                for line in BufReader::new(file).lines() {
+                    lines.push(line?);
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    // Lines of multi-line messages carry the level of their first line
+    let mut level = None;
+    let mut parsed: Vec<LogLine> = lines
+        .into_iter()
+        .map(|text| {
+            if let Some(line_level) = parse_level(&text) {
+                level = Some(line_level);
+            }
+            LogLine { level, text }
+        })
+        .collect();
+    let skip = parsed.len().saturating_sub(max_lines);
+    parsed.drain(..skip);
+    Ok(parsed)
+}
+
+/// Get the path of the `index`th rotated file ("daemon.1.log")
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let name = match path.extension() {
+        Some(ext) => format!("{}.{}.{}", stem, index, ext.to_string_lossy()),
+        None => format!("{}.{}", stem, index),
+    };
+    path.with_file_name(name)
+}
+
+/// Get the level of a line starting a message ("2024-05-01 12:00:00.000 WARN  ...")
+fn parse_level(line: &str) -> Option<Level> {
+    line.split_whitespace().nth(2)?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotation_keeps_recent_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("daemon.log");
+        let mut file = RotatingFile::open(&path, 20).unwrap();
+        for index in 0..(KEPT_FILES + 3) {
+            file.write_line(&format!("line number {:04}", index))
+                .unwrap();
+        }
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            format!("line number {:04}\n", KEPT_FILES + 2)
+        );
+        assert_eq!(
+            fs::read_to_string(rotated_path(&path, 1)).unwrap(),
+            format!("line number {:04}\n", KEPT_FILES + 1)
+        );
+        assert!(rotated_path(&path, KEPT_FILES).exists());
+        assert!(!rotated_path(&path, KEPT_FILES + 1).exists());
+    }
+
+    #[test]
+    fn test_rotated_path() {
+        assert_eq!(
+            rotated_path(Path::new("/logs/daemon.log"), 2),
+            PathBuf::from("/logs/daemon.2.log")
+        );
+        assert_eq!(
+            rotated_path(Path::new("/logs/daemon"), 1),
+            PathBuf::from("/logs/daemon.1")
+        );
+    }
+
+    #[test]
+    fn test_parse_level() {
+        assert_eq!(
+            parse_level("2024-05-01 12:00:00.000 WARN  aether_desk::core: Low memory"),
+            Some(Level::Warn)
+        );
+        assert_eq!(
+            parse_level("2024-05-01 12:00:00.000 INFO  aether_desk: Started"),
+            Some(Level::Info)
+        );
+        assert_eq!(parse_level("   at the second line of a message"), None);
+    }
+}
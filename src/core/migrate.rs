@@ -0,0 +1,1222 @@
+//! Importing the setup of other wallpaper tools
+//!
+//! Komorebi's wallpaper and library, Variety's rotation, folders and
+//! favorites, and folders of wallpapers on a schedule are read into a
+//! [`Migration`], which is shown before it's merged into the configuration
+//! and the schedule. Scheduled folders are either GNOME timed backgrounds
+//! (an XML file listing images and how long each is shown) or images named
+//! after the time or part of the day they're for, like `07-30.jpg` or
+//! `evening.png`. Windows `.theme` files and `.themepack` archives bring
+//! their wallpaper, fit and slideshow; their images are copied into a folder
+//! of their own that the slideshow's rotation goes through. Whatever doesn't
+//! map onto Aether-Desk is listed in the migration's notes rather than
+//! dropped silently.
+use crate::core::config::FitMode;
+use crate::core::{
+    format, AppError, AppResult, Config, ScheduleItem, TriggerType, WallpaperInfo, WallpaperType,
+};
+use chrono::{Duration, NaiveTime, Timelike};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Length of a day, which timed backgrounds repeating daily add up to
+const DAY_SECONDS: i64 = 24 * 60 * 60;
+
+/// Parts of the day folder images may be named after, with the time they start
+const DAY_PARTS: [(&str, u32); 10] = [
+    ("dawn", 5),
+    ("sunrise", 6),
+    ("morning", 8),
+    ("noon", 12),
+    ("day", 12),
+    ("afternoon", 15),
+    ("evening", 18),
+    ("sunset", 19),
+    ("dusk", 20),
+    ("night", 21),
+];
+
+/// Minutes between slideshow images of Windows themes that don't say
+const THEME_SLIDESHOW_MINUTES: u32 = 30;
+
+/// Extensions of Windows theme archives
+const THEMEPACK_EXTENSIONS: [&str; 2] = ["themepack", "deskthemepack"];
+
+/// Tool a setup is imported from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    /// Komorebi, on Linux
+    Komorebi,
+
+    /// Variety, on Linux
+    Variety,
+
+    /// A folder of wallpapers on a schedule, or a GNOME timed background
+    Folder,
+
+    /// A Windows `.theme` file or `.themepack` archive
+    WindowsTheme,
+}
+
+impl Tool {
+    /// Get the tool's name
+    pub fn name(self) -> &'static str {
+        match self {
+            Tool::Komorebi => "Komorebi",
+            Tool::Variety => "Variety",
+            Tool::Folder => "Folder",
+            Tool::WindowsTheme => "Windows theme",
+        }
+    }
+}
+
+/// Rotation through a folder, mapped onto auto-change
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rotation {
+    /// Whether the wallpaper changes on its own
+    pub enabled: bool,
+
+    /// Minutes between changes
+    pub interval: u32,
+
+    /// Folder rotated through, if the tool had one
+    pub folder: Option<PathBuf>,
+}
+
+/// What was read from another tool
+#[derive(Debug, Clone, Default)]
+pub struct Migration {
+    /// Tool it was read from
+    pub source: String,
+
+    /// Rotation, if the tool rotated wallpapers
+    pub rotation: Option<Rotation>,
+
+    /// Wallpapers to show at set times
+    pub schedule: Vec<ScheduleItem>,
+
+    /// Wallpaper the tool showed
+    pub wallpaper: Option<WallpaperInfo>,
+
+    /// Wallpapers to add to the favorites
+    pub favorites: Vec<PathBuf>,
+
+    /// Fit mode, and whether the image spans all monitors
+    pub placement: Option<(FitMode, bool)>,
+
+    /// Files to copy into the library, as (from, to), before anything points at them
+    pub files: Vec<(PathBuf, PathBuf)>,
+
+    /// Settings that couldn't be imported, and other remarks
+    pub notes: Vec<String>,
+}
+
+impl Migration {
+    /// Check whether nothing was found to import
+    pub fn is_empty(&self) -> bool {
+        self.rotation.is_none()
+            && self.schedule.is_empty()
+            && self.wallpaper.is_none()
+            && self.favorites.is_empty()
+    }
+
+    /// Merge into the configuration and append to the schedule file
+    ///
+    /// Schedule items and favorites already there aren't added twice, so
+    /// importing again is harmless. The configuration is changed but not
+    /// saved; the schedule file is saved. Returns the schedule items added.
+    pub fn merge_into(&self, config: &mut Config) -> AppResult<usize> {
+        for (from, to) in &self.files {
+            if to.exists() {
+                continue;
+            }
+            if let Some(folder) = to.parent() {
+                fs::create_dir_all(folder)?;
+            }
+            fs::copy(from, to)?;
+        }
+
+        if let Some((mode, span)) = self.placement {
+            config.wallpaper.placement.mode = mode;
+            config.wallpaper.placement.span = span;
+        }
+
+        if let Some(rotation) = &self.rotation {
+            let auto_change = &mut config.wallpaper.auto_change;
+            auto_change.enabled = rotation.enabled;
+            auto_change.interval = rotation.interval.max(1);
+            if let Some(folder) = &rotation.folder {
+                auto_change.folder = Some(folder.to_string_lossy().to_string());
+            }
+        }
+
+        for favorite in &self.favorites {
+            let favorite = favorite.to_string_lossy().to_string();
+            if !config.wallpaper.favorites.contains(&favorite) {
+                config.wallpaper.favorites.push(favorite);
+            }
+        }
+
+        if let Some(wallpaper) = &self.wallpaper {
+            wallpaper.remember(&mut config.wallpaper);
+        }
+
+        if self.schedule.is_empty() {
+            return Ok(0);
+        }
+        let file = config.get_schedule_file();
+        let mut items: Vec<ScheduleItem> = if file.exists() {
+            format::load(&file).map_err(|e| {
+                AppError::ConfigError(format!("Failed to load schedule file: {}", e))
+            })?
+        } else {
+            Vec::new()
+        };
+        let before = items.len();
+        for item in &self.schedule {
+            let known = items.iter().any(|existing| {
+                existing.trigger == item.trigger && existing.wallpaper.path == item.wallpaper.path
+            });
+            if !known {
+                items.push(item.clone());
+            }
+        }
+        format::save(&file, &items)
+            .map_err(|e| AppError::ConfigError(format!("Failed to save schedule file: {}", e)))?;
+        Ok(items.len() - before)
+    }
+}
+
+/// Read another tool's setup, from where it keeps it unless a path is given
+///
+/// For [`Tool::Folder`] the path is required: the folder, or a timed background's
+/// XML file. So is it for [`Tool::WindowsTheme`]: the `.theme` or `.themepack` file.
+pub fn read(tool: Tool, path: Option<&Path>) -> AppResult<Migration> {
+    let home = dirs::home_dir().unwrap_or_default();
+    let mut migration = match tool {
+        Tool::Komorebi => {
+            let file = path
+                .map(Path::to_path_buf)
+                .or_else(|| {
+                    [
+                        home.join(".config/komorebi/komorebi.prop"),
+                        home.join(".Komorebi.prop"),
+                    ]
+                    .into_iter()
+                    .find(|file| file.is_file())
+                })
+                .ok_or_else(|| not_found(tool))?;
+            let libraries = [
+                home.join(".local/share/komorebi"),
+                PathBuf::from("/System/Resources/Komorebi"),
+            ];
+            komorebi(&fs::read_to_string(&file)?, &libraries)
+        }
+        Tool::Variety => {
+            let file = path
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| home.join(".config/variety/variety.conf"));
+            if !file.is_file() {
+                return Err(not_found(tool));
+            }
+            variety(&fs::read_to_string(&file)?, &home)
+        }
+        Tool::Folder => {
+            let path = path
+                .ok_or_else(|| AppError::ConfigError("Choose the folder to import".to_string()))?;
+            let xml = if path.is_dir() {
+                timed_background_in(path)
+            } else {
+                Some(path.to_path_buf())
+            };
+            match xml {
+                Some(xml) => {
+                    timed_background(&fs::read_to_string(&xml)?, xml.parent().unwrap_or(path))
+                }
+                None => scheduled_folder(path)?,
+            }
+        }
+        Tool::WindowsTheme => {
+            let path = path.ok_or_else(|| {
+                AppError::ConfigError("Choose the .theme or .themepack file to import".to_string())
+            })?;
+            windows_theme_file(path, &Config::get_imported_dir())?
+        }
+    };
+    migration.source = tool.name().to_string();
+    for item in &mut migration.schedule {
+        item.wallpaper.description = format!("Imported from {}", tool.name());
+    }
+    Ok(migration)
+}
+
+/// Error for a tool whose setup isn't where it keeps it
+fn not_found(tool: Tool) -> AppError {
+    AppError::ConfigError(format!(
+        "No {} configuration found; choose its file",
+        tool.name()
+    ))
+}
+
+/// Read Komorebi's properties, finding its wallpaper in the libraries
+///
+/// Komorebi shows one wallpaper and doesn't rotate; every wallpaper found in
+/// the libraries becomes a favorite, so they stay one click away.
+fn komorebi(properties: &str, libraries: &[PathBuf]) -> Migration {
+    let mut migration = Migration::default();
+    let properties = key_values(properties);
+
+    for library in libraries {
+        let Ok(entries) = fs::read_dir(library) else {
+            continue;
+        };
+        let mut folders: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .collect();
+        folders.sort();
+        for folder in folders {
+            let Some(wallpaper) = komorebi_wallpaper(&folder) else {
+                continue;
+            };
+            let current = properties.get("WallpaperName").is_some_and(|name| {
+                folder
+                    .file_name()
+                    .is_some_and(|file_name| file_name.to_string_lossy() == *name)
+            });
+            if let Some(path) = &wallpaper.path {
+                migration.favorites.push(path.clone());
+            }
+            if current {
+                migration.wallpaper = Some(wallpaper);
+            }
+        }
+    }
+
+    if let (None, Some(name)) = (&migration.wallpaper, properties.get("WallpaperName")) {
+        migration.notes.push(format!(
+            "Komorebi's wallpaper {} wasn't found in its libraries",
+            name
+        ));
+    }
+    if properties
+        .keys()
+        .any(|key| key.starts_with("Time") || key.starts_with("Date"))
+    {
+        migration
+            .notes
+            .push("Komorebi's clock isn't imported; add a Clock widget instead".to_string());
+    }
+    if properties
+        .get("ShowDesktopIcons")
+        .is_some_and(|value| value.eq_ignore_ascii_case("true"))
+    {
+        migration
+            .notes
+            .push("Desktop icons are left to the desktop environment".to_string());
+    }
+    migration
+}
+
+/// Read a Komorebi wallpaper folder: an image, a video or a web page
+fn komorebi_wallpaper(folder: &Path) -> Option<WallpaperInfo> {
+    let config = key_values(&fs::read_to_string(folder.join("config")).ok()?);
+    let mut wallpaper = match config.get("WallpaperType").map(String::as_str) {
+        Some("video") => WallpaperInfo::new(
+            WallpaperType::Video,
+            Some(folder.join(config.get("VideoFileName")?)),
+            None,
+        ),
+        Some("web_page") => WallpaperInfo::new(
+            WallpaperType::Web,
+            None,
+            Some(config.get("WebPageUrl")?.clone()),
+        ),
+        _ => WallpaperInfo::new(WallpaperType::Static, Some(folder.join("bg.jpg")), None),
+    };
+    if wallpaper.path.as_ref().is_some_and(|path| !path.is_file()) {
+        return None;
+    }
+    wallpaper.name = folder.file_name()?.to_string_lossy().replace('_', " ");
+    Some(wallpaper)
+}
+
+/// Read Variety's configuration: its rotation, folders and favorites
+fn variety(conf: &str, home: &Path) -> Migration {
+    let mut migration = Migration::default();
+    let settings = key_values(conf);
+    let expand = |value: &str| match value.strip_prefix("~/") {
+        Some(rest) => home.join(rest),
+        None => PathBuf::from(value),
+    };
+
+    let favorites_folder = settings
+        .get("favorites_folder")
+        .map(|folder| expand(folder.as_str()))
+        .unwrap_or_else(|| home.join(".config/variety/Favorites"));
+    // Sources look like `src3 = True|folder|/home/me/Pictures`, first one first
+    let mut sources: Vec<(u32, &String)> = settings
+        .iter()
+        .filter_map(|(key, value)| Some((key.strip_prefix("src")?.parse().ok()?, value)))
+        .collect();
+    sources.sort();
+    let mut folders = Vec::new();
+    for (_, value) in sources {
+        let mut fields = value.splitn(3, '|');
+        let (Some(enabled), Some(kind), location) = (
+            fields.next(),
+            fields.next(),
+            fields.next().unwrap_or_default(),
+        ) else {
+            continue;
+        };
+        if !enabled.eq_ignore_ascii_case("true") {
+            continue;
+        }
+        match kind {
+            "folder" => folders.push(expand(location)),
+            "image" => migration.favorites.push(expand(location)),
+            "favorites" => folders.push(favorites_folder.clone()),
+            "fetched" => {}
+            "wallhaven" => migration.notes.push(format!(
+                "Set up Wallhaven downloads for \"{}\" under Online Sources",
+                location
+            )),
+            "earth" => migration
+                .notes
+                .push("Turn on Live Earth under Online Sources for the Earth view".to_string()),
+            other => migration
+                .notes
+                .push(format!("Variety's {} source isn't available", other)),
+        }
+    }
+
+    if folders.len() > 1 {
+        let rest: Vec<String> = folders[1..]
+            .iter()
+            .map(|folder| folder.display().to_string())
+            .collect();
+        migration.notes.push(format!(
+            "Only one folder is rotated through; also used by Variety: {}",
+            rest.join(", ")
+        ));
+    }
+    if let Ok(entries) = fs::read_dir(&favorites_folder) {
+        let mut favorites: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| WallpaperType::Static.accepts(path))
+            .collect();
+        favorites.sort();
+        migration.favorites.extend(favorites);
+    }
+
+    let seconds: u32 = settings
+        .get("change_interval")
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(300);
+    migration.rotation = Some(Rotation {
+        enabled: settings
+            .get("change_enabled")
+            .map_or(true, |value| value.eq_ignore_ascii_case("true")),
+        interval: ((seconds + 30) / 60).max(1),
+        folder: folders.into_iter().next(),
+    });
+    if settings
+        .get("quotes_enabled")
+        .is_some_and(|value| value.eq_ignore_ascii_case("true"))
+    {
+        migration
+            .notes
+            .push("Variety's quotes aren't imported".to_string());
+    }
+    if settings.get("filters").is_some() {
+        migration
+            .notes
+            .push("Variety's filters aren't imported".to_string());
+    }
+    migration
+}
+
+/// Find a timed background's XML file in a folder
+fn timed_background_in(folder: &Path) -> Option<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(folder)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("xml"))
+        })
+        .collect();
+    files.sort();
+    files
+        .into_iter()
+        .find(|file| fs::read_to_string(file).is_ok_and(|xml| xml.contains("<background")))
+}
+
+/// Read a GNOME timed background
+///
+/// A background repeating daily becomes one schedule item per image, at the
+/// time its turn starts. Any other cycle is a rotation through the images'
+/// folder at their average duration.
+fn timed_background(xml: &str, base: &Path) -> Migration {
+    let mut migration = Migration::default();
+    let start = tag(xml, "starttime")
+        .map(|start| {
+            let field = |name| {
+                tag(start, name)
+                    .and_then(|value| value.trim().parse::<u32>().ok())
+                    .unwrap_or(0)
+            };
+            NaiveTime::from_hms_opt(
+                field("hour") % 24,
+                field("minute") % 60,
+                field("second") % 60,
+            )
+            .unwrap_or_default()
+        })
+        .unwrap_or_default();
+
+    let mut offset = 0i64;
+    let mut shown: Vec<(i64, i64, PathBuf)> = Vec::new();
+    for (name, body) in elements(xml, &["static", "transition"]) {
+        let duration = tag(body, "duration")
+            .and_then(|value| value.trim().parse::<f64>().ok())
+            .unwrap_or(0.0) as i64;
+        if name == "static" {
+            if let Some(file) = tag(body, "file").map(file_text) {
+                let file = Path::new(file.trim());
+                shown.push((
+                    offset,
+                    duration,
+                    if file.is_absolute() {
+                        file.to_path_buf()
+                    } else {
+                        base.join(file)
+                    },
+                ));
+            }
+        }
+        offset += duration;
+    }
+
+    if shown.is_empty() {
+        migration
+            .notes
+            .push("The timed background lists no images".to_string());
+    } else if (offset - DAY_SECONDS).abs() <= 60 {
+        for (at, _, path) in shown {
+            let time = start + Duration::seconds(at);
+            migration.schedule.push(schedule_item(
+                NaiveTime::from_hms_opt(time.hour(), time.minute(), 0).unwrap_or_default(),
+                path,
+            ));
+        }
+    } else {
+        let average =
+            shown.iter().map(|(_, duration, _)| duration).sum::<i64>() / shown.len() as i64;
+        let folder = shown[0].2.parent().map(Path::to_path_buf);
+        if shown
+            .iter()
+            .any(|(_, _, path)| path.parent() != folder.as_deref())
+        {
+            migration.notes.push(
+                "The images are in several folders; only the first one's is rotated through"
+                    .to_string(),
+            );
+        }
+        migration.rotation = Some(Rotation {
+            enabled: true,
+            interval: ((average + 30) / 60).max(1) as u32,
+            folder,
+        });
+    }
+    migration
+}
+
+/// Read a folder of images named after times or parts of the day
+///
+/// A folder without any is rotated through instead, hourly.
+fn scheduled_folder(folder: &Path) -> AppResult<Migration> {
+    let mut migration = Migration::default();
+    let mut files: Vec<PathBuf> = fs::read_dir(folder)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            WallpaperType::from_path(path)
+                .is_some_and(|wallpaper_type| wallpaper_type != WallpaperType::Slideshow)
+        })
+        .collect();
+    files.sort();
+
+    for file in files {
+        let stem = file
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        match time_in_name(&stem) {
+            Some(time) => migration.schedule.push(schedule_item(time, file)),
+            None => migration.notes.push(format!(
+                "{} isn't named after a time, so it isn't scheduled",
+                file.display()
+            )),
+        }
+    }
+
+    if migration.schedule.is_empty() {
+        migration.notes.clear();
+        migration.rotation = Some(Rotation {
+            enabled: true,
+            interval: 60,
+            folder: Some(folder.to_path_buf()),
+        });
+    }
+    migration.schedule.sort_by_key(|item| match item.trigger {
+        TriggerType::Time(time) => time,
+        _ => NaiveTime::default(),
+    });
+    Ok(migration)
+}
+
+/// Read the time a file name starts with, like `0730`, `07-30` or `7h30`, or the part of the day it names
+fn time_in_name(stem: &str) -> Option<NaiveTime> {
+    let digits: String = stem.chars().take_while(char::is_ascii_digit).collect();
+    let rest = &stem[digits.len()..];
+    let time = match digits.len() {
+        4 => Some((digits[..2].parse().ok()?, digits[2..].parse().ok()?)),
+        1 | 2 => {
+            let separated = rest.strip_prefix(['-', ':', '.', '_', 'h']);
+            let minutes: String = separated
+                .unwrap_or_default()
+                .chars()
+                .take_while(char::is_ascii_digit)
+                .collect();
+            match (separated, minutes.len()) {
+                (Some(_), 2) => Some((digits.parse().ok()?, minutes.parse().ok()?)),
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+    if let Some((hour, minute)) = time {
+        return NaiveTime::from_hms_opt(hour, minute, 0);
+    }
+
+    let words: Vec<&str> = stem.split(|c: char| !c.is_ascii_alphabetic()).collect();
+    DAY_PARTS
+        .iter()
+        .find(|(part, _)| words.contains(part))
+        .and_then(|(_, hour)| NaiveTime::from_hms_opt(*hour, 0, 0))
+}
+
+/// Read a Windows `.theme` file, or the theme in a `.themepack` archive
+///
+/// Archives are unpacked into a temporary folder, which the images are
+/// copied from when the migration is merged.
+fn windows_theme_file(path: &Path, imported: &Path) -> AppResult<Migration> {
+    let stem = path.file_stem().map_or_else(
+        || Tool::WindowsTheme.name().to_string(),
+        |stem| stem.to_string_lossy().to_string(),
+    );
+    let is_pack = path.extension().is_some_and(|extension| {
+        THEMEPACK_EXTENSIONS
+            .iter()
+            .any(|pack| extension.eq_ignore_ascii_case(pack))
+    });
+    if !is_pack {
+        let base = path.parent().unwrap_or(Path::new("."));
+        return Ok(windows_theme(
+            &theme_text(&fs::read(path)?),
+            &stem,
+            base,
+            imported,
+        ));
+    }
+
+    let unpacked = std::env::temp_dir().join(format!("aether-desk-theme-{}", std::process::id()));
+    unpack_themepack(path, &unpacked)?;
+    let theme = find_file(&unpacked, "theme")
+        .ok_or_else(|| AppError::ConfigError(format!("{} holds no .theme file", path.display())))?;
+    let base = theme.parent().unwrap_or(&unpacked);
+    Ok(windows_theme(
+        &theme_text(&fs::read(&theme)?),
+        &stem,
+        base,
+        imported,
+    ))
+}
+
+/// Unpack a themepack, which is a cabinet archive, with the system's tool
+fn unpack_themepack(pack: &Path, dir: &Path) -> AppResult<()> {
+    if dir.exists() {
+        fs::remove_dir_all(dir)?;
+    }
+    fs::create_dir_all(dir)?;
+
+    let (tool, output) = if cfg!(target_os = "windows") {
+        (
+            "expand",
+            Command::new("expand")
+                .arg(pack)
+                .arg("-F:*")
+                .arg(dir)
+                .output(),
+        )
+    } else {
+        (
+            "cabextract",
+            Command::new("cabextract")
+                .arg("-q")
+                .arg("-d")
+                .arg(dir)
+                .arg(pack)
+                .output(),
+        )
+    };
+    let output = output.map_err(|e| AppError::from_spawn(tool, e))?;
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(AppError::ConfigError(format!(
+            "{} couldn't unpack {}: {}",
+            tool,
+            pack.display(),
+            error.trim()
+        )));
+    }
+    Ok(())
+}
+
+/// Find the first file with an extension in a folder or the folders in it
+fn find_file(dir: &Path, extension: &str) -> Option<PathBuf> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .collect();
+    entries.sort();
+    let file = entries
+        .iter()
+        .find(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .is_some_and(|found| found.eq_ignore_ascii_case(extension))
+        })
+        .cloned();
+    file.or_else(|| {
+        entries
+            .iter()
+            .filter(|path| path.is_dir())
+            .find_map(|folder| find_file(folder, extension))
+    })
+}
+
+/// Decode a `.theme` file, which Windows saves as UTF-16 or in the local code page
+fn theme_text(bytes: &[u8]) -> String {
+    match bytes {
+        [0xFF, 0xFE, rest @ ..] => {
+            let units: Vec<u16> = rest
+                .chunks_exact(2)
+                .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+                .collect();
+            String::from_utf16_lossy(&units)
+        }
+        [0xEF, 0xBB, 0xBF, rest @ ..] => String::from_utf8_lossy(rest).to_string(),
+        _ => String::from_utf8_lossy(bytes).to_string(),
+    }
+}
+
+/// Read a Windows theme's wallpaper, fit and slideshow
+///
+/// Paths are looked up where the theme says, then by name next to it and in
+/// its `DesktopBackground` folder, where themepacks keep their images. The
+/// images are copied into a folder named after the theme under `imported`.
+fn windows_theme(text: &str, stem: &str, base: &Path, imported: &Path) -> Migration {
+    let mut migration = Migration::default();
+    let sections = ini_sections(text);
+    let empty = BTreeMap::new();
+    let section = |name: &str| sections.get(name).unwrap_or(&empty);
+    let (theme, desktop, slideshow) = (
+        section("theme"),
+        section("control panel\\desktop"),
+        section("slideshow"),
+    );
+    let search = [base.join("DesktopBackground"), base.to_path_buf()];
+
+    // Names like `@%SystemRoot%\System32\themeui.dll,-2013` point into Windows' resources
+    let name = theme
+        .get("displayname")
+        .filter(|name| !name.is_empty() && !name.starts_with('@'))
+        .map_or(stem, String::as_str);
+    let collection = imported.join(folder_name(name));
+
+    // The slideshow's own list, else every image in the folder it goes through
+    let mut images: Vec<PathBuf> = slideshow
+        .iter()
+        .filter(|(key, _)| key.starts_with("item") && key.ends_with("path"))
+        .filter_map(|(_, value)| find_theme_file(value, &search))
+        .collect();
+    if images.is_empty() {
+        let root = slideshow.get("imagesrootpath").and_then(|root| {
+            let expanded = PathBuf::from(expand_variables(root));
+            [expanded, base.join("DesktopBackground")]
+                .into_iter()
+                .find(|folder| folder.is_dir())
+        });
+        if let Some(root) = root {
+            images = fs::read_dir(root)
+                .into_iter()
+                .flatten()
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| WallpaperType::Static.accepts(path))
+                .collect();
+            images.sort();
+        }
+    }
+    let wallpaper = match desktop
+        .get("wallpaper")
+        .filter(|wallpaper| !wallpaper.is_empty())
+    {
+        Some(wallpaper) => {
+            let found = find_theme_file(wallpaper, &search);
+            if found.is_none() {
+                migration
+                    .notes
+                    .push(format!("The theme's wallpaper {} wasn't found", wallpaper));
+            }
+            found
+        }
+        None => None,
+    };
+
+    // Copies keep their names, numbered when two images share one
+    let mut copies: BTreeMap<PathBuf, PathBuf> = BTreeMap::new();
+    for image in wallpaper.iter().chain(&images) {
+        if copies.contains_key(image) {
+            continue;
+        }
+        let file_name = image
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let mut to = collection.join(&file_name);
+        let mut number = 1;
+        while copies.values().any(|taken| *taken == to) {
+            number += 1;
+            to = collection.join(format!("{}-{}", number, file_name));
+        }
+        copies.insert(image.clone(), to);
+    }
+
+    if !images.is_empty() {
+        let milliseconds: u64 = slideshow
+            .get("interval")
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(0);
+        let interval = match milliseconds {
+            0 => THEME_SLIDESHOW_MINUTES,
+            _ => ((milliseconds / 1000 + 30) / 60).max(1) as u32,
+        };
+        migration.rotation = Some(Rotation {
+            enabled: true,
+            interval,
+            folder: Some(collection.clone()),
+        });
+        if slideshow
+            .get("shuffle")
+            .is_some_and(|shuffle| shuffle == "1")
+        {
+            migration
+                .notes
+                .push("Windows shuffled the slideshow; it's shown in name order".to_string());
+        }
+    }
+    if slideshow.contains_key("rssfeed") {
+        migration
+            .notes
+            .push("The theme's RSS feed slideshow isn't imported".to_string());
+    }
+
+    // The wallpaper is applied from the copy, so the rotation carries on from it
+    if let Some(shown) = wallpaper
+        .as_ref()
+        .or(images.first())
+        .and_then(|image| copies.get(image))
+    {
+        let mut shown = WallpaperInfo::new(WallpaperType::Static, Some(shown.clone()), None);
+        shown.name = name.to_string();
+        migration.wallpaper = Some(shown);
+        migration.placement = theme_placement(desktop);
+    }
+    if [
+        "sounds",
+        "control panel\\cursors",
+        "control panel\\colors",
+        "visualstyles",
+    ]
+    .iter()
+    .any(|name| sections.contains_key(*name))
+    {
+        migration
+            .notes
+            .push("The theme's colors, sounds and cursors are left to Windows".to_string());
+    }
+    migration.files = copies.into_iter().collect();
+    migration
+}
+
+/// Map a theme's `WallpaperStyle` and `TileWallpaper` onto a fit mode and spanning
+fn theme_placement(desktop: &BTreeMap<String, String>) -> Option<(FitMode, bool)> {
+    let tiled = desktop.get("tilewallpaper").is_some_and(|tile| tile == "1");
+    match desktop.get("wallpaperstyle")?.as_str() {
+        "0" if tiled => Some((FitMode::Tile, false)),
+        "0" => Some((FitMode::Center, false)),
+        "2" => Some((FitMode::Stretch, false)),
+        "6" => Some((FitMode::Fit, false)),
+        "10" => Some((FitMode::Fill, false)),
+        "22" => Some((FitMode::Fill, true)),
+        _ => None,
+    }
+}
+
+/// Find a file a theme names, by its path or by its name in the folders searched
+fn find_theme_file(value: &str, search: &[PathBuf]) -> Option<PathBuf> {
+    let expanded = PathBuf::from(expand_variables(value));
+    if expanded.is_file() {
+        return Some(expanded);
+    }
+    let file_name = value
+        .rsplit(['\\', '/'])
+        .next()
+        .filter(|name| !name.is_empty())?;
+    search
+        .iter()
+        .map(|folder| folder.join(file_name))
+        .find(|path| path.is_file())
+}
+
+/// Expand the `%VARIABLE%`s of a Windows path, leaving unknown ones as they are
+fn expand_variables(value: &str) -> String {
+    let mut expanded = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find('%') {
+        let Some(length) = rest[start + 1..].find('%') else {
+            break;
+        };
+        let variable = &rest[start + 1..start + 1 + length];
+        expanded.push_str(&rest[..start]);
+        match std::env::var(variable) {
+            Ok(value) if !variable.is_empty() => expanded.push_str(&value),
+            _ => expanded.push_str(&rest[start..start + length + 2]),
+        }
+        rest = &rest[start + length + 2..];
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+/// Make a theme's name safe to use as a folder name
+fn folder_name(name: &str) -> String {
+    let name: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, ' ' | '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    match name.trim_matches('.') {
+        "" => "theme".to_string(),
+        name => name.to_string(),
+    }
+}
+
+/// Read the sections of an INI file, with lowercase section names and keys
+fn ini_sections(text: &str) -> BTreeMap<String, BTreeMap<String, String>> {
+    let mut sections: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+    let mut current = String::new();
+    for line in text.lines().map(str::trim) {
+        if let Some(name) = line
+            .strip_prefix('[')
+            .and_then(|line| line.strip_suffix(']'))
+        {
+            current = name.trim().to_lowercase();
+        } else if let Some((key, value)) = line.split_once('=').filter(|_| !line.starts_with(';')) {
+            sections.entry(current.clone()).or_default().insert(
+                key.trim().to_lowercase(),
+                value.trim().trim_matches('"').to_string(),
+            );
+        }
+    }
+    sections
+}
+
+/// Make a schedule item showing a file at a time
+fn schedule_item(time: NaiveTime, path: PathBuf) -> ScheduleItem {
+    let wallpaper_type = WallpaperType::from_path(&path).unwrap_or(WallpaperType::Static);
+    ScheduleItem {
+        trigger: TriggerType::Time(time),
+        wallpaper: WallpaperInfo::new(wallpaper_type, Some(path), None),
+        enabled: true,
+    }
+}
+
+/// Read `key = value` lines, ignoring sections, comments and quotes
+fn key_values(text: &str) -> std::collections::BTreeMap<String, String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with(['#', ';', '[']))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| {
+            (
+                key.trim().to_string(),
+                value.trim().trim_matches('"').to_string(),
+            )
+        })
+        .collect()
+}
+
+/// Get the text of the first element with a tag name
+fn tag<'a>(xml: &'a str, name: &str) -> Option<&'a str> {
+    elements(xml, &[name])
+        .into_iter()
+        .next()
+        .map(|(_, body)| body)
+}
+
+/// Get the elements with any of the tag names, in document order, with their content
+///
+/// Enough for timed backgrounds, which don't nest an element in one of the same name.
+fn elements<'a, 'n>(xml: &'a str, names: &[&'n str]) -> Vec<(&'n str, &'a str)> {
+    let mut found = Vec::new();
+    let mut rest = xml;
+    while let Some(open) = rest.find('<') {
+        rest = &rest[open + 1..];
+        let name_end = rest
+            .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+            .unwrap_or(rest.len());
+        let Some(name) = names.iter().find(|name| **name == &rest[..name_end]) else {
+            continue;
+        };
+        let Some(content_start) = rest.find('>').map(|end| end + 1) else {
+            break;
+        };
+        let closing = format!("</{}>", name);
+        let Some(content_end) = rest[content_start..].find(&closing) else {
+            break;
+        };
+        found.push((*name, &rest[content_start..content_start + content_end]));
+        rest = &rest[content_start + content_end + closing.len()..];
+    }
+    found
+}
+
+/// Get the path of a `<file>`, the first size when several are given
+fn file_text(file: &str) -> &str {
+    tag(file, "size").unwrap_or(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_timed_background() {
+        let xml = r#"<background>
+  <starttime><year>2011</year><month>11</month><day>24</day><hour>7</hour><minute>00</minute><second>00</second></starttime>
+  <static><duration>39600.0</duration><file>day.jpg</file></static>
+  <transition type="overlay"><duration>3600.0</duration><from>day.jpg</from><to>night.jpg</to></transition>
+  <static><duration>39600.0</duration><file><size width="1920" height="1080">/usr/share/backgrounds/night.jpg</size></file></static>
+  <transition type="overlay"><duration>3600.0</duration><from>night.jpg</from><to>day.jpg</to></transition>
+</background>"#;
+        let migration = timed_background(xml, Path::new("/walls"));
+        let times: Vec<_> = migration
+            .schedule
+            .iter()
+            .map(|item| (item.trigger.clone(), item.wallpaper.path.clone().unwrap()))
+            .collect();
+        assert_eq!(
+            times,
+            vec![
+                (
+                    TriggerType::Time(NaiveTime::from_hms_opt(7, 0, 0).unwrap()),
+                    PathBuf::from("/walls/day.jpg")
+                ),
+                (
+                    TriggerType::Time(NaiveTime::from_hms_opt(19, 0, 0).unwrap()),
+                    PathBuf::from("/usr/share/backgrounds/night.jpg")
+                ),
+            ]
+        );
+
+        // A shorter cycle is a rotation
+        let xml = "<background><static><duration>600</duration><file>/walls/a.jpg</file></static><static><duration>1200</duration><file>/walls/b.jpg</file></static></background>";
+        let migration = timed_background(xml, Path::new("/"));
+        assert!(migration.schedule.is_empty());
+        assert_eq!(
+            migration.rotation,
+            Some(Rotation {
+                enabled: true,
+                interval: 15,
+                folder: Some(PathBuf::from("/walls"))
+            })
+        );
+    }
+
+    #[test]
+    fn test_time_in_name() {
+        let at = |hour, minute| NaiveTime::from_hms_opt(hour, minute, 0);
+        assert_eq!(time_in_name("0730"), at(7, 30));
+        assert_eq!(time_in_name("18-45 city"), at(18, 45));
+        assert_eq!(time_in_name("7h05"), at(7, 5));
+        assert_eq!(time_in_name("03_evening"), at(18, 0));
+        assert_eq!(time_in_name("lake-night"), at(21, 0));
+        assert_eq!(time_in_name("2560x1440"), None);
+        assert_eq!(time_in_name("nightingale"), None);
+    }
+
+    #[test]
+    fn test_variety() {
+        let home = tempdir().unwrap();
+        let favorites = home.path().join("favs");
+        fs::create_dir(&favorites).unwrap();
+        fs::write(favorites.join("liked.jpg"), b"").unwrap();
+        let conf = format!(
+            r#"
+change_enabled = True
+change_interval = 900
+favorites_folder = "{}"
+quotes_enabled = False
+
+[sources]
+src1 = True|favorites|The Favorites folder
+src2 = True|folder|~/Pictures/Walls
+src3 = False|folder|/mnt/old
+src4 = True|wallhaven|nature
+"#,
+            favorites.display()
+        );
+
+        let migration = variety(&conf, home.path());
+        assert_eq!(
+            migration.rotation,
+            Some(Rotation {
+                enabled: true,
+                interval: 15,
+                folder: Some(favorites.clone())
+            })
+        );
+        assert_eq!(migration.favorites, vec![favorites.join("liked.jpg")]);
+        assert!(migration
+            .notes
+            .iter()
+            .any(|note| note.contains("Pictures/Walls")));
+        assert!(migration
+            .notes
+            .iter()
+            .any(|note| note.contains("\"nature\"")));
+    }
+
+    #[test]
+    fn test_windows_theme() {
+        let base = tempdir().unwrap();
+        let backgrounds = base.path().join("DesktopBackground");
+        fs::create_dir(&backgrounds).unwrap();
+        for name in ["img1.jpg", "img2.jpg"] {
+            fs::write(backgrounds.join(name), b"").unwrap();
+        }
+        let theme = r#"; Copyright © Microsoft Corp.
+[Theme]
+DisplayName=Nature: Lakes
+[Control Panel\Desktop]
+Wallpaper=%LocalAppData%\Microsoft\Windows\Themes\Lakes\DesktopBackground\img2.jpg
+WallpaperStyle=6
+TileWallpaper=0
+[Slideshow]
+Interval=600000
+Shuffle=1
+ImagesRootPath=%LocalAppData%\Microsoft\Windows\Themes\Lakes\DesktopBackground
+[Sounds]
+SchemeName=@%SystemRoot%\System32\mmres.dll,-800
+"#;
+        let imported = Path::new("/imported");
+        let migration = windows_theme(
+            &theme_text(theme.as_bytes()),
+            "lakes",
+            base.path(),
+            imported,
+        );
+
+        let collection = imported.join("Nature_ Lakes");
+        assert_eq!(
+            migration.rotation,
+            Some(Rotation {
+                enabled: true,
+                interval: 10,
+                folder: Some(collection.clone())
+            })
+        );
+        assert_eq!(
+            migration.files,
+            vec![
+                (backgrounds.join("img1.jpg"), collection.join("img1.jpg")),
+                (backgrounds.join("img2.jpg"), collection.join("img2.jpg")),
+            ]
+        );
+        let wallpaper = migration.wallpaper.unwrap();
+        assert_eq!(wallpaper.path, Some(collection.join("img2.jpg")));
+        assert_eq!(wallpaper.name, "Nature: Lakes");
+        assert_eq!(migration.placement, Some((FitMode::Fit, false)));
+        assert_eq!(migration.notes.len(), 2);
+
+        // Windows saves themes as UTF-16 too
+        let utf16: Vec<u8> = [0xFF, 0xFE]
+            .into_iter()
+            .chain("[Theme]".encode_utf16().flat_map(u16::to_le_bytes))
+            .collect();
+        assert_eq!(theme_text(&utf16), "[Theme]");
+    }
+
+    #[test]
+    fn test_komorebi_and_merge() {
+        let library = tempdir().unwrap();
+        for (name, config) in [
+            ("foggy_mountain", "[Info]\nWallpaperType=image\n"),
+            (
+                "rain",
+                "[Info]\nWallpaperType=video\nVideoFileName=rain.mp4\n",
+            ),
+        ] {
+            let folder = library.path().join(name);
+            fs::create_dir(&folder).unwrap();
+            fs::write(folder.join("config"), config).unwrap();
+        }
+        fs::write(library.path().join("foggy_mountain/bg.jpg"), b"").unwrap();
+        fs::write(library.path().join("rain/rain.mp4"), b"").unwrap();
+
+        let migration = komorebi(
+            "[KomorebiProperties]\nWallpaperName=rain\nTimeTwentyFour=true\n",
+            &[library.path().to_path_buf()],
+        );
+        let wallpaper = migration.wallpaper.clone().unwrap();
+        assert_eq!(wallpaper.r#type, WallpaperType::Video);
+        assert_eq!(wallpaper.name, "rain");
+        assert_eq!(migration.favorites.len(), 2);
+        assert_eq!(migration.notes.len(), 1);
+
+        let mut config = Config::default();
+        migration.merge_into(&mut config).unwrap();
+        migration.merge_into(&mut config).unwrap();
+        assert_eq!(config.wallpaper.favorites.len(), 2);
+        assert_eq!(config.wallpaper.wallpaper_type, WallpaperType::Video);
+    }
+}
@@ -1,19 +1,78 @@
+pub mod adjustments;
+pub mod audio_overlay;
+pub mod auto_change;
+pub mod autopause;
+pub mod benchmark;
 pub mod config;
+pub mod config_history;
+pub mod crash;
+pub mod current;
 pub mod error;
+pub mod events;
+pub mod focus;
+pub mod format;
+pub mod history;
+pub mod hooks;
+pub mod http;
+pub mod image_format;
+pub mod image_pipeline;
+pub mod logging;
+pub mod migrate;
+pub mod orphans;
+pub mod pack;
+pub mod palette;
 pub mod performance;
+pub mod placement;
 pub mod plugin;
+pub mod process;
+pub mod profile;
+pub mod qr;
+pub mod quality;
 pub mod resource_manager;
+pub mod runtime;
 pub mod scheduler;
+pub mod secrets;
+pub mod span;
+pub mod tags;
+pub mod theme;
+pub mod theming;
 pub mod types;
+pub mod validate;
+pub mod virtual_camera;
+pub mod watchdog;
+pub mod watcher;
+pub mod weather;
 pub mod widget;
+pub mod zip;
 
-pub use config::{Config, WallpaperType, Theme};
-pub use error::AppError;
-pub use plugin::{PluginManager};
-pub use resource_manager::{ResourceManager, ResourceLimits, ResourceUsage};
+pub use adjustments::{AdjustmentStore, Adjustments};
+pub use audio_overlay::AudioOverlay;
+pub use auto_change::AutoChange;
+pub use autopause::AutoPause;
+pub use config::{Config, Theme, WallpaperType};
+pub use current::{CurrentWallpaperState, CurrentWallpaperStore};
+pub use error::{AppError, ErrorContext};
+pub use events::{Event, EventLevel, EventLog};
+pub use focus::FocusMode;
+pub use history::WallpaperHistory;
+pub use hooks::ChangeHooks;
+pub use http::{HttpClient, RateLimit};
+pub use orphans::Orphan;
+pub use palette::PaletteStore;
+pub use plugin::PluginManager;
+pub use process::{PlayerSlot, ProcessController, ProcessHandle, ProcessInfo};
+pub use profile::ProfileStore;
+pub use quality::AdaptiveQuality;
+pub use resource_manager::{ResourceMonitor, WallpaperUsage};
 pub use scheduler::{ScheduleItem, TriggerType, WallpaperScheduler};
+pub use secrets::SecretStore;
+pub use tags::{TagEntry, TagStore};
 pub use types::WallpaperInfo;
+pub use virtual_camera::VirtualCamera;
+pub use watchdog::Watchdog;
+pub use watcher::{ConfigFile, ConfigWatcher};
+pub use weather::WeatherEffects;
 pub use widget::{WidgetConfig, WidgetManager, WidgetPosition, WidgetSize, WidgetType};
 
 /// Application result type
-pub type AppResult<T> = Result<T, AppError>; 
\ No newline at end of file
+pub type AppResult<T> = Result<T, AppError>;
@@ -0,0 +1,242 @@
+//! Players left running by a previous run
+//!
+//! When Aether-Desk crashes, the players it started keep playing. On the next
+//! start they are found from the PID file the last run left behind, or by a
+//! player started with arguments only Aether-Desk uses, and the user decides
+//! whether to stop them or keep them.
+use crate::core::process::PidRecord;
+use crate::core::{Config, WallpaperType};
+use crate::wallpapers::video_wallpaper::IPC_PREFIX;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use sysinfo::{Pid, System};
+
+/// Executables that play wallpapers
+const PLAYERS: &[&str] = &["mpv", "firefox", "msedge", "shadertoy"];
+
+/// Player left running by a previous run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Orphan {
+    /// Process ID
+    pub pid: u32,
+
+    /// Executable name
+    pub name: String,
+
+    /// What the process was used for
+    pub role: String,
+
+    /// Command line
+    pub command: String,
+}
+
+/// Get the folders wallpapers are played from
+pub fn library_dirs(config: &Config) -> Vec<PathBuf> {
+    let current = config
+        .wallpaper
+        .current_path
+        .as_ref()
+        .and_then(|path| Path::new(path).parent().map(Path::to_path_buf));
+    let folder = config
+        .wallpaper
+        .auto_change
+        .folder
+        .as_ref()
+        .map(PathBuf::from);
+    current
+        .into_iter()
+        .chain(folder)
+        .chain([
+            config.get_downloads_dir(),
+            config.get_live_earth_dir(),
+            Config::get_generated_dir(),
+        ])
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .collect()
+}
+
+/// Get the pages the web wallpapers show, on every monitor
+pub fn web_urls(config: &Config) -> Vec<String> {
+    let current = (config.wallpaper.wallpaper_type == WallpaperType::Web)
+        .then(|| config.wallpaper.current_path.clone())
+        .flatten();
+    let monitors = config
+        .wallpaper
+        .monitor_wallpapers
+        .values()
+        .filter(|wallpaper| wallpaper.r#type == WallpaperType::Web)
+        .filter_map(|wallpaper| wallpaper.url.clone());
+    current.into_iter().chain(monitors).collect()
+}
+
+/// Find the players a previous run left running
+///
+/// `web_urls` are the pages of the web wallpapers, which tell their browser
+/// windows apart from the user's own.
+pub fn find(pid_file: &Path, web_urls: &[String]) -> Vec<Orphan> {
+    let records: Vec<PidRecord> = fs::read_to_string(pid_file)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+
+    let mut system = System::new();
+    system.refresh_processes();
+    let own_pid = std::process::id();
+
+    let mut orphans = Vec::new();
+    for (pid, process) in system.processes() {
+        let pid = pid.as_u32();
+        if pid == own_pid {
+            continue;
+        }
+
+        let record = records.iter().find(|record| record.pid == pid);
+        let role = match record {
+            Some(record) if is_same_process(record, process.name(), process.start_time()) => {
+                record.role.clone()
+            }
+            _ if is_player(process.name()) && is_wallpaper_player(process.cmd(), web_urls) => {
+                "wallpaper player".to_string()
+            }
+            _ => continue,
+        };
+
+        orphans.push(Orphan {
+            pid,
+            name: process.name().to_string(),
+            role,
+            command: process.cmd().join(" "),
+        });
+    }
+    orphans.sort_by_key(|orphan| orphan.pid);
+    orphans
+}
+
+/// Stop an orphaned player
+///
+/// Returns false when it has already exited or can't be stopped.
+pub fn terminate(orphan: &Orphan) -> bool {
+    let mut system = System::new();
+    let pid = Pid::from_u32(orphan.pid);
+    system.refresh_process(pid);
+    system.process(pid).map_or(false, |process| {
+        process.name() == orphan.name && process.kill()
+    })
+}
+
+/// Check whether a running process is the one a PID file record describes
+fn is_same_process(record: &PidRecord, name: &str, start_time: u64) -> bool {
+    // Process IDs get reused, so the start time has to match too
+    name.trim_end_matches(".exe") == record.name && start_time == record.start_time
+}
+
+/// Check whether an executable is a wallpaper player
+fn is_player(name: &str) -> bool {
+    PLAYERS.contains(&name.trim_end_matches(".exe"))
+}
+
+/// Check whether a command line is one Aether-Desk starts players with
+///
+/// Playing a file from the wallpaper folders isn't enough, as the user may
+/// play their own videos from the same folders.
+fn is_wallpaper_player(command: &[String], web_urls: &[String]) -> bool {
+    let args = command.get(1..).unwrap_or_default();
+    // Video wallpapers are controlled over an IPC endpoint named for Aether-Desk
+    let video = args
+        .iter()
+        .any(|arg| arg.starts_with("--input-ipc-server=") && arg.contains(IPC_PREFIX));
+    // The frame player feeds raw RGBA frames over stdin
+    let frames = args
+        .iter()
+        .any(|arg| arg == "--demuxer-rawvideo-mp-format=rgba")
+        && args.last().is_some_and(|arg| arg == "-");
+    // Web wallpapers open their page in a new browser window
+    let web = args
+        .windows(2)
+        .any(|pair| pair[0] == "--new-window" && web_urls.contains(&pair[1]));
+    video || frames || web
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_same_process() {
+        let record = PidRecord {
+            pid: 42,
+            name: "mpv".to_string(),
+            role: "video wallpaper".to_string(),
+            start_time: 1000,
+        };
+        assert!(is_same_process(&record, "mpv", 1000));
+        assert!(is_same_process(&record, "mpv.exe", 1000));
+        assert!(!is_same_process(&record, "mpv", 2000));
+        assert!(!is_same_process(&record, "bash", 1000));
+    }
+
+    #[test]
+    fn test_is_wallpaper_player() {
+        let command = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        let ipc = format!("--input-ipc-server=/tmp/{}42-0", IPC_PREFIX);
+        assert!(is_wallpaper_player(
+            &command(&["mpv", "--loop-file=inf", &ipc, "/home/me/Videos/sea.mp4"]),
+            &[]
+        ));
+        assert!(is_wallpaper_player(
+            &command(&[
+                "mpv",
+                "--demuxer=rawvideo",
+                "--demuxer-rawvideo-mp-format=rgba",
+                "--fs",
+                "-"
+            ]),
+            &[]
+        ));
+        assert!(!is_wallpaper_player(
+            &command(&["mpv", "/home/me/Videos/film.mkv"]),
+            &[]
+        ));
+        assert!(!is_wallpaper_player(
+            &command(&[
+                "mpv",
+                "--input-ipc-server=/tmp/mpvsocket",
+                "/home/me/Videos/film.mkv"
+            ]),
+            &[]
+        ));
+
+        let url = &["https://example.com/wallpaper".to_string()];
+        assert!(is_wallpaper_player(
+            &command(&["firefox", "--new-window", "https://example.com/wallpaper"]),
+            url
+        ));
+        assert!(!is_wallpaper_player(
+            &command(&["firefox", "--new-window", "https://example.com/news"]),
+            url
+        ));
+        assert!(!is_wallpaper_player(&command(&["firefox"]), url));
+        assert!(!is_wallpaper_player(&command(&[]), url));
+
+        assert!(is_player("mpv.exe"));
+        assert!(is_player("msedge.exe"));
+        assert!(!is_player("vlc"));
+    }
+
+    #[test]
+    fn test_find_ignores_own_process() {
+        let dir = tempfile::tempdir().unwrap();
+        let pid_file = dir.path().join("players.json");
+        let own = PidRecord {
+            pid: std::process::id(),
+            name: "test".to_string(),
+            role: "test".to_string(),
+            start_time: 0,
+        };
+        fs::write(&pid_file, serde_json::to_string(&vec![own]).unwrap()).unwrap();
+        assert!(find(&pid_file, &[])
+            .iter()
+            .all(|orphan| orphan.pid != std::process::id()));
+    }
+}
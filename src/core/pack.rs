@@ -0,0 +1,564 @@
+//! Wallpaper packs: curated sets shared as one `.aetherpack` file
+//!
+//! A pack is a zip archive holding `manifest.json`, the wallpapers under
+//! `wallpapers/`, a thumbnail of each image under `thumbnails/`, the folder
+//! of each local web page under `assets/<n>/`, and suggested schedule items
+//! pointing at the wallpapers by their place in the manifest. Shaders travel
+//! as wallpaper files, slideshows as folders of them, and remote web pages
+//! and built-in wallpapers by their URL or pattern alone.
+//!
+//! Installing extracts a pack into its own folder under the packs folder,
+//! replacing an earlier install of the same pack, and the gallery picks the
+//! installed packs up again on every start.
+use crate::core::image_pipeline::{Encoding, ImagePipeline};
+use crate::core::zip::{safe_path, ZipReader, ZipWriter};
+use crate::core::{AppError, AppResult, ScheduleItem, TriggerType, WallpaperInfo, WallpaperType};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File extension of packs
+pub const EXTENSION: &str = "aetherpack";
+
+/// Name of the manifest inside a pack
+const MANIFEST: &str = "manifest.json";
+
+/// Version of the manifest written; packs of later versions are refused
+const FORMAT: u32 = 1;
+
+/// Largest manifest read, in bytes
+const MAX_MANIFEST: u64 = 4 * 1024 * 1024;
+
+/// Most files packed from the folder of a local web page
+const MAX_ASSETS: usize = 500;
+
+/// Largest edge of a thumbnail in pixels
+const THUMBNAIL_SIZE: u32 = 256;
+
+/// What a pack holds, as written in its manifest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Version of the format
+    pub format: u32,
+
+    /// Name of the pack
+    pub name: String,
+
+    /// Who put it together
+    #[serde(default)]
+    pub author: String,
+
+    /// What's in it
+    #[serde(default)]
+    pub description: String,
+
+    /// Wallpapers, in order
+    pub wallpapers: Vec<ManifestWallpaper>,
+
+    /// Suggested schedule
+    #[serde(default)]
+    pub schedule: Vec<ManifestScheduleItem>,
+}
+
+/// Wallpaper in a manifest
+///
+/// Paths are names of entries in the pack.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestWallpaper {
+    /// Wallpaper name
+    pub name: String,
+
+    /// Wallpaper description
+    #[serde(default)]
+    pub description: String,
+
+    /// Wallpaper author
+    #[serde(default)]
+    pub author: String,
+
+    /// Wallpaper version
+    #[serde(default)]
+    pub version: String,
+
+    /// Wallpaper type
+    #[serde(rename = "type")]
+    pub wallpaper_type: WallpaperType,
+
+    /// Wallpaper file, or folder for slideshows
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+
+    /// Remote web page, or the pattern of procedural wallpapers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+
+    /// Local web page, shown from where it's extracted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page: Option<String>,
+
+    /// Thumbnail image
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<String>,
+}
+
+/// Suggested schedule item in a manifest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestScheduleItem {
+    /// When it triggers
+    pub trigger: TriggerType,
+
+    /// Index of the wallpaper in the manifest
+    pub wallpaper: usize,
+
+    /// Whether it starts enabled
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+/// Wallpaper of an installed pack
+#[derive(Debug, Clone)]
+pub struct PackWallpaper {
+    /// Wallpaper, pointing into the pack's folder
+    pub wallpaper: WallpaperInfo,
+
+    /// Thumbnail, if the pack has one
+    pub thumbnail: Option<PathBuf>,
+}
+
+/// Installed pack
+#[derive(Debug, Clone)]
+pub struct Pack {
+    /// Manifest as read
+    pub manifest: Manifest,
+
+    /// Folder it's installed in
+    pub folder: PathBuf,
+
+    /// Wallpapers, in the manifest's order
+    pub wallpapers: Vec<PackWallpaper>,
+
+    /// Suggested schedule, pointing at the installed wallpapers
+    pub schedule: Vec<ScheduleItem>,
+}
+
+/// Default for fields that are on unless turned off
+fn default_true() -> bool {
+    true
+}
+
+/// Write wallpapers to a pack, with the schedule items showing them
+///
+/// Schedule items for other wallpapers are left out. Returns the manifest written.
+pub fn export(
+    path: &Path,
+    name: &str,
+    wallpapers: &[WallpaperInfo],
+    schedule: &[ScheduleItem],
+) -> AppResult<Manifest> {
+    let mut zip = ZipWriter::create(path)?;
+    let mut manifest = Manifest {
+        format: FORMAT,
+        name: name.to_string(),
+        author: String::new(),
+        description: String::new(),
+        wallpapers: Vec::new(),
+        schedule: Vec::new(),
+    };
+    let mut used = HashSet::new();
+
+    for (index, wallpaper) in wallpapers.iter().enumerate() {
+        let mut packed = ManifestWallpaper {
+            name: wallpaper.name.clone(),
+            description: wallpaper.description.clone(),
+            author: wallpaper.author.clone(),
+            version: wallpaper.version.clone(),
+            wallpaper_type: wallpaper.r#type.clone(),
+            file: None,
+            url: wallpaper.url.clone(),
+            page: None,
+            thumbnail: None,
+        };
+
+        match (&wallpaper.path, local_page(wallpaper)) {
+            (Some(file), _) if file.is_file() => {
+                let entry = format!("wallpapers/{}", unique_name(file, &mut used));
+                zip.add_file(&entry, file)?;
+                packed.file = Some(entry);
+            }
+            (Some(folder), _) if folder.is_dir() => {
+                let entry = format!("wallpapers/{}", unique_name(folder, &mut used));
+                for file in files_in(folder, false)? {
+                    let relative = file.strip_prefix(folder).unwrap_or(&file);
+                    zip.add_file(&format!("{}/{}", entry, entry_name(relative)), &file)?;
+                }
+                packed.file = Some(entry);
+            }
+            (Some(missing), _) => {
+                return Err(AppError::WallpaperError(format!(
+                    "{} no longer exists",
+                    missing.display()
+                )));
+            }
+            (None, Some(page)) => {
+                let folder = page.parent().unwrap_or(Path::new("/"));
+                let files = files_in(folder, true)?;
+                if files.len() > MAX_ASSETS {
+                    return Err(AppError::WallpaperError(format!(
+                        "The folder of {} has over {} files; move the page into a folder of its own",
+                        page.display(), MAX_ASSETS
+                    )));
+                }
+                for file in files {
+                    let relative = file.strip_prefix(folder).unwrap_or(&file);
+                    zip.add_file(&format!("assets/{}/{}", index, entry_name(relative)), &file)?;
+                }
+                let relative = page.strip_prefix(folder).unwrap_or(&page);
+                packed.page = Some(format!("assets/{}/{}", index, entry_name(relative)));
+                packed.url = None;
+            }
+            (None, None) => {}
+        }
+
+        if let Some(thumbnail) = wallpaper
+            .path
+            .as_deref()
+            .filter(|_| wallpaper.r#type == WallpaperType::Static)
+            .and_then(thumbnail)
+        {
+            let entry = format!("thumbnails/{}.png", index);
+            zip.add_bytes(&entry, &thumbnail)?;
+            packed.thumbnail = Some(entry);
+        }
+        manifest.wallpapers.push(packed);
+    }
+
+    for item in schedule {
+        if let Some(index) = wallpapers
+            .iter()
+            .position(|wallpaper| same_wallpaper(wallpaper, &item.wallpaper))
+        {
+            manifest.schedule.push(ManifestScheduleItem {
+                trigger: item.trigger.clone(),
+                wallpaper: index,
+                enabled: item.enabled,
+            });
+        }
+    }
+
+    zip.add_bytes(MANIFEST, &serde_json::to_vec_pretty(&manifest)?)?;
+    zip.finish()?;
+    Ok(manifest)
+}
+
+/// Install a pack into its own folder under `packs_dir`
+///
+/// The pack is extracted beside its folder first, so a broken pack leaves an
+/// earlier install of it alone.
+pub fn install(path: &Path, packs_dir: &Path) -> AppResult<Pack> {
+    let mut zip = ZipReader::open(path)?;
+    let entry = zip.entry(MANIFEST).cloned().ok_or_else(|| {
+        AppError::WallpaperError(format!("{} is not a wallpaper pack", path.display()))
+    })?;
+    let manifest: Manifest = serde_json::from_slice(&zip.read(&entry, MAX_MANIFEST)?)?;
+    check_format(&manifest)?;
+
+    let folder = packs_dir.join(folder_name(&manifest.name));
+    let staging = folder.with_extension("installing");
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+    let entries: Vec<_> = zip
+        .entries()
+        .iter()
+        .filter(|entry| !entry.name.ends_with('/'))
+        .cloned()
+        .collect();
+    let extracted = entries
+        .iter()
+        .try_for_each(|entry| zip.extract(entry, &staging).map(|_| ()));
+    if let Err(e) = extracted.and_then(|_| open(&staging)) {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(e);
+    }
+
+    if folder.exists() {
+        fs::remove_dir_all(&folder)?;
+    }
+    fs::rename(&staging, &folder)?;
+    open(&folder)
+}
+
+/// Read an installed pack
+pub fn open(folder: &Path) -> AppResult<Pack> {
+    let manifest: Manifest = serde_json::from_slice(&fs::read(folder.join(MANIFEST))?)?;
+    check_format(&manifest)?;
+
+    let resolve = |entry: &Option<String>| -> AppResult<Option<PathBuf>> {
+        entry
+            .as_deref()
+            .map(|entry| Ok(folder.join(safe_path(entry)?)))
+            .transpose()
+    };
+    let mut wallpapers = Vec::new();
+    for packed in &manifest.wallpapers {
+        let path = resolve(&packed.file)?;
+        if let Some(missing) = path.as_ref().filter(|path| !path.exists()) {
+            return Err(AppError::WallpaperError(format!(
+                "{} is missing from the pack",
+                missing.display()
+            )));
+        }
+        let url = match resolve(&packed.page)? {
+            Some(page) => Some(format!("file://{}", page.display())),
+            None => packed.url.clone(),
+        };
+        wallpapers.push(PackWallpaper {
+            wallpaper: WallpaperInfo {
+                name: packed.name.clone(),
+                description: packed.description.clone(),
+                author: packed.author.clone(),
+                version: packed.version.clone(),
+                r#type: packed.wallpaper_type.clone(),
+                path,
+                url,
+                monitor: None,
+            },
+            thumbnail: resolve(&packed.thumbnail)?.filter(|thumbnail| thumbnail.is_file()),
+        });
+    }
+
+    let schedule = manifest
+        .schedule
+        .iter()
+        .filter_map(|item| {
+            Some(ScheduleItem {
+                trigger: item.trigger.clone(),
+                wallpaper: wallpapers.get(item.wallpaper)?.wallpaper.clone(),
+                enabled: item.enabled,
+            })
+        })
+        .collect();
+    Ok(Pack {
+        manifest,
+        folder: folder.to_path_buf(),
+        wallpapers,
+        schedule,
+    })
+}
+
+/// Read every pack installed under `packs_dir`, skipping broken ones
+pub fn installed(packs_dir: &Path) -> Vec<Pack> {
+    let Ok(entries) = fs::read_dir(packs_dir) else {
+        return Vec::new();
+    };
+    let mut folders: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir() && path.extension().is_none())
+        .collect();
+    folders.sort();
+    folders
+        .into_iter()
+        .filter_map(|folder| {
+            open(&folder)
+                .map_err(|e| warn!("Skipping wallpaper pack {}: {}", folder.display(), e))
+                .ok()
+        })
+        .collect()
+}
+
+/// Check whether two wallpapers are the same, monitor aside
+pub fn same_wallpaper(a: &WallpaperInfo, b: &WallpaperInfo) -> bool {
+    a.r#type == b.r#type && a.path == b.path && a.url == b.url
+}
+
+/// Refuse manifests of a later version
+fn check_format(manifest: &Manifest) -> AppResult<()> {
+    if manifest.format > FORMAT {
+        return Err(AppError::WallpaperError(format!(
+            "{} needs a newer Aether-Desk (pack format {})",
+            manifest.name, manifest.format
+        )));
+    }
+    Ok(())
+}
+
+/// Name the folder of an installed pack after it, as one safe path component
+fn folder_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' || c == ' ' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    match name.trim() {
+        "" => "pack".to_string(),
+        name => name.to_string(),
+    }
+}
+
+/// Get the page of a web wallpaper showing a local file
+fn local_page(wallpaper: &WallpaperInfo) -> Option<PathBuf> {
+    let url = wallpaper
+        .url
+        .as_deref()
+        .filter(|_| wallpaper.r#type == WallpaperType::Web)?;
+    let page = PathBuf::from(url.strip_prefix("file://")?);
+    page.is_file().then_some(page)
+}
+
+/// Pick an entry name for a file, numbering names already used
+fn unique_name(path: &Path, used: &mut HashSet<String>) -> String {
+    let stem = path.file_stem().map_or("wallpaper".to_string(), |stem| {
+        stem.to_string_lossy().to_string()
+    });
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+    let mut name = format!("{}{}", stem, extension);
+    let mut number = 2;
+    while !used.insert(name.clone()) {
+        name = format!("{}-{}{}", stem, number, extension);
+        number += 1;
+    }
+    name
+}
+
+/// Write a relative path with forward slashes, as entry names are
+fn entry_name(relative: &Path) -> String {
+    relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// List the files in a folder, sorted, and in its subfolders when `recursive`
+fn files_in(folder: &Path, recursive: bool) -> AppResult<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(folder)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        if file_type.is_file() {
+            files.push(entry.path());
+        } else if file_type.is_dir() && recursive {
+            files.extend(files_in(&entry.path(), true)?);
+            if files.len() > MAX_ASSETS {
+                break;
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Make a PNG thumbnail of an image, or None when it can't be read
+fn thumbnail(path: &Path) -> Option<Vec<u8>> {
+    ImagePipeline::open(path)
+        .and_then(|image| image.thumbnail(THUMBNAIL_SIZE).encode(Encoding::Png))
+        .map_err(|e| warn!("No thumbnail for {}: {}", path.display(), e))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::ProceduralPattern;
+    use chrono::NaiveTime;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_export_and_install() {
+        let dir = tempdir().unwrap();
+        let image = dir.path().join("lake.png");
+        image::RgbImage::new(640, 480).save(&image).unwrap();
+        let site = dir.path().join("site");
+        fs::create_dir_all(site.join("js")).unwrap();
+        fs::write(site.join("index.html"), "<script src=js/app.js></script>").unwrap();
+        fs::write(site.join("js/app.js"), "").unwrap();
+
+        let lake = WallpaperInfo::new(WallpaperType::Static, Some(image.clone()), None);
+        let page = WallpaperInfo::new(
+            WallpaperType::Web,
+            None,
+            Some(format!("file://{}", site.join("index.html").display())),
+        );
+        let wallpapers = vec![
+            lake.clone(),
+            page,
+            WallpaperInfo::procedural(ProceduralPattern::ALL[0]),
+        ];
+        let morning = ScheduleItem {
+            trigger: TriggerType::Time(NaiveTime::from_hms_opt(8, 0, 0).unwrap()),
+            wallpaper: lake,
+            enabled: true,
+        };
+        let elsewhere = ScheduleItem {
+            wallpaper: WallpaperInfo::new(
+                WallpaperType::Static,
+                Some(dir.path().join("other.png")),
+                None,
+            ),
+            ..morning.clone()
+        };
+
+        let file = dir.path().join("Lakes & Sites.aetherpack");
+        let manifest = export(&file, "Lakes & Sites", &wallpapers, &[morning, elsewhere]).unwrap();
+        assert_eq!(
+            manifest.wallpapers[0].file.as_deref(),
+            Some("wallpapers/lake.png")
+        );
+        assert_eq!(
+            manifest.wallpapers[0].thumbnail.as_deref(),
+            Some("thumbnails/0.png")
+        );
+        assert_eq!(
+            manifest.wallpapers[1].page.as_deref(),
+            Some("assets/1/index.html")
+        );
+        assert_eq!(manifest.schedule.len(), 1);
+
+        let packs = dir.path().join("packs");
+        let pack = install(&file, &packs).unwrap();
+        assert_eq!(pack.folder, packs.join("Lakes _ Sites"));
+        assert_eq!(
+            pack.wallpapers[0].wallpaper.path,
+            Some(pack.folder.join("wallpapers/lake.png"))
+        );
+        assert!(pack.wallpapers[0].thumbnail.is_some());
+        assert_eq!(
+            pack.wallpapers[1].wallpaper.url,
+            Some(format!(
+                "file://{}",
+                pack.folder.join("assets/1/index.html").display()
+            ))
+        );
+        assert!(pack.folder.join("assets/1/js/app.js").is_file());
+        assert_eq!(pack.wallpapers[2].wallpaper.url, wallpapers[2].url);
+        assert_eq!(
+            pack.schedule[0].wallpaper.path,
+            pack.wallpapers[0].wallpaper.path
+        );
+
+        // Installing again replaces the install, which is found on the next start
+        install(&file, &packs).unwrap();
+        assert_eq!(installed(&packs).len(), 1);
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!(folder_name("../../etc"), "______etc");
+        assert_eq!(folder_name("  "), "pack");
+        let mut used = HashSet::new();
+        assert_eq!(unique_name(Path::new("/a/sky.jpg"), &mut used), "sky.jpg");
+        assert_eq!(unique_name(Path::new("/b/sky.jpg"), &mut used), "sky-2.jpg");
+        assert_eq!(unique_name(Path::new("/b/Slides"), &mut used), "Slides");
+    }
+}
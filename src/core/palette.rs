@@ -0,0 +1,661 @@
+//! Color palettes extracted from static wallpapers
+//!
+//! The dominant colors are found with median cut and mapped to the 16
+//! terminal colors, then written in pywal's cache layout (`colors`,
+//! `colors.json`, `colors.sh`, `colors.Xresources`, `colors.css`) so
+//! terminals and bars that follow pywal pick them up.
+//!
+//! The dominant colors of the library are also kept, to find wallpapers with
+//! similar colors and sort them into palette families.
+use crate::core::config::{PaletteConfig, PaletteFamily};
+use crate::core::{image_format, AppResult, Config, WallpaperType};
+use image::DynamicImage;
+use log::{debug, info, warn};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+
+/// Edge length the image is scaled down to before sampling
+const SAMPLE_SIZE: u32 = 128;
+
+/// Number of dominant colors to extract
+const DOMINANT_COLORS: usize = 8;
+
+/// Darkest average brightness (0-1) of a palette that isn't dark
+const DARK_VALUE: f32 = 0.2;
+
+/// Least saturation (0-1) of a color counted towards a hue
+const MIN_SATURATION: f32 = 0.25;
+
+/// Least share of colorful weight a palette needs to get a hue family
+const MIN_COLORFUL: f32 = 0.15;
+
+/// RGB color
+pub type Rgb = [u8; 3];
+
+impl PaletteConfig {
+    /// Get the directory the palette files are written to (pywal's cache by default)
+    pub fn output_dir(&self) -> PathBuf {
+        match &self.output_dir {
+            Some(dir) => PathBuf::from(dir),
+            None => dirs::cache_dir()
+                .unwrap_or_else(std::env::temp_dir)
+                .join("wal"),
+        }
+    }
+}
+
+/// Find the dominant colors of an image, darkest first
+pub fn dominant_colors(image: &DynamicImage, count: usize) -> Vec<Rgb> {
+    let pixels: Vec<Rgb> = image
+        .thumbnail(SAMPLE_SIZE, SAMPLE_SIZE)
+        .to_rgb8()
+        .pixels()
+        .map(|pixel| pixel.0)
+        .collect();
+    if pixels.is_empty() {
+        return Vec::new();
+    }
+
+    // Median cut: keep splitting the box with the widest channel at its median
+    let mut boxes = vec![pixels];
+    while boxes.len() < count {
+        let widest = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, pixels)| pixels.len() > 1)
+            .map(|(index, pixels)| {
+                let (channel, range) = widest_channel(pixels);
+                (index, channel, range)
+            })
+            .max_by_key(|(_, _, range)| *range);
+        let (index, channel) = match widest {
+            Some((index, channel, range)) if range > 0 => (index, channel),
+            _ => break,
+        };
+
+        let mut pixels = boxes.swap_remove(index);
+        pixels.sort_unstable_by_key(|pixel| pixel[channel]);
+        let upper = pixels.split_off(pixels.len() / 2);
+        boxes.push(pixels);
+        boxes.push(upper);
+    }
+
+    let mut colors: Vec<Rgb> = boxes.iter().map(|pixels| average(pixels)).collect();
+    colors.sort_by_key(|color| luminance(*color));
+    colors
+}
+
+/// Get the channel with the largest range and that range
+fn widest_channel(pixels: &[Rgb]) -> (usize, u8) {
+    (0..3)
+        .map(|channel| {
+            let min = pixels.iter().map(|pixel| pixel[channel]).min().unwrap_or(0);
+            let max = pixels.iter().map(|pixel| pixel[channel]).max().unwrap_or(0);
+            (channel, max - min)
+        })
+        .max_by_key(|(_, range)| *range)
+        .unwrap_or((0, 0))
+}
+
+/// Average color of a set of pixels
+fn average(pixels: &[Rgb]) -> Rgb {
+    let mut sum = [0u64; 3];
+    for pixel in pixels {
+        for (total, value) in sum.iter_mut().zip(pixel) {
+            *total += *value as u64;
+        }
+    }
+    let count = pixels.len().max(1) as u64;
+    [
+        (sum[0] / count) as u8,
+        (sum[1] / count) as u8,
+        (sum[2] / count) as u8,
+    ]
+}
+
+/// Perceived brightness (0-255 scaled by 1000)
+fn luminance(color: Rgb) -> u32 {
+    299 * color[0] as u32 + 587 * color[1] as u32 + 114 * color[2] as u32
+}
+
+/// Mix a color towards another by `amount` (0-1)
+fn blend(color: Rgb, target: Rgb, amount: f32) -> Rgb {
+    let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * amount).round() as u8;
+    [
+        mix(color[0], target[0]),
+        mix(color[1], target[1]),
+        mix(color[2], target[2]),
+    ]
+}
+
+/// Map dominant colors (darkest first) to the 16 terminal colors
+///
+/// Like pywal, color0 is a dark background, color7 and color15 are a light
+/// foreground, and 9-14 are brighter versions of 1-6.
+pub fn terminal_colors(dominant: &[Rgb]) -> [Rgb; 16] {
+    let mut dominant = dominant.to_vec();
+    if dominant.is_empty() {
+        dominant.push([0, 0, 0]);
+    }
+    while dominant.len() < DOMINANT_COLORS {
+        let last = dominant[dominant.len() - 1];
+        dominant.push(last);
+    }
+
+    let background = blend(dominant[0], [0, 0, 0], 0.5);
+    let foreground = blend(dominant[DOMINANT_COLORS - 1], [255, 255, 255], 0.6);
+
+    let mut colors = [[0u8; 3]; 16];
+    colors[0] = background;
+    colors[7] = foreground;
+    colors[8] = blend(background, [255, 255, 255], 0.25);
+    colors[15] = foreground;
+    for (index, color) in dominant.iter().enumerate().take(7).skip(1) {
+        colors[index] = *color;
+        colors[index + 8] = blend(*color, [255, 255, 255], 0.15);
+    }
+    colors
+}
+
+/// Pick the accent of a palette: its most vivid color that isn't too dark
+///
+/// Palettes without one fall back to their middle color.
+pub fn accent_color(colors: &[Rgb]) -> Rgb {
+    colors
+        .iter()
+        .map(|color| (*color, hsv(*color)))
+        .filter(|(_, (_, _, value))| *value >= DARK_VALUE)
+        .max_by(|(_, (_, a, v)), (_, (_, b, w))| (a * v).total_cmp(&(b * w)))
+        .map(|(color, _)| color)
+        .or_else(|| colors.get(colors.len() / 2).copied())
+        .unwrap_or([128, 128, 128])
+}
+
+/// Hue (0-360), saturation (0-1) and value (0-1) of a color
+pub fn hsv(color: Rgb) -> (f32, f32, f32) {
+    let [r, g, b] = color.map(|channel| channel as f32 / 255.0);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+    let hue = if delta == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    let saturation = if max > 0.0 { delta / max } else { 0.0 };
+    (hue, saturation, max)
+}
+
+impl PaletteFamily {
+    /// Every family, in hue order
+    pub const ALL: [PaletteFamily; 10] = [
+        PaletteFamily::Red,
+        PaletteFamily::Orange,
+        PaletteFamily::Yellow,
+        PaletteFamily::Green,
+        PaletteFamily::Cyan,
+        PaletteFamily::Blue,
+        PaletteFamily::Purple,
+        PaletteFamily::Pink,
+        PaletteFamily::Neutral,
+        PaletteFamily::Dark,
+    ];
+
+    /// Sort a palette into its family
+    ///
+    /// Dark palettes are dark whatever their hue. Otherwise the hue is the
+    /// average of the colorful colors, weighted by how vivid they are, and a
+    /// palette with too little color is neutral.
+    pub fn of(colors: &[Rgb]) -> Self {
+        if colors.is_empty() {
+            return PaletteFamily::Neutral;
+        }
+        let colors: Vec<(f32, f32, f32)> = colors.iter().map(|color| hsv(*color)).collect();
+        let brightness =
+            colors.iter().map(|(_, _, value)| value).sum::<f32>() / colors.len() as f32;
+        if brightness < DARK_VALUE {
+            return PaletteFamily::Dark;
+        }
+
+        // Hues are averaged as angles so reds on both sides of 0° meet
+        let (mut x, mut y, mut colorful) = (0.0f32, 0.0f32, 0.0f32);
+        for (hue, saturation, value) in &colors {
+            if *saturation < MIN_SATURATION || *value < DARK_VALUE {
+                continue;
+            }
+            let weight = saturation * value;
+            x += weight * hue.to_radians().cos();
+            y += weight * hue.to_radians().sin();
+            colorful += weight;
+        }
+        if colorful / (colors.len() as f32) < MIN_COLORFUL {
+            return PaletteFamily::Neutral;
+        }
+
+        match y.atan2(x).to_degrees().rem_euclid(360.0) {
+            hue if hue < 15.0 => PaletteFamily::Red,
+            hue if hue < 45.0 => PaletteFamily::Orange,
+            hue if hue < 70.0 => PaletteFamily::Yellow,
+            hue if hue < 160.0 => PaletteFamily::Green,
+            hue if hue < 200.0 => PaletteFamily::Cyan,
+            hue if hue < 255.0 => PaletteFamily::Blue,
+            hue if hue < 290.0 => PaletteFamily::Purple,
+            hue if hue < 345.0 => PaletteFamily::Pink,
+            _ => PaletteFamily::Red,
+        }
+    }
+}
+
+/// How far apart two palettes are (0 = same colors)
+///
+/// Each color is matched with the closest one of the other palette, both
+/// ways, and the distances are averaged.
+pub fn distance(a: &[Rgb], b: &[Rgb]) -> f32 {
+    if a.is_empty() || b.is_empty() {
+        return f32::MAX;
+    }
+    let color_distance = |x: &Rgb, y: &Rgb| -> f32 {
+        x.iter()
+            .zip(y)
+            .map(|(p, q)| (*p as f32 - *q as f32).powi(2))
+            .sum::<f32>()
+            .sqrt()
+    };
+    let closest = |from: &[Rgb], to: &[Rgb]| -> f32 {
+        from.iter()
+            .map(|x| {
+                to.iter()
+                    .map(|y| color_distance(x, y))
+                    .fold(f32::MAX, f32::min)
+            })
+            .sum::<f32>()
+            / from.len() as f32
+    };
+    (closest(a, b) + closest(b, a)) / 2.0
+}
+
+/// Format a color as `#rrggbb`
+pub fn hex(color: Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+}
+
+/// Extract the 16 terminal colors of a wallpaper
+pub fn wallpaper_palette(wallpaper: &Path) -> AppResult<[Rgb; 16]> {
+    let image = image_format::open(wallpaper)?;
+    Ok(terminal_colors(&dominant_colors(&image, DOMINANT_COLORS)))
+}
+
+/// Extract the palette of a wallpaper and write the pywal-style files into `dir`
+pub fn export(wallpaper: &Path, dir: &Path) -> AppResult<[Rgb; 16]> {
+    let colors = wallpaper_palette(wallpaper)?;
+    fs::create_dir_all(dir)?;
+    for (name, contents) in files(&colors, wallpaper)? {
+        fs::write(dir.join(name), contents)?;
+    }
+    info!(
+        "Exported color palette of {} to {}",
+        wallpaper.display(),
+        dir.display()
+    );
+    Ok(colors)
+}
+
+/// Get the names and contents of the palette files
+pub fn files(colors: &[Rgb; 16], wallpaper: &Path) -> AppResult<Vec<(&'static str, String)>> {
+    let wallpaper = wallpaper.to_string_lossy();
+    let hex_colors: Vec<String> = colors.iter().map(|color| hex(*color)).collect();
+    let (background, foreground) = (&hex_colors[0], &hex_colors[15]);
+
+    let mut plain = hex_colors.join("\n");
+    plain.push('\n');
+
+    let json = serde_json::json!({
+        "wallpaper": wallpaper,
+        "alpha": "100",
+        "special": {
+            "background": background,
+            "foreground": foreground,
+            "cursor": foreground,
+        },
+        "colors": hex_colors.iter()
+            .enumerate()
+            .map(|(index, color)| (format!("color{}", index), serde_json::Value::from(color.as_str())))
+            .collect::<serde_json::Map<_, _>>(),
+    });
+
+    let mut shell = format!(
+        "wallpaper='{}'\nbackground='{}'\nforeground='{}'\ncursor='{}'\n",
+        wallpaper.replace('\'', "'\\''"),
+        background,
+        foreground,
+        foreground
+    );
+    let mut xresources = format!(
+        "*.background: {}\n*.foreground: {}\n*.cursorColor: {}\n",
+        background, foreground, foreground
+    );
+    let mut css = format!(
+        ":root {{\n    --wallpaper: url(\"{}\");\n    --background: {};\n    --foreground: {};\n    --cursor: {};\n",
+        wallpaper.replace('\\', "/").replace('"', "\\\""), background, foreground, foreground
+    );
+    for (index, color) in hex_colors.iter().enumerate() {
+        shell.push_str(&format!("color{}='{}'\n", index, color));
+        xresources.push_str(&format!("*.color{}: {}\n", index, color));
+        css.push_str(&format!("    --color{}: {};\n", index, color));
+    }
+    css.push_str("}\n");
+
+    Ok(vec![
+        ("colors", plain),
+        ("colors.json", serde_json::to_string_pretty(&json)?),
+        ("colors.sh", shell),
+        ("colors.Xresources", xresources),
+        ("colors.css", css),
+    ])
+}
+
+/// Dominant colors of the wallpapers in the library, by path
+///
+/// Colors are kept in a file shared by every process, which is read again
+/// before each write so processes don't drop each other's palettes.
+#[derive(Debug, Default)]
+pub struct PaletteStore {
+    /// File the palettes are kept in (None = memory only)
+    path: Option<PathBuf>,
+
+    /// Dominant colors by wallpaper path, darkest first
+    entries: Mutex<BTreeMap<PathBuf, Vec<Rgb>>>,
+}
+
+#[allow(dead_code)]
+impl PaletteStore {
+    /// Create a store kept in memory only
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open the store kept in a file
+    pub fn open(path: PathBuf) -> Self {
+        let entries = read(&path);
+        Self {
+            path: Some(path),
+            entries: Mutex::new(entries),
+        }
+    }
+
+    /// Get the process-wide store
+    pub fn shared() -> Arc<PaletteStore> {
+        static SHARED: OnceLock<Arc<PaletteStore>> = OnceLock::new();
+        SHARED
+            .get_or_init(|| match Config::get_config_dir() {
+                Ok(dir) => Arc::new(PaletteStore::open(dir.join("palettes.json"))),
+                Err(_) => Arc::new(PaletteStore::new()),
+            })
+            .clone()
+    }
+
+    /// Get the known dominant colors of a wallpaper
+    pub fn get(&self, path: &Path) -> Option<Vec<Rgb>> {
+        self.entries.lock().unwrap().get(path).cloned()
+    }
+
+    /// Get the dominant colors of a wallpaper, extracting them when unknown
+    pub fn colors(&self, path: &Path) -> Option<Vec<Rgb>> {
+        if let Some(colors) = self.get(path) {
+            return Some(colors);
+        }
+        let colors = extract(path)?;
+        self.save(vec![(path.to_path_buf(), colors.clone())]);
+        Some(colors)
+    }
+
+    /// Get the palette family of a wallpaper, extracting its colors when unknown
+    pub fn family(&self, path: &Path) -> Option<PaletteFamily> {
+        self.colors(path).map(|colors| PaletteFamily::of(&colors))
+    }
+
+    /// Record the dominant colors of a wallpaper
+    pub fn set(&self, path: &Path, colors: Vec<Rgb>) {
+        self.save(vec![(path.to_path_buf(), colors)]);
+    }
+
+    /// Extract the colors of the static images not known yet, saving once at the end
+    pub fn extract_all(&self, paths: &[PathBuf]) {
+        let missing: Vec<(PathBuf, Vec<Rgb>)> = paths
+            .iter()
+            .filter(|path| WallpaperType::Static.accepts(path) && self.get(path).is_none())
+            .filter_map(|path| Some((path.clone(), extract(path)?)))
+            .collect();
+        if !missing.is_empty() {
+            debug!("Extracted the palettes of {} wallpapers", missing.len());
+            self.save(missing);
+        }
+    }
+
+    /// Extract the colors of the static images not known yet on a worker thread
+    pub fn extract_in_background(self: &Arc<Self>, paths: Vec<PathBuf>) {
+        let store = self.clone();
+        thread::spawn(move || store.extract_all(&paths));
+    }
+
+    /// Rank wallpapers by how close their colors are to the one at `path`, closest first
+    ///
+    /// Only wallpapers in `candidates` with known colors are ranked.
+    pub fn similar(&self, path: &Path, candidates: &[PathBuf]) -> Vec<(PathBuf, f32)> {
+        let entries = self.entries.lock().unwrap();
+        let Some(target) = entries.get(path) else {
+            return Vec::new();
+        };
+        let mut ranked: Vec<(PathBuf, f32)> = candidates
+            .iter()
+            .filter(|candidate| candidate.as_path() != path)
+            .filter_map(|candidate| {
+                Some((candidate.clone(), distance(target, entries.get(candidate)?)))
+            })
+            .collect();
+        ranked.sort_by(|a, b| a.1.total_cmp(&b.1));
+        ranked
+    }
+
+    /// Add palettes and write the file
+    fn save(&self, palettes: Vec<(PathBuf, Vec<Rgb>)>) {
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(file) = &self.path {
+            *entries = read(file);
+        }
+        entries.extend(palettes);
+        if let Some(file) = &self.path {
+            let result = serde_json::to_string(&*entries)
+                .map_err(|e| e.to_string())
+                .and_then(|json| fs::write(file, json).map_err(|e| e.to_string()));
+            if let Err(e) = result {
+                warn!("Failed to save palettes to {}: {}", file.display(), e);
+            }
+        }
+    }
+}
+
+/// Extract the dominant colors of an image, logging failures
+fn extract(path: &Path) -> Option<Vec<Rgb>> {
+    match image_format::open(path) {
+        Ok(image) => Some(dominant_colors(&image, DOMINANT_COLORS)),
+        Err(e) => {
+            debug!("Failed to read the colors of {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// Read the palettes kept in a file
+fn read(path: &Path) -> BTreeMap<PathBuf, Vec<Rgb>> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb as Pixel, RgbImage};
+    use tempfile::tempdir;
+
+    /// Image split into a dark blue and a light orange half
+    fn two_tone() -> DynamicImage {
+        let mut image = RgbImage::new(8, 8);
+        for (x, _, pixel) in image.enumerate_pixels_mut() {
+            *pixel = if x < 4 {
+                Pixel([10, 20, 80])
+            } else {
+                Pixel([240, 160, 60])
+            };
+        }
+        DynamicImage::ImageRgb8(image)
+    }
+
+    #[test]
+    fn test_dominant_colors() {
+        let colors = dominant_colors(&two_tone(), 8);
+        assert_eq!(colors.len(), 2);
+        assert_eq!(colors[0], [10, 20, 80]);
+        assert_eq!(colors[1], [240, 160, 60]);
+    }
+
+    #[test]
+    fn test_terminal_colors() {
+        let colors = terminal_colors(&dominant_colors(&two_tone(), 8));
+        assert!(luminance(colors[0]) < luminance([10, 20, 80]));
+        assert!(luminance(colors[15]) > luminance([240, 160, 60]));
+        assert_eq!(colors[7], colors[15]);
+        assert!(luminance(colors[9]) >= luminance(colors[1]));
+
+        // A blank image still gives a full palette
+        assert_eq!(terminal_colors(&[]).len(), 16);
+    }
+
+    #[test]
+    fn test_export() {
+        let dir = tempdir().unwrap();
+        let wallpaper = dir.path().join("wall.png");
+        two_tone().save(&wallpaper).unwrap();
+
+        let output = dir.path().join("wal");
+        let colors = export(&wallpaper, &output).unwrap();
+
+        let plain = fs::read_to_string(output.join("colors")).unwrap();
+        assert_eq!(plain.lines().count(), 16);
+        assert_eq!(plain.lines().next().unwrap(), hex(colors[0]));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(output.join("colors.json")).unwrap()).unwrap();
+        assert_eq!(json["colors"]["color15"], hex(colors[15]));
+        assert_eq!(json["special"]["background"], hex(colors[0]));
+
+        assert!(fs::read_to_string(output.join("colors.Xresources"))
+            .unwrap()
+            .contains("*.color4: "));
+        assert!(fs::read_to_string(output.join("colors.css"))
+            .unwrap()
+            .contains("--color4: "));
+        assert!(fs::read_to_string(output.join("colors.sh"))
+            .unwrap()
+            .starts_with("wallpaper='"));
+    }
+
+    #[test]
+    fn test_accent_color() {
+        // The vivid orange beats the brighter gray and the dark blue
+        assert_eq!(
+            accent_color(&[[5, 10, 40], [200, 200, 200], [240, 140, 30]]),
+            [240, 140, 30]
+        );
+        assert_eq!(
+            accent_color(&[[5, 5, 5], [10, 10, 10], [20, 20, 20]]),
+            [10, 10, 10]
+        );
+        assert_eq!(accent_color(&[]), [128, 128, 128]);
+    }
+
+    #[test]
+    fn test_palette_families() {
+        assert_eq!(
+            PaletteFamily::of(&[[10, 20, 80], [30, 60, 200], [90, 140, 230]]),
+            PaletteFamily::Blue
+        );
+        assert_eq!(
+            PaletteFamily::of(&[[240, 160, 60], [200, 110, 30]]),
+            PaletteFamily::Orange
+        );
+        assert_eq!(
+            PaletteFamily::of(&[[40, 150, 60], [90, 200, 80]]),
+            PaletteFamily::Green
+        );
+        // Reds on both sides of 0° stay red
+        assert_eq!(
+            PaletteFamily::of(&[[220, 30, 50], [220, 50, 30]]),
+            PaletteFamily::Red
+        );
+        assert_eq!(
+            PaletteFamily::of(&[[120, 120, 120], [200, 200, 205], [240, 240, 240]]),
+            PaletteFamily::Neutral
+        );
+        assert_eq!(
+            PaletteFamily::of(&[[5, 5, 20], [30, 10, 10], [20, 20, 40]]),
+            PaletteFamily::Dark
+        );
+        assert_eq!(PaletteFamily::of(&[]), PaletteFamily::Neutral);
+    }
+
+    #[test]
+    fn test_similar_ranks_by_colors() {
+        let store = PaletteStore::new();
+        store.set(Path::new("sea.png"), vec![[10, 20, 80], [30, 60, 200]]);
+        store.set(Path::new("sky.png"), vec![[20, 30, 90], [40, 70, 210]]);
+        store.set(
+            Path::new("sunset.png"),
+            vec![[240, 160, 60], [200, 110, 30]],
+        );
+        let candidates: Vec<PathBuf> = ["sea.png", "sky.png", "sunset.png", "unknown.png"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+
+        let ranked: Vec<PathBuf> = store
+            .similar(Path::new("sea.png"), &candidates)
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        assert_eq!(
+            ranked,
+            [PathBuf::from("sky.png"), PathBuf::from("sunset.png")]
+        );
+        assert_eq!(distance(&[[1, 2, 3]], &[[1, 2, 3]]), 0.0);
+    }
+
+    #[test]
+    fn test_palettes_are_extracted_and_kept() {
+        let dir = tempdir().unwrap();
+        let wallpaper = dir.path().join("wall.png");
+        two_tone().save(&wallpaper).unwrap();
+        let file = dir.path().join("palettes.json");
+
+        let store = PaletteStore::open(file.clone());
+        assert!(store.get(&wallpaper).is_none());
+        store.extract_all(&[wallpaper.clone(), dir.path().join("missing.png")]);
+        assert_eq!(
+            store.get(&wallpaper).unwrap(),
+            [[10, 20, 80], [240, 160, 60]]
+        );
+
+        let reopened = PaletteStore::open(file);
+        assert_eq!(reopened.get(&wallpaper).unwrap().len(), 2);
+        assert!(reopened.get(&dir.path().join("missing.png")).is_none());
+    }
+}
@@ -1,8 +1,17 @@
 use log::{debug, warn};
-use std::time::{Duration, Instant};
-use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
-use sysinfo::System;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, System};
+
+/// Frame time above which the UI can't keep up 30 FPS, in milliseconds
+const FRAME_BUDGET_MS: f32 = 1000.0 / 30.0;
+
+/// Memory use of the app above which performance counts as degraded, in MB
+const MEMORY_LIMIT_MB: f64 = 500.0;
+
+/// System CPU usage above which performance counts as degraded, in percent
+const CPU_LIMIT: f32 = 80.0;
 
 /// Performance metrics for the application
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +26,9 @@ pub struct PerformanceMetrics {
     pub fps: f32,
     /// Wallpaper load time in milliseconds
     pub wallpaper_load_time: u128,
+    /// Wallpaper loaded during this sample, if any
+    #[serde(default)]
+    pub wallpaper: Option<String>,
 }
 
 /// Performance monitor to track application performance
@@ -28,8 +40,8 @@ pub struct PerformanceMonitor {
     metrics_history: Vec<PerformanceMetrics>,
     /// Maximum history size
     max_history_size: usize,
-    /// Last frame time
-    last_frame_time: Instant,
+    /// Time spent building frames since the last sample
+    frame_time_total: Duration,
     /// Frame count for FPS calculation
     frame_count: u32,
     /// Time for FPS calculation
@@ -40,6 +52,10 @@ pub struct PerformanceMonitor {
     last_system_update: Instant,
     /// System update interval
     system_update_interval: Duration,
+    /// This process
+    pid: Option<Pid>,
+    /// Wallpaper load waiting for the next sample
+    pending_load: Option<(String, Duration)>,
 }
 
 impl Default for PerformanceMonitor {
@@ -53,25 +69,31 @@ impl PerformanceMonitor {
     /// Create a new performance monitor
     pub fn new() -> Self {
         let mut system = System::new();
+        let pid = sysinfo::get_current_pid().ok();
         system.refresh_cpu_usage();
-        system.refresh_memory();
-        
+        if let Some(pid) = pid {
+            system.refresh_process(pid);
+        }
+
         Self {
             operation_start_times: HashMap::new(),
             metrics_history: Vec::new(),
             max_history_size: 100,
-            last_frame_time: Instant::now(),
+            frame_time_total: Duration::ZERO,
             frame_count: 0,
             fps_timer: Instant::now(),
             system,
             last_system_update: Instant::now(),
             system_update_interval: Duration::from_millis(500), // Update every 500ms
+            pid,
+            pending_load: None,
         }
     }
 
     /// Start timing an operation
     pub fn start_timing(&mut self, operation: &str) {
-        self.operation_start_times.insert(operation.to_string(), Instant::now());
+        self.operation_start_times
+            .insert(operation.to_string(), Instant::now());
         debug!("Started timing operation: {}", operation);
     }
 
@@ -87,47 +109,84 @@ impl PerformanceMonitor {
         }
     }
 
-    /// Update frame timing
-    pub fn update_frame_timing(&mut self) {
+    /// Update frame timing with the time it took to build a frame
+    pub fn update_frame_timing(&mut self, frame_time: Duration) {
         let now = Instant::now();
-        let frame_time = now.duration_since(self.last_frame_time).as_millis() as f32;
-        self.last_frame_time = now;
-        
+        self.frame_time_total += frame_time;
         self.frame_count += 1;
-        
+
         // Update system metrics if enough time has passed
         if self.last_system_update.elapsed() >= self.system_update_interval {
             self.system.refresh_cpu_usage();
-            self.system.refresh_memory();
+            if let Some(pid) = self.pid {
+                self.system.refresh_process(pid);
+            }
             self.last_system_update = now;
         }
-        
+
         // Calculate FPS every second
         if self.fps_timer.elapsed().as_secs() >= 1 {
             let fps = self.frame_count as f32 / self.fps_timer.elapsed().as_secs_f32();
-            
+            let frame_time = self.frame_time_total.as_secs_f32() * 1000.0 / self.frame_count as f32;
+
             // Get real system metrics
             let cpu_usage = self.system.global_cpu_info().cpu_usage();
-            let memory_usage = (self.system.used_memory() as f64 / self.system.total_memory() as f64) * 100.0;
-            
+            let memory_usage = self
+                .pid
+                .and_then(|pid| self.system.process(pid))
+                .map_or(0.0, |process| process.memory() as f64 / (1024.0 * 1024.0));
+            let (wallpaper, load_time) = self
+                .pending_load
+                .take()
+                .map_or((None, 0), |(wallpaper, load_time)| {
+                    (Some(wallpaper), load_time.as_millis())
+                });
+
             // Update metrics
             self.update_metrics(PerformanceMetrics {
                 cpu_usage,
                 memory_usage,
                 frame_time,
                 fps,
-                wallpaper_load_time: 0, // Will be updated separately
+                wallpaper_load_time: load_time,
+                wallpaper,
             });
-            
+
             self.frame_count = 0;
+            self.frame_time_total = Duration::ZERO;
             self.fps_timer = Instant::now();
         }
     }
 
+    /// Record how long a wallpaper took to load
+    ///
+    /// The load time is included in the next metrics sample.
+    pub fn record_wallpaper_load(&mut self, wallpaper: &str, load_time: Duration) {
+        debug!("Wallpaper '{}' loaded in {:?}", wallpaper, load_time);
+        self.pending_load = Some((wallpaper.to_string(), load_time));
+    }
+
+    /// Get the load times in milliseconds of each wallpaper in the history
+    ///
+    /// Wallpapers are listed in the order they were first loaded.
+    pub fn wallpaper_load_times(&self) -> Vec<(String, Vec<f32>)> {
+        let mut load_times: Vec<(String, Vec<f32>)> = Vec::new();
+        for metrics in &self.metrics_history {
+            if let Some(wallpaper) = &metrics.wallpaper {
+                let time = metrics.wallpaper_load_time as f32;
+                match load_times.iter_mut().find(|(name, _)| name == wallpaper) {
+                    Some((_, times)) => times.push(time),
+                    None => load_times.push((wallpaper.clone(), vec![time])),
+                }
+            }
+        }
+        load_times
+    }
+
     /// Update performance metrics
     pub fn update_metrics(&mut self, metrics: PerformanceMetrics) {
         self.metrics_history.push(metrics);
-        
+
         // Keep only the last N metrics
         if self.metrics_history.len() > self.max_history_size {
             self.metrics_history.remove(0);
@@ -151,7 +210,8 @@ impl PerformanceMonitor {
             return 0.0;
         }
 
-        let total_fps: f32 = self.metrics_history
+        let total_fps: f32 = self
+            .metrics_history
             .iter()
             .rev()
             .take(frames)
@@ -162,9 +222,14 @@ impl PerformanceMonitor {
     }
 
     /// Check if performance is below acceptable thresholds
+    ///
+    /// Frames are only drawn when something changes, so a low FPS on its own
+    /// doesn't mean anything; the time spent per frame is checked instead.
     pub fn is_performance_degraded(&self) -> bool {
         if let Some(metrics) = self.get_current_metrics() {
-            metrics.fps < 30.0 || metrics.memory_usage > 500.0 || metrics.cpu_usage > 80.0
+            metrics.frame_time > FRAME_BUDGET_MS
+                || metrics.memory_usage > MEMORY_LIMIT_MB
+                || metrics.cpu_usage > CPU_LIMIT
         } else {
             false
         }
@@ -210,11 +275,11 @@ mod tests {
     #[test]
     fn test_performance_monitor_timing() {
         let mut monitor = PerformanceMonitor::new();
-        
+
         monitor.start_timing("test_operation");
         thread::sleep(Duration::from_millis(10));
         let duration = monitor.end_timing("test_operation");
-        
+
         assert!(duration.is_some());
         assert!(duration.unwrap().as_millis() >= 10);
     }
@@ -223,7 +288,7 @@ mod tests {
     fn test_metrics_history_limit() {
         let mut monitor = PerformanceMonitor::new();
         monitor.max_history_size = 5;
-        
+
         for i in 0..10 {
             monitor.update_metrics(PerformanceMetrics {
                 cpu_usage: i as f32,
@@ -231,9 +296,10 @@ mod tests {
                 frame_time: i as f32,
                 fps: i as f32,
                 wallpaper_load_time: i as u128,
+                wallpaper: None,
             });
         }
-        
+
         assert_eq!(monitor.metrics_history.len(), 5);
         assert_eq!(monitor.metrics_history[0].cpu_usage, 5.0);
     }
@@ -241,7 +307,7 @@ mod tests {
     #[test]
     fn test_average_fps_calculation() {
         let mut monitor = PerformanceMonitor::new();
-        
+
         for i in 1..=5 {
             monitor.update_metrics(PerformanceMetrics {
                 cpu_usage: 0.0,
@@ -249,10 +315,57 @@ mod tests {
                 frame_time: 0.0,
                 fps: i as f32 * 10.0,
                 wallpaper_load_time: 0,
+                wallpaper: None,
             });
         }
-        
+
         let avg_fps = monitor.get_average_fps(3);
         assert_eq!(avg_fps, 40.0); // (30 + 40 + 50) / 3
     }
+
+    #[test]
+    fn test_wallpaper_load_times() {
+        let mut monitor = PerformanceMonitor::new();
+        let sample = |wallpaper: Option<&str>, load_time| PerformanceMetrics {
+            cpu_usage: 0.0,
+            memory_usage: 0.0,
+            frame_time: 0.0,
+            fps: 0.0,
+            wallpaper_load_time: load_time,
+            wallpaper: wallpaper.map(str::to_string),
+        };
+        monitor.update_metrics(sample(Some("ocean.mp4"), 120));
+        monitor.update_metrics(sample(None, 0));
+        monitor.update_metrics(sample(Some("forest.png"), 15));
+        monitor.update_metrics(sample(Some("ocean.mp4"), 90));
+
+        assert_eq!(
+            monitor.wallpaper_load_times(),
+            vec![
+                ("ocean.mp4".to_string(), vec![120.0, 90.0]),
+                ("forest.png".to_string(), vec![15.0]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_performance_degraded() {
+        let mut monitor = PerformanceMonitor::new();
+        assert!(!monitor.is_performance_degraded());
+
+        let sample = |frame_time, memory_usage| PerformanceMetrics {
+            cpu_usage: 10.0,
+            memory_usage,
+            frame_time,
+            fps: 2.0,
+            wallpaper_load_time: 0,
+            wallpaper: None,
+        };
+        monitor.update_metrics(sample(4.0, 120.0));
+        assert!(!monitor.is_performance_degraded());
+        monitor.update_metrics(sample(50.0, 120.0));
+        assert!(monitor.is_performance_degraded());
+        monitor.update_metrics(sample(4.0, 800.0));
+        assert!(monitor.is_performance_degraded());
+    }
 }
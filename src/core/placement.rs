@@ -0,0 +1,729 @@
+//! Static wallpaper placement
+//!
+//! Fit modes a backend supports are passed through; the others, and monitors
+//! given their own image, are rendered to one image per monitor at the
+//! monitor's resolution. A spanned image is fitted to the canvas covering all
+//! monitors, then handed to the backend whole or cut into each monitor's part.
+//! While the time-of-day tint is on, every image is rendered to be tinted.
+//! Images the backend can't read, such as HEIC, AVIF and JPEG XL, reach it
+//! converted to PNG, and images with adjustments as their adjusted copy.
+//! Cropping and resizing go through `ImagePipeline`.
+use crate::core::config::{CropRect, FitMode, PlacementConfig};
+use crate::core::image_pipeline::ImagePipeline;
+use crate::core::span::SpanLayout;
+use crate::core::{adjustments, image_format, AppError, AppResult, Config};
+use crate::experiments::effects::temperature;
+use crate::platform::display::{self, MonitorInfo};
+use crate::platform::WallpaperManager;
+use chrono::Utc;
+use image::imageops;
+use image::{DynamicImage, Rgba, RgbaImage};
+use log::{debug, info, warn};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Smallest crop edge as a fraction of the image
+const MIN_CROP: f32 = 0.05;
+
+/// Longest edge of the thumbnail searched for detail by the smart crop
+const SMART_CROP_SAMPLE: u32 = 256;
+
+/// Color of the bars around fitted and centered images
+const BACKGROUND: Rgba<u8> = Rgba([0, 0, 0, 255]);
+
+impl CropRect {
+    /// Largest centered crop with the monitor's aspect ratio
+    pub fn centered(
+        image_width: u32,
+        image_height: u32,
+        monitor_width: u32,
+        monitor_height: u32,
+    ) -> Self {
+        let aspect = crop_aspect(image_width, image_height, monitor_width, monitor_height);
+        let (width, height) = if aspect > 1.0 {
+            (1.0 / aspect, 1.0)
+        } else {
+            (1.0, aspect)
+        };
+        Self {
+            x: (1.0 - width) / 2.0,
+            y: (1.0 - height) / 2.0,
+            width,
+            height,
+        }
+    }
+
+    /// Resize to `width`, keeping the monitor's aspect ratio and staying inside the image
+    pub fn with_width(
+        self,
+        width: f32,
+        image_width: u32,
+        image_height: u32,
+        monitor_width: u32,
+        monitor_height: u32,
+    ) -> Self {
+        let aspect = crop_aspect(image_width, image_height, monitor_width, monitor_height);
+        let max_width = 1.0_f32.min(1.0 / aspect);
+        let width = width.clamp(MIN_CROP.min(max_width), max_width);
+        Self {
+            width,
+            height: width * aspect,
+            ..self
+        }
+        .clamped()
+    }
+
+    /// Largest crop with the monitor's aspect ratio around the image's most detailed part
+    ///
+    /// The crop slides along the side the image overflows and stops where the
+    /// edges inside it are strongest, keeping to the middle on ties.
+    pub fn smart(image: &DynamicImage, monitor_width: u32, monitor_height: u32) -> Self {
+        let centered = Self::centered(image.width(), image.height(), monitor_width, monitor_height);
+        let horizontal = centered.width < 1.0;
+        if !horizontal && centered.height >= 1.0 {
+            return centered;
+        }
+
+        let thumbnail = image
+            .thumbnail(SMART_CROP_SAMPLE, SMART_CROP_SAMPLE)
+            .to_luma8();
+        let (width, height) = thumbnail.dimensions();
+        let lines = if horizontal { width } else { height };
+        let mut detail = vec![0u64; lines as usize];
+        for (x, y, pixel) in thumbnail.enumerate_pixels() {
+            let right = thumbnail.get_pixel((x + 1).min(width - 1), y)[0];
+            let below = thumbnail.get_pixel(x, (y + 1).min(height - 1))[0];
+            let edge = pixel[0].abs_diff(right) as u64 + pixel[0].abs_diff(below) as u64;
+            detail[if horizontal { x } else { y } as usize] += edge;
+        }
+
+        let size = if horizontal {
+            centered.width
+        } else {
+            centered.height
+        };
+        let window = ((size * lines as f32).round() as usize).clamp(1, lines as usize);
+        let middle = (lines as usize - window) as f32 / 2.0;
+        let mut sum: u64 = detail[..window].iter().sum();
+        let mut best = (sum, 0);
+        for start in 1..=lines as usize - window {
+            sum = sum + detail[start + window - 1] - detail[start - 1];
+            let closer = (start as f32 - middle).abs() < (best.1 as f32 - middle).abs();
+            if sum > best.0 || (sum == best.0 && closer) {
+                best = (sum, start);
+            }
+        }
+
+        let offset = best.1 as f32 / lines as f32;
+        if horizontal {
+            Self {
+                x: offset,
+                ..centered
+            }
+            .clamped()
+        } else {
+            Self {
+                y: offset,
+                ..centered
+            }
+            .clamped()
+        }
+    }
+
+    /// Move the rectangle back inside the image
+    pub fn clamped(self) -> Self {
+        let width = self.width.clamp(MIN_CROP, 1.0);
+        let height = self.height.clamp(MIN_CROP, 1.0);
+        Self {
+            x: self.x.clamp(0.0, 1.0 - width),
+            y: self.y.clamp(0.0, 1.0 - height),
+            width,
+            height,
+        }
+    }
+}
+
+/// Crop height as a multiple of its width, both as image fractions
+fn crop_aspect(
+    image_width: u32,
+    image_height: u32,
+    monitor_width: u32,
+    monitor_height: u32,
+) -> f32 {
+    (image_width as f32 * monitor_height as f32)
+        / (image_height.max(1) as f32 * monitor_width.max(1) as f32)
+}
+
+/// Render an image for a monitor of `width` x `height` pixels
+pub fn render(
+    image: &DynamicImage,
+    mode: FitMode,
+    crop: Option<CropRect>,
+    width: u32,
+    height: u32,
+) -> RgbaImage {
+    let pipeline = ImagePipeline::of(image);
+    match mode {
+        FitMode::Fill => pipeline.fill(width, height).into_rgba8(),
+        FitMode::Stretch => pipeline.resize(width, height).into_rgba8(),
+        FitMode::Fit => {
+            centered_on_background(&pipeline.fit(width, height).into_image(), width, height)
+        }
+        FitMode::Center => centered_on_background(image, width, height),
+        FitMode::Tile => {
+            let mut canvas = RgbaImage::new(width, height);
+            imageops::tile(&mut canvas, &image.to_rgba8());
+            canvas
+        }
+        FitMode::Crop => {
+            let crop = crop.unwrap_or_else(|| {
+                CropRect::centered(image.width(), image.height(), width, height)
+            });
+            pipeline.crop(crop).resize(width, height).into_rgba8()
+        }
+    }
+}
+
+/// Render an image for a monitor with the placement's fit mode
+///
+/// Filled images are cropped around their most detailed part when smart
+/// cropping is on, and so are landscape images filling or fitted to a
+/// portrait monitor unless it letterboxes them.
+pub fn render_placed(
+    image: &DynamicImage,
+    placement: &PlacementConfig,
+    crop: Option<CropRect>,
+    width: u32,
+    height: u32,
+) -> RgbaImage {
+    let turned = height > width && image.width() > image.height();
+    if (placement.smart_crop && placement.mode == FitMode::Fill)
+        || (turned && crops_portrait(placement))
+    {
+        return render(
+            image,
+            FitMode::Crop,
+            Some(CropRect::smart(image, width, height)),
+            width,
+            height,
+        );
+    }
+    render(image, placement.mode, crop, width, height)
+}
+
+/// Check whether the placement crops landscape images for portrait monitors
+fn crops_portrait(placement: &PlacementConfig) -> bool {
+    !placement.letterbox_portrait && matches!(placement.mode, FitMode::Fill | FitMode::Fit)
+}
+
+/// Draw an image centered on a background of the given size
+fn centered_on_background(image: &DynamicImage, width: u32, height: u32) -> RgbaImage {
+    let mut canvas = RgbaImage::from_pixel(width, height, BACKGROUND);
+    let x = (width as i64 - image.width() as i64) / 2;
+    let y = (height as i64 - image.height() as i64) / 2;
+    imageops::overlay(&mut canvas, &image.to_rgba8(), x, y);
+    canvas
+}
+
+/// Empty the render directory, returning a stamp for the new file names
+///
+/// The file names change each time so backends that cache by path pick up
+/// the new images.
+fn clear_renders(dir: &Path) -> AppResult<i64> {
+    fs::create_dir_all(dir)?;
+    for entry in fs::read_dir(dir)?.flatten() {
+        let _ = fs::remove_file(entry.path());
+    }
+    Ok(Utc::now().timestamp_millis())
+}
+
+/// Save a rendered image, tinted to the time of day's color temperature
+fn save_render(image: &RgbaImage, path: &Path) -> AppResult<()> {
+    let tinted = temperature::kelvin_now().map(|kelvin| {
+        let mut copy = image.clone();
+        temperature::tint(&mut copy, kelvin);
+        copy
+    });
+    tinted
+        .as_ref()
+        .unwrap_or(image)
+        .save(path)
+        .map_err(|e| AppError::WallpaperError(format!("Failed to save {}: {}", path.display(), e)))
+}
+
+/// Render an image fitted to the canvas of a span layout
+///
+/// Crops are chosen per monitor, so the crop mode shows a centered crop.
+fn render_canvas(image: &DynamicImage, mode: FitMode, layout: &SpanLayout) -> RgbaImage {
+    render(image, mode, None, layout.width, layout.height)
+}
+
+/// Render an image spanning all monitors as one file in `dir`
+pub fn render_spanned(
+    path: &Path,
+    mode: FitMode,
+    layout: &SpanLayout,
+    dir: &Path,
+) -> AppResult<PathBuf> {
+    let canvas = render_canvas(&image_format::open(path)?, mode, layout);
+    let output = dir.join(format!("span-{}.png", clear_renders(dir)?));
+    save_render(&canvas, &output)?;
+    debug!(
+        "Rendered {} spanning {}x{}",
+        output.display(),
+        layout.width,
+        layout.height
+    );
+    Ok(output)
+}
+
+/// Render an image for each monitor into `dir`
+///
+/// Monitors with an image of their own in the placement show that one,
+/// unless it can't be opened. When the placement spans the monitors, the
+/// others show their part of the canvas. Returns the monitor names with their
+/// image paths. Older renders in `dir` are removed.
+pub fn render_for_monitors(
+    path: &Path,
+    placement: &PlacementConfig,
+    monitors: &[MonitorInfo],
+    dir: &Path,
+) -> AppResult<Vec<(String, PathBuf)>> {
+    let image = image_format::open(path)?;
+    let layout = if placement.span {
+        SpanLayout::of(monitors)
+    } else {
+        None
+    };
+    let canvas = layout.map(|layout| {
+        (
+            DynamicImage::ImageRgba8(render_canvas(&image, placement.mode, &layout)),
+            layout,
+        )
+    });
+    let mut own_images = HashMap::new();
+    for own in monitors
+        .iter()
+        .filter_map(|monitor| placement.monitor_images.get(&monitor.id))
+    {
+        if own_images.contains_key(own) {
+            continue;
+        }
+        match image_format::open(Path::new(own)) {
+            Ok(own_image) => {
+                own_images.insert(own.clone(), own_image);
+            }
+            Err(e) => warn!("{}; showing the wallpaper instead", e),
+        }
+    }
+
+    let stamp = clear_renders(dir)?;
+    monitors
+        .iter()
+        .enumerate()
+        .map(|(index, monitor)| {
+            // Crops are chosen on the wallpaper, so an image of the monitor's own is centered
+            let own_image = placement
+                .monitor_images
+                .get(&monitor.id)
+                .and_then(|own| own_images.get(own));
+            let rendered = match (own_image, &canvas) {
+                (Some(own_image), _) => {
+                    render_placed(own_image, placement, None, monitor.width, monitor.height)
+                }
+                (None, Some((canvas, layout))) => {
+                    let region = &layout.regions[index];
+                    // Monitors at a lower scale than the canvas get their part shrunk
+                    ImagePipeline::of(canvas)
+                        .region(region.x, region.y, region.width, region.height)
+                        .resize(monitor.width, monitor.height)
+                        .into_rgba8()
+                }
+                (None, None) => render_placed(
+                    &image,
+                    placement,
+                    placement.crops.get(&monitor.name).copied(),
+                    monitor.width,
+                    monitor.height,
+                ),
+            };
+
+            let file_name: String = monitor
+                .name
+                .chars()
+                .map(|c| {
+                    if c.is_alphanumeric() || c == '-' {
+                        c
+                    } else {
+                        '_'
+                    }
+                })
+                .collect();
+            let output = dir.join(format!("{}-{}.png", file_name, stamp));
+            save_render(&rendered, &output)?;
+            debug!(
+                "Rendered {} for {} ({}x{})",
+                output.display(),
+                monitor.name,
+                monitor.width,
+                monitor.height
+            );
+            Ok((monitor.name.clone(), output))
+        })
+        .collect()
+}
+
+/// Set a static wallpaper with the configured placement
+///
+/// Returns the monitors given their own image, none when the backend fits a
+/// single image itself. Falls back to the backend's native fill when the
+/// monitors can't be detected.
+pub async fn set_static_wallpaper(
+    wallpaper_manager: &(dyn WallpaperManager + Send + Sync),
+    path: &Path,
+    placement: &PlacementConfig,
+) -> AppResult<Vec<String>> {
+    // Adjusted images, the monitors' own included, are shown through a copy
+    let adjusted = adjustments::adjusted(path).await?;
+    let mut placement = placement.clone();
+    for own in placement.monitor_images.values_mut() {
+        let copy = adjustments::adjusted(Path::new(own.as_str())).await;
+        match copy {
+            Ok(copy) => *own = copy.display().to_string(),
+            Err(e) => warn!("{}; showing it without its adjustments", e),
+        }
+    }
+    let placement = &placement;
+
+    let converted = image_format::compatible(&adjusted, wallpaper_manager.image_formats()).await?;
+    let path = converted.as_path();
+    let smart_fill = placement.smart_crop && placement.mode == FitMode::Fill;
+    let portrait = || {
+        crops_portrait(placement)
+            && display::monitors().map_or(false, |monitors| {
+                monitors.iter().any(MonitorInfo::is_portrait)
+            })
+    };
+    if placement.monitor_images.is_empty()
+        && !placement.span
+        && !smart_fill
+        && temperature::kelvin_now().is_none()
+        && wallpaper_manager
+            .native_fit_modes()
+            .contains(&placement.mode)
+        && !portrait()
+    {
+        wallpaper_manager
+            .set_static_wallpaper_fitted(path, placement.mode)
+            .await?;
+        return Ok(Vec::new());
+    }
+
+    let monitors = match display::monitors() {
+        Ok(monitors) if !monitors.is_empty() => monitors,
+        Ok(_) => {
+            warn!("No monitors detected; setting the wallpaper without placement");
+            wallpaper_manager.set_static_wallpaper(path).await?;
+            return Ok(Vec::new());
+        }
+        Err(e) => {
+            warn!(
+                "Failed to detect monitors ({}); setting the wallpaper without placement",
+                e
+            );
+            wallpaper_manager.set_static_wallpaper(path).await?;
+            return Ok(Vec::new());
+        }
+    };
+
+    // A backend spanning one image itself is given the whole canvas
+    if placement.span && placement.monitor_images.is_empty() && wallpaper_manager.native_span() {
+        if let Some(layout) = SpanLayout::of(&monitors) {
+            let (path, mode) = (path.to_path_buf(), placement.mode);
+            let canvas = tokio::task::spawn_blocking(move || {
+                render_spanned(&path, mode, &layout, &Config::get_placement_dir())
+            })
+            .await
+            .map_err(|e| {
+                AppError::WallpaperError(format!("Failed to render wallpaper: {}", e))
+            })??;
+            wallpaper_manager.set_spanned_wallpaper(&canvas).await?;
+            return Ok(Vec::new());
+        }
+    }
+
+    let path = path.to_path_buf();
+    let placement = placement.clone();
+    let images = tokio::task::spawn_blocking(move || {
+        render_for_monitors(&path, &placement, &monitors, &Config::get_placement_dir())
+    })
+    .await
+    .map_err(|e| AppError::WallpaperError(format!("Failed to render wallpaper: {}", e)))??;
+
+    info!("Rendered wallpaper for {} monitors", images.len());
+    wallpaper_manager.set_monitor_wallpapers(&images).await?;
+    Ok(images.into_iter().map(|(monitor, _)| monitor).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    /// 4x2 image: left half red, right half blue
+    fn test_image() -> DynamicImage {
+        let mut image = RgbaImage::new(4, 2);
+        for (x, _, pixel) in image.enumerate_pixels_mut() {
+            *pixel = if x < 2 {
+                Rgba([255, 0, 0, 255])
+            } else {
+                Rgba([0, 0, 255, 255])
+            };
+        }
+        DynamicImage::ImageRgba8(image)
+    }
+
+    #[test]
+    fn test_render_sizes() {
+        let image = test_image();
+        for mode in [
+            FitMode::Fill,
+            FitMode::Fit,
+            FitMode::Stretch,
+            FitMode::Center,
+            FitMode::Tile,
+            FitMode::Crop,
+        ] {
+            let rendered = render(&image, mode, None, 8, 8);
+            assert_eq!(rendered.dimensions(), (8, 8), "{:?}", mode);
+        }
+    }
+
+    #[test]
+    fn test_render_modes() {
+        let image = test_image();
+
+        // Fit leaves bars above and below
+        let fit = render(&image, FitMode::Fit, None, 8, 8);
+        assert_eq!(fit.get_pixel(0, 0), &BACKGROUND);
+        assert_eq!(fit.get_pixel(0, 4)[0], 255);
+
+        // Center keeps the original size
+        let center = render(&image, FitMode::Center, None, 8, 8);
+        assert_eq!(center.get_pixel(1, 1), &BACKGROUND);
+        assert_eq!(center.get_pixel(2, 3), &Rgba([255, 0, 0, 255]));
+        assert_eq!(center.get_pixel(5, 4), &Rgba([0, 0, 255, 255]));
+
+        // Tile repeats from the top-left corner
+        let tile = render(&image, FitMode::Tile, None, 8, 4);
+        assert_eq!(tile.get_pixel(4, 2), &Rgba([255, 0, 0, 255]));
+        assert_eq!(tile.get_pixel(7, 3), &Rgba([0, 0, 255, 255]));
+
+        // Crop shows only the chosen part
+        let crop = CropRect {
+            x: 0.5,
+            y: 0.0,
+            width: 0.5,
+            height: 1.0,
+        };
+        let cropped = render(&image, FitMode::Crop, Some(crop), 4, 4);
+        assert!(cropped.pixels().all(|pixel| pixel[2] > pixel[0]));
+    }
+
+    #[test]
+    fn test_crop_rect() {
+        // A 16:9 monitor on a square image uses the full width
+        let crop = CropRect::centered(1000, 1000, 1920, 1080);
+        assert_eq!(crop.width, 1.0);
+        assert!((crop.height - 0.5625).abs() < 1e-4);
+        assert!((crop.y - 0.21875).abs() < 1e-4);
+
+        // Resizing keeps the aspect ratio and stays inside the image
+        let smaller = crop.with_width(0.5, 1000, 1000, 1920, 1080);
+        assert!((smaller.height - 0.28125).abs() < 1e-4);
+        let larger = CropRect { x: 0.8, ..smaller }.with_width(2.0, 1000, 1000, 1920, 1080);
+        assert_eq!(larger.width, 1.0);
+        assert_eq!(larger.x, 0.0);
+
+        let moved = CropRect {
+            x: -0.3,
+            y: 0.9,
+            width: 0.5,
+            height: 0.5,
+        }
+        .clamped();
+        assert_eq!((moved.x, moved.y), (0.0, 0.5));
+    }
+
+    #[test]
+    fn test_smart_crop_follows_detail() {
+        // Flat 4:1 image with a checkerboard in its right quarter
+        let mut image = RgbaImage::from_pixel(400, 100, Rgba([40, 90, 160, 255]));
+        for (x, y, pixel) in image.enumerate_pixels_mut() {
+            if x >= 300 && (x / 5 + y / 5) % 2 == 0 {
+                *pixel = Rgba([255, 255, 255, 255]);
+            }
+        }
+        let image = DynamicImage::ImageRgba8(image);
+
+        let crop = CropRect::smart(&image, 1000, 1000);
+        assert!((crop.width - 0.25).abs() < 1e-4);
+        assert!((crop.x - 0.75).abs() < 0.02);
+
+        // Without detail, or when nothing overflows, it stays centered
+        let flat = DynamicImage::ImageRgba8(RgbaImage::from_pixel(400, 100, Rgba([0, 0, 0, 255])));
+        assert!((CropRect::smart(&flat, 1000, 1000).x - 0.375).abs() < 0.02);
+        assert_eq!(
+            CropRect::smart(&image, 1600, 400),
+            CropRect::centered(400, 100, 1600, 400)
+        );
+    }
+
+    #[test]
+    fn test_portrait_monitors_crop_landscape_images() {
+        // Landscape image: left half red, right half blue
+        let image = DynamicImage::ImageRgba8(RgbaImage::from_fn(200, 100, |x, _| {
+            if x < 100 {
+                Rgba([255, 0, 0, 255])
+            } else {
+                Rgba([0, 0, 255, 255])
+            }
+        }));
+
+        // Fitting to a portrait monitor would leave bars above and below
+        let placement = PlacementConfig {
+            mode: FitMode::Fit,
+            ..Default::default()
+        };
+        let cropped = render_placed(&image, &placement, None, 50, 100);
+        assert_ne!(cropped.get_pixel(25, 0), &BACKGROUND);
+
+        let letterboxed = render_placed(
+            &image,
+            &PlacementConfig {
+                letterbox_portrait: true,
+                ..placement.clone()
+            },
+            None,
+            50,
+            100,
+        );
+        assert_eq!(letterboxed.get_pixel(25, 0), &BACKGROUND);
+
+        // Landscape monitors are fitted as usual
+        let fitted = render_placed(&image, &placement, None, 100, 100);
+        assert_eq!(fitted.get_pixel(50, 0), &BACKGROUND);
+    }
+
+    #[test]
+    fn test_render_for_monitors() {
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("source.png");
+        test_image().save(&source).unwrap();
+
+        let monitors = vec![
+            MonitorInfo::new("DP-1", 8, 4),
+            MonitorInfo::new("HDMI-A-1", 4, 4),
+        ];
+        let output_dir = dir.path().join("placement");
+        fs::create_dir_all(&output_dir).unwrap();
+        fs::write(output_dir.join("stale.png"), "old").unwrap();
+
+        let placement = PlacementConfig {
+            mode: FitMode::Stretch,
+            ..Default::default()
+        };
+        let images = render_for_monitors(&source, &placement, &monitors, &output_dir).unwrap();
+        assert_eq!(images.len(), 2);
+        assert_eq!(images[1].0, "HDMI-A-1");
+        assert_eq!(image::image_dimensions(&images[0].1).unwrap(), (8, 4));
+        assert!(!output_dir.join("stale.png").exists());
+    }
+
+    #[test]
+    fn test_render_monitor_images() {
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("source.png");
+        test_image().save(&source).unwrap();
+        let green = dir.path().join("green.png");
+        RgbaImage::from_pixel(2, 2, Rgba([0, 255, 0, 255]))
+            .save(&green)
+            .unwrap();
+
+        let monitors = vec![
+            MonitorInfo::new("DP-1", 4, 4),
+            MonitorInfo::new("HDMI-A-1", 4, 4),
+            MonitorInfo::new("DP-2", 4, 4),
+        ];
+        let placement = PlacementConfig {
+            mode: FitMode::Stretch,
+            monitor_images: HashMap::from([
+                ("HDMI-A-1".to_string(), green.display().to_string()),
+                (
+                    "DP-2".to_string(),
+                    dir.path().join("missing.png").display().to_string(),
+                ),
+            ]),
+            ..Default::default()
+        };
+        let images = render_for_monitors(
+            &source,
+            &placement,
+            &monitors,
+            &dir.path().join("placement"),
+        )
+        .unwrap();
+        let pixel = |index: usize| {
+            *image::open(&images[index].1)
+                .unwrap()
+                .to_rgba8()
+                .get_pixel(0, 0)
+        };
+        assert_eq!(pixel(0), Rgba([255, 0, 0, 255]));
+        assert_eq!(pixel(1), Rgba([0, 255, 0, 255]));
+        assert_eq!(pixel(2), Rgba([255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_render_spanned() {
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("source.png");
+        test_image().save(&source).unwrap();
+
+        // Side by side, each shows its half of the image
+        let mut right = MonitorInfo::new("HDMI-A-1", 4, 4);
+        right.x = 4;
+        let monitors = vec![MonitorInfo::new("DP-1", 4, 4), right];
+        let placement = PlacementConfig {
+            mode: FitMode::Stretch,
+            span: true,
+            ..Default::default()
+        };
+        let images = render_for_monitors(
+            &source,
+            &placement,
+            &monitors,
+            &dir.path().join("placement"),
+        )
+        .unwrap();
+        let rendered = |index: usize| image::open(&images[index].1).unwrap().to_rgba8();
+        assert_eq!(rendered(0).dimensions(), (4, 4));
+        assert!(rendered(0).pixels().all(|pixel| pixel[0] > pixel[2]));
+        assert!(rendered(1).pixels().all(|pixel| pixel[2] > pixel[0]));
+
+        let layout = SpanLayout::of(&monitors).unwrap();
+        let canvas = render_spanned(
+            &source,
+            FitMode::Stretch,
+            &layout,
+            &dir.path().join("placement"),
+        )
+        .unwrap();
+        assert_eq!(image::image_dimensions(&canvas).unwrap(), (8, 4));
+        assert_eq!(
+            fs::read_dir(dir.path().join("placement")).unwrap().count(),
+            1
+        );
+    }
+}
@@ -0,0 +1,623 @@
+//! Helper process tracking
+//!
+//! Wallpapers that run external players register their processes here so
+//! frontends can show what is running and how much it costs. The daemon also
+//! keeps the registry in a PID file, so the players a crash leaves behind can
+//! be found on the next start.
+//!
+//! Players are started through `ProcessHandle`, which owns the child process,
+//! registers it and pauses it by suspending it.
+use crate::core::{AppError, AppResult};
+use chrono::Local;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Child, ChildStdin, ChildStdout, Command, ExitStatus};
+use std::sync::{Arc, Mutex, OnceLock};
+use sysinfo::{Pid, Signal, System};
+
+/// Helper process snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessInfo {
+    /// Process ID
+    pub pid: u32,
+
+    /// Executable name (e.g. "mpv")
+    pub name: String,
+
+    /// What the process is used for (e.g. "video wallpaper")
+    pub role: String,
+
+    /// Time the process was registered (RFC 3339)
+    pub started: String,
+
+    /// Resident memory in bytes
+    pub memory_bytes: u64,
+
+    /// CPU usage percentage since the previous snapshot
+    pub cpu_usage: f32,
+
+    /// Whether the process was suspended for going over its resource limits
+    #[serde(default)]
+    pub paused: bool,
+}
+
+/// Registered helper process
+#[derive(Debug, Clone)]
+struct TrackedProcess {
+    /// Process ID
+    pid: u32,
+
+    /// Executable name
+    name: String,
+
+    /// What the process is used for
+    role: String,
+
+    /// Registration time (RFC 3339)
+    started: String,
+
+    /// Whether the process is suspended
+    paused: bool,
+
+    /// Process start time in seconds since the epoch, telling it apart from a later process with the same ID
+    start_time: u64,
+}
+
+/// Registered process as saved in the PID file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PidRecord {
+    /// Process ID
+    pub pid: u32,
+
+    /// Executable name
+    pub name: String,
+
+    /// What the process is used for
+    pub role: String,
+
+    /// Process start time in seconds since the epoch
+    pub start_time: u64,
+}
+
+/// Registry of helper processes spawned for wallpapers
+pub struct ProcessController {
+    /// Registered processes
+    processes: Mutex<Vec<TrackedProcess>>,
+
+    /// System information used to sample resource use
+    system: Mutex<System>,
+
+    /// File the registered processes are saved to, if any
+    pid_file: Mutex<Option<PathBuf>>,
+}
+
+#[allow(dead_code)]
+impl ProcessController {
+    /// Create an empty process controller
+    pub fn new() -> Self {
+        Self {
+            processes: Mutex::new(Vec::new()),
+            system: Mutex::new(System::new()),
+            pid_file: Mutex::new(None),
+        }
+    }
+
+    /// Get the process-wide process controller
+    pub fn shared() -> Arc<ProcessController> {
+        static SHARED: OnceLock<Arc<ProcessController>> = OnceLock::new();
+        SHARED
+            .get_or_init(|| Arc::new(ProcessController::new()))
+            .clone()
+    }
+
+    /// Register a helper process
+    pub fn register(&self, pid: u32, name: &str, role: &str) {
+        let start_time = {
+            let mut system = self.system.lock().unwrap();
+            system.refresh_process(Pid::from_u32(pid));
+            system
+                .process(Pid::from_u32(pid))
+                .map_or(0, |sample| sample.start_time())
+        };
+
+        let mut processes = self.processes.lock().unwrap();
+        processes.retain(|process| process.pid != pid);
+        processes.push(TrackedProcess {
+            pid,
+            name: name.to_string(),
+            role: role.to_string(),
+            started: Local::now().to_rfc3339(),
+            paused: false,
+            start_time,
+        });
+        self.save(&processes);
+        debug!("Registered {} process {} ({})", role, name, pid);
+    }
+
+    /// Unregister a helper process
+    pub fn unregister(&self, pid: u32) {
+        let mut processes = self.processes.lock().unwrap();
+        processes.retain(|process| process.pid != pid);
+        self.save(&processes);
+        debug!("Unregistered process {}", pid);
+    }
+
+    /// Keep the registered processes in a file from now on
+    pub fn set_pid_file(&self, path: PathBuf) {
+        *self.pid_file.lock().unwrap() = Some(path);
+        self.save(&self.processes.lock().unwrap());
+    }
+
+    /// Write the registered processes to the PID file
+    fn save(&self, processes: &[TrackedProcess]) {
+        let Some(path) = self.pid_file.lock().unwrap().clone() else {
+            return;
+        };
+
+        let records: Vec<PidRecord> = processes
+            .iter()
+            .map(|process| PidRecord {
+                pid: process.pid,
+                name: process.name.clone(),
+                role: process.role.clone(),
+                start_time: process.start_time,
+            })
+            .collect();
+        let result = serde_json::to_string_pretty(&records)
+            .map_err(|e| e.to_string())
+            .and_then(|json| fs::write(&path, json).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            warn!("Failed to save PID file {}: {}", path.display(), e);
+        }
+    }
+
+    /// Check whether a process is registered
+    pub fn is_registered(&self, pid: u32) -> bool {
+        self.processes
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|process| process.pid == pid)
+    }
+
+    /// Get the registered process IDs
+    pub fn pids(&self) -> Vec<u32> {
+        self.processes
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|process| process.pid)
+            .collect()
+    }
+
+    /// Sample the registered processes
+    ///
+    /// Processes that have exited are dropped from the registry.
+    pub fn snapshot(&self) -> Vec<ProcessInfo> {
+        let mut processes = self.processes.lock().unwrap();
+        let mut system = self.system.lock().unwrap();
+
+        let count = processes.len();
+        processes.retain(|process| system.refresh_process(Pid::from_u32(process.pid)));
+        if processes.len() != count {
+            self.save(&processes);
+        }
+        processes
+            .iter()
+            .filter_map(|process| {
+                let sample = system.process(Pid::from_u32(process.pid))?;
+                Some(ProcessInfo {
+                    pid: process.pid,
+                    name: process.name.clone(),
+                    role: process.role.clone(),
+                    started: process.started.clone(),
+                    memory_bytes: sample.memory(),
+                    cpu_usage: sample.cpu_usage(),
+                    paused: process.paused,
+                })
+            })
+            .collect()
+    }
+
+    /// Suspend a registered process
+    ///
+    /// Returns false when the platform can't suspend processes (Windows).
+    pub fn suspend(&self, pid: u32) -> bool {
+        let mut processes = self.processes.lock().unwrap();
+        let Some(process) = processes.iter_mut().find(|process| process.pid == pid) else {
+            return false;
+        };
+
+        let mut system = self.system.lock().unwrap();
+        system.refresh_process(Pid::from_u32(pid));
+        let suspended = system
+            .process(Pid::from_u32(pid))
+            .and_then(|sample| sample.kill_with(Signal::Stop))
+            .unwrap_or(false);
+        if suspended {
+            process.paused = true;
+            debug!("Suspended process {}", pid);
+        }
+        suspended
+    }
+
+    /// Resume a suspended process
+    pub fn resume(&self, pid: u32) -> bool {
+        let mut processes = self.processes.lock().unwrap();
+        let Some(process) = processes.iter_mut().find(|process| process.pid == pid) else {
+            return false;
+        };
+
+        let mut system = self.system.lock().unwrap();
+        system.refresh_process(Pid::from_u32(pid));
+        let resumed = system
+            .process(Pid::from_u32(pid))
+            .and_then(|sample| sample.kill_with(Signal::Continue))
+            .unwrap_or(false);
+        if resumed {
+            process.paused = false;
+            debug!("Resumed process {}", pid);
+        }
+        resumed
+    }
+
+    /// Kill a registered process and unregister it
+    pub fn kill(&self, pid: u32) -> bool {
+        let mut processes = self.processes.lock().unwrap();
+        if !processes.iter().any(|process| process.pid == pid) {
+            return false;
+        }
+
+        let mut system = self.system.lock().unwrap();
+        system.refresh_process(Pid::from_u32(pid));
+        let killed = system
+            .process(Pid::from_u32(pid))
+            .map_or(false, |sample| sample.kill());
+        if killed {
+            processes.retain(|process| process.pid != pid);
+            self.save(&processes);
+            debug!("Killed process {}", pid);
+        }
+        killed
+    }
+}
+
+impl Default for ProcessController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Player process owned by a wallpaper
+///
+/// The process is registered with the shared controller while it runs and is
+/// killed when the handle is dropped.
+#[derive(Debug)]
+pub struct ProcessHandle {
+    /// Child process
+    child: Child,
+
+    /// Executable name
+    name: String,
+}
+
+#[allow(dead_code)]
+impl ProcessHandle {
+    /// Start a player and register it
+    pub fn spawn(command: &mut Command, name: &str, role: &str) -> AppResult<Self> {
+        let child = command.spawn().map_err(|e| AppError::from_spawn(name, e))?;
+        ProcessController::shared().register(child.id(), name, role);
+        debug!("Started {} process {} ({})", role, name, child.id());
+        Ok(Self {
+            child,
+            name: name.to_string(),
+        })
+    }
+
+    /// Get the process ID
+    pub fn id(&self) -> u32 {
+        self.child.id()
+    }
+
+    /// Get the executable name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Suspend the player
+    pub fn suspend(&self) -> AppResult<()> {
+        if ProcessController::shared().suspend(self.id()) {
+            Ok(())
+        } else {
+            Err(self.not_controllable("can't be paused"))
+        }
+    }
+
+    /// Resume a suspended player
+    pub fn resume(&self) -> AppResult<()> {
+        if ProcessController::shared().resume(self.id()) {
+            Ok(())
+        } else {
+            Err(self.not_controllable("can't be resumed"))
+        }
+    }
+
+    /// Check whether the player exited, and how
+    pub fn exited(&mut self) -> Option<ExitStatus> {
+        self.child.try_wait().ok().flatten()
+    }
+
+    /// Take the process's standard output, when it was piped
+    pub fn take_stdout(&mut self) -> Option<ChildStdout> {
+        self.child.stdout.take()
+    }
+
+    /// Take the process's standard input, when it was piped
+    pub fn take_stdin(&mut self) -> Option<ChildStdin> {
+        self.child.stdin.take()
+    }
+
+    /// Check whether the player is still registered
+    ///
+    /// Players killed over their resource limits are unregistered first.
+    pub fn is_tracked(&self) -> bool {
+        ProcessController::shared().is_registered(self.id())
+    }
+
+    /// Stop the player and wait for it to exit
+    pub fn kill(mut self) {
+        self.terminate();
+    }
+
+    /// Unregister the player and kill it if it is still running
+    fn terminate(&mut self) {
+        ProcessController::shared().unregister(self.id());
+        if self.exited().is_some() {
+            return;
+        }
+        if let Err(e) = self.child.kill() {
+            warn!("Failed to kill {} process {}: {}", self.name, self.id(), e);
+        }
+        let _ = self.child.wait();
+    }
+
+    /// Get the error for a player that can't be signalled
+    fn not_controllable(&self, what: &str) -> AppError {
+        let reason = if cfg!(windows) {
+            format!("processes {} on Windows", what)
+        } else {
+            format!("process {} {}", self.id(), what)
+        };
+        AppError::BackendUnavailable {
+            backend: self.name.clone(),
+            reason,
+        }
+    }
+}
+
+impl Drop for ProcessHandle {
+    fn drop(&mut self) {
+        self.terminate();
+    }
+}
+
+/// Player of the wallpaper a backend is showing, if any
+///
+/// A wallpaper spanning the monitors has a player per monitor; the first
+/// one stands for them all where a single process is asked for. Wallpapers
+/// shown on single monitors have players of their own, kept apart from the
+/// ones covering every monitor.
+#[derive(Debug, Default)]
+pub struct PlayerSlot {
+    /// Running players
+    handles: Mutex<Vec<ProcessHandle>>,
+
+    /// Players of wallpapers shown on single monitors, by monitor name
+    monitors: Mutex<HashMap<String, ProcessHandle>>,
+}
+
+#[allow(dead_code)]
+impl PlayerSlot {
+    /// Create an empty slot
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a player in place of the current one
+    pub fn start(&self, command: &mut Command, name: &str, role: &str) -> AppResult<()> {
+        self.start_all(std::slice::from_mut(command), name, role)
+    }
+
+    /// Start several players together in place of the current ones
+    ///
+    /// When one fails to start, those already started are stopped again.
+    pub fn start_all(&self, commands: &mut [Command], name: &str, role: &str) -> AppResult<()> {
+        self.stop();
+        let handles = commands
+            .iter_mut()
+            .map(|command| ProcessHandle::spawn(command, name, role))
+            .collect::<AppResult<Vec<_>>>()?;
+        *self.handles.lock().unwrap() = handles;
+        Ok(())
+    }
+
+    /// Stop the current players
+    ///
+    /// Players on single monitors keep running.
+    pub fn stop(&self) {
+        for handle in self.handles.lock().unwrap().drain(..) {
+            handle.kill();
+        }
+    }
+
+    /// Start a player on one monitor in place of the one there
+    pub fn start_on(
+        &self,
+        monitor: &str,
+        command: &mut Command,
+        name: &str,
+        role: &str,
+    ) -> AppResult<()> {
+        self.stop_on(monitor);
+        let handle = ProcessHandle::spawn(command, name, role)?;
+        self.monitors
+            .lock()
+            .unwrap()
+            .insert(monitor.to_string(), handle);
+        Ok(())
+    }
+
+    /// Stop the player on one monitor
+    pub fn stop_on(&self, monitor: &str) {
+        if let Some(handle) = self.monitors.lock().unwrap().remove(monitor) {
+            handle.kill();
+        }
+    }
+
+    /// Get the process ID of the player on one monitor
+    pub fn pid_on(&self, monitor: &str) -> Option<u32> {
+        self.monitors
+            .lock()
+            .unwrap()
+            .get(monitor)
+            .map(ProcessHandle::id)
+    }
+
+    /// Suspend the current player
+    pub fn suspend(&self) -> AppResult<()> {
+        self.with_player(ProcessHandle::suspend)
+    }
+
+    /// Resume the current player
+    pub fn resume(&self) -> AppResult<()> {
+        self.with_player(ProcessHandle::resume)
+    }
+
+    /// Get the process ID of the current player, the first one when spanning
+    pub fn pid(&self) -> Option<u32> {
+        self.handles.lock().unwrap().first().map(ProcessHandle::id)
+    }
+
+    /// Check whether a player is running
+    pub fn is_running(&self) -> bool {
+        !self.handles.lock().unwrap().is_empty() || !self.monitors.lock().unwrap().is_empty()
+    }
+
+    /// Run an action on each current player, including those on single monitors
+    fn with_player(&self, action: impl Fn(&ProcessHandle) -> AppResult<()>) -> AppResult<()> {
+        let handles = self.handles.lock().unwrap();
+        let monitors = self.monitors.lock().unwrap();
+        if handles.is_empty() && monitors.is_empty() {
+            return Err(AppError::WallpaperError(
+                "No wallpaper player is running".to_string(),
+            ));
+        }
+        handles.iter().chain(monitors.values()).try_for_each(action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_tracks_live_processes_only() {
+        let controller = ProcessController::new();
+        controller.register(std::process::id(), "aether-desk", "test");
+        controller.register(u32::MAX, "gone", "test");
+
+        let snapshot = controller.snapshot();
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!(snapshot[0].pid, std::process::id());
+        assert_eq!(controller.pids(), vec![std::process::id()]);
+
+        controller.unregister(std::process::id());
+        assert!(controller.snapshot().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_suspend_and_kill() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let controller = ProcessController::new();
+        assert!(!controller.suspend(child.id()));
+
+        controller.register(child.id(), "sleep", "test");
+        assert!(controller.suspend(child.id()));
+        assert!(controller.snapshot()[0].paused);
+
+        assert!(controller.kill(child.id()));
+        assert!(controller.pids().is_empty());
+        assert!(!child.wait().unwrap().success());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_process_handle() {
+        let mut handle =
+            ProcessHandle::spawn(Command::new("sleep").arg("30"), "sleep", "test").unwrap();
+        let pid = handle.id();
+        assert!(handle.is_tracked());
+        handle.suspend().unwrap();
+        handle.resume().unwrap();
+        assert!(handle.exited().is_none());
+
+        handle.kill();
+        assert!(!ProcessController::shared().is_registered(pid));
+
+        let missing = ProcessHandle::spawn(
+            &mut Command::new("aether-desk-missing-player"),
+            "aether-desk-missing-player",
+            "test",
+        );
+        assert!(matches!(missing, Err(AppError::MissingExternalTool { .. })));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_player_slot_keeps_monitors_apart() {
+        let slot = PlayerSlot::new();
+        slot.start(Command::new("sleep").arg("30"), "sleep", "test")
+            .unwrap();
+        slot.start_on("DP-1", Command::new("sleep").arg("30"), "sleep", "test")
+            .unwrap();
+        let on_monitor = slot.pid_on("DP-1").unwrap();
+
+        // Stopping the player on every monitor leaves the monitor's own one running
+        slot.stop();
+        assert!(slot.is_running());
+        assert!(ProcessController::shared().is_registered(on_monitor));
+        slot.suspend().unwrap();
+        slot.resume().unwrap();
+
+        slot.stop_on("DP-1");
+        assert!(!slot.is_running());
+        assert!(!ProcessController::shared().is_registered(on_monitor));
+    }
+
+    #[test]
+    fn test_pid_file_follows_registry() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("players.json");
+        let controller = ProcessController::new();
+        controller.register(std::process::id(), "aether-desk", "test");
+        controller.set_pid_file(path.clone());
+
+        let records: Vec<PidRecord> =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].pid, std::process::id());
+        assert!(records[0].start_time > 0);
+
+        controller.unregister(std::process::id());
+        let records: Vec<PidRecord> =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert!(records.is_empty());
+    }
+}
@@ -0,0 +1,317 @@
+//! Named configuration profiles
+//!
+//! A profile ("Work", "Gaming", "Presentation", ...) is a snapshot of the
+//! wallpaper settings, the schedule and the widgets, kept in
+//! `profiles/<name>/` under the configuration directory. Favorites stay
+//! shared between profiles.
+use crate::core::config::WallpaperConfig;
+use crate::core::format::{self, FileFormat};
+use crate::core::{AppError, AppResult, Config};
+use log::info;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Wallpaper settings file inside a profile
+const WALLPAPER_FILE: &str = "wallpaper.json";
+
+/// Name of the schedule file inside a profile, without the extension
+const SCHEDULE_NAME: &str = "schedule";
+
+/// Name of the widgets file inside a profile, without the extension
+const WIDGETS_NAME: &str = "widgets";
+
+/// Profiles on disk
+#[derive(Debug, Clone)]
+pub struct ProfileStore {
+    /// Directory holding one folder per profile
+    dir: PathBuf,
+
+    /// Live schedule file
+    schedule_file: PathBuf,
+
+    /// Live widgets file
+    widgets_file: PathBuf,
+}
+
+impl ProfileStore {
+    /// Open the profiles of a configuration
+    pub fn open(config: &Config) -> Self {
+        Self::at(
+            Config::get_profiles_dir(),
+            config.get_schedule_file(),
+            config.get_widgets_file(),
+        )
+    }
+
+    /// Open profiles in `dir` that snapshot the given live files
+    pub(crate) fn at(dir: PathBuf, schedule_file: PathBuf, widgets_file: PathBuf) -> Self {
+        Self {
+            dir,
+            schedule_file,
+            widgets_file,
+        }
+    }
+
+    /// List the profile names in alphabetical order
+    pub fn list(&self) -> AppResult<Vec<String>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut names: Vec<String> = fs::read_dir(&self.dir)?
+            .flatten()
+            .filter(|entry| entry.path().join(WALLPAPER_FILE).is_file())
+            .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+            .collect();
+        names.sort_by_key(|name| name.to_lowercase());
+        Ok(names)
+    }
+
+    /// Check whether a profile exists
+    pub fn exists(&self, name: &str) -> bool {
+        validate_name(name).is_ok() && self.dir.join(name).join(WALLPAPER_FILE).is_file()
+    }
+
+    /// Save the current wallpaper settings, schedule and widgets as a profile
+    ///
+    /// Overwrites a profile with the same name.
+    pub fn save(&self, name: &str, config: &Config) -> AppResult<()> {
+        let profile_dir = self.profile_dir(name)?;
+        fs::create_dir_all(&profile_dir)?;
+
+        fs::write(
+            profile_dir.join(WALLPAPER_FILE),
+            serde_json::to_string_pretty(&config.wallpaper)?,
+        )?;
+        snapshot(&self.schedule_file, &profile_dir, SCHEDULE_NAME)?;
+        snapshot(&self.widgets_file, &profile_dir, WIDGETS_NAME)?;
+
+        info!("Saved profile {}", name);
+        Ok(())
+    }
+
+    /// Switch to a profile
+    ///
+    /// The current state is saved into the active profile first, so changes
+    /// made since the last switch aren't lost. Writes the schedule and
+    /// widgets files and updates `config`; the caller saves the configuration.
+    pub fn switch(&self, name: &str, config: &mut Config) -> AppResult<()> {
+        let profile_dir = self.profile_dir(name)?;
+        if !self.exists(name) {
+            return Err(AppError::ConfigError(format!("No profile named {}", name)));
+        }
+
+        if let Some(active) = config.app.active_profile.clone() {
+            if active != name && self.exists(&active) {
+                self.save(&active, config)?;
+            }
+        }
+
+        let mut wallpaper = self.wallpaper(name)?;
+        wallpaper.favorites = std::mem::take(&mut config.wallpaper.favorites);
+        config.wallpaper = wallpaper;
+
+        // Profiles saved in the other format are converted to the live one
+        copy_if_exists(
+            &format::config_file(&profile_dir, SCHEDULE_NAME),
+            &self.schedule_file,
+        )?;
+        copy_if_exists(
+            &format::config_file(&profile_dir, WIDGETS_NAME),
+            &self.widgets_file,
+        )?;
+        config.app.active_profile = Some(name.to_string());
+
+        info!("Switched to profile {}", name);
+        Ok(())
+    }
+
+    /// Delete a profile
+    ///
+    /// Deleting the active profile leaves the current settings in place.
+    pub fn delete(&self, name: &str, config: &mut Config) -> AppResult<()> {
+        let profile_dir = self.profile_dir(name)?;
+        if !self.exists(name) {
+            return Err(AppError::ConfigError(format!("No profile named {}", name)));
+        }
+
+        fs::remove_dir_all(profile_dir)?;
+        if config.app.active_profile.as_deref() == Some(name) {
+            config.app.active_profile = None;
+        }
+        config
+            .app
+            .topology_profiles
+            .retain(|_, bound| bound != name);
+
+        info!("Deleted profile {}", name);
+        Ok(())
+    }
+
+    /// Get the wallpaper settings saved in a profile
+    pub fn wallpaper(&self, name: &str) -> AppResult<WallpaperConfig> {
+        let wallpaper = fs::read_to_string(self.profile_dir(name)?.join(WALLPAPER_FILE))?;
+        Ok(serde_json::from_str(&wallpaper)?)
+    }
+
+    /// Get the schedule file saved in a profile
+    pub fn schedule_file(&self, name: &str) -> AppResult<PathBuf> {
+        Ok(format::config_file(&self.profile_dir(name)?, SCHEDULE_NAME))
+    }
+
+    /// Get the folder of a profile, rejecting names that aren't plain folder names
+    fn profile_dir(&self, name: &str) -> AppResult<PathBuf> {
+        validate_name(name)?;
+        Ok(self.dir.join(name))
+    }
+}
+
+/// Check that a profile name is usable as a folder name
+pub fn validate_name(name: &str) -> AppResult<()> {
+    let valid = !name.trim().is_empty()
+        && name.trim() == name
+        && name.len() <= 64
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(AppError::ConfigError(format!(
+            "Invalid profile name {:?}; use letters, digits, spaces, '-', '_' and '.'",
+            name
+        )))
+    }
+}
+
+/// Copy a live file into a profile, keeping its format
+fn snapshot(live: &Path, profile_dir: &Path, name: &str) -> AppResult<()> {
+    let target = profile_dir.join(format!("{}.{}", name, FileFormat::of(live).extension()));
+
+    // A copy in the other format would be picked over it when switching back
+    for format in [FileFormat::Json, FileFormat::Toml] {
+        let other = profile_dir.join(format!("{}.{}", name, format.extension()));
+        if other != target && other.is_file() {
+            fs::remove_file(other)?;
+        }
+    }
+    copy_if_exists(live, &target)
+}
+
+/// Copy a file, converting it to the target's format, and remove the target
+/// when the source doesn't exist
+fn copy_if_exists(from: &Path, to: &Path) -> AppResult<()> {
+    if from.is_file() {
+        format::copy(from, to).map_err(|e| {
+            AppError::ConfigError(format!("Failed to copy {}: {}", from.display(), e))
+        })?;
+    } else if to.is_file() {
+        fs::remove_file(to)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_validate_name() {
+        for name in ["Work", "Gaming 2", "late-night_v1.5", "Präsentation"] {
+            assert!(validate_name(name).is_ok(), "{}", name);
+        }
+        for name in ["", " Work", "..", ".hidden", "a/b", "a\\b", "C:"] {
+            assert!(validate_name(name).is_err(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_save_and_switch() {
+        let dir = tempdir().unwrap();
+        let schedule_file = dir.path().join("schedule.json");
+        let widgets_file = dir.path().join("widgets.json");
+        let store = ProfileStore::at(
+            dir.path().join("profiles"),
+            schedule_file.clone(),
+            widgets_file.clone(),
+        );
+        assert!(store.list().unwrap().is_empty());
+
+        let mut config = Config::default();
+        config.wallpaper.current_path = Some("/walls/desk.png".to_string());
+        config.wallpaper.favorites = vec!["/walls/fav.png".to_string()];
+        fs::write(&schedule_file, "work schedule").unwrap();
+        fs::write(&widgets_file, "work widgets").unwrap();
+        store.save("Work", &config).unwrap();
+        config.app.active_profile = Some("Work".to_string());
+
+        config.wallpaper.current_path = Some("/walls/game.mp4".to_string());
+        fs::write(&schedule_file, "gaming schedule").unwrap();
+        fs::remove_file(&widgets_file).unwrap();
+        store.save("Gaming", &config).unwrap();
+        assert_eq!(store.list().unwrap(), vec!["Gaming", "Work"]);
+
+        // Edits since the last switch land in the active profile before switching away
+        config.wallpaper.current_path = Some("/walls/desk2.png".to_string());
+        store.switch("Gaming", &mut config).unwrap();
+        assert_eq!(config.app.active_profile.as_deref(), Some("Gaming"));
+        assert_eq!(
+            config.wallpaper.current_path.as_deref(),
+            Some("/walls/game.mp4")
+        );
+        assert_eq!(config.wallpaper.favorites, vec!["/walls/fav.png"]);
+        assert_eq!(
+            fs::read_to_string(&schedule_file).unwrap(),
+            "gaming schedule"
+        );
+        assert!(!widgets_file.exists());
+
+        store.switch("Work", &mut config).unwrap();
+        assert_eq!(
+            config.wallpaper.current_path.as_deref(),
+            Some("/walls/desk2.png")
+        );
+        assert_eq!(fs::read_to_string(&widgets_file).unwrap(), "work widgets");
+
+        assert!(store.switch("Missing", &mut config).is_err());
+        assert!(store.switch("../etc", &mut config).is_err());
+
+        store.delete("Work", &mut config).unwrap();
+        assert_eq!(config.app.active_profile, None);
+        assert_eq!(store.list().unwrap(), vec!["Gaming"]);
+    }
+
+    #[test]
+    fn test_switch_converts_format() {
+        let dir = tempdir().unwrap();
+        let json_store = ProfileStore::at(
+            dir.path().join("profiles"),
+            dir.path().join("schedule.json"),
+            dir.path().join("widgets.json"),
+        );
+        let mut config = Config::default();
+        fs::write(dir.path().join("schedule.json"), "[]").unwrap();
+        json_store.save("Work", &config).unwrap();
+
+        // The schedule was renamed to TOML since
+        let toml_file = dir.path().join("schedule.toml");
+        let toml_store = ProfileStore::at(
+            dir.path().join("profiles"),
+            toml_file.clone(),
+            dir.path().join("widgets.json"),
+        );
+        toml_store.switch("Work", &mut config).unwrap();
+        assert_eq!(
+            format::load::<Vec<crate::core::ScheduleItem>>(&toml_file)
+                .unwrap()
+                .len(),
+            0
+        );
+
+        toml_store.save("Work", &config).unwrap();
+        assert!(dir.path().join("profiles/Work/schedule.toml").is_file());
+        assert!(!dir.path().join("profiles/Work/schedule.json").exists());
+    }
+}
@@ -0,0 +1,464 @@
+//! QR codes for links shown to phones
+//!
+//! Encodes bytes in byte mode at error correction level M, in the smallest
+//! version from 1 to 10 that fits: up to 213 bytes, plenty for a LAN address
+//! with a token. The mask is picked by the penalty rules of the standard.
+use crate::core::{AppError, AppResult};
+
+/// Codewords of each version, from 1 to 10
+const TOTAL_CODEWORDS: [usize; 10] = [26, 44, 70, 100, 134, 172, 196, 242, 292, 346];
+
+/// Error correction codewords per block at level M, by version
+const EC_PER_BLOCK: [usize; 10] = [10, 16, 26, 18, 24, 16, 18, 22, 22, 26];
+
+/// Error correction blocks at level M, by version
+const BLOCKS: [usize; 10] = [1, 1, 1, 2, 2, 4, 4, 4, 5, 5];
+
+/// Alignment pattern centers, by version
+const ALIGNMENT: [&[usize]; 10] = [
+    &[],
+    &[6, 18],
+    &[6, 22],
+    &[6, 26],
+    &[6, 30],
+    &[6, 34],
+    &[6, 22, 38],
+    &[6, 24, 42],
+    &[6, 26, 46],
+    &[6, 28, 50],
+];
+
+/// Format bits of level M
+const LEVEL_M: u32 = 0b00;
+
+/// QR code, as a square of dark and light modules
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QrCode {
+    /// Modules per side
+    size: usize,
+
+    /// Modules row by row, true when dark
+    modules: Vec<bool>,
+}
+
+impl QrCode {
+    /// Encode bytes, like a URL
+    pub fn encode(data: &[u8]) -> AppResult<Self> {
+        let version = (1..=10)
+            .find(|&version| {
+                4 + count_bits(version) + data.len() * 8 <= data_codewords(version) * 8
+            })
+            .ok_or_else(|| {
+                AppError::Other(format!("{} bytes are too many for a QR code", data.len()))
+            })?;
+        let codewords = add_error_correction(&data_bits(data, version), version);
+
+        let mut best: Option<(u32, QrCode)> = None;
+        for mask in 0..8 {
+            let mut code = Grid::new(version);
+            code.draw_function_patterns();
+            code.draw_codewords(&codewords);
+            code.apply_mask(mask);
+            code.draw_format(mask);
+            let penalty = code.penalty();
+            if best.as_ref().map_or(true, |(lowest, _)| penalty < *lowest) {
+                best = Some((
+                    penalty,
+                    QrCode {
+                        size: code.size,
+                        modules: code.modules,
+                    },
+                ));
+            }
+        }
+        Ok(best.map(|(_, code)| code).expect("eight masks were tried"))
+    }
+
+    /// Get the number of modules per side, without the quiet zone
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Check whether the module in column `x` and row `y` is dark
+    pub fn is_dark(&self, x: usize, y: usize) -> bool {
+        x < self.size && y < self.size && self.modules[y * self.size + x]
+    }
+}
+
+/// Get the data codewords of a version at level M
+fn data_codewords(version: usize) -> usize {
+    TOTAL_CODEWORDS[version - 1] - EC_PER_BLOCK[version - 1] * BLOCKS[version - 1]
+}
+
+/// Get the length of the byte count in byte mode
+fn count_bits(version: usize) -> usize {
+    if version < 10 {
+        8
+    } else {
+        16
+    }
+}
+
+/// Build the data codewords: mode, count, bytes, terminator and padding
+fn data_bits(data: &[u8], version: usize) -> Vec<u8> {
+    let mut bits = Vec::new();
+    let mut push =
+        |value: u32, length: usize| bits.extend((0..length).rev().map(|i| (value >> i) & 1 == 1));
+    push(0b0100, 4);
+    push(data.len() as u32, count_bits(version));
+    for byte in data {
+        push(*byte as u32, 8);
+    }
+
+    let capacity = data_codewords(version) * 8;
+    let terminator = (capacity - bits.len()).min(4);
+    bits.extend(std::iter::repeat(false).take(terminator));
+    bits.extend(std::iter::repeat(false).take((8 - bits.len() % 8) % 8));
+
+    let mut codewords: Vec<u8> = bits
+        .chunks(8)
+        .map(|byte| byte.iter().fold(0, |value, bit| value << 1 | *bit as u8))
+        .collect();
+    for pad in [0xEC, 0x11].into_iter().cycle() {
+        if codewords.len() >= data_codewords(version) {
+            break;
+        }
+        codewords.push(pad);
+    }
+    codewords
+}
+
+/// Split data codewords into blocks, add their error correction and interleave them
+fn add_error_correction(data: &[u8], version: usize) -> Vec<u8> {
+    let blocks = BLOCKS[version - 1];
+    let ec_length = EC_PER_BLOCK[version - 1];
+    let short_length = data.len() / blocks;
+    let long_blocks = data.len() % blocks;
+    let generator = generator(ec_length);
+
+    let mut data_blocks = Vec::with_capacity(blocks);
+    let mut start = 0;
+    for block in 0..blocks {
+        let length = short_length + usize::from(block >= blocks - long_blocks);
+        data_blocks.push(&data[start..start + length]);
+        start += length;
+    }
+    let ec_blocks: Vec<Vec<u8>> = data_blocks
+        .iter()
+        .map(|block| remainder(block, &generator))
+        .collect();
+
+    let mut codewords = Vec::with_capacity(TOTAL_CODEWORDS[version - 1]);
+    for i in 0..=short_length {
+        codewords.extend(data_blocks.iter().filter_map(|block| block.get(i)));
+    }
+    for i in 0..ec_length {
+        codewords.extend(ec_blocks.iter().map(|block| block[i]));
+    }
+    codewords
+}
+
+/// Multiply in GF(256) with the QR polynomial x^8 + x^4 + x^3 + x^2 + 1
+fn gf_multiply(a: u8, b: u8) -> u8 {
+    let mut product: u16 = 0;
+    for i in (0..8).rev() {
+        product = (product << 1) ^ ((product >> 7) * 0x11D);
+        product ^= ((b >> i) & 1) as u16 * a as u16;
+    }
+    product as u8
+}
+
+/// Build the Reed-Solomon generator of a degree, highest coefficient first without the leading 1
+fn generator(degree: usize) -> Vec<u8> {
+    let mut coefficients = vec![0u8; degree];
+    coefficients[degree - 1] = 1;
+    let mut root = 1u8;
+    for _ in 0..degree {
+        for i in 0..degree {
+            coefficients[i] = gf_multiply(coefficients[i], root);
+            if i + 1 < degree {
+                coefficients[i] ^= coefficients[i + 1];
+            }
+        }
+        root = gf_multiply(root, 2);
+    }
+    coefficients
+}
+
+/// Get the error correction codewords of a block
+fn remainder(data: &[u8], generator: &[u8]) -> Vec<u8> {
+    let mut remainder = vec![0u8; generator.len()];
+    for byte in data {
+        let factor = byte ^ remainder.remove(0);
+        remainder.push(0);
+        for (value, coefficient) in remainder.iter_mut().zip(generator) {
+            *value ^= gf_multiply(*coefficient, factor);
+        }
+    }
+    remainder
+}
+
+/// BCH code of `value`, `bits` long, with the given generator
+fn bch(value: u32, generator: u32, bits: u32) -> u32 {
+    let degree = 31 - generator.leading_zeros();
+    let mut remainder = value << degree;
+    for bit in (degree..degree + bits).rev() {
+        if remainder >> bit & 1 == 1 {
+            remainder ^= generator << (bit - degree);
+        }
+    }
+    value << degree | remainder
+}
+
+/// Modules being drawn, with the ones taken by function patterns
+struct Grid {
+    /// Version, from 1 to 10
+    version: usize,
+
+    /// Modules per side
+    size: usize,
+
+    /// Modules row by row, true when dark
+    modules: Vec<bool>,
+
+    /// Modules of function patterns, which data and masks leave alone
+    function: Vec<bool>,
+}
+
+impl Grid {
+    fn new(version: usize) -> Self {
+        let size = 17 + 4 * version;
+        Self {
+            version,
+            size,
+            modules: vec![false; size * size],
+            function: vec![false; size * size],
+        }
+    }
+
+    /// Set a function module
+    fn set(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y * self.size + x] = dark;
+        self.function[y * self.size + x] = true;
+    }
+
+    /// Draw the finders, timing, alignment and version patterns, and reserve the format areas
+    fn draw_function_patterns(&mut self) {
+        let size = self.size;
+        for i in 0..size {
+            self.set(6, i, i % 2 == 0);
+            self.set(i, 6, i % 2 == 0);
+        }
+        for (x, y) in [(3, 3), (size - 4, 3), (3, size - 4)] {
+            self.draw_finder(x, y);
+        }
+
+        let centers = ALIGNMENT[self.version - 1];
+        for &x in centers {
+            for &y in centers {
+                // Corners already taken by finders
+                let at_finder =
+                    (x == 6 && y == 6) || (x == 6 && y == size - 7) || (x == size - 7 && y == 6);
+                if !at_finder {
+                    for dy in 0..5 {
+                        for dx in 0..5 {
+                            let ring = (dx as i32 - 2).abs().max((dy as i32 - 2).abs());
+                            self.set(x + dx - 2, y + dy - 2, ring != 1);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Reserved now, written once the mask is known
+        self.draw_format(0);
+
+        if self.version >= 7 {
+            let bits = bch(self.version as u32, 0x1F25, 6);
+            for i in 0..18 {
+                let dark = bits >> i & 1 == 1;
+                let (a, b) = (size - 11 + i % 3, i / 3);
+                self.set(a, b, dark);
+                self.set(b, a, dark);
+            }
+        }
+    }
+
+    /// Draw a finder pattern and its separator around a center
+    fn draw_finder(&mut self, x: usize, y: usize) {
+        for dy in -4i32..=4 {
+            for dx in -4i32..=4 {
+                let (mx, my) = (x as i32 + dx, y as i32 + dy);
+                if mx < 0 || my < 0 || mx >= self.size as i32 || my >= self.size as i32 {
+                    continue;
+                }
+                let ring = dx.abs().max(dy.abs());
+                self.set(mx as usize, my as usize, ring != 2 && ring != 4);
+            }
+        }
+    }
+
+    /// Draw both copies of the format bits and the dark module
+    fn draw_format(&mut self, mask: u32) {
+        let bits = bch(LEVEL_M << 3 | mask, 0x537, 5) ^ 0x5412;
+        let bit = |i: usize| bits >> i & 1 == 1;
+        let size = self.size;
+        for i in 0..6 {
+            self.set(8, i, bit(i));
+        }
+        self.set(8, 7, bit(6));
+        self.set(8, 8, bit(7));
+        self.set(7, 8, bit(8));
+        for i in 9..15 {
+            self.set(14 - i, 8, bit(i));
+        }
+        for i in 0..8 {
+            self.set(size - 1 - i, 8, bit(i));
+        }
+        for i in 8..15 {
+            self.set(8, size - 15 + i, bit(i));
+        }
+        self.set(8, size - 8, true);
+    }
+
+    /// Place the codewords in the zigzag from the bottom right corner
+    fn draw_codewords(&mut self, codewords: &[u8]) {
+        let size = self.size;
+        let mut index = 0;
+        let mut right = size - 1;
+        loop {
+            // The vertical timing pattern shifts the columns to its left
+            if right == 6 {
+                right = 5;
+            }
+            for vertical in 0..size {
+                for x in [right, right - 1] {
+                    let upward = (right + 1) & 2 == 0;
+                    let y = if upward {
+                        size - 1 - vertical
+                    } else {
+                        vertical
+                    };
+                    if !self.function[y * size + x] && index < codewords.len() * 8 {
+                        self.modules[y * size + x] =
+                            codewords[index / 8] >> (7 - index % 8) & 1 == 1;
+                        index += 1;
+                    }
+                }
+            }
+            if right < 2 {
+                break;
+            }
+            right -= 2;
+        }
+    }
+
+    /// Flip the data modules picked by a mask
+    fn apply_mask(&mut self, mask: u32) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let flip = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+                let index = y * self.size + x;
+                self.modules[index] ^= flip && !self.function[index];
+            }
+        }
+    }
+
+    /// Score how hard the code is to read: long runs, blocks, finder look-alikes and imbalance
+    fn penalty(&self) -> u32 {
+        let size = self.size;
+        let at = |x: usize, y: usize| self.modules[y * size + x];
+        let mut penalty = 0;
+
+        for transposed in [false, true] {
+            for line in 0..size {
+                let module = |i: usize| if transposed { at(line, i) } else { at(i, line) };
+                let mut run = 1;
+                for i in 1..=size {
+                    if i < size && module(i) == module(i - 1) {
+                        run += 1;
+                        continue;
+                    }
+                    if run >= 5 {
+                        penalty += run - 2;
+                    }
+                    run = 1;
+                }
+                let finder = [true, false, true, true, true, false, true];
+                for i in 0..size.saturating_sub(10) {
+                    let matches =
+                        |offset: usize| (0..7).all(|j| module(i + offset + j) == finder[j]);
+                    let light = |from: usize| (from..from + 4).all(|j| !module(j));
+                    if (matches(0) && light(i + 7)) || (light(i) && matches(4)) {
+                        penalty += 40;
+                    }
+                }
+            }
+        }
+
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let dark = at(x, y);
+                if at(x + 1, y) == dark && at(x, y + 1) == dark && at(x + 1, y + 1) == dark {
+                    penalty += 3;
+                }
+            }
+        }
+
+        let dark = self.modules.iter().filter(|dark| **dark).count();
+        let total = size * size;
+        let deviation = (dark * 20).abs_diff(total * 10);
+        penalty += ((deviation + total - 1) / total).saturating_sub(1) as u32 * 10;
+        penalty
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_correction() {
+        // "HELLO WORLD" at 1-M, from the usual worked example
+        let data = [
+            32, 91, 11, 120, 209, 114, 220, 77, 67, 64, 236, 17, 236, 17, 236, 17,
+        ];
+        assert_eq!(
+            remainder(&data, &generator(10)),
+            [196, 35, 39, 119, 235, 215, 231, 226, 93, 23]
+        );
+    }
+
+    #[test]
+    fn test_format_and_version_bits() {
+        assert_eq!(bch(LEVEL_M << 3, 0x537, 5) ^ 0x5412, 0b101010000010010);
+        assert_eq!(bch(7, 0x1F25, 6), 0b000111110010010100);
+    }
+
+    #[test]
+    fn test_encode() {
+        let url = b"http://192.168.100.200:47932/upload#token=0123456789abcdef0123456789abcdef0123456789abcdef";
+        let code = QrCode::encode(url).unwrap();
+        assert_eq!(code.size(), 17 + 4 * 6);
+
+        // Finder patterns in three corners, timing between them
+        for (x, y) in [(0, 0), (code.size() - 7, 0), (0, code.size() - 7)] {
+            assert!(code.is_dark(x, y) && code.is_dark(x + 6, y + 6) && code.is_dark(x + 3, y + 3));
+            assert!(!code.is_dark(x + 1, y + 1) && code.is_dark(x + 2, y + 2));
+        }
+        assert!(code.is_dark(8, 6) && !code.is_dark(9, 6) && code.is_dark(10, 6));
+        assert!(code.is_dark(8, code.size() - 8));
+
+        assert_eq!(QrCode::encode(b"a").unwrap().size(), 21);
+        assert!(QrCode::encode(&[b'a'; 300]).is_err());
+    }
+}
@@ -0,0 +1,229 @@
+//! Adaptive quality for animated wallpapers
+//!
+//! While the system CPU stays busy the running wallpaper is switched to a
+//! lower frame rate and resolution, and back to full quality once there is
+//! headroom again. Only video wallpapers can change their quality; other
+//! players keep running as they are.
+use crate::core::config::AdaptiveQualityConfig;
+use crate::core::scheduler::CurrentWallpaper;
+use crate::core::{runtime, AppError, AppResult, EventLog, WallpaperType};
+use crate::wallpapers::ReducedQuality;
+use log::{debug, info};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use sysinfo::System;
+
+/// Time between CPU samples
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Consecutive busy samples before quality is lowered
+const SAMPLES_TO_LOWER: u32 = 3;
+
+/// Consecutive quiet samples before full quality returns
+const SAMPLES_TO_RESTORE: u32 = 6;
+
+/// Decides from CPU samples when quality is lowered and restored
+#[derive(Debug, Default)]
+struct QualityGovernor {
+    /// Whether quality is lowered
+    reduced: bool,
+
+    /// Consecutive samples asking for a change
+    streak: u32,
+}
+
+impl QualityGovernor {
+    /// Record a system CPU sample and get whether quality should be lowered
+    fn sample(&mut self, cpu: f32, config: &AdaptiveQualityConfig) -> bool {
+        let wants_change = if self.reduced {
+            cpu < config.restore_below_cpu
+        } else {
+            cpu > config.lower_above_cpu
+        };
+        self.streak = if wants_change { self.streak + 1 } else { 0 };
+
+        let needed = if self.reduced {
+            SAMPLES_TO_RESTORE
+        } else {
+            SAMPLES_TO_LOWER
+        };
+        if self.streak >= needed {
+            self.reduced = !self.reduced;
+            self.streak = 0;
+        }
+        self.reduced
+    }
+}
+
+/// Lowers the quality of the running wallpaper while the system is busy
+pub struct AdaptiveQuality {
+    /// Adaptive quality configuration
+    config: Arc<Mutex<AdaptiveQualityConfig>>,
+
+    /// Wallpaper whose quality is adjusted
+    current_wallpaper: CurrentWallpaper,
+
+    /// Sample thread handle
+    sample_thread: Option<thread::JoinHandle<()>>,
+
+    /// Whether the samples are running
+    is_running: Arc<Mutex<bool>>,
+
+    /// Whether quality is lowered right now
+    is_reduced: Arc<Mutex<bool>>,
+}
+
+#[allow(dead_code)]
+impl AdaptiveQuality {
+    /// Create a new adaptive quality service for the scheduler's wallpaper
+    pub fn new(config: AdaptiveQualityConfig, current_wallpaper: CurrentWallpaper) -> Self {
+        Self {
+            config: Arc::new(Mutex::new(config)),
+            current_wallpaper,
+            sample_thread: None,
+            is_running: Arc::new(Mutex::new(false)),
+            is_reduced: Arc::new(Mutex::new(false)),
+        }
+    }
+
+    /// Update the adaptive quality configuration
+    pub fn update_config(&self, config: AdaptiveQualityConfig) {
+        *self.config.lock().unwrap() = config;
+    }
+
+    /// Check whether wallpaper quality is lowered
+    pub fn is_reduced(&self) -> bool {
+        *self.is_reduced.lock().unwrap()
+    }
+
+    /// Start the sample thread
+    pub fn start(&mut self) -> AppResult<()> {
+        if *self.is_running.lock().unwrap() {
+            debug!("Adaptive quality is already running");
+            return Ok(());
+        }
+
+        *self.is_running.lock().unwrap() = true;
+
+        let config = self.config.clone();
+        let current_wallpaper = self.current_wallpaper.clone();
+        let is_running = self.is_running.clone();
+        let is_reduced = self.is_reduced.clone();
+
+        self.sample_thread = Some(thread::spawn(move || {
+            let mut system = System::new();
+            let mut governor = QualityGovernor::default();
+            let mut last_sample: Option<Instant> = None;
+            // Wallpaper path and the quality it was given, by the monitor it runs on
+            let mut applied: HashMap<Option<String>, (Option<PathBuf>, Option<ReducedQuality>)> =
+                HashMap::new();
+
+            while *is_running.lock().unwrap() {
+                if last_sample.map_or(true, |last| last.elapsed() >= SAMPLE_INTERVAL) {
+                    last_sample = Some(Instant::now());
+                    let config = config.lock().unwrap().clone();
+
+                    system.refresh_cpu_usage();
+                    let reduce = if config.enabled {
+                        governor.sample(system.global_cpu_info().cpu_usage(), &config)
+                    } else {
+                        governor = QualityGovernor::default();
+                        false
+                    };
+
+                    if reduce != std::mem::replace(&mut *is_reduced.lock().unwrap(), reduce) {
+                        let message = if reduce {
+                            "Lowered wallpaper quality while the system is busy"
+                        } else {
+                            "Restored full wallpaper quality"
+                        };
+                        info!("{}", message);
+                        EventLog::shared().info("quality", message);
+                    }
+
+                    let quality = reduce.then_some(ReducedQuality {
+                        max_fps: config.reduced_fps,
+                        scale: config.reduced_scale,
+                    });
+                    let active = current_wallpaper.lock().unwrap();
+                    applied.retain(|monitor, _| active.get(monitor.as_deref()).is_some());
+                    for (monitor, wallpaper) in active.iter() {
+                        let path = wallpaper.get_path().map(Path::to_path_buf);
+                        let target = (path, quality);
+                        let monitor = monitor.map(str::to_string);
+                        // A wallpaper applied in the meantime starts at full quality
+                        let previous = applied.get(&monitor);
+                        let unchanged =
+                            previous == Some(&target) || (previous.is_none() && quality.is_none());
+                        if wallpaper.get_type() != WallpaperType::Static && !unchanged {
+                            if let Err(e) =
+                                runtime::block_on(wallpaper.set_reduced_quality(quality))
+                            {
+                                debug!("Failed to change wallpaper quality: {}", e);
+                            }
+                        }
+                        applied.insert(monitor, target);
+                    }
+                }
+
+                thread::sleep(Duration::from_secs(1));
+            }
+        }));
+
+        info!("Adaptive quality started");
+        Ok(())
+    }
+
+    /// Stop the sample thread
+    pub fn stop(&mut self) -> AppResult<()> {
+        if !*self.is_running.lock().unwrap() {
+            debug!("Adaptive quality is not running");
+            return Ok(());
+        }
+
+        *self.is_running.lock().unwrap() = false;
+
+        if let Some(thread) = self.sample_thread.take() {
+            thread.join().map_err(|e| {
+                AppError::Other(format!("Failed to join adaptive quality thread: {:?}", e))
+            })?;
+        }
+
+        info!("Adaptive quality stopped");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_governor_lowers_and_restores() {
+        let config = AdaptiveQualityConfig::default();
+        let mut governor = QualityGovernor::default();
+
+        // A short spike doesn't lower quality
+        assert!(!governor.sample(95.0, &config));
+        assert!(!governor.sample(95.0, &config));
+        assert!(!governor.sample(30.0, &config));
+
+        for _ in 1..SAMPLES_TO_LOWER {
+            assert!(!governor.sample(95.0, &config));
+        }
+        assert!(governor.sample(95.0, &config));
+
+        // Between the thresholds nothing changes
+        for _ in 0..10 {
+            assert!(governor.sample(70.0, &config));
+        }
+
+        for _ in 1..SAMPLES_TO_RESTORE {
+            assert!(governor.sample(40.0, &config));
+        }
+        assert!(!governor.sample(40.0, &config));
+    }
+}
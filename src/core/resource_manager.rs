@@ -1,10 +1,27 @@
 //! Resource management for wallpapers and widgets
-use log::{debug, info};
+use crate::core::config::{LimitAction, ResourceLimitsConfig};
+use crate::core::scheduler::CurrentWallpaper;
+use crate::core::{
+    runtime, AppError, AppResult, Config, EventLevel, EventLog, ProcessController, ProcessInfo,
+};
+use crate::platform::gpu;
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
+/// Time between samples of the player processes
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Consecutive samples over a limit before a player is paused or killed
+///
+/// Players spike briefly while they start or seek.
+const SAMPLES_OVER_LIMIT: u32 = 3;
+
 /// Resource usage statistics
 #[derive(Debug, Clone)]
 pub struct ResourceUsage {
@@ -18,6 +35,72 @@ pub struct ResourceUsage {
     pub active_processes: u32,
 }
 
+/// Resource use of a wallpaper's players
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WallpaperUsage {
+    /// Resident memory in bytes
+    pub memory_bytes: u64,
+
+    /// CPU usage percentage (100 = one core)
+    pub cpu_usage: f32,
+
+    /// GPU load in percent while the wallpaper ran, if the GPU reports it
+    pub gpu_busy: Option<f32>,
+
+    /// Number of samples averaged
+    pub samples: u32,
+}
+
+impl WallpaperUsage {
+    /// Add a sample to the averages
+    fn add(&mut self, sample: &WallpaperUsage) {
+        let samples = self.samples as f64;
+        let average = |average: f64, value: f64| (average * samples + value) / (samples + 1.0);
+        self.memory_bytes = average(self.memory_bytes as f64, sample.memory_bytes as f64) as u64;
+        self.cpu_usage = average(self.cpu_usage as f64, sample.cpu_usage as f64) as f32;
+        // A GPU either reports its load or doesn't, so a missing value keeps the average
+        self.gpu_busy = match (self.gpu_busy, sample.gpu_busy) {
+            (Some(busy), Some(value)) => Some(average(busy as f64, value as f64) as f32),
+            (busy, value) => busy.or(value),
+        };
+        self.samples += 1;
+    }
+}
+
+/// Latest and average resource use of each wallpaper that ran
+#[derive(Debug, Default)]
+struct UsageLog {
+    /// Wallpaper running at the last sample and its usage
+    current: Option<(String, WallpaperUsage)>,
+
+    /// Average usage by wallpaper path
+    averages: HashMap<String, WallpaperUsage>,
+}
+
+impl UsageLog {
+    /// Record the players' usage while a wallpaper runs
+    ///
+    /// Nothing is recorded when the wallpaper has no players to measure.
+    fn record(&mut self, wallpaper: Option<String>, usage: &ResourceUsage, gpu_busy: Option<f32>) {
+        let Some(wallpaper) = wallpaper.filter(|_| usage.active_processes > 0) else {
+            self.current = None;
+            return;
+        };
+
+        let sample = WallpaperUsage {
+            memory_bytes: usage.memory_used,
+            cpu_usage: usage.cpu_usage,
+            gpu_busy,
+            samples: 1,
+        };
+        self.averages
+            .entry(wallpaper.clone())
+            .or_default()
+            .add(&sample);
+        self.current = Some((wallpaper, sample));
+    }
+}
+
 /// Resource limits for wallpapers
 #[derive(Debug, Clone)]
 pub struct ResourceLimits {
@@ -31,11 +114,21 @@ pub struct ResourceLimits {
     pub max_processes: u32,
 }
 
+impl From<&ResourceLimitsConfig> for ResourceLimits {
+    fn from(config: &ResourceLimitsConfig) -> Self {
+        Self {
+            max_memory: config.max_memory_mb * 1024 * 1024,
+            max_cpu: config.max_cpu,
+            ..Self::default()
+        }
+    }
+}
+
 impl Default for ResourceLimits {
     fn default() -> Self {
         Self {
-            max_memory: 1024 * 1024 * 512, // 512 MB
-            max_cpu: 80.0,                 // 80%
+            max_memory: 1024 * 1024 * 512,     // 512 MB
+            max_cpu: 80.0,                     // 80%
             max_gpu_memory: 1024 * 1024 * 256, // 256 MB
             max_processes: 10,
         }
@@ -47,7 +140,7 @@ pub struct ResourceManager {
     /// Current resource usage
     usage: Arc<RwLock<ResourceUsage>>,
     /// Resource limits
-    limits: ResourceLimits,
+    limits: Arc<RwLock<ResourceLimits>>,
     /// Active resource IDs
     active_resources: Arc<RwLock<HashMap<String, ResourceUsage>>>,
     /// Total memory allocated counter
@@ -66,7 +159,7 @@ impl ResourceManager {
                 gpu_memory_used: 0,
                 active_processes: 0,
             })),
-            limits,
+            limits: Arc::new(RwLock::new(limits)),
             active_resources: Arc::new(RwLock::new(HashMap::new())),
             total_allocated: AtomicU64::new(0),
             total_freed: AtomicU64::new(0),
@@ -76,19 +169,20 @@ impl ResourceManager {
     /// Register a new resource with the manager
     pub async fn register_resource(&self, id: String, usage: ResourceUsage) -> Result<(), String> {
         {
+            let limits = self.limits.read().await;
             let active = self.active_resources.read().await;
 
             // Check if we're already at the process limit
-            if active.len() >= self.limits.max_processes as usize {
+            if active.len() >= limits.max_processes as usize {
                 return Err("Maximum number of active processes reached".to_string());
             }
 
             // Check if adding this resource would exceed limits
             let current = self.usage.read().await;
-            if current.memory_used + usage.memory_used > self.limits.max_memory {
+            if current.memory_used + usage.memory_used > limits.max_memory {
                 return Err("Would exceed maximum memory limit".to_string());
             }
-            if current.gpu_memory_used + usage.gpu_memory_used > self.limits.max_gpu_memory {
+            if current.gpu_memory_used + usage.gpu_memory_used > limits.max_gpu_memory {
                 return Err("Would exceed maximum GPU memory limit".to_string());
             }
         }
@@ -108,7 +202,8 @@ impl ResourceManager {
             current.active_processes += 1;
         }
 
-        self.total_allocated.fetch_add(usage.memory_used, Ordering::SeqCst);
+        self.total_allocated
+            .fetch_add(usage.memory_used, Ordering::SeqCst);
 
         debug!(
             "Registered resource {}: {}MB memory, {}MB GPU memory",
@@ -121,49 +216,33 @@ impl ResourceManager {
     }
 
     /// Update resource usage
+    ///
+    /// The new usage is always recorded, since it is what the resource really
+    /// uses; an error reports that the totals are now over a limit.
     pub async fn update_resource(&self, id: &str, new_usage: ResourceUsage) -> Result<(), String> {
         let mut active = self.active_resources.write().await;
-        if let Some(old_usage) = active.get(id) {
-            // Calculate difference
-            let mem_diff = new_usage.memory_used as i64 - old_usage.memory_used as i64;
-            let gpu_mem_diff = new_usage.gpu_memory_used as i64 - old_usage.gpu_memory_used as i64;
-            let cpu_diff = new_usage.cpu_usage - old_usage.cpu_usage;
-
-            // Update the resource
-            active.insert(id.to_string(), new_usage);
+        let old_usage = active
+            .insert(id.to_string(), new_usage.clone())
+            .ok_or_else(|| "Resource not found".to_string())?;
 
-            // Update global usage
-            let mut current = self.usage.write().await;
-            if mem_diff > 0 {
-                let new_memory = (current.memory_used as i64 + mem_diff) as u64;
-                if new_memory > self.limits.max_memory {
-                    return Err("Would exceed maximum memory limit".to_string());
-                }
-                current.memory_used = new_memory;
-            } else {
-                // Subtraction case - use saturating arithmetic
-                let abs_diff = mem_diff.abs() as u64;
-                current.memory_used = current.memory_used.saturating_sub(abs_diff);
-            }
-
-            if gpu_mem_diff > 0 {
-                let new_gpu_memory = (current.gpu_memory_used as i64 + gpu_mem_diff) as u64;
-                if new_gpu_memory > self.limits.max_gpu_memory {
-                    return Err("Would exceed maximum GPU memory limit".to_string());
-                }
-                current.gpu_memory_used = new_gpu_memory;
-            } else {
-                // Subtraction case - use saturating arithmetic
-                let abs_diff = gpu_mem_diff.abs() as u64;
-                current.gpu_memory_used = current.gpu_memory_used.saturating_sub(abs_diff);
-            }
-
-            current.cpu_usage = (current.cpu_usage + cpu_diff).max(0.0);
-
-            Ok(())
-        } else {
-            Err("Resource not found".to_string())
+        // Update global usage
+        let mut current = self.usage.write().await;
+        current.memory_used =
+            (current.memory_used + new_usage.memory_used).saturating_sub(old_usage.memory_used);
+        current.gpu_memory_used = (current.gpu_memory_used + new_usage.gpu_memory_used)
+            .saturating_sub(old_usage.gpu_memory_used);
+        current.cpu_usage =
+            (current.cpu_usage + new_usage.cpu_usage - old_usage.cpu_usage).max(0.0);
+
+        let limits = self.limits.read().await;
+        if current.memory_used > limits.max_memory {
+            return Err("Exceeds maximum memory limit".to_string());
         }
+        if current.gpu_memory_used > limits.max_gpu_memory {
+            return Err("Exceeds maximum GPU memory limit".to_string());
+        }
+
+        Ok(())
     }
 
     /// Unregister a resource from the manager
@@ -173,11 +252,14 @@ impl ResourceManager {
             // Update global usage
             let mut current = self.usage.write().await;
             current.memory_used = current.memory_used.saturating_sub(usage.memory_used);
-            current.gpu_memory_used = current.gpu_memory_used.saturating_sub(usage.gpu_memory_used);
+            current.gpu_memory_used = current
+                .gpu_memory_used
+                .saturating_sub(usage.gpu_memory_used);
             current.cpu_usage = (current.cpu_usage - usage.cpu_usage).max(0.0);
             current.active_processes = current.active_processes.saturating_sub(1);
 
-            self.total_freed.fetch_add(usage.memory_used, Ordering::SeqCst);
+            self.total_freed
+                .fetch_add(usage.memory_used, Ordering::SeqCst);
 
             debug!(
                 "Unregistered resource {}: {}MB memory freed",
@@ -202,22 +284,62 @@ impl ResourceManager {
         active.get(id).cloned()
     }
 
+    /// Get the IDs of the registered resources
+    pub async fn resource_ids(&self) -> Vec<String> {
+        self.active_resources.read().await.keys().cloned().collect()
+    }
+
+    /// Replace the resource limits
+    pub async fn set_limits(&self, limits: ResourceLimits) {
+        *self.limits.write().await = limits;
+    }
+
+    /// Check the usage of a single resource against the limits
+    ///
+    /// Returns a description of the first limit it goes over.
+    pub async fn check_usage(&self, usage: &ResourceUsage) -> Option<String> {
+        let limits = self.limits.read().await;
+        if usage.memory_used > limits.max_memory {
+            Some(format!(
+                "uses {} MB of memory, over the {} MB limit",
+                usage.memory_used / (1024 * 1024),
+                limits.max_memory / (1024 * 1024)
+            ))
+        } else if usage.cpu_usage > limits.max_cpu {
+            Some(format!(
+                "uses {:.0}% CPU, over the {:.0}% limit",
+                usage.cpu_usage, limits.max_cpu
+            ))
+        } else if usage.gpu_memory_used > limits.max_gpu_memory {
+            Some(format!(
+                "uses {} MB of GPU memory, over the {} MB limit",
+                usage.gpu_memory_used / (1024 * 1024),
+                limits.max_gpu_memory / (1024 * 1024)
+            ))
+        } else {
+            None
+        }
+    }
+
     /// Check if resource usage is within limits
     pub async fn is_within_limits(&self) -> bool {
         let usage = self.usage.read().await;
-        usage.memory_used <= self.limits.max_memory
-            && usage.gpu_memory_used <= self.limits.max_gpu_memory
-            && usage.cpu_usage <= self.limits.max_cpu
-            && usage.active_processes <= self.limits.max_processes
+        let limits = self.limits.read().await;
+        usage.memory_used <= limits.max_memory
+            && usage.gpu_memory_used <= limits.max_gpu_memory
+            && usage.cpu_usage <= limits.max_cpu
+            && usage.active_processes <= limits.max_processes
     }
 
     /// Get resource utilization percentage
     pub async fn get_utilization(&self) -> (f32, f32, f32) {
         let usage = self.usage.read().await;
-        let memory_util = (usage.memory_used as f32 / self.limits.max_memory as f32).min(1.0) * 100.0;
-        let gpu_util = (usage.gpu_memory_used as f32 / self.limits.max_gpu_memory as f32).min(1.0) * 100.0;
-        let cpu_util = (usage.cpu_usage / self.limits.max_cpu).min(1.0) * 100.0;
-        
+        let limits = self.limits.read().await;
+        let memory_util = (usage.memory_used as f32 / limits.max_memory as f32).min(1.0) * 100.0;
+        let gpu_util =
+            (usage.gpu_memory_used as f32 / limits.max_gpu_memory as f32).min(1.0) * 100.0;
+        let cpu_util = (usage.cpu_usage / limits.max_cpu).min(1.0) * 100.0;
+
         (memory_util, gpu_util, cpu_util)
     }
 
@@ -236,7 +358,10 @@ impl ResourceManager {
 
         // In a real implementation, we would check if resources are still alive
         // For now, we'll just log the activity
-        info!("Resource manager garbage collection completed. Active resources: {}", initial_count);
+        info!(
+            "Resource manager garbage collection completed. Active resources: {}",
+            initial_count
+        );
 
         0 // No resources were collected in this basic implementation
     }
@@ -248,32 +373,303 @@ impl Default for ResourceManager {
     }
 }
 
+/// Samples the wallpaper players into a resource manager and pauses or
+/// kills the ones that stay over the configured limits
+pub struct ResourceMonitor {
+    /// Resource limit configuration
+    config: Arc<Mutex<ResourceLimitsConfig>>,
+
+    /// Resource manager fed with the samples
+    manager: Arc<ResourceManager>,
+
+    /// Wallpaper the samples are recorded for
+    current_wallpaper: CurrentWallpaper,
+
+    /// Usage of each wallpaper that ran
+    usage_log: Arc<Mutex<UsageLog>>,
+
+    /// Monitor thread handle
+    monitor_thread: Option<thread::JoinHandle<()>>,
+
+    /// Whether the monitor is running
+    is_running: Arc<Mutex<bool>>,
+}
+
+#[allow(dead_code)]
+impl ResourceMonitor {
+    /// Create a new resource monitor for the scheduler's wallpaper
+    pub fn new(config: &Config, current_wallpaper: CurrentWallpaper) -> Self {
+        let limits = config.app.resource_limits.clone();
+        Self {
+            manager: Arc::new(ResourceManager::new(ResourceLimits::from(&limits))),
+            current_wallpaper,
+            usage_log: Arc::new(Mutex::new(UsageLog::default())),
+            config: Arc::new(Mutex::new(limits)),
+            monitor_thread: None,
+            is_running: Arc::new(Mutex::new(false)),
+        }
+    }
+
+    /// Update the resource limit configuration
+    pub fn update_config(&self, config: ResourceLimitsConfig) {
+        *self.config.lock().unwrap() = config;
+    }
+
+    /// Get the resource manager the samples are recorded in
+    pub fn manager(&self) -> Arc<ResourceManager> {
+        self.manager.clone()
+    }
+
+    /// Get the latest usage of the running wallpaper's players
+    pub fn current_usage(&self) -> Option<WallpaperUsage> {
+        self.usage_log
+            .lock()
+            .unwrap()
+            .current
+            .as_ref()
+            .map(|(_, usage)| usage.clone())
+    }
+
+    /// Get the average usage of each wallpaper that ran, by path
+    pub fn usage_by_wallpaper(&self) -> HashMap<String, WallpaperUsage> {
+        self.usage_log.lock().unwrap().averages.clone()
+    }
+
+    /// Start the monitor thread
+    pub fn start(&mut self) -> AppResult<()> {
+        if *self.is_running.lock().unwrap() {
+            debug!("Resource monitor is already running");
+            return Ok(());
+        }
+
+        *self.is_running.lock().unwrap() = true;
+
+        let config = self.config.clone();
+        let manager = self.manager.clone();
+        let current_wallpaper = self.current_wallpaper.clone();
+        let usage_log = self.usage_log.clone();
+        let is_running = self.is_running.clone();
+
+        self.monitor_thread = Some(thread::spawn(move || {
+            let mut last_sample: Option<Instant> = None;
+            let mut over_limit = HashMap::new();
+
+            while *is_running.lock().unwrap() {
+                if last_sample.map_or(true, |last| last.elapsed() >= SAMPLE_INTERVAL) {
+                    last_sample = Some(Instant::now());
+                    let limits = config.lock().unwrap().clone();
+                    runtime::block_on(Self::sample(&manager, &limits, &mut over_limit));
+
+                    let wallpaper =
+                        current_wallpaper
+                            .lock()
+                            .unwrap()
+                            .main()
+                            .and_then(|wallpaper| {
+                                wallpaper
+                                    .get_path()
+                                    .map(|path| path.to_string_lossy().to_string())
+                            });
+                    let usage = runtime::block_on(manager.get_usage());
+                    let gpu_busy = if usage.active_processes > 0 {
+                        gpu::busy_percent()
+                    } else {
+                        None
+                    };
+                    usage_log
+                        .lock()
+                        .unwrap()
+                        .record(wallpaper, &usage, gpu_busy);
+                }
+
+                thread::sleep(Duration::from_secs(1));
+            }
+        }));
+
+        info!("Resource monitor started");
+        Ok(())
+    }
+
+    /// Stop the monitor thread
+    pub fn stop(&mut self) -> AppResult<()> {
+        if !*self.is_running.lock().unwrap() {
+            debug!("Resource monitor is not running");
+            return Ok(());
+        }
+
+        *self.is_running.lock().unwrap() = false;
+
+        if let Some(thread) = self.monitor_thread.take() {
+            thread.join().map_err(|e| {
+                AppError::Other(format!("Failed to join resource monitor thread: {:?}", e))
+            })?;
+        }
+
+        info!("Resource monitor stopped");
+        Ok(())
+    }
+
+    /// Record the usage of every player and enforce the limits
+    ///
+    /// `over_limit` counts the consecutive samples each process has been over a limit.
+    async fn sample(
+        manager: &ResourceManager,
+        config: &ResourceLimitsConfig,
+        over_limit: &mut HashMap<u32, u32>,
+    ) {
+        manager.set_limits(ResourceLimits::from(config)).await;
+
+        let controller = ProcessController::shared();
+        let processes = controller.snapshot();
+        for id in manager.resource_ids().await {
+            if !processes
+                .iter()
+                .any(|process| process.pid.to_string() == id)
+            {
+                let _ = manager.unregister_resource(&id).await;
+            }
+        }
+        over_limit.retain(|pid, _| processes.iter().any(|process| process.pid == *pid));
+
+        for process in processes {
+            let id = process.pid.to_string();
+            let usage = ResourceUsage {
+                memory_used: process.memory_bytes,
+                cpu_usage: process.cpu_usage,
+                gpu_memory_used: 0,
+                active_processes: 1,
+            };
+
+            let recorded = if manager.get_resource_usage(&id).await.is_some() {
+                manager.update_resource(&id, usage.clone()).await
+            } else {
+                manager.register_resource(id, usage.clone()).await
+            };
+            if let Err(e) = recorded {
+                debug!(
+                    "Resource usage of {} ({}): {}",
+                    process.name, process.pid, e
+                );
+            }
+
+            let exceeded = if config.enabled && !process.paused {
+                manager.check_usage(&usage).await
+            } else {
+                None
+            };
+            let Some(reason) = exceeded else {
+                over_limit.remove(&process.pid);
+                continue;
+            };
+
+            let count = over_limit.entry(process.pid).or_insert(0);
+            *count += 1;
+            if *count >= SAMPLES_OVER_LIMIT {
+                over_limit.remove(&process.pid);
+                Self::enforce(&controller, &process, config.action, &reason);
+            }
+        }
+    }
+
+    /// Pause or kill a player that stayed over a limit
+    fn enforce(
+        controller: &ProcessController,
+        process: &ProcessInfo,
+        action: LimitAction,
+        reason: &str,
+    ) {
+        // Processes can't be suspended on Windows, so they are killed instead
+        let message = if action == LimitAction::Pause && controller.suspend(process.pid) {
+            format!(
+                "Paused the {} because {} {}",
+                process.role, process.name, reason
+            )
+        } else if controller.kill(process.pid) {
+            format!(
+                "Stopped the {} because {} {}",
+                process.role, process.name, reason
+            )
+        } else {
+            error!(
+                "Failed to stop {} ({}), which {}",
+                process.name, process.pid, reason
+            );
+            return;
+        };
+
+        warn!("{}", message);
+        EventLog::shared().record(
+            EventLevel::Warning,
+            "resources",
+            message,
+            Some(
+                "Raise the limits in Settings → Resource Limits or choose a lighter wallpaper"
+                    .to_string(),
+            ),
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tokio::time::{sleep, Duration};
+
+    #[test]
+    fn test_usage_log_averages_by_wallpaper() {
+        let mut log = UsageLog::default();
+        let usage = |memory_mb: u64, cpu_usage: f32, active_processes: u32| ResourceUsage {
+            memory_used: memory_mb * 1024 * 1024,
+            cpu_usage,
+            gpu_memory_used: 0,
+            active_processes,
+        };
+
+        log.record(
+            Some("ocean.mp4".to_string()),
+            &usage(100, 10.0, 1),
+            Some(20.0),
+        );
+        log.record(Some("ocean.mp4".to_string()), &usage(200, 30.0, 1), None);
+        let ocean = &log.averages["ocean.mp4"];
+        assert_eq!(ocean.memory_bytes, 150 * 1024 * 1024);
+        assert_eq!(ocean.cpu_usage, 20.0);
+        assert_eq!(ocean.gpu_busy, Some(20.0));
+        assert_eq!(ocean.samples, 2);
+        assert_eq!(
+            log.current.as_ref().map(|(_, usage)| usage.cpu_usage),
+            Some(30.0)
+        );
+
+        // Wallpapers without players aren't recorded
+        log.record(Some("forest.png".to_string()), &usage(0, 0.0, 0), None);
+        assert!(log.current.is_none());
+        assert!(!log.averages.contains_key("forest.png"));
+    }
 
     #[tokio::test]
     async fn test_resource_registration() {
         let rm = ResourceManager::new(ResourceLimits::default());
-        
+
         let usage = ResourceUsage {
             memory_used: 1024 * 1024, // 1MB
             cpu_usage: 10.0,
             gpu_memory_used: 512 * 1024, // 512KB
             active_processes: 1,
         };
-        
-        assert!(rm.register_resource("test_resource".to_string(), usage).await.is_ok());
-        
+
+        assert!(rm
+            .register_resource("test_resource".to_string(), usage)
+            .await
+            .is_ok());
+
         let current_usage = rm.get_usage().await;
         assert_eq!(current_usage.memory_used, 1024 * 1024);
         assert_eq!(current_usage.cpu_usage, 10.0);
         assert_eq!(current_usage.gpu_memory_used, 512 * 1024);
         assert_eq!(current_usage.active_processes, 1);
-        
+
         assert!(rm.unregister_resource("test_resource").await.is_ok());
-        
+
         let final_usage = rm.get_usage().await;
         assert_eq!(final_usage.memory_used, 0);
         assert_eq!(final_usage.cpu_usage, 0.0);
@@ -289,59 +685,162 @@ mod tests {
             max_gpu_memory: 512 * 1024, // 512KB
             max_processes: 2,
         };
-        
+
         let rm = ResourceManager::new(limits);
-        
+
         let usage1 = ResourceUsage {
             memory_used: 512 * 1024, // 512KB
             cpu_usage: 20.0,
             gpu_memory_used: 256 * 1024, // 256KB
             active_processes: 1,
         };
-        
+
         let usage2 = ResourceUsage {
             memory_used: 768 * 1024, // 768KB
             cpu_usage: 40.0,
             gpu_memory_used: 384 * 1024, // 384KB
             active_processes: 1,
         };
-        
-        assert!(rm.register_resource("resource1".to_string(), usage1).await.is_ok());
+
+        assert!(rm
+            .register_resource("resource1".to_string(), usage1)
+            .await
+            .is_ok());
         // This should fail due to exceeding memory limit
-        assert!(rm.register_resource("resource2".to_string(), usage2).await.is_err());
-        
+        assert!(rm
+            .register_resource("resource2".to_string(), usage2.clone())
+            .await
+            .is_err());
+
         // Unregister first resource
         assert!(rm.unregister_resource("resource1").await.is_ok());
-        
+
         // Now registering the second should work
-        assert!(rm.register_resource("resource2".to_string(), usage2).await.is_ok());
+        assert!(rm
+            .register_resource("resource2".to_string(), usage2)
+            .await
+            .is_ok());
     }
 
     #[tokio::test]
     async fn test_resource_updates() {
         let rm = ResourceManager::new(ResourceLimits::default());
-        
+
         let initial_usage = ResourceUsage {
             memory_used: 1024 * 1024, // 1MB
             cpu_usage: 10.0,
             gpu_memory_used: 512 * 1024, // 512KB
             active_processes: 1,
         };
-        
-        assert!(rm.register_resource("test_resource".to_string(), initial_usage).await.is_ok());
-        
+
+        assert!(rm
+            .register_resource("test_resource".to_string(), initial_usage)
+            .await
+            .is_ok());
+
         let updated_usage = ResourceUsage {
             memory_used: 2 * 1024 * 1024, // 2MB
             cpu_usage: 20.0,
             gpu_memory_used: 1024 * 1024, // 1MB
             active_processes: 1,
         };
-        
-        assert!(rm.update_resource("test_resource", updated_usage).await.is_ok());
-        
+
+        assert!(rm
+            .update_resource("test_resource", updated_usage)
+            .await
+            .is_ok());
+
         let current_usage = rm.get_usage().await;
         assert_eq!(current_usage.memory_used, 2 * 1024 * 1024);
         assert_eq!(current_usage.cpu_usage, 20.0);
         assert_eq!(current_usage.gpu_memory_used, 1024 * 1024);
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_check_usage() {
+        let rm = ResourceManager::new(ResourceLimits::from(&ResourceLimitsConfig {
+            max_memory_mb: 100,
+            max_cpu: 50.0,
+            ..ResourceLimitsConfig::default()
+        }));
+
+        let mut usage = ResourceUsage {
+            memory_used: 50 * 1024 * 1024,
+            cpu_usage: 10.0,
+            gpu_memory_used: 0,
+            active_processes: 1,
+        };
+        assert_eq!(rm.check_usage(&usage).await, None);
+
+        usage.cpu_usage = 75.0;
+        assert_eq!(
+            rm.check_usage(&usage).await.unwrap(),
+            "uses 75% CPU, over the 50% limit"
+        );
+
+        usage.memory_used = 200 * 1024 * 1024;
+        assert_eq!(
+            rm.check_usage(&usage).await.unwrap(),
+            "uses 200 MB of memory, over the 100 MB limit"
+        );
+
+        // Usage over the limits is still recorded
+        assert!(rm
+            .register_resource(
+                "player".to_string(),
+                ResourceUsage {
+                    memory_used: 0,
+                    ..usage.clone()
+                }
+            )
+            .await
+            .is_ok());
+        assert!(rm.update_resource("player", usage).await.is_err());
+        assert_eq!(rm.get_usage().await.memory_used, 200 * 1024 * 1024);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_monitor_pauses_player_over_limit() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let controller = ProcessController::shared();
+        controller.register(child.id(), "sleep", "test wallpaper");
+
+        let rm = ResourceManager::default();
+        let config = ResourceLimitsConfig {
+            enabled: true,
+            max_memory_mb: 0,
+            ..ResourceLimitsConfig::default()
+        };
+        let mut over_limit = HashMap::new();
+        for _ in 1..SAMPLES_OVER_LIMIT {
+            ResourceMonitor::sample(&rm, &config, &mut over_limit).await;
+        }
+        assert!(rm
+            .get_resource_usage(&child.id().to_string())
+            .await
+            .is_some());
+        assert!(controller
+            .snapshot()
+            .iter()
+            .any(|process| process.pid == child.id() && !process.paused));
+
+        ResourceMonitor::sample(&rm, &config, &mut over_limit).await;
+        assert!(controller
+            .snapshot()
+            .iter()
+            .any(|process| process.pid == child.id() && process.paused));
+        assert!(EventLog::shared().recent(10).iter().any(|event| {
+            event.source == "resources"
+                && event
+                    .message
+                    .starts_with("Paused the test wallpaper because sleep uses")
+        }));
+
+        assert!(controller.kill(child.id()));
+        let _ = child.wait();
+    }
+}
@@ -0,0 +1,44 @@
+//! Tokio runtime shared by the whole application
+//!
+//! The UI, the scheduler and the background services are synchronous and
+//! drive the async wallpaper calls through this one runtime instead of
+//! building their own.
+use std::future::Future;
+use std::sync::OnceLock;
+use tokio::runtime::Runtime;
+
+/// Get the process-wide runtime
+pub fn shared() -> &'static Runtime {
+    static SHARED: OnceLock<Runtime> = OnceLock::new();
+    SHARED.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .thread_name("aether-desk-async")
+            .build()
+            .expect("Failed to create Tokio runtime")
+    })
+}
+
+/// Run a future to completion on the shared runtime
+///
+/// Must not be called from async code running on the runtime itself.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    shared().block_on(future)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_on_from_threads() {
+        let handles: Vec<_> = (0..4)
+            .map(|i| std::thread::spawn(move || block_on(async move { i * 2 })))
+            .collect();
+        let results: Vec<i32> = handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect();
+        assert_eq!(results, vec![0, 2, 4, 6]);
+    }
+}
@@ -1,29 +1,189 @@
-use crate::core::{AppError, AppResult, Config, WallpaperInfo, WallpaperType};
-use crate::platform::WallpaperManager;
-use crate::wallpapers::{AudioWallpaper, ShaderWallpaper, StaticWallpaper, VideoWallpaper, WebWallpaper, Wallpaper};
+use crate::core::config::{
+    AnimatedImageConfig, AnimatedStaticConfig, ColorTemperatureConfig, FrameRateConfig,
+    NowPlayingConfig, ParticlesConfig, PlacementConfig, ProceduralConfig, ProceduralPattern,
+    SlideshowConfig, TransitionConfig,
+};
+use crate::core::{
+    format, image_format, runtime, validate, AppError, AppResult, ChangeHooks, Config,
+    CurrentWallpaperState, CurrentWallpaperStore, ErrorContext, EventLog, WallpaperHistory,
+    WallpaperInfo, WallpaperType,
+};
+use crate::experiments::effects::temperature;
+use crate::platform::{display, MonitorInfo, WallpaperManager};
+use crate::sources::Calendar;
+use crate::wallpapers::{
+    AnimatedImageWallpaper, AnimatedStaticWallpaper, AudioWallpaper, NowPlayingWallpaper,
+    ParticlesWallpaper, ProceduralWallpaper, ShaderWallpaper, SlideshowWallpaper, StaticWallpaper,
+    VideoWallpaper, Wallpaper, WebWallpaper,
+};
 use chrono::{DateTime, Duration, Local, NaiveTime, Timelike};
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 use serde::{Deserialize, Serialize};
 
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration as StdDuration;
+use std::time::{Duration as StdDuration, Instant};
+
+/// Running wallpaper
+pub type BoxedWallpaper = Box<dyn Wallpaper + Send + Sync>;
+
+/// Running wallpapers, shared with the services that pause them
+pub type CurrentWallpaper = Arc<Mutex<ActiveWallpapers>>;
+
+/// Wallpapers on screen: one covering every monitor, or one per monitor
+///
+/// Monitors are keyed by ID. Monitors without a wallpaper of their own show
+/// whatever the desktop shows while others run theirs.
+#[derive(Default)]
+pub struct ActiveWallpapers {
+    /// Wallpaper on every monitor
+    all: Option<BoxedWallpaper>,
+
+    /// Wallpapers on single monitors, by monitor ID
+    monitors: BTreeMap<String, BoxedWallpaper>,
+}
+
+#[allow(dead_code)]
+impl ActiveWallpapers {
+    /// Check whether no wallpaper is running
+    pub fn is_empty(&self) -> bool {
+        self.all.is_none() && self.monitors.is_empty()
+    }
+
+    /// Get the wallpaper on a monitor, or the one on every monitor with None
+    pub fn get(&self, monitor: Option<&str>) -> Option<&BoxedWallpaper> {
+        match monitor {
+            Some(monitor) => self.monitors.get(monitor),
+            None => self.all.as_ref(),
+        }
+    }
+
+    /// Get the wallpaper on every monitor, or the first monitor's
+    pub fn main(&self) -> Option<&BoxedWallpaper> {
+        self.all.as_ref().or_else(|| self.monitors.values().next())
+    }
+
+    /// Iterate over the wallpapers with the monitor ID they run on, None for every monitor
+    pub fn iter(&self) -> impl Iterator<Item = (Option<&str>, &BoxedWallpaper)> {
+        self.all.iter().map(|wallpaper| (None, wallpaper)).chain(
+            self.monitors
+                .iter()
+                .map(|(monitor, wallpaper)| (Some(monitor.as_str()), wallpaper)),
+        )
+    }
+
+    /// Get the wallpapers a new one for `monitor` replaces
+    ///
+    /// One on every monitor replaces them all; one on a single monitor
+    /// replaces that monitor's and the one on every monitor.
+    pub fn replaced_by(&self, monitor: Option<&str>) -> Vec<&BoxedWallpaper> {
+        match monitor {
+            Some(monitor) => self.all.iter().chain(self.monitors.get(monitor)).collect(),
+            None => self.iter().map(|(_, wallpaper)| wallpaper).collect(),
+        }
+    }
+
+    /// Run a wallpaper on a monitor, or on every monitor with None
+    ///
+    /// Drops the wallpapers it replaces, which should be stopped first.
+    pub fn insert(&mut self, monitor: Option<String>, wallpaper: BoxedWallpaper) {
+        match monitor {
+            Some(monitor) => {
+                self.all = None;
+                self.monitors.insert(monitor, wallpaper);
+            }
+            None => {
+                self.monitors.clear();
+                self.all = Some(wallpaper);
+            }
+        }
+    }
+
+    /// Remove the wallpaper on a monitor, or the one on every monitor with None
+    pub fn take(&mut self, monitor: Option<&str>) -> Option<BoxedWallpaper> {
+        match monitor {
+            Some(monitor) => self.monitors.remove(monitor),
+            None => self.all.take(),
+        }
+    }
+
+    /// Remove every wallpaper
+    pub fn take_all(&mut self) -> Vec<BoxedWallpaper> {
+        self.all
+            .take()
+            .into_iter()
+            .chain(std::mem::take(&mut self.monitors).into_values())
+            .collect()
+    }
+
+    /// Get the images of the static wallpapers on single monitors, by monitor ID
+    fn static_images(&self) -> Vec<(String, PathBuf)> {
+        self.monitors
+            .iter()
+            .filter(|(_, wallpaper)| wallpaper.get_type() == WallpaperType::Static)
+            .filter_map(|(monitor, wallpaper)| {
+                Some((monitor.clone(), wallpaper.get_path()?.to_path_buf()))
+            })
+            .collect()
+    }
+}
+
+/// Settings new wallpapers are started with
+#[derive(Debug, Clone, Default)]
+struct ApplySettings {
+    /// How static wallpapers are fitted to the monitors
+    placement: PlacementConfig,
+
+    /// Frame rate limits of animated wallpapers
+    frame_rate: FrameRateConfig,
+
+    /// Whether static wallpapers are also set on the lock screen
+    sync_lock_screen: bool,
+
+    /// Animation between static wallpapers
+    transition: TransitionConfig,
+
+    /// Motion of animated static wallpapers
+    animation: AnimatedStaticConfig,
+
+    /// Speed and loops of animated image wallpapers
+    animated_image: AnimatedImageConfig,
+
+    /// Look and behavior of the particle wallpaper
+    particles: ParticlesConfig,
+
+    /// Settings of the procedural wallpapers
+    procedural: ProceduralConfig,
+
+    /// Timing, order and transition of slideshows
+    slideshow: SlideshowConfig,
+
+    /// Blur, cover and transition of the now-playing wallpaper
+    now_playing: NowPlayingConfig,
+
+    /// Time-of-day tint of static wallpapers
+    color_temperature: ColorTemperatureConfig,
+}
 
 /// Schedule trigger type
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum TriggerType {
     /// Time-based trigger (hour:minute)
     Time(NaiveTime),
-    
+
     /// Interval-based trigger (hours, minutes, seconds)
     Interval(Duration),
-    
+
     /// System event trigger (startup, shutdown, etc.)
     SystemEvent(String),
-    
+
     /// Custom trigger (user-defined)
     Custom(String),
+
+    /// Calendar trigger, active while a busy event whose title contains the text is on (empty for any)
+    Calendar(String),
 }
 
 /// Schedule item
@@ -31,10 +191,10 @@ pub enum TriggerType {
 pub struct ScheduleItem {
     /// Trigger type
     pub trigger: TriggerType,
-    
+
     /// Wallpaper information
     pub wallpaper: WallpaperInfo,
-    
+
     /// Whether the schedule item is enabled
     pub enabled: bool,
 }
@@ -43,21 +203,24 @@ pub struct ScheduleItem {
 pub struct WallpaperScheduler {
     /// Platform-specific wallpaper manager
     wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>,
-    
+
     /// Schedule items
     schedule_items: Arc<Mutex<Vec<ScheduleItem>>>,
-    
+
     /// Current wallpaper
-    current_wallpaper: Arc<Mutex<Option<Box<dyn Wallpaper + Send + Sync>>>>,
-    
+    current_wallpaper: CurrentWallpaper,
+
     /// Scheduler thread handle
     scheduler_thread: Option<thread::JoinHandle<()>>,
-    
+
     /// Whether the scheduler is running
     is_running: Arc<Mutex<bool>>,
-    
+
     /// Last check time
     last_check: Arc<Mutex<DateTime<Local>>>,
+
+    /// Settings new wallpapers are started with
+    settings: Arc<Mutex<ApplySettings>>,
 }
 
 impl WallpaperScheduler {
@@ -66,51 +229,101 @@ impl WallpaperScheduler {
         Self {
             wallpaper_manager,
             schedule_items: Arc::new(Mutex::new(Vec::new())),
-            current_wallpaper: Arc::new(Mutex::new(None)),
+            current_wallpaper: Arc::new(Mutex::new(ActiveWallpapers::default())),
             scheduler_thread: None,
             is_running: Arc::new(Mutex::new(false)),
             last_check: Arc::new(Mutex::new(Local::now())),
+            settings: Arc::new(Mutex::new(ApplySettings::default())),
         }
     }
-    
+
+    /// Set how static wallpapers are fitted; applies from the next wallpaper change
+    pub fn set_placement(&self, placement: PlacementConfig) {
+        self.settings.lock().unwrap().placement = placement;
+    }
+
+    /// Set the frame rate limits; applies from the next wallpaper change
+    pub fn set_frame_rate(&self, frame_rate: FrameRateConfig) {
+        self.settings.lock().unwrap().frame_rate = frame_rate;
+    }
+
+    /// Set whether static wallpapers are also set on the lock screen; applies from the next wallpaper change
+    pub fn set_lock_screen_sync(&self, sync: bool) {
+        self.settings.lock().unwrap().sync_lock_screen = sync;
+    }
+
+    /// Set the animation between static wallpapers; applies from the next wallpaper change
+    pub fn set_transition(&self, transition: TransitionConfig) {
+        self.settings.lock().unwrap().transition = transition;
+    }
+
+    /// Set the motion of animated static wallpapers; applies from the next wallpaper change
+    pub fn set_animation(&self, animation: AnimatedStaticConfig) {
+        self.settings.lock().unwrap().animation = animation;
+    }
+
+    /// Set the speed and loops of animated image wallpapers; applies from the next wallpaper change
+    pub fn set_animated_image(&self, animated_image: AnimatedImageConfig) {
+        self.settings.lock().unwrap().animated_image = animated_image;
+    }
+
+    /// Set the look of the particle wallpaper; applies from the next wallpaper change
+    pub fn set_particles(&self, particles: ParticlesConfig) {
+        self.settings.lock().unwrap().particles = particles;
+    }
+
+    /// Set the settings of the procedural wallpapers; applies from the next wallpaper change
+    pub fn set_procedural(&self, procedural: ProceduralConfig) {
+        self.settings.lock().unwrap().procedural = procedural;
+    }
+
+    /// Set the settings of slideshow wallpapers; applies from the next wallpaper change
+    pub fn set_slideshow(&self, slideshow: SlideshowConfig) {
+        self.settings.lock().unwrap().slideshow = slideshow;
+    }
+
+    /// Set the settings of the now-playing wallpaper; applies from the next wallpaper change
+    pub fn set_now_playing(&self, now_playing: NowPlayingConfig) {
+        self.settings.lock().unwrap().now_playing = now_playing;
+    }
+
+    /// Set the time-of-day tint; static wallpapers are redrawn at its next update
+    pub fn set_color_temperature(&self, color_temperature: ColorTemperatureConfig) {
+        self.settings.lock().unwrap().color_temperature = color_temperature;
+    }
+
     /// Load schedule items from configuration
     pub fn load_schedule(&mut self, config: &Config) -> AppResult<()> {
         let schedule_file = config.get_schedule_file();
-        
+
         if !schedule_file.exists() {
             debug!("Schedule file does not exist, creating default schedule");
             self.create_default_schedule(&schedule_file)?;
             return Ok(());
         }
-        
-        let schedule_content = std::fs::read_to_string(&schedule_file)
-            .map_err(|e| AppError::ConfigError(format!("Failed to read schedule file: {}", e)))?;
-        
-        let schedule_items: Vec<ScheduleItem> = serde_json::from_str(&schedule_content)
-            .map_err(|e| AppError::ConfigError(format!("Failed to parse schedule file: {}", e)))?;
-        
+
+        let schedule_items: Vec<ScheduleItem> = format::load(&schedule_file)
+            .map_err(|e| AppError::ConfigError(format!("Failed to load schedule file: {}", e)))?;
+
         let mut items = self.schedule_items.lock().unwrap();
         *items = schedule_items;
-        
+
         info!("Loaded {} schedule items", items.len());
         Ok(())
     }
-    
+
     /// Save schedule items to configuration
     pub fn save_schedule(&self, config: &Config) -> AppResult<()> {
         let schedule_file = config.get_schedule_file();
         let items = self.schedule_items.lock().unwrap();
-        
-        let schedule_content = serde_json::to_string_pretty(&*items)
-            .map_err(|e| AppError::ConfigError(format!("Failed to serialize schedule: {}", e)))?;
-        
-        std::fs::write(&schedule_file, schedule_content)
-            .map_err(|e| AppError::ConfigError(format!("Failed to write schedule file: {}", e)))?;
-        
+
+        format::save(&schedule_file, &*items)
+            .map_err(|e| AppError::ConfigError(format!("Failed to save schedule file: {}", e)))?;
+
         info!("Saved {} schedule items", items.len());
         Ok(())
     }
-    
+
     /// Create default schedule
     fn create_default_schedule(&self, schedule_file: &Path) -> AppResult<()> {
         let default_items = vec![
@@ -124,6 +337,7 @@ impl WallpaperScheduler {
                     r#type: WallpaperType::Static,
                     path: Some(PathBuf::from("assets/wallpapers/morning.jpg")),
                     url: None,
+                    monitor: None,
                 },
                 enabled: true,
             },
@@ -137,24 +351,27 @@ impl WallpaperScheduler {
                     r#type: WallpaperType::Static,
                     path: Some(PathBuf::from("assets/wallpapers/evening.jpg")),
                     url: None,
+                    monitor: None,
                 },
                 enabled: true,
             },
         ];
-        
-        let schedule_content = serde_json::to_string_pretty(&default_items)
-            .map_err(|e| AppError::ConfigError(format!("Failed to serialize default schedule: {}", e)))?;
-        
-        std::fs::write(schedule_file, schedule_content)
-            .map_err(|e| AppError::ConfigError(format!("Failed to write default schedule file: {}", e)))?;
-        
+
+        let schedule_content = serde_json::to_string_pretty(&default_items).map_err(|e| {
+            AppError::ConfigError(format!("Failed to serialize default schedule: {}", e))
+        })?;
+
+        std::fs::write(schedule_file, schedule_content).map_err(|e| {
+            AppError::ConfigError(format!("Failed to write default schedule file: {}", e))
+        })?;
+
         let mut items = self.schedule_items.lock().unwrap();
         *items = default_items;
-        
+
         info!("Created default schedule with {} items", items.len());
         Ok(())
     }
-    
+
     /// Start the scheduler
     pub fn start(&mut self) -> AppResult<()> {
         let is_running = *self.is_running.lock().unwrap();
@@ -162,69 +379,193 @@ impl WallpaperScheduler {
             debug!("Scheduler is already running");
             return Ok(());
         }
-        
+
         *self.is_running.lock().unwrap() = true;
-        
+
         let wallpaper_manager = self.wallpaper_manager.clone();
         let schedule_items = self.schedule_items.clone();
         let current_wallpaper = self.current_wallpaper.clone();
         let is_running = self.is_running.clone();
         let last_check = self.last_check.clone();
-        
+        let settings = self.settings.clone();
+
         self.scheduler_thread = Some(thread::spawn(move || {
             let check_interval = StdDuration::from_secs(60); // Check every minute
-            
+            let mut drawn_tint = temperature::kelvin_now();
+            let mut last_redraw: Option<Instant> = None;
+            // Calendar items showing their wallpaper, with the wallpaper to put back
+            let mut calendar_active: BTreeMap<usize, Option<WallpaperInfo>> = BTreeMap::new();
+
             while *is_running.lock().unwrap() {
                 let now = Local::now();
                 let mut last_check_time = last_check.lock().unwrap();
-                
+
                 // Check if a minute has passed
                 if now.signed_duration_since(*last_check_time) >= chrono::Duration::minutes(1) {
                     *last_check_time = now;
-                    
-                    // Check schedule items
-                    let items = schedule_items.lock().unwrap();
-                    for item in items.iter() {
+
+                    // Check schedule items, unlocked as calendar triggers may fetch
+                    let items = schedule_items.lock().unwrap().clone();
+                    let apply_settings = settings.lock().unwrap().clone();
+                    calendar_active.retain(|index, _| *index < items.len());
+                    for (index, item) in items.iter().enumerate() {
                         if !item.enabled {
                             continue;
                         }
-                        
+
                         match &item.trigger {
                             TriggerType::Time(time) => {
                                 // Check if current time matches the trigger time
                                 let current_time = now.time();
-                                if current_time.hour() == time.hour() && current_time.minute() == time.minute() {
+                                if current_time.hour() == time.hour()
+                                    && current_time.minute() == time.minute()
+                                {
                                     debug!("Time trigger activated: {:?}", time);
-                                    Self::apply_wallpaper(&wallpaper_manager, &current_wallpaper, &item.wallpaper);
+                                    if let Err(e) = Self::apply_wallpaper(
+                                        &wallpaper_manager,
+                                        &current_wallpaper,
+                                        &item.wallpaper,
+                                        &apply_settings,
+                                    ) {
+                                        error!("Failed to apply scheduled wallpaper: {}", e);
+                                        EventLog::shared().error(
+                                            "scheduler",
+                                            format!(
+                                                "Failed to apply scheduled wallpaper '{}'",
+                                                item.wallpaper.name
+                                            ),
+                                            e,
+                                        );
+                                    }
                                 }
-                            },
+                            }
                             TriggerType::Interval(interval) => {
                                 // Check if the interval has passed
                                 // This is a simplified implementation
                                 // A more robust implementation would track the last time each interval was triggered
                                 debug!("Interval trigger activated: {:?}", interval);
-                                Self::apply_wallpaper(&wallpaper_manager, &current_wallpaper, &item.wallpaper);
-                            },
+                                if let Err(e) = Self::apply_wallpaper(
+                                    &wallpaper_manager,
+                                    &current_wallpaper,
+                                    &item.wallpaper,
+                                    &apply_settings,
+                                ) {
+                                    error!("Failed to apply scheduled wallpaper: {}", e);
+                                    EventLog::shared().error(
+                                        "scheduler",
+                                        format!(
+                                            "Failed to apply scheduled wallpaper '{}'",
+                                            item.wallpaper.name
+                                        ),
+                                        e,
+                                    );
+                                }
+                            }
                             TriggerType::SystemEvent(event) => {
                                 // System events are not implemented in this version
                                 debug!("System event trigger not implemented: {}", event);
-                            },
+                            }
                             TriggerType::Custom(trigger) => {
                                 // Custom triggers are not implemented in this version
                                 debug!("Custom trigger not implemented: {}", trigger);
-                            },
+                            }
+                            TriggerType::Calendar(title) => {
+                                let event = Calendar::shared().busy_now(title);
+                                match (event, calendar_active.contains_key(&index)) {
+                                    (Some(event), false) => {
+                                        debug!("Calendar trigger activated: {}", event.summary);
+                                        let previous = CurrentWallpaperStore::shared()
+                                            .get()
+                                            .map(|state| state.wallpaper);
+                                        match Self::apply_wallpaper(
+                                            &wallpaper_manager,
+                                            &current_wallpaper,
+                                            &item.wallpaper,
+                                            &apply_settings,
+                                        ) {
+                                            Ok(()) => {
+                                                calendar_active.insert(index, previous);
+                                            }
+                                            Err(e) => {
+                                                error!(
+                                                    "Failed to apply scheduled wallpaper: {}",
+                                                    e
+                                                );
+                                                EventLog::shared().error(
+                                                    "scheduler",
+                                                    format!(
+                                                        "Failed to apply scheduled wallpaper '{}'",
+                                                        item.wallpaper.name
+                                                    ),
+                                                    e,
+                                                );
+                                            }
+                                        }
+                                    }
+                                    (None, true) => {
+                                        // Put back the earlier wallpaper, unless another was chosen during the event
+                                        let previous = calendar_active.remove(&index).flatten();
+                                        let showing = CurrentWallpaperStore::shared()
+                                            .get()
+                                            .map(|state| state.wallpaper);
+                                        if let Some(previous) = previous
+                                            .filter(|_| showing.as_ref() == Some(&item.wallpaper))
+                                        {
+                                            debug!(
+                                                "Calendar event ended, restoring {}",
+                                                previous.name
+                                            );
+                                            if let Err(e) = Self::apply_wallpaper(
+                                                &wallpaper_manager,
+                                                &current_wallpaper,
+                                                &previous,
+                                                &apply_settings,
+                                            ) {
+                                                error!("Failed to restore wallpaper: {}", e);
+                                                EventLog::shared().error(
+                                                    "scheduler",
+                                                    format!(
+                                                        "Failed to restore wallpaper '{}'",
+                                                        previous.name
+                                                    ),
+                                                    e,
+                                                );
+                                            }
+                                        }
+                                    }
+                                    _ => {}
+                                }
+                            }
                         }
                     }
+
+                    // Static wallpapers are redrawn as the time-of-day tint changes
+                    let tint = temperature::kelvin_now();
+                    let update = StdDuration::from_secs(
+                        apply_settings.color_temperature.update_minutes.max(1) as u64 * 60,
+                    );
+                    if tint != drawn_tint
+                        && last_redraw.map_or(true, |last| last.elapsed() >= update)
+                    {
+                        debug!("Color temperature changed to {:?}, redrawing", tint);
+                        for (_, wallpaper) in current_wallpaper.lock().unwrap().iter() {
+                            if let Err(e) = runtime::block_on(wallpaper.redraw()) {
+                                warn!("Failed to redraw wallpaper: {}", e);
+                            }
+                        }
+                        drawn_tint = tint;
+                        last_redraw = Some(Instant::now());
+                    }
                 }
-                
+
                 thread::sleep(check_interval);
             }
         }));
-        
+
         info!("Scheduler started");
         Ok(())
     }
-    
+
     /// Stop the scheduler
     #[allow(dead_code)]
     pub fn stop(&mut self) -> AppResult<()> {
@@ -233,19 +574,24 @@ impl WallpaperScheduler {
             debug!("Scheduler is not running");
             return Ok(());
         }
-        
+
         *self.is_running.lock().unwrap() = false;
-        
+
         if let Some(thread) = self.scheduler_thread.take() {
             thread.join().map_err(|e| {
                 AppError::Other(format!("Failed to join scheduler thread: {:?}", e))
             })?;
         }
-        
+
         info!("Scheduler stopped");
         Ok(())
     }
-    
+
+    /// Check whether the scheduler is running
+    pub fn is_running(&self) -> bool {
+        *self.is_running.lock().unwrap()
+    }
+
     /// Add a schedule item
     pub fn add_schedule_item(&self, item: ScheduleItem) -> AppResult<()> {
         let mut items = self.schedule_items.lock().unwrap();
@@ -253,7 +599,7 @@ impl WallpaperScheduler {
         info!("Added schedule item");
         Ok(())
     }
-    
+
     /// Remove a schedule item
     pub fn remove_schedule_item(&self, index: usize) -> AppResult<()> {
         let mut items = self.schedule_items.lock().unwrap();
@@ -261,11 +607,14 @@ impl WallpaperScheduler {
             items.remove(index);
             info!("Removed schedule item at index {}", index);
         } else {
-            return Err(AppError::Other(format!("Invalid schedule item index: {}", index)));
+            return Err(AppError::Other(format!(
+                "Invalid schedule item index: {}",
+                index
+            )));
         }
         Ok(())
     }
-    
+
     /// Update a schedule item
     pub fn update_schedule_item(&self, index: usize, item: ScheduleItem) -> AppResult<()> {
         let mut items = self.schedule_items.lock().unwrap();
@@ -273,82 +622,304 @@ impl WallpaperScheduler {
             items[index] = item;
             info!("Updated schedule item at index {}", index);
         } else {
-            return Err(AppError::Other(format!("Invalid schedule item index: {}", index)));
+            return Err(AppError::Other(format!(
+                "Invalid schedule item index: {}",
+                index
+            )));
         }
         Ok(())
     }
-    
+
     /// Get all schedule items
     pub fn get_schedule_items(&self) -> Vec<ScheduleItem> {
         let items = self.schedule_items.lock().unwrap();
         items.clone()
     }
-    
-    /// Apply a wallpaper
+
+    /// Apply a wallpaper right away, replacing the current one
+    pub fn apply_now(&self, wallpaper_info: &WallpaperInfo) -> AppResult<()> {
+        self.applier()(wallpaper_info)
+    }
+
+    /// Get a function applying wallpapers like `apply_now`, for use on other threads
+    pub fn applier(&self) -> impl Fn(&WallpaperInfo) -> AppResult<()> + Send + 'static {
+        let wallpaper_manager = self.wallpaper_manager.clone();
+        let current_wallpaper = self.current_wallpaper.clone();
+        let settings = self.settings.clone();
+        move |wallpaper_info| {
+            let settings = settings.lock().unwrap().clone();
+            Self::apply_wallpaper(
+                &wallpaper_manager,
+                &current_wallpaper,
+                wallpaper_info,
+                &settings,
+            )
+            .with_context(|| {
+                format!(
+                    "{} wallpaper {}",
+                    wallpaper_info.r#type.as_str(),
+                    wallpaper_info.name
+                )
+            })
+        }
+    }
+
+    /// Get the running wallpaper
+    pub fn current_wallpaper(&self) -> CurrentWallpaper {
+        self.current_wallpaper.clone()
+    }
+
+    /// Stop the current wallpapers, on every monitor
+    pub fn stop_current(&self) -> AppResult<()> {
+        for wallpaper in self.current_wallpaper.lock().unwrap().take_all() {
+            runtime::block_on(wallpaper.stop())?;
+            info!("Stopped current wallpaper");
+        }
+        CurrentWallpaperStore::shared().clear();
+        Ok(())
+    }
+
+    /// Stop the wallpaper shown on one monitor only, given by ID or name
+    pub fn stop_on(&self, monitor: &str) -> AppResult<()> {
+        let monitor =
+            Self::find_monitor(monitor).map_or_else(|_| monitor.to_string(), |monitor| monitor.id);
+        if let Some(wallpaper) = self.current_wallpaper.lock().unwrap().take(Some(&monitor)) {
+            runtime::block_on(wallpaper.stop())?;
+            info!("Stopped wallpaper on {}", monitor);
+        }
+        CurrentWallpaperStore::shared().clear_on(&monitor);
+        Ok(())
+    }
+
+    /// Find a connected monitor by ID or name
+    ///
+    /// When the monitors can't be detected, the target is taken as a name.
+    fn find_monitor(target: &str) -> AppResult<MonitorInfo> {
+        match display::monitors() {
+            Ok(monitors) => monitors
+                .into_iter()
+                .find(|monitor| monitor.id == target || monitor.name == target)
+                .ok_or_else(|| {
+                    AppError::WallpaperError(format!("Monitor {} is not connected", target))
+                }),
+            Err(e) => {
+                warn!(
+                    "Failed to detect monitors ({}); assuming {} names one",
+                    e, target
+                );
+                Ok(MonitorInfo::new(target, 0, 0))
+            }
+        }
+    }
+
+    /// Apply a wallpaper, on the monitor it names or on every monitor
     fn apply_wallpaper(
         wallpaper_manager: &Arc<dyn WallpaperManager + Send + Sync>,
-        current_wallpaper: &Arc<Mutex<Option<Box<dyn Wallpaper + Send + Sync>>>>,
+        current_wallpaper: &CurrentWallpaper,
         wallpaper_info: &WallpaperInfo,
-    ) {
-        // Stop current wallpaper if any
-        if let Some(wallpaper) = &mut *current_wallpaper.lock().unwrap() {
-            let rt = tokio::runtime::Runtime::new().unwrap();
-            if let Err(e) = rt.block_on(wallpaper.stop()) {
-                error!("Failed to stop current wallpaper: {}", e);
+        settings: &ApplySettings,
+    ) -> AppResult<()> {
+        let max_fps = |path: &Path| {
+            settings
+                .frame_rate
+                .limit_for(path)
+                .fps(display::max_refresh_rate)
+        };
+
+        // Mirroring shows the wallpaper on every monitor the same way
+        let mut placement = settings.placement.clone();
+        let mirrored;
+        let wallpaper_info = if placement.mirror {
+            placement.monitor_images.clear();
+            placement.span = false;
+            mirrored = wallpaper_info.clone().on_monitor(None);
+            &mirrored
+        } else {
+            wallpaper_info
+        };
+
+        let monitor = match &wallpaper_info.monitor {
+            Some(_) if !wallpaper_info.r#type.runs_per_monitor() => {
+                return Err(AppError::WallpaperError(format!(
+                    "{} wallpapers can't be shown on a single monitor",
+                    wallpaper_info.r#type.as_str()
+                )));
+            }
+            Some(target) => Some(Self::find_monitor(target)?),
+            None => None,
+        };
+        let target = monitor.as_ref().map(|monitor| monitor.id.clone());
+        let screen = monitor.map(|monitor| monitor.name);
+        let wallpaper_info = &WallpaperInfo {
+            monitor: target.clone(),
+            ..wallpaper_info.clone()
+        };
+
+        // A broken file is refused while the current wallpaper still shows
+        validate::check(wallpaper_info)?;
+
+        // A static image on every monitor is where a transition starts from
+        let previous = CurrentWallpaperStore::shared()
+            .get()
+            .filter(|state| {
+                state.wallpaper.r#type == WallpaperType::Static && state.wallpaper.monitor.is_none()
+            })
+            .and_then(|state| state.wallpaper.path);
+
+        // Stop the wallpapers the new one replaces, if any
+        {
+            let active = current_wallpaper.lock().unwrap();
+            for wallpaper in active.replaced_by(target.as_deref()) {
+                if let Err(e) = runtime::block_on(wallpaper.stop()) {
+                    error!("Failed to stop current wallpaper: {}", e);
+                }
+            }
+            // Monitors showing a static image of their own keep it
+            if let Some(target) = &target {
+                for (monitor, path) in active.static_images() {
+                    if monitor != *target {
+                        placement
+                            .monitor_images
+                            .insert(monitor, path.display().to_string());
+                    }
+                }
             }
         }
-        
+
         // Create and start new wallpaper
         let wallpaper: Box<dyn Wallpaper + Send + Sync> = match wallpaper_info.r#type {
             WallpaperType::Static => {
-                if let Some(path) = &wallpaper_info.path {
-                    Box::new(StaticWallpaper::new(path, wallpaper_manager.clone()))
-                } else {
-                    error!("Static wallpaper path is missing");
-                    return;
-                }
-            },
+                let path = wallpaper_info.path.as_ref().ok_or_else(|| {
+                    AppError::WallpaperError("Static wallpaper path is missing".to_string())
+                })?;
+                Box::new(
+                    StaticWallpaper::new(path, wallpaper_manager.clone())
+                        .with_placement(placement.clone())
+                        .with_monitor(target.clone())
+                        .with_transition(previous, settings.transition),
+                )
+            }
+            WallpaperType::AnimatedStatic => {
+                let path = wallpaper_info.path.as_ref().ok_or_else(|| {
+                    AppError::WallpaperError(
+                        "Animated static wallpaper path is missing".to_string(),
+                    )
+                })?;
+                Box::new(
+                    AnimatedStaticWallpaper::new(path, wallpaper_manager.clone())
+                        .with_animation(settings.animation.clone()),
+                )
+            }
+            WallpaperType::AnimatedImage => {
+                let path = wallpaper_info.path.as_ref().ok_or_else(|| {
+                    AppError::WallpaperError("Animated image wallpaper path is missing".to_string())
+                })?;
+                Box::new(
+                    AnimatedImageWallpaper::new(path, wallpaper_manager.clone())
+                        .with_settings(settings.animated_image.clone())
+                        .with_placement(placement.clone()),
+                )
+            }
             WallpaperType::Video => {
-                if let Some(path) = &wallpaper_info.path {
-                    Box::new(VideoWallpaper::new(path, wallpaper_manager.clone()))
-                } else {
-                    error!("Video wallpaper path is missing");
-                    return;
-                }
-            },
+                let path = wallpaper_info.path.as_ref().ok_or_else(|| {
+                    AppError::WallpaperError("Video wallpaper path is missing".to_string())
+                })?;
+                Box::new(
+                    VideoWallpaper::new(path, wallpaper_manager.clone())
+                        .with_max_fps(max_fps(path))
+                        .with_span(placement.span)
+                        .with_monitor(screen.clone()),
+                )
+            }
             WallpaperType::Web => {
-                if let Some(url) = &wallpaper_info.url {
-                    Box::new(WebWallpaper::new(url, wallpaper_manager.clone()))
-                } else {
-                    error!("Web wallpaper URL is missing");
-                    return;
-                }
-            },
+                let url = wallpaper_info.url.as_ref().ok_or_else(|| {
+                    AppError::WallpaperError("Web wallpaper URL is missing".to_string())
+                })?;
+                Box::new(WebWallpaper::new(url, wallpaper_manager.clone()))
+            }
             WallpaperType::Shader => {
-                if let Some(path) = &wallpaper_info.path {
-                    Box::new(ShaderWallpaper::new(path, wallpaper_manager.clone()))
-                } else {
-                    error!("Shader wallpaper path is missing");
-                    return;
-                }
-            },
+                let path = wallpaper_info.path.as_ref().ok_or_else(|| {
+                    AppError::WallpaperError("Shader wallpaper path is missing".to_string())
+                })?;
+                Box::new(
+                    ShaderWallpaper::new(path, wallpaper_manager.clone())
+                        .with_max_fps(max_fps(path))
+                        .with_span(placement.span)
+                        .with_monitor(screen.clone()),
+                )
+            }
             WallpaperType::Audio => {
-                if let Some(path) = &wallpaper_info.path {
-                    Box::new(AudioWallpaper::new(path, wallpaper_manager.clone()))
-                } else {
-                    error!("Audio wallpaper path is missing");
-                    return;
-                }
-            },
+                let path = wallpaper_info.path.as_ref().ok_or_else(|| {
+                    AppError::WallpaperError("Audio wallpaper path is missing".to_string())
+                })?;
+                Box::new(AudioWallpaper::new(path, wallpaper_manager.clone()))
+            }
+            WallpaperType::Particles => Box::new(
+                ParticlesWallpaper::new(wallpaper_manager.clone())
+                    .with_settings(settings.particles.clone()),
+            ),
+            WallpaperType::Procedural => {
+                let id = wallpaper_info.url.as_deref().unwrap_or_default();
+                let pattern = ProceduralPattern::from_id(id).ok_or_else(|| {
+                    AppError::WallpaperError(format!("Unknown procedural wallpaper: {}", id))
+                })?;
+                Box::new(
+                    ProceduralWallpaper::new(pattern, wallpaper_manager.clone())
+                        .with_settings(settings.procedural.clone()),
+                )
+            }
+            WallpaperType::Slideshow => {
+                let folder = wallpaper_info.path.as_ref().ok_or_else(|| {
+                    AppError::WallpaperError("Slideshow folder is missing".to_string())
+                })?;
+                Box::new(
+                    SlideshowWallpaper::new(folder, wallpaper_manager.clone())
+                        .with_settings(settings.slideshow.clone())
+                        .with_placement(placement.clone()),
+                )
+            }
+            WallpaperType::NowPlaying => Box::new(
+                NowPlayingWallpaper::new(wallpaper_manager.clone())
+                    .with_settings(settings.now_playing.clone()),
+            ),
         };
-        
-        let rt = tokio::runtime::Runtime::new().unwrap();
-        if let Err(e) = rt.block_on(wallpaper.start()) {
-            error!("Failed to start wallpaper: {}", e);
-            return;
+
+        runtime::block_on(wallpaper.start())?;
+
+        let state = CurrentWallpaperState::new(
+            wallpaper_info.clone(),
+            wallpaper.monitors(),
+            runtime::block_on(wallpaper.process_id()),
+        );
+        current_wallpaper
+            .lock()
+            .unwrap()
+            .insert(target.clone(), wallpaper);
+        CurrentWallpaperStore::shared().set(state);
+        match &screen {
+            Some(screen) => info!("Applied wallpaper on {}: {}", screen, wallpaper_info.name),
+            None => info!("Applied wallpaper: {}", wallpaper_info.name),
         }
-        
-        *current_wallpaper.lock().unwrap() = Some(wallpaper);
-        info!("Applied wallpaper: {}", wallpaper_info.name);
+        WallpaperHistory::shared().record(wallpaper_info);
+        ChangeHooks::shared().run(wallpaper_info);
+
+        // The lock screen follows images set on the whole desktop; a failure there leaves the desktop as is
+        if let (true, WallpaperType::Static, Some(path), None) = (
+            settings.sync_lock_screen,
+            &wallpaper_info.r#type,
+            &wallpaper_info.path,
+            &target,
+        ) {
+            let set_lock_screen = async {
+                let path =
+                    image_format::compatible(path, wallpaper_manager.image_formats()).await?;
+                wallpaper_manager.set_lock_screen_wallpaper(&path).await
+            };
+            if let Err(e) = runtime::block_on(set_lock_screen) {
+                warn!("Failed to set lock screen wallpaper: {}", e);
+                EventLog::shared().error("lockscreen", "Failed to set lock screen wallpaper", e);
+            }
+        }
+        Ok(())
     }
-} 
\ No newline at end of file
+}
@@ -0,0 +1,594 @@
+//! Secret storage for API keys and tokens
+//!
+//! Secrets go to the OS keyring where there is one. Otherwise they're sealed
+//! with ChaCha20-Poly1305 in `secrets.json`, under a random key kept in
+//! `secrets.key`. Both are created readable by the current user only, with
+//! mode 0600 on Unix and an access list granting only that user on Windows.
+//! The file keeps secrets out of the configuration and anything copying it, and a
+//! tampered or swapped entry fails to open rather than reading as garbage.
+use crate::core::{AppError, AppResult, Config};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Service name used for keyring entries
+const SERVICE_NAME: &str = "aether-desk";
+
+/// Secret storage backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecretBackend {
+    /// OS keyring (Windows Credential Manager, Secret Service)
+    Keyring,
+
+    /// Encrypted file in the configuration directory
+    EncryptedFile,
+}
+
+/// Encrypted secrets file contents
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct EncryptedSecrets {
+    /// Encrypted entries (hex nonce, hex ciphertext)
+    entries: HashMap<String, EncryptedEntry>,
+}
+
+/// Single encrypted secret
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EncryptedEntry {
+    /// Nonce (hex)
+    nonce: String,
+
+    /// Ciphertext (hex)
+    data: String,
+}
+
+/// Secret store backed by the OS keyring with an encrypted file fallback
+pub struct SecretStore {
+    /// Active backend
+    backend: SecretBackend,
+
+    /// Directory holding the fallback files
+    dir: PathBuf,
+}
+
+impl SecretStore {
+    /// Create a new secret store, preferring the OS keyring when available
+    pub fn new() -> Self {
+        let backend = if keyring_available() {
+            SecretBackend::Keyring
+        } else {
+            SecretBackend::EncryptedFile
+        };
+        info!("Using {:?} secret storage backend", backend);
+
+        Self::with_backend(backend, Self::default_dir())
+    }
+
+    /// Create a secret store with an explicit backend and fallback directory
+    pub fn with_backend(backend: SecretBackend, dir: PathBuf) -> Self {
+        Self { backend, dir }
+    }
+
+    /// Default directory for the fallback files
+    fn default_dir() -> PathBuf {
+        Config::get_config_dir().unwrap_or_else(|_| {
+            let mut dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+            dir.push("config");
+            dir
+        })
+    }
+
+    /// Get the active backend
+    pub fn backend(&self) -> SecretBackend {
+        self.backend
+    }
+
+    /// Get a secret
+    pub fn get(&self, key: &str) -> AppResult<Option<String>> {
+        if self.backend == SecretBackend::Keyring {
+            match keyring_get(key) {
+                Ok(Some(value)) => return Ok(Some(value)),
+                Ok(None) => {}
+                Err(e) => warn!("Keyring lookup failed for {}: {}", key, e),
+            }
+        }
+
+        // Secrets written while the keyring was unavailable live in the file
+        self.file_get(key)
+    }
+
+    /// Store a secret
+    pub fn set(&self, key: &str, value: &str) -> AppResult<()> {
+        if self.backend == SecretBackend::Keyring {
+            match keyring_set(key, value) {
+                Ok(()) => {
+                    // Drop any stale copy from the fallback file
+                    self.file_delete(key)?;
+                    debug!("Stored secret {} in keyring", key);
+                    return Ok(());
+                }
+                Err(e) => warn!(
+                    "Keyring store failed for {}, using encrypted file: {}",
+                    key, e
+                ),
+            }
+        }
+
+        self.file_set(key, value)?;
+        debug!("Stored secret {} in encrypted file", key);
+        Ok(())
+    }
+
+    /// Delete a secret
+    pub fn delete(&self, key: &str) -> AppResult<()> {
+        if self.backend == SecretBackend::Keyring {
+            if let Err(e) = keyring_delete(key) {
+                warn!("Keyring delete failed for {}: {}", key, e);
+            }
+        }
+
+        self.file_delete(key)
+    }
+
+    /// Get the encrypted secrets file path
+    fn secrets_path(&self) -> PathBuf {
+        self.dir.join("secrets.json")
+    }
+
+    /// Get the encryption key file path
+    fn key_path(&self) -> PathBuf {
+        self.dir.join("secrets.key")
+    }
+
+    /// Load or create the file encryption key
+    fn load_key(&self) -> AppResult<[u8; 32]> {
+        let key_path = self.key_path();
+
+        if key_path.exists() {
+            let bytes = std::fs::read(&key_path)?;
+            return bytes.try_into().map_err(|_| {
+                AppError::SecretError(format!("Invalid key file: {}", key_path.display()))
+            });
+        }
+
+        let mut key = [0u8; 32];
+        getrandom::fill(&mut key)
+            .map_err(|e| AppError::SecretError(format!("Failed to generate key: {}", e)))?;
+
+        std::fs::create_dir_all(&self.dir)?;
+        let mut file = match create_private(&key_path) {
+            Ok(file) => file,
+            // Another process created it first; use theirs
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => return self.load_key(),
+            Err(e) => return Err(e.into()),
+        };
+        file.write_all(&key)?;
+        file.sync_all()?;
+
+        info!("Created secrets key at {}", key_path.display());
+        Ok(key)
+    }
+
+    /// Load the encrypted secrets file
+    fn load_file(&self) -> AppResult<EncryptedSecrets> {
+        let path = self.secrets_path();
+
+        if !path.exists() {
+            return Ok(EncryptedSecrets::default());
+        }
+
+        let content = std::fs::read_to_string(&path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Save the encrypted secrets file
+    fn save_file(&self, secrets: &EncryptedSecrets) -> AppResult<()> {
+        let path = self.secrets_path();
+        std::fs::create_dir_all(&self.dir)?;
+
+        // Written in full to a private file first, then moved over the old one
+        let mut file = tempfile::Builder::new()
+            .prefix(".secrets-")
+            .tempfile_in(&self.dir)?;
+        restrict_permissions(file.path())?;
+        file.write_all(serde_json::to_string_pretty(secrets)?.as_bytes())?;
+        file.as_file().sync_all()?;
+        file.persist(&path)
+            .map_err(|e| AppError::IoError(e.error))?;
+        Ok(())
+    }
+
+    /// Get a secret from the encrypted file
+    fn file_get(&self, key: &str) -> AppResult<Option<String>> {
+        let secrets = self.load_file()?;
+        let entry = match secrets.entries.get(key) {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+
+        let nonce: [u8; 12] = decode_hex(&entry.nonce)
+            .and_then(|n| n.try_into().ok())
+            .ok_or_else(|| AppError::SecretError(format!("Corrupt nonce for {}", key)))?;
+        let data = decode_hex(&entry.data)
+            .ok_or_else(|| AppError::SecretError(format!("Corrupt data for {}", key)))?;
+
+        // The name is authenticated too, so an entry copied under another name won't open
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(&self.load_key()?));
+        let value = cipher
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &data,
+                    aad: key.as_bytes(),
+                },
+            )
+            .map_err(|_| AppError::SecretError(format!("Failed to decrypt {}", key)))?;
+
+        String::from_utf8(value)
+            .map(Some)
+            .map_err(|_| AppError::SecretError(format!("Failed to decrypt {}", key)))
+    }
+
+    /// Store a secret in the encrypted file
+    fn file_set(&self, key: &str, value: &str) -> AppResult<()> {
+        let mut nonce = [0u8; 12];
+        getrandom::fill(&mut nonce)
+            .map_err(|e| AppError::SecretError(format!("Failed to generate nonce: {}", e)))?;
+
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(&self.load_key()?));
+        let data = cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: value.as_bytes(),
+                    aad: key.as_bytes(),
+                },
+            )
+            .map_err(|_| AppError::SecretError(format!("Failed to encrypt {}", key)))?;
+
+        let mut secrets = self.load_file()?;
+        secrets.entries.insert(
+            key.to_string(),
+            EncryptedEntry {
+                nonce: encode_hex(&nonce),
+                data: encode_hex(&data),
+            },
+        );
+        self.save_file(&secrets)
+    }
+
+    /// Delete a secret from the encrypted file
+    fn file_delete(&self, key: &str) -> AppResult<()> {
+        let mut secrets = self.load_file()?;
+        if secrets.entries.remove(key).is_some() {
+            self.save_file(&secrets)?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for SecretStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Check whether the OS keyring can be used
+fn keyring_available() -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        // A lookup for a missing entry exits non-zero with empty stderr when
+        // the Secret Service is reachable
+        match Command::new("secret-tool")
+            .args(["lookup", "service", SERVICE_NAME, "account", "__probe__"])
+            .output()
+        {
+            Ok(output) => output.stderr.is_empty(),
+            Err(_) => false,
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        true
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    {
+        false
+    }
+}
+
+/// Run a command with the given stdin and return its output
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+fn run_with_stdin(mut command: Command, input: &str) -> AppResult<std::process::Output> {
+    use std::process::Stdio;
+
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input.as_bytes())?;
+    }
+
+    Ok(child.wait_with_output()?)
+}
+
+/// PowerShell prelude loading the Windows PasswordVault and reading the service and key from the input
+#[cfg(target_os = "windows")]
+const VAULT_PRELUDE: &str = "$utf8 = New-Object Text.UTF8Encoding $false; [Console]::InputEncoding = $utf8; [Console]::OutputEncoding = $utf8; [void][Windows.Security.Credentials.PasswordVault,Windows.Security.Credentials,ContentType=WindowsRuntime]; $v = New-Object Windows.Security.Credentials.PasswordVault; $s = [Console]::In.ReadLine(); $k = [Console]::In.ReadLine();";
+
+/// Run a PasswordVault script, giving it the service, the key and the value on its input
+///
+/// Nothing is written into the script itself, so no key or value can change what it does.
+#[cfg(target_os = "windows")]
+fn run_vault(script: &str, key: &str, value: &str) -> AppResult<std::process::Output> {
+    let mut command = Command::new("powershell");
+    command.args([
+        "-NoProfile",
+        "-NonInteractive",
+        "-Command",
+        &format!("{} {}", VAULT_PRELUDE, script),
+    ]);
+    run_with_stdin(command, &format!("{}\n{}\n{}", SERVICE_NAME, key, value))
+}
+
+/// Look up a secret in the OS keyring
+fn keyring_get(key: &str) -> AppResult<Option<String>> {
+    #[cfg(target_os = "linux")]
+    let output = Command::new("secret-tool")
+        .args(["lookup", "service", SERVICE_NAME, "account", key])
+        .output()?;
+
+    #[cfg(target_os = "windows")]
+    let output = run_vault(
+        "try { $c = $v.Retrieve($s, $k); $c.RetrievePassword(); [Console]::Out.Write($c.Password) } catch { exit 1 }",
+        key,
+        "",
+    )?;
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    return Err(AppError::UnsupportedPlatform);
+
+    #[cfg(any(target_os = "windows", target_os = "linux"))]
+    {
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            if error.trim().is_empty() {
+                return Ok(None);
+            }
+            return Err(AppError::SecretError(error.to_string()));
+        }
+
+        Ok(Some(String::from_utf8_lossy(&output.stdout).to_string()))
+    }
+}
+
+/// Store a secret in the OS keyring
+fn keyring_set(key: &str, value: &str) -> AppResult<()> {
+    #[cfg(target_os = "linux")]
+    let output = {
+        let mut command = Command::new("secret-tool");
+        command.args([
+            "store",
+            &format!("--label=Aether-Desk {}", key),
+            "service",
+            SERVICE_NAME,
+            "account",
+            key,
+        ]);
+        run_with_stdin(command, value)?
+    };
+
+    #[cfg(target_os = "windows")]
+    let output = run_vault(
+        "$p = [Console]::In.ReadToEnd(); $v.Add((New-Object Windows.Security.Credentials.PasswordCredential($s, $k, $p)))",
+        key,
+        value,
+    )?;
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    return {
+        let _ = (key, value);
+        Err(AppError::UnsupportedPlatform)
+    };
+
+    #[cfg(any(target_os = "windows", target_os = "linux"))]
+    {
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(AppError::SecretError(error.to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+/// Delete a secret from the OS keyring
+fn keyring_delete(key: &str) -> AppResult<()> {
+    #[cfg(target_os = "linux")]
+    let output = Command::new("secret-tool")
+        .args(["clear", "service", SERVICE_NAME, "account", key])
+        .output()?;
+
+    #[cfg(target_os = "windows")]
+    let output = run_vault("try { $v.Remove($v.Retrieve($s, $k)) } catch { }", key, "")?;
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    return Err(AppError::UnsupportedPlatform);
+
+    #[cfg(any(target_os = "windows", target_os = "linux"))]
+    {
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(AppError::SecretError(error.to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+/// Create a new file only the current user can read, failing if it exists
+fn create_private(path: &Path) -> std::io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let file = options.open(path)?;
+    #[cfg(not(unix))]
+    if let Err(e) = restrict_permissions(path) {
+        let _ = std::fs::remove_file(path);
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            e.to_string(),
+        ));
+    }
+    Ok(file)
+}
+
+/// Restrict a file to the current user
+///
+/// On Windows the inherited permissions are dropped and only the current
+/// user is granted access.
+fn restrict_permissions(path: &Path) -> AppResult<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    }
+
+    #[cfg(windows)]
+    {
+        let user = std::env::var("USERNAME").map_err(|_| {
+            AppError::SecretError(
+                "Can't tell the current user to restrict secret files to".to_string(),
+            )
+        })?;
+        let output = Command::new("icacls")
+            .arg(path)
+            .args(["/inheritance:r", "/grant:r"])
+            .arg(format!("{}:F", user))
+            .output()
+            .map_err(|e| AppError::from_spawn("icacls", e))?;
+        if !output.status.success() {
+            return Err(AppError::SecretError(format!(
+                "Failed to restrict {}: {}",
+                path.display(),
+                String::from_utf8_lossy(&output.stdout).trim()
+            )));
+        }
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    let _ = path;
+
+    Ok(())
+}
+
+/// Encode bytes as lowercase hex
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Decode a hex string
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_encrypted_file_roundtrip() {
+        let temp_dir = TempDir::new().expect("Failed to create temp directory");
+        let store =
+            SecretStore::with_backend(SecretBackend::EncryptedFile, temp_dir.path().to_path_buf());
+
+        assert_eq!(store.get("weather.api_key").unwrap(), None);
+
+        store.set("weather.api_key", "abc123").unwrap();
+        assert_eq!(
+            store.get("weather.api_key").unwrap(),
+            Some("abc123".to_string())
+        );
+
+        // The plaintext must not appear on disk
+        let on_disk = std::fs::read_to_string(temp_dir.path().join("secrets.json")).unwrap();
+        assert!(!on_disk.contains("abc123"));
+
+        store.delete("weather.api_key").unwrap();
+        assert_eq!(store.get("weather.api_key").unwrap(), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_files_are_private() {
+        use std::os::unix::fs::PermissionsExt;
+        let temp_dir = TempDir::new().expect("Failed to create temp directory");
+        let store =
+            SecretStore::with_backend(SecretBackend::EncryptedFile, temp_dir.path().to_path_buf());
+        store.set("weather.api_key", "abc123").unwrap();
+        store.set("weather.api_key", "def456").unwrap();
+
+        for name in ["secrets.key", "secrets.json"] {
+            let mode = std::fs::metadata(temp_dir.path().join(name))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600, "{}", name);
+        }
+        // Only the two files are left, no temporary copies
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn test_tampered_entries_are_refused() {
+        let temp_dir = TempDir::new().expect("Failed to create temp directory");
+        let store =
+            SecretStore::with_backend(SecretBackend::EncryptedFile, temp_dir.path().to_path_buf());
+        store.set("weather.api_key", "abc123").unwrap();
+        store.set("wallhaven.api_key", "def456").unwrap();
+
+        // An entry moved under another name doesn't open
+        let mut secrets = store.load_file().unwrap();
+        let weather = secrets.entries["weather.api_key"].clone();
+        secrets
+            .entries
+            .insert("wallhaven.api_key".to_string(), weather.clone());
+        store.save_file(&secrets).unwrap();
+        assert!(store.get("wallhaven.api_key").is_err());
+
+        // Neither does a changed one
+        let mut data = decode_hex(&weather.data).unwrap();
+        data[0] ^= 1;
+        secrets.entries.insert(
+            "weather.api_key".to_string(),
+            EncryptedEntry {
+                data: encode_hex(&data),
+                ..weather
+            },
+        );
+        store.save_file(&secrets).unwrap();
+        assert!(store.get("weather.api_key").is_err());
+    }
+}
@@ -0,0 +1,255 @@
+//! Spanning one wallpaper across all monitors
+//!
+//! The monitors are treated as one canvas covering the desktop. A static
+//! image is rendered to the canvas and cut into each monitor's part; video and
+//! shader players run one window per monitor, each showing its part of the
+//! canvas and sharing a start time so their frames line up at the edges.
+use crate::core::{AppError, AppResult};
+use crate::platform::display::{self, MonitorInfo};
+use log::warn;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Part of the canvas a monitor shows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpanRegion {
+    /// Monitor name
+    pub monitor: String,
+
+    /// Left edge on the canvas, in pixels
+    pub x: u32,
+
+    /// Top edge on the canvas, in pixels
+    pub y: u32,
+
+    /// Width in pixels
+    pub width: u32,
+
+    /// Height in pixels
+    pub height: u32,
+}
+
+/// Monitors laid out on one canvas
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpanLayout {
+    /// Canvas width in pixels
+    pub width: u32,
+
+    /// Canvas height in pixels
+    pub height: u32,
+
+    /// Part of the canvas each monitor shows
+    pub regions: Vec<SpanRegion>,
+}
+
+impl SpanLayout {
+    /// Lay out monitors on the smallest canvas holding them all
+    ///
+    /// Where monitors are positioned in logical pixels, the canvas has the
+    /// highest monitor scale, so no monitor's part is blown up; a part then
+    /// differs from its monitor's size when the scales are mixed. Returns None
+    /// without monitors.
+    pub fn of(monitors: &[MonitorInfo]) -> Option<Self> {
+        let canvas_scale = monitors
+            .iter()
+            .map(MonitorInfo::layout_scale)
+            .fold(1.0, f32::max);
+        let size = |m: &MonitorInfo| {
+            (
+                m.width as f32 / m.layout_scale(),
+                m.height as f32 / m.layout_scale(),
+            )
+        };
+        let left = monitors.iter().map(|m| m.x).min()?;
+        let top = monitors.iter().map(|m| m.y).min()?;
+        let right = monitors
+            .iter()
+            .map(|m| m.x as f32 + size(m).0)
+            .fold(f32::MIN, f32::max);
+        let bottom = monitors
+            .iter()
+            .map(|m| m.y as f32 + size(m).1)
+            .fold(f32::MIN, f32::max);
+        let pixels = |length: f32| (length * canvas_scale).round() as u32;
+
+        let regions = monitors
+            .iter()
+            .map(|monitor| SpanRegion {
+                monitor: monitor.name.clone(),
+                x: pixels((monitor.x - left) as f32),
+                y: pixels((monitor.y - top) as f32),
+                width: pixels(size(monitor).0),
+                height: pixels(size(monitor).1),
+            })
+            .collect();
+        Some(Self {
+            width: pixels(right - left as f32).max(1),
+            height: pixels(bottom - top as f32).max(1),
+            regions,
+        })
+    }
+
+    /// Lay out the connected monitors, None when there is only one to span
+    pub fn detect() -> Option<Self> {
+        match display::monitors() {
+            Ok(monitors) if monitors.len() > 1 => Self::of(&monitors),
+            Ok(_) => None,
+            Err(e) => {
+                warn!(
+                    "Failed to detect monitors ({}); not spanning the wallpaper",
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    /// Get the part of the canvas a monitor shows
+    pub fn region(&self, monitor: &str) -> Option<&SpanRegion> {
+        self.regions.iter().find(|region| region.monitor == monitor)
+    }
+}
+
+/// Find where a connected monitor is on the canvas holding them all
+pub fn monitor_region(monitor: &str) -> AppResult<SpanRegion> {
+    SpanLayout::of(&display::monitors()?)
+        .and_then(|layout| layout.region(monitor).cloned())
+        .ok_or_else(|| AppError::WallpaperError(format!("Monitor {} is not connected", monitor)))
+}
+
+/// Get the time spanned players count from, in milliseconds since the Unix epoch
+///
+/// Taken once before the players start, so they agree however long each takes.
+pub fn start_time() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis())
+}
+
+/// Arguments telling a shader player which part of the canvas to draw
+///
+/// The shader sees the whole canvas as its resolution, so patterns continue
+/// from one monitor to the next, and counts time from `start_time`.
+pub fn shader_args(layout: &SpanLayout, region: &SpanRegion, start_time: u128) -> Vec<String> {
+    vec![
+        format!("--output={}", region.monitor),
+        format!(
+            "--viewport={}x{}+{}+{}",
+            layout.width, layout.height, region.x, region.y
+        ),
+        format!("--start-time={}", start_time),
+    ]
+}
+
+/// MPV filters cutting a monitor's part out of a video scaled to fill the canvas
+pub fn video_crop_filter(layout: &SpanLayout, region: &SpanRegion) -> String {
+    format!(
+        "scale=w={w}:h={h}:force_original_aspect_ratio=increase,crop=w={w}:h={h},crop=w={}:h={}:x={}:y={}",
+        region.width,
+        region.height,
+        region.x,
+        region.y,
+        w = layout.width,
+        h = layout.height,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(name: &str, x: i32, y: i32, width: u32, height: u32) -> MonitorInfo {
+        let mut monitor = MonitorInfo::new(name, width, height);
+        monitor.x = x;
+        monitor.y = y;
+        monitor
+    }
+
+    #[test]
+    fn test_layout() {
+        assert_eq!(SpanLayout::of(&[]), None);
+
+        // A taller monitor left of the primary one, raised a little
+        let layout = SpanLayout::of(&[
+            monitor("DP-1", 0, 0, 1920, 1080),
+            monitor("HDMI-A-1", -1440, -200, 1440, 2560),
+        ])
+        .unwrap();
+        assert_eq!((layout.width, layout.height), (3360, 2560));
+        assert_eq!(
+            layout.regions[0],
+            SpanRegion {
+                monitor: "DP-1".to_string(),
+                x: 1440,
+                y: 200,
+                width: 1920,
+                height: 1080
+            }
+        );
+        assert_eq!(
+            layout.regions[1],
+            SpanRegion {
+                monitor: "HDMI-A-1".to_string(),
+                x: 0,
+                y: 0,
+                width: 1440,
+                height: 2560
+            }
+        );
+        assert_eq!(layout.region("DP-1"), layout.regions.first());
+        assert_eq!(layout.region("DP-2"), None);
+    }
+
+    #[test]
+    fn test_layout_of_scaled_monitors() {
+        // A 4K monitor at 150% takes 2560x1440 of a Wayland layout
+        let mut scaled = monitor("DP-1", 0, 0, 3840, 2160);
+        scaled.scale = 1.5;
+        scaled.logical_position = true;
+        let mut unscaled = monitor("HDMI-A-1", 2560, 0, 1920, 1080);
+        unscaled.logical_position = true;
+
+        let layout = SpanLayout::of(&[scaled, unscaled]).unwrap();
+        assert_eq!((layout.width, layout.height), (6720, 2160));
+        assert_eq!(
+            layout.regions[0],
+            SpanRegion {
+                monitor: "DP-1".to_string(),
+                x: 0,
+                y: 0,
+                width: 3840,
+                height: 2160
+            }
+        );
+        assert_eq!(
+            layout.regions[1],
+            SpanRegion {
+                monitor: "HDMI-A-1".to_string(),
+                x: 3840,
+                y: 0,
+                width: 2880,
+                height: 1620
+            }
+        );
+    }
+
+    #[test]
+    fn test_player_arguments() {
+        let layout = SpanLayout::of(&[
+            monitor("DP-1", 0, 0, 1920, 1080),
+            monitor("DP-2", 1920, 0, 1920, 1080),
+        ])
+        .unwrap();
+        assert_eq!(
+            shader_args(&layout, &layout.regions[1], 1_700_000_000_000),
+            vec![
+                "--output=DP-2",
+                "--viewport=3840x1080+1920+0",
+                "--start-time=1700000000000"
+            ]
+        );
+        assert_eq!(
+            video_crop_filter(&layout, &layout.regions[1]),
+            "scale=w=3840:h=1080:force_original_aspect_ratio=increase,crop=w=3840:h=1080,crop=w=1920:h=1080:x=1920:y=0"
+        );
+    }
+}
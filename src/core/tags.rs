@@ -0,0 +1,206 @@
+//! Tags of the wallpapers in the library
+//!
+//! The auto-tagging pass labels images ("nature", "dark", ...) and keeps the
+//! image embedding the labels were picked from, which also finds wallpapers
+//! that look alike. Tags are kept in a file shared by every process, which
+//! is read again before each write so processes don't drop each other's tags.
+use crate::core::Config;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Tags of one wallpaper
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TagEntry {
+    /// Labels, most likely first
+    pub tags: Vec<String>,
+
+    /// Image embedding the labels were picked from
+    #[serde(default)]
+    pub embedding: Vec<f32>,
+}
+
+/// Tags of the wallpapers, by path
+#[derive(Debug, Default)]
+pub struct TagStore {
+    /// File the tags are kept in (None = memory only)
+    path: Option<PathBuf>,
+
+    /// Tags by wallpaper path
+    entries: Mutex<BTreeMap<PathBuf, TagEntry>>,
+}
+
+#[allow(dead_code)]
+impl TagStore {
+    /// Create a store kept in memory only
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open the store kept in a file
+    pub fn open(path: PathBuf) -> Self {
+        let entries = read(&path);
+        Self {
+            path: Some(path),
+            entries: Mutex::new(entries),
+        }
+    }
+
+    /// Get the process-wide store
+    pub fn shared() -> Arc<TagStore> {
+        static SHARED: OnceLock<Arc<TagStore>> = OnceLock::new();
+        SHARED
+            .get_or_init(|| match Config::get_config_dir() {
+                Ok(dir) => Arc::new(TagStore::open(dir.join("tags.json"))),
+                Err(_) => Arc::new(TagStore::new()),
+            })
+            .clone()
+    }
+
+    /// Check whether a wallpaper has been tagged
+    pub fn is_tagged(&self, path: &Path) -> bool {
+        self.entries.lock().unwrap().contains_key(path)
+    }
+
+    /// Get the tags of a wallpaper
+    pub fn tags(&self, path: &Path) -> Vec<String> {
+        self.entries
+            .lock()
+            .unwrap()
+            .get(path)
+            .map(|entry| entry.tags.clone())
+            .unwrap_or_default()
+    }
+
+    /// Record the tags of a wallpaper
+    pub fn set(&self, path: &Path, entry: TagEntry) {
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(file) = &self.path {
+            *entries = read(file);
+        }
+        entries.insert(path.to_path_buf(), entry);
+        if let Some(file) = &self.path {
+            let result = serde_json::to_string(&*entries)
+                .map_err(|e| e.to_string())
+                .and_then(|json| fs::write(file, json).map_err(|e| e.to_string()));
+            if let Err(e) = result {
+                warn!("Failed to save tags to {}: {}", file.display(), e);
+            }
+        }
+    }
+
+    /// Check whether a wallpaper has a tag starting with each word of a query
+    pub fn matches(&self, path: &Path, query: &str) -> bool {
+        let tags = self.tags(path);
+        query
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .all(|word| tags.iter().any(|tag| tag.starts_with(&word)))
+    }
+
+    /// Rank wallpapers by how much they look like the one at `path`, most alike first
+    ///
+    /// Only wallpapers in `candidates` with an embedding are ranked.
+    pub fn similar(&self, path: &Path, candidates: &[PathBuf]) -> Vec<(PathBuf, f32)> {
+        let entries = self.entries.lock().unwrap();
+        let Some(target) = entries
+            .get(path)
+            .filter(|entry| !entry.embedding.is_empty())
+        else {
+            return Vec::new();
+        };
+        let mut ranked: Vec<(PathBuf, f32)> = candidates
+            .iter()
+            .filter(|candidate| candidate.as_path() != path)
+            .filter_map(|candidate| {
+                let entry = entries.get(candidate)?;
+                (entry.embedding.len() == target.embedding.len()).then(|| {
+                    (
+                        candidate.clone(),
+                        cosine(&target.embedding, &entry.embedding),
+                    )
+                })
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+        ranked
+    }
+}
+
+/// Read the tags kept in a file
+fn read(path: &Path) -> BTreeMap<PathBuf, TagEntry> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Cosine similarity of two vectors of the same length
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norms = norm(a) * norm(b);
+    if norms > 0.0 {
+        dot / norms
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(tags: &[&str], embedding: &[f32]) -> TagEntry {
+        TagEntry {
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            embedding: embedding.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_matches_tag_prefixes() {
+        let store = TagStore::new();
+        store.set(Path::new("forest.png"), entry(&["nature", "dark"], &[]));
+
+        assert!(store.matches(Path::new("forest.png"), "Nat"));
+        assert!(store.matches(Path::new("forest.png"), "dark nature"));
+        assert!(!store.matches(Path::new("forest.png"), "city"));
+        assert!(!store.matches(Path::new("untagged.png"), "nature"));
+    }
+
+    #[test]
+    fn test_similar_ranks_by_embedding() {
+        let store = TagStore::new();
+        store.set(Path::new("a.png"), entry(&[], &[1.0, 0.0]));
+        store.set(Path::new("b.png"), entry(&[], &[0.0, 1.0]));
+        store.set(Path::new("c.png"), entry(&[], &[0.9, 0.1]));
+        let candidates: Vec<PathBuf> = ["a.png", "b.png", "c.png", "d.png"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+
+        let ranked: Vec<PathBuf> = store
+            .similar(Path::new("a.png"), &candidates)
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        assert_eq!(ranked, [PathBuf::from("c.png"), PathBuf::from("b.png")]);
+    }
+
+    #[test]
+    fn test_tags_survive_other_writers() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("tags.json");
+        let first = TagStore::open(file.clone());
+        let second = TagStore::open(file.clone());
+
+        first.set(Path::new("a.png"), entry(&["city"], &[]));
+        second.set(Path::new("b.png"), entry(&["minimal"], &[]));
+
+        assert_eq!(TagStore::open(file).tags(Path::new("a.png")), ["city"]);
+    }
+}
@@ -0,0 +1,210 @@
+//! Named custom themes
+//!
+//! Themes are stored as one JSON file per theme so they can be shared by
+//! copying the file.
+use crate::core::config::ThemeConfig;
+use crate::core::{AppError, AppResult};
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Custom theme colors as `#rrggbb` strings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomTheme {
+    /// Theme name
+    pub name: String,
+
+    /// Window background
+    pub background: String,
+
+    /// Panels and windows
+    pub panel: String,
+
+    /// Text
+    pub text: String,
+
+    /// Accent for headings, selections and links
+    pub accent: String,
+
+    /// Buttons and other widget chrome
+    pub widget: String,
+}
+
+impl Default for CustomTheme {
+    fn default() -> Self {
+        Self {
+            name: "Custom".to_string(),
+            background: "#202225".to_string(),
+            panel: "#2b2d31".to_string(),
+            text: "#dcddde".to_string(),
+            accent: "#00bcd4".to_string(),
+            widget: "#3c3f45".to_string(),
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl CustomTheme {
+    /// Get the custom colors from the theme configuration
+    ///
+    /// Colors that aren't set use the defaults.
+    pub fn from_config(config: &ThemeConfig) -> Self {
+        let defaults = Self::default();
+        Self {
+            name: defaults.name,
+            background: config
+                .background_color
+                .clone()
+                .unwrap_or(defaults.background),
+            panel: config.panel_color.clone().unwrap_or(defaults.panel),
+            text: config.text_color.clone().unwrap_or(defaults.text),
+            accent: config.accent_color.clone().unwrap_or(defaults.accent),
+            widget: config.widget_color.clone().unwrap_or(defaults.widget),
+        }
+    }
+
+    /// Store the colors in the theme configuration
+    pub fn apply_to(&self, config: &mut ThemeConfig) {
+        config.background_color = Some(self.background.clone());
+        config.panel_color = Some(self.panel.clone());
+        config.text_color = Some(self.text.clone());
+        config.accent_color = Some(self.accent.clone());
+        config.widget_color = Some(self.widget.clone());
+    }
+
+    /// Load a theme from a JSON file
+    pub fn load(path: &Path) -> AppResult<Self> {
+        let content = fs::read_to_string(path)?;
+        let theme: Self = serde_json::from_str(&content).map_err(|e| {
+            AppError::ConfigError(format!("Invalid theme file {}: {}", path.display(), e))
+        })?;
+        debug!("Loaded theme '{}' from {}", theme.name, path.display());
+        Ok(theme)
+    }
+
+    /// Write the theme to a JSON file
+    pub fn export(&self, path: &Path) -> AppResult<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        info!("Exported theme '{}' to {}", self.name, path.display());
+        Ok(())
+    }
+}
+
+/// Get the file a named theme is saved to
+pub fn theme_path(dir: &Path, name: &str) -> PathBuf {
+    let file_name: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    dir.join(format!("{}.json", file_name))
+}
+
+/// Save a theme to the themes directory, replacing one with the same name
+#[allow(dead_code)]
+pub fn save(dir: &Path, theme: &CustomTheme) -> AppResult<PathBuf> {
+    if theme.name.trim().is_empty() {
+        return Err(AppError::ConfigError("Theme name is empty".to_string()));
+    }
+
+    fs::create_dir_all(dir)?;
+    let path = theme_path(dir, &theme.name);
+    theme.export(&path)?;
+    Ok(path)
+}
+
+/// Delete a saved theme
+#[allow(dead_code)]
+pub fn delete(dir: &Path, name: &str) -> AppResult<()> {
+    fs::remove_file(theme_path(dir, name))?;
+    info!("Deleted theme '{}'", name);
+    Ok(())
+}
+
+/// List the saved themes, sorted by name
+///
+/// Files that can't be parsed are skipped.
+#[allow(dead_code)]
+pub fn list(dir: &Path) -> AppResult<Vec<CustomTheme>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut themes: Vec<CustomTheme> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().map_or(false, |ext| ext == "json"))
+        .filter_map(|path| {
+            CustomTheme::load(&path)
+                .map_err(|e| debug!("Skipping theme: {}", e))
+                .ok()
+        })
+        .collect();
+
+    themes.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    Ok(themes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_save_list_and_delete_themes() {
+        let dir = tempdir().unwrap();
+
+        let nord = CustomTheme {
+            name: "Nord Night".to_string(),
+            accent: "#88c0d0".to_string(),
+            ..Default::default()
+        };
+        let amber = CustomTheme {
+            name: "amber".to_string(),
+            ..Default::default()
+        };
+        let path = save(dir.path(), &nord).unwrap();
+        save(dir.path(), &amber).unwrap();
+        assert_eq!(path, dir.path().join("nord_night.json"));
+
+        // Unrelated and broken files are ignored
+        fs::write(dir.path().join("notes.txt"), "hello").unwrap();
+        fs::write(dir.path().join("broken.json"), "{").unwrap();
+
+        let themes = list(dir.path()).unwrap();
+        assert_eq!(themes, vec![amber, nord.clone()]);
+
+        delete(dir.path(), "Nord Night").unwrap();
+        assert_eq!(list(dir.path()).unwrap().len(), 1);
+
+        assert!(save(
+            dir.path(),
+            &CustomTheme {
+                name: " ".to_string(),
+                ..Default::default()
+            }
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_config_round_trip() {
+        let mut config = ThemeConfig::default();
+        assert_eq!(CustomTheme::from_config(&config), CustomTheme::default());
+
+        let theme = CustomTheme {
+            text: "#ffffff".to_string(),
+            ..Default::default()
+        };
+        theme.apply_to(&mut config);
+        assert_eq!(config.text_color.as_deref(), Some("#ffffff"));
+        assert_eq!(CustomTheme::from_config(&config), theme);
+    }
+}
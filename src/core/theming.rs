@@ -0,0 +1,298 @@
+//! System theming from the wallpaper's palette
+//!
+//! After a static wallpaper is applied its palette recolors terminals, bars
+//! and GTK themes. Aether-Desk can fill in pywal-style templates itself
+//! (`{color0}` to `{color15}`, `{background}`, `{foreground}`, `{cursor}`,
+//! `{wallpaper}` and `{alpha}`, with `.strip`, `.rgb`, `.rgba` and `.xrgba`
+//! variants and `{{`/`}}` for plain braces), each written into the palette
+//! folder under its own name as pywal does, or hand the image to pywal or
+//! wallust and let them do the same with their own templates.
+//!
+//! What would happen is worked out first as a [`ThemingPlan`], which the
+//! settings show as a dry run without writing or running anything.
+use crate::core::config::{PaletteConfig, ThemingTool};
+use crate::core::palette::{self, Rgb};
+use crate::core::{AppError, AppResult};
+use log::{debug, info, warn};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+impl PaletteConfig {
+    /// Get the folder of the templates filled in (pywal's templates folder by default)
+    pub fn templates_dir(&self) -> PathBuf {
+        match &self.templates_dir {
+            Some(dir) => PathBuf::from(dir),
+            None => dirs::config_dir()
+                .unwrap_or_else(std::env::temp_dir)
+                .join("wal")
+                .join("templates"),
+        }
+    }
+}
+
+/// What recoloring the system for a wallpaper writes and runs
+#[derive(Debug, Clone)]
+pub struct ThemingPlan {
+    /// Terminal colors of the wallpaper
+    ///
+    /// With pywal or wallust these are Aether-Desk's colors, close to but
+    /// not exactly the ones the tool picks.
+    pub colors: [Rgb; 16],
+
+    /// Files written, with their contents
+    pub files: Vec<(PathBuf, String)>,
+
+    /// Tool run afterwards, with its arguments
+    pub command: Option<Vec<String>>,
+}
+
+/// Work out what recoloring the system for `wallpaper` writes and runs, without doing it
+pub fn plan(wallpaper: &Path, config: &PaletteConfig) -> AppResult<ThemingPlan> {
+    let colors = palette::wallpaper_palette(wallpaper)?;
+    let output = config.output_dir();
+
+    // pywal writes the same palette files to its cache itself
+    let mut files = Vec::new();
+    if config.theming != ThemingTool::Pywal {
+        for (name, contents) in palette::files(&colors, wallpaper)? {
+            files.push((output.join(name), contents));
+        }
+    }
+    if config.theming == ThemingTool::Templates {
+        for (name, template) in read_templates(&config.templates_dir()) {
+            files.push((
+                output.join(name),
+                fill_template(&template, &colors, wallpaper),
+            ));
+        }
+    }
+
+    let image = wallpaper.to_string_lossy().to_string();
+    let command = match config.theming {
+        ThemingTool::Pywal => Some(vec![
+            "wal".to_string(),
+            "-n".to_string(),
+            "-q".to_string(),
+            "-i".to_string(),
+            image,
+        ]),
+        ThemingTool::Wallust => Some(vec![
+            "wallust".to_string(),
+            "run".to_string(),
+            "-q".to_string(),
+            image,
+        ]),
+        ThemingTool::None | ThemingTool::Templates => None,
+    };
+
+    Ok(ThemingPlan {
+        colors,
+        files,
+        command,
+    })
+}
+
+/// Write the files of a plan and run its tool
+pub fn apply(plan: &ThemingPlan) -> AppResult<()> {
+    for (path, contents) in &plan.files {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, contents)?;
+    }
+    debug!("Wrote {} theming files", plan.files.len());
+
+    if let Some((program, args)) = plan
+        .command
+        .as_ref()
+        .and_then(|command| command.split_first())
+    {
+        info!("Running {} to recolor the system", program);
+        let status = Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map_err(|e| AppError::Other(format!("Failed to run {}: {}", program, e)))?;
+        if !status.success() {
+            return Err(AppError::Other(format!(
+                "{} exited with {}",
+                program, status
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Read the templates of a folder, by file name
+///
+/// Subfolders and files that aren't text are skipped, and a missing folder has no templates.
+fn read_templates(dir: &Path) -> Vec<(String, String)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        debug!("No templates folder at {}", dir.display());
+        return Vec::new();
+    };
+    let mut templates: Vec<(String, String)> = entries
+        .flatten()
+        .filter(|entry| entry.path().is_file())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            match fs::read_to_string(entry.path()) {
+                Ok(template) => Some((name, template)),
+                Err(e) => {
+                    warn!("Skipping template {}: {}", entry.path().display(), e);
+                    None
+                }
+            }
+        })
+        .collect();
+    templates.sort();
+    templates
+}
+
+/// Fill in the placeholders of a pywal-style template
+///
+/// Unknown placeholders are left as they are.
+pub fn fill_template(template: &str, colors: &[Rgb; 16], wallpaper: &Path) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find(['{', '}']) {
+        filled.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if rest.starts_with("{{") || rest.starts_with("}}") {
+            filled.push_str(&rest[..1]);
+            rest = &rest[2..];
+            continue;
+        }
+        let value = rest
+            .strip_prefix('{')
+            .and_then(|inner| inner.find('}').map(|end| &inner[..end]))
+            .and_then(|name| Some((name.len(), placeholder(name, colors, wallpaper)?)));
+        match value {
+            Some((length, value)) => {
+                filled.push_str(&value);
+                rest = &rest[length + 2..];
+            }
+            None => {
+                filled.push_str(&rest[..1]);
+                rest = &rest[1..];
+            }
+        }
+    }
+    filled.push_str(rest);
+    filled
+}
+
+/// Get the value of a placeholder, or None when it's unknown
+fn placeholder(name: &str, colors: &[Rgb; 16], wallpaper: &Path) -> Option<String> {
+    match name {
+        "wallpaper" => return Some(wallpaper.to_string_lossy().to_string()),
+        "alpha" => return Some("100".to_string()),
+        _ => {}
+    }
+
+    let (color, format) = name.split_once('.').unwrap_or((name, ""));
+    let color = match color {
+        "background" => colors[0],
+        "foreground" | "cursor" => colors[15],
+        _ => {
+            colors[color
+                .strip_prefix("color")?
+                .parse::<usize>()
+                .ok()
+                .filter(|index| *index < 16)?]
+        }
+    };
+    let [r, g, b] = color;
+    match format {
+        "" => Some(palette::hex(color)),
+        "strip" => Some(palette::hex(color)[1..].to_string()),
+        "rgb" => Some(format!("{},{},{}", r, g, b)),
+        "rgba" => Some(format!("{},{},{},1.0", r, g, b)),
+        "xrgba" => Some(format!("{:02x}/{:02x}/{:02x}/ff", r, g, b)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb as Pixel, RgbImage};
+
+    fn colors() -> [Rgb; 16] {
+        let mut colors = [[0u8; 3]; 16];
+        for (index, color) in colors.iter_mut().enumerate() {
+            *color = [index as u8 * 16, 0, 255];
+        }
+        colors
+    }
+
+    #[test]
+    fn test_fill_template() {
+        let wallpaper = Path::new("/walls/sea.png");
+        let filled = fill_template(
+            "bg={background} c1={color1.strip} {color2.rgb} {color15.xrgba} {wallpaper} {{literal}} {unknown} {color16}",
+            &colors(),
+            wallpaper,
+        );
+        assert_eq!(filled, "bg=#0000ff c1=1000ff 32,0,255 f0/00/ff/ff /walls/sea.png {literal} {unknown} {color16}");
+
+        // CSS braces without placeholders stay, and an unclosed brace doesn't end the template
+        assert_eq!(
+            fill_template("a { color: {color3}; }", &colors(), wallpaper),
+            "a { color: #3000ff; }"
+        );
+        assert_eq!(fill_template("{color1", &colors(), wallpaper), "{color1");
+    }
+
+    #[test]
+    fn test_plan_is_a_dry_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let wallpaper = dir.path().join("wall.png");
+        RgbImage::from_pixel(8, 8, Pixel([30, 60, 200]))
+            .save(&wallpaper)
+            .unwrap();
+        let templates = dir.path().join("templates");
+        fs::create_dir_all(&templates).unwrap();
+        fs::write(
+            templates.join("colors-kitty.conf"),
+            "background {background}\n",
+        )
+        .unwrap();
+
+        let output = dir.path().join("wal");
+        let mut config = PaletteConfig {
+            enabled: true,
+            output_dir: Some(output.to_string_lossy().to_string()),
+            theming: ThemingTool::Templates,
+            templates_dir: Some(templates.to_string_lossy().to_string()),
+        };
+        let planned = plan(&wallpaper, &config).unwrap();
+        let kitty = planned
+            .files
+            .iter()
+            .find(|(path, _)| path.ends_with("colors-kitty.conf"))
+            .unwrap();
+        assert_eq!(
+            kitty.1,
+            format!("background {}\n", palette::hex(planned.colors[0]))
+        );
+        assert!(planned
+            .files
+            .iter()
+            .any(|(path, _)| path.ends_with("colors.json")));
+        assert!(planned.command.is_none());
+        assert!(!output.exists());
+
+        apply(&planned).unwrap();
+        assert!(output.join("colors-kitty.conf").exists());
+
+        // pywal writes its own files, so only the command is planned
+        config.theming = ThemingTool::Pywal;
+        let planned = plan(&wallpaper, &config).unwrap();
+        assert!(planned.files.is_empty());
+        assert_eq!(planned.command.unwrap()[0], "wal");
+    }
+}
@@ -1,9 +1,9 @@
+use crate::core::config::{ProceduralPattern, WallpaperConfig, WallpaperType};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
-use crate::core::config::WallpaperType;
 
 /// Wallpaper information for scheduler
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct WallpaperInfo {
     /// Wallpaper name
     pub name: String,
@@ -17,8 +17,107 @@ pub struct WallpaperInfo {
     pub r#type: WallpaperType,
     /// Wallpaper file path (for local files)
     pub path: Option<PathBuf>,
-    /// Wallpaper URL (for web wallpapers)
+    /// Wallpaper URL (for web wallpapers), or the pattern of procedural ones
     pub url: Option<String>,
+    /// ID of the monitor the wallpaper is shown on, every monitor when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monitor: Option<String>,
+}
+
+impl WallpaperInfo {
+    /// Describe a local file or web wallpaper chosen by the user
+    pub fn new(wallpaper_type: WallpaperType, path: Option<PathBuf>, url: Option<String>) -> Self {
+        let name = path
+            .as_ref()
+            .and_then(|p| p.file_stem())
+            .map(|stem| stem.to_string_lossy().to_string())
+            .or_else(|| url.clone())
+            .unwrap_or_else(|| {
+                if wallpaper_type.is_builtin() {
+                    wallpaper_type.as_str().to_string()
+                } else {
+                    String::new()
+                }
+            });
+
+        Self {
+            name,
+            description: format!("{} wallpaper", wallpaper_type.as_str()),
+            author: "Unknown".to_string(),
+            version: "1.0.0".to_string(),
+            r#type: wallpaper_type,
+            path,
+            url,
+            monitor: None,
+        }
+    }
+
+    /// Describe a procedural wallpaper drawing `pattern`
+    pub fn procedural(pattern: ProceduralPattern) -> Self {
+        let mut wallpaper = Self::new(
+            WallpaperType::Procedural,
+            None,
+            Some(pattern.id().to_string()),
+        );
+        wallpaper.name = pattern.name().to_string();
+        wallpaper
+    }
+
+    /// Show the wallpaper on one monitor only, or on all of them with None
+    pub fn on_monitor(mut self, monitor: Option<String>) -> Self {
+        self.monitor = monitor;
+        self
+    }
+
+    /// Get the wallpaper saved in the configuration, if any
+    pub fn saved(config: &WallpaperConfig) -> Option<Self> {
+        let current = config.current_path.clone()?;
+        // Procedural wallpapers are saved by their pattern, particles by their name
+        let (path, url) = match config.wallpaper_type {
+            WallpaperType::Web | WallpaperType::Procedural => (None, Some(current.clone())),
+            WallpaperType::Particles | WallpaperType::NowPlaying => (None, None),
+            _ => (Some(PathBuf::from(&current)), None),
+        };
+        Some(Self {
+            name: current,
+            description: "Restored wallpaper".to_string(),
+            author: "Unknown".to_string(),
+            version: "1.0.0".to_string(),
+            r#type: config.wallpaper_type.clone(),
+            path,
+            url,
+            monitor: None,
+        })
+    }
+
+    /// Save this wallpaper as the current one in the configuration
+    ///
+    /// A wallpaper for one monitor is saved for that monitor; one for every
+    /// monitor replaces those. While mirroring, every wallpaper is saved as
+    /// the one for every monitor and the monitors' own are kept for later.
+    pub fn remember(&self, config: &mut WallpaperConfig) {
+        let mirror = config.placement.mirror;
+        if let Some(monitor) = self.monitor.as_ref().filter(|_| !mirror) {
+            config
+                .monitor_wallpapers
+                .insert(monitor.clone(), self.clone());
+            return;
+        }
+        if !mirror {
+            config.monitor_wallpapers.clear();
+        }
+        config.wallpaper_type = self.r#type.clone();
+        config.current_path = self
+            .path
+            .as_ref()
+            .map(|p| p.to_string_lossy().to_string())
+            .or_else(|| self.url.clone())
+            // Built-in wallpapers have no source, so their name marks them as set
+            .or_else(|| self.r#type.is_builtin().then(|| self.name.clone()));
+        if self.r#type == WallpaperType::Static {
+            config.last_static = config.current_path.clone();
+        }
+    }
 }
 
 /// Wallpaper metadata
@@ -26,19 +125,19 @@ pub struct WallpaperInfo {
 pub struct WallpaperMetadata {
     /// Wallpaper name
     pub name: String,
-    
+
     /// Wallpaper description
     pub description: Option<String>,
-    
+
     /// Wallpaper author
     pub author: Option<String>,
-    
+
     /// Wallpaper tags
     pub tags: Vec<String>,
-    
+
     /// Wallpaper path
     pub path: PathBuf,
-    
+
     /// Wallpaper type
     pub wallpaper_type: crate::core::config::WallpaperType,
 }
@@ -48,10 +147,10 @@ pub struct WallpaperMetadata {
 pub struct WallpaperCollection {
     /// Collection name
     pub name: String,
-    
+
     /// Collection description
     pub description: Option<String>,
-    
+
     /// Collection wallpapers
     pub wallpapers: Vec<WallpaperMetadata>,
 }
@@ -61,19 +160,19 @@ pub struct WallpaperCollection {
 pub struct PluginMetadata {
     /// Plugin name
     pub name: String,
-    
+
     /// Plugin version
     pub version: String,
-    
+
     /// Plugin description
     pub description: Option<String>,
-    
+
     /// Plugin author
     pub author: Option<String>,
-    
+
     /// Plugin entry point
     pub entry_point: String,
-    
+
     /// Plugin dependencies
     pub dependencies: Vec<String>,
-} 
\ No newline at end of file
+}
@@ -0,0 +1,387 @@
+//! Checking wallpaper files before they're applied
+//!
+//! A missing or broken file would otherwise replace the current wallpaper
+//! with a blank desktop or a player that quits right away. Files must exist
+//! and be readable; images must have a header the decoders understand, videos
+//! a video stream ffprobe (or mpv, when ffprobe isn't installed) can find,
+//! and shaders balanced brackets and a `main` or `mainImage`. Shaders are
+//! also compiled with `glslangValidator` when it's installed. Missing probe
+//! tools and probes that take too long let the file through.
+use crate::core::{image_format, AppError, AppResult, WallpaperInfo, WallpaperType};
+use image::io::Reader;
+use log::debug;
+use std::fs::{self, File};
+use std::io::{self, ErrorKind};
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Longest a probe may take before the file is let through unchecked
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Uniforms Shadertoy gives its shaders, declared for compiling them on their own
+const SHADERTOY_UNIFORMS: &str = "uniform vec3 iResolution;
+uniform float iTime;
+uniform float iTimeDelta;
+uniform float iFrameRate;
+uniform int iFrame;
+uniform float iChannelTime[4];
+uniform vec3 iChannelResolution[4];
+uniform vec4 iMouse;
+uniform vec4 iDate;
+uniform float iSampleRate;
+uniform sampler2D iChannel0;
+uniform sampler2D iChannel1;
+uniform sampler2D iChannel2;
+uniform sampler2D iChannel3;
+";
+
+/// Check that a wallpaper's file can be shown
+///
+/// Wallpapers without a file, like web and built-in ones, always pass.
+pub fn check(wallpaper: &WallpaperInfo) -> AppResult<()> {
+    let Some(path) = wallpaper.path.as_deref() else {
+        return Ok(());
+    };
+    let result = match wallpaper.r#type {
+        WallpaperType::Slideshow => check_folder(path),
+        WallpaperType::Static | WallpaperType::AnimatedStatic | WallpaperType::AnimatedImage => {
+            check_readable(path).and_then(|()| check_image(path))
+        }
+        WallpaperType::Video => check_readable(path).and_then(|()| check_video(path)),
+        WallpaperType::Shader | WallpaperType::Audio => {
+            check_readable(path).and_then(|()| check_shader(path))
+        }
+        WallpaperType::Web
+        | WallpaperType::Particles
+        | WallpaperType::Procedural
+        | WallpaperType::NowPlaying => Ok(()),
+    };
+    result.map_err(|e| e.context(format!("{} can't be shown", path.display())))
+}
+
+/// Check that a folder of images exists
+fn check_folder(path: &Path) -> AppResult<()> {
+    match fs::metadata(path) {
+        Ok(metadata) if metadata.is_dir() => Ok(()),
+        Ok(_) => Err(AppError::WallpaperError("it isn't a folder".to_string())),
+        Err(e) => Err(missing(e)),
+    }
+}
+
+/// Check that a file exists and can be opened
+fn check_readable(path: &Path) -> AppResult<()> {
+    let metadata = fs::metadata(path).map_err(missing)?;
+    if !metadata.is_file() {
+        return Err(AppError::WallpaperError("it isn't a file".to_string()));
+    }
+    if metadata.len() == 0 {
+        return Err(AppError::WallpaperError("the file is empty".to_string()));
+    }
+    File::open(path).map_err(missing)?;
+    Ok(())
+}
+
+/// Describe why a file couldn't be read
+fn missing(error: io::Error) -> AppError {
+    match error.kind() {
+        ErrorKind::NotFound => AppError::WallpaperError("the file doesn't exist".to_string()),
+        ErrorKind::PermissionDenied => {
+            AppError::WallpaperError("the file can't be read; check its permissions".to_string())
+        }
+        _ => AppError::IoError(error),
+    }
+}
+
+/// Check that an image's header can be read by a decoder of this build
+fn check_image(path: &Path) -> AppResult<()> {
+    image_format::check_supported(path)?;
+    // libheif and jxl-oxide only look at a file by decoding all of it
+    if image_format::ExtraFormat::of(path)
+        .is_some_and(|format| format != image_format::ExtraFormat::Avif)
+    {
+        return Ok(());
+    }
+    Reader::open(path)?
+        .with_guessed_format()?
+        .into_dimensions()
+        .map(|_| ())
+        .map_err(|e| AppError::WallpaperError(format!("it isn't an image that can be read: {}", e)))
+}
+
+/// Check that a video has a video stream, with ffprobe or else mpv
+fn check_video(path: &Path) -> AppResult<()> {
+    let mut ffprobe = Command::new("ffprobe");
+    ffprobe
+        .args([
+            "-v",
+            "error",
+            "-select_streams",
+            "v:0",
+            "-show_entries",
+            "stream=codec_name",
+            "-of",
+            "csv=p=0",
+        ])
+        .arg(path);
+    match run_probe(&mut ffprobe) {
+        Ok(Some(output))
+            if output.status.success()
+                && !String::from_utf8_lossy(&output.stdout).trim().is_empty() =>
+        {
+            return Ok(())
+        }
+        Ok(Some(output)) => {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(AppError::WallpaperError(match error.trim() {
+                "" => "ffprobe found no video in it".to_string(),
+                error => format!("ffprobe can't read it: {}", error),
+            }));
+        }
+        Ok(None) => return Ok(()),
+        Err(e) if e.kind() != ErrorKind::NotFound => {
+            return Err(AppError::from_spawn("ffprobe", e))
+        }
+        Err(_) => debug!(
+            "ffprobe isn't installed; probing {} with mpv",
+            path.display()
+        ),
+    }
+
+    let mut mpv = Command::new("mpv");
+    mpv.args([
+        "--no-config",
+        "--really-quiet",
+        "--vo=null",
+        "--ao=null",
+        "--frames=1",
+    ])
+    .arg(path);
+    match run_probe(&mut mpv) {
+        Ok(Some(output)) if !output.status.success() => {
+            Err(AppError::WallpaperError("mpv can't play it".to_string()))
+        }
+        Ok(_) => Ok(()),
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(AppError::from_spawn("mpv", e)),
+        Err(_) => {
+            debug!(
+                "Neither ffprobe nor mpv is installed; {} isn't probed",
+                path.display()
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Check a fragment shader's structure, and compile it when glslangValidator is installed
+fn check_shader(path: &Path) -> AppResult<()> {
+    let source = fs::read_to_string(path)
+        .map_err(|_| AppError::WallpaperError("it isn't a text file".to_string()))?;
+    check_shader_source(&source)?;
+
+    let file = std::env::temp_dir().join(format!("aether-desk-check-{}.frag", std::process::id()));
+    fs::write(&file, compilable_shader(&source))?;
+    let result = run_probe(Command::new("glslangValidator").arg(&file));
+    let _ = fs::remove_file(&file);
+    match result {
+        Ok(Some(output)) if !output.status.success() => {
+            let log = String::from_utf8_lossy(&output.stdout);
+            let errors: Vec<&str> = log
+                .lines()
+                .filter(|line| {
+                    line.starts_with("ERROR:") && !line.contains("compilation terminated")
+                })
+                .collect();
+            Err(AppError::WallpaperError(format!(
+                "the shader doesn't compile:\n{}",
+                errors.join("\n")
+            )))
+        }
+        Ok(_) => Ok(()),
+        Err(e) if e.kind() != ErrorKind::NotFound => {
+            Err(AppError::from_spawn("glslangValidator", e))
+        }
+        Err(_) => {
+            debug!(
+                "glslangValidator isn't installed; {} isn't compiled",
+                path.display()
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Check that a shader has an entry point and balanced brackets, outside its comments
+fn check_shader_source(source: &str) -> AppResult<()> {
+    let code = strip_comments(source);
+    if !code.contains("main") {
+        return Err(AppError::WallpaperError(
+            "the shader has no main or mainImage function".to_string(),
+        ));
+    }
+    for (open, close) in [('{', '}'), ('(', ')'), ('[', ']')] {
+        let mut depth = 0i32;
+        for (number, line) in code.lines().enumerate() {
+            for c in line.chars() {
+                if c == open {
+                    depth += 1;
+                } else if c == close {
+                    depth -= 1;
+                }
+                if depth < 0 {
+                    return Err(AppError::WallpaperError(format!(
+                        "the shader has an unmatched '{}' on line {}",
+                        close,
+                        number + 1
+                    )));
+                }
+            }
+        }
+        if depth > 0 {
+            return Err(AppError::WallpaperError(format!(
+                "the shader has an unclosed '{}'",
+                open
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Remove the comments of GLSL source, keeping its lines where they were
+fn strip_comments(source: &str) -> String {
+    let mut code = String::with_capacity(source.len());
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('/', Some('/')) => {
+                while chars.peek().is_some_and(|&next| next != '\n') {
+                    chars.next();
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = ' ';
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        code.push('\n');
+                    }
+                    if previous == '*' && next == '/' {
+                        break;
+                    }
+                    previous = next;
+                }
+            }
+            _ => code.push(c),
+        }
+    }
+    code
+}
+
+/// Wrap a shader so it compiles on its own, keeping its line numbers
+///
+/// Shadertoy shaders get its uniforms and a `main`; others without a
+/// `#version` are taken as GLSL 1.10, as players without one assume.
+fn compilable_shader(source: &str) -> String {
+    if source.contains("mainImage") {
+        let body: String = source
+            .lines()
+            .map(|line| {
+                if line.trim_start().starts_with("#version") {
+                    ""
+                } else {
+                    line
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        format!(
+            "#version 330\nout vec4 aether_FragColor;\n{}#line 1\n{}\nvoid main() {{\n    mainImage(aether_FragColor, gl_FragCoord.xy);\n}}\n",
+            SHADERTOY_UNIFORMS, body
+        )
+    } else if source.trim_start().starts_with("#version") {
+        source.to_string()
+    } else {
+        format!("#version 110\n#line 1\n{}", source)
+    }
+}
+
+/// Run a probe with its output captured
+///
+/// Returns None when it took longer than `PROBE_TIMEOUT` and was stopped.
+fn run_probe(command: &mut Command) -> io::Result<Option<Output>> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let started = Instant::now();
+    while child.try_wait()?.is_none() {
+        if started.elapsed() > PROBE_TIMEOUT {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(None);
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    child.wait_with_output().map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_check_files() {
+        let dir = tempdir().unwrap();
+        let wallpaper = |wallpaper_type, name: &str| {
+            WallpaperInfo::new(wallpaper_type, Some(dir.path().join(name)), None)
+        };
+
+        let image = dir.path().join("ok.png");
+        image::RgbImage::new(4, 4).save(&image).unwrap();
+        assert!(check(&wallpaper(WallpaperType::Static, "ok.png")).is_ok());
+
+        let error = check(&wallpaper(WallpaperType::Static, "gone.png")).unwrap_err();
+        assert!(error.to_string().contains("gone.png can't be shown"));
+
+        fs::write(dir.path().join("fake.png"), b"not an image").unwrap();
+        assert!(check(&wallpaper(WallpaperType::Static, "fake.png")).is_err());
+        fs::write(dir.path().join("empty.mp4"), b"").unwrap();
+        assert!(check(&wallpaper(WallpaperType::Video, "empty.mp4")).is_err());
+
+        assert!(check(&wallpaper(WallpaperType::Slideshow, "ok.png")).is_err());
+        assert!(check(&WallpaperInfo::new(
+            WallpaperType::Slideshow,
+            Some(dir.path().to_path_buf()),
+            None
+        ))
+        .is_ok());
+        assert!(check(&WallpaperInfo::new(
+            WallpaperType::Web,
+            None,
+            Some("https://example.com".to_string())
+        ))
+        .is_ok());
+    }
+
+    #[test]
+    fn test_check_shader_source() {
+        let shadertoy = "// Waves {\nvoid mainImage(out vec4 fragColor, in vec2 fragCoord) {\n    fragColor = vec4(fragCoord / iResolution.xy, 0.5, 1.0);\n}\n";
+        assert!(check_shader_source(shadertoy).is_ok());
+        assert!(check_shader_source("/* main( */ void f() {}").is_err());
+
+        let error = check_shader_source("void main() {\n    float x = (1.0;\n}\n").unwrap_err();
+        assert!(error.to_string().contains("unclosed '('"));
+        let error = check_shader_source("void main() {\n}\n}\n").unwrap_err();
+        assert!(error.to_string().contains("line 3"));
+
+        // Line numbers of compiler errors match the file
+        let wrapped = compilable_shader(shadertoy);
+        assert!(wrapped.starts_with("#version 330\n"));
+        assert!(wrapped.contains("uniform sampler2D iChannel3;\n#line 1\n// Waves {\n"));
+        assert!(compilable_shader("#version 120\nvoid main() {}").starts_with("#version 120\n"));
+        assert!(compilable_shader("void main() {}").starts_with("#version 110\n#line 1\n"));
+    }
+}
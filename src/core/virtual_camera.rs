@@ -0,0 +1,358 @@
+//! Virtual camera: the wallpaper as a webcam or an OBS source
+//!
+//! ffmpeg sends a copy of the wallpaper to a v4l2loopback device, which OBS
+//! and video calls see as a webcam, or as an MPEG-TS stream OBS's Media
+//! Source plays (`udp://127.0.0.1:47940` by default). Video wallpapers are
+//! streamed from their file, looping, and static ones from their image.
+//! Wallpapers drawn in-process or by a player have their frame captured
+//! and piped to ffmpeg every frame. The picture is cropped to fill the
+//! configured size, which stays the same across wallpaper changes.
+use crate::core::config::{CameraOutput, VirtualCameraConfig};
+use crate::core::scheduler::CurrentWallpaper;
+use crate::core::{runtime, AppError, AppResult, Config, EventLog, ProcessHandle, WallpaperType};
+use log::{debug, info, warn};
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{ChildStdin, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Time between checks of the wallpaper while nothing is piped frame by frame
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Time before starting ffmpeg again after it failed
+const RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// What ffmpeg reads the picture from
+#[derive(Debug, Clone, PartialEq)]
+enum Source {
+    /// Video file, looped
+    Video(PathBuf),
+
+    /// Image, repeated
+    Image(PathBuf),
+
+    /// Frames captured from the wallpaper, piped in; the type and path tell wallpapers apart
+    Frames(WallpaperType, Option<PathBuf>),
+}
+
+/// What the running ffmpeg sends
+#[derive(Debug, Clone, PartialEq)]
+struct Sent {
+    /// Picture sent
+    source: Source,
+
+    /// Where and how it's sent
+    settings: VirtualCameraConfig,
+}
+
+/// Sends the wallpaper to a virtual camera or OBS
+pub struct VirtualCamera {
+    /// Virtual camera configuration
+    config: Arc<Mutex<VirtualCameraConfig>>,
+
+    /// Wallpaper sent
+    current_wallpaper: CurrentWallpaper,
+
+    /// Sending thread handle
+    send_thread: Option<thread::JoinHandle<()>>,
+
+    /// Whether the camera is running
+    is_running: Arc<Mutex<bool>>,
+}
+
+#[allow(dead_code)]
+impl VirtualCamera {
+    /// Create a new virtual camera for the scheduler's wallpaper
+    pub fn new(config: VirtualCameraConfig, current_wallpaper: CurrentWallpaper) -> Self {
+        Self {
+            config: Arc::new(Mutex::new(config)),
+            current_wallpaper,
+            send_thread: None,
+            is_running: Arc::new(Mutex::new(false)),
+        }
+    }
+
+    /// Update the virtual camera configuration
+    pub fn update_config(&self, config: VirtualCameraConfig) {
+        *self.config.lock().unwrap() = config;
+    }
+
+    /// Start the sending thread
+    pub fn start(&mut self) -> AppResult<()> {
+        if *self.is_running.lock().unwrap() {
+            debug!("Virtual camera is already running");
+            return Ok(());
+        }
+
+        *self.is_running.lock().unwrap() = true;
+
+        let config = self.config.clone();
+        let current_wallpaper = self.current_wallpaper.clone();
+        let is_running = self.is_running.clone();
+
+        self.send_thread = Some(thread::spawn(move || {
+            let frame = Config::get_frames_dir().join("virtual-camera.png");
+            let mut running: Option<(Sent, ProcessHandle, Option<ChildStdin>)> = None;
+            let mut failed: Option<(Instant, Sent)> = None;
+
+            while *is_running.lock().unwrap() {
+                let started = Instant::now();
+                let settings = config.lock().unwrap().clone();
+
+                let source = current_wallpaper
+                    .lock()
+                    .unwrap()
+                    .get(None)
+                    .and_then(|wallpaper| {
+                        let path = wallpaper.get_path().map(|path| path.to_path_buf());
+                        match (wallpaper.get_type(), path) {
+                            (WallpaperType::Video, Some(path)) => Some(Source::Video(path)),
+                            (WallpaperType::Static, Some(path)) => Some(Source::Image(path)),
+                            (WallpaperType::Web, _) => None,
+                            (other, path) => Some(Source::Frames(other, path)),
+                        }
+                    });
+                let wanted = source.filter(|_| settings.enabled).map(|source| Sent {
+                    source,
+                    settings: settings.clone(),
+                });
+
+                // ffmpeg that stopped on its own is started again, unless it just failed
+                if let Some((_, process, _)) = running.as_mut() {
+                    if let Some(status) = process.exited() {
+                        let (sent, _, _) = running.take().unwrap();
+                        warn!("Virtual camera ffmpeg exited with {}", status);
+                        EventLog::shared().error(
+                            "virtual-camera",
+                            "The virtual camera stopped",
+                            status,
+                        );
+                        failed = Some((Instant::now(), sent));
+                    }
+                }
+
+                if wanted.as_ref() != running.as_ref().map(|(sent, _, _)| sent) {
+                    if running
+                        .take()
+                        .map(|(_, process, _)| process.kill())
+                        .is_some()
+                    {
+                        info!("Virtual camera stopped");
+                    }
+                    let retry = failed.as_ref().map_or(true, |(at, sent)| {
+                        at.elapsed() >= RETRY_INTERVAL || Some(sent) != wanted.as_ref()
+                    });
+                    if let Some(wanted) = wanted.filter(|_| retry) {
+                        match spawn(&wanted) {
+                            Ok((process, stdin)) => {
+                                info!(
+                                    "Virtual camera sending the wallpaper to {}",
+                                    target(&wanted.settings)
+                                );
+                                running = Some((wanted, process, stdin));
+                                failed = None;
+                            }
+                            Err(e) => {
+                                warn!("Failed to start the virtual camera: {}", e);
+                                EventLog::shared().error(
+                                    "virtual-camera",
+                                    "Failed to start the virtual camera",
+                                    &e,
+                                );
+                                failed = Some((Instant::now(), wanted));
+                            }
+                        }
+                    }
+                }
+
+                // Captured frames are piped in here, one per frame
+                let interval = match running.as_mut() {
+                    Some((_, _, Some(stdin))) => {
+                        let captured = current_wallpaper
+                            .lock()
+                            .unwrap()
+                            .get(None)
+                            .map_or(
+                                Err(AppError::WallpaperError("No wallpaper".to_string())),
+                                |wallpaper| runtime::block_on(wallpaper.capture_frame(&frame)),
+                            )
+                            .and_then(|_| Ok(fs::read(&frame)?))
+                            .and_then(|png| Ok(stdin.write_all(&png)?));
+                        if let Err(e) = captured {
+                            // Wallpapers that can't be captured are tried again later, not every frame
+                            warn!("Virtual camera can't copy this wallpaper: {}", e);
+                            let (sent, process, _) = running.take().unwrap();
+                            process.kill();
+                            failed = Some((Instant::now(), sent));
+                        }
+                        Duration::from_secs(1) / settings.fps.clamp(1, 60)
+                    }
+                    _ => CHECK_INTERVAL,
+                };
+
+                thread::sleep(interval.saturating_sub(started.elapsed()));
+            }
+
+            if let Some((_, process, _)) = running {
+                process.kill();
+            }
+            let _ = fs::remove_file(&frame);
+        }));
+
+        info!("Virtual camera started");
+        Ok(())
+    }
+
+    /// Stop the sending thread and ffmpeg
+    pub fn stop(&mut self) -> AppResult<()> {
+        if !*self.is_running.lock().unwrap() {
+            debug!("Virtual camera is not running");
+            return Ok(());
+        }
+
+        *self.is_running.lock().unwrap() = false;
+
+        if let Some(thread) = self.send_thread.take() {
+            thread.join().map_err(|e| {
+                AppError::Other(format!("Failed to join virtual camera thread: {:?}", e))
+            })?;
+        }
+
+        info!("Virtual camera stopped");
+        Ok(())
+    }
+}
+
+/// Describe where the picture goes
+fn target(settings: &VirtualCameraConfig) -> &str {
+    match settings.output {
+        CameraOutput::Loopback => &settings.device,
+        CameraOutput::Stream => &settings.stream_url,
+    }
+}
+
+/// Start ffmpeg sending a picture, with its input when frames are piped in
+fn spawn(sent: &Sent) -> AppResult<(ProcessHandle, Option<ChildStdin>)> {
+    let piped = matches!(sent.source, Source::Frames(..));
+    let mut command = Command::new("ffmpeg");
+    command
+        .args(ffmpeg_args(sent))
+        .stdin(if piped { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    let mut process = ProcessHandle::spawn(&mut command, "ffmpeg", "virtual camera")?;
+    let stdin = process.take_stdin();
+    Ok((process, stdin))
+}
+
+/// Build ffmpeg's arguments for a picture and where it goes
+fn ffmpeg_args(sent: &Sent) -> Vec<String> {
+    let settings = &sent.settings;
+    let fps = settings.fps.clamp(1, 60).to_string();
+    let mut args: Vec<String> = ["-hide_banner", "-loglevel", "error"]
+        .map(String::from)
+        .to_vec();
+
+    match &sent.source {
+        Source::Video(path) => {
+            args.extend(["-re", "-stream_loop", "-1", "-i"].map(String::from));
+            args.push(path.to_string_lossy().to_string());
+        }
+        Source::Image(path) => {
+            args.extend(["-re", "-loop", "1", "-framerate", &fps, "-i"].map(String::from));
+            args.push(path.to_string_lossy().to_string());
+        }
+        Source::Frames(..) => {
+            args.extend(
+                [
+                    "-f",
+                    "image2pipe",
+                    "-c:v",
+                    "png",
+                    "-framerate",
+                    &fps,
+                    "-i",
+                    "-",
+                ]
+                .map(String::from),
+            );
+        }
+    }
+
+    // Cropped to fill, so the size consumers see never changes
+    let (width, height) = (settings.width.max(2) & !1, settings.height.max(2) & !1);
+    args.extend(["-an", "-r", &fps, "-vf"].map(String::from));
+    args.push(format!(
+        "scale={w}:{h}:force_original_aspect_ratio=increase,crop={w}:{h},format=yuv420p",
+        w = width,
+        h = height
+    ));
+
+    match settings.output {
+        CameraOutput::Loopback => {
+            args.extend(["-f", "v4l2"].map(String::from));
+            args.push(settings.device.clone());
+        }
+        CameraOutput::Stream => {
+            args.extend(
+                [
+                    "-c:v",
+                    "libx264",
+                    "-preset",
+                    "ultrafast",
+                    "-tune",
+                    "zerolatency",
+                    "-g",
+                    &fps,
+                    "-f",
+                    "mpegts",
+                ]
+                .map(String::from),
+            );
+            args.push(settings.stream_url.clone());
+        }
+    }
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ffmpeg_args() {
+        let video = Sent {
+            source: Source::Video(PathBuf::from("/walls/waves.mp4")),
+            settings: VirtualCameraConfig::default(),
+        };
+        let args = ffmpeg_args(&video).join(" ");
+        assert!(
+            args.contains("-stream_loop -1 -i /walls/waves.mp4"),
+            "{}",
+            args
+        );
+        assert!(args.contains("crop=1280:720"));
+        assert!(args.ends_with("-f v4l2 /dev/video10"));
+
+        // Odd sizes are evened out, as yuv420p needs
+        let frames = Sent {
+            source: Source::Frames(WallpaperType::Particles, None),
+            settings: VirtualCameraConfig {
+                output: CameraOutput::Stream,
+                width: 853,
+                ..VirtualCameraConfig::default()
+            },
+        };
+        let args = ffmpeg_args(&frames).join(" ");
+        assert!(
+            args.contains("-f image2pipe -c:v png -framerate 30 -i -"),
+            "{}",
+            args
+        );
+        assert!(args.contains("crop=852:720"));
+        assert!(args.ends_with("-f mpegts udp://127.0.0.1:47940"));
+    }
+}
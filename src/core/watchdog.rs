@@ -0,0 +1,421 @@
+//! Restarting crashed wallpaper players
+//!
+//! When the player of the running wallpaper exits on its own, it is started
+//! again after a growing delay. After too many crashes in a row the last
+//! static wallpaper is shown instead, so the desktop doesn't stay black; a
+//! wallpaper on a single monitor is stopped, leaving the others running.
+//! Only players the app keeps a process handle for (mpv) can be watched.
+use crate::core::config::{PlacementConfig, WatchdogConfig};
+use crate::core::scheduler::CurrentWallpaper;
+use crate::core::{
+    runtime, AppError, AppResult, CurrentWallpaperState, CurrentWallpaperStore, EventLevel,
+    EventLog, WallpaperInfo, WallpaperType,
+};
+use crate::platform::WallpaperManager;
+use crate::wallpapers::{StaticWallpaper, Wallpaper};
+use log::{debug, error, info, warn};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Delay before the first restart, doubled after every further crash
+const RESTART_DELAY: Duration = Duration::from_secs(2);
+
+/// Time a player has to run before its earlier crashes are forgotten
+const STABLE_AFTER: Duration = Duration::from_secs(300);
+
+/// What to do about a crash
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CrashAction {
+    /// Start the player again after a delay
+    Restart(Duration),
+
+    /// Stop trying and show the fallback wallpaper
+    GiveUp,
+}
+
+/// Counts the crashes of one wallpaper and picks the backoff
+#[derive(Debug, Default)]
+struct RestartPolicy {
+    /// Crashes since the player last ran stably
+    crashes: u32,
+
+    /// Time of the last crash
+    last_crash: Option<Instant>,
+}
+
+impl RestartPolicy {
+    /// Record a crash and decide what to do
+    fn on_crash(&mut self, now: Instant, max_restarts: u32) -> CrashAction {
+        if self
+            .last_crash
+            .map_or(false, |last| now.duration_since(last) >= STABLE_AFTER)
+        {
+            self.crashes = 0;
+        }
+        self.crashes += 1;
+        self.last_crash = Some(now);
+
+        if self.crashes > max_restarts {
+            CrashAction::GiveUp
+        } else {
+            CrashAction::Restart(RESTART_DELAY * 2u32.pow(self.crashes - 1))
+        }
+    }
+}
+
+/// Next step after a crash
+#[derive(Debug, Clone)]
+enum Recovery {
+    /// Start the crashed wallpaper again at a time
+    Restart(PathBuf, Instant),
+
+    /// Show the fallback wallpaper
+    Fallback,
+}
+
+/// Crash tracking of one running wallpaper
+#[derive(Debug, Default)]
+struct Watch {
+    /// Wallpaper the policy counts crashes for
+    path: Option<PathBuf>,
+
+    /// Crashes so far
+    policy: RestartPolicy,
+
+    /// Recovery under way
+    recovery: Option<Recovery>,
+}
+
+/// Static wallpaper shown when an animated one keeps crashing
+#[derive(Debug, Clone, Default)]
+struct Fallback {
+    /// Image path
+    path: Option<PathBuf>,
+
+    /// How the image is fitted
+    placement: PlacementConfig,
+}
+
+/// Restarts the running wallpaper's player when it crashes
+pub struct Watchdog {
+    /// Watchdog configuration
+    config: Arc<Mutex<WatchdogConfig>>,
+
+    /// Wallpaper whose player is watched
+    current_wallpaper: CurrentWallpaper,
+
+    /// Wallpaper manager for the fallback wallpaper
+    wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>,
+
+    /// Static wallpaper to fall back to
+    fallback: Arc<Mutex<Fallback>>,
+
+    /// Watch thread handle
+    watch_thread: Option<thread::JoinHandle<()>>,
+
+    /// Whether the watchdog is running
+    is_running: Arc<Mutex<bool>>,
+}
+
+#[allow(dead_code)]
+impl Watchdog {
+    /// Create a new watchdog for the scheduler's wallpaper
+    pub fn new(
+        config: WatchdogConfig,
+        current_wallpaper: CurrentWallpaper,
+        wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>,
+    ) -> Self {
+        Self {
+            config: Arc::new(Mutex::new(config)),
+            current_wallpaper,
+            wallpaper_manager,
+            fallback: Arc::new(Mutex::new(Fallback::default())),
+            watch_thread: None,
+            is_running: Arc::new(Mutex::new(false)),
+        }
+    }
+
+    /// Update the watchdog configuration
+    pub fn update_config(&self, config: WatchdogConfig) {
+        *self.config.lock().unwrap() = config;
+    }
+
+    /// Set the static wallpaper shown after repeated crashes
+    ///
+    /// A static wallpaper applied later replaces it.
+    pub fn set_fallback(&self, path: Option<PathBuf>, placement: PlacementConfig) {
+        let mut fallback = self.fallback.lock().unwrap();
+        if path.is_some() {
+            fallback.path = path;
+        }
+        fallback.placement = placement;
+    }
+
+    /// Start the watch thread
+    pub fn start(&mut self) -> AppResult<()> {
+        if *self.is_running.lock().unwrap() {
+            debug!("Watchdog is already running");
+            return Ok(());
+        }
+
+        *self.is_running.lock().unwrap() = true;
+
+        let config = self.config.clone();
+        let current_wallpaper = self.current_wallpaper.clone();
+        let wallpaper_manager = self.wallpaper_manager.clone();
+        let fallback = self.fallback.clone();
+        let is_running = self.is_running.clone();
+
+        self.watch_thread = Some(thread::spawn(move || {
+            // Each running wallpaper, by the monitor ID it runs on
+            let mut watches: HashMap<Option<String>, Watch> = HashMap::new();
+
+            while *is_running.lock().unwrap() {
+                let config = config.lock().unwrap().clone();
+                let mut current = current_wallpaper.lock().unwrap();
+                watches.retain(|monitor, _| current.get(monitor.as_deref()).is_some());
+                let mut given_up = Vec::new();
+
+                for (monitor, wallpaper) in current.iter() {
+                    let path = wallpaper.get_path().map(Path::to_path_buf);
+                    if wallpaper.get_type() == WallpaperType::Static && path.is_some() {
+                        fallback.lock().unwrap().path = path.clone();
+                    }
+                    let watch = watches.entry(monitor.map(str::to_string)).or_default();
+                    if path != watch.path {
+                        *watch = Watch {
+                            path: path.clone(),
+                            ..Watch::default()
+                        };
+                    }
+
+                    match &watch.recovery {
+                        Some(Recovery::Restart(crashed, restart_at))
+                            if Instant::now() >= *restart_at =>
+                        {
+                            let crashed = crashed.clone();
+                            watch.recovery = match runtime::block_on(wallpaper.start()) {
+                                Ok(()) => {
+                                    let pid = runtime::block_on(wallpaper.process_id());
+                                    match monitor {
+                                        Some(monitor) => {
+                                            CurrentWallpaperStore::shared().set_pid_on(monitor, pid)
+                                        }
+                                        None => CurrentWallpaperStore::shared().set_pid(pid),
+                                    }
+                                    info!("Restarted crashed wallpaper {}", crashed.display());
+                                    EventLog::shared().info(
+                                        "watchdog",
+                                        format!(
+                                            "Restarted the wallpaper player for {}",
+                                            file_name(&crashed)
+                                        ),
+                                    );
+                                    None
+                                }
+                                Err(e) => {
+                                    warn!("Failed to restart wallpaper: {}", e);
+                                    Some(Self::handle_crash(
+                                        &mut watch.policy,
+                                        &crashed,
+                                        monitor,
+                                        &e.to_string(),
+                                        &config,
+                                    ))
+                                }
+                            };
+                        }
+                        Some(_) => {}
+                        None if config.enabled => {
+                            if let (Some(error), Some(path)) =
+                                (runtime::block_on(wallpaper.crashed()), &path)
+                            {
+                                watch.recovery = Some(Self::handle_crash(
+                                    &mut watch.policy,
+                                    path,
+                                    monitor,
+                                    &error.to_string(),
+                                    &config,
+                                ));
+                            }
+                        }
+                        None => {}
+                    }
+
+                    if matches!(watch.recovery, Some(Recovery::Fallback)) {
+                        watch.recovery = None;
+                        given_up.push(monitor.map(str::to_string));
+                    }
+                }
+
+                // The restarts ran out
+                for monitor in given_up {
+                    if let Some(wallpaper) = current.take(monitor.as_deref()) {
+                        if let Err(e) = runtime::block_on(wallpaper.stop()) {
+                            debug!("Failed to stop crashed wallpaper: {}", e);
+                        }
+                    }
+                    match monitor {
+                        Some(monitor) => CurrentWallpaperStore::shared().clear_on(&monitor),
+                        None => {
+                            let fallback = fallback.lock().unwrap().clone();
+                            match Self::show_fallback(&wallpaper_manager, fallback) {
+                                Some(wallpaper) => current.insert(None, wallpaper),
+                                None => CurrentWallpaperStore::shared().clear(),
+                            }
+                        }
+                    }
+                }
+
+                drop(current);
+                thread::sleep(Duration::from_secs(1));
+            }
+        }));
+
+        info!("Watchdog started");
+        Ok(())
+    }
+
+    /// Stop the watch thread
+    pub fn stop(&mut self) -> AppResult<()> {
+        if !*self.is_running.lock().unwrap() {
+            debug!("Watchdog is not running");
+            return Ok(());
+        }
+
+        *self.is_running.lock().unwrap() = false;
+
+        if let Some(thread) = self.watch_thread.take() {
+            thread
+                .join()
+                .map_err(|e| AppError::Other(format!("Failed to join watchdog thread: {:?}", e)))?;
+        }
+
+        info!("Watchdog stopped");
+        Ok(())
+    }
+
+    /// Report a crash of the wallpaper on `monitor`, or on every monitor, and decide how to recover
+    fn handle_crash(
+        policy: &mut RestartPolicy,
+        path: &Path,
+        monitor: Option<&str>,
+        reason: &str,
+        config: &WatchdogConfig,
+    ) -> Recovery {
+        let name = file_name(path);
+        match policy.on_crash(Instant::now(), config.max_restarts) {
+            CrashAction::Restart(delay) => {
+                warn!(
+                    "Wallpaper player for {} crashed ({}), restarting in {:?}",
+                    path.display(),
+                    reason,
+                    delay
+                );
+                EventLog::shared().record(
+                    EventLevel::Warning,
+                    "watchdog",
+                    format!(
+                        "The wallpaper player for {} crashed and restarts in {} seconds",
+                        name,
+                        delay.as_secs()
+                    ),
+                    Some(reason.to_string()),
+                );
+                Recovery::Restart(path.to_path_buf(), Instant::now() + delay)
+            }
+            CrashAction::GiveUp => {
+                error!(
+                    "Wallpaper player for {} keeps crashing ({}), giving up",
+                    path.display(),
+                    reason
+                );
+                let message = match monitor {
+                    Some(monitor) => format!("The wallpaper player for {} keeps crashing, so it was stopped on {}", name, monitor),
+                    None => format!("The wallpaper player for {} keeps crashing, so the last static wallpaper is shown", name),
+                };
+                EventLog::shared().error("watchdog", message, reason);
+                Recovery::Fallback
+            }
+        }
+    }
+
+    /// Show the fallback wallpaper, if there is one
+    fn show_fallback(
+        wallpaper_manager: &Arc<dyn WallpaperManager + Send + Sync>,
+        fallback: Fallback,
+    ) -> Option<Box<dyn Wallpaper + Send + Sync>> {
+        let Some(path) = fallback.path else {
+            warn!("No static wallpaper to fall back to");
+            return None;
+        };
+
+        let wallpaper = StaticWallpaper::new(&path, wallpaper_manager.clone())
+            .with_placement(fallback.placement);
+        match runtime::block_on(wallpaper.start()) {
+            Ok(()) => {
+                let info = WallpaperInfo::new(WallpaperType::Static, Some(path.clone()), None);
+                CurrentWallpaperStore::shared().set(CurrentWallpaperState::new(
+                    info,
+                    wallpaper.monitors(),
+                    None,
+                ));
+                info!("Fell back to static wallpaper {}", path.display());
+                Some(Box::new(wallpaper))
+            }
+            Err(e) => {
+                error!("Failed to show fallback wallpaper: {}", e);
+                EventLog::shared().error("watchdog", "Failed to show the last static wallpaper", e);
+                None
+            }
+        }
+    }
+}
+
+/// Get a file's name for messages
+fn file_name(path: &Path) -> String {
+    path.file_name().map_or_else(
+        || path.display().to_string(),
+        |name| name.to_string_lossy().to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restart_policy_backs_off_and_gives_up() {
+        let mut policy = RestartPolicy::default();
+        let start = Instant::now();
+
+        assert_eq!(
+            policy.on_crash(start, 3),
+            CrashAction::Restart(Duration::from_secs(2))
+        );
+        assert_eq!(
+            policy.on_crash(start, 3),
+            CrashAction::Restart(Duration::from_secs(4))
+        );
+        assert_eq!(
+            policy.on_crash(start, 3),
+            CrashAction::Restart(Duration::from_secs(8))
+        );
+        assert_eq!(policy.on_crash(start, 3), CrashAction::GiveUp);
+
+        // A player that ran stably starts over
+        let later = start + STABLE_AFTER;
+        assert_eq!(
+            policy.on_crash(later, 3),
+            CrashAction::Restart(Duration::from_secs(2))
+        );
+
+        assert_eq!(
+            RestartPolicy::default().on_crash(start, 0),
+            CrashAction::GiveUp
+        );
+    }
+}
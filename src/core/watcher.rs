@@ -0,0 +1,254 @@
+//! Watching the configuration files for edits made outside the app
+//!
+//! The configuration, schedule and widget files can be edited by hand while
+//! the app runs. The watcher reports a file once it has stopped changing, and
+//! skips changes that leave the content as it was or that this process wrote
+//! itself, so saving from the app doesn't trigger a reload of the same file.
+use crate::core::{format, AppError, AppResult};
+use log::{debug, info, warn};
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+/// Time a file must stay unchanged before it is reported, as editors save in several steps
+const SETTLE_TIME: Duration = Duration::from_millis(300);
+
+/// Configuration file the app reads at startup
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConfigFile {
+    /// Application configuration
+    Config,
+
+    /// Wallpaper schedule
+    Schedule,
+
+    /// Widget layout
+    Widgets,
+}
+
+impl ConfigFile {
+    /// Every watched file
+    pub const ALL: [ConfigFile; 3] = [
+        ConfigFile::Config,
+        ConfigFile::Schedule,
+        ConfigFile::Widgets,
+    ];
+
+    /// Get the file name without extension
+    pub fn name(self) -> &'static str {
+        match self {
+            ConfigFile::Config => "config",
+            ConfigFile::Schedule => "schedule",
+            ConfigFile::Widgets => "widgets",
+        }
+    }
+
+    /// Get the file a path refers to, if it is a watched one
+    pub fn of(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?;
+        if !ext.eq_ignore_ascii_case("json") && !ext.eq_ignore_ascii_case("toml") {
+            return None;
+        }
+        let stem = path.file_stem()?.to_str()?;
+        Self::ALL.into_iter().find(|file| file.name() == stem)
+    }
+
+    /// Get the path of the file in `dir`, in the format in use
+    fn path_in(self, dir: &Path) -> PathBuf {
+        format::config_file(dir, self.name())
+    }
+}
+
+/// Watches the configuration directory
+#[derive(Default)]
+pub struct ConfigWatcher {
+    /// Active watcher; dropping it ends the thread reporting changes
+    watcher: Option<RecommendedWatcher>,
+}
+
+impl ConfigWatcher {
+    /// Create a stopped watcher
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start watching `dir`, calling `on_change` for each file edited from outside
+    pub fn start(
+        &mut self,
+        dir: &Path,
+        on_change: impl Fn(ConfigFile) + Send + 'static,
+    ) -> AppResult<()> {
+        if self.watcher.is_some() {
+            return Ok(());
+        }
+
+        let (sender, receiver) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender)
+            .map_err(|e| AppError::Other(format!("Failed to watch the configuration: {}", e)))?;
+        // Editors often replace the file, so the directory is watched rather than the files
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .map_err(|e| AppError::Other(format!("Failed to watch {}: {}", dir.display(), e)))?;
+
+        let mut snapshots = Snapshots::new(dir);
+        thread::spawn(move || {
+            let mut pending = HashSet::new();
+            loop {
+                match receiver.recv_timeout(SETTLE_TIME) {
+                    Ok(Ok(Event { kind, paths, .. })) if !kind.is_access() => {
+                        pending.extend(paths.iter().filter_map(|path| ConfigFile::of(path)));
+                    }
+                    Ok(Ok(_)) => {}
+                    Ok(Err(e)) => warn!("Configuration watcher error: {}", e),
+                    Err(RecvTimeoutError::Timeout) => {
+                        for file in pending.drain() {
+                            if snapshots.changed(file) {
+                                info!("{} was edited outside the app; reloading", file.name());
+                                on_change(file);
+                            }
+                        }
+                    }
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            }
+            debug!("Configuration watcher stopped");
+        });
+
+        self.watcher = Some(watcher);
+        info!("Watching {} for configuration edits", dir.display());
+        Ok(())
+    }
+
+    /// Stop watching
+    pub fn stop(&mut self) {
+        self.watcher = None;
+    }
+}
+
+/// Record content this process wrote to a configuration file
+pub(crate) fn note_own_write(path: &Path, content: &str) {
+    own_writes()
+        .lock()
+        .unwrap()
+        .insert(path.to_path_buf(), content_hash(content));
+}
+
+/// Hashes of the content this process last wrote, by path
+fn own_writes() -> &'static Mutex<HashMap<PathBuf, u64>> {
+    static OWN_WRITES: OnceLock<Mutex<HashMap<PathBuf, u64>>> = OnceLock::new();
+    OWN_WRITES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Hash file content
+fn content_hash(content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Content of the watched files when last reported
+struct Snapshots {
+    /// Configuration directory
+    dir: PathBuf,
+
+    /// Content hash of each file, None while it doesn't exist
+    seen: HashMap<ConfigFile, Option<u64>>,
+}
+
+impl Snapshots {
+    /// Take the current content of the files in `dir`
+    fn new(dir: &Path) -> Self {
+        let mut snapshots = Self {
+            dir: dir.to_path_buf(),
+            seen: HashMap::new(),
+        };
+        for file in ConfigFile::ALL {
+            let hash = snapshots.read(file);
+            snapshots.seen.insert(file, hash);
+        }
+        snapshots
+    }
+
+    /// Hash the current content of a file
+    fn read(&self, file: ConfigFile) -> Option<u64> {
+        fs::read_to_string(file.path_in(&self.dir))
+            .ok()
+            .map(|content| content_hash(&content))
+    }
+
+    /// Check whether a file was edited from outside since it was last seen
+    fn changed(&mut self, file: ConfigFile) -> bool {
+        // A removed file is left alone; loading it would write the defaults
+        let Some(hash) = self.read(file) else {
+            self.seen.insert(file, None);
+            return false;
+        };
+        let previous = self.seen.insert(file, Some(hash));
+        let own = own_writes().lock().unwrap().get(&file.path_in(&self.dir)) == Some(&hash);
+        previous != Some(Some(hash)) && !own
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_file_of() {
+        assert_eq!(
+            ConfigFile::of(Path::new("/config/aether-desk/config.json")),
+            Some(ConfigFile::Config)
+        );
+        assert_eq!(
+            ConfigFile::of(Path::new("/config/aether-desk/schedule.toml")),
+            Some(ConfigFile::Schedule)
+        );
+        assert_eq!(
+            ConfigFile::of(Path::new("widgets.JSON")),
+            Some(ConfigFile::Widgets)
+        );
+        assert_eq!(
+            ConfigFile::of(Path::new("/config/aether-desk/config.json~")),
+            None
+        );
+        assert_eq!(
+            ConfigFile::of(Path::new("/config/aether-desk/themes.json")),
+            None
+        );
+        assert_eq!(
+            ConfigFile::of(Path::new("/config/aether-desk/config")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_only_outside_edits_are_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("schedule.json");
+        fs::write(&path, "[]").unwrap();
+        let mut snapshots = Snapshots::new(dir.path());
+        assert!(!snapshots.changed(ConfigFile::Schedule));
+
+        fs::write(&path, "[ ]").unwrap();
+        assert!(snapshots.changed(ConfigFile::Schedule));
+        assert!(!snapshots.changed(ConfigFile::Schedule));
+
+        note_own_write(&path, "[1]");
+        fs::write(&path, "[1]").unwrap();
+        assert!(!snapshots.changed(ConfigFile::Schedule));
+
+        fs::remove_file(&path).unwrap();
+        assert!(!snapshots.changed(ConfigFile::Schedule));
+        fs::write(&path, "[1]").unwrap();
+        assert!(!snapshots.changed(ConfigFile::Schedule));
+        fs::write(&path, "[2]").unwrap();
+        assert!(snapshots.changed(ConfigFile::Schedule));
+    }
+}
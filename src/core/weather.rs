@@ -0,0 +1,281 @@
+//! Weather effects: the weather outside drawn over the wallpaper
+//!
+//! The current weather at the configured location is looked up every few
+//! minutes. While it rains, snows or is foggy, a static wallpaper filling
+//! every monitor is drawn in-process with the matching effect on top; once
+//! the weather clears, or another wallpaper is applied, the frames stop and
+//! the wallpaper itself is shown again. Spanned wallpapers and monitors with
+//! images of their own are left as they are.
+use crate::core::config::{PlacementConfig, WeatherEffect, WeatherEffectsConfig};
+use crate::core::scheduler::CurrentWallpaper;
+use crate::core::{
+    runtime, AppError, AppResult, HttpClient, RateLimit, SecretStore, WallpaperType,
+};
+use crate::experiments::effects::weather as effects;
+use crate::platform::WallpaperManager;
+use crate::sources::weather::{self, Weather};
+use crate::wallpapers::frame_player::FramePlayer;
+use log::{debug, info, warn};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Time between checks of the wallpaper and weather
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// What the frames on screen are drawn from
+#[derive(Debug, Clone, PartialEq)]
+struct Drawn {
+    /// Image of the wallpaper
+    path: PathBuf,
+
+    /// Weather drawn over it
+    effect: WeatherEffect,
+
+    /// How dense or thick the weather is
+    intensity: f32,
+
+    /// Most frames per second
+    fps: u32,
+
+    /// How the image is fitted
+    placement: PlacementConfig,
+}
+
+/// Draws the weather outside over static wallpapers
+pub struct WeatherEffects {
+    /// Weather effects configuration
+    config: Arc<Mutex<WeatherEffectsConfig>>,
+
+    /// How static wallpapers are fitted
+    placement: Arc<Mutex<PlacementConfig>>,
+
+    /// Wallpaper to draw over
+    current_wallpaper: CurrentWallpaper,
+
+    /// Platform-specific wallpaper manager, which shows the frames
+    wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>,
+
+    /// Store holding the weather API key
+    secret_store: Arc<SecretStore>,
+
+    /// Shared HTTP client
+    http_client: Arc<HttpClient>,
+
+    /// Check thread handle
+    check_thread: Option<thread::JoinHandle<()>>,
+
+    /// Whether the checks are running
+    is_running: Arc<Mutex<bool>>,
+
+    /// Effect matching the weather right now
+    current_effect: Arc<Mutex<Option<WeatherEffect>>>,
+}
+
+#[allow(dead_code)]
+impl WeatherEffects {
+    /// Create a new weather effects service for the scheduler's wallpaper
+    pub fn new(
+        config: WeatherEffectsConfig,
+        current_wallpaper: CurrentWallpaper,
+        wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>,
+        secret_store: Arc<SecretStore>,
+    ) -> Self {
+        let http_client = HttpClient::shared();
+        http_client.set_rate_limit(weather::PROVIDER, RateLimit::per_minute(10));
+
+        Self {
+            config: Arc::new(Mutex::new(config)),
+            placement: Arc::new(Mutex::new(PlacementConfig::default())),
+            current_wallpaper,
+            wallpaper_manager,
+            secret_store,
+            http_client,
+            check_thread: None,
+            is_running: Arc::new(Mutex::new(false)),
+            current_effect: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Update the weather effects configuration
+    pub fn update_config(&self, config: WeatherEffectsConfig) {
+        *self.config.lock().unwrap() = config;
+    }
+
+    /// Set how static wallpapers are fitted
+    pub fn set_placement(&self, placement: PlacementConfig) {
+        *self.placement.lock().unwrap() = placement;
+    }
+
+    /// Get the effect matching the weather right now, if any
+    pub fn current_effect(&self) -> Option<WeatherEffect> {
+        *self.current_effect.lock().unwrap()
+    }
+
+    /// Get the effect shown, shared with services drawing over the same wallpapers
+    pub fn effect_handle(&self) -> Arc<Mutex<Option<WeatherEffect>>> {
+        self.current_effect.clone()
+    }
+
+    /// Start the check thread
+    pub fn start(&mut self) -> AppResult<()> {
+        if *self.is_running.lock().unwrap() {
+            debug!("Weather effects are already running");
+            return Ok(());
+        }
+
+        *self.is_running.lock().unwrap() = true;
+
+        let config = self.config.clone();
+        let placement = self.placement.clone();
+        let current_wallpaper = self.current_wallpaper.clone();
+        let wallpaper_manager = self.wallpaper_manager.clone();
+        let secret_store = self.secret_store.clone();
+        let http_client = self.http_client.clone();
+        let is_running = self.is_running.clone();
+        let current_effect = self.current_effect.clone();
+
+        self.check_thread = Some(thread::spawn(move || {
+            let player = FramePlayer::new(wallpaper_manager.clone());
+            let mut weather: Option<Weather> = None;
+            let mut fetched: Option<(Instant, String)> = None;
+            let mut drawn: Option<Drawn> = None;
+
+            while *is_running.lock().unwrap() {
+                let config = config.lock().unwrap().clone();
+                let placement = placement.lock().unwrap().clone();
+
+                // Looked up again when due or when the location changed
+                let refresh = Duration::from_secs(config.refresh_minutes.max(1) as u64 * 60);
+                let due = fetched.as_ref().map_or(true, |(at, location)| {
+                    at.elapsed() >= refresh || *location != config.location
+                });
+                if config.enabled && config.preview.is_none() && due {
+                    fetched = Some((Instant::now(), config.location.clone()));
+                    weather = fetch(&http_client, &secret_store, &config.location).or(weather);
+                }
+
+                let effect = if config.enabled {
+                    config
+                        .preview
+                        .or_else(|| weather.as_ref().and_then(Weather::effect))
+                } else {
+                    None
+                };
+                *current_effect.lock().unwrap() = effect;
+
+                // Only a static wallpaper filling every monitor alike is drawn over
+                let shown = current_wallpaper
+                    .lock()
+                    .unwrap()
+                    .get(None)
+                    .filter(|wallpaper| wallpaper.get_type() == WallpaperType::Static)
+                    .and_then(|wallpaper| wallpaper.get_path().map(Path::to_path_buf))
+                    .filter(|_| !placement.span && placement.monitor_images.is_empty());
+
+                let wanted = shown.clone().zip(effect).map(|(path, effect)| Drawn {
+                    path,
+                    effect,
+                    intensity: config.intensity,
+                    fps: config.fps,
+                    placement: placement.clone(),
+                });
+                if wanted != drawn {
+                    player.stop();
+                    match &wanted {
+                        Some(wanted) => match effects::over_wallpaper(
+                            &wanted.path,
+                            &wanted.placement,
+                            wanted.effect,
+                            wanted.intensity,
+                        ) {
+                            Ok(overlay) => {
+                                info!("Drawing {:?} over the wallpaper", wanted.effect);
+                                player.start(Box::new(overlay), wanted.fps);
+                            }
+                            Err(e) => warn!("Failed to draw the weather over the wallpaper: {}", e),
+                        },
+                        None => restore(&current_wallpaper, drawn.as_ref(), shown.as_deref()),
+                    }
+                    drawn = wanted;
+                }
+
+                thread::sleep(CHECK_INTERVAL);
+            }
+
+            player.stop();
+            let shown = current_wallpaper
+                .lock()
+                .unwrap()
+                .get(None)
+                .and_then(|wallpaper| wallpaper.get_path().map(Path::to_path_buf));
+            restore(&current_wallpaper, drawn.as_ref(), shown.as_deref());
+            *current_effect.lock().unwrap() = None;
+        }));
+
+        info!("Weather effects started");
+        Ok(())
+    }
+
+    /// Stop the check thread, bringing back the wallpaper without the weather
+    pub fn stop(&mut self) -> AppResult<()> {
+        if !*self.is_running.lock().unwrap() {
+            debug!("Weather effects are not running");
+            return Ok(());
+        }
+
+        *self.is_running.lock().unwrap() = false;
+
+        if let Some(thread) = self.check_thread.take() {
+            thread.join().map_err(|e| {
+                AppError::Other(format!("Failed to join weather effects thread: {:?}", e))
+            })?;
+        }
+
+        info!("Weather effects stopped");
+        Ok(())
+    }
+}
+
+/// Look up the weather, None without an API key or location or when it fails
+fn fetch(http_client: &HttpClient, secret_store: &SecretStore, location: &str) -> Option<Weather> {
+    let api_key = match secret_store.get(weather::API_KEY_SECRET) {
+        Ok(api_key) => api_key.unwrap_or_default(),
+        Err(e) => {
+            warn!("Failed to read the weather API key: {}", e);
+            return None;
+        }
+    };
+    if api_key.is_empty() || location.is_empty() {
+        debug!("Set a weather API key and location to draw the weather");
+        return None;
+    }
+
+    match weather::fetch(http_client, &api_key, location) {
+        Ok((weather, _)) => {
+            debug!(
+                "Weather in {}: {} at {:.1}°C",
+                location, weather.condition, weather.temperature
+            );
+            Some(weather)
+        }
+        Err(e) => {
+            // Keep the last known weather; the HTTP client logs connectivity changes once
+            debug!("Weather update failed: {}", e);
+            None
+        }
+    }
+}
+
+/// Show a wallpaper drawn over without the weather, unless another one replaced it meanwhile
+fn restore(current_wallpaper: &CurrentWallpaper, drawn: Option<&Drawn>, shown: Option<&Path>) {
+    if drawn.map(|drawn| drawn.path.as_path()) != shown || shown.is_none() {
+        return;
+    }
+    if let Some(wallpaper) = current_wallpaper.lock().unwrap().get(None) {
+        if let Err(e) = runtime::block_on(wallpaper.redraw()) {
+            warn!("Failed to bring back the wallpaper: {}", e);
+        }
+    }
+}
@@ -1,31 +1,77 @@
-use crate::core::{AppError, AppResult, Config};
-use chrono::{Datelike, Local};
+use crate::core::autopause::{self, IDLE_POLL_INTERVAL};
+use crate::core::config::AutoPauseConfig;
+use crate::core::{format, AppError, AppResult, Config, HttpClient, RateLimit, SecretStore};
+use crate::platform::{power, session};
+use crate::sources::calendar::{Calendar, Event};
+use crate::sources::weather::{self, Weather};
+use chrono::Local;
 use log::{debug, error, info};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration as StdDuration;
+use std::time::{Duration as StdDuration, Instant};
+
+/// Time between checks of the power source and screen lock
+const POWER_CHECK_INTERVAL: StdDuration = StdDuration::from_secs(30);
+
+/// Time after the last render during which widgets count as shown
+const VISIBLE_TIMEOUT: StdDuration = StdDuration::from_secs(2);
+
+/// Widget settings that hold secrets and are kept out of widgets.json
+const SECRET_SETTINGS: &[&str] = &["api_key"];
+
+/// Get the secret store key for a widget setting
+pub fn widget_secret_key(widget_id: &str, setting: &str) -> String {
+    format!("widget.{}.{}", widget_id, setting)
+}
+
+/// Move secret settings of widgets into the store
+///
+/// Cleared settings and the secrets of widgets saved before but no longer
+/// configured are deleted from the store.
+fn store_secrets<'a>(
+    store: &SecretStore,
+    configs: &mut HashMap<String, WidgetConfig>,
+    saved_ids: impl Iterator<Item = &'a String>,
+) -> AppResult<()> {
+    for (id, widget_config) in configs.iter_mut() {
+        for setting in SECRET_SETTINGS {
+            match widget_config.settings.remove(*setting) {
+                Some(value) if value.is_empty() => store.delete(&widget_secret_key(id, setting))?,
+                Some(value) => store.set(&widget_secret_key(id, setting), &value)?,
+                None => {}
+            }
+        }
+    }
+
+    for id in saved_ids.filter(|id| !configs.contains_key(*id)) {
+        for setting in SECRET_SETTINGS {
+            store.delete(&widget_secret_key(id, setting))?;
+        }
+    }
+    Ok(())
+}
 
 /// Widget type
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum WidgetType {
     /// Clock widget
     Clock,
-    
+
     /// Weather widget
     Weather,
-    
+
     /// System monitor widget
     SystemMonitor,
-    
+
     /// Calendar widget
     Calendar,
-    
+
     /// Notes widget
     Notes,
-    
+
     /// Custom widget
     Custom(String),
 }
@@ -35,16 +81,16 @@ pub enum WidgetType {
 pub enum WidgetPosition {
     /// Top left
     TopLeft,
-    
+
     /// Top right
     TopRight,
-    
+
     /// Bottom left
     BottomLeft,
-    
+
     /// Bottom right
     BottomRight,
-    
+
     /// Custom position (x, y)
     Custom(i32, i32),
 }
@@ -54,13 +100,13 @@ pub enum WidgetPosition {
 pub enum WidgetSize {
     /// Small size
     Small,
-    
+
     /// Medium size
     Medium,
-    
+
     /// Large size
     Large,
-    
+
     /// Custom size (width, height)
     Custom(u32, u32),
 }
@@ -70,22 +116,22 @@ pub enum WidgetSize {
 pub struct WidgetConfig {
     /// Widget type
     pub widget_type: WidgetType,
-    
+
     /// Widget position
     pub position: WidgetPosition,
-    
+
     /// Widget size
     pub size: WidgetSize,
-    
+
     /// Widget settings
     pub settings: HashMap<String, String>,
-    
+
     /// Whether the widget is enabled
     pub enabled: bool,
-    
+
     /// Widget background color (RGBA)
     pub background_color: Option<[u8; 4]>,
-    
+
     /// Widget opacity (0.0 to 1.0)
     pub opacity: Option<f32>,
 }
@@ -95,39 +141,79 @@ pub struct WidgetConfig {
 pub trait Widget: Send + Sync {
     /// Get widget type
     fn get_type(&self) -> WidgetType;
-    
+
     /// Get widget name
     fn get_name(&self) -> String;
-    
+
     /// Get widget description
     fn get_description(&self) -> String;
-    
+
     /// Get widget settings
     fn get_settings(&self) -> HashMap<String, String>;
-    
+
     /// Update widget settings
     fn update_settings(&mut self, settings: HashMap<String, String>) -> AppResult<()>;
-    
+
     /// Render widget
     fn render(&self, ui: &mut egui::Ui) -> AppResult<()>;
-    
+
     /// Update widget
     fn update(&mut self) -> AppResult<()>;
+
+    /// Time between updates, or `None` for widgets that never need one
+    fn update_interval(&self) -> Option<StdDuration> {
+        Some(StdDuration::from_secs(1))
+    }
+}
+
+/// Widget and the time of its next update
+struct ScheduledWidget {
+    /// The widget
+    widget: Box<dyn Widget>,
+
+    /// Next update, or `None` when one is due right away
+    next_update: Option<Instant>,
+}
+
+impl ScheduledWidget {
+    /// Update the widget if it's due and get the time until its next update
+    ///
+    /// Widgets that never need updates return `None`.
+    fn update_if_due(&mut self, now: Instant, min_interval: StdDuration) -> Option<StdDuration> {
+        let interval = self.widget.update_interval()?.max(min_interval);
+        if self.next_update.map_or(true, |next| next <= now) {
+            if let Err(e) = self.widget.update() {
+                error!("Failed to update widget: {}", e);
+            }
+            self.next_update = Some(now + interval);
+        }
+        self.next_update
+            .map(|next| next.saturating_duration_since(now))
+    }
 }
 
 /// Widget manager
 pub struct WidgetManager {
     /// Widgets
-    widgets: Arc<Mutex<Vec<Box<dyn Widget>>>>,
-    
+    widgets: Arc<Mutex<Vec<ScheduledWidget>>>,
+
     /// Widget configurations
     widget_configs: Arc<Mutex<HashMap<String, WidgetConfig>>>,
-    
+
     /// Widget update thread handle
     update_thread: Option<thread::JoinHandle<()>>,
-    
+
     /// Whether the widget manager is running
     is_running: Arc<Mutex<bool>>,
+
+    /// Secret store for API keys
+    secret_store: Option<Arc<SecretStore>>,
+
+    /// When widgets update less often to save power
+    power_policy: Arc<Mutex<AutoPauseConfig>>,
+
+    /// Last time the widgets were rendered
+    last_rendered: Arc<Mutex<Option<Instant>>>,
 }
 
 #[allow(dead_code)]
@@ -139,53 +225,87 @@ impl WidgetManager {
             widget_configs: Arc::new(Mutex::new(HashMap::new())),
             update_thread: None,
             is_running: Arc::new(Mutex::new(false)),
+            secret_store: None,
+            power_policy: Arc::new(Mutex::new(AutoPauseConfig::default())),
+            last_rendered: Arc::new(Mutex::new(None)),
         }
     }
-    
+
+    /// Set when widgets update less often to save power
+    pub fn set_power_policy(&self, policy: AutoPauseConfig) {
+        *self.power_policy.lock().unwrap() = policy;
+        self.wake();
+    }
+
+    /// Wake the update thread to re-check what is due
+    fn wake(&self) {
+        if let Some(thread) = &self.update_thread {
+            thread.thread().unpark();
+        }
+    }
+
+    /// Set the secret store used for widget API keys
+    pub fn set_secret_store(&mut self, secret_store: Arc<SecretStore>) {
+        self.secret_store = Some(secret_store);
+    }
+
     /// Load widget configurations
     pub fn load_widgets(&mut self, config: &Config) -> AppResult<()> {
         let widgets_file = config.get_widgets_file();
-        
+
         if !widgets_file.exists() {
             debug!("Widgets file does not exist, creating default widgets");
             self.create_default_widgets(&widgets_file)?;
             return Ok(());
         }
-        
-        let widgets_content = std::fs::read_to_string(&widgets_file)
-            .map_err(|e| AppError::ConfigError(format!("Failed to read widgets file: {}", e)))?;
-        
-        let widget_configs: HashMap<String, WidgetConfig> = serde_json::from_str(&widgets_content)
-            .map_err(|e| AppError::ConfigError(format!("Failed to parse widgets file: {}", e)))?;
-        
+
+        let widget_configs: HashMap<String, WidgetConfig> = format::load(&widgets_file)
+            .map_err(|e| AppError::ConfigError(format!("Failed to load widgets file: {}", e)))?;
+
         let config_count = widget_configs.len();
+        let has_plaintext_secrets = widget_configs
+            .values()
+            .any(|c| SECRET_SETTINGS.iter().any(|s| c.settings.contains_key(*s)));
         {
             let mut configs = self.widget_configs.lock().unwrap();
             *configs = widget_configs;
         }
-        
+
+        // Move plaintext API keys from older widgets.json files into the secret store
+        if has_plaintext_secrets && self.secret_store.is_some() {
+            info!("Migrating widget API keys to secure storage");
+            self.save_widgets(config)?;
+        }
+
         // Create widgets from configurations
         self.create_widgets_from_configs()?;
-        
+
         info!("Loaded {} widget configurations", config_count);
         Ok(())
     }
-    
+
     /// Save widget configurations
     pub fn save_widgets(&self, config: &Config) -> AppResult<()> {
         let widgets_file = config.get_widgets_file();
-        let configs = self.widget_configs.lock().unwrap();
-        
-        let widgets_content = serde_json::to_string_pretty(&*configs)
-            .map_err(|e| AppError::ConfigError(format!("Failed to serialize widgets: {}", e)))?;
-        
-        std::fs::write(&widgets_file, widgets_content)
-            .map_err(|e| AppError::ConfigError(format!("Failed to write widgets file: {}", e)))?;
-        
+        let mut configs = self.widget_configs.lock().unwrap().clone();
+
+        // Keep secrets out of the widgets file
+        if let Some(store) = &self.secret_store {
+            let saved: HashMap<String, WidgetConfig> = if widgets_file.exists() {
+                format::load(&widgets_file).unwrap_or_default()
+            } else {
+                HashMap::new()
+            };
+            store_secrets(store, &mut configs, saved.keys())?;
+        }
+
+        format::save(&widgets_file, &configs)
+            .map_err(|e| AppError::ConfigError(format!("Failed to save widgets file: {}", e)))?;
+
         info!("Saved {} widget configurations", configs.len());
         Ok(())
     }
-    
+
     /// Create default widgets
     fn create_default_widgets(&self, widgets_file: &Path) -> AppResult<()> {
         let default_configs = vec![
@@ -232,64 +352,91 @@ impl WidgetManager {
                 },
             ),
         ];
-        
-        let default_configs_map: HashMap<String, WidgetConfig> = default_configs.into_iter().collect();
-        
-        let widgets_content = serde_json::to_string_pretty(&default_configs_map)
-            .map_err(|e| AppError::ConfigError(format!("Failed to serialize default widgets: {}", e)))?;
-        
-        std::fs::write(widgets_file, widgets_content)
-            .map_err(|e| AppError::ConfigError(format!("Failed to write default widgets file: {}", e)))?;
-        
+
+        let default_configs_map: HashMap<String, WidgetConfig> =
+            default_configs.into_iter().collect();
+
+        let widgets_content = serde_json::to_string_pretty(&default_configs_map).map_err(|e| {
+            AppError::ConfigError(format!("Failed to serialize default widgets: {}", e))
+        })?;
+
+        std::fs::write(widgets_file, widgets_content).map_err(|e| {
+            AppError::ConfigError(format!("Failed to write default widgets file: {}", e))
+        })?;
+
         let mut configs = self.widget_configs.lock().unwrap();
         *configs = default_configs_map;
-        
+
         info!("Created default widgets");
         Ok(())
     }
-    
+
     /// Create widgets from configurations
     fn create_widgets_from_configs(&mut self) -> AppResult<()> {
         let configs = self.widget_configs.lock().unwrap();
         let mut widgets = self.widgets.lock().unwrap();
-        
+
         widgets.clear();
-        
-        for (_id, config) in configs.iter() {
+
+        for (id, config) in configs.iter() {
             if !config.enabled {
                 continue;
             }
-            
+
+            let settings = self.resolve_secret_settings(id, &config.settings);
+
             let widget: Box<dyn Widget> = match config.widget_type {
-                WidgetType::Clock => {
-                    Box::new(ClockWidget::new(config.settings.clone()))
-                },
-                WidgetType::Weather => {
-                    Box::new(WeatherWidget::new(config.settings.clone()))
-                },
-                WidgetType::SystemMonitor => {
-                    Box::new(SystemMonitorWidget::new(config.settings.clone()))
-                },
-                WidgetType::Calendar => {
-                    Box::new(CalendarWidget::new(config.settings.clone()))
-                },
-                WidgetType::Notes => {
-                    Box::new(NotesWidget::new(config.settings.clone()))
-                },
+                WidgetType::Clock => Box::new(ClockWidget::new(settings)),
+                WidgetType::Weather => Box::new(WeatherWidget::new(settings)),
+                WidgetType::SystemMonitor => Box::new(SystemMonitorWidget::new(settings)),
+                WidgetType::Calendar => Box::new(CalendarWidget::new(settings)),
+                WidgetType::Notes => Box::new(NotesWidget::new(settings)),
                 WidgetType::Custom(ref widget_type) => {
                     // Custom widgets are not implemented in this version
                     debug!("Custom widget not implemented: {}", widget_type);
                     continue;
-                },
+                }
             };
-            
-            widgets.push(widget);
+
+            widgets.push(ScheduledWidget {
+                widget,
+                next_update: None,
+            });
         }
-        
+
         info!("Created {} widgets", widgets.len());
+        drop(widgets);
+        drop(configs);
+        self.wake();
         Ok(())
     }
-    
+
+    /// Fill in secret settings from the secret store
+    fn resolve_secret_settings(
+        &self,
+        id: &str,
+        settings: &HashMap<String, String>,
+    ) -> HashMap<String, String> {
+        let mut settings = settings.clone();
+
+        if let Some(store) = &self.secret_store {
+            for setting in SECRET_SETTINGS {
+                if settings.contains_key(*setting) {
+                    continue;
+                }
+                match store.get(&widget_secret_key(id, setting)) {
+                    Ok(Some(value)) => {
+                        settings.insert(setting.to_string(), value);
+                    }
+                    Ok(None) => {}
+                    Err(e) => error!("Failed to read secret for widget {}: {}", id, e),
+                }
+            }
+        }
+
+        settings
+    }
+
     /// Start the widget manager
     pub fn start(&mut self) -> AppResult<()> {
         let is_running = *self.is_running.lock().unwrap();
@@ -297,31 +444,81 @@ impl WidgetManager {
             debug!("Widget manager is already running");
             return Ok(());
         }
-        
+
         *self.is_running.lock().unwrap() = true;
-        
+
         let widgets = self.widgets.clone();
         let is_running = self.is_running.clone();
-        
+        let power_policy = self.power_policy.clone();
+
+        let last_rendered = self.last_rendered.clone();
+
+        // Updates run only while the widgets are shown, the screen is unlocked and
+        // the user is around; the thread sleeps until the next update is due or it is woken
         self.update_thread = Some(thread::spawn(move || {
-            let update_interval = StdDuration::from_secs(1); // Update every second
-            
+            let mut last_check: Option<Instant> = None;
+            let mut saving_power = false;
+            let mut locked = false;
+
             while *is_running.lock().unwrap() {
-                let mut widgets = widgets.lock().unwrap();
-                for widget in widgets.iter_mut() {
-                    if let Err(e) = widget.update() {
-                        error!("Failed to update widget: {}", e);
-                    }
+                let policy = power_policy.lock().unwrap().clone();
+                if last_check.map_or(true, |last| last.elapsed() >= POWER_CHECK_INTERVAL) {
+                    last_check = Some(Instant::now());
+                    saving_power = policy.on_battery
+                        && power::power_status()
+                            .map_or(false, |status| status.should_save(policy.battery_threshold));
+                    locked = session::is_locked();
+                }
+                let until_check = last_check.map_or(StdDuration::ZERO, |last| {
+                    POWER_CHECK_INTERVAL.saturating_sub(last.elapsed())
+                });
+
+                let shown = last_rendered
+                    .lock()
+                    .unwrap()
+                    .map_or(false, |last| last.elapsed() < VISIBLE_TIMEOUT);
+                if !shown {
+                    // Rendering the widgets again wakes the thread
+                    thread::park();
+                    continue;
+                }
+                if locked {
+                    thread::park_timeout(until_check);
+                    continue;
+                }
+                if autopause::is_user_away(&policy) {
+                    // Poll often so input resumes the updates right away
+                    thread::park_timeout(IDLE_POLL_INTERVAL);
+                    continue;
+                }
+
+                // Update less often on battery power
+                let min_interval = if saving_power {
+                    StdDuration::from_secs(policy.battery_widget_interval.max(1))
+                } else {
+                    StdDuration::ZERO
+                };
+
+                let now = Instant::now();
+                let next_update = widgets
+                    .lock()
+                    .unwrap()
+                    .iter_mut()
+                    .filter_map(|scheduled| scheduled.update_if_due(now, min_interval))
+                    .min();
+
+                match next_update {
+                    // Check back in time to notice the widgets being hidden or the screen locked
+                    Some(wait) => thread::park_timeout(wait.min(VISIBLE_TIMEOUT).min(until_check)),
+                    None => thread::park(),
                 }
-                
-                thread::sleep(update_interval);
             }
         }));
-        
+
         info!("Widget manager started");
         Ok(())
     }
-    
+
     /// Stop the widget manager
     pub fn stop(&mut self) -> AppResult<()> {
         let is_running = *self.is_running.lock().unwrap();
@@ -329,85 +526,110 @@ impl WidgetManager {
             debug!("Widget manager is not running");
             return Ok(());
         }
-        
+
         *self.is_running.lock().unwrap() = false;
-        
+        self.wake();
+
         if let Some(thread) = self.update_thread.take() {
             thread.join().map_err(|e| {
                 AppError::Other(format!("Failed to join widget update thread: {:?}", e))
             })?;
         }
-        
+
         info!("Widget manager stopped");
         Ok(())
     }
-    
+
     /// Add a widget
     pub fn add_widget(&mut self, id: String, config: WidgetConfig) -> AppResult<()> {
         {
             let mut configs = self.widget_configs.lock().unwrap();
             configs.insert(id, config);
         }
-        
+
         // Recreate widgets
         self.create_widgets_from_configs()?;
-        
+
         info!("Added widget");
         Ok(())
     }
-    
+
     /// Remove a widget
     pub fn remove_widget(&mut self, id: &str) -> AppResult<()> {
         {
             let mut configs = self.widget_configs.lock().unwrap();
             configs.remove(id);
         }
-        
+
+        // Drop any secrets belonging to the widget
+        if let Some(store) = &self.secret_store {
+            for setting in SECRET_SETTINGS {
+                store.delete(&widget_secret_key(id, setting))?;
+            }
+        }
+
         // Recreate widgets
         self.create_widgets_from_configs()?;
-        
+
         info!("Removed widget");
         Ok(())
     }
-    
+
     /// Update a widget
     pub fn update_widget(&mut self, id: &str, config: WidgetConfig) -> AppResult<()> {
         {
             let mut configs = self.widget_configs.lock().unwrap();
             configs.insert(id.to_string(), config);
         }
-        
+
         // Recreate widgets
         self.create_widgets_from_configs()?;
-        
+
         info!("Updated widget");
         Ok(())
     }
-    
+
     /// Get all widget configurations
     pub fn get_widget_configs(&self) -> HashMap<String, WidgetConfig> {
         let configs = self.widget_configs.lock().unwrap();
         configs.clone()
     }
-    
+
     /// Get widget count
     pub fn get_widget_count(&self) -> usize {
         let widgets = self.widgets.lock().unwrap();
         widgets.len()
     }
-    
+
     /// Render all widgets
-    pub fn render_widgets(&self, ui: &mut egui::Ui, bg_color: egui::Color32, accent_color: egui::Color32) -> AppResult<()> {
+    ///
+    /// Widgets only update while they are rendered.
+    pub fn render_widgets(
+        &self,
+        ui: &mut egui::Ui,
+        bg_color: egui::Color32,
+        accent_color: egui::Color32,
+    ) -> AppResult<()> {
+        let was_hidden = self
+            .last_rendered
+            .lock()
+            .unwrap()
+            .replace(Instant::now())
+            .map_or(true, |last| last.elapsed() >= VISIBLE_TIMEOUT);
+        if was_hidden {
+            self.wake();
+        }
+
         let widgets = self.widgets.lock().unwrap();
         let configs = self.widget_configs.lock().unwrap();
-        
-        for widget in widgets.iter() {
+
+        for widget in widgets.iter().map(|scheduled| &scheduled.widget) {
             let widget_type = widget.get_type();
             let widget_name = widget.get_name();
-            
+
             // Find the configuration for this widget
             let config = configs.iter().find(|(_, c)| c.widget_type == widget_type);
-            
+
             if let Some((_, config)) = config {
                 if !config.enabled {
                     continue;
@@ -419,7 +641,7 @@ impl WidgetManager {
                     .shadow(egui::epaint::Shadow::big_dark())
                     .stroke(egui::Stroke::new(2.0, accent_color))
                     .inner_margin(egui::Margin::same(12.0));
-                
+
                 frame.show(ui, |ui| {
                     ui.heading(egui::RichText::new(&widget_name).color(accent_color));
                     if let Err(e) = widget.render(ui) {
@@ -449,134 +671,196 @@ impl Widget for ClockWidget {
     fn get_type(&self) -> WidgetType {
         WidgetType::Clock
     }
-    
+
     fn get_name(&self) -> String {
         "Clock".to_string()
     }
-    
+
     fn get_description(&self) -> String {
         "Displays the current time".to_string()
     }
-    
+
     fn get_settings(&self) -> HashMap<String, String> {
         self.settings.clone()
     }
-    
+
     fn update_settings(&mut self, settings: HashMap<String, String>) -> AppResult<()> {
         self.settings = settings;
         Ok(())
     }
-    
+
     fn render(&self, ui: &mut egui::Ui) -> AppResult<()> {
         let now = Local::now();
         let default_time_format = "%H:%M:%S".to_string();
         let default_date_format = "%Y-%m-%d".to_string();
-        let time_format = self.settings.get("time_format").unwrap_or(&default_time_format);
-        let date_format = self.settings.get("date_format").unwrap_or(&default_date_format);
-        
+        let time_format = self
+            .settings
+            .get("time_format")
+            .unwrap_or(&default_time_format);
+        let date_format = self
+            .settings
+            .get("date_format")
+            .unwrap_or(&default_date_format);
+
         let time_str = now.format(time_format).to_string();
         let date_str = now.format(date_format).to_string();
-        
+
         ui.horizontal(|ui| {
             ui.label(&time_str);
             ui.label(&date_str);
         });
-        
+
         Ok(())
     }
-    
+
     fn update(&mut self) -> AppResult<()> {
         // Nothing to update
         Ok(())
     }
+
+    fn update_interval(&self) -> Option<StdDuration> {
+        // Drawn from the current time when rendered
+        None
+    }
 }
 
 /// Weather widget
 pub struct WeatherWidget {
     /// Widget settings
     settings: HashMap<String, String>,
-    
+
     /// Current weather data
-    weather_data: Option<WeatherData>,
-}
+    weather_data: Option<Weather>,
 
-/// Weather data
-#[derive(Debug, Clone)]
-#[allow(dead_code)]
-struct WeatherData {
-    /// Temperature in Celsius
-    temperature: f32,
-    
-    /// Weather condition
-    condition: String,
-    
-    /// Weather icon
-    icon: String,
+    /// Shared HTTP client
+    http_client: Arc<HttpClient>,
+
+    /// Last fetch attempt
+    last_fetch: Option<Instant>,
+
+    /// Status message shown when data is missing or stale
+    status: Option<String>,
 }
 
 impl WeatherWidget {
     /// Create a new weather widget
     pub fn new(settings: HashMap<String, String>) -> Self {
+        let http_client = HttpClient::shared();
+        http_client.set_rate_limit(weather::PROVIDER, RateLimit::per_minute(10));
+
         Self {
             settings,
             weather_data: None,
+            http_client,
+            last_fetch: None,
+            status: None,
         }
     }
+
+    /// Get the refresh interval
+    fn refresh_interval(&self) -> StdDuration {
+        let minutes = self
+            .settings
+            .get("refresh_minutes")
+            .and_then(|m| m.parse::<u64>().ok())
+            .unwrap_or(15)
+            .max(1);
+        StdDuration::from_secs(minutes * 60)
+    }
 }
 
 impl Widget for WeatherWidget {
     fn get_type(&self) -> WidgetType {
         WidgetType::Weather
     }
-    
+
     fn get_name(&self) -> String {
         "Weather".to_string()
     }
-    
+
     fn get_description(&self) -> String {
         "Displays the current weather".to_string()
     }
-    
+
     fn get_settings(&self) -> HashMap<String, String> {
         self.settings.clone()
     }
-    
+
     fn update_settings(&mut self, settings: HashMap<String, String>) -> AppResult<()> {
         self.settings = settings;
+        self.last_fetch = None;
         Ok(())
     }
-    
+
     fn render(&self, ui: &mut egui::Ui) -> AppResult<()> {
         if let Some(weather) = &self.weather_data {
             ui.horizontal(|ui| {
-                ui.label(format!("{}°C", weather.temperature));
+                ui.label(format!("{:.1}°C", weather.temperature));
                 ui.label(&weather.condition);
             });
+            if let Some(status) = &self.status {
+                ui.small(status);
+            }
         } else {
-            ui.label("Weather data not available");
+            ui.label(
+                self.status
+                    .as_deref()
+                    .unwrap_or("Weather data not available"),
+            );
         }
-        
+
         Ok(())
     }
-    
+
     fn update(&mut self) -> AppResult<()> {
-        // In a real implementation, this would fetch weather data from an API
-        // For now, we'll just use dummy data
-        self.weather_data = Some(WeatherData {
-            temperature: 22.5,
-            condition: "Sunny".to_string(),
-            icon: "☀️".to_string(),
-        });
-        
+        if self
+            .last_fetch
+            .map_or(false, |t| t.elapsed() < self.refresh_interval())
+        {
+            return Ok(());
+        }
+        self.last_fetch = Some(Instant::now());
+
+        let api_key = self.settings.get("api_key").cloned().unwrap_or_default();
+        let location = self.settings.get("location").cloned().unwrap_or_default();
+        if api_key.is_empty() || location.is_empty() {
+            self.status = Some("Set an API key and location to show the weather".to_string());
+            return Ok(());
+        }
+
+        match weather::fetch(&self.http_client, &api_key, &location) {
+            Ok((data, from_cache)) => {
+                self.weather_data = Some(data);
+                self.status = if from_cache && self.http_client.is_offline() {
+                    Some("Offline - showing last known weather".to_string())
+                } else {
+                    None
+                };
+            }
+            Err(e) => {
+                // Keep the last known data; the HTTP client logs connectivity changes once
+                debug!("Weather update failed: {}", e);
+                self.status = Some(if self.http_client.is_offline() {
+                    "Offline".to_string()
+                } else {
+                    "Weather update failed".to_string()
+                });
+            }
+        }
+
         Ok(())
     }
+
+    fn update_interval(&self) -> Option<StdDuration> {
+        Some(self.refresh_interval())
+    }
 }
 
 /// System monitor widget
 pub struct SystemMonitorWidget {
     /// Widget settings
     settings: HashMap<String, String>,
-    
+
     /// System data
     system_data: SystemData,
 }
@@ -586,10 +870,10 @@ pub struct SystemMonitorWidget {
 struct SystemData {
     /// CPU usage in percent
     cpu_usage: f32,
-    
+
     /// Memory usage in percent
     memory_usage: f32,
-    
+
     /// Disk usage in percent
     disk_usage: f32,
 }
@@ -612,41 +896,41 @@ impl Widget for SystemMonitorWidget {
     fn get_type(&self) -> WidgetType {
         WidgetType::SystemMonitor
     }
-    
+
     fn get_name(&self) -> String {
         "System Monitor".to_string()
     }
-    
+
     fn get_description(&self) -> String {
         "Displays system resource usage".to_string()
     }
-    
+
     fn get_settings(&self) -> HashMap<String, String> {
         self.settings.clone()
     }
-    
+
     fn update_settings(&mut self, settings: HashMap<String, String>) -> AppResult<()> {
         self.settings = settings;
         Ok(())
     }
-    
+
     fn render(&self, ui: &mut egui::Ui) -> AppResult<()> {
         ui.horizontal(|ui| {
             ui.label(format!("CPU: {:.1}%", self.system_data.cpu_usage));
             ui.label(format!("RAM: {:.1}%", self.system_data.memory_usage));
             ui.label(format!("Disk: {:.1}%", self.system_data.disk_usage));
         });
-        
+
         Ok(())
     }
-    
+
     fn update(&mut self) -> AppResult<()> {
         // In a real implementation, this would fetch system data
         // For now, we'll just use dummy data
         self.system_data.cpu_usage = 25.5;
         self.system_data.memory_usage = 45.2;
         self.system_data.disk_usage = 60.8;
-        
+
         Ok(())
     }
 }
@@ -655,12 +939,30 @@ impl Widget for SystemMonitorWidget {
 pub struct CalendarWidget {
     /// Widget settings
     settings: HashMap<String, String>,
+
+    /// Upcoming events from the subscribed calendars
+    events: Vec<Event>,
+
+    /// Status message shown when there are no events
+    status: Option<String>,
 }
 
 impl CalendarWidget {
     /// Create a new calendar widget
     pub fn new(settings: HashMap<String, String>) -> Self {
-        Self { settings }
+        Self {
+            settings,
+            events: Vec::new(),
+            status: None,
+        }
+    }
+
+    /// Get the most events to list
+    fn max_events(&self) -> usize {
+        self.settings
+            .get("max_events")
+            .and_then(|m| m.parse().ok())
+            .unwrap_or(5)
     }
 }
 
@@ -668,49 +970,86 @@ impl Widget for CalendarWidget {
     fn get_type(&self) -> WidgetType {
         WidgetType::Calendar
     }
-    
+
     fn get_name(&self) -> String {
         "Calendar".to_string()
     }
-    
+
     fn get_description(&self) -> String {
         "Displays a calendar".to_string()
     }
-    
+
     fn get_settings(&self) -> HashMap<String, String> {
         self.settings.clone()
     }
-    
+
     fn update_settings(&mut self, settings: HashMap<String, String>) -> AppResult<()> {
         self.settings = settings;
         Ok(())
     }
-    
+
     fn render(&self, ui: &mut egui::Ui) -> AppResult<()> {
-        let now = Local::now();
-        let month = now.month();
-        let year = now.year();
-        
-        ui.label(format!("{} {}", month, year));
-        
-        // In a real implementation, this would render a calendar
-        // For now, we'll just display the current date
-        ui.label(format!("Today: {}", now.format("%Y-%m-%d")));
-        
+        let now = Local::now().naive_local();
+        let today = now.date();
+
+        ui.strong(now.format("%B %Y").to_string());
+        ui.label(format!("Today: {}", now.format("%A, %Y-%m-%d")));
+        ui.separator();
+
+        let upcoming: Vec<&Event> = self
+            .events
+            .iter()
+            .filter(|event| event.end > now)
+            .take(self.max_events())
+            .collect();
+        if upcoming.is_empty() {
+            ui.label(self.status.as_deref().unwrap_or("No upcoming events"));
+            return Ok(());
+        }
+
+        for event in upcoming {
+            let day = match (event.start.date() - today).num_days() {
+                days if days <= 0 => "Today".to_string(),
+                1 => "Tomorrow".to_string(),
+                _ => event.start.format("%a %d").to_string(),
+            };
+            let time = if event.all_day {
+                "All day".to_string()
+            } else if event.is_on(now) {
+                format!("until {}", event.end.format("%H:%M"))
+            } else {
+                event.start.format("%H:%M").to_string()
+            };
+            ui.horizontal(|ui| {
+                ui.label(format!("{} {}", day, time));
+                ui.label(&event.summary);
+            });
+        }
+        if let Some(status) = &self.status {
+            ui.small(status);
+        }
+
         Ok(())
     }
-    
+
     fn update(&mut self) -> AppResult<()> {
-        // Nothing to update
+        let calendar = Calendar::shared();
+        self.events = calendar.upcoming();
+        self.status = calendar.status();
         Ok(())
     }
+
+    fn update_interval(&self) -> Option<StdDuration> {
+        // The calendar fetches on its own refresh time; this keeps the list current
+        Some(StdDuration::from_secs(60))
+    }
 }
 
 /// Notes widget
 pub struct NotesWidget {
     /// Widget settings
     settings: HashMap<String, String>,
-    
+
     /// Notes content
     notes: String,
 }
@@ -719,11 +1058,8 @@ impl NotesWidget {
     /// Create a new notes widget
     pub fn new(settings: HashMap<String, String>) -> Self {
         let notes = settings.get("content").unwrap_or(&"".to_string()).clone();
-        
-        Self {
-            settings,
-            notes,
-        }
+
+        Self { settings, notes }
     }
 }
 
@@ -731,40 +1067,44 @@ impl Widget for NotesWidget {
     fn get_type(&self) -> WidgetType {
         WidgetType::Notes
     }
-    
+
     fn get_name(&self) -> String {
         "Notes".to_string()
     }
-    
+
     fn get_description(&self) -> String {
         "Displays notes on your desktop".to_string()
     }
-    
+
     fn get_settings(&self) -> HashMap<String, String> {
         let mut settings = self.settings.clone();
         settings.insert("content".to_string(), self.notes.clone());
         settings
     }
-    
+
     fn update_settings(&mut self, settings: HashMap<String, String>) -> AppResult<()> {
         // Update notes content if provided
         if let Some(content) = settings.get("content") {
             self.notes = content.clone();
         }
-        
+
         // Update other settings
         self.settings = settings;
-        
+
         Ok(())
     }
-    
+
     fn render(&self, ui: &mut egui::Ui) -> AppResult<()> {
         // Get settings
-        let font_size = self.settings.get("font_size")
+        let font_size = self
+            .settings
+            .get("font_size")
             .and_then(|s| s.parse::<f32>().ok())
             .unwrap_or(14.0);
-        
-        let bg_color = self.settings.get("bg_color")
+
+        let bg_color = self
+            .settings
+            .get("bg_color")
             .map(|c| {
                 // Parse hex color (#RRGGBB)
                 if c.starts_with('#') && c.len() == 7 {
@@ -777,21 +1117,28 @@ impl Widget for NotesWidget {
                 }
             })
             .unwrap_or(egui::Color32::WHITE);
-        
+
         // Create a frame with the background color
         let frame = egui::Frame::none()
             .fill(bg_color)
             .rounding(5.0)
-            .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgba_premultiplied(0, 0, 0, 50)));
-        
+            .stroke(egui::Stroke::new(
+                1.0,
+                egui::Color32::from_rgba_premultiplied(0, 0, 0, 50),
+            ));
+
         frame.show(ui, |ui| {
             // Set the font size
             let style = ui.style_mut();
-            style.text_styles.get_mut(&egui::TextStyle::Body).unwrap().size = font_size;
-            
+            style
+                .text_styles
+                .get_mut(&egui::TextStyle::Body)
+                .unwrap()
+                .size = font_size;
+
             // Display the notes content
             ui.label("Notes:");
-            
+
             // Create a text area for the notes
             let mut notes = self.notes.clone();
             if ui.text_edit_multiline(&mut notes).changed() {
@@ -800,12 +1147,177 @@ impl Widget for NotesWidget {
                 debug!("Notes content changed");
             }
         });
-        
+
         Ok(())
     }
-    
+
     fn update(&mut self) -> AppResult<()> {
         // Nothing to update
         Ok(())
     }
-} 
\ No newline at end of file
+
+    fn update_interval(&self) -> Option<StdDuration> {
+        // Notes only change when edited
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::secrets::SecretBackend;
+
+    /// Widget that counts its updates
+    struct CountingWidget {
+        updates: u32,
+        interval: Option<StdDuration>,
+    }
+
+    impl Widget for CountingWidget {
+        fn get_type(&self) -> WidgetType {
+            WidgetType::Custom("counting".to_string())
+        }
+        fn get_name(&self) -> String {
+            "Counting".to_string()
+        }
+        fn get_description(&self) -> String {
+            String::new()
+        }
+        fn get_settings(&self) -> HashMap<String, String> {
+            HashMap::new()
+        }
+        fn update_settings(&mut self, _settings: HashMap<String, String>) -> AppResult<()> {
+            Ok(())
+        }
+        fn render(&self, _ui: &mut egui::Ui) -> AppResult<()> {
+            Ok(())
+        }
+        fn update(&mut self) -> AppResult<()> {
+            self.updates += 1;
+            Ok(())
+        }
+        fn update_interval(&self) -> Option<StdDuration> {
+            self.interval
+        }
+    }
+
+    fn scheduled(interval: Option<StdDuration>) -> ScheduledWidget {
+        ScheduledWidget {
+            widget: Box::new(CountingWidget {
+                updates: 0,
+                interval,
+            }),
+            next_update: None,
+        }
+    }
+
+    #[test]
+    fn test_scheduled_widget_updates_when_due() {
+        let start = Instant::now();
+        let mut widget = scheduled(Some(StdDuration::from_secs(5)));
+
+        assert_eq!(
+            widget.update_if_due(start, StdDuration::ZERO),
+            Some(StdDuration::from_secs(5))
+        );
+        assert_eq!(
+            widget.update_if_due(start + StdDuration::from_secs(2), StdDuration::ZERO),
+            Some(StdDuration::from_secs(3))
+        );
+        // The battery interval stretches the next wait
+        assert_eq!(
+            widget.update_if_due(
+                start + StdDuration::from_secs(5),
+                StdDuration::from_secs(10)
+            ),
+            Some(StdDuration::from_secs(10))
+        );
+
+        let mut idle = scheduled(None);
+        assert_eq!(idle.update_if_due(start, StdDuration::ZERO), None);
+        assert!(idle.next_update.is_none());
+    }
+
+    fn weather_config(api_key: Option<&str>) -> WidgetConfig {
+        WidgetConfig {
+            widget_type: WidgetType::Weather,
+            position: WidgetPosition::TopRight,
+            size: WidgetSize::Medium,
+            settings: api_key
+                .map(|key| ("api_key".to_string(), key.to_string()))
+                .into_iter()
+                .collect(),
+            enabled: true,
+            background_color: None,
+            opacity: None,
+        }
+    }
+
+    /// Save the in-memory configs the way `save_widgets` does, returning what lands in the file
+    fn save(
+        manager: &WidgetManager,
+        saved: &HashMap<String, WidgetConfig>,
+    ) -> HashMap<String, WidgetConfig> {
+        let mut configs = manager.get_widget_configs();
+        store_secrets(
+            manager.secret_store.as_ref().unwrap(),
+            &mut configs,
+            saved.keys(),
+        )
+        .unwrap();
+        configs
+    }
+
+    #[test]
+    fn test_widget_secrets_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(SecretStore::with_backend(
+            SecretBackend::EncryptedFile,
+            dir.path().to_path_buf(),
+        ));
+        let mut manager = WidgetManager::new();
+        manager.set_secret_store(store.clone());
+        let key = widget_secret_key("weather", "api_key");
+
+        manager
+            .widget_configs
+            .lock()
+            .unwrap()
+            .insert("weather".to_string(), weather_config(Some("secret")));
+        let saved = save(&manager, &HashMap::new());
+        assert!(!saved["weather"].settings.contains_key("api_key"));
+        assert_eq!(store.get(&key).unwrap().as_deref(), Some("secret"));
+
+        // Loading the saved file gets the key back from the store
+        let resolved = manager.resolve_secret_settings("weather", &saved["weather"].settings);
+        assert_eq!(resolved.get("api_key").map(String::as_str), Some("secret"));
+
+        // Saving again without touching the key keeps it
+        *manager.widget_configs.lock().unwrap() = saved.clone();
+        let saved = save(&manager, &saved);
+        assert_eq!(store.get(&key).unwrap().as_deref(), Some("secret"));
+
+        // Clearing the key deletes it, so it does not come back on load
+        manager
+            .widget_configs
+            .lock()
+            .unwrap()
+            .insert("weather".to_string(), weather_config(Some("")));
+        let saved = save(&manager, &saved);
+        assert_eq!(store.get(&key).unwrap(), None);
+        assert!(!manager
+            .resolve_secret_settings("weather", &saved["weather"].settings)
+            .contains_key("api_key"));
+
+        // Dropping a widget deletes its key
+        manager
+            .widget_configs
+            .lock()
+            .unwrap()
+            .insert("weather".to_string(), weather_config(Some("secret")));
+        let saved = save(&manager, &saved);
+        manager.widget_configs.lock().unwrap().clear();
+        save(&manager, &saved);
+        assert_eq!(store.get(&key).unwrap(), None);
+    }
+}
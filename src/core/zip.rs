@@ -0,0 +1,586 @@
+//! Just enough of the zip format for wallpaper packs
+//!
+//! Entries are written uncompressed: wallpapers are images and videos that
+//! are compressed already, and storing them lets files of any size be
+//! streamed in and out without holding them in memory. Written archives are
+//! limited to 4 GiB. Reading also accepts Deflate entries and zip64 records,
+//! so packs zipped by hand with the usual tools open too.
+use crate::core::{AppError, AppResult};
+use flate2::read::DeflateDecoder;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Component, Path, PathBuf};
+
+/// Signatures of the records
+const LOCAL_HEADER: u32 = 0x0403_4b50;
+const CENTRAL_HEADER: u32 = 0x0201_4b50;
+const END_OF_DIRECTORY: u32 = 0x0605_4b50;
+const ZIP64_END_OF_DIRECTORY: u32 = 0x0606_4b50;
+const ZIP64_LOCATOR: u32 = 0x0706_4b50;
+
+/// Size of the end of central directory record without its comment
+const END_SIZE: u64 = 22;
+
+/// Size of the zip64 end of central directory locator
+const LOCATOR_SIZE: usize = 20;
+
+/// Size of the zip64 end of central directory record read
+const ZIP64_END_SIZE: usize = 56;
+
+/// ID of the extra field holding zip64 sizes and offsets
+const ZIP64_EXTRA: u16 = 0x0001;
+
+/// Compression methods read
+const STORED: u16 = 0;
+const DEFLATED: u16 = 8;
+
+/// Flag marking encrypted entries
+const ENCRYPTED: u16 = 1;
+
+/// Version needed to extract stored entries
+const VERSION: u16 = 10;
+
+/// Flag marking names as UTF-8
+const UTF8_NAMES: u16 = 1 << 11;
+
+/// Compression method (stored), time and date (1980-01-01) of every entry
+const STORED_1980: [u8; 6] = [0, 0, 0, 0, 0x21, 0];
+
+/// Entry written, kept for the central directory
+struct Written {
+    name: String,
+    crc: u32,
+    size: u32,
+    offset: u32,
+}
+
+/// Entry of an archive being read
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    /// Path inside the archive, with forward slashes
+    pub name: String,
+
+    /// Size in bytes
+    pub size: u64,
+
+    /// Size in the archive, once compressed
+    compressed: u64,
+
+    /// CRC-32 of the content
+    crc: u32,
+
+    /// Offset of the local header
+    offset: u64,
+
+    /// Compression method, 0 when stored
+    method: u16,
+
+    /// General purpose flags
+    flags: u16,
+}
+
+/// Writes an archive of stored entries
+pub struct ZipWriter {
+    out: BufWriter<File>,
+    offset: u64,
+    written: Vec<Written>,
+}
+
+impl ZipWriter {
+    /// Create an archive, replacing any file at the path
+    pub fn create(path: &Path) -> AppResult<Self> {
+        Ok(Self {
+            out: BufWriter::new(File::create(path)?),
+            offset: 0,
+            written: Vec::new(),
+        })
+    }
+
+    /// Add an entry holding bytes
+    pub fn add_bytes(&mut self, name: &str, data: &[u8]) -> AppResult<()> {
+        self.add(name, crc32(0, data), data.len() as u64, &mut &data[..])
+    }
+
+    /// Add an entry copied from a file
+    ///
+    /// The file is read twice, once for its checksum, so it isn't held in memory.
+    pub fn add_file(&mut self, name: &str, path: &Path) -> AppResult<()> {
+        let mut crc = 0;
+        let mut size = 0u64;
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut buffer = [0u8; 64 * 1024];
+        loop {
+            let read = reader.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            crc = crc32(crc, &buffer[..read]);
+            size += read as u64;
+        }
+        reader.seek(SeekFrom::Start(0))?;
+        self.add(name, crc, size, &mut reader.take(size))
+    }
+
+    /// Write the central directory and close the archive
+    pub fn finish(mut self) -> AppResult<()> {
+        let directory = u32_field(self.offset, "Pack")?;
+        let mut size = 0u64;
+        for entry in &self.written {
+            let mut header = Vec::with_capacity(46 + entry.name.len());
+            header.extend(CENTRAL_HEADER.to_le_bytes());
+            header.extend(VERSION.to_le_bytes());
+            header.extend(VERSION.to_le_bytes());
+            header.extend(UTF8_NAMES.to_le_bytes());
+            header.extend(STORED_1980);
+            header.extend(entry.crc.to_le_bytes());
+            header.extend(entry.size.to_le_bytes());
+            header.extend(entry.size.to_le_bytes());
+            header.extend((entry.name.len() as u16).to_le_bytes());
+            header.extend([0u8; 12]);
+            header.extend(entry.offset.to_le_bytes());
+            header.extend(entry.name.as_bytes());
+            self.out.write_all(&header)?;
+            size += header.len() as u64;
+        }
+
+        let count = u16::try_from(self.written.len())
+            .map_err(|_| AppError::Other("Too many files for a pack".to_string()))?;
+        let mut end = Vec::with_capacity(END_SIZE as usize);
+        end.extend(END_OF_DIRECTORY.to_le_bytes());
+        end.extend([0u8; 4]);
+        end.extend(count.to_le_bytes());
+        end.extend(count.to_le_bytes());
+        end.extend(u32_field(size, "Pack")?.to_le_bytes());
+        end.extend(directory.to_le_bytes());
+        end.extend([0u8; 2]);
+        self.out.write_all(&end)?;
+        self.out.flush()?;
+        Ok(())
+    }
+
+    /// Write a local header and the content of an entry
+    fn add(&mut self, name: &str, crc: u32, size: u64, content: &mut dyn Read) -> AppResult<()> {
+        let offset = u32_field(self.offset, "Pack")?;
+        let size = u32_field(size, name)?;
+        let mut header = Vec::with_capacity(30 + name.len());
+        header.extend(LOCAL_HEADER.to_le_bytes());
+        header.extend(VERSION.to_le_bytes());
+        header.extend(UTF8_NAMES.to_le_bytes());
+        header.extend(STORED_1980);
+        header.extend(crc.to_le_bytes());
+        header.extend(size.to_le_bytes());
+        header.extend(size.to_le_bytes());
+        header.extend((name.len() as u16).to_le_bytes());
+        header.extend([0u8; 2]);
+        header.extend(name.as_bytes());
+        self.out.write_all(&header)?;
+
+        let copied = io::copy(content, &mut self.out)?;
+        if copied != size as u64 {
+            return Err(AppError::Other(format!(
+                "{} changed while it was packed",
+                name
+            )));
+        }
+        self.offset += header.len() as u64 + copied;
+        self.written.push(Written {
+            name: name.to_string(),
+            crc,
+            size,
+            offset,
+        });
+        Ok(())
+    }
+}
+
+/// Reads the entries of an archive
+pub struct ZipReader {
+    file: File,
+    entries: Vec<Entry>,
+}
+
+impl ZipReader {
+    /// Open an archive, reading its central directory
+    pub fn open(path: &Path) -> AppResult<Self> {
+        let mut file = File::open(path)?;
+        let length = file.metadata()?.len();
+        let invalid = || AppError::Other(format!("{} is not a zip archive", path.display()));
+
+        // The end record is last, followed by a comment of up to 64 KiB
+        let tail_start = length.saturating_sub(END_SIZE + u16::MAX as u64);
+        let mut tail = Vec::new();
+        file.seek(SeekFrom::Start(tail_start))?;
+        (&mut file)
+            .take(length - tail_start)
+            .read_to_end(&mut tail)?;
+        let end = (0..tail.len().saturating_sub(END_SIZE as usize - 1))
+            .rev()
+            .find(|&at| read_u32(&tail, at) == Some(END_OF_DIRECTORY))
+            .ok_or_else(invalid)?;
+        let mut count = read_u16(&tail, end + 10).ok_or_else(invalid)? as u64;
+        let mut size = read_u32(&tail, end + 12).ok_or_else(invalid)? as u64;
+        let mut offset = read_u32(&tail, end + 16).ok_or_else(invalid)? as u64;
+
+        // Zip64 archives keep the real values in a record found through the locator before the end record
+        let locator = end
+            .checked_sub(LOCATOR_SIZE)
+            .filter(|&at| read_u32(&tail, at) == Some(ZIP64_LOCATOR));
+        if let Some(locator) = locator {
+            let mut record = [0u8; ZIP64_END_SIZE];
+            file.seek(SeekFrom::Start(
+                read_u64(&tail, locator + 8).ok_or_else(invalid)?,
+            ))?;
+            file.read_exact(&mut record)?;
+            if read_u32(&record, 0) != Some(ZIP64_END_OF_DIRECTORY) {
+                return Err(invalid());
+            }
+            count = read_u64(&record, 32).ok_or_else(invalid)?;
+            size = read_u64(&record, 40).ok_or_else(invalid)?;
+            offset = read_u64(&record, 48).ok_or_else(invalid)?;
+        }
+        if offset.saturating_add(size) > length {
+            return Err(invalid());
+        }
+
+        let mut directory = vec![0u8; size as usize];
+        file.seek(SeekFrom::Start(offset))?;
+        file.read_exact(&mut directory)?;
+        // Every header takes at least 46 bytes, which bounds a corrupt count
+        let mut entries = Vec::with_capacity(count.min(size / 46) as usize);
+        let mut at = 0;
+        for _ in 0..count {
+            if read_u32(&directory, at) != Some(CENTRAL_HEADER) {
+                return Err(invalid());
+            }
+            let field16 = |offset| read_u16(&directory, at + offset).ok_or_else(invalid);
+            let field32 = |offset| read_u32(&directory, at + offset).ok_or_else(invalid);
+            let name_length = field16(28)? as usize;
+            let extra_length = field16(30)? as usize;
+            let skipped = name_length + extra_length + field16(32)? as usize;
+            let name = directory
+                .get(at + 46..at + 46 + name_length)
+                .ok_or_else(invalid)?;
+            let extra = directory
+                .get(at + 46 + name_length..at + 46 + name_length + extra_length)
+                .ok_or_else(invalid)?;
+
+            // Values too large for their field are in the zip64 extra field, in this order
+            let mut values = [
+                field32(24)? as u64,
+                field32(20)? as u64,
+                field32(42)? as u64,
+            ];
+            let mut zip64 = zip64_values(extra).into_iter();
+            for value in values.iter_mut().filter(|value| **value == u32::MAX as u64) {
+                *value = zip64.next().ok_or_else(invalid)?;
+            }
+            let [size, compressed, offset] = values;
+
+            entries.push(Entry {
+                name: String::from_utf8_lossy(name).to_string(),
+                size,
+                compressed,
+                crc: field32(16)?,
+                offset,
+                method: field16(10)?,
+                flags: field16(8)?,
+            });
+            at += 46 + skipped;
+        }
+        Ok(Self { file, entries })
+    }
+
+    /// Get the entries, directories included
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// Find an entry by name
+    pub fn entry(&self, name: &str) -> Option<&Entry> {
+        self.entries.iter().find(|entry| entry.name == name)
+    }
+
+    /// Read an entry into memory, refusing ones larger than `limit` bytes
+    pub fn read(&mut self, entry: &Entry, limit: u64) -> AppResult<Vec<u8>> {
+        if entry.size > limit {
+            return Err(AppError::Other(format!("{} is too large", entry.name)));
+        }
+        let mut data = Vec::with_capacity(entry.size as usize);
+        self.copy(entry, &mut data)?;
+        Ok(data)
+    }
+
+    /// Extract an entry under a folder, at the path its name gives
+    ///
+    /// Names leaving the folder, like `../x` or `/etc/x`, are refused.
+    pub fn extract(&mut self, entry: &Entry, folder: &Path) -> AppResult<PathBuf> {
+        let path = folder.join(safe_path(&entry.name)?);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut out = BufWriter::new(File::create(&path)?);
+        self.copy(entry, &mut out)?;
+        out.flush()?;
+        Ok(path)
+    }
+
+    /// Copy an entry's content, checking its checksum
+    fn copy(&mut self, entry: &Entry, out: &mut dyn Write) -> AppResult<()> {
+        if entry.flags & ENCRYPTED != 0 {
+            return Err(AppError::Other(format!("{} is encrypted", entry.name)));
+        }
+        if entry.method != STORED && entry.method != DEFLATED {
+            return Err(AppError::Other(format!(
+                "{} is compressed with method {}; only stored and Deflate entries are read",
+                entry.name, entry.method
+            )));
+        }
+
+        let mut header = [0u8; 30];
+        self.file.seek(SeekFrom::Start(entry.offset))?;
+        self.file.read_exact(&mut header)?;
+        if read_u32(&header, 0) != Some(LOCAL_HEADER) {
+            return Err(AppError::Other(format!("Corrupt entry {}", entry.name)));
+        }
+        let skipped =
+            read_u16(&header, 26).unwrap_or(0) as i64 + read_u16(&header, 28).unwrap_or(0) as i64;
+        self.file.seek(SeekFrom::Current(skipped))?;
+
+        let content = (&mut self.file).take(entry.compressed);
+        let reader: Box<dyn Read + '_> = match entry.method {
+            DEFLATED => Box::new(DeflateDecoder::new(content)),
+            _ => Box::new(content),
+        };
+        // Nothing past the size the directory gives is read, however much a corrupt entry inflates to
+        let mut reader = reader.take(entry.size);
+        let mut buffer = [0u8; 64 * 1024];
+        let mut crc = 0;
+        let mut size = 0u64;
+        loop {
+            let read = reader.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            crc = crc32(crc, &buffer[..read]);
+            size += read as u64;
+            out.write_all(&buffer[..read])?;
+        }
+        if crc != entry.crc || size != entry.size {
+            return Err(AppError::Other(format!(
+                "{} is corrupt (checksum mismatch)",
+                entry.name
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Turn an entry name into a relative path, refusing ones that leave the folder
+pub fn safe_path(name: &str) -> AppResult<PathBuf> {
+    let path = Path::new(name);
+    let safe = !name.is_empty()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+    if !safe || name.contains('\\') {
+        return Err(AppError::Other(format!("Refusing to extract {}", name)));
+    }
+    Ok(path.to_path_buf())
+}
+
+/// Continue a CRC-32 over more bytes, starting from 0
+fn crc32(crc: u32, data: &[u8]) -> u32 {
+    let mut crc = !crc;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xedb8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+/// Check that a size or offset fits the 32-bit fields
+fn u32_field(value: u64, what: &str) -> AppResult<u32> {
+    u32::try_from(value)
+        .map_err(|_| AppError::Other(format!("{} is over the 4 GiB a pack can hold", what)))
+}
+
+/// Get the values of the zip64 extra field among an entry's extra fields
+fn zip64_values(extra: &[u8]) -> Vec<u64> {
+    let mut at = 0;
+    while let (Some(id), Some(length)) = (read_u16(extra, at), read_u16(extra, at + 2)) {
+        let data = extra
+            .get(at + 4..at + 4 + length as usize)
+            .unwrap_or_default();
+        if id == ZIP64_EXTRA {
+            return data
+                .chunks_exact(8)
+                .filter_map(|bytes| read_u64(bytes, 0))
+                .collect();
+        }
+        at += 4 + length as usize;
+    }
+    Vec::new()
+}
+
+/// Read a little-endian u16
+fn read_u16(data: &[u8], at: usize) -> Option<u16> {
+    data.get(at..at + 2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+/// Read a little-endian u32
+fn read_u32(data: &[u8], at: usize) -> Option<u32> {
+    data.get(at..at + 4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// Read a little-endian u64
+fn read_u64(data: &[u8], at: usize) -> Option<u64> {
+    data.get(at..at + 8)
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::DeflateEncoder;
+    use flate2::Compression;
+    use tempfile::tempdir;
+
+    /// Write an archive of one Deflate entry the way zip tools do, with zip64 records if asked
+    fn write_deflated(path: &Path, name: &str, data: &[u8], zip64: bool) {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        let compressed = encoder.finish().unwrap();
+        let crc = crc32(0, data);
+
+        let mut out = Vec::new();
+        out.extend(LOCAL_HEADER.to_le_bytes());
+        out.extend([20, 0, 0, 0, 8, 0, 0, 0, 0x21, 0]);
+        out.extend(crc.to_le_bytes());
+        out.extend((compressed.len() as u32).to_le_bytes());
+        out.extend((data.len() as u32).to_le_bytes());
+        out.extend((name.len() as u16).to_le_bytes());
+        out.extend([0u8; 2]);
+        out.extend(name.as_bytes());
+        out.extend(&compressed);
+
+        let directory = out.len() as u64;
+        let (sizes, extra) = if zip64 {
+            let extra = [
+                &ZIP64_EXTRA.to_le_bytes()[..],
+                &16u16.to_le_bytes(),
+                &(data.len() as u64).to_le_bytes(),
+                &(compressed.len() as u64).to_le_bytes(),
+            ]
+            .concat();
+            ([u32::MAX, u32::MAX], extra)
+        } else {
+            ([compressed.len() as u32, data.len() as u32], Vec::new())
+        };
+        out.extend(CENTRAL_HEADER.to_le_bytes());
+        out.extend([45, 0, 45, 0, 0, 0, 8, 0, 0, 0, 0x21, 0]);
+        out.extend(crc.to_le_bytes());
+        out.extend(sizes[0].to_le_bytes());
+        out.extend(sizes[1].to_le_bytes());
+        out.extend((name.len() as u16).to_le_bytes());
+        out.extend((extra.len() as u16).to_le_bytes());
+        out.extend([0u8; 14]);
+        out.extend(name.as_bytes());
+        out.extend(&extra);
+        let size = out.len() as u64 - directory;
+
+        if zip64 {
+            let record = out.len() as u64;
+            out.extend(ZIP64_END_OF_DIRECTORY.to_le_bytes());
+            out.extend((ZIP64_END_SIZE as u64 - 12).to_le_bytes());
+            out.extend([45, 0, 45, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+            for value in [1, 1, size, directory] {
+                out.extend(value.to_le_bytes());
+            }
+            out.extend(ZIP64_LOCATOR.to_le_bytes());
+            out.extend([0u8; 4]);
+            out.extend(record.to_le_bytes());
+            out.extend(1u32.to_le_bytes());
+        }
+        let (count, size, directory) = if zip64 {
+            (u16::MAX, u32::MAX, u32::MAX)
+        } else {
+            (1, size as u32, directory as u32)
+        };
+        out.extend(END_OF_DIRECTORY.to_le_bytes());
+        out.extend([0u8; 4]);
+        out.extend(count.to_le_bytes());
+        out.extend(count.to_le_bytes());
+        out.extend(size.to_le_bytes());
+        out.extend(directory.to_le_bytes());
+        out.extend([0u8; 2]);
+        fs::write(path, out).unwrap();
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("sky.jpg");
+        fs::write(&source, vec![7u8; 200_000]).unwrap();
+
+        let archive = dir.path().join("pack.zip");
+        let mut writer = ZipWriter::create(&archive).unwrap();
+        writer.add_bytes("manifest.json", b"{}").unwrap();
+        writer.add_file("wallpapers/sky.jpg", &source).unwrap();
+        writer.finish().unwrap();
+
+        let mut reader = ZipReader::open(&archive).unwrap();
+        let names: Vec<&str> = reader
+            .entries()
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["manifest.json", "wallpapers/sky.jpg"]);
+        let manifest = reader.entry("manifest.json").unwrap().clone();
+        assert_eq!(reader.read(&manifest, 1024).unwrap(), b"{}");
+        assert!(reader.read(&manifest, 1).is_err());
+
+        let wallpaper = reader.entry("wallpapers/sky.jpg").unwrap().clone();
+        let extracted = reader.extract(&wallpaper, &dir.path().join("out")).unwrap();
+        assert_eq!(extracted, dir.path().join("out/wallpapers/sky.jpg"));
+        assert_eq!(fs::read(extracted).unwrap(), fs::read(&source).unwrap());
+    }
+
+    #[test]
+    fn test_read_deflated() {
+        let dir = tempdir().unwrap();
+        let data = b"{\"name\": \"Misty\"}".repeat(100);
+        for zip64 in [false, true] {
+            let archive = dir.path().join(format!("pack-{}.zip", zip64));
+            write_deflated(&archive, "manifest.json", &data, zip64);
+
+            let mut reader = ZipReader::open(&archive).unwrap();
+            let manifest = reader.entry("manifest.json").unwrap().clone();
+            assert_eq!(manifest.size, data.len() as u64);
+            assert!(manifest.compressed < manifest.size);
+            assert_eq!(reader.read(&manifest, 1 << 20).unwrap(), data);
+        }
+
+        // A size smaller than the content is caught rather than read past
+        let archive = dir.path().join("pack-false.zip");
+        let mut reader = ZipReader::open(&archive).unwrap();
+        let mut manifest = reader.entry("manifest.json").unwrap().clone();
+        manifest.size -= 1;
+        assert!(reader.read(&manifest, 1 << 20).is_err());
+    }
+
+    #[test]
+    fn test_crc32_and_safe_path() {
+        assert_eq!(crc32(0, b"123456789"), 0xcbf4_3926);
+        assert_eq!(crc32(crc32(0, b"1234"), b"56789"), 0xcbf4_3926);
+
+        assert!(safe_path("assets/0/index.html").is_ok());
+        assert!(safe_path("../evil.sh").is_err());
+        assert!(safe_path("/etc/passwd").is_err());
+        assert!(safe_path("assets\\..\\evil").is_err());
+        assert!(ZipReader::open(Path::new(file!())).is_err());
+    }
+}
@@ -0,0 +1,1074 @@
+//! Local HTTP API
+//!
+//! A small JSON API on 127.0.0.1 for integrations and dashboards. Every
+//! request needs the token from the secret store, sent as
+//! `Authorization: Bearer <token>`. Calls are forwarded to the daemon over
+//! the local transport, so the API behaves exactly like the other frontends.
+//!
+//! `/` serves a remote-control page built on the same endpoints, and
+//! `/upload` a page sending a photo to the desktop. With `allow_remote` the
+//! server listens on every interface, so the pages work from a phone on the
+//! LAN.
+use crate::core::config::{WallpaperType, WebhookAction};
+use crate::core::image_pipeline::{Encoding, ImagePipeline};
+use crate::core::{AppError, AppResult, Config, EventLog, SecretStore, WallpaperInfo};
+use crate::daemon::client::DaemonClient;
+use crate::daemon::protocol::{Request, Response};
+use crate::daemon::upload::{self, MAX_UPLOAD, UPLOAD_PATH};
+use crate::daemon::{browser, homeassistant};
+use log::{debug, error, info, warn};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, TcpListener, TcpStream, UdpSocket};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// Secret store key of the API token
+pub const API_TOKEN_SECRET: &str = "api-token";
+
+/// How often the server checks for connections and configuration changes
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Timeout for reading a request and writing the response
+const IO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Largest accepted request body
+const MAX_BODY: usize = 64 * 1024;
+
+/// Largest accepted request line or header
+const MAX_LINE: usize = 8 * 1024;
+
+/// Remote-control page
+const REMOTE_PAGE: &str = include_str!("../../web/remote.html");
+
+/// Photo upload page
+const UPLOAD_PAGE: &str = include_str!("../../web/upload.html");
+
+/// Edge length of the thumbnails served to the remote page
+const THUMBNAIL_SIZE: u32 = 360;
+
+/// Thumbnails kept in memory before the cache is cleared
+const MAX_CACHED_THUMBNAILS: usize = 256;
+
+/// Encoded thumbnails by path, with the modification time they were made from
+type ThumbnailCache = HashMap<PathBuf, (SystemTime, Vec<u8>)>;
+
+/// Get the API token, creating one on first use
+pub fn api_token(secret_store: &SecretStore) -> AppResult<String> {
+    if let Some(token) = secret_store.get(API_TOKEN_SECRET)? {
+        return Ok(token);
+    }
+    regenerate_token(secret_store)
+}
+
+/// Replace the API token with a new random one
+pub fn regenerate_token(secret_store: &SecretStore) -> AppResult<String> {
+    let mut bytes = [0u8; 24];
+    getrandom::fill(&mut bytes)
+        .map_err(|e| AppError::SecretError(format!("Failed to generate API token: {}", e)))?;
+    let token: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    secret_store.set(API_TOKEN_SECRET, &token)?;
+    info!("Generated a new API token");
+    Ok(token)
+}
+
+/// HTTP API server
+pub struct ApiServer {
+    /// Application configuration, for the API settings and the library folders
+    config: Arc<Mutex<Config>>,
+
+    /// Secret store holding the token
+    secret_store: Arc<SecretStore>,
+
+    /// Cached token (None = read it again)
+    token: Arc<Mutex<Option<String>>>,
+
+    /// Server thread
+    server_thread: Option<thread::JoinHandle<()>>,
+
+    /// Whether the server thread is running
+    is_running: Arc<Mutex<bool>>,
+}
+
+impl ApiServer {
+    /// Create a new API server
+    pub fn new(config: &Config, secret_store: Arc<SecretStore>) -> Self {
+        Self {
+            config: Arc::new(Mutex::new(config.clone())),
+            secret_store,
+            token: Arc::new(Mutex::new(None)),
+            server_thread: None,
+            is_running: Arc::new(Mutex::new(false)),
+        }
+    }
+
+    /// Update the configuration
+    ///
+    /// Also re-reads the token, in case it was regenerated.
+    pub fn update_config(&self, config: &Config) {
+        *self.config.lock().unwrap() = config.clone();
+        *self.token.lock().unwrap() = None;
+    }
+
+    /// Start the server thread
+    ///
+    /// The thread idles while the API is disabled and rebinds when the port changes.
+    pub fn start(&mut self) -> AppResult<()> {
+        if *self.is_running.lock().unwrap() {
+            debug!("API server is already running");
+            return Ok(());
+        }
+
+        *self.is_running.lock().unwrap() = true;
+
+        let config = self.config.clone();
+        let secret_store = self.secret_store.clone();
+        let token = self.token.clone();
+        let is_running = self.is_running.clone();
+
+        self.server_thread = Some(thread::spawn(move || {
+            let mut bound: Option<((u16, bool), TcpListener)> = None;
+            let mut failed: Option<(u16, bool)> = None;
+            let thumbnails = Arc::new(Mutex::new(ThumbnailCache::new()));
+            let daemon = DaemonClient::new();
+
+            while *is_running.lock().unwrap() {
+                let api = config.lock().unwrap().api.clone();
+                if !api.enabled {
+                    if bound.take().is_some() {
+                        info!("API server stopped listening");
+                    }
+                    failed = None;
+                    thread::sleep(POLL_INTERVAL);
+                    continue;
+                }
+
+                // Retry a failed address only after the configuration changes
+                let address = (api.port, api.allow_remote);
+                let needs_bind = bound
+                    .as_ref()
+                    .map_or(true, |(bound_address, _)| *bound_address != address);
+                if needs_bind && failed != Some(address) {
+                    bound = None;
+                    match bind(api.port, api.allow_remote) {
+                        Ok(listener) => {
+                            info!(
+                                "API server listening on {}",
+                                listener
+                                    .local_addr()
+                                    .map_or_else(|_| api.port.to_string(), |a| a.to_string())
+                            );
+                            bound = Some((address, listener));
+                            failed = None;
+                        }
+                        Err(e) => {
+                            error!("Failed to bind API port {}: {}", api.port, e);
+                            EventLog::shared().error(
+                                "api",
+                                format!("Failed to listen on port {}", api.port),
+                                e,
+                            );
+                            failed = Some(address);
+                        }
+                    }
+                }
+
+                let listener = match &bound {
+                    Some((_, listener)) => listener,
+                    None => {
+                        thread::sleep(POLL_INTERVAL);
+                        continue;
+                    }
+                };
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        let expected = token.lock().unwrap().clone().or_else(|| {
+                            match api_token(&secret_store) {
+                                Ok(value) => {
+                                    *token.lock().unwrap() = Some(value.clone());
+                                    Some(value)
+                                }
+                                Err(e) => {
+                                    error!("Failed to read API token: {}", e);
+                                    None
+                                }
+                            }
+                        });
+                        let library_config = config.lock().unwrap().clone();
+                        let daemon = daemon.clone();
+                        let thumbnails = thumbnails.clone();
+                        // A slow client or a large upload doesn't hold up the others
+                        thread::spawn(move || {
+                            if let Err(e) = handle_connection(
+                                stream,
+                                expected.as_deref(),
+                                &daemon,
+                                &library_config,
+                                &thumbnails,
+                            ) {
+                                warn!("Failed to handle API request: {}", e);
+                            }
+                        });
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+                    Err(e) => {
+                        warn!("Failed to accept API connection: {}", e);
+                        thread::sleep(POLL_INTERVAL);
+                    }
+                }
+            }
+        }));
+
+        info!("API server started");
+        Ok(())
+    }
+
+    /// Stop the server thread
+    pub fn stop(&mut self) -> AppResult<()> {
+        if !*self.is_running.lock().unwrap() {
+            debug!("API server is not running");
+            return Ok(());
+        }
+
+        *self.is_running.lock().unwrap() = false;
+
+        if let Some(thread) = self.server_thread.take() {
+            thread.join().map_err(|e| {
+                AppError::Other(format!("Failed to join API server thread: {:?}", e))
+            })?;
+        }
+
+        info!("API server stopped");
+        Ok(())
+    }
+}
+
+/// Bind a non-blocking listener on the loopback interface, or every interface for remote access
+fn bind(port: u16, allow_remote: bool) -> io::Result<TcpListener> {
+    let host = if allow_remote {
+        Ipv4Addr::UNSPECIFIED
+    } else {
+        Ipv4Addr::LOCALHOST
+    };
+    let listener = TcpListener::bind((host, port))?;
+    listener.set_nonblocking(true)?;
+    Ok(listener)
+}
+
+/// Get this computer's address on the LAN, for showing the remote page URL
+///
+/// Connecting a UDP socket sends nothing; it only picks the outgoing interface.
+pub fn lan_address() -> Option<IpAddr> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).ok()?;
+    socket.connect((Ipv4Addr::new(192, 0, 2, 1), 9)).ok()?;
+    socket
+        .local_addr()
+        .ok()
+        .map(|address| address.ip())
+        .filter(|ip| !ip.is_unspecified())
+}
+
+/// Parsed HTTP request
+#[derive(Debug, Default)]
+struct HttpRequest {
+    /// Method, e.g. "GET"
+    method: String,
+
+    /// Path without the query string
+    path: String,
+
+    /// Query string parameters
+    query: HashMap<String, String>,
+
+    /// Headers with lowercase names
+    headers: HashMap<String, String>,
+
+    /// Request body
+    body: Vec<u8>,
+}
+
+/// HTTP response
+#[derive(Debug)]
+struct HttpResponse {
+    /// Status code
+    status: u16,
+
+    /// Content-Type header
+    content_type: &'static str,
+
+    /// Response body
+    body: Vec<u8>,
+}
+
+impl HttpResponse {
+    /// Create a JSON response
+    fn json(status: u16, body: &Value) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: serde_json::to_vec(body).unwrap_or_default(),
+        }
+    }
+}
+
+impl HttpRequest {
+    /// Get the token sent with the request
+    ///
+    /// Webhooks may pass it as `?token=`, since many webhook senders can't set headers.
+    fn token(&self) -> Option<&str> {
+        let header = self
+            .headers
+            .get("authorization")
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(str::trim);
+        if header.is_some() || !self.path.starts_with("/api/hooks/") {
+            return header;
+        }
+        self.query.get("token").map(String::as_str)
+    }
+}
+
+/// Read one request, handle it and send the response
+fn handle_connection(
+    stream: TcpStream,
+    token: Option<&str>,
+    daemon: &DaemonClient,
+    config: &Config,
+    thumbnails: &Mutex<ThumbnailCache>,
+) -> AppResult<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+
+    let response = match read_head(&mut reader) {
+        Ok(mut request) => {
+            debug!("API request: {} {}", request.method, request.path);
+            // Requests without the token are turned away before their body is read
+            if !is_page(&request) && !authorized(&request, token) {
+                HttpResponse::json(401, &json!({ "error": "Missing or invalid API token" }))
+            } else {
+                match read_body(&mut reader, &mut request) {
+                    Ok(()) => respond(&request, token, daemon, config, thumbnails),
+                    Err(e) => HttpResponse::json(400, &json!({ "error": e.to_string() })),
+                }
+            }
+        }
+        Err(e) => HttpResponse::json(400, &json!({ "error": e.to_string() })),
+    };
+
+    write_response(&mut writer, &response)
+}
+
+/// Build the response to a request
+///
+/// The pages and thumbnails are served here; everything else is JSON from `route`.
+fn respond(
+    request: &HttpRequest,
+    token: Option<&str>,
+    daemon: &DaemonClient,
+    config: &Config,
+    thumbnails: &Mutex<ThumbnailCache>,
+) -> HttpResponse {
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/") => HttpResponse {
+            status: 200,
+            content_type: "text/html; charset=utf-8",
+            body: REMOTE_PAGE.as_bytes().to_vec(),
+        },
+        ("GET", "/upload") => HttpResponse {
+            status: 200,
+            content_type: "text/html; charset=utf-8",
+            body: UPLOAD_PAGE.as_bytes().to_vec(),
+        },
+        ("GET", "/api/thumbnail") if authorized(request, token) => {
+            match thumbnail(request, daemon, config, thumbnails) {
+                Ok(jpeg) => HttpResponse {
+                    status: 200,
+                    content_type: "image/jpeg",
+                    body: jpeg,
+                },
+                Err(e) => HttpResponse::json(404, &json!({ "error": e.to_string() })),
+            }
+        }
+        _ => {
+            let (status, body) = route(request, token, daemon, config);
+            HttpResponse::json(status, &body)
+        }
+    }
+}
+
+/// Check whether a request is for one of the pages, which need no token
+fn is_page(request: &HttpRequest) -> bool {
+    request.method == "GET" && matches!(request.path.as_str(), "/" | "/upload")
+}
+
+/// Read the request line and headers from a connection
+fn read_head(reader: &mut impl BufRead) -> AppResult<HttpRequest> {
+    let request_line = read_line(reader)?;
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = match (parts.next(), parts.next()) {
+        (Some(method), Some(target)) => (method.to_string(), target),
+        _ => return Err(AppError::Other("Malformed request line".to_string())),
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let path = path.to_string();
+    let query = query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(name, value)| (percent_decode(name), percent_decode(value)))
+        .collect();
+
+    let mut headers = HashMap::new();
+    loop {
+        let line = read_line(reader)?;
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+
+    Ok(HttpRequest {
+        method,
+        path,
+        query,
+        headers,
+        body: Vec::new(),
+    })
+}
+
+/// Read the body of a request whose head was read
+///
+/// The size is checked before anything is read, and memory is only taken
+/// as the body arrives.
+fn read_body(reader: &mut impl BufRead, request: &mut HttpRequest) -> AppResult<()> {
+    let length: usize = match request.headers.get("content-length") {
+        Some(value) => value
+            .parse()
+            .map_err(|_| AppError::Other("Invalid Content-Length".to_string()))?,
+        None => 0,
+    };
+    // Photos from the upload page are far larger than JSON bodies
+    let limit = if request.path == UPLOAD_PATH {
+        MAX_UPLOAD
+    } else {
+        MAX_BODY
+    };
+    if length > limit {
+        return Err(AppError::Other("Request body is too large".to_string()));
+    }
+    reader
+        .by_ref()
+        .take(length as u64)
+        .read_to_end(&mut request.body)?;
+    if request.body.len() < length {
+        return Err(AppError::Other("Incomplete request".to_string()));
+    }
+    Ok(())
+}
+
+/// Read a CRLF-terminated line
+fn read_line(reader: &mut impl BufRead) -> AppResult<String> {
+    let mut line = String::new();
+    reader.by_ref().take(MAX_LINE as u64).read_line(&mut line)?;
+    if !line.ends_with('\n') {
+        return Err(AppError::Other("Incomplete request".to_string()));
+    }
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Decode a `%XX`-escaped query string component
+pub(super) fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'%' => match value
+                .get(index + 1..index + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                Some(byte) => {
+                    decoded.push(byte);
+                    index += 3;
+                    continue;
+                }
+                None => decoded.push(b'%'),
+            },
+            b'+' => decoded.push(b' '),
+            byte => decoded.push(byte),
+        }
+        index += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Write a response and close the connection
+fn write_response(writer: &mut impl Write, response: &HttpResponse) -> AppResult<()> {
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    };
+    write!(
+        writer,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        reason,
+        response.content_type,
+        response.body.len()
+    )?;
+    writer.write_all(&response.body)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Compare tokens without leaking where they differ
+pub(super) fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Check the token sent with a request
+fn authorized(request: &HttpRequest, token: Option<&str>) -> bool {
+    match (request.token(), token) {
+        (Some(given), Some(expected)) => tokens_match(given, expected),
+        _ => false,
+    }
+}
+
+/// Body of `POST /api/wallpaper`
+#[derive(Debug, Deserialize)]
+struct ApplyBody {
+    /// Local file
+    path: Option<PathBuf>,
+
+    /// Web wallpaper URL
+    url: Option<String>,
+
+    /// Wallpaper type (guessed from the file extension when omitted)
+    #[serde(rename = "type")]
+    wallpaper_type: Option<WallpaperType>,
+}
+
+/// Route a request to its handler
+///
+/// Returns the HTTP status and the JSON body.
+fn route(
+    request: &HttpRequest,
+    token: Option<&str>,
+    daemon: &DaemonClient,
+    config: &Config,
+) -> (u16, Value) {
+    if !authorized(request, token) {
+        return (401, json!({ "error": "Missing or invalid API token" }));
+    }
+
+    let path = request.path.trim_end_matches('/');
+    if let Some(name) = path.strip_prefix("/api/hooks/") {
+        if !matches!(request.method.as_str(), "GET" | "POST") {
+            return (405, json!({ "error": "Method not allowed" }));
+        }
+        let action = match config.api.webhooks.get(name) {
+            Some(action) => action,
+            None => {
+                return (
+                    404,
+                    json!({ "error": format!("No webhook named {}", name) }),
+                )
+            }
+        };
+        info!("Running webhook {}", name);
+        return match run_webhook(action, daemon) {
+            Ok(body) => (200, body),
+            Err(e) => (500, json!({ "error": e.to_string() })),
+        };
+    }
+    if let Some(rest) = path.strip_prefix("/api/homeassistant") {
+        if rest.is_empty() || rest.starts_with('/') {
+            return homeassistant::route(&request.method, rest, &request.body, daemon, config);
+        }
+    }
+    if let Some(rest) = path.strip_prefix("/api/browser") {
+        if rest.is_empty() || rest.starts_with('/') {
+            return browser::route(&request.method, rest, &request.body, daemon, config);
+        }
+    }
+
+    let result = match (request.method.as_str(), path) {
+        ("GET", "/api/status") => daemon.status().map(|status| json!(status)),
+        ("GET", "/api/library") => Ok(json!(library(config))),
+        ("GET", "/api/monitors") => upload::monitors(),
+        ("GET", "/api/metrics") => daemon.status().map(|status| {
+            json!({
+                "uptime_secs": status.uptime_secs,
+                "memory_bytes": status.processes.iter().map(|p| p.memory_bytes).sum::<u64>(),
+                "cpu_usage": status.processes.iter().map(|p| p.cpu_usage).sum::<f32>(),
+                "processes": status.processes,
+            })
+        }),
+        ("POST", "/api/wallpaper") => apply_wallpaper(&request.body, daemon),
+        ("POST", UPLOAD_PATH) => upload::upload(
+            &request.body,
+            request.query.get("monitor").map(String::as_str),
+            daemon,
+            config,
+        ),
+        ("DELETE", "/api/wallpaper") => forward(daemon, &Request::StopWallpaper),
+        ("POST", "/api/wallpaper/next") => forward(daemon, &Request::NextWallpaper),
+        ("POST", "/api/wallpaper/pause") => forward(daemon, &Request::PauseWallpaper),
+        ("POST", "/api/wallpaper/resume") => forward(daemon, &Request::ResumeWallpaper),
+        ("POST", "/api/scheduler/start") => forward(daemon, &Request::StartScheduler),
+        ("POST", "/api/scheduler/stop") => forward(daemon, &Request::StopScheduler),
+        ("POST", "/api/scheduler/reload") => forward(daemon, &Request::ReloadSchedule),
+        (
+            _,
+            "/api/status"
+            | "/api/library"
+            | "/api/metrics"
+            | "/api/monitors"
+            | "/api/upload"
+            | "/api/wallpaper"
+            | "/api/wallpaper/next"
+            | "/api/wallpaper/pause"
+            | "/api/wallpaper/resume"
+            | "/api/scheduler/start"
+            | "/api/scheduler/stop"
+            | "/api/scheduler/reload",
+        ) => {
+            return (405, json!({ "error": "Method not allowed" }));
+        }
+        _ => return (404, json!({ "error": "Not found" })),
+    };
+
+    match result {
+        Ok(body) => (200, body),
+        Err(e) => (500, json!({ "error": e.to_string() })),
+    }
+}
+
+/// Send a request to the daemon and return its message
+pub(super) fn forward(daemon: &DaemonClient, request: &Request) -> AppResult<Value> {
+    let Response { message, .. } = daemon.send(request)?;
+    Ok(json!({ "ok": true, "message": message }))
+}
+
+/// Apply the wallpaper described by the request body
+fn apply_wallpaper(body: &[u8], daemon: &DaemonClient) -> AppResult<Value> {
+    let body: ApplyBody = serde_json::from_slice(body)?;
+    let wallpaper = match (body.path, body.url) {
+        (Some(path), _) => file_wallpaper(path, body.wallpaper_type)?,
+        (None, Some(url)) => WallpaperInfo::new(WallpaperType::Web, None, Some(url)),
+        (None, None) => {
+            return Err(AppError::WallpaperError(
+                "Either path or url is required".to_string(),
+            ))
+        }
+    };
+    apply(wallpaper, daemon)
+}
+
+/// Describe a wallpaper file, guessing its type when not given
+pub(super) fn file_wallpaper(
+    path: PathBuf,
+    wallpaper_type: Option<WallpaperType>,
+) -> AppResult<WallpaperInfo> {
+    if !path.is_file() {
+        return Err(AppError::WallpaperError(format!(
+            "{} does not exist",
+            path.display()
+        )));
+    }
+    let wallpaper_type = wallpaper_type
+        .or_else(|| WallpaperType::from_path(&path))
+        .ok_or_else(|| {
+            AppError::WallpaperError(format!(
+                "Can't tell the wallpaper type of {}",
+                path.display()
+            ))
+        })?;
+    Ok(WallpaperInfo::new(wallpaper_type, Some(path), None))
+}
+
+/// Apply a wallpaper and remember it so the daemon restores it on the next start
+pub(super) fn apply(wallpaper: WallpaperInfo, daemon: &DaemonClient) -> AppResult<Value> {
+    daemon.send(&Request::ApplyWallpaper {
+        wallpaper: wallpaper.clone(),
+    })?;
+
+    let mut config = Config::load().map_err(|e| AppError::ConfigError(e.to_string()))?;
+    wallpaper.remember(&mut config.wallpaper);
+    config
+        .save()
+        .map_err(|e| AppError::ConfigError(e.to_string()))?;
+
+    Ok(json!(wallpaper))
+}
+
+/// Run the action of a webhook
+fn run_webhook(action: &WebhookAction, daemon: &DaemonClient) -> AppResult<Value> {
+    match action {
+        WebhookAction::ApplyWallpaper {
+            path,
+            wallpaper_type,
+        } => apply(
+            file_wallpaper(PathBuf::from(path), wallpaper_type.clone())?,
+            daemon,
+        ),
+        WebhookAction::NextWallpaper => forward(daemon, &Request::NextWallpaper),
+        WebhookAction::StopWallpaper => forward(daemon, &Request::StopWallpaper),
+        WebhookAction::StartScheduler => forward(daemon, &Request::StartScheduler),
+        WebhookAction::StopScheduler => forward(daemon, &Request::StopScheduler),
+        WebhookAction::SwitchProfile { profile } => forward(
+            daemon,
+            &Request::SwitchProfile {
+                name: profile.clone(),
+            },
+        ),
+    }
+}
+
+/// List the wallpapers in the auto-change folder, the downloads, the generated wallpapers and the favorites
+fn library(config: &Config) -> Vec<WallpaperInfo> {
+    let mut paths = BTreeSet::new();
+
+    let folders = config
+        .wallpaper
+        .auto_change
+        .folder
+        .iter()
+        .map(PathBuf::from)
+        .chain([config.get_downloads_dir(), Config::get_generated_dir()]);
+    for folder in folders {
+        if let Ok(entries) = fs::read_dir(&folder) {
+            paths.extend(
+                entries
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| path.is_file()),
+            );
+        }
+    }
+    paths.extend(
+        config
+            .wallpaper
+            .favorites
+            .iter()
+            .map(PathBuf::from)
+            .filter(|path| path.is_file()),
+    );
+
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let wallpaper_type = WallpaperType::from_path(&path)?;
+            Some(WallpaperInfo::new(wallpaper_type, Some(path), None))
+        })
+        .collect()
+}
+
+/// Get a JPEG thumbnail of a static wallpaper from the library or the current wallpaper
+///
+/// Other files are refused, so the endpoint can't be used to read arbitrary images.
+fn thumbnail(
+    request: &HttpRequest,
+    daemon: &DaemonClient,
+    config: &Config,
+    cache: &Mutex<ThumbnailCache>,
+) -> AppResult<Vec<u8>> {
+    let path = request
+        .query
+        .get("path")
+        .map(PathBuf::from)
+        .ok_or_else(|| AppError::Other("Missing path parameter".to_string()))?;
+
+    let is_static = |wallpaper: &WallpaperInfo| {
+        wallpaper.r#type == WallpaperType::Static && wallpaper.path.as_ref() == Some(&path)
+    };
+    let served = library(config).iter().any(is_static)
+        || daemon
+            .status()
+            .ok()
+            .and_then(|status| status.current_wallpaper)
+            .map_or(false, |wallpaper| is_static(&wallpaper));
+    if !served {
+        return Err(AppError::WallpaperError(format!(
+            "{} is not a static wallpaper in the library",
+            path.display()
+        )));
+    }
+
+    let modified = fs::metadata(&path)?.modified()?;
+    if let Some((cached_modified, jpeg)) = cache.lock().unwrap().get(&path) {
+        if *cached_modified == modified {
+            return Ok(jpeg.clone());
+        }
+    }
+
+    let jpeg = ImagePipeline::open(&path)?
+        .thumbnail(THUMBNAIL_SIZE)
+        .encode(Encoding::Jpeg(80))?;
+
+    let mut cache = cache.lock().unwrap();
+    if cache.len() >= MAX_CACHED_THUMBNAILS {
+        cache.clear();
+    }
+    cache.insert(path, (modified, jpeg.clone()));
+    Ok(jpeg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use tempfile::tempdir;
+
+    fn read_request(reader: &mut impl BufRead) -> AppResult<HttpRequest> {
+        let mut request = read_head(reader)?;
+        read_body(reader, &mut request)?;
+        Ok(request)
+    }
+
+    fn request(raw: &str) -> HttpRequest {
+        read_request(&mut Cursor::new(raw.as_bytes())).unwrap()
+    }
+
+    #[test]
+    fn test_read_request() {
+        let parsed = request("POST /api/wallpaper?x=1 HTTP/1.1\r\nHost: localhost\r\nAuthorization: Bearer abc\r\nContent-Length: 4\r\n\r\n{}\r\n");
+        assert_eq!(parsed.method, "POST");
+        assert_eq!(parsed.path, "/api/wallpaper");
+        assert_eq!(parsed.token(), Some("abc"));
+        assert_eq!(parsed.body, b"{}\r\n");
+
+        assert!(
+            read_request(&mut Cursor::new(b"GET /api/status HTTP/1.1\r\n".as_slice())).is_err()
+        );
+        assert!(read_request(&mut Cursor::new(
+            format!(
+                "POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+                MAX_BODY + 1
+            )
+            .as_bytes()
+        ))
+        .is_err());
+
+        // Photos may be larger
+        let photo = vec![0u8; MAX_BODY + 1];
+        let mut raw = format!(
+            "POST {} HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+            UPLOAD_PATH,
+            photo.len()
+        )
+        .into_bytes();
+        raw.extend(&photo);
+        assert_eq!(
+            read_request(&mut Cursor::new(raw)).unwrap().body.len(),
+            MAX_BODY + 1
+        );
+    }
+
+    #[test]
+    fn test_unauthorized_body_is_not_read() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+
+        // An upload claiming the largest size, with no body ever sent
+        write!(
+            client,
+            "POST {} HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+            UPLOAD_PATH, MAX_UPLOAD
+        )
+        .unwrap();
+        let thumbnails = Mutex::new(ThumbnailCache::new());
+        handle_connection(
+            stream,
+            Some("secret"),
+            &DaemonClient::new(),
+            &Config::default(),
+            &thumbnails,
+        )
+        .unwrap();
+
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 401"), "{}", response);
+    }
+
+    #[test]
+    fn test_route_auth() {
+        let daemon = DaemonClient::new();
+        let config = Config::default();
+
+        let unauthorized = request("GET /api/status HTTP/1.1\r\n\r\n");
+        assert_eq!(
+            route(&unauthorized, Some("secret"), &daemon, &config).0,
+            401
+        );
+
+        let wrong = request("GET /api/status HTTP/1.1\r\nAuthorization: Bearer secreT\r\n\r\n");
+        assert_eq!(route(&wrong, Some("secret"), &daemon, &config).0, 401);
+        assert_eq!(route(&wrong, None, &daemon, &config).0, 401);
+
+        let unknown = request("GET /api/nothing HTTP/1.1\r\nAuthorization: Bearer secret\r\n\r\n");
+        assert_eq!(route(&unknown, Some("secret"), &daemon, &config).0, 404);
+
+        let wrong_method =
+            request("GET /api/wallpaper/next HTTP/1.1\r\nAuthorization: Bearer secret\r\n\r\n");
+        assert_eq!(
+            route(&wrong_method, Some("secret"), &daemon, &config).0,
+            405
+        );
+    }
+
+    #[test]
+    fn test_webhooks() {
+        let daemon = DaemonClient::new();
+        let mut config = Config::default();
+        config.api.webhooks.insert(
+            "ci-failed".to_string(),
+            WebhookAction::ApplyWallpaper {
+                path: "/nonexistent/red.png".to_string(),
+                wallpaper_type: None,
+            },
+        );
+
+        // Webhooks accept the token in the query string; other endpoints don't
+        let hook = request("POST /api/hooks/ci-failed?token=secret HTTP/1.1\r\n\r\n");
+        assert_eq!(hook.token(), Some("secret"));
+        let status = request("GET /api/status?token=secret HTTP/1.1\r\n\r\n");
+        assert_eq!(status.token(), None);
+
+        let (code, body) = route(&hook, Some("secret"), &daemon, &config);
+        assert_eq!(code, 500);
+        assert!(body["error"].as_str().unwrap().contains("does not exist"));
+
+        let unknown = request("POST /api/hooks/other?token=secret HTTP/1.1\r\n\r\n");
+        assert_eq!(route(&unknown, Some("secret"), &daemon, &config).0, 404);
+
+        let action: WebhookAction =
+            serde_json::from_str(r#"{"action":"apply_wallpaper","path":"a.mp4","type":"Video"}"#)
+                .unwrap();
+        assert_eq!(
+            action,
+            WebhookAction::ApplyWallpaper {
+                path: "a.mp4".to_string(),
+                wallpaper_type: Some(WallpaperType::Video)
+            }
+        );
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(
+            percent_decode("%2Fhome%2Fme%2Fa%20b.png"),
+            "/home/me/a b.png"
+        );
+        assert_eq!(percent_decode("a+b%zz%"), "a b%zz%");
+
+        let parsed = request("GET /api/thumbnail?path=%2Ftmp%2Fx.png HTTP/1.1\r\n\r\n");
+        assert_eq!(parsed.query["path"], "/tmp/x.png");
+    }
+
+    #[test]
+    fn test_remote_page_and_thumbnails() {
+        let daemon = DaemonClient::new();
+        let thumbnails = Mutex::new(ThumbnailCache::new());
+        let dir = tempdir().unwrap();
+        image::RgbImage::new(800, 600)
+            .save(dir.path().join("a.png"))
+            .unwrap();
+        let mut config = Config::default();
+        config.wallpaper.auto_change.folder = Some(dir.path().to_string_lossy().to_string());
+
+        // The page itself needs no token; it asks for one
+        let page = respond(
+            &request("GET / HTTP/1.1\r\n\r\n"),
+            Some("secret"),
+            &daemon,
+            &config,
+            &thumbnails,
+        );
+        assert_eq!(page.status, 200);
+        assert!(page.content_type.starts_with("text/html"));
+        let upload = respond(
+            &request("GET /upload HTTP/1.1\r\n\r\n"),
+            Some("secret"),
+            &daemon,
+            &config,
+            &thumbnails,
+        );
+        assert_eq!(upload.status, 200);
+
+        let target = format!(
+            "GET /api/thumbnail?path={}",
+            dir.path().join("a.png").display()
+        );
+        let unauthorized = request(&format!("{} HTTP/1.1\r\n\r\n", target));
+        assert_eq!(
+            respond(&unauthorized, Some("secret"), &daemon, &config, &thumbnails).status,
+            401
+        );
+
+        let authorized = request(&format!(
+            "{} HTTP/1.1\r\nAuthorization: Bearer secret\r\n\r\n",
+            target
+        ));
+        let response = respond(&authorized, Some("secret"), &daemon, &config, &thumbnails);
+        assert_eq!(response.status, 200);
+        assert_eq!(response.content_type, "image/jpeg");
+        let thumbnail = image::load_from_memory(&response.body).unwrap();
+        assert_eq!(
+            (thumbnail.width(), thumbnail.height()),
+            (THUMBNAIL_SIZE, 270)
+        );
+        assert_eq!(thumbnails.lock().unwrap().len(), 1);
+
+        // Files outside the library are refused
+        let outside = request(
+            "GET /api/thumbnail?path=/etc/passwd HTTP/1.1\r\nAuthorization: Bearer secret\r\n\r\n",
+        );
+        assert_eq!(
+            respond(&outside, Some("secret"), &daemon, &config, &thumbnails).status,
+            404
+        );
+    }
+
+    #[test]
+    fn test_library() {
+        let dir = tempdir().unwrap();
+        for name in ["b.png", "a.mp4", "notes.txt"] {
+            fs::write(dir.path().join(name), "").unwrap();
+        }
+
+        let mut config = Config::default();
+        config.wallpaper.auto_change.folder = Some(dir.path().to_string_lossy().to_string());
+        config.wallpaper.favorites = vec![dir.path().join("b.png").to_string_lossy().to_string()];
+
+        // Other entries may come from the real downloads folder
+        let wallpapers: Vec<_> = library(&config)
+            .into_iter()
+            .filter(|w| w.path.as_ref().map_or(false, |p| p.starts_with(dir.path())))
+            .collect();
+        assert_eq!(wallpapers.len(), 2);
+        assert_eq!(wallpapers[0].name, "a");
+        assert_eq!(wallpapers[0].r#type, WallpaperType::Video);
+        assert_eq!(wallpapers[1].name, "b");
+    }
+}
@@ -0,0 +1,840 @@
+//! Remote control from a Telegram or Discord bot
+//!
+//! Users on the allowlist can message the bot a command such as "next" or
+//! "pause", or send it an image to show as the wallpaper. Messages from
+//! anyone else are ignored without an answer; their sender's ID is logged
+//! once, so it can be copied into the allowlist.
+//!
+//! Telegram bots are long-polled for updates. Discord only delivers messages
+//! live over its gateway, so the bridge opens a direct message channel with
+//! each allowed user instead and polls it. Only messages sent after the
+//! bridge started are answered, so old commands aren't replayed.
+//!
+//! The bot token is kept in the secret store. Received images are kept in
+//! `downloads/bot` and applied like wallpapers set from the API.
+use crate::core::config::{BotConfig, BotPlatform};
+use crate::core::{AppError, AppResult, Config, EventLog, HttpClient, RateLimit, SecretStore};
+use crate::daemon::api::{apply, file_wallpaper};
+use crate::daemon::client::DaemonClient;
+use crate::daemon::protocol::{DaemonStatus, Request};
+use log::{debug, error, info, warn};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Secret store key of the bot token
+pub const BOT_TOKEN_SECRET: &str = "bot-token";
+
+/// Rate limit providers of the two platforms
+const TELEGRAM: &str = "telegram-bot";
+const DISCORD: &str = "discord-bot";
+
+/// Telegram Bot API
+const TELEGRAM_API: &str = "https://api.telegram.org";
+
+/// Discord REST API
+const DISCORD_API: &str = "https://discord.com/api/v10";
+
+/// First second of 2015, which Discord message IDs count from, in milliseconds
+const DISCORD_EPOCH: u64 = 1_420_070_400_000;
+
+/// How long Telegram holds a poll open waiting for a message
+///
+/// Kept short so stopping the bridge doesn't wait long for the poll to end.
+const LONG_POLL: Duration = Duration::from_secs(5);
+
+/// Time between polls of the Discord channels
+const DISCORD_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Time before polling again after a failure
+const RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often the thread checks whether it should stop or poll
+const TICK: Duration = Duration::from_millis(200);
+
+/// Timeout for requests other than polls and downloads
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Timeout for downloading a received image
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Folder under the downloads folder the received images are kept in
+const RECEIVED_FOLDER: &str = "bot";
+
+/// Answer to "help" and to messages that aren't commands
+const HELP: &str = "Send an image to show it as the wallpaper, or one of: \
+next, pause, resume, stop, undo, redo, status";
+
+/// Command sent to the bot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    /// Switch to the next wallpaper
+    Next,
+
+    /// Hold animated wallpapers paused
+    Pause,
+
+    /// Let animated wallpapers run again
+    Resume,
+
+    /// Stop the wallpaper
+    Stop,
+
+    /// Go back to the previous wallpaper
+    Undo,
+
+    /// Go forward to the last undone wallpaper
+    Redo,
+
+    /// Tell what's shown
+    Status,
+
+    /// List the commands
+    Help,
+}
+
+impl Action {
+    /// Get the answer to send when the daemon answered without a message
+    fn done(self) -> &'static str {
+        match self {
+            Action::Next => "Showing the next wallpaper",
+            Action::Pause => "Paused",
+            Action::Resume => "Resumed",
+            Action::Stop => "Stopped the wallpaper",
+            Action::Undo => "Went back",
+            Action::Redo => "Went forward",
+            Action::Status | Action::Help => "",
+        }
+    }
+}
+
+/// File attached to a message
+#[derive(Debug, Clone, PartialEq)]
+struct Attachment {
+    /// Telegram file ID or Discord attachment URL
+    source: String,
+
+    /// Name to save it under
+    name: String,
+}
+
+/// Message received by the bot
+#[derive(Debug, Clone, PartialEq)]
+struct Message {
+    /// ID of the user who sent it
+    sender: String,
+
+    /// Telegram chat or Discord channel to answer in
+    chat: String,
+
+    /// Text or caption
+    text: String,
+
+    /// Attached image or video, if any
+    attachment: Option<Attachment>,
+}
+
+/// Direct message channel with a Discord user
+#[derive(Debug, Clone)]
+struct DmChannel {
+    /// Channel ID
+    id: String,
+
+    /// ID of the last message seen in it
+    after: u64,
+}
+
+/// Connection state of the bot on one platform
+enum Session {
+    /// Telegram bot
+    Telegram {
+        /// ID of the next update to fetch
+        offset: i64,
+
+        /// Start of the bridge, in Unix seconds
+        since: i64,
+    },
+
+    /// Discord bot
+    Discord {
+        /// Start of the bridge, as a message ID
+        since: u64,
+
+        /// Direct message channels, by user ID
+        channels: HashMap<String, DmChannel>,
+    },
+}
+
+impl Session {
+    /// Start a session for a platform, answering messages sent from `started` on
+    fn new(platform: BotPlatform, started: SystemTime) -> Self {
+        let elapsed = started.duration_since(UNIX_EPOCH).unwrap_or_default();
+        match platform {
+            BotPlatform::Telegram => Session::Telegram {
+                offset: 0,
+                since: elapsed.as_secs() as i64,
+            },
+            BotPlatform::Discord => Session::Discord {
+                since: snowflake(elapsed),
+                channels: HashMap::new(),
+            },
+        }
+    }
+
+    /// Get the platform of the session
+    fn platform(&self) -> BotPlatform {
+        match self {
+            Session::Telegram { .. } => BotPlatform::Telegram,
+            Session::Discord { .. } => BotPlatform::Discord,
+        }
+    }
+
+    /// Get the time to wait between polls
+    fn interval(&self) -> Duration {
+        match self {
+            // Polls already wait for messages
+            Session::Telegram { .. } => Duration::ZERO,
+            Session::Discord { .. } => DISCORD_INTERVAL,
+        }
+    }
+
+    /// Fetch the messages sent since the last poll
+    fn poll(
+        &mut self,
+        http: &HttpClient,
+        token: &str,
+        config: &BotConfig,
+    ) -> AppResult<Vec<Message>> {
+        match self {
+            Session::Telegram { offset, since } => {
+                let body = json!({ "offset": *offset, "timeout": LONG_POLL.as_secs(), "allowed_updates": ["message"] });
+                let updates = telegram(
+                    http,
+                    token,
+                    "getUpdates",
+                    &body,
+                    LONG_POLL + REQUEST_TIMEOUT,
+                )?;
+                let mut messages = Vec::new();
+                for update in updates.as_array().into_iter().flatten() {
+                    if let Some(id) = update["update_id"].as_i64() {
+                        *offset = (*offset).max(id + 1);
+                    }
+                    if update["message"]["date"].as_i64().unwrap_or_default() >= *since {
+                        messages.extend(telegram_message(&update["message"]));
+                    }
+                }
+                Ok(messages)
+            }
+            Session::Discord { since, channels } => {
+                let mut messages = Vec::new();
+                for user in config
+                    .allowed_users
+                    .iter()
+                    .map(|user| user.trim())
+                    .filter(|user| !user.is_empty())
+                {
+                    if !channels.contains_key(user) {
+                        let channel = discord(
+                            http,
+                            token,
+                            "POST",
+                            "/users/@me/channels",
+                            Some(&json!({ "recipient_id": user })),
+                        )?;
+                        let id = channel["id"].as_str().ok_or_else(|| {
+                            AppError::NetworkError(format!(
+                                "Discord opened no channel with {}",
+                                user
+                            ))
+                        })?;
+                        channels.insert(
+                            user.to_string(),
+                            DmChannel {
+                                id: id.to_string(),
+                                after: *since,
+                            },
+                        );
+                    }
+                    let Some(channel) = channels.get_mut(user) else {
+                        continue;
+                    };
+
+                    let path = format!(
+                        "/channels/{}/messages?after={}&limit=50",
+                        channel.id, channel.after
+                    );
+                    let mut found: Vec<(u64, Value)> = discord(http, token, "GET", &path, None)?
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|message| {
+                            Some((message["id"].as_str()?.parse().ok()?, message.clone()))
+                        })
+                        .collect();
+                    found.sort_by_key(|(id, _)| *id);
+                    for (id, message) in found {
+                        channel.after = channel.after.max(id);
+                        messages.extend(discord_message(&message));
+                    }
+                }
+                Ok(messages)
+            }
+        }
+    }
+
+    /// Answer in a chat
+    fn reply(&self, http: &HttpClient, token: &str, chat: &str, text: &str) -> AppResult<()> {
+        match self {
+            Session::Telegram { .. } => {
+                telegram(
+                    http,
+                    token,
+                    "sendMessage",
+                    &json!({ "chat_id": chat, "text": text }),
+                    REQUEST_TIMEOUT,
+                )?;
+            }
+            Session::Discord { .. } => {
+                discord(
+                    http,
+                    token,
+                    "POST",
+                    &format!("/channels/{}/messages", chat),
+                    Some(&json!({ "content": text })),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Download an attachment to a file
+    fn fetch(
+        &self,
+        http: &HttpClient,
+        token: &str,
+        attachment: &Attachment,
+        dest: &Path,
+    ) -> AppResult<()> {
+        match self {
+            Session::Telegram { .. } => {
+                let file = telegram(
+                    http,
+                    token,
+                    "getFile",
+                    &json!({ "file_id": attachment.source }),
+                    REQUEST_TIMEOUT,
+                )?;
+                let file_path = file["file_path"].as_str().ok_or_else(|| {
+                    AppError::NetworkError("Telegram has no file to download".to_string())
+                })?;
+                // The file URL holds the token, so it goes through a request rather than a download
+                let url = format!("{}/file/bot{}/{}", TELEGRAM_API, token, file_path);
+                let response = http.request(TELEGRAM, "GET", &url, None, &[], DOWNLOAD_TIMEOUT)?;
+                if !(200..300).contains(&response.status) {
+                    return Err(AppError::NetworkError(format!(
+                        "Download failed with status {}",
+                        response.status
+                    )));
+                }
+                fs::write(dest, response.body)?;
+                Ok(())
+            }
+            Session::Discord { .. } => http.download(DISCORD, &attachment.source, dest),
+        }
+    }
+}
+
+/// Applies wallpapers and runs commands sent to a chat bot
+pub struct BotBridge {
+    /// Bot configuration
+    config: Arc<Mutex<BotConfig>>,
+
+    /// Secret store holding the token
+    secret_store: Arc<SecretStore>,
+
+    /// Cached token, empty when none is set (None = read it again)
+    token: Arc<Mutex<Option<String>>>,
+
+    /// Bot thread handle
+    bot_thread: Option<thread::JoinHandle<()>>,
+
+    /// Whether the bridge is running
+    is_running: Arc<Mutex<bool>>,
+}
+
+#[allow(dead_code)]
+impl BotBridge {
+    /// Create a new bot bridge
+    pub fn new(config: BotConfig, secret_store: Arc<SecretStore>) -> Self {
+        Self {
+            config: Arc::new(Mutex::new(config)),
+            secret_store,
+            token: Arc::new(Mutex::new(None)),
+            bot_thread: None,
+            is_running: Arc::new(Mutex::new(false)),
+        }
+    }
+
+    /// Update the bot configuration
+    ///
+    /// Also re-reads the token, in case it was changed.
+    pub fn update_config(&self, config: BotConfig) {
+        *self.config.lock().unwrap() = config;
+        *self.token.lock().unwrap() = None;
+    }
+
+    /// Start the bot thread
+    ///
+    /// The thread idles while the bridge is off or has no token.
+    pub fn start(&mut self) -> AppResult<()> {
+        if *self.is_running.lock().unwrap() {
+            debug!("Bot bridge is already running");
+            return Ok(());
+        }
+
+        *self.is_running.lock().unwrap() = true;
+
+        let config = self.config.clone();
+        let secret_store = self.secret_store.clone();
+        let token = self.token.clone();
+        let is_running = self.is_running.clone();
+
+        self.bot_thread = Some(thread::spawn(move || {
+            let http = HttpClient::shared();
+            http.set_rate_limit(TELEGRAM, RateLimit::per_minute(60));
+            http.set_rate_limit(DISCORD, RateLimit::per_minute(120));
+            let started = SystemTime::now();
+            let mut session: Option<(String, Session)> = None;
+            let mut next_poll = Instant::now();
+            let mut warned_token = false;
+            let mut failing = false;
+            let mut ignored: HashSet<String> = HashSet::new();
+
+            while *is_running.lock().unwrap() {
+                if Instant::now() < next_poll {
+                    thread::sleep(TICK);
+                    continue;
+                }
+
+                let settings = config.lock().unwrap().clone();
+                let cached = token.lock().unwrap().clone();
+                let bot_token = match cached {
+                    _ if !settings.enabled => None,
+                    Some(cached) => Some(cached),
+                    // A missing token is cached as empty, so it isn't looked up on every poll
+                    None => {
+                        let value = secret_store
+                            .get(BOT_TOKEN_SECRET)
+                            .unwrap_or_else(|e| {
+                                error!("Failed to read the bot token: {}", e);
+                                None
+                            })
+                            .unwrap_or_default();
+                        *token.lock().unwrap() = Some(value.clone());
+                        Some(value)
+                    }
+                }
+                .filter(|value| !value.is_empty());
+
+                let Some(bot_token) = bot_token else {
+                    if settings.enabled && !warned_token {
+                        warn!("The bot bridge is on but no bot token is set");
+                        EventLog::shared().error(
+                            "bot",
+                            "The bot bridge needs a bot token",
+                            "Set the token of your bot",
+                        );
+                        warned_token = true;
+                    }
+                    if session.take().is_some() {
+                        info!("Bot bridge stopped polling");
+                    }
+                    next_poll = Instant::now() + Duration::from_secs(1);
+                    continue;
+                };
+                warned_token = false;
+
+                // Another platform or bot starts over
+                if session.as_ref().map_or(true, |(for_token, session)| {
+                    *for_token != bot_token || session.platform() != settings.platform
+                }) {
+                    info!("Bot bridge polling {}", settings.platform.name());
+                    session = Some((bot_token.clone(), Session::new(settings.platform, started)));
+                    failing = false;
+                }
+                let Some((_, current)) = session.as_mut() else {
+                    continue;
+                };
+
+                match current.poll(&http, &bot_token, &settings) {
+                    Ok(messages) => {
+                        if failing {
+                            info!("Bot bridge reached {} again", settings.platform.name());
+                            failing = false;
+                        }
+                        for message in messages {
+                            if !is_allowed(&settings, &message.sender) {
+                                if ignored.insert(message.sender.clone()) {
+                                    warn!(
+                                        "Ignoring bot messages from user {}, who isn't allowed",
+                                        message.sender
+                                    );
+                                    EventLog::shared().info(
+                                        "bot",
+                                        format!(
+                                            "Ignored a message from user {}, who isn't allowed",
+                                            message.sender
+                                        ),
+                                    );
+                                }
+                                continue;
+                            }
+                            let answer = answer(current, &http, &bot_token, &message);
+                            if let Err(e) = current.reply(&http, &bot_token, &message.chat, &answer)
+                            {
+                                warn!("Failed to answer user {}: {}", message.sender, e);
+                            }
+                        }
+                        next_poll = Instant::now() + current.interval();
+                    }
+                    Err(e) => {
+                        if !failing {
+                            warn!("Failed to poll {}: {}", settings.platform.name(), e);
+                            EventLog::shared().error(
+                                "bot",
+                                format!("Failed to poll {}", settings.platform.name()),
+                                e,
+                            );
+                            failing = true;
+                        }
+                        next_poll = Instant::now() + RETRY_INTERVAL;
+                    }
+                }
+            }
+        }));
+
+        info!("Bot bridge started");
+        Ok(())
+    }
+
+    /// Stop the bot thread
+    pub fn stop(&mut self) -> AppResult<()> {
+        if !*self.is_running.lock().unwrap() {
+            debug!("Bot bridge is not running");
+            return Ok(());
+        }
+
+        *self.is_running.lock().unwrap() = false;
+
+        if let Some(thread) = self.bot_thread.take() {
+            thread
+                .join()
+                .map_err(|e| AppError::Other(format!("Failed to join bot thread: {:?}", e)))?;
+        }
+
+        info!("Bot bridge stopped");
+        Ok(())
+    }
+}
+
+/// Check whether a user may control the wallpaper
+pub fn is_allowed(config: &BotConfig, user: &str) -> bool {
+    !user.is_empty()
+        && config
+            .allowed_users
+            .iter()
+            .any(|allowed| allowed.trim() == user)
+}
+
+/// Act on a message from an allowed user and return the answer
+fn answer(session: &Session, http: &HttpClient, token: &str, message: &Message) -> String {
+    let daemon = DaemonClient::new();
+    let result = match (&message.attachment, parse_action(&message.text)) {
+        (Some(attachment), _) => show_attachment(session, http, token, attachment, &daemon),
+        (None, Some(action)) => run(action, &daemon),
+        (None, None) => Ok(HELP.to_string()),
+    };
+    result.unwrap_or_else(|e| {
+        warn!("Failed to act on a bot message: {}", e);
+        format!("Failed: {}", e)
+    })
+}
+
+/// Read the command at the start of a message
+fn parse_action(text: &str) -> Option<Action> {
+    let word = text.split_whitespace().next()?.trim_start_matches('/');
+    // Telegram adds the bot's name to commands picked from its menu in groups
+    let word = word.split('@').next().unwrap_or_default().to_lowercase();
+    Some(match word.as_str() {
+        "next" | "skip" => Action::Next,
+        "pause" => Action::Pause,
+        "resume" | "play" => Action::Resume,
+        "stop" => Action::Stop,
+        "undo" | "back" | "previous" => Action::Undo,
+        "redo" => Action::Redo,
+        "status" => Action::Status,
+        // Telegram sends /start when a chat with the bot is opened
+        "help" | "start" => Action::Help,
+        _ => return None,
+    })
+}
+
+/// Run a command through the daemon
+fn run(action: Action, daemon: &DaemonClient) -> AppResult<String> {
+    let request = match action {
+        Action::Next => Request::NextWallpaper,
+        Action::Pause => Request::PauseWallpaper,
+        Action::Resume => Request::ResumeWallpaper,
+        Action::Stop => Request::StopWallpaper,
+        Action::Undo => Request::UndoWallpaper,
+        Action::Redo => Request::RedoWallpaper,
+        Action::Status => return daemon.status().map(|status| describe(&status)),
+        Action::Help => return Ok(HELP.to_string()),
+    };
+    let response = daemon.send(&request)?;
+    Ok(response
+        .message
+        .filter(|message| !message.is_empty())
+        .unwrap_or_else(|| action.done().to_string()))
+}
+
+/// Describe what the desktop shows
+fn describe(status: &DaemonStatus) -> String {
+    let shown = status.current_wallpaper.as_ref().map_or_else(
+        || "No wallpaper is running".to_string(),
+        |wallpaper| format!("Showing {}", wallpaper.name),
+    );
+    if status.held {
+        format!("{}, paused", shown)
+    } else {
+        shown
+    }
+}
+
+/// Download an attachment among the received images and apply it
+fn show_attachment(
+    session: &Session,
+    http: &HttpClient,
+    token: &str,
+    attachment: &Attachment,
+    daemon: &DaemonClient,
+) -> AppResult<String> {
+    let path = received_path(&attachment.name)?;
+    session.fetch(http, token, attachment, &path)?;
+    let wallpaper = file_wallpaper(path, None)?;
+    let name = wallpaper.name.clone();
+    apply(wallpaper, daemon)?;
+    info!("Applied {} sent to the bot", name);
+    Ok(format!("Showing {}", name))
+}
+
+/// Get where a received file is saved, creating the folder
+fn received_path(name: &str) -> AppResult<PathBuf> {
+    let file_name = Path::new(name)
+        .file_name()
+        .ok_or_else(|| AppError::NetworkError(format!("Bad file name: {}", name)))?;
+    let folder = Config::default().get_downloads_dir().join(RECEIVED_FOLDER);
+    fs::create_dir_all(&folder)?;
+    Ok(folder.join(file_name))
+}
+
+/// Call a Telegram Bot API method and return its result
+fn telegram(
+    http: &HttpClient,
+    token: &str,
+    method: &str,
+    body: &Value,
+    timeout: Duration,
+) -> AppResult<Value> {
+    let url = format!("{}/bot{}/{}", TELEGRAM_API, token, method);
+    let response = http
+        .request(TELEGRAM, "POST", &url, Some(body), &[], timeout)?
+        .json()?;
+    if response["ok"].as_bool() != Some(true) {
+        let description = response["description"]
+            .as_str()
+            .unwrap_or("Telegram request failed");
+        return Err(AppError::NetworkError(description.to_string()));
+    }
+    Ok(response["result"].clone())
+}
+
+/// Read a Telegram message, taking the largest size of a photo
+fn telegram_message(message: &Value) -> Option<Message> {
+    let photo = message["photo"]
+        .as_array()
+        .and_then(|sizes| sizes.last())
+        .and_then(|photo| {
+            Some(Attachment {
+                source: photo["file_id"].as_str()?.to_string(),
+                name: format!("{}.jpg", photo["file_unique_id"].as_str()?),
+            })
+        });
+    // Images sent as files keep their quality and name
+    let document = || {
+        let document = &message["document"];
+        Some(Attachment {
+            source: document["file_id"].as_str()?.to_string(),
+            name: document["file_name"].as_str().map_or_else(
+                || {
+                    format!(
+                        "{}.jpg",
+                        document["file_unique_id"].as_str().unwrap_or("image")
+                    )
+                },
+                String::from,
+            ),
+        })
+    };
+
+    Some(Message {
+        sender: message["from"]["id"].as_i64()?.to_string(),
+        chat: message["chat"]["id"].as_i64()?.to_string(),
+        text: message["text"]
+            .as_str()
+            .or_else(|| message["caption"].as_str())
+            .unwrap_or_default()
+            .to_string(),
+        attachment: photo.or_else(document),
+    })
+}
+
+/// Call the Discord API
+fn discord(
+    http: &HttpClient,
+    token: &str,
+    method: &str,
+    path: &str,
+    body: Option<&Value>,
+) -> AppResult<Value> {
+    let url = format!("{}{}", DISCORD_API, path);
+    let response = http.request(
+        DISCORD,
+        method,
+        &url,
+        body,
+        &[format!("Authorization: Bot {}", token)],
+        REQUEST_TIMEOUT,
+    )?;
+    let value = if response.body.is_empty() {
+        Value::Null
+    } else {
+        response.json()?
+    };
+    if !(200..300).contains(&response.status) {
+        let message = value["message"]
+            .as_str()
+            .unwrap_or("Discord request failed");
+        return Err(AppError::NetworkError(format!(
+            "{} (status {})",
+            message, response.status
+        )));
+    }
+    Ok(value)
+}
+
+/// Read a Discord message, taking its first attachment
+fn discord_message(message: &Value) -> Option<Message> {
+    let attachment = message["attachments"]
+        .as_array()
+        .and_then(|attachments| attachments.first())
+        .and_then(|attachment| {
+            Some(Attachment {
+                source: attachment["url"].as_str()?.to_string(),
+                name: attachment["filename"].as_str()?.to_string(),
+            })
+        });
+
+    Some(Message {
+        sender: message["author"]["id"].as_str()?.to_string(),
+        chat: message["channel_id"].as_str()?.to_string(),
+        text: message["content"].as_str().unwrap_or_default().to_string(),
+        attachment,
+    })
+}
+
+/// Make the Discord message ID of a time since the Unix epoch
+fn snowflake(since_epoch: Duration) -> u64 {
+    (since_epoch.as_millis() as u64).saturating_sub(DISCORD_EPOCH) << 22
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_action() {
+        assert_eq!(parse_action("next"), Some(Action::Next));
+        assert_eq!(parse_action("  /Pause please"), Some(Action::Pause));
+        assert_eq!(parse_action("/resume@AetherDeskBot"), Some(Action::Resume));
+        assert_eq!(parse_action("/start"), Some(Action::Help));
+        assert_eq!(parse_action("what's up"), None);
+        assert_eq!(parse_action(""), None);
+    }
+
+    #[test]
+    fn test_is_allowed() {
+        let config = BotConfig {
+            allowed_users: vec![" 12345 ".to_string(), String::new()],
+            ..BotConfig::default()
+        };
+        assert!(is_allowed(&config, "12345"));
+        assert!(!is_allowed(&config, "1234"));
+        assert!(!is_allowed(&config, ""));
+        assert!(!is_allowed(&BotConfig::default(), "12345"));
+    }
+
+    #[test]
+    fn test_read_messages() {
+        let telegram = json!({
+            "from": { "id": 42 },
+            "chat": { "id": 42 },
+            "caption": "lake",
+            "photo": [
+                { "file_id": "small", "file_unique_id": "s" },
+                { "file_id": "large", "file_unique_id": "l" },
+            ],
+        });
+        assert_eq!(
+            telegram_message(&telegram),
+            Some(Message {
+                sender: "42".to_string(),
+                chat: "42".to_string(),
+                text: "lake".to_string(),
+                attachment: Some(Attachment {
+                    source: "large".to_string(),
+                    name: "l.jpg".to_string()
+                }),
+            })
+        );
+        assert!(telegram_message(&json!({ "text": "next" })).is_none());
+
+        let discord = json!({
+            "author": { "id": "7" },
+            "channel_id": "99",
+            "content": "pause",
+            "attachments": [],
+        });
+        let message = discord_message(&discord).unwrap();
+        assert_eq!(
+            (
+                message.sender.as_str(),
+                message.chat.as_str(),
+                message.attachment
+            ),
+            ("7", "99", None)
+        );
+    }
+
+    #[test]
+    fn test_snowflake() {
+        assert_eq!(snowflake(Duration::from_millis(DISCORD_EPOCH)), 0);
+        assert_eq!(snowflake(Duration::from_millis(DISCORD_EPOCH + 1)), 1 << 22);
+        assert_eq!(snowflake(Duration::ZERO), 0);
+    }
+}
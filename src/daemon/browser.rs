@@ -0,0 +1,331 @@
+//! Browser extension endpoints
+//!
+//! The companion extension in `integrations/browser` adds "Set as wallpaper"
+//! to the context menu of images. It hands the image's address to the app
+//! over native messaging, where the browser starts `aether-desk native-host`
+//! and talks to it on stdin and stdout, or over `POST /api/browser/image` on
+//! the local API when the host isn't registered. Either way the image is
+//! downloaded into the downloads folder, where the library finds it, and
+//! applied.
+use crate::core::config::WallpaperType;
+use crate::core::{AppError, AppResult, Config, HttpClient, WallpaperInfo};
+use crate::daemon::api::apply;
+use crate::daemon::client::DaemonClient;
+use crate::experiments::ai::decode_base64;
+use crate::wallpapers::animated_image;
+use log::{debug, info};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Provider name of image downloads, for rate limiting
+const PROVIDER: &str = "browser";
+
+/// Folder under the downloads folder the images are kept in
+const IMAGES_FOLDER: &str = "browser";
+
+/// Largest message accepted from the browser; images may come as `data:` URLs
+const MAX_MESSAGE: usize = 64 * 1024 * 1024;
+
+/// Longest file name kept from an image's address
+const MAX_STEM: usize = 48;
+
+/// Image sent by the extension
+#[derive(Debug, Deserialize)]
+struct ImageBody {
+    /// Address of the image: `http(s)://` or `data:`
+    url: String,
+
+    /// Page the image was on, for the log
+    #[serde(default)]
+    page: Option<String>,
+}
+
+/// Route a request under `/api/browser`
+///
+/// `path` is what follows the prefix. Returns the HTTP status and the JSON body.
+pub fn route(
+    method: &str,
+    path: &str,
+    body: &[u8],
+    daemon: &DaemonClient,
+    config: &Config,
+) -> (u16, Value) {
+    let result = match (method, path.trim_matches('/')) {
+        ("GET", "") => Ok(hello()),
+        ("POST", "image") => serde_json::from_slice(body)
+            .map_err(AppError::from)
+            .and_then(|body| set_image(&body, daemon, config)),
+        (_, "" | "image") => return (405, json!({ "error": "Method not allowed" })),
+        _ => return (404, json!({ "error": "Not found" })),
+    };
+
+    match result {
+        Ok(body) => (200, body),
+        Err(e) => (500, json!({ "error": e.to_string() })),
+    }
+}
+
+/// Tell the extension what it is talking to
+fn hello() -> Value {
+    json!({ "ok": true, "name": "Aether-Desk", "version": env!("CARGO_PKG_VERSION") })
+}
+
+/// Download an image from the browser and apply it
+fn set_image(body: &ImageBody, daemon: &DaemonClient, config: &Config) -> AppResult<Value> {
+    let path = import(&body.url, &config.get_downloads_dir().join(IMAGES_FOLDER))?;
+    info!(
+        "Setting image from the browser: {} (from {})",
+        path.display(),
+        body.page.as_deref().unwrap_or("an unknown page")
+    );
+    let wallpaper_type = WallpaperType::from_path(&path).unwrap_or(WallpaperType::Static);
+    apply(WallpaperInfo::new(wallpaper_type, Some(path), None), daemon)
+}
+
+/// Save the image at `url` into `dir`, returning its path
+///
+/// Images in formats the desktop may not show, like still WebPs, are saved
+/// as PNG; animated ones are kept to play as animated image wallpapers.
+fn import(url: &str, dir: &Path) -> AppResult<PathBuf> {
+    let bytes = if url.starts_with("data:image/") {
+        decode_base64(url)?
+    } else if url.starts_with("http://") || url.starts_with("https://") {
+        let download = dir.join(format!("{}.download", file_stem(url)));
+        HttpClient::shared().download(PROVIDER, url, &download)?;
+        let bytes = fs::read(&download);
+        let _ = fs::remove_file(&download);
+        bytes?
+    } else {
+        return Err(AppError::WallpaperError(format!(
+            "Not an image address: {}",
+            url
+        )));
+    };
+
+    let format = image::guess_format(&bytes)
+        .map_err(|e| AppError::WallpaperError(format!("{} is not an image: {}", url, e)))?;
+    fs::create_dir_all(dir)?;
+    let extension = format.extensions_str().first().copied().unwrap_or("png");
+    let path = dir.join(format!("{}.{}", file_stem(url), extension));
+    fs::write(&path, &bytes)?;
+    if WallpaperType::Static.extensions().contains(&extension) || animated_image::is_animated(&path)
+    {
+        return Ok(path);
+    }
+    let _ = fs::remove_file(&path);
+
+    debug!("Converting {:?} image from the browser to PNG", format);
+    let image = image::load_from_memory_with_format(&bytes, format)
+        .map_err(|e| AppError::WallpaperError(format!("Failed to open {}: {}", url, e)))?;
+    let path = dir.join(format!("{}.png", file_stem(url)));
+    image.save(&path).map_err(|e| {
+        AppError::WallpaperError(format!("Failed to save {}: {}", path.display(), e))
+    })?;
+    Ok(path)
+}
+
+/// Name an image after the last part of its address, with a hash of the address
+///
+/// The hash keeps images with the same name from different sites apart,
+/// while setting the same image twice replaces the earlier copy.
+fn file_stem(url: &str) -> String {
+    // FNV-1a is enough to tell addresses apart
+    let hash = url.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    });
+    let name = if url.starts_with("data:") {
+        ""
+    } else {
+        let path = url.split(['?', '#']).next().unwrap_or(url);
+        let name = path.rsplit('/').next().unwrap_or_default();
+        name.rsplit_once('.').map_or(name, |(stem, _)| stem)
+    };
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .take(MAX_STEM)
+        .collect();
+    let name = name.trim_matches('-');
+    format!(
+        "{}-{:08x}",
+        if name.is_empty() { "image" } else { name },
+        hash as u32
+    )
+}
+
+/// Serve the extension over native messaging until the browser closes the pipe
+///
+/// Each message is JSON preceded by its length as a 32-bit integer in the
+/// machine's byte order. Nothing else may be written to stdout.
+pub fn run_native_host() -> AppResult<()> {
+    let daemon = DaemonClient::new();
+    let (mut stdin, mut stdout) = (io::stdin().lock(), io::stdout().lock());
+    while let Some(message) = read_message(&mut stdin)? {
+        let reply = match handle_message(&message, &daemon) {
+            Ok(reply) => reply,
+            Err(e) => json!({ "ok": false, "error": e.to_string() }),
+        };
+        write_message(&mut stdout, &reply)?;
+    }
+    Ok(())
+}
+
+/// Answer a message from the extension
+fn handle_message(message: &Value, daemon: &DaemonClient) -> AppResult<Value> {
+    match message["action"].as_str().unwrap_or_default() {
+        "ping" => Ok(hello()),
+        "set_image" => {
+            let body: ImageBody = serde_json::from_value(message.clone())?;
+            daemon.ensure_running()?;
+            let config = Config::load().map_err(|e| AppError::ConfigError(e.to_string()))?;
+            let wallpaper = set_image(&body, daemon, &config)?;
+            Ok(json!({ "ok": true, "wallpaper": wallpaper }))
+        }
+        action => Err(AppError::ConfigError(format!("Unknown action: {}", action))),
+    }
+}
+
+/// Read a native message, or None once the browser closed the pipe
+fn read_message(reader: &mut impl Read) -> AppResult<Option<Value>> {
+    let mut length = [0u8; 4];
+    match reader.read_exact(&mut length) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+    let length = u32::from_ne_bytes(length) as usize;
+    if length > MAX_MESSAGE {
+        return Err(AppError::ConfigError(format!(
+            "Message of {} bytes is too large",
+            length
+        )));
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    Ok(Some(serde_json::from_slice(&body)?))
+}
+
+/// Write a native message
+fn write_message(writer: &mut impl Write, message: &Value) -> AppResult<()> {
+    let body = serde_json::to_vec(message)?;
+    writer.write_all(&(body.len() as u32).to_ne_bytes())?;
+    writer.write_all(&body)?;
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_native_messages() {
+        let mut buffer = Vec::new();
+        write_message(&mut buffer, &json!({ "action": "ping" })).unwrap();
+        write_message(
+            &mut buffer,
+            &json!({ "action": "set_image", "url": "https://a.example/b.jpg" }),
+        )
+        .unwrap();
+
+        let mut reader = Cursor::new(buffer);
+        assert_eq!(
+            read_message(&mut reader).unwrap().unwrap()["action"],
+            "ping"
+        );
+        assert_eq!(
+            read_message(&mut reader).unwrap().unwrap()["url"],
+            "https://a.example/b.jpg"
+        );
+        assert!(read_message(&mut reader).unwrap().is_none());
+
+        let daemon = DaemonClient::new();
+        assert_eq!(
+            handle_message(&json!({ "action": "ping" }), &daemon).unwrap()["name"],
+            "Aether-Desk"
+        );
+        assert!(handle_message(&json!({ "action": "format_disk" }), &daemon).is_err());
+    }
+
+    #[test]
+    fn test_file_stem() {
+        let stem = file_stem("https://cdn.example.com/photos/Lake%20view.jpg?w=1920");
+        assert!(stem.starts_with("Lake-20view-"), "{}", stem);
+        assert_eq!(
+            stem,
+            file_stem("https://cdn.example.com/photos/Lake%20view.jpg?w=1920")
+        );
+        assert_ne!(
+            stem,
+            file_stem("https://other.example.com/photos/Lake%20view.jpg?w=1920")
+        );
+        assert!(file_stem("data:image/png;base64,iVBORw0KGgo=").starts_with("image-"));
+        assert!(file_stem("https://example.com/").starts_with("image-"));
+    }
+
+    #[test]
+    fn test_import() {
+        let dir = tempdir().unwrap();
+        let mut png = Cursor::new(Vec::new());
+        image::RgbImage::new(4, 3)
+            .write_to(&mut png, image::ImageOutputFormat::Png)
+            .unwrap();
+        let base64 = encode_base64(png.get_ref());
+
+        let path = import(&format!("data:image/png;base64,{}", base64), dir.path()).unwrap();
+        assert_eq!(path.extension().unwrap(), "png");
+        assert_eq!(image::image_dimensions(&path).unwrap(), (4, 3));
+
+        assert!(import("data:image/png;base64,aGVsbG8=", dir.path()).is_err());
+        assert!(import("file:///etc/passwd", dir.path()).is_err());
+
+        let (status, body) = route(
+            "POST",
+            "image",
+            br#"{"url": "javascript:alert(1)"}"#,
+            &DaemonClient::new(),
+            &Config::default(),
+        );
+        assert_eq!(status, 500);
+        assert!(body["error"]
+            .as_str()
+            .unwrap()
+            .contains("Not an image address"));
+        assert_eq!(
+            route(
+                "GET",
+                "image",
+                b"",
+                &DaemonClient::new(),
+                &Config::default()
+            )
+            .0,
+            405
+        );
+    }
+
+    fn encode_base64(bytes: &[u8]) -> String {
+        const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+        let mut text = String::new();
+        for chunk in bytes.chunks(3) {
+            let value = chunk.iter().enumerate().fold(0u32, |value, (i, byte)| {
+                value | (*byte as u32) << (16 - 8 * i)
+            });
+            for i in 0..=chunk.len() {
+                text.push(ALPHABET[(value >> (18 - 6 * i) & 63) as usize] as char);
+            }
+        }
+        text
+    }
+}
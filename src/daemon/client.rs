@@ -0,0 +1,118 @@
+//! Client used by frontends to talk to the daemon
+use crate::core::{AppError, AppResult, Event};
+use crate::daemon::protocol::{DaemonStatus, Request, Response};
+use crate::daemon::{transport, DAEMON_FLAG};
+use log::{debug, info};
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long to wait for a newly spawned daemon
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Connection to the background daemon
+///
+/// Each request opens a new connection, so the client survives daemon restarts.
+#[derive(Debug, Clone, Default)]
+pub struct DaemonClient;
+
+#[allow(dead_code)]
+impl DaemonClient {
+    /// Create a new daemon client
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Send a request and wait for the response
+    pub fn request(&self, request: &Request) -> AppResult<Response> {
+        let mut stream = transport::connect(transport::DAEMON_ENDPOINT)
+            .map_err(|e| AppError::Other(format!("Daemon is not running: {}", e)))?;
+
+        let mut line = serde_json::to_string(request)?;
+        line.push('\n');
+        stream.write_all(line.as_bytes())?;
+        stream.flush()?;
+
+        let mut reader = BufReader::new(stream);
+        let mut response = String::new();
+        reader.read_line(&mut response)?;
+        if response.is_empty() {
+            return Err(AppError::Other("Daemon closed the connection".to_string()));
+        }
+
+        Ok(serde_json::from_str(&response)?)
+    }
+
+    /// Send a request, turning error responses into errors
+    pub fn send(&self, request: &Request) -> AppResult<Response> {
+        let response = self.request(request)?;
+        if !response.ok {
+            return Err(AppError::Remote {
+                message: response
+                    .message
+                    .unwrap_or_else(|| "Daemon request failed".to_string()),
+                hint: response.hint,
+            });
+        }
+        Ok(response)
+    }
+
+    /// Check whether the daemon is running
+    pub fn is_running(&self) -> bool {
+        self.request(&Request::Ping).map(|r| r.ok).unwrap_or(false)
+    }
+
+    /// Get the daemon status
+    pub fn status(&self) -> AppResult<DaemonStatus> {
+        self.send(&Request::Status)?
+            .status
+            .ok_or_else(|| AppError::Other("Daemon did not return a status".to_string()))
+    }
+
+    /// Get the events recorded after the given event ID
+    pub fn events_since(&self, since: u64) -> AppResult<Vec<Event>> {
+        Ok(self
+            .send(&Request::Events { since })?
+            .events
+            .unwrap_or_default())
+    }
+
+    /// Start the daemon if it isn't running and wait until it accepts requests
+    pub fn ensure_running(&self) -> AppResult<()> {
+        if self.is_running() {
+            debug!("Daemon is already running");
+            return Ok(());
+        }
+
+        let exe = std::env::current_exe()?;
+        let mut command = Command::new(exe);
+        command
+            .arg(DAEMON_FLAG)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+
+        #[cfg(windows)]
+        {
+            use std::os::windows::process::CommandExt;
+            // DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP
+            command.creation_flags(0x0000_0008 | 0x0000_0200);
+        }
+
+        command
+            .spawn()
+            .map_err(|e| AppError::Other(format!("Failed to start daemon: {}", e)))?;
+
+        let started = Instant::now();
+        while started.elapsed() < STARTUP_TIMEOUT {
+            if self.is_running() {
+                info!("Started daemon");
+                return Ok(());
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+
+        Err(AppError::Other("Daemon did not start in time".to_string()))
+    }
+}
@@ -0,0 +1,250 @@
+//! Home Assistant endpoints of the local API
+//!
+//! The `aether_desk` custom component in `integrations/homeassistant` talks
+//! to these: it lists the scenes set up under Settings → Local API as Home
+//! Assistant scenes, activates them, and pushes images from any URL to the
+//! desktop. A scene switches a profile, applies a wallpaper and shows or hides
+//! the widgets, each only when set, in that order.
+use crate::core::config::{SceneConfig, WallpaperType};
+use crate::core::{format, AppError, AppResult, Config, WallpaperInfo, WidgetConfig};
+use crate::daemon::api::{apply, file_wallpaper, forward, percent_decode};
+use crate::daemon::client::DaemonClient;
+use crate::daemon::protocol::Request;
+use log::info;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Provider name of image downloads, for rate limiting
+const PROVIDER: &str = "homeassistant";
+
+/// Folder under the downloads folder the pushed images are kept in
+const PUSHED_FOLDER: &str = "homeassistant";
+
+/// Body of `POST /api/homeassistant/image`
+#[derive(Debug, Deserialize)]
+struct PushBody {
+    /// Image to show
+    url: String,
+}
+
+/// Route a request under `/api/homeassistant`
+///
+/// `path` is what follows the prefix. Returns the HTTP status and the JSON body.
+pub fn route(
+    method: &str,
+    path: &str,
+    body: &[u8],
+    daemon: &DaemonClient,
+    config: &Config,
+) -> (u16, Value) {
+    let result = match (method, path.trim_matches('/')) {
+        ("GET", "") => Ok(discovery(daemon, config)),
+        ("POST", "image") => push_image(body, daemon, config),
+        ("POST", scene) if scene.starts_with("scenes/") => {
+            let name = percent_decode(&scene["scenes/".len()..]);
+            match config.api.scenes.get(&name) {
+                Some(scene) => {
+                    info!("Activating Home Assistant scene {}", name);
+                    activate(scene, daemon, config).map(|_| json!({ "ok": true, "scene": name }))
+                }
+                None => return (404, json!({ "error": format!("No scene named {}", name) })),
+            }
+        }
+        (_, "" | "image") => return (405, json!({ "error": "Method not allowed" })),
+        _ => return (404, json!({ "error": "Not found" })),
+    };
+
+    match result {
+        Ok(body) => (200, body),
+        Err(e) => (500, json!({ "error": e.to_string() })),
+    }
+}
+
+/// Describe this desktop for Home Assistant: its scenes and what it shows
+fn discovery(daemon: &DaemonClient, config: &Config) -> Value {
+    let current = daemon
+        .status()
+        .ok()
+        .and_then(|status| status.current_wallpaper);
+    json!({
+        "name": "Aether-Desk",
+        "version": env!("CARGO_PKG_VERSION"),
+        "scenes": config.api.scenes.keys().collect::<Vec<_>>(),
+        "wallpaper": current.map(|wallpaper| wallpaper.name),
+    })
+}
+
+/// Run the parts of a scene that are set
+fn activate(scene: &SceneConfig, daemon: &DaemonClient, config: &Config) -> AppResult<()> {
+    if let Some(profile) = &scene.profile {
+        forward(
+            daemon,
+            &Request::SwitchProfile {
+                name: profile.clone(),
+            },
+        )?;
+    }
+    if let Some(path) = &scene.wallpaper {
+        apply(file_wallpaper(PathBuf::from(path), None)?, daemon)?;
+    }
+    if let Some(visible) = scene.widgets {
+        // A profile switched to above brought its own widgets file
+        let config = if scene.profile.is_some() {
+            Config::load().map_err(|e| AppError::ConfigError(e.to_string()))?
+        } else {
+            config.clone()
+        };
+        show_widgets(&config.get_widgets_file(), visible)?;
+        forward(daemon, &Request::ReloadWidgets)?;
+    }
+    Ok(())
+}
+
+/// Show or hide every widget in the widgets file
+fn show_widgets(file: &Path, visible: bool) -> AppResult<()> {
+    if !file.exists() {
+        return Ok(());
+    }
+    let mut widgets: HashMap<String, WidgetConfig> = format::load(file)
+        .map_err(|e| AppError::ConfigError(format!("Failed to load widgets file: {}", e)))?;
+    for widget in widgets.values_mut() {
+        widget.enabled = visible;
+    }
+    format::save(file, &widgets)
+        .map_err(|e| AppError::ConfigError(format!("Failed to save widgets file: {}", e)))
+}
+
+/// Download an image pushed from Home Assistant and apply it
+fn push_image(body: &[u8], daemon: &DaemonClient, config: &Config) -> AppResult<Value> {
+    let body: PushBody = serde_json::from_slice(body)?;
+    if !(body.url.starts_with("http://") || body.url.starts_with("https://")) {
+        return Err(AppError::WallpaperError(format!(
+            "Not an http(s) URL: {}",
+            body.url
+        )));
+    }
+
+    let path = config
+        .get_downloads_dir()
+        .join(PUSHED_FOLDER)
+        .join(pushed_file_name(&body.url));
+    crate::core::HttpClient::shared().download(PROVIDER, &body.url, &path)?;
+    if let Err(e) = image::image_dimensions(&path) {
+        let _ = fs::remove_file(&path);
+        return Err(AppError::WallpaperError(format!(
+            "{} is not an image: {}",
+            body.url, e
+        )));
+    }
+
+    info!("Showing image pushed from Home Assistant: {}", body.url);
+    apply(
+        WallpaperInfo::new(WallpaperType::Static, Some(path), None),
+        daemon,
+    )
+}
+
+/// Name a pushed image after its URL, keeping its extension when it has a known one
+///
+/// The same URL maps to the same file, so pushing a camera snapshot again
+/// replaces the previous one instead of filling the disk.
+fn pushed_file_name(url: &str) -> String {
+    // FNV-1a is enough to tell URLs apart
+    let hash = url.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    });
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let extension = Path::new(path)
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .filter(|extension| {
+            WallpaperType::Static
+                .extensions()
+                .contains(&extension.as_str())
+        })
+        .unwrap_or_else(|| "jpg".to_string());
+    format!("{:016x}.{}", hash, extension)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{WidgetPosition, WidgetSize, WidgetType};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_route() {
+        let daemon = DaemonClient::new();
+        let mut config = Config::default();
+        config
+            .api
+            .scenes
+            .insert("movie night".to_string(), SceneConfig::default());
+
+        let (status, body) = route("GET", "", b"", &daemon, &config);
+        assert_eq!(status, 200);
+        assert_eq!(body["scenes"], json!(["movie night"]));
+
+        // Names arrive percent-encoded; a scene with nothing set does nothing
+        assert_eq!(
+            route("POST", "scenes/movie%20night", b"", &daemon, &config).0,
+            200
+        );
+        assert_eq!(route("POST", "scenes/party", b"", &daemon, &config).0, 404);
+        assert_eq!(route("GET", "image", b"", &daemon, &config).0, 405);
+
+        let (status, body) = route(
+            "POST",
+            "image",
+            br#"{"url": "file:///etc/passwd"}"#,
+            &daemon,
+            &config,
+        );
+        assert_eq!(status, 500);
+        assert!(body["error"]
+            .as_str()
+            .unwrap()
+            .contains("Not an http(s) URL"));
+    }
+
+    #[test]
+    fn test_pushed_file_name() {
+        let name = pushed_file_name("https://ha.local/api/camera_proxy/camera.door.png?token=abc");
+        assert!(name.ends_with(".png"));
+        assert_eq!(
+            name,
+            pushed_file_name("https://ha.local/api/camera_proxy/camera.door.png?token=abc")
+        );
+        assert_ne!(
+            name,
+            pushed_file_name("https://ha.local/api/camera_proxy/camera.yard.png?token=abc")
+        );
+        assert!(
+            pushed_file_name("https://ha.local/api/camera_proxy/camera.door").ends_with(".jpg")
+        );
+    }
+
+    #[test]
+    fn test_show_widgets() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("widgets.json");
+        let widget = WidgetConfig {
+            widget_type: WidgetType::Clock,
+            position: WidgetPosition::TopLeft,
+            size: WidgetSize::Small,
+            settings: HashMap::new(),
+            enabled: true,
+            background_color: None,
+            opacity: None,
+        };
+        format::save(&file, &HashMap::from([("clock".to_string(), widget)])).unwrap();
+
+        show_widgets(&file, false).unwrap();
+        let widgets: HashMap<String, WidgetConfig> = format::load(&file).unwrap();
+        assert!(!widgets["clock"].enabled);
+        show_widgets(&dir.path().join("missing.json"), true).unwrap();
+    }
+}
@@ -0,0 +1,335 @@
+//! Just enough multicast DNS to find other Aether-Desk instances on the LAN
+//!
+//! Each instance answers DNS-SD queries for `_aether-desk._tcp.local` with a
+//! PTR, SRV, TXT and A record, and asks for the service now and then. Queries
+//! are sent from an ephemeral port, so answers come back to it directly
+//! (RFC 6762 one-shot queries) and Avahi or Bonjour can answer for an
+//! instance registered with them. Only what's needed for that is encoded and
+//! parsed; everything else in a packet is skipped.
+use crate::core::{AppError, AppResult};
+use std::net::Ipv4Addr;
+
+/// Service type the instances announce
+pub const SERVICE: &str = "_aether-desk._tcp.local";
+
+/// Multicast group of mDNS
+pub const GROUP: Ipv4Addr = Ipv4Addr::new(224, 0, 0, 251);
+
+/// Port of mDNS
+pub const PORT: u16 = 5353;
+
+/// Time peers keep the records, in seconds
+const TTL: u32 = 120;
+
+/// Record types used
+const TYPE_A: u16 = 1;
+const TYPE_PTR: u16 = 12;
+const TYPE_TXT: u16 = 16;
+const TYPE_SRV: u16 = 33;
+const TYPE_ANY: u16 = 255;
+
+/// Class IN, and the cache-flush bit set on unique records
+const CLASS_IN: u16 = 1;
+const CACHE_FLUSH: u16 = 0x8000;
+
+/// Most compression pointers followed in one name, so loops end
+const MAX_POINTERS: usize = 16;
+
+/// Instance found in an answer
+#[derive(Debug, Clone, PartialEq)]
+pub struct Instance {
+    /// Instance name, the first label of its service name
+    pub name: String,
+
+    /// Port its service listens on
+    pub port: u16,
+
+    /// Key-value pairs of its TXT record
+    pub txt: Vec<(String, String)>,
+}
+
+/// Build a query for the service
+pub fn query() -> Vec<u8> {
+    let mut packet = header(0, 0, 1, 0);
+    write_name(&mut packet, SERVICE);
+    packet.extend(TYPE_PTR.to_be_bytes());
+    packet.extend(CLASS_IN.to_be_bytes());
+    packet
+}
+
+/// Build the answer announcing an instance
+///
+/// `id` is the query ID to echo, 0 for multicast answers.
+pub fn announcement(
+    id: u16,
+    instance: &str,
+    host: &str,
+    address: Ipv4Addr,
+    port: u16,
+    txt: &[(&str, &str)],
+) -> Vec<u8> {
+    let full_name = format!("{}.{}", instance, SERVICE);
+    let host = format!("{}.local", host);
+    let mut packet = header(id, 0x8400, 0, 4);
+
+    let mut ptr = Vec::new();
+    write_name(&mut ptr, &full_name);
+    write_record(&mut packet, SERVICE, TYPE_PTR, CLASS_IN, &ptr);
+
+    let mut srv = [0u8; 4].to_vec();
+    srv.extend(port.to_be_bytes());
+    write_name(&mut srv, &host);
+    write_record(
+        &mut packet,
+        &full_name,
+        TYPE_SRV,
+        CLASS_IN | CACHE_FLUSH,
+        &srv,
+    );
+
+    let mut strings = Vec::new();
+    for (key, value) in txt {
+        let entry = format!("{}={}", key, value);
+        let entry = &entry.as_bytes()[..entry.len().min(255)];
+        strings.push(entry.len() as u8);
+        strings.extend(entry);
+    }
+    write_record(
+        &mut packet,
+        &full_name,
+        TYPE_TXT,
+        CLASS_IN | CACHE_FLUSH,
+        &strings,
+    );
+
+    write_record(
+        &mut packet,
+        &host,
+        TYPE_A,
+        CLASS_IN | CACHE_FLUSH,
+        &address.octets(),
+    );
+    packet
+}
+
+/// Check whether a packet is a query asking for the service, returning its ID
+pub fn asks_for_service(packet: &[u8]) -> AppResult<Option<u16>> {
+    let id = read_u16(packet, 0)?;
+    if read_u16(packet, 2)? & 0x8000 != 0 {
+        return Ok(None);
+    }
+
+    let mut offset = 12;
+    for _ in 0..read_u16(packet, 4)? {
+        let (name, next) = read_name(packet, offset)?;
+        let record_type = read_u16(packet, next)?;
+        offset = next + 4;
+        if name.eq_ignore_ascii_case(SERVICE)
+            && (record_type == TYPE_PTR || record_type == TYPE_ANY)
+        {
+            return Ok(Some(id));
+        }
+    }
+    Ok(None)
+}
+
+/// Read the instances of the service an answer describes
+///
+/// Instances come from SRV records, with the TXT record of the same name.
+pub fn instances(packet: &[u8]) -> AppResult<Vec<Instance>> {
+    if read_u16(packet, 2)? & 0x8000 == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut offset = 12;
+    for _ in 0..read_u16(packet, 4)? {
+        offset = read_name(packet, offset)?.1 + 4;
+    }
+
+    let records = [6, 8, 10].iter().try_fold(0usize, |count, &at| {
+        read_u16(packet, at).map(|n| count + n as usize)
+    })?;
+    let suffix = format!(".{}", SERVICE);
+    let mut found: Vec<Instance> = Vec::new();
+    let mut txts: Vec<(String, Vec<(String, String)>)> = Vec::new();
+    for _ in 0..records {
+        let (name, next) = read_name(packet, offset)?;
+        let record_type = read_u16(packet, next)?;
+        let length = read_u16(packet, next + 8)? as usize;
+        let data = next + 10;
+        if packet.len() < data + length {
+            return Err(AppError::NetworkError("Truncated mDNS record".to_string()));
+        }
+        offset = data + length;
+
+        let lowercase = name.to_ascii_lowercase();
+        let instance = match lowercase.strip_suffix(&suffix) {
+            Some(prefix) if !prefix.is_empty() => name[..prefix.len()].to_string(),
+            _ => continue,
+        };
+        match record_type {
+            TYPE_SRV if length >= 6 => found.push(Instance {
+                name: instance,
+                port: read_u16(packet, data + 4)?,
+                txt: Vec::new(),
+            }),
+            TYPE_TXT => txts.push((instance, read_txt(&packet[data..data + length]))),
+            _ => {}
+        }
+    }
+
+    for (name, txt) in txts {
+        if let Some(instance) = found.iter_mut().find(|instance| instance.name == name) {
+            instance.txt = txt;
+        }
+    }
+    Ok(found)
+}
+
+/// Write a packet header
+fn header(id: u16, flags: u16, questions: u16, answers: u16) -> Vec<u8> {
+    [id, flags, questions, answers, 0, 0]
+        .iter()
+        .flat_map(|value| value.to_be_bytes())
+        .collect()
+}
+
+/// Write a name as labels, without compression
+fn write_name(out: &mut Vec<u8>, name: &str) {
+    for label in split_name(name) {
+        let label = &label.as_bytes()[..label.len().min(63)];
+        out.push(label.len() as u8);
+        out.extend(label);
+    }
+    out.push(0);
+}
+
+/// Split a name into labels, keeping dots in the instance label
+///
+/// Instance names are free text, so everything before the service type is one label.
+fn split_name(name: &str) -> Vec<&str> {
+    match name
+        .strip_suffix(SERVICE)
+        .and_then(|prefix| prefix.strip_suffix('.'))
+    {
+        Some(instance) => std::iter::once(instance)
+            .chain(SERVICE.split('.'))
+            .collect(),
+        None => name.split('.').filter(|label| !label.is_empty()).collect(),
+    }
+}
+
+/// Write a resource record
+fn write_record(out: &mut Vec<u8>, name: &str, record_type: u16, class: u16, data: &[u8]) {
+    write_name(out, name);
+    out.extend(record_type.to_be_bytes());
+    out.extend(class.to_be_bytes());
+    out.extend(TTL.to_be_bytes());
+    out.extend((data.len() as u16).to_be_bytes());
+    out.extend(data);
+}
+
+/// Read a big-endian u16
+fn read_u16(packet: &[u8], offset: usize) -> AppResult<u16> {
+    packet
+        .get(offset..offset + 2)
+        .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+        .ok_or_else(|| AppError::NetworkError("Truncated mDNS packet".to_string()))
+}
+
+/// Read a possibly compressed name, returning it and the offset after it
+fn read_name(packet: &[u8], mut offset: usize) -> AppResult<(String, usize)> {
+    let truncated = || AppError::NetworkError("Truncated mDNS name".to_string());
+    let mut labels = Vec::new();
+    let mut end = None;
+    let mut pointers = 0;
+
+    loop {
+        let length = *packet.get(offset).ok_or_else(truncated)? as usize;
+        match length {
+            0 => break,
+            _ if length & 0xc0 == 0xc0 => {
+                pointers += 1;
+                if pointers > MAX_POINTERS {
+                    return Err(AppError::NetworkError("mDNS name loops".to_string()));
+                }
+                end.get_or_insert(offset + 2);
+                offset = (read_u16(packet, offset)? & 0x3fff) as usize;
+            }
+            _ => {
+                let label = packet
+                    .get(offset + 1..offset + 1 + length)
+                    .ok_or_else(truncated)?;
+                labels.push(String::from_utf8_lossy(label).to_string());
+                offset += 1 + length;
+            }
+        }
+    }
+
+    Ok((labels.join("."), end.unwrap_or(offset + 1)))
+}
+
+/// Read the key-value pairs of a TXT record
+fn read_txt(data: &[u8]) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    let mut offset = 0;
+    while let Some(&length) = data.get(offset) {
+        let Some(entry) = data.get(offset + 1..offset + 1 + length as usize) else {
+            break;
+        };
+        let entry = String::from_utf8_lossy(entry);
+        let (key, value) = entry.split_once('=').unwrap_or((&entry, ""));
+        pairs.push((key.to_string(), value.to_string()));
+        offset += 1 + length as usize;
+    }
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_round_trip() {
+        assert_eq!(asks_for_service(&query()).unwrap(), Some(0));
+
+        let answer = announcement(
+            7,
+            "Living Room.PC",
+            "desk",
+            Ipv4Addr::new(192, 168, 1, 20),
+            47941,
+            &[("id", "abc")],
+        );
+        assert_eq!(read_u16(&answer, 0).unwrap(), 7);
+        assert_eq!(asks_for_service(&answer).unwrap(), None);
+        assert_eq!(
+            instances(&answer).unwrap(),
+            vec![Instance {
+                name: "Living Room.PC".to_string(),
+                port: 47941,
+                txt: vec![("id".to_string(), "abc".to_string())],
+            }]
+        );
+        assert!(instances(&query()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_read_name() {
+        // "local" at 0, then "desk" followed by a pointer to it
+        let packet = [
+            5, b'l', b'o', b'c', b'a', b'l', 0, 4, b'd', b'e', b's', b'k', 0xc0, 0,
+        ];
+        assert_eq!(
+            read_name(&packet, 7).unwrap(),
+            ("desk.local".to_string(), 14)
+        );
+
+        // A pointer to itself is rejected, as is a name running past the end
+        assert!(read_name(&[0xc0, 0], 0).is_err());
+        assert!(read_name(&[5, b'l', b'o'], 0).is_err());
+        assert!(
+            instances(&announcement(0, "pc", "desk", Ipv4Addr::LOCALHOST, 1, &[])[..40]).is_err()
+        );
+    }
+}
@@ -0,0 +1,24 @@
+//! Background daemon and the client used by frontends
+//!
+//! The daemon owns the scheduler, the running wallpaper and the online
+//! sources, so wallpapers keep running while the settings window is closed.
+pub mod api;
+pub mod bot;
+pub mod browser;
+pub mod client;
+pub mod homeassistant;
+pub mod mdns;
+#[cfg(windows)]
+pub mod pipe;
+pub mod protocol;
+pub mod server;
+pub mod sync;
+pub mod transport;
+pub mod upload;
+
+pub use client::DaemonClient;
+pub use protocol::{DaemonStatus, Request, Response};
+pub use server::Daemon;
+
+/// Command-line flag that runs the daemon instead of the window
+pub const DAEMON_FLAG: &str = "--daemon";
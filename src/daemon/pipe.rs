@@ -0,0 +1,172 @@
+//! Named pipe transport for Windows
+//!
+//! The daemon serves the same line-JSON protocol on `\\.\pipe\aether-desk-<user>`,
+//! so PowerShell and AutoHotkey scripts can talk to it without a helper binary.
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::os::windows::ffi::OsStrExt;
+use std::os::windows::io::{AsRawHandle, FromRawHandle, RawHandle};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use windows::core::PCWSTR;
+use windows::Win32::Foundation::{ERROR_PIPE_CONNECTED, HANDLE};
+use windows::Win32::Storage::FileSystem::{FILE_FLAG_FIRST_PIPE_INSTANCE, PIPE_ACCESS_DUPLEX};
+use windows::Win32::System::Pipes::{
+    ConnectNamedPipe, CreateNamedPipeW, PIPE_READMODE_BYTE, PIPE_REJECT_REMOTE_CLIENTS,
+    PIPE_TYPE_BYTE, PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+};
+
+/// Size of the pipe's input and output buffers
+const BUFFER_SIZE: u32 = 64 * 1024;
+
+/// How often a client retries while every pipe instance is busy
+const BUSY_RETRIES: u32 = 20;
+
+/// Windows error code for a pipe with no free instance
+const ERROR_PIPE_BUSY: i32 = 231;
+
+/// Get the pipe name of an endpoint for the current user
+pub fn pipe_name(endpoint: &str) -> String {
+    let user = std::env::var("USERNAME").unwrap_or_else(|_| "default".to_string());
+    format!(r"\\.\pipe\{}-{}", endpoint, user)
+}
+
+/// Named pipe listener
+///
+/// A new pipe instance is created as soon as a client connects, so the pipe
+/// never disappears between connections.
+pub struct Listener {
+    /// Endpoint name
+    endpoint: String,
+
+    /// Instance waiting for the next client
+    pending: Mutex<Option<File>>,
+}
+
+impl Listener {
+    /// Create the first pipe instance
+    ///
+    /// Fails if another process already owns the pipe.
+    pub fn bind(endpoint: &str) -> io::Result<Self> {
+        let first = create_instance(endpoint, true)?;
+        Ok(Self {
+            endpoint: endpoint.to_string(),
+            pending: Mutex::new(Some(first)),
+        })
+    }
+
+    /// Iterate over incoming connections
+    pub fn incoming(&self) -> impl Iterator<Item = io::Result<Stream>> + '_ {
+        std::iter::repeat_with(move || self.accept())
+    }
+
+    /// Wait for the next client
+    pub fn accept(&self) -> io::Result<Stream> {
+        let instance = match self.pending.lock().unwrap().take() {
+            Some(instance) => instance,
+            None => create_instance(&self.endpoint, false)?,
+        };
+
+        let handle = HANDLE(instance.as_raw_handle() as isize);
+        if let Err(e) = unsafe { ConnectNamedPipe(handle, None) } {
+            // The client connected between creating the instance and waiting
+            if e.code() != ERROR_PIPE_CONNECTED.to_hresult() {
+                return Err(io::Error::new(io::ErrorKind::Other, e));
+            }
+        }
+
+        *self.pending.lock().unwrap() = Some(create_instance(&self.endpoint, false)?);
+        Ok(Stream(instance))
+    }
+}
+
+/// Create a pipe instance
+fn create_instance(endpoint: &str, first: bool) -> io::Result<File> {
+    let name: Vec<u16> = std::ffi::OsStr::new(&pipe_name(endpoint))
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+
+    let mut open_mode = PIPE_ACCESS_DUPLEX;
+    if first {
+        open_mode |= FILE_FLAG_FIRST_PIPE_INSTANCE;
+    }
+
+    let handle = unsafe {
+        CreateNamedPipeW(
+            PCWSTR(name.as_ptr()),
+            open_mode,
+            PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT | PIPE_REJECT_REMOTE_CLIENTS,
+            PIPE_UNLIMITED_INSTANCES,
+            BUFFER_SIZE,
+            BUFFER_SIZE,
+            0,
+            None,
+        )
+    };
+    if handle.is_invalid() {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(unsafe { File::from_raw_handle(handle.0 as RawHandle) })
+}
+
+/// Connection over a named pipe
+#[derive(Debug)]
+pub struct Stream(File);
+
+impl Stream {
+    /// Connect to an endpoint's pipe
+    pub fn connect(endpoint: &str) -> io::Result<Self> {
+        let mut retries = 0;
+        loop {
+            match OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(pipe_name(endpoint))
+            {
+                Ok(file) => return Ok(Self(file)),
+                Err(e) if e.raw_os_error() == Some(ERROR_PIPE_BUSY) && retries < BUSY_RETRIES => {
+                    retries += 1;
+                    thread::sleep(Duration::from_millis(50));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Set the read timeout
+    ///
+    /// Synchronous pipes have no timeouts; kept so the daemon code is the
+    /// same on every platform.
+    pub fn set_read_timeout(&self, _timeout: Option<Duration>) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Set the write timeout
+    pub fn set_write_timeout(&self, _timeout: Option<Duration>) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Clone the connection so it can be read and written separately
+    pub fn try_clone(&self) -> io::Result<Self> {
+        Ok(Self(self.0.try_clone()?))
+    }
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
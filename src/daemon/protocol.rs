@@ -0,0 +1,294 @@
+//! Messages exchanged between the daemon and its frontends
+//!
+//! Each request and response is a single line of JSON.
+use crate::core::config::WeatherEffect;
+use crate::core::{
+    AppError, CurrentWallpaperState, Event, Orphan, ProcessInfo, WallpaperInfo, WallpaperUsage,
+};
+use crate::platform::workspace::Workspace;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Request sent to the daemon
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Request {
+    /// Check that the daemon is alive
+    Ping,
+
+    /// Get the daemon status
+    Status,
+
+    /// Apply a wallpaper
+    ApplyWallpaper {
+        /// Wallpaper to apply
+        wallpaper: WallpaperInfo,
+    },
+
+    /// Switch to the next wallpaper in the current wallpaper's folder
+    ///
+    /// Uses the auto-change folder when one is configured.
+    NextWallpaper,
+
+    /// Stop the current wallpaper
+    StopWallpaper,
+
+    /// Hold animated wallpapers paused until resumed
+    PauseWallpaper,
+
+    /// Let animated wallpapers held paused run again
+    ResumeWallpaper,
+
+    /// Set the image shown on the lock screen
+    SetLockScreen {
+        /// Image to show
+        path: PathBuf,
+    },
+
+    /// Stop the wallpaper shown on one monitor only
+    StopMonitorWallpaper {
+        /// Monitor ID or name
+        monitor: String,
+    },
+
+    /// Show the wallpaper of the workspace switched to
+    SwitchWorkspace {
+        /// Workspace now active
+        workspace: Workspace,
+    },
+
+    /// Switch to the profile of the monitor setup just connected
+    SwitchTopology {
+        /// Monitor setup now connected
+        topology: String,
+    },
+
+    /// Go back to the wallpaper before the last change
+    UndoWallpaper,
+
+    /// Go forward to the last undone wallpaper
+    RedoWallpaper,
+
+    /// Reload the configuration from disk
+    ReloadConfig,
+
+    /// Reload the schedule from disk
+    ReloadSchedule,
+
+    /// Reload the widgets from disk, when the daemon runs them
+    ReloadWidgets,
+
+    /// Start the scheduler
+    StartScheduler,
+
+    /// Stop the scheduler
+    StopScheduler,
+
+    /// Download wallpapers from the configured source right away
+    DownloadNow,
+
+    /// Fetch the latest Live Earth image right away
+    UpdateLiveEarth,
+
+    /// Switch to a profile and apply its wallpaper, schedule and widgets
+    SwitchProfile {
+        /// Profile name
+        name: String,
+    },
+
+    /// Get the events recorded after the given event ID
+    Events {
+        /// Last event ID the frontend has seen
+        since: u64,
+    },
+
+    /// Stop or keep the players a previous run left running
+    ResolveOrphans {
+        /// Keep them running and track them like the daemon's own players
+        adopt: bool,
+    },
+
+    /// Stop the daemon
+    Shutdown,
+}
+
+/// Response from the daemon
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Response {
+    /// Whether the request succeeded
+    pub ok: bool,
+
+    /// Error or informational message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+
+    /// Daemon status, for status requests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<DaemonStatus>,
+
+    /// Recorded events, for event requests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub events: Option<Vec<Event>>,
+
+    /// Suggested fix for a failed request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+impl Response {
+    /// Create a successful response
+    pub fn ok() -> Self {
+        Self {
+            ok: true,
+            ..Default::default()
+        }
+    }
+
+    /// Create a successful response with a message
+    pub fn ok_with_message(message: impl Into<String>) -> Self {
+        Self {
+            ok: true,
+            message: Some(message.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Create an error response
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            message: Some(message.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Create an error response carrying the error's suggested fix
+    pub fn failed(error: &AppError) -> Self {
+        Self {
+            hint: error.hint(),
+            ..Self::error(error.to_string())
+        }
+    }
+}
+
+/// Daemon status
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DaemonStatus {
+    /// Daemon process ID
+    pub pid: u32,
+
+    /// Seconds since the daemon started
+    pub uptime_secs: u64,
+
+    /// Currently applied wallpaper
+    pub current_wallpaper: Option<WallpaperInfo>,
+
+    /// Monitors, start time and player of the current wallpaper
+    #[serde(default)]
+    pub current: Option<CurrentWallpaperState>,
+
+    /// Wallpapers shown on single monitors, ordered by monitor ID
+    #[serde(default)]
+    pub monitor_wallpapers: Vec<CurrentWallpaperState>,
+
+    /// Active workspace, while workspaces have wallpapers of their own
+    #[serde(default)]
+    pub workspace: Option<Workspace>,
+
+    /// Last auto-download run (RFC 3339)
+    pub last_download: Option<String>,
+
+    /// Last Live Earth update (RFC 3339)
+    pub last_live_earth_update: Option<String>,
+
+    /// Wallpaper backend (e.g. "Hyprland via hyprctl")
+    #[serde(default)]
+    pub backend: String,
+
+    /// Whether the scheduler is running
+    #[serde(default)]
+    pub scheduler_running: bool,
+
+    /// Number of schedule items
+    #[serde(default)]
+    pub schedule_items: usize,
+
+    /// Number of enabled schedule items
+    #[serde(default)]
+    pub enabled_schedule_items: usize,
+
+    /// Active profile
+    #[serde(default)]
+    pub active_profile: Option<String>,
+
+    /// Running helper processes
+    #[serde(default)]
+    pub processes: Vec<ProcessInfo>,
+
+    /// Whether the wallpaper is paused because a fullscreen application is focused
+    #[serde(default)]
+    pub auto_paused: bool,
+
+    /// Latest resource use of the running wallpaper's players
+    #[serde(default)]
+    pub wallpaper_usage: Option<WallpaperUsage>,
+
+    /// Average resource use of each wallpaper that ran, by path
+    #[serde(default)]
+    pub usage_by_wallpaper: HashMap<String, WallpaperUsage>,
+
+    /// Whether the wallpaper is paused because nobody is using the computer
+    #[serde(default)]
+    pub user_away: bool,
+
+    /// Whether the wallpaper is held paused on request
+    #[serde(default)]
+    pub held: bool,
+
+    /// Whether the battery saver replaced animated wallpapers with still frames
+    #[serde(default)]
+    pub saving_power: bool,
+
+    /// Whether focus mode toned down the wallpaper because windows cover the desktop
+    #[serde(default)]
+    pub focused: bool,
+
+    /// Weather drawn over the wallpaper
+    #[serde(default)]
+    pub weather_effect: Option<WeatherEffect>,
+
+    /// Whether the wallpaper's quality is lowered because the system is busy
+    #[serde(default)]
+    pub quality_reduced: bool,
+
+    /// Most recent events, newest last
+    #[serde(default)]
+    pub recent_events: Vec<Event>,
+
+    /// Players a previous run left running, until stopped or kept
+    #[serde(default)]
+    pub orphans: Vec<Orphan>,
+
+    /// Whether a wallpaper change can be undone
+    #[serde(default)]
+    pub can_undo: bool,
+
+    /// Whether an undone wallpaper change can be redone
+    #[serde(default)]
+    pub can_redo: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_wire_format() {
+        let json = serde_json::to_string(&Request::ReloadConfig).unwrap();
+        assert_eq!(json, r#"{"command":"reload_config"}"#);
+
+        let request: Request = serde_json::from_str(r#"{"command":"stop_wallpaper"}"#).unwrap();
+        assert!(matches!(request, Request::StopWallpaper));
+    }
+}
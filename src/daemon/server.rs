@@ -0,0 +1,1270 @@
+//! Long-running daemon owning the scheduler, wallpapers and online sources
+use crate::core::config::{ResolutionFilterConfig, WallpaperType};
+use crate::core::{
+    config_history, image_format, logging, orphans, runtime, AdaptiveQuality, AppError, AppResult,
+    AudioOverlay, AutoChange, AutoPause, ChangeHooks, Config, ConfigFile, ConfigWatcher,
+    CurrentWallpaperStore, EventLevel, EventLog, FocusMode, Orphan, PaletteStore,
+    ProcessController, ProfileStore, ResourceMonitor, SecretStore, VirtualCamera, WallpaperHistory,
+    WallpaperInfo, WallpaperScheduler, Watchdog, WeatherEffects, WidgetManager,
+};
+use crate::daemon::api::ApiServer;
+use crate::daemon::bot::BotBridge;
+use crate::daemon::client::DaemonClient;
+use crate::daemon::protocol::{DaemonStatus, Request, Response};
+use crate::daemon::sync::LanSync;
+use crate::daemon::transport::{self, Stream};
+use crate::experiments::effects::temperature;
+use crate::platform::display::{self, MonitorInfo};
+use crate::platform::topology::{self, TopologyWatcher};
+use crate::platform::workspace::{self, Workspace, WorkspaceWatcher};
+use crate::platform::{gpu, signals, WallpaperManager};
+use crate::sources::{AutoDownloader, Calendar, LiveEarth, ResolutionFilter};
+use log::{debug, error, info, warn};
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Instant;
+
+/// Number of recent events included in the status
+const STATUS_EVENTS: usize = 10;
+
+/// A frontend request, with where to send its reply
+type Incoming = (Request, mpsc::Sender<Reply>);
+
+/// The request loop's answer to a request
+enum Reply {
+    /// Response to send back as is
+    Done(Response),
+
+    /// Long job, run on the connection's own thread so the request loop
+    /// keeps answering other frontends meanwhile
+    Job(Box<dyn FnOnce() -> Response + Send>),
+}
+
+/// Background daemon
+///
+/// Frontends attach over the local transport and can come and go while
+/// wallpapers and the scheduler keep running.
+pub struct Daemon {
+    /// Application configuration
+    config: Config,
+
+    /// Wallpaper scheduler, which also owns the current wallpaper
+    scheduler: WallpaperScheduler,
+
+    /// Online source auto-downloader
+    auto_downloader: AutoDownloader,
+
+    /// Live Earth updater
+    live_earth: LiveEarth,
+
+    /// Changes to the next wallpaper of the auto-change folder on a timer
+    auto_change: AutoChange,
+
+    /// Local HTTP API
+    api: ApiServer,
+
+    /// Wallpaper sync with other computers on the LAN
+    lan_sync: LanSync,
+
+    /// Remote control from a chat bot
+    bot: BotBridge,
+
+    /// Player resource monitor
+    resources: ResourceMonitor,
+
+    /// Pauses the wallpaper while a fullscreen application is focused and
+    /// swaps it for a still frame on battery power
+    auto_pause: AutoPause,
+
+    /// Toning down the wallpaper while windows cover the desktop
+    focus_mode: FocusMode,
+
+    /// Draws the weather outside over static wallpapers
+    weather_effects: WeatherEffects,
+
+    /// Draws audio-reactive effects over static and video wallpapers
+    audio_overlay: AudioOverlay,
+
+    /// Sends the wallpaper to a virtual camera or OBS
+    virtual_camera: VirtualCamera,
+
+    /// Lowers the wallpaper's quality while the system is busy
+    quality: AdaptiveQuality,
+
+    /// Restarts the wallpaper's player when it crashes
+    watchdog: Watchdog,
+
+    /// Widget data updater, when running without the settings window
+    widgets: Option<WidgetManager>,
+
+    /// Reloads the configuration files when they are edited by hand
+    config_watcher: ConfigWatcher,
+
+    /// Reports workspace switches while workspaces have wallpapers of their own
+    workspace_watcher: WorkspaceWatcher,
+
+    /// Active workspace, while watched
+    workspace: Option<Workspace>,
+
+    /// Wallpaper shown for the active workspace, if it has one
+    workspace_wallpaper: Option<WallpaperInfo>,
+
+    /// Reports monitor setup changes while setups have profiles of their own
+    topology_watcher: TopologyWatcher,
+
+    /// Secret store shared with the online sources and widgets
+    secret_store: Arc<SecretStore>,
+
+    /// Platform-specific wallpaper manager
+    wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>,
+
+    /// Image the desktop showed before the daemon applied any, put back on shutdown
+    system_wallpaper: Option<PathBuf>,
+
+    /// Players a previous run left running, until the user stops or keeps them
+    orphans: Vec<Orphan>,
+
+    /// Wallpaper backend description
+    backend: String,
+
+    /// Start time
+    started: Instant,
+}
+
+impl Daemon {
+    /// Create a new daemon
+    pub fn new(wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>) -> Self {
+        let config = Config::load().unwrap_or_else(|e| {
+            error!("Failed to load configuration: {}", e);
+            Config::default()
+        });
+
+        let secret_store = Arc::new(SecretStore::new());
+        let backend = wallpaper_manager.backend_name();
+
+        let mut scheduler = WallpaperScheduler::new(wallpaper_manager.clone());
+        scheduler.set_placement(config.wallpaper.placement.clone());
+        scheduler.set_frame_rate(config.wallpaper.frame_rate.clone());
+        scheduler.set_lock_screen_sync(config.wallpaper.sync_lock_screen);
+        scheduler.set_transition(config.wallpaper.transition);
+        scheduler.set_animation(config.wallpaper.animation.clone());
+        scheduler.set_animated_image(config.wallpaper.animated_image.clone());
+        scheduler.set_particles(config.wallpaper.particles.clone());
+        scheduler.set_procedural(config.wallpaper.procedural.clone());
+        scheduler.set_slideshow(config.wallpaper.slideshow.clone());
+        scheduler.set_now_playing(config.wallpaper.now_playing.clone());
+        scheduler.set_color_temperature(config.wallpaper.color_temperature.clone());
+        temperature::set_config(config.wallpaper.color_temperature.clone());
+        ChangeHooks::shared().set_commands(config.wallpaper.change_hooks.clone());
+        ChangeHooks::shared().set_palette(config.wallpaper.palette.clone());
+        ChangeHooks::shared().set_accent(config.wallpaper.accent_sync.clone());
+        gpu::set_preference(config.app.gpu_preference);
+        Calendar::shared().configure(config.sources.calendar.clone(), secret_store.clone());
+        if let Err(e) = scheduler.load_schedule(&config) {
+            error!("Failed to load schedule: {}", e);
+        }
+
+        let auto_downloader =
+            AutoDownloader::new(&config, wallpaper_manager.clone(), secret_store.clone());
+        let live_earth = LiveEarth::new(&config, wallpaper_manager.clone());
+        let auto_change = AutoChange::new(config.wallpaper.auto_change.clone());
+        let api = ApiServer::new(&config, secret_store.clone());
+        let lan_sync = LanSync::new(config.sync.clone(), secret_store.clone());
+        let bot = BotBridge::new(config.bot.clone(), secret_store.clone());
+        let resources = ResourceMonitor::new(&config, scheduler.current_wallpaper());
+        let auto_pause = AutoPause::new(
+            config.app.auto_pause.clone(),
+            scheduler.current_wallpaper(),
+            wallpaper_manager.clone(),
+        );
+        let focus_mode = FocusMode::new(
+            config.app.focus_mode.clone(),
+            scheduler.current_wallpaper(),
+            wallpaper_manager.clone(),
+        );
+        focus_mode.set_placement(config.wallpaper.placement.clone());
+        let weather_effects = WeatherEffects::new(
+            config.wallpaper.weather_effects.clone(),
+            scheduler.current_wallpaper(),
+            wallpaper_manager.clone(),
+            secret_store.clone(),
+        );
+        weather_effects.set_placement(config.wallpaper.placement.clone());
+        let audio_overlay = AudioOverlay::new(
+            config.wallpaper.audio_overlay.clone(),
+            scheduler.current_wallpaper(),
+            wallpaper_manager.clone(),
+            weather_effects.effect_handle(),
+        );
+        audio_overlay.set_placement(config.wallpaper.placement.clone());
+        let virtual_camera = VirtualCamera::new(
+            config.wallpaper.virtual_camera.clone(),
+            scheduler.current_wallpaper(),
+        );
+        let quality = AdaptiveQuality::new(
+            config.app.adaptive_quality.clone(),
+            scheduler.current_wallpaper(),
+        );
+        let watchdog = Watchdog::new(
+            config.app.watchdog.clone(),
+            scheduler.current_wallpaper(),
+            wallpaper_manager.clone(),
+        );
+        watchdog.set_fallback(
+            config.wallpaper.last_static.as_ref().map(PathBuf::from),
+            config.wallpaper.placement.clone(),
+        );
+
+        Self {
+            config,
+            scheduler,
+            auto_downloader,
+            live_earth,
+            auto_change,
+            api,
+            lan_sync,
+            bot,
+            resources,
+            auto_pause,
+            focus_mode,
+            weather_effects,
+            audio_overlay,
+            virtual_camera,
+            quality,
+            watchdog,
+            widgets: None,
+            config_watcher: ConfigWatcher::new(),
+            workspace_watcher: WorkspaceWatcher::new(),
+            topology_watcher: TopologyWatcher::new(),
+            workspace: None,
+            workspace_wallpaper: None,
+            secret_store,
+            wallpaper_manager,
+            system_wallpaper: None,
+            orphans: Vec::new(),
+            backend,
+            started: Instant::now(),
+        }
+    }
+
+    /// Also keep the widgets updated
+    ///
+    /// Used in headless mode, where no settings window runs the widgets.
+    pub fn with_widgets(mut self) -> Self {
+        let mut widgets = WidgetManager::new();
+        widgets.set_secret_store(self.secret_store.clone());
+        widgets.set_power_policy(self.config.app.auto_pause.clone());
+        if let Err(e) = widgets.load_widgets(&self.config) {
+            error!("Failed to load widgets: {}", e);
+        }
+        self.widgets = Some(widgets);
+        self
+    }
+
+    /// Run the daemon until a shutdown request or a termination signal arrives
+    pub fn run(&mut self) -> AppResult<()> {
+        let listener = transport::bind(transport::DAEMON_ENDPOINT)?;
+        info!("Daemon listening for frontends");
+
+        // Shut down through the request loop so the players are stopped
+        signals::on_termination(|| {
+            if let Err(e) = DaemonClient::new().send(&Request::Shutdown) {
+                error!("Failed to shut down the daemon: {}", e);
+                std::process::exit(1);
+            }
+        });
+
+        self.find_orphans();
+        self.remember_system_wallpaper();
+        self.start_services();
+        self.show_workspace_wallpaper();
+        if self.workspace_wallpaper.is_none() {
+            self.restore_wallpaper();
+        }
+        self.lan_sync.follow_changes();
+
+        // Connections are read and answered on threads of their own, and
+        // their requests handled here one at a time
+        let (sender, requests) = mpsc::channel::<Incoming>();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        let sender = sender.clone();
+                        thread::spawn(move || serve_connection(stream, sender));
+                    }
+                    Err(e) => warn!("Failed to accept frontend connection: {}", e),
+                }
+            }
+        });
+
+        for (request, reply) in requests {
+            debug!("Daemon request: {:?}", request);
+            let shutdown = matches!(request, Request::Shutdown);
+            let _ = reply.send(self.handle_request(request));
+            if shutdown {
+                break;
+            }
+        }
+
+        self.stop_services();
+        self.stop_leftover_players();
+        self.restore_system_wallpaper();
+        transport::cleanup(transport::DAEMON_ENDPOINT);
+        info!("Daemon stopped");
+        Ok(())
+    }
+
+    /// Start the background services
+    fn start_services(&mut self) {
+        if let Err(e) = self.scheduler.start() {
+            error!("Failed to start scheduler: {}", e);
+        }
+
+        if let Err(e) = self.auto_downloader.start() {
+            error!("Failed to start auto-downloader: {}", e);
+        }
+
+        if let Err(e) = self.live_earth.start() {
+            error!("Failed to start Live Earth: {}", e);
+        }
+
+        if let Err(e) = self.auto_change.start(request_next_wallpaper) {
+            error!("Failed to start auto-change: {}", e);
+        }
+
+        if let Err(e) = self.api.start() {
+            error!("Failed to start API server: {}", e);
+        }
+
+        if let Err(e) = self.lan_sync.start() {
+            error!("Failed to start LAN sync: {}", e);
+        }
+
+        if let Err(e) = self.bot.start() {
+            error!("Failed to start bot bridge: {}", e);
+        }
+
+        if let Err(e) = self.resources.start() {
+            error!("Failed to start resource monitor: {}", e);
+        }
+
+        if let Err(e) = self.auto_pause.start() {
+            error!("Failed to start auto-pause: {}", e);
+        }
+
+        if let Err(e) = self.focus_mode.start() {
+            error!("Failed to start focus mode: {}", e);
+        }
+
+        if let Err(e) = self.weather_effects.start() {
+            error!("Failed to start weather effects: {}", e);
+        }
+
+        if let Err(e) = self.audio_overlay.start() {
+            error!("Failed to start audio overlay: {}", e);
+        }
+
+        if let Err(e) = self.virtual_camera.start() {
+            error!("Failed to start virtual camera: {}", e);
+        }
+
+        if let Err(e) = self.quality.start() {
+            error!("Failed to start adaptive quality: {}", e);
+        }
+
+        if let Err(e) = self.watchdog.start() {
+            error!("Failed to start watchdog: {}", e);
+        }
+
+        if let Some(widgets) = &mut self.widgets {
+            if let Err(e) = widgets.start() {
+                error!("Failed to start widgets: {}", e);
+            }
+        }
+
+        let watched = Config::get_config_dir()
+            .map_err(|e| AppError::ConfigError(e.to_string()))
+            .and_then(|dir| self.config_watcher.start(&dir, request_reload));
+        if let Err(e) = watched {
+            error!("Failed to watch the configuration: {}", e);
+        }
+
+        self.watch_workspaces();
+        self.watch_topology();
+    }
+
+    /// Stop the background services and the current wallpaper
+    fn stop_services(&mut self) {
+        self.config_watcher.stop();
+        self.workspace_watcher.stop();
+        self.topology_watcher.stop();
+
+        if let Err(e) = self.scheduler.stop() {
+            error!("Failed to stop scheduler: {}", e);
+        }
+
+        if let Err(e) = self.auto_downloader.stop() {
+            error!("Failed to stop auto-downloader: {}", e);
+        }
+
+        if let Err(e) = self.live_earth.stop() {
+            error!("Failed to stop Live Earth: {}", e);
+        }
+
+        if let Err(e) = self.auto_change.stop() {
+            error!("Failed to stop auto-change: {}", e);
+        }
+
+        if let Err(e) = self.api.stop() {
+            error!("Failed to stop API server: {}", e);
+        }
+
+        if let Err(e) = self.lan_sync.stop() {
+            error!("Failed to stop LAN sync: {}", e);
+        }
+
+        if let Err(e) = self.bot.stop() {
+            error!("Failed to stop bot bridge: {}", e);
+        }
+
+        if let Err(e) = self.resources.stop() {
+            error!("Failed to stop resource monitor: {}", e);
+        }
+
+        if let Err(e) = self.auto_pause.stop() {
+            error!("Failed to stop auto-pause: {}", e);
+        }
+
+        if let Err(e) = self.focus_mode.stop() {
+            error!("Failed to stop focus mode: {}", e);
+        }
+
+        if let Err(e) = self.weather_effects.stop() {
+            error!("Failed to stop weather effects: {}", e);
+        }
+
+        if let Err(e) = self.audio_overlay.stop() {
+            error!("Failed to stop audio overlay: {}", e);
+        }
+
+        if let Err(e) = self.virtual_camera.stop() {
+            error!("Failed to stop virtual camera: {}", e);
+        }
+
+        if let Err(e) = self.quality.stop() {
+            error!("Failed to stop adaptive quality: {}", e);
+        }
+
+        if let Err(e) = self.watchdog.stop() {
+            error!("Failed to stop watchdog: {}", e);
+        }
+
+        if let Some(widgets) = &mut self.widgets {
+            if let Err(e) = widgets.stop() {
+                error!("Failed to stop widgets: {}", e);
+            }
+        }
+
+        if let Err(e) = self.scheduler.stop_current() {
+            error!("Failed to stop wallpaper: {}", e);
+        }
+    }
+
+    /// Look for players a crashed run left behind, then start a new PID file
+    fn find_orphans(&mut self) {
+        let pid_file = Config::get_players_file();
+        self.orphans = orphans::find(&pid_file, &orphans::web_urls(&self.config));
+        ProcessController::shared().set_pid_file(pid_file);
+
+        if !self.orphans.is_empty() {
+            warn!(
+                "Found {} players left running by a previous run",
+                self.orphans.len()
+            );
+            let message = format!(
+                "{} wallpaper players from a previous run are still running",
+                self.orphans.len()
+            );
+            EventLog::shared().record(EventLevel::Warning, "daemon", message, None);
+        }
+    }
+
+    /// Stop the orphaned players, or keep them and track them as the daemon's own
+    fn resolve_orphans(&mut self, adopt: bool) -> usize {
+        let controller = ProcessController::shared();
+        let mut resolved = 0;
+        for orphan in self.orphans.drain(..) {
+            if adopt {
+                controller.register(orphan.pid, &orphan.name, &orphan.role);
+                resolved += 1;
+            } else if orphans::terminate(&orphan) {
+                info!("Stopped orphaned player {} ({})", orphan.name, orphan.pid);
+                resolved += 1;
+            }
+        }
+        resolved
+    }
+
+    /// Kill players still running after the wallpaper was stopped
+    fn stop_leftover_players(&self) {
+        let controller = ProcessController::shared();
+        for pid in controller.pids() {
+            if controller.kill(pid) {
+                info!("Stopped leftover player {}", pid);
+            }
+        }
+    }
+
+    /// Note the image the desktop shows before applying any wallpaper
+    ///
+    /// The note is kept on disk until shutdown, so after a crash the restarted
+    /// daemon doesn't take its own wallpaper for the desktop's.
+    fn remember_system_wallpaper(&mut self) {
+        let file = Config::get_system_wallpaper_file();
+        if let Ok(path) = fs::read_to_string(&file) {
+            self.system_wallpaper = Some(PathBuf::from(path.trim()));
+            return;
+        }
+
+        match runtime::block_on(self.wallpaper_manager.system_wallpaper()) {
+            Ok(Some(path)) => {
+                if let Err(e) = fs::write(&file, path.to_string_lossy().as_bytes()) {
+                    warn!("Failed to save the system wallpaper: {}", e);
+                }
+                self.system_wallpaper = Some(path);
+            }
+            Ok(None) => debug!("The desktop doesn't report its wallpaper"),
+            Err(e) => warn!("Failed to get the system wallpaper: {}", e),
+        }
+    }
+
+    /// Put back the image the desktop showed before the daemon started
+    fn restore_system_wallpaper(&mut self) {
+        let Some(path) = self.system_wallpaper.take() else {
+            return;
+        };
+
+        match runtime::block_on(self.wallpaper_manager.set_static_wallpaper(&path)) {
+            Ok(()) => info!("Restored the system wallpaper {}", path.display()),
+            Err(e) => warn!(
+                "Failed to restore the system wallpaper {}: {}",
+                path.display(),
+                e
+            ),
+        }
+        if let Err(e) = fs::remove_file(Config::get_system_wallpaper_file()) {
+            warn!("Failed to remove the system wallpaper note: {}", e);
+        }
+    }
+
+    /// Re-apply the wallpaper saved in the configuration, or those saved per monitor
+    ///
+    /// While mirroring, the saved wallpaper, or else a monitor's own, goes on every monitor.
+    fn restore_wallpaper(&mut self) {
+        let saved = &self.config.wallpaper;
+        let wallpapers: Vec<WallpaperInfo> = if saved.placement.mirror {
+            WallpaperInfo::saved(saved)
+                .or_else(|| saved.monitor_wallpapers.values().next().cloned())
+                .into_iter()
+                .collect()
+        } else if saved.monitor_wallpapers.is_empty() {
+            WallpaperInfo::saved(saved).into_iter().collect()
+        } else {
+            saved.monitor_wallpapers.values().cloned().collect()
+        };
+
+        for wallpaper in wallpapers {
+            if let Err(e) = self.scheduler.apply_now(&wallpaper) {
+                warn!("Failed to restore wallpaper: {}", e);
+                EventLog::shared().error("daemon", "Failed to restore the previous wallpaper", e);
+            }
+        }
+    }
+
+    /// Watch for workspace switches while any workspace has a wallpaper of its own
+    fn watch_workspaces(&mut self) {
+        if self.config.wallpaper.workspace_wallpapers.is_empty() {
+            self.workspace_watcher.stop();
+            self.workspace = None;
+        } else if !self.workspace_watcher.is_running() {
+            match self.workspace_watcher.start(request_workspace_switch) {
+                Ok(()) => self.workspace = workspace::current_workspace(),
+                Err(e) => warn!("Workspace wallpapers won't switch: {}", e),
+            }
+        }
+    }
+
+    /// Watch for monitor setup changes while any setup has a profile of its own
+    ///
+    /// The setup connected when watching starts gets its profile right away.
+    fn watch_topology(&mut self) {
+        if self.config.app.topology_profiles.is_empty() {
+            self.topology_watcher.stop();
+        } else if !self.topology_watcher.is_running() {
+            match self.topology_watcher.start(request_topology_switch) {
+                Ok(()) => {
+                    if let Some(current) = topology::current_topology() {
+                        self.switch_topology(&current);
+                    }
+                }
+                Err(e) => warn!("Profiles won't follow the monitors: {}", e),
+            }
+        }
+    }
+
+    /// Switch to the profile of a monitor setup, if it has one and it isn't active
+    fn switch_topology(&mut self, topology: &str) {
+        let Some(name) = self.config.app.topology_profiles.get(topology).cloned() else {
+            debug!("No profile for monitor setup {}", topology);
+            return;
+        };
+        if self.config.app.active_profile.as_deref() == Some(name.as_str()) {
+            return;
+        }
+
+        info!("Monitor setup changed to {}", topology);
+        if let Err(e) = self.switch_profile(&name) {
+            error!("Failed to switch to profile {}: {}", name, e);
+            EventLog::shared().error(
+                "profiles",
+                format!(
+                    "Failed to switch to profile {} for the connected monitors",
+                    name
+                ),
+                &e,
+            );
+        }
+    }
+
+    /// Show the active workspace's wallpaper, or the usual one on workspaces without
+    fn show_workspace_wallpaper(&mut self) {
+        let assigned = self
+            .workspace
+            .as_ref()
+            .and_then(|workspace| {
+                self.config
+                    .wallpaper
+                    .workspace_wallpapers
+                    .get(&workspace.id)
+            })
+            .map(|assigned| assigned.wallpaper.clone());
+        if assigned == self.workspace_wallpaper {
+            return;
+        }
+
+        let had_own = std::mem::replace(&mut self.workspace_wallpaper, assigned.clone()).is_some();
+        match assigned {
+            Some(wallpaper) => {
+                let name = self
+                    .workspace
+                    .as_ref()
+                    .map(|workspace| workspace.name.clone())
+                    .unwrap_or_default();
+                info!(
+                    "Showing the wallpaper of workspace {}: {}",
+                    name, wallpaper.name
+                );
+                if let Err(e) = self.scheduler.apply_now(&wallpaper) {
+                    warn!("Failed to show the workspace's wallpaper: {}", e);
+                    EventLog::shared().error(
+                        "daemon",
+                        format!("Failed to show the wallpaper of workspace {}", name),
+                        e,
+                    );
+                }
+            }
+            None if had_own => self.restore_wallpaper(),
+            None => {}
+        }
+    }
+
+    /// Handle a request
+    ///
+    /// Requests that can take a while come back as jobs, so they don't hold
+    /// up the ones after them.
+    fn handle_request(&mut self, request: Request) -> Reply {
+        let response = match request {
+            Request::Ping => Response::ok(),
+            Request::Status => Response {
+                status: Some(self.status()),
+                ..Response::ok()
+            },
+            Request::ApplyWallpaper { wallpaper } => {
+                let apply = self.scheduler.applier();
+                return Reply::Job(Box::new(move || match apply(&wallpaper) {
+                    Ok(()) => Response::ok(),
+                    Err(e) => Response::failed(&e),
+                }));
+            }
+            Request::NextWallpaper => match self.next_wallpaper() {
+                Ok(wallpaper) => {
+                    let message = wallpaper
+                        .path
+                        .as_ref()
+                        .map(|p| p.display().to_string())
+                        .unwrap_or_default();
+                    Response {
+                        status: Some(self.status()),
+                        ..Response::ok_with_message(message)
+                    }
+                }
+                Err(e) => Response::failed(&e),
+            },
+            Request::StopWallpaper => match self.scheduler.stop_current() {
+                Ok(()) => Response::ok(),
+                Err(e) => Response::failed(&e),
+            },
+            Request::PauseWallpaper => {
+                self.auto_pause.hold(true);
+                Response::ok_with_message("Paused")
+            }
+            Request::ResumeWallpaper => {
+                self.auto_pause.hold(false);
+                Response::ok_with_message("Resumed")
+            }
+            Request::StopMonitorWallpaper { monitor } => match self.scheduler.stop_on(&monitor) {
+                Ok(()) => Response::ok(),
+                Err(e) => Response::failed(&e),
+            },
+            Request::SwitchWorkspace { workspace } => {
+                debug!("Switched to workspace {}", workspace.name);
+                if self.workspace_watcher.is_running() {
+                    self.workspace = Some(workspace);
+                    self.show_workspace_wallpaper();
+                }
+                Response::ok()
+            }
+            Request::SetLockScreen { path } => match runtime::block_on(async {
+                let path =
+                    image_format::compatible(&path, self.wallpaper_manager.image_formats()).await?;
+                self.wallpaper_manager
+                    .set_lock_screen_wallpaper(&path)
+                    .await
+            }) {
+                Ok(()) => Response::ok(),
+                Err(e) => Response::failed(&e),
+            },
+            Request::SwitchTopology { topology } => {
+                if self.topology_watcher.is_running() {
+                    self.switch_topology(&topology);
+                }
+                Response::ok()
+            }
+            Request::UndoWallpaper => {
+                let result = WallpaperHistory::shared()
+                    .undo(|wallpaper| self.scheduler.apply_now(wallpaper));
+                self.history_response(result, "Nothing to undo")
+            }
+            Request::RedoWallpaper => {
+                let result = WallpaperHistory::shared()
+                    .redo(|wallpaper| self.scheduler.apply_now(wallpaper));
+                self.history_response(result, "Nothing to redo")
+            }
+            Request::ReloadConfig => match Config::load() {
+                Ok(config) => {
+                    // Turning mirroring on or off re-arranges the wallpapers right away
+                    let mirror_changed =
+                        config.wallpaper.placement.mirror != self.config.wallpaper.placement.mirror;
+                    self.update_config(config);
+                    if mirror_changed && self.workspace_wallpaper.is_none() {
+                        self.restore_wallpaper();
+                    }
+                    self.watch_workspaces();
+                    self.show_workspace_wallpaper();
+                    self.watch_topology();
+                    info!("Reloaded configuration");
+                    Response::ok()
+                }
+                Err(e) => Response::error(format!("Failed to reload configuration: {}", e)),
+            },
+            Request::ReloadSchedule => match self.scheduler.load_schedule(&self.config) {
+                Ok(()) => Response::ok(),
+                Err(e) => Response::failed(&e),
+            },
+            // The settings window runs the widgets when the daemon doesn't
+            Request::ReloadWidgets => match self
+                .widgets
+                .as_mut()
+                .map_or(Ok(()), |widgets| widgets.load_widgets(&self.config))
+            {
+                Ok(()) => Response::ok(),
+                Err(e) => Response::failed(&e),
+            },
+            Request::StartScheduler => match self.scheduler.start() {
+                Ok(()) => Response::ok(),
+                Err(e) => Response::failed(&e),
+            },
+            Request::StopScheduler => match self.scheduler.stop() {
+                Ok(()) => Response::ok(),
+                Err(e) => Response::failed(&e),
+            },
+            Request::DownloadNow => {
+                let run = self.auto_downloader.runner();
+                return Reply::Job(Box::new(move || match run() {
+                    Ok(paths) => {
+                        Response::ok_with_message(format!("Downloaded {} wallpapers", paths.len()))
+                    }
+                    Err(e) => Response::failed(&e),
+                }));
+            }
+            Request::UpdateLiveEarth => {
+                let update = self.live_earth.updater();
+                return Reply::Job(Box::new(move || match update() {
+                    Ok(path) => Response::ok_with_message(path.display().to_string()),
+                    Err(e) => Response::failed(&e),
+                }));
+            }
+            Request::SwitchProfile { name } => match self.switch_profile(&name) {
+                Ok(()) => Response {
+                    status: Some(self.status()),
+                    ..Response::ok_with_message(format!("Switched to profile {}", name))
+                },
+                Err(e) => Response::failed(&e),
+            },
+            Request::Events { since } => Response {
+                events: Some(EventLog::shared().since(since)),
+                ..Response::ok()
+            },
+            Request::ResolveOrphans { adopt } => {
+                let resolved = self.resolve_orphans(adopt);
+                let action = if adopt { "Kept" } else { "Stopped" };
+                Response::ok_with_message(format!(
+                    "{} {} players from a previous run",
+                    action, resolved
+                ))
+            }
+            Request::Shutdown => {
+                info!("Shutdown requested");
+                Response::ok()
+            }
+        };
+        Reply::Done(response)
+    }
+
+    /// Answer an undo or redo with the wallpaper it went to
+    fn history_response(
+        &mut self,
+        result: AppResult<Option<WallpaperInfo>>,
+        nothing: &str,
+    ) -> Response {
+        match result {
+            Ok(Some(wallpaper)) => {
+                info!("Switched to wallpaper from history: {}", wallpaper.name);
+                let message = wallpaper.name;
+                Response {
+                    status: Some(self.status()),
+                    ..Response::ok_with_message(message)
+                }
+            }
+            Ok(None) => Response::ok_with_message(nothing),
+            Err(e) => Response::failed(&e),
+        }
+    }
+
+    /// Hand a new configuration to the services
+    fn update_config(&mut self, config: Config) {
+        self.auto_downloader.update_config(config.sources.clone());
+        self.live_earth
+            .update_config(config.sources.live_earth.clone());
+        self.auto_change
+            .update_config(config.wallpaper.auto_change.clone());
+        self.scheduler
+            .set_placement(config.wallpaper.placement.clone());
+        self.scheduler
+            .set_frame_rate(config.wallpaper.frame_rate.clone());
+        self.scheduler
+            .set_lock_screen_sync(config.wallpaper.sync_lock_screen);
+        self.scheduler.set_transition(config.wallpaper.transition);
+        self.scheduler
+            .set_animation(config.wallpaper.animation.clone());
+        self.scheduler
+            .set_animated_image(config.wallpaper.animated_image.clone());
+        self.scheduler
+            .set_particles(config.wallpaper.particles.clone());
+        self.scheduler
+            .set_procedural(config.wallpaper.procedural.clone());
+        self.scheduler
+            .set_slideshow(config.wallpaper.slideshow.clone());
+        self.scheduler
+            .set_now_playing(config.wallpaper.now_playing.clone());
+        self.scheduler
+            .set_color_temperature(config.wallpaper.color_temperature.clone());
+        temperature::set_config(config.wallpaper.color_temperature.clone());
+        ChangeHooks::shared().set_commands(config.wallpaper.change_hooks.clone());
+        ChangeHooks::shared().set_palette(config.wallpaper.palette.clone());
+        ChangeHooks::shared().set_accent(config.wallpaper.accent_sync.clone());
+        gpu::set_preference(config.app.gpu_preference);
+        Calendar::shared().configure(config.sources.calendar.clone(), self.secret_store.clone());
+        logging::set_level(config.app.log_level);
+        config_history::set_enabled(config.app.config_history);
+        self.api.update_config(&config);
+        self.lan_sync.update_config(config.sync.clone());
+        self.bot.update_config(config.bot.clone());
+        self.resources
+            .update_config(config.app.resource_limits.clone());
+        self.auto_pause.update_config(config.app.auto_pause.clone());
+        self.focus_mode.update_config(config.app.focus_mode.clone());
+        self.focus_mode
+            .set_placement(config.wallpaper.placement.clone());
+        self.weather_effects
+            .update_config(config.wallpaper.weather_effects.clone());
+        self.weather_effects
+            .set_placement(config.wallpaper.placement.clone());
+        self.audio_overlay
+            .update_config(config.wallpaper.audio_overlay.clone());
+        self.audio_overlay
+            .set_placement(config.wallpaper.placement.clone());
+        self.virtual_camera
+            .update_config(config.wallpaper.virtual_camera.clone());
+        self.quality
+            .update_config(config.app.adaptive_quality.clone());
+        self.watchdog.update_config(config.app.watchdog.clone());
+        self.watchdog.set_fallback(
+            config.wallpaper.last_static.as_ref().map(PathBuf::from),
+            config.wallpaper.placement.clone(),
+        );
+        if let Some(widgets) = &self.widgets {
+            widgets.set_power_policy(config.app.auto_pause.clone());
+        }
+        self.config = config;
+    }
+
+    /// Switch to a profile and apply its wallpaper, schedule and widgets
+    fn switch_profile(&mut self, name: &str) -> AppResult<()> {
+        // Start from the saved configuration, which has the frontends' latest changes
+        let mut config = Config::load().map_err(|e| AppError::ConfigError(e.to_string()))?;
+        ProfileStore::open(&config).switch(name, &mut config)?;
+        config
+            .save()
+            .map_err(|e| AppError::ConfigError(e.to_string()))?;
+        self.update_config(config);
+
+        self.scheduler.load_schedule(&self.config)?;
+        if let Some(widgets) = &mut self.widgets {
+            widgets.load_widgets(&self.config)?;
+        }
+
+        match WallpaperInfo::saved(&self.config.wallpaper) {
+            Some(wallpaper) => self.scheduler.apply_now(&wallpaper)?,
+            None => self.scheduler.stop_current()?,
+        }
+
+        EventLog::shared().info("profiles", format!("Switched to profile {}", name));
+        Ok(())
+    }
+
+    /// Apply the wallpaper after the current one in its folder
+    fn next_wallpaper(&mut self) -> AppResult<WallpaperInfo> {
+        let current = CurrentWallpaperStore::shared()
+            .get()
+            .map(|state| state.wallpaper)
+            .or_else(|| WallpaperInfo::saved(&self.config.wallpaper));
+        let wallpaper_type = current
+            .as_ref()
+            .map(|wallpaper| wallpaper.r#type.clone())
+            .unwrap_or_else(|| self.config.wallpaper.wallpaper_type.clone());
+        let monitor = current
+            .as_ref()
+            .and_then(|wallpaper| wallpaper.monitor.clone());
+        let current_path = current.and_then(|wallpaper| wallpaper.path);
+
+        let folder = self
+            .config
+            .wallpaper
+            .auto_change
+            .folder
+            .as_ref()
+            .map(PathBuf::from)
+            .or_else(|| {
+                current_path
+                    .as_ref()
+                    .and_then(|p| p.parent())
+                    .map(Path::to_path_buf)
+            })
+            .ok_or_else(|| {
+                AppError::WallpaperError(
+                    "No wallpaper folder to pick the next wallpaper from".to_string(),
+                )
+            })?;
+
+        // Images are matched against the monitor the wallpaper is shown on;
+        // portrait monitors get portrait images when the folder has any
+        let auto_change = &self.config.wallpaper.auto_change;
+        let target = (wallpaper_type == WallpaperType::Static)
+            .then(|| target_monitor(monitor.as_deref()))
+            .flatten();
+        let portrait = target.as_ref().map_or(false, MonitorInfo::is_portrait);
+        let filter = target
+            .filter(|_| auto_change.match_resolution)
+            .map(|target| {
+                let tolerance = auto_change.resolution_tolerance.clamp(0.0, 1.0);
+                ResolutionFilter::new(
+                    &[target],
+                    &ResolutionFilterConfig {
+                        enabled: true,
+                        match_aspect_ratio: true,
+                        aspect_tolerance: tolerance,
+                        min_scale: 1.0 - tolerance,
+                    },
+                )
+            });
+        // Static images can be kept to one palette family
+        let family = auto_change
+            .palette_family
+            .filter(|_| wallpaper_type == WallpaperType::Static);
+        let palettes = PaletteStore::shared();
+        let path = next_in_folder(&folder, current_path.as_deref(), &wallpaper_type, |path| {
+            if family.map_or(false, |family| palettes.family(path) != Some(family)) {
+                return false;
+            }
+            if filter.is_none() && !portrait {
+                return true;
+            }
+            image::image_dimensions(path).map_or(false, |(width, height)| {
+                filter
+                    .as_ref()
+                    .map_or(true, |filter| filter.accepts(width, height))
+                    && (!portrait || height > width)
+            })
+        })?;
+        let wallpaper = WallpaperInfo::new(wallpaper_type, Some(path), None).on_monitor(monitor);
+        self.scheduler.apply_now(&wallpaper)?;
+        info!("Switched to next wallpaper: {}", wallpaper.name);
+        Ok(wallpaper)
+    }
+
+    /// Get the daemon status
+    fn status(&self) -> DaemonStatus {
+        let current = CurrentWallpaperStore::shared().get();
+        let schedule_items = self.scheduler.get_schedule_items();
+        DaemonStatus {
+            pid: std::process::id(),
+            uptime_secs: self.started.elapsed().as_secs(),
+            current_wallpaper: current.as_ref().map(|state| state.wallpaper.clone()),
+            current,
+            monitor_wallpapers: CurrentWallpaperStore::shared().per_monitor(),
+            workspace: self.workspace.clone(),
+            last_download: self.auto_downloader.last_run().map(|t| t.to_rfc3339()),
+            last_live_earth_update: self.live_earth.last_update().map(|t| t.to_rfc3339()),
+            backend: self.backend.clone(),
+            scheduler_running: self.scheduler.is_running(),
+            schedule_items: schedule_items.len(),
+            enabled_schedule_items: schedule_items.iter().filter(|item| item.enabled).count(),
+            active_profile: self.config.app.active_profile.clone(),
+            processes: ProcessController::shared().snapshot(),
+            auto_paused: self.auto_pause.is_paused(),
+            user_away: self.auto_pause.is_user_away(),
+            held: self.auto_pause.is_held(),
+            wallpaper_usage: self.resources.current_usage(),
+            usage_by_wallpaper: self.resources.usage_by_wallpaper(),
+            saving_power: self.auto_pause.is_saving_power(),
+            focused: self.focus_mode.is_focused(),
+            weather_effect: self.weather_effects.current_effect(),
+            quality_reduced: self.quality.is_reduced(),
+            recent_events: EventLog::shared().recent(STATUS_EVENTS),
+            orphans: self.orphans.clone(),
+            can_undo: WallpaperHistory::shared().can_undo(),
+            can_redo: WallpaperHistory::shared().can_redo(),
+        }
+    }
+}
+
+/// Read a frontend's request, hand it to the request loop and send back the reply
+fn serve_connection(stream: Stream, requests: mpsc::Sender<Incoming>) {
+    let _ = stream.set_read_timeout(Some(transport::IO_TIMEOUT));
+    let _ = stream.set_write_timeout(Some(transport::IO_TIMEOUT));
+
+    let mut writer = match stream.try_clone() {
+        Ok(writer) => writer,
+        Err(e) => {
+            warn!("Failed to clone frontend connection: {}", e);
+            return;
+        }
+    };
+
+    let mut line = String::new();
+    if let Err(e) = BufReader::new(stream).read_line(&mut line) {
+        warn!("Failed to read frontend request: {}", e);
+        return;
+    }
+
+    let response = match serde_json::from_str::<Request>(&line) {
+        Ok(request) => {
+            let (reply, answer) = mpsc::channel();
+            let answer = requests
+                .send((request, reply))
+                .ok()
+                .and_then(|_| answer.recv().ok());
+            match answer {
+                Some(Reply::Done(response)) => response,
+                Some(Reply::Job(job)) => job(),
+                None => Response::error("The daemon is shutting down"),
+            }
+        }
+        Err(e) => Response::error(format!("Invalid request: {}", e)),
+    };
+
+    match serde_json::to_string(&response) {
+        Ok(mut json) => {
+            json.push('\n');
+            if let Err(e) = writer.write_all(json.as_bytes()) {
+                warn!("Failed to send daemon response: {}", e);
+            }
+        }
+        Err(e) => error!("Failed to serialize daemon response: {}", e),
+    }
+}
+
+/// Have the daemon reload a configuration file edited by hand
+///
+/// Runs on the watcher's thread, so the request goes through the daemon's own
+/// request loop like any frontend's.
+fn request_reload(file: ConfigFile) {
+    let request = match file {
+        ConfigFile::Config => Request::ReloadConfig,
+        ConfigFile::Schedule => Request::ReloadSchedule,
+        ConfigFile::Widgets => Request::ReloadWidgets,
+    };
+    if let Err(e) = DaemonClient::new().send(&request) {
+        error!("Failed to reload the edited {} file: {}", file.name(), e);
+    }
+}
+
+/// Have the daemon show the wallpaper of the workspace switched to
+///
+/// Runs on the watcher's thread, so the request goes through the daemon's own
+/// request loop like any frontend's.
+fn request_workspace_switch(workspace: Workspace) {
+    if let Err(e) = DaemonClient::new().send(&Request::SwitchWorkspace { workspace }) {
+        error!("Failed to switch to the workspace's wallpaper: {}", e);
+    }
+}
+
+/// Have the daemon change to the next wallpaper of the auto-change folder
+///
+/// Runs on the auto-change thread, so the request goes through the daemon's
+/// own request loop like any frontend's.
+fn request_next_wallpaper() {
+    if let Err(e) = DaemonClient::new().send(&Request::NextWallpaper) {
+        warn!("Failed to change to the next wallpaper: {}", e);
+        EventLog::shared().error("auto-change", "Failed to change to the next wallpaper", e);
+    }
+}
+
+/// Have the daemon switch to the profile of the monitor setup connected
+fn request_topology_switch(topology: String) {
+    if let Err(e) = DaemonClient::new().send(&Request::SwitchTopology { topology }) {
+        error!("Failed to switch to the monitor setup's profile: {}", e);
+    }
+}
+
+/// Get the monitor with the given ID, the primary one when there is none
+fn target_monitor(monitor: Option<&str>) -> Option<MonitorInfo> {
+    let monitors = display::monitors()
+        .map_err(|e| {
+            debug!(
+                "Failed to detect monitors to match wallpapers against: {}",
+                e
+            )
+        })
+        .ok()?;
+    monitors
+        .into_iter()
+        .find(|candidate| monitor.map_or(candidate.primary, |id| candidate.id == id))
+}
+
+/// Find the file after `current` in `folder`, in name order
+///
+/// Wraps around at the end and starts at the first file when `current`
+/// isn't in the folder. Files passing `preferred` come first, so ones that
+/// don't are skipped unless none do.
+fn next_in_folder(
+    folder: &Path,
+    current: Option<&Path>,
+    wallpaper_type: &WallpaperType,
+    preferred: impl Fn(&Path) -> bool,
+) -> AppResult<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(folder)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && wallpaper_type.accepts(path))
+        .collect();
+    files.sort();
+
+    let position = current.and_then(|current| files.iter().position(|path| path == current));
+    let index = position.map_or(0, |i| (i + 1) % files.len().max(1));
+    let preferred = (0..files.len())
+        .map(|offset| (index + offset) % files.len())
+        .find(|&i| preferred(&files[i]));
+    files
+        .into_iter()
+        .nth(preferred.unwrap_or(index))
+        .ok_or_else(|| {
+            AppError::WallpaperError(format!(
+                "No {} wallpapers in {}",
+                wallpaper_type.as_str().to_lowercase(),
+                folder.display()
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_next_in_folder() {
+        let dir = tempdir().unwrap();
+        for name in ["b.png", "a.jpg", "c.PNG", "clip.mp4", "notes.txt"] {
+            fs::write(dir.path().join(name), "").unwrap();
+        }
+        let next = |current: Option<&str>| {
+            let current = current.map(|name| dir.path().join(name));
+            next_in_folder(
+                dir.path(),
+                current.as_deref(),
+                &WallpaperType::Static,
+                |_| true,
+            )
+            .unwrap()
+        };
+
+        assert_eq!(next(None), dir.path().join("a.jpg"));
+        assert_eq!(next(Some("a.jpg")), dir.path().join("b.png"));
+        assert_eq!(next(Some("c.PNG")), dir.path().join("a.jpg"));
+        assert_eq!(next(Some("missing.png")), dir.path().join("a.jpg"));
+
+        let video = next_in_folder(dir.path(), None, &WallpaperType::Video, |_| true).unwrap();
+        assert_eq!(video, dir.path().join("clip.mp4"));
+        assert!(next_in_folder(dir.path(), None, &WallpaperType::Web, |_| true).is_err());
+    }
+
+    #[test]
+    fn test_next_in_folder_prefers_matching_files() {
+        let dir = tempdir().unwrap();
+        for name in ["a.png", "b.png", "c.png", "d.png"] {
+            fs::write(dir.path().join(name), "").unwrap();
+        }
+        let wide = |path: &Path| path.ends_with("a.png") || path.ends_with("c.png");
+        let next = |current: &str, preferred: &dyn Fn(&Path) -> bool| {
+            next_in_folder(
+                dir.path(),
+                Some(&dir.path().join(current)),
+                &WallpaperType::Static,
+                preferred,
+            )
+            .unwrap()
+        };
+
+        assert_eq!(next("a.png", &wide), dir.path().join("c.png"));
+        assert_eq!(next("c.png", &wide), dir.path().join("a.png"));
+
+        // With nothing matching, the plain next file is used
+        assert_eq!(next("b.png", &|_| false), dir.path().join("c.png"));
+    }
+}
@@ -0,0 +1,777 @@
+//! Wallpaper sync between computers on the LAN
+//!
+//! Instances with sync on find each other over mDNS and send each other
+//! their wallpaper changes, so a desktop and a laptop show the same
+//! wallpaper. A change is sent to every peer as a JSON header line followed
+//! by the wallpaper file; wallpapers without a file (web pages, procedural
+//! patterns, particles) send only their URL or pattern, and folders aren't
+//! sent. Peers only take wallpapers sent with the sync key they share, which
+//! travels in the clear, like everything on the LAN.
+//!
+//! Received files are kept in `downloads/sync` and applied like wallpapers
+//! set from the API. A received wallpaper isn't sent on, so changes don't
+//! bounce between peers, and neither is the one restored at startup.
+use crate::core::config::{SyncConfig, WallpaperType};
+use crate::core::{
+    AppError, AppResult, Config, CurrentWallpaperStore, EventLog, ProcessHandle, SecretStore,
+    WallpaperInfo,
+};
+use crate::daemon::api::{self, apply, tokens_match};
+use crate::daemon::client::DaemonClient;
+use crate::daemon::mdns;
+use chrono::{DateTime, Utc};
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener, TcpStream, ToSocketAddrs, UdpSocket};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Secret store key of the key peers share
+pub const SYNC_KEY_SECRET: &str = "sync-key";
+
+/// How often the sync checks for connections, peers and wallpaper changes
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Time between queries for peers
+const QUERY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Time a peer is kept after it was last heard of
+const PEER_EXPIRY: Duration = Duration::from_secs(120);
+
+/// Timeout for connecting to a peer
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Timeout for each read and write of a transfer
+const IO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Largest accepted header line
+const MAX_HEADER: u64 = 8 * 1024;
+
+/// Largest accepted wallpaper file
+const MAX_FILE: u64 = 512 * 1024 * 1024;
+
+/// Folder under the downloads folder the received wallpapers are kept in
+const RECEIVED_FOLDER: &str = "sync";
+
+/// Header sent before a wallpaper
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Header {
+    /// Shared sync key
+    key: String,
+
+    /// Name of the sending computer
+    from: String,
+
+    /// Wallpaper type
+    wallpaper_type: WallpaperType,
+
+    /// Name of the file that follows, if any
+    file_name: Option<String>,
+
+    /// Size of the file that follows
+    size: u64,
+
+    /// URL or pattern of wallpapers without a file
+    url: Option<String>,
+}
+
+/// Wallpaper on screen, as far as the sync is concerned
+type Shown = (WallpaperType, Option<PathBuf>, Option<String>);
+
+/// Peer found over mDNS
+#[derive(Debug, Clone)]
+struct Peer {
+    /// Name it announced
+    name: String,
+
+    /// Address it receives wallpapers on
+    address: SocketAddr,
+
+    /// When it was last heard of
+    seen: Instant,
+}
+
+/// Sockets and processes of the sync while it's on
+struct Listening {
+    /// Port the listener is bound to
+    port: u16,
+
+    /// Receives wallpapers
+    listener: TcpListener,
+
+    /// Answers queries on the mDNS port, unless another responder holds it
+    responder: Option<UdpSocket>,
+
+    /// Sends queries and receives the answers
+    browser: UdpSocket,
+
+    /// `avahi-publish` announcing this computer when the mDNS port was taken
+    avahi: Option<ProcessHandle>,
+}
+
+impl Drop for Listening {
+    fn drop(&mut self) {
+        if let Some(avahi) = self.avahi.take() {
+            avahi.kill();
+        }
+    }
+}
+
+/// Syncs the wallpaper with other computers on the LAN
+pub struct LanSync {
+    /// Sync configuration
+    config: Arc<Mutex<SyncConfig>>,
+
+    /// Secret store holding the key
+    secret_store: Arc<SecretStore>,
+
+    /// Cached key, empty when none is set (None = read it again)
+    key: Arc<Mutex<Option<String>>>,
+
+    /// Since when changes are sent, which is once the startup wallpaper is shown
+    following: Arc<Mutex<Option<DateTime<Utc>>>>,
+
+    /// Sync thread handle
+    sync_thread: Option<thread::JoinHandle<()>>,
+
+    /// Whether the sync is running
+    is_running: Arc<Mutex<bool>>,
+}
+
+#[allow(dead_code)]
+impl LanSync {
+    /// Create a new LAN sync
+    pub fn new(config: SyncConfig, secret_store: Arc<SecretStore>) -> Self {
+        Self {
+            config: Arc::new(Mutex::new(config)),
+            secret_store,
+            key: Arc::new(Mutex::new(None)),
+            following: Arc::new(Mutex::new(None)),
+            sync_thread: None,
+            is_running: Arc::new(Mutex::new(false)),
+        }
+    }
+
+    /// Update the sync configuration
+    ///
+    /// Also re-reads the key, in case it was changed.
+    pub fn update_config(&self, config: SyncConfig) {
+        *self.config.lock().unwrap() = config;
+        *self.key.lock().unwrap() = None;
+    }
+
+    /// Send wallpaper changes from now on
+    ///
+    /// Called once the wallpaper restored at startup is shown, so peers aren't
+    /// sent an older wallpaper each time this computer starts.
+    pub fn follow_changes(&self) {
+        *self.following.lock().unwrap() = Some(Utc::now());
+    }
+
+    /// Start the sync thread
+    ///
+    /// The thread idles while the sync is off and listens again when the port changes.
+    pub fn start(&mut self) -> AppResult<()> {
+        if *self.is_running.lock().unwrap() {
+            debug!("LAN sync is already running");
+            return Ok(());
+        }
+
+        *self.is_running.lock().unwrap() = true;
+
+        let config = self.config.clone();
+        let secret_store = self.secret_store.clone();
+        let key = self.key.clone();
+        let following = self.following.clone();
+        let is_running = self.is_running.clone();
+
+        self.sync_thread = Some(thread::spawn(move || {
+            let id = instance_id();
+            let received: Arc<Mutex<Option<Shown>>> = Arc::new(Mutex::new(None));
+            let mut listening: Option<Listening> = None;
+            let mut failed: Option<u16> = None;
+            let mut peers: HashMap<String, Peer> = HashMap::new();
+            let mut last_query: Option<Instant> = None;
+            let mut shown = current().map(|(wallpaper, _)| wallpaper);
+            let mut warned_key = false;
+
+            while *is_running.lock().unwrap() {
+                let settings = config.lock().unwrap().clone();
+                let cached = key.lock().unwrap().clone();
+                let sync_key = match cached {
+                    _ if !settings.enabled => None,
+                    Some(cached) => Some(cached),
+                    // A missing key is cached as empty, so it isn't looked up on every check
+                    None => {
+                        let value = secret_store
+                            .get(SYNC_KEY_SECRET)
+                            .unwrap_or_else(|e| {
+                                error!("Failed to read the sync key: {}", e);
+                                None
+                            })
+                            .unwrap_or_default();
+                        *key.lock().unwrap() = Some(value.clone());
+                        Some(value)
+                    }
+                }
+                .filter(|value| !value.is_empty());
+
+                if !settings.enabled || sync_key.is_none() {
+                    if settings.enabled && !warned_key {
+                        warn!("LAN sync is on but no sync key is set");
+                        EventLog::shared().error(
+                            "sync",
+                            "LAN sync needs a sync key",
+                            "Set the same key on every computer",
+                        );
+                        warned_key = true;
+                    }
+                    if listening.take().is_some() {
+                        info!("LAN sync stopped listening");
+                    }
+                    peers.clear();
+                    failed = None;
+                    shown = current().map(|(wallpaper, _)| wallpaper);
+                    thread::sleep(POLL_INTERVAL);
+                    continue;
+                }
+                let sync_key = sync_key.unwrap_or_default();
+                warned_key = false;
+
+                // Listen again only after the port changes
+                let name = device_name(&settings);
+                if listening
+                    .as_ref()
+                    .map_or(true, |listening| listening.port != settings.port)
+                    && failed != Some(settings.port)
+                {
+                    listening = None;
+                    match listen(&settings, &name, &id) {
+                        Ok(bound) => {
+                            info!("LAN sync listening on port {} as {}", settings.port, name);
+                            listening = Some(bound);
+                            failed = None;
+                            last_query = None;
+                        }
+                        Err(e) => {
+                            error!(
+                                "Failed to listen for LAN sync on port {}: {}",
+                                settings.port, e
+                            );
+                            EventLog::shared().error(
+                                "sync",
+                                format!("Failed to listen on port {}", settings.port),
+                                e,
+                            );
+                            failed = Some(settings.port);
+                        }
+                    }
+                }
+                let Some(bound) = listening.as_mut() else {
+                    thread::sleep(POLL_INTERVAL);
+                    continue;
+                };
+
+                // Wallpapers sent by peers
+                match bound.listener.accept() {
+                    Ok((stream, address)) => {
+                        let (settings, sync_key, received) =
+                            (settings.clone(), sync_key.clone(), received.clone());
+                        thread::spawn(move || {
+                            if let Err(e) = receive(stream, &settings, &sync_key, &received) {
+                                warn!("Failed to receive a wallpaper from {}: {}", address, e);
+                            }
+                        });
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                    Err(e) => warn!("Failed to accept LAN sync connection: {}", e),
+                }
+
+                // Peers announcing themselves, and queries for this computer
+                if last_query.map_or(true, |at| at.elapsed() >= QUERY_INTERVAL) {
+                    if let Err(e) = bound
+                        .browser
+                        .send_to(&mdns::query(), (mdns::GROUP, mdns::PORT))
+                    {
+                        debug!("Failed to query for LAN sync peers: {}", e);
+                    }
+                    last_query = Some(Instant::now());
+                }
+                discover(bound, &settings, &name, &id, &mut peers);
+                peers.retain(|_, peer| peer.seen.elapsed() < PEER_EXPIRY);
+
+                // Wallpaper changes made here
+                let (now, started_at) = current().unzip();
+                if now != shown {
+                    let echoed = {
+                        let mut received = received.lock().unwrap();
+                        let echoed = received.is_some() && *received == now;
+                        if echoed {
+                            *received = None;
+                        }
+                        echoed
+                    };
+                    let follow = following
+                        .lock()
+                        .unwrap()
+                        .zip(started_at)
+                        .map_or(false, |(since, started_at)| started_at > since);
+                    if let (Some(wallpaper), true, false, true) =
+                        (&now, settings.send, echoed, follow)
+                    {
+                        let mut targets: Vec<(String, SocketAddr)> = peers
+                            .values()
+                            .map(|peer| (peer.name.clone(), peer.address))
+                            .collect();
+                        targets.extend(settings.peers.iter().filter_map(|peer| {
+                            peer.to_socket_addrs()
+                                .ok()?
+                                .next()
+                                .map(|address| (peer.clone(), address))
+                        }));
+                        send(wallpaper.clone(), targets, &name, &sync_key);
+                    }
+                    shown = now;
+                }
+
+                thread::sleep(POLL_INTERVAL);
+            }
+        }));
+
+        info!("LAN sync started");
+        Ok(())
+    }
+
+    /// Stop the sync thread
+    pub fn stop(&mut self) -> AppResult<()> {
+        if !*self.is_running.lock().unwrap() {
+            debug!("LAN sync is not running");
+            return Ok(());
+        }
+
+        *self.is_running.lock().unwrap() = false;
+
+        if let Some(thread) = self.sync_thread.take() {
+            thread
+                .join()
+                .map_err(|e| AppError::Other(format!("Failed to join LAN sync thread: {:?}", e)))?;
+        }
+
+        info!("LAN sync stopped");
+        Ok(())
+    }
+}
+
+/// Get the wallpaper on screen and when it was applied
+fn current() -> Option<(Shown, DateTime<Utc>)> {
+    CurrentWallpaperStore::shared().get().map(|state| {
+        let wallpaper = state.wallpaper;
+        (
+            (wallpaper.r#type, wallpaper.path, wallpaper.url),
+            state.started_at,
+        )
+    })
+}
+
+/// Make a random ID telling this instance apart from the others
+fn instance_id() -> String {
+    let mut bytes = [0u8; 8];
+    if getrandom::fill(&mut bytes).is_err() {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos() as u64;
+        bytes = (nanos ^ std::process::id() as u64).to_be_bytes();
+    }
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Get the name this computer is announced with
+pub fn device_name(config: &SyncConfig) -> String {
+    if !config.name.trim().is_empty() {
+        return config.name.trim().to_string();
+    }
+    std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .ok()
+        .or_else(|| fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "Aether-Desk".to_string())
+}
+
+/// Turn a free-text name into a host name label
+fn host_label(name: &str) -> String {
+    let label: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    match label.trim_matches('-') {
+        "" => "aether-desk".to_string(),
+        label => label.to_string(),
+    }
+}
+
+/// Bind the listener and the mDNS sockets, publishing this computer
+fn listen(settings: &SyncConfig, name: &str, id: &str) -> io::Result<Listening> {
+    let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, settings.port))?;
+    listener.set_nonblocking(true)?;
+    let browser = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+    browser.set_nonblocking(true)?;
+
+    // Avahi or Bonjour may hold the mDNS port; Avahi can then announce for us
+    let responder = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, mdns::PORT))
+        .and_then(|socket| {
+            socket.join_multicast_v4(&mdns::GROUP, &Ipv4Addr::UNSPECIFIED)?;
+            socket.set_nonblocking(true)?;
+            Ok(socket)
+        })
+        .map_err(|e| debug!("mDNS port unavailable: {}", e))
+        .ok();
+    let avahi = match responder {
+        Some(_) => None,
+        None => {
+            let mut command = Command::new("avahi-publish");
+            command
+                .args([
+                    "-s",
+                    name,
+                    "_aether-desk._tcp",
+                    &settings.port.to_string(),
+                    &format!("id={}", id),
+                ])
+                .stdout(Stdio::null())
+                .stderr(Stdio::null());
+            match ProcessHandle::spawn(&mut command, "avahi-publish", "LAN sync announcement") {
+                Ok(process) => Some(process),
+                Err(e) => {
+                    warn!("Other computers can't discover this one, add it to their peers by address: {}", e);
+                    None
+                }
+            }
+        }
+    };
+
+    Ok(Listening {
+        port: settings.port,
+        listener,
+        responder,
+        browser,
+        avahi,
+    })
+}
+
+/// Read the pending mDNS packets, answering queries and recording peers
+fn discover(
+    bound: &Listening,
+    settings: &SyncConfig,
+    name: &str,
+    id: &str,
+    peers: &mut HashMap<String, Peer>,
+) {
+    let mut buffer = [0u8; 9000];
+    for socket in bound.responder.iter().chain([&bound.browser]) {
+        while let Ok((length, from)) = socket.recv_from(&mut buffer) {
+            let packet = &buffer[..length];
+
+            if let (Some(responder), Ok(Some(query))) =
+                (&bound.responder, mdns::asks_for_service(packet))
+            {
+                let address = match api::lan_address() {
+                    Some(IpAddr::V4(address)) => address,
+                    _ => Ipv4Addr::UNSPECIFIED,
+                };
+                // Queries from the mDNS port get a multicast answer, one-shot queries a direct one
+                let (query, to) = match from.port() {
+                    mdns::PORT => (0, SocketAddr::from((mdns::GROUP, mdns::PORT))),
+                    _ => (query, from),
+                };
+                let answer = mdns::announcement(
+                    query,
+                    name,
+                    &host_label(name),
+                    address,
+                    settings.port,
+                    &[("id", id)],
+                );
+                if let Err(e) = responder.send_to(&answer, to) {
+                    debug!("Failed to answer mDNS query from {}: {}", from, e);
+                }
+                continue;
+            }
+
+            for instance in mdns::instances(packet).unwrap_or_default() {
+                let peer_id = instance
+                    .txt
+                    .iter()
+                    .find(|(key, _)| key == "id")
+                    .map_or_else(|| instance.name.clone(), |(_, value)| value.clone());
+                if peer_id == id {
+                    continue;
+                }
+                if !peers.contains_key(&peer_id) {
+                    info!("Found LAN sync peer {} at {}", instance.name, from.ip());
+                }
+                peers.insert(
+                    peer_id,
+                    Peer {
+                        name: instance.name,
+                        address: SocketAddr::new(from.ip(), instance.port),
+                        seen: Instant::now(),
+                    },
+                );
+            }
+        }
+    }
+}
+
+/// Send a wallpaper to peers in the background
+fn send(wallpaper: Shown, targets: Vec<(String, SocketAddr)>, name: &str, key: &str) {
+    let (wallpaper_type, path, url) = wallpaper;
+    let file = match path {
+        Some(path) if path.is_file() => Some(path),
+        Some(path) => {
+            debug!("Not syncing {}, which isn't a single file", path.display());
+            return;
+        }
+        None => None,
+    };
+    if targets.is_empty() {
+        return;
+    }
+
+    let header = Header {
+        key: key.to_string(),
+        from: name.to_string(),
+        wallpaper_type,
+        file_name: file
+            .as_ref()
+            .and_then(|file| file.file_name())
+            .map(|name| name.to_string_lossy().to_string()),
+        size: file
+            .as_ref()
+            .and_then(|file| fs::metadata(file).ok())
+            .map_or(0, |metadata| metadata.len()),
+        url,
+    };
+    if header.size > MAX_FILE {
+        warn!(
+            "Not syncing a wallpaper of {} MB, peers take up to {} MB",
+            header.size >> 20,
+            MAX_FILE >> 20
+        );
+        return;
+    }
+
+    for (peer, address) in targets {
+        let (header, file) = (header.clone(), file.clone());
+        thread::spawn(move || match send_to(address, &header, file.as_deref()) {
+            Ok(()) => info!("Sent the wallpaper to {}", peer),
+            Err(e) => {
+                warn!("Failed to send the wallpaper to {}: {}", peer, e);
+                EventLog::shared().error(
+                    "sync",
+                    format!("Failed to send the wallpaper to {}", peer),
+                    e,
+                );
+            }
+        });
+    }
+}
+
+/// Send a wallpaper to one peer and wait for its answer
+fn send_to(address: SocketAddr, header: &Header, file: Option<&Path>) -> AppResult<()> {
+    let mut stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)?;
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+
+    serde_json::to_writer(&mut stream, header)?;
+    stream.write_all(b"\n")?;
+    if let Some(file) = file {
+        io::copy(&mut File::open(file)?.take(header.size), &mut stream)?;
+    }
+    stream.flush()?;
+
+    let mut answer = String::new();
+    BufReader::new(stream)
+        .take(MAX_HEADER)
+        .read_line(&mut answer)?;
+    let answer: serde_json::Value = serde_json::from_str(&answer)?;
+    match answer["error"].as_str() {
+        Some(error) => Err(AppError::NetworkError(error.to_string())),
+        None => Ok(()),
+    }
+}
+
+/// Receive a wallpaper from a peer, apply it and answer
+fn receive(
+    stream: TcpStream,
+    settings: &SyncConfig,
+    key: &str,
+    received: &Mutex<Option<Shown>>,
+) -> AppResult<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+
+    let result = read_wallpaper(&mut reader, settings, key).and_then(|wallpaper| {
+        *received.lock().unwrap() = Some((
+            wallpaper.r#type.clone(),
+            wallpaper.path.clone(),
+            wallpaper.url.clone(),
+        ));
+        apply(wallpaper, &DaemonClient::new())
+    });
+    let answer = match &result {
+        Ok(_) => serde_json::json!({ "ok": true }),
+        Err(e) => serde_json::json!({ "error": e.to_string() }),
+    };
+    serde_json::to_writer(&mut writer, &answer)?;
+    writer.write_all(b"\n")?;
+    result.map(|_| ())
+}
+
+/// Read a header and the file after it, saving the file among the received wallpapers
+fn read_wallpaper(
+    reader: &mut impl BufRead,
+    settings: &SyncConfig,
+    key: &str,
+) -> AppResult<WallpaperInfo> {
+    let mut line = String::new();
+    reader.take(MAX_HEADER).read_line(&mut line)?;
+    let header: Header = serde_json::from_str(&line)?;
+
+    if !tokens_match(&header.key, key) {
+        return Err(AppError::NetworkError("Wrong sync key".to_string()));
+    }
+    if !settings.receive {
+        return Err(AppError::NetworkError(
+            "This computer doesn't take synced wallpapers".to_string(),
+        ));
+    }
+    info!("Receiving a wallpaper from {}", header.from);
+
+    let Some(file_name) = &header.file_name else {
+        return Ok(WallpaperInfo::new(header.wallpaper_type, None, header.url));
+    };
+    let file_name = Path::new(file_name)
+        .file_name()
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| AppError::NetworkError(format!("Bad file name: {}", file_name)))?;
+    if header.size > MAX_FILE {
+        return Err(AppError::NetworkError(format!(
+            "Wallpapers are taken up to {} MB",
+            MAX_FILE >> 20
+        )));
+    }
+
+    let folder = Config::default().get_downloads_dir().join(RECEIVED_FOLDER);
+    fs::create_dir_all(&folder)?;
+    let path = folder.join(file_name);
+    let partial = path.with_extension("part");
+    let copied = io::copy(&mut reader.take(header.size), &mut File::create(&partial)?)?;
+    if copied != header.size {
+        let _ = fs::remove_file(&partial);
+        return Err(AppError::NetworkError(format!(
+            "Received {} of {} bytes",
+            copied, header.size
+        )));
+    }
+    fs::rename(&partial, &path)?;
+
+    Ok(WallpaperInfo::new(header.wallpaper_type, Some(path), None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn header(key: &str, file_name: Option<&str>, size: u64) -> Vec<u8> {
+        let header = Header {
+            key: key.to_string(),
+            from: "desktop".to_string(),
+            wallpaper_type: WallpaperType::Procedural,
+            file_name: file_name.map(String::from),
+            size,
+            url: Some("aurora".to_string()),
+        };
+        let mut bytes = serde_json::to_vec(&header).unwrap();
+        bytes.push(b'\n');
+        bytes
+    }
+
+    #[test]
+    fn test_read_wallpaper() {
+        let settings = SyncConfig::default();
+        let wallpaper = read_wallpaper(
+            &mut Cursor::new(header("secret", None, 0)),
+            &settings,
+            "secret",
+        )
+        .unwrap();
+        assert_eq!(wallpaper.r#type, WallpaperType::Procedural);
+        assert_eq!(wallpaper.url.as_deref(), Some("aurora"));
+
+        let error = read_wallpaper(
+            &mut Cursor::new(header("guess", None, 0)),
+            &settings,
+            "secret",
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("Wrong sync key"));
+
+        let closed = SyncConfig {
+            receive: false,
+            ..SyncConfig::default()
+        };
+        assert!(read_wallpaper(
+            &mut Cursor::new(header("secret", None, 0)),
+            &closed,
+            "secret"
+        )
+        .is_err());
+
+        // Files that don't fit, and names that leave the folder, are refused before anything is written
+        assert!(read_wallpaper(
+            &mut Cursor::new(header("secret", Some("big.mp4"), MAX_FILE + 1)),
+            &settings,
+            "secret"
+        )
+        .is_err());
+        assert!(read_wallpaper(
+            &mut Cursor::new(header("secret", Some(".."), 1)),
+            &settings,
+            "secret"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!(
+            device_name(&SyncConfig {
+                name: " Laptop ".to_string(),
+                ..SyncConfig::default()
+            }),
+            "Laptop"
+        );
+        assert!(!device_name(&SyncConfig::default()).is_empty());
+        assert_eq!(host_label("Living Room PC"), "living-room-pc");
+        assert_eq!(host_label("✨"), "aether-desk");
+    }
+}
@@ -0,0 +1,80 @@
+//! Local transport between the daemon and its frontends
+//!
+//! Unix systems use a domain socket in the runtime directory; Windows uses a
+//! named pipe. Each endpoint has a name, so the settings window can use the
+//! same transport to find a running window.
+use crate::core::{AppError, AppResult};
+use std::time::Duration;
+
+#[cfg(unix)]
+pub use std::os::unix::net::{UnixListener as Listener, UnixStream as Stream};
+
+#[cfg(windows)]
+pub use crate::daemon::pipe::{Listener, Stream};
+
+/// Endpoint of the daemon
+pub const DAEMON_ENDPOINT: &str = "aether-desk";
+
+/// Endpoint of the settings window
+pub const WINDOW_ENDPOINT: &str = "aether-desk-window";
+
+/// Timeout for reading and writing a single message
+pub const IO_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Get the socket path of an endpoint
+#[cfg(unix)]
+pub fn socket_path(endpoint: &str) -> std::path::PathBuf {
+    dirs::runtime_dir()
+        .or_else(|| crate::core::Config::get_config_dir().ok())
+        .unwrap_or_else(std::env::temp_dir)
+        .join(format!("{}.sock", endpoint))
+}
+
+/// Bind the listener of an endpoint
+///
+/// Fails if another process is already listening.
+pub fn bind(endpoint: &str) -> AppResult<Listener> {
+    #[cfg(unix)]
+    {
+        let path = socket_path(endpoint);
+        if path.exists() {
+            if Stream::connect(&path).is_ok() {
+                return Err(AppError::Other(format!("{} is already running", endpoint)));
+            }
+            // Left behind by a daemon that didn't shut down cleanly
+            std::fs::remove_file(&path)?;
+        }
+        Ok(Listener::bind(&path)?)
+    }
+
+    #[cfg(windows)]
+    {
+        Listener::bind(endpoint).map_err(|e| {
+            AppError::Other(format!(
+                "Failed to create pipe {} (is it already running?): {}",
+                endpoint, e
+            ))
+        })
+    }
+}
+
+/// Connect to an endpoint
+pub fn connect(endpoint: &str) -> AppResult<Stream> {
+    #[cfg(unix)]
+    let stream = Stream::connect(socket_path(endpoint))?;
+
+    #[cfg(windows)]
+    let stream = Stream::connect(endpoint)?;
+
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    Ok(stream)
+}
+
+/// Remove the socket file of an endpoint
+pub fn cleanup(endpoint: &str) {
+    #[cfg(unix)]
+    {
+        let _ = std::fs::remove_file(socket_path(endpoint));
+    }
+}
@@ -0,0 +1,281 @@
+//! Photo uploads from phones
+//!
+//! `/upload` on the local API is a page for sending a photo from a phone's
+//! camera or gallery straight to the desktop; the settings show a QR code
+//! opening it with the token. Photos are turned upright by their EXIF
+//! orientation, shrunk to cover the monitor they're for and saved as JPEG in
+//! the `uploads` folder of the downloads folder, where the library finds them.
+use crate::core::config::WallpaperType;
+use crate::core::image_pipeline::{Encoding, ImagePipeline};
+use crate::core::{AppError, AppResult, Config, WallpaperInfo};
+use crate::daemon::api::apply;
+use crate::daemon::client::DaemonClient;
+use crate::platform::{self, MonitorInfo};
+use chrono::Local;
+use image::DynamicImage;
+use log::info;
+use serde_json::{json, Value};
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Path photos are posted to
+pub const UPLOAD_PATH: &str = "/api/upload";
+
+/// Largest photo accepted
+pub const MAX_UPLOAD: usize = 32 * 1024 * 1024;
+
+/// Folder under the downloads folder the photos are kept in
+const UPLOADS_FOLDER: &str = "uploads";
+
+/// JPEG quality of the saved photos
+const JPEG_QUALITY: u8 = 90;
+
+/// Size photos are fitted to when no monitor is known
+const FALLBACK_SIZE: (u32, u32) = (3840, 2160);
+
+/// List the monitors a photo can be sent to
+pub fn monitors() -> AppResult<Value> {
+    let monitors = platform::monitors()?;
+    Ok(json!(monitors
+        .iter()
+        .map(|monitor| json!({
+            "id": monitor.id,
+            "name": monitor.name,
+            "width": monitor.width,
+            "height": monitor.height,
+            "primary": monitor.primary,
+        }))
+        .collect::<Vec<_>>()))
+}
+
+/// Save a posted photo for a monitor, or all of them, and apply it
+pub fn upload(
+    body: &[u8],
+    monitor: Option<&str>,
+    daemon: &DaemonClient,
+    config: &Config,
+) -> AppResult<Value> {
+    if body.is_empty() {
+        return Err(AppError::WallpaperError("No photo was sent".to_string()));
+    }
+    let photo = image::load_from_memory(body).map_err(|e| {
+        AppError::WallpaperError(format!(
+            "The upload is not an image this computer can open: {}",
+            e
+        ))
+    })?;
+    let photo = orient(photo, exif_orientation(body));
+
+    let monitors = platform::monitors().unwrap_or_default();
+    let target = match monitor.filter(|monitor| !monitor.is_empty()) {
+        Some(wanted) => Some(
+            monitors
+                .iter()
+                .find(|monitor| monitor.id == wanted || monitor.name == wanted)
+                .ok_or_else(|| AppError::WallpaperError(format!("No monitor named {}", wanted)))?,
+        ),
+        None => None,
+    };
+    let size = target
+        .or_else(|| largest(&monitors))
+        .map_or(FALLBACK_SIZE, |monitor| (monitor.width, monitor.height));
+    // Shrunk to just cover the monitor; the fit mode decides how smaller photos are shown
+    let photo = ImagePipeline::new(photo).cover(size.0, size.1);
+
+    let dir = config.get_downloads_dir().join(UPLOADS_FOLDER);
+    fs::create_dir_all(&dir)?;
+    let path = new_upload_path(&dir, &Local::now().format("%Y%m%d-%H%M%S").to_string())?;
+    photo.save(&path, Encoding::Jpeg(JPEG_QUALITY))?;
+
+    let (width, height) = photo.size();
+    info!("Received a {}x{} photo from the upload page", width, height);
+    let wallpaper = WallpaperInfo::new(WallpaperType::Static, Some(path), None)
+        .on_monitor(target.map(|monitor| monitor.id.clone()));
+    apply(wallpaper, daemon)
+}
+
+/// Reserve the file for a photo, numbering it when another arrived the same second
+///
+/// Photos for several monitors are often sent at once, and must not replace
+/// each other.
+fn new_upload_path(dir: &Path, stamp: &str) -> AppResult<PathBuf> {
+    let mut number = 1;
+    loop {
+        let name = match number {
+            1 => format!("upload-{}.jpg", stamp),
+            _ => format!("upload-{}-{}.jpg", stamp, number),
+        };
+        let path = dir.join(name);
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(_) => return Ok(path),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => number += 1,
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+/// Get the monitor with the most pixels
+fn largest(monitors: &[MonitorInfo]) -> Option<&MonitorInfo> {
+    monitors
+        .iter()
+        .max_by_key(|monitor| monitor.width as u64 * monitor.height as u64)
+}
+
+/// Turn a photo upright by its EXIF orientation, from 1 to 8
+fn orient(photo: DynamicImage, orientation: u16) -> DynamicImage {
+    match orientation {
+        2 => photo.fliph(),
+        3 => photo.rotate180(),
+        4 => photo.flipv(),
+        5 => photo.rotate90().fliph(),
+        6 => photo.rotate90(),
+        7 => photo.rotate270().fliph(),
+        8 => photo.rotate270(),
+        _ => photo,
+    }
+}
+
+/// Read the orientation from a JPEG's EXIF data, 1 (upright) when it has none
+fn exif_orientation(jpeg: &[u8]) -> u16 {
+    exif_tiff(jpeg).and_then(tiff_orientation).unwrap_or(1)
+}
+
+/// Find the TIFF data of the EXIF segment of a JPEG
+fn exif_tiff(jpeg: &[u8]) -> Option<&[u8]> {
+    if !jpeg.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+    let mut offset = 2;
+    // Segments follow each other until the image data starts
+    while offset + 4 <= jpeg.len() && jpeg[offset] == 0xFF {
+        let marker = jpeg[offset + 1];
+        let length = u16::from_be_bytes([jpeg[offset + 2], jpeg[offset + 3]]) as usize;
+        let segment = jpeg.get(offset + 4..offset + 2 + length)?;
+        if marker == 0xE1 && segment.starts_with(b"Exif\0\0") {
+            return Some(&segment[6..]);
+        }
+        if marker == 0xDA {
+            return None;
+        }
+        offset += 2 + length;
+    }
+    None
+}
+
+/// Read the orientation tag from the first directory of TIFF data
+fn tiff_orientation(tiff: &[u8]) -> Option<u16> {
+    let little_endian = match tiff.get(..2)? {
+        b"II" => true,
+        b"MM" => false,
+        _ => return None,
+    };
+    let read_u16 = |at: usize| {
+        tiff.get(at..at + 2).map(|bytes| {
+            if little_endian {
+                u16::from_le_bytes([bytes[0], bytes[1]])
+            } else {
+                u16::from_be_bytes([bytes[0], bytes[1]])
+            }
+        })
+    };
+    let read_u32 = |at: usize| {
+        tiff.get(at..at + 4).map(|bytes| {
+            let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+            if little_endian {
+                u32::from_le_bytes(bytes)
+            } else {
+                u32::from_be_bytes(bytes)
+            }
+        })
+    };
+
+    let directory = read_u32(4)? as usize;
+    let entries = read_u16(directory)? as usize;
+    (0..entries)
+        .map(|index| directory + 2 + index * 12)
+        .find(|&entry| read_u16(entry) == Some(0x0112))
+        .and_then(|entry| read_u16(entry + 8))
+        .filter(|orientation| (1..=8).contains(orientation))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::RgbImage;
+
+    /// Build a JPEG header with an EXIF segment holding only an orientation
+    fn jpeg_with_orientation(orientation: u16, little_endian: bool) -> Vec<u8> {
+        let u16_bytes = |value: u16| {
+            if little_endian {
+                value.to_le_bytes()
+            } else {
+                value.to_be_bytes()
+            }
+        };
+        let u32_bytes = |value: u32| {
+            if little_endian {
+                value.to_le_bytes()
+            } else {
+                value.to_be_bytes()
+            }
+        };
+        let mut tiff: Vec<u8> = Vec::new();
+        tiff.extend(if little_endian { b"II" } else { b"MM" });
+        tiff.extend(u16_bytes(42));
+        tiff.extend(u32_bytes(8));
+        tiff.extend(u16_bytes(1));
+        tiff.extend(u16_bytes(0x0112));
+        tiff.extend(u16_bytes(3));
+        tiff.extend(u32_bytes(1));
+        tiff.extend(u16_bytes(orientation));
+        tiff.extend([0, 0]);
+
+        let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x4A, 0x46, 0xFF, 0xE1];
+        jpeg.extend(((tiff.len() + 8) as u16).to_be_bytes());
+        jpeg.extend(b"Exif\0\0");
+        jpeg.extend(tiff);
+        jpeg.extend([0xFF, 0xDA, 0x00, 0x02]);
+        jpeg
+    }
+
+    #[test]
+    fn test_exif_orientation() {
+        assert_eq!(exif_orientation(&jpeg_with_orientation(6, true)), 6);
+        assert_eq!(exif_orientation(&jpeg_with_orientation(8, false)), 8);
+        assert_eq!(exif_orientation(&jpeg_with_orientation(42, true)), 1);
+        assert_eq!(exif_orientation(b"\x89PNG\r\n"), 1);
+        assert_eq!(exif_orientation(&[0xFF, 0xD8, 0xFF, 0xE1, 0xFF]), 1);
+    }
+
+    #[test]
+    fn test_orient_and_fit() {
+        let photo = DynamicImage::ImageRgb8(RgbImage::new(4000, 3000));
+        let upright = orient(photo, 6);
+        assert_eq!((upright.width(), upright.height()), (3000, 4000));
+
+        // Shrunk to cover the monitor without cropping
+        assert_eq!(
+            ImagePipeline::new(upright).cover(1920, 1080).size(),
+            (1920, 2560)
+        );
+    }
+
+    #[test]
+    fn test_new_upload_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = new_upload_path(dir.path(), "20240101-120000").unwrap();
+        let second = new_upload_path(dir.path(), "20240101-120000").unwrap();
+        assert_eq!(first, dir.path().join("upload-20240101-120000.jpg"));
+        assert_eq!(second, dir.path().join("upload-20240101-120000-2.jpg"));
+        assert!(second.exists());
+    }
+
+    #[test]
+    fn test_rejects_non_images() {
+        let config = Config::default();
+        let error = upload(b"not a photo", None, &DaemonClient::new(), &config).unwrap_err();
+        assert!(error.to_string().contains("not an image"));
+        assert!(upload(b"", None, &DaemonClient::new(), &config).is_err());
+    }
+}
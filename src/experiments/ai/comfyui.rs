@@ -0,0 +1,227 @@
+//! ComfyUI generator
+//!
+//! A text-to-image workflow is queued through the API, the history is polled
+//! until it has run and the saved images are fetched from the server.
+use crate::core::config::AiConfig;
+use crate::core::http::encode_query_component;
+use crate::core::{AppError, AppResult, HttpClient, RateLimit};
+use crate::experiments::ai::{random_seed, GenerationRequest, ImageGenerator};
+use log::debug;
+use serde_json::{json, Value};
+use std::fs;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Generator name and rate limit key
+const GENERATOR_NAME: &str = "ComfyUI";
+
+/// Longest wait for a batch of images
+const TIMEOUT: Duration = Duration::from_secs(15 * 60);
+
+/// Time between checks of whether the workflow has run
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Generator queueing workflows on a ComfyUI server
+pub struct ComfyUiGenerator {
+    /// Shared HTTP client
+    http_client: Arc<HttpClient>,
+
+    /// Server address
+    endpoint: String,
+
+    /// Checkpoint file to load
+    model: String,
+
+    /// Sampling steps
+    steps: u32,
+}
+
+impl ComfyUiGenerator {
+    /// Create a generator for the configured server
+    pub fn new(config: &AiConfig) -> Self {
+        let http_client = HttpClient::shared();
+        http_client.set_rate_limit(GENERATOR_NAME, RateLimit::per_minute(120));
+
+        Self {
+            http_client,
+            endpoint: config.endpoint().to_string(),
+            model: config.model.clone(),
+            steps: config.steps,
+        }
+    }
+
+    /// Wait for a queued workflow and get the images it saved
+    fn wait_for_images(&self, prompt_id: &str) -> AppResult<Vec<Value>> {
+        let started = Instant::now();
+        let url = format!("{}/history/{}", self.endpoint, prompt_id);
+        while started.elapsed() < TIMEOUT {
+            thread::sleep(POLL_INTERVAL);
+            let response = self.http_client.get(GENERATOR_NAME, &url)?;
+            let history = response.json()?;
+            let Some(entry) = history.get(prompt_id) else {
+                continue;
+            };
+
+            if entry["status"]["status_str"] == "error" {
+                return Err(AppError::WallpaperError(format!(
+                    "{} failed to run the workflow",
+                    GENERATOR_NAME
+                )));
+            }
+            return Ok(entry["outputs"]
+                .as_object()
+                .map(|outputs| {
+                    outputs
+                        .values()
+                        .filter_map(|output| output["images"].as_array())
+                        .flatten()
+                        .cloned()
+                        .collect()
+                })
+                .unwrap_or_default());
+        }
+        Err(AppError::NetworkError(format!(
+            "{} took too long to generate",
+            GENERATOR_NAME
+        )))
+    }
+
+    /// Fetch an image saved by the server
+    fn fetch_image(&self, image: &Value) -> AppResult<Vec<u8>> {
+        let field = |name: &str| encode_query_component(image[name].as_str().unwrap_or_default());
+        let url = format!(
+            "{}/view?filename={}&subfolder={}&type={}",
+            self.endpoint,
+            field("filename"),
+            field("subfolder"),
+            field("type")
+        );
+        let path = std::env::temp_dir().join(format!("aether-desk-comfyui-{}.png", random_seed()));
+        self.http_client.download(GENERATOR_NAME, &url, &path)?;
+        let bytes = fs::read(&path);
+        let _ = fs::remove_file(&path);
+        Ok(bytes?)
+    }
+}
+
+impl ImageGenerator for ComfyUiGenerator {
+    fn name(&self) -> &str {
+        GENERATOR_NAME
+    }
+
+    fn generate(&self, request: &GenerationRequest) -> AppResult<Vec<Vec<u8>>> {
+        if self.model.is_empty() {
+            return Err(AppError::ConfigError(format!(
+                "{} needs the checkpoint to generate with, set as the model",
+                GENERATOR_NAME
+            )));
+        }
+
+        let body = json!({ "prompt": workflow(request, &self.model, self.steps, random_seed()) });
+        let response = self.http_client.post_json(
+            GENERATOR_NAME,
+            &format!("{}/prompt", self.endpoint),
+            &body,
+            &[],
+            Duration::from_secs(60),
+        )?;
+        if response.status != 200 {
+            return Err(AppError::NetworkError(format!(
+                "{} refused the workflow with status {}: {}",
+                GENERATOR_NAME,
+                response.status,
+                response.text()
+            )));
+        }
+        let prompt_id = response.json()?["prompt_id"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| {
+                AppError::NetworkError(format!("{} didn't queue the workflow", GENERATOR_NAME))
+            })?;
+        debug!("Queued ComfyUI workflow {}", prompt_id);
+
+        self.wait_for_images(&prompt_id)?
+            .iter()
+            .map(|image| self.fetch_image(image))
+            .collect()
+    }
+}
+
+/// Build the default text-to-image workflow
+fn workflow(request: &GenerationRequest, checkpoint: &str, steps: u32, seed: u64) -> Value {
+    json!({
+        "checkpoint": {
+            "class_type": "CheckpointLoaderSimple",
+            "inputs": { "ckpt_name": checkpoint },
+        },
+        "positive": {
+            "class_type": "CLIPTextEncode",
+            "inputs": { "text": request.prompt, "clip": ["checkpoint", 1] },
+        },
+        "negative": {
+            "class_type": "CLIPTextEncode",
+            "inputs": { "text": request.negative_prompt, "clip": ["checkpoint", 1] },
+        },
+        "latent": {
+            "class_type": "EmptyLatentImage",
+            "inputs": { "width": request.width, "height": request.height, "batch_size": request.count },
+        },
+        "sampler": {
+            "class_type": "KSampler",
+            "inputs": {
+                "model": ["checkpoint", 0],
+                "positive": ["positive", 0],
+                "negative": ["negative", 0],
+                "latent_image": ["latent", 0],
+                "seed": seed,
+                "steps": steps,
+                "cfg": 7.0,
+                "sampler_name": "euler",
+                "scheduler": "normal",
+                "denoise": 1.0,
+            },
+        },
+        "decode": {
+            "class_type": "VAEDecode",
+            "inputs": { "samples": ["sampler", 0], "vae": ["checkpoint", 2] },
+        },
+        "save": {
+            "class_type": "SaveImage",
+            "inputs": { "images": ["decode", 0], "filename_prefix": "aether-desk" },
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workflow_links_nodes() {
+        let request = GenerationRequest {
+            prompt: "aurora".to_string(),
+            width: 1024,
+            height: 576,
+            count: 2,
+            ..Default::default()
+        };
+        let workflow = workflow(&request, "sdxl.safetensors", 20, 7);
+
+        let nodes = workflow.as_object().unwrap();
+        for node in nodes.values() {
+            for input in node["inputs"].as_object().unwrap().values() {
+                if let Some(link) = input.as_array() {
+                    assert!(
+                        nodes.contains_key(link[0].as_str().unwrap()),
+                        "{} links to a missing node",
+                        input
+                    );
+                }
+            }
+        }
+        assert_eq!(workflow["latent"]["inputs"]["batch_size"], 2);
+        assert_eq!(workflow["positive"]["inputs"]["text"], "aurora");
+    }
+}
@@ -0,0 +1,155 @@
+//! Hosted image generation API
+//!
+//! Speaks the OpenAI image generation API, which several hosted services
+//! and proxies also offer.
+use crate::core::config::AiConfig;
+use crate::core::{AppError, AppResult, HttpClient, RateLimit, SecretStore};
+use crate::experiments::ai::{decode_base64, random_seed, GenerationRequest, ImageGenerator};
+use serde_json::{json, Value};
+use std::fs;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Generator name and rate limit key
+const GENERATOR_NAME: &str = "hosted image API";
+
+/// Secret store key for the API key
+pub const API_KEY_SECRET: &str = "ai.hosted.api_key";
+
+/// Model used when none is configured
+const DEFAULT_MODEL: &str = "gpt-image-1";
+
+/// Longest wait for a batch of images
+const TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// Generator using a hosted API
+pub struct HostedGenerator {
+    /// Shared HTTP client
+    http_client: Arc<HttpClient>,
+
+    /// Secret store for the API key
+    secret_store: Arc<SecretStore>,
+
+    /// API address
+    endpoint: String,
+
+    /// Model to generate with
+    model: String,
+}
+
+impl HostedGenerator {
+    /// Create a generator for the configured API
+    pub fn new(config: &AiConfig, secret_store: Arc<SecretStore>) -> Self {
+        let http_client = HttpClient::shared();
+        // Hosted services bill per image, so runaway requests are cut short
+        http_client.set_rate_limit(GENERATOR_NAME, RateLimit::per_minute(5));
+
+        let model = if config.model.is_empty() {
+            DEFAULT_MODEL.to_string()
+        } else {
+            config.model.clone()
+        };
+        Self {
+            http_client,
+            secret_store,
+            endpoint: config.endpoint().to_string(),
+            model,
+        }
+    }
+
+    /// Get an image from an entry of the response
+    fn image(&self, entry: &Value) -> AppResult<Vec<u8>> {
+        if let Some(data) = entry["b64_json"].as_str() {
+            return decode_base64(data);
+        }
+        let url = entry["url"].as_str().ok_or_else(|| {
+            AppError::NetworkError(format!(
+                "The {} returned an entry without an image",
+                GENERATOR_NAME
+            ))
+        })?;
+        let path =
+            std::env::temp_dir().join(format!("aether-desk-generated-{}.png", random_seed()));
+        self.http_client.download(GENERATOR_NAME, url, &path)?;
+        let bytes = fs::read(&path);
+        let _ = fs::remove_file(&path);
+        Ok(bytes?)
+    }
+}
+
+impl ImageGenerator for HostedGenerator {
+    fn name(&self) -> &str {
+        GENERATOR_NAME
+    }
+
+    fn generate(&self, request: &GenerationRequest) -> AppResult<Vec<Vec<u8>>> {
+        let api_key = self
+            .secret_store
+            .get(API_KEY_SECRET)?
+            .filter(|key| !key.is_empty())
+            .ok_or_else(|| {
+                AppError::ConfigError(format!("The {} needs an API key", GENERATOR_NAME))
+            })?;
+
+        // The API has no negative prompt, so it's part of the description
+        let prompt = if request.negative_prompt.is_empty() {
+            request.prompt.clone()
+        } else {
+            format!("{}\n\nAvoid: {}", request.prompt, request.negative_prompt)
+        };
+        let body = json!({
+            "model": self.model,
+            "prompt": prompt,
+            "n": request.count,
+            "size": hosted_size(request.width, request.height),
+        });
+
+        let url = format!("{}/v1/images/generations", self.endpoint);
+        let headers = [format!("Authorization: Bearer {}", api_key)];
+        let response =
+            self.http_client
+                .post_json(GENERATOR_NAME, &url, &body, &headers, TIMEOUT)?;
+        let json = response.json().unwrap_or_default();
+        if response.status != 200 {
+            let message = json["error"]["message"]
+                .as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| response.text());
+            return Err(AppError::NetworkError(format!(
+                "The {} failed with status {}: {}",
+                GENERATOR_NAME, response.status, message
+            )));
+        }
+
+        json["data"]
+            .as_array()
+            .map(|entries| entries.iter().map(|entry| self.image(entry)).collect())
+            .unwrap_or_else(|| Ok(Vec::new()))
+    }
+}
+
+/// Get the supported size closest to the aspect ratio of a monitor
+///
+/// Hosted models only generate a few fixed sizes.
+fn hosted_size(width: u32, height: u32) -> &'static str {
+    let aspect = width as f32 / height.max(1) as f32;
+    if aspect > 1.2 {
+        "1536x1024"
+    } else if aspect < 1.0 / 1.2 {
+        "1024x1536"
+    } else {
+        "1024x1024"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hosted_size_follows_orientation() {
+        assert_eq!(hosted_size(1024, 576), "1536x1024");
+        assert_eq!(hosted_size(576, 1024), "1024x1536");
+        assert_eq!(hosted_size(1280, 1024), "1024x1024");
+    }
+}
@@ -1,9 +1,249 @@
-// Experimental AI-powered wallpaper suggestions
+//! Wallpaper generation from text prompts, and tagging of wallpapers
+//!
+//! A prompt is sent to an image generation service, either one running on
+//! this machine (Stable Diffusion WebUI, ComfyUI) or a hosted API. Services
+//! generate a few candidates at a size close to a monitor's aspect ratio; the
+//! one picked is scaled to the monitor's resolution and saved to the library.
+//! `tagging` labels wallpapers in the library with a local classifier.
+pub mod comfyui;
+pub mod hosted;
+pub mod stable_diffusion;
+pub mod tagging;
 
-pub fn enable() {
-    // Enable AI experiments
+pub use comfyui::ComfyUiGenerator;
+pub use hosted::HostedGenerator;
+pub use stable_diffusion::StableDiffusionGenerator;
+
+use crate::core::config::{AiConfig, AiProvider};
+use crate::core::{AppError, AppResult, Config, SecretStore};
+use image::imageops::FilterType;
+use log::info;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Longest part of a file name taken from the prompt
+const MAX_NAME_LENGTH: usize = 48;
+
+/// What to generate
+#[derive(Debug, Clone, Default)]
+pub struct GenerationRequest {
+    /// What the images should show
+    pub prompt: String,
+
+    /// What the images should not show
+    pub negative_prompt: String,
+
+    /// Image width in pixels
+    pub width: u32,
+
+    /// Image height in pixels
+    pub height: u32,
+
+    /// Number of images
+    pub count: u32,
+}
+
+/// Image generation service
+pub trait ImageGenerator: Send + Sync {
+    /// Get the service name
+    fn name(&self) -> &str;
+
+    /// Generate images, returned as encoded image files
+    fn generate(&self, request: &GenerationRequest) -> AppResult<Vec<Vec<u8>>>;
+}
+
+/// Create the generator for the configured service
+pub fn create_generator(
+    config: &AiConfig,
+    secret_store: Arc<SecretStore>,
+) -> Arc<dyn ImageGenerator> {
+    match config.provider {
+        AiProvider::StableDiffusion => Arc::new(StableDiffusionGenerator::new(config)),
+        AiProvider::ComfyUi => Arc::new(ComfyUiGenerator::new(config)),
+        AiProvider::Hosted => Arc::new(HostedGenerator::new(config, secret_store)),
+    }
+}
+
+/// Get the size to generate for a monitor, keeping its aspect ratio
+///
+/// The longest edge is kept to `max_edge` and both edges are rounded to
+/// multiples of 64, which diffusion models need.
+pub fn generation_size(width: u32, height: u32, max_edge: u32) -> (u32, u32) {
+    let scale = (max_edge as f32 / width.max(height).max(1) as f32).min(1.0);
+    let round = |edge: u32| (((edge as f32 * scale) / 64.0).round() as u32).max(1) * 64;
+    (round(width), round(height))
+}
+
+/// Generate candidates, replacing the previous ones
+pub fn generate_candidates(
+    generator: &dyn ImageGenerator,
+    request: &GenerationRequest,
+) -> AppResult<Vec<PathBuf>> {
+    if request.prompt.trim().is_empty() {
+        return Err(AppError::ConfigError(
+            "Describe the wallpaper to generate".to_string(),
+        ));
+    }
+
+    info!(
+        "Generating {} wallpapers with {}",
+        request.count,
+        generator.name()
+    );
+    let images = generator.generate(request)?;
+    if images.is_empty() {
+        return Err(AppError::WallpaperError(format!(
+            "{} returned no images",
+            generator.name()
+        )));
+    }
+
+    let dir = candidates_dir();
+    fs::create_dir_all(&dir)?;
+    for entry in fs::read_dir(&dir)?.flatten() {
+        let _ = fs::remove_file(entry.path());
+    }
+
+    // New names each time, so thumbnails of older candidates aren't reused
+    let stamp = chrono::Utc::now().timestamp_millis();
+    images
+        .iter()
+        .enumerate()
+        .map(|(index, bytes)| {
+            let image = image::load_from_memory(bytes).map_err(|e| {
+                AppError::WallpaperError(format!(
+                    "{} returned an unreadable image: {}",
+                    generator.name(),
+                    e
+                ))
+            })?;
+            let path = dir.join(format!("candidate-{}-{}.png", stamp, index + 1));
+            image.save(&path).map_err(|e| {
+                AppError::WallpaperError(format!("Failed to save {}: {}", path.display(), e))
+            })?;
+            Ok(path)
+        })
+        .collect()
+}
+
+/// Save a candidate to the library, scaled to cover `width` x `height`
+pub fn save_to_library(
+    candidate: &Path,
+    prompt: &str,
+    width: u32,
+    height: u32,
+) -> AppResult<PathBuf> {
+    let image = image::open(candidate).map_err(|e| {
+        AppError::WallpaperError(format!("Failed to open {}: {}", candidate.display(), e))
+    })?;
+    let image = image.resize_to_fill(width, height, FilterType::Lanczos3);
+
+    let path = Config::get_generated_dir().join(format!(
+        "{}-{}.png",
+        file_stem(prompt),
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    image.save(&path).map_err(|e| {
+        AppError::WallpaperError(format!("Failed to save {}: {}", path.display(), e))
+    })?;
+    info!("Saved generated wallpaper to {}", path.display());
+    Ok(path)
+}
+
+/// Get the directory holding the last candidates
+fn candidates_dir() -> PathBuf {
+    Config::get_generated_dir().join("candidates")
+}
+
+/// Turn a prompt into a file name
+fn file_stem(prompt: &str) -> String {
+    let mut stem = String::new();
+    for word in prompt
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+    {
+        if stem.len() + word.len() >= MAX_NAME_LENGTH {
+            break;
+        }
+        if !stem.is_empty() {
+            stem.push('-');
+        }
+        stem.push_str(&word.to_lowercase());
+    }
+    if stem.is_empty() {
+        "generated".to_string()
+    } else {
+        stem
+    }
 }
 
-pub fn disable() {
-    // Disable AI experiments
-} 
\ No newline at end of file
+/// Decode base64 image data, with or without a `data:` URL prefix
+pub fn decode_base64(text: &str) -> AppResult<Vec<u8>> {
+    let text = text.split_once("base64,").map_or(text, |(_, data)| data);
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    let (mut buffer, mut bits) = (0u32, 0);
+    for c in text
+        .bytes()
+        .filter(|c| !c.is_ascii_whitespace() && *c != b'=')
+    {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            _ => {
+                return Err(AppError::WallpaperError(
+                    "The generated image isn't valid base64".to_string(),
+                ))
+            }
+        };
+        buffer = (buffer << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    Ok(bytes)
+}
+
+/// Pick a random seed, so each generation gives new images
+pub fn random_seed() -> u64 {
+    let mut bytes = [0u8; 8];
+    let _ = getrandom::fill(&mut bytes);
+    // Some services store seeds as doubles
+    u64::from_le_bytes(bytes) >> 12
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generation_size_keeps_aspect_ratio() {
+        assert_eq!(generation_size(3840, 2160, 1024), (1024, 576));
+        assert_eq!(generation_size(1080, 1920, 1024), (576, 1024));
+        assert_eq!(generation_size(3440, 1440, 1536), (1536, 640));
+        // Small monitors aren't scaled up
+        assert_eq!(generation_size(800, 600, 1024), (832, 576));
+    }
+
+    #[test]
+    fn test_file_stem() {
+        assert_eq!(
+            file_stem("A misty forest, at dawn!"),
+            "a-misty-forest-at-dawn"
+        );
+        assert_eq!(file_stem("   "), "generated");
+        assert!(file_stem(&"word ".repeat(40)).len() < MAX_NAME_LENGTH);
+    }
+
+    #[test]
+    fn test_decode_base64() {
+        assert_eq!(decode_base64("aGVsbG8=").unwrap(), b"hello");
+        assert_eq!(decode_base64("data:image/png;base64,aGk=").unwrap(), b"hi");
+        assert!(decode_base64("not base64!").is_err());
+    }
+}
@@ -0,0 +1,90 @@
+//! Stable Diffusion WebUI generator
+//!
+//! Works with AUTOMATIC1111 and Forge when they're started with `--api`.
+use crate::core::config::AiConfig;
+use crate::core::{AppError, AppResult, HttpClient, RateLimit};
+use crate::experiments::ai::{decode_base64, GenerationRequest, ImageGenerator};
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Generator name and rate limit key
+const GENERATOR_NAME: &str = "Stable Diffusion WebUI";
+
+/// Longest wait for a batch of images
+const TIMEOUT: Duration = Duration::from_secs(15 * 60);
+
+/// Generator using the Stable Diffusion WebUI API
+pub struct StableDiffusionGenerator {
+    /// Shared HTTP client
+    http_client: Arc<HttpClient>,
+
+    /// WebUI address
+    endpoint: String,
+
+    /// Checkpoint to switch to (empty = the one loaded)
+    model: String,
+
+    /// Sampling steps
+    steps: u32,
+}
+
+impl StableDiffusionGenerator {
+    /// Create a generator for the configured WebUI
+    pub fn new(config: &AiConfig) -> Self {
+        let http_client = HttpClient::shared();
+        http_client.set_rate_limit(GENERATOR_NAME, RateLimit::per_minute(30));
+
+        Self {
+            http_client,
+            endpoint: config.endpoint().to_string(),
+            model: config.model.clone(),
+            steps: config.steps,
+        }
+    }
+}
+
+impl ImageGenerator for StableDiffusionGenerator {
+    fn name(&self) -> &str {
+        GENERATOR_NAME
+    }
+
+    fn generate(&self, request: &GenerationRequest) -> AppResult<Vec<Vec<u8>>> {
+        let mut body = json!({
+            "prompt": request.prompt,
+            "negative_prompt": request.negative_prompt,
+            "width": request.width,
+            "height": request.height,
+            "steps": self.steps,
+            "batch_size": request.count,
+            "seed": -1,
+        });
+        if !self.model.is_empty() {
+            body["override_settings"] = json!({ "sd_model_checkpoint": self.model });
+        }
+
+        let url = format!("{}/sdapi/v1/txt2img", self.endpoint);
+        let response = self
+            .http_client
+            .post_json(GENERATOR_NAME, &url, &body, &[], TIMEOUT)?;
+        if response.status != 200 {
+            return Err(AppError::NetworkError(format!(
+                "{} failed with status {}: {}",
+                GENERATOR_NAME,
+                response.status,
+                response.text()
+            )));
+        }
+
+        response.json()?["images"]
+            .as_array()
+            .map(|images| {
+                images
+                    .iter()
+                    .filter_map(|image| image.as_str())
+                    .map(decode_base64)
+                    .collect()
+            })
+            .unwrap_or_else(|| Ok(Vec::new()))
+    }
+}
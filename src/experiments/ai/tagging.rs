@@ -0,0 +1,312 @@
+//! Automatic tagging with a local CLIP model
+//!
+//! The model folder holds `vision_model.onnx`, a CLIP image encoder taking
+//! `pixel_values` (1 x 3 x 224 x 224) and giving the image embedding as its
+//! first output, and `labels.json`, mapping each label to the text embedding
+//! of a prompt describing it. Labels are scored by how close their text is to
+//! the image, as in zero-shot CLIP classification. Running the model needs
+//! the `auto-tagging` feature; the rest works without it.
+use crate::core::config::TaggingConfig;
+use crate::core::tags::cosine;
+use crate::core::{image_format, AppError, AppResult, EventLog, TagEntry, TagStore, WallpaperType};
+use image::imageops::FilterType;
+use image::DynamicImage;
+use log::{debug, warn};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Image encoder file in the model folder
+pub const MODEL_FILE: &str = "vision_model.onnx";
+
+/// Label embeddings file in the model folder
+pub const LABELS_FILE: &str = "labels.json";
+
+/// Width and height of the images the model sees
+const IMAGE_SIZE: u32 = 224;
+
+/// Channel means of the images CLIP was trained on
+const MEAN: [f32; 3] = [0.481_454_66, 0.457_827_5, 0.408_210_73];
+
+/// Channel standard deviations of the images CLIP was trained on
+const STD: [f32; 3] = [0.268_629_54, 0.261_302_58, 0.275_777_11];
+
+/// Scale CLIP applies to similarities before comparing labels
+const LOGIT_SCALE: f32 = 100.0;
+
+/// Most tags given to one image
+const MAX_TAGS: usize = 3;
+
+/// Loaded image encoder
+#[cfg(feature = "auto-tagging")]
+type Model = Mutex<ort::session::Session>;
+
+/// Builds without the feature can't load a model
+#[cfg(not(feature = "auto-tagging"))]
+type Model = ();
+
+/// Labels images with a CLIP model
+pub struct Tagger {
+    /// Image encoder
+    model: Model,
+
+    /// Text embeddings by label
+    labels: Vec<(String, Vec<f32>)>,
+}
+
+impl Tagger {
+    /// Load the model from a folder
+    pub fn load(dir: &Path) -> AppResult<Self> {
+        let model = load_model(&dir.join(MODEL_FILE))?;
+        let labels = load_labels(&dir.join(LABELS_FILE))?;
+        Ok(Self { model, labels })
+    }
+
+    /// Tag an image with the labels at least `threshold` likely
+    pub fn tag(&self, path: &Path, threshold: f32) -> AppResult<TagEntry> {
+        let image = image_format::open(path)?;
+        let embedding = self.embed(preprocess(&image))?;
+        let tags = pick_tags(&score_labels(&embedding, &self.labels), threshold);
+        Ok(TagEntry { tags, embedding })
+    }
+
+    /// Run the image encoder
+    #[cfg(feature = "auto-tagging")]
+    fn embed(&self, pixels: Vec<f32>) -> AppResult<Vec<f32>> {
+        let error =
+            |e: ort::Error| AppError::WallpaperError(format!("The tagging model failed: {}", e));
+        let size = IMAGE_SIZE as usize;
+        let input = ort::value::Tensor::from_array(([1, 3, size, size], pixels)).map_err(error)?;
+        let session = self.model.lock().unwrap();
+        let outputs = session
+            .run(ort::inputs![input].map_err(error)?)
+            .map_err(error)?;
+        let (_, embedding) = outputs[0].try_extract_raw_tensor::<f32>().map_err(error)?;
+        Ok(embedding.to_vec())
+    }
+
+    /// Run the image encoder
+    #[cfg(not(feature = "auto-tagging"))]
+    fn embed(&self, _pixels: Vec<f32>) -> AppResult<Vec<f32>> {
+        let () = self.model;
+        Err(feature_missing())
+    }
+}
+
+/// Load the image encoder
+#[cfg(feature = "auto-tagging")]
+fn load_model(path: &Path) -> AppResult<Model> {
+    let session = ort::session::Session::builder()
+        .and_then(|builder| builder.commit_from_file(path))
+        .map_err(|e| {
+            AppError::WallpaperError(format!(
+                "Failed to load the tagging model {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+    Ok(Mutex::new(session))
+}
+
+/// Load the image encoder
+#[cfg(not(feature = "auto-tagging"))]
+fn load_model(_path: &Path) -> AppResult<Model> {
+    Err(feature_missing())
+}
+
+/// Error for builds without the feature
+#[cfg(not(feature = "auto-tagging"))]
+fn feature_missing() -> AppError {
+    AppError::BackendUnavailable {
+        backend: "Auto-tagging".to_string(),
+        reason: "this build doesn't include ONNX Runtime (build with --features auto-tagging)"
+            .to_string(),
+    }
+}
+
+/// Load the text embeddings of the labels
+fn load_labels(path: &Path) -> AppResult<Vec<(String, Vec<f32>)>> {
+    let json = fs::read_to_string(path).map_err(|e| {
+        AppError::ConfigError(format!(
+            "Failed to read the tagging labels {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    let labels: BTreeMap<String, Vec<f32>> = serde_json::from_str(&json)?;
+    if labels.is_empty() {
+        return Err(AppError::ConfigError(format!(
+            "{} has no labels",
+            path.display()
+        )));
+    }
+    Ok(labels.into_iter().collect())
+}
+
+/// Turn an image into model input: the center square, normalized, channel by channel
+pub fn preprocess(image: &DynamicImage) -> Vec<f32> {
+    let image = image
+        .resize_to_fill(IMAGE_SIZE, IMAGE_SIZE, FilterType::CatmullRom)
+        .to_rgb8();
+    let plane = (IMAGE_SIZE * IMAGE_SIZE) as usize;
+    let mut pixels = vec![0.0; 3 * plane];
+    for (index, pixel) in image.pixels().enumerate() {
+        for channel in 0..3 {
+            pixels[channel * plane + index] =
+                (pixel[channel] as f32 / 255.0 - MEAN[channel]) / STD[channel];
+        }
+    }
+    pixels
+}
+
+/// Get the likelihood of each label for an image embedding, most likely first
+pub fn score_labels(embedding: &[f32], labels: &[(String, Vec<f32>)]) -> Vec<(String, f32)> {
+    let logits: Vec<f32> = labels
+        .iter()
+        .map(|(_, text)| LOGIT_SCALE * cosine(embedding, text))
+        .collect();
+    let max = logits.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let exps: Vec<f32> = logits.iter().map(|logit| (logit - max).exp()).collect();
+    let total: f32 = exps.iter().sum();
+
+    let mut scores: Vec<(String, f32)> = labels
+        .iter()
+        .zip(exps)
+        .map(|((label, _), exp)| (label.clone(), exp / total))
+        .collect();
+    scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+    scores
+}
+
+/// Pick the tags of an image from its label scores
+///
+/// The most likely label is always a tag, so every image gets one.
+pub fn pick_tags(scores: &[(String, f32)], threshold: f32) -> Vec<String> {
+    scores
+        .iter()
+        .enumerate()
+        .take_while(|(rank, (_, score))| *rank == 0 || *score >= threshold)
+        .take(MAX_TAGS)
+        .map(|(_, (label, _))| label.clone())
+        .collect()
+}
+
+/// Tags wallpapers one at a time on a worker thread
+#[derive(Debug)]
+pub struct TagQueue {
+    /// Wallpapers for the worker
+    sender: Sender<PathBuf>,
+
+    /// Tagging settings, read for each wallpaper
+    config: Arc<Mutex<TaggingConfig>>,
+}
+
+impl TagQueue {
+    /// Start the worker recording tags in `store`
+    ///
+    /// The model is loaded once there is an untagged wallpaper, and again
+    /// when its folder changes. When it can't be loaded, the error is logged
+    /// once and queued wallpapers are left untagged. The worker stops when
+    /// the queue is dropped.
+    pub fn start(config: TaggingConfig, store: Arc<TagStore>) -> Self {
+        let (sender, receiver) = mpsc::channel::<PathBuf>();
+        let config = Arc::new(Mutex::new(config));
+        let worker_config = config.clone();
+        thread::spawn(move || {
+            // Model folder with the tagger loaded from it, or None if loading failed
+            let mut loaded: Option<(PathBuf, Option<Tagger>)> = None;
+            for path in receiver {
+                if store.is_tagged(&path) {
+                    continue;
+                }
+                let config = worker_config.lock().unwrap().clone();
+                let dir = config.model_dir();
+                if loaded
+                    .as_ref()
+                    .map_or(true, |(loaded_dir, _)| *loaded_dir != dir)
+                {
+                    let tagger = Tagger::load(&dir)
+                        .map_err(|e| {
+                            warn!("Auto-tagging is unavailable: {}", e);
+                            EventLog::shared().error("tagging", "Auto-tagging is unavailable", e);
+                        })
+                        .ok();
+                    loaded = Some((dir, tagger));
+                }
+                let Some((_, Some(tagger))) = &loaded else {
+                    continue;
+                };
+                match tagger.tag(&path, config.threshold) {
+                    Ok(entry) => {
+                        debug!("Tagged {} as {}", path.display(), entry.tags.join(", "));
+                        store.set(&path, entry);
+                    }
+                    Err(e) => warn!("Failed to tag {}: {}", path.display(), e),
+                }
+            }
+        });
+        Self { sender, config }
+    }
+
+    /// Update the tagging settings
+    pub fn update_config(&self, config: TaggingConfig) {
+        *self.config.lock().unwrap() = config;
+    }
+
+    /// Queue a wallpaper for tagging; only static images are tagged
+    pub fn queue(&self, path: &Path) {
+        if WallpaperType::Static.accepts(path) {
+            let _ = self.sender.send(path.to_path_buf());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels() -> Vec<(String, Vec<f32>)> {
+        vec![
+            ("city".to_string(), vec![0.0, 1.0, 0.0]),
+            ("dark".to_string(), vec![0.7, 0.0, 0.7]),
+            ("nature".to_string(), vec![1.0, 0.0, 0.0]),
+        ]
+    }
+
+    #[test]
+    fn test_score_labels() {
+        let scores = score_labels(&[1.0, 0.0, 0.1], &labels());
+        assert_eq!(scores[0].0, "nature");
+        assert!((scores.iter().map(|(_, score)| score).sum::<f32>() - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_pick_tags() {
+        let scores = vec![
+            ("nature".to_string(), 0.6),
+            ("dark".to_string(), 0.3),
+            ("city".to_string(), 0.1),
+        ];
+        assert_eq!(pick_tags(&scores, 0.15), ["nature", "dark"]);
+        // The best label is kept even below the threshold
+        assert_eq!(pick_tags(&scores, 0.9), ["nature"]);
+    }
+
+    #[test]
+    fn test_preprocess_layout() {
+        let image = DynamicImage::ImageRgb8(image::RgbImage::from_pixel(
+            400,
+            300,
+            image::Rgb([255, 0, 0]),
+        ));
+        let pixels = preprocess(&image);
+        let plane = (IMAGE_SIZE * IMAGE_SIZE) as usize;
+
+        assert_eq!(pixels.len(), 3 * plane);
+        assert!((pixels[0] - (1.0 - MEAN[0]) / STD[0]).abs() < 1e-4);
+        assert!((pixels[plane] - (0.0 - MEAN[1]) / STD[1]).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,233 @@
+//! Audio-reactive effects drawn over wallpapers
+//!
+//! Spectrum bars rise from the bottom edge and a vignette darkens the edges
+//! in time with what's playing. Both jump up at once and settle back slowly,
+//! so the motion follows the beat instead of flickering with every sample.
+use crate::core::config::{AudioOverlayConfig, PlacementConfig};
+use crate::core::AppResult;
+use crate::platform::audio::{self, Samples};
+use crate::wallpapers::frame_player::FrameSource;
+use crate::wallpapers::particles::parse_color;
+use image::{Rgba, RgbaImage};
+use std::path::Path;
+use std::time::Duration;
+
+/// How far the bars fall per second, as a share of their full height
+const BAR_FALL: f32 = 1.5;
+
+/// How far the vignette's pulse fades per second
+const PULSE_FALL: f32 = 2.0;
+
+/// Quietest level shown, 60 dB below full scale
+const FLOOR: f32 = 0.001;
+
+/// Share of a bar's width left as a gap to the next
+const BAR_GAP: f32 = 0.2;
+
+/// Color of the bars when the configured one can't be read
+const DEFAULT_COLOR: Rgba<u8> = Rgba([139, 233, 253, 255]);
+
+/// State of the audio-reactive effects
+pub struct AudioVisuals {
+    /// What is drawn and how strongly it reacts
+    settings: AudioOverlayConfig,
+
+    /// Color of the bars
+    color: Rgba<u8>,
+
+    /// Height of each bar, from 0.0 to 1.0
+    bars: Vec<f32>,
+
+    /// Strength of the vignette's pulse, from 0.0 to 1.0
+    pulse: f32,
+}
+
+impl AudioVisuals {
+    /// Create effects at rest
+    pub fn new(settings: AudioOverlayConfig) -> Self {
+        Self {
+            color: parse_color(&settings.color).unwrap_or(DEFAULT_COLOR),
+            bars: vec![0.0; settings.bar_count.clamp(1, 256) as usize],
+            pulse: 0.0,
+            settings,
+        }
+    }
+
+    /// Follow the latest samples, `seconds` after the last update
+    pub fn update(&mut self, samples: &[f32], seconds: f32) {
+        let sensitivity = self.settings.sensitivity.max(0.0);
+        let levels = audio::spectrum(samples, self.bars.len());
+        for (bar, level) in self.bars.iter_mut().zip(levels) {
+            // Levels on a decibel scale, as hearing works
+            let target =
+                ((level * sensitivity).max(FLOOR).log10() / -FLOOR.log10() + 1.0).clamp(0.0, 1.0);
+            *bar = target.max(*bar - BAR_FALL * seconds);
+        }
+        let target = (audio::loudness(samples) * sensitivity * 3.0).min(1.0);
+        self.pulse = target.max(self.pulse - PULSE_FALL * seconds);
+    }
+
+    /// Draw the effects over a frame
+    pub fn draw_over(&self, frame: &mut RgbaImage) {
+        let (width, height) = frame.dimensions();
+        self.paint(width, height, |x, y, color, alpha| {
+            let pixel = frame.get_pixel_mut(x, y);
+            for channel in 0..3 {
+                pixel[channel] = (pixel[channel] as f32 * (1.0 - alpha)
+                    + color[channel] as f32 * alpha)
+                    .round() as u8;
+            }
+        });
+    }
+
+    /// Draw the effects alone on a transparent frame, for players showing them over their video
+    pub fn draw_alone(&self, width: u32, height: u32) -> RgbaImage {
+        let mut frame = RgbaImage::new(width, height);
+        self.paint(width, height, |x, y, color, alpha| {
+            // The color laid over what is already there
+            let pixel = frame.get_pixel_mut(x, y);
+            let below = pixel[3] as f32 / 255.0;
+            let covered = alpha + below * (1.0 - alpha);
+            if covered > 0.0 {
+                for channel in 0..3 {
+                    let mixed = color[channel] as f32 * alpha
+                        + pixel[channel] as f32 * below * (1.0 - alpha);
+                    pixel[channel] = (mixed / covered).round() as u8;
+                }
+            }
+            pixel[3] = (covered * 255.0).round() as u8;
+        });
+        frame
+    }
+
+    /// Paint the vignette, then the bars, as colors laid over pixels with some opacity
+    fn paint(&self, width: u32, height: u32, mut put: impl FnMut(u32, u32, Rgba<u8>, f32)) {
+        if self.settings.vignette {
+            let darkness = 0.35 + 0.45 * self.pulse;
+            let (half_width, half_height) = (width as f32 / 2.0, height as f32 / 2.0);
+            for y in 0..height {
+                for x in 0..width {
+                    let (dx, dy) = (
+                        (x as f32 - half_width) / half_width,
+                        (y as f32 - half_height) / half_height,
+                    );
+                    let edge = (((dx * dx + dy * dy).sqrt() - 0.55) / 0.85).clamp(0.0, 1.0);
+                    if edge > 0.0 {
+                        put(x, y, Rgba([0, 0, 0, 255]), edge * edge * darkness);
+                    }
+                }
+            }
+        }
+
+        if self.settings.bars {
+            let slot = width as f32 / self.bars.len() as f32;
+            let tallest = self.settings.bar_height.clamp(0.0, 1.0) * height as f32;
+            for (index, bar) in self.bars.iter().enumerate() {
+                let left = (index as f32 * slot + slot * BAR_GAP / 2.0).round() as u32;
+                let right =
+                    (((index + 1) as f32 * slot - slot * BAR_GAP / 2.0).round() as u32).min(width);
+                let top = height.saturating_sub((bar * tallest).round() as u32);
+                for y in top..height {
+                    // Fading towards the bar's top
+                    let alpha = 0.9 - 0.4 * (height - y) as f32 / tallest.max(1.0);
+                    for x in left..right {
+                        put(x, y, self.color, alpha);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Audio-reactive effects over a static image
+pub struct OverStatic {
+    /// Image drawn over
+    base: RgbaImage,
+
+    /// Effects drawn
+    visuals: AudioVisuals,
+
+    /// Recording followed
+    samples: Samples,
+
+    /// Time of the last frame
+    last: Option<Duration>,
+}
+
+/// Draw the effects over a static wallpaper, rendered for the primary monitor with its placement
+pub fn over_wallpaper(
+    path: &Path,
+    placement: &PlacementConfig,
+    settings: AudioOverlayConfig,
+    samples: Samples,
+) -> AppResult<OverStatic> {
+    Ok(OverStatic {
+        base: super::render_for_primary(path, placement)?,
+        visuals: AudioVisuals::new(settings),
+        samples,
+        last: None,
+    })
+}
+
+impl FrameSource for OverStatic {
+    fn frame(&mut self, elapsed: Duration) -> Option<RgbaImage> {
+        let seconds = self
+            .last
+            .map_or(0.0, |last| elapsed.saturating_sub(last).as_secs_f32());
+        self.last = Some(elapsed);
+        self.visuals.update(&self.samples.latest(), seconds);
+        let mut frame = self.base.clone();
+        self.visuals.draw_over(&mut frame);
+        Some(frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn loud() -> Vec<f32> {
+        (0..audio::WINDOW)
+            .map(|index| (index as f32 * 0.3).sin())
+            .collect()
+    }
+
+    #[test]
+    fn test_bars_jump_and_settle() {
+        let mut visuals = AudioVisuals::new(AudioOverlayConfig {
+            bar_count: 8,
+            ..AudioOverlayConfig::default()
+        });
+        visuals.update(&loud(), 0.0);
+        let peak = visuals.bars.iter().copied().fold(0.0, f32::max);
+        assert!(peak > 0.5 && visuals.pulse > 0.5);
+
+        // Silence lets them fall back bit by bit
+        visuals.update(&vec![0.0; audio::WINDOW], 0.1);
+        let settling = visuals.bars.iter().copied().fold(0.0, f32::max);
+        assert!(settling < peak && settling > 0.0);
+        visuals.update(&vec![0.0; audio::WINDOW], 2.0);
+        assert!(visuals.bars.iter().all(|bar| *bar == 0.0) && visuals.pulse == 0.0);
+    }
+
+    #[test]
+    fn test_draw() {
+        let mut visuals = AudioVisuals::new(AudioOverlayConfig {
+            bar_count: 4,
+            ..AudioOverlayConfig::default()
+        });
+        visuals.update(&loud(), 0.0);
+
+        // The edges darken and the bars cover part of the bottom
+        let mut frame = RgbaImage::from_pixel(200, 100, Rgba([100, 100, 100, 255]));
+        visuals.draw_over(&mut frame);
+        assert_eq!(*frame.get_pixel(100, 50), Rgba([100, 100, 100, 255]));
+        assert!(frame.get_pixel(0, 0)[0] < 100);
+        assert!((0..200).any(|x| frame.get_pixel(x, 99)[2] > 150));
+
+        // Alone, untouched pixels stay see-through
+        let alone = visuals.draw_alone(200, 100);
+        assert_eq!(alone.get_pixel(100, 50)[3], 0);
+        assert!(alone.get_pixel(0, 0)[3] > 0);
+    }
+}
@@ -0,0 +1,207 @@
+//! Toned down copies of static wallpapers for focus mode
+//!
+//! The wallpaper is rendered for the primary monitor with its placement,
+//! then darkened, blurred or both. Fading in shows frames of growing strength
+//! in a wallpaper window, like transitions, and sets the toned down copy
+//! underneath; fading out shows them in reverse before the wallpaper itself
+//! is set again.
+use crate::core::config::{FocusEffect, FocusModeConfig, PlacementConfig};
+use crate::core::{placement, AppError, AppResult, Config};
+use crate::platform::WallpaperManager;
+use image::imageops::{self, FilterType};
+use image::{Rgba, RgbaImage};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Frames rendered per second of a fade
+const FRAME_RATE: u32 = 12;
+
+/// Most frames rendered for one fade
+const MAX_FRAMES: u32 = 12;
+
+/// Share of the brightness taken away at full strength
+const MAX_DIM: f32 = 0.8;
+
+/// Blur at full strength, in pixels of a 1920 pixel wide frame
+const MAX_BLUR: f32 = 24.0;
+
+/// Shrinking applied before blurring, which keeps strong blurs fast
+const BLUR_DOWNSCALE: u32 = 4;
+
+/// Darken and/or blur an image by `amount` (0.0 - 1.0)
+pub fn tone_down(image: &RgbaImage, effect: FocusEffect, amount: f32) -> RgbaImage {
+    let amount = amount.clamp(0.0, 1.0);
+    let blurred = match effect {
+        FocusEffect::Blur | FocusEffect::DimAndBlur if amount > 0.0 => {
+            blur(image, amount * MAX_BLUR * image.width() as f32 / 1920.0)
+        }
+        _ => image.clone(),
+    };
+    match effect {
+        FocusEffect::Dim | FocusEffect::DimAndBlur => {
+            let keep = 1.0 - amount * MAX_DIM;
+            let mut dimmed = blurred;
+            for pixel in dimmed.pixels_mut() {
+                let Rgba([r, g, b, a]) = *pixel;
+                let dim = |channel: u8| (channel as f32 * keep).round() as u8;
+                *pixel = Rgba([dim(r), dim(g), dim(b), a]);
+            }
+            dimmed
+        }
+        FocusEffect::Blur => blurred,
+    }
+}
+
+/// Blur an image by `sigma` pixels, on a smaller copy
+fn blur(image: &RgbaImage, sigma: f32) -> RgbaImage {
+    let (width, height) = image.dimensions();
+    let small = imageops::resize(
+        image,
+        (width / BLUR_DOWNSCALE).max(1),
+        (height / BLUR_DOWNSCALE).max(1),
+        FilterType::Triangle,
+    );
+    let blurred = imageops::blur(&small, (sigma / BLUR_DOWNSCALE as f32).max(0.5));
+    imageops::resize(&blurred, width, height, FilterType::Triangle)
+}
+
+/// Fade a static wallpaper to its toned down copy
+///
+/// Returns the toned down copy set as the wallpaper, for `fade_out`.
+pub async fn fade_in(
+    wallpaper_manager: &Arc<dyn WallpaperManager + Send + Sync>,
+    path: &Path,
+    placement: &PlacementConfig,
+    focus: &FocusModeConfig,
+) -> AppResult<PathBuf> {
+    let (path, placement, settings) = (path.to_path_buf(), placement.clone(), focus.clone());
+    let (frames, copy) = tokio::task::spawn_blocking(move || -> AppResult<_> {
+        let frames = render_frames(&path, &placement, &settings, true)?;
+        let copy = save_copy(frames.last())?;
+        Ok((frames, copy))
+    })
+    .await
+    .map_err(|e| {
+        AppError::WallpaperError(format!("Failed to render focus mode frames: {}", e))
+    })??;
+    let player = super::play_frames(
+        wallpaper_manager,
+        frames,
+        Duration::from_millis(focus.fade_ms as u64),
+    )
+    .await?;
+    wallpaper_manager.set_static_wallpaper(&copy).await?;
+    drop(player);
+    Ok(copy)
+}
+
+/// Fade back from a toned down copy to the wallpaper itself
+pub async fn fade_out(
+    wallpaper_manager: &Arc<dyn WallpaperManager + Send + Sync>,
+    copy: &Path,
+    path: &Path,
+    placement: &PlacementConfig,
+    focus: &FocusModeConfig,
+) -> AppResult<()> {
+    let player = if focus.fade_ms > 0 {
+        let (path, placement, settings) = (path.to_path_buf(), placement.clone(), focus.clone());
+        let frames =
+            tokio::task::spawn_blocking(move || render_frames(&path, &placement, &settings, false))
+                .await
+                .map_err(|e| {
+                    AppError::WallpaperError(format!("Failed to render focus mode frames: {}", e))
+                })??;
+        super::play_frames(
+            wallpaper_manager,
+            frames,
+            Duration::from_millis(focus.fade_ms as u64),
+        )
+        .await?
+    } else {
+        None
+    };
+    placement::set_static_wallpaper(wallpaper_manager.as_ref(), path, placement).await?;
+    drop(player);
+    let _ = fs::remove_file(copy);
+    Ok(())
+}
+
+/// Render the frames of a fade, growing in strength when fading in
+///
+/// The last frame is the toned down copy when fading in and the wallpaper
+/// itself when fading out.
+fn render_frames(
+    path: &Path,
+    placement: &PlacementConfig,
+    focus: &FocusModeConfig,
+    fading_in: bool,
+) -> AppResult<Vec<RgbaImage>> {
+    let placed = super::render_for_primary(path, placement)?;
+    let count = (focus.fade_ms * FRAME_RATE / 1000).clamp(1, MAX_FRAMES);
+    let steps: Vec<u32> = if fading_in {
+        (1..=count).collect()
+    } else {
+        (0..count).rev().collect()
+    };
+    Ok(steps
+        .into_iter()
+        .map(|index| {
+            tone_down(
+                &placed,
+                focus.effect,
+                focus.strength * super::ease(index as f32 / count as f32),
+            )
+        })
+        .collect())
+}
+
+/// Save the toned down copy into the placement folder, replacing older ones
+fn save_copy(frame: Option<&RgbaImage>) -> AppResult<PathBuf> {
+    let frame = frame
+        .ok_or_else(|| AppError::WallpaperError("No focus mode frame was rendered".to_string()))?;
+
+    // New names each time, as backends caching by path would show an old copy
+    let dir = Config::get_placement_dir().join("focus");
+    fs::create_dir_all(&dir)?;
+    for entry in fs::read_dir(&dir)?.flatten() {
+        let _ = fs::remove_file(entry.path());
+    }
+    let path = dir.join(format!(
+        "focus-{}.png",
+        chrono::Utc::now().timestamp_millis()
+    ));
+    frame.save(&path).map_err(|e| {
+        AppError::WallpaperError(format!("Failed to save {}: {}", path.display(), e))
+    })?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tone_down() {
+        let image = RgbaImage::from_fn(64, 32, |x, _| {
+            if x < 32 {
+                Rgba([200, 100, 50, 255])
+            } else {
+                Rgba([0, 0, 0, 255])
+            }
+        });
+
+        assert_eq!(tone_down(&image, FocusEffect::Dim, 0.0), image);
+        let dimmed = tone_down(&image, FocusEffect::Dim, 1.0);
+        assert_eq!(*dimmed.get_pixel(0, 0), Rgba([40, 20, 10, 255]));
+
+        // Blurring softens the edge between the halves but keeps the brightness
+        let blurred = tone_down(&image, FocusEffect::Blur, 1.0);
+        assert!(blurred.get_pixel(31, 16)[0] < 200 && blurred.get_pixel(32, 16)[0] > 0);
+        assert!(blurred.get_pixel(2, 16)[0] > dimmed.get_pixel(2, 16)[0]);
+
+        let both = tone_down(&image, FocusEffect::DimAndBlur, 1.0);
+        assert!(both.get_pixel(31, 16)[0] < blurred.get_pixel(31, 16)[0]);
+    }
+}
@@ -1,9 +1,537 @@
-// Experimental enhanced wallpaper effects
+//! Transitions between static wallpapers
+//!
+//! Where swww is running it animates the switch itself, given the matching
+//! `--transition-*` flags. Elsewhere frames blending the old wallpaper into
+//! the new one are rendered for the primary monitor with the same placement as
+//! the final image and shown in a wallpaper window, and the new wallpaper is
+//! set underneath before the window closes. Without MPV to show the window, a
+//! few of the frames are set as still wallpapers instead.
+pub mod audio;
+pub mod focus;
+pub mod motion;
+pub mod temperature;
+pub mod weather;
 
-pub fn enable() {
-    // Enable effects experiments
+use crate::core::config::{CropRect, PlacementConfig, TransitionConfig, TransitionEffect};
+use crate::core::{adjustments, image_format, placement, AppError, AppResult, Config};
+use crate::platform::display;
+use crate::platform::WallpaperManager;
+use crate::wallpapers::frame_player::{self, FramePlayer, FrameSource};
+use crate::wallpapers::video_wallpaper::VideoWallpaper;
+use image::imageops::{self, FilterType};
+use image::{Rgba, RgbaImage};
+use log::{debug, warn};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Frames rendered per second of a transition
+const FRAME_RATE: u32 = 12;
+
+/// Most frames rendered for one transition
+const MAX_FRAMES: u32 = 24;
+
+/// Widest frame rendered; the backend scales it up to the monitor
+const MAX_FRAME_WIDTH: u32 = 1920;
+
+/// Largest block of the pixelate effect, in pixels
+const MAX_BLOCK: f32 = 48.0;
+
+/// Width of the soft edge of the wipe effect, as a fraction of the image width
+const WIPE_EDGE: f32 = 1.0 / 32.0;
+
+/// Most the morph effect shrinks the images to blur them
+const MORPH_SHRINK: f32 = 32.0;
+
+/// Most frames set as still wallpapers when no wallpaper window can be shown
+const FALLBACK_FRAMES: usize = 3;
+
+/// Longest MPV may take to open the wallpaper window
+const WINDOW_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often the end of the frames is checked for
+const PLAYING_POLL: Duration = Duration::from_millis(20);
+
+/// Check whether transitions can animate a wallpaper with this placement
+///
+/// Spanned wallpapers and monitors with images of their own switch at once.
+pub fn applies(placement: &PlacementConfig, transition: &TransitionConfig) -> bool {
+    transition.effect != TransitionEffect::None
+        && !placement.span
+        && placement.monitor_images.is_empty()
+}
+
+/// Set a static wallpaper, animating the switch from the one at `from`
+///
+/// The new wallpaper is set even when the animation fails. Returns the
+/// monitors given their own image, like `placement::set_static_wallpaper`.
+pub async fn transition(
+    wallpaper_manager: &Arc<dyn WallpaperManager + Send + Sync>,
+    from: &Path,
+    to: &Path,
+    placement: &PlacementConfig,
+    transition: &TransitionConfig,
+) -> AppResult<Vec<String>> {
+    // Both ends are shown with their adjustments; a failure with the old image only loses the animation
+    let from = adjustments::adjusted(from)
+        .await
+        .unwrap_or_else(|_| from.to_path_buf());
+    let to = adjustments::adjusted(to).await?;
+
+    // swww would be handed the untinted image
+    if wallpaper_manager.native_transitions() && temperature::kelvin_now().is_none() {
+        wallpaper_manager
+            .set_static_wallpaper_transition(
+                &image_format::compatible(&to, wallpaper_manager.image_formats()).await?,
+                transition,
+            )
+            .await?;
+        return Ok(Vec::new());
+    }
+
+    let player = match play(wallpaper_manager, &from, &to, placement, transition).await {
+        Ok(player) => player,
+        Err(e) => {
+            warn!("Failed to animate the wallpaper change: {}", e);
+            None
+        }
+    };
+    // The window keeps the last frame up until the new wallpaper is set underneath
+    let monitors =
+        placement::set_static_wallpaper(wallpaper_manager.as_ref(), &to, placement).await;
+    drop(player);
+    monitors
+}
+
+/// Show the frames of a transition
+async fn play(
+    wallpaper_manager: &Arc<dyn WallpaperManager + Send + Sync>,
+    from: &Path,
+    to: &Path,
+    placement: &PlacementConfig,
+    transition: &TransitionConfig,
+) -> AppResult<Option<FramePlayer>> {
+    let started = Instant::now();
+    let (from, to, placement, transition) = (
+        from.to_path_buf(),
+        to.to_path_buf(),
+        placement.clone(),
+        *transition,
+    );
+    let frames =
+        tokio::task::spawn_blocking(move || render_frames(&from, &to, &placement, &transition))
+            .await
+            .map_err(|e| {
+                AppError::WallpaperError(format!("Failed to render transition: {}", e))
+            })??;
+    debug!(
+        "Rendered {} transition frames in {:?}",
+        frames.len(),
+        started.elapsed()
+    );
+
+    play_frames(
+        wallpaper_manager,
+        frames,
+        Duration::from_millis(transition.duration_ms as u64),
+    )
+    .await
+}
+
+/// Show frames spread evenly over `duration` in a wallpaper window
+///
+/// Returns the player still showing the last frame, so the final wallpaper
+/// can be set underneath before it's dropped and the window closes. Without
+/// MPV a few of the frames are set as still wallpapers instead, leaving the
+/// last one to the caller, and None is returned.
+pub async fn play_frames(
+    wallpaper_manager: &Arc<dyn WallpaperManager + Send + Sync>,
+    frames: Vec<RgbaImage>,
+    duration: Duration,
+) -> AppResult<Option<FramePlayer>> {
+    if frames.is_empty() {
+        return Ok(None);
+    }
+    if VideoWallpaper::get_mpv_command().is_err() {
+        play_stills(wallpaper_manager.as_ref(), &frames, duration).await?;
+        return Ok(None);
+    }
+
+    let interval = duration / frames.len() as u32;
+    let fps = (frames.len() as f32 / duration.as_secs_f32()).ceil() as u32;
+    let done = Arc::new(AtomicBool::new(false));
+    let player = FramePlayer::new(wallpaper_manager.clone());
+    player.start(
+        Box::new(Sequence {
+            frames,
+            interval,
+            shown: None,
+            done: done.clone(),
+        }),
+        fps,
+    );
+
+    let deadline = Instant::now() + duration + WINDOW_TIMEOUT;
+    while !done.load(Ordering::SeqCst) && player.is_playing() && Instant::now() < deadline {
+        tokio::time::sleep(PLAYING_POLL).await;
+    }
+    // MPV draws the last frame after reading it
+    tokio::time::sleep(interval.max(PLAYING_POLL)).await;
+    Ok(Some(player))
+}
+
+/// Set a few of the frames as still wallpapers, leaving out the last one
+async fn play_stills(
+    wallpaper_manager: &(dyn WallpaperManager + Send + Sync),
+    frames: &[RgbaImage],
+    duration: Duration,
+) -> AppResult<()> {
+    let shown = frames.len().saturating_sub(1);
+    let count = shown.min(FALLBACK_FRAMES);
+    if count == 0 {
+        return Ok(());
+    }
+
+    // New names each time, as backends caching by path would show old frames
+    let dir = transition_dir();
+    fs::create_dir_all(&dir)?;
+    for entry in fs::read_dir(&dir)?.flatten() {
+        let _ = fs::remove_file(entry.path());
+    }
+    let stamp = chrono::Utc::now().timestamp_millis();
+    let interval = duration / (count + 1) as u32;
+    for step in 1..=count {
+        let started = Instant::now();
+        let path = dir.join(format!("frame-{}-{:02}.png", stamp, step));
+        frames[step * shown / (count + 1)]
+            .save(&path)
+            .map_err(|e| {
+                AppError::WallpaperError(format!("Failed to save {}: {}", path.display(), e))
+            })?;
+        wallpaper_manager.set_static_wallpaper(&path).await?;
+        tokio::time::sleep(interval.saturating_sub(started.elapsed())).await;
+    }
+    Ok(())
+}
+
+/// Frames spread evenly over a duration, the last one held once shown
+struct Sequence {
+    /// Frames in the order they're shown
+    frames: Vec<RgbaImage>,
+
+    /// Time each frame is shown for
+    interval: Duration,
+
+    /// Index of the frame on screen
+    shown: Option<usize>,
+
+    /// Set once the last frame was handed out
+    done: Arc<AtomicBool>,
+}
+
+impl FrameSource for Sequence {
+    fn frame(&mut self, elapsed: Duration) -> Option<RgbaImage> {
+        let last = self.frames.len().checked_sub(1)?;
+        let index = ((elapsed.as_millis() / self.interval.as_millis().max(1)) as usize).min(last);
+        if self.shown == Some(index) {
+            return None;
+        }
+        self.shown = Some(index);
+        if index == last {
+            self.done.store(true, Ordering::SeqCst);
+        }
+        Some(self.frames[index].clone())
+    }
+}
+
+/// Render a static wallpaper for the primary monitor with its placement and tint
+///
+/// Effects drawn in-process start from this image.
+pub fn render_for_primary(path: &Path, placement: &PlacementConfig) -> AppResult<RgbaImage> {
+    let image = image_format::open(path)?;
+    let (width, height) = frame_player::frame_size();
+    let mut placed =
+        placement::render_placed(&image, placement, primary_crop(placement), width, height);
+    if let Some(kelvin) = temperature::kelvin_now() {
+        temperature::tint(&mut placed, kelvin);
+    }
+    Ok(placed)
+}
+
+/// Get the part of images cropped by hand for the primary monitor, if any
+pub fn primary_crop(placement: &PlacementConfig) -> Option<CropRect> {
+    display::monitors()
+        .ok()
+        .and_then(|monitors| {
+            monitors
+                .iter()
+                .find(|m| m.primary)
+                .or(monitors.first())
+                .cloned()
+        })
+        .and_then(|monitor| placement.crops.get(&monitor.name).copied())
+}
+
+/// Get the directory holding the still frames of the last transition
+fn transition_dir() -> PathBuf {
+    Config::get_placement_dir().join("transition")
+}
+
+/// Render the frames between two images, the last being the new one
+fn render_frames(
+    from: &Path,
+    to: &Path,
+    placement: &PlacementConfig,
+    transition: &TransitionConfig,
+) -> AppResult<Vec<RgbaImage>> {
+    let (from_image, to_image) = (image_format::open(from)?, image_format::open(to)?);
+
+    let monitor = display::monitors().ok().and_then(|monitors| {
+        monitors
+            .iter()
+            .find(|m| m.primary)
+            .or(monitors.first())
+            .cloned()
+    });
+    let (width, height) = monitor
+        .as_ref()
+        .map(|m| (m.width, m.height))
+        .unwrap_or_else(|| (to_image.width(), to_image.height()));
+    let scale = (MAX_FRAME_WIDTH as f32 / width.max(1) as f32).min(1.0);
+    let (width, height) = (
+        ((width as f32 * scale) as u32).max(1),
+        ((height as f32 * scale) as u32).max(1),
+    );
+    let crop = monitor.and_then(|m| placement.crops.get(&m.name).copied());
+    let mut from_frame = placement::render_placed(&from_image, placement, crop, width, height);
+    let mut to_frame = placement::render_placed(&to_image, placement, crop, width, height);
+    if let Some(kelvin) = temperature::kelvin_now() {
+        temperature::tint(&mut from_frame, kelvin);
+        temperature::tint(&mut to_frame, kelvin);
+    }
+
+    let count = frame_count(transition);
+    Ok((1..=count)
+        .map(|index| {
+            render_frame(
+                &from_frame,
+                &to_frame,
+                transition.effect,
+                ease(index as f32 / count as f32),
+            )
+        })
+        .collect())
+}
+
+/// Get how many frames a transition takes, the last being the new wallpaper itself
+fn frame_count(transition: &TransitionConfig) -> u32 {
+    (transition.duration_ms * FRAME_RATE / 1000).clamp(2, MAX_FRAMES)
+}
+
+/// Slow the animation down at its start and end
+pub fn ease(progress: f32) -> f32 {
+    let progress = progress.clamp(0.0, 1.0);
+    progress * progress * (3.0 - 2.0 * progress)
 }
 
-pub fn disable() {
-    // Disable effects experiments
-} 
\ No newline at end of file
+/// Draw the frame of a transition at `progress` (0.0 - 1.0) between two images of the same size
+pub fn render_frame(
+    from: &RgbaImage,
+    to: &RgbaImage,
+    effect: TransitionEffect,
+    progress: f32,
+) -> RgbaImage {
+    let progress = progress.clamp(0.0, 1.0);
+    let (width, height) = to.dimensions();
+    match effect {
+        TransitionEffect::None => {
+            if progress < 1.0 {
+                from.clone()
+            } else {
+                to.clone()
+            }
+        }
+        TransitionEffect::Crossfade => RgbaImage::from_fn(width, height, |x, y| {
+            blend(from.get_pixel(x, y), to.get_pixel(x, y), progress)
+        }),
+        TransitionEffect::Slide => {
+            let offset = (progress * width as f32).round() as u32;
+            RgbaImage::from_fn(width, height, |x, y| {
+                if x + offset < width {
+                    *from.get_pixel(x + offset, y)
+                } else {
+                    *to.get_pixel(x + offset - width, y)
+                }
+            })
+        }
+        TransitionEffect::Wipe => {
+            // The edge travels from just left of the image to just right of it
+            let edge = progress * (1.0 + WIPE_EDGE) - WIPE_EDGE;
+            RgbaImage::from_fn(width, height, |x, y| {
+                let amount = ((edge - x as f32 / width as f32) / WIPE_EDGE + 1.0).clamp(0.0, 1.0);
+                blend(from.get_pixel(x, y), to.get_pixel(x, y), amount)
+            })
+        }
+        TransitionEffect::Pixelate => {
+            // Blocks grow on the old image, then shrink on the new one
+            let image = if progress < 0.5 { from } else { to };
+            let block =
+                (1.0 + (1.0 - (2.0 * progress - 1.0).abs()) * (MAX_BLOCK - 1.0)).round() as u32;
+            if block <= 1 {
+                return image.clone();
+            }
+            let small = imageops::resize(
+                image,
+                (width / block).max(1),
+                (height / block).max(1),
+                FilterType::Triangle,
+            );
+            imageops::resize(&small, width, height, FilterType::Nearest)
+        }
+        TransitionEffect::Morph => {
+            // Blurriest halfway, where neither image's shapes are left to tell apart
+            let mixed = RgbaImage::from_fn(width, height, |x, y| {
+                blend(from.get_pixel(x, y), to.get_pixel(x, y), progress)
+            });
+            let shrink =
+                1.0 + (progress * std::f32::consts::PI).sin().max(0.0) * (MORPH_SHRINK - 1.0);
+            if shrink < 1.5 {
+                return mixed;
+            }
+            let small = imageops::resize(
+                &mixed,
+                ((width as f32 / shrink) as u32).max(1),
+                ((height as f32 / shrink) as u32).max(1),
+                FilterType::Triangle,
+            );
+            imageops::resize(&small, width, height, FilterType::Triangle)
+        }
+    }
+}
+
+/// Mix two pixels, `amount` of the way from `a` to `b`
+fn blend(a: &Rgba<u8>, b: &Rgba<u8>, amount: f32) -> Rgba<u8> {
+    Rgba(std::array::from_fn(|channel| {
+        (a[channel] as f32 + (b[channel] as f32 - a[channel] as f32) * amount).round() as u8
+    }))
+}
+
+/// Get the swww flags animating a transition
+pub fn swww_args(transition: &TransitionConfig) -> Vec<String> {
+    let kind = match transition.effect {
+        TransitionEffect::None => "none",
+        TransitionEffect::Crossfade => "fade",
+        TransitionEffect::Slide => "left",
+        TransitionEffect::Wipe => "wipe",
+        // swww has no pixelation or morph, so it fades instead
+        TransitionEffect::Pixelate | TransitionEffect::Morph => "fade",
+    };
+    vec![
+        "--transition-type".to_string(),
+        kind.to_string(),
+        "--transition-duration".to_string(),
+        format!("{:.2}", transition.duration_ms as f32 / 1000.0),
+        "--transition-fps".to_string(),
+        "60".to_string(),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(value: u8) -> RgbaImage {
+        RgbaImage::from_pixel(64, 32, Rgba([value, value, value, 255]))
+    }
+
+    #[test]
+    fn test_frames_run_from_old_to_new() {
+        let (from, to) = (solid(0), solid(200));
+        for effect in [
+            TransitionEffect::Crossfade,
+            TransitionEffect::Slide,
+            TransitionEffect::Wipe,
+            TransitionEffect::Pixelate,
+            TransitionEffect::Morph,
+        ] {
+            assert_eq!(
+                render_frame(&from, &to, effect, 0.0),
+                from,
+                "{:?} at the start",
+                effect
+            );
+            assert_eq!(
+                render_frame(&from, &to, effect, 1.0),
+                to,
+                "{:?} at the end",
+                effect
+            );
+        }
+    }
+
+    #[test]
+    fn test_frame_effects() {
+        let (from, to) = (solid(0), solid(200));
+        assert_eq!(
+            render_frame(&from, &to, TransitionEffect::Crossfade, 0.5).get_pixel(10, 10)[0],
+            100
+        );
+
+        // Halfway, the new image fills the right half of a slide and the left half of a wipe
+        let slide = render_frame(&from, &to, TransitionEffect::Slide, 0.5);
+        assert_eq!(
+            (slide.get_pixel(10, 10)[0], slide.get_pixel(50, 10)[0]),
+            (0, 200)
+        );
+        let wipe = render_frame(&from, &to, TransitionEffect::Wipe, 0.5);
+        assert_eq!(
+            (wipe.get_pixel(10, 10)[0], wipe.get_pixel(50, 10)[0]),
+            (200, 0)
+        );
+    }
+
+    #[test]
+    fn test_frame_count() {
+        let transition = |duration_ms| TransitionConfig {
+            effect: TransitionEffect::Crossfade,
+            duration_ms,
+        };
+        assert_eq!(frame_count(&transition(1000)), FRAME_RATE);
+        assert_eq!(frame_count(&transition(0)), 2);
+        assert_eq!(frame_count(&transition(60_000)), MAX_FRAMES);
+    }
+
+    #[test]
+    fn test_sequence() {
+        let done = Arc::new(AtomicBool::new(false));
+        let frames = vec![solid(0), solid(100), solid(200)];
+        let mut sequence = Sequence {
+            frames,
+            interval: Duration::from_millis(100),
+            shown: None,
+            done: done.clone(),
+        };
+        assert_eq!(sequence.frame(Duration::ZERO), Some(solid(0)));
+        assert_eq!(sequence.frame(Duration::from_millis(50)), None);
+        assert!(!done.load(Ordering::SeqCst));
+
+        // Late frames are skipped and the last one is held
+        assert_eq!(sequence.frame(Duration::from_millis(450)), Some(solid(200)));
+        assert!(done.load(Ordering::SeqCst));
+        assert_eq!(sequence.frame(Duration::from_secs(2)), None);
+    }
+
+    #[test]
+    fn test_swww_args() {
+        let args = swww_args(&TransitionConfig {
+            effect: TransitionEffect::Wipe,
+            duration_ms: 1500,
+        });
+        assert_eq!(
+            args[..4],
+            ["--transition-type", "wipe", "--transition-duration", "1.50"]
+        );
+    }
+}
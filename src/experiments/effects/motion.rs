@@ -0,0 +1,245 @@
+//! Motion given to still images by animated static wallpapers
+//!
+//! Parallax shifts the image against the mouse: a slightly enlarged copy is
+//! moved by the cursor's offset from the middle of the desktop. With a depth
+//! map, brighter (nearer) parts move further than darker (distant) ones. The
+//! depth map is a grayscale `<name>.depth.png` next to the image, such as one
+//! made by a depth estimation model, or is estimated from the image itself.
+//!
+//! Ken Burns shows a part of the image that slowly moves and grows or
+//! shrinks, easing in and out of each move.
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, GrayImage, Luma, RgbaImage};
+use log::debug;
+use std::path::{Path, PathBuf};
+
+/// Longest edge of the copy depth is estimated on
+const DEPTH_SAMPLE: u32 = 512;
+
+/// Blur applied to the estimated depth so neighbouring pixels move together
+const DEPTH_BLUR: f32 = 12.0;
+
+/// Share of the estimated depth taken from the height in the image
+///
+/// Lower parts of a landscape are usually nearer; the rest comes from
+/// brightness, as subjects tend to be lit and lighter than backgrounds.
+const DEPTH_FROM_HEIGHT: f32 = 0.6;
+
+/// Get the path of the depth map kept next to an image
+pub fn depth_map_path(image: &Path) -> PathBuf {
+    let stem = image
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    image.with_file_name(format!("{}.depth.png", stem))
+}
+
+/// Load the depth map next to an image, or estimate one, at the image's size
+pub fn depth_map(image_path: &Path, image: &DynamicImage) -> GrayImage {
+    let path = depth_map_path(image_path);
+    match image::open(&path) {
+        Ok(depth) => imageops::resize(
+            &depth.to_luma8(),
+            image.width(),
+            image.height(),
+            FilterType::Triangle,
+        ),
+        Err(_) => {
+            debug!("No depth map at {}; estimating one", path.display());
+            let estimated = estimate_depth(&image.thumbnail(DEPTH_SAMPLE, DEPTH_SAMPLE));
+            imageops::resize(
+                &estimated,
+                image.width(),
+                image.height(),
+                FilterType::Triangle,
+            )
+        }
+    }
+}
+
+/// Guess how near each part of an image is, white nearest
+pub fn estimate_depth(image: &DynamicImage) -> GrayImage {
+    let brightness = imageops::blur(&image.to_luma8(), DEPTH_BLUR);
+    let height = brightness.height().max(2) - 1;
+    GrayImage::from_fn(brightness.width(), brightness.height(), |x, y| {
+        let lower = y as f32 / height as f32;
+        let value = DEPTH_FROM_HEIGHT * lower
+            + (1.0 - DEPTH_FROM_HEIGHT) * brightness.get_pixel(x, y)[0] as f32 / 255.0;
+        Luma([(value * 255.0).round() as u8])
+    })
+}
+
+/// Where the cursor is on the desktop, from -1 (left or top edge) to 1
+pub fn cursor_offset(cursor: (i32, i32), desktop: (i32, i32, u32, u32)) -> (f32, f32) {
+    let (x, y, width, height) = desktop;
+    let axis = |position: i32, start: i32, size: u32| {
+        let half = size.max(2) as f32 / 2.0;
+        ((position - start) as f32 / half - 1.0).clamp(-1.0, 1.0)
+    };
+    (axis(cursor.0, x, width), axis(cursor.1, y, height))
+}
+
+/// Draw the parallax frame for a cursor offset (-1 to 1 on each axis)
+///
+/// The image is enlarged just enough that moving it by `strength` of its
+/// width never shows an edge. Without a depth map every part moves alike.
+pub fn parallax(
+    image: &RgbaImage,
+    depth: Option<&GrayImage>,
+    offset: (f32, f32),
+    strength: f32,
+) -> RgbaImage {
+    let (width, height) = image.dimensions();
+    let strength = strength.clamp(0.0, 0.25);
+    let zoom = 1.0 + 2.0 * strength;
+    let shift = strength * width as f32;
+    let (center_x, center_y) = (width as f32 / 2.0, height as f32 / 2.0);
+
+    RgbaImage::from_fn(width, height, |x, y| {
+        let near = depth.map_or(1.0, |depth| {
+            depth.get_pixel(x.min(depth.width() - 1), y.min(depth.height() - 1))[0] as f32 / 255.0
+        });
+        // The image moves against the cursor, so the source moves with it
+        let source_x = center_x + (x as f32 - center_x) / zoom + offset.0 * shift * near / zoom;
+        let source_y = center_y + (y as f32 - center_y) / zoom + offset.1 * shift * near / zoom;
+        *image.get_pixel(
+            (source_x.round().max(0.0) as u32).min(width - 1),
+            (source_y.round().max(0.0) as u32).min(height - 1),
+        )
+    })
+}
+
+/// Part of an image shown by a Ken Burns move
+///
+/// `x` and `y` are where its middle is and `size` how much of the image's
+/// width and height it shows, all as fractions of the image.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct View {
+    pub x: f32,
+    pub y: f32,
+    pub size: f32,
+}
+
+impl View {
+    /// The whole image
+    pub const FULL: View = View {
+        x: 0.5,
+        y: 0.5,
+        size: 1.0,
+    };
+
+    /// Create a view of `1 / zoom` of the image, `spot` (0 to 1 on each axis)
+    /// of the way from its top left to its bottom right
+    pub fn zoomed(zoom: f32, spot: (f32, f32)) -> Self {
+        let size = 1.0 / zoom.max(1.0);
+        let room = 1.0 - size;
+        View {
+            x: size / 2.0 + spot.0.clamp(0.0, 1.0) * room,
+            y: size / 2.0 + spot.1.clamp(0.0, 1.0) * room,
+            size,
+        }
+    }
+
+    /// Get the view `progress` (0 to 1) of the way to `to`, easing in and out
+    pub fn towards(self, to: View, progress: f32) -> View {
+        let t = progress.clamp(0.0, 1.0);
+        let t = t * t * (3.0 - 2.0 * t);
+        View {
+            x: self.x + (to.x - self.x) * t,
+            y: self.y + (to.y - self.y) * t,
+            size: self.size + (to.size - self.size) * t,
+        }
+    }
+
+    /// Get the view's rectangle (x, y, width, height) in an image
+    pub fn rect(self, width: u32, height: u32) -> (u32, u32, u32, u32) {
+        let axis = |middle: f32, length: u32| {
+            let part = ((length as f32 * self.size).round() as u32).clamp(1, length.max(1));
+            let start = ((middle - self.size / 2.0) * length as f32)
+                .round()
+                .max(0.0) as u32;
+            (start.min(length.saturating_sub(part)), part)
+        };
+        let (x, view_width) = axis(self.x, width);
+        let (y, view_height) = axis(self.y, height);
+        (x, y, view_width, view_height)
+    }
+}
+
+/// Draw the Ken Burns frame showing `view` of the image at `width` x `height`
+pub fn ken_burns(image: &RgbaImage, view: View, width: u32, height: u32) -> RgbaImage {
+    let (x, y, view_width, view_height) = view.rect(image.width(), image.height());
+    let part = imageops::crop_imm(image, x, y, view_width, view_height).to_image();
+    imageops::resize(&part, width, height, FilterType::Triangle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    /// Image whose red channel is the column and green channel the row
+    fn gradient() -> RgbaImage {
+        RgbaImage::from_fn(200, 100, |x, y| Rgba([x as u8, y as u8, 0, 255]))
+    }
+
+    #[test]
+    fn test_parallax_follows_the_cursor() {
+        let image = gradient();
+        let still = parallax(&image, None, (0.0, 0.0), 0.05);
+        let right = parallax(&image, None, (1.0, 0.0), 0.05);
+        // Enlarged around the middle, then shifted towards the cursor's side
+        assert_eq!(still.get_pixel(100, 50)[0], 100);
+        assert!(right.get_pixel(100, 50)[0] > still.get_pixel(100, 50)[0]);
+        assert_eq!(right.get_pixel(100, 50)[1], still.get_pixel(100, 50)[1]);
+
+        // Distant parts stay put
+        let flat = GrayImage::from_pixel(200, 100, Luma([0]));
+        assert_eq!(parallax(&image, Some(&flat), (1.0, 1.0), 0.05), still);
+    }
+
+    #[test]
+    fn test_ken_burns_views() {
+        assert_eq!(View::FULL.rect(200, 100), (0, 0, 200, 100));
+
+        // Zoomed views stay inside the image
+        let corner = View::zoomed(2.0, (1.0, 1.0));
+        assert_eq!(corner.rect(200, 100), (100, 50, 100, 50));
+        assert_eq!(
+            View::zoomed(2.0, (0.0, 0.0)).rect(200, 100),
+            (0, 0, 100, 50)
+        );
+
+        // Moves ease in and out between their ends
+        assert_eq!(View::FULL.towards(corner, 0.0), View::FULL);
+        assert_eq!(View::FULL.towards(corner, 1.0), corner);
+        let early = View::FULL.towards(corner, 0.1);
+        let middle = View::FULL.towards(corner, 0.5);
+        assert!(1.0 - early.size < 0.1 * (1.0 - corner.size));
+        assert!((middle.size - 0.75).abs() < 1e-6);
+
+        let frame = ken_burns(&gradient(), corner, 40, 20);
+        assert_eq!(frame.dimensions(), (40, 20));
+        assert!(frame.get_pixel(0, 0)[0] >= 100);
+    }
+
+    #[test]
+    fn test_cursor_offset() {
+        let desktop = (0, 0, 1920, 1080);
+        assert_eq!(cursor_offset((960, 540), desktop), (0.0, 0.0));
+        assert_eq!(cursor_offset((0, 1080), desktop), (-1.0, 1.0));
+        assert_eq!(cursor_offset((5000, -20), desktop), (1.0, -1.0));
+    }
+
+    #[test]
+    fn test_estimated_depth_is_nearer_below() {
+        let image =
+            DynamicImage::ImageRgba8(RgbaImage::from_pixel(64, 64, Rgba([90, 90, 90, 255])));
+        let depth = estimate_depth(&image);
+        assert!(depth.get_pixel(32, 60)[0] > depth.get_pixel(32, 4)[0]);
+        assert_eq!(
+            depth_map_path(Path::new("/walls/lake.jpg")),
+            Path::new("/walls/lake.depth.png")
+        );
+    }
+}
@@ -0,0 +1,160 @@
+//! Time-of-day color temperature of static wallpapers
+//!
+//! Like a night light, images are tinted warmer in the evening and back to
+//! neutral during the day, following a curve of temperatures set for times
+//! of day. The tint is applied whenever static wallpapers are rendered, and
+//! the scheduler redraws them as the temperature changes.
+use crate::core::config::{ColorTemperatureConfig, TemperaturePoint};
+use chrono::{Local, NaiveTime, Timelike};
+use image::{Rgba, RgbaImage};
+use std::sync::Mutex;
+
+/// Temperature that leaves images as they are, in kelvin
+pub const NEUTRAL: u32 = 6500;
+
+/// Seconds in a day
+const DAY: i64 = 24 * 60 * 60;
+
+/// Tint settings of the profile in use
+static CONFIG: Mutex<Option<ColorTemperatureConfig>> = Mutex::new(None);
+
+/// Set the tint settings
+pub fn set_config(config: ColorTemperatureConfig) {
+    *CONFIG.lock().unwrap() = Some(config);
+}
+
+/// Get the temperature static wallpapers are tinted to now
+///
+/// Returns None while the tint is off or leaves images as they are.
+pub fn kelvin_now() -> Option<u32> {
+    let config = CONFIG.lock().unwrap().clone()?;
+    let kelvin = kelvin_at(&config.curve, Local::now().time());
+    (config.enabled && kelvin != NEUTRAL).then_some(kelvin)
+}
+
+/// Get the temperature of the curve at a time of day
+///
+/// Temperatures are blended linearly between the points, wrapping around
+/// midnight; an empty curve is neutral.
+pub fn kelvin_at(curve: &[TemperaturePoint], time: NaiveTime) -> u32 {
+    let mut points: Vec<(i64, u32)> = curve
+        .iter()
+        .map(|point| (point.time.num_seconds_from_midnight() as i64, point.kelvin))
+        .collect();
+    points.sort_by_key(|(seconds, _)| *seconds);
+    let now = time.num_seconds_from_midnight() as i64;
+
+    let Some(&(first, first_kelvin)) = points.first() else {
+        return NEUTRAL;
+    };
+    let &(last, last_kelvin) = points.last().unwrap();
+    let (from, to) = match points.iter().position(|(seconds, _)| *seconds > now) {
+        Some(0) => ((last - DAY, last_kelvin), (first, first_kelvin)),
+        Some(index) => (points[index - 1], points[index]),
+        None => ((last, last_kelvin), (first + DAY, first_kelvin)),
+    };
+    if to.0 == from.0 {
+        return to.1;
+    }
+    let progress = (now - from.0) as f32 / (to.0 - from.0) as f32;
+    (from.1 as f32 + (to.1 as f32 - from.1 as f32) * progress).round() as u32
+}
+
+/// Get the color of a light of this temperature, 0.0 - 255.0 per channel
+///
+/// Tanner Helland's fit of the black body colors.
+fn light_color(kelvin: u32) -> [f32; 3] {
+    let t = kelvin.clamp(1000, 40000) as f32 / 100.0;
+    let red = if t <= 66.0 {
+        255.0
+    } else {
+        329.69873 * (t - 60.0).powf(-0.13320476)
+    };
+    let green = if t <= 66.0 {
+        99.4708 * t.ln() - 161.11957
+    } else {
+        288.12217 * (t - 60.0).powf(-0.075514849)
+    };
+    let blue = if t >= 66.0 {
+        255.0
+    } else if t <= 19.0 {
+        0.0
+    } else {
+        138.51773 * (t - 10.0).ln() - 305.04479
+    };
+    [
+        red.clamp(0.0, 255.0),
+        green.clamp(0.0, 255.0),
+        blue.clamp(0.0, 255.0),
+    ]
+}
+
+/// Get what each channel is multiplied by to tint to a temperature
+pub fn multipliers(kelvin: u32) -> [f32; 3] {
+    let (light, neutral) = (light_color(kelvin), light_color(NEUTRAL));
+    [
+        light[0] / neutral[0],
+        light[1] / neutral[1],
+        light[2] / neutral[2],
+    ]
+}
+
+/// Tint an image to a color temperature
+pub fn tint(image: &mut RgbaImage, kelvin: u32) {
+    let factors = multipliers(kelvin);
+    for pixel in image.pixels_mut() {
+        let Rgba([r, g, b, a]) = *pixel;
+        let scale =
+            |channel: u8, factor: f32| (channel as f32 * factor).round().clamp(0.0, 255.0) as u8;
+        *pixel = Rgba([
+            scale(r, factors[0]),
+            scale(g, factors[1]),
+            scale(b, factors[2]),
+            a,
+        ]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_kelvin_at_follows_the_curve() {
+        let curve = ColorTemperatureConfig::default().curve;
+        assert_eq!(kelvin_at(&curve, at(12, 0)), 6500);
+        assert_eq!(kelvin_at(&curve, at(7, 0)), 4950);
+        assert_eq!(kelvin_at(&curve, at(19, 30)), 4950);
+        assert_eq!(kelvin_at(&curve, at(2, 0)), 3400);
+        assert_eq!(kelvin_at(&[], at(2, 0)), NEUTRAL);
+
+        // Blended across midnight
+        let night = [
+            TemperaturePoint {
+                time: at(22, 0),
+                kelvin: 4000,
+            },
+            TemperaturePoint {
+                time: at(2, 0),
+                kelvin: 3000,
+            },
+        ];
+        assert_eq!(kelvin_at(&night, at(0, 0)), 3500);
+        assert_eq!(kelvin_at(&night, at(12, 0)), 3500);
+    }
+
+    #[test]
+    fn test_tint_warms_the_image() {
+        assert_eq!(multipliers(NEUTRAL), [1.0, 1.0, 1.0]);
+
+        let mut image = RgbaImage::from_pixel(2, 2, Rgba([200, 200, 200, 255]));
+        tint(&mut image, 3400);
+        let Rgba([r, g, b, a]) = *image.get_pixel(0, 0);
+        assert!(r >= g && g > b);
+        assert_eq!(a, 255);
+    }
+}
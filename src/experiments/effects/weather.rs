@@ -0,0 +1,301 @@
+//! Weather drawn over static wallpapers
+//!
+//! Rain falls as slanted streaks, snow as flakes swaying on their way down
+//! and fog as a pale haze whose thickness drifts slowly across the image.
+//! Particles move in pixels per second of a 1080 pixel high frame, so the
+//! motion looks the same at any frame rate and size.
+use crate::core::config::{PlacementConfig, WeatherEffect};
+use crate::core::AppResult;
+use crate::wallpapers::frame_player::FrameSource;
+use crate::wallpapers::random::Rng;
+use image::{Rgba, RgbaImage};
+use std::path::Path;
+use std::time::Duration;
+
+/// Raindrops on screen per megapixel at full intensity
+const RAIN_PER_MEGAPIXEL: f32 = 400.0;
+
+/// Snowflakes on screen per megapixel at full intensity
+const SNOW_PER_MEGAPIXEL: f32 = 250.0;
+
+/// Color of the rain streaks
+const RAIN_COLOR: [f32; 3] = [205.0, 215.0, 230.0];
+
+/// Color of the snowflakes
+const SNOW_COLOR: [f32; 3] = [250.0, 250.0, 255.0];
+
+/// Color of the fog
+const FOG_COLOR: [f32; 3] = [215.0, 220.0, 225.0];
+
+/// Cells of the fog's thickness across the image
+const FOG_CELLS: usize = 6;
+
+/// Rain moving sideways for each pixel it falls
+const RAIN_SLANT: f32 = 0.15;
+
+/// Raindrop or snowflake
+#[derive(Debug, Clone, Copy)]
+struct Particle {
+    x: f32,
+    y: f32,
+
+    /// Falling speed in pixels per second
+    speed: f32,
+
+    /// Streak length of rain, radius of snow
+    size: f32,
+
+    /// Where snow is in its sway
+    phase: f32,
+}
+
+/// Weather drawn over an image
+pub struct WeatherOverlay {
+    /// Image the weather is drawn over
+    base: RgbaImage,
+
+    /// Weather shown
+    effect: WeatherEffect,
+
+    /// How dense or thick, from 0.0 to 1.0
+    intensity: f32,
+
+    /// Raindrops or snowflakes
+    particles: Vec<Particle>,
+
+    /// Fog thickness at the corners of its cells, row by row
+    fog: Vec<f32>,
+
+    /// Randomness of new particles
+    rng: Rng,
+
+    /// Time of the last frame
+    last: Option<Duration>,
+}
+
+impl WeatherOverlay {
+    /// Create weather over an image, with particles spread over it
+    pub fn new(base: RgbaImage, effect: WeatherEffect, intensity: f32) -> Self {
+        let mut rng = Rng::new();
+        let intensity = intensity.clamp(0.0, 1.0);
+        let megapixels = base.width() as f32 * base.height() as f32 / 1_000_000.0;
+        let count = match effect {
+            WeatherEffect::Rain => RAIN_PER_MEGAPIXEL * megapixels * intensity,
+            WeatherEffect::Snow => SNOW_PER_MEGAPIXEL * megapixels * intensity,
+            WeatherEffect::Fog => 0.0,
+        } as usize;
+        let mut overlay = Self {
+            fog: (0..(FOG_CELLS + 1) * (FOG_CELLS + 1))
+                .map(|_| rng.fraction())
+                .collect(),
+            base,
+            effect,
+            intensity,
+            particles: Vec::with_capacity(count),
+            rng,
+            last: None,
+        };
+        for _ in 0..count {
+            let mut particle = overlay.spawn();
+            particle.y = overlay.rng.between(0.0, overlay.base.height() as f32);
+            overlay.particles.push(particle);
+        }
+        overlay
+    }
+
+    /// Create a particle above the top edge
+    fn spawn(&mut self) -> Particle {
+        let scale = self.base.height() as f32 / 1080.0;
+        let (speed, size) = match self.effect {
+            WeatherEffect::Rain => (
+                self.rng.between(900.0, 1400.0),
+                self.rng.between(18.0, 34.0),
+            ),
+            _ => (self.rng.between(40.0, 110.0), self.rng.between(1.0, 3.5)),
+        };
+        // Rain drifts sideways, so it may enter from beyond the left edge
+        let left = match self.effect {
+            WeatherEffect::Rain => -(self.base.height() as f32) * RAIN_SLANT,
+            _ => 0.0,
+        };
+        Particle {
+            x: self.rng.between(left, self.base.width() as f32),
+            y: -size * scale,
+            speed: speed * scale,
+            size: size * scale,
+            phase: self.rng.between(0.0, std::f32::consts::TAU),
+        }
+    }
+
+    /// Move the particles on by `seconds`
+    fn step(&mut self, seconds: f32, time: f32) {
+        let height = self.base.height() as f32;
+        let scale = height / 1080.0;
+        for index in 0..self.particles.len() {
+            let mut particle = self.particles[index];
+            let fall = particle.speed * seconds;
+            particle.y += fall;
+            particle.x += match self.effect {
+                WeatherEffect::Rain => fall * RAIN_SLANT,
+                _ => (time * 0.8 + particle.phase).sin() * 20.0 * scale * seconds,
+            };
+            self.particles[index] = if particle.y - particle.size > height {
+                self.spawn()
+            } else {
+                particle
+            };
+        }
+    }
+
+    /// Draw the weather over the image, `time` seconds in
+    pub fn draw(&self, time: f32) -> RgbaImage {
+        let mut frame = self.base.clone();
+        let (width, height) = (frame.width() as i32, frame.height() as i32);
+        let mut blend = |x: i32, y: i32, color: [f32; 3], alpha: f32| {
+            if x >= 0 && y >= 0 && x < width && y < height {
+                let pixel = frame.get_pixel_mut(x as u32, y as u32);
+                for channel in 0..3 {
+                    pixel[channel] = (pixel[channel] as f32 * (1.0 - alpha)
+                        + color[channel] * alpha)
+                        .round() as u8;
+                }
+            }
+        };
+
+        match self.effect {
+            WeatherEffect::Rain => {
+                for particle in &self.particles {
+                    // Brightest at the head, fading towards the tail
+                    let length = particle.size.max(1.0) as i32;
+                    for step in 0..length {
+                        let alpha = 0.4 * (1.0 - step as f32 / length as f32);
+                        let x = particle.x - step as f32 * RAIN_SLANT;
+                        blend(
+                            x.round() as i32,
+                            (particle.y - step as f32).round() as i32,
+                            RAIN_COLOR,
+                            alpha,
+                        );
+                    }
+                }
+            }
+            WeatherEffect::Snow => {
+                for particle in &self.particles {
+                    let radius = particle.size.ceil() as i32;
+                    for dy in -radius..=radius {
+                        for dx in -radius..=radius {
+                            let distance = ((dx * dx + dy * dy) as f32).sqrt();
+                            if distance <= particle.size {
+                                let alpha = 0.9 * (1.0 - distance / (particle.size + 1.0));
+                                blend(
+                                    particle.x as i32 + dx,
+                                    particle.y as i32 + dy,
+                                    SNOW_COLOR,
+                                    alpha,
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+            WeatherEffect::Fog => {
+                let drift = time * 0.01 * FOG_CELLS as f32;
+                for y in 0..height {
+                    for x in 0..width {
+                        let thickness = self.fog_at(
+                            x as f32 / width as f32 * FOG_CELLS as f32 + drift,
+                            y as f32 / height as f32 * FOG_CELLS as f32,
+                        );
+                        blend(
+                            x,
+                            y,
+                            FOG_COLOR,
+                            self.intensity * 0.55 * (0.5 + 0.5 * thickness),
+                        );
+                    }
+                }
+            }
+        }
+        frame
+    }
+
+    /// Get the fog thickness at a point in cells, wrapping around horizontally
+    fn fog_at(&self, u: f32, v: f32) -> f32 {
+        let corner = |column: usize, row: usize| {
+            self.fog[row.min(FOG_CELLS) * (FOG_CELLS + 1) + column % FOG_CELLS]
+        };
+        let u = u.rem_euclid(FOG_CELLS as f32);
+        let (column, row) = (u.floor() as usize, v.floor().max(0.0) as usize);
+        let (fx, fy) = (u.fract(), v.fract());
+        let (fx, fy) = (fx * fx * (3.0 - 2.0 * fx), fy * fy * (3.0 - 2.0 * fy));
+        let top = corner(column, row) * (1.0 - fx) + corner(column + 1, row) * fx;
+        let bottom = corner(column, row + 1) * (1.0 - fx) + corner(column + 1, row + 1) * fx;
+        top * (1.0 - fy) + bottom * fy
+    }
+}
+
+/// Draw the weather over a static wallpaper, rendered for the primary monitor with its placement
+pub fn over_wallpaper(
+    path: &Path,
+    placement: &PlacementConfig,
+    effect: WeatherEffect,
+    intensity: f32,
+) -> AppResult<WeatherOverlay> {
+    Ok(WeatherOverlay::new(
+        super::render_for_primary(path, placement)?,
+        effect,
+        intensity,
+    ))
+}
+
+impl FrameSource for WeatherOverlay {
+    fn frame(&mut self, elapsed: Duration) -> Option<RgbaImage> {
+        let seconds = self
+            .last
+            .map_or(0.0, |last| elapsed.saturating_sub(last).as_secs_f32());
+        self.last = Some(elapsed);
+        self.step(seconds, elapsed.as_secs_f32());
+        Some(self.draw(elapsed.as_secs_f32()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dark() -> RgbaImage {
+        RgbaImage::from_pixel(400, 300, Rgba([20, 20, 30, 255]))
+    }
+
+    #[test]
+    fn test_particles_follow_the_intensity() {
+        let light = WeatherOverlay::new(dark(), WeatherEffect::Rain, 0.25);
+        let heavy = WeatherOverlay::new(dark(), WeatherEffect::Rain, 1.0);
+        assert!(heavy.particles.len() > light.particles.len() && !light.particles.is_empty());
+        assert!(WeatherOverlay::new(dark(), WeatherEffect::Fog, 1.0)
+            .particles
+            .is_empty());
+    }
+
+    #[test]
+    fn test_snow_falls() {
+        let mut snow = WeatherOverlay::new(dark(), WeatherEffect::Snow, 1.0);
+        let before: f32 = snow.particles.iter().map(|particle| particle.y).sum();
+        snow.step(0.1, 0.0);
+        let after: f32 = snow.particles.iter().map(|particle| particle.y).sum();
+        assert!(after > before);
+
+        // Flakes lighten the image where they are
+        let frame = snow.frame(Duration::from_millis(200)).unwrap();
+        assert!(frame.pixels().any(|pixel| pixel[0] > 20));
+        assert!(frame.pixels().all(|pixel| pixel[3] == 255));
+    }
+
+    #[test]
+    fn test_fog_covers_the_image() {
+        let fog = WeatherOverlay::new(dark(), WeatherEffect::Fog, 1.0);
+        let frame = fog.draw(0.0);
+        assert!(frame.pixels().all(|pixel| pixel[0] > 60));
+        assert!((fog.fog_at(0.0, 0.0) - fog.fog_at(FOG_CELLS as f32, 0.0)).abs() < 1e-6);
+    }
+}
@@ -1 +1,2 @@
- 
\ No newline at end of file
+pub mod ai;
+pub mod effects;
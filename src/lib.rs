@@ -1,6 +1,9 @@
 pub mod core;
+pub mod daemon;
+pub mod experiments;
 pub mod platform;
-pub mod wallpapers;
+pub mod sources;
 pub mod ui;
+pub mod wallpapers;
 
-pub use core::*;
\ No newline at end of file
+pub use core::*;
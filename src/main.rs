@@ -1,30 +1,122 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod cli;
 mod core;
+mod daemon;
+mod experiments;
 mod platform;
-mod wallpapers;
+mod sources;
 mod ui;
+mod wallpapers;
 
 use anyhow::Result;
-use log::{error, info};
-use ui::AetherDeskApp;
+use clap::Parser;
+use cli::Cli;
+use core::logging::{self, LogFile};
 use eframe::egui;
-use core::ResourceManager;
-
+use log::{error, info, warn};
+use ui::AetherDeskApp;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Initialize logger
-    env_logger::init();
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(e) => {
+            // Help, version and usage errors go to the terminal like a subcommand's output
+            #[cfg(windows)]
+            platform::windows::attach_parent_console();
+            e.exit();
+        }
+    };
+
+    // Subcommands print their results, which a release build has no console for
+    #[cfg(windows)]
+    if cli.command.is_some() {
+        platform::windows::attach_parent_console();
+    }
+
+    // Work in physical pixels on scaled Windows monitors
+    #[cfg(windows)]
+    platform::windows::enable_dpi_awareness();
+
+    // Initialize logger, showing the startup timings when asked for
+    let log_file = if cli.command.is_some() {
+        None
+    } else if cli.headless || cli.daemon {
+        Some(LogFile::Daemon)
+    } else {
+        Some(LogFile::App)
+    };
+    let saved = core::Config::load().ok();
+    let log_level = saved
+        .as_ref()
+        .map(|config| config.app.log_level)
+        .unwrap_or_default();
+    core::config_history::set_enabled(saved.map_or(false, |config| config.app.config_history));
+    let mut log_targets = Vec::new();
+    if cli.profile_startup {
+        log_targets.push(ui::startup::LOG_TARGET);
+        ui::startup::enable_profiling();
+    }
+    logging::init(log_file, log_level, &log_targets);
+    core::crash::install();
+
+    // Run a subcommand against the daemon without opening the window
+    if let Some(command) = cli.command {
+        if let Err(e) = cli::run(command) {
+            eprintln!("Error: {}", e);
+            if let Some(hint) = e.hint() {
+                eprintln!("Hint: {}", hint);
+            }
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     info!("Starting Aether-Desk");
 
-    // Create resource manager
-    let resource_manager = ResourceManager::default();
+    // Run in the foreground without a window, e.g. on bare Wayland compositors
+    if cli.headless {
+        let wallpaper_manager = platform::create_wallpaper_manager()?;
+        info!("Running headless");
+        daemon::Daemon::new(wallpaper_manager)
+            .with_widgets()
+            .run()?;
+        info!("Aether-Desk stopped");
+        return Ok(());
+    }
+
+    // Run as the background daemon when requested
+    if cli.daemon {
+        let wallpaper_manager = platform::create_wallpaper_manager()?;
+        daemon::Daemon::new(wallpaper_manager).run()?;
+        info!("Aether-Desk daemon stopped");
+        return Ok(());
+    }
+
+    // Only one settings window: hand this launch over to a running one
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if ui::instance::forward(&args).is_ok() {
+        info!("Aether-Desk is already open");
+        return Ok(());
+    }
+    let instance = match ui::instance::InstanceGuard::acquire() {
+        Ok(instance) => Some(instance),
+        Err(e) => {
+            warn!("Failed to claim the settings window: {}", e);
+            None
+        }
+    };
 
     // Create wallpaper manager
-    let wallpaper_manager = platform::create_wallpaper_manager()?;
+    let wallpaper_manager =
+        ui::startup::phase("Wallpaper manager", platform::create_wallpaper_manager)?;
 
     // Create application UI
-    let app = AetherDeskApp::new(wallpaper_manager, resource_manager);
+    let mut app = ui::startup::phase("Settings window", || AetherDeskApp::new(wallpaper_manager));
+    app.set_start_minimized(cli.minimized);
+    if let Some(instance) = instance {
+        app.set_instance(instance);
+    }
 
     // Run application
     let options = eframe::NativeOptions {
@@ -35,7 +127,15 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     if let Err(e) = eframe::run_native(
         "Aether-Desk",
         options,
-        Box::new(|_cc| Box::new(app))
+        Box::new(|cc| {
+            // Close the window, running its exit path, on Ctrl-C or logout
+            let ctx = cc.egui_ctx.clone();
+            platform::signals::on_termination(move || {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                ctx.request_repaint();
+            });
+            Box::new(app)
+        }),
     ) {
         error!("Failed to run application: {}", e);
         return Err(e.into());
@@ -0,0 +1,417 @@
+//! Desktop accent color
+//!
+//! Windows keeps the accent in the registry (`DWM\AccentColor`,
+//! `DWM\ColorizationColor` and `Explorer\Accent\AccentColorMenu`). KDE Plasma
+//! takes any color through `plasma-apply-colorscheme --accent-color`, while
+//! GNOME 47 and later only offer a few named accents, so the closest one is
+//! picked.
+//!
+//! The accent the desktop had before the first change is saved to
+//! `accent.json` in the configuration folder, so it can be put back even
+//! after a restart.
+use crate::core::palette::{self, Rgb};
+use crate::core::{AppError, AppResult, Config};
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+use std::process::Command;
+
+/// Registry values holding the Windows accent: key, value name and whether it's ABGR
+#[cfg(target_os = "windows")]
+const WINDOWS_VALUES: [(&str, &str, bool); 3] = [
+    (r"HKCU\Software\Microsoft\Windows\DWM", "AccentColor", true),
+    (
+        r"HKCU\Software\Microsoft\Windows\DWM",
+        "ColorizationColor",
+        false,
+    ),
+    (
+        r"HKCU\Software\Microsoft\Windows\CurrentVersion\Explorer\Accent",
+        "AccentColorMenu",
+        true,
+    ),
+];
+
+/// GNOME's named accents with their colors
+const GNOME_ACCENTS: [(&str, Rgb); 9] = [
+    ("blue", [0x35, 0x84, 0xe4]),
+    ("teal", [0x21, 0x90, 0xa4]),
+    ("green", [0x3a, 0x94, 0x4a]),
+    ("yellow", [0xc8, 0x88, 0x00]),
+    ("orange", [0xed, 0x5b, 0x00]),
+    ("red", [0xe6, 0x2d, 0x42]),
+    ("pink", [0xd5, 0x61, 0x99]),
+    ("purple", [0x91, 0x41, 0xac]),
+    ("slate", [0x6f, 0x83, 0x96]),
+];
+
+/// Least saturation (0-1) of a color given a hued GNOME accent instead of slate
+const GNOME_MIN_SATURATION: f32 = 0.2;
+
+/// Accent the desktop had before following the wallpaper
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SavedAccent {
+    /// Windows registry values by name, None when they were unset
+    Windows(Vec<(String, Option<u32>)>),
+
+    /// GNOME's named accent
+    Gnome(String),
+
+    /// KDE's accent as "r,g,b", None when it came from the color scheme
+    Kde(Option<String>),
+}
+
+/// Set the desktop accent color, saving the one it had first
+pub fn apply(color: Rgb) -> AppResult<()> {
+    let file = saved_file()?;
+    if !file.exists() {
+        let saved = current()?;
+        fs::write(&file, serde_json::to_string(&saved)?)?;
+        debug!("Saved the desktop accent: {:?}", saved);
+    }
+    set(color)?;
+    info!("Set the desktop accent to {}", palette::hex(color));
+    Ok(())
+}
+
+/// Check whether the accent was changed and can be put back
+pub fn is_changed() -> bool {
+    saved_file().map_or(false, |file| file.exists())
+}
+
+/// Put back the accent the desktop had before it followed the wallpaper
+pub fn restore() -> AppResult<()> {
+    let file = saved_file()?;
+    let Ok(json) = fs::read_to_string(&file) else {
+        return Ok(());
+    };
+    let saved: SavedAccent = serde_json::from_str(&json)?;
+    put_back(&saved)?;
+    fs::remove_file(&file)?;
+    info!("Restored the desktop accent");
+    Ok(())
+}
+
+/// File the original accent is saved to
+fn saved_file() -> AppResult<PathBuf> {
+    Config::get_config_dir()
+        .map(|dir| dir.join("accent.json"))
+        .map_err(|e| AppError::ConfigError(e.to_string()))
+}
+
+/// Desktop whose accent is followed
+#[cfg(target_os = "linux")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Desktop {
+    /// GNOME, with named accents
+    Gnome,
+
+    /// KDE Plasma, with any accent color
+    Kde,
+}
+
+/// Find the desktop from `XDG_CURRENT_DESKTOP`
+#[cfg(target_os = "linux")]
+fn desktop() -> AppResult<Desktop> {
+    let desktop = std::env::var("XDG_CURRENT_DESKTOP").unwrap_or_default();
+    if desktop.contains("KDE") {
+        Ok(Desktop::Kde)
+    } else if desktop.contains("GNOME") {
+        Ok(Desktop::Gnome)
+    } else {
+        Err(AppError::BackendUnavailable {
+            backend: if desktop.is_empty() {
+                "This desktop".to_string()
+            } else {
+                desktop
+            },
+            reason: "accent colors can be set on Windows, GNOME and KDE Plasma".to_string(),
+        })
+    }
+}
+
+/// Read the accent the desktop has now
+fn current() -> AppResult<SavedAccent> {
+    #[cfg(target_os = "windows")]
+    {
+        let values = WINDOWS_VALUES
+            .iter()
+            .map(|(key, name, _)| {
+                let output = Command::new("reg")
+                    .args(["query", key, "/v", name])
+                    .output()?;
+                let value = output
+                    .status
+                    .success()
+                    .then(|| parse_reg_dword(&String::from_utf8_lossy(&output.stdout), name))
+                    .flatten();
+                Ok((name.to_string(), value))
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+        Ok(SavedAccent::Windows(values))
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        match desktop()? {
+            Desktop::Gnome => {
+                let output = Command::new("gsettings")
+                    .args(["get", "org.gnome.desktop.interface", "accent-color"])
+                    .output()?;
+                if !output.status.success() {
+                    return Err(AppError::BackendUnavailable {
+                        backend: "GNOME".to_string(),
+                        reason: "accent colors need GNOME 47 or later".to_string(),
+                    });
+                }
+                Ok(SavedAccent::Gnome(
+                    String::from_utf8_lossy(&output.stdout)
+                        .trim()
+                        .trim_matches('\'')
+                        .to_string(),
+                ))
+            }
+            Desktop::Kde => {
+                let output = ["kreadconfig6", "kreadconfig5"]
+                    .iter()
+                    .find_map(|tool| {
+                        Command::new(tool)
+                            .args([
+                                "--file",
+                                "kdeglobals",
+                                "--group",
+                                "General",
+                                "--key",
+                                "AccentColor",
+                            ])
+                            .output()
+                            .ok()
+                            .filter(|output| output.status.success())
+                    })
+                    .ok_or_else(|| AppError::MissingExternalTool {
+                        tool: "kreadconfig6".to_string(),
+                        install_hint: "Install KDE Plasma's kconfig tools".to_string(),
+                    })?;
+                let accent = String::from_utf8_lossy(&output.stdout).trim().to_string();
+                Ok(SavedAccent::Kde(
+                    Some(accent).filter(|accent| !accent.is_empty()),
+                ))
+            }
+        }
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    {
+        Err(AppError::UnsupportedPlatform)
+    }
+}
+
+/// Set the accent to a color
+fn set(color: Rgb) -> AppResult<()> {
+    #[cfg(target_os = "windows")]
+    {
+        for (key, name, abgr) in WINDOWS_VALUES {
+            let value = if abgr { to_abgr(color) } else { to_argb(color) };
+            run(Command::new("reg").args([
+                "add",
+                key,
+                "/v",
+                name,
+                "/t",
+                "REG_DWORD",
+                "/d",
+                &format!("0x{:08x}", value),
+                "/f",
+            ]))?;
+        }
+        Ok(())
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        match desktop()? {
+            Desktop::Gnome => run(Command::new("gsettings").args([
+                "set",
+                "org.gnome.desktop.interface",
+                "accent-color",
+                gnome_accent(color),
+            ])),
+            Desktop::Kde => run(Command::new("plasma-apply-colorscheme")
+                .args(["--accent-color", &palette::hex(color)])),
+        }
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    {
+        let _ = color;
+        Err(AppError::UnsupportedPlatform)
+    }
+}
+
+/// Set the accent back to what was saved
+fn put_back(saved: &SavedAccent) -> AppResult<()> {
+    #[cfg(target_os = "windows")]
+    if let SavedAccent::Windows(values) = saved {
+        for (key, name, _) in WINDOWS_VALUES {
+            match values
+                .iter()
+                .find(|(saved, _)| saved == name)
+                .and_then(|(_, value)| *value)
+            {
+                Some(value) => run(Command::new("reg").args([
+                    "add",
+                    key,
+                    "/v",
+                    name,
+                    "/t",
+                    "REG_DWORD",
+                    "/d",
+                    &format!("0x{:08x}", value),
+                    "/f",
+                ]))?,
+                // A value that's missing already is as good as deleted
+                None => {
+                    let _ = run(Command::new("reg").args(["delete", key, "/v", name, "/f"]));
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    #[cfg(target_os = "linux")]
+    match saved {
+        SavedAccent::Gnome(name) => {
+            return run(Command::new("gsettings").args([
+                "set",
+                "org.gnome.desktop.interface",
+                "accent-color",
+                name,
+            ]))
+        }
+        SavedAccent::Kde(Some(accent)) => {
+            let color = parse_kde_color(accent)
+                .ok_or_else(|| AppError::Other(format!("Unknown KDE accent color: {}", accent)))?;
+            return run(Command::new("plasma-apply-colorscheme")
+                .args(["--accent-color", &palette::hex(color)]));
+        }
+        SavedAccent::Kde(None) => {
+            // The accent comes from the color scheme again once the setting is gone
+            let delete = |tool: &str| {
+                run(Command::new(tool).args([
+                    "--file",
+                    "kdeglobals",
+                    "--group",
+                    "General",
+                    "--key",
+                    "AccentColor",
+                    "--delete",
+                ]))
+            };
+            return delete("kwriteconfig6").or_else(|_| delete("kwriteconfig5"));
+        }
+        SavedAccent::Windows(_) => {}
+    }
+
+    Err(AppError::Other(format!(
+        "The saved accent is for another desktop: {:?}",
+        saved
+    )))
+}
+
+/// Run a command and check it succeeded
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+fn run(command: &mut Command) -> AppResult<()> {
+    let output = command.output()?;
+    if !output.status.success() {
+        return Err(AppError::PlatformError(format!(
+            "{:?} failed: {}",
+            command.get_program(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+/// Pick the GNOME accent closest in hue to a color, slate for grays
+#[allow(dead_code)]
+fn gnome_accent(color: Rgb) -> &'static str {
+    let (hue, saturation, _) = palette::hsv(color);
+    if saturation < GNOME_MIN_SATURATION {
+        return "slate";
+    }
+    let distance = |other: f32| {
+        let difference = (hue - other).abs();
+        difference.min(360.0 - difference)
+    };
+    GNOME_ACCENTS
+        .iter()
+        .filter(|(name, _)| *name != "slate")
+        .min_by(|(_, a), (_, b)| {
+            distance(palette::hsv(*a).0).total_cmp(&distance(palette::hsv(*b).0))
+        })
+        .map_or("blue", |(name, _)| name)
+}
+
+/// Parse KDE's "r,g,b" color
+#[allow(dead_code)]
+fn parse_kde_color(value: &str) -> Option<Rgb> {
+    let channels: Vec<u8> = value
+        .split(',')
+        .map(|channel| channel.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    channels.try_into().ok()
+}
+
+/// Parse a `reg query` DWORD value line, e.g. "    AccentColor    REG_DWORD    0xff9d5a00"
+#[allow(dead_code)]
+fn parse_reg_dword(output: &str, name: &str) -> Option<u32> {
+    let line = output
+        .lines()
+        .find(|line| line.split_whitespace().next() == Some(name))?;
+    let value = line.split_whitespace().last()?;
+    u32::from_str_radix(value.trim_start_matches("0x"), 16).ok()
+}
+
+/// Pack a color as an opaque 0xAABBGGRR DWORD, as `AccentColor` is
+#[allow(dead_code)]
+fn to_abgr(color: Rgb) -> u32 {
+    0xff00_0000 | (color[2] as u32) << 16 | (color[1] as u32) << 8 | color[0] as u32
+}
+
+/// Pack a color as a 0xAARRGGBB DWORD, as `ColorizationColor` is
+#[allow(dead_code)]
+fn to_argb(color: Rgb) -> u32 {
+    0xc400_0000 | (color[0] as u32) << 16 | (color[1] as u32) << 8 | color[2] as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gnome_accent() {
+        assert_eq!(gnome_accent([30, 90, 220]), "blue");
+        assert_eq!(gnome_accent([230, 40, 60]), "red");
+        assert_eq!(gnome_accent([60, 160, 70]), "green");
+        assert_eq!(gnome_accent([240, 110, 20]), "orange");
+        assert_eq!(gnome_accent([120, 122, 125]), "slate");
+    }
+
+    #[test]
+    fn test_registry_values() {
+        assert_eq!(to_abgr([0x12, 0x34, 0x56]), 0xff56_3412);
+        assert_eq!(to_argb([0x12, 0x34, 0x56]), 0xc412_3456);
+        let output = "\r\nHKEY_CURRENT_USER\\Software\\Microsoft\\Windows\\DWM\r\n    AccentColor    REG_DWORD    0xff9d5a00\r\n";
+        assert_eq!(parse_reg_dword(output, "AccentColor"), Some(0xff9d_5a00));
+        assert_eq!(parse_reg_dword(output, "ColorizationColor"), None);
+    }
+
+    #[test]
+    fn test_parse_kde_color() {
+        assert_eq!(parse_kde_color("61,174,233"), Some([61, 174, 233]));
+        assert_eq!(parse_kde_color("61,174"), None);
+        assert_eq!(parse_kde_color(""), None);
+    }
+}
@@ -0,0 +1,237 @@
+//! OS light/dark preference
+//!
+//! Windows reads `AppsUseLightTheme` from the registry; Linux asks the
+//! freedesktop Appearance portal and falls back to GNOME's `color-scheme`.
+use crate::core::{AppError, AppResult};
+use log::{debug, info};
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often the watcher re-reads the OS preference
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Registry key holding the personalization settings
+#[cfg(target_os = "windows")]
+const PERSONALIZE_KEY: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Themes\Personalize";
+
+/// Check whether the OS prefers a dark theme
+///
+/// Returns `None` when the preference can't be determined.
+pub fn prefers_dark() -> Option<bool> {
+    #[cfg(target_os = "windows")]
+    {
+        let output = Command::new("reg")
+            .args(["query", PERSONALIZE_KEY, "/v", "AppsUseLightTheme"])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        parse_reg_light_theme(&String::from_utf8_lossy(&output.stdout)).map(|light| !light)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let portal = Command::new("gdbus")
+            .args([
+                "call",
+                "--session",
+                "--dest",
+                "org.freedesktop.portal.Desktop",
+                "--object-path",
+                "/org/freedesktop/portal/desktop",
+                "--method",
+                "org.freedesktop.portal.Settings.Read",
+                "org.freedesktop.appearance",
+                "color-scheme",
+            ])
+            .output();
+
+        if let Ok(output) = portal {
+            if output.status.success() {
+                if let Some(dark) =
+                    parse_portal_color_scheme(&String::from_utf8_lossy(&output.stdout))
+                {
+                    return Some(dark);
+                }
+            }
+        }
+
+        let output = Command::new("gsettings")
+            .args(["get", "org.gnome.desktop.interface", "color-scheme"])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        parse_gsettings_color_scheme(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    {
+        None
+    }
+}
+
+/// Parse the appearance portal reply, e.g. "(<<uint32 1>>,)"
+///
+/// 1 means prefer dark, 2 prefer light and 0 no preference.
+#[allow(dead_code)]
+fn parse_portal_color_scheme(output: &str) -> Option<bool> {
+    let (_, value) = output.split_once("uint32 ")?;
+    let value: String = value.chars().take_while(|c| c.is_ascii_digit()).collect();
+    match value.parse::<u32>().ok()? {
+        1 => Some(true),
+        2 => Some(false),
+        _ => None,
+    }
+}
+
+/// Parse `gsettings get org.gnome.desktop.interface color-scheme`, e.g. "'prefer-dark'"
+#[allow(dead_code)]
+fn parse_gsettings_color_scheme(output: &str) -> Option<bool> {
+    match output.trim().trim_matches('\'') {
+        "prefer-dark" => Some(true),
+        "prefer-light" | "default" => Some(false),
+        _ => None,
+    }
+}
+
+/// Parse `reg query ... /v AppsUseLightTheme`
+///
+/// The value line looks like "    AppsUseLightTheme    REG_DWORD    0x0".
+#[allow(dead_code)]
+fn parse_reg_light_theme(output: &str) -> Option<bool> {
+    let line = output
+        .lines()
+        .find(|line| line.contains("AppsUseLightTheme"))?;
+    let value = line.split_whitespace().last()?;
+    let value = u32::from_str_radix(value.trim_start_matches("0x"), 16).ok()?;
+    Some(value != 0)
+}
+
+/// Follows the OS light/dark preference in the background
+///
+/// Reading the preference spawns a helper process, so it is kept off the UI thread.
+pub struct AppearanceWatcher {
+    /// Last known preference (None = unknown)
+    prefers_dark: Arc<Mutex<Option<bool>>>,
+
+    /// Watcher thread handle
+    watch_thread: Option<thread::JoinHandle<()>>,
+
+    /// Whether the watcher is running
+    is_running: Arc<Mutex<bool>>,
+}
+
+#[allow(dead_code)]
+impl AppearanceWatcher {
+    /// Create a new appearance watcher
+    pub fn new() -> Self {
+        Self {
+            prefers_dark: Arc::new(Mutex::new(None)),
+            watch_thread: None,
+            is_running: Arc::new(Mutex::new(false)),
+        }
+    }
+
+    /// Get the last known OS preference
+    pub fn prefers_dark(&self) -> Option<bool> {
+        *self.prefers_dark.lock().unwrap()
+    }
+
+    /// Check whether the watcher is running
+    pub fn is_running(&self) -> bool {
+        *self.is_running.lock().unwrap()
+    }
+
+    /// Start the watcher thread
+    pub fn start(&mut self) -> AppResult<()> {
+        if self.is_running() {
+            debug!("Appearance watcher is already running");
+            return Ok(());
+        }
+
+        *self.is_running.lock().unwrap() = true;
+
+        let prefers_dark = self.prefers_dark.clone();
+        let is_running = self.is_running.clone();
+
+        self.watch_thread = Some(thread::spawn(move || {
+            let mut last_poll: Option<Instant> = None;
+
+            while *is_running.lock().unwrap() {
+                if last_poll.map_or(true, |last| last.elapsed() >= POLL_INTERVAL) {
+                    last_poll = Some(Instant::now());
+
+                    let current = self::prefers_dark();
+                    let mut known = prefers_dark.lock().unwrap();
+                    if *known != current {
+                        debug!("OS prefers dark theme: {:?}", current);
+                        *known = current;
+                    }
+                }
+
+                thread::sleep(Duration::from_millis(250));
+            }
+        }));
+
+        info!("Appearance watcher started");
+        Ok(())
+    }
+
+    /// Stop the watcher thread
+    pub fn stop(&mut self) -> AppResult<()> {
+        if !self.is_running() {
+            return Ok(());
+        }
+
+        *self.is_running.lock().unwrap() = false;
+
+        if let Some(thread) = self.watch_thread.take() {
+            thread.join().map_err(|e| {
+                AppError::Other(format!("Failed to join appearance watcher thread: {:?}", e))
+            })?;
+        }
+
+        info!("Appearance watcher stopped");
+        Ok(())
+    }
+}
+
+impl Default for AppearanceWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for AppearanceWatcher {
+    fn drop(&mut self) {
+        let _ = self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_color_scheme() {
+        assert_eq!(parse_portal_color_scheme("(<<uint32 1>>,)\n"), Some(true));
+        assert_eq!(parse_portal_color_scheme("(<<uint32 2>>,)\n"), Some(false));
+        assert_eq!(parse_portal_color_scheme("(<<uint32 0>>,)\n"), None);
+
+        assert_eq!(parse_gsettings_color_scheme("'prefer-dark'\n"), Some(true));
+        assert_eq!(parse_gsettings_color_scheme("'default'\n"), Some(false));
+
+        let reg = "\r\nHKEY_CURRENT_USER\\Software\\Microsoft\\Windows\\CurrentVersion\\Themes\\Personalize\r\n    AppsUseLightTheme    REG_DWORD    0x0\r\n";
+        assert_eq!(parse_reg_light_theme(reg), Some(false));
+        assert_eq!(
+            parse_reg_light_theme(&reg.replace("0x0", "0x1")),
+            Some(true)
+        );
+    }
+}
@@ -0,0 +1,271 @@
+//! System audio capture for audio-reactive effects
+//!
+//! What's playing is recorded from the default output's monitor with `parec`
+//! (PulseAudio, or PipeWire's PulseAudio server) on Linux, and from a capture
+//! device such as "Stereo Mix" with ffmpeg on Windows. Either tool writes
+//! 16-bit mono samples to its standard output; a thread keeps the latest
+//! window of them for the effects to analyse.
+use crate::core::{AppError, AppResult, ProcessHandle};
+use std::f32::consts::PI;
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Samples per second recorded
+pub const SAMPLE_RATE: u32 = 22_050;
+
+/// Samples kept for analysis
+pub const WINDOW: usize = 1024;
+
+/// Samples read from the recording at once, about 12 ms worth
+const CHUNK: usize = 256;
+
+/// Lowest frequency of the spectrum, in Hz
+const LOWEST: f32 = 40.0;
+
+/// Highest frequency of the spectrum, in Hz
+const HIGHEST: f32 = 10_000.0;
+
+/// Latest samples of a recording, from -1.0 to 1.0, oldest first
+#[derive(Debug, Clone)]
+pub struct Samples(Arc<Mutex<Vec<f32>>>);
+
+impl Samples {
+    /// Create a window of silence
+    fn new() -> Self {
+        Self(Arc::new(Mutex::new(vec![0.0; WINDOW])))
+    }
+
+    /// Get the latest window of samples
+    pub fn latest(&self) -> Vec<f32> {
+        self.0.lock().unwrap().clone()
+    }
+
+    /// Add samples, dropping as many of the oldest
+    fn push(&self, new: &[f32]) {
+        let mut samples = self.0.lock().unwrap();
+        let dropped = new.len().min(samples.len());
+        samples.drain(..dropped);
+        samples.extend_from_slice(&new[new.len() - dropped..]);
+    }
+}
+
+/// Running recording of the system's audio
+pub struct AudioCapture {
+    /// Recording tool
+    process: ProcessHandle,
+
+    /// Latest samples
+    samples: Samples,
+}
+
+impl AudioCapture {
+    /// Start recording `device`, or the default output when empty
+    pub fn start(device: &str) -> AppResult<Self> {
+        let (mut command, tool) = record_command(device)?;
+        command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null());
+        let mut process = ProcessHandle::spawn(&mut command, tool, "audio capture")?;
+        let mut output = process
+            .take_stdout()
+            .ok_or_else(|| AppError::PlatformError(format!("No output to read from {}", tool)))?;
+
+        // Ends when the recording is stopped and its output closes
+        let samples = Samples::new();
+        let shared = samples.clone();
+        thread::spawn(move || {
+            let mut chunk = [0u8; CHUNK * 2];
+            while output.read_exact(&mut chunk).is_ok() {
+                let new: Vec<f32> = chunk
+                    .chunks_exact(2)
+                    .map(|bytes| i16::from_le_bytes([bytes[0], bytes[1]]) as f32 / 32768.0)
+                    .collect();
+                shared.push(&new);
+            }
+        });
+
+        Ok(Self { process, samples })
+    }
+
+    /// Get the latest samples, which keep updating while the recording runs
+    pub fn samples(&self) -> Samples {
+        self.samples.clone()
+    }
+
+    /// Check whether the recording tool is still running
+    pub fn is_running(&mut self) -> bool {
+        self.process.exited().is_none()
+    }
+}
+
+/// Build the command recording `device`, with the tool's name
+fn record_command(device: &str) -> AppResult<(Command, &'static str)> {
+    #[cfg(target_os = "linux")]
+    {
+        let device = if device.is_empty() {
+            "@DEFAULT_MONITOR@"
+        } else {
+            device
+        };
+        let mut command = Command::new("parec");
+        command.args([
+            format!("--device={}", device),
+            "--format=s16le".to_string(),
+            format!("--rate={}", SAMPLE_RATE),
+            "--channels=1".to_string(),
+            "--latency-msec=20".to_string(),
+            "--raw".to_string(),
+        ]);
+        Ok((command, "parec"))
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let device = if device.is_empty() {
+            "Stereo Mix"
+        } else {
+            device
+        };
+        let mut command = Command::new("ffmpeg");
+        command
+            .args([
+                "-loglevel",
+                "quiet",
+                "-f",
+                "dshow",
+                "-audio_buffer_size",
+                "20",
+            ])
+            .arg("-i")
+            .arg(format!("audio={}", device))
+            .args([
+                "-ac",
+                "1",
+                "-ar",
+                &SAMPLE_RATE.to_string(),
+                "-f",
+                "s16le",
+                "-",
+            ]);
+        Ok((command, "ffmpeg"))
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    {
+        let _ = device;
+        Err(AppError::UnsupportedPlatform)
+    }
+}
+
+/// Get the strength of `bands` frequency bands, from 0.0 for silence
+///
+/// The bands are spread evenly on a log scale from 40 Hz to 10 kHz, as
+/// hearing is; a full-scale tone gives its band about 1.0.
+pub fn spectrum(samples: &[f32], bands: usize) -> Vec<f32> {
+    if samples.is_empty() {
+        return vec![0.0; bands];
+    }
+    // A Hann window keeps the loud low notes from leaking into every band
+    let length = samples.len();
+    let windowed: Vec<f32> = samples
+        .iter()
+        .enumerate()
+        .map(|(index, sample)| {
+            sample * (0.5 - 0.5 * (2.0 * PI * index as f32 / length as f32).cos())
+        })
+        .collect();
+
+    // Each band is as strong as its strongest frequency bin
+    let bin = SAMPLE_RATE as f32 / length as f32;
+    let top = (HIGHEST / bin).ceil() as usize;
+    let magnitudes: Vec<f32> = (0..=top)
+        .map(|index| magnitude(&windowed, index as f32 * bin))
+        .collect();
+    let ratio = HIGHEST / LOWEST;
+    (0..bands)
+        .map(|band| {
+            let edge = |position: f32| LOWEST * ratio.powf(position / bands as f32) / bin;
+            let (low, high) = (
+                edge(band as f32).ceil() as usize,
+                edge(band as f32 + 1.0).floor() as usize,
+            );
+            if low > high {
+                // Low bands narrower than a bin take the bin nearest their middle
+                return magnitudes[(edge(band as f32 + 0.5).round() as usize).min(top)];
+            }
+            magnitudes[low.min(top)..=high.min(top)]
+                .iter()
+                .copied()
+                .fold(0.0, f32::max)
+        })
+        .collect()
+}
+
+/// Get the strength of one frequency with the Goertzel algorithm, scaled for a Hann window
+fn magnitude(samples: &[f32], frequency: f32) -> f32 {
+    let coefficient = 2.0 * (2.0 * PI * frequency / SAMPLE_RATE as f32).cos();
+    let (mut previous, mut before) = (0.0f32, 0.0f32);
+    for sample in samples {
+        let current = sample + coefficient * previous - before;
+        before = previous;
+        previous = current;
+    }
+    let power = previous * previous + before * before - coefficient * previous * before;
+    power.max(0.0).sqrt() * 4.0 / samples.len() as f32
+}
+
+/// Get how loud the samples are, as their root mean square
+pub fn loudness(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    (samples.iter().map(|sample| sample * sample).sum::<f32>() / samples.len() as f32).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(frequency: f32) -> Vec<f32> {
+        (0..WINDOW)
+            .map(|index| (2.0 * PI * frequency * index as f32 / SAMPLE_RATE as f32).sin())
+            .collect()
+    }
+
+    #[test]
+    fn test_spectrum_finds_the_tone() {
+        let bands = spectrum(&tone(1000.0), 24);
+        let loudest = (0..bands.len())
+            .max_by(|a, b| bands[*a].total_cmp(&bands[*b]))
+            .unwrap();
+
+        // 1 kHz sits a little past the middle of 40 Hz - 10 kHz on a log scale
+        let expected = ((1000.0f32 / LOWEST).ln() / (HIGHEST / LOWEST).ln() * 24.0) as usize;
+        assert!(
+            loudest.abs_diff(expected) <= 1,
+            "loudest band {} instead of {}",
+            loudest,
+            expected
+        );
+        assert!(bands[loudest] > 0.5 && bands[0] < 0.05);
+
+        assert!(spectrum(&vec![0.0; WINDOW], 8)
+            .iter()
+            .all(|band| *band == 0.0));
+    }
+
+    #[test]
+    fn test_loudness_and_samples() {
+        assert!((loudness(&tone(440.0)) - 0.707).abs() < 0.01);
+        assert_eq!(loudness(&[]), 0.0);
+
+        let samples = Samples::new();
+        samples.push(&[0.5; 10]);
+        let latest = samples.latest();
+        assert_eq!(latest.len(), WINDOW);
+        assert_eq!((latest[WINDOW - 11], latest[WINDOW - 1]), (0.0, 0.5));
+    }
+}
@@ -0,0 +1,182 @@
+//! Start with system
+//!
+//! Windows uses the per-user `Run` registry key; Linux uses an XDG autostart entry.
+use crate::core::{AppError, AppResult};
+use log::info;
+use std::path::PathBuf;
+#[cfg(target_os = "windows")]
+use std::process::Command;
+
+/// Command-line flag that starts the application minimized
+pub const MINIMIZED_FLAG: &str = "--minimized";
+
+/// Registry key holding per-user startup programs
+#[cfg(target_os = "windows")]
+const RUN_KEY: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run";
+
+/// Registry value name
+#[cfg(target_os = "windows")]
+const RUN_VALUE: &str = "AetherDesk";
+
+/// Enable or disable starting with the system
+pub fn set_enabled(enabled: bool) -> AppResult<()> {
+    if enabled {
+        enable()?;
+    } else {
+        disable()?;
+    }
+
+    info!(
+        "Start with system {}",
+        if enabled { "enabled" } else { "disabled" }
+    );
+    Ok(())
+}
+
+/// Check whether the application starts with the system
+pub fn is_enabled() -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        Command::new("reg")
+            .args(["query", RUN_KEY, "/v", RUN_VALUE])
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        desktop_entry_path()
+            .map(|path| path.exists())
+            .unwrap_or(false)
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    {
+        false
+    }
+}
+
+/// Get the path of the running executable
+fn current_exe() -> AppResult<PathBuf> {
+    std::env::current_exe()
+        .map_err(|e| AppError::PlatformError(format!("Failed to locate executable: {}", e)))
+}
+
+/// Register the application to start with the system
+fn enable() -> AppResult<()> {
+    let exe = current_exe()?;
+
+    #[cfg(target_os = "windows")]
+    {
+        let command = format!("\"{}\" {}", exe.display(), MINIMIZED_FLAG);
+        let output = Command::new("reg")
+            .args([
+                "add", RUN_KEY, "/v", RUN_VALUE, "/t", "REG_SZ", "/d", &command, "/f",
+            ])
+            .output()?;
+
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(AppError::PlatformError(format!(
+                "Failed to add startup entry: {}",
+                error
+            )));
+        }
+
+        Ok(())
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let path = desktop_entry_path()?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        std::fs::write(&path, desktop_entry(&exe))?;
+        Ok(())
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    {
+        let _ = exe;
+        Err(AppError::UnsupportedPlatform)
+    }
+}
+
+/// Remove the startup registration
+fn disable() -> AppResult<()> {
+    #[cfg(target_os = "windows")]
+    {
+        if !is_enabled() {
+            return Ok(());
+        }
+
+        let output = Command::new("reg")
+            .args(["delete", RUN_KEY, "/v", RUN_VALUE, "/f"])
+            .output()?;
+
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(AppError::PlatformError(format!(
+                "Failed to remove startup entry: {}",
+                error
+            )));
+        }
+
+        Ok(())
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let path = desktop_entry_path()?;
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    {
+        Err(AppError::UnsupportedPlatform)
+    }
+}
+
+/// Get the XDG autostart entry path
+#[cfg(target_os = "linux")]
+fn desktop_entry_path() -> AppResult<PathBuf> {
+    let config_dir = dirs::config_dir().ok_or_else(|| {
+        AppError::PlatformError("Could not find configuration directory".to_string())
+    })?;
+
+    Ok(config_dir.join("autostart").join("aether-desk.desktop"))
+}
+
+/// Build the XDG autostart entry
+#[allow(dead_code)]
+fn desktop_entry(exe: &std::path::Path) -> String {
+    format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name=Aether-Desk\n\
+         Comment=Desktop wallpaper manager\n\
+         Exec=\"{}\" {}\n\
+         Terminal=false\n\
+         X-GNOME-Autostart-enabled=true\n",
+        exe.display(),
+        MINIMIZED_FLAG
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_desktop_entry() {
+        let entry = desktop_entry(std::path::Path::new("/opt/aether desk/aether-desk"));
+        assert!(entry.starts_with("[Desktop Entry]\n"));
+        assert!(entry.contains("Exec=\"/opt/aether desk/aether-desk\" --minimized\n"));
+    }
+}
@@ -0,0 +1,343 @@
+//! Share of the desktop covered by application windows
+//!
+//! Windows enumerates the visible top-level windows, leaving out minimized,
+//! cloaked and tool windows. On Linux Hyprland reports the clients on each
+//! monitor's active workspace, Sway the visible nodes of its tree, and X11
+//! sessions list the windows of the current desktop with `wmctrl`. Other
+//! Wayland compositors don't expose windows, so nothing is reported there.
+use crate::platform::display;
+use serde_json::Value;
+#[cfg(target_os = "linux")]
+use std::process::Command;
+
+/// Rectangle on the desktop as (left, top, right, bottom)
+pub type Rect = (i32, i32, i32, i32);
+
+/// Get the share (0.0 - 1.0) of the monitors covered by windows
+///
+/// Returns None when the platform doesn't report windows.
+pub fn desktop_coverage() -> Option<f32> {
+    let monitors: Vec<Rect> = display::monitors()
+        .ok()?
+        .iter()
+        .map(|m| (m.x, m.y, m.x + m.width as i32, m.y + m.height as i32))
+        .collect();
+    let windows = window_rects()?;
+    Some(covered_share(&windows, &monitors))
+}
+
+/// Get the rectangles of the windows shown on the desktop
+fn window_rects() -> Option<Vec<Rect>> {
+    #[cfg(windows)]
+    {
+        Some(visible_windows())
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        if crate::platform::hyprland::is_hyprland() {
+            let monitors = command_json("hyprctl", &["monitors", "-j"])?;
+            let clients = command_json("hyprctl", &["clients", "-j"])?;
+            Some(hyprland_windows(&monitors, &clients))
+        } else if std::env::var_os("SWAYSOCK").is_some() {
+            command_json("swaymsg", &["-t", "get_tree"]).map(|tree| sway_windows(&tree))
+        } else if std::env::var_os("DISPLAY").is_some() {
+            x11_windows()
+        } else {
+            None
+        }
+    }
+
+    #[cfg(not(any(windows, target_os = "linux")))]
+    {
+        None
+    }
+}
+
+/// Get the share of the monitors' area covered by any of the windows
+///
+/// Overlapping windows count once, and parts off the monitors not at all.
+pub fn covered_share(windows: &[Rect], monitors: &[Rect]) -> f32 {
+    let area = |rect: &Rect| (rect.2 - rect.0).max(0) as i64 * (rect.3 - rect.1).max(0) as i64;
+    let total: i64 = monitors.iter().map(area).sum();
+    if total == 0 {
+        return 0.0;
+    }
+
+    let parts: Vec<Rect> = windows
+        .iter()
+        .flat_map(|window| {
+            monitors.iter().map(move |monitor| {
+                (
+                    window.0.max(monitor.0),
+                    window.1.max(monitor.1),
+                    window.2.min(monitor.2),
+                    window.3.min(monitor.3),
+                )
+            })
+        })
+        .filter(|part| area(part) > 0)
+        .collect();
+
+    // Split the desktop at every edge, then add up the cells inside a window
+    let edges = |pick: fn(&Rect) -> [i32; 2]| {
+        let mut edges: Vec<i32> = parts.iter().flat_map(pick).collect();
+        edges.sort_unstable();
+        edges.dedup();
+        edges
+    };
+    let xs = edges(|part| [part.0, part.2]);
+    let ys = edges(|part| [part.1, part.3]);
+    let mut covered = 0i64;
+    for x in xs.windows(2) {
+        for y in ys.windows(2) {
+            if parts
+                .iter()
+                .any(|part| part.0 <= x[0] && x[1] <= part.2 && part.1 <= y[0] && y[1] <= part.3)
+            {
+                covered += (x[1] - x[0]) as i64 * (y[1] - y[0]) as i64;
+            }
+        }
+    }
+    (covered as f64 / total as f64) as f32
+}
+
+/// Run a command and parse its JSON output
+#[cfg(target_os = "linux")]
+fn command_json(program: &str, args: &[&str]) -> Option<Value> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    serde_json::from_slice(&output.stdout).ok()
+}
+
+/// Get the windows on the monitors' active workspaces from `hyprctl monitors -j` and `hyprctl clients -j`
+#[allow(dead_code)]
+fn hyprland_windows(monitors: &Value, clients: &Value) -> Vec<Rect> {
+    let active: Vec<i64> = monitors
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|monitor| monitor["activeWorkspace"]["id"].as_i64())
+        .collect();
+    clients
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|client| {
+            client["mapped"].as_bool() != Some(false) && client["hidden"].as_bool() != Some(true)
+        })
+        .filter(|client| {
+            client["workspace"]["id"]
+                .as_i64()
+                .map_or(false, |id| active.contains(&id))
+        })
+        .filter_map(|client| {
+            let (x, y) = (client["at"][0].as_i64()?, client["at"][1].as_i64()?);
+            let (width, height) = (client["size"][0].as_i64()?, client["size"][1].as_i64()?);
+            Some((x as i32, y as i32, (x + width) as i32, (y + height) as i32))
+        })
+        .collect()
+}
+
+/// Get the visible application windows from `swaymsg -t get_tree` output
+#[allow(dead_code)]
+fn sway_windows(node: &Value) -> Vec<Rect> {
+    let mut windows = Vec::new();
+    if node["visible"].as_bool() == Some(true) && node["pid"].is_number() {
+        let rect = &node["rect"];
+        if let (Some(x), Some(y), Some(width), Some(height)) = (
+            rect["x"].as_i64(),
+            rect["y"].as_i64(),
+            rect["width"].as_i64(),
+            rect["height"].as_i64(),
+        ) {
+            windows.push((x as i32, y as i32, (x + width) as i32, (y + height) as i32));
+        }
+    }
+    for key in ["nodes", "floating_nodes"] {
+        for child in node[key].as_array().into_iter().flatten() {
+            windows.extend(sway_windows(child));
+        }
+    }
+    windows
+}
+
+/// List the windows of the current X11 desktop, leaving out minimized ones
+#[cfg(target_os = "linux")]
+fn x11_windows() -> Option<Vec<Rect>> {
+    let output = |program: &str, args: &[&str]| {
+        Command::new(program)
+            .args(args)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+    };
+    let desktop = output("wmctrl", &["-d"])
+        .and_then(|desktops| x11_current_desktop(&desktops))
+        .unwrap_or(0);
+    let windows = parse_wmctrl(&output("wmctrl", &["-lG"])?, desktop)
+        .into_iter()
+        .filter(|(id, _)| {
+            output("xprop", &["-id", id, "_NET_WM_STATE"])
+                .map_or(true, |state| !state.contains("_NET_WM_STATE_HIDDEN"))
+        })
+        .map(|(_, rect)| rect)
+        .collect();
+    Some(windows)
+}
+
+/// Get the current desktop from `wmctrl -d` output, marked with `*`
+#[allow(dead_code)]
+fn x11_current_desktop(output: &str) -> Option<i64> {
+    output
+        .lines()
+        .find(|line| line.split_whitespace().nth(1) == Some("*"))
+        .and_then(|line| line.split_whitespace().next()?.parse().ok())
+}
+
+/// Parse `wmctrl -lG` output into the IDs and rectangles of a desktop's windows
+///
+/// Lines are "id desktop x y width height host title". Windows on every
+/// desktop (-1) are panels and the desktop itself, so they are left out.
+#[allow(dead_code)]
+fn parse_wmctrl(output: &str, desktop: i64) -> Vec<(String, Rect)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().take(6).collect();
+            if fields.len() < 6 || fields[1].parse::<i64>().ok()? != desktop {
+                return None;
+            }
+            let number = |index: usize| fields[index].parse::<i32>().ok();
+            let (x, y, width, height) = (number(2)?, number(3)?, number(4)?, number(5)?);
+            Some((fields[0].to_string(), (x, y, x + width, y + height)))
+        })
+        .collect()
+}
+
+/// Enumerate the visible top-level windows
+#[cfg(windows)]
+fn visible_windows() -> Vec<Rect> {
+    use windows::Win32::Foundation::{BOOL, HWND, LPARAM, RECT, TRUE};
+    use windows::Win32::Graphics::Dwm::{DwmGetWindowAttribute, DWMWA_CLOAKED};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        EnumWindows, GetClassNameW, GetWindowLongW, GetWindowRect, IsIconic, IsWindowVisible,
+        GWL_EXSTYLE, WS_EX_TOOLWINDOW,
+    };
+
+    unsafe extern "system" fn collect(window: HWND, rects: LPARAM) -> BOOL {
+        if !IsWindowVisible(window).as_bool() || IsIconic(window).as_bool() {
+            return TRUE;
+        }
+        if GetWindowLongW(window, GWL_EXSTYLE) as u32 & WS_EX_TOOLWINDOW.0 != 0 {
+            return TRUE;
+        }
+
+        // Suspended store apps stay visible but cloaked
+        let mut cloaked = 0u32;
+        let _ = DwmGetWindowAttribute(
+            window,
+            DWMWA_CLOAKED,
+            &mut cloaked as *mut u32 as *mut _,
+            std::mem::size_of::<u32>() as u32,
+        );
+        if cloaked != 0 {
+            return TRUE;
+        }
+
+        // The desktop and the taskbars always cover their part of the screen
+        let mut class = [0u16; 64];
+        let length = GetClassNameW(window, &mut class).max(0) as usize;
+        let class = String::from_utf16_lossy(&class[..length]);
+        if matches!(
+            class.as_str(),
+            "WorkerW" | "Progman" | "Shell_TrayWnd" | "Shell_SecondaryTrayWnd"
+        ) {
+            return TRUE;
+        }
+
+        let mut rect = RECT::default();
+        if GetWindowRect(window, &mut rect).is_ok() {
+            (*(rects.0 as *mut Vec<Rect>)).push((rect.left, rect.top, rect.right, rect.bottom));
+        }
+        TRUE
+    }
+
+    let mut rects: Vec<Rect> = Vec::new();
+    unsafe {
+        let _ = EnumWindows(Some(collect), LPARAM(&mut rects as *mut _ as isize));
+    }
+    rects
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_covered_share() {
+        let monitors = [(0, 0, 100, 100), (100, 0, 200, 100)];
+        assert_eq!(covered_share(&[], &monitors), 0.0);
+        assert_eq!(covered_share(&[(0, 0, 100, 100)], &monitors), 0.5);
+
+        // Overlaps count once, and parts off the monitors not at all
+        assert_eq!(
+            covered_share(&[(0, 0, 100, 100), (50, 0, 150, 100)], &monitors),
+            0.75
+        );
+        assert_eq!(covered_share(&[(-50, -50, 50, 200)], &monitors), 0.25);
+        assert_eq!(covered_share(&[(-8, -8, 208, 108)], &monitors), 1.0);
+        assert_eq!(covered_share(&[(0, 0, 10, 10)], &[]), 0.0);
+    }
+
+    #[test]
+    fn test_hyprland_windows() {
+        let monitors =
+            json!([{ "activeWorkspace": { "id": 1 } }, { "activeWorkspace": { "id": 4 } }]);
+        let clients = json!([
+            { "mapped": true, "hidden": false, "at": [0, 0], "size": [960, 1080], "workspace": { "id": 1 } },
+            { "mapped": true, "hidden": false, "at": [960, 0], "size": [960, 1080], "workspace": { "id": 2 } },
+            { "mapped": true, "hidden": true, "at": [0, 0], "size": [100, 100], "workspace": { "id": 4 } },
+        ]);
+        assert_eq!(
+            hyprland_windows(&monitors, &clients),
+            vec![(0, 0, 960, 1080)]
+        );
+    }
+
+    #[test]
+    fn test_sway_windows() {
+        let tree = json!({
+            "nodes": [{
+                "nodes": [
+                    { "visible": true, "pid": 10, "rect": { "x": 0, "y": 0, "width": 800, "height": 600 }, "nodes": [] },
+                    { "visible": false, "pid": 11, "rect": { "x": 0, "y": 0, "width": 800, "height": 600 }, "nodes": [] },
+                ],
+                "floating_nodes": [
+                    { "visible": true, "pid": 12, "rect": { "x": 100, "y": 100, "width": 200, "height": 100 }, "nodes": [] },
+                ],
+            }],
+        });
+        assert_eq!(
+            sway_windows(&tree),
+            vec![(0, 0, 800, 600), (100, 100, 300, 200)]
+        );
+    }
+
+    #[test]
+    fn test_parse_wmctrl() {
+        assert_eq!(x11_current_desktop("0  - DG: 1920x1080  VP: 0,0  WA: 0,0 1920x1050  Main\n1  * DG: 1920x1080  VP: 0,0  WA: 0,0 1920x1050  Web\n"), Some(1));
+
+        let output = "0x01000003 -1 0    1050 1920 30   host panel\n\
+                      0x03a00003  1 10   20   800  600  host Terminal\n\
+                      0x04200007  0 0    0    1920 1050 host Editor\n";
+        assert_eq!(
+            parse_wmctrl(output, 1),
+            vec![("0x03a00003".to_string(), (10, 20, 810, 620))]
+        );
+    }
+}
@@ -0,0 +1,86 @@
+//! Mouse cursor position on the desktop
+//!
+//! Windows reports it with `GetCursorPos`. On Linux Hyprland is asked with
+//! `hyprctl cursorpos` and X11 sessions with `xdotool`. Other Wayland
+//! compositors don't expose the pointer to other clients.
+#[cfg(target_os = "linux")]
+use std::process::Command;
+
+/// Get the cursor position in desktop coordinates
+///
+/// Returns None when the platform doesn't report it.
+pub fn position() -> Option<(i32, i32)> {
+    #[cfg(windows)]
+    {
+        use windows::Win32::Foundation::POINT;
+        use windows::Win32::UI::WindowsAndMessaging::GetCursorPos;
+
+        let mut point = POINT::default();
+        unsafe { GetCursorPos(&mut point) }.ok()?;
+        Some((point.x, point.y))
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        if crate::platform::hyprland::is_hyprland() {
+            command_output("hyprctl", &["cursorpos"]).and_then(|output| parse_hyprland(&output))
+        } else if std::env::var_os("DISPLAY").is_some() {
+            command_output("xdotool", &["getmouselocation", "--shell"])
+                .and_then(|output| parse_xdotool(&output))
+        } else {
+            None
+        }
+    }
+
+    #[cfg(not(any(windows, target_os = "linux")))]
+    {
+        None
+    }
+}
+
+/// Run a command and get its output
+#[cfg(target_os = "linux")]
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Parse `hyprctl cursorpos` output, e.g. "1280, 720"
+#[allow(dead_code)]
+fn parse_hyprland(output: &str) -> Option<(i32, i32)> {
+    let (x, y) = output.trim().split_once(',')?;
+    Some((x.trim().parse().ok()?, y.trim().parse().ok()?))
+}
+
+/// Parse `xdotool getmouselocation --shell` output, lines like "X=1280"
+#[allow(dead_code)]
+fn parse_xdotool(output: &str) -> Option<(i32, i32)> {
+    let value = |name: &str| {
+        output
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix('='))
+            .and_then(|value| value.trim().parse().ok())
+    };
+    Some((value("X")?, value("Y")?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cursor_positions() {
+        assert_eq!(parse_hyprland("1280, 720\n"), Some((1280, 720)));
+        assert_eq!(parse_hyprland("-40, 12"), Some((-40, 12)));
+        assert_eq!(parse_hyprland("error"), None);
+
+        assert_eq!(
+            parse_xdotool("X=1280\nY=720\nSCREEN=0\nWINDOW=123\n"),
+            Some((1280, 720))
+        );
+        assert_eq!(parse_xdotool("X=5\n"), None);
+    }
+}
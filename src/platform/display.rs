@@ -0,0 +1,600 @@
+//! Monitor queries
+//!
+//! Windows asks `EnumDisplayMonitors`. Linux asks hyprctl under Hyprland,
+//! wlr-randr on other Wayland compositors and xrandr otherwise, falling back
+//! to xrandr when wlr-randr isn't there.
+use crate::core::{AppError, AppResult};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+
+/// Connected monitor
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MonitorInfo {
+    /// Identifier for settings kept per monitor: the make, model and serial
+    /// where the platform reports them, so it survives a change of port, and
+    /// the name otherwise
+    #[serde(default)]
+    pub id: String,
+
+    /// Monitor name (output or device name)
+    pub name: String,
+
+    /// Horizontal position of the top-left corner on the desktop, in pixels
+    #[serde(default)]
+    pub x: i32,
+
+    /// Vertical position of the top-left corner on the desktop, in pixels
+    #[serde(default)]
+    pub y: i32,
+
+    /// Width in pixels
+    pub width: u32,
+
+    /// Height in pixels
+    pub height: u32,
+
+    /// Scale factor set for the monitor, 1.0 when unscaled
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+
+    /// Whether this is the primary monitor
+    #[serde(default)]
+    pub primary: bool,
+
+    /// Refresh rate in Hz, when known
+    #[serde(default)]
+    pub refresh_rate: Option<f32>,
+
+    /// Rotation in degrees counter-clockwise, 0 when upright
+    ///
+    /// Width and height are already those of the rotated monitor.
+    #[serde(default)]
+    pub rotation: u32,
+
+    /// Whether `x` and `y` are in logical pixels, as Wayland compositors lay
+    /// out scaled monitors, rather than physical ones
+    #[serde(default)]
+    pub logical_position: bool,
+}
+
+impl MonitorInfo {
+    /// Describe an unscaled monitor at the origin, identified by its name
+    pub fn new(name: impl Into<String>, width: u32, height: u32) -> Self {
+        let name = name.into();
+        Self {
+            id: name.clone(),
+            name,
+            x: 0,
+            y: 0,
+            width,
+            height,
+            scale: default_scale(),
+            primary: false,
+            refresh_rate: None,
+            rotation: 0,
+            logical_position: false,
+        }
+    }
+
+    /// Get the physical pixels per unit of the layout `x` and `y` are given in
+    pub fn layout_scale(&self) -> f32 {
+        if self.logical_position && self.scale > 0.0 {
+            self.scale
+        } else {
+            1.0
+        }
+    }
+
+    /// Check whether the monitor is taller than it is wide, as rotated ones usually are
+    pub fn is_portrait(&self) -> bool {
+        self.height > self.width
+    }
+}
+
+/// Scale of monitors that don't report one
+fn default_scale() -> f32 {
+    1.0
+}
+
+/// Refresh rate assumed when no monitor reports one
+pub const DEFAULT_REFRESH_RATE: f32 = 60.0;
+
+/// Get the highest refresh rate of the connected monitors
+///
+/// Falls back to `DEFAULT_REFRESH_RATE` when the monitors can't be queried or
+/// don't report a rate.
+pub fn max_refresh_rate() -> f32 {
+    monitors()
+        .ok()
+        .and_then(|monitors| {
+            monitors
+                .iter()
+                .filter_map(|m| m.refresh_rate)
+                .reduce(f32::max)
+        })
+        .unwrap_or(DEFAULT_REFRESH_RATE)
+}
+
+/// Get the connected monitors
+///
+/// Exactly one monitor is marked primary when any are connected; where the
+/// platform has no such notion, it is the one at the desktop's origin.
+pub fn monitors() -> AppResult<Vec<MonitorInfo>> {
+    let monitors = with_primary(query_monitors()?);
+    debug!("Detected monitors: {:?}", monitors);
+    Ok(monitors)
+}
+
+/// Ask the platform for the connected monitors
+fn query_monitors() -> AppResult<Vec<MonitorInfo>> {
+    #[cfg(target_os = "windows")]
+    {
+        get_monitors_windows()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        if crate::platform::hyprland::is_hyprland() {
+            get_monitors_hyprland()
+        } else if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            get_monitors_wlr_randr().or_else(|e| {
+                debug!("wlr-randr failed ({}); asking xrandr", e);
+                get_monitors_xrandr()
+            })
+        } else {
+            get_monitors_xrandr()
+        }
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    {
+        Err(AppError::UnsupportedPlatform)
+    }
+}
+
+/// Mark one monitor primary when the platform didn't
+fn with_primary(mut monitors: Vec<MonitorInfo>) -> Vec<MonitorInfo> {
+    if monitors.iter().any(|m| m.primary) {
+        return monitors;
+    }
+    let index = monitors
+        .iter()
+        .position(|m| m.x == 0 && m.y == 0)
+        .unwrap_or(0);
+    if let Some(monitor) = monitors.get_mut(index) {
+        monitor.primary = true;
+    }
+    monitors
+}
+
+/// Query monitors through EnumDisplayMonitors
+#[cfg(target_os = "windows")]
+fn get_monitors_windows() -> AppResult<Vec<MonitorInfo>> {
+    use windows::core::PCWSTR;
+    use windows::Win32::Foundation::{BOOL, LPARAM, RECT, TRUE};
+    use windows::Win32::Graphics::Gdi::{
+        EnumDisplayMonitors, EnumDisplaySettingsW, GetMonitorInfoW, DEVMODEW,
+        ENUM_CURRENT_SETTINGS, HDC, HMONITOR, MONITORINFO, MONITORINFOEXW, MONITORINFOF_PRIMARY,
+    };
+    use windows::Win32::UI::HiDpi::{GetDpiForMonitor, MDT_EFFECTIVE_DPI};
+
+    unsafe extern "system" fn collect(
+        monitor: HMONITOR,
+        _: HDC,
+        _: *mut RECT,
+        handles: LPARAM,
+    ) -> BOOL {
+        (*(handles.0 as *mut Vec<HMONITOR>)).push(monitor);
+        TRUE
+    }
+
+    let mut handles: Vec<HMONITOR> = Vec::new();
+    unsafe {
+        if !EnumDisplayMonitors(
+            HDC::default(),
+            None,
+            Some(collect),
+            LPARAM(&mut handles as *mut _ as isize),
+        )
+        .as_bool()
+        {
+            return Err(AppError::PlatformError(
+                "Failed to enumerate monitors".to_string(),
+            ));
+        }
+    }
+
+    let monitors = handles
+        .into_iter()
+        .filter_map(|handle| unsafe {
+            let mut info = MONITORINFOEXW::default();
+            info.monitorInfo.cbSize = std::mem::size_of::<MONITORINFOEXW>() as u32;
+            if !GetMonitorInfoW(handle, &mut info as *mut MONITORINFOEXW as *mut MONITORINFO)
+                .as_bool()
+            {
+                return None;
+            }
+            let length = info
+                .szDevice
+                .iter()
+                .position(|&c| c == 0)
+                .unwrap_or(info.szDevice.len());
+            let name = String::from_utf16_lossy(&info.szDevice[..length]);
+            let bounds = info.monitorInfo.rcMonitor;
+
+            let (mut dpi_x, mut dpi_y) = (0, 0);
+            let scale = match GetDpiForMonitor(handle, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y) {
+                Ok(()) if dpi_x > 0 => dpi_x as f32 / 96.0,
+                _ => default_scale(),
+            };
+
+            let mut mode = DEVMODEW {
+                dmSize: std::mem::size_of::<DEVMODEW>() as u16,
+                ..Default::default()
+            };
+            let has_mode = EnumDisplaySettingsW(
+                PCWSTR(info.szDevice.as_ptr()),
+                ENUM_CURRENT_SETTINGS,
+                &mut mode,
+            )
+            .as_bool();
+            // 0 and 1 stand for the hardware's default rate
+            let refresh_rate =
+                (has_mode && mode.dmDisplayFrequency > 1).then_some(mode.dmDisplayFrequency as f32);
+            // Windows counts quarter turns clockwise
+            let quarter_turns = if has_mode {
+                mode.Anonymous1.Anonymous2.dmDisplayOrientation.0 % 4
+            } else {
+                0
+            };
+
+            Some(MonitorInfo {
+                id: name.clone(),
+                name,
+                x: bounds.left,
+                y: bounds.top,
+                width: (bounds.right - bounds.left).max(0) as u32,
+                height: (bounds.bottom - bounds.top).max(0) as u32,
+                scale,
+                primary: info.monitorInfo.dwFlags & MONITORINFOF_PRIMARY != 0,
+                refresh_rate,
+                rotation: (4 - quarter_turns) % 4 * 90,
+                logical_position: false,
+            })
+        })
+        .collect();
+    Ok(monitors)
+}
+
+/// Query monitors through hyprctl
+#[cfg(target_os = "linux")]
+fn get_monitors_hyprland() -> AppResult<Vec<MonitorInfo>> {
+    let output = Command::new("hyprctl")
+        .args(["monitors", "-j"])
+        .output()
+        .map_err(|e| AppError::from_spawn("hyprctl", e))?;
+
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(AppError::PlatformError(format!(
+            "Failed to query monitors: {}",
+            error
+        )));
+    }
+
+    Ok(parse_hyprctl(&serde_json::from_slice(&output.stdout)?))
+}
+
+/// Parse `hyprctl monitors -j` output
+#[allow(dead_code)]
+fn parse_hyprctl(json: &serde_json::Value) -> Vec<MonitorInfo> {
+    let Some(entries) = json.as_array() else {
+        return Vec::new();
+    };
+    entries
+        .iter()
+        .filter_map(|m| {
+            let name = m["name"].as_str()?.to_string();
+            let scale = m["scale"].as_f64().unwrap_or(1.0) as f32;
+            // Width and height are the mode's; the layout uses the transformed size
+            let (width, height) = (m["width"].as_u64()? as u32, m["height"].as_u64()? as u32);
+            // Transforms 4 to 7 are flipped versions of 0 to 3
+            let quarter_turns = m["transform"].as_u64().unwrap_or(0) % 4;
+            let (width, height) = if quarter_turns % 2 == 1 {
+                (height, width)
+            } else {
+                (width, height)
+            };
+            Some(MonitorInfo {
+                id: m["description"]
+                    .as_str()
+                    .filter(|d| !d.is_empty())
+                    .unwrap_or(&name)
+                    .to_string(),
+                name,
+                x: m["x"].as_i64().unwrap_or(0) as i32,
+                y: m["y"].as_i64().unwrap_or(0) as i32,
+                width,
+                height,
+                scale,
+                primary: false,
+                refresh_rate: m["refreshRate"].as_f64().map(|rate| rate as f32),
+                rotation: quarter_turns as u32 * 90,
+                logical_position: true,
+            })
+        })
+        .collect()
+}
+
+/// Query monitors through wlr-randr, on wlroots-based compositors such as Sway
+#[cfg(target_os = "linux")]
+fn get_monitors_wlr_randr() -> AppResult<Vec<MonitorInfo>> {
+    let output = Command::new("wlr-randr")
+        .arg("--json")
+        .output()
+        .map_err(|e| AppError::from_spawn("wlr-randr", e))?;
+
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(AppError::PlatformError(format!(
+            "Failed to query monitors: {}",
+            error
+        )));
+    }
+
+    Ok(parse_wlr_randr(&serde_json::from_slice(&output.stdout)?))
+}
+
+/// Parse `wlr-randr --json` output, leaving out disabled outputs
+#[allow(dead_code)]
+fn parse_wlr_randr(json: &serde_json::Value) -> Vec<MonitorInfo> {
+    let Some(entries) = json.as_array() else {
+        return Vec::new();
+    };
+    entries
+        .iter()
+        .filter(|m| m["enabled"].as_bool().unwrap_or(true))
+        .filter_map(|m| {
+            let name = m["name"].as_str()?.to_string();
+            let mode = m["modes"]
+                .as_array()?
+                .iter()
+                .find(|mode| mode["current"].as_bool() == Some(true))?;
+            let (width, height) = (
+                mode["width"].as_u64()? as u32,
+                mode["height"].as_u64()? as u32,
+            );
+            let rotation = match m["transform"]
+                .as_str()
+                .map(|t| t.trim_start_matches("flipped-"))
+            {
+                Some("90") => 90,
+                Some("180") => 180,
+                Some("270") => 270,
+                _ => 0,
+            };
+            let (width, height) = if rotation % 180 == 90 {
+                (height, width)
+            } else {
+                (width, height)
+            };
+
+            let hardware: Vec<&str> = ["make", "model", "serial"]
+                .iter()
+                .filter_map(|key| {
+                    m[*key]
+                        .as_str()
+                        .filter(|value| !value.is_empty() && *value != "Unknown")
+                })
+                .collect();
+            Some(MonitorInfo {
+                id: if hardware.is_empty() {
+                    name.clone()
+                } else {
+                    hardware.join(" ")
+                },
+                name,
+                x: m["position"]["x"].as_i64().unwrap_or(0) as i32,
+                y: m["position"]["y"].as_i64().unwrap_or(0) as i32,
+                width,
+                height,
+                scale: m["scale"].as_f64().unwrap_or(1.0) as f32,
+                primary: false,
+                refresh_rate: mode["refresh"].as_f64().map(|rate| rate as f32),
+                rotation,
+                logical_position: true,
+            })
+        })
+        .collect()
+}
+
+/// Query monitors through xrandr
+#[cfg(target_os = "linux")]
+fn get_monitors_xrandr() -> AppResult<Vec<MonitorInfo>> {
+    let output = Command::new("xrandr")
+        .arg("--query")
+        .output()
+        .map_err(|e| AppError::from_spawn("xrandr", e))?;
+
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(AppError::PlatformError(format!(
+            "Failed to query monitors: {}",
+            error
+        )));
+    }
+
+    Ok(parse_xrandr(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse `xrandr --query` output
+///
+/// Lines look like "DP-1 connected primary 2560x1440+0+0 (normal left ...) 597mm x 336mm".
+/// Rotated outputs already report their rotated geometry, followed by the
+/// rotation ("left", "inverted" or "right"). The mode lines
+/// below an output mark the current refresh rate with `*`, e.g. "2560x1440 59.95*+".
+#[allow(dead_code)]
+fn parse_xrandr(output: &str) -> Vec<MonitorInfo> {
+    let mut monitors: Vec<MonitorInfo> = Vec::new();
+    // Whether the mode lines being read belong to the last connected output
+    let mut in_connected = false;
+
+    for line in output.lines() {
+        let is_mode = line.trim_start().starts_with(|c: char| c.is_ascii_digit());
+        if !is_mode {
+            in_connected = false;
+            if let Some(monitor) = parse_xrandr_output(line) {
+                monitors.push(monitor);
+                in_connected = true;
+            }
+        } else if in_connected {
+            let current = line
+                .split_whitespace()
+                .skip(1)
+                .find(|rate| rate.contains('*'))
+                .and_then(|rate| rate.trim_end_matches(['*', '+']).parse().ok());
+            if let (Some(rate), Some(monitor)) = (current, monitors.last_mut()) {
+                monitor.refresh_rate = Some(rate);
+            }
+        }
+    }
+
+    monitors
+}
+
+/// Parse a connected output line of `xrandr --query`
+#[allow(dead_code)]
+fn parse_xrandr_output(line: &str) -> Option<MonitorInfo> {
+    if !line.contains(" connected") {
+        return None;
+    }
+    let name = line.split_whitespace().next()?.to_string();
+    let geometry = line
+        .split_whitespace()
+        .find(|part| part.contains('x') && part.contains('+'))?;
+    let (size, position) = geometry.split_once('+')?;
+    let (width, height) = size.split_once('x')?;
+    let (x, y) = position.split_once('+')?;
+    // The rotation follows the geometry, before the list of supported ones in parentheses
+    let rotation = match line
+        .split(geometry)
+        .nth(1)?
+        .split('(')
+        .next()?
+        .split_whitespace()
+        .next()
+    {
+        Some("left") => 90,
+        Some("inverted") => 180,
+        Some("right") => 270,
+        _ => 0,
+    };
+    Some(MonitorInfo {
+        x: x.parse().ok()?,
+        y: y.parse().ok()?,
+        primary: line.split_whitespace().any(|part| part == "primary"),
+        rotation,
+        ..MonitorInfo::new(name, width.parse().ok()?, height.parse().ok()?)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_xrandr() {
+        let output = "Screen 0: minimum 8 x 8, current 3640 x 2560, maximum 32767 x 32767\n\
+            DP-1 connected primary 2560x1440+0+0 (normal left inverted right x axis y axis) 597mm x 336mm\n\
+               2560x1440     59.95*+\n\
+            HDMI-1 connected 1080x1920+2560+0 left (normal left inverted right x axis y axis) 527mm x 296mm\n\
+               1920x1080     60.00 +  144.00*\n\
+            DP-2 disconnected (normal left inverted right x axis y axis)\n";
+
+        let monitors = parse_xrandr(output);
+        assert_eq!(monitors.len(), 2);
+        assert_eq!(
+            monitors[0],
+            MonitorInfo {
+                primary: true,
+                refresh_rate: Some(59.95),
+                ..MonitorInfo::new("DP-1", 2560, 1440)
+            }
+        );
+        assert_eq!(monitors[1].width, 1080);
+        assert_eq!(monitors[1].height, 1920);
+        assert_eq!(monitors[1].rotation, 90);
+        assert!(monitors[1].is_portrait());
+        assert_eq!((monitors[1].x, monitors[1].y), (2560, 0));
+        assert!(!monitors[1].primary);
+        assert_eq!(monitors[1].refresh_rate, Some(144.0));
+    }
+
+    #[test]
+    fn test_parse_hyprctl() {
+        let json = serde_json::json!([
+            { "id": 0, "name": "DP-1", "description": "Dell Inc. DELL U2720Q 8T4Z", "width": 3840, "height": 2160,
+              "refreshRate": 59.997, "x": 0, "y": 0, "scale": 1.5, "transform": 0 },
+            { "id": 1, "name": "HDMI-A-1", "description": "", "width": 1920, "height": 1080,
+              "refreshRate": 60.0, "x": 2560, "y": 0, "scale": 1.0, "transform": 1 },
+        ]);
+        let monitors = parse_hyprctl(&json);
+        assert_eq!(monitors.len(), 2);
+        assert_eq!(monitors[0].id, "Dell Inc. DELL U2720Q 8T4Z");
+        assert_eq!(monitors[0].scale, 1.5);
+        assert_eq!(monitors[1].id, "HDMI-A-1");
+        assert_eq!(
+            (monitors[1].width, monitors[1].height, monitors[1].x),
+            (1080, 1920, 2560)
+        );
+        assert_eq!((monitors[0].rotation, monitors[1].rotation), (0, 90));
+    }
+
+    #[test]
+    fn test_parse_wlr_randr() {
+        let json = serde_json::json!([
+            { "name": "eDP-1", "make": "BOE", "model": "0x0BCA", "serial": "Unknown", "enabled": true,
+              "modes": [{ "width": 2256, "height": 1504, "refresh": 59.999, "preferred": true, "current": true }],
+              "position": { "x": 0, "y": 0 }, "transform": "normal", "scale": 1.5 },
+            { "name": "DP-3", "make": "Unknown", "model": "Unknown", "serial": "Unknown", "enabled": true,
+              "modes": [{ "width": 1920, "height": 1080, "refresh": 60.0, "current": false },
+                        { "width": 2560, "height": 1440, "refresh": 144.0, "current": true }],
+              "position": { "x": 1504, "y": -200 }, "transform": "90", "scale": 1.0 },
+            { "name": "HDMI-A-1", "enabled": false, "modes": [] },
+        ]);
+        let monitors = parse_wlr_randr(&json);
+        assert_eq!(monitors.len(), 2);
+        assert_eq!(monitors[0].id, "BOE 0x0BCA");
+        assert_eq!(monitors[0].refresh_rate, Some(59.999));
+        assert_eq!(monitors[1].id, "DP-3");
+        assert_eq!(
+            (monitors[1].width, monitors[1].height, monitors[1].y),
+            (1440, 2560, -200)
+        );
+        assert_eq!((monitors[0].rotation, monitors[1].rotation), (0, 90));
+    }
+
+    #[test]
+    fn test_with_primary() {
+        let left = MonitorInfo {
+            x: -1920,
+            ..MonitorInfo::new("HDMI-A-1", 1920, 1080)
+        };
+        let main = MonitorInfo::new("DP-1", 2560, 1440);
+        let monitors = with_primary(vec![left.clone(), main]);
+        assert!(!monitors[0].primary);
+        assert!(monitors[1].primary);
+
+        let monitors = with_primary(vec![
+            MonitorInfo {
+                primary: true,
+                ..left
+            },
+            MonitorInfo::new("DP-1", 2560, 1440),
+        ]);
+        assert!(monitors[0].primary);
+        assert!(!monitors[1].primary);
+        assert!(with_primary(Vec::new()).is_empty());
+    }
+}
@@ -0,0 +1,592 @@
+//! Environment diagnostics
+//!
+//! Checks the external tools, desktop integration, permissions and
+//! configuration files the app relies on, with a fix for every problem.
+//! Shared by `aether-desk doctor` and the diagnostics panel in the settings.
+use crate::core::error::install_hint;
+use crate::core::{format, Config, ScheduleItem, WidgetConfig};
+use crate::platform::display;
+use crate::wallpapers::VideoWallpaper;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Result of a check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    /// Everything is fine
+    Ok,
+
+    /// Some features won't work
+    Warning,
+
+    /// The app can't work properly
+    Error,
+}
+
+/// Group of checks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckCategory {
+    /// External programs
+    Tools,
+
+    /// Desktop and compositor integration
+    Desktop,
+
+    /// Writable directories
+    Permissions,
+
+    /// Configuration files
+    Configuration,
+}
+
+impl CheckCategory {
+    /// All categories in display order
+    pub const ALL: [CheckCategory; 4] = [
+        CheckCategory::Tools,
+        CheckCategory::Desktop,
+        CheckCategory::Permissions,
+        CheckCategory::Configuration,
+    ];
+
+    /// English name, for the CLI
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CheckCategory::Tools => "Tools",
+            CheckCategory::Desktop => "Desktop",
+            CheckCategory::Permissions => "Permissions",
+            CheckCategory::Configuration => "Configuration",
+        }
+    }
+}
+
+/// One diagnostic check
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+    /// Group
+    pub category: CheckCategory,
+
+    /// What was checked, e.g. "mpv"
+    pub name: String,
+
+    /// Result
+    pub status: CheckStatus,
+
+    /// What was found
+    pub detail: String,
+
+    /// How to fix a problem
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<String>,
+}
+
+impl Check {
+    /// Passed check
+    fn ok(category: CheckCategory, name: &str, detail: impl Into<String>) -> Self {
+        Self {
+            category,
+            name: name.to_string(),
+            status: CheckStatus::Ok,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    /// Check that found a problem
+    fn problem(
+        category: CheckCategory,
+        name: &str,
+        status: CheckStatus,
+        detail: impl Into<String>,
+        fix: impl Into<String>,
+    ) -> Self {
+        Self {
+            category,
+            name: name.to_string(),
+            status,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+/// Run every check for this platform
+pub fn run() -> Vec<Check> {
+    let (config, config_checks) = check_configuration();
+    let mut checks = check_tools(&config);
+    checks.extend(check_desktop());
+    checks.extend(check_permissions(&config));
+    checks.extend(config_checks);
+    checks
+}
+
+/// Check the external programs
+fn check_tools(config: &Config) -> Vec<Check> {
+    let mut checks = Vec::new();
+    let category = CheckCategory::Tools;
+
+    checks.push(match VideoWallpaper::get_mpv_command() {
+        Ok(command) => Check::ok(category, "mpv", format!("found ({})", command)),
+        Err(_) => Check::problem(
+            category,
+            "mpv",
+            CheckStatus::Warning,
+            "not found; video wallpapers and previews won't work",
+            install_hint("mpv"),
+        ),
+    });
+
+    checks.push(tool_check(
+        "curl",
+        CheckStatus::Warning,
+        "online sources and Live Earth won't download anything",
+        install_hint("curl"),
+    ));
+
+    checks.push(tool_check(
+        "shadertoy",
+        CheckStatus::Warning,
+        "shader and audio wallpapers won't work",
+        install_hint("shadertoy"),
+    ));
+
+    #[cfg(target_os = "linux")]
+    {
+        if crate::platform::hyprland::is_hyprland() {
+            checks.push(tool_check(
+                "hyprctl",
+                CheckStatus::Error,
+                "wallpapers can't be set on Hyprland",
+                install_hint("hyprctl"),
+            ));
+            checks.push(tool_check(
+                "hyprpaper",
+                CheckStatus::Error,
+                "static wallpapers can't be set on Hyprland",
+                install_hint("hyprpaper"),
+            ));
+        } else {
+            let setters: Vec<&str> = ["gsettings", "feh", "nitrogen", "swww", "xfconf-query"]
+                .into_iter()
+                .filter(|tool| find_in_path(tool).is_some())
+                .collect();
+            checks.push(if setters.is_empty() {
+                Check::problem(category, "wallpaper setter", CheckStatus::Error,
+                    "none of gsettings, feh, nitrogen, swww or xfconf-query found; static wallpapers can't be set",
+                    "Install feh (X11) or swww (Wayland) with your package manager")
+            } else {
+                Check::ok(category, "wallpaper setter", format!("found {}", setters.join(", ")))
+            });
+        }
+
+        checks.push(tool_check(
+            "secret-tool",
+            CheckStatus::Warning,
+            "API keys are kept in an encrypted file instead of the keyring",
+            install_hint("secret-tool"),
+        ));
+
+        if config.app.desktop_notifications {
+            checks.push(tool_check(
+                "notify-send",
+                CheckStatus::Warning,
+                "desktop notifications are turned on but can't be shown",
+                install_hint("notify-send"),
+            ));
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    let _ = config;
+
+    checks
+}
+
+/// Check a program on the PATH
+fn tool_check(tool: &str, status: CheckStatus, missing: &str, fix: impl Into<String>) -> Check {
+    match find_in_path(tool) {
+        Some(path) => Check::ok(
+            CheckCategory::Tools,
+            tool,
+            format!("found ({})", path.display()),
+        ),
+        None => Check::problem(
+            CheckCategory::Tools,
+            tool,
+            status,
+            format!("not found; {}", missing),
+            fix,
+        ),
+    }
+}
+
+/// Check the desktop and compositor integration
+fn check_desktop() -> Vec<Check> {
+    let mut checks = Vec::new();
+    let category = CheckCategory::Desktop;
+
+    #[cfg(target_os = "linux")]
+    {
+        let desktop = std::env::var("XDG_CURRENT_DESKTOP").unwrap_or_default();
+        let session = std::env::var("XDG_SESSION_TYPE").unwrap_or_default();
+        checks.push(Check::ok(
+            category,
+            "session",
+            format!(
+                "{} on {}",
+                if desktop.is_empty() {
+                    "unknown desktop"
+                } else {
+                    &desktop
+                },
+                if session.is_empty() {
+                    "unknown session type"
+                } else {
+                    &session
+                },
+            ),
+        ));
+
+        if crate::platform::hyprland::is_hyprland() {
+            checks.push(if command_succeeds("hyprctl", &["version"]) {
+                Check::ok(category, "Hyprland", "hyprctl can reach the compositor")
+            } else {
+                Check::problem(category, "Hyprland", CheckStatus::Error,
+                    "hyprctl can't reach the compositor",
+                    "Start Aether-Desk from inside the Hyprland session so HYPRLAND_INSTANCE_SIGNATURE is set")
+            });
+        } else {
+            if find_in_path("gsettings").is_some() {
+                checks.push(if command_succeeds("gsettings", &["list-keys", "org.gnome.desktop.background"]) {
+                    Check::ok(category, "gsettings schema", "org.gnome.desktop.background is available")
+                } else {
+                    Check::problem(category, "gsettings schema", CheckStatus::Warning,
+                        "org.gnome.desktop.background is missing, so gsettings can't set wallpapers",
+                        "Install gsettings-desktop-schemas with your package manager")
+                });
+            }
+
+            // feh and nitrogen draw on the X root window, which Wayland compositors don't show
+            let is_gnome = desktop.to_lowercase().contains("gnome");
+            if session == "wayland" && !is_gnome && find_in_path("swww").is_none() {
+                checks.push(Check::problem(
+                    category,
+                    "Wayland",
+                    CheckStatus::Warning,
+                    "feh and nitrogen only work on X11 and swww isn't installed",
+                    "Install swww and start `swww-daemon` with your compositor",
+                ));
+            }
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        checks.push(match crate::platform::windows::desktop::find_workerw() {
+            Ok(_) => Check::ok(
+                category,
+                "WorkerW",
+                "the desktop window behind the icons is reachable",
+            ),
+            Err(e) => Check::problem(
+                category,
+                "WorkerW",
+                CheckStatus::Error,
+                format!("can't reach the desktop window: {}", e),
+                "Restart Windows Explorer from the Task Manager, then try again",
+            ),
+        });
+    }
+
+    checks.push(match display::monitors() {
+        Ok(monitors) if !monitors.is_empty() => {
+            let names: Vec<String> = monitors
+                .iter()
+                .map(|m| {
+                    let primary = if m.primary { ", primary" } else { "" };
+                    format!(
+                        "{} ({}x{} at {},{}, scale {}{})",
+                        m.name, m.width, m.height, m.x, m.y, m.scale, primary
+                    )
+                })
+                .collect();
+            Check::ok(category, "monitors", names.join(", "))
+        }
+        Ok(_) => Check::problem(
+            category,
+            "monitors",
+            CheckStatus::Warning,
+            "no monitors found; per-monitor wallpapers and fit modes fall back to one image",
+            monitor_fix(),
+        ),
+        Err(e) => Check::problem(
+            category,
+            "monitors",
+            CheckStatus::Warning,
+            format!("can't list monitors: {}", e),
+            monitor_fix(),
+        ),
+    });
+
+    checks
+}
+
+/// Suggest how to make monitors visible
+fn monitor_fix() -> &'static str {
+    if cfg!(target_os = "windows") {
+        "Make sure PowerShell is available"
+    } else {
+        "Install xrandr (X11) or wlr-randr (Wayland), or run under Hyprland"
+    }
+}
+
+/// Check that the app's directories are writable
+fn check_permissions(config: &Config) -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    match Config::get_config_dir() {
+        Ok(dir) => checks.push(writable_check("config directory", &dir)),
+        Err(e) => checks.push(Check::problem(
+            CheckCategory::Permissions,
+            "config directory",
+            CheckStatus::Error,
+            e.to_string(),
+            "Set HOME (or APPDATA on Windows) to your user directory",
+        )),
+    }
+    checks.push(writable_check(
+        "downloads directory",
+        &config.get_downloads_dir(),
+    ));
+
+    #[cfg(unix)]
+    {
+        if dirs::runtime_dir().is_none() {
+            checks.push(Check::problem(
+                CheckCategory::Permissions,
+                "runtime directory",
+                CheckStatus::Warning,
+                "XDG_RUNTIME_DIR isn't set; the daemon socket is kept in the config directory",
+                "Log in through a session manager that sets XDG_RUNTIME_DIR (e.g. systemd-logind)",
+            ));
+        }
+    }
+
+    checks
+}
+
+/// Check that a file can be created in a directory
+fn writable_check(name: &str, dir: &Path) -> Check {
+    let probe = dir.join(".aether-desk-doctor");
+    let result = fs::create_dir_all(dir)
+        .and_then(|_| fs::write(&probe, b""))
+        .and_then(|_| fs::remove_file(&probe));
+    match result {
+        Ok(()) => Check::ok(
+            CheckCategory::Permissions,
+            name,
+            format!("{} is writable", dir.display()),
+        ),
+        Err(e) => Check::problem(
+            CheckCategory::Permissions,
+            name,
+            CheckStatus::Error,
+            format!("can't write to {}: {}", dir.display(), e),
+            format!(
+                "Make {} writable by your user, e.g. `chown -R $USER` on it",
+                dir.display()
+            ),
+        ),
+    }
+}
+
+/// Check the configuration files
+///
+/// Also returns the configuration to check the rest with (the defaults when it is broken).
+fn check_configuration() -> (Config, Vec<Check>) {
+    let mut checks = Vec::new();
+    let category = CheckCategory::Configuration;
+
+    let config = match Config::get_config_path() {
+        Ok(path) if path.exists() => {
+            let (check, config) = file_check::<Config>(&path);
+            checks.push(check);
+            config.unwrap_or_default()
+        }
+        _ => {
+            checks.push(Check::ok(
+                category,
+                "config.json",
+                "not created yet; using the defaults",
+            ));
+            Config::default()
+        }
+    };
+
+    let schedule_file = config.get_schedule_file();
+    if schedule_file.exists() {
+        checks.push(file_check::<Vec<ScheduleItem>>(&schedule_file).0);
+    }
+    let widgets_file = config.get_widgets_file();
+    if widgets_file.exists() {
+        checks.push(file_check::<HashMap<String, WidgetConfig>>(&widgets_file).0);
+    }
+
+    if let Some(folder) = &config.wallpaper.auto_change.folder {
+        if !Path::new(folder).is_dir() {
+            checks.push(Check::problem(
+                category,
+                "wallpaper folder",
+                CheckStatus::Warning,
+                format!(
+                    "{} doesn't exist; auto-change and \"next\" have nothing to pick from",
+                    folder
+                ),
+                "Choose another folder under Settings or in the Wallpaper tab",
+            ));
+        }
+    }
+
+    let missing = config
+        .wallpaper
+        .favorites
+        .iter()
+        .filter(|path| !Path::new(path).exists())
+        .count();
+    if missing > 0 {
+        checks.push(Check::problem(
+            category,
+            "favorites",
+            CheckStatus::Warning,
+            format!("{} favorite(s) no longer exist", missing),
+            "Remove them from the favorites in the gallery",
+        ));
+    }
+
+    (config, checks)
+}
+
+/// Check that a JSON or TOML file parses as `T`
+fn file_check<T: serde::de::DeserializeOwned>(path: &Path) -> (Check, Option<T>) {
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("configuration");
+    match format::load::<T>(path) {
+        Ok(value) => (
+            Check::ok(
+                CheckCategory::Configuration,
+                name,
+                format!("{} is valid", path.display()),
+            ),
+            Some(value),
+        ),
+        Err(e) => (
+            Check::problem(
+                CheckCategory::Configuration,
+                name,
+                CheckStatus::Error,
+                format!("{} is invalid: {}", path.display(), e),
+                format!(
+                    "Fix the file, or move it away to start over with defaults: {}",
+                    path.display()
+                ),
+            ),
+            None,
+        ),
+    }
+}
+
+/// Find a program on the PATH
+pub fn find_in_path(program: &str) -> Option<PathBuf> {
+    find_in(program, &std::env::var_os("PATH")?)
+}
+
+/// Find a program in a PATH-style list of directories
+fn find_in(program: &str, path: &OsStr) -> Option<PathBuf> {
+    let extensions: &[&str] = if cfg!(windows) {
+        &["", ".exe", ".cmd", ".bat"]
+    } else {
+        &[""]
+    };
+    std::env::split_paths(path)
+        .flat_map(|dir| {
+            extensions
+                .iter()
+                .map(move |ext| dir.join(format!("{}{}", program, ext)))
+        })
+        .find(|candidate| candidate.is_file())
+}
+
+/// Run a command quietly and report whether it succeeded
+#[allow(dead_code)]
+fn command_succeeds(program: &str, args: &[&str]) -> bool {
+    Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_or(false, |status| status.success())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_find_in() {
+        let dir = tempdir().unwrap();
+        let name = if cfg!(windows) {
+            "wallsetter.exe"
+        } else {
+            "wallsetter"
+        };
+        fs::write(dir.path().join(name), "").unwrap();
+
+        let path = std::env::join_paths([PathBuf::from("/nonexistent"), dir.path().to_path_buf()])
+            .unwrap();
+        assert_eq!(find_in("wallsetter", &path), Some(dir.path().join(name)));
+        assert_eq!(find_in("missing", &path), None);
+    }
+
+    #[test]
+    fn test_file_check() {
+        let dir = tempdir().unwrap();
+        let valid = dir.path().join("widgets.json");
+        fs::write(&valid, "{}").unwrap();
+        let (check, parsed) = file_check::<HashMap<String, WidgetConfig>>(&valid);
+        assert_eq!(check.status, CheckStatus::Ok);
+        assert!(parsed.unwrap().is_empty());
+
+        let broken = dir.path().join("schedule.json");
+        fs::write(&broken, "[{").unwrap();
+        let (check, parsed) = file_check::<Vec<ScheduleItem>>(&broken);
+        assert_eq!(check.status, CheckStatus::Error);
+        assert!(check.fix.unwrap().contains("schedule.json"));
+        assert!(parsed.is_none());
+
+        let broken = dir.path().join("schedule.toml");
+        fs::write(&broken, "[[items]\n").unwrap();
+        let (check, _) = file_check::<Vec<ScheduleItem>>(&broken);
+        assert_eq!(check.name, "schedule.toml");
+        assert_eq!(check.status, CheckStatus::Error);
+    }
+
+    #[test]
+    fn test_writable_check() {
+        let dir = tempdir().unwrap();
+        let check = writable_check("downloads directory", &dir.path().join("downloads"));
+        assert_eq!(check.status, CheckStatus::Ok);
+        assert!(fs::read_dir(dir.path().join("downloads"))
+            .unwrap()
+            .next()
+            .is_none());
+    }
+}
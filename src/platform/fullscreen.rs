@@ -0,0 +1,211 @@
+//! Detection of focused fullscreen applications
+//!
+//! Windows compares the foreground window with its monitor. On Linux the
+//! compositor is asked on Hyprland and Sway, and X11 sessions check the
+//! active window's `_NET_WM_STATE`. Other Wayland compositors don't expose
+//! the focused window, so nothing is reported there.
+use serde_json::Value;
+use std::process::Command;
+
+/// Check whether the focused window is a fullscreen application
+pub fn fullscreen_app_focused() -> bool {
+    #[cfg(windows)]
+    {
+        foreground_window_fullscreen()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        if crate::platform::hyprland::is_hyprland() {
+            command_json("hyprctl", &["activewindow", "-j"])
+                .map_or(false, |window| hyprland_fullscreen(&window))
+        } else if std::env::var_os("SWAYSOCK").is_some() {
+            command_json("swaymsg", &["-t", "get_tree"])
+                .map_or(false, |tree| sway_fullscreen(&tree))
+        } else if std::env::var_os("DISPLAY").is_some() {
+            x11_active_window_fullscreen()
+        } else {
+            false
+        }
+    }
+
+    #[cfg(not(any(windows, target_os = "linux")))]
+    {
+        false
+    }
+}
+
+/// Run a command and parse its JSON output
+#[allow(dead_code)]
+fn command_json(program: &str, args: &[&str]) -> Option<Value> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    serde_json::from_slice(&output.stdout).ok()
+}
+
+/// Check `hyprctl activewindow -j` output
+///
+/// Hyprland 0.42 and later report the fullscreen state as a number
+/// (0 none, 1 maximized, 2 fullscreen); older versions as a bool with a
+/// separate `fullscreenMode` (1 = maximized).
+#[allow(dead_code)]
+fn hyprland_fullscreen(window: &Value) -> bool {
+    match &window["fullscreen"] {
+        Value::Bool(fullscreen) => *fullscreen && window["fullscreenMode"].as_u64() != Some(1),
+        Value::Number(state) => state.as_u64().map_or(false, |state| state >= 2),
+        _ => false,
+    }
+}
+
+/// Check `swaymsg -t get_tree` output for a focused fullscreen node
+#[allow(dead_code)]
+fn sway_fullscreen(node: &Value) -> bool {
+    if node["focused"].as_bool() == Some(true) {
+        return node["fullscreen_mode"]
+            .as_u64()
+            .map_or(false, |mode| mode > 0);
+    }
+
+    ["nodes", "floating_nodes"]
+        .iter()
+        .filter_map(|key| node[key].as_array())
+        .flatten()
+        .any(sway_fullscreen)
+}
+
+/// Check the `_NET_WM_STATE` of the active X11 window
+#[allow(dead_code)]
+fn x11_active_window_fullscreen() -> bool {
+    let active = match Command::new("xprop")
+        .args(["-root", "_NET_ACTIVE_WINDOW"])
+        .output()
+    {
+        Ok(output) if output.status.success() => {
+            String::from_utf8_lossy(&output.stdout).to_string()
+        }
+        _ => return false,
+    };
+    // "_NET_ACTIVE_WINDOW(WINDOW): window id # 0x4a00003"
+    let window = match active.rsplit(' ').next().map(str::trim) {
+        Some(window) if window.starts_with("0x") && window != "0x0" => window.to_string(),
+        _ => return false,
+    };
+
+    Command::new("xprop")
+        .args(["-id", &window, "_NET_WM_STATE"])
+        .output()
+        .map_or(false, |output| {
+            String::from_utf8_lossy(&output.stdout).contains("_NET_WM_STATE_FULLSCREEN")
+        })
+}
+
+/// Check whether a window rectangle covers a monitor rectangle
+///
+/// Rectangles are (left, top, right, bottom).
+#[allow(dead_code)]
+fn covers(window: (i32, i32, i32, i32), monitor: (i32, i32, i32, i32)) -> bool {
+    window.0 <= monitor.0 && window.1 <= monitor.1 && window.2 >= monitor.2 && window.3 >= monitor.3
+}
+
+/// Check whether the foreground window covers its whole monitor
+#[cfg(windows)]
+fn foreground_window_fullscreen() -> bool {
+    use windows::Win32::Foundation::RECT;
+    use windows::Win32::Graphics::Gdi::{
+        GetMonitorInfoW, MonitorFromWindow, MONITORINFO, MONITOR_DEFAULTTONEAREST,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetClassNameW, GetDesktopWindow, GetForegroundWindow, GetShellWindow, GetWindowRect,
+    };
+
+    unsafe {
+        let window = GetForegroundWindow();
+        if window.0 == 0 || window == GetDesktopWindow() || window == GetShellWindow() {
+            return false;
+        }
+
+        // Clicking the desktop focuses these, and they always cover the monitor
+        let mut class = [0u16; 64];
+        let length = GetClassNameW(window, &mut class).max(0) as usize;
+        let class = String::from_utf16_lossy(&class[..length]);
+        if class == "WorkerW" || class == "Progman" {
+            return false;
+        }
+
+        let mut rect = RECT::default();
+        if GetWindowRect(window, &mut rect).is_err() {
+            return false;
+        }
+
+        let mut info = MONITORINFO {
+            cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+            ..Default::default()
+        };
+        if !GetMonitorInfoW(
+            MonitorFromWindow(window, MONITOR_DEFAULTTONEAREST),
+            &mut info,
+        )
+        .as_bool()
+        {
+            return false;
+        }
+
+        let monitor = info.rcMonitor;
+        covers(
+            (rect.left, rect.top, rect.right, rect.bottom),
+            (monitor.left, monitor.top, monitor.right, monitor.bottom),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_hyprland_fullscreen() {
+        assert!(hyprland_fullscreen(
+            &json!({ "fullscreen": 2, "fullscreenClient": 2 })
+        ));
+        assert!(!hyprland_fullscreen(&json!({ "fullscreen": 1 })));
+        assert!(hyprland_fullscreen(
+            &json!({ "fullscreen": true, "fullscreenMode": 0 })
+        ));
+        assert!(!hyprland_fullscreen(
+            &json!({ "fullscreen": true, "fullscreenMode": 1 })
+        ));
+        assert!(!hyprland_fullscreen(&json!({ "fullscreen": false })));
+        assert!(!hyprland_fullscreen(&json!({})));
+    }
+
+    #[test]
+    fn test_sway_fullscreen() {
+        let tree = |mode| {
+            json!({
+                "focused": false,
+                "nodes": [{
+                    "focused": false,
+                    "nodes": [
+                        { "focused": false, "fullscreen_mode": 0, "nodes": [] },
+                        { "focused": true, "fullscreen_mode": mode, "nodes": [] },
+                    ],
+                    "floating_nodes": [],
+                }],
+            })
+        };
+        assert!(sway_fullscreen(&tree(1)));
+        assert!(!sway_fullscreen(&tree(0)));
+    }
+
+    #[test]
+    fn test_covers() {
+        let monitor = (0, 0, 1920, 1080);
+        assert!(covers((0, 0, 1920, 1080), monitor));
+        assert!(covers((-8, -8, 1928, 1088), monitor));
+        assert!(!covers((0, 0, 1920, 1040), monitor));
+        assert!(!covers((1920, 0, 3840, 1080), monitor));
+    }
+}
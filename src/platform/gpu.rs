@@ -0,0 +1,345 @@
+//! GPU selection for wallpaper players
+//!
+//! Hybrid-graphics laptops render on the integrated GPU unless a program asks
+//! for the discrete one. Shader and web players are started with the PRIME
+//! render offload variables on Linux and a per-program DirectX GPU preference
+//! on Windows. Players built on wgpu read `WGPU_POWER_PREF` as well.
+use crate::core::config::GpuPreference;
+#[cfg(target_os = "linux")]
+use std::path::Path;
+use std::process::Command;
+use std::sync::Mutex;
+
+/// Preference applied to players started from now on
+static PREFERENCE: Mutex<GpuPreference> = Mutex::new(GpuPreference::Default);
+
+/// Graphics adapter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GpuAdapter {
+    /// Vendor and model, e.g. "NVIDIA (10de:2520)"
+    pub name: String,
+
+    /// Whether this is NVIDIA hardware, which has its own offload variables
+    pub nvidia: bool,
+
+    /// Whether the firmware drew on this adapter at boot, which on laptops is the integrated GPU
+    pub boot_vga: bool,
+}
+
+/// Set the GPU preference for players started from now on
+pub fn set_preference(preference: GpuPreference) {
+    *PREFERENCE.lock().unwrap() = preference;
+}
+
+/// Get the GPU preference
+pub fn preference() -> GpuPreference {
+    *PREFERENCE.lock().unwrap()
+}
+
+/// Get the graphics adapters
+pub fn adapters() -> Vec<GpuAdapter> {
+    #[cfg(target_os = "linux")]
+    {
+        read_drm_cards(Path::new("/sys/class/drm"))
+    }
+
+    #[cfg(windows)]
+    {
+        video_controllers()
+    }
+
+    #[cfg(not(any(windows, target_os = "linux")))]
+    {
+        Vec::new()
+    }
+}
+
+/// Get the load of the busiest GPU in percent
+///
+/// AMD GPUs report it in sysfs on Linux, NVIDIA GPUs through `nvidia-smi`.
+/// Returns None when no GPU reports its load.
+pub fn busy_percent() -> Option<f32> {
+    #[cfg(target_os = "linux")]
+    if let Some(busy) = read_busy_percent(Path::new("/sys/class/drm")) {
+        return Some(busy);
+    }
+
+    let output = Command::new("nvidia-smi")
+        .args([
+            "--query-gpu=utilization.gpu",
+            "--format=csv,noheader,nounits",
+        ])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.trim().parse::<f32>().ok())
+        .reduce(f32::max)
+}
+
+/// Apply the GPU preference to a player before it is started
+pub fn apply(command: &mut Command) {
+    let preference = preference();
+
+    #[cfg(target_os = "linux")]
+    let nvidia_offload = preference == GpuPreference::Discrete
+        && adapters()
+            .iter()
+            .any(|adapter| adapter.nvidia && !adapter.boot_vga);
+
+    #[cfg(not(target_os = "linux"))]
+    let nvidia_offload = false;
+
+    for (key, value) in environment(preference, nvidia_offload) {
+        command.env(key, value);
+    }
+
+    #[cfg(windows)]
+    set_program_preference(command.get_program(), preference);
+}
+
+/// Get the environment variables that select a GPU
+///
+/// NVIDIA's driver ignores `DRI_PRIME` and needs its own variables to render
+/// on the discrete GPU.
+fn environment(
+    preference: GpuPreference,
+    nvidia_offload: bool,
+) -> Vec<(&'static str, &'static str)> {
+    match preference {
+        GpuPreference::Default => Vec::new(),
+        GpuPreference::Integrated => vec![("DRI_PRIME", "0"), ("WGPU_POWER_PREF", "low")],
+        GpuPreference::Discrete if nvidia_offload => vec![
+            ("__NV_PRIME_RENDER_OFFLOAD", "1"),
+            ("__GLX_VENDOR_LIBRARY_NAME", "nvidia"),
+            ("__VK_LAYER_NV_optimus", "NVIDIA_only"),
+            ("WGPU_POWER_PREF", "high"),
+        ],
+        GpuPreference::Discrete => vec![("DRI_PRIME", "1"), ("WGPU_POWER_PREF", "high")],
+    }
+}
+
+/// Read the graphics cards in a sysfs `drm` directory
+#[cfg(target_os = "linux")]
+fn read_drm_cards(dir: &Path) -> Vec<GpuAdapter> {
+    let read = |device: &Path, name: &str| {
+        std::fs::read_to_string(device.join(name))
+            .ok()
+            .map(|value| value.trim().to_string())
+    };
+
+    drm_cards(dir)
+        .iter()
+        .filter_map(|card| {
+            let device = card.join("device");
+            let vendor = read(&device, "vendor")?;
+            let model = read(&device, "device").unwrap_or_default();
+            let vendor_name = match vendor.as_str() {
+                "0x8086" => "Intel",
+                "0x10de" => "NVIDIA",
+                "0x1002" => "AMD",
+                _ => "Unknown",
+            };
+            Some(GpuAdapter {
+                name: format!(
+                    "{} ({}:{})",
+                    vendor_name,
+                    vendor.trim_start_matches("0x"),
+                    model.trim_start_matches("0x")
+                ),
+                nvidia: vendor == "0x10de",
+                boot_vga: read(&device, "boot_vga").as_deref() == Some("1"),
+            })
+        })
+        .collect()
+}
+
+/// Read the highest `gpu_busy_percent` of the cards in a sysfs `drm` directory
+#[cfg(target_os = "linux")]
+fn read_busy_percent(dir: &Path) -> Option<f32> {
+    drm_cards(dir)
+        .iter()
+        .filter_map(|card| {
+            std::fs::read_to_string(card.join("device").join("gpu_busy_percent")).ok()
+        })
+        .filter_map(|value| value.trim().parse::<f32>().ok())
+        .reduce(f32::max)
+}
+
+/// List the cards in a sysfs `drm` directory
+#[cfg(target_os = "linux")]
+fn drm_cards(dir: &Path) -> Vec<std::path::PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut cards: Vec<_> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        // Connectors such as "card0-DP-1" sit next to the cards
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix("card"))
+                .map_or(false, |number| number.chars().all(|c| c.is_ascii_digit()))
+        })
+        .collect();
+    cards.sort();
+    cards
+}
+
+/// Get the video controllers from Windows
+#[cfg(windows)]
+fn video_controllers() -> Vec<GpuAdapter> {
+    let output = Command::new("powershell")
+        .args([
+            "-NoProfile",
+            "-Command",
+            "Get-CimInstance Win32_VideoController | ForEach-Object { $_.Name }",
+        ])
+        .output();
+
+    match output {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| GpuAdapter {
+                name: name.to_string(),
+                nvidia: name.contains("NVIDIA"),
+                boot_vga: false,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Store the per-program GPU preference that DirectX and WebView2 read
+///
+/// Windows looks the preference up by the full executable path, so players
+/// that aren't on the PATH keep the system choice.
+#[cfg(windows)]
+fn set_program_preference(program: &std::ffi::OsStr, preference: GpuPreference) {
+    use log::debug;
+
+    const KEY: &str = r"HKCU\Software\Microsoft\DirectX\UserGpuPreferences";
+
+    let Some(executable) = Command::new("where.exe")
+        .arg(program)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .next()
+                .map(|line| line.trim().to_string())
+        })
+    else {
+        debug!(
+            "Can't set a GPU preference for {:?}: not found on the PATH",
+            program
+        );
+        return;
+    };
+
+    let mut reg = Command::new("reg");
+    match preference {
+        GpuPreference::Default => reg.args(["delete", KEY, "/v", &executable, "/f"]),
+        GpuPreference::Integrated => reg.args([
+            "add",
+            KEY,
+            "/v",
+            &executable,
+            "/t",
+            "REG_SZ",
+            "/d",
+            "GpuPreference=1;",
+            "/f",
+        ]),
+        GpuPreference::Discrete => reg.args([
+            "add",
+            KEY,
+            "/v",
+            &executable,
+            "/t",
+            "REG_SZ",
+            "/d",
+            "GpuPreference=2;",
+            "/f",
+        ]),
+    };
+    // Deleting a preference that was never set fails, which is fine
+    if let Err(e) = reg.output() {
+        debug!("Failed to set the GPU preference for {}: {}", executable, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_environment() {
+        assert!(environment(GpuPreference::Default, true).is_empty());
+        assert!(environment(GpuPreference::Integrated, false).contains(&("DRI_PRIME", "0")));
+        assert!(environment(GpuPreference::Discrete, false).contains(&("DRI_PRIME", "1")));
+
+        let nvidia = environment(GpuPreference::Discrete, true);
+        assert!(nvidia.contains(&("__NV_PRIME_RENDER_OFFLOAD", "1")));
+        assert!(!nvidia.iter().any(|(key, _)| *key == "DRI_PRIME"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_read_drm_cards() {
+        let dir = tempfile::tempdir().unwrap();
+        let card = |name: &str, files: &[(&str, &str)]| {
+            let device = dir.path().join(name).join("device");
+            std::fs::create_dir_all(&device).unwrap();
+            for (file, value) in files {
+                std::fs::write(device.join(file), format!("{}\n", value)).unwrap();
+            }
+        };
+
+        card(
+            "card0",
+            &[
+                ("vendor", "0x8086"),
+                ("device", "0x9a49"),
+                ("boot_vga", "1"),
+            ],
+        );
+        card(
+            "card1",
+            &[
+                ("vendor", "0x10de"),
+                ("device", "0x2520"),
+                ("boot_vga", "0"),
+            ],
+        );
+        card("card0-eDP-1", &[("vendor", "0x8086")]);
+
+        let adapters = read_drm_cards(dir.path());
+        assert_eq!(
+            adapters,
+            vec![
+                GpuAdapter {
+                    name: "Intel (8086:9a49)".to_string(),
+                    nvidia: false,
+                    boot_vga: true
+                },
+                GpuAdapter {
+                    name: "NVIDIA (10de:2520)".to_string(),
+                    nvidia: true,
+                    boot_vga: false
+                },
+            ]
+        );
+        assert_eq!(read_busy_percent(dir.path()), None);
+
+        card("card2", &[("vendor", "0x1002"), ("gpu_busy_percent", "37")]);
+        card("card3", &[("vendor", "0x1002"), ("gpu_busy_percent", "12")]);
+        assert_eq!(read_busy_percent(dir.path()), Some(37.0));
+    }
+}
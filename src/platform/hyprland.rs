@@ -1,7 +1,10 @@
-use crate::core::AppResult;
+use crate::core::config::{FitMode, TransitionConfig};
+use crate::core::{AppError, AppResult};
+use crate::experiments::effects;
+use crate::platform::linux;
 use crate::platform::WallpaperManager;
 use async_trait::async_trait;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::Arc;
 
@@ -10,95 +13,196 @@ pub struct HyprlandWallpaperManager;
 
 #[async_trait]
 impl WallpaperManager for HyprlandWallpaperManager {
-async fn set_static_wallpaper(&self, path: &Path) -> AppResult<()> {
+    async fn set_static_wallpaper(&self, path: &Path) -> AppResult<()> {
         // Convert path to string
         let path_str = path.to_string_lossy().to_string();
-        
+
         // Get list of monitors
         let monitors = self.get_monitors()?;
-        
+
         if monitors.is_empty() {
             return Err("No monitors detected".into());
         }
-        
+
         // Set wallpaper for each monitor
         for monitor in monitors {
-            let output = Command::new("hyprctl")
-                .args(&["hyprpaper", "wallpaper", &format!("{},", monitor), &path_str])
-                .output()
-                .map_err(|e| format!("Failed to execute hyprctl: {}", e))?;
-            
-            if !output.status.success() {
-                let error = String::from_utf8_lossy(&output.stderr);
-                return Err(format!("Failed to set wallpaper for monitor {}: {}", monitor, error).into());
-            }
+            self.set_monitor_wallpaper(&monitor, &path_str, "")?;
         }
-        
+
         Ok(())
     }
-    
-async fn set_video_wallpaper(&self, _path: &Path) -> AppResult<()> {
+
+    async fn set_static_wallpaper_fitted(&self, path: &Path, mode: FitMode) -> AppResult<()> {
+        // hyprpaper takes the mode as a prefix on the path
+        let prefix = match mode {
+            FitMode::Fit => "contain:",
+            FitMode::Tile => "tile:",
+            _ => "",
+        };
+        let path_str = path.to_string_lossy().to_string();
+
+        let monitors = self.get_monitors()?;
+        if monitors.is_empty() {
+            return Err("No monitors detected".into());
+        }
+
+        for monitor in monitors {
+            self.set_monitor_wallpaper(&monitor, &path_str, prefix)?;
+        }
+
+        Ok(())
+    }
+
+    async fn set_monitor_wallpapers(&self, wallpapers: &[(String, PathBuf)]) -> AppResult<()> {
+        for (monitor, path) in wallpapers {
+            self.set_monitor_wallpaper(monitor, &path.to_string_lossy(), "")?;
+        }
+
+        Ok(())
+    }
+
+    fn native_fit_modes(&self) -> Vec<FitMode> {
+        vec![FitMode::Fill, FitMode::Fit, FitMode::Tile]
+    }
+
+    // hyprpaper reads fewer formats than swww
+    fn image_formats(&self) -> &'static [&'static str] {
+        &["png", "jpg", "jpeg", "webp"]
+    }
+
+    // swww, when it runs instead of hyprpaper, animates the change itself
+    fn native_transitions(&self) -> bool {
+        linux::swww_running()
+    }
+
+    async fn set_static_wallpaper_transition(
+        &self,
+        path: &Path,
+        transition: &TransitionConfig,
+    ) -> AppResult<()> {
+        linux::set_with_swww(&path.canonicalize()?, &effects::swww_args(transition))
+    }
+
+    async fn set_video_wallpaper(&self, _path: &Path) -> AppResult<()> {
         // TODO: Implement video wallpaper support for Hyprland
-        Err("Video wallpapers not yet supported for Hyprland".into())
+        Err(AppError::BackendUnavailable {
+            backend: "Hyprland".to_string(),
+            reason: "video wallpapers are not supported yet".to_string(),
+        })
     }
-    
-async fn set_web_wallpaper(&self, _url: &str) -> AppResult<()> {
+
+    async fn set_web_wallpaper(&self, _url: &str) -> AppResult<()> {
         // TODO: Implement web wallpaper support for Hyprland
-        Err("Web wallpapers not yet supported for Hyprland".into())
+        Err(AppError::BackendUnavailable {
+            backend: "Hyprland".to_string(),
+            reason: "web wallpapers are not supported yet".to_string(),
+        })
     }
-    
-async fn set_shader_wallpaper(&self, _path: &Path) -> AppResult<()> {
+
+    async fn set_shader_wallpaper(&self, _path: &Path) -> AppResult<()> {
         // TODO: Implement shader wallpaper support for Hyprland
-        Err("Shader wallpapers not yet supported for Hyprland".into())
+        Err(AppError::BackendUnavailable {
+            backend: "Hyprland".to_string(),
+            reason: "shader wallpapers are not supported yet".to_string(),
+        })
     }
-    
-async fn set_audio_wallpaper(&self, _path: &Path) -> AppResult<()> {
+
+    async fn set_audio_wallpaper(&self, _path: &Path) -> AppResult<()> {
         // TODO: Implement audio wallpaper support for Hyprland
-        Err("Audio wallpapers not yet supported for Hyprland".into())
+        Err(AppError::BackendUnavailable {
+            backend: "Hyprland".to_string(),
+            reason: "audio wallpapers are not supported yet".to_string(),
+        })
     }
-    
+
     async fn clear_wallpaper(&self) -> AppResult<()> {
         // Use hyprctl to clear the wallpaper
         let output = Command::new("hyprctl")
             .args(&["hyprpaper", "unload", "all"])
             .output()
-            .map_err(|e| format!("Failed to execute hyprctl: {}", e))?;
-        
+            .map_err(|e| AppError::from_spawn("hyprctl", e))?;
+
         if !output.status.success() {
             let error = String::from_utf8_lossy(&output.stderr);
             return Err(format!("Failed to clear wallpaper: {}", error).into());
         }
-        
+
         Ok(())
     }
-    
+
     async fn stop_wallpaper(&self) -> AppResult<()> {
         // For Hyprland, stopping wallpaper is the same as clearing it
         self.clear_wallpaper().await
     }
-    
-    async fn get_current_wallpaper(&self) -> AppResult<Option<std::path::PathBuf>> {
-        // For initial compilation, return placeholder value
-        Ok(None)
+
+    async fn system_wallpaper(&self) -> AppResult<Option<PathBuf>> {
+        let output = Command::new("hyprctl")
+            .args(&["hyprpaper", "listactive"])
+            .output()
+            .map_err(|e| AppError::from_spawn("hyprctl", e))?;
+        if !output.status.success() {
+            return Ok(None);
+        }
+        Ok(parse_active_wallpaper(&String::from_utf8_lossy(
+            &output.stdout,
+        )))
+    }
+
+    fn backend_name(&self) -> String {
+        "Hyprland via hyprctl".to_string()
     }
 }
 
 impl HyprlandWallpaperManager {
+    /// Load an image into hyprpaper and show it on a monitor
+    ///
+    /// `mode` is a hyprpaper mode prefix such as "contain:", or empty to fill.
+    fn set_monitor_wallpaper(&self, monitor: &str, path: &str, mode: &str) -> AppResult<()> {
+        let output = Command::new("hyprctl")
+            .args(&["hyprpaper", "preload", path])
+            .output()
+            .map_err(|e| AppError::from_spawn("hyprctl", e))?;
+
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("Failed to preload wallpaper {}: {}", path, error).into());
+        }
+
+        let output = Command::new("hyprctl")
+            .args(&[
+                "hyprpaper",
+                "wallpaper",
+                &format!("{},", monitor),
+                &format!("{}{}", mode, path),
+            ])
+            .output()
+            .map_err(|e| AppError::from_spawn("hyprctl", e))?;
+
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(
+                format!("Failed to set wallpaper for monitor {}: {}", monitor, error).into(),
+            );
+        }
+
+        Ok(())
+    }
+
     /// Get a list of available monitors
     fn get_monitors(&self) -> AppResult<Vec<String>> {
         let output = Command::new("hyprctl")
             .args(&["monitors"])
             .output()
-            .map_err(|e| format!("Failed to execute hyprctl: {}", e))?;
-        
+            .map_err(|e| AppError::from_spawn("hyprctl", e))?;
+
         if !output.status.success() {
             let error = String::from_utf8_lossy(&output.stderr);
             return Err(format!("Failed to get monitors: {}", error).into());
         }
-        
+
         let output_str = String::from_utf8_lossy(&output.stdout);
         let mut monitors = Vec::new();
-        
+
         // Parse the output to extract monitor names
         for line in output_str.lines() {
             if line.contains("Monitor") && line.contains("(") {
@@ -106,13 +210,13 @@ impl HyprlandWallpaperManager {
                 if let Some(start) = line.find("Monitor ") {
                     let start = start + 8; // Skip "Monitor "
                     if let Some(end) = line[start..].find(" ") {
-                        let monitor_name = line[start..start+end].to_string();
+                        let monitor_name = line[start..start + end].to_string();
                         monitors.push(monitor_name);
                     }
                 }
             }
         }
-        
+
         Ok(monitors)
     }
 }
@@ -125,4 +229,28 @@ pub fn is_hyprland() -> bool {
 #[allow(dead_code)]
 pub fn create_hyprland_wallpaper_manager() -> Arc<dyn WallpaperManager + Send + Sync> {
     Arc::new(HyprlandWallpaperManager)
-} 
\ No newline at end of file
+}
+/// Get the first image in `hyprctl hyprpaper listactive` output, e.g. "eDP-1 = /home/me/sky.jpg"
+fn parse_active_wallpaper(output: &str) -> Option<PathBuf> {
+    output
+        .lines()
+        .filter_map(|line| line.split_once(" = "))
+        .map(|(_, path)| path.trim())
+        .find(|path| !path.is_empty())
+        .map(PathBuf::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_active_wallpaper() {
+        let output = "eDP-1 = /home/me/sky.jpg\nHDMI-A-1 = /home/me/sea.png\n";
+        assert_eq!(
+            parse_active_wallpaper(output),
+            Some(PathBuf::from("/home/me/sky.jpg"))
+        );
+        assert_eq!(parse_active_wallpaper("no wallpapers active\n"), None);
+    }
+}
@@ -0,0 +1,119 @@
+//! Time since the last user input
+//!
+//! Windows reports it with `GetLastInputInfo`. On Linux GNOME's idle monitor
+//! and KDE's screensaver service are asked over D-Bus, and X11 sessions fall
+//! back to `xprintidle`. Other Wayland compositors don't expose it.
+#[cfg(target_os = "linux")]
+use std::process::Command;
+use std::time::Duration;
+
+/// Get how long the user has not touched the keyboard or mouse
+///
+/// Returns None when the platform doesn't report it.
+pub fn idle_time() -> Option<Duration> {
+    #[cfg(windows)]
+    {
+        last_input_age()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let dbus = [
+            (
+                "org.gnome.Mutter.IdleMonitor",
+                "/org/gnome/Mutter/IdleMonitor/Core",
+                "org.gnome.Mutter.IdleMonitor.GetIdletime",
+            ),
+            (
+                "org.freedesktop.ScreenSaver",
+                "/org/freedesktop/ScreenSaver",
+                "org.freedesktop.ScreenSaver.GetSessionIdleTime",
+            ),
+        ];
+        for (dest, object, method) in dbus {
+            let output = Command::new("gdbus")
+                .args([
+                    "call",
+                    "--session",
+                    "--dest",
+                    dest,
+                    "--object-path",
+                    object,
+                    "--method",
+                    method,
+                ])
+                .output();
+            if let Ok(output) = output {
+                if output.status.success() {
+                    if let Some(millis) =
+                        parse_gdbus_millis(&String::from_utf8_lossy(&output.stdout))
+                    {
+                        return Some(Duration::from_millis(millis));
+                    }
+                }
+            }
+        }
+
+        if std::env::var_os("DISPLAY").is_none() {
+            return None;
+        }
+        let output = Command::new("xprintidle").output().ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .parse()
+            .ok()
+            .map(Duration::from_millis)
+    }
+
+    #[cfg(not(any(windows, target_os = "linux")))]
+    {
+        None
+    }
+}
+
+/// Parse an idle time reply in milliseconds, e.g. "(uint64 12345,)" or "(uint32 12345,)"
+#[allow(dead_code)]
+fn parse_gdbus_millis(output: &str) -> Option<u64> {
+    let (_, value) = output.split_once("int")?;
+    let value = value
+        .trim_start_matches(|c: char| c.is_ascii_digit())
+        .trim_start();
+    let value: String = value.chars().take_while(|c| c.is_ascii_digit()).collect();
+    value.parse().ok()
+}
+
+/// Get the time since the last input event
+#[cfg(windows)]
+fn last_input_age() -> Option<Duration> {
+    use windows::Win32::System::SystemInformation::GetTickCount;
+    use windows::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+
+    let mut info = LASTINPUTINFO {
+        cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+        dwTime: 0,
+    };
+    unsafe {
+        if !GetLastInputInfo(&mut info).as_bool() {
+            return None;
+        }
+        // Both tick counts wrap after 49 days
+        Some(Duration::from_millis(
+            GetTickCount().wrapping_sub(info.dwTime) as u64,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_gdbus_millis() {
+        assert_eq!(parse_gdbus_millis("(uint64 12345,)\n"), Some(12345));
+        assert_eq!(parse_gdbus_millis("(uint32 0,)"), Some(0));
+        assert_eq!(parse_gdbus_millis("Error: GDBus.Error"), None);
+    }
+}
@@ -1,20 +1,20 @@
+use crate::core::config::{FitMode, TransitionConfig};
+use crate::core::span::{self, SpanLayout};
+use crate::core::{AppError, AppResult, PlayerSlot};
+use crate::experiments::effects;
+use crate::platform::{gpu, WallpaperManager};
 use async_trait::async_trait;
-use crate::core::{AppError, AppResult};
-use crate::platform::WallpaperManager;
 use log::{debug, error, info};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::sync::Arc;
-use tokio::sync::Mutex;
 
 /// Linux wallpaper manager
 pub struct LinuxWallpaperManager {
-    /// Current wallpaper path
-    current_wallpaper: Arc<Mutex<Option<String>>>,
-    
     /// Desktop environment
-    #[allow(dead_code)]
     desktop_env: String,
+
+    /// Player of the web, shader or audio wallpaper
+    player: PlayerSlot,
 }
 
 #[allow(dead_code)]
@@ -23,140 +23,178 @@ impl LinuxWallpaperManager {
     pub fn new() -> AppResult<Self> {
         let desktop_env = Self::detect_desktop_environment();
         info!("Detected desktop environment: {}", desktop_env);
-        
+
         Ok(Self {
-            current_wallpaper: Arc::new(Mutex::new(None)),
             desktop_env,
+            player: PlayerSlot::new(),
         })
     }
-    
+
     /// Initialize the Linux wallpaper manager
     pub fn init() -> AppResult<()> {
         info!("Initializing Linux wallpaper manager");
         Ok(())
     }
-    
+
     /// Detect the current desktop environment
     fn detect_desktop_environment() -> String {
         // Check for common desktop environment variables
         if let Ok(env) = std::env::var("XDG_CURRENT_DESKTOP") {
             return env;
         }
-        
+
         if let Ok(env) = std::env::var("DESKTOP_SESSION") {
             return env;
         }
-        
+
         if let Ok(_env) = std::env::var("GNOME_DESKTOP_SESSION_ID") {
             return "GNOME".to_string();
         }
-        
+
         if let Ok(_env) = std::env::var("KDE_FULL_SESSION") {
             return "KDE".to_string();
         }
-        
+
         // Default to generic
         "generic".to_string()
     }
-    
+
     /// Set wallpaper using feh (works on most X11 environments)
     fn set_wallpaper_with_feh(&self, path: &Path) -> AppResult<()> {
         let path_str = path.to_string_lossy().to_string();
         debug!("Setting wallpaper with feh: {}", path_str);
-        
+
         let output = Command::new("feh")
             .args(&["--bg-fill", &path_str])
             .output()?;
-        
+
         if !output.status.success() {
             let error = String::from_utf8_lossy(&output.stderr);
             return Err(AppError::PlatformError(format!("feh failed: {}", error)));
         }
-        
+
         Ok(())
     }
-    
+
     /// Set wallpaper using gsettings (works on GNOME)
     fn set_wallpaper_with_gsettings(&self, path: &Path) -> AppResult<()> {
         let path_str = path.to_string_lossy().to_string();
         debug!("Setting wallpaper with gsettings: {}", path_str);
-        
+
         let output = Command::new("gsettings")
-            .args(&["set", "org.gnome.desktop.background", "picture-uri", &format!("file://{}", path_str)])
+            .args(&[
+                "set",
+                "org.gnome.desktop.background",
+                "picture-uri",
+                &format!("file://{}", path_str),
+            ])
             .output()?;
-        
+
         if !output.status.success() {
             let error = String::from_utf8_lossy(&output.stderr);
-            return Err(AppError::PlatformError(format!("gsettings failed: {}", error)));
+            return Err(AppError::PlatformError(format!(
+                "gsettings failed: {}",
+                error
+            )));
         }
-        
+
         Ok(())
     }
-    
+
     /// Set wallpaper using xfconf-query (works on XFCE)
     fn set_wallpaper_with_xfconf(&self, path: &Path) -> AppResult<()> {
         let path_str = path.to_string_lossy().to_string();
         debug!("Setting wallpaper with xfconf-query: {}", path_str);
-        
+
         let output = Command::new("xfconf-query")
-            .args(&["-c", "xfce4-desktop", "-p", "/backdrop/screen0/monitor0/image-path", "-s", &path_str])
+            .args(&[
+                "-c",
+                "xfce4-desktop",
+                "-p",
+                "/backdrop/screen0/monitor0/image-path",
+                "-s",
+                &path_str,
+            ])
             .output()?;
-        
+
         if !output.status.success() {
             let error = String::from_utf8_lossy(&output.stderr);
-            return Err(AppError::PlatformError(format!("xfconf-query failed: {}", error)));
+            return Err(AppError::PlatformError(format!(
+                "xfconf-query failed: {}",
+                error
+            )));
         }
-        
+
         Ok(())
     }
-    
+
     /// Set wallpaper using swww (works on Wayland with Hyprland)
     fn set_wallpaper_with_swww(&self, path: &Path) -> AppResult<()> {
-        let path_str = path.to_string_lossy().to_string();
-        debug!("Setting wallpaper with swww: {}", path_str);
-        
-        let output = Command::new("swww")
-            .args(&["img", &path_str])
-            .output()?;
-        
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            return Err(AppError::PlatformError(format!("swww failed: {}", error)));
-        }
-        
-        Ok(())
+        set_with_swww(path, &[])
     }
 }
 
+/// Check whether swww's daemon is running to animate wallpaper changes
+pub fn swww_running() -> bool {
+    Command::new("swww")
+        .arg("query")
+        .output()
+        .map_or(false, |output| output.status.success())
+}
+
+/// Set a wallpaper through swww, with extra flags such as its transition
+pub fn set_with_swww(path: &Path, args: &[String]) -> AppResult<()> {
+    let path_str = path.to_string_lossy().to_string();
+    debug!("Setting wallpaper with swww: {}", path_str);
+
+    let output = Command::new("swww")
+        .args(&["img", &path_str])
+        .args(args)
+        .output()
+        .map_err(|e| AppError::from_spawn("swww", e))?;
+
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(AppError::PlatformError(format!("swww failed: {}", error)));
+    }
+
+    Ok(())
+}
+
 #[async_trait]
 impl WallpaperManager for LinuxWallpaperManager {
     async fn set_static_wallpaper(&self, path: &Path) -> AppResult<()> {
         info!("Setting static wallpaper: {}", path.display());
-        
+
         // Convert path to absolute path
         let path = path.canonicalize()?;
-        
+
         // Try different methods to set the wallpaper
         let mut success = false;
-        
+
         // Try using gsettings (GNOME)
         let output = Command::new("gsettings")
-            .args(&["set", "org.gnome.desktop.background", "picture-uri", &format!("file://{}", path.to_string_lossy().to_string())])
+            .args(&[
+                "set",
+                "org.gnome.desktop.background",
+                "picture-uri",
+                &format!("file://{}", path.to_string_lossy().to_string()),
+            ])
             .output();
-        
+
         if let Ok(output) = output {
             if output.status.success() {
                 success = true;
                 info!("Static wallpaper set successfully using gsettings");
             }
         }
-        
+
         // Try using feh
         if !success {
             let output = Command::new("feh")
                 .args(&["--bg-fill", &path.to_string_lossy().to_string()])
                 .output();
-            
+
             if let Ok(output) = output {
                 if output.status.success() {
                     success = true;
@@ -164,13 +202,13 @@ impl WallpaperManager for LinuxWallpaperManager {
                 }
             }
         }
-        
+
         // Try using nitrogen
         if !success {
             let output = Command::new("nitrogen")
                 .args(&["--set-zoom-fill", &path.to_string_lossy().to_string()])
                 .output();
-            
+
             if let Ok(output) = output {
                 if output.status.success() {
                     success = true;
@@ -178,25 +216,161 @@ impl WallpaperManager for LinuxWallpaperManager {
                 }
             }
         }
-        
+
+        if !success {
+            error!("Failed to set static wallpaper using any method");
+            return Err(AppError::BackendUnavailable {
+                backend: self.desktop_env.clone(),
+                reason: "none of gsettings, feh or nitrogen could set the wallpaper".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    async fn set_static_wallpaper_fitted(&self, path: &Path, mode: FitMode) -> AppResult<()> {
+        info!("Setting static wallpaper ({:?}): {}", mode, path.display());
+
+        let path = path.canonicalize()?;
+        let path_str = path.to_string_lossy().to_string();
+
+        // Try using gsettings (GNOME), which takes the mode as a separate key
+        let options = Command::new("gsettings")
+            .args(&[
+                "set",
+                "org.gnome.desktop.background",
+                "picture-options",
+                gsettings_picture_option(mode),
+            ])
+            .output();
+        let mut success = matches!(options, Ok(output) if output.status.success())
+            && self.set_wallpaper_with_gsettings(&path).is_ok();
+
+        // Try using feh, then nitrogen
+        for (program, option) in [
+            ("feh", feh_option(mode)),
+            ("nitrogen", nitrogen_option(mode)),
+        ] {
+            if success {
+                break;
+            }
+            if let Ok(output) = Command::new(program).args(&[option, &path_str]).output() {
+                success = output.status.success();
+            }
+        }
+
         if !success {
             error!("Failed to set static wallpaper using any method");
-            return Err(crate::core::AppError::WallpaperError("Failed to set static wallpaper".to_string()));
+            return Err(AppError::BackendUnavailable {
+                backend: self.desktop_env.clone(),
+                reason: "none of gsettings, feh or nitrogen could set the wallpaper".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn native_span(&self) -> bool {
+        true
+    }
+
+    async fn set_spanned_wallpaper(&self, path: &Path) -> AppResult<()> {
+        info!("Setting spanned wallpaper: {}", path.display());
+
+        let path = path.canonicalize()?;
+
+        // GNOME stretches one image over all monitors, as does feh ignoring Xinerama
+        let options = Command::new("gsettings")
+            .args(&[
+                "set",
+                "org.gnome.desktop.background",
+                "picture-options",
+                "spanned",
+            ])
+            .output();
+        let mut success = matches!(options, Ok(output) if output.status.success())
+            && self.set_wallpaper_with_gsettings(&path).is_ok();
+        if !success {
+            if let Ok(output) = Command::new("feh")
+                .args(["--no-xinerama", "--bg-fill"])
+                .arg(&path)
+                .output()
+            {
+                success = output.status.success();
+            }
         }
-        
-        // Update current wallpaper
-        let mut current = self.current_wallpaper.lock().await;
-        *current = Some(path.to_string_lossy().to_string());
-        
+
+        if !success {
+            error!("Failed to span the wallpaper using any method");
+            return Err(AppError::BackendUnavailable {
+                backend: self.desktop_env.clone(),
+                reason: "neither gsettings nor feh could span the wallpaper".to_string(),
+            });
+        }
+
         Ok(())
     }
-    
+
+    async fn set_monitor_wallpapers(&self, wallpapers: &[(String, PathBuf)]) -> AppResult<()> {
+        // feh gives each screen its own image, in Xinerama order
+        if wallpapers.len() > 1 {
+            let output = Command::new("feh")
+                .arg("--bg-fill")
+                .args(wallpapers.iter().map(|(_, path)| path))
+                .output();
+            if let Ok(output) = output {
+                if output.status.success() {
+                    info!("Set wallpapers for {} monitors using feh", wallpapers.len());
+                    return Ok(());
+                }
+            }
+            debug!("feh is unavailable; using the first image on all monitors");
+        }
+
+        match wallpapers.first() {
+            Some((_, path)) => self.set_static_wallpaper_fitted(path, FitMode::Fill).await,
+            None => Ok(()),
+        }
+    }
+
+    fn native_fit_modes(&self) -> Vec<FitMode> {
+        vec![
+            FitMode::Fill,
+            FitMode::Fit,
+            FitMode::Stretch,
+            FitMode::Center,
+            FitMode::Tile,
+        ]
+    }
+
+    // gdk-pixbuf and imlib2 read these everywhere; WebP needs a loader that may be missing
+    fn image_formats(&self) -> &'static [&'static str] {
+        &["png", "jpg", "jpeg", "bmp", "gif"]
+    }
+
+    fn native_transitions(&self) -> bool {
+        swww_running()
+    }
+
+    async fn set_static_wallpaper_transition(
+        &self,
+        path: &Path,
+        transition: &TransitionConfig,
+    ) -> AppResult<()> {
+        info!(
+            "Setting static wallpaper with a {:?} transition: {}",
+            transition.effect,
+            path.display()
+        );
+        set_with_swww(&path.canonicalize()?, &effects::swww_args(transition))
+    }
+
     async fn set_video_wallpaper(&self, path: &Path) -> AppResult<()> {
         info!("Setting video wallpaper: {}", path.display());
-        
+
         // Convert path to absolute path
         let path = path.canonicalize()?;
-        
+
         // Use VLC to play the video as wallpaper
         let output = Command::new("vlc")
             .args(&[
@@ -205,102 +379,164 @@ impl WallpaperManager for LinuxWallpaperManager {
                 "--loop",
                 &path.to_string_lossy().to_string(),
             ])
-            .output()?;
-        
+            .output()
+            .map_err(|e| AppError::from_spawn("vlc", e))?;
+
         if !output.status.success() {
             let error = String::from_utf8_lossy(&output.stderr);
             error!("Failed to set video wallpaper: {}", error);
             return Err(crate::core::AppError::WallpaperError(error.to_string()));
         }
-        
+
         info!("Video wallpaper set successfully");
         Ok(())
     }
-    
+
     async fn set_web_wallpaper(&self, url: &str) -> AppResult<()> {
         info!("Setting web wallpaper: {}", url);
-        
+
         // Use a web browser to display the webpage as wallpaper
-        let output = Command::new("firefox")
-            .args(&["--new-window", url])
-            .output()?;
-        
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            error!("Failed to set web wallpaper: {}", error);
-            return Err(crate::core::AppError::WallpaperError(error.to_string()));
-        }
-        
+        let mut command = Command::new("firefox");
+        command.args(&["--new-window", url]);
+        gpu::apply(&mut command);
+        self.player
+            .start(&mut command, "firefox", "web wallpaper")?;
+
         info!("Web wallpaper set successfully");
         Ok(())
     }
-    
+
     async fn set_shader_wallpaper(&self, path: &Path) -> AppResult<()> {
+        self.set_shader_wallpaper_limited(path, None).await
+    }
+
+    async fn set_shader_wallpaper_limited(
+        &self,
+        path: &Path,
+        max_fps: Option<u32>,
+    ) -> AppResult<()> {
         info!("Setting shader wallpaper: {}", path.display());
-        
+
         // Convert path to absolute path
         let path = path.canonicalize()?;
-        
+
         // Use a shader player to display the shader as wallpaper
-        let output = Command::new("shadertoy")
-            .args(&[&path.to_string_lossy().to_string()])
-            .output()?;
-        
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            error!("Failed to set shader wallpaper: {}", error);
-            return Err(crate::core::AppError::WallpaperError(error.to_string()));
+        let mut command = Command::new("shadertoy");
+        if let Some(fps) = max_fps {
+            command.arg(format!("--fps={}", fps));
         }
-        
+        gpu::apply(&mut command);
+        command.arg(&path);
+        self.player
+            .start(&mut command, "shadertoy", "shader wallpaper")?;
+
         info!("Shader wallpaper set successfully");
         Ok(())
     }
-    
+
+    async fn set_shader_wallpaper_spanned(
+        &self,
+        path: &Path,
+        max_fps: Option<u32>,
+        layout: &SpanLayout,
+    ) -> AppResult<()> {
+        info!(
+            "Setting shader wallpaper across {} monitors: {}",
+            layout.regions.len(),
+            path.display()
+        );
+
+        let path = path.canonicalize()?;
+        let start_time = span::start_time();
+        let mut commands: Vec<Command> = layout
+            .regions
+            .iter()
+            .map(|region| {
+                let mut command = Command::new("shadertoy");
+                if let Some(fps) = max_fps {
+                    command.arg(format!("--fps={}", fps));
+                }
+                gpu::apply(&mut command);
+                command
+                    .args(span::shader_args(layout, region, start_time))
+                    .arg(&path);
+                command
+            })
+            .collect();
+        self.player
+            .start_all(&mut commands, "shadertoy", "shader wallpaper")?;
+
+        info!("Shader wallpaper set successfully");
+        Ok(())
+    }
+
+    async fn set_shader_wallpaper_on(
+        &self,
+        path: &Path,
+        max_fps: Option<u32>,
+        monitor: &str,
+    ) -> AppResult<()> {
+        info!(
+            "Setting shader wallpaper on {}: {}",
+            monitor,
+            path.display()
+        );
+
+        let path = path.canonicalize()?;
+        let mut command = Command::new("shadertoy");
+        if let Some(fps) = max_fps {
+            command.arg(format!("--fps={}", fps));
+        }
+        gpu::apply(&mut command);
+        command.arg(format!("--output={}", monitor)).arg(&path);
+        self.player
+            .start_on(monitor, &mut command, "shadertoy", "shader wallpaper")?;
+
+        info!("Shader wallpaper set successfully on {}", monitor);
+        Ok(())
+    }
+
     async fn set_audio_wallpaper(&self, path: &Path) -> AppResult<()> {
         info!("Setting audio wallpaper: {}", path.display());
-        
+
         // Convert path to absolute path
         let path = path.canonicalize()?;
-        
+
         // Use a shader player with audio visualization to display the shader as wallpaper
-        let output = Command::new("shadertoy")
-            .args(&["--audio", &path.to_string_lossy().to_string()])
-            .output()?;
-        
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            error!("Failed to set audio wallpaper: {}", error);
-            return Err(crate::core::AppError::WallpaperError(error.to_string()));
-        }
-        
+        let mut command = Command::new("shadertoy");
+        command.args(&["--audio", &path.to_string_lossy().to_string()]);
+        gpu::apply(&mut command);
+        self.player
+            .start(&mut command, "shadertoy", "audio wallpaper")?;
+
         info!("Audio wallpaper set successfully");
         Ok(())
     }
-    
+
     async fn clear_wallpaper(&self) -> AppResult<()> {
         info!("Clearing wallpaper");
-        
+
         // Try different methods to clear the wallpaper
         let mut success = false;
-        
+
         // Try using gsettings (GNOME)
         let output = Command::new("gsettings")
             .args(&["set", "org.gnome.desktop.background", "picture-uri", ""])
             .output();
-        
+
         if let Ok(output) = output {
             if output.status.success() {
                 success = true;
                 info!("Wallpaper cleared successfully using gsettings");
             }
         }
-        
+
         // Try using feh
         if !success {
             let output = Command::new("feh")
                 .args(&["--bg-fill", "--no-fehbg"])
                 .output();
-            
+
             if let Ok(output) = output {
                 if output.status.success() {
                     success = true;
@@ -308,13 +544,11 @@ impl WallpaperManager for LinuxWallpaperManager {
                 }
             }
         }
-        
+
         // Try using nitrogen
         if !success {
-            let output = Command::new("nitrogen")
-                .args(&["--restore"])
-                .output();
-            
+            let output = Command::new("nitrogen").args(&["--restore"]).output();
+
             if let Ok(output) = output {
                 if output.status.success() {
                     success = true;
@@ -322,26 +556,142 @@ impl WallpaperManager for LinuxWallpaperManager {
                 }
             }
         }
-        
+
         if !success {
             error!("Failed to clear wallpaper using any method");
-            return Err(crate::core::AppError::WallpaperError("Failed to clear wallpaper".to_string()));
+            return Err(crate::core::AppError::WallpaperError(
+                "Failed to clear wallpaper".to_string(),
+            ));
         }
-        
-        // Clear current wallpaper
-        let mut current = self.current_wallpaper.lock().await;
-        *current = None;
-        
+
         Ok(())
     }
-    
-    async fn get_current_wallpaper(&self) -> AppResult<Option<std::path::PathBuf>> {
-        let current = self.current_wallpaper.lock().await;
-        Ok(current.as_ref().map(|path| std::path::PathBuf::from(path)))
+
+    async fn set_lock_screen_wallpaper(&self, path: &Path) -> AppResult<()> {
+        info!("Setting lock screen wallpaper: {}", path.display());
+        let uri = format!("file://{}", path.canonicalize()?.to_string_lossy());
+
+        // GNOME's lock screen shows the screensaver picture
+        let output = Command::new("gsettings")
+            .args(&["set", "org.gnome.desktop.screensaver", "picture-uri", &uri])
+            .output();
+        match output {
+            Ok(output) if output.status.success() => {
+                info!("Lock screen wallpaper set successfully using gsettings");
+                Ok(())
+            }
+            Ok(output) => Err(AppError::BackendUnavailable {
+                backend: self.backend_name(),
+                reason: format!(
+                    "gsettings couldn't set the lock screen: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+            }),
+            Err(e) => Err(AppError::from_spawn("gsettings", e)),
+        }
     }
-    
+
+    async fn system_wallpaper(&self) -> AppResult<Option<PathBuf>> {
+        // GNOME keeps it in gsettings, feh in a script it re-runs at login
+        let output = Command::new("gsettings")
+            .args(&["get", "org.gnome.desktop.background", "picture-uri"])
+            .output();
+        if let Ok(output) = output {
+            if output.status.success() {
+                if let Some(path) = parse_gsettings_uri(&String::from_utf8_lossy(&output.stdout)) {
+                    return Ok(Some(path));
+                }
+            }
+        }
+
+        let fehbg = dirs::home_dir().map(|home| home.join(".fehbg"));
+        Ok(fehbg
+            .and_then(|fehbg| std::fs::read_to_string(fehbg).ok())
+            .and_then(|script| parse_fehbg(&script)))
+    }
+
     async fn stop_wallpaper(&self) -> AppResult<()> {
         info!("Stopping wallpaper");
+        self.player.stop();
         self.clear_wallpaper().await
     }
+
+    fn backend_name(&self) -> String {
+        format!("{} via gsettings, feh or nitrogen", self.desktop_env)
+    }
+
+    fn player(&self) -> Option<&PlayerSlot> {
+        Some(&self.player)
+    }
+}
+
+/// GNOME `picture-options` value for a fit mode
+fn gsettings_picture_option(mode: FitMode) -> &'static str {
+    match mode {
+        FitMode::Fill | FitMode::Crop => "zoom",
+        FitMode::Fit => "scaled",
+        FitMode::Stretch => "stretched",
+        FitMode::Center => "centered",
+        FitMode::Tile => "wallpaper",
+    }
+}
+
+/// feh option for a fit mode
+fn feh_option(mode: FitMode) -> &'static str {
+    match mode {
+        FitMode::Fill | FitMode::Crop => "--bg-fill",
+        FitMode::Fit => "--bg-max",
+        FitMode::Stretch => "--bg-scale",
+        FitMode::Center => "--bg-center",
+        FitMode::Tile => "--bg-tile",
+    }
+}
+
+/// Get the file of a gsettings `picture-uri` value such as `'file:///home/me/sky.jpg'`
+fn parse_gsettings_uri(value: &str) -> Option<PathBuf> {
+    let path = value.trim().trim_matches('\'').strip_prefix("file://")?;
+    Some(PathBuf::from(path.replace("%20", " ")))
+}
+
+/// Get the image set by a `~/.fehbg` script, the last quoted argument of its feh command
+fn parse_fehbg(script: &str) -> Option<PathBuf> {
+    let command = script
+        .lines()
+        .rev()
+        .find(|line| line.trim_start().starts_with("feh"))?;
+    let end = command.rfind('\'')?;
+    let start = command[..end].rfind('\'')?;
+    Some(PathBuf::from(&command[start + 1..end]))
+}
+
+/// nitrogen option for a fit mode
+fn nitrogen_option(mode: FitMode) -> &'static str {
+    match mode {
+        FitMode::Fill | FitMode::Crop => "--set-zoom-fill",
+        FitMode::Fit => "--set-zoom",
+        FitMode::Stretch => "--set-scaled",
+        FitMode::Center => "--set-centered",
+        FitMode::Tile => "--set-tiled",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_gsettings_uri() {
+        assert_eq!(
+            parse_gsettings_uri("'file:///home/me/My%20Sky.jpg'\n"),
+            Some(PathBuf::from("/home/me/My Sky.jpg"))
+        );
+        assert_eq!(parse_gsettings_uri("''"), None);
+    }
+
+    #[test]
+    fn test_parse_fehbg() {
+        let script = "#!/bin/sh\nfeh --no-fehbg --bg-fill '/home/me/sky.jpg' \n";
+        assert_eq!(parse_fehbg(script), Some(PathBuf::from("/home/me/sky.jpg")));
+        assert_eq!(parse_fehbg("#!/bin/sh\n"), None);
+    }
 }
@@ -0,0 +1,212 @@
+//! Track playing in the desktop's media players
+//!
+//! Linux asks the MPRIS players through `playerctl`, preferring one that is
+//! playing. Windows asks the System Media Transport Controls (SMTC) through
+//! PowerShell, which saves the cover to a file as SMTC hands it out as a
+//! stream rather than an address.
+use std::path::PathBuf;
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+use std::process::Command;
+
+/// Track of a media player
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Track {
+    /// Title
+    pub title: String,
+
+    /// Artist, empty when unknown
+    pub artist: String,
+
+    /// Album art: a `file://` or `http(s)://` address, or a file path
+    pub art: Option<String>,
+
+    /// Whether it's playing rather than paused
+    pub playing: bool,
+}
+
+/// Get the track of the media player, preferring one that is playing
+///
+/// None when no player is open or the platform has no way to ask.
+pub fn now_playing() -> Option<Track> {
+    #[cfg(target_os = "linux")]
+    {
+        let output = Command::new("playerctl")
+            .args([
+                "--all-players",
+                "metadata",
+                "--format",
+                "{{status}}\t{{xesam:title}}\t{{xesam:artist}}\t{{mpris:artUrl}}",
+            ])
+            .output()
+            .ok()?;
+        parse_tracks(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+        let cover = art_dir().join("smtc-cover");
+        let script =
+            SMTC_SCRIPT.replace("{cover}", &cover.display().to_string().replace('\'', "''"));
+        let output = Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+            .ok()?;
+        parse_tracks(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+    {
+        None
+    }
+}
+
+/// Get the directory album art is saved in
+pub fn art_dir() -> PathBuf {
+    let dir = crate::core::Config::get_placement_dir().join("now-playing");
+    let _ = std::fs::create_dir_all(&dir);
+    dir
+}
+
+/// PowerShell script printing the SMTC session's track like `playerctl` does
+///
+/// The WinRT calls are asynchronous, so each is waited for through `AsTask`.
+#[cfg(target_os = "windows")]
+const SMTC_SCRIPT: &str = r#"
+Add-Type -AssemblyName System.Runtime.WindowsRuntime
+$asTask = [System.WindowsRuntimeSystemExtensions].GetMethods() | Where-Object { $_.Name -eq 'AsTask' -and $_.GetParameters().Count -eq 1 -and $_.GetParameters()[0].ParameterType.Name -eq 'IAsyncOperation`1' } | Select-Object -First 1
+function Wait-Operation($operation, $type) {
+    $task = $asTask.MakeGenericMethod($type).Invoke($null, @($operation))
+    $task.Wait(-1) | Out-Null
+    $task.Result
+}
+$managerType = [Windows.Media.Control.GlobalSystemMediaTransportControlsSessionManager, Windows.Media.Control, ContentType = WindowsRuntime]
+$manager = Wait-Operation ($managerType::RequestAsync()) $managerType
+$session = $manager.GetCurrentSession()
+if ($session -eq $null) { exit 0 }
+$properties = Wait-Operation ($session.TryGetMediaPropertiesAsync()) ([Windows.Media.Control.GlobalSystemMediaTransportControlsSessionMediaProperties, Windows.Media.Control, ContentType = WindowsRuntime])
+$art = ''
+if ($properties.Thumbnail -ne $null) {
+    $streamType = [Windows.Storage.Streams.IRandomAccessStreamWithContentType, Windows.Storage.Streams, ContentType = WindowsRuntime]
+    $stream = Wait-Operation ($properties.Thumbnail.OpenReadAsync()) $streamType
+    $reader = [Windows.Storage.Streams.DataReader, Windows.Storage.Streams, ContentType = WindowsRuntime]::new($stream)
+    $size = [uint32]$stream.Size
+    Wait-Operation ($reader.LoadAsync($size)) ([uint32]) | Out-Null
+    $bytes = New-Object byte[] $size
+    $reader.ReadBytes($bytes)
+    [System.IO.File]::WriteAllBytes('{cover}', $bytes)
+    $art = '{cover}'
+}
+$status = $session.GetPlaybackInfo().PlaybackStatus
+"$status`t$($properties.Title)`t$($properties.Artist)`t$art"
+"#;
+
+/// Read `status<TAB>title<TAB>artist<TAB>art` lines, one per player
+///
+/// The first playing track wins, then the first with a title.
+#[cfg(any(target_os = "linux", target_os = "windows", test))]
+fn parse_tracks(output: &str) -> Option<Track> {
+    let tracks: Vec<Track> = output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t').map(str::trim);
+            let status = fields.next()?;
+            let title = fields.next()?.to_string();
+            let artist = fields.next().unwrap_or_default().to_string();
+            let art = fields
+                .next()
+                .filter(|art| !art.is_empty())
+                .map(str::to_string);
+            if title.is_empty() && art.is_none() {
+                return None;
+            }
+            Some(Track {
+                title,
+                artist,
+                art,
+                playing: status.eq_ignore_ascii_case("Playing"),
+            })
+        })
+        .collect();
+    tracks
+        .iter()
+        .find(|track| track.playing)
+        .or(tracks.first())
+        .cloned()
+}
+
+/// Turn a `file://` address into a path, undoing its percent escapes
+pub fn file_url_path(url: &str) -> Option<PathBuf> {
+    let path = url.strip_prefix("file://")?;
+    // Hosts other than the local machine aren't followed
+    let path = path.strip_prefix("localhost").unwrap_or(path);
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let hex = bytes
+            .get(index + 1..index + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[index], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+    let path = String::from_utf8_lossy(&decoded).into_owned();
+    // Windows paths come as file:///C:/Music/cover.jpg
+    #[cfg(windows)]
+    let path = path
+        .strip_prefix('/')
+        .filter(|rest| rest.get(1..2) == Some(":"))
+        .map_or(path.clone(), str::to_string);
+    Some(PathBuf::from(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tracks() {
+        let output = "Paused\tIntro\tThe xx\tfile:///tmp/a.jpg\nPlaying\tTeardrop\tMassive Attack\thttps://i.scdn.co/image/ab67\n";
+        let track = parse_tracks(output).unwrap();
+        assert_eq!(
+            (track.title.as_str(), track.artist.as_str(), track.playing),
+            ("Teardrop", "Massive Attack", true)
+        );
+        assert_eq!(track.art.as_deref(), Some("https://i.scdn.co/image/ab67"));
+
+        let paused = parse_tracks("Paused\tIntro\t\t\n").unwrap();
+        assert_eq!(
+            (paused.title.as_str(), paused.art, paused.playing),
+            ("Intro", None, false)
+        );
+        // Players without a track, and no players at all
+        assert_eq!(parse_tracks("Stopped\t\t\t\n"), None);
+        assert_eq!(parse_tracks(""), None);
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn test_file_url_path() {
+        assert_eq!(
+            file_url_path("file:///home/me/My%20Music/cover%2B.jpg"),
+            Some(PathBuf::from("/home/me/My Music/cover+.jpg"))
+        );
+        assert_eq!(
+            file_url_path("file://localhost/tmp/a.png"),
+            Some(PathBuf::from("/tmp/a.png"))
+        );
+        assert_eq!(file_url_path("https://example.com/a.png"), None);
+    }
+}
@@ -0,0 +1,228 @@
+//! Wallpaper manager that records calls instead of changing the desktop
+//!
+//! Lets the scheduler, the daemon and the UI command flow be tested without a
+//! display server or any of the desktop tools the real backends run.
+use crate::core::config::FitMode;
+use crate::core::{AppError, AppResult};
+use crate::platform::WallpaperManager;
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Call made to the mock
+#[derive(Debug, Clone, PartialEq)]
+pub enum MockCall {
+    /// A static wallpaper was set, with its fit mode
+    Static(PathBuf, FitMode),
+    /// Separate images were set per monitor
+    Monitors(Vec<(String, PathBuf)>),
+    /// A video wallpaper was set
+    Video(PathBuf),
+    /// A web wallpaper was set
+    Web(String),
+    /// A shader wallpaper was set, with its frame rate limit
+    Shader(PathBuf, Option<u32>),
+    /// A shader wallpaper was set on one monitor, with its frame rate limit
+    ShaderOn(PathBuf, Option<u32>, String),
+    /// An audio wallpaper was set
+    Audio(PathBuf),
+    /// The lock screen image was set
+    LockScreen(PathBuf),
+    /// The wallpaper was cleared
+    Clear,
+    /// The wallpaper was stopped
+    Stop,
+    /// The wallpaper on one monitor was stopped
+    StopOn(String),
+    /// The player was paused
+    Pause,
+    /// The player was resumed
+    Resume,
+}
+
+/// Wallpaper manager for tests
+#[derive(Debug, Default)]
+pub struct MockWallpaperManager {
+    /// Calls made so far
+    calls: Mutex<Vec<MockCall>>,
+
+    /// Reason every call fails for, while set
+    failure: Mutex<Option<String>>,
+
+    /// Image reported as the desktop's own wallpaper
+    system_wallpaper: Mutex<Option<PathBuf>>,
+
+    /// Whether the pretend player is paused
+    paused: Mutex<bool>,
+}
+
+impl MockWallpaperManager {
+    /// Create a mock that accepts every call
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Make every following call fail, or succeed again with None
+    pub fn fail_with(&self, reason: Option<&str>) {
+        *self.failure.lock().unwrap() = reason.map(str::to_string);
+    }
+
+    /// Set the image reported as the desktop's own wallpaper
+    pub fn set_system_wallpaper(&self, path: Option<PathBuf>) {
+        *self.system_wallpaper.lock().unwrap() = path;
+    }
+
+    /// Get the calls made so far
+    pub fn calls(&self) -> Vec<MockCall> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// Get the last call
+    pub fn last_call(&self) -> Option<MockCall> {
+        self.calls.lock().unwrap().last().cloned()
+    }
+
+    /// Forget the calls made so far
+    pub fn clear_calls(&self) {
+        self.calls.lock().unwrap().clear();
+    }
+
+    /// Check whether the pretend player is paused
+    pub fn is_paused(&self) -> bool {
+        *self.paused.lock().unwrap()
+    }
+
+    /// Record a call, or fail it while a failure is set
+    fn record(&self, call: MockCall) -> AppResult<()> {
+        if let Some(reason) = self.failure.lock().unwrap().clone() {
+            return Err(AppError::BackendUnavailable {
+                backend: self.backend_name(),
+                reason,
+            });
+        }
+        self.calls.lock().unwrap().push(call);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl WallpaperManager for MockWallpaperManager {
+    async fn set_static_wallpaper(&self, path: &Path) -> AppResult<()> {
+        self.record(MockCall::Static(path.to_path_buf(), FitMode::Fill))
+    }
+
+    async fn set_static_wallpaper_fitted(&self, path: &Path, mode: FitMode) -> AppResult<()> {
+        self.record(MockCall::Static(path.to_path_buf(), mode))
+    }
+
+    async fn set_monitor_wallpapers(&self, wallpapers: &[(String, PathBuf)]) -> AppResult<()> {
+        self.record(MockCall::Monitors(wallpapers.to_vec()))
+    }
+
+    fn native_fit_modes(&self) -> Vec<FitMode> {
+        vec![FitMode::Fill]
+    }
+
+    async fn set_video_wallpaper(&self, path: &Path) -> AppResult<()> {
+        self.record(MockCall::Video(path.to_path_buf()))
+    }
+
+    async fn set_web_wallpaper(&self, url: &str) -> AppResult<()> {
+        self.record(MockCall::Web(url.to_string()))
+    }
+
+    async fn set_shader_wallpaper(&self, path: &Path) -> AppResult<()> {
+        self.record(MockCall::Shader(path.to_path_buf(), None))
+    }
+
+    async fn set_shader_wallpaper_limited(
+        &self,
+        path: &Path,
+        max_fps: Option<u32>,
+    ) -> AppResult<()> {
+        self.record(MockCall::Shader(path.to_path_buf(), max_fps))
+    }
+
+    async fn set_shader_wallpaper_on(
+        &self,
+        path: &Path,
+        max_fps: Option<u32>,
+        monitor: &str,
+    ) -> AppResult<()> {
+        self.record(MockCall::ShaderOn(
+            path.to_path_buf(),
+            max_fps,
+            monitor.to_string(),
+        ))
+    }
+
+    async fn set_audio_wallpaper(&self, path: &Path) -> AppResult<()> {
+        self.record(MockCall::Audio(path.to_path_buf()))
+    }
+
+    async fn clear_wallpaper(&self) -> AppResult<()> {
+        self.record(MockCall::Clear)
+    }
+
+    async fn stop_wallpaper(&self) -> AppResult<()> {
+        *self.paused.lock().unwrap() = false;
+        self.record(MockCall::Stop)
+    }
+
+    async fn stop_wallpaper_on(&self, monitor: &str) -> AppResult<()> {
+        self.record(MockCall::StopOn(monitor.to_string()))
+    }
+
+    async fn set_lock_screen_wallpaper(&self, path: &Path) -> AppResult<()> {
+        self.record(MockCall::LockScreen(path.to_path_buf()))
+    }
+
+    async fn system_wallpaper(&self) -> AppResult<Option<PathBuf>> {
+        Ok(self.system_wallpaper.lock().unwrap().clone())
+    }
+
+    fn backend_name(&self) -> String {
+        "Mock".to_string()
+    }
+
+    async fn pause_wallpaper(&self) -> AppResult<()> {
+        self.record(MockCall::Pause)?;
+        *self.paused.lock().unwrap() = true;
+        Ok(())
+    }
+
+    async fn resume_wallpaper(&self) -> AppResult<()> {
+        self.record(MockCall::Resume)?;
+        *self.paused.lock().unwrap() = false;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::runtime;
+
+    #[test]
+    fn test_records_and_fails_calls() {
+        let mock = MockWallpaperManager::new();
+        runtime::block_on(mock.set_web_wallpaper("https://example.com")).unwrap();
+        runtime::block_on(mock.pause_wallpaper()).unwrap();
+        assert!(mock.is_paused());
+        assert_eq!(
+            mock.calls(),
+            vec![
+                MockCall::Web("https://example.com".to_string()),
+                MockCall::Pause
+            ]
+        );
+
+        mock.fail_with(Some("display is gone"));
+        assert!(runtime::block_on(mock.stop_wallpaper()).is_err());
+        assert_eq!(mock.last_call(), Some(MockCall::Pause));
+
+        mock.fail_with(None);
+        runtime::block_on(mock.stop_wallpaper()).unwrap();
+        assert!(!mock.is_paused());
+    }
+}
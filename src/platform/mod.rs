@@ -1,38 +1,218 @@
-pub mod windows;
-pub mod linux;
+pub mod accent;
+pub mod appearance;
+pub mod audio;
+pub mod autostart;
+pub mod coverage;
+pub mod cursor;
+pub mod display;
+pub mod doctor;
+pub mod fullscreen;
+pub mod gpu;
 pub mod hyprland;
+pub mod idle;
+pub mod linux;
+pub mod media;
+pub mod mock;
+pub mod native_messaging;
+pub mod notify;
+pub mod opener;
+pub mod power;
+pub mod session;
+pub mod signals;
+pub mod topology;
+pub mod windows;
+pub mod workspace;
 
-use crate::core::AppResult;
-use std::sync::Arc;
+pub use display::{monitors, MonitorInfo};
+
+use crate::core::config::{FitMode, TransitionConfig};
+use crate::core::span::SpanLayout;
+use crate::core::{AppError, AppResult, CurrentWallpaperStore, PlayerSlot};
 use async_trait::async_trait;
+use std::sync::Arc;
 
 /// Platform-specific wallpaper manager
 #[async_trait]
 pub trait WallpaperManager: Send + Sync {
     /// Set a static wallpaper
     async fn set_static_wallpaper(&self, path: &std::path::Path) -> AppResult<()>;
-    
+
+    /// Set a static wallpaper with a fit mode from `native_fit_modes`
+    async fn set_static_wallpaper_fitted(
+        &self,
+        path: &std::path::Path,
+        _mode: FitMode,
+    ) -> AppResult<()> {
+        self.set_static_wallpaper(path).await
+    }
+
+    /// Set a separate static wallpaper per monitor, given as (monitor name, image)
+    ///
+    /// Backends that can't set per-monitor wallpapers use the first image everywhere.
+    async fn set_monitor_wallpapers(
+        &self,
+        wallpapers: &[(String, std::path::PathBuf)],
+    ) -> AppResult<()> {
+        match wallpapers.first() {
+            Some((_, path)) => self.set_static_wallpaper(path).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Fit modes the backend applies itself; other modes are rendered to images
+    fn native_fit_modes(&self) -> Vec<FitMode> {
+        vec![FitMode::Fill]
+    }
+
+    /// Image extensions the backend reads, in lowercase; other images are converted to PNG
+    fn image_formats(&self) -> &'static [&'static str] {
+        &["png", "jpg", "jpeg"]
+    }
+
+    /// Whether the backend can stretch one image across all monitors
+    ///
+    /// Other backends are given each monitor's part of a spanned image.
+    fn native_span(&self) -> bool {
+        false
+    }
+
+    /// Set an image rendered for the whole desktop across all monitors
+    async fn set_spanned_wallpaper(&self, path: &std::path::Path) -> AppResult<()> {
+        self.set_static_wallpaper(path).await
+    }
+
+    /// Whether the backend animates transitions between static wallpapers itself
+    ///
+    /// Other backends are given the frames of the transition one by one.
+    fn native_transitions(&self) -> bool {
+        false
+    }
+
+    /// Set a static wallpaper, animating the switch from the current one
+    async fn set_static_wallpaper_transition(
+        &self,
+        path: &std::path::Path,
+        _transition: &TransitionConfig,
+    ) -> AppResult<()> {
+        self.set_static_wallpaper(path).await
+    }
+
     /// Set a video wallpaper
     async fn set_video_wallpaper(&self, path: &std::path::Path) -> AppResult<()>;
-    
+
     /// Set a web wallpaper
     async fn set_web_wallpaper(&self, url: &str) -> AppResult<()>;
-    
+
     /// Set a shader wallpaper
     async fn set_shader_wallpaper(&self, path: &std::path::Path) -> AppResult<()>;
-    
+
+    /// Set a shader wallpaper rendered at no more than `max_fps` frames per second
+    ///
+    /// Backends that can't limit the frame rate render at full speed.
+    async fn set_shader_wallpaper_limited(
+        &self,
+        path: &std::path::Path,
+        _max_fps: Option<u32>,
+    ) -> AppResult<()> {
+        self.set_shader_wallpaper(path).await
+    }
+
+    /// Set a shader wallpaper spanning all monitors, with a player per monitor
+    ///
+    /// Backends that can't run several players show the shader as usual.
+    async fn set_shader_wallpaper_spanned(
+        &self,
+        path: &std::path::Path,
+        max_fps: Option<u32>,
+        _layout: &SpanLayout,
+    ) -> AppResult<()> {
+        self.set_shader_wallpaper_limited(path, max_fps).await
+    }
+
+    /// Set a shader wallpaper on one monitor, beside the wallpapers on the others
+    ///
+    /// Backends that can't run a player per monitor refuse.
+    async fn set_shader_wallpaper_on(
+        &self,
+        _path: &std::path::Path,
+        _max_fps: Option<u32>,
+        _monitor: &str,
+    ) -> AppResult<()> {
+        Err(AppError::BackendUnavailable {
+            backend: self.backend_name(),
+            reason: "it can't show a shader on a single monitor".to_string(),
+        })
+    }
+
     /// Set an audio wallpaper
     async fn set_audio_wallpaper(&self, path: &std::path::Path) -> AppResult<()>;
-    
+
     /// Clear the current wallpaper
     async fn clear_wallpaper(&self) -> AppResult<()>;
-    
+
     /// Stop the current wallpaper
     async fn stop_wallpaper(&self) -> AppResult<()>;
-    
+
+    /// Stop the player of the wallpaper on one monitor, leaving the others
+    async fn stop_wallpaper_on(&self, monitor: &str) -> AppResult<()> {
+        if let Some(player) = self.player() {
+            player.stop_on(monitor);
+        }
+        Ok(())
+    }
+
+    /// Set the image shown on the lock screen
+    ///
+    /// Backends that can't change the lock screen refuse.
+    async fn set_lock_screen_wallpaper(&self, _path: &std::path::Path) -> AppResult<()> {
+        Err(AppError::BackendUnavailable {
+            backend: self.backend_name(),
+            reason: "it can't change the lock screen".to_string(),
+        })
+    }
+
+    /// Get the image the desktop shows, which may have been set outside the app
+    ///
+    /// Returns None when the backend can't tell.
+    async fn system_wallpaper(&self) -> AppResult<Option<std::path::PathBuf>> {
+        Ok(None)
+    }
+
     /// Get the current wallpaper path
     #[allow(dead_code)]
-    async fn get_current_wallpaper(&self) -> AppResult<Option<std::path::PathBuf>>;
+    async fn get_current_wallpaper(&self) -> AppResult<Option<std::path::PathBuf>> {
+        Ok(CurrentWallpaperStore::shared().path())
+    }
+
+    /// Describe the backend used to set wallpapers (e.g. "Hyprland via hyprctl")
+    fn backend_name(&self) -> String;
+
+    /// Get the player the backend runs web, shader and audio wallpapers in
+    fn player(&self) -> Option<&PlayerSlot> {
+        None
+    }
+
+    /// Pause the wallpaper's player
+    async fn pause_wallpaper(&self) -> AppResult<()> {
+        match self.player() {
+            Some(player) => player.suspend(),
+            None => Err(AppError::BackendUnavailable {
+                backend: self.backend_name(),
+                reason: "it doesn't run wallpaper players".to_string(),
+            }),
+        }
+    }
+
+    /// Resume the wallpaper's player
+    async fn resume_wallpaper(&self) -> AppResult<()> {
+        match self.player() {
+            Some(player) => player.resume(),
+            None => Err(AppError::BackendUnavailable {
+                backend: self.backend_name(),
+                reason: "it doesn't run wallpaper players".to_string(),
+            }),
+        }
+    }
 }
 
 /// Create a platform-specific wallpaper manager
@@ -41,7 +221,7 @@ pub fn create_wallpaper_manager() -> AppResult<Arc<dyn WallpaperManager + Send +
     {
         Ok(Arc::new(windows::WindowsWallpaperManager::new()?))
     }
-    
+
     #[cfg(target_os = "linux")]
     {
         // Check if running on Hyprland
@@ -51,9 +231,9 @@ pub fn create_wallpaper_manager() -> AppResult<Arc<dyn WallpaperManager + Send +
             Ok(Arc::new(linux::LinuxWallpaperManager::new()?))
         }
     }
-    
+
     #[cfg(not(any(target_os = "windows", target_os = "linux")))]
     {
         Err(crate::core::AppError::UnsupportedPlatform.into())
     }
-} 
\ No newline at end of file
+}
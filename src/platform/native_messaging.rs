@@ -0,0 +1,360 @@
+//! Native messaging host registration for the browser extension
+//!
+//! Browsers find native messaging hosts through a JSON manifest naming the
+//! program to start and the extensions allowed to start it: a file in a
+//! per-browser folder on Linux and macOS, a registry key pointing at the file
+//! on Windows. Browsers start the program with arguments of their own, so the
+//! manifest points at a small launcher running `aether-desk native-host`.
+//! Chromium-based browsers need the extension's ID, which differs for every
+//! unpacked copy; Firefox uses the ID set in the extension's manifest.
+use crate::core::{AppError, AppResult, Config};
+use log::info;
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+#[cfg(target_os = "windows")]
+use std::process::Command;
+
+/// Name of the native messaging host, as the extension asks for it
+pub const HOST_NAME: &str = "dev.aether_desk";
+
+/// ID of the extension in Firefox
+pub const FIREFOX_EXTENSION_ID: &str = "browser@aether-desk.dev";
+
+/// Browser family, which decides the manifest's format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Family {
+    /// Chrome, Chromium, Edge and Brave
+    Chromium,
+    /// Firefox
+    Firefox,
+}
+
+/// Where each browser looks for manifests: a folder, or a registry key on Windows
+#[cfg(target_os = "linux")]
+fn locations() -> Vec<(Family, PathBuf)> {
+    let (Some(config), Some(home)) = (dirs::config_dir(), dirs::home_dir()) else {
+        return Vec::new();
+    };
+    vec![
+        (
+            Family::Chromium,
+            config.join("google-chrome/NativeMessagingHosts"),
+        ),
+        (
+            Family::Chromium,
+            config.join("chromium/NativeMessagingHosts"),
+        ),
+        (
+            Family::Chromium,
+            config.join("microsoft-edge/NativeMessagingHosts"),
+        ),
+        (
+            Family::Chromium,
+            config.join("BraveSoftware/Brave-Browser/NativeMessagingHosts"),
+        ),
+        (
+            Family::Firefox,
+            home.join(".mozilla/native-messaging-hosts"),
+        ),
+    ]
+}
+
+/// Where each browser looks for manifests: a folder, or a registry key on Windows
+#[cfg(target_os = "macos")]
+fn locations() -> Vec<(Family, PathBuf)> {
+    let Some(support) = dirs::data_dir() else {
+        return Vec::new();
+    };
+    vec![
+        (
+            Family::Chromium,
+            support.join("Google/Chrome/NativeMessagingHosts"),
+        ),
+        (
+            Family::Chromium,
+            support.join("Chromium/NativeMessagingHosts"),
+        ),
+        (
+            Family::Chromium,
+            support.join("Microsoft Edge/NativeMessagingHosts"),
+        ),
+        (
+            Family::Chromium,
+            support.join("BraveSoftware/Brave-Browser/NativeMessagingHosts"),
+        ),
+        (
+            Family::Firefox,
+            support.join("Mozilla/NativeMessagingHosts"),
+        ),
+    ]
+}
+
+/// Where each browser looks for manifests: a folder, or a registry key on Windows
+#[cfg(target_os = "windows")]
+fn locations() -> Vec<(Family, PathBuf)> {
+    [
+        (
+            Family::Chromium,
+            r"HKCU\Software\Google\Chrome\NativeMessagingHosts",
+        ),
+        (
+            Family::Chromium,
+            r"HKCU\Software\Chromium\NativeMessagingHosts",
+        ),
+        (
+            Family::Chromium,
+            r"HKCU\Software\Microsoft\Edge\NativeMessagingHosts",
+        ),
+        (
+            Family::Chromium,
+            r"HKCU\Software\BraveSoftware\Brave-Browser\NativeMessagingHosts",
+        ),
+        (
+            Family::Firefox,
+            r"HKCU\Software\Mozilla\NativeMessagingHosts",
+        ),
+    ]
+    .into_iter()
+    .map(|(family, key)| (family, PathBuf::from(key).join(HOST_NAME)))
+    .collect()
+}
+
+/// Where each browser looks for manifests: a folder, or a registry key on Windows
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn locations() -> Vec<(Family, PathBuf)> {
+    Vec::new()
+}
+
+/// Get the folder holding the launcher, and the manifests on Windows
+fn host_dir() -> AppResult<PathBuf> {
+    Config::get_config_dir()
+        .map(|dir| dir.join("browser"))
+        .map_err(|e| AppError::ConfigError(e.to_string()))
+}
+
+/// Register the native messaging host with every browser
+///
+/// Chromium-based browsers are only registered when extension IDs are given.
+/// Returns where the manifests went.
+pub fn install(chrome_ids: &[String]) -> AppResult<Vec<PathBuf>> {
+    if let Some(id) = chrome_ids.iter().find(|id| !is_chrome_id(id)) {
+        return Err(AppError::ConfigError(format!(
+            "{} isn't an extension ID; copy it from the browser's extensions page",
+            id
+        )));
+    }
+
+    let dir = host_dir()?;
+    fs::create_dir_all(&dir)?;
+    let exe = std::env::current_exe()
+        .map_err(|e| AppError::PlatformError(format!("Failed to locate executable: {}", e)))?;
+    let launcher = dir.join(if cfg!(windows) {
+        "native-host.bat"
+    } else {
+        "native-host.sh"
+    });
+    fs::write(&launcher, launcher_script(&exe))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&launcher, fs::Permissions::from_mode(0o755))?;
+    }
+
+    let mut installed = Vec::new();
+    for (family, location) in locations() {
+        if family == Family::Chromium && chrome_ids.is_empty() {
+            continue;
+        }
+        let manifest = serde_json::to_string_pretty(&manifest(family, &launcher, chrome_ids))?;
+        installed.push(write_manifest(family, &location, &dir, &manifest)?);
+    }
+
+    info!(
+        "Registered the browser extension's native messaging host in {} place(s)",
+        installed.len()
+    );
+    Ok(installed)
+}
+
+/// Remove the native messaging host from every browser
+pub fn uninstall() -> AppResult<()> {
+    for (_, location) in locations() {
+        #[cfg(target_os = "windows")]
+        {
+            let key = location.display().to_string();
+            if Command::new("reg")
+                .args(["query", &key])
+                .output()
+                .map_or(false, |output| output.status.success())
+            {
+                let output = Command::new("reg").args(["delete", &key, "/f"]).output()?;
+                if !output.status.success() {
+                    let error = String::from_utf8_lossy(&output.stderr);
+                    return Err(AppError::PlatformError(format!(
+                        "Failed to remove {}: {}",
+                        key, error
+                    )));
+                }
+            }
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            let path = location.join(format!("{}.json", HOST_NAME));
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+        }
+    }
+
+    let dir = host_dir()?;
+    if dir.exists() {
+        fs::remove_dir_all(dir)?;
+    }
+    info!("Removed the browser extension's native messaging host");
+    Ok(())
+}
+
+/// Check whether the native messaging host was registered
+pub fn is_installed() -> bool {
+    host_dir().map_or(false, |dir| {
+        dir.join("native-host.sh").exists() || dir.join("native-host.bat").exists()
+    })
+}
+
+/// Write a manifest where a browser looks for it, returning where that is
+#[cfg(not(target_os = "windows"))]
+fn write_manifest(
+    _family: Family,
+    location: &Path,
+    _dir: &Path,
+    manifest: &str,
+) -> AppResult<PathBuf> {
+    fs::create_dir_all(location)?;
+    let path = location.join(format!("{}.json", HOST_NAME));
+    fs::write(&path, manifest)?;
+    Ok(path)
+}
+
+/// Write a manifest where a browser looks for it, returning where that is
+///
+/// The registry key points at a file beside the launcher, one per family.
+#[cfg(target_os = "windows")]
+fn write_manifest(
+    family: Family,
+    location: &Path,
+    dir: &Path,
+    manifest: &str,
+) -> AppResult<PathBuf> {
+    let path = dir.join(match family {
+        Family::Chromium => "chromium.json",
+        Family::Firefox => "firefox.json",
+    });
+    fs::write(&path, manifest)?;
+
+    let key = location.display().to_string();
+    let output = Command::new("reg")
+        .args([
+            "add",
+            &key,
+            "/ve",
+            "/t",
+            "REG_SZ",
+            "/d",
+            &path.display().to_string(),
+            "/f",
+        ])
+        .output()?;
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(AppError::PlatformError(format!(
+            "Failed to add {}: {}",
+            key, error
+        )));
+    }
+    Ok(location.to_path_buf())
+}
+
+/// Build the manifest of the host for a browser family
+fn manifest(family: Family, launcher: &Path, chrome_ids: &[String]) -> Value {
+    let mut manifest = json!({
+        "name": HOST_NAME,
+        "description": "Aether-Desk: set images from the browser as the wallpaper",
+        "path": launcher,
+        "type": "stdio",
+    });
+    match family {
+        Family::Chromium => {
+            let origins: Vec<String> = chrome_ids
+                .iter()
+                .map(|id| format!("chrome-extension://{}/", id))
+                .collect();
+            manifest["allowed_origins"] = json!(origins);
+        }
+        Family::Firefox => manifest["allowed_extensions"] = json!([FIREFOX_EXTENSION_ID]),
+    }
+    manifest
+}
+
+/// Build the launcher, which drops the browser's arguments on the app's command line
+fn launcher_script(exe: &Path) -> String {
+    if cfg!(windows) {
+        format!("@echo off\r\n\"{}\" native-host %*\r\n", exe.display())
+    } else {
+        format!(
+            "#!/bin/sh\nexec '{}' native-host \"$@\"\n",
+            exe.display().to_string().replace('\'', r"'\''")
+        )
+    }
+}
+
+/// Check that an ID looks like a Chromium extension ID: 32 letters from a to p
+fn is_chrome_id(id: &str) -> bool {
+    id.len() == 32 && id.bytes().all(|byte| (b'a'..=b'p').contains(&byte))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest() {
+        let launcher = Path::new("/home/me/.config/aether-desk/browser/native-host.sh");
+        let ids = vec!["abcdefghijklmnopabcdefghijklmnop".to_string()];
+
+        let chrome = manifest(Family::Chromium, launcher, &ids);
+        assert_eq!(chrome["name"], HOST_NAME);
+        assert_eq!(chrome["type"], "stdio");
+        assert_eq!(
+            chrome["path"],
+            "/home/me/.config/aether-desk/browser/native-host.sh"
+        );
+        assert_eq!(
+            chrome["allowed_origins"],
+            json!(["chrome-extension://abcdefghijklmnopabcdefghijklmnop/"])
+        );
+        assert!(chrome.get("allowed_extensions").is_none());
+
+        let firefox = manifest(Family::Firefox, launcher, &ids);
+        assert_eq!(firefox["allowed_extensions"], json!([FIREFOX_EXTENSION_ID]));
+        assert!(firefox.get("allowed_origins").is_none());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_launcher_script() {
+        let script = launcher_script(Path::new("/opt/it's/aether-desk"));
+        assert_eq!(
+            script,
+            "#!/bin/sh\nexec '/opt/it'\\''s/aether-desk' native-host \"$@\"\n"
+        );
+    }
+
+    #[test]
+    fn test_is_chrome_id() {
+        assert!(is_chrome_id("abcdefghijklmnopabcdefghijklmnop"));
+        assert!(!is_chrome_id("abcdefghijklmnopabcdefghijklmnoz"));
+        assert!(!is_chrome_id("abc"));
+    }
+}
@@ -0,0 +1,70 @@
+//! Desktop notifications
+//!
+//! Linux uses `notify-send`; Windows shows a toast through PowerShell.
+use crate::core::{AppError, AppResult};
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+use std::process::Command;
+
+/// Application name shown by the notification server
+const APP_NAME: &str = "Aether-Desk";
+
+/// Show a desktop notification
+pub fn send(title: &str, body: &str, is_error: bool) -> AppResult<()> {
+    #[cfg(target_os = "linux")]
+    {
+        let urgency = if is_error { "critical" } else { "normal" };
+        let status = Command::new("notify-send")
+            .args(["--app-name", APP_NAME, "--urgency", urgency, title, body])
+            .status()
+            .map_err(|e| AppError::PlatformError(format!("Failed to run notify-send: {}", e)))?;
+
+        if !status.success() {
+            return Err(AppError::PlatformError(format!(
+                "notify-send exited with {}",
+                status
+            )));
+        }
+        Ok(())
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let _ = is_error;
+        let script = format!(
+            "[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] > $null; \
+             $template = [Windows.UI.Notifications.ToastNotificationManager]::GetTemplateContent([Windows.UI.Notifications.ToastTemplateType]::ToastText02); \
+             $text = $template.GetElementsByTagName('text'); \
+             $text.Item(0).AppendChild($template.CreateTextNode('{}')) > $null; \
+             $text.Item(1).AppendChild($template.CreateTextNode('{}')) > $null; \
+             [Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier('{}').Show([Windows.UI.Notifications.ToastNotification]::new($template))",
+            powershell_escape(title),
+            powershell_escape(body),
+            APP_NAME,
+        );
+
+        let status = Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+            .status()
+            .map_err(|e| AppError::PlatformError(format!("Failed to run PowerShell: {}", e)))?;
+
+        if !status.success() {
+            return Err(AppError::PlatformError(format!(
+                "PowerShell exited with {}",
+                status
+            )));
+        }
+        Ok(())
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    {
+        let _ = (title, body, is_error, APP_NAME);
+        Err(AppError::UnsupportedPlatform)
+    }
+}
+
+/// Escape a string for a single-quoted PowerShell literal
+#[cfg(target_os = "windows")]
+fn powershell_escape(value: &str) -> String {
+    value.replace('\'', "''").replace(['\r', '\n'], " ")
+}
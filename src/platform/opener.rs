@@ -0,0 +1,24 @@
+//! Opening files in the user's default application
+//!
+//! Linux uses `xdg-open`, Windows Explorer and macOS `open`.
+use crate::core::{AppError, AppResult};
+use std::path::Path;
+use std::process::Command;
+
+/// Open a file or folder with the application the desktop associates with it
+pub fn open(path: &Path) -> AppResult<()> {
+    let program = if cfg!(target_os = "windows") {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+
+    // The opener hands the file over and exits; its status isn't meaningful on Windows
+    Command::new(program)
+        .arg(path)
+        .spawn()
+        .map_err(|e| AppError::from_spawn(program, e))?;
+    Ok(())
+}
@@ -0,0 +1,181 @@
+//! Battery and AC power status
+//!
+//! Linux reads the power supplies under `/sys/class/power_supply`, Windows
+//! asks `GetSystemPowerStatus`. Machines without a battery report nothing.
+#[cfg(target_os = "linux")]
+use std::path::Path;
+
+/// Power source of a machine with a battery
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PowerStatus {
+    /// Whether the machine runs from its battery
+    pub on_battery: bool,
+
+    /// Battery charge in percent, when known
+    pub percent: Option<u8>,
+}
+
+impl PowerStatus {
+    /// Check whether power should be saved
+    ///
+    /// True on battery once the charge is at or below `threshold` percent;
+    /// a threshold of 100 saves power whenever the battery is in use.
+    pub fn should_save(&self, threshold: u8) -> bool {
+        self.on_battery && self.percent.map_or(true, |percent| percent <= threshold)
+    }
+}
+
+/// Get the power status, or `None` when there is no battery
+pub fn power_status() -> Option<PowerStatus> {
+    #[cfg(windows)]
+    {
+        system_power_status()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        read_power_supplies(Path::new("/sys/class/power_supply"))
+    }
+
+    #[cfg(not(any(windows, target_os = "linux")))]
+    {
+        None
+    }
+}
+
+/// Read the power supplies in a sysfs `power_supply` directory
+///
+/// The machine runs from its battery when a battery is discharging and no
+/// mains or USB supply is online.
+#[cfg(target_os = "linux")]
+fn read_power_supplies(dir: &Path) -> Option<PowerStatus> {
+    let read = |supply: &Path, name: &str| {
+        std::fs::read_to_string(supply.join(name))
+            .ok()
+            .map(|value| value.trim().to_string())
+    };
+
+    let mut battery: Option<PowerStatus> = None;
+    let mut external_online = false;
+
+    for entry in std::fs::read_dir(dir).ok()?.flatten() {
+        let supply = entry.path();
+        match read(&supply, "type").as_deref() {
+            Some("Battery") => {
+                // Peripherals such as mice report batteries with a scope of "Device"
+                if read(&supply, "scope").as_deref() == Some("Device") {
+                    continue;
+                }
+                let discharging = read(&supply, "status").as_deref() == Some("Discharging");
+                let percent =
+                    read(&supply, "capacity").and_then(|capacity| capacity.parse::<u8>().ok());
+                let status = battery.get_or_insert(PowerStatus {
+                    on_battery: false,
+                    percent: None,
+                });
+                status.on_battery |= discharging;
+                status.percent = match (status.percent, percent) {
+                    (Some(a), Some(b)) => Some(a.min(b)),
+                    (a, b) => a.or(b),
+                };
+            }
+            Some("Mains") | Some("USB") => {
+                external_online |= read(&supply, "online").as_deref() == Some("1");
+            }
+            _ => {}
+        }
+    }
+
+    battery.map(|status| PowerStatus {
+        on_battery: status.on_battery && !external_online,
+        ..status
+    })
+}
+
+/// Get the power status from Windows
+#[cfg(windows)]
+fn system_power_status() -> Option<PowerStatus> {
+    use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    /// `BatteryFlag` bit set when there is no system battery
+    const NO_BATTERY: u8 = 128;
+
+    let mut status = SYSTEM_POWER_STATUS::default();
+    unsafe { GetSystemPowerStatus(&mut status) }.ok()?;
+    if status.BatteryFlag & NO_BATTERY != 0 || status.BatteryFlag == 255 {
+        return None;
+    }
+
+    Some(PowerStatus {
+        on_battery: status.ACLineStatus == 0,
+        percent: (status.BatteryLifePercent <= 100).then_some(status.BatteryLifePercent),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_save() {
+        let battery = |on_battery, percent| PowerStatus {
+            on_battery,
+            percent,
+        };
+        assert!(battery(true, Some(80)).should_save(100));
+        assert!(!battery(true, Some(80)).should_save(30));
+        assert!(battery(true, Some(30)).should_save(30));
+        assert!(battery(true, None).should_save(30));
+        assert!(!battery(false, Some(10)).should_save(100));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_read_power_supplies() {
+        let dir = tempfile::tempdir().unwrap();
+        let supply = |name: &str, files: &[(&str, &str)]| {
+            let path = dir.path().join(name);
+            std::fs::create_dir_all(&path).unwrap();
+            for (file, value) in files {
+                std::fs::write(path.join(file), format!("{}\n", value)).unwrap();
+            }
+        };
+
+        assert_eq!(read_power_supplies(dir.path()), None);
+
+        supply("AC", &[("type", "Mains"), ("online", "0")]);
+        supply(
+            "BAT0",
+            &[
+                ("type", "Battery"),
+                ("status", "Discharging"),
+                ("capacity", "42"),
+            ],
+        );
+        supply(
+            "hidpp_battery_0",
+            &[
+                ("type", "Battery"),
+                ("scope", "Device"),
+                ("status", "Discharging"),
+                ("capacity", "5"),
+            ],
+        );
+        assert_eq!(
+            read_power_supplies(dir.path()),
+            Some(PowerStatus {
+                on_battery: true,
+                percent: Some(42)
+            })
+        );
+
+        supply("AC", &[("online", "1")]);
+        assert_eq!(
+            read_power_supplies(dir.path()),
+            Some(PowerStatus {
+                on_battery: false,
+                percent: Some(42)
+            })
+        );
+    }
+}
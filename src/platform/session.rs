@@ -0,0 +1,37 @@
+//! Desktop session state
+//!
+//! Linux asks logind for the session's `LockedHint`; Windows treats a running
+//! `LogonUI.exe`, which draws the lock screen, as locked.
+#[cfg(any(windows, target_os = "linux"))]
+use std::process::Command;
+
+/// Check whether the screen is locked
+///
+/// Returns false when the lock state can't be determined.
+pub fn is_locked() -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        let session = std::env::var("XDG_SESSION_ID").unwrap_or_else(|_| "self".to_string());
+        Command::new("loginctl")
+            .args(["show-session", &session, "--property=LockedHint", "--value"])
+            .output()
+            .map_or(false, |output| {
+                output.status.success() && String::from_utf8_lossy(&output.stdout).trim() == "yes"
+            })
+    }
+
+    #[cfg(windows)]
+    {
+        Command::new("tasklist")
+            .args(["/FI", "IMAGENAME eq LogonUI.exe", "/NH"])
+            .output()
+            .map_or(false, |output| {
+                String::from_utf8_lossy(&output.stdout).contains("LogonUI.exe")
+            })
+    }
+
+    #[cfg(not(any(windows, target_os = "linux")))]
+    {
+        false
+    }
+}
@@ -0,0 +1,43 @@
+//! Termination requests from the terminal or the session
+//!
+//! Ctrl-C is caught on every platform. On Unix SIGTERM, which service managers
+//! and logout send, and SIGHUP, sent when the terminal closes, are caught too.
+use crate::core::runtime;
+use log::{info, warn};
+
+/// Run `shutdown` once the process is asked to terminate
+///
+/// The handler replaces the default of exiting at once, so `shutdown` must
+/// make the process exit.
+pub fn on_termination(shutdown: impl FnOnce() + Send + 'static) {
+    runtime::shared().spawn(async move {
+        match termination().await {
+            Ok(signal) => {
+                info!("Received {}, shutting down", signal);
+                // Blocking work such as stopping players may follow
+                let _ = tokio::task::spawn_blocking(shutdown).await;
+            }
+            Err(e) => warn!("Failed to listen for termination signals: {}", e),
+        }
+    });
+}
+
+/// Wait for a termination signal and get its name
+#[cfg(unix)]
+async fn termination() -> std::io::Result<&'static str> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut terminate = signal(SignalKind::terminate())?;
+    let mut hangup = signal(SignalKind::hangup())?;
+    tokio::select! {
+        result = tokio::signal::ctrl_c() => result.map(|_| "Ctrl-C"),
+        _ = terminate.recv() => Ok("SIGTERM"),
+        _ = hangup.recv() => Ok("SIGHUP"),
+    }
+}
+
+/// Wait for a termination signal and get its name
+#[cfg(not(unix))]
+async fn termination() -> std::io::Result<&'static str> {
+    tokio::signal::ctrl_c().await.map(|_| "Ctrl-C")
+}
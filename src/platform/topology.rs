@@ -0,0 +1,113 @@
+//! Detection of the connected monitor setup
+//!
+//! A setup ("laptop only", "docked with two externals", ...) is identified by
+//! the names and resolutions of its monitors, so plugging in the same monitors
+//! gives the same setup again. Monitors are polled, as no platform reports
+//! hotplugs in a way shared by all compositors.
+use crate::core::{AppError, AppResult};
+use crate::platform::display::{self, MonitorInfo};
+use log::{debug, info};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Time between checks of the connected monitors
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Identify a monitor setup, independent of the order monitors are listed in
+pub fn topology(monitors: &[MonitorInfo]) -> String {
+    let mut names: Vec<String> = monitors
+        .iter()
+        .map(|monitor| format!("{} {}x{}", monitor.name, monitor.width, monitor.height))
+        .collect();
+    names.sort();
+    names.join(" + ")
+}
+
+/// Get the connected monitor setup, if monitors can be queried
+pub fn current_topology() -> Option<String> {
+    display::monitors()
+        .ok()
+        .filter(|monitors| !monitors.is_empty())
+        .map(|monitors| topology(&monitors))
+}
+
+/// Watches for monitors being connected or disconnected
+#[derive(Default)]
+pub struct TopologyWatcher {
+    /// Whether the watch thread is running
+    is_running: Arc<Mutex<bool>>,
+
+    /// Watch thread handle
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl TopologyWatcher {
+    /// Create a stopped watcher
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check whether the watcher is running
+    pub fn is_running(&self) -> bool {
+        *self.is_running.lock().unwrap()
+    }
+
+    /// Start watching, calling `on_change` with each monitor setup changed to
+    pub fn start(&mut self, on_change: impl Fn(String) + Send + 'static) -> AppResult<()> {
+        if self.is_running() {
+            return Ok(());
+        }
+        let Some(mut last) = current_topology() else {
+            return Err(AppError::Other(
+                "The connected monitors can't be detected on this desktop".to_string(),
+            ));
+        };
+
+        *self.is_running.lock().unwrap() = true;
+        let is_running = self.is_running.clone();
+        self.thread = Some(thread::spawn(move || {
+            while *is_running.lock().unwrap() {
+                thread::sleep(POLL_INTERVAL);
+                // Monitors briefly missing while reconfiguring aren't a setup of their own
+                if let Some(topology) = current_topology().filter(|topology| *topology != last) {
+                    last = topology.clone();
+                    on_change(topology);
+                }
+            }
+            debug!("Monitor setup watcher stopped");
+        }));
+
+        info!("Watching for monitor setup changes");
+        Ok(())
+    }
+
+    /// Stop watching
+    pub fn stop(&mut self) {
+        *self.is_running.lock().unwrap() = false;
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_topology_ignores_monitor_order() {
+        let laptop = MonitorInfo::new("eDP-1", 1920, 1080);
+        let external = MonitorInfo::new("DP-1", 2560, 1440);
+
+        assert_eq!(topology(&[laptop.clone()]), "eDP-1 1920x1080");
+        assert_eq!(
+            topology(&[laptop.clone(), external.clone()]),
+            "DP-1 2560x1440 + eDP-1 1920x1080"
+        );
+        assert_eq!(
+            topology(&[laptop.clone(), external.clone()]),
+            topology(&[external, laptop])
+        );
+    }
+}
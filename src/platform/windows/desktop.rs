@@ -1,15 +1,15 @@
+use crate::core::AppError;
+use log::{debug, error, info, warn};
 use windows::{
     core::*,
     Win32::{
-        Foundation::{HWND, LPARAM, WPARAM, BOOL},
+        Foundation::{BOOL, HWND, LPARAM, WPARAM},
         UI::WindowsAndMessaging::{
-            FindWindowW, SendMessageW, SetParent, EnumWindows, GetWindow, FindWindowExW, 
-            GW_HWNDNEXT
+            EnumWindows, FindWindowExW, FindWindowW, GetWindow, SendMessageW, SetParent,
+            GW_HWNDNEXT,
         },
     },
 };
-use crate::core::AppError;
-use log::{debug, error, info, warn};
 
 /// Find the WorkerW window that we can parent wallpaper windows to
 pub fn find_workerw() -> std::result::Result<HWND, AppError> {
@@ -17,7 +17,9 @@ pub fn find_workerw() -> std::result::Result<HWND, AppError> {
         // Find the Program Manager window
         let progman = FindWindowW(w!("Progman"), None);
         if progman == HWND(0) {
-            return Err(AppError::WallpaperError("Failed to find Progman window".to_string()));
+            return Err(AppError::WallpaperError(
+                "Failed to find Progman window".to_string(),
+            ));
         }
 
         debug!("Found Progman window: {:?}", progman);
@@ -31,7 +33,7 @@ pub fn find_workerw() -> std::result::Result<HWND, AppError> {
         std::thread::sleep(std::time::Duration::from_millis(100));
 
         // Try multiple methods to find WorkerW
-        
+
         // Method 1: Look for WorkerW with SHELLDLL_DefView child
         let mut workerw = None;
         let result = EnumWindows(
@@ -48,7 +50,10 @@ pub fn find_workerw() -> std::result::Result<HWND, AppError> {
         // Method 2: Fallback - try to find any WorkerW window
         let workerw_fallback = FindWindowW(w!("WorkerW"), None);
         if workerw_fallback != HWND(0) {
-            info!("Found WorkerW window (method 2 - fallback): {:?}", workerw_fallback);
+            info!(
+                "Found WorkerW window (method 2 - fallback): {:?}",
+                workerw_fallback
+            );
             return Ok(workerw_fallback);
         }
 
@@ -89,10 +94,15 @@ pub fn parent_to_desktop(window_hwnd: HWND) -> std::result::Result<(), AppError>
         // Parent our window to WorkerW
         let result = SetParent(window_hwnd, workerw);
         if result == HWND(0) {
-            return Err(AppError::WallpaperError("Failed to parent window to WorkerW".to_string()));
+            return Err(AppError::WallpaperError(
+                "Failed to parent window to WorkerW".to_string(),
+            ));
         }
 
-        info!("Successfully parented window {:?} to WorkerW {:?}", window_hwnd, workerw);
+        info!(
+            "Successfully parented window {:?} to WorkerW {:?}",
+            window_hwnd, workerw
+        );
         Ok(())
     }
 }
@@ -125,7 +135,10 @@ mod tests {
                     assert_ne!(hwnd, HWND(0));
                 }
                 Err(e) => {
-                    println!("Could not find WorkerW (this is normal in some environments): {}", e);
+                    println!(
+                        "Could not find WorkerW (this is normal in some environments): {}",
+                        e
+                    );
                 }
             }
         }
@@ -137,4 +150,4 @@ mod tests {
         let available = check_desktop_integration();
         println!("Desktop integration available: {}", available);
     }
-}
\ No newline at end of file
+}
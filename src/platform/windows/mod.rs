@@ -1,23 +1,58 @@
 pub mod desktop;
 pub mod window_manager;
 
+use crate::core::config::FitMode;
+use crate::core::span::{self, SpanLayout};
+use crate::core::{AppResult, PlayerSlot};
+use crate::platform::{gpu, WallpaperManager};
 use async_trait::async_trait;
-use crate::core::AppResult;
-use crate::platform::WallpaperManager;
-use log::{error, info};
+use log::{debug, error, info};
 use std::path::Path;
 use std::process::Command;
 
+/// Make the process aware of each monitor's DPI
+///
+/// Monitor bounds and window sizes are then in physical pixels instead of
+/// being scaled to 96 DPI, so wallpapers match the monitors' real resolution.
+pub fn enable_dpi_awareness() {
+    use windows::Win32::UI::HiDpi::{
+        SetProcessDpiAwarenessContext, DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2,
+    };
+
+    if let Err(e) =
+        unsafe { SetProcessDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2) }
+    {
+        debug!("DPI awareness left unchanged: {}", e);
+    }
+}
+
+/// Print to the console of the terminal the process was started from
+///
+/// Release builds use the windows subsystem so the settings window opens
+/// without a console, which leaves subcommands with nowhere to print. Does
+/// nothing when started from Explorer or with the output redirected.
+pub fn attach_parent_console() {
+    use windows::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+
+    if let Err(e) = unsafe { AttachConsole(ATTACH_PARENT_PROCESS) } {
+        debug!("No parent console to attach to: {}", e);
+    }
+}
 
 /// Windows-specific wallpaper manager
-pub struct WindowsWallpaperManager;
+pub struct WindowsWallpaperManager {
+    /// Player of the web, shader or audio wallpaper
+    player: PlayerSlot,
+}
 
 impl WindowsWallpaperManager {
     /// Create a new Windows wallpaper manager
     pub fn new() -> AppResult<Self> {
-        Ok(Self)
+        Ok(Self {
+            player: PlayerSlot::new(),
+        })
     }
-    
+
     /// Initialize the Windows wallpaper manager
     #[allow(dead_code)]
     pub fn init() -> AppResult<()> {
@@ -30,37 +65,63 @@ impl WindowsWallpaperManager {
 impl WallpaperManager for WindowsWallpaperManager {
     async fn set_static_wallpaper(&self, path: &std::path::Path) -> AppResult<()> {
         info!("Setting static wallpaper: {}", path.display());
-        
+
         // Convert path to absolute path
         let path = path.canonicalize()?;
-        
+
         // Use PowerShell to set the wallpaper
         let output = Command::new("powershell")
-            .args(&[
-                "-Command",
-                &format!(
-                    "Add-Type -TypeDefinition @'\nusing System;\nusing System.Runtime.InteropServices;\npublic class Wallpaper {{\n    [DllImport(\"user32.dll\", CharSet = CharSet.Auto)]\n    public static extern int SystemParametersInfo(int uAction, int uParam, string lpvParam, int fuWinIni);\n}}\n'@;\n[Wallpaper]::SystemParametersInfo(0x0014, 0, '{}', 0x01 -bor 0x02)",
-                    path.to_string_lossy()
-                ),
-            ])
+            .args(&["-Command", &set_wallpaper_script(&path)])
             .output()?;
-        
+
         if !output.status.success() {
             let error = String::from_utf8_lossy(&output.stderr);
             error!("Failed to set static wallpaper: {}", error);
             return Err(crate::core::AppError::WallpaperError(error.to_string()));
         }
-        
+
         info!("Static wallpaper set successfully");
         Ok(())
     }
-    
+
+    async fn set_static_wallpaper_fitted(&self, path: &Path, mode: FitMode) -> AppResult<()> {
+        info!("Setting static wallpaper ({:?}): {}", mode, path.display());
+        set_styled_wallpaper(&path.canonicalize()?, wallpaper_style(mode))?;
+        info!("Static wallpaper set successfully");
+        Ok(())
+    }
+
+    fn native_span(&self) -> bool {
+        true
+    }
+
+    async fn set_spanned_wallpaper(&self, path: &Path) -> AppResult<()> {
+        info!("Setting spanned wallpaper: {}", path.display());
+        set_styled_wallpaper(&path.canonicalize()?, SPAN_STYLE)?;
+        info!("Spanned wallpaper set successfully");
+        Ok(())
+    }
+
+    fn native_fit_modes(&self) -> Vec<FitMode> {
+        vec![
+            FitMode::Fill,
+            FitMode::Fit,
+            FitMode::Stretch,
+            FitMode::Center,
+            FitMode::Tile,
+        ]
+    }
+
+    fn image_formats(&self) -> &'static [&'static str] {
+        &["png", "jpg", "jpeg", "bmp", "gif"]
+    }
+
     async fn set_video_wallpaper(&self, path: &Path) -> AppResult<()> {
         info!("Setting video wallpaper: {}", path.display());
-        
+
         // Convert path to absolute path
         let path = path.canonicalize()?;
-        
+
         // Use VLC to play the video as wallpaper
         let output = Command::new("vlc")
             .args(&[
@@ -69,81 +130,142 @@ impl WallpaperManager for WindowsWallpaperManager {
                 "--loop",
                 &path.to_string_lossy().to_string(),
             ])
-            .output()?;
-        
+            .output()
+            .map_err(|e| crate::core::AppError::from_spawn("vlc", e))?;
+
         if !output.status.success() {
             let error = String::from_utf8_lossy(&output.stderr);
             error!("Failed to set video wallpaper: {}", error);
             return Err(crate::core::AppError::WallpaperError(error.to_string()));
         }
-        
+
         info!("Video wallpaper set successfully");
         Ok(())
     }
-    
+
     async fn set_web_wallpaper(&self, url: &str) -> AppResult<()> {
         info!("Setting web wallpaper: {}", url);
-        
+
         // Use a web browser to display the webpage as wallpaper
-        let output = Command::new("start")
-            .args(&["msedge", "--new-window", url])
-            .output()?;
-        
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            error!("Failed to set web wallpaper: {}", error);
-            return Err(crate::core::AppError::WallpaperError(error.to_string()));
-        }
-        
+        let mut command = Command::new("msedge");
+        command.args(&["--new-window", url]);
+        gpu::apply(&mut command);
+        self.player.start(&mut command, "msedge", "web wallpaper")?;
+
         info!("Web wallpaper set successfully");
         Ok(())
     }
-    
+
     async fn set_shader_wallpaper(&self, path: &Path) -> AppResult<()> {
+        self.set_shader_wallpaper_limited(path, None).await
+    }
+
+    async fn set_shader_wallpaper_limited(
+        &self,
+        path: &Path,
+        max_fps: Option<u32>,
+    ) -> AppResult<()> {
         info!("Setting shader wallpaper: {}", path.display());
-        
+
         // Convert path to absolute path
         let path = path.canonicalize()?;
-        
+
         // Use a shader player to display the shader as wallpaper
-        let output = Command::new("shadertoy")
-            .args(&[&path.to_string_lossy().to_string()])
-            .output()?;
-        
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            error!("Failed to set shader wallpaper: {}", error);
-            return Err(crate::core::AppError::WallpaperError(error.to_string()));
+        let mut command = Command::new("shadertoy");
+        if let Some(fps) = max_fps {
+            command.arg(format!("--fps={}", fps));
         }
-        
+        gpu::apply(&mut command);
+        command.arg(&path);
+        self.player
+            .start(&mut command, "shadertoy", "shader wallpaper")?;
+
         info!("Shader wallpaper set successfully");
         Ok(())
     }
-    
+
+    async fn set_shader_wallpaper_spanned(
+        &self,
+        path: &Path,
+        max_fps: Option<u32>,
+        layout: &SpanLayout,
+    ) -> AppResult<()> {
+        info!(
+            "Setting shader wallpaper across {} monitors: {}",
+            layout.regions.len(),
+            path.display()
+        );
+
+        let path = path.canonicalize()?;
+        let start_time = span::start_time();
+        let mut commands: Vec<Command> = layout
+            .regions
+            .iter()
+            .map(|region| {
+                let mut command = Command::new("shadertoy");
+                if let Some(fps) = max_fps {
+                    command.arg(format!("--fps={}", fps));
+                }
+                gpu::apply(&mut command);
+                command
+                    .args(span::shader_args(layout, region, start_time))
+                    .arg(&path);
+                command
+            })
+            .collect();
+        self.player
+            .start_all(&mut commands, "shadertoy", "shader wallpaper")?;
+
+        info!("Shader wallpaper set successfully");
+        Ok(())
+    }
+
+    async fn set_shader_wallpaper_on(
+        &self,
+        path: &Path,
+        max_fps: Option<u32>,
+        monitor: &str,
+    ) -> AppResult<()> {
+        info!(
+            "Setting shader wallpaper on {}: {}",
+            monitor,
+            path.display()
+        );
+
+        let path = path.canonicalize()?;
+        let mut command = Command::new("shadertoy");
+        if let Some(fps) = max_fps {
+            command.arg(format!("--fps={}", fps));
+        }
+        gpu::apply(&mut command);
+        command.arg(format!("--output={}", monitor)).arg(&path);
+        self.player
+            .start_on(monitor, &mut command, "shadertoy", "shader wallpaper")?;
+
+        info!("Shader wallpaper set successfully on {}", monitor);
+        Ok(())
+    }
+
     async fn set_audio_wallpaper(&self, path: &Path) -> AppResult<()> {
         info!("Setting audio wallpaper: {}", path.display());
-        
+
         // Convert path to absolute path
         let path = path.canonicalize()?;
-        
+
         // Use a shader player with audio visualization to display the shader as wallpaper
-        let output = Command::new("shadertoy")
-            .args(&["--audio", &path.to_string_lossy().to_string()])
-            .output()?;
-        
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            error!("Failed to set audio wallpaper: {}", error);
-            return Err(crate::core::AppError::WallpaperError(error.to_string()));
-        }
-        
+        let mut command = Command::new("shadertoy");
+        command.args(&["--audio", &path.to_string_lossy().to_string()]);
+        gpu::apply(&mut command);
+        self.player
+            .start(&mut command, "shadertoy", "audio wallpaper")?;
+
         info!("Audio wallpaper set successfully");
         Ok(())
     }
-    
+
     async fn clear_wallpaper(&self) -> AppResult<()> {
         info!("Clearing wallpaper");
-        
+
         // Use PowerShell to clear the wallpaper
         let output = Command::new("powershell")
             .args(&[
@@ -151,28 +273,121 @@ impl WallpaperManager for WindowsWallpaperManager {
                 "Add-Type -TypeDefinition @'\nusing System;\nusing System.Runtime.InteropServices;\npublic class Wallpaper {\n    [DllImport(\"user32.dll\", CharSet = CharSet.Auto)]\n    public static extern int SystemParametersInfo(int uAction, int uParam, string lpvParam, int fuWinIni);\n}\n'@;\n[Wallpaper]::SystemParametersInfo(0x0014, 0, '', 0x01 -bor 0x02)",
             ])
             .output()?;
-        
+
         if !output.status.success() {
             let error = String::from_utf8_lossy(&output.stderr);
             error!("Failed to clear wallpaper: {}", error);
             return Err(crate::core::AppError::WallpaperError(error.to_string()));
         }
-        
+
         info!("Wallpaper cleared successfully");
         Ok(())
     }
-    
+
     async fn stop_wallpaper(&self) -> AppResult<()> {
         info!("Stopping wallpaper");
-        
+
         // For Windows, this is essentially the same as clearing the wallpaper
+        self.player.stop();
         self.clear_wallpaper().await
     }
-    
-    async fn get_current_wallpaper(&self) -> AppResult<Option<std::path::PathBuf>> {
-        info!("Getting current wallpaper");
-        
-        // For initial compilation, return placeholder value
-        Ok(None)
+
+    async fn set_lock_screen_wallpaper(&self, path: &Path) -> AppResult<()> {
+        info!("Setting lock screen wallpaper: {}", path.display());
+        let output = Command::new("powershell")
+            .args(&["-Command", &lock_screen_script(&path.canonicalize()?)])
+            .output()?;
+
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            error!("Failed to set lock screen wallpaper: {}", error);
+            return Err(crate::core::AppError::PlatformError(format!(
+                "Setting the lock screen needs administrator rights: {}",
+                error.trim()
+            )));
+        }
+
+        info!("Lock screen wallpaper set successfully");
+        Ok(())
+    }
+
+    async fn system_wallpaper(&self) -> AppResult<Option<std::path::PathBuf>> {
+        let output = Command::new("reg")
+            .args(&["query", "HKCU\\Control Panel\\Desktop", "/v", "WallPaper"])
+            .output()?;
+        if !output.status.success() {
+            return Ok(None);
+        }
+
+        // e.g. "    WallPaper    REG_SZ    C:\Users\me\sky.jpg"
+        let output = String::from_utf8_lossy(&output.stdout);
+        let path = output
+            .lines()
+            .find_map(|line| line.split_once("REG_SZ"))
+            .map(|(_, path)| path.trim())
+            .filter(|path| !path.is_empty());
+        Ok(path.map(std::path::PathBuf::from))
+    }
+
+    fn backend_name(&self) -> String {
+        "Windows via SystemParametersInfo".to_string()
+    }
+
+    fn player(&self) -> Option<&PlayerSlot> {
+        Some(&self.player)
+    }
+}
+
+/// PowerShell script setting the wallpaper through SystemParametersInfo
+fn set_wallpaper_script(path: &Path) -> String {
+    format!(
+        "Add-Type -TypeDefinition @'\nusing System;\nusing System.Runtime.InteropServices;\npublic class Wallpaper {{\n    [DllImport(\"user32.dll\", CharSet = CharSet.Auto)]\n    public static extern int SystemParametersInfo(int uAction, int uParam, string lpvParam, int fuWinIni);\n}}\n'@;\n[Wallpaper]::SystemParametersInfo(0x0014, 0, '{}', 0x01 -bor 0x02)",
+        path.to_string_lossy()
+    )
+}
+
+/// PowerShell script setting the lock screen image through the PersonalizationCSP registry key
+///
+/// The key is under HKLM, so writing it needs administrator rights.
+fn lock_screen_script(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\'', "''");
+    format!(
+        "$key = 'HKLM:\\SOFTWARE\\Microsoft\\Windows\\CurrentVersion\\PersonalizationCSP';\nif (!(Test-Path $key)) {{ New-Item -Path $key | Out-Null }};\nSet-ItemProperty -Path $key -Name LockScreenImagePath -Value '{0}';\nSet-ItemProperty -Path $key -Name LockScreenImageUrl -Value '{0}';\nNew-ItemProperty -Path $key -Name LockScreenImageStatus -Value 1 -PropertyType DWord -Force | Out-Null",
+        path
+    )
+}
+
+/// Set the wallpaper with `WallpaperStyle` and `TileWallpaper` registry values
+fn set_styled_wallpaper(path: &Path, (style, tile): (&str, &str)) -> AppResult<()> {
+    // The style is read from the registry when the wallpaper is set
+    let script = format!(
+        "Set-ItemProperty -Path 'HKCU:\\Control Panel\\Desktop' -Name WallpaperStyle -Value '{}';\nSet-ItemProperty -Path 'HKCU:\\Control Panel\\Desktop' -Name TileWallpaper -Value '{}';\n{}",
+        style,
+        tile,
+        set_wallpaper_script(path)
+    );
+    let output = Command::new("powershell")
+        .args(&["-Command", &script])
+        .output()?;
+
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        error!("Failed to set static wallpaper: {}", error);
+        return Err(crate::core::AppError::WallpaperError(error.to_string()));
+    }
+    Ok(())
+}
+
+/// `WallpaperStyle` and `TileWallpaper` registry values stretching one image across all monitors
+const SPAN_STYLE: (&str, &str) = ("22", "0");
+
+/// `WallpaperStyle` and `TileWallpaper` registry values for a fit mode
+fn wallpaper_style(mode: FitMode) -> (&'static str, &'static str) {
+    match mode {
+        FitMode::Fill | FitMode::Crop => ("10", "0"),
+        FitMode::Fit => ("6", "0"),
+        FitMode::Stretch => ("2", "0"),
+        FitMode::Center => ("0", "0"),
+        FitMode::Tile => ("0", "1"),
     }
 }
@@ -1,22 +1,22 @@
+use crate::core::span::SpanLayout;
+use crate::core::AppError;
+use crate::platform::display;
+use crate::platform::windows::desktop::find_workerw;
+use log::{debug, info};
+use std::ptr;
 use windows::{
     core::*,
     Win32::{
-        Foundation::{HWND, LPARAM, WPARAM, RECT, LRESULT},
+        Foundation::{HWND, LPARAM, LRESULT, RECT, WPARAM},
+        System::LibraryLoader::GetModuleHandleW,
         UI::WindowsAndMessaging::{
-            CreateWindowExW, DestroyWindow, ShowWindow, GetSystemMetrics,
-            SetWindowPos, GetWindowRect, RegisterClassExW, WNDCLASSEXW,
-            LoadCursorW, DefWindowProcW, PostQuitMessage,
-            WINDOW_EX_STYLE, WS_POPUP, SW_SHOW, SW_HIDE, WM_DESTROY,
-            SM_CXSCREEN, SM_CYSCREEN, SWP_NOACTIVATE, SWP_NOZORDER,
-            CS_HREDRAW, CS_VREDRAW, IDC_ARROW
+            CreateWindowExW, DefWindowProcW, DestroyWindow, GetSystemMetrics, GetWindowRect,
+            LoadCursorW, PostQuitMessage, RegisterClassExW, SetWindowPos, ShowWindow, CS_HREDRAW,
+            CS_VREDRAW, IDC_ARROW, SM_CXSCREEN, SM_CYSCREEN, SWP_NOACTIVATE, SWP_NOZORDER, SW_HIDE,
+            SW_SHOW, WINDOW_EX_STYLE, WM_DESTROY, WNDCLASSEXW, WS_POPUP,
         },
-        System::LibraryLoader::GetModuleHandleW,
     },
 };
-use crate::core::AppError;
-use crate::platform::windows::desktop::find_workerw;
-use log::{debug, info};
-use std::ptr;
 
 /// Window manager for creating and managing wallpaper windows
 pub struct WindowManager {
@@ -40,12 +40,21 @@ impl WindowManager {
 
     /// Register the window class for wallpaper windows
     fn register_window_class(&self) -> std::result::Result<(), AppError> {
-        let class_name_wide: Vec<u16> = self.class_name.encode_utf16().chain(std::iter::once(0)).collect();
-        
+        let class_name_wide: Vec<u16> = self
+            .class_name
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .collect();
+
         unsafe {
             let hinstance = match GetModuleHandleW(None) {
                 Ok(h) => h,
-                Err(e) => return Err(AppError::WallpaperError(format!("Failed to get module handle: {}", e))),
+                Err(e) => {
+                    return Err(AppError::WallpaperError(format!(
+                        "Failed to get module handle: {}",
+                        e
+                    )))
+                }
             };
 
             let wc = WNDCLASSEXW {
@@ -65,7 +74,9 @@ impl WindowManager {
 
             let atom = RegisterClassExW(&wc);
             if atom == 0 {
-                return Err(AppError::WallpaperError("Failed to register window class".to_string()));
+                return Err(AppError::WallpaperError(
+                    "Failed to register window class".to_string(),
+                ));
             }
         }
 
@@ -80,7 +91,9 @@ impl WindowManager {
         wparam: WPARAM,
         lparam: LPARAM,
     ) -> LRESULT {
-        use windows::Win32::UI::WindowsAndMessaging::{DefWindowProcW, WM_DESTROY, PostQuitMessage};
+        use windows::Win32::UI::WindowsAndMessaging::{
+            DefWindowProcW, PostQuitMessage, WM_DESTROY,
+        };
 
         unsafe {
             match msg {
@@ -109,33 +122,46 @@ impl WindowManager {
             Ok::<(), AppError>(())
         })?;
 
-        // Get screen dimensions
-        let screen_width = unsafe { GetSystemMetrics(SM_CXSCREEN) };
-        let screen_height = unsafe { GetSystemMetrics(SM_CYSCREEN) };
+        // Cover the primary monitor, in physical pixels
+        let (screen_x, screen_y, screen_width, screen_height) = primary_screen();
 
-        debug!("Screen dimensions: {}x{}", screen_width, screen_height);
+        debug!(
+            "Screen bounds: {}x{} at {},{}",
+            screen_width, screen_height, screen_x, screen_y
+        );
 
         // Create the window
-        let class_name_wide: Vec<u16> = self.class_name.encode_utf16().chain(std::iter::once(0)).collect();
-        let window_title_wide: Vec<u16> = "Aether-Desk Wallpaper".encode_utf16().chain(std::iter::once(0)).collect();
+        let class_name_wide: Vec<u16> = self
+            .class_name
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .collect();
+        let window_title_wide: Vec<u16> = "Aether-Desk Wallpaper"
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .collect();
 
         let window = unsafe {
             CreateWindowExW(
-                WINDOW_EX_STYLE(0),                    // Extended styles
-                PCWSTR(class_name_wide.as_ptr()),      // Window class
-                PCWSTR(window_title_wide.as_ptr()),    // Window title
-                WS_POPUP,                              // Window style (borderless)
-                0, 0,                                  // Position
-                screen_width, screen_height,           // Size
-                None,                                  // Parent window (will be set later)
-                None,                                  // Menu
+                WINDOW_EX_STYLE(0),                 // Extended styles
+                PCWSTR(class_name_wide.as_ptr()),   // Window class
+                PCWSTR(window_title_wide.as_ptr()), // Window title
+                WS_POPUP,                           // Window style (borderless)
+                screen_x,
+                screen_y, // Position
+                screen_width,
+                screen_height,                              // Size
+                None,                                       // Parent window (will be set later)
+                None,                                       // Menu
                 GetModuleHandleW(None).unwrap_or_default(), // Instance
-                Some(ptr::null_mut()),                 // Additional data
+                Some(ptr::null_mut()),                      // Additional data
             )
         };
 
         if window == HWND(0) {
-            return Err(AppError::WallpaperError("Failed to create wallpaper window".to_string()));
+            return Err(AppError::WallpaperError(
+                "Failed to create wallpaper window".to_string(),
+            ));
         }
 
         debug!("Created wallpaper window: {:?}", window);
@@ -157,10 +183,15 @@ impl WindowManager {
             unsafe {
                 let result = SetParent(window, workerw);
                 if result == HWND(0) {
-                    return Err(AppError::WallpaperError("Failed to parent window to WorkerW".to_string()));
+                    return Err(AppError::WallpaperError(
+                        "Failed to parent window to WorkerW".to_string(),
+                    ));
                 }
             }
-            debug!("Successfully parented window {:?} to WorkerW {:?}", window, workerw);
+            debug!(
+                "Successfully parented window {:?} to WorkerW {:?}",
+                window, workerw
+            );
         } else {
             return Err(AppError::WallpaperError("WorkerW not found".to_string()));
         }
@@ -207,12 +238,54 @@ impl WindowManager {
                 match SetWindowPos(
                     window,
                     None,
-                    0, 0,
-                    width, height,
+                    0,
+                    0,
+                    width,
+                    height,
                     SWP_NOACTIVATE | SWP_NOZORDER,
                 ) {
                     Ok(_) => debug!("Wallpaper window resized to {}x{}", width, height),
-                    Err(e) => return Err(AppError::WallpaperError(format!("Failed to resize window: {}", e))),
+                    Err(e) => {
+                        return Err(AppError::WallpaperError(format!(
+                            "Failed to resize window: {}",
+                            e
+                        )))
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Move the wallpaper window over part of the desktop, relative to its top-left corner
+    pub fn place_window(
+        &self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    ) -> std::result::Result<(), AppError> {
+        if let Some(window) = self.window {
+            unsafe {
+                match SetWindowPos(
+                    window,
+                    None,
+                    x,
+                    y,
+                    width,
+                    height,
+                    SWP_NOACTIVATE | SWP_NOZORDER,
+                ) {
+                    Ok(_) => debug!(
+                        "Wallpaper window placed at {},{} ({}x{})",
+                        x, y, width, height
+                    ),
+                    Err(e) => {
+                        return Err(AppError::WallpaperError(format!(
+                            "Failed to place window: {}",
+                            e
+                        )))
+                    }
                 }
             }
         }
@@ -226,7 +299,10 @@ impl WindowManager {
             unsafe {
                 match GetWindowRect(window, &mut rect) {
                     Ok(_) => Ok(rect),
-                    Err(e) => Err(AppError::WallpaperError(format!("Failed to get window rect: {}", e))),
+                    Err(e) => Err(AppError::WallpaperError(format!(
+                        "Failed to get window rect: {}",
+                        e
+                    ))),
                 }
             }
         } else {
@@ -247,6 +323,30 @@ impl WindowManager {
     }
 }
 
+/// Get the primary monitor's bounds within WorkerW, which spans all monitors
+///
+/// Falls back to the screen metrics when monitors can't be queried.
+fn primary_screen() -> (i32, i32, i32, i32) {
+    let monitors = display::monitors().unwrap_or_default();
+    let primary = monitors.iter().position(|m| m.primary).unwrap_or(0);
+    match SpanLayout::of(&monitors).and_then(|layout| layout.regions.get(primary).cloned()) {
+        Some(region) => (
+            region.x as i32,
+            region.y as i32,
+            region.width as i32,
+            region.height as i32,
+        ),
+        None => unsafe {
+            (
+                0,
+                0,
+                GetSystemMetrics(SM_CXSCREEN),
+                GetSystemMetrics(SM_CYSCREEN),
+            )
+        },
+    }
+}
+
 impl Drop for WindowManager {
     fn drop(&mut self) {
         if let Some(window) = self.window {
@@ -281,9 +381,12 @@ mod tests {
                     assert!(wm.is_window_valid());
                 }
                 Err(e) => {
-                    println!("Could not create window (this is normal in some environments): {}", e);
+                    println!(
+                        "Could not create window (this is normal in some environments): {}",
+                        e
+                    );
                 }
             }
         }
     }
-}
\ No newline at end of file
+}
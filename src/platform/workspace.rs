@@ -0,0 +1,408 @@
+//! Detection of the active workspace or virtual desktop
+//!
+//! Hyprland reports workspace switches on its event socket. Elsewhere the
+//! active workspace is polled: Windows reads the current virtual desktop from
+//! the registry, KDE on Wayland asks KWin over D-Bus and X11 sessions (GNOME,
+//! KDE and most window managers) read `_NET_CURRENT_DESKTOP`. GNOME on Wayland
+//! doesn't expose it, so nothing is reported there.
+use crate::core::{AppError, AppResult};
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Time between checks where switches aren't reported as events
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Workspace or virtual desktop
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Workspace {
+    /// Identifier wallpapers are assigned by: the workspace name on Hyprland,
+    /// the desktop number on X11 and KDE, the desktop GUID on Windows
+    pub id: String,
+
+    /// Name to show
+    pub name: String,
+}
+
+impl Workspace {
+    /// Describe a workspace whose name is its identifier
+    pub fn named(name: impl Into<String>) -> Self {
+        let name = name.into();
+        Self {
+            id: name.clone(),
+            name,
+        }
+    }
+}
+
+/// Get the active workspace, if the platform reports one
+pub fn current_workspace() -> Option<Workspace> {
+    #[cfg(windows)]
+    {
+        windows_virtual_desktop()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        if crate::platform::hyprland::is_hyprland() {
+            let output = command_output("hyprctl", &["activeworkspace", "-j"])?;
+            let workspace: serde_json::Value = serde_json::from_str(&output).ok()?;
+            workspace["name"].as_str().map(Workspace::named)
+        } else if std::env::var_os("DISPLAY").is_some()
+            && std::env::var_os("WAYLAND_DISPLAY").is_none()
+        {
+            let current = parse_current_desktop(&command_output(
+                "xprop",
+                &["-root", "_NET_CURRENT_DESKTOP"],
+            )?)?;
+            let names = command_output("xprop", &["-root", "_NET_DESKTOP_NAMES"])
+                .map(|output| parse_desktop_names(&output))
+                .unwrap_or_default();
+            Some(Workspace {
+                id: (current + 1).to_string(),
+                name: names
+                    .get(current)
+                    .cloned()
+                    .unwrap_or_else(|| (current + 1).to_string()),
+            })
+        } else if std::env::var("XDG_CURRENT_DESKTOP")
+            .map_or(false, |desktop| desktop.contains("KDE"))
+        {
+            ["qdbus6", "qdbus"]
+                .iter()
+                .find_map(|program| {
+                    command_output(
+                        program,
+                        &["org.kde.KWin", "/KWin", "org.kde.KWin.currentDesktop"],
+                    )
+                })
+                .map(|output| Workspace::named(output.trim()))
+                .filter(|workspace| !workspace.id.is_empty())
+        } else {
+            None
+        }
+    }
+
+    #[cfg(not(any(windows, target_os = "linux")))]
+    {
+        None
+    }
+}
+
+/// Run a command and get its output
+#[allow(dead_code)]
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Parse `xprop -root _NET_CURRENT_DESKTOP` output into a zero-based desktop index
+///
+/// The output looks like "_NET_CURRENT_DESKTOP(CARDINAL) = 1".
+#[allow(dead_code)]
+fn parse_current_desktop(output: &str) -> Option<usize> {
+    output.split('=').nth(1)?.trim().parse().ok()
+}
+
+/// Parse `xprop -root _NET_DESKTOP_NAMES` output into the desktop names
+///
+/// The output looks like `_NET_DESKTOP_NAMES(UTF8_STRING) = "Main", "Web"`.
+#[allow(dead_code)]
+fn parse_desktop_names(output: &str) -> Vec<String> {
+    let Some((_, names)) = output.split_once('=') else {
+        return Vec::new();
+    };
+    names
+        .split(',')
+        .map(|name| name.trim().trim_matches('"').to_string())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+/// Get the workspace a Hyprland event switches to
+///
+/// Switching workspace reports `workspace>>NAME`; focusing another monitor
+/// reports `focusedmon>>MONITOR,NAME`, as its workspace becomes the active one.
+#[allow(dead_code)]
+fn parse_hyprland_event(line: &str) -> Option<&str> {
+    let (event, data) = line.trim_end().split_once(">>")?;
+    match event {
+        "workspace" => Some(data),
+        "focusedmon" => data.split_once(',').map(|(_, workspace)| workspace),
+        _ => None,
+    }
+}
+
+/// Format a GUID stored as 16 bytes in the Windows layout
+#[allow(dead_code)]
+fn format_guid(bytes: &[u8]) -> Option<String> {
+    let bytes: &[u8; 16] = bytes.get(..16)?.try_into().ok()?;
+    Some(format!(
+        "{{{:08X}-{:04X}-{:04X}-{:02X}{:02X}-{:02X}{:02X}{:02X}{:02X}{:02X}{:02X}}}",
+        u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+        u16::from_le_bytes([bytes[4], bytes[5]]),
+        u16::from_le_bytes([bytes[6], bytes[7]]),
+        bytes[8],
+        bytes[9],
+        bytes[10],
+        bytes[11],
+        bytes[12],
+        bytes[13],
+        bytes[14],
+        bytes[15],
+    ))
+}
+
+/// Registry key of the virtual desktops on Windows 11 and recent Windows 10
+#[cfg(windows)]
+const VIRTUAL_DESKTOPS_KEY: &str =
+    r"Software\Microsoft\Windows\CurrentVersion\Explorer\VirtualDesktops";
+
+/// Get the current Windows virtual desktop, named as in Task View
+///
+/// Desktops that weren't renamed are called "Desktop N" by their position.
+#[cfg(windows)]
+fn windows_virtual_desktop() -> Option<Workspace> {
+    use windows::Win32::System::Registry::{RRF_RT_REG_BINARY, RRF_RT_REG_SZ};
+
+    let id = format_guid(&registry_value(
+        VIRTUAL_DESKTOPS_KEY,
+        "CurrentVirtualDesktop",
+        RRF_RT_REG_BINARY,
+    )?)?;
+    let name = registry_value(
+        &format!(r"{}\Desktops\{}", VIRTUAL_DESKTOPS_KEY, id),
+        "Name",
+        RRF_RT_REG_SZ,
+    )
+    .map(|data| {
+        let wide: Vec<u16> = data
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .collect();
+        String::from_utf16_lossy(&wide)
+            .trim_end_matches('\0')
+            .to_string()
+    })
+    .filter(|name| !name.is_empty())
+    .or_else(|| {
+        let ids = registry_value(VIRTUAL_DESKTOPS_KEY, "VirtualDesktopIDs", RRF_RT_REG_BINARY)?;
+        let position = ids
+            .chunks_exact(16)
+            .position(|guid| format_guid(guid).as_deref() == Some(id.as_str()))?;
+        Some(format!("Desktop {}", position + 1))
+    })
+    .unwrap_or_else(|| id.clone());
+    Some(Workspace { id, name })
+}
+
+/// Read a value under HKEY_CURRENT_USER
+#[cfg(windows)]
+fn registry_value(
+    key: &str,
+    value: &str,
+    flags: windows::Win32::System::Registry::REG_ROUTINE_FLAGS,
+) -> Option<Vec<u8>> {
+    use windows::core::HSTRING;
+    use windows::Win32::System::Registry::{RegGetValueW, HKEY_CURRENT_USER};
+
+    let (key, value) = (HSTRING::from(key), HSTRING::from(value));
+    let mut size = 0u32;
+    unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            &key,
+            &value,
+            flags,
+            None,
+            None,
+            Some(&mut size),
+        )
+        .ok()
+        .ok()?;
+        let mut data = vec![0u8; size as usize];
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            &key,
+            &value,
+            flags,
+            None,
+            Some(data.as_mut_ptr().cast()),
+            Some(&mut size),
+        )
+        .ok()
+        .ok()?;
+        data.truncate(size as usize);
+        Some(data)
+    }
+}
+
+/// Reports switches to another workspace
+#[derive(Default)]
+pub struct WorkspaceWatcher {
+    /// Whether the watch thread is running
+    is_running: Arc<Mutex<bool>>,
+
+    /// Watch thread handle
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl WorkspaceWatcher {
+    /// Create a stopped watcher
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check whether the watcher is running
+    pub fn is_running(&self) -> bool {
+        *self.is_running.lock().unwrap()
+    }
+
+    /// Start watching, calling `on_change` with each workspace switched to
+    pub fn start(&mut self, on_change: impl Fn(Workspace) + Send + 'static) -> AppResult<()> {
+        if self.is_running() {
+            return Ok(());
+        }
+        if current_workspace().is_none() {
+            return Err(AppError::Other(
+                "The active workspace can't be detected on this desktop".to_string(),
+            ));
+        }
+
+        *self.is_running.lock().unwrap() = true;
+        let is_running = self.is_running.clone();
+        self.thread = Some(thread::spawn(move || {
+            let mut last = current_workspace();
+
+            #[cfg(target_os = "linux")]
+            if crate::platform::hyprland::is_hyprland() {
+                match watch_hyprland(&is_running, &mut last, &on_change) {
+                    Ok(()) => return,
+                    Err(e) => debug!("Hyprland events unavailable ({}); polling the workspace", e),
+                }
+            }
+
+            while *is_running.lock().unwrap() {
+                thread::sleep(POLL_INTERVAL);
+                if let Some(workspace) =
+                    current_workspace().filter(|workspace| last.as_ref() != Some(workspace))
+                {
+                    last = Some(workspace.clone());
+                    on_change(workspace);
+                }
+            }
+            debug!("Workspace watcher stopped");
+        }));
+
+        info!("Watching for workspace switches");
+        Ok(())
+    }
+
+    /// Stop watching
+    pub fn stop(&mut self) {
+        *self.is_running.lock().unwrap() = false;
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Report workspace switches from Hyprland's event socket until stopped
+#[cfg(target_os = "linux")]
+fn watch_hyprland(
+    is_running: &Mutex<bool>,
+    last: &mut Option<Workspace>,
+    on_change: &dyn Fn(Workspace),
+) -> AppResult<()> {
+    use std::io::{BufRead, BufReader, ErrorKind};
+    use std::os::unix::net::UnixStream;
+    use std::path::PathBuf;
+
+    let signature = std::env::var("HYPRLAND_INSTANCE_SIGNATURE")
+        .map_err(|_| AppError::Other("HYPRLAND_INSTANCE_SIGNATURE is not set".to_string()))?;
+    // Hyprland 0.40 moved the sockets from /tmp to the runtime directory
+    let stream = std::env::var_os("XDG_RUNTIME_DIR")
+        .map(|dir| PathBuf::from(dir).join("hypr"))
+        .into_iter()
+        .chain([PathBuf::from("/tmp/hypr")])
+        .find_map(|dir| UnixStream::connect(dir.join(&signature).join(".socket2.sock")).ok())
+        .ok_or_else(|| {
+            AppError::Other("Failed to connect to Hyprland's event socket".to_string())
+        })?;
+    stream.set_read_timeout(Some(POLL_INTERVAL))?;
+
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    while *is_running.lock().unwrap() {
+        match reader.read_line(&mut line) {
+            Ok(0) => {
+                return Err(AppError::Other(
+                    "Hyprland closed its event socket".to_string(),
+                ))
+            }
+            Ok(_) => {
+                let workspace = parse_hyprland_event(&line).map(Workspace::named);
+                line.clear();
+                if let Some(workspace) =
+                    workspace.filter(|workspace| last.as_ref() != Some(workspace))
+                {
+                    *last = Some(workspace.clone());
+                    on_change(workspace);
+                }
+            }
+            // Part of a line read before the timeout stays in `line`
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    debug!("Workspace watcher stopped");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_x11_desktops() {
+        assert_eq!(
+            parse_current_desktop("_NET_CURRENT_DESKTOP(CARDINAL) = 2\n"),
+            Some(2)
+        );
+        assert_eq!(
+            parse_current_desktop("_NET_CURRENT_DESKTOP:  not found.\n"),
+            None
+        );
+        assert_eq!(
+            parse_desktop_names("_NET_DESKTOP_NAMES(UTF8_STRING) = \"Main\", \"Web\"\n"),
+            vec!["Main", "Web"]
+        );
+        assert!(parse_desktop_names("_NET_DESKTOP_NAMES:  not found.\n").is_empty());
+    }
+
+    #[test]
+    fn test_parse_hyprland_event() {
+        assert_eq!(parse_hyprland_event("workspace>>3\n"), Some("3"));
+        assert_eq!(parse_hyprland_event("focusedmon>>DP-1,web\n"), Some("web"));
+        assert_eq!(parse_hyprland_event("activewindow>>kitty,~\n"), None);
+    }
+
+    #[test]
+    fn test_format_guid() {
+        let bytes = [
+            0x78, 0x56, 0x34, 0x12, 0xBC, 0x9A, 0xF0, 0xDE, 0x01, 0x23, 0x45, 0x67, 0x89, 0xAB,
+            0xCD, 0xEF,
+        ];
+        assert_eq!(
+            format_guid(&bytes).as_deref(),
+            Some("{12345678-9ABC-DEF0-0123-456789ABCDEF}")
+        );
+        assert_eq!(format_guid(&bytes[..8]), None);
+    }
+}
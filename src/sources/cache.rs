@@ -0,0 +1,424 @@
+//! Download cache size and age limits
+use crate::core::config::{DownloadCacheConfig, WallpaperConfig};
+use crate::core::{format, AppResult, Config, ProfileStore, ScheduleItem};
+use log::{debug, info, warn};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// File in the download cache
+#[derive(Debug, Clone)]
+pub struct CacheFile {
+    /// File path
+    pub path: PathBuf,
+
+    /// File size in bytes
+    pub size: u64,
+
+    /// Last access or modification time
+    pub last_used: SystemTime,
+}
+
+/// Current download cache usage
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheUsage {
+    /// Number of files
+    pub files: usize,
+
+    /// Total size in bytes
+    pub bytes: u64,
+}
+
+/// Result of a cleanup run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CleanupReport {
+    /// Number of files removed
+    pub removed: usize,
+
+    /// Bytes freed
+    pub freed: u64,
+}
+
+/// List the files in the download cache
+pub fn scan(dir: &Path) -> AppResult<Vec<CacheFile>> {
+    let mut files = Vec::new();
+    if !dir.exists() {
+        return Ok(files);
+    }
+
+    for entry in std::fs::read_dir(dir)?.flatten() {
+        let metadata = match entry.metadata() {
+            Ok(metadata) if metadata.is_file() => metadata,
+            _ => continue,
+        };
+
+        let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        let last_used = metadata
+            .accessed()
+            .map_or(modified, |accessed| accessed.max(modified));
+
+        files.push(CacheFile {
+            path: entry.path(),
+            size: metadata.len(),
+            last_used,
+        });
+    }
+
+    Ok(files)
+}
+
+/// Get the download cache usage
+pub fn usage(dir: &Path) -> AppResult<CacheUsage> {
+    let files = scan(dir)?;
+    Ok(CacheUsage {
+        files: files.len(),
+        bytes: files.iter().map(|f| f.size).sum(),
+    })
+}
+
+/// Get the wallpapers that must never be removed from the cache
+///
+/// These are favorites, the current wallpaper, the last static one, the wallpapers
+/// of single monitors and workspaces, anything referenced by the schedule, and the
+/// same in every saved profile.
+pub fn protected_paths(config: &Config) -> HashSet<PathBuf> {
+    collect_protected(
+        &config.wallpaper,
+        &config.get_schedule_file(),
+        &ProfileStore::open(config),
+    )
+}
+
+/// Collect the protected wallpapers of the live settings and the profiles
+fn collect_protected(
+    wallpaper: &WallpaperConfig,
+    schedule_file: &Path,
+    profiles: &ProfileStore,
+) -> HashSet<PathBuf> {
+    let mut paths = wallpaper
+        .favorites
+        .iter()
+        .map(PathBuf::from)
+        .collect::<Vec<_>>();
+    paths.extend(wallpaper_paths(wallpaper));
+    paths.extend(schedule_paths(schedule_file));
+
+    match profiles.list() {
+        Ok(names) => {
+            for name in names {
+                match profiles.wallpaper(&name) {
+                    Ok(profile_wallpaper) => paths.extend(wallpaper_paths(&profile_wallpaper)),
+                    Err(e) => warn!(
+                        "Failed to load profile {} while protecting downloads: {}",
+                        name, e
+                    ),
+                }
+                if let Ok(schedule_file) = profiles.schedule_file(&name) {
+                    paths.extend(schedule_paths(&schedule_file));
+                }
+            }
+        }
+        Err(e) => warn!("Failed to list profiles while protecting downloads: {}", e),
+    }
+
+    paths.into_iter().map(|path| normalize(&path)).collect()
+}
+
+/// Get the wallpapers in use by wallpaper settings
+fn wallpaper_paths(wallpaper: &WallpaperConfig) -> Vec<PathBuf> {
+    wallpaper
+        .current_path
+        .iter()
+        .chain(&wallpaper.last_static)
+        .map(PathBuf::from)
+        .chain(
+            wallpaper
+                .monitor_wallpapers
+                .values()
+                .filter_map(|info| info.path.clone()),
+        )
+        .chain(
+            wallpaper
+                .workspace_wallpapers
+                .values()
+                .filter_map(|assigned| assigned.wallpaper.path.clone()),
+        )
+        .collect()
+}
+
+/// Get the wallpapers referenced by a schedule file
+fn schedule_paths(schedule_file: &Path) -> Vec<PathBuf> {
+    if !schedule_file.exists() {
+        return Vec::new();
+    }
+    match format::load::<Vec<ScheduleItem>>(schedule_file) {
+        Ok(items) => items
+            .into_iter()
+            .filter_map(|item| item.wallpaper.path)
+            .collect(),
+        Err(e) => {
+            warn!(
+                "Failed to load schedule {} while protecting downloads: {}",
+                schedule_file.display(),
+                e
+            );
+            Vec::new()
+        }
+    }
+}
+
+/// Choose which files to remove, least recently used first
+///
+/// Files older than the age limit are always removed; after that the oldest files are
+/// removed until the cache fits the size limit. Protected files are never chosen but
+/// still count towards the size.
+pub fn plan_cleanup(
+    mut files: Vec<CacheFile>,
+    config: &DownloadCacheConfig,
+    protected: &HashSet<PathBuf>,
+    now: SystemTime,
+) -> Vec<CacheFile> {
+    files.sort_by_key(|f| f.last_used);
+
+    let max_age = Duration::from_secs(config.max_age_days as u64 * 24 * 60 * 60);
+    let max_bytes = config.max_size_mb * 1024 * 1024;
+    let mut total = files.iter().map(|f| f.size).sum::<u64>();
+
+    let mut removals = Vec::new();
+    for file in files {
+        if protected.contains(&normalize(&file.path)) {
+            continue;
+        }
+
+        let expired = config.max_age_days > 0
+            && now
+                .duration_since(file.last_used)
+                .map_or(false, |age| age > max_age);
+        let over_size = config.max_size_mb > 0 && total > max_bytes;
+
+        if expired || over_size {
+            total -= file.size;
+            removals.push(file);
+        }
+    }
+
+    removals
+}
+
+/// Enforce the cache limits
+pub fn cleanup(
+    dir: &Path,
+    config: &DownloadCacheConfig,
+    protected: &HashSet<PathBuf>,
+) -> AppResult<CleanupReport> {
+    let removals = plan_cleanup(scan(dir)?, config, protected, SystemTime::now());
+    let report = remove_files(&removals);
+
+    if report.removed > 0 {
+        info!(
+            "Download cache cleanup removed {} files ({} bytes)",
+            report.removed, report.freed
+        );
+    } else {
+        debug!("Download cache is within limits");
+    }
+
+    Ok(report)
+}
+
+/// Remove every unprotected file from the cache
+pub fn clear(dir: &Path, protected: &HashSet<PathBuf>) -> AppResult<CleanupReport> {
+    let removals = scan(dir)?
+        .into_iter()
+        .filter(|f| !protected.contains(&normalize(&f.path)))
+        .collect::<Vec<_>>();
+    let report = remove_files(&removals);
+
+    info!(
+        "Cleared download cache: {} files ({} bytes)",
+        report.removed, report.freed
+    );
+    Ok(report)
+}
+
+/// Delete files, skipping any that fail
+fn remove_files(files: &[CacheFile]) -> CleanupReport {
+    let mut report = CleanupReport::default();
+    for file in files {
+        match std::fs::remove_file(&file.path) {
+            Ok(()) => {
+                report.removed += 1;
+                report.freed += file.size;
+            }
+            Err(e) => warn!("Failed to remove {}: {}", file.path.display(), e),
+        }
+    }
+    report
+}
+
+/// Normalize a path for comparison
+fn normalize(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::WorkspaceWallpaper;
+    use crate::core::{TriggerType, WallpaperInfo, WallpaperType};
+    use tempfile::tempdir;
+
+    const DAY: u64 = 24 * 60 * 60;
+
+    fn file(name: &str, size_mb: u64, days_ago: u64, now: SystemTime) -> CacheFile {
+        CacheFile {
+            path: PathBuf::from(format!("/nonexistent/{}", name)),
+            size: size_mb * 1024 * 1024,
+            last_used: now - Duration::from_secs(days_ago * DAY),
+        }
+    }
+
+    fn names(files: &[CacheFile]) -> Vec<String> {
+        files
+            .iter()
+            .map(|f| f.path.file_name().unwrap().to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_removes_least_recently_used_until_under_limit() {
+        let now = SystemTime::now();
+        let files = vec![
+            file("new.jpg", 4, 1, now),
+            file("old.jpg", 4, 5, now),
+            file("mid.jpg", 4, 3, now),
+        ];
+        let config = DownloadCacheConfig {
+            max_size_mb: 8,
+            max_age_days: 0,
+        };
+
+        let removals = plan_cleanup(files, &config, &HashSet::new(), now);
+        assert_eq!(names(&removals), vec!["old.jpg"]);
+    }
+
+    #[test]
+    fn test_protected_files_are_kept() {
+        let now = SystemTime::now();
+        let files = vec![file("fav.jpg", 4, 60, now), file("other.jpg", 4, 2, now)];
+        let config = DownloadCacheConfig {
+            max_size_mb: 4,
+            max_age_days: 30,
+        };
+        let protected = [PathBuf::from("/nonexistent/fav.jpg")]
+            .into_iter()
+            .collect();
+
+        let removals = plan_cleanup(files, &config, &protected, now);
+        assert_eq!(names(&removals), vec!["other.jpg"]);
+    }
+
+    /// Collect the protected paths of `config` with its files in `dir`
+    fn protected_in(dir: &Path, config: &Config) -> HashSet<PathBuf> {
+        let schedule_file = dir.join("schedule.json");
+        let profiles = ProfileStore::at(
+            dir.join("profiles"),
+            schedule_file.clone(),
+            dir.join("widgets.json"),
+        );
+        collect_protected(&config.wallpaper, &schedule_file, &profiles)
+    }
+
+    fn static_wallpaper(path: &str) -> WallpaperInfo {
+        WallpaperInfo::new(WallpaperType::Static, Some(PathBuf::from(path)), None)
+    }
+
+    #[test]
+    fn test_protects_monitor_wallpapers() {
+        let dir = tempdir().unwrap();
+        let mut config = Config::default();
+        config
+            .wallpaper
+            .monitor_wallpapers
+            .insert("DP-1".to_string(), static_wallpaper("/downloads/left.jpg"));
+
+        assert!(protected_in(dir.path(), &config).contains(Path::new("/downloads/left.jpg")));
+    }
+
+    #[test]
+    fn test_protects_workspace_wallpapers() {
+        let dir = tempdir().unwrap();
+        let mut config = Config::default();
+        config.wallpaper.workspace_wallpapers.insert(
+            "2".to_string(),
+            WorkspaceWallpaper {
+                workspace: "Code".to_string(),
+                wallpaper: static_wallpaper("/downloads/code.jpg"),
+            },
+        );
+
+        assert!(protected_in(dir.path(), &config).contains(Path::new("/downloads/code.jpg")));
+    }
+
+    #[test]
+    fn test_protects_last_static_wallpaper() {
+        let dir = tempdir().unwrap();
+        let mut config = Config::default();
+        config.wallpaper.last_static = Some("/downloads/fallback.jpg".to_string());
+
+        assert!(protected_in(dir.path(), &config).contains(Path::new("/downloads/fallback.jpg")));
+    }
+
+    #[test]
+    fn test_protects_saved_profiles() {
+        let dir = tempdir().unwrap();
+        let schedule_file = dir.path().join("schedule.json");
+        let profiles = ProfileStore::at(
+            dir.path().join("profiles"),
+            schedule_file.clone(),
+            dir.path().join("widgets.json"),
+        );
+
+        let mut saved = Config::default();
+        saved.wallpaper.current_path = Some("/downloads/gaming.jpg".to_string());
+        saved.wallpaper.monitor_wallpapers.insert(
+            "HDMI-1".to_string(),
+            static_wallpaper("/downloads/side.jpg"),
+        );
+        let item = ScheduleItem {
+            trigger: TriggerType::SystemEvent("startup".to_string()),
+            wallpaper: static_wallpaper("/downloads/night.jpg"),
+            enabled: true,
+        };
+        format::save(&schedule_file, &vec![item]).unwrap();
+        profiles.save("Gaming", &saved).unwrap();
+
+        // The live settings moved on to other wallpapers
+        std::fs::remove_file(&schedule_file).unwrap();
+        let mut config = Config::default();
+        config.wallpaper.current_path = Some("/downloads/work.jpg".to_string());
+
+        let protected = protected_in(dir.path(), &config);
+        for path in [
+            "/downloads/work.jpg",
+            "/downloads/gaming.jpg",
+            "/downloads/side.jpg",
+            "/downloads/night.jpg",
+        ] {
+            assert!(protected.contains(Path::new(path)), "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_removes_expired_files() {
+        let now = SystemTime::now();
+        let files = vec![file("stale.jpg", 1, 45, now), file("fresh.jpg", 1, 2, now)];
+        let config = DownloadCacheConfig {
+            max_size_mb: 0,
+            max_age_days: 30,
+        };
+
+        let removals = plan_cleanup(files, &config, &HashSet::new(), now);
+        assert_eq!(names(&removals), vec!["stale.jpg"]);
+    }
+}
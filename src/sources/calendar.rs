@@ -0,0 +1,859 @@
+//! Upcoming events from calendar subscriptions
+//!
+//! Calendars are read from ICS files on the web (`webcal://` and `https://`
+//! links, including the export links of CalDAV servers) or on disk. The
+//! Calendar widget lists the upcoming events, and schedule items can show a
+//! wallpaper while a busy event is on. Subscription addresses often carry an
+//! access key, so they're kept in the secret store and passed to curl
+//! through a file.
+//!
+//! Recurring events are expanded for the common rules: daily, weekly on
+//! given days, monthly on given days or weekdays, and yearly, each with an
+//! interval, a count or an end. Times in a named time zone are read as local
+//! time.
+use crate::core::config::CalendarConfig;
+use crate::core::{AppError, AppResult, HttpClient, RateLimit, SecretStore};
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use log::{debug, warn};
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration as StdDuration, Instant};
+
+/// Rate limit key for calendar fetches
+pub const PROVIDER: &str = "calendar";
+
+/// Secret store key of the subscribed addresses, one per line
+pub const URLS_SECRET: &str = "calendar.urls";
+
+/// Timeout for fetching a calendar
+const FETCH_TIMEOUT: StdDuration = StdDuration::from_secs(30);
+
+/// Most periods a recurring event is followed through, so broken rules end
+const MAX_PERIODS: i64 = 50_000;
+
+/// Occurrence of an event, in local time
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    /// Title
+    pub summary: String,
+
+    /// Start
+    pub start: NaiveDateTime,
+
+    /// End
+    pub end: NaiveDateTime,
+
+    /// Whether it lasts whole days
+    pub all_day: bool,
+
+    /// Whether it blocks time, rather than being marked free
+    pub busy: bool,
+}
+
+impl Event {
+    /// Check whether the event is on at a time
+    pub fn is_on(&self, at: NaiveDateTime) -> bool {
+        self.start <= at && at < self.end
+    }
+}
+
+/// How often a recurring event repeats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// Recurrence rule of an event
+#[derive(Debug, Clone, PartialEq)]
+struct Rule {
+    /// Period it repeats in
+    frequency: Frequency,
+
+    /// Number of periods between repeats
+    interval: i64,
+
+    /// Number of occurrences, counting the first
+    count: Option<u32>,
+
+    /// Last time an occurrence may start
+    until: Option<NaiveDateTime>,
+
+    /// Weekdays it falls on, with the week of the month for monthly rules (negative counts from the end)
+    weekdays: Vec<(Option<i32>, Weekday)>,
+
+    /// Days of the month it falls on (negative counts from the end)
+    month_days: Vec<i32>,
+}
+
+/// Event as written in a calendar, before recurrences are expanded
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    /// Unique ID shared by an event and its changed occurrences
+    uid: String,
+
+    /// Title
+    summary: String,
+
+    /// Start of the first occurrence
+    start: NaiveDateTime,
+
+    /// Length of each occurrence
+    length: Duration,
+
+    /// Whether it lasts whole days
+    all_day: bool,
+
+    /// Whether it blocks time
+    busy: bool,
+
+    /// Recurrence rule, if it repeats
+    rule: Option<Rule>,
+
+    /// Starts of occurrences left out or changed
+    exceptions: Vec<NaiveDateTime>,
+
+    /// Start of the occurrence this entry changes, if it's a changed occurrence
+    recurrence_id: Option<NaiveDateTime>,
+}
+
+/// Fetched events and the settings they were fetched with
+#[derive(Default)]
+struct State {
+    /// Calendar settings
+    config: CalendarConfig,
+
+    /// Secret store holding the addresses
+    secret_store: Option<Arc<SecretStore>>,
+
+    /// Occurrences around the time of the last fetch, by start
+    events: Vec<Event>,
+
+    /// Last fetch
+    fetched: Option<Instant>,
+
+    /// Why the last fetch failed or found nothing to fetch
+    status: Option<String>,
+}
+
+/// Process-wide cache of the subscribed calendars
+pub struct Calendar {
+    /// Events and settings
+    state: Mutex<State>,
+}
+
+#[allow(dead_code)]
+impl Calendar {
+    /// Get the process-wide calendar
+    pub fn shared() -> &'static Calendar {
+        static SHARED: OnceLock<Calendar> = OnceLock::new();
+        SHARED.get_or_init(|| {
+            HttpClient::shared().set_rate_limit(PROVIDER, RateLimit::per_minute(10));
+            Calendar {
+                state: Mutex::new(State::default()),
+            }
+        })
+    }
+
+    /// Set the calendar settings, fetching again on the next use
+    ///
+    /// Also called after the addresses change, so they're read again.
+    pub fn configure(&self, config: CalendarConfig, secret_store: Arc<SecretStore>) {
+        let mut state = self.state.lock().unwrap();
+        state.config = config;
+        state.secret_store = Some(secret_store);
+        state.fetched = None;
+    }
+
+    /// Get the events that haven't ended yet, fetching the calendars when due
+    pub fn upcoming(&self) -> Vec<Event> {
+        self.refresh();
+        let now = Local::now().naive_local();
+        self.state
+            .lock()
+            .unwrap()
+            .events
+            .iter()
+            .filter(|event| event.end > now)
+            .cloned()
+            .collect()
+    }
+
+    /// Get the busy event on now whose title contains a text, if any
+    ///
+    /// An empty text matches every busy event.
+    pub fn busy_now(&self, title: &str) -> Option<Event> {
+        self.refresh();
+        let now = Local::now().naive_local();
+        let title = title.trim().to_lowercase();
+        self.state
+            .lock()
+            .unwrap()
+            .events
+            .iter()
+            .find(|event| {
+                event.busy && event.is_on(now) && event.summary.to_lowercase().contains(&title)
+            })
+            .cloned()
+    }
+
+    /// Get why no events could be fetched, if they couldn't
+    pub fn status(&self) -> Option<String> {
+        self.state.lock().unwrap().status.clone()
+    }
+
+    /// Fetch the calendars if the last fetch is older than the refresh time
+    ///
+    /// The lock isn't held while fetching, so a slow server doesn't hold up
+    /// the scheduler.
+    fn refresh(&self) {
+        let (config, secret_store) = {
+            let mut state = self.state.lock().unwrap();
+            let refresh = StdDuration::from_secs(state.config.refresh_minutes.max(1) as u64 * 60);
+            if state
+                .fetched
+                .map_or(false, |fetched| fetched.elapsed() < refresh)
+            {
+                return;
+            }
+            state.fetched = Some(Instant::now());
+            let Some(secret_store) = state.secret_store.clone() else {
+                return;
+            };
+            (state.config.clone(), secret_store)
+        };
+
+        let urls = match secret_store.get(URLS_SECRET) {
+            Ok(urls) => urls.unwrap_or_default(),
+            Err(e) => {
+                warn!("Failed to read the calendar addresses: {}", e);
+                String::new()
+            }
+        };
+        let urls: Vec<&str> = urls
+            .lines()
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .collect();
+
+        let mut entries = Vec::new();
+        let mut failed = Vec::new();
+        for url in &urls {
+            match fetch(&HttpClient::shared(), url) {
+                Ok(ics) => entries.extend(parse(&ics)),
+                Err(e) => {
+                    // The address may hold an access key, so it isn't logged
+                    warn!("Failed to fetch a calendar: {}", e);
+                    failed.push(e.to_string());
+                }
+            }
+        }
+
+        let now = Local::now().naive_local();
+        let events = expand(
+            &entries,
+            now - Duration::days(1),
+            now + Duration::days(config.days_ahead.max(1) as i64 + 1),
+        );
+        debug!(
+            "Fetched {} calendar events from {} calendars",
+            events.len(),
+            urls.len()
+        );
+
+        let mut state = self.state.lock().unwrap();
+        state.status = match (urls.is_empty(), failed.first()) {
+            (true, _) => Some("Subscribe to a calendar in the settings".to_string()),
+            (false, Some(error)) => Some(format!("Failed to fetch a calendar: {}", error)),
+            (false, None) => None,
+        };
+        // Keep the last known events when every calendar failed
+        if failed.len() < urls.len() || urls.is_empty() {
+            state.events = events;
+        }
+    }
+}
+
+/// Read a calendar from the web or from disk
+fn fetch(http_client: &HttpClient, url: &str) -> AppResult<String> {
+    if let Some(path) = url
+        .strip_prefix("file://")
+        .or_else(|| (!url.contains("://")).then_some(url))
+    {
+        return Ok(fs::read_to_string(Path::new(path))?);
+    }
+    let url = match url.strip_prefix("webcal://") {
+        Some(rest) => format!("https://{}", rest),
+        None => url.to_string(),
+    };
+
+    let response = http_client.request(PROVIDER, "GET", &url, None, &[], FETCH_TIMEOUT)?;
+    if response.status != 200 {
+        return Err(AppError::NetworkError(format!(
+            "Calendar request failed with status {}",
+            response.status
+        )));
+    }
+    let text = response.text();
+    if !text.contains("BEGIN:VCALENDAR") {
+        return Err(AppError::NetworkError("Not an ICS calendar".to_string()));
+    }
+    Ok(text)
+}
+
+/// Read the events of an ICS calendar
+///
+/// Cancelled events and events without a start are left out.
+pub fn parse(ics: &str) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    let mut properties: Option<Vec<(String, String, String)>> = None;
+    let mut nested = 0;
+
+    for line in unfold(ics) {
+        let Some((name, params, value)) = split_line(&line) else {
+            continue;
+        };
+        match (name.as_str(), value.as_str()) {
+            ("BEGIN", "VEVENT") => {
+                properties = Some(Vec::new());
+                nested = 0;
+            }
+            ("END", "VEVENT") => {
+                entries.extend(properties.take().and_then(|properties| entry(&properties)));
+            }
+            // Alarms and other components inside an event have properties of their own
+            ("BEGIN", _) if properties.is_some() => nested += 1,
+            ("END", _) if properties.is_some() => nested -= 1,
+            _ if nested == 0 => {
+                if let Some(properties) = properties.as_mut() {
+                    properties.push((name, params, value));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    entries
+}
+
+/// Expand events into their occurrences overlapping a time range, ordered by start
+pub fn expand(entries: &[Entry], from: NaiveDateTime, to: NaiveDateTime) -> Vec<Event> {
+    // Changed occurrences replace the occurrence of the event they belong to
+    let changed: Vec<(&str, NaiveDateTime)> = entries
+        .iter()
+        .filter_map(|entry| Some((entry.uid.as_str(), entry.recurrence_id?)))
+        .collect();
+
+    let mut events: Vec<Event> = entries
+        .iter()
+        .flat_map(|entry| {
+            occurrences(entry, from, to)
+                .into_iter()
+                .filter(|start| {
+                    entry.recurrence_id.is_some()
+                        || !changed.contains(&(entry.uid.as_str(), *start))
+                })
+                .map(|start| Event {
+                    summary: entry.summary.clone(),
+                    start,
+                    end: start + entry.length,
+                    all_day: entry.all_day,
+                    busy: entry.busy,
+                })
+        })
+        .collect();
+    events.sort_by_key(|event| event.start);
+    events
+}
+
+/// Get the starts of an entry's occurrences overlapping a time range
+fn occurrences(entry: &Entry, from: NaiveDateTime, to: NaiveDateTime) -> Vec<NaiveDateTime> {
+    let overlaps = |start: NaiveDateTime| {
+        start < to && start + entry.length > from && !entry.exceptions.contains(&start)
+    };
+    let Some(rule) = &entry.rule else {
+        return if overlaps(entry.start) {
+            vec![entry.start]
+        } else {
+            Vec::new()
+        };
+    };
+
+    let mut starts = Vec::new();
+    let mut count = 0;
+    for period in 0..MAX_PERIODS {
+        for start in period_starts(entry.start, rule, period * rule.interval.max(1)) {
+            if start < entry.start {
+                continue;
+            }
+            count += 1;
+            if start >= to
+                || rule.until.map_or(false, |until| start > until)
+                || rule.count.map_or(false, |limit| count > limit)
+            {
+                return starts;
+            }
+            if overlaps(start) {
+                starts.push(start);
+            }
+        }
+    }
+    starts
+}
+
+/// Get the starts a rule gives in one period, in order
+///
+/// `offset` counts periods from the first one. Dates that don't exist, like
+/// February 30, are skipped.
+fn period_starts(first: NaiveDateTime, rule: &Rule, offset: i64) -> Vec<NaiveDateTime> {
+    let time = first.time();
+    let date = first.date();
+    let mut dates = match rule.frequency {
+        Frequency::Daily => vec![date + Duration::days(offset)],
+        Frequency::Weekly if rule.weekdays.is_empty() => vec![date + Duration::weeks(offset)],
+        Frequency::Weekly => {
+            let monday = date - Duration::days(date.weekday().num_days_from_monday() as i64)
+                + Duration::weeks(offset);
+            rule.weekdays
+                .iter()
+                .map(|(_, weekday)| monday + Duration::days(weekday.num_days_from_monday() as i64))
+                .collect()
+        }
+        Frequency::Monthly => {
+            let Some(month) = add_months(date.with_day(1).unwrap_or(date), offset) else {
+                return Vec::new();
+            };
+            month_dates(month, date.day(), rule)
+        }
+        Frequency::Yearly => add_months(date.with_day(1).unwrap_or(date), offset * 12)
+            .and_then(|month| month.with_day(date.day()))
+            .into_iter()
+            .collect(),
+    };
+    dates.sort();
+    dates.dedup();
+    dates.into_iter().map(|date| date.and_time(time)).collect()
+}
+
+/// Get the dates a monthly rule gives in a month
+fn month_dates(month: NaiveDate, first_day: u32, rule: &Rule) -> Vec<NaiveDate> {
+    let days: Vec<NaiveDate> = month
+        .iter_days()
+        .take_while(|day| day.month() == month.month())
+        .collect();
+    if rule.weekdays.is_empty() && rule.month_days.is_empty() {
+        return month.with_day(first_day).into_iter().collect();
+    }
+
+    let mut dates: Vec<NaiveDate> = rule
+        .month_days
+        .iter()
+        .filter_map(|&day| match day {
+            1.. => days.get(day as usize - 1),
+            _ => days
+                .len()
+                .checked_sub(day.unsigned_abs() as usize)
+                .and_then(|index| days.get(index)),
+        })
+        .copied()
+        .collect();
+    for &(week, weekday) in &rule.weekdays {
+        let matching: Vec<NaiveDate> = days
+            .iter()
+            .filter(|day| day.weekday() == weekday)
+            .copied()
+            .collect();
+        match week {
+            None => dates.extend(matching),
+            Some(week @ 1..) => dates.extend(matching.get(week as usize - 1)),
+            Some(week) => dates.extend(
+                matching
+                    .len()
+                    .checked_sub(week.unsigned_abs() as usize)
+                    .and_then(|index| matching.get(index)),
+            ),
+        }
+    }
+    dates
+}
+
+/// Move the first of a month by a number of months
+fn add_months(first: NaiveDate, months: i64) -> Option<NaiveDate> {
+    let total = first.year() as i64 * 12 + first.month0() as i64 + months;
+    NaiveDate::from_ymd_opt(
+        i32::try_from(total.div_euclid(12)).ok()?,
+        total.rem_euclid(12) as u32 + 1,
+        1,
+    )
+}
+
+/// Build an entry from the properties of an event
+fn entry(properties: &[(String, String, String)]) -> Option<Entry> {
+    let property = |name: &str| properties.iter().find(|(key, _, _)| key == name);
+    if property("STATUS").map_or(false, |(_, _, value)| {
+        value.eq_ignore_ascii_case("CANCELLED")
+    }) {
+        return None;
+    }
+
+    let (start, all_day) =
+        property("DTSTART").and_then(|(_, params, value)| parse_time(params, value))?;
+    let length = match (property("DTEND"), property("DURATION")) {
+        (Some((_, params, value)), _) => parse_time(params, value).map(|(end, _)| end - start),
+        (None, Some((_, _, value))) => parse_duration(value),
+        (None, None) => None,
+    }
+    .unwrap_or_else(|| {
+        if all_day {
+            Duration::days(1)
+        } else {
+            Duration::zero()
+        }
+    });
+
+    let exceptions = properties
+        .iter()
+        .filter(|(key, _, _)| key == "EXDATE")
+        .flat_map(|(_, params, value)| {
+            value
+                .split(',')
+                .filter_map(|value| parse_time(params, value).map(|(time, _)| time))
+        })
+        .collect();
+
+    Some(Entry {
+        uid: property("UID")
+            .map(|(_, _, value)| value.clone())
+            .unwrap_or_default(),
+        summary: property("SUMMARY")
+            .map_or_else(|| "Busy".to_string(), |(_, _, value)| unescape(value)),
+        start,
+        length: length.max(Duration::zero()),
+        all_day,
+        busy: !property("TRANSP").map_or(false, |(_, _, value)| {
+            value.eq_ignore_ascii_case("TRANSPARENT")
+        }),
+        rule: property("RRULE").and_then(|(_, _, value)| parse_rule(value)),
+        exceptions,
+        recurrence_id: property("RECURRENCE-ID")
+            .and_then(|(_, params, value)| parse_time(params, value))
+            .map(|(time, _)| time),
+    })
+}
+
+/// Join folded lines, which continue on lines starting with a space or tab
+fn unfold(ics: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in ics.lines() {
+        let continued = line.strip_prefix(' ').or_else(|| line.strip_prefix('\t'));
+        match (continued, lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// Split a content line into its upper-case name, its parameters and its value
+fn split_line(line: &str) -> Option<(String, String, String)> {
+    // Parameter values may be quoted and hold colons
+    let mut quoted = false;
+    let colon = line
+        .char_indices()
+        .find(|&(_, c)| {
+            if c == '"' {
+                quoted = !quoted;
+            }
+            c == ':' && !quoted
+        })?
+        .0;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+    let (name, params) = head.split_once(';').unwrap_or((head, ""));
+    Some((
+        name.trim().to_ascii_uppercase(),
+        params.to_ascii_uppercase(),
+        value.trim().to_string(),
+    ))
+}
+
+/// Read a date or date-time value, returning it in local time and whether it's a date
+fn parse_time(params: &str, value: &str) -> Option<(NaiveDateTime, bool)> {
+    let value = value.trim();
+    if (params.contains("VALUE=DATE") && !params.contains("VALUE=DATE-TIME")) || value.len() == 8 {
+        return NaiveDate::parse_from_str(value, "%Y%m%d")
+            .ok()?
+            .and_hms_opt(0, 0, 0)
+            .map(|time| (time, true));
+    }
+    match value.strip_suffix('Z') {
+        Some(utc) => {
+            let utc = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+            Some((
+                Utc.from_utc_datetime(&utc)
+                    .with_timezone(&Local)
+                    .naive_local(),
+                false,
+            ))
+        }
+        None => NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+            .ok()
+            .map(|time| (time, false)),
+    }
+}
+
+/// Read a duration like `PT1H30M` or `P1D`
+fn parse_duration(value: &str) -> Option<Duration> {
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    for c in value.strip_prefix('P')?.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            'W' | 'D' | 'H' | 'M' | 'S' => {
+                let amount: i64 = std::mem::take(&mut number).parse().ok()?;
+                total = total
+                    + match c {
+                        'W' => Duration::weeks(amount),
+                        'D' => Duration::days(amount),
+                        'H' => Duration::hours(amount),
+                        'M' => Duration::minutes(amount),
+                        _ => Duration::seconds(amount),
+                    };
+            }
+            _ => return None,
+        }
+    }
+    Some(if negative { -total } else { total })
+}
+
+/// Read a recurrence rule like `FREQ=WEEKLY;BYDAY=MO,WE;COUNT=10`
+///
+/// Rules repeating more often than daily aren't followed.
+fn parse_rule(value: &str) -> Option<Rule> {
+    let mut rule = Rule {
+        frequency: Frequency::Daily,
+        interval: 1,
+        count: None,
+        until: None,
+        weekdays: Vec::new(),
+        month_days: Vec::new(),
+    };
+    let mut frequency = None;
+    for part in value.split(';') {
+        let Some((key, value)) = part.split_once('=') else {
+            continue;
+        };
+        match key.trim().to_ascii_uppercase().as_str() {
+            "FREQ" => {
+                frequency = match value.trim().to_ascii_uppercase().as_str() {
+                    "DAILY" => Some(Frequency::Daily),
+                    "WEEKLY" => Some(Frequency::Weekly),
+                    "MONTHLY" => Some(Frequency::Monthly),
+                    "YEARLY" => Some(Frequency::Yearly),
+                    _ => None,
+                }
+            }
+            "INTERVAL" => {
+                rule.interval = value.trim().parse().ok().filter(|&interval| interval > 0)?
+            }
+            "COUNT" => rule.count = value.trim().parse().ok(),
+            "UNTIL" => {
+                rule.until = parse_time("", value).map(|(time, all_day)| {
+                    // A date ends the rule after that whole day
+                    if all_day {
+                        time + Duration::days(1) - Duration::seconds(1)
+                    } else {
+                        time
+                    }
+                })
+            }
+            "BYDAY" => rule.weekdays = value.split(',').filter_map(parse_weekday).collect(),
+            "BYMONTHDAY" => {
+                rule.month_days = value
+                    .split(',')
+                    .filter_map(|day| day.trim().parse().ok())
+                    .collect()
+            }
+            _ => {}
+        }
+    }
+    rule.frequency = frequency?;
+    Some(rule)
+}
+
+/// Read a weekday like `MO`, or `2TU` and `-1FR` for the week of the month
+fn parse_weekday(value: &str) -> Option<(Option<i32>, Weekday)> {
+    let value = value.trim().to_ascii_uppercase();
+    let (week, day) = value.split_at(value.len().checked_sub(2)?);
+    let weekday = match day {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    };
+    let week = match week.trim_start_matches('+') {
+        "" => None,
+        week => Some(week.parse().ok().filter(|&week: &i32| week != 0)?),
+    };
+    Some((week, weekday))
+}
+
+/// Undo the escaping of a text value
+fn unescape(value: &str) -> String {
+    let mut text = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => text.push(' '),
+            Some(escaped) => text.push(escaped),
+            None => {}
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(date: &str, time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(&format!("{} {}", date, time), "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    const ICS: &str = "BEGIN:VCALENDAR\r
+BEGIN:VEVENT\r
+UID:standup\r
+SUMMARY:Stand\r
+  up\r
+DTSTART;TZID=Europe/Berlin:20261012T093000\r
+DURATION:PT15M\r
+RRULE:FREQ=WEEKLY;BYDAY=MO,WE,FR;COUNT=5\r
+EXDATE;TZID=Europe/Berlin:20261014T093000\r
+BEGIN:VALARM\r
+SUMMARY:Reminder\r
+END:VALARM\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:standup\r
+RECURRENCE-ID;TZID=Europe/Berlin:20261016T093000\r
+SUMMARY:Stand up (moved)\r
+DTSTART;TZID=Europe/Berlin:20261016T110000\r
+DTEND;TZID=Europe/Berlin:20261016T111500\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:holiday\r
+SUMMARY:Holiday\\, at last\r
+DTSTART;VALUE=DATE:20261015\r
+TRANSP:TRANSPARENT\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:gone\r
+SUMMARY:Cancelled\r
+DTSTART:20261013T100000\r
+STATUS:CANCELLED\r
+END:VEVENT\r
+END:VCALENDAR\r
+";
+
+    #[test]
+    fn test_parse_and_expand() {
+        let entries = parse(ICS);
+        assert_eq!(entries.len(), 3);
+
+        let events = expand(
+            &entries,
+            at("2026-10-12", "00:00"),
+            at("2026-10-31", "00:00"),
+        );
+        let summary: Vec<(&str, NaiveDateTime)> = events
+            .iter()
+            .map(|event| (event.summary.as_str(), event.start))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Stand up", at("2026-10-12", "09:30")),
+                ("Holiday, at last", at("2026-10-15", "00:00")),
+                ("Stand up (moved)", at("2026-10-16", "11:00")),
+                ("Stand up", at("2026-10-19", "09:30")),
+                ("Stand up", at("2026-10-21", "09:30")),
+            ]
+        );
+        assert_eq!(events[0].end, at("2026-10-12", "09:45"));
+        assert!(events[0].busy && events[0].is_on(at("2026-10-12", "09:40")));
+        assert!(!events[1].busy && events[1].all_day);
+        assert_eq!(events[1].end, at("2026-10-16", "00:00"));
+    }
+
+    #[test]
+    fn test_monthly_rules() {
+        let rule = |value: &str| Entry {
+            uid: String::new(),
+            summary: String::new(),
+            start: at("2026-01-31", "18:00"),
+            length: Duration::hours(1),
+            all_day: false,
+            busy: true,
+            rule: parse_rule(value),
+            exceptions: Vec::new(),
+            recurrence_id: None,
+        };
+        let starts = |value: &str| {
+            occurrences(
+                &rule(value),
+                at("2026-01-01", "00:00"),
+                at("2026-05-01", "00:00"),
+            )
+        };
+
+        // Months without a 31st are skipped
+        assert_eq!(
+            starts("FREQ=MONTHLY"),
+            vec![at("2026-01-31", "18:00"), at("2026-03-31", "18:00")]
+        );
+        // Last Friday of the month, every other month
+        assert_eq!(
+            starts("FREQ=MONTHLY;INTERVAL=2;BYDAY=-1FR"),
+            vec![at("2026-03-27", "18:00")]
+        );
+        assert_eq!(
+            starts("FREQ=DAILY;UNTIL=20260202"),
+            vec![
+                at("2026-01-31", "18:00"),
+                at("2026-02-01", "18:00"),
+                at("2026-02-02", "18:00"),
+            ]
+        );
+        assert!(parse_rule("FREQ=HOURLY").is_none());
+    }
+
+    #[test]
+    fn test_values() {
+        assert_eq!(parse_duration("PT1H30M"), Some(Duration::minutes(90)));
+        assert_eq!(parse_duration("P1W"), Some(Duration::days(7)));
+        assert_eq!(parse_duration("-P1D"), Some(Duration::days(-1)));
+        assert_eq!(parse_duration("1H"), None);
+        assert_eq!(parse_weekday("-1FR"), Some((Some(-1), Weekday::Fri)));
+        assert_eq!(parse_weekday("SU"), Some((None, Weekday::Sun)));
+        assert_eq!(unescape("a\\nb\\;c\\\\"), "a b;c\\");
+        assert_eq!(
+            split_line("ATTENDEE;CN=\"A: B\":mailto:a@b").map(|(name, _, value)| (name, value)),
+            Some(("ATTENDEE".to_string(), "mailto:a@b".to_string()))
+        );
+    }
+}
@@ -0,0 +1,289 @@
+//! Automatic wallpaper downloads from online sources
+use crate::core::config::SourcesConfig;
+use crate::core::{
+    runtime, AppError, AppResult, ChangeHooks, Config, CurrentWallpaperState,
+    CurrentWallpaperStore, EventLog, HttpClient, SecretStore, WallpaperHistory, WallpaperInfo,
+    WallpaperType,
+};
+use crate::platform::display;
+use crate::platform::WallpaperManager;
+use crate::sources::cache;
+use crate::sources::{
+    create_source, search_filtered, ContentFilter, ResolutionFilter, SearchQuery,
+};
+use chrono::{DateTime, Local};
+use log::{debug, error, info, warn};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration as StdDuration, Instant};
+
+/// How often the download cache limits are enforced
+const CLEANUP_INTERVAL: StdDuration = StdDuration::from_secs(60 * 60);
+
+/// Periodically downloads wallpapers from the configured source
+pub struct AutoDownloader {
+    /// Source configuration
+    config: Arc<Mutex<SourcesConfig>>,
+
+    /// Download directory
+    downloads_dir: PathBuf,
+
+    /// Platform-specific wallpaper manager
+    wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>,
+
+    /// Secret store for source API keys
+    secret_store: Arc<SecretStore>,
+
+    /// Download thread handle
+    download_thread: Option<thread::JoinHandle<()>>,
+
+    /// Whether the downloader is running
+    is_running: Arc<Mutex<bool>>,
+
+    /// Last download run
+    last_run: Arc<Mutex<Option<DateTime<Local>>>>,
+}
+
+#[allow(dead_code)]
+impl AutoDownloader {
+    /// Create a new auto-downloader
+    pub fn new(
+        config: &Config,
+        wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>,
+        secret_store: Arc<SecretStore>,
+    ) -> Self {
+        Self {
+            config: Arc::new(Mutex::new(config.sources.clone())),
+            downloads_dir: config.get_downloads_dir(),
+            wallpaper_manager,
+            secret_store,
+            download_thread: None,
+            is_running: Arc::new(Mutex::new(false)),
+            last_run: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Update the source configuration
+    pub fn update_config(&self, config: SourcesConfig) {
+        *self.config.lock().unwrap() = config;
+    }
+
+    /// Get the time of the last download run
+    pub fn last_run(&self) -> Option<DateTime<Local>> {
+        *self.last_run.lock().unwrap()
+    }
+
+    /// Start the download thread
+    pub fn start(&mut self) -> AppResult<()> {
+        if *self.is_running.lock().unwrap() {
+            debug!("Auto-downloader is already running");
+            return Ok(());
+        }
+
+        *self.is_running.lock().unwrap() = true;
+
+        let config = self.config.clone();
+        let downloads_dir = self.downloads_dir.clone();
+        let wallpaper_manager = self.wallpaper_manager.clone();
+        let secret_store = self.secret_store.clone();
+        let is_running = self.is_running.clone();
+        let last_run = self.last_run.clone();
+
+        self.download_thread = Some(thread::spawn(move || {
+            let mut last_cleanup: Option<Instant> = None;
+
+            while *is_running.lock().unwrap() {
+                let sources_config = config.lock().unwrap().clone();
+                let auto_download = &sources_config.auto_download;
+
+                let due = auto_download.enabled
+                    && last_run.lock().unwrap().map_or(true, |last| {
+                        Local::now().signed_duration_since(last)
+                            >= chrono::Duration::minutes(auto_download.interval.max(1) as i64)
+                    });
+
+                if due {
+                    *last_run.lock().unwrap() = Some(Local::now());
+
+                    match Self::download_batch(&sources_config, &downloads_dir, &secret_store) {
+                        Ok(paths) => {
+                            if auto_download.set_as_wallpaper {
+                                if let Some(path) = paths.first() {
+                                    Self::apply_wallpaper(&wallpaper_manager, path);
+                                }
+                            }
+                        }
+                        Err(AppError::NetworkError(e)) => debug!("Auto-download skipped: {}", e),
+                        Err(e) => {
+                            error!("Auto-download failed: {}", e);
+                            EventLog::shared().error("auto-download", "Auto-download failed", e);
+                        }
+                    }
+
+                    // Enforce the limits right after new files arrive
+                    last_cleanup = None;
+                }
+
+                if last_cleanup.map_or(true, |last| last.elapsed() >= CLEANUP_INTERVAL) {
+                    last_cleanup = Some(Instant::now());
+                    Self::enforce_cache_limits(&sources_config, &downloads_dir);
+                }
+
+                thread::sleep(StdDuration::from_secs(1));
+            }
+        }));
+
+        info!("Auto-downloader started");
+        Ok(())
+    }
+
+    /// Stop the download thread
+    pub fn stop(&mut self) -> AppResult<()> {
+        if !*self.is_running.lock().unwrap() {
+            debug!("Auto-downloader is not running");
+            return Ok(());
+        }
+
+        *self.is_running.lock().unwrap() = false;
+
+        if let Some(thread) = self.download_thread.take() {
+            thread.join().map_err(|e| {
+                AppError::Other(format!("Failed to join auto-download thread: {:?}", e))
+            })?;
+        }
+
+        info!("Auto-downloader stopped");
+        Ok(())
+    }
+
+    /// Download a batch of wallpapers right away
+    pub fn run_now(&self) -> AppResult<Vec<PathBuf>> {
+        self.runner()()
+    }
+
+    /// Get a function downloading a batch like `run_now`, for use on other threads
+    pub fn runner(&self) -> impl FnOnce() -> AppResult<Vec<PathBuf>> + Send + 'static {
+        let sources_config = self.config.lock().unwrap().clone();
+        let downloads_dir = self.downloads_dir.clone();
+        let secret_store = self.secret_store.clone();
+        let last_run = self.last_run.clone();
+        move || {
+            *last_run.lock().unwrap() = Some(Local::now());
+            Self::download_batch(&sources_config, &downloads_dir, &secret_store)
+        }
+    }
+
+    /// Enforce the download cache limits from the download thread
+    ///
+    /// The full configuration is reloaded so favorites and the schedule are current.
+    fn enforce_cache_limits(config: &SourcesConfig, downloads_dir: &PathBuf) {
+        let protected = match Config::load() {
+            Ok(full_config) => cache::protected_paths(&full_config),
+            Err(e) => {
+                warn!(
+                    "Skipping download cache cleanup, failed to load config: {}",
+                    e
+                );
+                return;
+            }
+        };
+
+        if let Err(e) = cache::cleanup(downloads_dir, &config.cache, &protected) {
+            error!("Download cache cleanup failed: {}", e);
+        }
+    }
+
+    /// Search the configured source and download matching wallpapers
+    fn download_batch(
+        config: &SourcesConfig,
+        downloads_dir: &PathBuf,
+        secret_store: &Arc<SecretStore>,
+    ) -> AppResult<Vec<PathBuf>> {
+        let auto_download = &config.auto_download;
+        let source = create_source(&auto_download.source, secret_store.clone())?;
+
+        let monitors = if config.resolution_filter.enabled {
+            display::monitors().unwrap_or_else(|e| {
+                warn!(
+                    "Failed to query monitors, resolution filter disabled: {}",
+                    e
+                );
+                Vec::new()
+            })
+        } else {
+            Vec::new()
+        };
+        let filter = ResolutionFilter::new(&monitors, &config.resolution_filter);
+
+        let query = SearchQuery {
+            query: auto_download.query.clone(),
+            min_resolution: filter.min_resolution(),
+            aspect_ratios: filter.aspect_ratios(),
+            random: true,
+            purity: config.content_filter.max_purity,
+        };
+
+        let content_filter = ContentFilter::new(&config.content_filter);
+        let results = search_filtered(source.as_ref(), &query, &content_filter)?;
+        let total = results.len();
+        let candidates = results
+            .into_iter()
+            .filter(|w| filter.accepts_wallpaper(w))
+            .filter(|w| !downloads_dir.join(w.file_name()).exists())
+            .take(auto_download.per_run.max(1) as usize)
+            .collect::<Vec<_>>();
+
+        debug!(
+            "{} of {} results from {} suit the connected monitors",
+            candidates.len(),
+            total,
+            source.name()
+        );
+
+        let http_client = HttpClient::shared();
+        let mut downloaded = Vec::new();
+        for wallpaper in candidates {
+            let dest = downloads_dir.join(wallpaper.file_name());
+            match http_client.download(source.name(), &wallpaper.url, &dest) {
+                Ok(()) => downloaded.push(dest),
+                Err(e) => warn!("Failed to download {}: {}", wallpaper.url, e),
+            }
+        }
+
+        info!(
+            "Auto-downloaded {} wallpapers from {}",
+            downloaded.len(),
+            source.name()
+        );
+        Ok(downloaded)
+    }
+
+    /// Apply a downloaded wallpaper
+    fn apply_wallpaper(
+        wallpaper_manager: &Arc<dyn WallpaperManager + Send + Sync>,
+        path: &PathBuf,
+    ) {
+        match runtime::block_on(wallpaper_manager.set_static_wallpaper(path)) {
+            Ok(()) => {
+                info!("Applied downloaded wallpaper: {}", path.display());
+                let wallpaper = WallpaperInfo::new(WallpaperType::Static, Some(path.clone()), None);
+                CurrentWallpaperStore::shared().set(CurrentWallpaperState::new(
+                    wallpaper.clone(),
+                    Vec::new(),
+                    None,
+                ));
+                WallpaperHistory::shared().record(&wallpaper);
+                ChangeHooks::shared().run(&wallpaper);
+            }
+            Err(e) => {
+                error!("Failed to apply downloaded wallpaper: {}", e);
+                EventLog::shared().error(
+                    "auto-download",
+                    "Failed to apply downloaded wallpaper",
+                    e,
+                );
+            }
+        }
+    }
+}
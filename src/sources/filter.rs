@@ -0,0 +1,223 @@
+//! Filters applied to online source results
+use crate::core::config::{ContentFilterConfig, Purity, ResolutionFilterConfig};
+use crate::platform::display::MonitorInfo;
+use crate::sources::SourceWallpaper;
+use std::collections::HashMap;
+
+/// Filters wallpapers that don't suit any connected monitor
+#[derive(Debug, Clone)]
+pub struct ResolutionFilter {
+    /// Monitor resolutions (width, height)
+    monitors: Vec<(u32, u32)>,
+
+    /// Whether the aspect ratio must match
+    match_aspect_ratio: bool,
+
+    /// Allowed relative aspect ratio difference
+    aspect_tolerance: f32,
+
+    /// Minimum image size relative to the monitor
+    min_scale: f32,
+}
+
+impl ResolutionFilter {
+    /// Create a filter for the given monitors
+    pub fn new(monitors: &[MonitorInfo], config: &ResolutionFilterConfig) -> Self {
+        Self {
+            monitors: monitors.iter().map(|m| (m.width, m.height)).collect(),
+            match_aspect_ratio: config.match_aspect_ratio,
+            aspect_tolerance: config.aspect_tolerance.max(0.0),
+            min_scale: config.min_scale.max(0.0),
+        }
+    }
+
+    /// Check whether an image of the given size suits at least one monitor
+    ///
+    /// With no known monitors every image is accepted.
+    pub fn accepts(&self, width: u32, height: u32) -> bool {
+        if self.monitors.is_empty() {
+            return true;
+        }
+
+        self.monitors
+            .iter()
+            .any(|&(mw, mh)| self.suits(mw, mh, width, height))
+    }
+
+    /// Check whether a wallpaper suits at least one monitor
+    pub fn accepts_wallpaper(&self, wallpaper: &SourceWallpaper) -> bool {
+        self.accepts(wallpaper.width, wallpaper.height)
+    }
+
+    /// Get the smallest monitor resolution, used as a search hint
+    pub fn min_resolution(&self) -> Option<(u32, u32)> {
+        self.monitors
+            .iter()
+            .min_by_key(|(w, h)| w * h)
+            .map(|&(w, h)| {
+                (
+                    (w as f32 * self.min_scale) as u32,
+                    (h as f32 * self.min_scale) as u32,
+                )
+            })
+    }
+
+    /// Get the reduced aspect ratios of the monitors, used as a search hint
+    pub fn aspect_ratios(&self) -> Vec<(u32, u32)> {
+        if !self.match_aspect_ratio {
+            return Vec::new();
+        }
+
+        let mut ratios = Vec::new();
+        for &(w, h) in &self.monitors {
+            let divisor = gcd(w, h).max(1);
+            let ratio = (w / divisor, h / divisor);
+            if !ratios.contains(&ratio) {
+                ratios.push(ratio);
+            }
+        }
+        ratios
+    }
+
+    /// Check an image against one monitor
+    fn suits(&self, monitor_width: u32, monitor_height: u32, width: u32, height: u32) -> bool {
+        if monitor_width == 0 || monitor_height == 0 || width == 0 || height == 0 {
+            return false;
+        }
+
+        let min_width = monitor_width as f32 * self.min_scale;
+        let min_height = monitor_height as f32 * self.min_scale;
+        if (width as f32) < min_width || (height as f32) < min_height {
+            return false;
+        }
+
+        if self.match_aspect_ratio {
+            let monitor_ratio = monitor_width as f32 / monitor_height as f32;
+            let image_ratio = width as f32 / height as f32;
+            if (image_ratio - monitor_ratio).abs() / monitor_ratio > self.aspect_tolerance {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// Filters wallpapers above the allowed content purity
+#[derive(Debug, Clone, Default)]
+pub struct ContentFilter {
+    /// Highest purity allowed from any source
+    max_purity: Purity,
+
+    /// Per-provider purity
+    providers: HashMap<String, Purity>,
+}
+
+impl ContentFilter {
+    /// Create a filter from the content filter configuration
+    pub fn new(config: &ContentFilterConfig) -> Self {
+        Self {
+            max_purity: config.max_purity,
+            providers: config.providers.clone(),
+        }
+    }
+
+    /// Get the highest purity allowed for a provider
+    ///
+    /// Provider settings can only narrow the global policy, never widen it.
+    pub fn purity_for(&self, provider: &str) -> Purity {
+        self.providers
+            .get(provider)
+            .map_or(self.max_purity, |&purity| purity.min(self.max_purity))
+    }
+
+    /// Check whether a wallpaper is allowed by its provider's purity
+    pub fn accepts(&self, wallpaper: &SourceWallpaper) -> bool {
+        wallpaper.purity <= self.purity_for(&wallpaper.source)
+    }
+}
+
+/// Greatest common divisor
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(width: u32, height: u32) -> MonitorInfo {
+        MonitorInfo::new("test", width, height)
+    }
+
+    #[test]
+    fn test_ultrawide_rejects_16_9() {
+        let filter =
+            ResolutionFilter::new(&[monitor(3440, 1440)], &ResolutionFilterConfig::default());
+
+        assert!(filter.accepts(3440, 1440));
+        assert!(filter.accepts(5120, 2160));
+        assert!(!filter.accepts(3840, 2160));
+        assert!(!filter.accepts(1720, 720));
+    }
+
+    #[test]
+    fn test_portrait_monitor() {
+        let filter = ResolutionFilter::new(
+            &[monitor(1920, 1080), monitor(1080, 1920)],
+            &ResolutionFilterConfig::default(),
+        );
+
+        assert!(filter.accepts(1080, 1920));
+        assert!(filter.accepts(2560, 1440));
+        assert!(!filter.accepts(1920, 1920));
+        assert_eq!(filter.aspect_ratios(), vec![(16, 9), (9, 16)]);
+    }
+
+    #[test]
+    fn test_no_monitors_accepts_everything() {
+        let filter = ResolutionFilter::new(&[], &ResolutionFilterConfig::default());
+        assert!(filter.accepts(640, 480));
+    }
+
+    #[test]
+    fn test_provider_purity_capped_by_global() {
+        let mut config = ContentFilterConfig {
+            max_purity: Purity::Sketchy,
+            ..Default::default()
+        };
+        config
+            .providers
+            .insert("wallhaven".to_string(), Purity::Nsfw);
+        config.providers.insert("strict".to_string(), Purity::Sfw);
+        let filter = ContentFilter::new(&config);
+
+        assert_eq!(filter.purity_for("wallhaven"), Purity::Sketchy);
+        assert_eq!(filter.purity_for("strict"), Purity::Sfw);
+        assert_eq!(filter.purity_for("other"), Purity::Sketchy);
+    }
+
+    #[test]
+    fn test_content_filter_rejects_above_purity() {
+        let filter = ContentFilter::new(&ContentFilterConfig::default());
+        let mut wallpaper = SourceWallpaper {
+            source: "wallhaven".to_string(),
+            id: "abc123".to_string(),
+            url: "https://example.com/abc123.jpg".to_string(),
+            thumbnail_url: None,
+            page_url: None,
+            width: 1920,
+            height: 1080,
+            extension: "jpg".to_string(),
+            purity: Purity::Sfw,
+        };
+
+        assert!(filter.accepts(&wallpaper));
+        wallpaper.purity = Purity::Sketchy;
+        assert!(!filter.accepts(&wallpaper));
+    }
+}
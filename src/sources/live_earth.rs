@@ -0,0 +1,398 @@
+//! Live Earth wallpapers from geostationary weather satellites
+//!
+//! Himawari imagery is served as 550px tiles that are stitched into a full
+//! disk; GOES imagery is available as a single full-disk image.
+use crate::core::config::{LiveEarthConfig, Satellite};
+use crate::core::{
+    runtime, AppError, AppResult, ChangeHooks, Config, CurrentWallpaperState,
+    CurrentWallpaperStore, EventLog, HttpClient, RateLimit, WallpaperHistory, WallpaperInfo,
+    WallpaperType,
+};
+use crate::platform::display;
+use crate::platform::WallpaperManager;
+use chrono::{DateTime, Local, NaiveDateTime};
+use image::{imageops, RgbImage};
+use log::{debug, error, info, warn};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration as StdDuration;
+
+/// Himawari rate limit key
+const HIMAWARI_PROVIDER: &str = "himawari";
+
+/// GOES rate limit key
+const GOES_PROVIDER: &str = "goes";
+
+/// Himawari image base URL
+const HIMAWARI_BASE_URL: &str = "https://himawari8.nict.go.jp/img/D531106";
+
+/// Himawari tile size in pixels
+const HIMAWARI_TILE_SIZE: u32 = 550;
+
+/// Periodically fetches satellite imagery and sets it as the wallpaper
+pub struct LiveEarth {
+    /// Live Earth configuration
+    config: Arc<Mutex<LiveEarthConfig>>,
+
+    /// Directory for generated images
+    output_dir: PathBuf,
+
+    /// Platform-specific wallpaper manager
+    wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>,
+
+    /// Update thread handle
+    update_thread: Option<thread::JoinHandle<()>>,
+
+    /// Whether the updater is running
+    is_running: Arc<Mutex<bool>>,
+
+    /// Last successful update
+    last_update: Arc<Mutex<Option<DateTime<Local>>>>,
+}
+
+#[allow(dead_code)]
+impl LiveEarth {
+    /// Create a new Live Earth updater
+    pub fn new(
+        config: &Config,
+        wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>,
+    ) -> Self {
+        let http_client = HttpClient::shared();
+        http_client.set_rate_limit(HIMAWARI_PROVIDER, RateLimit::per_minute(120));
+        http_client.set_rate_limit(GOES_PROVIDER, RateLimit::per_minute(10));
+
+        Self {
+            config: Arc::new(Mutex::new(config.sources.live_earth.clone())),
+            output_dir: config.get_live_earth_dir(),
+            wallpaper_manager,
+            update_thread: None,
+            is_running: Arc::new(Mutex::new(false)),
+            last_update: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Update the Live Earth configuration
+    pub fn update_config(&self, config: LiveEarthConfig) {
+        *self.config.lock().unwrap() = config;
+    }
+
+    /// Get the time of the last successful update
+    pub fn last_update(&self) -> Option<DateTime<Local>> {
+        *self.last_update.lock().unwrap()
+    }
+
+    /// Start the update thread
+    pub fn start(&mut self) -> AppResult<()> {
+        if *self.is_running.lock().unwrap() {
+            debug!("Live Earth is already running");
+            return Ok(());
+        }
+
+        *self.is_running.lock().unwrap() = true;
+
+        let config = self.config.clone();
+        let output_dir = self.output_dir.clone();
+        let wallpaper_manager = self.wallpaper_manager.clone();
+        let is_running = self.is_running.clone();
+        let last_update = self.last_update.clone();
+
+        self.update_thread = Some(thread::spawn(move || {
+            let mut last_attempt: Option<DateTime<Local>> = None;
+
+            while *is_running.lock().unwrap() {
+                let live_earth = config.lock().unwrap().clone();
+
+                let due = live_earth.enabled
+                    && last_attempt.map_or(true, |last| {
+                        Local::now().signed_duration_since(last)
+                            >= chrono::Duration::minutes(live_earth.interval.max(10) as i64)
+                    });
+
+                if due {
+                    last_attempt = Some(Local::now());
+
+                    match Self::update(&live_earth, &output_dir, &wallpaper_manager) {
+                        Ok(_) => *last_update.lock().unwrap() = Some(Local::now()),
+                        Err(AppError::NetworkError(e)) => {
+                            debug!("Live Earth update skipped: {}", e)
+                        }
+                        Err(e) => {
+                            error!("Live Earth update failed: {}", e);
+                            EventLog::shared().error("live-earth", "Live Earth update failed", e);
+                        }
+                    }
+                }
+
+                thread::sleep(StdDuration::from_secs(1));
+            }
+        }));
+
+        info!("Live Earth started");
+        Ok(())
+    }
+
+    /// Stop the update thread
+    pub fn stop(&mut self) -> AppResult<()> {
+        if !*self.is_running.lock().unwrap() {
+            debug!("Live Earth is not running");
+            return Ok(());
+        }
+
+        *self.is_running.lock().unwrap() = false;
+
+        if let Some(thread) = self.update_thread.take() {
+            thread.join().map_err(|e| {
+                AppError::Other(format!("Failed to join Live Earth thread: {:?}", e))
+            })?;
+        }
+
+        info!("Live Earth stopped");
+        Ok(())
+    }
+
+    /// Fetch the latest image and apply it right away
+    pub fn update_now(&self) -> AppResult<PathBuf> {
+        self.updater()()
+    }
+
+    /// Get a function updating like `update_now`, for use on other threads
+    pub fn updater(&self) -> impl FnOnce() -> AppResult<PathBuf> + Send + 'static {
+        let live_earth = self.config.lock().unwrap().clone();
+        let output_dir = self.output_dir.clone();
+        let wallpaper_manager = self.wallpaper_manager.clone();
+        let last_update = self.last_update.clone();
+        move || {
+            let path = Self::update(&live_earth, &output_dir, &wallpaper_manager)?;
+            *last_update.lock().unwrap() = Some(Local::now());
+            Ok(path)
+        }
+    }
+
+    /// Fetch, compose and apply the latest image
+    fn update(
+        config: &LiveEarthConfig,
+        output_dir: &Path,
+        wallpaper_manager: &Arc<dyn WallpaperManager + Send + Sync>,
+    ) -> AppResult<PathBuf> {
+        let disk = match config.satellite {
+            Satellite::Himawari => fetch_himawari(config.detail, output_dir)?,
+            Satellite::GoesEast | Satellite::GoesWest => {
+                fetch_goes(config.satellite, config.detail, output_dir)?
+            }
+        };
+
+        let target = display::monitors()
+            .ok()
+            .and_then(|monitors| monitors.into_iter().find(|monitor| monitor.primary))
+            .map(|monitor| (monitor.width, monitor.height));
+        let image = compose(&disk, config.zoom, target);
+
+        // A new file name each time so desktops that cache by path pick up the change
+        let path = output_dir.join(format!("earth-{}.png", Local::now().format("%Y%m%d%H%M%S")));
+        image.save(&path).map_err(|e| {
+            AppError::WallpaperError(format!("Failed to save Live Earth image: {}", e))
+        })?;
+        remove_old_images(output_dir, &path);
+
+        runtime::block_on(wallpaper_manager.set_static_wallpaper(&path))?;
+
+        info!("Applied Live Earth image: {}", path.display());
+        let wallpaper = WallpaperInfo::new(WallpaperType::Static, Some(path.clone()), None);
+        CurrentWallpaperStore::shared().set(CurrentWallpaperState::new(
+            wallpaper.clone(),
+            Vec::new(),
+            None,
+        ));
+        WallpaperHistory::shared().record(&wallpaper);
+        ChangeHooks::shared().run(&wallpaper);
+        Ok(path)
+    }
+}
+
+/// Fetch the latest Himawari full disk by stitching its tiles
+fn fetch_himawari(detail: u32, output_dir: &Path) -> AppResult<RgbImage> {
+    let http_client = HttpClient::shared();
+    let level = himawari_level(detail);
+
+    let latest = http_client
+        .get(
+            HIMAWARI_PROVIDER,
+            &format!("{}/latest.json", HIMAWARI_BASE_URL),
+        )?
+        .json()?;
+    let date = latest["date"].as_str().ok_or_else(|| {
+        AppError::NetworkError("Himawari response is missing the image date".to_string())
+    })?;
+    let timestamp = NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M:%S").map_err(|e| {
+        AppError::NetworkError(format!("Invalid Himawari image date '{}': {}", date, e))
+    })?;
+    debug!(
+        "Fetching Himawari image from {} at level {}",
+        timestamp, level
+    );
+
+    let tile_dir = output_dir.join("tiles");
+    let mut disk = RgbImage::new(HIMAWARI_TILE_SIZE * level, HIMAWARI_TILE_SIZE * level);
+    for y in 0..level {
+        for x in 0..level {
+            let tile_path = tile_dir.join(format!("{}_{}.png", x, y));
+            http_client.download(
+                HIMAWARI_PROVIDER,
+                &himawari_tile_url(&timestamp, level, x, y),
+                &tile_path,
+            )?;
+
+            let tile = image::open(&tile_path).map_err(|e| {
+                AppError::NetworkError(format!("Invalid Himawari tile {}_{}: {}", x, y, e))
+            })?;
+            imageops::replace(
+                &mut disk,
+                &tile.to_rgb8(),
+                (x * HIMAWARI_TILE_SIZE) as i64,
+                (y * HIMAWARI_TILE_SIZE) as i64,
+            );
+        }
+    }
+
+    let _ = std::fs::remove_dir_all(&tile_dir);
+    Ok(disk)
+}
+
+/// Fetch the latest GOES full disk
+fn fetch_goes(satellite: Satellite, detail: u32, output_dir: &Path) -> AppResult<RgbImage> {
+    let url = goes_url(satellite, detail);
+    debug!("Fetching GOES image from {}", url);
+
+    let path = output_dir.join("goes.jpg");
+    HttpClient::shared().download(GOES_PROVIDER, &url, &path)?;
+
+    let image = image::open(&path)
+        .map_err(|e| AppError::NetworkError(format!("Invalid GOES image: {}", e)))?;
+    let _ = std::fs::remove_file(&path);
+    Ok(image.to_rgb8())
+}
+
+/// Get the Himawari tile grid size for a detail level
+fn himawari_level(detail: u32) -> u32 {
+    match detail {
+        0..=1 => 1,
+        2..=3 => 2,
+        4..=7 => 4,
+        _ => 8,
+    }
+}
+
+/// Build a Himawari tile URL
+fn himawari_tile_url(timestamp: &NaiveDateTime, level: u32, x: u32, y: u32) -> String {
+    format!(
+        "{}/{}d/{}/{}_{}_{}.png",
+        HIMAWARI_BASE_URL,
+        level,
+        HIMAWARI_TILE_SIZE,
+        timestamp.format("%Y/%m/%d/%H%M%S"),
+        x,
+        y
+    )
+}
+
+/// Build a GOES full-disk image URL
+fn goes_url(satellite: Satellite, detail: u32) -> String {
+    let spacecraft = match satellite {
+        Satellite::GoesWest => "GOES18",
+        _ => "GOES19",
+    };
+    let size = match detail {
+        0..=1 => "678x678",
+        2..=3 => "1808x1808",
+        _ => "5424x5424",
+    };
+    format!(
+        "https://cdn.star.nesdis.noaa.gov/{}/ABI/FD/GEOCOLOR/{}.jpg",
+        spacecraft, size
+    )
+}
+
+/// Crop the disk to the zoom level and center it on a canvas of the target size
+///
+/// Without a target size the cropped disk is returned as is.
+fn compose(disk: &RgbImage, zoom: f32, target: Option<(u32, u32)>) -> RgbImage {
+    let zoom = zoom.clamp(1.0, 8.0);
+    let crop_size = ((disk.width().min(disk.height()) as f32 / zoom) as u32).max(1);
+    let crop_x = (disk.width() - crop_size) / 2;
+    let crop_y = (disk.height() - crop_size) / 2;
+    let cropped = imageops::crop_imm(disk, crop_x, crop_y, crop_size, crop_size).to_image();
+
+    let (width, height) = match target {
+        Some((width, height)) if width > 0 && height > 0 => (width, height),
+        _ => return cropped,
+    };
+
+    // Zoomed images fill the screen, the full disk fits inside it
+    let side = if zoom > 1.0 {
+        width.max(height)
+    } else {
+        width.min(height)
+    };
+    let scaled = imageops::resize(&cropped, side, side, imageops::FilterType::Triangle);
+
+    let mut canvas = RgbImage::new(width, height);
+    let x = (width as i64 - side as i64) / 2;
+    let y = (height as i64 - side as i64) / 2;
+    imageops::overlay(&mut canvas, &scaled, x, y);
+    canvas
+}
+
+/// Remove previously generated images
+fn remove_old_images(output_dir: &Path, keep: &Path) {
+    let entries = match std::fs::read_dir(output_dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_earth_image = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map_or(false, |name| name.starts_with("earth-"));
+
+        if is_earth_image && path != keep {
+            if let Err(e) = std::fs::remove_file(&path) {
+                warn!(
+                    "Failed to remove old Live Earth image {}: {}",
+                    path.display(),
+                    e
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_himawari_tile_url() {
+        let timestamp =
+            NaiveDateTime::parse_from_str("2024-05-01 03:40:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        assert_eq!(
+            himawari_tile_url(&timestamp, 4, 1, 2),
+            "https://himawari8.nict.go.jp/img/D531106/4d/550/2024/05/01/034000_1_2.png"
+        );
+    }
+
+    #[test]
+    fn test_compose_fits_disk_on_canvas() {
+        let disk = RgbImage::from_pixel(100, 100, image::Rgb([255, 255, 255]));
+
+        let image = compose(&disk, 1.0, Some((160, 90)));
+        assert_eq!(image.dimensions(), (160, 90));
+        assert_eq!(image.get_pixel(0, 45), &image::Rgb([0, 0, 0]));
+        assert_eq!(image.get_pixel(80, 45), &image::Rgb([255, 255, 255]));
+
+        let zoomed = compose(&disk, 2.0, Some((160, 90)));
+        assert_eq!(zoomed.get_pixel(0, 45), &image::Rgb([255, 255, 255]));
+    }
+}
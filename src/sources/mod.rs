@@ -0,0 +1,131 @@
+//! Online wallpaper sources
+pub mod cache;
+pub mod calendar;
+pub mod downloader;
+pub mod filter;
+pub mod live_earth;
+pub mod wallhaven;
+pub mod weather;
+
+pub use calendar::Calendar;
+pub use downloader::AutoDownloader;
+pub use filter::{ContentFilter, ResolutionFilter};
+pub use live_earth::LiveEarth;
+pub use wallhaven::WallhavenSource;
+
+use crate::core::config::Purity;
+use crate::core::{AppError, AppResult, SecretStore};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Wallpaper offered by an online source
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceWallpaper {
+    /// Source name
+    pub source: String,
+
+    /// Source-specific wallpaper ID
+    pub id: String,
+
+    /// Full-size image URL
+    pub url: String,
+
+    /// Thumbnail URL
+    pub thumbnail_url: Option<String>,
+
+    /// Page URL for attribution
+    pub page_url: Option<String>,
+
+    /// Image width in pixels
+    pub width: u32,
+
+    /// Image height in pixels
+    pub height: u32,
+
+    /// File extension of the full-size image
+    pub extension: String,
+
+    /// Content purity reported by the source
+    pub purity: Purity,
+}
+
+impl SourceWallpaper {
+    /// Get the file name used when downloading
+    pub fn file_name(&self) -> String {
+        format!("{}-{}.{}", self.source, self.id, self.extension)
+    }
+}
+
+/// Search parameters
+#[derive(Debug, Clone, Default)]
+pub struct SearchQuery {
+    /// Free-text query
+    pub query: String,
+
+    /// Minimum resolution hint (width, height)
+    pub min_resolution: Option<(u32, u32)>,
+
+    /// Aspect ratio hints (width, height)
+    pub aspect_ratios: Vec<(u32, u32)>,
+
+    /// Whether results should be randomized
+    pub random: bool,
+
+    /// Highest purity to request
+    pub purity: Purity,
+}
+
+/// Online wallpaper source
+pub trait WallpaperSource: Send + Sync {
+    /// Get the source name
+    fn name(&self) -> &str;
+
+    /// Search for wallpapers
+    fn search(&self, query: &SearchQuery) -> AppResult<Vec<SourceWallpaper>>;
+}
+
+/// Create a source by name
+pub fn create_source(
+    name: &str,
+    secret_store: Arc<SecretStore>,
+) -> AppResult<Arc<dyn WallpaperSource>> {
+    match name {
+        "wallhaven" => Ok(Arc::new(WallhavenSource::new(secret_store))),
+        _ => Err(AppError::ConfigError(format!(
+            "Unknown wallpaper source: {}",
+            name
+        ))),
+    }
+}
+
+/// Search a source with the content filter applied
+///
+/// The query purity is lowered to what the filter allows for the source, and any result
+/// above it is dropped in case the source ignores the request.
+pub fn search_filtered(
+    source: &dyn WallpaperSource,
+    query: &SearchQuery,
+    content_filter: &ContentFilter,
+) -> AppResult<Vec<SourceWallpaper>> {
+    let mut query = query.clone();
+    query.purity = query.purity.min(content_filter.purity_for(source.name()));
+
+    let results = source.search(&query)?;
+    let total = results.len();
+    let allowed = results
+        .into_iter()
+        .filter(|w| content_filter.accepts(w))
+        .collect::<Vec<_>>();
+
+    if allowed.len() < total {
+        debug!(
+            "Content filter removed {} of {} results from {}",
+            total - allowed.len(),
+            total,
+            source.name()
+        );
+    }
+
+    Ok(allowed)
+}
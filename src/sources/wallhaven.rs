@@ -0,0 +1,147 @@
+//! Wallhaven source
+use crate::core::config::Purity;
+use crate::core::http::encode_query_component;
+use crate::core::{AppError, AppResult, HttpClient, RateLimit, SecretStore};
+use crate::sources::{SearchQuery, SourceWallpaper, WallpaperSource};
+use log::{debug, error};
+use std::sync::Arc;
+
+/// Source name and rate limit key
+const SOURCE_NAME: &str = "wallhaven";
+
+/// Secret store key for the Wallhaven API key
+pub const API_KEY_SECRET: &str = "source.wallhaven.api_key";
+
+/// Wallhaven wallpaper source
+pub struct WallhavenSource {
+    /// Shared HTTP client
+    http_client: Arc<HttpClient>,
+
+    /// Secret store for the API key
+    secret_store: Arc<SecretStore>,
+}
+
+impl WallhavenSource {
+    /// Create a new Wallhaven source
+    pub fn new(secret_store: Arc<SecretStore>) -> Self {
+        let http_client = HttpClient::shared();
+        // Wallhaven allows 45 API calls per minute
+        http_client.set_rate_limit(SOURCE_NAME, RateLimit::per_minute(45));
+
+        Self {
+            http_client,
+            secret_store,
+        }
+    }
+
+    /// Build the search URL
+    fn search_url(&self, query: &SearchQuery) -> String {
+        let mut url = format!(
+            "https://wallhaven.cc/api/v1/search?categories=111&purity={}&sorting={}",
+            purity_param(query.purity),
+            if query.random { "random" } else { "toplist" }
+        );
+
+        if !query.query.is_empty() {
+            url.push_str(&format!("&q={}", encode_query_component(&query.query)));
+        }
+
+        if let Some((width, height)) = query.min_resolution {
+            url.push_str(&format!("&atleast={}x{}", width, height));
+        }
+
+        if !query.aspect_ratios.is_empty() {
+            let ratios = query
+                .aspect_ratios
+                .iter()
+                .map(|(w, h)| format!("{}x{}", w, h))
+                .collect::<Vec<_>>()
+                .join(",");
+            url.push_str(&format!("&ratios={}", ratios));
+        }
+
+        url
+    }
+
+    /// Build the request headers, sending the API key if one is set
+    ///
+    /// The key goes in a header rather than the query so it stays out of
+    /// logs and the response cache.
+    fn headers(&self) -> Vec<String> {
+        match self.secret_store.get(API_KEY_SECRET) {
+            Ok(Some(api_key)) if !api_key.is_empty() => vec![format!("X-API-Key: {}", api_key)],
+            Ok(_) => Vec::new(),
+            Err(e) => {
+                error!("Failed to read Wallhaven API key: {}", e);
+                Vec::new()
+            }
+        }
+    }
+}
+
+impl WallpaperSource for WallhavenSource {
+    fn name(&self) -> &str {
+        SOURCE_NAME
+    }
+
+    fn search(&self, query: &SearchQuery) -> AppResult<Vec<SourceWallpaper>> {
+        let url = self.search_url(query);
+        debug!("Searching Wallhaven: {}", query.query);
+
+        let response = self
+            .http_client
+            .get_with_headers(SOURCE_NAME, &url, &self.headers())?;
+        if response.status != 200 {
+            return Err(AppError::NetworkError(format!(
+                "Wallhaven search failed with status {}",
+                response.status
+            )));
+        }
+
+        let json = response.json()?;
+        let results = json["data"]
+            .as_array()
+            .map(|entries| entries.iter().filter_map(parse_wallpaper).collect())
+            .unwrap_or_default();
+
+        Ok(results)
+    }
+}
+
+/// Parse a Wallhaven search result
+fn parse_wallpaper(entry: &serde_json::Value) -> Option<SourceWallpaper> {
+    let url = entry["path"].as_str()?.to_string();
+    let extension = url.rsplit('.').next().unwrap_or("jpg").to_string();
+
+    Some(SourceWallpaper {
+        source: SOURCE_NAME.to_string(),
+        id: entry["id"].as_str()?.to_string(),
+        url,
+        thumbnail_url: entry["thumbs"]["large"].as_str().map(|s| s.to_string()),
+        page_url: entry["url"].as_str().map(|s| s.to_string()),
+        width: entry["dimension_x"].as_u64()? as u32,
+        height: entry["dimension_y"].as_u64()? as u32,
+        extension,
+        purity: parse_purity(entry["purity"].as_str().unwrap_or_default()),
+    })
+}
+
+/// Get the purity bit flags (sfw, sketchy, nsfw) allowing everything up to `purity`
+fn purity_param(purity: Purity) -> &'static str {
+    match purity {
+        Purity::Sfw => "100",
+        Purity::Sketchy => "110",
+        Purity::Nsfw => "111",
+    }
+}
+
+/// Parse a Wallhaven purity value
+///
+/// Unknown values are treated as NSFW so the content filter errs on the side of caution.
+fn parse_purity(value: &str) -> Purity {
+    match value {
+        "sfw" => Purity::Sfw,
+        "sketchy" => Purity::Sketchy,
+        _ => Purity::Nsfw,
+    }
+}
@@ -0,0 +1,91 @@
+//! Current weather from OpenWeatherMap
+//!
+//! Used by the Weather widget and by the weather effects drawn over the
+//! wallpaper. Responses go through the shared HTTP client, so the last known
+//! weather is served from its cache while offline.
+use crate::core::config::WeatherEffect;
+use crate::core::http::encode_query_component;
+use crate::core::{AppError, AppResult, HttpClient};
+
+/// Rate limit key for the weather provider
+pub const PROVIDER: &str = "openweathermap";
+
+/// Secret store key of the API key used by the weather effects
+pub const API_KEY_SECRET: &str = "weather.api_key";
+
+/// Current weather at a location
+#[derive(Debug, Clone, PartialEq)]
+pub struct Weather {
+    /// Temperature in Celsius
+    pub temperature: f32,
+
+    /// Weather condition, e.g. "Rain" or "Clouds"
+    pub condition: String,
+
+    /// Weather icon
+    pub icon: String,
+}
+
+impl Weather {
+    /// Get the effect matching the weather, if any
+    pub fn effect(&self) -> Option<WeatherEffect> {
+        effect_for(&self.condition)
+    }
+}
+
+/// Fetch the current weather at a location
+///
+/// Returns whether it came from the cache along with it.
+pub fn fetch(
+    http_client: &HttpClient,
+    api_key: &str,
+    location: &str,
+) -> AppResult<(Weather, bool)> {
+    let url = format!(
+        "https://api.openweathermap.org/data/2.5/weather?q={}&appid={}&units=metric",
+        encode_query_component(location),
+        encode_query_component(api_key)
+    );
+
+    let response = http_client.get(PROVIDER, &url)?;
+    if response.status != 200 {
+        return Err(AppError::NetworkError(format!(
+            "Weather request failed with status {}",
+            response.status
+        )));
+    }
+
+    let json = response.json()?;
+    let weather = &json["weather"][0];
+    let data = Weather {
+        temperature: json["main"]["temp"].as_f64().unwrap_or_default() as f32,
+        condition: weather["main"].as_str().unwrap_or("Unknown").to_string(),
+        icon: weather["icon"].as_str().unwrap_or_default().to_string(),
+    };
+
+    Ok((data, response.from_cache))
+}
+
+/// Get the effect matching an OpenWeatherMap condition group
+pub fn effect_for(condition: &str) -> Option<WeatherEffect> {
+    match condition {
+        "Rain" | "Drizzle" | "Thunderstorm" => Some(WeatherEffect::Rain),
+        "Snow" => Some(WeatherEffect::Snow),
+        "Mist" | "Fog" | "Haze" | "Smoke" | "Dust" | "Sand" | "Ash" => Some(WeatherEffect::Fog),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_effect_for() {
+        assert_eq!(effect_for("Drizzle"), Some(WeatherEffect::Rain));
+        assert_eq!(effect_for("Snow"), Some(WeatherEffect::Snow));
+        assert_eq!(effect_for("Haze"), Some(WeatherEffect::Fog));
+        assert_eq!(effect_for("Clear"), None);
+        assert_eq!(effect_for("Clouds"), None);
+    }
+}
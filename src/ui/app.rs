@@ -1,38 +1,81 @@
-use crate::core::{Config, PluginManager, ResourceManager, ResourceLimits, ResourceUsage, ScheduleItem, TriggerType, WallpaperScheduler, WidgetConfig, WidgetManager, WidgetPosition, WidgetSize, WidgetType, WallpaperType, Theme};
-use crate::platform::WallpaperManager;
-use crate::ui::gallery::GalleryView;
-use crate::wallpapers::{AudioWallpaper, ShaderWallpaper, StaticWallpaper, VideoWallpaper, WebWallpaper, Wallpaper};
-use chrono::{NaiveTime, Timelike};
+use crate::core::benchmark::{self, Benchmark, BenchmarkReport, Bottleneck, Probe, Suitability};
+use crate::core::config::{
+    BotPlatform, CameraOutput, ColorScheme, ContentFilterConfig, FocusEffect, FrameRateLimit,
+    GpuPreference, LimitAction, MotionEffect, MouseReaction, PaletteFamily, ProceduralPattern,
+    Purity, Satellite, SceneConfig, SlideshowOrder, SourcesConfig, SyncConfig, TemperaturePoint,
+    ThemingTool, TransitionEffect, WeatherEffect, WebhookAction, WorkspaceWallpaper,
+};
+use crate::core::config_history::{self, ConfigHistory, Version};
+use crate::core::migrate::{self, Tool};
+use crate::core::pack;
+use crate::core::profile::{self, ProfileStore};
+use crate::core::qr::QrCode;
+use crate::core::secrets::SecretBackend;
+use crate::core::theme::CustomTheme;
+use crate::core::theming::{self, ThemingPlan};
+use crate::core::{crash, logging, orphans};
+use crate::core::{
+    runtime, AppError, AppResult, Config, ConfigFile, ConfigWatcher, Event, EventLevel,
+    PluginManager, ScheduleItem, SecretStore, TagStore, Theme, TriggerType, WallpaperInfo,
+    WallpaperScheduler, WallpaperType, WallpaperUsage, WidgetConfig, WidgetManager, WidgetPosition,
+    WidgetSize, WidgetType,
+};
+use crate::daemon::{api, bot, sync, DaemonClient, DaemonStatus, Request};
+use crate::experiments::ai::tagging::TagQueue;
+use crate::experiments::effects::temperature;
+use crate::platform::appearance::AppearanceWatcher;
+use crate::platform::autostart;
+use crate::platform::doctor::{self, Check, CheckCategory, CheckStatus};
+use crate::platform::gpu::{self, GpuAdapter};
+use crate::platform::native_messaging;
+use crate::platform::opener;
+use crate::platform::topology;
+use crate::platform::workspace;
+use crate::platform::{MonitorInfo, WallpaperManager};
+use crate::sources::cache::{self, CacheUsage};
+use crate::sources::{calendar, wallhaven, weather, Calendar};
+use crate::ui::commands::{self, Command, CommandEvent, CommandQueue, Intent};
+use crate::ui::gallery::{GalleryAction, GalleryItem, GalleryView};
+use crate::ui::generation_panel::{GenerationAction, GenerationPanel};
+use crate::ui::i18n::{self, tr, tr_args};
+use crate::ui::instance::InstanceGuard;
+use crate::ui::log_viewer::LogViewer;
+use crate::ui::monitor_layout::MonitorLayout;
+use crate::ui::notifications::{self, NotificationCenter};
+use crate::ui::performance_panel::PerformancePanel;
+use crate::ui::placement_editor::PlacementEditor;
+use crate::ui::preview::WallpaperPreview;
+use crate::ui::startup::{self, Subsystems};
+use crate::ui::theme_editor::{self, parse_hex_color, ThemeEditor};
+use crate::wallpapers::animated_image;
+use crate::wallpapers::particles::{ParticlePreset, MAX_PARTICLES};
+use crate::wallpapers::random::Rng;
+use chrono::{DateTime, Local, NaiveTime, Timelike};
 use eframe::egui;
-use log::{error, info};
+use log::{error, info, warn};
 use rfd::FileDialog;
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
 use std::sync::Arc;
-use tokio::runtime::Runtime;
+use std::time::{Duration, Instant};
 
 /// Main application UI
 pub struct AetherDeskApp {
     /// Application configuration
     config: Config,
 
-    /// Platform-specific wallpaper manager
-    wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>,
-
-    /// Resource manager for tracking resource usage
-    resource_manager: ResourceManager,
-
     /// Plugin manager
     plugin_manager: PluginManager,
 
-    /// Wallpaper scheduler
+    /// Schedule editor; the daemon runs the schedule
     scheduler: WallpaperScheduler,
 
     /// Widget manager
     widget_manager: WidgetManager,
 
-    /// Current wallpaper
-    current_wallpaper: Option<Box<dyn Wallpaper + Send + Sync>>,
+    /// Secret store for API keys
+    secret_store: Arc<SecretStore>,
 
     /// Selected wallpaper type
     selected_wallpaper_type: WallpaperType,
@@ -43,6 +86,12 @@ pub struct AetherDeskApp {
     /// Selected web URL
     selected_web_url: String,
 
+    /// Selected procedural pattern
+    selected_pattern: ProceduralPattern,
+
+    /// ID of the monitor the selected wallpaper is shown on, every monitor when unset
+    selected_monitor: Option<String>,
+
     /// Selected tab
     selected_tab: Tab,
 
@@ -58,13 +107,194 @@ pub struct AetherDeskApp {
     /// Editing widget ID
     editing_widget_id: Option<String>,
 
-    /// Tokio runtime for async operations
-    runtime: Arc<Runtime>,
-
     /// Gallery view for browsing wallpapers
     gallery_view: GalleryView,
+
+    /// Client for the background daemon
+    daemon: DaemonClient,
+
+    /// Last known daemon status
+    daemon_status: Option<DaemonStatus>,
+
+    /// Last daemon status refresh
+    last_status_refresh: Option<Instant>,
+
+    /// Status refresh waiting for the daemon's answer
+    status_answer: Option<Receiver<Option<DaemonStatus>>>,
+
+    /// Monitors detected at startup
+    monitors: Vec<MonitorInfo>,
+
+    /// GPUs detected at startup
+    gpu_adapters: Vec<GpuAdapter>,
+
+    /// Pending source API key input
+    source_api_key_input: String,
+
+    /// Pending weather API key input
+    weather_api_key_input: String,
+
+    /// Pending LAN sync key input
+    sync_key_input: String,
+
+    /// Pending bot token input
+    bot_token_input: String,
+
+    /// Pending calendar addresses input
+    calendar_urls_input: String,
+
+    /// Versions of the configuration history, loaded when first shown
+    history_versions: Option<Vec<Version>>,
+
+    /// File of a version being viewed, with its content
+    history_view: Option<(Version, String, String)>,
+
+    /// Local API token, once the user asked to see it
+    api_token: Option<String>,
+
+    /// Chromium extension IDs to register the browser extension's host for
+    browser_extension_ids: String,
+
+    /// QR code of the upload page, with the page's address it was made for
+    upload_qr: Option<(String, QrCode)>,
+
+    /// Name of the webhook being added
+    new_webhook_name: String,
+
+    /// Name of the Home Assistant scene being added
+    new_scene_name: String,
+
+    /// Saved profile names
+    profile_names: Vec<String>,
+
+    /// Name of the profile being added
+    new_profile_name: String,
+
+    /// Less restrictive content policy awaiting confirmation
+    pending_content_filter: Option<ContentFilterConfig>,
+
+    /// Download cache usage, refreshed after cache changes
+    download_cache_usage: Option<CacheUsage>,
+
+    /// Whether to minimize the window on the first frame
+    start_minimized: bool,
+
+    /// Ownership of the settings window, receiving later launches' arguments
+    instance: Option<InstanceGuard>,
+
+    /// Results of the last diagnostics run
+    diagnostics: Option<Vec<Check>>,
+
+    /// Dry run of the system theming, until closed
+    theming_preview: Option<Result<ThemingPlan, String>>,
+
+    /// Toasts and notification history
+    notifications: NotificationCenter,
+
+    /// Last daemon event ID turned into a notification
+    last_event_id: u64,
+
+    /// Last daemon event poll
+    last_event_poll: Option<Instant>,
+
+    /// Event poll waiting for the daemon's answer
+    events_answer: Option<Receiver<AppResult<Vec<Event>>>>,
+
+    /// Time the UI started; older daemon events are not shown
+    launched: DateTime<Local>,
+
+    /// OS light/dark preference, watched while the System theme is selected
+    appearance: AppearanceWatcher,
+
+    /// Visuals applied to the context (None until the first frame)
+    applied_visuals: Option<egui::Visuals>,
+
+    /// Custom theme editor
+    theme_editor: ThemeEditor,
+
+    /// Panel generating wallpapers from prompts
+    generation_panel: GenerationPanel,
+
+    /// Preview of the selected wallpaper
+    preview: WallpaperPreview,
+
+    /// Fit mode and crop editor for static wallpapers
+    placement_editor: PlacementEditor,
+
+    /// Image assignment per monitor
+    monitor_layout: MonitorLayout,
+
+    /// Wallpaper being tried on the desktop and when the try-on started
+    try_on: Option<(WallpaperInfo, Instant)>,
+
+    /// Frame, system and wallpaper load metrics
+    performance: PerformancePanel,
+
+    /// Log tab state
+    log_viewer: LogViewer,
+
+    /// Running wallpaper benchmark
+    benchmark: Option<Benchmark>,
+
+    /// How long a benchmark runs, in seconds
+    benchmark_seconds: u64,
+
+    /// Results of the last benchmark, until closed
+    benchmark_report: Option<BenchmarkReport>,
+
+    /// Crash report from an earlier run, until the user dealt with it
+    crash_report: Option<PathBuf>,
+
+    /// Platform-specific wallpaper manager
+    wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>,
+
+    /// Progress of the subsystems loaded after the first frame
+    startup: StartupState,
+
+    /// Result of starting the daemon, while it starts
+    daemon_start: Option<Receiver<AppResult<()>>>,
+
+    /// Wallpaper commands sent to the daemon in the background
+    commands: CommandQueue,
+
+    /// Reloads the configuration files when they are edited by hand
+    config_watcher: ConfigWatcher,
+
+    /// Configuration files edited by hand, as the watcher reports them
+    config_changes: Option<Receiver<ConfigFile>>,
+}
+
+/// Progress of the deferred startup
+enum StartupState {
+    /// The first frame hasn't been painted yet
+    FirstFrame,
+
+    /// The first frame was painted; loading starts with the next one
+    Painted,
+
+    /// The subsystems are loading in the background
+    Loading(Receiver<Subsystems>),
+
+    /// Everything is loaded
+    Done,
 }
 
+/// How long a wallpaper is tried on the desktop before reverting
+const TRY_ON_DURATION: Duration = Duration::from_secs(10);
+
+/// Versions of the configuration history listed
+const HISTORY_VERSIONS: usize = 50;
+
+/// Transition effects, in the order shown
+const TRANSITION_EFFECTS: [TransitionEffect; 6] = [
+    TransitionEffect::None,
+    TransitionEffect::Crossfade,
+    TransitionEffect::Slide,
+    TransitionEffect::Wipe,
+    TransitionEffect::Pixelate,
+    TransitionEffect::Morph,
+];
+
 /// UI tab
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Tab {
@@ -83,83 +313,133 @@ enum Tab {
     /// Plugins tab
     Plugins,
 
+    /// Status tab
+    Status,
+
+    /// Generate tab
+    Generate,
+
+    /// Logs tab
+    Logs,
+
     /// Settings tab
     Settings,
 }
 
 impl AetherDeskApp {
     /// Create a new application UI
-    pub fn new(wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>, resource_manager: ResourceManager) -> Self {
-        // Create Tokio runtime for async operations
-        let runtime = Arc::new(
-            tokio::runtime::Builder::new_multi_thread()
-                .enable_all()
-                .build()
-                .expect("Failed to create Tokio runtime")
-        );
+    ///
+    /// Only what the first frame needs is loaded here; the rest follows once it is painted.
+    pub fn new(wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>) -> Self {
+        let mut notifications = NotificationCenter::new(false);
 
         // Load configuration
-        let config = Config::load().unwrap_or_else(|e| {
+        let config = startup::phase("Configuration", Config::load).unwrap_or_else(|e| {
             error!("Failed to load configuration: {}", e);
+            notifications.error(tr("error-load-configuration"), &e);
             Config::default()
         });
 
-        // Create plugin manager
-        let plugin_dir = config.get_plugin_dir();
-        let mut plugin_manager = PluginManager::new(&plugin_dir);
-
-        // Load plugins
-        if let Err(e) = plugin_manager.load_plugins(&config) {
-            error!("Failed to load plugins: {}", e);
-        }
-
-        // Create scheduler
-        let mut scheduler = WallpaperScheduler::new(wallpaper_manager.clone());
-
-        // Load schedule
-        if let Err(e) = scheduler.load_schedule(&config) {
-            error!("Failed to load schedule: {}", e);
-        }
-
-        // Start scheduler
-        if let Err(e) = scheduler.start() {
-            error!("Failed to start scheduler: {}", e);
-        }
-
-        // Create widget manager
-        let mut widget_manager = WidgetManager::new();
+        // Apply the UI language
+        startup::phase("Language", || {
+            i18n::set_language(config.app.language.as_deref())
+        });
+        notifications.set_desktop_notifications(config.app.desktop_notifications);
 
-        // Load widgets
-        if let Err(e) = widget_manager.load_widgets(&config) {
-            error!("Failed to load widgets: {}", e);
+        // Follow the OS theme if requested
+        let mut appearance = AppearanceWatcher::new();
+        if config.app.theme.theme == Theme::System {
+            if let Err(e) = startup::phase("Appearance", || appearance.start()) {
+                error!("Failed to watch the system theme: {}", e);
+            }
         }
 
-        // Start widget manager
-        if let Err(e) = widget_manager.start() {
-            error!("Failed to start widget manager: {}", e);
+        // Loaded after the first frame
+        let plugin_manager = PluginManager::new(&config.get_plugin_dir());
+        let scheduler = WallpaperScheduler::new(wallpaper_manager.clone());
+        let widget_manager = WidgetManager::new();
+        let secret_store = Arc::new(SecretStore::new());
+        let daemon = DaemonClient::new();
+        let commands = CommandQueue::new(daemon.clone());
+        let mut gallery_view = GalleryView::new(commands.sender());
+        if config.tagging.enabled {
+            gallery_view.set_tagger(Some(TagQueue::start(
+                config.tagging.clone(),
+                TagStore::shared(),
+            )));
         }
-
-        // Create gallery view
-        let gallery_view = GalleryView::new(wallpaper_manager.clone());
+        let profile_names = Vec::new();
+        let monitors = Vec::new();
+        let gpu_adapters = Vec::new();
 
         Self {
             config,
-            wallpaper_manager,
-            resource_manager,
             plugin_manager,
             scheduler,
             widget_manager,
-            current_wallpaper: None,
+            secret_store,
             selected_wallpaper_type: WallpaperType::Static,
             selected_wallpaper_path: None,
             selected_web_url: String::new(),
+            selected_pattern: ProceduralPattern::MatrixRain,
+            selected_monitor: None,
             selected_tab: Tab::Wallpaper,
             new_schedule_item: None,
             editing_schedule_index: None,
             new_widget: None,
             editing_widget_id: None,
-            runtime,
             gallery_view,
+            daemon,
+            daemon_status: None,
+            last_status_refresh: None,
+            status_answer: None,
+            monitors,
+            gpu_adapters,
+            source_api_key_input: String::new(),
+            weather_api_key_input: String::new(),
+            sync_key_input: String::new(),
+            bot_token_input: String::new(),
+            calendar_urls_input: String::new(),
+            history_versions: None,
+            history_view: None,
+            api_token: None,
+            browser_extension_ids: String::new(),
+            upload_qr: None,
+            new_webhook_name: String::new(),
+            new_scene_name: String::new(),
+            profile_names,
+            new_profile_name: String::new(),
+            pending_content_filter: None,
+            download_cache_usage: None,
+            start_minimized: false,
+            instance: None,
+            diagnostics: None,
+            theming_preview: None,
+            notifications,
+            last_event_id: 0,
+            last_event_poll: None,
+            events_answer: None,
+            launched: Local::now(),
+            appearance,
+            applied_visuals: None,
+            theme_editor: ThemeEditor::new(),
+            generation_panel: GenerationPanel::new(),
+            preview: WallpaperPreview::new(),
+            placement_editor: PlacementEditor::new(),
+            monitor_layout: MonitorLayout::new(),
+            try_on: None,
+            performance: PerformancePanel::new(),
+            log_viewer: LogViewer::new(),
+            benchmark: None,
+            benchmark_seconds: benchmark::DEFAULT_DURATION.as_secs(),
+            benchmark_report: None,
+            crash_report: crash::latest_unseen(),
+            wallpaper_manager,
+            startup: StartupState::FirstFrame,
+            daemon_start: None,
+            commands,
+            config_watcher: ConfigWatcher::new(),
+            config_changes: None,
         }
     }
 }
@@ -167,679 +447,2476 @@ impl AetherDeskApp {
 // Implement eframe::App trait
 impl eframe::App for AetherDeskApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        let frame_started = Instant::now();
+        if self.start_minimized {
+            self.start_minimized = false;
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+        }
+
+        self.poll_startup(ctx);
+        self.handle_forwarded_launches(ctx);
+        self.apply_visuals(ctx);
+        self.poll_daemon_events(ctx);
+        self.poll_config_changes();
+        self.poll_commands();
+        self.handle_shortcuts(ctx);
+        self.check_try_on(ctx);
+        self.check_benchmark(ctx);
+        self.performance.show(ctx);
         self.show(ctx);
+        self.show_detached_windows(ctx);
+        self.show_benchmark_report(ctx);
+        self.show_crash_report(ctx);
+        self.notifications.show(ctx);
+        self.performance.end_frame(frame_started.elapsed());
+        if let Some(benchmark) = &mut self.benchmark {
+            benchmark.end_frame(frame_started.elapsed());
+        }
+
+        if matches!(self.startup, StartupState::FirstFrame) {
+            startup::mark("First frame built");
+            self.startup = StartupState::Painted;
+            ctx.request_repaint();
+        }
+    }
+
+    /// Stop what the window runs itself; the wallpaper keeps running in the daemon
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        // A wallpaper being tried on or benchmarked isn't the saved one
+        if self.try_on.take().is_some() || self.benchmark.take().is_some() {
+            if let Err(e) = self.daemon.send(&self.restore_command().request()) {
+                error!("Failed to revert wallpaper: {}", e);
+            }
+        }
+
+        if let Err(e) = self.widget_manager.stop() {
+            error!("Failed to stop widget manager: {}", e);
+        }
+        if let Err(e) = self.appearance.stop() {
+            error!("Failed to stop watching the system theme: {}", e);
+        }
+        self.config_watcher.stop();
+        info!("Settings window closed");
     }
 }
 
 impl AetherDeskApp {
-    /// Show the main UI
-    pub fn show(&mut self, ctx: &egui::Context) {
-        // Compute theme colors
-        let (bg_color, accent_color) = {
-            let theme_config = &self.config.app.theme;
-            match theme_config.theme {
-                Theme::Light => (
-                    egui::Color32::from_rgb(245, 245, 245),
-                    egui::Color32::from_rgb(33, 150, 243),
-                ),
-                Theme::Dark => (
-                    egui::Color32::from_rgb(32, 34, 37),
-                    egui::Color32::from_rgb(0, 188, 212),
-                ),
-                Theme::Custom => {
-                    let bg = theme_config.background_color.as_ref().and_then(|c| parse_hex_color(c)).unwrap_or(egui::Color32::from_rgb(32, 34, 37));
-                    let accent = theme_config.accent_color.as_ref().and_then(|c| parse_hex_color(c)).unwrap_or(egui::Color32::from_rgb(0, 188, 212));
-                    (bg, accent)
+    /// Load the subsystems once the first frame is on screen and install them when ready
+    fn poll_startup(&mut self, ctx: &egui::Context) {
+        if let Some(receiver) = &self.daemon_start {
+            match receiver.try_recv() {
+                Ok(result) => {
+                    self.daemon_start = None;
+                    self.last_status_refresh = None;
+                    if let Err(e) = result {
+                        error!("Failed to start daemon: {}", e);
+                        self.notifications.error(tr("error-start-daemon"), &e);
+                    }
+                }
+                Err(TryRecvError::Empty) => ctx.request_repaint_after(Duration::from_millis(100)),
+                Err(TryRecvError::Disconnected) => self.daemon_start = None,
+            }
+        }
+
+        match &self.startup {
+            StartupState::Painted => {
+                self.daemon_start = Some(startup::start_daemon());
+                let receiver = startup::load_subsystems(
+                    self.config.clone(),
+                    self.wallpaper_manager.clone(),
+                    self.secret_store.clone(),
+                );
+                self.startup = StartupState::Loading(receiver);
+                ctx.request_repaint_after(Duration::from_millis(50));
+            }
+            StartupState::Loading(receiver) => match receiver.try_recv() {
+                Ok(subsystems) => {
+                    for (id, e) in &subsystems.errors {
+                        self.notifications.error(tr(id), e);
+                    }
+                    self.plugin_manager = subsystems.plugin_manager;
+                    self.scheduler = subsystems.scheduler;
+                    self.widget_manager = subsystems.widget_manager;
+                    self.profile_names = subsystems.profile_names;
+                    for pack in &subsystems.packs {
+                        self.gallery_view.add_pack(pack);
+                    }
+                    self.monitors = subsystems.monitors;
+                    self.gpu_adapters = subsystems.gpu_adapters;
+                    self.startup = StartupState::Done;
+                    self.watch_config(ctx);
+                    startup::mark("Subsystems ready");
                 }
+                Err(TryRecvError::Empty) => ctx.request_repaint_after(Duration::from_millis(50)),
+                Err(TryRecvError::Disconnected) => {
+                    error!("Loading the subsystems failed");
+                    self.startup = StartupState::Done;
+                }
+            },
+            StartupState::FirstFrame | StartupState::Done => {}
+        }
+    }
+
+    /// Watch the configuration files for edits made outside the app
+    fn watch_config(&mut self, ctx: &egui::Context) {
+        let (sender, receiver) = mpsc::channel();
+        let ctx = ctx.clone();
+        let watched = Config::get_config_dir()
+            .map_err(|e| AppError::ConfigError(e.to_string()))
+            .and_then(|dir| {
+                self.config_watcher.start(&dir, move |file| {
+                    let _ = sender.send(file);
+                    ctx.request_repaint();
+                })
+            });
+        match watched {
+            Ok(()) => self.config_changes = Some(receiver),
+            Err(e) => error!("Failed to watch the configuration: {}", e),
+        }
+    }
+
+    /// Pick up configuration files edited outside the app
+    ///
+    /// The daemon reloads them on its own; this keeps the window from saving
+    /// its older copy over the user's edits.
+    fn poll_config_changes(&mut self) {
+        let Some(receiver) = &self.config_changes else {
+            return;
+        };
+        let changed: HashSet<ConfigFile> = receiver.try_iter().collect();
+
+        for file in changed {
+            match file {
+                ConfigFile::Config => self.reload_edited_config(),
+                ConfigFile::Schedule => match self.scheduler.load_schedule(&self.config) {
+                    Ok(()) => self.notifications.info(tr("schedule-reloaded")),
+                    Err(e) => {
+                        error!("Failed to load schedule: {}", e);
+                        self.notifications.error(tr("error-load-schedule"), &e);
+                    }
+                },
+                ConfigFile::Widgets => match self.widget_manager.load_widgets(&self.config) {
+                    Ok(()) => self.notifications.info(tr("widgets-reloaded")),
+                    Err(e) => {
+                        error!("Failed to load widgets: {}", e);
+                        self.notifications.error(tr("error-load-widgets"), &e);
+                    }
+                },
             }
+        }
+    }
+
+    /// Replace the configuration with the edited file and apply what the window uses itself
+    fn reload_edited_config(&mut self) {
+        let config = match Config::load() {
+            Ok(config) => config,
+            Err(e) => {
+                error!("Failed to load edited config: {}", e);
+                self.notifications.error(tr("error-edited-config"), &e);
+                return;
+            }
+        };
+        if serde_json::to_value(&config).ok() == serde_json::to_value(&self.config).ok() {
+            return;
+        }
+
+        if config.app.theme.theme != self.config.app.theme.theme {
+            let result = if config.app.theme.theme == Theme::System {
+                self.appearance.start()
+            } else {
+                self.appearance.stop()
+            };
+            if let Err(e) = result {
+                error!("Failed to update system theme watcher: {}", e);
+            }
+        }
+        i18n::set_language(config.app.language.as_deref());
+        self.notifications
+            .set_desktop_notifications(config.app.desktop_notifications);
+        self.widget_manager
+            .set_power_policy(config.app.auto_pause.clone());
+        self.config = config;
+        self.notifications.info(tr("config-reloaded"));
+    }
+
+    /// Check whether the subsystems are loaded
+    fn is_loaded(&self) -> bool {
+        matches!(self.startup, StartupState::Done)
+    }
+
+    /// Set whether the window starts minimized
+    pub fn set_start_minimized(&mut self, minimized: bool) {
+        self.start_minimized = minimized;
+    }
+
+    /// Keep ownership of the settings window
+    pub fn set_instance(&mut self, instance: InstanceGuard) {
+        self.instance = Some(instance);
+    }
+
+    /// Bring the window to the front when the app is launched again
+    fn handle_forwarded_launches(&mut self, ctx: &egui::Context) {
+        let instance = match &self.instance {
+            Some(instance) => instance,
+            None => return,
+        };
+        instance.set_context(ctx);
+
+        for args in instance.take_forwarded() {
+            // Autostart launches shouldn't pop the window up
+            if args.iter().any(|arg| arg == autostart::MINIMIZED_FLAG) {
+                continue;
+            }
+            info!("Raising window for a new launch");
+            ctx.send_viewport_cmd(egui::ViewportCommand::Visible(true));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        }
+    }
+
+    /// Check whether the selected theme is dark
+    fn is_dark_theme(&self) -> bool {
+        match self.config.app.theme.theme {
+            Theme::Light => false,
+            Theme::Dark | Theme::Custom => true,
+            // Stay dark until the OS preference is known
+            Theme::System => self.appearance.prefers_dark().unwrap_or(true),
+        }
+    }
+
+    /// Get the background and accent colors of the selected theme
+    fn theme_colors(&self) -> (egui::Color32, egui::Color32) {
+        let theme_config = &self.config.app.theme;
+        match theme_config.theme {
+            Theme::Custom => {
+                let bg = theme_config
+                    .background_color
+                    .as_ref()
+                    .and_then(|c| parse_hex_color(c))
+                    .unwrap_or(egui::Color32::from_rgb(32, 34, 37));
+                let accent = theme_config
+                    .accent_color
+                    .as_ref()
+                    .and_then(|c| parse_hex_color(c))
+                    .unwrap_or(egui::Color32::from_rgb(0, 188, 212));
+                (bg, accent)
+            }
+            _ if self.is_dark_theme() => (
+                egui::Color32::from_rgb(32, 34, 37),
+                egui::Color32::from_rgb(0, 188, 212),
+            ),
+            _ => (
+                egui::Color32::from_rgb(245, 245, 245),
+                egui::Color32::from_rgb(33, 150, 243),
+            ),
+        }
+    }
+
+    /// Update the egui visuals when the theme changes
+    fn apply_visuals(&mut self, ctx: &egui::Context) {
+        let visuals = match self.config.app.theme.theme {
+            Theme::Custom => {
+                theme_editor::custom_visuals(&CustomTheme::from_config(&self.config.app.theme))
+            }
+            _ if self.is_dark_theme() => egui::Visuals::dark(),
+            _ => egui::Visuals::light(),
         };
-        
+        if self.applied_visuals.as_ref() != Some(&visuals) {
+            ctx.set_visuals(visuals.clone());
+            self.applied_visuals = Some(visuals);
+        }
+
+        // Pick up OS theme changes without waiting for input
+        if self.config.app.theme.theme == Theme::System {
+            ctx.request_repaint_after(Duration::from_secs(1));
+        }
+    }
+
+    /// Show the gallery and widget preview in their own windows when detached
+    fn show_detached_windows(&mut self, ctx: &egui::Context) {
+        if self.config.app.detach_gallery {
+            let closed = self.show_detached(
+                ctx,
+                "gallery_window",
+                tr("wallpaper-gallery"),
+                egui::vec2(800.0, 600.0),
+                |app, ui| {
+                    app.refresh_daemon_status(ui.ctx());
+                    if let Some(action) = app.gallery_view.show(ui) {
+                        app.handle_gallery_action(action);
+                    }
+                },
+            );
+            if closed {
+                self.set_gallery_detached(false);
+            }
+        }
+
+        if self.config.app.detach_widget_preview {
+            let closed = self.show_detached(
+                ctx,
+                "widget_preview_window",
+                tr("widget-preview"),
+                egui::vec2(640.0, 440.0),
+                |app, ui| {
+                    app.show_widget_preview(ui);
+                },
+            );
+            if closed {
+                self.set_widget_preview_detached(false);
+            }
+        }
+    }
+
+    /// Show content in a separate window
+    ///
+    /// Uses a native window where the backend supports several viewports and
+    /// a floating egui window otherwise. Returns true once the window is closed.
+    fn show_detached(
+        &mut self,
+        ctx: &egui::Context,
+        id: &str,
+        title: String,
+        size: egui::Vec2,
+        add_contents: impl FnOnce(&mut Self, &mut egui::Ui),
+    ) -> bool {
+        let builder = egui::ViewportBuilder::default()
+            .with_title(format!("Aether-Desk - {}", title))
+            .with_inner_size(size);
+
+        ctx.show_viewport_immediate(egui::ViewportId::from_hash_of(id), builder, |ctx, class| {
+            if class == egui::ViewportClass::Embedded {
+                let mut open = true;
+                egui::Window::new(title)
+                    .id(egui::Id::new(id))
+                    .open(&mut open)
+                    .default_size(size)
+                    .show(ctx, |ui| {
+                        egui::ScrollArea::vertical().show(ui, |ui| add_contents(self, ui));
+                    });
+                !open
+            } else {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    egui::ScrollArea::vertical().show(ui, |ui| add_contents(self, ui));
+                });
+                ctx.input(|i| i.viewport().close_requested())
+            }
+        })
+    }
+
+    /// Show the detach/attach button of a detachable view
+    ///
+    /// Returns the new detached state when the button is clicked.
+    fn show_detach_toggle(&self, ui: &mut egui::Ui, detached: bool) -> Option<bool> {
+        let mut toggled = None;
+        ui.horizontal(|ui| {
+            if detached {
+                ui.label(tr("detached-window"));
+                if ui.button(tr("attach")).clicked() {
+                    toggled = Some(false);
+                }
+            } else if ui
+                .button(tr("detach"))
+                .on_hover_text(tr("detach-hint"))
+                .clicked()
+            {
+                toggled = Some(true);
+            }
+        });
+        toggled
+    }
+
+    /// Move the gallery into or out of its own window
+    fn set_gallery_detached(&mut self, detached: bool) {
+        self.config.app.detach_gallery = detached;
+        if let Err(e) = self.config.save() {
+            error!("Failed to save config: {}", e);
+            self.notifications.error(tr("error-save-config"), &e);
+        }
+    }
+
+    /// Move the widget preview into or out of its own window
+    fn set_widget_preview_detached(&mut self, detached: bool) {
+        self.config.app.detach_widget_preview = detached;
+        if let Err(e) = self.config.save() {
+            error!("Failed to save config: {}", e);
+            self.notifications.error(tr("error-save-config"), &e);
+        }
+    }
+
+    /// Show the main UI
+    pub fn show(&mut self, ctx: &egui::Context) {
+        // Compute theme colors
+        let (bg_color, accent_color) = self.theme_colors();
+
         egui::CentralPanel::default()
             .frame(egui::Frame::none().fill(bg_color))
             .show(ctx, |ui| {
-            ui.heading(egui::RichText::new("Aether-Desk").color(accent_color).size(32.0));
-            
-            // Tab selection
-            ui.horizontal(|ui| {
-                let tab_names = [
-                    (Tab::Wallpaper, "Wallpaper"),
-                    (Tab::Gallery, "Gallery"),
-                    (Tab::Scheduler, "Scheduler"),
-                    (Tab::Widgets, "Widgets"),
-                    (Tab::Plugins, "Plugins"),
-                    (Tab::Settings, "Settings"),
-                ];
-                for (tab, label) in tab_names.iter() {
-                    let selected = self.selected_tab == *tab;
-                    let button = if selected {
-                        egui::SelectableLabel::new(selected, egui::RichText::new(*label).color(accent_color))
-                    } else {
-                        egui::SelectableLabel::new(selected, *label)
-                    };
-                    if ui.add(button).clicked() {
-                        self.selected_tab = *tab;
+                ui.heading(
+                    egui::RichText::new("Aether-Desk")
+                        .color(accent_color)
+                        .size(32.0),
+                );
+
+                // Tab selection
+                ui.horizontal(|ui| {
+                    let tab_names = [
+                        (Tab::Wallpaper, tr("tab-wallpaper")),
+                        (Tab::Gallery, tr("tab-gallery")),
+                        (Tab::Scheduler, tr("tab-scheduler")),
+                        (Tab::Widgets, tr("tab-widgets")),
+                        (Tab::Plugins, tr("tab-plugins")),
+                        (Tab::Status, tr("tab-status")),
+                        (Tab::Generate, tr("tab-generate")),
+                        (Tab::Logs, tr("tab-logs")),
+                        (Tab::Settings, tr("tab-settings")),
+                    ];
+                    for (tab, label) in tab_names.iter() {
+                        let selected = self.selected_tab == *tab;
+                        let button = if selected {
+                            egui::SelectableLabel::new(
+                                selected,
+                                egui::RichText::new(label.as_str()).color(accent_color),
+                            )
+                        } else {
+                            egui::SelectableLabel::new(selected, label.as_str())
+                        };
+                        if ui.add(button).clicked() {
+                            self.selected_tab = *tab;
+                        }
                     }
+
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        self.notifications.show_button(ui);
+                    });
+                });
+
+                ui.separator();
+
+                // Tab content, read-only until the subsystems it edits are loaded
+                let loaded = self.is_loaded();
+                if !loaded {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(tr("loading"));
+                    });
                 }
+                ui.add_enabled_ui(loaded, |ui| match self.selected_tab {
+                    Tab::Wallpaper => self.show_wallpaper_tab(ui),
+                    Tab::Gallery => self.show_gallery_tab(ui),
+                    Tab::Scheduler => self.show_scheduler_tab(ui),
+                    Tab::Widgets => self.show_widgets_tab(ui),
+                    Tab::Plugins => self.show_plugins_tab(ui),
+                    Tab::Status => self.show_status_tab(ui),
+                    Tab::Generate => self.show_generate_tab(ui),
+                    Tab::Logs => self.show_logs_tab(ui),
+                    Tab::Settings => self.show_settings_tab(ui),
+                });
             });
-            
-            ui.separator();
-            
-            // Tab content
-            match self.selected_tab {
-                Tab::Wallpaper => self.show_wallpaper_tab(ui),
-                Tab::Gallery => self.show_gallery_tab(ui),
-                Tab::Scheduler => self.show_scheduler_tab(ui),
-                Tab::Widgets => self.show_widgets_tab(ui),
-                Tab::Plugins => self.show_plugins_tab(ui),
-                Tab::Settings => self.show_settings_tab(ui),
-            }
-        });
     }
-    
+
     /// Show wallpaper tab
     fn show_wallpaper_tab(&mut self, ui: &mut egui::Ui) {
+        self.refresh_daemon_status(ui.ctx());
+        self.show_orphans(ui);
+
+        // What the running wallpaper costs
+        if let Some(status) = &self.daemon_status {
+            if let (Some(wallpaper), Some(usage)) =
+                (&status.current_wallpaper, &status.wallpaper_usage)
+            {
+                ui.label(tr_args(
+                    "running-wallpaper-usage",
+                    &[
+                        ("name", &wallpaper.name),
+                        ("usage", &wallpaper_usage_text(usage)),
+                    ],
+                ));
+                ui.ctx().request_repaint_after(Duration::from_secs(2));
+                ui.separator();
+            }
+        }
+
+        // Wallpapers shown on single monitors
+        let per_monitor = self
+            .daemon_status
+            .as_ref()
+            .map(|status| status.monitor_wallpapers.clone())
+            .unwrap_or_default();
+        if !per_monitor.is_empty() {
+            let mut stop = None;
+            ui.label(tr("monitor-wallpapers"));
+            for state in &per_monitor {
+                let monitor = state.wallpaper.monitor.clone().unwrap_or_default();
+                let name = self.monitor_name(&monitor);
+                ui.horizontal(|ui| {
+                    let kind = wallpaper_type_label(state.wallpaper_type());
+                    ui.label(tr_args(
+                        "monitor-wallpaper",
+                        &[
+                            ("monitor", &name),
+                            ("name", &state.wallpaper.name),
+                            ("type", &kind),
+                        ],
+                    ));
+                    if ui.small_button(tr("stop")).clicked() {
+                        stop = Some(monitor.clone());
+                    }
+                });
+            }
+            if let Some(monitor) = stop {
+                self.stop_monitor_wallpaper(&monitor);
+            }
+            ui.separator();
+        }
+
+        // Wallpapers of workspaces
+        if !self.config.wallpaper.workspace_wallpapers.is_empty() {
+            let mut remove = None;
+            ui.label(tr("workspace-wallpapers"));
+            for (id, assigned) in &self.config.wallpaper.workspace_wallpapers {
+                ui.horizontal(|ui| {
+                    ui.label(tr_args(
+                        "workspace-wallpaper",
+                        &[
+                            ("workspace", &assigned.workspace),
+                            ("name", &assigned.wallpaper.name),
+                        ],
+                    ));
+                    if ui.small_button(tr("delete")).clicked() {
+                        remove = Some(id.clone());
+                    }
+                });
+            }
+            if let Some(id) = remove {
+                self.config.wallpaper.workspace_wallpapers.remove(&id);
+                self.save_config_and_reload();
+            }
+            ui.separator();
+        }
+
         // Wallpaper type selection
         ui.horizontal(|ui| {
-            ui.label("Wallpaper Type:");
+            ui.label(tr("wallpaper-type"));
             egui::ComboBox::from_label("")
-                .selected_text(format!("{:?}", self.selected_wallpaper_type))
+                .selected_text(wallpaper_type_label(&self.selected_wallpaper_type))
                 .show_ui(ui, |ui| {
-                    ui.selectable_value(&mut self.selected_wallpaper_type, WallpaperType::Static, "Static");
-                    ui.selectable_value(&mut self.selected_wallpaper_type, WallpaperType::Video, "Video");
-                    ui.selectable_value(&mut self.selected_wallpaper_type, WallpaperType::Web, "Web");
-                    ui.selectable_value(&mut self.selected_wallpaper_type, WallpaperType::Shader, "Shader");
-                    ui.selectable_value(&mut self.selected_wallpaper_type, WallpaperType::Audio, "Audio");
+                    ui.selectable_value(
+                        &mut self.selected_wallpaper_type,
+                        WallpaperType::Static,
+                        tr("static"),
+                    );
+                    ui.selectable_value(
+                        &mut self.selected_wallpaper_type,
+                        WallpaperType::AnimatedStatic,
+                        tr("animated-static"),
+                    );
+                    ui.selectable_value(
+                        &mut self.selected_wallpaper_type,
+                        WallpaperType::AnimatedImage,
+                        tr("animated-image"),
+                    );
+                    ui.selectable_value(
+                        &mut self.selected_wallpaper_type,
+                        WallpaperType::Video,
+                        tr("video"),
+                    );
+                    ui.selectable_value(
+                        &mut self.selected_wallpaper_type,
+                        WallpaperType::Web,
+                        tr("web"),
+                    );
+                    ui.selectable_value(
+                        &mut self.selected_wallpaper_type,
+                        WallpaperType::Shader,
+                        tr("shader"),
+                    );
+                    ui.selectable_value(
+                        &mut self.selected_wallpaper_type,
+                        WallpaperType::Audio,
+                        tr("audio"),
+                    );
+                    ui.selectable_value(
+                        &mut self.selected_wallpaper_type,
+                        WallpaperType::Particles,
+                        tr("particles"),
+                    );
+                    ui.selectable_value(
+                        &mut self.selected_wallpaper_type,
+                        WallpaperType::Procedural,
+                        tr("procedural"),
+                    );
+                    ui.selectable_value(
+                        &mut self.selected_wallpaper_type,
+                        WallpaperType::Slideshow,
+                        tr("slideshow"),
+                    );
+                    ui.selectable_value(
+                        &mut self.selected_wallpaper_type,
+                        WallpaperType::NowPlaying,
+                        tr("now-playing"),
+                    );
                 });
         });
-        
+
         ui.separator();
-        
+
         // Wallpaper selection based on type
         match self.selected_wallpaper_type {
-            WallpaperType::Static | WallpaperType::Video | WallpaperType::Shader | WallpaperType::Audio => {
+            WallpaperType::Static
+            | WallpaperType::AnimatedStatic
+            | WallpaperType::AnimatedImage
+            | WallpaperType::Video
+            | WallpaperType::Shader
+            | WallpaperType::Audio
+            | WallpaperType::Slideshow => {
                 ui.horizontal(|ui| {
-                    ui.label("Wallpaper Path:");
-                    
+                    ui.label(tr("wallpaper-path"));
+
                     if let Some(path) = &self.selected_wallpaper_path {
                         ui.label(path.to_string_lossy());
+
+                        let path = path.to_string_lossy().to_string();
+                        let mut favorite = self.config.wallpaper.favorites.contains(&path);
+                        if ui.checkbox(&mut favorite, tr("favorite")).changed() {
+                            if favorite {
+                                self.config.wallpaper.favorites.push(path);
+                            } else {
+                                self.config.wallpaper.favorites.retain(|p| *p != path);
+                            }
+                            if let Err(e) = self.config.save() {
+                                error!("Failed to save config: {}", e);
+                                self.notifications.error(tr("error-save-config"), &e);
+                            }
+                        }
                     } else {
-                        ui.label("No file selected");
+                        ui.label(tr("no-file-selected"));
                     }
-                    
-                    if ui.button("Browse...").clicked() {
+
+                    if ui.button(tr("browse")).clicked() {
                         let file_dialog = match self.selected_wallpaper_type {
-                            WallpaperType::Static => {
-                                FileDialog::new()
-                                    .add_filter("Images", &["png", "jpg", "jpeg", "bmp", "gif"])
-                            },
-                            WallpaperType::Video => {
-                                FileDialog::new()
-                                    .add_filter("Videos", &["mp4", "webm", "avi", "mkv"])
-                            },
-                            WallpaperType::Shader => {
-                                FileDialog::new()
-                                    .add_filter("Shaders", &["glsl", "frag", "vert"])
-                            },
-                            WallpaperType::Audio => {
-                                FileDialog::new()
-                                    .add_filter("Shaders", &["glsl", "frag", "vert"])
-                            },
+                            WallpaperType::Static | WallpaperType::AnimatedStatic => {
+                                FileDialog::new().add_filter(
+                                    &tr("filter-images"),
+                                    WallpaperType::Static.extensions(),
+                                )
+                            }
+                            WallpaperType::AnimatedImage => FileDialog::new().add_filter(
+                                &tr("filter-animations"),
+                                WallpaperType::AnimatedImage.extensions(),
+                            ),
+                            WallpaperType::Video => FileDialog::new()
+                                .add_filter(&tr("filter-videos"), &["mp4", "webm", "avi", "mkv"]),
+                            WallpaperType::Shader => FileDialog::new()
+                                .add_filter(&tr("filter-shaders"), &["glsl", "frag", "vert"]),
+                            WallpaperType::Audio => FileDialog::new()
+                                .add_filter(&tr("filter-shaders"), &["glsl", "frag", "vert"]),
                             _ => FileDialog::new(),
                         };
-                        
-                        if let Some(path) = file_dialog.pick_file() {
-                            self.selected_wallpaper_path = Some(path);
-                        }
+
+                        // Slideshows show a whole folder
+                        let picked = if self.selected_wallpaper_type == WallpaperType::Slideshow {
+                            file_dialog.pick_folder()
+                        } else {
+                            file_dialog.pick_file()
+                        };
+                        if let Some(path) = picked {
+                            // Moving GIFs play as animations rather than their first frame
+                            if self.selected_wallpaper_type == WallpaperType::Static
+                                && animated_image::is_animated(&path)
+                            {
+                                self.selected_wallpaper_type = WallpaperType::AnimatedImage;
+                            }
+                            self.selected_wallpaper_path = Some(path);
+                        }
                     }
                 });
-            },
+            }
             WallpaperType::Web => {
                 ui.horizontal(|ui| {
-                    ui.label("Web URL:");
+                    ui.label(tr("web-url"));
                     ui.text_edit_singleline(&mut self.selected_web_url);
                 });
-            },
-        }
-        
-        ui.separator();
-        
-        // Apply button
-        if ui.button("Apply").clicked() {
-            self.apply_wallpaper();
-        }
-        
-        // Stop button
-        if ui.button("Stop").clicked() {
-            self.stop_wallpaper();
+            }
+            WallpaperType::Particles | WallpaperType::NowPlaying => {
+                ui.label(tr("builtin-no-file"));
+            }
+            WallpaperType::Procedural => {
+                ui.horizontal(|ui| {
+                    ui.label(tr("procedural-pattern"));
+                    egui::ComboBox::from_id_source("procedural_pattern")
+                        .selected_text(procedural_pattern_label(self.selected_pattern))
+                        .show_ui(ui, |ui| {
+                            for pattern in ProceduralPattern::ALL {
+                                ui.selectable_value(
+                                    &mut self.selected_pattern,
+                                    pattern,
+                                    procedural_pattern_label(pattern),
+                                );
+                            }
+                        });
+                });
+                ui.label(tr("builtin-no-file"));
+            }
         }
-    }
 
-    /// Show gallery tab
-    fn show_gallery_tab(&mut self, ui: &mut egui::Ui) {
-        self.gallery_view.show(ui);
-    }
+        // Same wallpaper on every monitor, e.g. for presentations
+        if self.monitors.len() > 1 {
+            let mirror = ui
+                .toggle_value(
+                    &mut self.config.wallpaper.placement.mirror,
+                    tr("mirror-monitors"),
+                )
+                .on_hover_text(tr("mirror-monitors-hint"));
+            if mirror.changed() {
+                self.selected_monitor = None;
+                self.save_config_and_reload();
+            }
+        }
+        let mirror = self.config.wallpaper.placement.mirror;
 
-    /// Show scheduler tab
-    fn show_scheduler_tab(&mut self, ui: &mut egui::Ui) {
-        ui.heading("Wallpaper Scheduler");
-        
-        // Schedule items
-        let schedule_items = self.scheduler.get_schedule_items();
-        
-        if schedule_items.is_empty() {
-            ui.label("No schedule items. Add a new schedule item to automatically change wallpapers.");
-        } else {
-            egui::ScrollArea::vertical().show(ui, |ui| {
-                for (index, item) in schedule_items.iter().enumerate() {
-                    ui.horizontal(|ui| {
-                        // Enable/disable checkbox
-                        let mut enabled = item.enabled;
-                        if ui.checkbox(&mut enabled, "").changed() {
-                            let mut updated_item = item.clone();
-                            updated_item.enabled = enabled;
-                            if let Err(e) = self.scheduler.update_schedule_item(index, updated_item) {
-                                error!("Failed to update schedule item: {}", e);
-                            }
-                        }
-                        
-                        // Trigger type
-                        ui.label(format!("{:?}", item.trigger));
-                        
-                        // Wallpaper name
-                        ui.label(&item.wallpaper.name);
-                        
-                        // Edit button
-                        if ui.button("Edit").clicked() {
-                            self.editing_schedule_index = Some(index);
-                            self.new_schedule_item = Some(item.clone());
-                        }
-                        
-                        // Delete button
-                        if ui.button("Delete").clicked() {
-                            if let Err(e) = self.scheduler.remove_schedule_item(index) {
-                                error!("Failed to remove schedule item: {}", e);
-                            }
+        // Monitor to show the wallpaper on
+        if self.selected_wallpaper_type.runs_per_monitor() && self.monitors.len() > 1 && !mirror {
+            ui.horizontal(|ui| {
+                ui.label(tr("wallpaper-monitor"));
+                let selected = self
+                    .selected_monitor
+                    .as_deref()
+                    .map_or_else(|| tr("all-monitors"), |id| self.monitor_name(id));
+                egui::ComboBox::from_id_source("wallpaper_monitor")
+                    .selected_text(selected)
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.selected_monitor, None, tr("all-monitors"));
+                        for monitor in &self.monitors {
+                            ui.selectable_value(
+                                &mut self.selected_monitor,
+                                Some(monitor.id.clone()),
+                                &monitor.name,
+                            );
                         }
                     });
+            })
+            .response
+            .on_hover_text(tr("wallpaper-monitor-hint"));
+        }
+
+        // One wallpaper across all monitors
+        if matches!(
+            self.selected_wallpaper_type,
+            WallpaperType::Static | WallpaperType::Video | WallpaperType::Shader
+        ) && self.monitors.len() > 1
+            && self.selected_monitor.is_none()
+            && !mirror
+        {
+            if ui
+                .checkbox(
+                    &mut self.config.wallpaper.placement.span,
+                    tr("span-monitors"),
+                )
+                .on_hover_text(tr("span-monitors-hint"))
+                .changed()
+            {
+                self.save_config_and_reload();
+            }
+        }
+
+        // Fit mode and crop for static images
+        if self.selected_wallpaper_type == WallpaperType::Static {
+            if let Some(path) = &self.selected_wallpaper_path {
+                let mut changed = false;
+                egui::CollapsingHeader::new(tr("placement")).show(ui, |ui| {
+                    changed = self.placement_editor.show(
+                        ui,
+                        path,
+                        &mut self.config.wallpaper.placement,
+                        &self.monitors,
+                    );
+                });
+                if changed {
+                    if let Err(e) = self.config.save() {
+                        error!("Failed to save config: {}", e);
+                        self.notifications.error(tr("error-save-config"), &e);
+                    } else if let Err(e) = self.daemon.send(&Request::ReloadConfig) {
+                        error!("Failed to reload config in daemon: {}", e);
+                        self.notifications.error(tr("error-reload-config"), &e);
+                    }
                 }
-            });
+            }
         }
-        
-        ui.separator();
-        
-        // Add new schedule item
-        if ui.button("Add Schedule Item").clicked() {
-            self.new_schedule_item = Some(ScheduleItem {
-                trigger: TriggerType::Time(NaiveTime::from_hms_opt(8, 0, 0).unwrap()),
-                wallpaper: crate::core::WallpaperInfo {
-                    name: "New Schedule".to_string(),
-                    description: "New schedule item".to_string(),
-                    author: "Aether-Desk".to_string(),
-                    version: "1.0.0".to_string(),
-                    r#type: WallpaperType::Static,
-                    path: None,
-                    url: None,
-                },
-                enabled: true,
+
+        // Own image per monitor, dragged from the gallery or the favorites
+        if self.selected_wallpaper_type == WallpaperType::Static {
+            let mut images: Vec<PathBuf> = self
+                .config
+                .wallpaper
+                .favorites
+                .iter()
+                .map(PathBuf::from)
+                .filter(|path| WallpaperType::Static.accepts(path))
+                .collect();
+            for path in self.gallery_view.static_images() {
+                if !images.contains(&path) {
+                    images.push(path);
+                }
+            }
+            let mut changed = false;
+            egui::CollapsingHeader::new(tr("monitor-layout")).show(ui, |ui| {
+                changed = self.monitor_layout.show(
+                    ui,
+                    &self.monitors,
+                    &images,
+                    &mut self.config.wallpaper.placement.monitor_images,
+                );
             });
-            self.editing_schedule_index = None;
+            if changed {
+                self.save_config_and_reload();
+                if self.config.wallpaper.wallpaper_type == WallpaperType::Static {
+                    self.restore_saved_wallpaper(ui.ctx());
+                }
+            }
         }
-        
-        // Edit schedule item
-        if let Some(item) = &mut self.new_schedule_item {
-            ui.separator();
-            ui.heading(if self.editing_schedule_index.is_some() {
-                "Edit Schedule Item"
-            } else {
-                "Add Schedule Item"
-            });
-            
-            // Trigger type
+
+        // Animation between static wallpapers
+        if self.selected_wallpaper_type == WallpaperType::Static {
+            let mut transition = self.config.wallpaper.transition;
             ui.horizontal(|ui| {
-                ui.label("Trigger Type:");
-                egui::ComboBox::from_label("")
-                    .selected_text(match &item.trigger {
-                        TriggerType::Time(_) => "Time",
-                        TriggerType::Interval(_) => "Interval",
-                        TriggerType::SystemEvent(_) => "System Event",
-                        TriggerType::Custom(_) => "Custom",
-                    })
+                ui.label(tr("transition"));
+                egui::ComboBox::from_id_source("wallpaper_transition")
+                    .selected_text(transition_label(transition.effect))
                     .show_ui(ui, |ui| {
-                        if ui.selectable_label(matches!(item.trigger, TriggerType::Time(_)), "Time").clicked() {
-                            item.trigger = TriggerType::Time(NaiveTime::from_hms_opt(8, 0, 0).unwrap());
+                        for effect in TRANSITION_EFFECTS {
+                            ui.selectable_value(
+                                &mut transition.effect,
+                                effect,
+                                transition_label(effect),
+                            );
                         }
-                        if ui.selectable_label(matches!(item.trigger, TriggerType::Interval(_)), "Interval").clicked() {
-                            item.trigger = TriggerType::Interval(chrono::Duration::hours(1));
-                        }
-                        if ui.selectable_label(matches!(item.trigger, TriggerType::SystemEvent(_)), "System Event").clicked() {
-                            item.trigger = TriggerType::SystemEvent("startup".to_string());
-                        }
-                        if ui.selectable_label(matches!(item.trigger, TriggerType::Custom(_)), "Custom").clicked() {
-                            item.trigger = TriggerType::Custom("custom".to_string());
-                        }
-                    });
-            });
-            
-            // Trigger details
-            match &mut item.trigger {
-                TriggerType::Time(time) => {
-                    ui.horizontal(|ui| {
-                        ui.label("Time:");
-                        let mut hour = time.hour() as u32;
-                        let mut minute = time.minute() as u32;
-                        
-                        if ui.add(egui::DragValue::new(&mut hour).speed(1).clamp_range(0..=23)).changed() {
-                            *time = NaiveTime::from_hms_opt(hour, minute, 0).unwrap();
-                        }
-                        
-                        ui.label(":");
-                        
-                        if ui.add(egui::DragValue::new(&mut minute).speed(1).clamp_range(0..=59)).changed() {
-                            *time = NaiveTime::from_hms_opt(hour, minute, 0).unwrap();
-                        }
-                    });
-                },
-                TriggerType::Interval(interval) => {
-                    ui.horizontal(|ui| {
-                        ui.label("Interval:");
-                        let mut hours = interval.num_hours() as u32;
-                        let mut minutes = (interval.num_minutes() % 60) as u32;
-                        
-                        if ui.add(egui::DragValue::new(&mut hours).speed(1)).changed() {
-                            *interval = chrono::Duration::hours(hours as i64) + chrono::Duration::minutes(minutes as i64);
-                        }
-                        
-                        ui.label("hours");
-                        
-                        if ui.add(egui::DragValue::new(&mut minutes).speed(1).clamp_range(0..=59)).changed() {
-                            *interval = chrono::Duration::hours(hours as i64) + chrono::Duration::minutes(minutes as i64);
-                        }
-                        
-                        ui.label("minutes");
-                    });
-                },
-                TriggerType::SystemEvent(event) => {
-                    ui.horizontal(|ui| {
-                        ui.label("Event:");
-                        ui.text_edit_singleline(event);
-                    });
-                },
-                TriggerType::Custom(trigger) => {
-                    ui.horizontal(|ui| {
-                        ui.label("Trigger:");
-                        ui.text_edit_singleline(trigger);
                     });
-                },
+                if transition.effect != TransitionEffect::None {
+                    ui.add(
+                        egui::Slider::new(&mut transition.duration_ms, 200..=3000)
+                            .suffix(" ms")
+                            .text(tr("transition-duration")),
+                    );
+                }
+            })
+            .response
+            .on_hover_text(tr("transition-hint"));
+            if transition != self.config.wallpaper.transition {
+                self.config.wallpaper.transition = transition;
+                self.save_config_and_reload();
             }
-            
-            // Wallpaper type
+        }
+
+        // Motion of animated static images
+        if self.selected_wallpaper_type == WallpaperType::AnimatedStatic {
+            let mut animation = self.config.wallpaper.animation.clone();
             ui.horizontal(|ui| {
-                ui.label("Wallpaper Type:");
-                egui::ComboBox::from_label("")
-                    .selected_text(format!("{:?}", item.wallpaper.r#type))
+                ui.label(tr("motion-effect"));
+                egui::ComboBox::from_id_source("motion_effect")
+                    .selected_text(motion_label(animation.effect))
                     .show_ui(ui, |ui| {
-                        ui.selectable_value(&mut item.wallpaper.r#type, WallpaperType::Static, "Static");
-                        ui.selectable_value(&mut item.wallpaper.r#type, WallpaperType::Video, "Video");
-                        ui.selectable_value(&mut item.wallpaper.r#type, WallpaperType::Web, "Web");
-                        ui.selectable_value(&mut item.wallpaper.r#type, WallpaperType::Shader, "Shader");
-                        ui.selectable_value(&mut item.wallpaper.r#type, WallpaperType::Audio, "Audio");
-                    });
-            });
-            
-            // Wallpaper selection based on type
-            match item.wallpaper.r#type {
-                WallpaperType::Static | WallpaperType::Video | WallpaperType::Shader | WallpaperType::Audio => {
-                    ui.horizontal(|ui| {
-                        ui.label("Wallpaper Path:");
-                        
-                        if let Some(path) = &item.wallpaper.path {
-                            ui.label(path.to_string_lossy());
-                        } else {
-                            ui.label("No file selected");
-                        }
-                        
-                        if ui.button("Browse...").clicked() {
-                            let file_dialog = match item.wallpaper.r#type {
-                                WallpaperType::Static => {
-                                    FileDialog::new()
-                                        .add_filter("Images", &["png", "jpg", "jpeg", "bmp", "gif"])
-                                },
-                                WallpaperType::Video => {
-                                    FileDialog::new()
-                                        .add_filter("Videos", &["mp4", "webm", "avi", "mkv"])
-                                },
-                                WallpaperType::Shader => {
-                                    FileDialog::new()
-                                        .add_filter("Shaders", &["glsl", "frag", "vert"])
-                                },
-                                WallpaperType::Audio => {
-                                    FileDialog::new()
-                                        .add_filter("Shaders", &["glsl", "frag", "vert"])
-                                },
-                                _ => FileDialog::new(),
-                            };
-                            
-                            if let Some(path) = file_dialog.pick_file() {
-                                item.wallpaper.path = Some(path);
-                            }
+                        for effect in [MotionEffect::Parallax, MotionEffect::KenBurns] {
+                            ui.selectable_value(
+                                &mut animation.effect,
+                                effect,
+                                motion_label(effect),
+                            );
                         }
                     });
-                },
-                WallpaperType::Web => {
-                    ui.horizontal(|ui| {
-                        ui.label("Web URL:");
-                        let mut url = item.wallpaper.url.clone().unwrap_or_default();
-                        if ui.text_edit_singleline(&mut url).changed() {
-                            item.wallpaper.url = Some(url);
-                        }
+                ui.add(
+                    egui::Slider::new(&mut animation.fps, 1..=30)
+                        .suffix(" fps")
+                        .text(tr("motion-fps")),
+                );
+            });
+            if animation.effect == MotionEffect::Parallax {
+                ui.horizontal(|ui| {
+                    let mut percent = animation.parallax_strength * 100.0;
+                    if ui
+                        .add(
+                            egui::Slider::new(&mut percent, 0.5..=5.0)
+                                .suffix("%")
+                                .text(tr("parallax-strength")),
+                        )
+                        .changed()
+                    {
+                        animation.parallax_strength = percent / 100.0;
+                    }
+                    ui.checkbox(&mut animation.depth_map, tr("parallax-depth-map"))
+                        .on_hover_text(tr("parallax-depth-map-hint"));
+                })
+                .response
+                .on_hover_text(tr("parallax-hint"));
+            }
+            if animation.effect == MotionEffect::KenBurns {
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::Slider::new(&mut animation.ken_burns_minutes, 1..=15)
+                            .suffix(" min")
+                            .text(tr("ken-burns-duration")),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut animation.ken_burns_zoom, 1.05..=1.5)
+                            .text(tr("ken-burns-zoom")),
+                    );
+                })
+                .response
+                .on_hover_text(tr("ken-burns-hint"));
+            }
+            if animation != self.config.wallpaper.animation {
+                self.config.wallpaper.animation = animation;
+                self.save_config_and_reload();
+            }
+        }
+
+        // Speed and loops of animated images
+        if self.selected_wallpaper_type == WallpaperType::AnimatedImage {
+            self.show_animated_image_settings(ui);
+        }
+
+        // Look and behavior of the particles
+        if self.selected_wallpaper_type == WallpaperType::Particles {
+            self.show_particle_settings(ui);
+        }
+
+        // Speed, colors and density of the selected pattern
+        if self.selected_wallpaper_type == WallpaperType::Procedural {
+            self.show_procedural_settings(ui);
+        }
+
+        // Timing, order and transition of slideshows
+        if self.selected_wallpaper_type == WallpaperType::Slideshow {
+            self.show_slideshow_settings(ui);
+        }
+
+        // Look of the album art
+        if self.selected_wallpaper_type == WallpaperType::NowPlaying {
+            self.show_now_playing_settings(ui);
+        }
+
+        // Lock screen following the desktop
+        if self.selected_wallpaper_type == WallpaperType::Static {
+            let sync = ui
+                .checkbox(
+                    &mut self.config.wallpaper.sync_lock_screen,
+                    tr("sync-lock-screen"),
+                )
+                .on_hover_text(tr("sync-lock-screen-hint"));
+            if sync.changed() {
+                self.save_config_and_reload();
+            }
+        }
+
+        // Frame rate limit of animated wallpapers
+        if matches!(
+            self.selected_wallpaper_type,
+            WallpaperType::Video | WallpaperType::Shader
+        ) {
+            if let Some(path) = self.selected_wallpaper_path.clone() {
+                let mut frame_rate = self.config.wallpaper.frame_rate.clone();
+                egui::CollapsingHeader::new(tr("frame-rate-limit")).show(ui, |ui| {
+                    let mut own = frame_rate
+                        .overrides
+                        .contains_key(path.to_string_lossy().as_ref());
+                    let mut limit = frame_rate.limit_for(&path);
+                    ui.checkbox(&mut own, tr("frame-rate-own-limit"));
+                    ui.add_enabled_ui(own, |ui| {
+                        frame_rate_limit_editor(ui, "wallpaper_frame_rate", &mut limit);
                     });
-                },
+                    frame_rate.set_override(&path, own.then_some(limit));
+                });
+                if frame_rate != self.config.wallpaper.frame_rate {
+                    self.config.wallpaper.frame_rate = frame_rate;
+                    self.save_config_and_reload();
+                }
             }
-            
-            // Wallpaper name
-            ui.horizontal(|ui| {
-                ui.label("Name:");
-                ui.text_edit_singleline(&mut item.wallpaper.name);
-            });
-            
-            // Wallpaper description
-            ui.horizontal(|ui| {
-                ui.label("Description:");
-                ui.text_edit_singleline(&mut item.wallpaper.description);
-            });
-            
-            // Enable/disable
-            ui.checkbox(&mut item.enabled, "Enabled");
-            
-            // Save button
-            if ui.button("Save").clicked() {
-                if let Some(index) = self.editing_schedule_index {
-                    if let Err(e) = self.scheduler.update_schedule_item(index, item.clone()) {
-                        error!("Failed to update schedule item: {}", e);
+        }
+
+        ui.separator();
+
+        // Apply button
+        ui.horizontal(|ui| {
+            if ui.button(tr("apply")).clicked() {
+                self.apply_wallpaper(ui.ctx());
+            }
+            if ui
+                .button(tr("apply-to-workspace"))
+                .on_hover_text(tr("apply-to-workspace-hint"))
+                .clicked()
+            {
+                self.assign_to_workspace();
+            }
+            if self.selected_wallpaper_type == WallpaperType::Static {
+                if let Some(path) = self.selected_wallpaper_path.clone() {
+                    if ui.button(tr("set-lock-screen")).clicked() {
+                        self.commands
+                            .send(Command::SetLockScreen { path }, ui.ctx());
                     }
+                }
+            }
+            if self.commands.is_busy() {
+                ui.spinner();
+                ui.label(tr("applying-wallpaper"));
+            }
+        });
+
+        // Stop, undo and redo buttons
+        ui.horizontal(|ui| {
+            if ui.button(tr("stop")).clicked() {
+                self.stop_wallpaper(ui.ctx());
+            }
+
+            let idle =
+                self.try_on.is_none() && self.benchmark.is_none() && !self.commands.is_busy();
+            let (can_undo, can_redo) = self
+                .daemon_status
+                .as_ref()
+                .map_or((false, false), |status| (status.can_undo, status.can_redo));
+            if ui
+                .add_enabled(idle && can_undo, egui::Button::new(tr("undo")))
+                .on_hover_text(tr("undo-hint"))
+                .clicked()
+            {
+                self.commands.send(Command::UndoWallpaper, ui.ctx());
+            }
+            if ui
+                .add_enabled(idle && can_redo, egui::Button::new(tr("redo")))
+                .on_hover_text(tr("redo-hint"))
+                .clicked()
+            {
+                self.commands.send(Command::RedoWallpaper, ui.ctx());
+            }
+        });
+
+        ui.horizontal(|ui| {
+            let preview_label = if self.preview.is_open() {
+                tr("close-preview")
+            } else {
+                tr("preview")
+            };
+            if ui.button(preview_label).clicked() {
+                if self.preview.is_open() {
+                    self.preview.close();
                 } else {
-                    if let Err(e) = self.scheduler.add_schedule_item(item.clone()) {
-                        error!("Failed to add schedule item: {}", e);
-                    }
+                    self.open_preview();
                 }
-                
-                // Save schedule
-                if let Err(e) = self.scheduler.save_schedule(&self.config) {
-                    error!("Failed to save schedule: {}", e);
+            }
+
+            let idle =
+                self.try_on.is_none() && self.benchmark.is_none() && !self.commands.is_busy();
+            if ui
+                .add_enabled(idle, egui::Button::new(tr("try-on-desktop")))
+                .on_hover_text(tr_args(
+                    "try-on-desktop-hint",
+                    &[("seconds", &TRY_ON_DURATION.as_secs().to_string())],
+                ))
+                .clicked()
+            {
+                self.try_on_wallpaper(ui.ctx());
+            }
+        });
+
+        if let Some((_, started)) = &self.try_on {
+            let remaining = TRY_ON_DURATION.saturating_sub(started.elapsed()).as_secs() + 1;
+            ui.horizontal(|ui| {
+                ui.label(tr_args(
+                    "try-on-remaining",
+                    &[("seconds", &remaining.to_string())],
+                ));
+                if ui.button(tr("keep")).clicked() {
+                    self.keep_try_on();
                 }
-                
-                self.new_schedule_item = None;
-                self.editing_schedule_index = None;
+                if ui.button(tr("revert")).clicked() {
+                    self.revert_try_on(ui.ctx());
+                }
+            });
+        }
+
+        ui.horizontal(|ui| {
+            let idle =
+                self.try_on.is_none() && self.benchmark.is_none() && !self.commands.is_busy();
+            let animated = self.selected_wallpaper_type != WallpaperType::Static;
+            if ui
+                .add_enabled(idle && animated, egui::Button::new(tr("benchmark")))
+                .on_hover_text(tr("benchmark-hint"))
+                .clicked()
+            {
+                self.start_benchmark(ui.ctx());
             }
-            
-            // Cancel button
-            if ui.button("Cancel").clicked() {
-                self.new_schedule_item = None;
-                self.editing_schedule_index = None;
+            ui.add_enabled(
+                idle,
+                egui::DragValue::new(&mut self.benchmark_seconds)
+                    .clamp_range(5..=300)
+                    .suffix(" s"),
+            );
+        });
+
+        if let Some(benchmark) = &self.benchmark {
+            let text = tr_args("benchmark-running", &[("wallpaper", benchmark.wallpaper())]);
+            let progress = benchmark.progress();
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::ProgressBar::new(progress)
+                        .text(text)
+                        .desired_width(300.0),
+                );
+                if ui.button(tr("cancel")).clicked() {
+                    self.benchmark = None;
+                    self.restore_saved_wallpaper(ui.ctx());
+                }
+            });
+        }
+
+        // Follow the selection while the preview is open
+        if self.preview.is_open() {
+            if let Some(path) = &self.selected_wallpaper_path {
+                if !self.preview.is_showing(path) {
+                    self.preview
+                        .open(self.selected_wallpaper_type.clone(), path);
+                }
             }
+            ui.separator();
+            self.preview.show(ui);
         }
     }
-    
-    /// Show widgets tab
-    fn show_widgets_tab(&mut self, ui: &mut egui::Ui) {
-        ui.heading("Widgets");
-        
-        // Widget list
-        let widget_configs = self.widget_manager.get_widget_configs();
-        
-        if widget_configs.is_empty() {
-            ui.label("No widgets installed. Add a new widget to display information on your desktop.");
+
+    /// Show the particle wallpaper's preset and parameters
+    fn show_particle_settings(&mut self, ui: &mut egui::Ui) {
+        let mut particles = self.config.wallpaper.particles.clone();
+
+        ui.horizontal(|ui| {
+            ui.label(tr("particle-preset"));
+            egui::ComboBox::from_id_source("particle_preset")
+                .selected_text(
+                    ParticlePreset::ALL
+                        .into_iter()
+                        .find(|preset| preset.settings() == particles)
+                        .map_or_else(|| tr("particle-preset-custom"), particle_preset_label),
+                )
+                .show_ui(ui, |ui| {
+                    for preset in ParticlePreset::ALL {
+                        if ui
+                            .selectable_label(
+                                preset.settings() == particles,
+                                particle_preset_label(preset),
+                            )
+                            .clicked()
+                        {
+                            particles = preset.settings();
+                        }
+                    }
+                });
+        });
+
+        ui.add(
+            egui::Slider::new(&mut particles.count, 10..=MAX_PARTICLES).text(tr("particle-count")),
+        );
+        ui.add(egui::Slider::new(&mut particles.size, 0.5..=12.0).text(tr("particle-size")));
+        ui.add(egui::Slider::new(&mut particles.speed, 0.0..=200.0).text(tr("particle-speed")));
+        ui.add(
+            egui::Slider::new(&mut particles.gravity, -200.0..=200.0).text(tr("particle-gravity")),
+        )
+        .on_hover_text(tr("particle-gravity-hint"));
+        ui.add(
+            egui::Slider::new(&mut particles.link_distance, 0.0..=0.3).text(tr("particle-links")),
+        )
+        .on_hover_text(tr("particle-links-hint"));
+
+        ui.horizontal(|ui| {
+            ui.label(tr("particle-mouse"));
+            egui::ComboBox::from_id_source("particle_mouse")
+                .selected_text(mouse_reaction_label(particles.mouse))
+                .show_ui(ui, |ui| {
+                    for reaction in [
+                        MouseReaction::None,
+                        MouseReaction::Attract,
+                        MouseReaction::Repel,
+                    ] {
+                        ui.selectable_value(
+                            &mut particles.mouse,
+                            reaction,
+                            mouse_reaction_label(reaction),
+                        );
+                    }
+                });
+        });
+        ui.add_enabled_ui(particles.mouse != MouseReaction::None, |ui| {
+            ui.add(
+                egui::Slider::new(&mut particles.mouse_strength, 0.1..=3.0)
+                    .text(tr("particle-mouse-strength")),
+            );
+            ui.add(
+                egui::Slider::new(&mut particles.mouse_radius, 0.05..=0.5)
+                    .text(tr("particle-mouse-radius")),
+            );
+        });
+
+        ui.horizontal(|ui| {
+            ui.label(tr("particle-colors"));
+            let mut removed = None;
+            for (index, hex) in particles.colors.iter_mut().enumerate() {
+                let mut color = parse_hex_color(hex).unwrap_or(egui::Color32::WHITE);
+                let response = ui.color_edit_button_srgba(&mut color);
+                if response.changed() {
+                    *hex = theme_editor::format_hex_color(color);
+                }
+                if response.secondary_clicked() {
+                    removed = Some(index);
+                }
+            }
+            if let Some(index) = removed.filter(|_| particles.colors.len() > 1) {
+                particles.colors.remove(index);
+            }
+            if ui
+                .small_button("+")
+                .on_hover_text(tr("particle-add-color"))
+                .clicked()
+            {
+                particles.colors.push("#ffffff".to_string());
+            }
+        })
+        .response
+        .on_hover_text(tr("particle-colors-hint"));
+
+        ui.horizontal(|ui| {
+            ui.label(tr("particle-background"));
+            let mut color = parse_hex_color(&particles.background).unwrap_or(egui::Color32::BLACK);
+            if ui.color_edit_button_srgba(&mut color).changed() {
+                particles.background = theme_editor::format_hex_color(color);
+            }
+            ui.add(
+                egui::Slider::new(&mut particles.fps, 1..=60)
+                    .suffix(" fps")
+                    .text(tr("motion-fps")),
+            );
+        });
+        ui.label(tr("particle-hint"));
+
+        if particles != self.config.wallpaper.particles {
+            self.config.wallpaper.particles = particles;
+            self.save_config_and_reload();
+        }
+    }
+
+    /// Show the selected procedural pattern's settings
+    fn show_procedural_settings(&mut self, ui: &mut egui::Ui) {
+        let mut procedural = self.config.wallpaper.procedural.clone();
+
+        let settings = procedural.settings_mut(self.selected_pattern);
+        ui.add(egui::Slider::new(&mut settings.speed, 0.1..=3.0).text(tr("procedural-speed")));
+        ui.add(egui::Slider::new(&mut settings.density, 0.0..=1.0).text(tr("procedural-density")))
+            .on_hover_text(tr("procedural-density-hint"));
+        ui.horizontal(|ui| {
+            ui.label(tr("procedural-colors"));
+            egui::ComboBox::from_id_source("procedural_scheme")
+                .selected_text(color_scheme_label(settings.scheme))
+                .show_ui(ui, |ui| {
+                    for scheme in ColorScheme::ALL {
+                        ui.selectable_value(
+                            &mut settings.scheme,
+                            scheme,
+                            color_scheme_label(scheme),
+                        );
+                    }
+                });
+        });
+        ui.horizontal(|ui| {
+            ui.label(tr("procedural-seed"));
+            ui.add(egui::DragValue::new(&mut settings.seed));
+            if ui.small_button(tr("procedural-new-seed")).clicked() {
+                settings.seed = Rng::new().between(1.0, 1_000_000.0) as u64;
+            }
+        })
+        .response
+        .on_hover_text(tr("procedural-seed-hint"));
+        ui.add(
+            egui::Slider::new(&mut procedural.fps, 1..=60)
+                .suffix(" fps")
+                .text(tr("motion-fps")),
+        );
+        ui.label(tr("procedural-hint"));
+
+        if procedural != self.config.wallpaper.procedural {
+            self.config.wallpaper.procedural = procedural;
+            self.save_config_and_reload();
+        }
+    }
+
+    /// Show the speed and loops of animated image wallpapers
+    fn show_animated_image_settings(&mut self, ui: &mut egui::Ui) {
+        let mut animated_image = self.config.wallpaper.animated_image.clone();
+
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::Slider::new(&mut animated_image.speed, 0.25..=4.0)
+                    .logarithmic(true)
+                    .suffix("×")
+                    .text(tr("animated-image-speed")),
+            );
+            ui.add(
+                egui::Slider::new(&mut animated_image.fps, 1..=60)
+                    .suffix(" fps")
+                    .text(tr("motion-fps")),
+            );
+        });
+        ui.horizontal(|ui| {
+            let mut forever = animated_image.loops == 0;
+            if ui
+                .checkbox(&mut forever, tr("animated-image-loop-forever"))
+                .changed()
+            {
+                animated_image.loops = if forever { 0 } else { 1 };
+            }
+            if !forever {
+                ui.add(
+                    egui::Slider::new(&mut animated_image.loops, 1..=100)
+                        .text(tr("animated-image-loops")),
+                );
+            }
+        });
+        ui.label(tr("animated-image-hint"));
+
+        if animated_image != self.config.wallpaper.animated_image {
+            self.config.wallpaper.animated_image = animated_image;
+            self.save_config_and_reload();
+        }
+    }
+
+    /// Show the settings of slideshow wallpapers
+    fn show_slideshow_settings(&mut self, ui: &mut egui::Ui) {
+        let mut slideshow = self.config.wallpaper.slideshow.clone();
+
+        let mut minutes = slideshow.interval_secs as f32 / 60.0;
+        if ui
+            .add(
+                egui::Slider::new(&mut minutes, 0.25..=120.0)
+                    .logarithmic(true)
+                    .suffix(" min")
+                    .text(tr("slideshow-interval")),
+            )
+            .changed()
+        {
+            slideshow.interval_secs = (minutes * 60.0).round() as u32;
+        }
+        ui.horizontal(|ui| {
+            ui.label(tr("slideshow-order"));
+            egui::ComboBox::from_id_source("slideshow_order")
+                .selected_text(slideshow_order_label(slideshow.order))
+                .show_ui(ui, |ui| {
+                    for order in [
+                        SlideshowOrder::Name,
+                        SlideshowOrder::Modified,
+                        SlideshowOrder::Shuffle,
+                    ] {
+                        ui.selectable_value(
+                            &mut slideshow.order,
+                            order,
+                            slideshow_order_label(order),
+                        );
+                    }
+                });
+        });
+        ui.horizontal(|ui| {
+            ui.label(tr("transition"));
+            egui::ComboBox::from_id_source("slideshow_transition")
+                .selected_text(transition_label(slideshow.transition.effect))
+                .show_ui(ui, |ui| {
+                    for effect in TRANSITION_EFFECTS {
+                        ui.selectable_value(
+                            &mut slideshow.transition.effect,
+                            effect,
+                            transition_label(effect),
+                        );
+                    }
+                });
+            if slideshow.transition.effect != TransitionEffect::None {
+                ui.add(
+                    egui::Slider::new(&mut slideshow.transition.duration_ms, 200..=10_000)
+                        .suffix(" ms")
+                        .text(tr("transition-duration")),
+                );
+                ui.add(
+                    egui::Slider::new(&mut slideshow.fps, 1..=60)
+                        .suffix(" fps")
+                        .text(tr("motion-fps")),
+                );
+            }
+        });
+        ui.label(tr("slideshow-hint"));
+
+        if slideshow != self.config.wallpaper.slideshow {
+            self.config.wallpaper.slideshow = slideshow;
+            self.save_config_and_reload();
+        }
+    }
+
+    /// Show the settings of the now-playing wallpaper
+    fn show_now_playing_settings(&mut self, ui: &mut egui::Ui) {
+        let mut now_playing = self.config.wallpaper.now_playing.clone();
+
+        ui.add(
+            egui::Slider::new(&mut now_playing.blur, 0..=120)
+                .suffix(" px")
+                .text(tr("now-playing-blur")),
+        );
+        ui.add(egui::Slider::new(&mut now_playing.dim, 0.0..=0.9).text(tr("now-playing-dim")));
+        ui.checkbox(&mut now_playing.show_cover, tr("now-playing-cover"));
+        ui.add(
+            egui::Slider::new(&mut now_playing.poll_secs, 1..=30)
+                .suffix(" s")
+                .text(tr("now-playing-poll")),
+        );
+        ui.horizontal(|ui| {
+            ui.label(tr("transition"));
+            egui::ComboBox::from_id_source("now_playing_transition")
+                .selected_text(transition_label(now_playing.transition.effect))
+                .show_ui(ui, |ui| {
+                    for effect in TRANSITION_EFFECTS {
+                        ui.selectable_value(
+                            &mut now_playing.transition.effect,
+                            effect,
+                            transition_label(effect),
+                        );
+                    }
+                });
+            if now_playing.transition.effect != TransitionEffect::None {
+                ui.add(
+                    egui::Slider::new(&mut now_playing.transition.duration_ms, 200..=5_000)
+                        .suffix(" ms")
+                        .text(tr("transition-duration")),
+                );
+                ui.add(
+                    egui::Slider::new(&mut now_playing.fps, 1..=60)
+                        .suffix(" fps")
+                        .text(tr("motion-fps")),
+                );
+            }
+        });
+        ui.label(tr("now-playing-hint"));
+
+        if now_playing != self.config.wallpaper.now_playing {
+            self.config.wallpaper.now_playing = now_playing;
+            self.save_config_and_reload();
+        }
+    }
+
+    /// Preview the selected wallpaper in the app
+    fn open_preview(&mut self) {
+        match (&self.selected_wallpaper_type, &self.selected_wallpaper_path) {
+            (
+                WallpaperType::Web
+                | WallpaperType::Particles
+                | WallpaperType::Procedural
+                | WallpaperType::Slideshow
+                | WallpaperType::NowPlaying,
+                _,
+            ) => self.notifications.info(tr("preview-unavailable")),
+            (wallpaper_type, Some(path)) => self.preview.open(wallpaper_type.clone(), path),
+            (wallpaper_type, None) => {
+                self.notifications.warning(
+                    tr_args(
+                        "error-no-path",
+                        &[("type", &wallpaper_type_label(wallpaper_type))],
+                    ),
+                    None,
+                );
+            }
+        }
+    }
+
+    /// Offer to stop or keep the players a crashed run left behind
+    fn show_orphans(&mut self, ui: &mut egui::Ui) {
+        let count = self
+            .daemon_status
+            .as_ref()
+            .map_or(0, |status| status.orphans.len());
+        if count == 0 {
+            return;
+        }
+
+        let mut resolve = None;
+        ui.colored_label(
+            egui::Color32::YELLOW,
+            tr_args("orphans-found", &[("count", &count.to_string())]),
+        );
+        ui.horizontal(|ui| {
+            if ui.button(tr("stop-orphans")).clicked() {
+                resolve = Some(false);
+            }
+            if ui
+                .button(tr("keep-orphans"))
+                .on_hover_text(tr("keep-orphans-hint"))
+                .clicked()
+            {
+                resolve = Some(true);
+            }
+        });
+        ui.separator();
+
+        if let Some(adopt) = resolve {
+            match self.daemon.send(&Request::ResolveOrphans { adopt }) {
+                Ok(_) => self.last_status_refresh = None,
+                Err(e) => {
+                    error!("Failed to resolve orphaned players: {}", e);
+                    self.notifications.error(tr("error-resolve-orphans"), &e);
+                }
+            }
+        }
+    }
+
+    /// Show gallery tab
+    fn show_gallery_tab(&mut self, ui: &mut egui::Ui) {
+        if let Some(detach) = self.show_detach_toggle(ui, self.config.app.detach_gallery) {
+            self.set_gallery_detached(detach);
+        }
+        if !self.config.app.detach_gallery {
+            self.refresh_daemon_status(ui.ctx());
+            if let Some(action) = self.gallery_view.show(ui) {
+                self.handle_gallery_action(action);
+            }
+        }
+    }
+
+    /// Export or install a wallpaper pack picked in the gallery
+    ///
+    /// Installed packs bring their suggested schedule items, added once.
+    fn handle_gallery_action(&mut self, action: GalleryAction) {
+        match action {
+            GalleryAction::ExportPack { path, wallpapers } => {
+                let name = path
+                    .file_stem()
+                    .map_or(String::new(), |stem| stem.to_string_lossy().to_string());
+                match pack::export(
+                    &path,
+                    &name,
+                    &wallpapers,
+                    &self.scheduler.get_schedule_items(),
+                ) {
+                    Ok(manifest) => {
+                        info!("Exported wallpaper pack {} to {}", name, path.display());
+                        let count = manifest.wallpapers.len().to_string();
+                        self.notifications.push(
+                            EventLevel::Info,
+                            tr_args("pack-exported", &[("name", &name), ("count", &count)]),
+                            Some(path.display().to_string()),
+                        );
+                    }
+                    Err(e) => {
+                        error!("Failed to export wallpaper pack: {}", e);
+                        let _ = std::fs::remove_file(&path);
+                        self.notifications.error(tr("error-export-pack"), &e);
+                    }
+                }
+            }
+            GalleryAction::InstallPack(path) => {
+                let installed = match pack::install(&path, &Config::get_packs_dir()) {
+                    Ok(installed) => installed,
+                    Err(e) => {
+                        error!("Failed to install wallpaper pack {}: {}", path.display(), e);
+                        self.notifications.error(tr("error-install-pack"), &e);
+                        return;
+                    }
+                };
+                self.gallery_view.add_pack(&installed);
+
+                let existing = self.scheduler.get_schedule_items();
+                let mut added = 0;
+                for item in &installed.schedule {
+                    let known = existing.iter().any(|existing| {
+                        existing.trigger == item.trigger
+                            && pack::same_wallpaper(&existing.wallpaper, &item.wallpaper)
+                    });
+                    if !known && self.scheduler.add_schedule_item(item.clone()).is_ok() {
+                        added += 1;
+                    }
+                }
+                if added > 0 {
+                    self.save_schedule();
+                }
+
+                info!(
+                    "Installed wallpaper pack {} into {}",
+                    installed.manifest.name,
+                    installed.folder.display()
+                );
+                let count = installed.wallpapers.len().to_string();
+                let message = tr_args(
+                    "pack-installed",
+                    &[
+                        ("name", &installed.manifest.name),
+                        ("count", &count),
+                        ("schedule", &added.to_string()),
+                    ],
+                );
+                self.notifications.info(message);
+            }
+        }
+    }
+
+    /// Show generate tab
+    fn show_generate_tab(&mut self, ui: &mut egui::Ui) {
+        let mut ai = self.config.ai.clone();
+        let action = self.generation_panel.show(
+            ui,
+            &mut ai,
+            &self.secret_store,
+            &self.monitors,
+            &mut self.notifications,
+        );
+        if ai != self.config.ai {
+            self.config.ai = ai;
+            if let Err(e) = self.config.save() {
+                error!("Failed to save config: {}", e);
+                self.notifications.error(tr("error-save-config"), &e);
+            }
+        }
+
+        let (path, apply) = match action {
+            Some(GenerationAction::Saved(path)) => (path, false),
+            Some(GenerationAction::Apply(path)) => (path, true),
+            None => return,
+        };
+        self.gallery_view
+            .add_wallpaper(GalleryItem::from_path(path.clone(), WallpaperType::Static));
+        if apply {
+            let wallpaper = WallpaperInfo::new(WallpaperType::Static, Some(path), None);
+            self.commands.send(
+                Command::ApplyWallpaper {
+                    wallpaper,
+                    intent: Intent::Keep,
+                },
+                ui.ctx(),
+            );
+        } else {
+            self.notifications.push(
+                EventLevel::Info,
+                tr("generated-saved"),
+                Some(path.display().to_string()),
+            );
+        }
+    }
+
+    /// Show scheduler tab
+    fn show_scheduler_tab(&mut self, ui: &mut egui::Ui) {
+        ui.heading(tr("wallpaper-scheduler"));
+
+        // Schedule items
+        let schedule_items = self.scheduler.get_schedule_items();
+
+        if schedule_items.is_empty() {
+            ui.label(tr("no-schedule-items"));
         } else {
             egui::ScrollArea::vertical().show(ui, |ui| {
-                for (id, config) in widget_configs.iter() {
+                for (index, item) in schedule_items.iter().enumerate() {
                     ui.horizontal(|ui| {
                         // Enable/disable checkbox
-                        let mut enabled = config.enabled;
+                        let mut enabled = item.enabled;
                         if ui.checkbox(&mut enabled, "").changed() {
-                            let mut updated_config = config.clone();
-                            updated_config.enabled = enabled;
-                            if let Err(e) = self.widget_manager.update_widget(id, updated_config) {
-                                error!("Failed to update widget: {}", e);
+                            let mut updated_item = item.clone();
+                            updated_item.enabled = enabled;
+                            if let Err(e) = self.scheduler.update_schedule_item(index, updated_item)
+                            {
+                                error!("Failed to update schedule item: {}", e);
+                                self.notifications
+                                    .error(tr("error-update-schedule-item"), &e);
                             }
+                            self.save_schedule();
                         }
-                        
-                        // Widget type
-                        ui.label(format!("{:?}", config.widget_type));
-                        
-                        // Widget position
-                        ui.label(format!("{:?}", config.position));
-                        
-                        // Widget size
-                        ui.label(format!("{:?}", config.size));
-                        
+
+                        // Trigger type
+                        ui.label(format!("{:?}", item.trigger));
+
+                        // Wallpaper name
+                        ui.label(&item.wallpaper.name);
+
                         // Edit button
-                        if ui.button("Edit").clicked() {
-                            self.editing_widget_id = Some(id.clone());
-                            self.new_widget = Some(config.clone());
+                        if ui.button(tr("edit")).clicked() {
+                            self.editing_schedule_index = Some(index);
+                            self.new_schedule_item = Some(item.clone());
                         }
-                        
+
                         // Delete button
-                        if ui.button("Delete").clicked() {
-                            if let Err(e) = self.widget_manager.remove_widget(id) {
-                                error!("Failed to remove widget: {}", e);
+                        if ui.button(tr("delete")).clicked() {
+                            if let Err(e) = self.scheduler.remove_schedule_item(index) {
+                                error!("Failed to remove schedule item: {}", e);
+                                self.notifications
+                                    .error(tr("error-remove-schedule-item"), &e);
                             }
+                            self.save_schedule();
                         }
                     });
                 }
             });
         }
-        
+
         ui.separator();
-        
-        // Add new widget
-        if ui.button("Add Widget").clicked() {
-            self.new_widget = Some(WidgetConfig {
-                widget_type: WidgetType::Clock,
-                position: WidgetPosition::TopRight,
-                size: WidgetSize::Medium,
-                settings: HashMap::new(),
+
+        // Add new schedule item
+        if ui.button(tr("add-schedule-item")).clicked() {
+            self.new_schedule_item = Some(ScheduleItem {
+                trigger: TriggerType::Time(NaiveTime::from_hms_opt(8, 0, 0).unwrap()),
+                wallpaper: crate::core::WallpaperInfo {
+                    name: "New Schedule".to_string(),
+                    description: "New schedule item".to_string(),
+                    author: "Aether-Desk".to_string(),
+                    version: "1.0.0".to_string(),
+                    r#type: WallpaperType::Static,
+                    path: None,
+                    url: None,
+                    monitor: None,
+                },
                 enabled: true,
-                background_color: None,
-                opacity: None,
             });
-            self.editing_widget_id = None;
+            self.editing_schedule_index = None;
         }
-        
-        // Edit widget
-        if let Some(config) = &mut self.new_widget {
+
+        // Import from another wallpaper tool
+        let mut import = None;
+        ui.horizontal(|ui| {
+            ui.label(tr("import-from"));
+            for tool in [
+                Tool::Komorebi,
+                Tool::Variety,
+                Tool::Folder,
+                Tool::WindowsTheme,
+            ] {
+                let label = match tool {
+                    Tool::Folder => tr("import-folder"),
+                    Tool::WindowsTheme => tr("import-windows-theme"),
+                    _ => tool.name().to_string(),
+                };
+                if ui.button(label).clicked() {
+                    import = Some(tool);
+                }
+            }
+        });
+        if let Some(tool) = import {
+            self.import_from(tool, ui.ctx());
+        }
+
+        // Edit schedule item
+        if let Some(item) = &mut self.new_schedule_item {
             ui.separator();
-            ui.heading(if self.editing_widget_id.is_some() {
-                "Edit Widget"
+            ui.heading(if self.editing_schedule_index.is_some() {
+                tr("edit-schedule-item")
             } else {
-                "Add Widget"
-            });
-            
-            // Widget type
-            ui.horizontal(|ui| {
-                ui.label("Widget Type:");
-                egui::ComboBox::from_label("")
-                    .selected_text(format!("{:?}", config.widget_type))
-                    .show_ui(ui, |ui| {
-                        ui.selectable_value(&mut config.widget_type, WidgetType::Clock, "Clock");
-                        ui.selectable_value(&mut config.widget_type, WidgetType::Weather, "Weather");
-                        ui.selectable_value(&mut config.widget_type, WidgetType::SystemMonitor, "System Monitor");
-                        ui.selectable_value(&mut config.widget_type, WidgetType::Calendar, "Calendar");
-                        ui.selectable_value(&mut config.widget_type, WidgetType::Notes, "Notes");
-                        ui.selectable_value(&mut config.widget_type, WidgetType::Custom("custom".to_string()), "Custom");
-                    });
-            });
-            
-            // Widget position
-            ui.horizontal(|ui| {
-                ui.label("Position:");
-                egui::ComboBox::from_label("")
-                    .selected_text(format!("{:?}", config.position))
-                    .show_ui(ui, |ui| {
-                        ui.selectable_value(&mut config.position, WidgetPosition::TopLeft, "Top Left");
-                        ui.selectable_value(&mut config.position, WidgetPosition::TopRight, "Top Right");
-                        ui.selectable_value(&mut config.position, WidgetPosition::BottomLeft, "Bottom Left");
-                        ui.selectable_value(&mut config.position, WidgetPosition::BottomRight, "Bottom Right");
-                        ui.selectable_value(&mut config.position, WidgetPosition::Custom(0, 0), "Custom");
-                    });
+                tr("add-schedule-item")
             });
-            
-            // Widget size
+
+            // Trigger type
             ui.horizontal(|ui| {
-                ui.label("Size:");
+                ui.label(tr("trigger-type"));
                 egui::ComboBox::from_label("")
-                    .selected_text(format!("{:?}", config.size))
+                    .selected_text(match &item.trigger {
+                        TriggerType::Time(_) => tr("trigger-time"),
+                        TriggerType::Interval(_) => tr("trigger-interval"),
+                        TriggerType::SystemEvent(_) => tr("trigger-system-event"),
+                        TriggerType::Custom(_) => tr("custom"),
+                        TriggerType::Calendar(_) => tr("trigger-calendar"),
+                    })
                     .show_ui(ui, |ui| {
-                        ui.selectable_value(&mut config.size, WidgetSize::Small, "Small");
-                        ui.selectable_value(&mut config.size, WidgetSize::Medium, "Medium");
-                        ui.selectable_value(&mut config.size, WidgetSize::Large, "Large");
-                        ui.selectable_value(&mut config.size, WidgetSize::Custom(100, 100), "Custom");
-                    });
-            });
-            
-            // Widget settings
-            ui.heading("Settings");
-            
-            match config.widget_type {
-                WidgetType::Clock => {
-                    ui.horizontal(|ui| {
-                        ui.label("Time Format:");
-                        let mut time_format = config.settings.get("time_format").unwrap_or(&"%H:%M:%S".to_string()).clone();
-                        if ui.text_edit_singleline(&mut time_format).changed() {
-                            config.settings.insert("time_format".to_string(), time_format);
+                        if ui
+                            .selectable_label(
+                                matches!(item.trigger, TriggerType::Time(_)),
+                                tr("trigger-time"),
+                            )
+                            .clicked()
+                        {
+                            item.trigger =
+                                TriggerType::Time(NaiveTime::from_hms_opt(8, 0, 0).unwrap());
                         }
-                    });
-                    
-                    ui.horizontal(|ui| {
-                        ui.label("Date Format:");
-                        let mut date_format = config.settings.get("date_format").unwrap_or(&"%Y-%m-%d".to_string()).clone();
-                        if ui.text_edit_singleline(&mut date_format).changed() {
-                            config.settings.insert("date_format".to_string(), date_format);
+                        if ui
+                            .selectable_label(
+                                matches!(item.trigger, TriggerType::Interval(_)),
+                                tr("trigger-interval"),
+                            )
+                            .clicked()
+                        {
+                            item.trigger = TriggerType::Interval(chrono::Duration::hours(1));
                         }
-                    });
-                },
-                WidgetType::Weather => {
-                    ui.horizontal(|ui| {
-                        ui.label("API Key:");
-                        let mut api_key = config.settings.get("api_key").unwrap_or(&"".to_string()).clone();
-                        if ui.text_edit_singleline(&mut api_key).changed() {
-                            config.settings.insert("api_key".to_string(), api_key);
+                        if ui
+                            .selectable_label(
+                                matches!(item.trigger, TriggerType::SystemEvent(_)),
+                                tr("trigger-system-event"),
+                            )
+                            .clicked()
+                        {
+                            item.trigger = TriggerType::SystemEvent("startup".to_string());
+                        }
+                        if ui
+                            .selectable_label(
+                                matches!(item.trigger, TriggerType::Custom(_)),
+                                tr("custom"),
+                            )
+                            .clicked()
+                        {
+                            item.trigger = TriggerType::Custom("custom".to_string());
+                        }
+                        if ui
+                            .selectable_label(
+                                matches!(item.trigger, TriggerType::Calendar(_)),
+                                tr("trigger-calendar"),
+                            )
+                            .clicked()
+                        {
+                            item.trigger = TriggerType::Calendar(String::new());
                         }
                     });
-                    
+            });
+
+            // Trigger details
+            match &mut item.trigger {
+                TriggerType::Time(time) => {
+                    ui.horizontal(|ui| {
+                        ui.label(tr("time"));
+                        let mut hour = time.hour() as u32;
+                        let mut minute = time.minute() as u32;
+
+                        if ui
+                            .add(egui::DragValue::new(&mut hour).speed(1).clamp_range(0..=23))
+                            .changed()
+                        {
+                            *time = NaiveTime::from_hms_opt(hour, minute, 0).unwrap();
+                        }
+
+                        ui.label(":");
+
+                        if ui
+                            .add(
+                                egui::DragValue::new(&mut minute)
+                                    .speed(1)
+                                    .clamp_range(0..=59),
+                            )
+                            .changed()
+                        {
+                            *time = NaiveTime::from_hms_opt(hour, minute, 0).unwrap();
+                        }
+                    });
+                }
+                TriggerType::Interval(interval) => {
+                    ui.horizontal(|ui| {
+                        ui.label(tr("interval"));
+                        let mut hours = interval.num_hours() as u32;
+                        let mut minutes = (interval.num_minutes() % 60) as u32;
+
+                        if ui.add(egui::DragValue::new(&mut hours).speed(1)).changed() {
+                            *interval = chrono::Duration::hours(hours as i64)
+                                + chrono::Duration::minutes(minutes as i64);
+                        }
+
+                        ui.label(tr("hours"));
+
+                        if ui
+                            .add(
+                                egui::DragValue::new(&mut minutes)
+                                    .speed(1)
+                                    .clamp_range(0..=59),
+                            )
+                            .changed()
+                        {
+                            *interval = chrono::Duration::hours(hours as i64)
+                                + chrono::Duration::minutes(minutes as i64);
+                        }
+
+                        ui.label(tr("minutes"));
+                    });
+                }
+                TriggerType::SystemEvent(event) => {
+                    ui.horizontal(|ui| {
+                        ui.label(tr("event"));
+                        ui.text_edit_singleline(event);
+                    });
+                }
+                TriggerType::Custom(trigger) => {
+                    ui.horizontal(|ui| {
+                        ui.label(tr("trigger"));
+                        ui.text_edit_singleline(trigger);
+                    });
+                }
+                TriggerType::Calendar(title) => {
+                    ui.horizontal(|ui| {
+                        ui.label(tr("trigger-calendar-title"));
+                        ui.add(
+                            egui::TextEdit::singleline(title).hint_text(tr("trigger-calendar-any")),
+                        );
+                    });
+                }
+            }
+
+            // Wallpaper type
+            ui.horizontal(|ui| {
+                ui.label(tr("wallpaper-type"));
+                egui::ComboBox::from_label("")
+                    .selected_text(wallpaper_type_label(&item.wallpaper.r#type))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(
+                            &mut item.wallpaper.r#type,
+                            WallpaperType::Static,
+                            tr("static"),
+                        );
+                        ui.selectable_value(
+                            &mut item.wallpaper.r#type,
+                            WallpaperType::AnimatedStatic,
+                            tr("animated-static"),
+                        );
+                        ui.selectable_value(
+                            &mut item.wallpaper.r#type,
+                            WallpaperType::AnimatedImage,
+                            tr("animated-image"),
+                        );
+                        ui.selectable_value(
+                            &mut item.wallpaper.r#type,
+                            WallpaperType::Video,
+                            tr("video"),
+                        );
+                        ui.selectable_value(
+                            &mut item.wallpaper.r#type,
+                            WallpaperType::Web,
+                            tr("web"),
+                        );
+                        ui.selectable_value(
+                            &mut item.wallpaper.r#type,
+                            WallpaperType::Shader,
+                            tr("shader"),
+                        );
+                        ui.selectable_value(
+                            &mut item.wallpaper.r#type,
+                            WallpaperType::Audio,
+                            tr("audio"),
+                        );
+                        ui.selectable_value(
+                            &mut item.wallpaper.r#type,
+                            WallpaperType::Particles,
+                            tr("particles"),
+                        );
+                        ui.selectable_value(
+                            &mut item.wallpaper.r#type,
+                            WallpaperType::Procedural,
+                            tr("procedural"),
+                        );
+                        ui.selectable_value(
+                            &mut item.wallpaper.r#type,
+                            WallpaperType::Slideshow,
+                            tr("slideshow"),
+                        );
+                        ui.selectable_value(
+                            &mut item.wallpaper.r#type,
+                            WallpaperType::NowPlaying,
+                            tr("now-playing"),
+                        );
+                    });
+            });
+
+            // Wallpaper selection based on type
+            match item.wallpaper.r#type {
+                WallpaperType::Static
+                | WallpaperType::AnimatedStatic
+                | WallpaperType::AnimatedImage
+                | WallpaperType::Video
+                | WallpaperType::Shader
+                | WallpaperType::Audio
+                | WallpaperType::Slideshow => {
+                    ui.horizontal(|ui| {
+                        ui.label(tr("wallpaper-path"));
+
+                        if let Some(path) = &item.wallpaper.path {
+                            ui.label(path.to_string_lossy());
+                        } else {
+                            ui.label(tr("no-file-selected"));
+                        }
+
+                        if ui.button(tr("browse")).clicked() {
+                            let file_dialog = match item.wallpaper.r#type {
+                                WallpaperType::Static | WallpaperType::AnimatedStatic => {
+                                    FileDialog::new().add_filter(
+                                        &tr("filter-images"),
+                                        WallpaperType::Static.extensions(),
+                                    )
+                                }
+                                WallpaperType::AnimatedImage => FileDialog::new().add_filter(
+                                    &tr("filter-animations"),
+                                    WallpaperType::AnimatedImage.extensions(),
+                                ),
+                                WallpaperType::Video => FileDialog::new().add_filter(
+                                    &tr("filter-videos"),
+                                    &["mp4", "webm", "avi", "mkv"],
+                                ),
+                                WallpaperType::Shader => FileDialog::new()
+                                    .add_filter(&tr("filter-shaders"), &["glsl", "frag", "vert"]),
+                                WallpaperType::Audio => FileDialog::new()
+                                    .add_filter(&tr("filter-shaders"), &["glsl", "frag", "vert"]),
+                                _ => FileDialog::new(),
+                            };
+
+                            let picked = if item.wallpaper.r#type == WallpaperType::Slideshow {
+                                file_dialog.pick_folder()
+                            } else {
+                                file_dialog.pick_file()
+                            };
+                            if let Some(path) = picked {
+                                item.wallpaper.path = Some(path);
+                            }
+                        }
+                    });
+                }
+                WallpaperType::Web => {
+                    ui.horizontal(|ui| {
+                        ui.label(tr("web-url"));
+                        let mut url = item.wallpaper.url.clone().unwrap_or_default();
+                        if ui.text_edit_singleline(&mut url).changed() {
+                            item.wallpaper.url = Some(url);
+                        }
+                    });
+                }
+                WallpaperType::Particles | WallpaperType::NowPlaying => {
+                    ui.label(tr("builtin-no-file"));
+                }
+                WallpaperType::Procedural => {
+                    ui.horizontal(|ui| {
+                        ui.label(tr("procedural-pattern"));
+                        let selected = item
+                            .wallpaper
+                            .url
+                            .as_deref()
+                            .and_then(ProceduralPattern::from_id);
+                        egui::ComboBox::from_id_source("schedule_procedural_pattern")
+                            .selected_text(selected.map_or_else(
+                                || tr("procedural-pick-pattern"),
+                                procedural_pattern_label,
+                            ))
+                            .show_ui(ui, |ui| {
+                                for pattern in ProceduralPattern::ALL {
+                                    if ui
+                                        .selectable_label(
+                                            selected == Some(pattern),
+                                            procedural_pattern_label(pattern),
+                                        )
+                                        .clicked()
+                                    {
+                                        item.wallpaper.url = Some(pattern.id().to_string());
+                                        item.wallpaper.name = pattern.name().to_string();
+                                    }
+                                }
+                            });
+                    });
+                }
+            }
+
+            // Wallpaper name
+            ui.horizontal(|ui| {
+                ui.label(tr("name"));
+                ui.text_edit_singleline(&mut item.wallpaper.name);
+            });
+
+            // Wallpaper description
+            ui.horizontal(|ui| {
+                ui.label(tr("description"));
+                ui.text_edit_singleline(&mut item.wallpaper.description);
+            });
+
+            // Enable/disable
+            ui.checkbox(&mut item.enabled, tr("enabled"));
+
+            // Save button
+            if ui.button(tr("save")).clicked() {
+                if let Some(index) = self.editing_schedule_index {
+                    if let Err(e) = self.scheduler.update_schedule_item(index, item.clone()) {
+                        error!("Failed to update schedule item: {}", e);
+                        self.notifications
+                            .error(tr("error-update-schedule-item"), &e);
+                    }
+                } else {
+                    if let Err(e) = self.scheduler.add_schedule_item(item.clone()) {
+                        error!("Failed to add schedule item: {}", e);
+                        self.notifications.error(tr("error-add-schedule-item"), &e);
+                    }
+                }
+
+                // Save schedule
+                self.save_schedule();
+
+                self.new_schedule_item = None;
+                self.editing_schedule_index = None;
+            }
+
+            // Cancel button
+            if ui.button(tr("cancel")).clicked() {
+                self.new_schedule_item = None;
+                self.editing_schedule_index = None;
+            }
+        }
+    }
+
+    /// Show widgets tab
+    fn show_widgets_tab(&mut self, ui: &mut egui::Ui) {
+        ui.heading(tr("widgets"));
+
+        // Widget list
+        let widget_configs = self.widget_manager.get_widget_configs();
+
+        if widget_configs.is_empty() {
+            ui.label(tr("no-widgets-installed"));
+        } else {
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for (id, config) in widget_configs.iter() {
+                    ui.horizontal(|ui| {
+                        // Enable/disable checkbox
+                        let mut enabled = config.enabled;
+                        if ui.checkbox(&mut enabled, "").changed() {
+                            let mut updated_config = config.clone();
+                            updated_config.enabled = enabled;
+                            if let Err(e) = self.widget_manager.update_widget(id, updated_config) {
+                                error!("Failed to update widget: {}", e);
+                                self.notifications.error(tr("error-update-widget"), &e);
+                            }
+                        }
+
+                        // Widget type
+                        ui.label(format!("{:?}", config.widget_type));
+
+                        // Widget position
+                        ui.label(format!("{:?}", config.position));
+
+                        // Widget size
+                        ui.label(format!("{:?}", config.size));
+
+                        // Edit button
+                        if ui.button(tr("edit")).clicked() {
+                            self.editing_widget_id = Some(id.clone());
+                            self.new_widget = Some(config.clone());
+                        }
+
+                        // Delete button
+                        if ui.button(tr("delete")).clicked() {
+                            if let Err(e) = self.widget_manager.remove_widget(id) {
+                                error!("Failed to remove widget: {}", e);
+                                self.notifications.error(tr("error-remove-widget"), &e);
+                            }
+                        }
+                    });
+                }
+            });
+        }
+
+        ui.separator();
+
+        // Add new widget
+        if ui.button(tr("add-widget")).clicked() {
+            self.new_widget = Some(WidgetConfig {
+                widget_type: WidgetType::Clock,
+                position: WidgetPosition::TopRight,
+                size: WidgetSize::Medium,
+                settings: HashMap::new(),
+                enabled: true,
+                background_color: None,
+                opacity: None,
+            });
+            self.editing_widget_id = None;
+        }
+
+        // Edit widget
+        if let Some(config) = &mut self.new_widget {
+            ui.separator();
+            ui.heading(if self.editing_widget_id.is_some() {
+                tr("edit-widget")
+            } else {
+                tr("add-widget")
+            });
+
+            // Widget type
+            ui.horizontal(|ui| {
+                ui.label(tr("widget-type"));
+                egui::ComboBox::from_label("")
+                    .selected_text(format!("{:?}", config.widget_type))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(
+                            &mut config.widget_type,
+                            WidgetType::Clock,
+                            tr("clock"),
+                        );
+                        ui.selectable_value(
+                            &mut config.widget_type,
+                            WidgetType::Weather,
+                            tr("weather"),
+                        );
+                        ui.selectable_value(
+                            &mut config.widget_type,
+                            WidgetType::SystemMonitor,
+                            tr("system-monitor"),
+                        );
+                        ui.selectable_value(
+                            &mut config.widget_type,
+                            WidgetType::Calendar,
+                            tr("calendar"),
+                        );
+                        ui.selectable_value(
+                            &mut config.widget_type,
+                            WidgetType::Notes,
+                            tr("notes"),
+                        );
+                        ui.selectable_value(
+                            &mut config.widget_type,
+                            WidgetType::Custom("custom".to_string()),
+                            tr("custom"),
+                        );
+                    });
+            });
+
+            // Widget position
+            ui.horizontal(|ui| {
+                ui.label(tr("position"));
+                egui::ComboBox::from_label("")
+                    .selected_text(format!("{:?}", config.position))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(
+                            &mut config.position,
+                            WidgetPosition::TopLeft,
+                            tr("top-left"),
+                        );
+                        ui.selectable_value(
+                            &mut config.position,
+                            WidgetPosition::TopRight,
+                            tr("top-right"),
+                        );
+                        ui.selectable_value(
+                            &mut config.position,
+                            WidgetPosition::BottomLeft,
+                            tr("bottom-left"),
+                        );
+                        ui.selectable_value(
+                            &mut config.position,
+                            WidgetPosition::BottomRight,
+                            tr("bottom-right"),
+                        );
+                        ui.selectable_value(
+                            &mut config.position,
+                            WidgetPosition::Custom(0, 0),
+                            tr("custom"),
+                        );
+                    });
+            });
+
+            // Widget size
+            ui.horizontal(|ui| {
+                ui.label(tr("size"));
+                egui::ComboBox::from_label("")
+                    .selected_text(format!("{:?}", config.size))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut config.size, WidgetSize::Small, tr("small"));
+                        ui.selectable_value(&mut config.size, WidgetSize::Medium, tr("medium"));
+                        ui.selectable_value(&mut config.size, WidgetSize::Large, tr("large"));
+                        ui.selectable_value(
+                            &mut config.size,
+                            WidgetSize::Custom(100, 100),
+                            tr("custom"),
+                        );
+                    });
+            });
+
+            // Widget settings
+            ui.heading(tr("settings"));
+
+            match config.widget_type {
+                WidgetType::Clock => {
+                    ui.horizontal(|ui| {
+                        ui.label(tr("time-format"));
+                        let mut time_format = config
+                            .settings
+                            .get("time_format")
+                            .unwrap_or(&"%H:%M:%S".to_string())
+                            .clone();
+                        if ui.text_edit_singleline(&mut time_format).changed() {
+                            config
+                                .settings
+                                .insert("time_format".to_string(), time_format);
+                        }
+                    });
+
+                    ui.horizontal(|ui| {
+                        ui.label(tr("date-format"));
+                        let mut date_format = config
+                            .settings
+                            .get("date_format")
+                            .unwrap_or(&"%Y-%m-%d".to_string())
+                            .clone();
+                        if ui.text_edit_singleline(&mut date_format).changed() {
+                            config
+                                .settings
+                                .insert("date_format".to_string(), date_format);
+                        }
+                    });
+                }
+                WidgetType::Weather => {
+                    ui.horizontal(|ui| {
+                        ui.label(tr("api-key"));
+                        let mut api_key = config
+                            .settings
+                            .get("api_key")
+                            .unwrap_or(&"".to_string())
+                            .clone();
+                        let api_key_edit = egui::TextEdit::singleline(&mut api_key)
+                            .password(true)
+                            .hint_text(tr("stored-securely"));
+                        if ui.add(api_key_edit).changed() {
+                            config.settings.insert("api_key".to_string(), api_key);
+                        }
+                    });
+
                     ui.horizontal(|ui| {
-                        ui.label("Location:");
-                        let mut location = config.settings.get("location").unwrap_or(&"".to_string()).clone();
+                        ui.label(tr("location"));
+                        let mut location = config
+                            .settings
+                            .get("location")
+                            .unwrap_or(&"".to_string())
+                            .clone();
                         if ui.text_edit_singleline(&mut location).changed() {
                             config.settings.insert("location".to_string(), location);
                         }
                     });
-                },
+
+                    ui.horizontal(|ui| {
+                        ui.label(tr("refresh-interval-minutes"));
+                        let mut refresh = config
+                            .settings
+                            .get("refresh_minutes")
+                            .unwrap_or(&"15".to_string())
+                            .clone();
+                        if ui.text_edit_singleline(&mut refresh).changed() {
+                            config
+                                .settings
+                                .insert("refresh_minutes".to_string(), refresh);
+                        }
+                    });
+                }
                 WidgetType::SystemMonitor => {
                     ui.horizontal(|ui| {
-                        ui.label("Update Interval (seconds):");
-                        let mut interval = config.settings.get("interval").unwrap_or(&"1".to_string()).clone();
+                        ui.label(tr("update-interval-seconds"));
+                        let mut interval = config
+                            .settings
+                            .get("interval")
+                            .unwrap_or(&"1".to_string())
+                            .clone();
                         if ui.text_edit_singleline(&mut interval).changed() {
                             config.settings.insert("interval".to_string(), interval);
                         }
                     });
-                },
+                }
                 WidgetType::Calendar => {
                     ui.horizontal(|ui| {
-                        ui.label("Show Week Numbers:");
-                        let show_week_numbers = config.settings.get("show_week_numbers").unwrap_or(&"false".to_string()).clone();
-                        if ui.checkbox(&mut (show_week_numbers == "true"), "").changed() {
-                            config.settings.insert("show_week_numbers".to_string(), show_week_numbers);
+                        ui.label(tr("show-week-numbers"));
+                        let show_week_numbers = config
+                            .settings
+                            .get("show_week_numbers")
+                            .unwrap_or(&"false".to_string())
+                            .clone();
+                        if ui
+                            .checkbox(&mut (show_week_numbers == "true"), "")
+                            .changed()
+                        {
+                            config
+                                .settings
+                                .insert("show_week_numbers".to_string(), show_week_numbers);
                         }
                     });
-                },
+                }
                 WidgetType::Notes => {
                     ui.horizontal(|ui| {
-                        ui.label("Notes Content:");
-                        let mut content = config.settings.get("content").unwrap_or(&"".to_string()).clone();
+                        ui.label(tr("notes-content"));
+                        let mut content = config
+                            .settings
+                            .get("content")
+                            .unwrap_or(&"".to_string())
+                            .clone();
                         if ui.text_edit_multiline(&mut content).changed() {
                             config.settings.insert("content".to_string(), content);
                         }
                     });
-                    
+
                     ui.horizontal(|ui| {
-                        ui.label("Font Size:");
-                        let mut font_size = config.settings.get("font_size").unwrap_or(&"14".to_string()).clone();
+                        ui.label(tr("font-size"));
+                        let mut font_size = config
+                            .settings
+                            .get("font_size")
+                            .unwrap_or(&"14".to_string())
+                            .clone();
                         if ui.text_edit_singleline(&mut font_size).changed() {
                             config.settings.insert("font_size".to_string(), font_size);
                         }
                     });
-                    
+
                     ui.horizontal(|ui| {
-                        ui.label("Background Color:");
-                        let mut bg_color = config.settings.get("bg_color").unwrap_or(&"#ffffff".to_string()).clone();
+                        ui.label(tr("background-color"));
+                        let mut bg_color = config
+                            .settings
+                            .get("bg_color")
+                            .unwrap_or(&"#ffffff".to_string())
+                            .clone();
                         if ui.text_edit_singleline(&mut bg_color).changed() {
                             config.settings.insert("bg_color".to_string(), bg_color);
                         }
                     });
-                },
+                }
                 WidgetType::Custom(_) => {
-                    ui.label("Custom widget settings are not supported in this version.");
-                },
+                    ui.label(tr("custom-widget-unsupported"));
+                }
             }
-            
+
             // Enable/disable
-            ui.checkbox(&mut config.enabled, "Enabled");
-            
+            ui.checkbox(&mut config.enabled, tr("enabled"));
+
             // Save button
-            if ui.button("Save").clicked() {
+            if ui.button(tr("save")).clicked() {
                 if let Some(id) = &self.editing_widget_id {
                     if let Err(e) = self.widget_manager.update_widget(id, config.clone()) {
                         error!("Failed to update widget: {}", e);
+                        self.notifications.error(tr("error-update-widget"), &e);
                     }
                 } else {
                     // Generate a unique ID for the new widget
                     let id = format!("widget_{}", chrono::Utc::now().timestamp_millis());
                     if let Err(e) = self.widget_manager.add_widget(id, config.clone()) {
                         error!("Failed to add widget: {}", e);
+                        self.notifications.error(tr("error-add-widget"), &e);
                     }
                 }
-                
+
                 // Save widgets
                 if let Err(e) = self.widget_manager.save_widgets(&self.config) {
                     error!("Failed to save widgets: {}", e);
+                    self.notifications.error(tr("error-save-widgets"), &e);
                 }
-                
+
                 self.new_widget = None;
                 self.editing_widget_id = None;
             }
-            
+
             // Cancel button
-            if ui.button("Cancel").clicked() {
+            if ui.button(tr("cancel")).clicked() {
                 self.new_widget = None;
                 self.editing_widget_id = None;
             }
         }
-        
+
         // Widget preview
         ui.separator();
-        ui.heading("Widget Preview");
-
+        ui.heading(tr("widget-preview"));
+        if let Some(detach) = self.show_detach_toggle(ui, self.config.app.detach_widget_preview) {
+            self.set_widget_preview_detached(detach);
+        }
+        if !self.config.app.detach_widget_preview {
+            self.show_widget_preview(ui);
+        }
+    }
+
+    /// Show the draggable widget layout preview
+    fn show_widget_preview(&mut self, ui: &mut egui::Ui) {
         let preview_size = egui::vec2(600.0, 400.0);
+        // Keep the widgets live while they are shown; they stop updating once they aren't drawn
+        ui.ctx().request_repaint_after(Duration::from_secs(1));
         let mut updated_positions = Vec::new();
-        let (bg_color, accent_color) = {
-            let theme_config = &self.config.app.theme;
-            match theme_config.theme {
-                Theme::Light => (
-                    egui::Color32::from_rgb(245, 245, 245),
-                    egui::Color32::from_rgb(33, 150, 243),
-                ),
-                Theme::Dark => (
-                    egui::Color32::from_rgb(32, 34, 37),
-                    egui::Color32::from_rgb(0, 188, 212),
-                ),
-                Theme::Custom => {
-                    let bg = theme_config.background_color.as_ref().and_then(|c| parse_hex_color(c)).unwrap_or(egui::Color32::from_rgb(32, 34, 37));
-                    let accent = theme_config.accent_color.as_ref().and_then(|c| parse_hex_color(c)).unwrap_or(egui::Color32::from_rgb(0, 188, 212));
-                    (bg, accent)
-                }
-            }
-        };
-        
+        let (bg_color, accent_color) = self.theme_colors();
+
         egui::Frame::none().fill(bg_color).show(ui, |ui| {
             ui.set_min_size(preview_size);
             let _response = ui.allocate_rect(ui.max_rect(), egui::Sense::hover());
@@ -858,7 +2935,10 @@ impl AetherDeskApp {
                     .current_pos(egui::pos2(x, y))
                     .show(ui.ctx(), |ui| {
                         let before = ui.min_rect().left_top();
-                        if let Err(e) = self.widget_manager.render_widgets(ui, bg_color, accent_color) {
+                        if let Err(e) =
+                            self.widget_manager
+                                .render_widgets(ui, bg_color, accent_color)
+                        {
                             error!("Failed to render widgets: {}", e);
                         }
                         let after = ui.min_rect().left_top();
@@ -866,7 +2946,10 @@ impl AetherDeskApp {
                             // Widget was moved
                             let new_x = after.x;
                             let new_y = after.y;
-                            updated_positions.push((id.clone(), WidgetPosition::Custom(new_x as i32, new_y as i32)));
+                            updated_positions.push((
+                                id.clone(),
+                                WidgetPosition::Custom(new_x as i32, new_y as i32),
+                            ));
                         }
                     });
             }
@@ -877,28 +2960,42 @@ impl AetherDeskApp {
                 config.position = pos.clone();
                 if let Err(e) = self.widget_manager.update_widget(&id, config.clone()) {
                     error!("Failed to update widget position: {}", e);
+                    self.notifications
+                        .error(tr("error-update-widget-position"), &e);
                 }
                 if let Err(e) = self.widget_manager.save_widgets(&self.config) {
                     error!("Failed to save widgets: {}", e);
+                    self.notifications.error(tr("error-save-widgets"), &e);
                 }
             }
         }
     }
-    
+
     /// Show plugins tab
     fn show_plugins_tab(&mut self, ui: &mut egui::Ui) {
-        ui.heading("Plugins");
-        
+        ui.heading(tr("plugins"));
+
         if self.plugin_manager.get_plugins().is_empty() {
-            ui.label("No plugins installed. Plugins will be available in a future release.");
+            ui.label(tr("no-plugins-installed"));
             return;
         }
-        
+
         // Plugin list
         egui::ScrollArea::vertical().show(ui, |ui| {
             // Collect plugin info to avoid borrowing conflicts
-            let plugin_info: Vec<(String, String, String, String, Option<String>, Option<String>, bool)> = 
-                self.plugin_manager.get_plugins().iter().map(|(name, plugin)| {
+            let plugin_info: Vec<(
+                String,
+                String,
+                String,
+                String,
+                Option<String>,
+                Option<String>,
+                bool,
+            )> = self
+                .plugin_manager
+                .get_plugins()
+                .iter()
+                .map(|(name, plugin)| {
                     let metadata = plugin.metadata();
                     let config = plugin.get_settings();
                     (
@@ -908,256 +3005,3587 @@ impl AetherDeskApp {
                         metadata.description.clone(),
                         metadata.homepage.clone(),
                         metadata.license.clone(),
-                        config.enabled
+                        config.enabled,
                     )
-                }).collect();
-            
-            for (name, version, author, description, homepage, license, mut enabled) in plugin_info {
+                })
+                .collect();
+
+            for (name, version, author, description, homepage, license, mut enabled) in plugin_info
+            {
                 ui.collapsing(format!("{} v{}", name, version), |ui| {
-                    ui.label(format!("Author: {}", author));
-                    ui.label(format!("Description: {}", description));
-                    
+                    ui.label(tr_args("plugin-author", &[("author", &author)]));
+                    ui.label(tr_args(
+                        "plugin-description",
+                        &[("description", &description)],
+                    ));
+
                     if let Some(homepage) = &homepage {
-                        ui.hyperlink_to("Homepage", homepage);
+                        ui.hyperlink_to(tr("homepage"), homepage);
                     }
-                    
+
                     if let Some(license) = &license {
-                        ui.label(format!("License: {}", license));
+                        ui.label(tr_args("plugin-license", &[("license", license.as_str())]));
                     }
-                    
+
                     ui.separator();
-                    
+
                     // Plugin settings
-                    ui.heading("Settings");
-                    
-                    if ui.checkbox(&mut enabled, "Enabled").changed() {
+                    ui.heading(tr("settings"));
+
+                    if ui.checkbox(&mut enabled, tr("enabled")).changed() {
                         if enabled {
                             if let Err(e) = self.plugin_manager.enable_plugin(&name) {
                                 error!("Failed to enable plugin: {}", e);
+                                self.notifications.error(tr("error-enable-plugin"), &e);
                             }
                         } else {
                             if let Err(e) = self.plugin_manager.disable_plugin(&name) {
                                 error!("Failed to disable plugin: {}", e);
+                                self.notifications.error(tr("error-disable-plugin"), &e);
                             }
                         }
                     }
-                    
+
                     // TODO: Add more plugin settings
                 });
             }
         });
     }
-    
+
+    /// Show logs tab
+    fn show_logs_tab(&mut self, ui: &mut egui::Ui) {
+        ui.heading(tr("logs"));
+        if self.log_viewer.show(ui, &mut self.config.app.log_level) {
+            logging::set_level(self.config.app.log_level);
+            self.save_config_and_reload();
+        }
+    }
+
+    /// Show status tab
+    fn show_status_tab(&mut self, ui: &mut egui::Ui) {
+        ui.heading(tr("status"));
+
+        self.refresh_daemon_status(ui.ctx());
+        let Some(status) = self.daemon_status.clone() else {
+            ui.label(tr("not-running"));
+            if ui.button(tr("start-service")).clicked() {
+                if let Err(e) = self.daemon.ensure_running() {
+                    error!("Failed to start daemon: {}", e);
+                    self.notifications.error(tr("error-start-daemon"), &e);
+                }
+                self.last_status_refresh = None;
+            }
+            return;
+        };
+
+        // Keep process and event figures current while the tab is open
+        ui.ctx().request_repaint_after(Duration::from_secs(2));
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            ui.label(tr_args(
+                "daemon-running",
+                &[
+                    ("pid", &status.pid.to_string()),
+                    ("minutes", &(status.uptime_secs / 60).to_string()),
+                ],
+            ));
+            ui.label(tr_args("status-backend", &[("backend", &status.backend)]));
+
+            ui.separator();
+            ui.heading(tr("status-monitors"));
+            let wallpaper = status.current_wallpaper.as_ref().map_or_else(
+                || tr("no-wallpaper-running"),
+                |wallpaper| wallpaper.name.clone(),
+            );
+            if status.saving_power {
+                ui.colored_label(egui::Color32::YELLOW, tr("battery-saver-active"));
+            } else if status.held {
+                ui.colored_label(egui::Color32::YELLOW, tr("wallpaper-held"));
+            } else if status.user_away {
+                ui.colored_label(egui::Color32::YELLOW, tr("wallpaper-idle-paused"));
+            } else if status.auto_paused {
+                ui.colored_label(egui::Color32::YELLOW, tr("wallpaper-auto-paused"));
+            } else if status.focused {
+                ui.colored_label(egui::Color32::YELLOW, tr("wallpaper-focus-mode"));
+            } else if status.quality_reduced {
+                ui.colored_label(egui::Color32::YELLOW, tr("wallpaper-quality-reduced"));
+            }
+            if let Some(effect) = status.weather_effect {
+                ui.label(tr_args(
+                    "wallpaper-weather-effect",
+                    &[("effect", &weather_effect_label(effect))],
+                ));
+            }
+            if self.monitors.is_empty() {
+                ui.label(tr_args("current-wallpaper", &[("name", &wallpaper)]));
+            } else {
+                egui::Grid::new("status_monitors")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for monitor in &self.monitors {
+                            ui.label(&monitor.name);
+                            ui.label(format!("{}x{}", monitor.width, monitor.height));
+                            ui.label(&wallpaper);
+                            ui.end_row();
+                        }
+                    });
+            }
+            if let Some(current) = &status.current {
+                let since = current
+                    .started_at
+                    .with_timezone(&Local)
+                    .format("%H:%M")
+                    .to_string();
+                match current.pid {
+                    Some(pid) => ui.label(tr_args(
+                        "wallpaper-since-player",
+                        &[("time", &since), ("pid", &pid.to_string())],
+                    )),
+                    None => ui.label(tr_args("wallpaper-since", &[("time", &since)])),
+                };
+            }
+
+            ui.separator();
+            ui.heading(tr("status-processes"));
+            if status.processes.is_empty() {
+                ui.label(tr("status-no-processes"));
+            } else {
+                egui::Grid::new("status_processes")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for process in &status.processes {
+                            ui.label(format!("{} ({})", process.name, process.role));
+                            ui.label(tr_args("status-pid", &[("pid", &process.pid.to_string())]));
+                            ui.label(format!(
+                                "{:.1} MB",
+                                process.memory_bytes as f64 / (1024.0 * 1024.0)
+                            ));
+                            ui.label(format!("{:.1}% CPU", process.cpu_usage));
+                            if process.paused {
+                                ui.colored_label(egui::Color32::YELLOW, tr("process-paused"));
+                            } else {
+                                ui.label("");
+                            }
+                            ui.end_row();
+                        }
+                    });
+            }
+
+            let memory_used: u64 = status
+                .processes
+                .iter()
+                .map(|process| process.memory_bytes)
+                .sum();
+            let cpu_usage: f32 = status
+                .processes
+                .iter()
+                .map(|process| process.cpu_usage)
+                .sum();
+            ui.label(tr_args(
+                "memory-used",
+                &[(
+                    "mb",
+                    &format!("{:.2}", memory_used as f64 / (1024.0 * 1024.0)),
+                )],
+            ));
+            ui.label(tr_args(
+                "cpu-usage",
+                &[("percent", &format!("{:.1}", cpu_usage))],
+            ));
+            ui.label(tr_args(
+                "active-processes",
+                &[("count", &status.processes.len().to_string())],
+            ));
+
+            ui.separator();
+            ui.heading(tr("wallpaper-scheduler"));
+            if status.scheduler_running {
+                ui.label(tr_args(
+                    "status-scheduler-running",
+                    &[
+                        ("enabled", &status.enabled_schedule_items.to_string()),
+                        ("total", &status.schedule_items.to_string()),
+                    ],
+                ));
+            } else {
+                ui.label(tr("status-scheduler-stopped"));
+            }
+            if let Some(last_run) = &status.last_download {
+                ui.label(tr_args(
+                    "last-run",
+                    &[("time", &format_timestamp(last_run))],
+                ));
+            }
+            if let Some(last_update) = &status.last_live_earth_update {
+                ui.label(tr_args(
+                    "last-update",
+                    &[("time", &format_timestamp(last_update))],
+                ));
+            }
+
+            ui.separator();
+            ui.heading(tr("status-recent-events"));
+            if status.recent_events.is_empty() {
+                ui.label(tr("status-no-events"));
+            }
+            for event in status.recent_events.iter().rev() {
+                ui.horizontal_wrapped(|ui| {
+                    ui.label(
+                        egui::RichText::new(notifications::level_label(event.level))
+                            .color(notifications::level_color(event.level))
+                            .strong(),
+                    );
+                    ui.label(format_timestamp(&event.time));
+                    ui.label(format!("[{}]", event.source));
+                    ui.label(&event.message);
+                })
+                .response
+                .on_hover_text(event.details.clone().unwrap_or_default());
+            }
+        });
+    }
+
     /// Show settings tab
     fn show_settings_tab(&mut self, ui: &mut egui::Ui) {
-        ui.heading("Settings");
+        ui.heading(tr("settings"));
 
         // General settings
-        ui.collapsing("General", |ui| {
-            // TODO: Add general settings
-            ui.label("General settings will be available in a future release.");
+        ui.collapsing(tr("general"), |ui| {
+            let mut start_with_system = self.config.app.start_with_system;
+            if ui
+                .checkbox(&mut start_with_system, tr("start-with-system"))
+                .changed()
+            {
+                match autostart::set_enabled(start_with_system) {
+                    Ok(()) => {
+                        self.config.app.start_with_system = start_with_system;
+                        if let Err(e) = self.config.save() {
+                            error!("Failed to save config: {}", e);
+                            self.notifications.error(tr("error-save-config"), &e);
+                        }
+                    }
+                    Err(e) => {
+                        error!("Failed to update start with system: {}", e);
+                        self.notifications.error(tr("error-update-autostart"), &e);
+                    }
+                }
+            }
+            ui.label(tr("start-with-system-hint"));
+
+            ui.horizontal(|ui| {
+                ui.label(tr("language"));
+                let mut language = self.config.app.language.clone();
+                let selected = match &language {
+                    Some(code) => i18n::LOCALES
+                        .iter()
+                        .find(|locale| locale.code == code)
+                        .map_or_else(|| code.clone(), |locale| locale.name.to_string()),
+                    None => tr("system-default"),
+                };
+                egui::ComboBox::from_id_source("language")
+                    .selected_text(selected)
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut language, None, tr("system-default"));
+                        for locale in i18n::LOCALES {
+                            ui.selectable_value(
+                                &mut language,
+                                Some(locale.code.to_string()),
+                                locale.name,
+                            );
+                        }
+                    });
+
+                if language != self.config.app.language {
+                    i18n::set_language(language.as_deref());
+                    self.config.app.language = language;
+                    if let Err(e) = self.config.save() {
+                        error!("Failed to save config: {}", e);
+                        self.notifications.error(tr("error-save-config"), &e);
+                    }
+                }
+            });
+
+            if ui
+                .checkbox(
+                    &mut self.config.app.desktop_notifications,
+                    tr("desktop-notifications"),
+                )
+                .changed()
+            {
+                self.notifications
+                    .set_desktop_notifications(self.config.app.desktop_notifications);
+                if let Err(e) = self.config.save() {
+                    error!("Failed to save config: {}", e);
+                    self.notifications.error(tr("error-save-config"), &e);
+                }
+            }
+        });
+
+        // Profile settings
+        ui.collapsing(tr("profiles"), |ui| {
+            self.show_profile_settings(ui);
         });
 
         // Wallpaper settings
-        ui.collapsing("Wallpaper", |ui| {
-            // TODO: Add wallpaper settings
-            ui.label("Wallpaper settings will be available in a future release.");
+        ui.collapsing(tr("wallpaper"), |ui| {
+            self.show_auto_change_settings(ui);
+            ui.separator();
+            self.show_auto_pause_settings(ui);
+            ui.separator();
+            self.show_focus_mode_settings(ui);
+            ui.separator();
+            self.show_adaptive_quality_settings(ui);
+            ui.separator();
+            self.show_frame_rate_settings(ui);
+            ui.separator();
+            self.show_gpu_settings(ui);
+            ui.separator();
+            self.show_watchdog_settings(ui);
+            ui.separator();
+            self.show_change_hooks_settings(ui);
+            ui.separator();
+            self.show_palette_settings(ui);
+            ui.separator();
+            self.show_color_temperature_settings(ui);
+            ui.separator();
+            self.show_weather_effects_settings(ui);
+            ui.separator();
+            self.show_audio_overlay_settings(ui);
+            ui.separator();
+            self.show_virtual_camera_settings(ui);
+            ui.separator();
+            self.show_tagging_settings(ui);
+        });
+
+        // Online source settings
+        ui.collapsing(tr("online-sources"), |ui| {
+            self.show_sources_settings(ui);
+        });
+
+        // Calendar subscription settings
+        ui.collapsing(tr("calendar"), |ui| {
+            self.show_calendar_settings(ui);
+        });
+
+        // Plugin settings
+        ui.collapsing(tr("plugins"), |ui| {
+            // TODO: Add plugin settings
+            ui.label(tr("plugin-settings-coming-soon"));
+        });
+
+        // Resource monitoring
+        ui.collapsing(tr("resource-monitoring"), |ui| {
+            self.show_resource_settings(ui);
+        });
+
+        // Background service settings
+        ui.collapsing(tr("background-service"), |ui| {
+            self.refresh_daemon_status(ui.ctx());
+
+            match &self.daemon_status {
+                Some(status) => {
+                    ui.label(tr_args(
+                        "daemon-running",
+                        &[
+                            ("pid", &status.pid.to_string()),
+                            ("minutes", &(status.uptime_secs / 60).to_string()),
+                        ],
+                    ));
+                    match &status.current_wallpaper {
+                        Some(wallpaper) => {
+                            ui.label(tr_args("current-wallpaper", &[("name", &wallpaper.name)]))
+                        }
+                        None => ui.label(tr("no-wallpaper-running")),
+                    };
+                    ui.label(tr("daemon-hint"));
+
+                    if ui.button(tr("stop-service")).clicked() {
+                        if let Err(e) = self.daemon.send(&Request::Shutdown) {
+                            error!("Failed to stop daemon: {}", e);
+                            self.notifications.error(tr("error-stop-daemon"), &e);
+                        }
+                        self.last_status_refresh = None;
+                    }
+                }
+                None => {
+                    ui.label(tr("not-running"));
+                    if ui.button(tr("start-service")).clicked() {
+                        if let Err(e) = self.daemon.ensure_running() {
+                            error!("Failed to start daemon: {}", e);
+                            self.notifications.error(tr("error-start-daemon"), &e);
+                        }
+                        self.last_status_refresh = None;
+                    }
+                }
+            }
+        });
+
+        // Local API settings
+        ui.collapsing(tr("local-api"), |ui| {
+            self.show_api_settings(ui);
+            ui.separator();
+            self.show_browser_extension_settings(ui);
+        });
+
+        // LAN sync settings
+        ui.collapsing(tr("lan-sync"), |ui| {
+            self.show_sync_settings(ui);
+        });
+
+        // Chat bot settings
+        ui.collapsing(tr("chat-bot"), |ui| {
+            self.show_bot_settings(ui);
+        });
+
+        // Security settings
+        ui.collapsing(tr("security"), |ui| {
+            let backend = match self.secret_store.backend() {
+                SecretBackend::Keyring => tr("secret-backend-keyring"),
+                SecretBackend::EncryptedFile => tr("secret-backend-encrypted-file"),
+            };
+            ui.label(tr_args("api-keys-stored-in", &[("backend", &backend)]));
+        });
+
+        // Configuration history
+        ui.collapsing(tr("config-history"), |ui| {
+            self.show_config_history(ui);
+        });
+
+        // Diagnostics
+        ui.collapsing(tr("diagnostics"), |ui| {
+            self.show_diagnostics(ui);
+        });
+
+        // Theme settings
+        ui.collapsing(tr("theme"), |ui| {
+            let mut selected_theme = self.config.app.theme.theme.clone();
+
+            ui.horizontal(|ui| {
+                ui.label(tr("theme-label"));
+                egui::ComboBox::from_label("")
+                    .selected_text(match selected_theme {
+                        Theme::Light => tr("light"),
+                        Theme::Dark => tr("dark"),
+                        Theme::Custom => tr("custom"),
+                        Theme::System => tr("system-theme"),
+                    })
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut selected_theme, Theme::System, tr("system-theme"));
+                        ui.selectable_value(&mut selected_theme, Theme::Light, tr("light"));
+                        ui.selectable_value(&mut selected_theme, Theme::Dark, tr("dark"));
+                        ui.selectable_value(&mut selected_theme, Theme::Custom, tr("custom"));
+                    });
+            });
+
+            if selected_theme != self.config.app.theme.theme {
+                let result = if selected_theme == Theme::System {
+                    self.appearance.start()
+                } else {
+                    self.appearance.stop()
+                };
+                if let Err(e) = result {
+                    error!("Failed to update system theme watcher: {}", e);
+                }
+
+                self.config.app.theme.theme = selected_theme.clone();
+                if let Err(e) = self.config.save() {
+                    error!("Failed to save config: {}", e);
+                    self.notifications.error(tr("error-save-config"), &e);
+                }
+            }
+
+            if selected_theme == Theme::Custom {
+                self.theme_editor
+                    .show(ui, &mut self.config, &mut self.notifications);
+            }
+        });
+    }
+
+    /// Show the environment checks and their fixes
+    fn show_diagnostics(&mut self, ui: &mut egui::Ui) {
+        ui.label(tr("diagnostics-hint"));
+        if ui.button(tr("run-diagnostics")).clicked() {
+            self.diagnostics = Some(doctor::run());
+        }
+
+        let Some(checks) = &self.diagnostics else {
+            return;
+        };
+
+        for category in CheckCategory::ALL {
+            let group: Vec<&Check> = checks
+                .iter()
+                .filter(|check| check.category == category)
+                .collect();
+            if group.is_empty() {
+                continue;
+            }
+
+            ui.strong(diagnostics_category_label(category));
+            for check in group {
+                let (color, status) = match check.status {
+                    CheckStatus::Ok => (egui::Color32::GREEN, tr("check-ok")),
+                    CheckStatus::Warning => (egui::Color32::YELLOW, tr("check-warning")),
+                    CheckStatus::Error => (egui::Color32::RED, tr("check-error")),
+                };
+                ui.horizontal_wrapped(|ui| {
+                    ui.colored_label(color, status);
+                    ui.label(format!("{}: {}", check.name, check.detail));
+                });
+                if let Some(fix) = &check.fix {
+                    ui.label(egui::RichText::new(tr_args("check-fix", &[("fix", fix)])).weak());
+                }
+            }
+        }
+    }
+
+    /// Show the profiles with switch, update and delete buttons
+    fn show_profile_settings(&mut self, ui: &mut egui::Ui) {
+        ui.label(tr("profiles-hint"));
+
+        let active = self.config.app.active_profile.clone();
+        let mut switch_to = None;
+        let mut save_as = None;
+        let mut deleted = None;
+        let mut bound = None;
+        for name in &self.profile_names {
+            ui.horizontal(|ui| {
+                if active.as_deref() == Some(name.as_str()) {
+                    ui.strong(tr_args("profile-active", &[("name", name)]));
+                } else {
+                    ui.label(name);
+                    if ui.button(tr("switch-profile")).clicked() {
+                        switch_to = Some(name.clone());
+                    }
+                }
+                if ui
+                    .button(tr("update-profile"))
+                    .on_hover_text(tr("update-profile-hint"))
+                    .clicked()
+                {
+                    save_as = Some(name.clone());
+                }
+                if ui
+                    .button(tr("bind-monitors"))
+                    .on_hover_text(tr("bind-monitors-hint"))
+                    .clicked()
+                {
+                    bound = Some(name.clone());
+                }
+                if ui.button(tr("delete")).clicked() {
+                    deleted = Some(name.clone());
+                }
+            });
+        }
+        if self.profile_names.is_empty() {
+            ui.label(tr("no-profiles"));
+        }
+
+        // Profiles switched to by the connected monitors
+        let mut unbound = None;
+        if !self.config.app.topology_profiles.is_empty() {
+            ui.label(tr("monitor-profiles"));
+            for (topology, name) in &self.config.app.topology_profiles {
+                ui.horizontal(|ui| {
+                    ui.label(tr_args(
+                        "monitor-profile",
+                        &[("monitors", topology), ("name", name)],
+                    ));
+                    if ui.small_button(tr("delete")).clicked() {
+                        unbound = Some(topology.clone());
+                    }
+                });
+            }
+        }
+
+        ui.horizontal(|ui| {
+            ui.label(tr("name"));
+            ui.text_edit_singleline(&mut self.new_profile_name);
+            let name = self.new_profile_name.trim().to_string();
+            if ui
+                .add_enabled(
+                    profile::validate_name(&name).is_ok(),
+                    egui::Button::new(tr("save-as-profile")),
+                )
+                .clicked()
+            {
+                save_as = Some(name);
+                self.new_profile_name.clear();
+            }
+        });
+
+        if let Some(name) = save_as {
+            self.save_profile(&name);
+        }
+        if let Some(name) = bound {
+            match topology::current_topology() {
+                Some(topology) => {
+                    self.config.app.topology_profiles.insert(topology, name);
+                    self.save_config_and_reload();
+                }
+                None => self.notifications.warning(tr("error-no-monitors"), None),
+            }
+        }
+        if let Some(topology) = unbound {
+            self.config.app.topology_profiles.remove(&topology);
+            self.save_config_and_reload();
+        }
+        if let Some(name) = switch_to {
+            if let Err(e) = self.daemon.send(&Request::SwitchProfile { name }) {
+                error!("Failed to switch profile: {}", e);
+                self.notifications.error(tr("error-switch-profile"), &e);
+            }
+            self.reload_profile();
+        }
+        if let Some(name) = deleted {
+            let store = ProfileStore::open(&self.config);
+            if let Err(e) = store.delete(&name, &mut self.config) {
+                error!("Failed to delete profile: {}", e);
+                self.notifications.error(tr("error-delete-profile"), &e);
+            }
+            self.save_config_and_reload();
+            self.refresh_profiles();
+        }
+    }
+
+    /// Save the current settings as a profile and make it the active one
+    fn save_profile(&mut self, name: &str) {
+        if let Err(e) = ProfileStore::open(&self.config).save(name, &self.config) {
+            error!("Failed to save profile: {}", e);
+            self.notifications.error(tr("error-save-profile"), &e);
+            return;
+        }
+        self.config.app.active_profile = Some(name.to_string());
+        self.save_config_and_reload();
+        self.refresh_profiles();
+    }
+
+    /// Save the configuration and have the daemon reload it
+    fn save_config_and_reload(&mut self) {
+        if let Err(e) = self.config.save() {
+            error!("Failed to save config: {}", e);
+            self.notifications.error(tr("error-save-config"), &e);
+        } else if let Err(e) = self.daemon.send(&Request::ReloadConfig) {
+            error!("Failed to reload config in daemon: {}", e);
+            self.notifications.error(tr("error-reload-config"), &e);
+        }
+    }
+
+    /// Re-read the profile list
+    fn refresh_profiles(&mut self) {
+        match ProfileStore::open(&self.config).list() {
+            Ok(names) => self.profile_names = names,
+            Err(e) => error!("Failed to list profiles: {}", e),
+        }
+    }
+
+    /// Pick up a profile switch made here or from the CLI, API or another frontend
+    fn reload_profile(&mut self) {
+        match Config::load() {
+            Ok(config) => self.config = config,
+            Err(e) => {
+                error!("Failed to load config: {}", e);
+                self.notifications.error(tr("error-load-configuration"), &e);
+                return;
+            }
+        }
+        if let Err(e) = self.scheduler.load_schedule(&self.config) {
+            error!("Failed to load schedule: {}", e);
+            self.notifications.error(tr("error-load-schedule"), &e);
+        }
+        if let Err(e) = self.widget_manager.load_widgets(&self.config) {
+            error!("Failed to load widgets: {}", e);
+            self.notifications.error(tr("error-load-widgets"), &e);
+        }
+        self.widget_manager
+            .set_power_policy(self.config.app.auto_pause.clone());
+        self.refresh_profiles();
+    }
+
+    /// Show the commands run after every wallpaper change
+    fn show_change_hooks_settings(&mut self, ui: &mut egui::Ui) {
+        let mut hooks = self.config.wallpaper.change_hooks.clone();
+
+        ui.heading(tr("change-hooks"));
+        ui.label(tr("change-hooks-hint"));
+
+        let mut removed = None;
+        for (index, command) in hooks.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(command)
+                        .code_editor()
+                        .desired_width(360.0),
+                );
+                if ui.button(tr("delete")).clicked() {
+                    removed = Some(index);
+                }
+            });
+        }
+        if let Some(index) = removed {
+            hooks.remove(index);
+        }
+
+        if ui.button(tr("add-change-hook")).clicked() {
+            hooks.push(String::new());
+        }
+
+        if hooks != self.config.wallpaper.change_hooks {
+            self.config.wallpaper.change_hooks = hooks;
+            if let Err(e) = self.config.save() {
+                error!("Failed to save config: {}", e);
+                self.notifications.error(tr("error-save-config"), &e);
+            } else if let Err(e) = self.daemon.send(&Request::ReloadConfig) {
+                error!("Failed to reload config in daemon: {}", e);
+                self.notifications.error(tr("error-reload-config"), &e);
+            }
+        }
+    }
+
+    /// Show the color palette export settings
+    fn show_palette_settings(&mut self, ui: &mut egui::Ui) {
+        let mut palette = self.config.wallpaper.palette.clone();
+
+        ui.heading(tr("color-palette"));
+        ui.checkbox(&mut palette.enabled, tr("export-palette"));
+        ui.label(tr("export-palette-hint"));
+
+        ui.horizontal(|ui| {
+            ui.label(tr("palette-folder"));
+            ui.monospace(palette.output_dir().display().to_string());
+            if ui.button(tr("browse")).clicked() {
+                if let Some(folder) = FileDialog::new().pick_folder() {
+                    palette.output_dir = Some(folder.to_string_lossy().to_string());
+                }
+            }
+            if palette.output_dir.is_some() && ui.button(tr("use-pywal-folder")).clicked() {
+                palette.output_dir = None;
+            }
         });
 
-        // Plugin settings
-        ui.collapsing("Plugins", |ui| {
-            // TODO: Add plugin settings
-            ui.label("Plugin settings will be available in a future release.");
-        });
+        ui.horizontal(|ui| {
+            ui.label(tr("theming-tool"));
+            egui::ComboBox::from_id_source("theming_tool")
+                .selected_text(theming_tool_label(palette.theming))
+                .show_ui(ui, |ui| {
+                    for tool in [
+                        ThemingTool::None,
+                        ThemingTool::Templates,
+                        ThemingTool::Pywal,
+                        ThemingTool::Wallust,
+                    ] {
+                        ui.selectable_value(&mut palette.theming, tool, theming_tool_label(tool));
+                    }
+                });
+        })
+        .response
+        .on_hover_text(tr("theming-tool-hint"));
+
+        if palette.theming == ThemingTool::Templates {
+            ui.horizontal(|ui| {
+                ui.label(tr("theming-templates"));
+                ui.monospace(palette.templates_dir().display().to_string());
+                if ui.button(tr("browse")).clicked() {
+                    if let Some(folder) = FileDialog::new().pick_folder() {
+                        palette.templates_dir = Some(folder.to_string_lossy().to_string());
+                    }
+                }
+                if palette.templates_dir.is_some() && ui.button(tr("use-pywal-folder")).clicked() {
+                    palette.templates_dir = None;
+                }
+            });
+        }
+
+        // Worked out for the last static wallpaper, without writing or running anything
+        let last_static = self.config.wallpaper.last_static.clone();
+        ui.add_enabled_ui(last_static.is_some(), |ui| {
+            if ui
+                .button(tr("theming-dry-run"))
+                .on_hover_text(tr("theming-dry-run-hint"))
+                .clicked()
+            {
+                if let Some(path) = &last_static {
+                    self.theming_preview =
+                        Some(theming::plan(Path::new(path), &palette).map_err(|e| e.to_string()));
+                }
+            }
+        });
+        let mut close_preview = false;
+        match &self.theming_preview {
+            Some(Ok(plan)) => {
+                ui.horizontal_wrapped(|ui| {
+                    for color in plan.colors {
+                        let (rect, _) =
+                            ui.allocate_exact_size(egui::vec2(18.0, 18.0), egui::Sense::hover());
+                        ui.painter().rect_filled(
+                            rect,
+                            2.0,
+                            egui::Color32::from_rgb(color[0], color[1], color[2]),
+                        );
+                    }
+                });
+                if plan.files.is_empty() {
+                    ui.label(tr("theming-no-files"));
+                }
+                for (path, contents) in &plan.files {
+                    egui::CollapsingHeader::new(tr_args(
+                        "theming-writes",
+                        &[("path", &path.display().to_string())],
+                    ))
+                    .id_source(path)
+                    .show(ui, |ui| {
+                        ui.monospace(contents);
+                    });
+                }
+                if let Some(command) = &plan.command {
+                    ui.label(tr_args("theming-runs", &[("command", &command.join(" "))]));
+                }
+                close_preview = ui.button(tr("close-preview")).clicked();
+            }
+            Some(Err(e)) => {
+                ui.colored_label(
+                    egui::Color32::RED,
+                    tr_args("theming-dry-run-failed", &[("error", e)]),
+                );
+                close_preview = ui.button(tr("close-preview")).clicked();
+            }
+            None => {}
+        }
+        if close_preview {
+            self.theming_preview = None;
+        }
+
+        let mut accent_sync = self.config.wallpaper.accent_sync.clone();
+        ui.checkbox(&mut accent_sync.enabled, tr("accent-sync"))
+            .on_hover_text(tr("accent-sync-hint"));
+
+        let mut family = self.config.wallpaper.auto_change.palette_family;
+        ui.horizontal(|ui| {
+            ui.label(tr("auto-change-palette"));
+            egui::ComboBox::from_id_source("auto_change_palette")
+                .selected_text(
+                    family.map_or_else(|| tr("palette-family-any"), palette_family_label),
+                )
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut family, None, tr("palette-family-any"));
+                    for option in PaletteFamily::ALL {
+                        ui.selectable_value(
+                            &mut family,
+                            Some(option),
+                            palette_family_label(option),
+                        );
+                    }
+                });
+        })
+        .response
+        .on_hover_text(tr("auto-change-palette-hint"));
+
+        let family_changed = family != self.config.wallpaper.auto_change.palette_family;
+        if palette != self.config.wallpaper.palette
+            || accent_sync != self.config.wallpaper.accent_sync
+            || family_changed
+        {
+            self.config.wallpaper.palette = palette;
+            self.config.wallpaper.accent_sync = accent_sync;
+            self.config.wallpaper.auto_change.palette_family = family;
+            if let Err(e) = self.config.save() {
+                error!("Failed to save config: {}", e);
+                self.notifications.error(tr("error-save-config"), &e);
+            } else if let Err(e) = self.daemon.send(&Request::ReloadConfig) {
+                error!("Failed to reload config in daemon: {}", e);
+                self.notifications.error(tr("error-reload-config"), &e);
+            }
+        }
+    }
+
+    /// Show the time-of-day tint of static wallpapers
+    fn show_color_temperature_settings(&mut self, ui: &mut egui::Ui) {
+        let mut tint = self.config.wallpaper.color_temperature.clone();
+
+        ui.heading(tr("color-temperature"));
+        ui.checkbox(&mut tint.enabled, tr("color-temperature-enabled"));
+        ui.label(tr("color-temperature-hint"));
+
+        ui.add_enabled_ui(tint.enabled, |ui| {
+            let now = temperature::kelvin_at(&tint.curve, Local::now().time());
+            ui.label(tr_args(
+                "color-temperature-now",
+                &[("kelvin", &now.to_string())],
+            ));
+
+            let mut removed = None;
+            egui::Grid::new("color_temperature_curve").show(ui, |ui| {
+                for (index, point) in tint.curve.iter_mut().enumerate() {
+                    let mut hour = point.time.hour();
+                    let mut minute = point.time.minute();
+                    ui.horizontal(|ui| {
+                        let hour_changed = ui
+                            .add(egui::DragValue::new(&mut hour).speed(1).clamp_range(0..=23))
+                            .changed();
+                        ui.label(":");
+                        let minute_changed = ui
+                            .add(
+                                egui::DragValue::new(&mut minute)
+                                    .speed(1)
+                                    .clamp_range(0..=59),
+                            )
+                            .changed();
+                        if hour_changed || minute_changed {
+                            point.time = NaiveTime::from_hms_opt(hour, minute, 0).unwrap();
+                        }
+                    });
+                    ui.add(
+                        egui::Slider::new(&mut point.kelvin, 1900..=10000)
+                            .step_by(100.0)
+                            .suffix(" K"),
+                    );
+                    if ui.small_button(tr("delete")).clicked() {
+                        removed = Some(index);
+                    }
+                    ui.end_row();
+                }
+            });
+            if let Some(index) = removed {
+                tint.curve.remove(index);
+            }
+            if ui.button(tr("color-temperature-add")).clicked() {
+                tint.curve.push(TemperaturePoint {
+                    time: NaiveTime::from_hms_opt(12, 0, 0).unwrap(),
+                    kelvin: temperature::NEUTRAL,
+                });
+            }
+
+            ui.add(
+                egui::Slider::new(&mut tint.update_minutes, 1..=60)
+                    .suffix(" min")
+                    .text(tr("color-temperature-update")),
+            );
+        });
+
+        if tint != self.config.wallpaper.color_temperature {
+            self.config.wallpaper.color_temperature = tint;
+            self.save_config_and_reload();
+        }
+    }
+
+    /// Show the weather drawn over static wallpapers
+    fn show_weather_effects_settings(&mut self, ui: &mut egui::Ui) {
+        let mut effects = self.config.wallpaper.weather_effects.clone();
+
+        ui.heading(tr("weather-effects"));
+        ui.checkbox(&mut effects.enabled, tr("weather-effects-enabled"));
+        ui.label(tr("weather-effects-hint"));
+
+        ui.add_enabled_ui(effects.enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(tr("weather-location"));
+                ui.text_edit_singleline(&mut effects.location);
+            });
+            ui.horizontal(|ui| {
+                ui.label(tr("weather-api-key"));
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.weather_api_key_input)
+                        .password(true)
+                        .hint_text(tr("stored-securely")),
+                );
+                if ui.button(tr("save-key")).clicked() {
+                    let result = if self.weather_api_key_input.is_empty() {
+                        self.secret_store.delete(weather::API_KEY_SECRET)
+                    } else {
+                        self.secret_store
+                            .set(weather::API_KEY_SECRET, &self.weather_api_key_input)
+                    };
+                    if let Err(e) = result {
+                        error!("Failed to store API key: {}", e);
+                        self.notifications.error(tr("error-store-api-key"), &e);
+                    }
+                    self.weather_api_key_input.clear();
+                }
+            });
+            ui.add(
+                egui::Slider::new(&mut effects.intensity, 0.1..=1.0).text(tr("weather-intensity")),
+            );
+            ui.add(
+                egui::Slider::new(&mut effects.refresh_minutes, 5..=120)
+                    .suffix(" min")
+                    .text(tr("weather-refresh")),
+            );
+            ui.add(egui::Slider::new(&mut effects.fps, 1..=30).text(tr("motion-fps")));
+            ui.horizontal(|ui| {
+                ui.label(tr("weather-preview"));
+                egui::ComboBox::from_id_source("weather_preview")
+                    .selected_text(
+                        effects
+                            .preview
+                            .map_or_else(|| tr("weather-preview-off"), weather_effect_label),
+                    )
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut effects.preview, None, tr("weather-preview-off"));
+                        for effect in [WeatherEffect::Rain, WeatherEffect::Snow, WeatherEffect::Fog]
+                        {
+                            ui.selectable_value(
+                                &mut effects.preview,
+                                Some(effect),
+                                weather_effect_label(effect),
+                            );
+                        }
+                    });
+            });
+        });
+
+        if effects != self.config.wallpaper.weather_effects {
+            self.config.wallpaper.weather_effects = effects;
+            self.save_config_and_reload();
+        }
+    }
+
+    /// Show the audio-reactive effects drawn over wallpapers
+    fn show_audio_overlay_settings(&mut self, ui: &mut egui::Ui) {
+        let mut overlay = self.config.wallpaper.audio_overlay.clone();
+
+        ui.heading(tr("audio-overlay"));
+        ui.checkbox(&mut overlay.enabled, tr("audio-overlay-enabled"));
+        ui.label(tr("audio-overlay-hint"));
+
+        ui.add_enabled_ui(overlay.enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut overlay.bars, tr("audio-overlay-bars"));
+                ui.checkbox(&mut overlay.vignette, tr("audio-overlay-vignette"));
+            });
+            ui.add_enabled_ui(overlay.bars, |ui| {
+                ui.add(
+                    egui::Slider::new(&mut overlay.bar_count, 8..=128)
+                        .text(tr("audio-overlay-bar-count")),
+                );
+                ui.add(
+                    egui::Slider::new(&mut overlay.bar_height, 0.05..=0.6)
+                        .text(tr("audio-overlay-bar-height")),
+                );
+                ui.horizontal(|ui| {
+                    ui.label(tr("audio-overlay-color"));
+                    let mut color = parse_hex_color(&overlay.color).unwrap_or(egui::Color32::WHITE);
+                    if ui.color_edit_button_srgba(&mut color).changed() {
+                        overlay.color = theme_editor::format_hex_color(color);
+                    }
+                });
+            });
+            ui.add(
+                egui::Slider::new(&mut overlay.sensitivity, 0.25..=4.0)
+                    .text(tr("audio-overlay-sensitivity")),
+            );
+            ui.add(egui::Slider::new(&mut overlay.fps, 1..=60).text(tr("motion-fps")));
+            ui.horizontal(|ui| {
+                ui.label(tr("audio-overlay-device"));
+                ui.add(
+                    egui::TextEdit::singleline(&mut overlay.device)
+                        .hint_text(tr("audio-overlay-device-default")),
+                );
+            })
+            .response
+            .on_hover_text(tr("audio-overlay-device-hint"));
+        });
+
+        if overlay != self.config.wallpaper.audio_overlay {
+            self.config.wallpaper.audio_overlay = overlay;
+            self.save_config_and_reload();
+        }
+    }
+
+    /// Show virtual camera settings
+    fn show_virtual_camera_settings(&mut self, ui: &mut egui::Ui) {
+        let mut camera = self.config.wallpaper.virtual_camera.clone();
+
+        ui.heading(tr("virtual-camera"));
+        ui.checkbox(&mut camera.enabled, tr("virtual-camera-enabled"));
+        ui.label(tr("virtual-camera-hint"));
+
+        ui.add_enabled_ui(camera.enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(tr("virtual-camera-output"));
+                egui::ComboBox::from_id_source("virtual_camera_output")
+                    .selected_text(camera_output_label(camera.output))
+                    .show_ui(ui, |ui| {
+                        for output in [CameraOutput::Loopback, CameraOutput::Stream] {
+                            ui.selectable_value(
+                                &mut camera.output,
+                                output,
+                                camera_output_label(output),
+                            );
+                        }
+                    });
+            });
+            match camera.output {
+                CameraOutput::Loopback => {
+                    ui.horizontal(|ui| {
+                        ui.label(tr("virtual-camera-device"));
+                        ui.text_edit_singleline(&mut camera.device);
+                    })
+                    .response
+                    .on_hover_text(tr("virtual-camera-device-hint"));
+                }
+                CameraOutput::Stream => {
+                    ui.horizontal(|ui| {
+                        ui.label(tr("virtual-camera-stream-url"));
+                        ui.text_edit_singleline(&mut camera.stream_url);
+                    })
+                    .response
+                    .on_hover_text(tr("virtual-camera-stream-url-hint"));
+                }
+            }
+            ui.horizontal(|ui| {
+                ui.label(tr("virtual-camera-size"));
+                ui.add(
+                    egui::DragValue::new(&mut camera.width)
+                        .clamp_range(320..=3840)
+                        .suffix(" px"),
+                );
+                ui.label("×");
+                ui.add(
+                    egui::DragValue::new(&mut camera.height)
+                        .clamp_range(240..=2160)
+                        .suffix(" px"),
+                );
+            });
+            ui.add(egui::Slider::new(&mut camera.fps, 1..=60).text(tr("motion-fps")));
+        });
+
+        if camera != self.config.wallpaper.virtual_camera {
+            self.config.wallpaper.virtual_camera = camera;
+            self.save_config_and_reload();
+        }
+    }
+
+    /// Show automatic tagging settings
+    fn show_tagging_settings(&mut self, ui: &mut egui::Ui) {
+        let mut tagging = self.config.tagging.clone();
+
+        ui.heading(tr("auto-tagging"));
+        ui.checkbox(&mut tagging.enabled, tr("auto-tagging-enabled"));
+        ui.label(tr("auto-tagging-hint"));
+
+        ui.add_enabled_ui(tagging.enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(tr("tagging-model-folder"));
+                ui.monospace(tagging.model_dir().display().to_string());
+                if ui.button(tr("browse")).clicked() {
+                    if let Some(folder) = FileDialog::new().pick_folder() {
+                        tagging.model_dir = Some(folder.to_string_lossy().to_string());
+                    }
+                }
+                if tagging.model_dir.is_some() && ui.button(tr("use-default-folder")).clicked() {
+                    tagging.model_dir = None;
+                }
+            });
+            ui.add(
+                egui::Slider::new(&mut tagging.threshold, 0.05..=0.5).text(tr("tagging-threshold")),
+            );
+
+            if ui.button(tr("tag-library")).clicked() {
+                if let Some(tagger) = self.gallery_view.tagger() {
+                    let folders = orphans::library_dirs(&self.config);
+                    let files = folders
+                        .iter()
+                        .filter_map(|folder| std::fs::read_dir(folder).ok())
+                        .flat_map(|entries| entries.flatten().map(|entry| entry.path()))
+                        .chain(self.config.wallpaper.favorites.iter().map(PathBuf::from));
+                    for path in files {
+                        tagger.queue(&path);
+                    }
+                }
+            }
+        });
+
+        if tagging != self.config.tagging {
+            match self.gallery_view.tagger() {
+                Some(tagger) if tagging.enabled => tagger.update_config(tagging.clone()),
+                Some(_) => self.gallery_view.set_tagger(None),
+                None if tagging.enabled => self
+                    .gallery_view
+                    .set_tagger(Some(TagQueue::start(tagging.clone(), TagStore::shared()))),
+                None => {}
+            }
+            self.config.tagging = tagging;
+            if let Err(e) = self.config.save() {
+                error!("Failed to save config: {}", e);
+                self.notifications.error(tr("error-save-config"), &e);
+            }
+        }
+    }
+
+    /// Show the timer changing to the next wallpaper of a folder
+    fn show_auto_change_settings(&mut self, ui: &mut egui::Ui) {
+        let mut auto_change = self.config.wallpaper.auto_change.clone();
+
+        ui.heading(tr("auto-change"));
+        ui.checkbox(&mut auto_change.enabled, tr("auto-change-enabled"));
+        ui.label(tr("auto-change-hint"));
+
+        ui.add_enabled_ui(auto_change.enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(tr("auto-change-folder"));
+                match &auto_change.folder {
+                    Some(folder) => ui.monospace(folder),
+                    None => ui.label(tr("auto-change-no-folder")),
+                };
+                if ui.button(tr("browse")).clicked() {
+                    if let Some(folder) = FileDialog::new().pick_folder() {
+                        auto_change.folder = Some(folder.to_string_lossy().to_string());
+                    }
+                }
+            });
+            ui.add(
+                egui::Slider::new(&mut auto_change.interval, 1..=1440)
+                    .logarithmic(true)
+                    .suffix(" min")
+                    .text(tr("auto-change-interval")),
+            );
+            ui.checkbox(
+                &mut auto_change.match_resolution,
+                tr("auto-change-match-resolution"),
+            )
+            .on_hover_text(tr("auto-change-match-resolution-hint"));
+            ui.add_enabled_ui(auto_change.match_resolution, |ui| {
+                ui.add(
+                    egui::Slider::new(&mut auto_change.resolution_tolerance, 0.0..=0.5)
+                        .text(tr("auto-change-tolerance")),
+                );
+            });
+        });
+
+        if ui.button(tr("auto-change-next")).clicked() {
+            self.commands.send(Command::NextWallpaper, ui.ctx());
+        }
+
+        if auto_change != self.config.wallpaper.auto_change {
+            self.config.wallpaper.auto_change = auto_change;
+            self.save_config_and_reload();
+        }
+    }
+
+    /// Show when animated wallpapers are paused automatically
+    fn show_auto_pause_settings(&mut self, ui: &mut egui::Ui) {
+        let mut auto_pause = self.config.app.auto_pause.clone();
+
+        ui.heading(tr("auto-pause"));
+        ui.checkbox(&mut auto_pause.on_fullscreen, tr("pause-on-fullscreen"));
+        ui.label(tr("pause-on-fullscreen-hint"));
+
+        ui.checkbox(&mut auto_pause.on_battery, tr("battery-saver"));
+        ui.add_enabled_ui(auto_pause.on_battery, |ui| {
+            ui.add(
+                egui::Slider::new(&mut auto_pause.battery_threshold, 5..=100)
+                    .suffix("%")
+                    .text(tr("battery-threshold")),
+            );
+            ui.add(
+                egui::Slider::new(&mut auto_pause.battery_widget_interval, 1..=60)
+                    .suffix(" s")
+                    .text(tr("battery-widget-interval")),
+            );
+        });
+        ui.label(tr("battery-saver-hint"));
+
+        ui.checkbox(&mut auto_pause.on_idle, tr("pause-on-idle"));
+        ui.add_enabled_ui(auto_pause.on_idle, |ui| {
+            ui.add(
+                egui::Slider::new(&mut auto_pause.idle_minutes, 1..=60)
+                    .suffix(" min")
+                    .text(tr("idle-minutes")),
+            );
+        });
+        ui.label(tr("pause-on-idle-hint"));
+
+        if auto_pause != self.config.app.auto_pause {
+            self.widget_manager.set_power_policy(auto_pause.clone());
+            self.config.app.auto_pause = auto_pause;
+            self.save_config_and_reload();
+        }
+    }
+
+    /// Show how the wallpaper is toned down while windows cover the desktop
+    fn show_focus_mode_settings(&mut self, ui: &mut egui::Ui) {
+        let mut focus = self.config.app.focus_mode.clone();
+
+        ui.heading(tr("focus-mode"));
+        ui.checkbox(&mut focus.enabled, tr("focus-mode-enabled"));
+        ui.add_enabled_ui(focus.enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(tr("focus-effect"));
+                egui::ComboBox::from_id_source("focus_effect")
+                    .selected_text(focus_effect_label(focus.effect))
+                    .show_ui(ui, |ui| {
+                        for effect in [FocusEffect::Dim, FocusEffect::Blur, FocusEffect::DimAndBlur]
+                        {
+                            ui.selectable_value(
+                                &mut focus.effect,
+                                effect,
+                                focus_effect_label(effect),
+                            );
+                        }
+                    });
+            });
+            ui.add(egui::Slider::new(&mut focus.strength, 0.1..=1.0).text(tr("focus-strength")));
+            ui.add(
+                egui::Slider::new(&mut focus.cover_above, 10..=100)
+                    .suffix("%")
+                    .text(tr("focus-cover-above")),
+            );
+            ui.add(
+                egui::Slider::new(&mut focus.uncover_below, 0..=focus.cover_above)
+                    .suffix("%")
+                    .text(tr("focus-uncover-below")),
+            );
+            ui.add(
+                egui::Slider::new(&mut focus.fade_ms, 0..=3000)
+                    .suffix(" ms")
+                    .text(tr("focus-fade")),
+            );
+            ui.checkbox(&mut focus.pause_animated, tr("focus-pause-animated"));
+        });
+        ui.label(tr("focus-mode-hint"));
+
+        if focus != self.config.app.focus_mode {
+            self.config.app.focus_mode = focus;
+            self.save_config_and_reload();
+        }
+    }
+
+    /// Show when animated wallpapers switch to lower quality
+    fn show_adaptive_quality_settings(&mut self, ui: &mut egui::Ui) {
+        let mut quality = self.config.app.adaptive_quality.clone();
+
+        ui.heading(tr("adaptive-quality"));
+        ui.checkbox(&mut quality.enabled, tr("lower-quality-when-busy"));
+        ui.add_enabled_ui(quality.enabled, |ui| {
+            ui.add(
+                egui::Slider::new(&mut quality.lower_above_cpu, 20.0..=100.0)
+                    .suffix("%")
+                    .text(tr("lower-above-cpu")),
+            );
+            ui.add(
+                egui::Slider::new(
+                    &mut quality.restore_below_cpu,
+                    10.0..=quality.lower_above_cpu,
+                )
+                .suffix("%")
+                .text(tr("restore-below-cpu")),
+            );
+            ui.add(egui::Slider::new(&mut quality.reduced_fps, 5..=60).text(tr("reduced-fps")));
+            ui.add(
+                egui::Slider::new(&mut quality.reduced_scale, 0.25..=1.0).text(tr("reduced-scale")),
+            );
+        });
+        ui.label(tr("adaptive-quality-hint"));
+
+        if quality != self.config.app.adaptive_quality {
+            self.config.app.adaptive_quality = quality;
+            self.save_config_and_reload();
+        }
+    }
+
+    /// Show the frame rate limit of animated wallpapers
+    fn show_frame_rate_settings(&mut self, ui: &mut egui::Ui) {
+        let mut limit = self.config.wallpaper.frame_rate.limit;
+
+        ui.heading(tr("frame-rate-limit"));
+        frame_rate_limit_editor(ui, "frame_rate_limit", &mut limit);
+        ui.label(tr("frame-rate-hint"));
+
+        if limit != self.config.wallpaper.frame_rate.limit {
+            self.config.wallpaper.frame_rate.limit = limit;
+            self.save_config_and_reload();
+        }
+    }
+
+    /// Show the GPU that renders shader and web wallpapers
+    fn show_gpu_settings(&mut self, ui: &mut egui::Ui) {
+        let mut preference = self.config.app.gpu_preference;
+
+        ui.heading(tr("gpu"));
+        ui.horizontal(|ui| {
+            ui.label(tr("gpu-preference"));
+            egui::ComboBox::from_id_source("gpu_preference")
+                .selected_text(gpu_preference_label(preference))
+                .show_ui(ui, |ui| {
+                    for option in [
+                        GpuPreference::Default,
+                        GpuPreference::Integrated,
+                        GpuPreference::Discrete,
+                    ] {
+                        ui.selectable_value(&mut preference, option, gpu_preference_label(option));
+                    }
+                });
+        });
+        if self.gpu_adapters.len() < 2 {
+            ui.label(tr("gpu-single"));
+        }
+        for adapter in &self.gpu_adapters {
+            ui.label(format!("• {}", adapter.name));
+        }
+        ui.label(tr("gpu-hint"));
+
+        if preference != self.config.app.gpu_preference {
+            self.config.app.gpu_preference = preference;
+            self.save_config_and_reload();
+        }
+    }
+
+    /// Show the restarting of crashed players
+    fn show_watchdog_settings(&mut self, ui: &mut egui::Ui) {
+        let mut watchdog = self.config.app.watchdog.clone();
+
+        ui.heading(tr("watchdog"));
+        ui.checkbox(&mut watchdog.enabled, tr("restart-crashed-players"));
+        ui.add_enabled_ui(watchdog.enabled, |ui| {
+            ui.add(
+                egui::Slider::new(&mut watchdog.max_restarts, 0..=10)
+                    .text(tr("watchdog-max-restarts")),
+            );
+        });
+        ui.label(tr("watchdog-hint"));
+
+        if watchdog != self.config.app.watchdog {
+            self.config.app.watchdog = watchdog;
+            self.save_config_and_reload();
+        }
+    }
+
+    /// Show the players' resource usage and limits
+    fn show_resource_settings(&mut self, ui: &mut egui::Ui) {
+        let mut limits = self.config.app.resource_limits.clone();
+
+        // The players run in the daemon, which reports their usage
+        self.refresh_daemon_status(ui.ctx());
+        let processes = self
+            .daemon_status
+            .as_ref()
+            .map(|status| status.processes.as_slice())
+            .unwrap_or_default();
+        let memory_mb = processes
+            .iter()
+            .map(|process| process.memory_bytes)
+            .sum::<u64>() as f64
+            / (1024.0 * 1024.0);
+        let cpu_usage: f32 = processes.iter().map(|process| process.cpu_usage).sum();
+        let memory_util = (memory_mb as f32 / limits.max_memory_mb.max(1) as f32).min(1.0);
+        let cpu_util = (cpu_usage / limits.max_cpu.max(1.0)).min(1.0);
+
+        ui.heading(tr("resource-usage"));
+        ui.label(tr_args(
+            "memory-used",
+            &[("mb", &format!("{:.2}", memory_mb))],
+        ));
+        ui.add(egui::ProgressBar::new(memory_util).text(format!("{:.1}%", memory_util * 100.0)));
+        ui.label(tr_args(
+            "cpu-usage",
+            &[("percent", &format!("{:.1}", cpu_usage))],
+        ));
+        ui.add(egui::ProgressBar::new(cpu_util).text(format!("{:.1}%", cpu_util * 100.0)));
+        ui.label(tr_args(
+            "active-processes",
+            &[("count", &processes.len().to_string())],
+        ));
+
+        ui.separator();
+
+        ui.heading(tr("resource-limits"));
+        ui.checkbox(&mut limits.enabled, tr("enforce-resource-limits"));
+        ui.label(tr("resource-limits-hint"));
+        ui.horizontal(|ui| {
+            ui.label(tr("memory-limit"));
+            ui.add(
+                egui::DragValue::new(&mut limits.max_memory_mb)
+                    .clamp_range(64..=65536)
+                    .suffix(" MB"),
+            );
+        });
+        ui.horizontal(|ui| {
+            ui.label(tr("cpu-limit"));
+            ui.add(
+                egui::DragValue::new(&mut limits.max_cpu)
+                    .clamp_range(5.0..=1600.0)
+                    .suffix("%"),
+            );
+        });
+        ui.horizontal(|ui| {
+            ui.label(tr("limit-action"));
+            egui::ComboBox::from_id_source("limit_action")
+                .selected_text(limit_action_label(limits.action))
+                .show_ui(ui, |ui| {
+                    for action in [LimitAction::Pause, LimitAction::Kill] {
+                        ui.selectable_value(&mut limits.action, action, limit_action_label(action));
+                    }
+                });
+        });
+
+        if limits != self.config.app.resource_limits {
+            self.config.app.resource_limits = limits;
+            self.save_config_and_reload();
+        }
+    }
+
+    /// Show local API settings
+    fn show_api_settings(&mut self, ui: &mut egui::Ui) {
+        let mut api_config = self.config.api.clone();
+
+        ui.checkbox(&mut api_config.enabled, tr("api-enabled"));
+        ui.horizontal(|ui| {
+            ui.label(tr("api-port"));
+            ui.add(egui::DragValue::new(&mut api_config.port).clamp_range(1024..=65535));
+        });
+        ui.checkbox(&mut api_config.allow_remote, tr("api-allow-remote"));
+        if api_config.allow_remote {
+            let host =
+                api::lan_address().map_or_else(|| "127.0.0.1".to_string(), |ip| ip.to_string());
+            let url = format!("http://{}:{}/", host, api_config.port);
+            ui.label(tr_args("api-remote-hint", &[("url", &url)]));
+            if ui.button(tr("copy-remote-link")).clicked() {
+                match api::api_token(&self.secret_store) {
+                    Ok(token) => ui.output_mut(|output| {
+                        output.copied_text = format!("{}#token={}", url, token)
+                    }),
+                    Err(e) => {
+                        error!("Failed to read API token: {}", e);
+                        self.notifications.error(tr("error-read-api-token"), &e);
+                    }
+                }
+            }
+
+            // The code holds the token, so it's only drawn when asked for
+            let upload_url = format!("{}upload", url);
+            if self
+                .upload_qr
+                .as_ref()
+                .map_or(false, |(made_for, _)| *made_for != upload_url)
+            {
+                self.upload_qr = None;
+            }
+            let label = if self.upload_qr.is_some() {
+                tr("hide-upload-qr")
+            } else {
+                tr("show-upload-qr")
+            };
+            if ui.button(label).clicked() {
+                if self.upload_qr.take().is_none() {
+                    let code = api::api_token(&self.secret_store).and_then(|token| {
+                        QrCode::encode(format!("{}#token={}", upload_url, token).as_bytes())
+                    });
+                    match code {
+                        Ok(code) => self.upload_qr = Some((upload_url, code)),
+                        Err(e) => {
+                            error!("Failed to make the upload QR code: {}", e);
+                            self.notifications.error(tr("error-upload-qr"), &e);
+                        }
+                    }
+                }
+            }
+            if let Some((_, code)) = &self.upload_qr {
+                ui.label(tr("upload-qr-hint"));
+                draw_qr_code(ui, code);
+            }
+        } else {
+            ui.label(tr_args(
+                "api-hint",
+                &[(
+                    "url",
+                    &format!("http://127.0.0.1:{}/api/status", api_config.port),
+                )],
+            ));
+        }
+
+        ui.horizontal(|ui| {
+            ui.label(tr("api-token"));
+            match &self.api_token {
+                Some(token) => {
+                    ui.monospace(token);
+                    if ui.button(tr("copy")).clicked() {
+                        ui.output_mut(|output| output.copied_text = token.clone());
+                    }
+                }
+                None => {
+                    if ui.button(tr("show-token")).clicked() {
+                        match api::api_token(&self.secret_store) {
+                            Ok(token) => self.api_token = Some(token),
+                            Err(e) => {
+                                error!("Failed to read API token: {}", e);
+                                self.notifications.error(tr("error-read-api-token"), &e);
+                            }
+                        }
+                    }
+                }
+            }
+
+            if ui.button(tr("regenerate-token")).clicked() {
+                match api::regenerate_token(&self.secret_store) {
+                    Ok(token) => {
+                        self.api_token = Some(token);
+                        self.upload_qr = None;
+                        if let Err(e) = self.daemon.send(&Request::ReloadConfig) {
+                            error!("Failed to reload config in daemon: {}", e);
+                            self.notifications.error(tr("error-reload-config"), &e);
+                        }
+                    }
+                    Err(e) => {
+                        error!("Failed to regenerate API token: {}", e);
+                        self.notifications.error(tr("error-store-api-token"), &e);
+                    }
+                }
+            }
+        });
+
+        ui.separator();
+        ui.heading(tr("webhooks"));
+        ui.label(tr("webhooks-hint"));
+
+        let mut removed = None;
+        for (name, action) in api_config.webhooks.iter_mut() {
+            ui.horizontal(|ui| {
+                ui.monospace(format!("/api/hooks/{}", name));
+
+                egui::ComboBox::from_id_source(("webhook_action", name.as_str()))
+                    .selected_text(webhook_action_label(action))
+                    .show_ui(ui, |ui| {
+                        let options = [
+                            WebhookAction::ApplyWallpaper {
+                                path: String::new(),
+                                wallpaper_type: None,
+                            },
+                            WebhookAction::NextWallpaper,
+                            WebhookAction::StopWallpaper,
+                            WebhookAction::StartScheduler,
+                            WebhookAction::StopScheduler,
+                            WebhookAction::SwitchProfile {
+                                profile: String::new(),
+                            },
+                        ];
+                        for option in options {
+                            let selected =
+                                std::mem::discriminant(action) == std::mem::discriminant(&option);
+                            if ui
+                                .selectable_label(selected, webhook_action_label(&option))
+                                .clicked()
+                                && !selected
+                            {
+                                *action = option;
+                            }
+                        }
+                    });
+
+                if let WebhookAction::ApplyWallpaper { path, .. } = action {
+                    ui.text_edit_singleline(path);
+                    if ui.button(tr("browse")).clicked() {
+                        if let Some(picked) = FileDialog::new().pick_file() {
+                            *path = picked.to_string_lossy().to_string();
+                        }
+                    }
+                }
+
+                if let WebhookAction::SwitchProfile { profile } = action {
+                    egui::ComboBox::from_id_source(("webhook_profile", name.as_str()))
+                        .selected_text(profile.as_str())
+                        .show_ui(ui, |ui| {
+                            for profile_name in &self.profile_names {
+                                ui.selectable_value(profile, profile_name.clone(), profile_name);
+                            }
+                        });
+                }
+
+                if ui.button(tr("delete")).clicked() {
+                    removed = Some(name.clone());
+                }
+            });
+        }
+        if let Some(name) = removed {
+            api_config.webhooks.remove(&name);
+        }
+
+        ui.horizontal(|ui| {
+            ui.label(tr("name"));
+            ui.text_edit_singleline(&mut self.new_webhook_name);
+            let name = self.new_webhook_name.trim().to_string();
+            let valid = !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+                && !api_config.webhooks.contains_key(&name);
+            if ui
+                .add_enabled(valid, egui::Button::new(tr("add-webhook")))
+                .clicked()
+            {
+                api_config
+                    .webhooks
+                    .insert(name, WebhookAction::NextWallpaper);
+                self.new_webhook_name.clear();
+            }
+        });
+
+        ui.separator();
+        ui.heading(tr("ha-scenes"));
+        ui.label(tr("ha-scenes-hint"));
+
+        let mut removed = None;
+        for (name, scene) in api_config.scenes.iter_mut() {
+            ui.horizontal(|ui| {
+                ui.strong(name);
+
+                ui.label(tr("ha-scene-profile"));
+                egui::ComboBox::from_id_source(("scene_profile", name.as_str()))
+                    .selected_text(
+                        scene
+                            .profile
+                            .clone()
+                            .unwrap_or_else(|| tr("ha-scene-unchanged")),
+                    )
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut scene.profile, None, tr("ha-scene-unchanged"));
+                        for profile_name in &self.profile_names {
+                            ui.selectable_value(
+                                &mut scene.profile,
+                                Some(profile_name.clone()),
+                                profile_name,
+                            );
+                        }
+                    });
+
+                ui.label(tr("ha-scene-wallpaper"));
+                ui.monospace(
+                    scene
+                        .wallpaper
+                        .clone()
+                        .unwrap_or_else(|| tr("ha-scene-unchanged")),
+                );
+                if ui.button(tr("browse")).clicked() {
+                    if let Some(picked) = FileDialog::new().pick_file() {
+                        scene.wallpaper = Some(picked.to_string_lossy().to_string());
+                    }
+                }
+                if scene.wallpaper.is_some() && ui.button(tr("ha-scene-keep-wallpaper")).clicked() {
+                    scene.wallpaper = None;
+                }
+
+                ui.label(tr("ha-scene-widgets"));
+                egui::ComboBox::from_id_source(("scene_widgets", name.as_str()))
+                    .selected_text(scene_widgets_label(scene.widgets))
+                    .show_ui(ui, |ui| {
+                        for option in [None, Some(true), Some(false)] {
+                            ui.selectable_value(
+                                &mut scene.widgets,
+                                option,
+                                scene_widgets_label(option),
+                            );
+                        }
+                    });
+
+                if ui.button(tr("delete")).clicked() {
+                    removed = Some(name.clone());
+                }
+            });
+        }
+        if let Some(name) = removed {
+            api_config.scenes.remove(&name);
+        }
+
+        ui.horizontal(|ui| {
+            ui.label(tr("name"));
+            ui.text_edit_singleline(&mut self.new_scene_name);
+            let name = self.new_scene_name.trim().to_string();
+            let valid = !name.is_empty() && !api_config.scenes.contains_key(&name);
+            if ui
+                .add_enabled(valid, egui::Button::new(tr("add-scene")))
+                .clicked()
+            {
+                api_config.scenes.insert(name, SceneConfig::default());
+                self.new_scene_name.clear();
+            }
+        });
+
+        if api_config != self.config.api {
+            self.config.api = api_config;
+            if let Err(e) = self.config.save() {
+                error!("Failed to save config: {}", e);
+                self.notifications.error(tr("error-save-config"), &e);
+            } else if let Err(e) = self.daemon.send(&Request::ReloadConfig) {
+                error!("Failed to reload config in daemon: {}", e);
+                self.notifications.error(tr("error-reload-config"), &e);
+            }
+        }
+    }
+
+    /// Show LAN sync settings
+    fn show_sync_settings(&mut self, ui: &mut egui::Ui) {
+        let mut sync_config = self.config.sync.clone();
+
+        ui.checkbox(&mut sync_config.enabled, tr("lan-sync-enabled"));
+        ui.label(tr("lan-sync-hint"));
+
+        ui.add_enabled_ui(sync_config.enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(tr("lan-sync-name"));
+                ui.add(
+                    egui::TextEdit::singleline(&mut sync_config.name)
+                        .hint_text(sync::device_name(&SyncConfig::default())),
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.label(tr("lan-sync-key"));
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.sync_key_input)
+                        .password(true)
+                        .hint_text(tr("stored-securely")),
+                );
+                if ui.button(tr("save-key")).clicked() {
+                    let result = if self.sync_key_input.is_empty() {
+                        self.secret_store.delete(sync::SYNC_KEY_SECRET)
+                    } else {
+                        self.secret_store
+                            .set(sync::SYNC_KEY_SECRET, &self.sync_key_input)
+                    };
+                    match result {
+                        Ok(()) => {
+                            if let Err(e) = self.daemon.send(&Request::ReloadConfig) {
+                                error!("Failed to reload config in daemon: {}", e);
+                                self.notifications.error(tr("error-reload-config"), &e);
+                            }
+                        }
+                        Err(e) => {
+                            error!("Failed to store sync key: {}", e);
+                            self.notifications.error(tr("error-store-sync-key"), &e);
+                        }
+                    }
+                    self.sync_key_input.clear();
+                }
+            })
+            .response
+            .on_hover_text(tr("lan-sync-key-hint"));
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut sync_config.send, tr("lan-sync-send"));
+                ui.checkbox(&mut sync_config.receive, tr("lan-sync-receive"));
+            });
+            ui.horizontal(|ui| {
+                ui.label(tr("api-port"));
+                ui.add(egui::DragValue::new(&mut sync_config.port).clamp_range(1024..=65535));
+            });
+
+            ui.label(tr("lan-sync-peers"));
+            let mut removed = None;
+            for (index, peer) in sync_config.peers.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(peer).hint_text("192.168.1.20:47941"));
+                    if ui.button(tr("delete")).clicked() {
+                        removed = Some(index);
+                    }
+                });
+            }
+            if let Some(index) = removed {
+                sync_config.peers.remove(index);
+            }
+            if ui.button(tr("add-peer")).clicked() {
+                sync_config.peers.push(String::new());
+            }
+        });
+
+        if sync_config != self.config.sync {
+            self.config.sync = sync_config;
+            self.save_config_and_reload();
+        }
+    }
+
+    /// Show chat bot settings
+    fn show_bot_settings(&mut self, ui: &mut egui::Ui) {
+        let mut bot_config = self.config.bot.clone();
+
+        ui.checkbox(&mut bot_config.enabled, tr("chat-bot-enabled"));
+        ui.label(tr("chat-bot-hint"));
+
+        ui.add_enabled_ui(bot_config.enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(tr("chat-bot-platform"));
+                egui::ComboBox::from_id_source("bot_platform")
+                    .selected_text(bot_config.platform.name())
+                    .show_ui(ui, |ui| {
+                        for platform in [BotPlatform::Telegram, BotPlatform::Discord] {
+                            ui.selectable_value(
+                                &mut bot_config.platform,
+                                platform,
+                                platform.name(),
+                            );
+                        }
+                    });
+            });
+            ui.horizontal(|ui| {
+                ui.label(tr("chat-bot-token"));
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.bot_token_input)
+                        .password(true)
+                        .hint_text(tr("stored-securely")),
+                );
+                if ui.button(tr("save-key")).clicked() {
+                    let result = if self.bot_token_input.is_empty() {
+                        self.secret_store.delete(bot::BOT_TOKEN_SECRET)
+                    } else {
+                        self.secret_store
+                            .set(bot::BOT_TOKEN_SECRET, self.bot_token_input.trim())
+                    };
+                    match result {
+                        Ok(()) => {
+                            if let Err(e) = self.daemon.send(&Request::ReloadConfig) {
+                                error!("Failed to reload config in daemon: {}", e);
+                                self.notifications.error(tr("error-reload-config"), &e);
+                            }
+                        }
+                        Err(e) => {
+                            error!("Failed to store bot token: {}", e);
+                            self.notifications.error(tr("error-store-bot-token"), &e);
+                        }
+                    }
+                    self.bot_token_input.clear();
+                }
+            });
+
+            ui.label(tr("chat-bot-users"));
+            let mut removed = None;
+            for (index, user) in bot_config.allowed_users.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(user).hint_text(tr("chat-bot-user-hint")));
+                    if ui.button(tr("delete")).clicked() {
+                        removed = Some(index);
+                    }
+                });
+            }
+            if let Some(index) = removed {
+                bot_config.allowed_users.remove(index);
+            }
+            if ui.button(tr("add-user")).clicked() {
+                bot_config.allowed_users.push(String::new());
+            }
+        });
+
+        if bot_config != self.config.bot {
+            self.config.bot = bot_config;
+            self.save_config_and_reload();
+        }
+    }
+
+    /// Show the configuration history, with its versions to view and restore
+    fn show_config_history(&mut self, ui: &mut egui::Ui) {
+        let mut enabled = self.config.app.config_history;
+        if ui
+            .checkbox(&mut enabled, tr("config-history-enabled"))
+            .changed()
+        {
+            self.config.app.config_history = enabled;
+            config_history::set_enabled(enabled);
+            self.save_config_and_reload();
+            if enabled {
+                // The files as they are now are the first version
+                if let Err(e) =
+                    ConfigHistory::current().and_then(|history| history.snapshot("Start history"))
+                {
+                    error!("Failed to start the configuration history: {}", e);
+                    self.notifications.error(tr("error-config-history"), &e);
+                }
+            }
+            self.history_versions = None;
+        }
+        ui.label(tr("config-history-hint"));
+        if !enabled {
+            return;
+        }
+
+        if ui.button(tr("config-history-refresh")).clicked() {
+            self.history_versions = None;
+        }
+        if self.history_versions.is_none() {
+            let versions = match ConfigHistory::current()
+                .and_then(|history| history.versions(HISTORY_VERSIONS))
+            {
+                Ok(versions) => versions,
+                Err(e) => {
+                    error!("Failed to read the configuration history: {}", e);
+                    self.notifications.error(tr("error-config-history"), &e);
+                    Vec::new()
+                }
+            };
+            self.history_versions = Some(versions);
+        }
+
+        let mut view = None;
+        let versions = self.history_versions.as_deref().unwrap_or_default();
+        if versions.is_empty() {
+            ui.label(tr("config-history-empty"));
+        }
+        egui::ScrollArea::vertical()
+            .id_source("config_history")
+            .max_height(240.0)
+            .show(ui, |ui| {
+                for version in versions {
+                    ui.horizontal(|ui| {
+                        ui.monospace(version.short_id());
+                        ui.label(version.time.format("%Y-%m-%d %H:%M:%S").to_string());
+                        ui.label(&version.message);
+                        for file in &version.files {
+                            if ui
+                                .small_button(file)
+                                .on_hover_text(tr("config-history-view"))
+                                .clicked()
+                            {
+                                view = Some((version.clone(), file.clone()));
+                            }
+                        }
+                    });
+                }
+            });
+        if let Some((version, file)) = view {
+            match ConfigHistory::current().and_then(|history| history.show(&version.id, &file)) {
+                Ok(content) => self.history_view = Some((version, file, content)),
+                Err(e) => {
+                    error!(
+                        "Failed to read {} from the configuration history: {}",
+                        file, e
+                    );
+                    self.notifications.error(tr("error-config-history"), &e);
+                }
+            }
+        }
+
+        let Some((version, file, content)) = self.history_view.clone() else {
+            return;
+        };
+        ui.separator();
+        ui.label(tr_args(
+            "config-history-viewing",
+            &[("file", file.as_str()), ("version", version.short_id())],
+        ));
+        egui::ScrollArea::vertical()
+            .id_source("config_history_view")
+            .max_height(300.0)
+            .show(ui, |ui| {
+                ui.add(
+                    egui::TextEdit::multiline(&mut content.as_str())
+                        .code_editor()
+                        .desired_width(f32::INFINITY),
+                );
+            });
+        let mut close = false;
+        ui.horizontal(|ui| {
+            if ui.button(tr("config-history-restore")).clicked() {
+                match ConfigHistory::current().and_then(|history| history.restore(&version, &file))
+                {
+                    // The watchers reload the restored file, here and in the service
+                    Ok(()) => {
+                        self.notifications.info(tr_args(
+                            "config-history-restored",
+                            &[("file", file.as_str()), ("version", version.short_id())],
+                        ));
+                        self.history_versions = None;
+                        close = true;
+                    }
+                    Err(e) => {
+                        error!("Failed to restore {}: {}", file, e);
+                        self.notifications.error(tr("error-config-history"), &e);
+                    }
+                }
+            }
+            if ui.button(tr("config-history-close")).clicked() {
+                close = true;
+            }
+        });
+        if close {
+            self.history_view = None;
+        }
+    }
+
+    /// Show the registration of the browser extension's native messaging host
+    fn show_browser_extension_settings(&mut self, ui: &mut egui::Ui) {
+        ui.heading(tr("browser-extension"));
+        ui.label(tr("browser-extension-hint"));
+        ui.horizontal(|ui| {
+            ui.label(tr("browser-extension-ids"));
+            ui.add(
+                egui::TextEdit::singleline(&mut self.browser_extension_ids)
+                    .hint_text(tr("browser-extension-ids-hint")),
+            );
+        });
+
+        ui.horizontal(|ui| {
+            let label = if native_messaging::is_installed() {
+                tr("browser-extension-reinstall")
+            } else {
+                tr("browser-extension-install")
+            };
+            if ui.button(label).clicked() {
+                let ids: Vec<String> = self
+                    .browser_extension_ids
+                    .split([',', ' '])
+                    .filter(|id| !id.is_empty())
+                    .map(str::to_string)
+                    .collect();
+                match native_messaging::install(&ids) {
+                    Ok(paths) => self.notifications.info(tr_args(
+                        "browser-extension-installed",
+                        &[("count", &paths.len().to_string())],
+                    )),
+                    Err(e) => {
+                        error!("Failed to register the browser extension: {}", e);
+                        self.notifications.error(tr("error-browser-extension"), &e);
+                    }
+                }
+            }
+            if native_messaging::is_installed()
+                && ui.button(tr("browser-extension-uninstall")).clicked()
+            {
+                if let Err(e) = native_messaging::uninstall() {
+                    error!("Failed to remove the browser extension's host: {}", e);
+                    self.notifications.error(tr("error-browser-extension"), &e);
+                }
+            }
+        });
+    }
+
+    /// Show calendar subscription settings
+    fn show_calendar_settings(&mut self, ui: &mut egui::Ui) {
+        let mut calendar_config = self.config.sources.calendar.clone();
+
+        ui.label(tr("calendar-hint"));
+        ui.label(tr("calendar-urls"));
+        ui.add(
+            egui::TextEdit::multiline(&mut self.calendar_urls_input)
+                .desired_rows(3)
+                .hint_text(tr("calendar-urls-hint")),
+        );
+        if ui.button(tr("save-key")).clicked() {
+            let urls = self.calendar_urls_input.trim();
+            let result = if urls.is_empty() {
+                self.secret_store.delete(calendar::URLS_SECRET)
+            } else {
+                self.secret_store.set(calendar::URLS_SECRET, urls)
+            };
+            match result {
+                Ok(()) => {
+                    Calendar::shared()
+                        .configure(calendar_config.clone(), self.secret_store.clone());
+                    if let Err(e) = self.daemon.send(&Request::ReloadConfig) {
+                        error!("Failed to reload config in daemon: {}", e);
+                        self.notifications.error(tr("error-reload-config"), &e);
+                    }
+                }
+                Err(e) => {
+                    error!("Failed to store calendar addresses: {}", e);
+                    self.notifications.error(tr("error-store-calendar"), &e);
+                }
+            }
+            self.calendar_urls_input.clear();
+        }
+
+        ui.add(
+            egui::Slider::new(&mut calendar_config.refresh_minutes, 5..=120)
+                .suffix(" min")
+                .text(tr("calendar-refresh")),
+        );
+        ui.add(
+            egui::Slider::new(&mut calendar_config.days_ahead, 1..=31)
+                .text(tr("calendar-days-ahead")),
+        );
+        if let Some(status) = Calendar::shared().status() {
+            ui.small(status);
+        }
+
+        if calendar_config != self.config.sources.calendar {
+            Calendar::shared().configure(calendar_config.clone(), self.secret_store.clone());
+            self.config.sources.calendar = calendar_config;
+            self.save_config_and_reload();
+        }
+    }
+
+    /// Show online source settings
+    fn show_sources_settings(&mut self, ui: &mut egui::Ui) {
+        let mut sources = self.config.sources.clone();
+
+        ui.heading(tr("auto-download"));
+        ui.checkbox(
+            &mut sources.auto_download.enabled,
+            tr("auto-download-enabled"),
+        );
+
+        ui.horizontal(|ui| {
+            ui.label(tr("source"));
+            egui::ComboBox::from_id_source("auto_download_source")
+                .selected_text(&sources.auto_download.source)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(
+                        &mut sources.auto_download.source,
+                        "wallhaven".to_string(),
+                        "Wallhaven",
+                    );
+                });
+        });
+
+        ui.horizontal(|ui| {
+            ui.label(tr("search-query"));
+            ui.text_edit_singleline(&mut sources.auto_download.query);
+        });
+
+        ui.horizontal(|ui| {
+            ui.label(tr("interval-minutes"));
+            ui.add(
+                egui::DragValue::new(&mut sources.auto_download.interval).clamp_range(1..=10080),
+            );
+            ui.label(tr("per-run"));
+            ui.add(egui::DragValue::new(&mut sources.auto_download.per_run).clamp_range(1..=24));
+        });
+
+        ui.checkbox(
+            &mut sources.auto_download.set_as_wallpaper,
+            tr("apply-newest-download"),
+        );
+
+        ui.horizontal(|ui| {
+            ui.label(tr("wallhaven-api-key"));
+            ui.add(
+                egui::TextEdit::singleline(&mut self.source_api_key_input)
+                    .password(true)
+                    .hint_text(tr("stored-securely")),
+            );
+            if ui.button(tr("save-key")).clicked() {
+                let result = if self.source_api_key_input.is_empty() {
+                    self.secret_store.delete(wallhaven::API_KEY_SECRET)
+                } else {
+                    self.secret_store
+                        .set(wallhaven::API_KEY_SECRET, &self.source_api_key_input)
+                };
+                if let Err(e) = result {
+                    error!("Failed to store API key: {}", e);
+                    self.notifications.error(tr("error-store-api-key"), &e);
+                }
+                self.source_api_key_input.clear();
+            }
+        });
+
+        ui.separator();
+        ui.heading(tr("resolution-filter"));
+        ui.checkbox(
+            &mut sources.resolution_filter.enabled,
+            tr("resolution-filter-enabled"),
+        );
+        ui.add_enabled_ui(sources.resolution_filter.enabled, |ui| {
+            ui.checkbox(
+                &mut sources.resolution_filter.match_aspect_ratio,
+                tr("match-monitor-aspect-ratio"),
+            );
+            ui.add(
+                egui::Slider::new(&mut sources.resolution_filter.aspect_tolerance, 0.0..=0.5)
+                    .text(tr("aspect-ratio-tolerance")),
+            );
+            ui.add(
+                egui::Slider::new(&mut sources.resolution_filter.min_scale, 0.25..=2.0)
+                    .text(tr("minimum-size")),
+            );
+        });
+
+        if self.monitors.is_empty() {
+            ui.label(tr("no-monitors-detected"));
+        } else {
+            for monitor in &self.monitors {
+                ui.label(format!(
+                    "{}: {}x{}",
+                    monitor.name, monitor.width, monitor.height
+                ));
+            }
+        }
+
+        ui.separator();
+        if ui.button(tr("download-now")).clicked() {
+            self.commands.send(Command::DownloadNow, ui.ctx());
+        }
+        self.refresh_daemon_status(ui.ctx());
+        if let Some(last_run) = self
+            .daemon_status
+            .as_ref()
+            .and_then(|status| status.last_download.as_deref())
+        {
+            ui.label(tr_args(
+                "last-run",
+                &[("time", &format_timestamp(last_run))],
+            ));
+        }
+
+        ui.separator();
+        self.show_content_filter_settings(ui, &mut sources);
+
+        ui.separator();
+        ui.heading(tr("download-cache"));
+        ui.horizontal(|ui| {
+            ui.label(tr("cache-max-size"));
+            ui.add(egui::DragValue::new(&mut sources.cache.max_size_mb).clamp_range(0..=1_048_576));
+        });
+        ui.horizontal(|ui| {
+            ui.label(tr("cache-max-age"));
+            ui.add(egui::DragValue::new(&mut sources.cache.max_age_days).clamp_range(0..=3650));
+        });
+
+        if self.download_cache_usage.is_none() {
+            match cache::usage(&self.config.get_downloads_dir()) {
+                Ok(usage) => self.download_cache_usage = Some(usage),
+                Err(e) => error!("Failed to read download cache usage: {}", e),
+            }
+        }
+        if let Some(usage) = self.download_cache_usage {
+            ui.label(tr_args(
+                "cache-usage",
+                &[
+                    (
+                        "mb",
+                        &format!("{:.1}", usage.bytes as f64 / (1024.0 * 1024.0)),
+                    ),
+                    ("files", &usage.files.to_string()),
+                ],
+            ));
+        }
+        ui.label(tr("cache-protected-hint"));
+
+        ui.horizontal(|ui| {
+            if ui.button(tr("clean-up-now")).clicked() {
+                let protected = cache::protected_paths(&self.config);
+                if let Err(e) = cache::cleanup(
+                    &self.config.get_downloads_dir(),
+                    &self.config.sources.cache,
+                    &protected,
+                ) {
+                    error!("Failed to clean up download cache: {}", e);
+                    self.notifications.error(tr("error-clean-up-cache"), &e);
+                }
+                self.download_cache_usage = None;
+            }
+            if ui.button(tr("clear-cache")).clicked() {
+                if let Err(e) = cache::clear(
+                    &self.config.get_downloads_dir(),
+                    &cache::protected_paths(&self.config),
+                ) {
+                    error!("Failed to clear download cache: {}", e);
+                    self.notifications.error(tr("error-clear-cache"), &e);
+                }
+                self.download_cache_usage = None;
+            }
+        });
+
+        ui.separator();
+        self.show_live_earth_settings(ui, &mut sources);
+
+        if sources != self.config.sources {
+            self.config.sources = sources;
+            if let Err(e) = self.config.save() {
+                error!("Failed to save config: {}", e);
+                self.notifications.error(tr("error-save-config"), &e);
+            } else if let Err(e) = self.daemon.send(&Request::ReloadConfig) {
+                error!("Failed to reload config in daemon: {}", e);
+                self.notifications.error(tr("error-reload-config"), &e);
+            }
+        }
+    }
+
+    /// Show Live Earth settings
+    fn show_live_earth_settings(&mut self, ui: &mut egui::Ui, sources: &mut SourcesConfig) {
+        let live_earth = &mut sources.live_earth;
+
+        ui.heading(tr("live-earth"));
+        ui.checkbox(&mut live_earth.enabled, tr("live-earth-enabled"));
+
+        ui.horizontal(|ui| {
+            ui.label(tr("hemisphere"));
+            egui::ComboBox::from_id_source("live_earth_satellite")
+                .selected_text(satellite_label(live_earth.satellite))
+                .show_ui(ui, |ui| {
+                    for satellite in [
+                        Satellite::Himawari,
+                        Satellite::GoesEast,
+                        Satellite::GoesWest,
+                    ] {
+                        ui.selectable_value(
+                            &mut live_earth.satellite,
+                            satellite,
+                            satellite_label(satellite),
+                        );
+                    }
+                });
+        });
+
+        ui.horizontal(|ui| {
+            ui.label(tr("detail"));
+            egui::ComboBox::from_id_source("live_earth_detail")
+                .selected_text(format!("{}x", live_earth.detail))
+                .show_ui(ui, |ui| {
+                    for detail in [1, 2, 4, 8] {
+                        ui.selectable_value(&mut live_earth.detail, detail, format!("{}x", detail));
+                    }
+                });
+        });
+
+        ui.add(egui::Slider::new(&mut live_earth.zoom, 1.0..=4.0).text(tr("zoom")));
+
+        ui.horizontal(|ui| {
+            ui.label(tr("update-every-minutes"));
+            ui.add(egui::DragValue::new(&mut live_earth.interval).clamp_range(10..=1440));
+        });
+
+        if ui.button(tr("update-now")).clicked() {
+            self.commands.send(Command::UpdateLiveEarth, ui.ctx());
+        }
+        if let Some(last_update) = self
+            .daemon_status
+            .as_ref()
+            .and_then(|status| status.last_live_earth_update.as_deref())
+        {
+            ui.label(tr_args(
+                "last-update",
+                &[("time", &format_timestamp(last_update))],
+            ));
+        }
+    }
+
+    /// Show content filter settings
+    ///
+    /// Tightening the policy applies immediately; loosening it has to be confirmed.
+    fn show_content_filter_settings(&mut self, ui: &mut egui::Ui, sources: &mut SourcesConfig) {
+        ui.heading(tr("content-filter"));
+
+        if let Some(pending) = self.pending_content_filter.clone() {
+            ui.colored_label(
+                egui::Color32::YELLOW,
+                tr_args(
+                    "loosen-content-filter",
+                    &[("purity", &purity_label(pending.max_purity))],
+                ),
+            );
+            ui.horizontal(|ui| {
+                if ui.button(tr("confirm")).clicked() {
+                    sources.content_filter = pending;
+                    self.pending_content_filter = None;
+                }
+                if ui.button(tr("cancel")).clicked() {
+                    self.pending_content_filter = None;
+                }
+            });
+            return;
+        }
+
+        let mut content_filter = sources.content_filter.clone();
+
+        ui.horizontal(|ui| {
+            ui.label(tr("allowed-content"));
+            purity_combo(ui, "content_filter_global", &mut content_filter.max_purity);
+        });
+
+        ui.label(tr("per-provider-purity"));
+        for provider in ["wallhaven"] {
+            let mut purity = content_filter
+                .providers
+                .get(provider)
+                .copied()
+                .unwrap_or(content_filter.max_purity);
+            ui.horizontal(|ui| {
+                ui.label(provider);
+                purity_combo(ui, provider, &mut purity);
+            });
+            if purity == content_filter.max_purity {
+                content_filter.providers.remove(provider);
+            } else {
+                content_filter
+                    .providers
+                    .insert(provider.to_string(), purity);
+            }
+        }
+
+        if content_filter != sources.content_filter {
+            let current = &sources.content_filter;
+            let loosened = content_filter.max_purity > current.max_purity
+                || content_filter.providers.iter().any(|(provider, purity)| {
+                    *purity
+                        > current
+                            .providers
+                            .get(provider)
+                            .copied()
+                            .unwrap_or(current.max_purity)
+                });
+
+            if loosened {
+                self.pending_content_filter = Some(content_filter);
+            } else {
+                sources.content_filter = content_filter;
+            }
+        }
+    }
+
+    /// Apply the selected wallpaper through the daemon
+    fn apply_wallpaper(&mut self, ctx: &egui::Context) {
+        if let Some(wallpaper) = self.selected_wallpaper() {
+            self.commands.send(
+                Command::ApplyWallpaper {
+                    wallpaper,
+                    intent: Intent::Keep,
+                },
+                ctx,
+            );
+        }
+    }
+
+    /// Give the active workspace the selected wallpaper, shown whenever it is active
+    ///
+    /// The daemon shows it right away, as the reloaded configuration assigns
+    /// it to the workspace on screen.
+    fn assign_to_workspace(&mut self) {
+        let Some(workspace) = workspace::current_workspace() else {
+            self.notifications.warning(tr("error-no-workspace"), None);
+            return;
+        };
+        if let Some(wallpaper) = self.selected_wallpaper() {
+            info!(
+                "Assigning {} to workspace {}",
+                wallpaper.name, workspace.name
+            );
+            let assigned = WorkspaceWallpaper {
+                workspace: workspace.name,
+                wallpaper: wallpaper.on_monitor(None),
+            };
+            self.config
+                .wallpaper
+                .workspace_wallpapers
+                .insert(workspace.id, assigned);
+            self.save_config_and_reload();
+        }
+    }
+
+    /// Build the wallpaper selected on the Wallpaper tab
+    ///
+    /// Warns and returns None when nothing is selected.
+    fn selected_wallpaper(&mut self) -> Option<WallpaperInfo> {
+        let wallpaper_type = self.selected_wallpaper_type.clone();
+        let (path, url) = match wallpaper_type {
+            WallpaperType::Web => {
+                if self.selected_web_url.is_empty() {
+                    error!("No URL provided for web wallpaper");
+                    self.notifications.warning(tr("error-no-url"), None);
+                    return None;
+                }
+                (None, Some(self.selected_web_url.clone()))
+            }
+            WallpaperType::Particles | WallpaperType::NowPlaying => (None, None),
+            WallpaperType::Procedural => {
+                return Some(WallpaperInfo::procedural(self.selected_pattern))
+            }
+            _ => match &self.selected_wallpaper_path {
+                Some(path) => (Some(path.clone()), None),
+                None => {
+                    error!("No path selected for {} wallpaper", wallpaper_type.as_str());
+                    self.notifications.warning(
+                        tr_args(
+                            "error-no-path",
+                            &[("type", &wallpaper_type_label(&wallpaper_type))],
+                        ),
+                        None,
+                    );
+                    return None;
+                }
+            },
+        };
+
+        let monitor = self
+            .selected_monitor
+            .clone()
+            .filter(|_| wallpaper_type.runs_per_monitor());
+        Some(WallpaperInfo::new(wallpaper_type, path, url).on_monitor(monitor))
+    }
+
+    /// Get the name of a monitor from its ID, or the ID when it isn't connected
+    fn monitor_name(&self, id: &str) -> String {
+        self.monitors
+            .iter()
+            .find(|monitor| monitor.id == id)
+            .map_or_else(|| id.to_string(), |monitor| monitor.name.clone())
+    }
+
+    /// Stop the wallpaper shown on one monitor and forget it
+    fn stop_monitor_wallpaper(&mut self, monitor: &str) {
+        if let Err(e) = self.daemon.send(&Request::StopMonitorWallpaper {
+            monitor: monitor.to_string(),
+        }) {
+            error!("Failed to stop wallpaper on {}: {}", monitor, e);
+            self.notifications.error(tr("error-stop-wallpaper"), &e);
+            return;
+        }
+        self.last_status_refresh = None;
+        self.config.wallpaper.monitor_wallpapers.remove(monitor);
+        if let Err(e) = self.config.save() {
+            error!("Failed to save config: {}", e);
+            self.notifications.error(tr("error-save-config"), &e);
+        }
+    }
+
+    /// Save a wallpaper as the current one so the daemon restores it on its next start
+    fn remember_wallpaper(&mut self, wallpaper: &WallpaperInfo) {
+        wallpaper.remember(&mut self.config.wallpaper);
+        if let Err(e) = self.config.save() {
+            error!("Failed to save config: {}", e);
+            self.notifications.error(tr("error-save-config"), &e);
+        }
+        self.last_status_refresh = None;
+    }
+
+    /// Show the selected wallpaper on the desktop for a few seconds
+    ///
+    /// The configuration keeps the previous wallpaper until the user keeps the new one.
+    fn try_on_wallpaper(&mut self, ctx: &egui::Context) {
+        if let Some(wallpaper) = self.selected_wallpaper() {
+            self.commands.send(
+                Command::ApplyWallpaper {
+                    wallpaper,
+                    intent: Intent::TryOn,
+                },
+                ctx,
+            );
+        }
+    }
+
+    /// Revert the try-on once its time is up
+    fn check_try_on(&mut self, ctx: &egui::Context) {
+        if let Some((_, started)) = &self.try_on {
+            if started.elapsed() >= TRY_ON_DURATION {
+                self.revert_try_on(ctx);
+            } else {
+                ctx.request_repaint_after(Duration::from_millis(250));
+            }
+        }
+    }
+
+    /// Keep the wallpaper being tried on
+    fn keep_try_on(&mut self) {
+        if let Some((wallpaper, _)) = self.try_on.take() {
+            self.remember_wallpaper(&wallpaper);
+        }
+    }
+
+    /// Go back to the wallpaper saved before the try-on
+    fn revert_try_on(&mut self, ctx: &egui::Context) {
+        if self.try_on.take().is_some() {
+            self.restore_saved_wallpaper(ctx);
+        }
+    }
+
+    /// Apply the wallpaper saved in the configuration again
+    fn restore_saved_wallpaper(&mut self, ctx: &egui::Context) {
+        self.commands.send(self.restore_command(), ctx);
+    }
+
+    /// Get the command bringing back the wallpaper saved in the configuration
+    fn restore_command(&self) -> Command {
+        match WallpaperInfo::saved(&self.config.wallpaper) {
+            Some(wallpaper) => Command::ApplyWallpaper {
+                wallpaper,
+                intent: Intent::Restore,
+            },
+            None => Command::StopWallpaper {
+                intent: Intent::Restore,
+            },
+        }
+    }
+
+    /// Run the selected wallpaper on the desktop and measure what it costs
+    ///
+    /// Like a try-on, the saved wallpaper comes back afterwards.
+    fn start_benchmark(&mut self, ctx: &egui::Context) {
+        if let Some(wallpaper) = self.selected_wallpaper() {
+            self.commands.send(
+                Command::ApplyWallpaper {
+                    wallpaper,
+                    intent: Intent::Benchmark,
+                },
+                ctx,
+            );
+        }
+    }
 
-        // Resource monitoring
-        ui.collapsing("Resource Monitoring", |ui| {
-            ui.heading("Resource Usage");
+    /// Sample the running benchmark and finish it once its time is up
+    fn check_benchmark(&mut self, ctx: &egui::Context) {
+        let Some(benchmark) = &mut self.benchmark else {
+            return;
+        };
 
-            // Get current resource usage
-            let usage = self.runtime.block_on(async {
-                self.resource_manager.get_usage().await
+        benchmark.collect_sample();
+        if benchmark.is_sample_due() {
+            // The players run in the daemon, which is asked off the UI thread
+            let daemon = self.daemon.clone();
+            benchmark.start_sample(move || {
+                let (processes, throttled) = match daemon.status() {
+                    Ok(status) => {
+                        let throttled =
+                            status.auto_paused || status.saving_power || status.quality_reduced;
+                        (status.processes, throttled)
+                    }
+                    Err(e) => {
+                        error!("Failed to get daemon status: {}", e);
+                        (Vec::new(), false)
+                    }
+                };
+                Probe {
+                    processes,
+                    throttled,
+                    gpu_busy: gpu::busy_percent(),
+                }
             });
+        }
 
-            let (memory_util, gpu_util, cpu_util) = self.runtime.block_on(async {
-                self.resource_manager.get_utilization().await
-            });
+        if benchmark.is_finished() {
+            let report = runtime::block_on(benchmark.report());
+            info!(
+                "Benchmark of {} finished: {:?}",
+                report.wallpaper, report.rating
+            );
+            self.benchmark = None;
+            self.benchmark_report = Some(report);
+            self.restore_saved_wallpaper(ctx);
+        } else {
+            // Keep drawing so the frame rate shows how smooth the desktop stays
+            ctx.request_repaint();
+        }
+    }
+
+    /// Show the results of the last benchmark
+    fn show_benchmark_report(&mut self, ctx: &egui::Context) {
+        let Some(report) = &self.benchmark_report else {
+            return;
+        };
 
-            // Display resource usage
-            ui.label(format!("Memory Used: {:.2} MB", usage.memory_used as f64 / (1024.0 * 1024.0)));
-            ui.add(egui::ProgressBar::new(memory_util / 100.0).text(format!("{:.1}%", memory_util)));
+        let mut open = true;
+        egui::Window::new(tr("benchmark-report"))
+            .open(&mut open)
+            .default_width(360.0)
+            .show(ctx, |ui| {
+                let not_reported = || tr("benchmark-not-reported");
+                let percent = |(average, peak): (f32, f32)| {
+                    tr_args(
+                        "benchmark-average-peak",
+                        &[
+                            ("average", &format!("{:.0}%", average)),
+                            ("peak", &format!("{:.0}%", peak)),
+                        ],
+                    )
+                };
 
-            ui.label(format!("GPU Memory Used: {:.2} MB", usage.gpu_memory_used as f64 / (1024.0 * 1024.0)));
-            ui.add(egui::ProgressBar::new(gpu_util / 100.0).text(format!("{:.1}%", gpu_util)));
+                egui::Grid::new("benchmark_report")
+                    .num_columns(2)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label(tr("benchmark-wallpaper"));
+                        ui.label(&report.wallpaper);
+                        ui.end_row();
 
-            ui.label(format!("CPU Usage: {:.1}%", usage.cpu_usage));
-            ui.add(egui::ProgressBar::new(cpu_util / 100.0).text(format!("{:.1}%", cpu_util)));
+                        ui.label(tr("benchmark-duration"));
+                        ui.label(tr_args(
+                            "benchmark-seconds",
+                            &[("seconds", &report.duration.as_secs().to_string())],
+                        ));
+                        ui.end_row();
 
-            ui.label(format!("Active Processes: {}", usage.active_processes));
+                        ui.label(tr("benchmark-rating"));
+                        ui.colored_label(
+                            suitability_color(report.rating),
+                            suitability_label(report.rating),
+                        );
+                        ui.end_row();
 
-            ui.separator();
+                        ui.label(tr("benchmark-system-cpu"));
+                        ui.label(report.system_cpu.map_or_else(not_reported, percent));
+                        ui.end_row();
 
-            // Resource limits
-            ui.heading("Resource Limits");
-            ui.label("These limits help prevent excessive resource consumption");
+                        ui.label(tr("benchmark-player-cpu"));
+                        ui.label(percent(report.player_cpu));
+                        ui.end_row();
 
-            // Note: In a real implementation, we would allow users to adjust these values
-            ui.label("Memory Limit: 512 MB");
-            ui.label("GPU Memory Limit: 256 MB");
-            ui.label("CPU Limit: 80%");
-            ui.label("Process Limit: 10");
-        });
+                        ui.label(tr("benchmark-player-memory"));
+                        ui.label(format!("{} MB", report.player_memory / (1024 * 1024)));
+                        ui.end_row();
 
-        // Theme settings
-        ui.collapsing("Theme", |ui| {
-            let mut selected_theme = self.config.app.theme.theme.clone();
+                        ui.label(tr("benchmark-gpu"));
+                        ui.label(report.gpu_busy.map_or_else(not_reported, percent));
+                        ui.end_row();
 
-            ui.horizontal(|ui| {
-                ui.label("Theme:");
-                egui::ComboBox::from_label("")
-                    .selected_text(format!("{:?}", selected_theme))
-                    .show_ui(ui, |ui| {
-                        ui.selectable_value(&mut selected_theme, Theme::Light, "Light");
-                        ui.selectable_value(&mut selected_theme, Theme::Dark, "Dark");
-                        ui.selectable_value(&mut selected_theme, Theme::Custom, "Custom");
+                        ui.label(tr("benchmark-fps"));
+                        ui.label(report.fps.map_or_else(not_reported, |(average, lowest)| {
+                            tr_args(
+                                "benchmark-average-lowest",
+                                &[
+                                    ("average", &format!("{:.0}", average)),
+                                    ("lowest", &format!("{:.0}", lowest)),
+                                ],
+                            )
+                        }));
+                        ui.end_row();
                     });
-            });
 
-            if selected_theme != self.config.app.theme.theme {
-                self.config.app.theme.theme = selected_theme.clone();
-                if let Err(e) = self.config.save() {
-                    error!("Failed to save config: {}", e);
+                for bottleneck in &report.bottlenecks {
+                    ui.label(bottleneck_label(bottleneck));
                 }
-            }
+                if report.throttled {
+                    ui.colored_label(egui::Color32::YELLOW, tr("benchmark-throttled"));
+                }
+            });
 
-            if selected_theme == Theme::Custom {
-                let mut accent = self.config.app.theme.accent_color.clone().unwrap_or("#00bcd4".to_string());
-                let mut bg = self.config.app.theme.background_color.clone().unwrap_or("#181818".to_string());
+        if !open {
+            self.benchmark_report = None;
+        }
+    }
+
+    /// Offer to open the report of a crash in an earlier run
+    fn show_crash_report(&mut self, ctx: &egui::Context) {
+        let Some(report) = &self.crash_report else {
+            return;
+        };
 
+        let mut open = true;
+        let mut handled = false;
+        egui::Window::new(tr("crash-report"))
+            .open(&mut open)
+            .collapsible(false)
+            .default_width(360.0)
+            .show(ctx, |ui| {
+                ui.label(tr("crash-report-hint"));
+                ui.label(report.display().to_string());
                 ui.horizontal(|ui| {
-                    ui.label("Accent Color (hex):");
-                    if ui.text_edit_singleline(&mut accent).changed() {
-                        self.config.app.theme.accent_color = Some(accent.clone());
-                        if let Err(e) = self.config.save() {
-                            error!("Failed to save config: {}", e);
+                    if ui.button(tr("open-crash-report")).clicked() {
+                        if let Err(e) = opener::open(report) {
+                            error!("Failed to open crash report: {}", e);
+                            self.notifications.error(tr("error-open-crash-report"), &e);
                         }
+                        handled = true;
                     }
-                });
-                ui.horizontal(|ui| {
-                    ui.label("Background Color (hex):");
-                    if ui.text_edit_singleline(&mut bg).changed() {
-                        self.config.app.theme.background_color = Some(bg.clone());
-                        if let Err(e) = self.config.save() {
-                            error!("Failed to save config: {}", e);
-                        }
+                    if ui.button(tr("dismiss")).clicked() {
+                        handled = true;
                     }
                 });
+            });
+
+        if !open || handled {
+            if let Err(e) = crash::mark_seen(report) {
+                warn!("Failed to note the crash report as seen: {}", e);
             }
-        });
+            self.crash_report = None;
+        }
     }
-    
-    /// Apply the selected wallpaper
-    fn apply_wallpaper(&mut self) {
-        let rt = Arc::clone(&self.runtime);
-        let wallpaper_type = self.selected_wallpaper_type.clone();
-        let wallpaper_path = self.selected_wallpaper_path.clone();
-        let web_url = self.selected_web_url.clone();
-        let wallpaper_manager = Arc::clone(&self.wallpaper_manager);
-        
-        // Stop current wallpaper if any
-        if let Some(wallpaper) = self.current_wallpaper.take() {
-            let rt_stop = Arc::clone(&rt);
-            rt_stop.spawn(async move {
-                if let Err(e) = wallpaper.stop().await {
-                    error!("Failed to stop current wallpaper: {}", e);
-                }
-            });
+
+    /// Stop the current wallpaper
+    fn stop_wallpaper(&mut self, ctx: &egui::Context) {
+        self.commands.send(
+            Command::StopWallpaper {
+                intent: Intent::Keep,
+            },
+            ctx,
+        );
+    }
+
+    /// Update the UI with the results of finished wallpaper commands
+    fn poll_commands(&mut self) {
+        for event in self.commands.poll() {
+            self.handle_command_event(event);
         }
-        
-        // Spawn async task to create and start new wallpaper
-        rt.spawn(async move {
-            let result = match wallpaper_type {
-                WallpaperType::Static => {
-                    if let Some(path) = wallpaper_path {
-                        let wallpaper = StaticWallpaper::new(&path, wallpaper_manager);
-                        wallpaper.start().await.map(|_| {
-                            info!("Static wallpaper applied successfully");
-                        })
-                    } else {
-                        Err(crate::core::AppError::WallpaperError("No path selected for static wallpaper".to_string()))
-                    }
+    }
+
+    /// Act on the result of a wallpaper command
+    fn handle_command_event(&mut self, event: CommandEvent) {
+        self.last_status_refresh = None;
+        match (event.command, event.result) {
+            (
+                Command::ApplyWallpaper {
+                    wallpaper,
+                    intent: Intent::Keep,
                 },
-                WallpaperType::Video => {
-                    if let Some(path) = wallpaper_path {
-                        let wallpaper = VideoWallpaper::new(&path, wallpaper_manager);
-                        wallpaper.start().await.map(|_| {
-                            info!("Video wallpaper applied successfully");
-                        })
-                    } else {
-                        Err(crate::core::AppError::WallpaperError("No path selected for video wallpaper".to_string()))
-                    }
+                Ok(_),
+            ) => {
+                info!("Wallpaper applied successfully");
+                self.performance
+                    .record_wallpaper_load(&wallpaper.name, event.elapsed);
+                self.try_on = None;
+                self.remember_wallpaper(&wallpaper);
+            }
+            (
+                Command::ApplyWallpaper {
+                    wallpaper,
+                    intent: Intent::TryOn,
                 },
-                WallpaperType::Web => {
-                    if !web_url.is_empty() {
-                        let wallpaper = WebWallpaper::new(&web_url, wallpaper_manager);
-                        wallpaper.start().await.map(|_| {
-                            info!("Web wallpaper applied successfully");
-                        })
-                    } else {
-                        Err(crate::core::AppError::WallpaperError("No URL provided for web wallpaper".to_string()))
-                    }
+                Ok(_),
+            ) => {
+                info!(
+                    "Trying wallpaper on for {} seconds",
+                    TRY_ON_DURATION.as_secs()
+                );
+                self.performance
+                    .record_wallpaper_load(&wallpaper.name, event.elapsed);
+                self.try_on = Some((wallpaper, Instant::now()));
+            }
+            (
+                Command::ApplyWallpaper {
+                    wallpaper,
+                    intent: Intent::Benchmark,
                 },
-                WallpaperType::Shader => {
-                    if let Some(path) = wallpaper_path {
-                        let wallpaper = ShaderWallpaper::new(&path, wallpaper_manager);
-                        wallpaper.start().await.map(|_| {
-                            info!("Shader wallpaper applied successfully");
-                        })
-                    } else {
-                        Err(crate::core::AppError::WallpaperError("No path selected for shader wallpaper".to_string()))
-                    }
+                Ok(_),
+            ) => {
+                info!(
+                    "Benchmarking {} for {} seconds",
+                    wallpaper.name, self.benchmark_seconds
+                );
+                let duration = Duration::from_secs(self.benchmark_seconds);
+                self.benchmark = Some(Benchmark::new(
+                    &wallpaper.name,
+                    duration,
+                    &self.config.app.resource_limits,
+                ));
+                self.benchmark_report = None;
+            }
+            (
+                Command::StopWallpaper {
+                    intent: Intent::Keep,
                 },
-                WallpaperType::Audio => {
-                    if let Some(path) = wallpaper_path {
-                        let wallpaper = AudioWallpaper::new(&path, wallpaper_manager);
-                        wallpaper.start().await.map(|_| {
-                            info!("Audio wallpaper applied successfully");
-                        })
-                    } else {
-                        Err(crate::core::AppError::WallpaperError("No path selected for audio wallpaper".to_string()))
+                Ok(_),
+            ) => {
+                info!("Wallpaper stopped successfully");
+                self.config.wallpaper.current_path = None;
+                self.config.wallpaper.monitor_wallpapers.clear();
+                if let Err(e) = self.config.save() {
+                    error!("Failed to save config: {}", e);
+                    self.notifications.error(tr("error-save-config"), &e);
+                }
+            }
+            (command @ (Command::UndoWallpaper | Command::RedoWallpaper), Ok(response)) => {
+                match response.status.and_then(|status| status.current_wallpaper) {
+                    Some(wallpaper) => {
+                        info!("Switched to wallpaper from history: {}", wallpaper.name);
+                        self.try_on = None;
+                        self.remember_wallpaper(&wallpaper);
+                    }
+                    None => {
+                        let key = if matches!(command, Command::UndoWallpaper) {
+                            "nothing-to-undo"
+                        } else {
+                            "nothing-to-redo"
+                        };
+                        self.notifications.push(EventLevel::Info, tr(key), None);
                     }
+                }
+            }
+            (Command::SetLockScreen { path }, Ok(_)) => {
+                info!("Lock screen wallpaper set: {}", path.display());
+                self.notifications
+                    .push(EventLevel::Info, tr("lock-screen-set"), None);
+            }
+            (Command::SetLockScreen { .. }, Err(e)) => {
+                error!("Failed to set lock screen wallpaper: {}", e);
+                self.notifications.error(tr("error-set-lock-screen"), &e);
+            }
+            (Command::NextWallpaper, Ok(response)) => {
+                info!(
+                    "Switched to next wallpaper: {}",
+                    response.message.unwrap_or_default()
+                );
+            }
+            (Command::NextWallpaper, Err(e)) => {
+                error!("Failed to change to the next wallpaper: {}", e);
+                self.notifications.error(tr("error-next-wallpaper"), &e);
+            }
+            (Command::DownloadNow, Ok(response)) => {
+                info!("{}", response.message.unwrap_or_default());
+                self.download_cache_usage = None;
+            }
+            (Command::DownloadNow, Err(e)) => {
+                error!("Failed to download wallpapers: {}", e);
+                self.notifications
+                    .error(tr("error-download-wallpapers"), &e);
+                self.download_cache_usage = None;
+            }
+            (Command::UpdateLiveEarth, Ok(response)) => info!(
+                "Updated Live Earth: {}",
+                response.message.unwrap_or_default()
+            ),
+            (Command::UpdateLiveEarth, Err(e)) => {
+                error!("Failed to update Live Earth: {}", e);
+                self.notifications.error(tr("error-update-live-earth"), &e);
+            }
+            (_, Ok(_)) => info!("Reverted the tried-on wallpaper"),
+            (Command::UndoWallpaper | Command::RedoWallpaper, Err(e)) => {
+                error!("Failed to undo or redo wallpaper change: {}", e);
+                self.notifications.error(tr("error-undo-wallpaper"), &e);
+            }
+            (
+                Command::StopWallpaper {
+                    intent: Intent::Keep,
                 },
-            };
-            
-            if let Err(e) = result {
+                Err(e),
+            ) => {
+                error!("Failed to stop wallpaper: {}", e);
+                self.notifications.error(tr("error-stop-wallpaper"), &e);
+            }
+            (
+                Command::ApplyWallpaper {
+                    intent: Intent::Restore,
+                    ..
+                }
+                | Command::StopWallpaper { .. },
+                Err(e),
+            ) => {
+                error!("Failed to revert wallpaper: {}", e);
+                self.notifications.error(tr("error-revert-wallpaper"), &e);
+            }
+            (Command::ApplyWallpaper { .. }, Err(e)) => {
                 error!("Failed to apply wallpaper: {}", e);
+                self.notifications.error(tr("error-apply-wallpaper"), &e);
             }
+        }
+    }
+
+    /// Undo or redo a wallpaper change from the keyboard
+    ///
+    /// Text fields keep Ctrl+Z for themselves while they have focus.
+    fn handle_shortcuts(&mut self, ctx: &egui::Context) {
+        if ctx.memory(|memory| memory.focus().is_some())
+            || self.try_on.is_some()
+            || self.benchmark.is_some()
+        {
+            return;
+        }
+
+        let redo = ctx.input_mut(|input| {
+            input.consume_shortcut(&egui::KeyboardShortcut::new(
+                egui::Modifiers::COMMAND | egui::Modifiers::SHIFT,
+                egui::Key::Z,
+            )) || input.consume_shortcut(&egui::KeyboardShortcut::new(
+                egui::Modifiers::COMMAND,
+                egui::Key::Y,
+            ))
         });
+        let undo = ctx.input_mut(|input| {
+            input.consume_shortcut(&egui::KeyboardShortcut::new(
+                egui::Modifiers::COMMAND,
+                egui::Key::Z,
+            ))
+        });
+        if redo {
+            self.commands.send(Command::RedoWallpaper, ctx);
+        } else if undo {
+            self.commands.send(Command::UndoWallpaper, ctx);
+        }
     }
-    
-    /// Stop the current wallpaper
-    fn stop_wallpaper(&mut self) {
-        if let Some(wallpaper) = self.current_wallpaper.take() {
-            let rt = Arc::clone(&self.runtime);
-            rt.spawn(async move {
-                if let Err(e) = wallpaper.stop().await {
-                    error!("Failed to stop wallpaper: {}", e);
-                } else {
-                    info!("Wallpaper stopped successfully");
+
+    /// Save the schedule and have the daemon reload it
+    fn save_schedule(&mut self) {
+        if let Err(e) = self.scheduler.save_schedule(&self.config) {
+            error!("Failed to save schedule: {}", e);
+            self.notifications.error(tr("error-save-schedule"), &e);
+            return;
+        }
+
+        if let Err(e) = self.daemon.send(&Request::ReloadSchedule) {
+            error!("Failed to reload schedule in daemon: {}", e);
+            self.notifications.error(tr("error-reload-schedule"), &e);
+        }
+    }
+
+    /// Import another wallpaper tool's rotation, schedule and favorites
+    ///
+    /// What couldn't be imported is shown with the notification.
+    fn import_from(&mut self, tool: Tool, ctx: &egui::Context) {
+        let path = match tool {
+            Tool::Folder => match FileDialog::new().pick_folder() {
+                Some(folder) => Some(folder),
+                None => return,
+            },
+            Tool::WindowsTheme => match FileDialog::new()
+                .add_filter(
+                    &tr("filter-windows-themes"),
+                    &["theme", "themepack", "deskthemepack"],
+                )
+                .pick_file()
+            {
+                Some(file) => Some(file),
+                None => return,
+            },
+            _ => None,
+        };
+        let migration = match migrate::read(tool, path.as_deref()) {
+            Ok(migration) => migration,
+            Err(e) => {
+                error!("Failed to import from {}: {}", tool.name(), e);
+                self.notifications
+                    .error(tr_args("error-import", &[("tool", tool.name())]), &e);
+                return;
+            }
+        };
+        if migration.is_empty() {
+            self.notifications.warning(
+                tr_args("nothing-to-import", &[("tool", tool.name())]),
+                Some(migration.notes.join("\n")),
+            );
+            return;
+        }
+
+        let added = match migration.merge_into(&mut self.config) {
+            Ok(added) => added,
+            Err(e) => {
+                error!("Failed to import from {}: {}", tool.name(), e);
+                self.notifications
+                    .error(tr_args("error-import", &[("tool", tool.name())]), &e);
+                return;
+            }
+        };
+        info!("Imported from {}: {} schedule item(s)", tool.name(), added);
+        self.save_config_and_reload();
+        if let Err(e) = self.scheduler.load_schedule(&self.config) {
+            error!("Failed to load schedule: {}", e);
+            self.notifications.error(tr("error-load-schedule"), &e);
+        }
+        if let Err(e) = self.daemon.send(&Request::ReloadSchedule) {
+            error!("Failed to reload schedule in daemon: {}", e);
+            self.notifications.error(tr("error-reload-schedule"), &e);
+        }
+        if let Some(wallpaper) = migration.wallpaper {
+            self.commands.send(
+                Command::ApplyWallpaper {
+                    wallpaper,
+                    intent: Intent::Keep,
+                },
+                ctx,
+            );
+        }
+
+        let title = tr_args(
+            "imported-from",
+            &[("tool", tool.name()), ("count", &added.to_string())],
+        );
+        if migration.notes.is_empty() {
+            self.notifications.info(title);
+        } else {
+            self.notifications
+                .warning(title, Some(migration.notes.join("\n")));
+        }
+    }
+
+    /// Refresh the daemon status at most every two seconds
+    ///
+    /// The daemon is asked off the UI thread; the status shown is the last
+    /// answer until the next one is in.
+    fn refresh_daemon_status(&mut self, ctx: &egui::Context) {
+        if let Some(answer) = &self.status_answer {
+            match answer.try_recv() {
+                Ok(status) => {
+                    self.status_answer = None;
+                    self.daemon_status = status;
+                    if let Some(status) = &self.daemon_status {
+                        self.gallery_view
+                            .set_usage(status.usage_by_wallpaper.clone());
+                    }
+                }
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => self.status_answer = None,
+            }
+        }
+
+        let due = self
+            .last_status_refresh
+            .map_or(true, |last| last.elapsed() >= Duration::from_secs(2));
+        if due {
+            self.last_status_refresh = Some(Instant::now());
+            self.status_answer = Some(commands::ask(&self.daemon, ctx, |daemon| {
+                daemon.status().ok()
+            }));
+        }
+    }
+
+    /// Turn new daemon events into notifications
+    fn poll_daemon_events(&mut self, ctx: &egui::Context) {
+        // A profile switch reloads subsystems that may still be loading
+        if !self.is_loaded() {
+            return;
+        }
+
+        let Some(answer) = &self.events_answer else {
+            let due = self
+                .last_event_poll
+                .map_or(true, |last| last.elapsed() >= Duration::from_secs(2));
+            if due {
+                self.last_event_poll = Some(Instant::now());
+                let since = self.last_event_id;
+                self.events_answer = Some(commands::ask(&self.daemon, ctx, move |daemon| {
+                    daemon.events_since(since)
+                }));
+            }
+            return;
+        };
+        let events = match answer.try_recv() {
+            Ok(events) => events,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => {
+                Err(AppError::Other("Event poll stopped".to_string()))
+            }
+        };
+        self.events_answer = None;
+
+        // The daemon may not be running; its absence is reported by the status section
+        let Ok(events) = events else {
+            return;
+        };
+
+        let mut profile_switched = false;
+        for event in events {
+            self.last_event_id = self.last_event_id.max(event.id);
+            let is_new = DateTime::parse_from_rfc3339(&event.time)
+                .map_or(true, |time| time >= self.launched);
+            if is_new && event.level != EventLevel::Info {
+                self.notifications.push_event(&event);
+            }
+            profile_switched |= is_new && event.source == "profiles";
+        }
+
+        if profile_switched {
+            self.reload_profile();
+        }
+    }
+}
+
+/// Describe a wallpaper's memory, CPU and GPU use
+pub(crate) fn wallpaper_usage_text(usage: &WallpaperUsage) -> String {
+    let memory = format!("{}", usage.memory_bytes / (1024 * 1024));
+    let cpu = format!("{:.0}", usage.cpu_usage);
+    match usage.gpu_busy {
+        Some(gpu) => tr_args(
+            "wallpaper-usage-gpu",
+            &[
+                ("memory", &memory),
+                ("cpu", &cpu),
+                ("gpu", &format!("{:.0}", gpu)),
+            ],
+        ),
+        None => tr_args("wallpaper-usage", &[("memory", &memory), ("cpu", &cpu)]),
+    }
+}
+
+/// Get the translated label for a benchmark rating
+fn suitability_label(rating: Suitability) -> String {
+    match rating {
+        Suitability::Good => tr("benchmark-good"),
+        Suitability::Fair => tr("benchmark-fair"),
+        Suitability::Poor => tr("benchmark-poor"),
+    }
+}
+
+/// Get the color a benchmark rating is shown in
+fn suitability_color(rating: Suitability) -> egui::Color32 {
+    match rating {
+        Suitability::Good => egui::Color32::GREEN,
+        Suitability::Fair => egui::Color32::YELLOW,
+        Suitability::Poor => egui::Color32::RED,
+    }
+}
+
+/// Get the translated explanation of what held a benchmark rating back
+fn bottleneck_label(bottleneck: &Bottleneck) -> String {
+    match bottleneck {
+        Bottleneck::ResourceLimit(reason) => {
+            tr_args("benchmark-bottleneck-limit", &[("reason", reason)])
+        }
+        Bottleneck::Cpu => tr("benchmark-bottleneck-cpu"),
+        Bottleneck::Gpu => tr("benchmark-bottleneck-gpu"),
+        Bottleneck::FrameRate => tr("benchmark-bottleneck-fps"),
+    }
+}
+
+/// Get the translated label for a focus mode effect
+fn focus_effect_label(effect: FocusEffect) -> String {
+    match effect {
+        FocusEffect::Dim => tr("focus-effect-dim"),
+        FocusEffect::Blur => tr("focus-effect-blur"),
+        FocusEffect::DimAndBlur => tr("focus-effect-dim-and-blur"),
+    }
+}
+
+/// Get the translated label for a particle preset
+fn particle_preset_label(preset: ParticlePreset) -> String {
+    match preset {
+        ParticlePreset::Constellation => tr("particle-preset-constellation"),
+        ParticlePreset::Fireflies => tr("particle-preset-fireflies"),
+        ParticlePreset::Snowfall => tr("particle-preset-snowfall"),
+        ParticlePreset::Bubbles => tr("particle-preset-bubbles"),
+    }
+}
+
+/// Get the translated label for how particles react to the mouse
+fn mouse_reaction_label(reaction: MouseReaction) -> String {
+    match reaction {
+        MouseReaction::None => tr("particle-mouse-none"),
+        MouseReaction::Attract => tr("particle-mouse-attract"),
+        MouseReaction::Repel => tr("particle-mouse-repel"),
+    }
+}
+
+/// Get the translated label for a procedural pattern
+fn procedural_pattern_label(pattern: ProceduralPattern) -> String {
+    match pattern {
+        ProceduralPattern::MatrixRain => tr("procedural-matrix-rain"),
+        ProceduralPattern::Starfield => tr("procedural-starfield"),
+        ProceduralPattern::Plasma => tr("procedural-plasma"),
+        ProceduralPattern::GameOfLife => tr("procedural-game-of-life"),
+        ProceduralPattern::ReactionDiffusion => tr("procedural-reaction-diffusion"),
+        ProceduralPattern::FlowField => tr("procedural-flow-field"),
+    }
+}
+
+/// Get the translated label for a procedural color scheme
+fn color_scheme_label(scheme: ColorScheme) -> String {
+    match scheme {
+        ColorScheme::Classic => tr("procedural-scheme-classic"),
+        ColorScheme::Ocean => tr("procedural-scheme-ocean"),
+        ColorScheme::Sunset => tr("procedural-scheme-sunset"),
+        ColorScheme::Neon => tr("procedural-scheme-neon"),
+        ColorScheme::Mono => tr("procedural-scheme-mono"),
+    }
+}
+
+/// Get the translated label for a weather effect
+fn weather_effect_label(effect: WeatherEffect) -> String {
+    match effect {
+        WeatherEffect::Rain => tr("weather-rain"),
+        WeatherEffect::Snow => tr("weather-snow"),
+        WeatherEffect::Fog => tr("weather-fog"),
+    }
+}
+
+/// Get the translated label for a motion effect
+fn motion_label(effect: MotionEffect) -> String {
+    match effect {
+        MotionEffect::Parallax => tr("motion-parallax"),
+        MotionEffect::KenBurns => tr("motion-ken-burns"),
+    }
+}
+
+/// Get the translated label for a transition effect
+fn transition_label(effect: TransitionEffect) -> String {
+    match effect {
+        TransitionEffect::None => tr("transition-none"),
+        TransitionEffect::Crossfade => tr("transition-crossfade"),
+        TransitionEffect::Slide => tr("transition-slide"),
+        TransitionEffect::Wipe => tr("transition-wipe"),
+        TransitionEffect::Pixelate => tr("transition-pixelate"),
+        TransitionEffect::Morph => tr("transition-morph"),
+    }
+}
+
+/// Get the translated label for a slideshow order
+fn slideshow_order_label(order: SlideshowOrder) -> String {
+    match order {
+        SlideshowOrder::Name => tr("slideshow-order-name"),
+        SlideshowOrder::Modified => tr("slideshow-order-modified"),
+        SlideshowOrder::Shuffle => tr("slideshow-order-shuffle"),
+    }
+}
+
+/// Get the translated name of a system theming tool
+fn theming_tool_label(tool: ThemingTool) -> String {
+    match tool {
+        ThemingTool::None => tr("theming-none"),
+        ThemingTool::Templates => tr("theming-templates-builtin"),
+        ThemingTool::Pywal => "pywal".to_string(),
+        ThemingTool::Wallust => "wallust".to_string(),
+    }
+}
+
+/// Get the translated name of a virtual camera output
+fn camera_output_label(output: CameraOutput) -> String {
+    match output {
+        CameraOutput::Loopback => tr("virtual-camera-loopback"),
+        CameraOutput::Stream => tr("virtual-camera-stream"),
+    }
+}
+
+/// Get the translated name of a palette family
+fn palette_family_label(family: PaletteFamily) -> String {
+    match family {
+        PaletteFamily::Red => tr("palette-family-red"),
+        PaletteFamily::Orange => tr("palette-family-orange"),
+        PaletteFamily::Yellow => tr("palette-family-yellow"),
+        PaletteFamily::Green => tr("palette-family-green"),
+        PaletteFamily::Cyan => tr("palette-family-cyan"),
+        PaletteFamily::Blue => tr("palette-family-blue"),
+        PaletteFamily::Purple => tr("palette-family-purple"),
+        PaletteFamily::Pink => tr("palette-family-pink"),
+        PaletteFamily::Neutral => tr("palette-family-neutral"),
+        PaletteFamily::Dark => tr("palette-family-dark"),
+    }
+}
+
+/// Get the translated label for a diagnostics category
+fn diagnostics_category_label(category: CheckCategory) -> String {
+    match category {
+        CheckCategory::Tools => tr("diagnostics-tools"),
+        CheckCategory::Desktop => tr("diagnostics-desktop"),
+        CheckCategory::Permissions => tr("diagnostics-permissions"),
+        CheckCategory::Configuration => tr("diagnostics-configuration"),
+    }
+}
+
+/// Get the translated label for a resource limit action
+fn limit_action_label(action: LimitAction) -> String {
+    match action {
+        LimitAction::Pause => tr("limit-action-pause"),
+        LimitAction::Kill => tr("limit-action-kill"),
+    }
+}
+
+/// Get the translated label for a GPU preference
+fn gpu_preference_label(preference: GpuPreference) -> String {
+    match preference {
+        GpuPreference::Default => tr("gpu-default"),
+        GpuPreference::Integrated => tr("gpu-integrated"),
+        GpuPreference::Discrete => tr("gpu-discrete"),
+    }
+}
+
+/// Get the translated label for a frame rate limit
+fn frame_rate_limit_label(limit: FrameRateLimit) -> String {
+    match limit {
+        FrameRateLimit::Unlimited => tr("frame-rate-unlimited"),
+        FrameRateLimit::Fixed(_) => tr("frame-rate-fixed"),
+        FrameRateLimit::MatchRefresh => tr("frame-rate-match-refresh"),
+        FrameRateLimit::HalfRefresh => tr("frame-rate-half-refresh"),
+    }
+}
+
+/// Show a frame rate limit picker, with the frame rate of a fixed limit
+fn frame_rate_limit_editor(ui: &mut egui::Ui, id: &str, limit: &mut FrameRateLimit) {
+    ui.horizontal(|ui| {
+        let fixed = match *limit {
+            FrameRateLimit::Fixed(fps) => fps,
+            _ => 30,
+        };
+        egui::ComboBox::from_id_source(id)
+            .selected_text(frame_rate_limit_label(*limit))
+            .show_ui(ui, |ui| {
+                for option in [
+                    FrameRateLimit::Unlimited,
+                    FrameRateLimit::Fixed(fixed),
+                    FrameRateLimit::MatchRefresh,
+                    FrameRateLimit::HalfRefresh,
+                ] {
+                    let selected = std::mem::discriminant(limit) == std::mem::discriminant(&option);
+                    if ui
+                        .selectable_label(selected, frame_rate_limit_label(option))
+                        .clicked()
+                    {
+                        *limit = option;
+                    }
                 }
             });
+        if let FrameRateLimit::Fixed(fps) = limit {
+            ui.add(
+                egui::DragValue::new(fps)
+                    .clamp_range(1..=240)
+                    .suffix(" FPS"),
+            );
         }
+    });
+}
+
+/// Get the translated label for what a scene does with the widgets
+fn scene_widgets_label(widgets: Option<bool>) -> String {
+    match widgets {
+        None => tr("ha-scene-unchanged"),
+        Some(true) => tr("ha-scene-show-widgets"),
+        Some(false) => tr("ha-scene-hide-widgets"),
+    }
+}
+
+/// Draw a QR code black on white, with the quiet zone scanners need around it
+fn draw_qr_code(ui: &mut egui::Ui, code: &QrCode) {
+    const MODULE: f32 = 4.0;
+    const QUIET_ZONE: usize = 4;
+    let side = (code.size() + 2 * QUIET_ZONE) as f32 * MODULE;
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(side, side), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 0.0, egui::Color32::WHITE);
+    for y in 0..code.size() {
+        for x in 0..code.size() {
+            if code.is_dark(x, y) {
+                let min = rect.min
+                    + egui::vec2((x + QUIET_ZONE) as f32, (y + QUIET_ZONE) as f32) * MODULE;
+                painter.rect_filled(
+                    egui::Rect::from_min_size(min, egui::vec2(MODULE, MODULE)),
+                    0.0,
+                    egui::Color32::BLACK,
+                );
+            }
+        }
+    }
+}
+
+/// Get the translated label for a webhook action
+fn webhook_action_label(action: &WebhookAction) -> String {
+    match action {
+        WebhookAction::ApplyWallpaper { .. } => tr("webhook-apply-wallpaper"),
+        WebhookAction::NextWallpaper => tr("webhook-next-wallpaper"),
+        WebhookAction::StopWallpaper => tr("webhook-stop-wallpaper"),
+        WebhookAction::StartScheduler => tr("webhook-start-scheduler"),
+        WebhookAction::StopScheduler => tr("webhook-stop-scheduler"),
+        WebhookAction::SwitchProfile { .. } => tr("webhook-switch-profile"),
+    }
+}
+
+/// Format an RFC 3339 timestamp for display
+fn format_timestamp(timestamp: &str) -> String {
+    chrono::DateTime::parse_from_rfc3339(timestamp)
+        .map(|t| {
+            t.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_else(|_| timestamp.to_string())
+}
+
+/// Get the display label for a purity level
+fn purity_label(purity: Purity) -> String {
+    match purity {
+        Purity::Sfw => tr("purity-sfw"),
+        Purity::Sketchy => tr("purity-sketchy"),
+        Purity::Nsfw => tr("purity-nsfw"),
+    }
+}
+
+/// Show a purity selector
+fn purity_combo(ui: &mut egui::Ui, id: &str, purity: &mut Purity) {
+    egui::ComboBox::from_id_source(id)
+        .selected_text(purity_label(*purity))
+        .show_ui(ui, |ui| {
+            for option in [Purity::Sfw, Purity::Sketchy, Purity::Nsfw] {
+                ui.selectable_value(purity, option, purity_label(option));
+            }
+        });
+}
+
+/// Get the display label for a Live Earth satellite
+fn satellite_label(satellite: Satellite) -> String {
+    match satellite {
+        Satellite::Himawari => tr("satellite-himawari"),
+        Satellite::GoesEast => tr("satellite-goes-east"),
+        Satellite::GoesWest => tr("satellite-goes-west"),
     }
 }
 
-// Helper function to parse hex color
-fn parse_hex_color(hex: &str) -> Option<egui::Color32> {
-    if hex.starts_with('#') && hex.len() == 7 {
-        let r = u8::from_str_radix(&hex[1..3], 16).ok()?;
-        let g = u8::from_str_radix(&hex[3..5], 16).ok()?;
-        let b = u8::from_str_radix(&hex[5..7], 16).ok()?;
-        Some(egui::Color32::from_rgb(r, g, b))
-    } else {
-        None
-    }
-} 
\ No newline at end of file
+/// Get the display label for a wallpaper type
+pub(crate) fn wallpaper_type_label(wallpaper_type: &WallpaperType) -> String {
+    match wallpaper_type {
+        WallpaperType::Static => tr("static"),
+        WallpaperType::AnimatedStatic => tr("animated-static"),
+        WallpaperType::AnimatedImage => tr("animated-image"),
+        WallpaperType::Video => tr("video"),
+        WallpaperType::Web => tr("web"),
+        WallpaperType::Shader => tr("shader"),
+        WallpaperType::Audio => tr("audio"),
+        WallpaperType::Particles => tr("particles"),
+        WallpaperType::Procedural => tr("procedural"),
+        WallpaperType::Slideshow => tr("slideshow"),
+        WallpaperType::NowPlaying => tr("now-playing"),
+    }
+}
@@ -0,0 +1,254 @@
+//! Wallpaper commands run off the UI thread
+//!
+//! Applying a wallpaper waits for the daemon to start the player, which can
+//! take seconds for video and web wallpapers, and downloads take longer
+//! still. The UI queues commands instead; a task on the shared runtime sends
+//! them to the daemon one at a time, in order, and hands each result back as
+//! an event polled every frame. The status and event polls are asked on the
+//! runtime too, with `ask`.
+use crate::core::{runtime, AppError, AppResult, WallpaperInfo};
+use crate::daemon::{DaemonClient, Request, Response};
+use eframe::egui;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+
+/// What a wallpaper change is for, which decides what happens once it's done
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Intent {
+    /// Make it the current wallpaper
+    Keep,
+    /// Show it for a few seconds
+    TryOn,
+    /// Measure what it costs
+    Benchmark,
+    /// Go back to the saved wallpaper
+    Restore,
+}
+
+/// Wallpaper command for the daemon
+#[derive(Debug, Clone)]
+pub enum Command {
+    /// Run a wallpaper on the desktop
+    ApplyWallpaper {
+        /// Wallpaper to run
+        wallpaper: WallpaperInfo,
+        /// What it is applied for
+        intent: Intent,
+    },
+    /// Stop the running wallpaper
+    StopWallpaper {
+        /// What it is stopped for
+        intent: Intent,
+    },
+    /// Go back to the wallpaper before the last change
+    UndoWallpaper,
+    /// Go forward to the last undone wallpaper
+    RedoWallpaper,
+    /// Show an image on the lock screen
+    SetLockScreen {
+        /// Image to show
+        path: PathBuf,
+    },
+    /// Change to the next wallpaper of the auto-change folder
+    NextWallpaper,
+    /// Download a batch of wallpapers from the online source
+    DownloadNow,
+    /// Fetch the latest Live Earth image
+    UpdateLiveEarth,
+}
+
+impl Command {
+    /// Get the daemon request carrying out the command
+    pub fn request(&self) -> Request {
+        match self {
+            Command::ApplyWallpaper { wallpaper, .. } => Request::ApplyWallpaper {
+                wallpaper: wallpaper.clone(),
+            },
+            Command::StopWallpaper { .. } => Request::StopWallpaper,
+            Command::UndoWallpaper => Request::UndoWallpaper,
+            Command::RedoWallpaper => Request::RedoWallpaper,
+            Command::SetLockScreen { path } => Request::SetLockScreen { path: path.clone() },
+            Command::NextWallpaper => Request::NextWallpaper,
+            Command::DownloadNow => Request::DownloadNow,
+            Command::UpdateLiveEarth => Request::UpdateLiveEarth,
+        }
+    }
+}
+
+/// Outcome of a command
+#[derive(Debug)]
+pub struct CommandEvent {
+    /// Command that ran
+    pub command: Command,
+    /// How long the daemon took
+    pub elapsed: Duration,
+    /// Daemon's answer
+    pub result: AppResult<Response>,
+}
+
+/// Handle for queueing commands, shared by the views
+#[derive(Debug, Clone)]
+pub struct CommandSender {
+    /// Commands for the worker, with the context to repaint when done
+    commands: UnboundedSender<(Command, egui::Context)>,
+    /// Commands queued or running
+    pending: Arc<AtomicUsize>,
+}
+
+impl CommandSender {
+    /// Queue a command
+    pub fn send(&self, command: Command, ctx: &egui::Context) {
+        self.pending.fetch_add(1, Ordering::SeqCst);
+        if self.commands.send((command, ctx.clone())).is_err() {
+            self.pending.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Check whether any command is queued or running
+    pub fn is_busy(&self) -> bool {
+        self.pending.load(Ordering::SeqCst) > 0
+    }
+}
+
+/// Queue of commands and their results
+pub struct CommandQueue {
+    /// Handle for queueing commands
+    sender: CommandSender,
+    /// Results of finished commands
+    events: Receiver<CommandEvent>,
+}
+
+impl CommandQueue {
+    /// Create a queue sending its commands through the given client
+    pub fn new(daemon: DaemonClient) -> Self {
+        let (commands, mut receiver) = unbounded_channel::<(Command, egui::Context)>();
+        let (events, events_receiver) = mpsc::channel();
+        let pending = Arc::new(AtomicUsize::new(0));
+
+        let worker_pending = pending.clone();
+        runtime::shared().spawn(async move {
+            while let Some((command, ctx)) = receiver.recv().await {
+                let request = command.request();
+                let daemon = daemon.clone();
+                let started = Instant::now();
+                // The client talks to the daemon over a blocking socket
+                let result = tokio::task::spawn_blocking(move || daemon.send(&request))
+                    .await
+                    .unwrap_or_else(|e| {
+                        Err(AppError::Other(format!("Command task failed: {}", e)))
+                    });
+
+                let event = CommandEvent {
+                    command,
+                    elapsed: started.elapsed(),
+                    result,
+                };
+                worker_pending.fetch_sub(1, Ordering::SeqCst);
+                let _ = events.send(event);
+                ctx.request_repaint();
+            }
+        });
+
+        Self {
+            sender: CommandSender { commands, pending },
+            events: events_receiver,
+        }
+    }
+
+    /// Get a handle for queueing commands
+    pub fn sender(&self) -> CommandSender {
+        self.sender.clone()
+    }
+
+    /// Queue a command
+    pub fn send(&self, command: Command, ctx: &egui::Context) {
+        self.sender.send(command, ctx);
+    }
+
+    /// Check whether any command is queued or running
+    pub fn is_busy(&self) -> bool {
+        self.sender.is_busy()
+    }
+
+    /// Take the results of the commands finished since the last call
+    pub fn poll(&self) -> Vec<CommandEvent> {
+        self.events.try_iter().collect()
+    }
+}
+
+/// Ask the daemon something on the shared runtime, repainting once the answer is in
+pub fn ask<T: Send + 'static>(
+    daemon: &DaemonClient,
+    ctx: &egui::Context,
+    question: impl FnOnce(&DaemonClient) -> T + Send + 'static,
+) -> Receiver<T> {
+    let (sender, receiver) = mpsc::channel();
+    let daemon = daemon.clone();
+    let ctx = ctx.clone();
+    runtime::shared().spawn_blocking(move || {
+        let _ = sender.send(question(&daemon));
+        ctx.request_repaint();
+    });
+    receiver
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::WallpaperType;
+
+    #[test]
+    fn test_results_arrive_in_order() {
+        let queue = CommandQueue::new(DaemonClient::new());
+        let ctx = egui::Context::default();
+        let wallpaper = WallpaperInfo::new(WallpaperType::Static, Some("a.png".into()), None);
+        queue.send(
+            Command::ApplyWallpaper {
+                wallpaper,
+                intent: Intent::TryOn,
+            },
+            &ctx,
+        );
+        queue.send(
+            Command::StopWallpaper {
+                intent: Intent::Restore,
+            },
+            &ctx,
+        );
+
+        let mut events = Vec::new();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while events.len() < 2 && Instant::now() < deadline {
+            events.extend(queue.poll());
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        // Whether the daemon is running or not, each command reports back
+        assert_eq!(events.len(), 2);
+        assert!(matches!(
+            events[0].command,
+            Command::ApplyWallpaper {
+                intent: Intent::TryOn,
+                ..
+            }
+        ));
+        assert!(matches!(
+            events[1].command,
+            Command::StopWallpaper {
+                intent: Intent::Restore
+            }
+        ));
+        assert!(!queue.is_busy());
+    }
+
+    #[test]
+    fn test_ask() {
+        let ctx = egui::Context::default();
+        let answer = ask(&DaemonClient::new(), &ctx, |_| 42);
+        assert_eq!(answer.recv_timeout(Duration::from_secs(5)).unwrap(), 42);
+    }
+}
@@ -1,13 +1,27 @@
 //! Gallery view for wallpapers
-use crate::core::WallpaperType;
-use crate::platform::WallpaperManager;
-use crate::wallpapers::{AudioWallpaper, ShaderWallpaper, StaticWallpaper, VideoWallpaper, WebWallpaper, Wallpaper};
+use crate::core::adjustments::MAX_BLUR;
+use crate::core::config::ProceduralPattern;
+use crate::core::pack::{self, Pack};
+use crate::core::{
+    AdjustmentStore, Adjustments, PaletteStore, TagStore, WallpaperInfo, WallpaperType,
+    WallpaperUsage,
+};
+use crate::experiments::ai::tagging::TagQueue;
+use crate::ui::app::{wallpaper_type_label, wallpaper_usage_text};
+use crate::ui::commands::{Command, CommandSender, Intent};
+use crate::ui::i18n::{tr, tr_args};
+use crate::ui::image_loader::{ImageLoader, LoadedImage};
+use crate::ui::monitor_layout;
+use crate::ui::preview::WallpaperPreview;
 use eframe::egui;
-use log::{error, info};
+use log::info;
 use rfd::FileDialog;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tokio::runtime::Runtime;
+
+/// Largest edge of a gallery thumbnail in pixels
+const THUMBNAIL_SIZE: u32 = 256;
 
 /// Gallery view for browsing and selecting wallpapers
 pub struct GalleryView {
@@ -15,10 +29,67 @@ pub struct GalleryView {
     wallpapers: Vec<GalleryItem>,
     /// Selected wallpaper index
     selected_index: Option<usize>,
-    /// Runtime for async operations
-    runtime: Arc<Runtime>,
-    /// Wallpaper manager
-    wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>,
+    /// Queue applying wallpapers through the daemon
+    commands: CommandSender,
+    /// Preview of the selected wallpaper
+    preview: WallpaperPreview,
+    /// Thumbnails decoded in the background
+    thumbnails: ImageLoader,
+    /// Average resource use of the wallpapers that ran, by path
+    usage: HashMap<String, WallpaperUsage>,
+    /// Which wallpapers are shown
+    category: Category,
+    /// Whether only portrait images are shown
+    portrait_only: bool,
+    /// Whether each image is taller than it is wide, read once per path
+    portrait: HashMap<PathBuf, bool>,
+    /// Text matched against names and tags
+    search: String,
+    /// Wallpaper whose look-alikes are shown, most alike first, and how they are alike
+    similar_to: Option<(PathBuf, Likeness)>,
+    /// Tags of the wallpapers
+    tags: Arc<TagStore>,
+    /// Dominant colors of the wallpapers
+    palettes: Arc<PaletteStore>,
+    /// Worker tagging added wallpapers, when auto-tagging is on
+    tagger: Option<TagQueue>,
+    /// Brightness, blur and the like of the wallpapers
+    adjustments: Arc<AdjustmentStore>,
+    /// Adjustments being edited, with the wallpaper they're for
+    adjusting: Option<(PathBuf, Adjustments)>,
+}
+
+/// What the gallery asks the app to do
+#[derive(Debug, Clone)]
+pub enum GalleryAction {
+    /// Write these wallpapers to a pack at the path
+    ExportPack {
+        path: PathBuf,
+        wallpapers: Vec<WallpaperInfo>,
+    },
+
+    /// Install the pack at the path
+    InstallPack(PathBuf),
+}
+
+/// Which wallpapers the gallery shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Category {
+    /// Every wallpaper
+    All,
+    /// Wallpapers from files and URLs
+    Library,
+    /// Wallpapers drawn by the app itself, needing no files
+    BuiltIn,
+}
+
+/// How wallpapers shown as alike are compared
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Likeness {
+    /// By what the tagging model sees in them
+    Look,
+    /// By their dominant colors
+    Colors,
 }
 
 /// Information about a wallpaper in the gallery
@@ -45,17 +116,16 @@ pub struct GalleryItem {
 impl GalleryItem {
     /// Create a new gallery item from a path
     pub fn from_path(path: PathBuf, wallpaper_type: WallpaperType) -> Self {
-        let name = path.file_stem()
+        let name = path
+            .file_stem()
             .and_then(|s| s.to_str())
             .unwrap_or("Unknown")
             .to_string();
-        
-        let extension = path.extension()
-            .and_then(|ext| ext.to_str())
-            .unwrap_or("");
-        
+
+        let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+
         let description = format!("{} wallpaper ({})", wallpaper_type.as_str(), extension);
-        
+
         Self {
             name,
             description,
@@ -67,7 +137,7 @@ impl GalleryItem {
             version: "1.0.0".to_string(),
         }
     }
-    
+
     /// Create a new gallery item from a URL
     pub fn from_url(url: String, wallpaper_type: WallpaperType) -> Self {
         Self {
@@ -81,88 +151,293 @@ impl GalleryItem {
             version: "1.0.0".to_string(),
         }
     }
+
+    /// Create a gallery item for a built-in wallpaper, `url` naming its pattern if it has several
+    pub fn builtin(wallpaper_type: WallpaperType, name: &str, url: Option<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            description: format!(
+                "Built-in {} wallpaper",
+                wallpaper_type.as_str().to_lowercase()
+            ),
+            path: None,
+            url,
+            wallpaper_type,
+            thumbnail_path: None,
+            author: "Aether-Desk".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+
+    /// Create a gallery item for a wallpaper of an installed pack
+    pub fn from_pack(wallpaper: &WallpaperInfo, thumbnail: Option<PathBuf>) -> Self {
+        Self {
+            name: wallpaper.name.clone(),
+            description: wallpaper.description.clone(),
+            path: wallpaper.path.clone(),
+            url: wallpaper.url.clone(),
+            wallpaper_type: wallpaper.r#type.clone(),
+            thumbnail_path: thumbnail,
+            author: wallpaper.author.clone(),
+            version: wallpaper.version.clone(),
+        }
+    }
+
+    /// Describe the wallpaper this item shows
+    pub fn wallpaper(&self) -> WallpaperInfo {
+        WallpaperInfo {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            author: self.author.clone(),
+            version: self.version.clone(),
+            r#type: self.wallpaper_type.clone(),
+            path: self.path.clone(),
+            url: self.url.clone(),
+            monitor: None,
+        }
+    }
+
+    /// Get the gallery items of every built-in wallpaper
+    pub fn builtins() -> Vec<Self> {
+        let mut items = vec![
+            Self::builtin(
+                WallpaperType::Particles,
+                WallpaperType::Particles.as_str(),
+                None,
+            ),
+            Self::builtin(
+                WallpaperType::NowPlaying,
+                WallpaperType::NowPlaying.as_str(),
+                None,
+            ),
+        ];
+        items.extend(ProceduralPattern::ALL.iter().map(|pattern| {
+            Self::builtin(
+                WallpaperType::Procedural,
+                pattern.name(),
+                Some(pattern.id().to_string()),
+            )
+        }));
+        items
+    }
 }
 
 impl GalleryView {
     /// Create a new gallery view
-    pub fn new(wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>) -> Self {
+    pub fn new(commands: CommandSender) -> Self {
         Self {
-            wallpapers: Vec::new(),
+            wallpapers: GalleryItem::builtins(),
             selected_index: None,
-            runtime: Arc::new(
-                tokio::runtime::Builder::new_multi_thread()
-                    .enable_all()
-                    .build()
-                    .expect("Failed to create Tokio runtime")
-            ),
-            wallpaper_manager,
+            commands,
+            preview: WallpaperPreview::new(),
+            thumbnails: ImageLoader::new(),
+            usage: HashMap::new(),
+            category: Category::All,
+            portrait_only: false,
+            portrait: HashMap::new(),
+            search: String::new(),
+            similar_to: None,
+            tags: TagStore::shared(),
+            palettes: PaletteStore::shared(),
+            tagger: None,
+            adjustments: AdjustmentStore::shared(),
+            adjusting: None,
         }
     }
-    
+
+    /// Set the worker tagging added wallpapers, tagging those already in the gallery
+    pub fn set_tagger(&mut self, tagger: Option<TagQueue>) {
+        if let Some(tagger) = &tagger {
+            for path in self.wallpapers.iter().filter_map(|item| item.path.as_ref()) {
+                tagger.queue(path);
+            }
+        }
+        self.tagger = tagger;
+    }
+
+    /// Get the worker tagging added wallpapers
+    pub fn tagger(&self) -> Option<&TagQueue> {
+        self.tagger.as_ref()
+    }
+
+    /// Get the indices of the wallpapers to show, in order
+    ///
+    /// Look-alikes of a wallpaper come most alike first; otherwise the
+    /// gallery keeps its order.
+    fn shown(&mut self) -> Vec<usize> {
+        let order: Vec<usize> = match &self.similar_to {
+            Some((target, likeness)) => {
+                let paths: Vec<PathBuf> = self
+                    .wallpapers
+                    .iter()
+                    .filter_map(|item| item.path.clone())
+                    .collect();
+                let ranked = match likeness {
+                    Likeness::Look => self.tags.similar(target, &paths),
+                    Likeness::Colors => self.palettes.similar(target, &paths),
+                };
+                ranked
+                    .into_iter()
+                    .filter_map(|(path, _)| {
+                        self.wallpapers
+                            .iter()
+                            .position(|item| item.path.as_ref() == Some(&path))
+                    })
+                    .collect()
+            }
+            None => (0..self.wallpapers.len()).collect(),
+        };
+
+        let query = self.search.trim().to_lowercase();
+        let matching: Vec<usize> = order
+            .into_iter()
+            .filter(|&index| {
+                let item = &self.wallpapers[index];
+                let in_category = match self.category {
+                    Category::All => true,
+                    Category::Library => !item.wallpaper_type.is_builtin(),
+                    Category::BuiltIn => item.wallpaper_type.is_builtin(),
+                };
+                in_category
+                    && (query.is_empty()
+                        || item.name.to_lowercase().contains(&query)
+                        || item
+                            .path
+                            .as_ref()
+                            .map_or(false, |path| self.tags.matches(path, &query)))
+            })
+            .collect();
+        matching
+            .into_iter()
+            .filter(|&index| !self.portrait_only || self.is_portrait(index))
+            .collect()
+    }
+
+    /// Set the resource use measured for each wallpaper, by path
+    pub fn set_usage(&mut self, usage: HashMap<String, WallpaperUsage>) {
+        self.usage = usage;
+    }
+
+    /// Check whether an item is a portrait image, reading only the image's header
+    fn is_portrait(&mut self, index: usize) -> bool {
+        let item = &self.wallpapers[index];
+        let Some(path) = item
+            .path
+            .clone()
+            .filter(|_| item.wallpaper_type == WallpaperType::Static)
+        else {
+            return false;
+        };
+        *self.portrait.entry(path).or_insert_with_key(|path| {
+            image::image_dimensions(path).map_or(false, |(width, height)| height > width)
+        })
+    }
+
     /// Load wallpapers from a directory
     pub fn load_from_directory(&mut self, directory: &PathBuf, wallpaper_type: WallpaperType) {
+        let mut loaded = Vec::new();
         if let Ok(entries) = std::fs::read_dir(directory) {
             for entry in entries.flatten() {
                 if let Some(file_type) = entry.file_type().ok() {
                     if file_type.is_file() {
                         let path = entry.path();
-                        
+
                         // Check if the file extension matches the wallpaper type
                         if self.is_valid_extension(&path, &wallpaper_type) {
-                            let gallery_item = GalleryItem::from_path(path, wallpaper_type.clone());
-                            self.wallpapers.push(gallery_item);
+                            loaded.push(GalleryItem::from_path(path, wallpaper_type.clone()));
                         }
                     }
                 }
             }
         }
+        let added: Vec<PathBuf> = loaded.iter().filter_map(|item| item.path.clone()).collect();
+        self.insert_into_library(loaded);
+        self.palettes.extract_in_background(added);
+    }
+
+    /// Insert wallpapers at the end of the library, before the built-in ones
+    fn insert_into_library(&mut self, items: Vec<GalleryItem>) {
+        let at = self
+            .wallpapers
+            .iter()
+            .position(|item| item.wallpaper_type.is_builtin())
+            .unwrap_or(self.wallpapers.len());
+        if let Some(selected) = self
+            .selected_index
+            .as_mut()
+            .filter(|selected| **selected >= at)
+        {
+            *selected += items.len();
+        }
+        self.wallpapers.splice(at..at, items);
     }
-    
+
     /// Check if a file has a valid extension for the wallpaper type
     fn is_valid_extension(&self, path: &PathBuf, wallpaper_type: &WallpaperType) -> bool {
-        let extension = path.extension()
-            .and_then(|ext| ext.to_str())
-            .map(|s| s.to_lowercase())
-            .unwrap_or_default();
-        
-        match wallpaper_type {
-            WallpaperType::Static => {
-                extension == "png" || extension == "jpg" || extension == "jpeg" || 
-                extension == "bmp" || extension == "gif"
-            },
-            WallpaperType::Video => {
-                extension == "mp4" || extension == "webm" || extension == "avi" || 
-                extension == "mkv" || extension == "mov" || extension == "wmv"
-            },
-            WallpaperType::Web => {
-                // Web wallpapers are typically URLs, not files
-                false
-            },
-            WallpaperType::Shader => {
-                extension == "glsl" || extension == "frag" || extension == "vert" || 
-                extension == "shader"
-            },
-            WallpaperType::Audio => {
-                extension == "glsl" || extension == "frag" || extension == "vert" || 
-                extension == "shader"
-            },
-        }
+        wallpaper_type.accepts(path)
     }
-    
+
     /// Add a wallpaper to the gallery
     pub fn add_wallpaper(&mut self, item: GalleryItem) {
-        self.wallpapers.push(item);
+        if let (Some(tagger), Some(path)) = (&self.tagger, &item.path) {
+            tagger.queue(path);
+        }
+        if let Some(path) = &item.path {
+            self.palettes.extract_in_background(vec![path.clone()]);
+        }
+        self.insert_into_library(vec![item]);
     }
-    
+
+    /// Add the wallpapers of an installed pack, replacing those of an earlier install
+    pub fn add_pack(&mut self, pack: &Pack) {
+        let installed: Vec<GalleryItem> = pack
+            .wallpapers
+            .iter()
+            .filter(|packed| !packed.wallpaper.r#type.is_builtin())
+            .map(|packed| GalleryItem::from_pack(&packed.wallpaper, packed.thumbnail.clone()))
+            .collect();
+        let earlier: Vec<usize> = self
+            .wallpapers
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| {
+                installed
+                    .iter()
+                    .any(|new| pack::same_wallpaper(&new.wallpaper(), &item.wallpaper()))
+            })
+            .map(|(index, _)| index)
+            .collect();
+        for index in earlier.into_iter().rev() {
+            self.remove_wallpaper(index);
+        }
+        for item in installed {
+            self.add_wallpaper(item);
+        }
+    }
+
     /// Remove a wallpaper from the gallery
     pub fn remove_wallpaper(&mut self, index: usize) -> Option<GalleryItem> {
         if index < self.wallpapers.len() {
+            match self.selected_index {
+                Some(selected) if selected == index => self.selected_index = None,
+                Some(selected) if selected > index => self.selected_index = Some(selected - 1),
+                _ => {}
+            }
             Some(self.wallpapers.remove(index))
         } else {
             None
         }
     }
-    
+
+    /// Get the paths of the static images in the gallery
+    pub fn static_images(&self) -> Vec<PathBuf> {
+        self.wallpapers
+            .iter()
+            .filter(|item| item.wallpaper_type == WallpaperType::Static)
+            .filter_map(|item| item.path.clone())
+            .collect()
+    }
+
     /// Get the selected wallpaper
     pub fn get_selected_wallpaper(&self) -> Option<&GalleryItem> {
         if let Some(index) = self.selected_index {
@@ -171,7 +446,7 @@ impl GalleryView {
             None
         }
     }
-    
+
     /// Set the selected wallpaper by index
     pub fn select_wallpaper(&mut self, index: usize) {
         if index < self.wallpapers.len() {
@@ -179,129 +454,210 @@ impl GalleryView {
         } else {
             self.selected_index = None;
         }
+        self.preview.close();
     }
-    
+
     /// Apply the selected wallpaper
-    pub fn apply_selected_wallpaper(&self) -> Result<(), String> {
-        if let Some(index) = self.selected_index {
-            if let Some(item) = self.wallpapers.get(index) {
-                // Create and start the appropriate wallpaper type
-                let result = match item.wallpaper_type {
-                    WallpaperType::Static => {
-                        if let Some(path) = &item.path {
-                            let wallpaper = StaticWallpaper::new(path, self.wallpaper_manager.clone());
-                            self.runtime.block_on(async {
-                                wallpaper.start().await
-                            })
-                        } else {
-                            return Err("Static wallpaper requires a path".to_string());
-                        }
-                    },
-                    WallpaperType::Video => {
-                        if let Some(path) = &item.path {
-                            let wallpaper = VideoWallpaper::new(path, self.wallpaper_manager.clone());
-                            self.runtime.block_on(async {
-                                wallpaper.start().await
-                            })
-                        } else {
-                            return Err("Video wallpaper requires a path".to_string());
-                        }
-                    },
-                    WallpaperType::Web => {
-                        if let Some(url) = &item.url {
-                            let wallpaper = WebWallpaper::new(url, self.wallpaper_manager.clone());
-                            self.runtime.block_on(async {
-                                wallpaper.start().await
-                            })
-                        } else {
-                            return Err("Web wallpaper requires a URL".to_string());
-                        }
-                    },
-                    WallpaperType::Shader => {
-                        if let Some(path) = &item.path {
-                            let wallpaper = ShaderWallpaper::new(path, self.wallpaper_manager.clone());
-                            self.runtime.block_on(async {
-                                wallpaper.start().await
-                            })
-                        } else {
-                            return Err("Shader wallpaper requires a path".to_string());
-                        }
-                    },
-                    WallpaperType::Audio => {
-                        if let Some(path) = &item.path {
-                            let wallpaper = AudioWallpaper::new(path, self.wallpaper_manager.clone());
-                            self.runtime.block_on(async {
-                                wallpaper.start().await
-                            })
-                        } else {
-                            return Err("Audio wallpaper requires a path".to_string());
-                        }
-                    },
-                };
-                
-                match result {
-                    Ok(_) => {
-                        info!("Applied wallpaper: {}", item.name);
-                        Ok(())
-                    },
-                    Err(e) => {
-                        error!("Failed to apply wallpaper: {}", e);
-                        Err(e.to_string())
-                    }
-                }
-            } else {
-                Err("Selected wallpaper not found".to_string())
+    ///
+    /// The wallpaper is queued; the daemon's answer arrives as a command event.
+    pub fn apply_selected_wallpaper(&self, ctx: &egui::Context) -> Result<(), String> {
+        let item = self
+            .get_selected_wallpaper()
+            .ok_or_else(|| "No wallpaper selected".to_string())?;
+        match item.wallpaper_type {
+            WallpaperType::Web if item.url.is_none() => {
+                return Err("Web wallpaper requires a URL".to_string())
             }
-        } else {
-            Err("No wallpaper selected".to_string())
+            WallpaperType::Web => {}
+            _ if item.path.is_none() && !item.wallpaper_type.is_builtin() => {
+                return Err(format!(
+                    "{} wallpaper requires a path",
+                    item.wallpaper_type.as_str()
+                ));
+            }
+            _ => {}
         }
+
+        let mut wallpaper = WallpaperInfo::new(
+            item.wallpaper_type.clone(),
+            item.path.clone(),
+            item.url.clone(),
+        );
+        wallpaper.name = item.name.clone();
+        info!("Applying wallpaper: {}", item.name);
+        self.commands.send(
+            Command::ApplyWallpaper {
+                wallpaper,
+                intent: Intent::Keep,
+            },
+            ctx,
+        );
+        Ok(())
     }
-    
-    /// Show the gallery view in the UI
-    pub fn show(&mut self, ui: &mut egui::Ui) {
-        ui.heading("Wallpaper Gallery");
-        
+
+    /// Show the gallery view in the UI, returning what the app should do
+    pub fn show(&mut self, ui: &mut egui::Ui) -> Option<GalleryAction> {
+        ui.heading(tr("wallpaper-gallery"));
+        let mut action = None;
+
         // Controls
         ui.horizontal(|ui| {
-            if ui.button("Refresh Gallery").clicked() {
+            if ui.button(tr("refresh-gallery")).clicked() {
                 // In a real implementation, this would reload from configured directories
                 info!("Gallery refresh requested");
             }
-            
-            if ui.button("Add Wallpaper").clicked() {
+
+            if ui.button(tr("add-wallpaper")).clicked() {
                 // Open file dialog to add a wallpaper
                 if let Some(path) = FileDialog::new().pick_file() {
                     // Determine wallpaper type based on extension
                     let wallpaper_type = self.determine_wallpaper_type(&path);
-                    
+
                     if wallpaper_type != WallpaperType::Web {
                         let gallery_item = GalleryItem::from_path(path, wallpaper_type);
                         self.add_wallpaper(gallery_item);
                     }
                 }
             }
-            
-            if let Some(_) = self.get_selected_wallpaper() {
-                if ui.button("Apply Selected").clicked() {
-                    if let Err(e) = self.apply_selected_wallpaper() {
-                        ui.label(egui::RichText::new(format!("Error: {}", e)).color(egui::Color32::RED));
+
+            if let Some(item) = self.get_selected_wallpaper().cloned() {
+                let apply = ui.add_enabled(
+                    !self.commands.is_busy(),
+                    egui::Button::new(tr("apply-selected")),
+                );
+                if apply.clicked() {
+                    if let Err(e) = self.apply_selected_wallpaper(ui.ctx()) {
+                        ui.label(
+                            egui::RichText::new(tr_args("error", &[("error", &e)]))
+                                .color(egui::Color32::RED),
+                        );
+                    }
+                }
+
+                if let Some(path) = &item.path {
+                    let preview_label = if self.preview.is_open() {
+                        tr("close-preview")
+                    } else {
+                        tr("preview")
+                    };
+                    if ui.button(preview_label).clicked() {
+                        if self.preview.is_open() {
+                            self.preview.close();
+                        } else {
+                            self.preview.open(item.wallpaper_type.clone(), path);
+                        }
                     }
                 }
             }
+
+            ui.checkbox(&mut self.portrait_only, tr("portrait-only"))
+                .on_hover_text(tr("portrait-only-hint"));
         });
-        
+
+        // Packs hold the library wallpapers shown, so the search and filters pick what's shared
+        ui.horizontal(|ui| {
+            let exported: Vec<WallpaperInfo> = self
+                .shown()
+                .into_iter()
+                .map(|index| &self.wallpapers[index])
+                .filter(|item| !item.wallpaper_type.is_builtin())
+                .map(GalleryItem::wallpaper)
+                .collect();
+            let export = ui
+                .add_enabled(!exported.is_empty(), egui::Button::new(tr("export-pack")))
+                .on_hover_text(tr_args(
+                    "export-pack-hint",
+                    &[("count", &exported.len().to_string())],
+                ));
+            if export.clicked() {
+                let file_name = format!("{}.{}", tr("wallpaper-pack"), pack::EXTENSION);
+                if let Some(path) = FileDialog::new()
+                    .add_filter(tr("wallpaper-pack"), &[pack::EXTENSION])
+                    .set_file_name(file_name)
+                    .save_file()
+                {
+                    let path = if path.extension().is_some() {
+                        path
+                    } else {
+                        path.with_extension(pack::EXTENSION)
+                    };
+                    action = Some(GalleryAction::ExportPack {
+                        path,
+                        wallpapers: exported,
+                    });
+                }
+            }
+            if ui.button(tr("install-pack")).clicked() {
+                if let Some(path) = FileDialog::new()
+                    .add_filter(tr("wallpaper-pack"), &[pack::EXTENSION])
+                    .pick_file()
+                {
+                    action = Some(GalleryAction::InstallPack(path));
+                }
+            }
+        });
+
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_source("gallery_category")
+                .selected_text(category_label(self.category))
+                .show_ui(ui, |ui| {
+                    for category in [Category::All, Category::Library, Category::BuiltIn] {
+                        ui.selectable_value(&mut self.category, category, category_label(category));
+                    }
+                });
+
+            ui.label(tr("search-gallery"));
+            ui.add(
+                egui::TextEdit::singleline(&mut self.search).hint_text(tr("search-gallery-hint")),
+            );
+
+            let selected_path = self
+                .get_selected_wallpaper()
+                .filter(|item| item.wallpaper_type == WallpaperType::Static)
+                .and_then(|item| item.path.clone());
+            if let Some((target, likeness)) = self.similar_to.clone() {
+                let name = target
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                let label = match likeness {
+                    Likeness::Look => "more-like",
+                    Likeness::Colors => "colors-like",
+                };
+                ui.label(tr_args(label, &[("name", &name)]));
+                if ui.button(tr("show-all")).clicked() {
+                    self.similar_to = None;
+                }
+            } else if let Some(path) = selected_path {
+                if self.tags.is_tagged(&path) && ui.button(tr("more-like-this")).clicked() {
+                    self.similar_to = Some((path.clone(), Likeness::Look));
+                }
+                if ui
+                    .button(tr("similar-colors"))
+                    .on_hover_text(tr("similar-colors-hint"))
+                    .clicked()
+                    && self.palettes.colors(&path).is_some()
+                {
+                    self.palettes.extract_in_background(self.static_images());
+                    self.similar_to = Some((path, Likeness::Colors));
+                }
+            }
+        });
+
         ui.separator();
-        
+
+        let shown = self.shown();
+
         // Gallery grid
         let item_size = egui::Vec2::new(150.0, 200.0);
         let spacing = egui::Vec2::new(10.0, 10.0);
-        
+
         // Calculate how many items fit in a row
         let available_width = ui.available_width();
         let item_width_with_spacing = item_size.x + spacing.x;
         let items_per_row = (available_width / item_width_with_spacing).floor() as usize;
         let items_per_row = items_per_row.max(1); // At least 1 item per row
-        
+
         // Create a grid
         let mut clicked_index = None;
 
@@ -309,7 +665,8 @@ impl GalleryView {
             .num_columns(items_per_row)
             .spacing(spacing)
             .show(ui, |ui| {
-                for (index, item) in self.wallpapers.iter().enumerate() {
+                for (position, &index) in shown.iter().enumerate() {
+                    let item = &self.wallpapers[index];
                     ui.group(|ui| {
                         // Calculate aspect ratio for thumbnail
                         let aspect_ratio = 1.0; // Square thumbnails for now
@@ -317,32 +674,70 @@ impl GalleryView {
                         // Create a square area for the thumbnail
                         let (response, painter) = ui.allocate_painter(
                             egui::Vec2::new(item_size.x, item_size.x * aspect_ratio),
-                            egui::Sense::click()
+                            egui::Sense::click_and_drag(),
                         );
+                        if let Some(path) = item
+                            .path
+                            .as_ref()
+                            .filter(|_| item.wallpaper_type == WallpaperType::Static)
+                        {
+                            monitor_layout::drag_source(&response, path);
+                        }
 
                         // Draw a placeholder for the thumbnail
                         painter.rect_filled(
                             response.rect,
                             egui::Rounding::same(4.0),
-                            ui.visuals().extreme_bg_color
+                            ui.visuals().extreme_bg_color,
                         );
 
-                        // Draw a symbol representing the wallpaper type
+                        // Draw the thumbnail once decoded, or a symbol representing the wallpaper type
+                        let thumbnail =
+                            item.thumbnail_path
+                                .as_ref()
+                                .or(item.path.as_ref().filter(|_| {
+                                    matches!(
+                                        item.wallpaper_type,
+                                        WallpaperType::Static | WallpaperType::AnimatedImage
+                                    )
+                                }));
+                        let mut has_thumbnail = false;
+                        if let Some(path) = thumbnail {
+                            if let LoadedImage::Ready(texture, _) =
+                                self.thumbnails.get(ui.ctx(), path, THUMBNAIL_SIZE)
+                            {
+                                let image_rect = fit_rect(texture.size_vec2(), response.rect);
+                                let uv = egui::Rect::from_min_max(
+                                    egui::pos2(0.0, 0.0),
+                                    egui::pos2(1.0, 1.0),
+                                );
+                                painter.image(texture.id(), image_rect, uv, egui::Color32::WHITE);
+                                has_thumbnail = true;
+                            }
+                        }
                         let text = match item.wallpaper_type {
                             WallpaperType::Static => "🖼️",
+                            WallpaperType::AnimatedStatic => "✨",
+                            WallpaperType::AnimatedImage => "🎞",
                             WallpaperType::Video => "🎬",
                             WallpaperType::Web => "🌐",
                             WallpaperType::Shader => "🎨",
                             WallpaperType::Audio => "🎵",
+                            WallpaperType::Particles => "✴",
+                            WallpaperType::Procedural => "🌌",
+                            WallpaperType::Slideshow => "🗂",
+                            WallpaperType::NowPlaying => "💿",
                         };
 
-                        painter.text(
-                            response.rect.center(),
-                            egui::Align2::CENTER_CENTER,
-                            text,
-                            egui::TextStyle::Heading.resolve(&ui.style()),
-                            ui.visuals().text_color()
-                        );
+                        if !has_thumbnail {
+                            painter.text(
+                                response.rect.center(),
+                                egui::Align2::CENTER_CENTER,
+                                text,
+                                egui::TextStyle::Heading.resolve(&ui.style()),
+                                ui.visuals().text_color(),
+                            );
+                        }
 
                         // Handle selection
                         if response.clicked() {
@@ -354,7 +749,7 @@ impl GalleryView {
                             painter.rect_stroke(
                                 response.rect,
                                 egui::Rounding::same(4.0),
-                                egui::Stroke::new(2.0, ui.visuals().selection.stroke.color)
+                                egui::Stroke::new(2.0, ui.visuals().selection.stroke.color),
                             );
                         }
 
@@ -371,22 +766,24 @@ impl GalleryView {
                         ui.label(egui::RichText::new(desc).size(10.0));
 
                         // Show type badge
-                        let type_text = match item.wallpaper_type {
-                            WallpaperType::Static => "Static",
-                            WallpaperType::Video => "Video",
-                            WallpaperType::Web => "Web",
-                            WallpaperType::Shader => "Shader",
-                            WallpaperType::Audio => "Audio",
-                        };
+                        let type_text = wallpaper_type_label(&item.wallpaper_type);
 
-                        ui.label(egui::RichText::new(type_text)
-                            .monospace()
-                            .color(egui::Color32::WHITE)
+                        ui.label(
+                            egui::RichText::new(type_text)
+                                .monospace()
+                                .color(egui::Color32::WHITE),
                         );
+
+                        if let Some(path) = &item.path {
+                            let tags = self.tags.tags(path);
+                            if !tags.is_empty() {
+                                ui.label(egui::RichText::new(tags.join(" · ")).size(10.0).weak());
+                            }
+                        }
                     });
 
                     // Move to next column, add row break if needed
-                    if (index + 1) % items_per_row != 0 {
+                    if (position + 1) % items_per_row != 0 {
                         ui.end_row();
                     }
                 }
@@ -396,53 +793,138 @@ impl GalleryView {
         if let Some(index) = clicked_index {
             self.select_wallpaper(index);
         }
-        
+
         // Show details of selected wallpaper
+        let mut adjustable = None;
         if let Some(item) = self.get_selected_wallpaper() {
             ui.separator();
-            ui.heading("Selected Wallpaper Details");
-            
-            ui.label(format!("Name: {}", item.name));
-            ui.label(format!("Type: {:?}", item.wallpaper_type));
-            ui.label(format!("Description: {}", item.description));
-            ui.label(format!("Author: {}", item.author));
-            ui.label(format!("Version: {}", item.version));
-            
+            ui.heading(tr("selected-wallpaper-details"));
+
+            ui.label(tr_args("details-name", &[("name", &item.name)]));
+            ui.label(tr_args(
+                "details-type",
+                &[("type", &wallpaper_type_label(&item.wallpaper_type))],
+            ));
+            ui.label(tr_args(
+                "details-description",
+                &[("description", &item.description)],
+            ));
+            ui.label(tr_args("details-author", &[("author", &item.author)]));
+            ui.label(tr_args("details-version", &[("version", &item.version)]));
+
             if let Some(path) = &item.path {
-                ui.label(format!("Path: {}", path.display()));
+                ui.label(tr_args(
+                    "details-path",
+                    &[("path", &path.display().to_string())],
+                ));
+                let tags = self.tags.tags(path);
+                if !tags.is_empty() {
+                    ui.label(tr_args("details-tags", &[("tags", &tags.join(", "))]));
+                }
+                match self.usage.get(path.to_string_lossy().as_ref()) {
+                    Some(usage) => ui.label(tr_args(
+                        "details-usage",
+                        &[("usage", &wallpaper_usage_text(usage))],
+                    )),
+                    None if item.wallpaper_type != WallpaperType::Static => {
+                        ui.label(tr("details-usage-unknown"))
+                    }
+                    None => ui.label(tr("details-usage-static")),
+                };
             }
-            
-            if let Some(url) = &item.url {
-                ui.label(format!("URL: {}", url));
+
+            if item.wallpaper_type.is_builtin() {
+                ui.label(tr("details-builtin-settings"));
+            } else if let Some(url) = &item.url {
+                ui.label(tr_args("details-url", &[("url", url)]));
+            }
+
+            if matches!(
+                item.wallpaper_type,
+                WallpaperType::Static
+                    | WallpaperType::AnimatedStatic
+                    | WallpaperType::AnimatedImage
+            ) {
+                adjustable = item.path.clone();
             }
         }
+        if let Some(path) = adjustable {
+            self.show_adjustments(ui, &path);
+        }
+
+        if self.preview.is_open() {
+            ui.separator();
+            self.preview.show(ui);
+        }
+
+        action
     }
-    
+
+    /// Show sliders adjusting an image, previewed while they move and saved when let go
+    fn show_adjustments(&mut self, ui: &mut egui::Ui, path: &Path) {
+        if self
+            .adjusting
+            .as_ref()
+            .map_or(true, |(adjusting, _)| adjusting != path)
+        {
+            self.adjusting = Some((path.to_path_buf(), self.adjustments.get(path)));
+        }
+        let Some((_, adjustments)) = &mut self.adjusting else {
+            return;
+        };
+
+        let mut save = false;
+        egui::CollapsingHeader::new(tr("adjustments")).show(ui, |ui| {
+            let sliders = [
+                (&mut adjustments.brightness, -1.0..=1.0, "adjust-brightness"),
+                (&mut adjustments.contrast, -1.0..=1.0, "adjust-contrast"),
+                (&mut adjustments.saturation, -1.0..=1.0, "adjust-saturation"),
+                (&mut adjustments.blur, 0.0..=MAX_BLUR, "adjust-blur"),
+                (&mut adjustments.vignette, 0.0..=1.0, "adjust-vignette"),
+            ];
+            for (value, range, label) in sliders {
+                let response = ui.add(egui::Slider::new(value, range).text(tr(label)));
+                save |= response.drag_released() || (response.changed() && !response.dragged());
+            }
+            if ui
+                .add_enabled(
+                    !adjustments.is_neutral(),
+                    egui::Button::new(tr("adjust-reset")),
+                )
+                .clicked()
+            {
+                *adjustments = Adjustments::default();
+                save = true;
+            }
+            ui.label(egui::RichText::new(tr("adjust-hint")).weak());
+        });
+
+        let adjustments = *adjustments;
+        if save {
+            self.adjustments.set(path, adjustments);
+        }
+        if self.preview.is_showing(path) {
+            self.preview.set_adjustments(adjustments);
+        }
+    }
+
     /// Determine wallpaper type based on file extension
     fn determine_wallpaper_type(&self, path: &PathBuf) -> WallpaperType {
-        let extension = path.extension()
-            .and_then(|ext| ext.to_str())
-            .map(|s| s.to_lowercase())
-            .unwrap_or_default();
-        
-        match extension.as_str() {
-            "png" | "jpg" | "jpeg" | "bmp" | "gif" => WallpaperType::Static,
-            "mp4" | "webm" | "avi" | "mkv" | "mov" | "wmv" => WallpaperType::Video,
-            "glsl" | "frag" | "vert" | "shader" => WallpaperType::Shader,
-            _ => WallpaperType::Static, // Default fallback
-        }
+        WallpaperType::from_path(path).unwrap_or(WallpaperType::Static)
     }
 }
 
-impl WallpaperType {
-    /// Get a string representation of the wallpaper type
-    pub fn as_str(&self) -> &'static str {
-        match self {
-            WallpaperType::Static => "Static",
-            WallpaperType::Video => "Video",
-            WallpaperType::Web => "Web",
-            WallpaperType::Shader => "Shader",
-            WallpaperType::Audio => "Audio",
-        }
+/// Label of a gallery category
+fn category_label(category: Category) -> String {
+    match category {
+        Category::All => tr("gallery-all"),
+        Category::Library => tr("gallery-library"),
+        Category::BuiltIn => tr("gallery-builtin"),
     }
-}
\ No newline at end of file
+}
+
+/// Largest rect with the aspect ratio of `size` centered in `rect`
+fn fit_rect(size: egui::Vec2, rect: egui::Rect) -> egui::Rect {
+    let scale = (rect.width() / size.x).min(rect.height() / size.y);
+    egui::Rect::from_center_size(rect.center(), size * scale)
+}
@@ -0,0 +1,365 @@
+//! Wallpaper generation panel
+//!
+//! Prompts are sent to the configured service on a background thread, as
+//! generating takes from seconds to minutes. The candidates are shown as
+//! thumbnails; the one picked is saved to the library and can be applied.
+use crate::core::config::{AiConfig, AiProvider};
+use crate::core::{AppResult, SecretStore};
+use crate::experiments::ai::{self, hosted, GenerationRequest};
+use crate::platform::MonitorInfo;
+use crate::ui::i18n::{tr, tr_args};
+use crate::ui::image_loader::{ImageLoader, LoadedImage};
+use crate::ui::notifications::NotificationCenter;
+use eframe::egui;
+use log::error;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread;
+
+/// Largest edge of a candidate thumbnail in pixels
+const THUMBNAIL_SIZE: u32 = 384;
+
+/// Monitor size used when no monitor can be detected
+const FALLBACK_SIZE: (u32, u32) = (1920, 1080);
+
+/// What the user did with a candidate
+pub enum GenerationAction {
+    /// Saved it to the library
+    Saved(PathBuf),
+
+    /// Saved it to the library and asked to apply it
+    Apply(PathBuf),
+}
+
+/// Panel generating wallpapers from prompts
+#[derive(Default)]
+pub struct GenerationPanel {
+    /// Description of the wallpaper
+    prompt: String,
+
+    /// Monitor generated for (None = the primary one)
+    monitor: Option<String>,
+
+    /// Last candidates generated
+    candidates: Vec<PathBuf>,
+
+    /// Candidate picked
+    selected: Option<usize>,
+
+    /// Prompt and monitor size the candidates were generated with
+    generated_for: Option<(String, (u32, u32))>,
+
+    /// Result of the running generation
+    job: Option<Receiver<AppResult<Vec<PathBuf>>>>,
+
+    /// Candidate thumbnails decoded in the background
+    thumbnails: Option<ImageLoader>,
+
+    /// API key being entered for the hosted service
+    api_key_input: String,
+}
+
+impl GenerationPanel {
+    /// Create an empty panel
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Show the panel, changing `config` when the service settings are edited
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        config: &mut AiConfig,
+        secret_store: &Arc<SecretStore>,
+        monitors: &[MonitorInfo],
+        notifications: &mut NotificationCenter,
+    ) -> Option<GenerationAction> {
+        self.poll_job(notifications);
+
+        ui.heading(tr("generate-wallpaper"));
+        ui.label(tr("generate-hint"));
+        ui.add(
+            egui::TextEdit::multiline(&mut self.prompt)
+                .hint_text(tr("generate-prompt"))
+                .desired_rows(3)
+                .desired_width(f32::INFINITY),
+        );
+        ui.horizontal(|ui| {
+            ui.label(tr("generate-negative-prompt"));
+            ui.text_edit_singleline(&mut config.negative_prompt);
+        });
+
+        let size = self.monitor_size(monitors);
+        ui.horizontal(|ui| {
+            ui.label(tr("generate-for-monitor"));
+            let selected = monitors
+                .iter()
+                .find(|monitor| Some(&monitor.name) == self.monitor.as_ref());
+            egui::ComboBox::from_id_source("generate_monitor")
+                .selected_text(
+                    selected
+                        .map(monitor_label)
+                        .unwrap_or_else(|| format!("{}x{}", size.0, size.1)),
+                )
+                .show_ui(ui, |ui| {
+                    for monitor in monitors {
+                        ui.selectable_value(
+                            &mut self.monitor,
+                            Some(monitor.name.clone()),
+                            monitor_label(monitor),
+                        );
+                    }
+                });
+            ui.label(tr("generate-candidates"));
+            ui.add(egui::DragValue::new(&mut config.candidates).clamp_range(1..=8));
+        });
+
+        ui.horizontal(|ui| {
+            let running = self.job.is_some();
+            if ui
+                .add_enabled(
+                    !running && !self.prompt.trim().is_empty(),
+                    egui::Button::new(tr("generate")),
+                )
+                .clicked()
+            {
+                self.start(ui.ctx(), config, secret_store, size);
+            }
+            if running {
+                ui.spinner();
+                ui.label(tr("generating"));
+            }
+        });
+
+        let action = self.show_candidates(ui, notifications);
+
+        ui.separator();
+        egui::CollapsingHeader::new(tr("generation-service"))
+            .id_source("generation_service")
+            .show(ui, |ui| {
+                self.show_service_settings(ui, config, secret_store, notifications);
+            });
+
+        action
+    }
+
+    /// Get the size of the monitor generated for
+    fn monitor_size(&self, monitors: &[MonitorInfo]) -> (u32, u32) {
+        monitors
+            .iter()
+            .find(|monitor| Some(&monitor.name) == self.monitor.as_ref())
+            .or_else(|| monitors.iter().find(|monitor| monitor.primary))
+            .or(monitors.first())
+            .map_or(FALLBACK_SIZE, |monitor| (monitor.width, monitor.height))
+    }
+
+    /// Start generating candidates in the background
+    fn start(
+        &mut self,
+        ctx: &egui::Context,
+        config: &AiConfig,
+        secret_store: &Arc<SecretStore>,
+        size: (u32, u32),
+    ) {
+        let generator = ai::create_generator(config, secret_store.clone());
+        let (width, height) = ai::generation_size(size.0, size.1, config.max_edge);
+        let request = GenerationRequest {
+            prompt: self.prompt.trim().to_string(),
+            negative_prompt: config.negative_prompt.clone(),
+            width,
+            height,
+            count: config.candidates.max(1),
+        };
+        self.generated_for = Some((request.prompt.clone(), size));
+
+        let (sender, receiver) = mpsc::channel();
+        let ctx = ctx.clone();
+        thread::spawn(move || {
+            let _ = sender.send(ai::generate_candidates(generator.as_ref(), &request));
+            ctx.request_repaint();
+        });
+        self.job = Some(receiver);
+    }
+
+    /// Take the result of the generation once it's done
+    fn poll_job(&mut self, notifications: &mut NotificationCenter) {
+        let Some(result) = self.job.as_ref().and_then(|job| job.try_recv().ok()) else {
+            return;
+        };
+        self.job = None;
+        match result {
+            Ok(candidates) => {
+                if let Some(thumbnails) = self.thumbnails.as_mut() {
+                    for candidate in &self.candidates {
+                        thumbnails.forget(candidate);
+                    }
+                }
+                self.selected = (!candidates.is_empty()).then_some(0);
+                self.candidates = candidates;
+            }
+            Err(e) => {
+                error!("Failed to generate wallpapers: {}", e);
+                notifications.error(tr("error-generate-wallpaper"), &e);
+            }
+        }
+    }
+
+    /// Show the candidates with the buttons saving the picked one
+    fn show_candidates(
+        &mut self,
+        ui: &mut egui::Ui,
+        notifications: &mut NotificationCenter,
+    ) -> Option<GenerationAction> {
+        if self.candidates.is_empty() {
+            return None;
+        }
+
+        ui.separator();
+        let thumbnails = self.thumbnails.get_or_insert_with(ImageLoader::new);
+        let width =
+            (ui.available_width() / self.candidates.len().min(4) as f32 - 12.0).clamp(96.0, 320.0);
+        ui.horizontal_wrapped(|ui| {
+            for (index, candidate) in self.candidates.iter().enumerate() {
+                let selected = self.selected == Some(index);
+                let response = match thumbnails.get(ui.ctx(), candidate, THUMBNAIL_SIZE) {
+                    LoadedImage::Ready(texture, _) => {
+                        let size = texture.size_vec2() * (width / texture.size_vec2().x);
+                        ui.add(egui::ImageButton::new((texture.id(), size)).selected(selected))
+                    }
+                    LoadedImage::Loading => {
+                        ui.add_sized([width, width * 9.0 / 16.0], egui::Spinner::new())
+                    }
+                    LoadedImage::Failed(e) => ui.label(tr_args("error", &[("error", e)])),
+                };
+                if response.clicked() {
+                    self.selected = Some(index);
+                }
+            }
+        });
+
+        let (Some(candidate), Some((prompt, (width, height)))) = (
+            self.selected.and_then(|index| self.candidates.get(index)),
+            &self.generated_for,
+        ) else {
+            return None;
+        };
+        let mut action = None;
+        ui.horizontal(|ui| {
+            let apply = ui.button(tr("generate-apply")).clicked();
+            if apply || ui.button(tr("generate-save")).clicked() {
+                match ai::save_to_library(candidate, prompt, *width, *height) {
+                    Ok(path) => {
+                        action = Some(if apply {
+                            GenerationAction::Apply(path)
+                        } else {
+                            GenerationAction::Saved(path)
+                        })
+                    }
+                    Err(e) => {
+                        error!("Failed to save generated wallpaper: {}", e);
+                        notifications.error(tr("error-save-generated"), &e);
+                    }
+                }
+            }
+            ui.label(tr_args(
+                "generate-saved-size",
+                &[
+                    ("width", &width.to_string()),
+                    ("height", &height.to_string()),
+                ],
+            ));
+        });
+        action
+    }
+
+    /// Show the settings of the generation service
+    fn show_service_settings(
+        &mut self,
+        ui: &mut egui::Ui,
+        config: &mut AiConfig,
+        secret_store: &Arc<SecretStore>,
+        notifications: &mut NotificationCenter,
+    ) {
+        ui.horizontal(|ui| {
+            ui.label(tr("generation-provider"));
+            egui::ComboBox::from_id_source("generation_provider")
+                .selected_text(provider_label(config.provider))
+                .show_ui(ui, |ui| {
+                    for provider in [
+                        AiProvider::StableDiffusion,
+                        AiProvider::ComfyUi,
+                        AiProvider::Hosted,
+                    ] {
+                        ui.selectable_value(
+                            &mut config.provider,
+                            provider,
+                            provider_label(provider),
+                        );
+                    }
+                });
+        });
+        ui.horizontal(|ui| {
+            ui.label(tr("generation-endpoint"));
+            ui.add(
+                egui::TextEdit::singleline(&mut config.endpoint)
+                    .hint_text(config.provider.default_endpoint()),
+            );
+        });
+        ui.horizontal(|ui| {
+            ui.label(tr("generation-model"));
+            ui.text_edit_singleline(&mut config.model);
+        });
+        if config.provider == AiProvider::ComfyUi {
+            ui.label(tr("generation-model-comfyui-hint"));
+        }
+
+        if config.provider == AiProvider::Hosted {
+            ui.horizontal(|ui| {
+                ui.label(tr("generation-api-key"));
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.api_key_input)
+                        .password(true)
+                        .hint_text(tr("stored-securely")),
+                );
+                if ui.button(tr("save-key")).clicked() {
+                    let result = if self.api_key_input.is_empty() {
+                        secret_store.delete(hosted::API_KEY_SECRET)
+                    } else {
+                        secret_store.set(hosted::API_KEY_SECRET, &self.api_key_input)
+                    };
+                    if let Err(e) = result {
+                        error!("Failed to store API key: {}", e);
+                        notifications.error(tr("error-store-api-key"), &e);
+                    }
+                    self.api_key_input.clear();
+                }
+            });
+        } else {
+            ui.horizontal(|ui| {
+                ui.label(tr("generation-steps"));
+                ui.add(egui::DragValue::new(&mut config.steps).clamp_range(1..=150));
+                ui.label(tr("generation-max-edge"));
+                ui.add(
+                    egui::DragValue::new(&mut config.max_edge)
+                        .clamp_range(512..=2048)
+                        .speed(64.0),
+                );
+            });
+        }
+    }
+}
+
+/// Describe a monitor in the monitor list
+fn monitor_label(monitor: &MonitorInfo) -> String {
+    format!("{} ({}x{})", monitor.name, monitor.width, monitor.height)
+}
+
+/// Get the display name of a generation service
+fn provider_label(provider: AiProvider) -> String {
+    match provider {
+        AiProvider::StableDiffusion => "Stable Diffusion WebUI".to_string(),
+        AiProvider::ComfyUi => "ComfyUI".to_string(),
+        AiProvider::Hosted => tr("generation-provider-hosted"),
+    }
+}
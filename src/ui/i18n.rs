@@ -0,0 +1,255 @@
+//! UI translations
+//!
+//! Messages live in Fluent `.ftl` files under `locales/`, are embedded at
+//! build time and formatted by `fluent-bundle`, so placeables, plural
+//! selectors and terms all work.
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource, FluentValue};
+use log::{debug, error, warn};
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+use unic_langid::LanguageIdentifier;
+
+/// Locale used when a message or language is missing
+pub const FALLBACK_LANGUAGE: &str = "en";
+
+/// Shipped locale
+#[derive(Debug, Clone, Copy)]
+pub struct Locale {
+    /// Language code
+    pub code: &'static str,
+
+    /// Native language name
+    pub name: &'static str,
+
+    /// Message source
+    source: &'static str,
+}
+
+/// Shipped locales
+pub const LOCALES: &[Locale] = &[
+    Locale {
+        code: "en",
+        name: "English",
+        source: include_str!("../../locales/en.ftl"),
+    },
+    Locale {
+        code: "es",
+        name: "Español",
+        source: include_str!("../../locales/es.ftl"),
+    },
+];
+
+/// Message bundle of one language
+type Bundle = FluentBundle<FluentResource>;
+
+/// Message bundles by language code
+static CATALOGS: OnceLock<HashMap<&'static str, Bundle>> = OnceLock::new();
+
+/// Current language code
+static CURRENT_LANGUAGE: RwLock<String> = RwLock::new(String::new());
+
+/// Get the message bundles
+fn catalogs() -> &'static HashMap<&'static str, Bundle> {
+    CATALOGS.get_or_init(|| {
+        LOCALES
+            .iter()
+            .map(|locale| (locale.code, bundle(locale.code, locale.source)))
+            .collect()
+    })
+}
+
+/// Build the message bundle of a language from its Fluent source
+///
+/// Messages that fail to parse are left out and logged; the rest still load.
+fn bundle(code: &str, source: &str) -> Bundle {
+    let resource =
+        FluentResource::try_new(source.to_string()).unwrap_or_else(|(resource, errors)| {
+            for e in errors {
+                error!("Invalid message in {}.ftl: {:?}", code, e);
+            }
+            resource
+        });
+
+    let language: LanguageIdentifier = code.parse().unwrap_or_default();
+    let mut bundle = FluentBundle::new_concurrent(vec![language]);
+    // egui draws the bidi isolation marks Fluent puts around placeables
+    bundle.set_use_isolating(false);
+    if let Err(errors) = bundle.add_resource(resource) {
+        for e in errors {
+            error!("Duplicate message in {}.ftl: {:?}", code, e);
+        }
+    }
+    bundle
+}
+
+/// Set the UI language
+///
+/// `None` selects the system language. Unsupported languages fall back to English.
+pub fn set_language(code: Option<&str>) {
+    let requested = code.map(|c| c.to_string()).unwrap_or_else(system_language);
+    let code = if catalogs().contains_key(requested.as_str()) {
+        requested
+    } else {
+        debug!(
+            "No translation for '{}', using {}",
+            requested, FALLBACK_LANGUAGE
+        );
+        FALLBACK_LANGUAGE.to_string()
+    };
+
+    *CURRENT_LANGUAGE.write().unwrap() = code;
+}
+
+/// Get the current UI language code
+pub fn language() -> String {
+    let current = CURRENT_LANGUAGE.read().unwrap();
+    if current.is_empty() {
+        FALLBACK_LANGUAGE.to_string()
+    } else {
+        current.clone()
+    }
+}
+
+/// Translate a message
+pub fn tr(id: &str) -> String {
+    tr_args(id, &[])
+}
+
+/// Translate a message with placeholder values
+pub fn tr_args(id: &str, args: &[(&str, &str)]) -> String {
+    let catalogs = catalogs();
+    let language = language();
+    let message = [language.as_str(), FALLBACK_LANGUAGE]
+        .into_iter()
+        .filter_map(|code| catalogs.get(code))
+        .find_map(|bundle| format(bundle, id, args));
+
+    message.unwrap_or_else(|| {
+        warn!("Missing translation for '{}'", id);
+        id.to_string()
+    })
+}
+
+/// Format a message of a bundle, or None when the bundle lacks it
+///
+/// Values that read as numbers are passed as numbers, so plural selectors match them.
+fn format(bundle: &Bundle, id: &str, args: &[(&str, &str)]) -> Option<String> {
+    let pattern = bundle.get_message(id)?.value()?;
+
+    let mut fluent_args = FluentArgs::new();
+    for (name, value) in args {
+        fluent_args.set(*name, FluentValue::try_number(value));
+    }
+
+    let mut errors = Vec::new();
+    let message = bundle.format_pattern(pattern, Some(&fluent_args), &mut errors);
+    if !errors.is_empty() {
+        warn!("Failed to format '{}': {:?}", id, errors);
+    }
+    Some(message.into_owned())
+}
+
+/// Get the system language code (e.g. "es" for "es_ES.UTF-8")
+pub fn system_language() -> String {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty() && value != "C" && value != "POSIX")
+        .map(|value| language_code(&value))
+        .unwrap_or_else(|| FALLBACK_LANGUAGE.to_string())
+}
+
+/// Reduce a locale name to its language code
+fn language_code(locale: &str) -> String {
+    locale
+        .split(['_', '-', '.', '@'])
+        .next()
+        .unwrap_or(FALLBACK_LANGUAGE)
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_placeables_and_multiline() {
+        let source = "# Comment\nhello = Hello, { $name }!\ntight = Hi {$name}\nmultiline =\n    First\n    Second\n";
+        let bundle = bundle("en", source);
+        assert_eq!(
+            format(&bundle, "hello", &[("name", "World")]).unwrap(),
+            "Hello, World!"
+        );
+        assert_eq!(
+            format(&bundle, "tight", &[("name", "World")]).unwrap(),
+            "Hi World"
+        );
+        assert_eq!(format(&bundle, "multiline", &[]).unwrap(), "First\nSecond");
+        assert_eq!(format(&bundle, "missing", &[]), None);
+    }
+
+    #[test]
+    fn test_plurals() {
+        let source = "items = { $count ->\n    [one] One item\n   *[other] { $count } items\n}\n";
+        let bundle = bundle("en", source);
+        assert_eq!(
+            format(&bundle, "items", &[("count", "1")]).unwrap(),
+            "One item"
+        );
+        assert_eq!(
+            format(&bundle, "items", &[("count", "3")]).unwrap(),
+            "3 items"
+        );
+    }
+
+    #[test]
+    fn test_language_code() {
+        assert_eq!(language_code("es_ES.UTF-8"), "es");
+        assert_eq!(language_code("pt-BR"), "pt");
+        assert_eq!(language_code("en"), "en");
+    }
+
+    #[test]
+    fn test_locales_define_the_same_messages() {
+        // Message IDs start a line, unlike attributes, variants and continuation lines
+        let ids = |locale: &Locale| {
+            locale
+                .source
+                .lines()
+                .filter_map(|line| line.split_once(" ="))
+                .map(|(id, _)| id)
+                .filter(|id| {
+                    id.chars().next().is_some_and(|c| c.is_ascii_alphabetic()) && !id.contains(' ')
+                })
+                .collect::<HashSet<_>>()
+        };
+        let catalogs = catalogs();
+        let english = &catalogs[FALLBACK_LANGUAGE];
+        for locale in LOCALES {
+            assert!(
+                FluentResource::try_new(locale.source.to_string()).is_ok(),
+                "{}.ftl does not parse",
+                locale.code
+            );
+            let catalog = &catalogs[locale.code];
+            for id in ids(&LOCALES[0]) {
+                assert!(
+                    catalog.has_message(id),
+                    "{} is missing '{}'",
+                    locale.code,
+                    id
+                );
+            }
+            for id in ids(locale) {
+                assert!(
+                    english.has_message(id),
+                    "{} has unknown message '{}'",
+                    locale.code,
+                    id
+                );
+            }
+        }
+    }
+}
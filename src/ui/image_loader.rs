@@ -0,0 +1,297 @@
+//! Background image decoding for thumbnails and previews
+//!
+//! Images are decoded one at a time on a worker thread, scaled down right
+//! away and uploaded as textures that are kept for reuse. Images larger than
+//! `MAX_IMAGE_EDGE` or needing more than `MAX_DECODED_BYTES` to decode are
+//! refused instead of stalling the UI or exhausting memory.
+use crate::core::image_format::{self, ExtraFormat};
+use crate::core::image_pipeline::ImagePipeline;
+use crate::core::{AppError, AppResult};
+use eframe::egui;
+use image::io::{Limits, Reader};
+use log::{debug, warn};
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Largest width or height of an image that is decoded
+pub const MAX_IMAGE_EDGE: u32 = 16384;
+
+/// Most memory a decoder may use for one image
+pub const MAX_DECODED_BYTES: u64 = 512 * 1024 * 1024;
+
+/// Number of textures kept for reuse
+const TEXTURE_CAPACITY: usize = 128;
+
+/// Image scaled to fit a size
+type ImageKey = (PathBuf, u32);
+
+/// Image decoded by the worker, with the original size
+struct Decoded {
+    /// Scaled-down pixels
+    image: egui::ColorImage,
+
+    /// Original size in pixels
+    size: [u32; 2],
+}
+
+/// Load state of an image
+enum Entry {
+    /// Queued or being decoded
+    Loading,
+
+    /// Uploaded texture and the original size
+    Ready(egui::TextureHandle, [u32; 2]),
+
+    /// Decoding failed
+    Failed(String),
+}
+
+/// Image as seen by the UI
+pub enum LoadedImage<'a> {
+    /// Still decoding
+    Loading,
+
+    /// Scaled-down texture and the original size in pixels
+    Ready(&'a egui::TextureHandle, [u32; 2]),
+
+    /// Decoding failed
+    Failed(&'a str),
+}
+
+/// Decodes images in the background and keeps their textures
+pub struct ImageLoader {
+    /// Requests for the worker thread
+    requests: Sender<(ImageKey, egui::Context)>,
+
+    /// Images decoded by the worker, waiting for upload
+    decoded: Arc<Mutex<Vec<(ImageKey, AppResult<Decoded>)>>>,
+
+    /// Load states by image
+    entries: HashMap<ImageKey, Entry>,
+
+    /// Images by last use, least recently used first
+    order: VecDeque<ImageKey>,
+}
+
+impl ImageLoader {
+    /// Create a loader with its worker thread
+    pub fn new() -> Self {
+        let (requests, receiver) = mpsc::channel();
+        let decoded = Arc::new(Mutex::new(Vec::new()));
+        spawn_worker(receiver, decoded.clone());
+
+        Self {
+            requests,
+            decoded,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Get an image scaled to fit `max_edge` pixels, starting to decode it if needed
+    pub fn get(&mut self, ctx: &egui::Context, path: &Path, max_edge: u32) -> LoadedImage<'_> {
+        self.upload_decoded(ctx);
+
+        let key = (path.to_path_buf(), max_edge);
+        if !self.entries.contains_key(&key) {
+            if self.requests.send((key.clone(), ctx.clone())).is_err() {
+                return LoadedImage::Failed("Image decoder stopped");
+            }
+            self.entries.insert(key.clone(), Entry::Loading);
+        }
+        self.touch(&key);
+
+        match &self.entries[&key] {
+            Entry::Loading => LoadedImage::Loading,
+            Entry::Ready(texture, size) => LoadedImage::Ready(texture, *size),
+            Entry::Failed(e) => LoadedImage::Failed(e),
+        }
+    }
+
+    /// Forget an image so it's decoded again, e.g. after the file changed
+    pub fn forget(&mut self, path: &Path) {
+        self.entries.retain(|(entry_path, _), _| entry_path != path);
+        self.order.retain(|(entry_path, _)| entry_path != path);
+    }
+
+    /// Upload the images the worker finished
+    fn upload_decoded(&mut self, ctx: &egui::Context) {
+        let decoded = std::mem::take(&mut *self.decoded.lock().unwrap());
+        for (key, result) in decoded {
+            // Dropped from the cache while decoding
+            if !self.entries.contains_key(&key) {
+                continue;
+            }
+            let entry = match result {
+                Ok(decoded) => {
+                    let name = format!("image:{}:{}", key.0.display(), key.1);
+                    Entry::Ready(
+                        ctx.load_texture(name, decoded.image, egui::TextureOptions::LINEAR),
+                        decoded.size,
+                    )
+                }
+                Err(e) => Entry::Failed(e.to_string()),
+            };
+            self.entries.insert(key, entry);
+        }
+    }
+
+    /// Mark an image as used and drop the least recently used textures
+    fn touch(&mut self, key: &ImageKey) {
+        self.order.retain(|entry| entry != key);
+        self.order.push_back(key.clone());
+
+        while self.order.len() > TEXTURE_CAPACITY {
+            // Images still decoding stay so they aren't requested twice
+            let Some(index) = self
+                .order
+                .iter()
+                .position(|entry| !matches!(self.entries.get(entry), Some(Entry::Loading)))
+            else {
+                break;
+            };
+            if let Some(evicted) = self.order.remove(index) {
+                self.entries.remove(&evicted);
+            }
+        }
+    }
+}
+
+impl Default for ImageLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Decode requested images until the loader is dropped
+fn spawn_worker(
+    requests: Receiver<(ImageKey, egui::Context)>,
+    decoded: Arc<Mutex<Vec<(ImageKey, AppResult<Decoded>)>>>,
+) {
+    thread::spawn(move || {
+        for ((path, max_edge), ctx) in requests {
+            let result = decode_image(&path, max_edge).map(|(image, size)| Decoded { image, size });
+            if let Err(e) = &result {
+                warn!("Failed to decode {}: {}", path.display(), e);
+            }
+            decoded.lock().unwrap().push(((path, max_edge), result));
+            ctx.request_repaint();
+        }
+        debug!("Image decoder stopped");
+    });
+}
+
+/// Decode an image scaled down to fit `max_edge` pixels
+///
+/// Returns the scaled image and the original size. Images over the size and
+/// memory bounds are refused before their pixels are decoded, except HEIC and
+/// JPEG XL images, which are decoded outside the image crate.
+pub fn decode_image(path: &Path, max_edge: u32) -> AppResult<(egui::ColorImage, [u32; 2])> {
+    if matches!(
+        ExtraFormat::of(path),
+        Some(ExtraFormat::Heif | ExtraFormat::Jxl)
+    ) {
+        let image = image_format::open(path)?;
+        check_size(path, image.width(), image.height())?;
+        return Ok((thumbnail(&image, max_edge), [image.width(), image.height()]));
+    }
+
+    let open_error = |e: &dyn std::fmt::Display| {
+        AppError::WallpaperError(format!("Failed to open {}: {}", path.display(), e))
+    };
+
+    let (width, height) = Reader::open(path)?
+        .with_guessed_format()?
+        .into_dimensions()
+        .map_err(|e| open_error(&e))?;
+    check_size(path, width, height)?;
+
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_IMAGE_EDGE);
+    limits.max_image_height = Some(MAX_IMAGE_EDGE);
+    limits.max_alloc = Some(MAX_DECODED_BYTES);
+
+    let mut reader = Reader::open(path)?.with_guessed_format()?;
+    reader.limits(limits);
+    let image = reader.decode().map_err(|e| open_error(&e))?;
+    Ok((thumbnail(&image, max_edge), [width, height]))
+}
+
+/// Refuse images larger than `MAX_IMAGE_EDGE`
+fn check_size(path: &Path, width: u32, height: u32) -> AppResult<()> {
+    if width > MAX_IMAGE_EDGE || height > MAX_IMAGE_EDGE {
+        return Err(AppError::WallpaperError(format!(
+            "{} is {}x{} pixels; images up to {}x{} can be shown",
+            path.display(),
+            width,
+            height,
+            MAX_IMAGE_EDGE,
+            MAX_IMAGE_EDGE
+        )));
+    }
+    Ok(())
+}
+
+/// Scale an image down to fit `max_edge` pixels for a texture
+fn thumbnail(image: &image::DynamicImage, max_edge: u32) -> egui::ColorImage {
+    let thumbnail = ImagePipeline::of(image).thumbnail(max_edge).into_rgba8();
+    egui::ColorImage::from_rgba_unmultiplied(
+        [thumbnail.width() as usize, thumbnail.height() as usize],
+        thumbnail.as_raw(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_decode_image_scales_down() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("wide.png");
+        image::RgbImage::new(800, 200).save(&path).unwrap();
+
+        let (scaled, size) = decode_image(&path, 100).unwrap();
+        assert_eq!(size, [800, 200]);
+        assert_eq!(scaled.size, [100, 25]);
+
+        assert!(decode_image(&dir.path().join("missing.png"), 100).is_err());
+    }
+
+    #[test]
+    fn test_decode_image_refuses_huge_images() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("huge.png");
+        // A PNG header claiming 20000x20000 pixels is enough; the pixels are never read
+        image::RgbImage::new(1, 1).save(&path).unwrap();
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes[16..20].copy_from_slice(&20000u32.to_be_bytes());
+        bytes[20..24].copy_from_slice(&20000u32.to_be_bytes());
+        let crc = crc32(&bytes[12..29]);
+        bytes[29..33].copy_from_slice(&crc.to_be_bytes());
+        std::fs::write(&path, bytes).unwrap();
+
+        let e = decode_image(&path, 100).unwrap_err().to_string();
+        assert!(e.contains("20000x20000"), "{}", e);
+    }
+
+    /// CRC-32 of a PNG chunk's type and data
+    fn crc32(bytes: &[u8]) -> u32 {
+        let mut crc = !0u32;
+        for byte in bytes {
+            crc ^= *byte as u32;
+            for _ in 0..8 {
+                crc = if crc & 1 != 0 {
+                    (crc >> 1) ^ 0xEDB8_8320
+                } else {
+                    crc >> 1
+                };
+            }
+        }
+        !crc
+    }
+}
@@ -0,0 +1,100 @@
+//! Single settings window per user
+//!
+//! The first window listens on its own local endpoint. Later launches send
+//! their arguments there and exit, and the running window comes to the front.
+use crate::core::AppResult;
+use crate::daemon::transport::{self, WINDOW_ENDPOINT};
+use eframe::egui;
+use log::{debug, info, warn};
+use std::io::{BufRead, BufReader, Write};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Send the arguments to a running window
+///
+/// Fails when no window is running.
+pub fn forward(args: &[String]) -> AppResult<()> {
+    let mut stream = transport::connect(WINDOW_ENDPOINT)?;
+    let mut line = serde_json::to_string(args)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())?;
+    stream.flush()?;
+    info!("Forwarded arguments to the running window");
+    Ok(())
+}
+
+/// Proof that this process owns the settings window
+///
+/// Receives the arguments of later launches.
+pub struct InstanceGuard {
+    /// Forwarded argument lists
+    forwarded: Receiver<Vec<String>>,
+
+    /// Context to wake when arguments arrive
+    ctx: Arc<Mutex<Option<egui::Context>>>,
+}
+
+impl InstanceGuard {
+    /// Claim the window endpoint
+    ///
+    /// Fails if another window already owns it.
+    pub fn acquire() -> AppResult<Self> {
+        let listener = transport::bind(WINDOW_ENDPOINT)?;
+        let (sender, forwarded) = mpsc::channel();
+        let ctx: Arc<Mutex<Option<egui::Context>>> = Arc::new(Mutex::new(None));
+
+        let repaint = ctx.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(e) => {
+                        warn!("Failed to accept forwarded arguments: {}", e);
+                        continue;
+                    }
+                };
+                let _ = stream.set_read_timeout(Some(transport::IO_TIMEOUT));
+
+                let mut line = String::new();
+                if let Err(e) = BufReader::new(stream).read_line(&mut line) {
+                    warn!("Failed to read forwarded arguments: {}", e);
+                    continue;
+                }
+                match serde_json::from_str::<Vec<String>>(&line) {
+                    Ok(args) => {
+                        debug!("Received forwarded arguments: {:?}", args);
+                        if sender.send(args).is_err() {
+                            break;
+                        }
+                        if let Some(ctx) = repaint.lock().unwrap().as_ref() {
+                            ctx.request_repaint();
+                        }
+                    }
+                    Err(e) => warn!("Invalid forwarded arguments: {}", e),
+                }
+            }
+        });
+
+        Ok(Self { forwarded, ctx })
+    }
+
+    /// Set the context to wake when arguments arrive
+    pub fn set_context(&self, ctx: &egui::Context) {
+        let mut current = self.ctx.lock().unwrap();
+        if current.is_none() {
+            *current = Some(ctx.clone());
+        }
+    }
+
+    /// Take the argument lists forwarded since the last call
+    pub fn take_forwarded(&self) -> Vec<Vec<String>> {
+        self.forwarded.try_iter().collect()
+    }
+}
+
+impl Drop for InstanceGuard {
+    fn drop(&mut self) {
+        transport::cleanup(WINDOW_ENDPOINT);
+    }
+}
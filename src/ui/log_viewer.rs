@@ -0,0 +1,203 @@
+//! Viewer for the log files
+//!
+//! Shows the end of the window's or the daemon's log, filtered by level and
+//! text, and copies what is shown for bug reports.
+use crate::core::config::LogLevel;
+use crate::core::logging::{self, LogFile, LogLine};
+use crate::ui::i18n::{tr, tr_args};
+use eframe::egui;
+use log::LevelFilter;
+use std::time::{Duration, Instant};
+
+/// Number of lines read from the end of a log
+const MAX_LINES: usize = 2000;
+
+/// Time between re-reads of the log while it is shown
+const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Log tab state
+#[derive(Debug)]
+pub struct LogViewer {
+    /// Log being shown
+    source: LogFile,
+
+    /// Least severe level shown
+    shown: LevelFilter,
+
+    /// Text lines must contain, ignoring case
+    search: String,
+
+    /// Lines read from the log
+    lines: Vec<LogLine>,
+
+    /// Error reading the log
+    error: Option<String>,
+
+    /// Last time the log was read
+    last_refresh: Option<Instant>,
+}
+
+impl LogViewer {
+    /// Create a viewer for the window's log
+    pub fn new() -> Self {
+        Self {
+            source: LogFile::App,
+            shown: LevelFilter::Trace,
+            search: String::new(),
+            lines: Vec::new(),
+            error: None,
+            last_refresh: None,
+        }
+    }
+
+    /// Show the viewer and the setting for the level written to the logs
+    ///
+    /// Returns true when the user changed `level`.
+    pub fn show(&mut self, ui: &mut egui::Ui, level: &mut LogLevel) -> bool {
+        let before = *level;
+        ui.horizontal(|ui| {
+            ui.label(tr("log-level"));
+            egui::ComboBox::from_id_source("log_level")
+                .selected_text(log_level_label(*level))
+                .show_ui(ui, |ui| {
+                    for option in [
+                        LogLevel::Error,
+                        LogLevel::Warn,
+                        LogLevel::Info,
+                        LogLevel::Debug,
+                        LogLevel::Trace,
+                    ] {
+                        ui.selectable_value(level, option, log_level_label(option));
+                    }
+                });
+        });
+        ui.label(tr("log-level-hint"));
+        ui.separator();
+
+        let source = self.source;
+        ui.horizontal(|ui| {
+            for (file, label) in [
+                (LogFile::App, tr("log-source-app")),
+                (LogFile::Daemon, tr("log-source-daemon")),
+            ] {
+                ui.selectable_value(&mut self.source, file, label);
+            }
+            ui.separator();
+            egui::ComboBox::from_id_source("log_shown")
+                .selected_text(shown_label(self.shown))
+                .show_ui(ui, |ui| {
+                    for option in [
+                        LevelFilter::Error,
+                        LevelFilter::Warn,
+                        LevelFilter::Info,
+                        LevelFilter::Debug,
+                        LevelFilter::Trace,
+                    ] {
+                        ui.selectable_value(&mut self.shown, option, shown_label(option));
+                    }
+                });
+            ui.add(
+                egui::TextEdit::singleline(&mut self.search)
+                    .hint_text(tr("log-search"))
+                    .desired_width(160.0),
+            );
+        });
+        if self.source != source {
+            self.last_refresh = None;
+        }
+        self.refresh();
+        ui.ctx().request_repaint_after(REFRESH_INTERVAL);
+
+        let search = self.search.to_lowercase();
+        let shown: Vec<&LogLine> = self
+            .lines
+            .iter()
+            .filter(|line| line.level.map_or(true, |level| level <= self.shown))
+            .filter(|line| search.is_empty() || line.text.to_lowercase().contains(&search))
+            .collect();
+
+        ui.horizontal(|ui| {
+            if ui.button(tr("copy-log")).clicked() {
+                let text = shown
+                    .iter()
+                    .map(|line| line.text.as_str())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                ui.output_mut(|output| output.copied_text = text);
+            }
+            if ui.button(tr("refresh-log")).clicked() {
+                self.last_refresh = None;
+            }
+            ui.label(tr_args(
+                "log-file",
+                &[("path", &self.source.path().display().to_string())],
+            ));
+        });
+
+        if let Some(error) = &self.error {
+            ui.colored_label(
+                egui::Color32::RED,
+                tr_args("error-read-log", &[("error", error)]),
+            );
+        } else if shown.is_empty() {
+            ui.label(tr("log-empty"));
+        }
+
+        egui::ScrollArea::both()
+            .stick_to_bottom(true)
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                for line in shown {
+                    let text = egui::RichText::new(&line.text).monospace();
+                    let text = match line.level {
+                        Some(log::Level::Error) => text.color(egui::Color32::RED),
+                        Some(log::Level::Warn) => text.color(egui::Color32::YELLOW),
+                        _ => text,
+                    };
+                    ui.label(text);
+                }
+            });
+
+        *level != before
+    }
+
+    /// Re-read the log when it is due
+    fn refresh(&mut self) {
+        if self
+            .last_refresh
+            .map_or(false, |last| last.elapsed() < REFRESH_INTERVAL)
+        {
+            return;
+        }
+        self.last_refresh = Some(Instant::now());
+        match logging::read_tail(self.source, MAX_LINES) {
+            Ok(lines) => {
+                self.lines = lines;
+                self.error = None;
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+    }
+}
+
+/// Label of a log level setting
+fn log_level_label(level: LogLevel) -> String {
+    match level {
+        LogLevel::Error => tr("log-level-error"),
+        LogLevel::Warn => tr("log-level-warn"),
+        LogLevel::Info => tr("log-level-info"),
+        LogLevel::Debug => tr("log-level-debug"),
+        LogLevel::Trace => tr("log-level-trace"),
+    }
+}
+
+/// Label of the least severe level shown
+fn shown_label(level: LevelFilter) -> String {
+    match level {
+        LevelFilter::Off | LevelFilter::Error => tr("log-show-error"),
+        LevelFilter::Warn => tr("log-show-warn"),
+        LevelFilter::Info => tr("log-show-info"),
+        LevelFilter::Debug => tr("log-show-debug"),
+        LevelFilter::Trace => tr("log-show-all"),
+    }
+}
@@ -1,5 +1,18 @@
 pub mod app;
+pub mod commands;
 pub mod gallery;
+pub mod generation_panel;
+pub mod i18n;
+pub mod image_loader;
+pub mod instance;
+pub mod log_viewer;
+pub mod monitor_layout;
+pub mod notifications;
+pub mod performance_panel;
+pub mod placement_editor;
+pub mod preview;
+pub mod startup;
+pub mod theme_editor;
 // pub mod settings; // TODO: Implement settings module
 // pub mod tray;     // TODO: Implement tray module
 
@@ -0,0 +1,291 @@
+//! Monitor layout for giving each monitor its own image
+//!
+//! Draws the monitors as they are arranged on the desktop. An image dragged
+//! from the gallery, or from the strip above the layout, onto a monitor is
+//! shown there instead of the static wallpaper.
+use crate::platform::MonitorInfo;
+use crate::ui::i18n::{tr, tr_args};
+use crate::ui::image_loader::{ImageLoader, LoadedImage};
+use eframe::egui;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Largest edge of the thumbnails shown on the monitors
+const THUMBNAIL_SIZE: u32 = 256;
+
+/// Size of the images in the strip above the layout
+const STRIP_ITEM: egui::Vec2 = egui::vec2(72.0, 48.0);
+
+/// Tallest the layout is drawn, in points
+const MAX_HEIGHT: f32 = 220.0;
+
+/// Key of the dragged image in egui's memory, shared by every drag source
+fn drag_id() -> egui::Id {
+    egui::Id::new("monitor_layout_drag")
+}
+
+/// Let a widget be dragged onto the monitor layout as the image at `path`
+pub fn drag_source(response: &egui::Response, path: &Path) {
+    let ctx = &response.ctx;
+    if response.drag_started() {
+        ctx.data_mut(|data| data.insert_temp(drag_id(), path.to_path_buf()));
+    }
+    if response.dragged() {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        egui::show_tooltip_at_pointer(ctx, drag_id(), |ui| {
+            ui.label(tr_args("monitor-layout-dragging", &[("name", &name)]));
+        });
+    }
+}
+
+/// Get the image being dragged, while the pointer button is held or released this frame
+fn dragged_image(ctx: &egui::Context) -> Option<PathBuf> {
+    let active = ctx.input(|input| input.pointer.any_down() || input.pointer.any_released());
+    if !active {
+        return None;
+    }
+    ctx.data(|data| data.get_temp::<PathBuf>(drag_id()))
+}
+
+/// Monitor layout view
+#[derive(Default)]
+pub struct MonitorLayout {
+    /// Decodes the thumbnails in the background
+    thumbnails: ImageLoader,
+}
+
+impl MonitorLayout {
+    /// Create a new monitor layout view
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Show the layout, with `images` in the strip to drag from
+    ///
+    /// `assignments` maps monitor IDs to image paths. Returns true when an
+    /// assignment changed and should be saved.
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        monitors: &[MonitorInfo],
+        images: &[PathBuf],
+        assignments: &mut HashMap<String, String>,
+    ) -> bool {
+        if monitors.is_empty() {
+            ui.label(tr("monitor-layout-none"));
+            return false;
+        }
+        ui.label(tr("monitor-layout-hint"));
+
+        if images.is_empty() {
+            ui.label(tr("monitor-layout-no-images"));
+        } else {
+            egui::ScrollArea::horizontal()
+                .id_source("monitor_layout_strip")
+                .show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        for path in images {
+                            let (response, painter) =
+                                ui.allocate_painter(STRIP_ITEM, egui::Sense::drag());
+                            self.paint_image(ui, &painter, path, response.rect);
+                            drag_source(&response.on_hover_text(path.display().to_string()), path);
+                        }
+                    });
+                });
+        }
+
+        let mut changed = false;
+        let dragged = dragged_image(ui.ctx());
+        let released = ui.input(|input| input.pointer.any_released());
+        let (left, top, right, bottom) = bounds(monitors);
+        let size = egui::vec2((right - left) as f32, (bottom - top) as f32);
+        let scale = (ui.available_width() / size.x).min(MAX_HEIGHT / size.y);
+        let (layout, painter) = ui.allocate_painter(size * scale, egui::Sense::hover());
+        let area = layout.rect;
+
+        for monitor in monitors {
+            let min =
+                area.min + egui::vec2((monitor.x - left) as f32, (monitor.y - top) as f32) * scale;
+            let rect = egui::Rect::from_min_size(
+                min,
+                egui::vec2(monitor.width as f32, monitor.height as f32) * scale,
+            )
+            .shrink(2.0);
+            let response = ui.interact(
+                rect,
+                ui.id().with(("monitor", &monitor.id)),
+                egui::Sense::click(),
+            );
+            let own = assignments.get(&monitor.id).map(PathBuf::from);
+
+            painter.rect_filled(
+                rect,
+                egui::Rounding::same(4.0),
+                ui.visuals().extreme_bg_color,
+            );
+            if let Some(path) = &own {
+                self.paint_image(ui, &painter, path, rect);
+            }
+            let drop_target = dragged.is_some() && response.hovered();
+            let stroke = if drop_target {
+                egui::Stroke::new(3.0, ui.visuals().selection.stroke.color)
+            } else {
+                egui::Stroke::new(1.0, ui.visuals().widgets.noninteractive.fg_stroke.color)
+            };
+            painter.rect_stroke(rect, egui::Rounding::same(4.0), stroke);
+
+            let primary = if monitor.primary { " ★" } else { "" };
+            painter.text(
+                rect.left_top() + egui::vec2(6.0, 4.0),
+                egui::Align2::LEFT_TOP,
+                format!(
+                    "{} ({}x{}){}",
+                    monitor.name, monitor.width, monitor.height, primary
+                ),
+                egui::TextStyle::Small.resolve(ui.style()),
+                ui.visuals().strong_text_color(),
+            );
+
+            if let (Some(path), true) = (&dragged, drop_target && released) {
+                assignments.insert(monitor.id.clone(), path.display().to_string());
+                changed = true;
+            }
+
+            let hover = match &own {
+                Some(path) => tr_args("monitor-image", &[("path", &path.display().to_string())]),
+                None => tr("monitor-image-wallpaper"),
+            };
+            response.on_hover_text(hover).context_menu(|ui| {
+                if ui
+                    .add_enabled(own.is_some(), egui::Button::new(tr("monitor-image-clear")))
+                    .clicked()
+                {
+                    assignments.remove(&monitor.id);
+                    changed = true;
+                    ui.close_menu();
+                }
+            });
+        }
+
+        if released && dragged.is_some() {
+            ui.ctx().data_mut(|data| data.remove::<PathBuf>(drag_id()));
+        }
+
+        let connected: Vec<&String> = monitors.iter().map(|monitor| &monitor.id).collect();
+        let disconnected = assignments
+            .keys()
+            .filter(|id| !connected.contains(id))
+            .count();
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(
+                    !assignments.is_empty(),
+                    egui::Button::new(tr("monitor-images-clear-all")),
+                )
+                .clicked()
+            {
+                assignments.clear();
+                changed = true;
+            }
+            if disconnected > 0 {
+                ui.label(tr_args(
+                    "monitor-images-disconnected",
+                    &[("count", &disconnected.to_string())],
+                ));
+            }
+        });
+        changed
+    }
+
+    /// Paint the thumbnail of an image filling `rect`, once it is decoded
+    fn paint_image(
+        &mut self,
+        ui: &egui::Ui,
+        painter: &egui::Painter,
+        path: &Path,
+        rect: egui::Rect,
+    ) {
+        match self.thumbnails.get(ui.ctx(), path, THUMBNAIL_SIZE) {
+            LoadedImage::Ready(texture, _) => {
+                painter.image(
+                    texture.id(),
+                    rect,
+                    fill_uv(texture.size_vec2(), rect.size()),
+                    egui::Color32::WHITE,
+                );
+            }
+            _ => {
+                painter.rect_filled(rect, egui::Rounding::same(2.0), ui.visuals().faint_bg_color);
+            }
+        }
+    }
+}
+
+/// Get the desktop area the monitors cover, as (left, top, right, bottom)
+fn bounds(monitors: &[MonitorInfo]) -> (i32, i32, i32, i32) {
+    let left = monitors.iter().map(|m| m.x).min().unwrap_or(0);
+    let top = monitors.iter().map(|m| m.y).min().unwrap_or(0);
+    let right = monitors
+        .iter()
+        .map(|m| m.x + m.width as i32)
+        .max()
+        .unwrap_or(1)
+        .max(left + 1);
+    let bottom = monitors
+        .iter()
+        .map(|m| m.y + m.height as i32)
+        .max()
+        .unwrap_or(1)
+        .max(top + 1);
+    (left, top, right, bottom)
+}
+
+/// Texture coordinates showing an image of `image` size cropped to fill `target`
+fn fill_uv(image: egui::Vec2, target: egui::Vec2) -> egui::Rect {
+    let image_aspect = image.x / image.y.max(1.0);
+    let target_aspect = target.x / target.y.max(1.0);
+    if image_aspect > target_aspect {
+        let width = target_aspect / image_aspect;
+        egui::Rect::from_min_max(
+            egui::pos2((1.0 - width) / 2.0, 0.0),
+            egui::pos2((1.0 + width) / 2.0, 1.0),
+        )
+    } else {
+        let height = image_aspect / target_aspect;
+        egui::Rect::from_min_max(
+            egui::pos2(0.0, (1.0 - height) / 2.0),
+            egui::pos2(1.0, (1.0 + height) / 2.0),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounds() {
+        let mut left = MonitorInfo::new("DP-1", 1920, 1080);
+        left.x = -1920;
+        let mut right = MonitorInfo::new("HDMI-A-1", 2560, 1440);
+        right.y = -200;
+        assert_eq!(bounds(&[left, right]), (-1920, -200, 2560, 1240));
+    }
+
+    #[test]
+    fn test_fill_uv_crops_the_longer_side() {
+        let wide = fill_uv(egui::vec2(200.0, 100.0), egui::vec2(100.0, 100.0));
+        assert_eq!(
+            wide,
+            egui::Rect::from_min_max(egui::pos2(0.25, 0.0), egui::pos2(0.75, 1.0))
+        );
+        let tall = fill_uv(egui::vec2(100.0, 200.0), egui::vec2(100.0, 100.0));
+        assert_eq!(
+            tall,
+            egui::Rect::from_min_max(egui::pos2(0.0, 0.25), egui::pos2(1.0, 0.75))
+        );
+    }
+}
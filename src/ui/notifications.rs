@@ -0,0 +1,374 @@
+//! In-app notifications
+//!
+//! Failures are shown as short-lived toasts and kept in a notification
+//! center where the underlying error can be expanded.
+use crate::core::{AppError, Event, EventLevel};
+use crate::platform::notify;
+use crate::ui::i18n::{tr, tr_args};
+use chrono::{DateTime, Local};
+use eframe::egui;
+use log::warn;
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long a toast stays on screen
+const TOAST_DURATION: Duration = Duration::from_secs(6);
+
+/// Maximum number of toasts shown at once
+const MAX_TOASTS: usize = 4;
+
+/// Number of notifications kept in the center
+const MAX_NOTIFICATIONS: usize = 100;
+
+/// Notification shown to the user
+#[derive(Debug, Clone)]
+pub struct Notification {
+    /// Notification ID
+    pub id: u64,
+
+    /// Severity
+    pub level: EventLevel,
+
+    /// Short summary
+    pub title: String,
+
+    /// Underlying error or extra details
+    pub details: Option<String>,
+
+    /// What the user can do about it
+    pub hint: Option<String>,
+
+    /// Time the notification was raised
+    pub time: DateTime<Local>,
+
+    /// When the notification was raised, for toast expiry
+    created: Instant,
+
+    /// Whether the toast was dismissed
+    dismissed: bool,
+
+    /// Whether the notification was seen in the center
+    read: bool,
+}
+
+/// Toasts and notification history
+#[derive(Debug, Default)]
+pub struct NotificationCenter {
+    /// Notifications, oldest first
+    notifications: VecDeque<Notification>,
+
+    /// Next notification ID
+    next_id: u64,
+
+    /// Whether errors are also sent as desktop notifications
+    desktop_notifications: bool,
+
+    /// Whether the notification center window is open
+    open: bool,
+}
+
+#[allow(dead_code)]
+impl NotificationCenter {
+    /// Create a new notification center
+    pub fn new(desktop_notifications: bool) -> Self {
+        Self {
+            desktop_notifications,
+            ..Default::default()
+        }
+    }
+
+    /// Enable or disable desktop notifications
+    pub fn set_desktop_notifications(&mut self, enabled: bool) {
+        self.desktop_notifications = enabled;
+    }
+
+    /// Raise a notification
+    pub fn push(&mut self, level: EventLevel, title: impl Into<String>, details: Option<String>) {
+        self.push_with_hint(level, title, details, None);
+    }
+
+    /// Raise a notification suggesting a fix
+    pub fn push_with_hint(
+        &mut self,
+        level: EventLevel,
+        title: impl Into<String>,
+        details: Option<String>,
+        hint: Option<String>,
+    ) {
+        let title = title.into();
+        if self.desktop_notifications && level == EventLevel::Error {
+            let details = details.clone().unwrap_or_default();
+            let body = match &hint {
+                Some(hint) => format!("{}\n{}", details, hint),
+                None => details,
+            };
+            let desktop_title = title.clone();
+            // Notification helpers can be slow to start; keep them off the UI thread
+            thread::spawn(move || {
+                if let Err(e) = notify::send(&desktop_title, &body, true) {
+                    warn!("Failed to show desktop notification: {}", e);
+                }
+            });
+        }
+
+        self.next_id += 1;
+        self.notifications.push_back(Notification {
+            id: self.next_id,
+            level,
+            title,
+            details,
+            hint,
+            time: Local::now(),
+            created: Instant::now(),
+            dismissed: false,
+            read: false,
+        });
+
+        while self.notifications.len() > MAX_NOTIFICATIONS {
+            self.notifications.pop_front();
+        }
+    }
+
+    /// Raise an error notification, with the fix the error suggests
+    pub fn error(&mut self, title: impl Into<String>, details: impl Remediable) {
+        let hint = details.hint();
+        self.push_with_hint(EventLevel::Error, title, Some(details.to_string()), hint);
+    }
+
+    /// Raise a warning notification
+    pub fn warning(&mut self, title: impl Into<String>, details: Option<String>) {
+        self.push(EventLevel::Warning, title, details);
+    }
+
+    /// Raise an informational notification
+    pub fn info(&mut self, title: impl Into<String>) {
+        self.push(EventLevel::Info, title, None);
+    }
+
+    /// Raise a notification for a daemon event
+    pub fn push_event(&mut self, event: &Event) {
+        let details = match &event.details {
+            Some(details) => format!("[{}] {}", event.source, details),
+            None => format!("[{}]", event.source),
+        };
+        self.push(event.level, event.message.clone(), Some(details));
+    }
+
+    /// Get all notifications, oldest first
+    pub fn notifications(&self) -> impl Iterator<Item = &Notification> {
+        self.notifications.iter()
+    }
+
+    /// Number of notifications not yet seen in the center
+    pub fn unread_count(&self) -> usize {
+        self.notifications.iter().filter(|n| !n.read).count()
+    }
+
+    /// Toggle the notification center window
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+    }
+
+    /// Remove all notifications
+    pub fn clear(&mut self) {
+        self.notifications.clear();
+    }
+
+    /// Show the notification bell with the unread count
+    pub fn show_button(&mut self, ui: &mut egui::Ui) {
+        let unread = self.unread_count();
+        let label = if unread > 0 {
+            tr_args("notifications-unread", &[("count", &unread.to_string())])
+        } else {
+            tr("notifications")
+        };
+
+        if ui.selectable_label(self.open, label).clicked() {
+            self.toggle();
+        }
+    }
+
+    /// Show active toasts and the notification center window
+    pub fn show(&mut self, ctx: &egui::Context) {
+        self.show_toasts(ctx);
+        self.show_center(ctx);
+    }
+
+    /// Show toasts in the bottom-right corner
+    fn show_toasts(&mut self, ctx: &egui::Context) {
+        if self.open {
+            return;
+        }
+
+        let active: Vec<u64> = self
+            .notifications
+            .iter()
+            .rev()
+            .filter(|n| !n.dismissed && n.created.elapsed() < TOAST_DURATION)
+            .take(MAX_TOASTS)
+            .map(|n| n.id)
+            .collect();
+
+        if active.is_empty() {
+            return;
+        }
+
+        egui::Area::new("notification_toasts")
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-12.0, -12.0))
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                ui.set_max_width(320.0);
+                for id in active.iter().rev() {
+                    let Some(notification) = self.notifications.iter_mut().find(|n| n.id == *id)
+                    else {
+                        continue;
+                    };
+
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label(
+                                egui::RichText::new(level_label(notification.level))
+                                    .color(level_color(notification.level))
+                                    .strong(),
+                            );
+                            ui.label(&notification.title);
+                            if ui.small_button("✕").on_hover_text(tr("dismiss")).clicked() {
+                                notification.dismissed = true;
+                            }
+                        });
+
+                        show_hint(ui, notification);
+                        if let Some(details) = &notification.details {
+                            egui::CollapsingHeader::new(tr("notification-details"))
+                                .id_source(("toast_details", notification.id))
+                                .show(ui, |ui| {
+                                    ui.label(egui::RichText::new(details).monospace());
+                                });
+                        }
+                    });
+                }
+            });
+
+        // Repaint so expired toasts disappear without user input
+        ctx.request_repaint_after(Duration::from_millis(500));
+    }
+
+    /// Show the notification center window
+    fn show_center(&mut self, ctx: &egui::Context) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        let mut clear = false;
+        egui::Window::new(tr("notifications"))
+            .open(&mut open)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button(tr("notifications-clear")).clicked() {
+                        clear = true;
+                    }
+                });
+
+                ui.separator();
+
+                if self.notifications.is_empty() {
+                    ui.label(tr("notifications-empty"));
+                    return;
+                }
+
+                egui::ScrollArea::vertical()
+                    .max_height(400.0)
+                    .show(ui, |ui| {
+                        for notification in self.notifications.iter_mut().rev() {
+                            notification.read = true;
+                            notification.dismissed = true;
+
+                            ui.horizontal(|ui| {
+                                ui.label(
+                                    egui::RichText::new(level_label(notification.level))
+                                        .color(level_color(notification.level))
+                                        .strong(),
+                                );
+                                ui.label(notification.time.format("%H:%M:%S").to_string());
+                                ui.label(&notification.title);
+                            });
+
+                            show_hint(ui, notification);
+                            if let Some(details) = &notification.details {
+                                egui::CollapsingHeader::new(tr("notification-details"))
+                                    .id_source(("center_details", notification.id))
+                                    .show(ui, |ui| {
+                                        ui.label(egui::RichText::new(details).monospace());
+                                    });
+                            }
+
+                            ui.separator();
+                        }
+                    });
+            });
+
+        if clear {
+            self.clear();
+        }
+        self.open = open;
+    }
+}
+
+/// Error shown in a notification, with a fix when one is known
+pub trait Remediable: Display {
+    /// Get a fix to suggest
+    fn hint(&self) -> Option<String> {
+        None
+    }
+}
+
+impl Remediable for AppError {
+    fn hint(&self) -> Option<String> {
+        AppError::hint(self)
+    }
+}
+
+impl Remediable for anyhow::Error {
+    fn hint(&self) -> Option<String> {
+        self.downcast_ref::<AppError>().and_then(AppError::hint)
+    }
+}
+
+impl Remediable for String {}
+
+impl Remediable for str {}
+
+impl<T: Remediable + ?Sized> Remediable for &T {
+    fn hint(&self) -> Option<String> {
+        (**self).hint()
+    }
+}
+
+/// Show the suggested fix of a notification
+fn show_hint(ui: &mut egui::Ui, notification: &Notification) {
+    if let Some(hint) = &notification.hint {
+        ui.label(egui::RichText::new(tr_args("notification-hint", &[("hint", hint)])).italics());
+    }
+}
+
+/// Get the translated label for a level
+pub(crate) fn level_label(level: EventLevel) -> String {
+    match level {
+        EventLevel::Info => tr("level-info"),
+        EventLevel::Warning => tr("level-warning"),
+        EventLevel::Error => tr("level-error"),
+    }
+}
+
+/// Get the color for a level
+pub(crate) fn level_color(level: EventLevel) -> egui::Color32 {
+    match level {
+        EventLevel::Info => egui::Color32::from_rgb(33, 150, 243),
+        EventLevel::Warning => egui::Color32::from_rgb(255, 193, 7),
+        EventLevel::Error => egui::Color32::from_rgb(244, 67, 54),
+    }
+}
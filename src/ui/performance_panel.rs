@@ -0,0 +1,164 @@
+//! Performance panel
+//!
+//! A collapsible strip at the bottom of the window with the UI's frame
+//! rate, frame time, CPU and memory use and the wallpaper load times, plus
+//! a warning banner while performance is degraded.
+use crate::core::performance::PerformanceMonitor;
+use crate::ui::i18n::{tr, tr_args};
+use eframe::egui;
+use std::time::Duration;
+
+/// Height of the history graphs
+const GRAPH_HEIGHT: f32 = 28.0;
+
+/// Width of the history graphs
+const GRAPH_WIDTH: f32 = 220.0;
+
+/// Performance panel and warning banner
+pub struct PerformancePanel {
+    /// Frame and system metrics
+    monitor: PerformanceMonitor,
+}
+
+impl Default for PerformancePanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PerformancePanel {
+    /// Create a new performance panel
+    pub fn new() -> Self {
+        Self {
+            monitor: PerformanceMonitor::new(),
+        }
+    }
+
+    /// Record the time it took to build a frame
+    pub fn end_frame(&mut self, frame_time: Duration) {
+        self.monitor.update_frame_timing(frame_time);
+    }
+
+    /// Record how long a wallpaper took to load
+    pub fn record_wallpaper_load(&mut self, wallpaper: &str, load_time: Duration) {
+        self.monitor.record_wallpaper_load(wallpaper, load_time);
+    }
+
+    /// Show the warning banner and the panel
+    ///
+    /// Must run before the central panel is shown.
+    pub fn show(&mut self, ctx: &egui::Context) {
+        if self.monitor.is_performance_degraded() {
+            egui::TopBottomPanel::top("performance_warning").show(ctx, |ui| {
+                ui.colored_label(egui::Color32::YELLOW, tr("performance-degraded"));
+            });
+        }
+
+        egui::TopBottomPanel::bottom("performance_panel").show(ctx, |ui| {
+            let open = egui::CollapsingHeader::new(tr("performance"))
+                .id_source("performance_panel_header")
+                .show(ui, |ui| self.show_metrics(ui))
+                .body_returned
+                .is_some();
+
+            // Keep drawing while open so the frame rate reflects what the UI can do
+            if open {
+                ctx.request_repaint();
+            }
+        });
+    }
+
+    /// Show the current metrics and their history
+    fn show_metrics(&self, ui: &mut egui::Ui) {
+        let history = self.monitor.get_metrics_history();
+        let Some(current) = self.monitor.get_current_metrics() else {
+            ui.label(tr("performance-collecting"));
+            return;
+        };
+
+        egui::Grid::new("performance_metrics")
+            .num_columns(3)
+            .show(ui, |ui| {
+                let rows: [(String, String, Vec<f32>); 4] = [
+                    (
+                        tr("performance-fps"),
+                        format!("{:.0}", current.fps),
+                        history.iter().map(|m| m.fps).collect(),
+                    ),
+                    (
+                        tr("performance-frame-time"),
+                        format!("{:.1} ms", current.frame_time),
+                        history.iter().map(|m| m.frame_time).collect(),
+                    ),
+                    (
+                        tr("performance-cpu"),
+                        format!("{:.0}%", current.cpu_usage),
+                        history.iter().map(|m| m.cpu_usage).collect(),
+                    ),
+                    (
+                        tr("performance-memory"),
+                        format!("{:.0} MB", current.memory_usage),
+                        history.iter().map(|m| m.memory_usage as f32).collect(),
+                    ),
+                ];
+                for (label, value, values) in rows {
+                    ui.label(label);
+                    ui.monospace(value);
+                    graph(ui, &values);
+                    ui.end_row();
+                }
+            });
+
+        let load_times = self.monitor.wallpaper_load_times();
+        if !load_times.is_empty() {
+            ui.separator();
+            ui.label(tr("performance-load-times"));
+            egui::Grid::new("performance_load_times")
+                .num_columns(3)
+                .show(ui, |ui| {
+                    for (wallpaper, times) in &load_times {
+                        let last = times.last().copied().unwrap_or_default();
+                        ui.label(wallpaper);
+                        ui.monospace(tr_args(
+                            "performance-load-time",
+                            &[("ms", &format!("{:.0}", last))],
+                        ));
+                        graph(ui, times);
+                        ui.end_row();
+                    }
+                });
+        }
+    }
+}
+
+/// Draw a line graph of values, scaled to the largest one
+fn graph(ui: &mut egui::Ui, values: &[f32]) {
+    let (rect, _) =
+        ui.allocate_exact_size(egui::vec2(GRAPH_WIDTH, GRAPH_HEIGHT), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+
+    let max = values.iter().copied().fold(f32::EPSILON, f32::max);
+    let step = rect.width() / (values.len().max(2) - 1) as f32;
+    let points: Vec<egui::Pos2> = values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            egui::pos2(
+                rect.left() + step * i as f32,
+                rect.bottom() - rect.height() * value / max,
+            )
+        })
+        .collect();
+
+    let stroke = egui::Stroke::new(1.5, ui.visuals().selection.bg_fill);
+    match points.as_slice() {
+        [] => {}
+        [point] => {
+            painter.circle_filled(*point, 2.0, stroke.color);
+        }
+        _ => {
+            painter.add(egui::Shape::line(points, stroke));
+        }
+    }
+}
@@ -0,0 +1,272 @@
+//! Fit mode and crop editor for static wallpapers
+use crate::core::config::{CropRect, FitMode, PlacementConfig};
+use crate::platform::MonitorInfo;
+use crate::ui::i18n::{tr, tr_args};
+use crate::ui::image_loader::{ImageLoader, LoadedImage};
+use eframe::egui;
+use std::path::Path;
+
+/// Largest edge of the image shown in the editor
+const EDITOR_SIZE: u32 = 480;
+
+/// Size of the resize handle in points
+const HANDLE_SIZE: f32 = 12.0;
+
+/// What the current drag does to the crop rectangle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CropDrag {
+    /// Move the rectangle
+    Move,
+
+    /// Resize from the bottom-right corner
+    Resize,
+}
+
+/// Editor for how static wallpapers are fitted to the monitors
+#[derive(Default)]
+pub struct PlacementEditor {
+    /// Decodes the image being cropped in the background
+    images: ImageLoader,
+
+    /// Monitor whose crop is being edited
+    monitor: usize,
+
+    /// Current drag on the crop rectangle
+    drag: Option<CropDrag>,
+}
+
+impl PlacementEditor {
+    /// Create a new placement editor
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Show the editor for the image at `path`
+    ///
+    /// Returns true when the placement changed and should be saved.
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        path: &Path,
+        placement: &mut PlacementConfig,
+        monitors: &[MonitorInfo],
+    ) -> bool {
+        let mut changed = false;
+
+        ui.horizontal(|ui| {
+            ui.label(tr("fit-mode"));
+            egui::ComboBox::from_id_source("fit_mode")
+                .selected_text(fit_mode_label(placement.mode))
+                .show_ui(ui, |ui| {
+                    for mode in [
+                        FitMode::Fill,
+                        FitMode::Fit,
+                        FitMode::Stretch,
+                        FitMode::Center,
+                        FitMode::Tile,
+                        FitMode::Crop,
+                    ] {
+                        changed |= ui
+                            .selectable_value(&mut placement.mode, mode, fit_mode_label(mode))
+                            .changed();
+                    }
+                });
+        });
+
+        if placement.mode == FitMode::Fill && !placement.span {
+            changed |= ui
+                .checkbox(&mut placement.smart_crop, tr("smart-crop"))
+                .on_hover_text(tr("smart-crop-hint"))
+                .changed();
+        }
+
+        // Rotated monitors would otherwise show landscape images as a thin strip
+        if matches!(placement.mode, FitMode::Fill | FitMode::Fit)
+            && !placement.span
+            && monitors.iter().any(MonitorInfo::is_portrait)
+        {
+            let mut crop = !placement.letterbox_portrait;
+            if ui
+                .checkbox(&mut crop, tr("crop-portrait"))
+                .on_hover_text(tr("crop-portrait-hint"))
+                .changed()
+            {
+                placement.letterbox_portrait = !crop;
+                changed = true;
+            }
+        }
+
+        if placement.mode == FitMode::Crop && placement.span {
+            ui.label(tr("placement-span-crop"));
+        } else if placement.mode == FitMode::Crop {
+            changed |= self.show_crop(ui, path, placement, monitors);
+        }
+
+        changed
+    }
+
+    /// Show the crop rectangle of the selected monitor over the image
+    fn show_crop(
+        &mut self,
+        ui: &mut egui::Ui,
+        path: &Path,
+        placement: &mut PlacementConfig,
+        monitors: &[MonitorInfo],
+    ) -> bool {
+        if monitors.is_empty() {
+            ui.label(tr("placement-no-monitors"));
+            return false;
+        }
+        self.monitor = self.monitor.min(monitors.len() - 1);
+
+        if monitors.len() > 1 {
+            egui::ComboBox::from_id_source("crop_monitor")
+                .selected_text(monitor_label(&monitors[self.monitor]))
+                .show_ui(ui, |ui| {
+                    for (index, monitor) in monitors.iter().enumerate() {
+                        ui.selectable_value(&mut self.monitor, index, monitor_label(monitor));
+                    }
+                });
+        }
+        let monitor = &monitors[self.monitor];
+
+        let (texture, [image_width, image_height]) =
+            match self.images.get(ui.ctx(), path, EDITOR_SIZE) {
+                LoadedImage::Ready(texture, size) => (texture.clone(), size),
+                LoadedImage::Loading => {
+                    ui.spinner();
+                    return false;
+                }
+                LoadedImage::Failed(e) => {
+                    ui.colored_label(
+                        egui::Color32::RED,
+                        tr_args("preview-failed", &[("error", e)]),
+                    );
+                    return false;
+                }
+            };
+
+        ui.label(tr("placement-crop-hint"));
+
+        let texture_size = texture.size_vec2();
+        let scale = ui.available_width().min(EDITOR_SIZE as f32) / texture_size.x;
+        let (rect, response) = ui.allocate_exact_size(texture_size * scale, egui::Sense::drag());
+
+        let mut crop = placement
+            .crops
+            .get(&monitor.name)
+            .copied()
+            .unwrap_or_else(|| {
+                CropRect::centered(image_width, image_height, monitor.width, monitor.height)
+            });
+        let to_screen = |crop: &CropRect| {
+            egui::Rect::from_min_size(
+                rect.min + egui::vec2(crop.x * rect.width(), crop.y * rect.height()),
+                egui::vec2(crop.width * rect.width(), crop.height * rect.height()),
+            )
+        };
+
+        if response.drag_started() {
+            let crop_rect = to_screen(&crop);
+            let handle = egui::Rect::from_center_size(
+                crop_rect.right_bottom(),
+                egui::Vec2::splat(HANDLE_SIZE),
+            );
+            self.drag = match response.interact_pointer_pos() {
+                Some(pos) if handle.contains(pos) => Some(CropDrag::Resize),
+                Some(pos) if crop_rect.contains(pos) => Some(CropDrag::Move),
+                _ => None,
+            };
+        }
+
+        let mut changed = false;
+        if response.dragged() {
+            let delta = response.drag_delta() / rect.size();
+            let dragged = match self.drag {
+                Some(CropDrag::Move) => Some(
+                    CropRect {
+                        x: crop.x + delta.x,
+                        y: crop.y + delta.y,
+                        ..crop
+                    }
+                    .clamped(),
+                ),
+                Some(CropDrag::Resize) => Some(crop.with_width(
+                    crop.width + delta.x,
+                    image_width,
+                    image_height,
+                    monitor.width,
+                    monitor.height,
+                )),
+                None => None,
+            };
+            if let Some(dragged) = dragged {
+                crop = dragged;
+                placement.crops.insert(monitor.name.clone(), crop);
+            }
+        }
+        if response.drag_released() {
+            changed = self.drag.take().is_some();
+        }
+
+        // Image with everything outside the crop dimmed
+        let painter = ui.painter_at(rect);
+        painter.image(
+            texture.id(),
+            rect,
+            egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+            egui::Color32::WHITE,
+        );
+
+        let crop_rect = to_screen(&crop);
+        let shade = egui::Color32::from_black_alpha(140);
+        for outside in [
+            egui::Rect::from_min_max(rect.min, egui::pos2(rect.max.x, crop_rect.min.y)),
+            egui::Rect::from_min_max(egui::pos2(rect.min.x, crop_rect.max.y), rect.max),
+            egui::Rect::from_min_max(
+                egui::pos2(rect.min.x, crop_rect.min.y),
+                egui::pos2(crop_rect.min.x, crop_rect.max.y),
+            ),
+            egui::Rect::from_min_max(
+                egui::pos2(crop_rect.max.x, crop_rect.min.y),
+                egui::pos2(rect.max.x, crop_rect.max.y),
+            ),
+        ] {
+            painter.rect_filled(outside, 0.0, shade);
+        }
+
+        let stroke_color = ui.visuals().selection.bg_fill;
+        painter.rect_stroke(crop_rect, 0.0, egui::Stroke::new(2.0, stroke_color));
+        painter.rect_filled(
+            egui::Rect::from_center_size(crop_rect.right_bottom(), egui::Vec2::splat(HANDLE_SIZE)),
+            2.0,
+            stroke_color,
+        );
+
+        if placement.crops.contains_key(&monitor.name)
+            && ui.button(tr("placement-reset-crop")).clicked()
+        {
+            placement.crops.remove(&monitor.name);
+            changed = true;
+        }
+
+        changed
+    }
+}
+
+/// Get the translated label for a fit mode
+fn fit_mode_label(mode: FitMode) -> String {
+    match mode {
+        FitMode::Fill => tr("fit-fill"),
+        FitMode::Fit => tr("fit-fit"),
+        FitMode::Stretch => tr("fit-stretch"),
+        FitMode::Center => tr("fit-center"),
+        FitMode::Tile => tr("fit-tile"),
+        FitMode::Crop => tr("fit-crop"),
+    }
+}
+
+/// Get the label for a monitor, e.g. "DP-1 (2560x1440)"
+fn monitor_label(monitor: &MonitorInfo) -> String {
+    format!("{} ({}x{})", monitor.name, monitor.width, monitor.height)
+}
@@ -0,0 +1,572 @@
+//! In-app wallpaper preview
+//!
+//! Images are decoded and the first seconds of videos and animated images
+//! sampled on a background thread. Adjustments being edited are applied to
+//! the decoded frames as they change. Shaders run in the UI's own OpenGL
+//! context through a paint callback.
+use crate::core::{Adjustments, AppError, AppResult, WallpaperType};
+use crate::ui::i18n::{tr, tr_args};
+use crate::ui::image_loader;
+use crate::wallpapers::{Animation, VideoWallpaper};
+use eframe::egui;
+use eframe::egui_glow;
+use eframe::glow::{self, HasContext};
+use image::RgbaImage;
+use log::{debug, warn};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Largest preview edge in pixels
+const PREVIEW_SIZE: u32 = 480;
+
+/// Length of the video sample in seconds
+const VIDEO_SAMPLE_SECONDS: u32 = 3;
+
+/// Frame rate of the video sample
+const VIDEO_SAMPLE_FPS: u32 = 8;
+
+/// Preview frames, filled in by the loader thread
+enum LoadState {
+    /// Still decoding
+    Loading,
+
+    /// Decoded frames, handed to the UI once
+    Loaded(Vec<egui::ColorImage>),
+
+    /// Loading failed
+    Failed(String),
+}
+
+/// Scaled live preview of a wallpaper
+pub struct WallpaperPreview {
+    /// Wallpaper being previewed
+    current: Option<(WallpaperType, PathBuf)>,
+
+    /// Frames decoded for the current wallpaper
+    loaded: Arc<Mutex<LoadState>>,
+
+    /// Frames as decoded, before adjustments
+    originals: Vec<egui::ColorImage>,
+
+    /// Adjustments shown on the frames
+    adjustments: Adjustments,
+
+    /// Frames uploaded as textures
+    textures: Vec<egui::TextureHandle>,
+
+    /// Shader renderer shared with the paint callback
+    shader: Arc<Mutex<ShaderRenderer>>,
+
+    /// Time the preview was opened
+    opened: Instant,
+}
+
+impl WallpaperPreview {
+    /// Create an empty preview
+    pub fn new() -> Self {
+        Self {
+            current: None,
+            loaded: Arc::new(Mutex::new(LoadState::Loading)),
+            originals: Vec::new(),
+            adjustments: Adjustments::default(),
+            textures: Vec::new(),
+            shader: Arc::new(Mutex::new(ShaderRenderer::default())),
+            opened: Instant::now(),
+        }
+    }
+
+    /// Check whether a wallpaper is being previewed
+    pub fn is_open(&self) -> bool {
+        self.current.is_some()
+    }
+
+    /// Check whether `path` is the wallpaper being previewed
+    pub fn is_showing(&self, path: &Path) -> bool {
+        self.current
+            .as_ref()
+            .map_or(false, |(_, current)| current == path)
+    }
+
+    /// Preview a wallpaper file
+    pub fn open(&mut self, wallpaper_type: WallpaperType, path: &Path) {
+        if self.current.as_ref() == Some(&(wallpaper_type.clone(), path.to_path_buf())) {
+            return;
+        }
+
+        debug!(
+            "Previewing {} wallpaper: {}",
+            wallpaper_type.as_str(),
+            path.display()
+        );
+        self.current = Some((wallpaper_type.clone(), path.to_path_buf()));
+        self.originals.clear();
+        self.adjustments = Adjustments::default();
+        self.textures.clear();
+        self.opened = Instant::now();
+
+        // A fresh slot, so a slow loader for the previous wallpaper can't overwrite it
+        let loaded = Arc::new(Mutex::new(LoadState::Loading));
+        self.loaded = loaded.clone();
+
+        match wallpaper_type {
+            WallpaperType::Static
+            | WallpaperType::AnimatedStatic
+            | WallpaperType::AnimatedImage
+            | WallpaperType::Video => {
+                let path = path.to_path_buf();
+                thread::spawn(move || {
+                    let result = match wallpaper_type {
+                        WallpaperType::Video => load_video_frames(&path),
+                        WallpaperType::AnimatedImage => load_animation_frames(&path),
+                        _ => load_image(&path).map(|image| vec![image]),
+                    };
+                    *loaded.lock().unwrap() = match result {
+                        Ok(frames) => LoadState::Loaded(frames),
+                        Err(e) => {
+                            warn!("Failed to load preview for {}: {}", path.display(), e);
+                            LoadState::Failed(e.to_string())
+                        }
+                    };
+                });
+            }
+            WallpaperType::Shader | WallpaperType::Audio => match fs::read_to_string(path) {
+                Ok(source) => self.shader.lock().unwrap().source = source,
+                Err(e) => *loaded.lock().unwrap() = LoadState::Failed(e.to_string()),
+            },
+            WallpaperType::Web
+            | WallpaperType::Particles
+            | WallpaperType::Procedural
+            | WallpaperType::Slideshow
+            | WallpaperType::NowPlaying => {}
+        }
+    }
+
+    /// Close the preview
+    pub fn close(&mut self) {
+        self.current = None;
+        self.originals.clear();
+        self.textures.clear();
+    }
+
+    /// Show the frames with adjustments, as the wallpaper would look
+    pub fn set_adjustments(&mut self, adjustments: Adjustments) {
+        if adjustments != self.adjustments {
+            self.adjustments = adjustments;
+            self.textures.clear();
+        }
+    }
+
+    /// Show the preview, scaled to fit the available width
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        let wallpaper_type = match &self.current {
+            Some((wallpaper_type, _)) => wallpaper_type.clone(),
+            None => return,
+        };
+        let width = ui.available_width().min(PREVIEW_SIZE as f32);
+
+        match wallpaper_type {
+            WallpaperType::Static
+            | WallpaperType::AnimatedStatic
+            | WallpaperType::AnimatedImage
+            | WallpaperType::Video => self.show_frames(ui, width),
+            WallpaperType::Shader | WallpaperType::Audio => self.show_shader(ui, width),
+            WallpaperType::Web
+            | WallpaperType::Particles
+            | WallpaperType::Procedural
+            | WallpaperType::Slideshow
+            | WallpaperType::NowPlaying => {
+                ui.label(tr("preview-unavailable"));
+            }
+        }
+    }
+
+    /// Show decoded image or video frames
+    fn show_frames(&mut self, ui: &mut egui::Ui, width: f32) {
+        if self.textures.is_empty() {
+            match &mut *self.loaded.lock().unwrap() {
+                LoadState::Loading => {
+                    ui.spinner();
+                    ui.ctx().request_repaint_after(Duration::from_millis(100));
+                    return;
+                }
+                LoadState::Failed(e) => {
+                    ui.colored_label(
+                        egui::Color32::RED,
+                        tr_args("preview-failed", &[("error", e)]),
+                    );
+                    return;
+                }
+                LoadState::Loaded(frames) => self.originals.append(frames),
+            }
+            self.textures = self
+                .originals
+                .iter()
+                .enumerate()
+                .map(|(i, frame)| {
+                    let frame = adjusted_frame(frame, &self.adjustments);
+                    ui.ctx().load_texture(
+                        format!("wallpaper_preview_{}", i),
+                        frame,
+                        egui::TextureOptions::LINEAR,
+                    )
+                })
+                .collect();
+        }
+
+        if self.textures.is_empty() {
+            ui.label(tr("preview-unavailable"));
+            return;
+        }
+
+        let frame_time = 1.0 / VIDEO_SAMPLE_FPS as f32;
+        let index =
+            (self.opened.elapsed().as_secs_f32() / frame_time) as usize % self.textures.len();
+        ui.add(egui::Image::new(&self.textures[index]).max_width(width));
+
+        if self.textures.len() > 1 {
+            ui.ctx()
+                .request_repaint_after(Duration::from_secs_f32(frame_time));
+        }
+    }
+
+    /// Run the shader in a 16:9 area
+    fn show_shader(&mut self, ui: &mut egui::Ui, width: f32) {
+        if let LoadState::Failed(e) = &*self.loaded.lock().unwrap() {
+            ui.colored_label(
+                egui::Color32::RED,
+                tr_args("preview-failed", &[("error", e)]),
+            );
+            return;
+        }
+
+        let (rect, _) =
+            ui.allocate_exact_size(egui::vec2(width, width * 9.0 / 16.0), egui::Sense::hover());
+        let time = self.opened.elapsed().as_secs_f32();
+        let shader = self.shader.clone();
+        ui.painter().add(egui::PaintCallback {
+            rect,
+            callback: Arc::new(egui_glow::CallbackFn::new(move |info, painter| {
+                shader.lock().unwrap().paint(painter.gl(), &info, time);
+            })),
+        });
+
+        if let Some(e) = &self.shader.lock().unwrap().error {
+            ui.colored_label(
+                egui::Color32::RED,
+                tr_args("preview-failed", &[("error", e)]),
+            );
+        }
+
+        ui.ctx().request_repaint();
+    }
+}
+
+impl Default for WallpaperPreview {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Apply adjustments to a decoded frame
+fn adjusted_frame(frame: &egui::ColorImage, adjustments: &Adjustments) -> egui::ColorImage {
+    if adjustments.is_neutral() {
+        return frame.clone();
+    }
+    let pixels = frame
+        .pixels
+        .iter()
+        .flat_map(|pixel| pixel.to_srgba_unmultiplied())
+        .collect();
+    let Some(mut image) = RgbaImage::from_raw(frame.size[0] as u32, frame.size[1] as u32, pixels)
+    else {
+        return frame.clone();
+    };
+    adjustments.apply(&mut image);
+    egui::ColorImage::from_rgba_unmultiplied(frame.size, image.as_raw())
+}
+
+/// Decode an image scaled down to the preview size
+fn load_image(path: &Path) -> AppResult<egui::ColorImage> {
+    image_loader::decode_image(path, PREVIEW_SIZE).map(|(image, _)| image)
+}
+
+/// Sample the first seconds of an animated image at the video sample rate
+fn load_animation_frames(path: &Path) -> AppResult<Vec<egui::ColorImage>> {
+    let animation = Animation::open(path, (PREVIEW_SIZE, PREVIEW_SIZE))?;
+    let seconds = animation
+        .duration()
+        .as_secs_f32()
+        .min(VIDEO_SAMPLE_SECONDS as f32);
+    let count = ((seconds * VIDEO_SAMPLE_FPS as f32).ceil() as usize).max(1);
+    Ok((0..count)
+        .map(|sample| {
+            let index = animation.index_at(
+                Duration::from_secs_f32(sample as f32 / VIDEO_SAMPLE_FPS as f32),
+                0,
+            );
+            let frame = animation
+                .frame(index)
+                .thumbnail(PREVIEW_SIZE, PREVIEW_SIZE)
+                .to_rgba8();
+            egui::ColorImage::from_rgba_unmultiplied(
+                [frame.width() as usize, frame.height() as usize],
+                frame.as_raw(),
+            )
+        })
+        .collect())
+}
+
+/// Sample the first seconds of a video with mpv
+fn load_video_frames(path: &Path) -> AppResult<Vec<egui::ColorImage>> {
+    static NEXT_SAMPLE: AtomicU64 = AtomicU64::new(0);
+
+    let mpv = VideoWallpaper::get_mpv_command()?;
+    let dir = std::env::temp_dir().join(format!(
+        "aether-desk-preview-{}-{}",
+        std::process::id(),
+        NEXT_SAMPLE.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::create_dir_all(&dir)?;
+
+    let output = Command::new(mpv)
+        .arg("--no-config")
+        .arg("--no-audio")
+        .arg("--really-quiet")
+        .arg("--vo=image")
+        .arg("--vo-image-format=png")
+        .arg(format!("--vo-image-outdir={}", dir.display()))
+        .arg(format!("--length={}", VIDEO_SAMPLE_SECONDS))
+        .arg(format!(
+            "--vf=fps={},scale={}:-2",
+            VIDEO_SAMPLE_FPS, PREVIEW_SIZE
+        ))
+        .arg(path)
+        .output();
+
+    let frames = output.map_err(AppError::from).and_then(|output| {
+        if !output.status.success() {
+            return Err(AppError::WallpaperError(format!(
+                "mpv failed to sample {}: {}",
+                path.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        let mut files: Vec<PathBuf> = fs::read_dir(&dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .collect();
+        files.sort();
+        files.iter().map(|file| load_image(file)).collect()
+    });
+
+    let _ = fs::remove_dir_all(&dir);
+    frames
+}
+
+/// Renders a fragment shader into the preview area
+///
+/// Shadertoy-style shaders (`mainImage`) and plain fragment shaders using
+/// `gl_FragColor` are supported. The `iTime`/`time` and
+/// `iResolution`/`resolution` uniforms are set when the shader declares them.
+#[derive(Default)]
+struct ShaderRenderer {
+    /// Shader source to show
+    source: String,
+
+    /// Source the current program was built from
+    compiled: Option<String>,
+
+    /// Linked program
+    program: Option<glow::Program>,
+
+    /// Empty vertex array for the full-screen triangle
+    vertex_array: Option<glow::VertexArray>,
+
+    /// Compile or link error of the current source
+    error: Option<String>,
+}
+
+impl ShaderRenderer {
+    /// Draw the shader; called from the paint callback
+    fn paint(&mut self, gl: &glow::Context, info: &egui::PaintCallbackInfo, time: f32) {
+        if self.compiled.as_deref() != Some(self.source.as_str()) {
+            if let Some(program) = self.program.take() {
+                unsafe { gl.delete_program(program) };
+            }
+            self.compiled = Some(self.source.clone());
+            match unsafe { compile_program(gl, &self.source) } {
+                Ok(program) => {
+                    self.program = Some(program);
+                    self.error = None;
+                }
+                Err(e) => {
+                    warn!("Failed to compile preview shader: {}", e);
+                    self.error = Some(e);
+                }
+            }
+        }
+
+        let program = match self.program {
+            Some(program) => program,
+            None => return,
+        };
+
+        let viewport = info.viewport_in_pixels();
+        let (width, height) = (viewport.width_px as f32, viewport.height_px as f32);
+
+        unsafe {
+            if self.vertex_array.is_none() {
+                self.vertex_array = gl.create_vertex_array().ok();
+            }
+
+            gl.disable(glow::BLEND);
+            gl.use_program(Some(program));
+            gl.uniform_2_f32(
+                gl.get_uniform_location(program, "aether_Offset").as_ref(),
+                viewport.left_px as f32,
+                viewport.from_bottom_px as f32,
+            );
+            gl.uniform_3_f32(
+                gl.get_uniform_location(program, "iResolution").as_ref(),
+                width,
+                height,
+                1.0,
+            );
+            gl.uniform_2_f32(
+                gl.get_uniform_location(program, "resolution").as_ref(),
+                width,
+                height,
+            );
+            gl.uniform_1_f32(gl.get_uniform_location(program, "iTime").as_ref(), time);
+            gl.uniform_1_f32(gl.get_uniform_location(program, "time").as_ref(), time);
+            gl.bind_vertex_array(self.vertex_array);
+            gl.draw_arrays(glow::TRIANGLES, 0, 3);
+        }
+    }
+}
+
+/// Compile and link the preview program for a fragment shader
+unsafe fn compile_program(gl: &glow::Context, source: &str) -> Result<glow::Program, String> {
+    let version = egui_glow::ShaderVersion::get(gl);
+    if !version.is_new_shader_interface() {
+        return Err("Shader previews need OpenGL 3 or OpenGL ES 3".to_string());
+    }
+
+    let program = gl.create_program()?;
+    let mut shaders = Vec::new();
+    for (kind, text) in [
+        (
+            glow::VERTEX_SHADER,
+            vertex_source(version.version_declaration()),
+        ),
+        (
+            glow::FRAGMENT_SHADER,
+            fragment_source(version.version_declaration(), source),
+        ),
+    ] {
+        let shader = gl.create_shader(kind)?;
+        gl.shader_source(shader, &text);
+        gl.compile_shader(shader);
+        if !gl.get_shader_compile_status(shader) {
+            let log = gl.get_shader_info_log(shader);
+            gl.delete_shader(shader);
+            gl.delete_program(program);
+            return Err(log);
+        }
+        gl.attach_shader(program, shader);
+        shaders.push(shader);
+    }
+
+    gl.link_program(program);
+    let linked = gl.get_program_link_status(program);
+    let log = gl.get_program_info_log(program);
+    for shader in shaders {
+        gl.detach_shader(program, shader);
+        gl.delete_shader(shader);
+    }
+
+    if linked {
+        Ok(program)
+    } else {
+        gl.delete_program(program);
+        Err(log)
+    }
+}
+
+/// Vertex shader drawing one triangle that covers the viewport
+fn vertex_source(version: &str) -> String {
+    format!(
+        "{}\
+const vec2 verts[3] = vec2[3](vec2(-1.0, -1.0), vec2(3.0, -1.0), vec2(-1.0, 3.0));
+void main() {{
+    gl_Position = vec4(verts[gl_VertexID], 0.0, 1.0);
+}}
+",
+        version
+    )
+}
+
+/// Wrap a user fragment shader for the preview
+///
+/// `gl_FragCoord` is made relative to the preview area, and Shadertoy's
+/// `mainImage` gets a `main` that calls it.
+fn fragment_source(version: &str, source: &str) -> String {
+    let body = source
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("#version"))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .replace("gl_FragColor", "aether_FragColor")
+        .replace("gl_FragCoord", "aether_FragCoord");
+    let shadertoy = body.contains("mainImage");
+
+    let mut text = format!(
+        "{}\
+#ifdef GL_ES
+precision highp float;
+#endif
+uniform vec2 aether_Offset;
+#define aether_FragCoord (gl_FragCoord - vec4(aether_Offset, 0.0, 0.0))
+",
+        version
+    );
+    if shadertoy || body.contains("aether_FragColor") {
+        text.push_str("out vec4 aether_FragColor;\n");
+    }
+    if shadertoy {
+        text.push_str("uniform vec3 iResolution;\nuniform float iTime;\nuniform vec4 iMouse;\n");
+    }
+    text.push_str(&body);
+    if shadertoy {
+        text.push_str("\nvoid main() {\n    mainImage(aether_FragColor, aether_FragCoord.xy);\n    aether_FragColor.a = 1.0;\n}\n");
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fragment_source() {
+        let shadertoy = "void mainImage(out vec4 fragColor, in vec2 fragCoord) {\n    fragColor = vec4(fragCoord / iResolution.xy, 0.5, 1.0);\n}";
+        let text = fragment_source("#version 140\n", shadertoy);
+        assert!(text.starts_with("#version 140\n"));
+        assert!(text.contains("uniform float iTime;"));
+        assert!(text.contains("mainImage(aether_FragColor, aether_FragCoord.xy);"));
+
+        let plain = "#version 120\nuniform float time;\nvoid main() {\n    gl_FragColor = vec4(gl_FragCoord.xy, time, 1.0);\n}";
+        let text = fragment_source("#version 140\n", plain);
+        assert!(!text.contains("#version 120"));
+        assert!(!text.contains("gl_FragColor"));
+        assert!(text.contains("out vec4 aether_FragColor;"));
+        assert!(text.contains("aether_FragColor = vec4(aether_FragCoord.xy, time, 1.0);"));
+        assert!(!text.contains("iTime"));
+    }
+}
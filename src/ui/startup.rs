@@ -0,0 +1,209 @@
+//! Deferred startup of the settings window
+//!
+//! Only the configuration is loaded before the first frame. Plugins, the
+//! schedule, widgets, profiles, installed wallpaper packs and the hardware
+//! probes, which run external tools, are loaded on a background thread once
+//! the window is painted, each in parallel. Starting the daemon waits for it to answer, so it gets its own
+//! thread. With `--profile-startup` every phase logs how long it took.
+use crate::core::pack::{self, Pack};
+use crate::core::{
+    AppError, AppResult, Config, PluginManager, ProfileStore, SecretStore, WallpaperScheduler,
+    WidgetManager,
+};
+use crate::daemon::DaemonClient;
+use crate::platform::autostart;
+use crate::platform::gpu::{self, GpuAdapter};
+use crate::platform::{self, MonitorInfo, WallpaperManager};
+use crate::sources::Calendar;
+use log::{error, info};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, OnceLock};
+use std::thread;
+use std::time::Instant;
+
+/// Log target of the phase timings
+pub const LOG_TARGET: &str = "startup";
+
+/// Whether phases are timed
+static PROFILING: AtomicBool = AtomicBool::new(false);
+
+/// Time profiling was enabled, close to the launch
+static LAUNCHED: OnceLock<Instant> = OnceLock::new();
+
+/// Time the startup phases from now on
+pub fn enable_profiling() {
+    LAUNCHED.get_or_init(Instant::now);
+    PROFILING.store(true, Ordering::Relaxed);
+}
+
+/// Run a startup phase, logging how long it took when profiling
+pub fn phase<T>(name: &str, run: impl FnOnce() -> T) -> T {
+    if !PROFILING.load(Ordering::Relaxed) {
+        return run();
+    }
+
+    let started = Instant::now();
+    let result = run();
+    info!(
+        target: LOG_TARGET,
+        "{}: {} ms (done {} ms after launch)",
+        name,
+        started.elapsed().as_millis(),
+        LAUNCHED.get().map_or(0, |launched| launched.elapsed().as_millis()),
+    );
+    result
+}
+
+/// Log a point in startup, such as the first frame
+pub fn mark(name: &str) {
+    if PROFILING.load(Ordering::Relaxed) {
+        info!(target: LOG_TARGET, "{} {} ms after launch", name, LAUNCHED.get().map_or(0, |launched| launched.elapsed().as_millis()));
+    }
+}
+
+/// Subsystems loaded after the first frame
+pub struct Subsystems {
+    /// Plugin manager with the plugins loaded
+    pub plugin_manager: PluginManager,
+
+    /// Schedule editor with the schedule loaded
+    pub scheduler: WallpaperScheduler,
+
+    /// Widget manager with the widgets loaded and started
+    pub widget_manager: WidgetManager,
+
+    /// Saved profile names
+    pub profile_names: Vec<String>,
+
+    /// Installed wallpaper packs
+    pub packs: Vec<Pack>,
+
+    /// Detected monitors
+    pub monitors: Vec<MonitorInfo>,
+
+    /// Detected GPUs
+    pub gpu_adapters: Vec<GpuAdapter>,
+
+    /// Failures to report, as the message ID and the error
+    pub errors: Vec<(&'static str, AppError)>,
+}
+
+/// Load the subsystems on a background thread
+pub fn load_subsystems(
+    config: Config,
+    wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>,
+    secret_store: Arc<SecretStore>,
+) -> Receiver<Subsystems> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let subsystems = phase("Deferred subsystems", || {
+            load(&config, wallpaper_manager, secret_store)
+        });
+        let _ = sender.send(subsystems);
+    });
+    receiver
+}
+
+/// Start the daemon on a background thread if it isn't running
+pub fn start_daemon() -> Receiver<AppResult<()>> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = sender.send(phase("Daemon", || DaemonClient::new().ensure_running()));
+    });
+    receiver
+}
+
+/// Load the subsystems, running independent loads in parallel
+fn load(
+    config: &Config,
+    wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>,
+    secret_store: Arc<SecretStore>,
+) -> Subsystems {
+    thread::scope(|scope| {
+        let plugins = scope.spawn(|| {
+            phase("Plugins", || {
+                let mut plugin_manager = PluginManager::new(&config.get_plugin_dir());
+                let result = plugin_manager.load_plugins(config);
+                (plugin_manager, result)
+            })
+        });
+        let schedule = scope.spawn(|| {
+            phase("Schedule", || {
+                let mut scheduler = WallpaperScheduler::new(wallpaper_manager);
+                let result = scheduler.load_schedule(config);
+                (scheduler, result)
+            })
+        });
+        let widgets = scope.spawn(|| {
+            phase("Widgets", || {
+                let mut widget_manager = WidgetManager::new();
+                Calendar::shared().configure(config.sources.calendar.clone(), secret_store.clone());
+                widget_manager.set_secret_store(secret_store);
+                widget_manager.set_power_policy(config.app.auto_pause.clone());
+                let loaded = widget_manager.load_widgets(config);
+                let started = widget_manager.start();
+                (widget_manager, loaded, started)
+            })
+        });
+        let profiles = scope.spawn(|| phase("Profiles", || ProfileStore::open(config).list()));
+        let packs = scope.spawn(|| phase("Packs", || pack::installed(&Config::get_packs_dir())));
+        let monitors = scope.spawn(|| phase("Monitors", platform::monitors));
+        let gpu_adapters = scope.spawn(|| phase("GPUs", gpu::adapters));
+        // Keep the startup entry in sync with the config, refreshing it in case the executable moved
+        let autostart = scope.spawn(|| {
+            phase("Autostart", || {
+                if config.app.start_with_system || autostart::is_enabled() {
+                    autostart::set_enabled(config.app.start_with_system)
+                } else {
+                    Ok(())
+                }
+            })
+        });
+
+        let mut errors = Vec::new();
+        let mut report = |id: &'static str, action: &str, result: AppResult<()>| {
+            if let Err(e) = result {
+                error!("Failed to {}: {}", action, e);
+                errors.push((id, e));
+            }
+        };
+
+        let (plugin_manager, result) = plugins.join().unwrap();
+        report("error-load-plugins", "load plugins", result);
+        let (scheduler, result) = schedule.join().unwrap();
+        report("error-load-schedule", "load schedule", result);
+        let (widget_manager, loaded, started) = widgets.join().unwrap();
+        report("error-load-widgets", "load widgets", loaded);
+        report(
+            "error-start-widget-manager",
+            "start widget manager",
+            started,
+        );
+        report(
+            "error-update-autostart",
+            "update start with system",
+            autostart.join().unwrap(),
+        );
+
+        let profile_names = profiles.join().unwrap().unwrap_or_else(|e| {
+            error!("Failed to list profiles: {}", e);
+            Vec::new()
+        });
+        let monitors = monitors.join().unwrap().unwrap_or_else(|e| {
+            error!("Failed to detect monitors: {}", e);
+            Vec::new()
+        });
+
+        Subsystems {
+            plugin_manager,
+            scheduler,
+            widget_manager,
+            profile_names,
+            packs: packs.join().unwrap(),
+            monitors,
+            gpu_adapters: gpu_adapters.join().unwrap(),
+            errors,
+        }
+    })
+}
@@ -0,0 +1,373 @@
+//! Custom theme editor
+//!
+//! Edits apply to the whole window right away as a live preview and are only
+//! written to the configuration when saved.
+use crate::core::config::ThemeConfig;
+use crate::core::theme::{self, CustomTheme};
+use crate::core::Config;
+use crate::ui::i18n::tr;
+use crate::ui::notifications::NotificationCenter;
+use eframe::egui;
+use log::error;
+use rfd::FileDialog;
+
+/// Editor for the Custom theme colors and named themes
+#[derive(Debug, Default)]
+pub struct ThemeEditor {
+    /// Theme configuration before the first unsaved edit
+    original: Option<ThemeConfig>,
+
+    /// Name used when saving the current colors
+    name: String,
+
+    /// Saved themes (None = reload from disk)
+    saved_themes: Option<Vec<CustomTheme>>,
+
+    /// Saved theme selected in the list
+    selected: Option<String>,
+}
+
+impl ThemeEditor {
+    /// Create a new theme editor
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Show the editor for the Custom theme in `config`
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        config: &mut Config,
+        notifications: &mut NotificationCenter,
+    ) {
+        let mut custom = CustomTheme::from_config(&config.app.theme);
+        let before = custom.clone();
+
+        egui::Grid::new("theme_editor_colors")
+            .num_columns(2)
+            .show(ui, |ui| {
+                for (label, color) in [
+                    (tr("theme-background"), &mut custom.background),
+                    (tr("theme-panel"), &mut custom.panel),
+                    (tr("theme-text"), &mut custom.text),
+                    (tr("theme-accent"), &mut custom.accent),
+                    (tr("theme-widget"), &mut custom.widget),
+                ] {
+                    ui.label(label);
+                    let mut value = parse_hex_color(color).unwrap_or(egui::Color32::GRAY);
+                    if egui::color_picker::color_edit_button_srgba(
+                        ui,
+                        &mut value,
+                        egui::color_picker::Alpha::Opaque,
+                    )
+                    .changed()
+                    {
+                        *color = format_hex_color(value);
+                    }
+                    ui.end_row();
+                }
+            });
+
+        if custom != before {
+            self.original
+                .get_or_insert_with(|| config.app.theme.clone());
+            custom.apply_to(&mut config.app.theme);
+        }
+
+        ui.add_space(4.0);
+        show_preview(ui, &custom);
+        ui.add_space(4.0);
+
+        if self.original.is_some() {
+            ui.horizontal(|ui| {
+                ui.label(tr("theme-unsaved"));
+                if ui.button(tr("save")).clicked() {
+                    match config.save() {
+                        Ok(()) => self.original = None,
+                        Err(e) => {
+                            error!("Failed to save config: {}", e);
+                            notifications.error(tr("error-save-config"), &e);
+                        }
+                    }
+                }
+                if ui.button(tr("revert")).clicked() {
+                    if let Some(original) = self.original.take() {
+                        config.app.theme = original;
+                    }
+                }
+            });
+        }
+
+        ui.separator();
+        self.show_saved_themes(ui, config, notifications);
+    }
+
+    /// Show the named theme list with save, load, import and export
+    fn show_saved_themes(
+        &mut self,
+        ui: &mut egui::Ui,
+        config: &mut Config,
+        notifications: &mut NotificationCenter,
+    ) {
+        let themes_dir = config.get_themes_dir();
+        let current = CustomTheme::from_config(&config.app.theme);
+
+        ui.heading(tr("saved-themes"));
+
+        ui.horizontal(|ui| {
+            ui.label(tr("name"));
+            ui.text_edit_singleline(&mut self.name);
+            if ui
+                .add_enabled(
+                    !self.name.trim().is_empty(),
+                    egui::Button::new(tr("theme-save-as")),
+                )
+                .clicked()
+            {
+                let theme = CustomTheme {
+                    name: self.name.trim().to_string(),
+                    ..current.clone()
+                };
+                match theme::save(&themes_dir, &theme) {
+                    Ok(_) => {
+                        self.selected = Some(theme.name);
+                        self.saved_themes = None;
+                    }
+                    Err(e) => {
+                        error!("Failed to save theme: {}", e);
+                        notifications.error(tr("error-save-theme"), &e);
+                    }
+                }
+            }
+        });
+
+        if self.saved_themes.is_none() {
+            match theme::list(&themes_dir) {
+                Ok(themes) => self.saved_themes = Some(themes),
+                Err(e) => {
+                    error!("Failed to list themes: {}", e);
+                    self.saved_themes = Some(Vec::new());
+                }
+            }
+        }
+        let saved_themes = self.saved_themes.clone().unwrap_or_default();
+
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_source("saved_themes")
+                .selected_text(
+                    self.selected
+                        .clone()
+                        .unwrap_or_else(|| tr("theme-none-selected")),
+                )
+                .show_ui(ui, |ui| {
+                    for theme in &saved_themes {
+                        ui.selectable_value(
+                            &mut self.selected,
+                            Some(theme.name.clone()),
+                            &theme.name,
+                        );
+                    }
+                });
+
+            let selected = self
+                .selected
+                .as_ref()
+                .and_then(|name| saved_themes.iter().find(|theme| theme.name == *name))
+                .cloned();
+
+            if ui
+                .add_enabled(selected.is_some(), egui::Button::new(tr("load")))
+                .clicked()
+            {
+                if let Some(theme) = &selected {
+                    self.load(theme, config);
+                }
+            }
+
+            if ui
+                .add_enabled(selected.is_some(), egui::Button::new(tr("export")))
+                .clicked()
+            {
+                if let Some(theme) = &selected {
+                    let dialog = FileDialog::new()
+                        .add_filter(&tr("filter-themes"), &["json"])
+                        .set_file_name(
+                            theme::theme_path(&themes_dir, &theme.name)
+                                .file_name()
+                                .unwrap_or_default()
+                                .to_string_lossy()
+                                .to_string(),
+                        );
+                    if let Some(path) = dialog.save_file() {
+                        if let Err(e) = theme.export(&path) {
+                            error!("Failed to export theme: {}", e);
+                            notifications.error(tr("error-export-theme"), &e);
+                        }
+                    }
+                }
+            }
+
+            if ui
+                .add_enabled(selected.is_some(), egui::Button::new(tr("delete")))
+                .clicked()
+            {
+                if let Some(theme) = &selected {
+                    if let Err(e) = theme::delete(&themes_dir, &theme.name) {
+                        error!("Failed to delete theme: {}", e);
+                        notifications.error(tr("error-delete-theme"), &e);
+                    }
+                    self.selected = None;
+                    self.saved_themes = None;
+                }
+            }
+
+            if ui.button(tr("import")).clicked() {
+                if let Some(path) = FileDialog::new()
+                    .add_filter(&tr("filter-themes"), &["json"])
+                    .pick_file()
+                {
+                    match CustomTheme::load(&path)
+                        .and_then(|theme| theme::save(&themes_dir, &theme).map(|_| theme))
+                    {
+                        Ok(theme) => {
+                            self.load(&theme, config);
+                            self.selected = Some(theme.name);
+                            self.saved_themes = None;
+                        }
+                        Err(e) => {
+                            error!("Failed to import theme: {}", e);
+                            notifications.error(tr("error-import-theme"), &e);
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    /// Preview a saved theme; it is kept once the configuration is saved
+    fn load(&mut self, theme: &CustomTheme, config: &mut Config) {
+        self.original
+            .get_or_insert_with(|| config.app.theme.clone());
+        theme.apply_to(&mut config.app.theme);
+        self.name = theme.name.clone();
+    }
+}
+
+/// Show a sample of the theme's colors
+fn show_preview(ui: &mut egui::Ui, theme: &CustomTheme) {
+    let color = |hex: &str| parse_hex_color(hex).unwrap_or(egui::Color32::GRAY);
+
+    egui::Frame::none()
+        .fill(color(&theme.background))
+        .inner_margin(8.0)
+        .show(ui, |ui| {
+            ui.label(
+                egui::RichText::new(tr("theme-preview"))
+                    .color(color(&theme.accent))
+                    .strong(),
+            );
+            egui::Frame::none()
+                .fill(color(&theme.panel))
+                .rounding(4.0)
+                .inner_margin(8.0)
+                .show(ui, |ui| {
+                    ui.label(
+                        egui::RichText::new(tr("theme-preview-text")).color(color(&theme.text)),
+                    );
+                    ui.horizontal(|ui| {
+                        egui::Frame::none()
+                            .fill(color(&theme.widget))
+                            .rounding(2.0)
+                            .inner_margin(egui::vec2(8.0, 2.0))
+                            .show(ui, |ui| {
+                                ui.label(
+                                    egui::RichText::new(tr("apply")).color(color(&theme.text)),
+                                );
+                            });
+                        egui::Frame::none()
+                            .fill(color(&theme.accent))
+                            .rounding(2.0)
+                            .inner_margin(egui::vec2(8.0, 2.0))
+                            .show(ui, |ui| {
+                                ui.label(
+                                    egui::RichText::new(tr("enabled")).color(color(&theme.panel)),
+                                );
+                            });
+                    });
+                });
+        });
+}
+
+/// Build egui visuals for a custom theme
+pub fn custom_visuals(theme: &CustomTheme) -> egui::Visuals {
+    let color = |hex: &str, fallback: egui::Color32| parse_hex_color(hex).unwrap_or(fallback);
+    let background = color(&theme.background, egui::Color32::from_rgb(32, 34, 37));
+
+    let mut visuals = if luminance(background) < 0.5 {
+        egui::Visuals::dark()
+    } else {
+        egui::Visuals::light()
+    };
+
+    let panel = color(&theme.panel, visuals.panel_fill);
+    let text = color(&theme.text, visuals.text_color());
+    let accent = color(&theme.accent, visuals.selection.bg_fill);
+    let widget = color(&theme.widget, visuals.widgets.inactive.bg_fill);
+
+    visuals.panel_fill = panel;
+    visuals.window_fill = panel;
+    visuals.faint_bg_color = mix(panel, text, 0.05);
+    visuals.extreme_bg_color = background;
+    visuals.override_text_color = Some(text);
+    visuals.hyperlink_color = accent;
+    visuals.selection.bg_fill = accent;
+    visuals.selection.stroke.color = text;
+
+    for (widget_visuals, amount) in [
+        (&mut visuals.widgets.inactive, 0.0),
+        (&mut visuals.widgets.hovered, 0.15),
+        (&mut visuals.widgets.active, 0.3),
+        (&mut visuals.widgets.open, 0.1),
+    ] {
+        widget_visuals.bg_fill = mix(widget, text, amount);
+        widget_visuals.weak_bg_fill = mix(widget, text, amount);
+        widget_visuals.fg_stroke.color = text;
+    }
+    visuals.widgets.noninteractive.bg_fill = panel;
+    visuals.widgets.noninteractive.weak_bg_fill = panel;
+    visuals.widgets.noninteractive.fg_stroke.color = text;
+
+    visuals
+}
+
+/// Parse a `#rrggbb` color
+pub(crate) fn parse_hex_color(hex: &str) -> Option<egui::Color32> {
+    if hex.starts_with('#') && hex.len() == 7 {
+        let r = u8::from_str_radix(&hex[1..3], 16).ok()?;
+        let g = u8::from_str_radix(&hex[3..5], 16).ok()?;
+        let b = u8::from_str_radix(&hex[5..7], 16).ok()?;
+        Some(egui::Color32::from_rgb(r, g, b))
+    } else {
+        None
+    }
+}
+
+/// Format a color as `#rrggbb`
+pub(crate) fn format_hex_color(color: egui::Color32) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
+}
+
+/// Blend two colors; `amount` 0.0 gives `from`, 1.0 gives `to`
+fn mix(from: egui::Color32, to: egui::Color32, amount: f32) -> egui::Color32 {
+    let channel = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * amount).round() as u8;
+    egui::Color32::from_rgb(
+        channel(from.r(), to.r()),
+        channel(from.g(), to.g()),
+        channel(from.b(), to.b()),
+    )
+}
+
+/// Approximate perceived brightness in 0.0..=1.0
+fn luminance(color: egui::Color32) -> f32 {
+    (0.299 * color.r() as f32 + 0.587 * color.g() as f32 + 0.114 * color.b() as f32) / 255.0
+}
@@ -0,0 +1,470 @@
+//! Animated image wallpapers: GIF, APNG and animated WebP
+//!
+//! Backends setting an image would only show the first frame, so the frames
+//! are decoded up front with their delays and drawn in-process, each placed on
+//! the monitor like a static image, with its adjustments, when its turn
+//! comes. Frames much larger than the monitor are shrunk while decoding to
+//! keep the memory in check.
+use crate::core::config::{AnimatedImageConfig, CropRect, PlacementConfig};
+use crate::core::{placement, AdjustmentStore, Adjustments, AppError, AppResult, WallpaperType};
+use crate::experiments::effects::{self, temperature};
+use crate::platform::WallpaperManager;
+use crate::wallpapers::frame_player::{self, FramePlayer, FrameSource};
+use async_trait::async_trait;
+use image::codecs::gif::GifDecoder;
+use image::codecs::png::PngDecoder;
+use image::codecs::webp::WebPDecoder;
+use image::imageops::FilterType;
+use image::{AnimationDecoder, DynamicImage, Frames, RgbaImage};
+use log::{debug, info, warn};
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Most memory the decoded frames may take; later frames are dropped
+const MAX_DECODED_BYTES: u64 = 512 * 1024 * 1024;
+
+/// Bytes read to tell what kind of image a file is
+const HEADER_SIZE: u64 = 64 * 1024;
+
+/// Delay shown for frames asking for 10 ms or less, as browsers do
+const DEFAULT_DELAY: Duration = Duration::from_millis(100);
+
+/// Image formats that can hold an animation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Container {
+    /// GIF, animated when it has several frames
+    Gif,
+
+    /// PNG, animated when it has an animation control chunk
+    Png { animated: bool },
+
+    /// WebP, animated when its extended header says so
+    WebP { animated: bool },
+}
+
+/// Tell the format of an image from its first bytes
+fn container(header: &[u8]) -> Option<Container> {
+    if header.starts_with(b"GIF8") {
+        return Some(Container::Gif);
+    }
+    if header.starts_with(b"\x89PNG\r\n\x1a\n") {
+        // APNGs have an acTL chunk before the image data
+        let mut offset = 8;
+        while let Some(chunk) = header.get(offset..offset + 8) {
+            let length = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]) as usize;
+            match &chunk[4..8] {
+                b"acTL" => return Some(Container::Png { animated: true }),
+                b"IDAT" | b"IEND" => break,
+                _ => offset += 12 + length,
+            }
+        }
+        return Some(Container::Png { animated: false });
+    }
+    if header.len() >= 21 && header.starts_with(b"RIFF") && &header[8..12] == b"WEBP" {
+        let animated = &header[12..16] == b"VP8X" && header[20] & 0x02 != 0;
+        return Some(Container::WebP { animated });
+    }
+    None
+}
+
+/// Read the first bytes of a file
+fn read_header(path: &Path) -> AppResult<Vec<u8>> {
+    let mut header = Vec::new();
+    File::open(path)?
+        .take(HEADER_SIZE)
+        .read_to_end(&mut header)?;
+    Ok(header)
+}
+
+/// Check whether an image moves: a GIF of several frames, an APNG or an animated WebP
+pub fn is_animated(path: &Path) -> bool {
+    match read_header(path).ok().as_deref().and_then(container) {
+        Some(Container::Gif) => File::open(path)
+            .ok()
+            .and_then(|file| GifDecoder::new(BufReader::new(file)).ok())
+            .map_or(false, |decoder| {
+                decoder.into_frames().take(2).filter(Result::is_ok).count() > 1
+            }),
+        Some(Container::Png { animated } | Container::WebP { animated }) => animated,
+        None => false,
+    }
+}
+
+/// Frames of an animated image with the time each is shown
+pub struct Animation {
+    /// Frames, each a whole picture
+    frames: Vec<DynamicImage>,
+
+    /// When each frame ends, counted from the start of a loop
+    ends: Vec<Duration>,
+}
+
+impl Animation {
+    /// Decode the frames of an image, shrinking them until they just cover `size`
+    ///
+    /// Still images become an animation of one frame.
+    pub fn open(path: &Path, size: (u32, u32)) -> AppResult<Self> {
+        let open_error = |e: &dyn std::fmt::Display| {
+            AppError::WallpaperError(format!("Failed to open {}: {}", path.display(), e))
+        };
+        let reader = || File::open(path).map(BufReader::new);
+        let frames: Frames<'static> = match container(&read_header(path)?) {
+            Some(Container::Gif) => GifDecoder::new(reader()?)
+                .map_err(|e| open_error(&e))?
+                .into_frames(),
+            Some(Container::Png { animated: true }) => PngDecoder::new(reader()?)
+                .map_err(|e| open_error(&e))?
+                .apng()
+                .into_frames(),
+            Some(Container::WebP { animated: true }) => WebPDecoder::new(reader()?)
+                .map_err(|e| open_error(&e))?
+                .into_frames(),
+            _ => {
+                let image = image::open(path).map_err(|e| open_error(&e))?;
+                return Ok(Self {
+                    frames: vec![cover(image, size)],
+                    ends: vec![DEFAULT_DELAY],
+                });
+            }
+        };
+
+        let mut animation = Self {
+            frames: Vec::new(),
+            ends: Vec::new(),
+        };
+        let mut decoded: u64 = 0;
+        let mut total = Duration::ZERO;
+        for frame in frames {
+            let frame = frame.map_err(|e| open_error(&e))?;
+            let delay = frame_delay(frame.delay().numer_denom_ms());
+            let image = cover(DynamicImage::ImageRgba8(frame.into_buffer()), size);
+            decoded += image.width() as u64 * image.height() as u64 * 4;
+            if decoded > MAX_DECODED_BYTES {
+                warn!(
+                    "{} is too large to keep every frame; playing the first {}",
+                    path.display(),
+                    animation.frames.len()
+                );
+                break;
+            }
+            total += delay;
+            animation.frames.push(image);
+            animation.ends.push(total);
+        }
+        if animation.frames.is_empty() {
+            return Err(AppError::WallpaperError(format!(
+                "{} has no frames",
+                path.display()
+            )));
+        }
+        debug!(
+            "Decoded {} frames of {} lasting {:?}",
+            animation.frames.len(),
+            path.display(),
+            total
+        );
+        Ok(animation)
+    }
+
+    /// Get the number of frames
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Check whether there are no frames
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Get how long one loop takes
+    pub fn duration(&self) -> Duration {
+        self.ends.last().copied().unwrap_or_default()
+    }
+
+    /// Get a frame
+    pub fn frame(&self, index: usize) -> &DynamicImage {
+        &self.frames[index]
+    }
+
+    /// Get the frame shown `elapsed` after the start
+    ///
+    /// After `loops` loops the last frame stays; 0 loops forever.
+    pub fn index_at(&self, elapsed: Duration, loops: u32) -> usize {
+        let duration = self.duration();
+        if duration.is_zero() {
+            return 0;
+        }
+        if loops > 0 && elapsed >= duration * loops {
+            return self.frames.len() - 1;
+        }
+        let into_loop = Duration::from_nanos((elapsed.as_nanos() % duration.as_nanos()) as u64);
+        self.ends
+            .partition_point(|end| *end <= into_loop)
+            .min(self.frames.len() - 1)
+    }
+}
+
+/// Turn a frame delay in milliseconds, as a fraction, into the time it's shown
+fn frame_delay((numer, denom): (u32, u32)) -> Duration {
+    let ms = numer as f64 / denom.max(1) as f64;
+    // Many GIFs say 0 and rely on browsers slowing them down
+    if ms <= 10.0 {
+        DEFAULT_DELAY
+    } else {
+        Duration::from_secs_f64(ms / 1000.0)
+    }
+}
+
+/// Shrink an image until it just covers `size`, keeping its aspect ratio
+fn cover(image: DynamicImage, (width, height): (u32, u32)) -> DynamicImage {
+    let scale = (width as f64 / image.width() as f64).max(height as f64 / image.height() as f64);
+    if scale >= 1.0 {
+        return image;
+    }
+    let new_width = ((image.width() as f64 * scale).round() as u32).max(1);
+    let new_height = ((image.height() as f64 * scale).round() as u32).max(1);
+    image.resize_exact(new_width, new_height, FilterType::Triangle)
+}
+
+/// Animated image wallpaper, drawn in-process
+pub struct AnimatedImageWallpaper {
+    /// Image path
+    path: PathBuf,
+
+    /// Speed, loops and frame rate
+    settings: AnimatedImageConfig,
+
+    /// How the frames are fitted
+    placement: PlacementConfig,
+
+    /// Player showing the frames
+    player: FramePlayer,
+}
+
+impl AnimatedImageWallpaper {
+    /// Create a new animated image wallpaper
+    pub fn new<P: AsRef<Path>>(
+        path: P,
+        wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>,
+    ) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            settings: AnimatedImageConfig::default(),
+            placement: PlacementConfig::default(),
+            player: FramePlayer::new(wallpaper_manager),
+        }
+    }
+
+    /// Set the speed, loops and frame rate
+    pub fn with_settings(mut self, settings: AnimatedImageConfig) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Set how the frames are fitted
+    pub fn with_placement(mut self, placement: PlacementConfig) -> Self {
+        self.placement = placement;
+        self
+    }
+}
+
+/// Frames of an animated image placed on the monitor
+struct Playback {
+    /// Decoded frames
+    animation: Animation,
+
+    /// How the frames are fitted
+    placement: PlacementConfig,
+
+    /// Part cropped by hand for the monitor, if any
+    crop: Option<CropRect>,
+
+    /// Frame size
+    size: (u32, u32),
+
+    /// Adjustments applied to each frame
+    adjustments: Adjustments,
+
+    /// Speed, loops and frame rate
+    settings: AnimatedImageConfig,
+
+    /// Frame on screen
+    shown: Option<usize>,
+}
+
+impl FrameSource for Playback {
+    fn frame(&mut self, elapsed: Duration) -> Option<RgbaImage> {
+        let elapsed = elapsed.mul_f32(self.settings.speed.clamp(0.1, 4.0));
+        let index = self.animation.index_at(elapsed, self.settings.loops);
+        if self.shown == Some(index) {
+            return None;
+        }
+        self.shown = Some(index);
+
+        let (width, height) = self.size;
+        let mut frame = placement::render_placed(
+            self.animation.frame(index),
+            &self.placement,
+            self.crop,
+            width,
+            height,
+        );
+        self.adjustments.apply(&mut frame);
+        if let Some(kelvin) = temperature::kelvin_now() {
+            temperature::tint(&mut frame, kelvin);
+        }
+        Some(frame)
+    }
+}
+
+#[async_trait]
+impl super::Wallpaper for AnimatedImageWallpaper {
+    fn get_type(&self) -> WallpaperType {
+        WallpaperType::AnimatedImage
+    }
+
+    fn get_path(&self) -> Option<&Path> {
+        Some(&self.path)
+    }
+
+    async fn start(&self) -> AppResult<()> {
+        debug!("Starting animated image wallpaper: {:?}", self.path);
+
+        let (path, settings, placement) = (
+            self.path.clone(),
+            self.settings.clone(),
+            self.placement.clone(),
+        );
+        let playback = tokio::task::spawn_blocking(move || {
+            let size = frame_player::frame_size();
+            Animation::open(&path, size).map(|animation| Playback {
+                animation,
+                adjustments: AdjustmentStore::shared().get(&path),
+                crop: effects::primary_crop(&placement),
+                placement,
+                size,
+                settings,
+                shown: None,
+            })
+        })
+        .await
+        .map_err(|e| {
+            AppError::WallpaperError(format!("Failed to decode the animation: {}", e))
+        })??;
+        self.player.start(Box::new(playback), self.settings.fps);
+
+        info!("Animated image wallpaper started");
+        Ok(())
+    }
+
+    async fn stop(&self) -> AppResult<()> {
+        debug!("Stopping animated image wallpaper");
+        self.player.stop();
+        info!("Animated image wallpaper stopped");
+        Ok(())
+    }
+
+    async fn pause(&self) -> AppResult<()> {
+        self.player.pause();
+        info!("Animated image wallpaper paused");
+        Ok(())
+    }
+
+    async fn resume(&self) -> AppResult<()> {
+        self.player.resume();
+        info!("Animated image wallpaper resumed");
+        Ok(())
+    }
+
+    async fn capture_frame(&self, path: &Path) -> AppResult<()> {
+        self.player.capture_frame(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::codecs::gif::GifEncoder;
+    use image::{Delay, Frame, Rgba};
+
+    /// Write a GIF whose frames are red, green and blue for the given delays
+    fn write_gif(path: &Path, delays_ms: &[u32]) {
+        let mut encoder = GifEncoder::new(File::create(path).unwrap());
+        let colors = [
+            Rgba([255, 0, 0, 255]),
+            Rgba([0, 255, 0, 255]),
+            Rgba([0, 0, 255, 255]),
+        ];
+        for (delay, color) in delays_ms.iter().zip(colors) {
+            let frame = Frame::from_parts(
+                RgbaImage::from_pixel(4, 4, color),
+                0,
+                0,
+                Delay::from_numer_denom_ms(*delay, 1),
+            );
+            encoder.encode_frame(frame).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_container() {
+        assert_eq!(container(b"GIF89a...."), Some(Container::Gif));
+
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png.extend([0, 0, 0, 13]);
+        png.extend(b"IHDR");
+        png.extend([0; 17]);
+        let mut apng = png.clone();
+        apng.extend([0, 0, 0, 8]);
+        apng.extend(b"acTL");
+        png.extend([0, 0, 0, 0]);
+        png.extend(b"IDAT");
+        png.extend([0; 4]);
+        assert_eq!(container(&apng), Some(Container::Png { animated: true }));
+        assert_eq!(container(&png), Some(Container::Png { animated: false }));
+
+        let mut webp = b"RIFF\0\0\0\0WEBPVP8X\x0a\0\0\0".to_vec();
+        webp.extend([0x02, 0, 0, 0]);
+        assert_eq!(container(&webp), Some(Container::WebP { animated: true }));
+        webp[20] = 0x10;
+        assert_eq!(container(&webp), Some(Container::WebP { animated: false }));
+        assert_eq!(container(b"\xff\xd8\xff\xe0"), None);
+    }
+
+    #[test]
+    fn test_frame_timing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dots.gif");
+        write_gif(&path, &[200, 0, 300]);
+        assert!(is_animated(&path));
+
+        let animation = Animation::open(&path, (2, 2)).unwrap();
+        assert_eq!(animation.len(), 3);
+        assert_eq!(
+            (animation.frame(0).width(), animation.frame(0).height()),
+            (2, 2)
+        );
+        // The frame without a delay is shown for the default one
+        assert_eq!(animation.duration(), Duration::from_millis(600));
+
+        let at = |ms: u64, loops: u32| animation.index_at(Duration::from_millis(ms), loops);
+        assert_eq!(at(0, 0), 0);
+        assert_eq!(at(199, 0), 0);
+        assert_eq!(at(200, 0), 1);
+        assert_eq!(at(350, 0), 2);
+        assert_eq!(at(650, 0), 0);
+        // Stops on the last frame once the loops are played
+        assert_eq!(at(1250, 2), 2);
+        assert_eq!(at(650, 2), 0);
+
+        let still = dir.path().join("still.gif");
+        write_gif(&still, &[100]);
+        assert!(!is_animated(&still));
+        let animation = Animation::open(&still, (8, 8)).unwrap();
+        assert_eq!(animation.len(), 1);
+        assert_eq!(animation.index_at(Duration::from_secs(60), 0), 0);
+    }
+}
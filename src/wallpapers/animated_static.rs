@@ -0,0 +1,290 @@
+use crate::core::config::{AnimatedStaticConfig, FitMode, MotionEffect};
+use crate::core::{image_format, placement, AdjustmentStore, AppError, AppResult, WallpaperType};
+use crate::experiments::effects::motion::{self, View};
+use crate::platform::{cursor, display, WallpaperManager};
+use crate::wallpapers::frame_player::{self, FramePlayer, FrameSource};
+use async_trait::async_trait;
+use image::{DynamicImage, GrayImage, RgbaImage};
+use log::{debug, info};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Share of the way to the cursor the parallax moves each frame, so it glides
+const PARALLAX_EASING: f32 = 0.25;
+
+/// Smallest cursor offset change worth a new parallax frame
+const PARALLAX_MIN_STEP: f32 = 0.005;
+
+/// Static image animated by a motion effect, drawn in-process
+pub struct AnimatedStaticWallpaper {
+    /// Image path
+    path: PathBuf,
+
+    /// Motion settings
+    animation: AnimatedStaticConfig,
+
+    /// Player showing the frames
+    player: FramePlayer,
+}
+
+impl AnimatedStaticWallpaper {
+    /// Create a new animated static wallpaper
+    pub fn new<P: AsRef<Path>>(
+        path: P,
+        wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>,
+    ) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            animation: AnimatedStaticConfig::default(),
+            player: FramePlayer::new(wallpaper_manager),
+        }
+    }
+
+    /// Set the motion settings
+    pub fn with_animation(mut self, animation: AnimatedStaticConfig) -> Self {
+        self.animation = animation;
+        self
+    }
+}
+
+/// Load the image at frame size, with its adjustments, and build its frame source
+pub(crate) fn frame_source(
+    path: &Path,
+    animation: &AnimatedStaticConfig,
+) -> AppResult<Box<dyn FrameSource>> {
+    let image = image_format::open(path)?;
+    let (width, height) = frame_player::frame_size();
+    let adjustments = AdjustmentStore::shared().get(path);
+
+    match animation.effect {
+        MotionEffect::Parallax => {
+            let mut fitted = placement::render(&image, FitMode::Fill, None, width, height);
+            adjustments.apply(&mut fitted);
+            // The depth map is cropped like the image so the two line up
+            let depth = animation.depth_map.then(|| {
+                let depth = DynamicImage::ImageLuma8(motion::depth_map(path, &image));
+                DynamicImage::ImageRgba8(placement::render(
+                    &depth,
+                    FitMode::Fill,
+                    None,
+                    width,
+                    height,
+                ))
+                .to_luma8()
+            });
+            Ok(Box::new(Parallax {
+                image: fitted,
+                depth,
+                strength: animation.parallax_strength,
+                desktop: desktop_bounds(),
+                offset: None,
+            }))
+        }
+        MotionEffect::KenBurns => {
+            // Fitted larger than the frame so zoomed in parts stay sharp
+            let zoom = animation.ken_burns_zoom.clamp(1.0, 2.0);
+            let mut fitted = placement::render(
+                &image,
+                FitMode::Fill,
+                None,
+                (width as f32 * zoom) as u32,
+                (height as f32 * zoom) as u32,
+            );
+            adjustments.apply(&mut fitted);
+            Ok(Box::new(KenBurns {
+                image: fitted,
+                size: (width, height),
+                zoom,
+                period: Duration::from_secs(animation.ken_burns_minutes.max(1) as u64 * 60),
+                from: View::FULL,
+                to: View::zoomed(zoom, random_spot()),
+                started: Duration::ZERO,
+                last_rect: None,
+            }))
+        }
+    }
+}
+
+/// Pick a random spot, 0 to 1 on each axis, for Ken Burns to move to
+fn random_spot() -> (f32, f32) {
+    let mut bytes = [0u8; 2];
+    let _ = getrandom::fill(&mut bytes);
+    (bytes[0] as f32 / 255.0, bytes[1] as f32 / 255.0)
+}
+
+/// Get the desktop's bounds (x, y, width, height) across all monitors
+fn desktop_bounds() -> (i32, i32, u32, u32) {
+    let monitors = display::monitors().unwrap_or_default();
+    let left = monitors.iter().map(|m| m.x).min().unwrap_or(0);
+    let top = monitors.iter().map(|m| m.y).min().unwrap_or(0);
+    let right = monitors
+        .iter()
+        .map(|m| m.x + m.width as i32)
+        .max()
+        .unwrap_or(1920);
+    let bottom = monitors
+        .iter()
+        .map(|m| m.y + m.height as i32)
+        .max()
+        .unwrap_or(1080);
+    (
+        left,
+        top,
+        (right - left).max(1) as u32,
+        (bottom - top).max(1) as u32,
+    )
+}
+
+/// Parallax following the mouse
+struct Parallax {
+    /// Image fitted to the frame
+    image: RgbaImage,
+
+    /// Depth of each pixel, white nearest, when parts move by their depth
+    depth: Option<GrayImage>,
+
+    /// Farthest the nearest parts move, as a fraction of the width
+    strength: f32,
+
+    /// Desktop the cursor moves on
+    desktop: (i32, i32, u32, u32),
+
+    /// Cursor offset of the frame on screen, None before the first frame
+    offset: Option<(f32, f32)>,
+}
+
+impl FrameSource for Parallax {
+    fn frame(&mut self, _elapsed: Duration) -> Option<RgbaImage> {
+        // Where the cursor isn't reported, the image rests in the middle
+        let target = cursor::position().map_or((0.0, 0.0), |position| {
+            motion::cursor_offset(position, self.desktop)
+        });
+        let next = match self.offset {
+            Some((x, y)) => (
+                x + (target.0 - x) * PARALLAX_EASING,
+                y + (target.1 - y) * PARALLAX_EASING,
+            ),
+            None => target,
+        };
+        if let Some((x, y)) = self.offset {
+            if (next.0 - x).abs() < PARALLAX_MIN_STEP && (next.1 - y).abs() < PARALLAX_MIN_STEP {
+                return None;
+            }
+        }
+        self.offset = Some(next);
+        Some(motion::parallax(
+            &self.image,
+            self.depth.as_ref(),
+            next,
+            self.strength,
+        ))
+    }
+}
+
+/// Slow pan and zoom across the image
+struct KenBurns {
+    /// Image fitted to the frame, enlarged by the zoom
+    image: RgbaImage,
+
+    /// Frame size
+    size: (u32, u32),
+
+    /// How far zoomed in views are
+    zoom: f32,
+
+    /// How long each move takes
+    period: Duration,
+
+    /// View the current move started from
+    from: View,
+
+    /// View the current move ends at
+    to: View,
+
+    /// When the current move started
+    started: Duration,
+
+    /// Rectangle of the image on screen
+    last_rect: Option<(u32, u32, u32, u32)>,
+}
+
+impl FrameSource for KenBurns {
+    fn frame(&mut self, elapsed: Duration) -> Option<RgbaImage> {
+        if elapsed >= self.started + self.period {
+            // After the whole image, zoom in somewhere; after a zoomed in
+            // view, either pan to another spot or zoom back out
+            let next = if self.to == View::FULL || random_spot().0 < 0.5 {
+                View::zoomed(self.zoom, random_spot())
+            } else {
+                View::FULL
+            };
+            self.from = std::mem::replace(&mut self.to, next);
+            self.started = elapsed;
+        }
+        let progress = (elapsed - self.started).as_secs_f32() / self.period.as_secs_f32();
+        let view = self.from.towards(self.to, progress);
+
+        // Moves take minutes, so most frames would show the same pixels
+        let rect = view.rect(self.image.width(), self.image.height());
+        if self.last_rect == Some(rect) {
+            return None;
+        }
+        self.last_rect = Some(rect);
+        Some(motion::ken_burns(
+            &self.image,
+            view,
+            self.size.0,
+            self.size.1,
+        ))
+    }
+}
+
+#[async_trait]
+impl super::Wallpaper for AnimatedStaticWallpaper {
+    fn get_type(&self) -> WallpaperType {
+        WallpaperType::AnimatedStatic
+    }
+
+    fn get_path(&self) -> Option<&Path> {
+        Some(&self.path)
+    }
+
+    async fn start(&self) -> AppResult<()> {
+        debug!("Starting animated static wallpaper: {:?}", self.path);
+
+        let (path, animation) = (self.path.clone(), self.animation.clone());
+        let source = tokio::task::spawn_blocking(move || frame_source(&path, &animation))
+            .await
+            .map_err(|e| {
+                AppError::WallpaperError(format!("Failed to prepare the animation: {}", e))
+            })??;
+        self.player.start(source, self.animation.fps);
+
+        info!("Animated static wallpaper started");
+        Ok(())
+    }
+
+    async fn stop(&self) -> AppResult<()> {
+        debug!("Stopping animated static wallpaper");
+        self.player.stop();
+        info!("Animated static wallpaper stopped");
+        Ok(())
+    }
+
+    async fn pause(&self) -> AppResult<()> {
+        self.player.pause();
+        info!("Animated static wallpaper paused");
+        Ok(())
+    }
+
+    async fn resume(&self) -> AppResult<()> {
+        self.player.resume();
+        info!("Animated static wallpaper resumed");
+        Ok(())
+    }
+
+    async fn capture_frame(&self, path: &Path) -> AppResult<()> {
+        self.player.capture_frame(path)
+    }
+}
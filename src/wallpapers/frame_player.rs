@@ -0,0 +1,458 @@
+//! Player for wallpapers drawn in-process
+//!
+//! A worker thread asks the wallpaper's frame source for frames and pipes
+//! them as raw pixels to a single MPV process, which shows them in a
+//! wallpaper window on the desktop the way video wallpapers play. Frames
+//! never touch the disk and the backend isn't asked to set anything while
+//! they're drawn. Sources only draw when the picture changed, MPV keeps the
+//! last frame up in between, and the worker waits while the wallpaper is
+//! paused. Where MPV can't be started, the first frame is set as a still
+//! wallpaper instead.
+use crate::core::image_pipeline::ImagePipeline;
+use crate::core::{runtime, AppError, AppResult, Config, ProcessHandle};
+use crate::platform::{display, WallpaperManager};
+use crate::wallpapers::video_wallpaper::VideoWallpaper;
+use image::RgbaImage;
+use log::{debug, warn};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+#[cfg(windows)]
+use crate::platform::windows::window_manager::WindowManager;
+
+/// Widest frame drawn; MPV scales it up to the monitor
+const MAX_FRAME_WIDTH: u32 = 1920;
+
+/// How often a paused player checks whether it was resumed
+const PAUSED_POLL: Duration = Duration::from_millis(200);
+
+/// Draws the frames of an in-process wallpaper
+pub trait FrameSource: Send {
+    /// Draw the frame `elapsed` after the start, not counting pauses
+    ///
+    /// Returns None while the frame on screen is still right.
+    fn frame(&mut self, elapsed: Duration) -> Option<RgbaImage>;
+}
+
+/// Shows the frames a player draws
+pub trait FrameOutput: Send {
+    /// Show a frame in place of the one on screen
+    fn show(&mut self, frame: &RgbaImage) -> AppResult<()>;
+}
+
+/// Get the size frames are drawn at: the primary monitor's, no wider than 1920 pixels
+pub fn frame_size() -> (u32, u32) {
+    let monitor = display::monitors().ok().and_then(|monitors| {
+        monitors
+            .iter()
+            .find(|m| m.primary)
+            .or(monitors.first())
+            .cloned()
+    });
+    let (width, height) = monitor.map_or((MAX_FRAME_WIDTH, 1080), |m| {
+        (m.width.max(1), m.height.max(1))
+    });
+    let scale = (MAX_FRAME_WIDTH as f32 / width as f32).min(1.0);
+    (
+        ((width as f32 * scale) as u32).max(1),
+        ((height as f32 * scale) as u32).max(1),
+    )
+}
+
+/// Shows the frames of a source on the desktop
+pub struct FramePlayer {
+    /// Platform-specific wallpaper manager, setting the still frame when MPV is missing
+    wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>,
+
+    /// Whether the worker should keep going
+    running: Arc<AtomicBool>,
+
+    /// Whether the worker is holding the current frame
+    paused: Arc<AtomicBool>,
+
+    /// Worker drawing the frames
+    worker: Mutex<Option<JoinHandle<()>>>,
+
+    /// Frame on screen
+    last_frame: Arc<Mutex<Option<RgbaImage>>>,
+}
+
+impl FramePlayer {
+    /// Create a player showing frames in a wallpaper window
+    pub fn new(wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>) -> Self {
+        Self {
+            wallpaper_manager,
+            running: Arc::new(AtomicBool::new(false)),
+            paused: Arc::new(AtomicBool::new(false)),
+            worker: Mutex::new(None),
+            last_frame: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Start showing frames of `source` in a wallpaper window, at most `fps` a second
+    pub fn start(&self, source: Box<dyn FrameSource>, fps: u32) {
+        self.start_with(source, fps, Box::new(WallpaperWindow::new(fps)));
+    }
+
+    /// Start showing frames of `source` through `output`, at most `fps` a second
+    ///
+    /// The output is dropped when the player stops.
+    pub fn start_with(
+        &self,
+        mut source: Box<dyn FrameSource>,
+        fps: u32,
+        mut output: Box<dyn FrameOutput>,
+    ) {
+        self.stop();
+        self.running.store(true, Ordering::SeqCst);
+        self.paused.store(false, Ordering::SeqCst);
+
+        let wallpaper_manager = self.wallpaper_manager.clone();
+        let running = self.running.clone();
+        let paused = self.paused.clone();
+        let last_frame = self.last_frame.clone();
+        let interval = Duration::from_secs_f32(1.0 / fps.clamp(1, 60) as f32);
+
+        *self.worker.lock().unwrap() = Some(thread::spawn(move || {
+            let mut elapsed = Duration::ZERO;
+            let mut count: u64 = 0;
+            while running.load(Ordering::SeqCst) {
+                if paused.load(Ordering::SeqCst) {
+                    thread::sleep(PAUSED_POLL);
+                    continue;
+                }
+                let started = Instant::now();
+                if let Some(frame) = source.frame(elapsed) {
+                    let shown = output.show(&frame);
+                    count += 1;
+                    *last_frame.lock().unwrap() = Some(frame);
+                    if let Err(e) = shown {
+                        if count == 1 {
+                            warn!("Failed to open a wallpaper window, showing a still frame instead: {}", e);
+                            if let Some(frame) = &*last_frame.lock().unwrap() {
+                                if let Err(e) = show_still(&*wallpaper_manager, frame) {
+                                    warn!("Failed to show the still frame: {}", e);
+                                }
+                            }
+                        } else {
+                            warn!("Wallpaper window stopped showing frames: {}", e);
+                        }
+                        break;
+                    }
+                }
+                thread::sleep(interval.saturating_sub(started.elapsed()));
+                elapsed += started.elapsed();
+            }
+            debug!("Frame player stopped after {} frames", count);
+        }));
+    }
+
+    /// Stop drawing frames and close the window
+    pub fn stop(&self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(worker) = self.worker.lock().unwrap().take() {
+            let _ = worker.join();
+        }
+    }
+
+    /// Check whether frames are still being shown
+    ///
+    /// False once stopped, or when the window closed or couldn't be opened.
+    pub fn is_playing(&self) -> bool {
+        self.worker
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|worker| !worker.is_finished())
+    }
+
+    /// Hold the current frame
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::SeqCst);
+    }
+
+    /// Continue drawing frames
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::SeqCst);
+    }
+
+    /// Save the frame on screen as an image
+    pub fn capture_frame(&self, path: &Path) -> AppResult<()> {
+        match &*self.last_frame.lock().unwrap() {
+            Some(frame) => frame.save(path).map_err(|e| {
+                AppError::WallpaperError(format!("Failed to save {}: {}", path.display(), e))
+            }),
+            None => Err(AppError::WallpaperError(
+                "No frame has been drawn yet".to_string(),
+            )),
+        }
+    }
+}
+
+impl Drop for FramePlayer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// MPV showing piped frames in a wallpaper window, opened with the first frame
+struct WallpaperWindow {
+    /// Frame rate the frames are timed at
+    fps: u32,
+
+    /// MPV once the first frame was shown
+    open: Option<OpenWindow>,
+}
+
+impl WallpaperWindow {
+    /// Create a window showing frames drawn at `fps` a second
+    fn new(fps: u32) -> Self {
+        Self {
+            fps: fps.clamp(1, 60),
+            open: None,
+        }
+    }
+}
+
+impl FrameOutput for WallpaperWindow {
+    fn show(&mut self, frame: &RgbaImage) -> AppResult<()> {
+        if self.open.is_none() {
+            self.open = Some(OpenWindow::open(frame.width(), frame.height(), self.fps)?);
+        }
+        let window = self.open.as_mut().unwrap();
+
+        // MPV reads frames of the size it was started with
+        let resized;
+        let frame = if frame.dimensions() == window.size {
+            frame
+        } else {
+            resized = ImagePipeline::new(frame.clone())
+                .resize(window.size.0, window.size.1)
+                .into_rgba8();
+            &resized
+        };
+        window
+            .input
+            .write_all(frame.as_raw())
+            .and_then(|()| window.input.flush())
+            .map_err(|e| AppError::WallpaperError(format!("MPV stopped reading frames: {}", e)))
+    }
+}
+
+/// Running MPV reading frames from its standard input
+struct OpenWindow {
+    /// MPV's standard input
+    input: ChildStdin,
+
+    /// Size of the frames MPV reads
+    size: (u32, u32),
+
+    /// MPV process, killed with the window
+    _process: ProcessHandle,
+
+    /// Window MPV draws in (Windows only)
+    #[cfg(windows)]
+    _window: WindowManager,
+}
+
+impl OpenWindow {
+    /// Start MPV showing `width` x `height` frames on the desktop
+    fn open(width: u32, height: u32, fps: u32) -> AppResult<Self> {
+        let mut command = Command::new(VideoWallpaper::get_mpv_command()?);
+        command
+            .args(mpv_args(width, height, fps))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+
+        // Placed like video wallpapers: behind the desktop icons on Windows, fullscreen elsewhere
+        #[cfg(windows)]
+        let window = {
+            let mut window = WindowManager::new();
+            let hwnd = window.create_wallpaper_window()?;
+            command
+                .arg(format!("--wid={}", hwnd.0))
+                .arg("--no-keepaspect-window");
+            window
+        };
+        #[cfg(not(windows))]
+        {
+            command.arg("--fs");
+            let monitors = display::monitors().unwrap_or_default();
+            if let Some(primary) = monitors.iter().find(|m| m.primary) {
+                command.arg(format!("--fs-screen-name={}", primary.name));
+            }
+        }
+        command.arg("-");
+
+        let mut process = ProcessHandle::spawn(&mut command, "mpv", "wallpaper window")?;
+        let input = process
+            .take_stdin()
+            .ok_or_else(|| AppError::WallpaperError("MPV's input couldn't be piped".to_string()))?;
+        debug!("Opened a {}x{} wallpaper window", width, height);
+        Ok(Self {
+            input,
+            size: (width, height),
+            _process: process,
+            #[cfg(windows)]
+            _window: window,
+        })
+    }
+}
+
+/// Build MPV's arguments for showing raw RGBA frames from its standard input
+///
+/// Frames are shown as soon as they arrive rather than on a clock, and the
+/// last one stays up until the next.
+fn mpv_args(width: u32, height: u32, fps: u32) -> Vec<String> {
+    let mut args: Vec<String> = [
+        "--no-config",
+        "--quiet",
+        "--no-terminal",
+        "--no-audio",
+        "--no-border",
+        "--osd-level=0",
+        "--no-input-default-bindings",
+        "--no-input-cursor",
+        "--keepaspect=no",
+        "--keep-open=yes",
+        "--untimed",
+        "--cache=no",
+        "--demuxer-readahead-secs=0",
+        "--demuxer=rawvideo",
+        "--demuxer-rawvideo-mp-format=rgba",
+    ]
+    .map(String::from)
+    .to_vec();
+    args.push(format!("--demuxer-rawvideo-w={}", width));
+    args.push(format!("--demuxer-rawvideo-h={}", height));
+    args.push(format!("--demuxer-rawvideo-fps={}", fps));
+    args
+}
+
+/// Set a frame as a still wallpaper, for when no wallpaper window can be opened
+fn show_still(
+    wallpaper_manager: &(dyn WallpaperManager + Send + Sync),
+    frame: &RgbaImage,
+) -> AppResult<()> {
+    // New names each time, as backends caching by path would show an old still
+    let dir = Config::get_placement_dir().join("frames");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!(
+        "still-{}.png",
+        chrono::Utc::now().timestamp_millis()
+    ));
+    frame.save(&path).map_err(|e| {
+        AppError::WallpaperError(format!("Failed to save {}: {}", path.display(), e))
+    })?;
+    runtime::block_on(wallpaper_manager.set_static_wallpaper(&path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::{AnimatedStaticConfig, MotionEffect};
+    use crate::platform::mock::MockWallpaperManager;
+    use crate::wallpapers::animated_static;
+    use image::Rgba;
+
+    /// Output keeping the frames shown, and whether it was dropped
+    struct Recorder {
+        frames: Arc<Mutex<Vec<RgbaImage>>>,
+        dropped: Arc<AtomicBool>,
+    }
+
+    impl FrameOutput for Recorder {
+        fn show(&mut self, frame: &RgbaImage) -> AppResult<()> {
+            self.frames.lock().unwrap().push(frame.clone());
+            Ok(())
+        }
+    }
+
+    impl Drop for Recorder {
+        fn drop(&mut self) {
+            self.dropped.store(true, Ordering::SeqCst);
+        }
+    }
+
+    /// Wait up to ten seconds for a condition
+    fn wait_for(condition: impl Fn() -> bool) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while Instant::now() < deadline {
+            if condition() {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        condition()
+    }
+
+    #[test]
+    fn test_mpv_args() {
+        let args = mpv_args(1920, 1080, 30).join(" ");
+        assert!(args.contains("--demuxer=rawvideo"), "{}", args);
+        assert!(
+            args.contains(
+                "--demuxer-rawvideo-w=1920 --demuxer-rawvideo-h=1080 --demuxer-rawvideo-fps=30"
+            ),
+            "{}",
+            args
+        );
+        assert!(args.contains("--untimed"));
+    }
+
+    #[test]
+    fn test_animated_static_playback() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dunes.png");
+        RgbaImage::from_fn(64, 36, |x, y| Rgba([(x * 4) as u8, (y * 7) as u8, 90, 255]))
+            .save(&path)
+            .unwrap();
+
+        // A Ken Burns move lasting a minute changes the view every frame
+        let animation = AnimatedStaticConfig {
+            effect: MotionEffect::KenBurns,
+            ken_burns_minutes: 1,
+            ..AnimatedStaticConfig::default()
+        };
+        let source = animated_static::frame_source(&path, &animation).unwrap();
+        let frames = Arc::new(Mutex::new(Vec::new()));
+        let dropped = Arc::new(AtomicBool::new(false));
+        let player = FramePlayer::new(Arc::new(MockWallpaperManager::new()));
+        player.start_with(
+            source,
+            60,
+            Box::new(Recorder {
+                frames: frames.clone(),
+                dropped: dropped.clone(),
+            }),
+        );
+
+        assert!(wait_for(|| frames.lock().unwrap().len() >= 3));
+        {
+            let frames = frames.lock().unwrap();
+            assert_eq!(frames[0].dimensions(), frame_size());
+            assert_ne!(frames[0], frames[frames.len() - 1]);
+        }
+        let captured = dir.path().join("captured.png");
+        player.capture_frame(&captured).unwrap();
+        assert_eq!(
+            image::open(&captured).unwrap().to_rgba8().dimensions(),
+            frame_size()
+        );
+
+        // Dropping the player stops the frames and closes the output
+        drop(player);
+        assert!(dropped.load(Ordering::SeqCst));
+        let shown = frames.lock().unwrap().len();
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(frames.lock().unwrap().len(), shown);
+    }
+}
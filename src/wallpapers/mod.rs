@@ -1,18 +1,43 @@
+pub mod animated_image;
+pub mod animated_static;
+pub mod audio_wallpaper;
+pub mod frame_player;
+pub mod now_playing;
+pub mod particles;
+pub mod procedural;
+pub mod random;
+pub mod shader_wallpaper;
+pub mod slideshow;
 pub mod static_wallpaper;
 pub mod video_wallpaper;
 pub mod web_wallpaper;
-pub mod shader_wallpaper;
-pub mod audio_wallpaper;
 
+pub use animated_image::*;
+pub use animated_static::*;
+pub use audio_wallpaper::*;
+pub use now_playing::*;
+pub use particles::*;
+pub use procedural::*;
+pub use shader_wallpaper::*;
+pub use slideshow::*;
 pub use static_wallpaper::*;
 pub use video_wallpaper::*;
 pub use web_wallpaper::*;
-pub use shader_wallpaper::*;
-pub use audio_wallpaper::*;
 
-use crate::core::{AppResult, WallpaperType};
-use std::path::Path;
+use crate::core::{AppError, AppResult, WallpaperType};
 use async_trait::async_trait;
+use image::RgbaImage;
+use std::path::Path;
+
+/// Lowered quality of an animated wallpaper
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReducedQuality {
+    /// Highest frame rate
+    pub max_fps: u32,
+
+    /// Resolution scale (0.5 = half width and height)
+    pub scale: f32,
+}
 
 /// Wallpaper trait
 #[async_trait]
@@ -20,19 +45,68 @@ use async_trait::async_trait;
 pub trait Wallpaper {
     /// Get the wallpaper type
     fn get_type(&self) -> WallpaperType;
-    
+
     /// Get the wallpaper path
     fn get_path(&self) -> Option<&Path>;
-    
+
     /// Start the wallpaper
     async fn start(&self) -> AppResult<()>;
-    
+
     /// Stop the wallpaper
     async fn stop(&self) -> AppResult<()>;
-    
+
     /// Pause the wallpaper
     async fn pause(&self) -> AppResult<()>;
-    
+
     /// Resume the wallpaper
     async fn resume(&self) -> AppResult<()>;
-} 
\ No newline at end of file
+
+    /// Lower the quality, or restore full quality with `None`
+    async fn set_reduced_quality(&self, _quality: Option<ReducedQuality>) -> AppResult<()> {
+        Err(AppError::WallpaperError(format!(
+            "{:?} wallpapers can't change their quality",
+            self.get_type()
+        )))
+    }
+
+    /// Get the process ID of the wallpaper's player, if it has one
+    async fn process_id(&self) -> Option<u32> {
+        None
+    }
+
+    /// Get the monitors given their own image, empty when all show the same one
+    fn monitors(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Check whether the wallpaper's player exited on its own
+    ///
+    /// Returns how it exited, once. Players stopped on purpose, and wallpapers
+    /// without a player process to watch, never report a crash.
+    async fn crashed(&self) -> Option<AppError> {
+        None
+    }
+
+    /// Draw the wallpaper again, e.g. after the color temperature changed
+    ///
+    /// Wallpapers that keep drawing themselves have nothing to do.
+    async fn redraw(&self) -> AppResult<()> {
+        Ok(())
+    }
+
+    /// Save the frame on screen as an image
+    async fn capture_frame(&self, _path: &Path) -> AppResult<()> {
+        Err(AppError::WallpaperError(format!(
+            "{:?} wallpapers can't capture a frame",
+            self.get_type()
+        )))
+    }
+
+    /// Show a partly transparent image over the wallpaper, or remove it with `None`
+    async fn set_overlay(&self, _overlay: Option<&RgbaImage>) -> AppResult<()> {
+        Err(AppError::WallpaperError(format!(
+            "{:?} wallpapers can't show an overlay",
+            self.get_type()
+        )))
+    }
+}
@@ -0,0 +1,337 @@
+//! Now-playing wallpapers: the album art of the track playing
+//!
+//! The cover is blown up to fill the monitor, blurred and darkened, with the
+//! sharp cover in the middle. The media player is asked for its track every
+//! few seconds, and a new cover fades in when the track changes. While no
+//! track has a cover the last one stays up.
+use crate::core::config::{NowPlayingConfig, TransitionEffect};
+use crate::core::{AppError, AppResult, HttpClient, RateLimit, WallpaperType};
+use crate::experiments::effects::{self, temperature};
+use crate::platform::media::{self, Track};
+use crate::platform::WallpaperManager;
+use crate::wallpapers::frame_player::{self, FramePlayer, FrameSource};
+use async_trait::async_trait;
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, Rgba, RgbaImage};
+use log::{debug, info, warn};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Rate limit key for album art downloads
+const PROVIDER: &str = "album-art";
+
+/// Color shown before any cover was found
+const EMPTY: Rgba<u8> = Rgba([18, 18, 24, 255]);
+
+/// Size of the sharp cover, as a fraction of the monitor's height
+const COVER_SIZE: f32 = 0.5;
+
+/// Album art of the track playing, drawn in-process
+pub struct NowPlayingWallpaper {
+    /// Blur, cover and transition
+    settings: NowPlayingConfig,
+
+    /// Player showing the frames
+    player: FramePlayer,
+}
+
+impl NowPlayingWallpaper {
+    /// Create a new now-playing wallpaper
+    pub fn new(wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>) -> Self {
+        Self {
+            settings: NowPlayingConfig::default(),
+            player: FramePlayer::new(wallpaper_manager),
+        }
+    }
+
+    /// Set the blur, cover and transition
+    pub fn with_settings(mut self, settings: NowPlayingConfig) -> Self {
+        self.settings = settings;
+        self
+    }
+}
+
+/// Frames of the now-playing wallpaper
+struct Covers {
+    /// Blur, cover and transition
+    settings: NowPlayingConfig,
+
+    /// Size frames are drawn at
+    size: (u32, u32),
+
+    /// Client fetching covers from the web
+    http_client: Arc<HttpClient>,
+
+    /// Track whose cover is shown or coming, to notice changes
+    track: Option<Track>,
+
+    /// Frame shown
+    shown: RgbaImage,
+
+    /// Frame of a new cover and when it started fading in
+    next: Option<(RgbaImage, Duration)>,
+
+    /// When the player is next asked for its track
+    next_poll: Duration,
+
+    /// Whether the first frame was drawn
+    started: bool,
+}
+
+impl Covers {
+    /// Start with a blank frame, replaced as soon as the first cover is drawn
+    fn new(settings: NowPlayingConfig) -> Self {
+        let http_client = HttpClient::shared();
+        http_client.set_rate_limit(PROVIDER, RateLimit::per_minute(30));
+        let size = frame_player::frame_size();
+        Self {
+            settings,
+            size,
+            http_client,
+            track: None,
+            shown: RgbaImage::from_pixel(size.0, size.1, EMPTY),
+            next: None,
+            next_poll: Duration::ZERO,
+            started: false,
+        }
+    }
+
+    /// Ask the player for its track, drawing the cover of a new one
+    ///
+    /// Pausing or resuming the same track changes nothing.
+    fn poll(&mut self) -> Option<RgbaImage> {
+        let track = media::now_playing()?;
+        let same = self.track.as_ref().map_or(false, |shown| {
+            (&shown.title, &shown.artist, &shown.art) == (&track.title, &track.artist, &track.art)
+        });
+        if same {
+            return None;
+        }
+        debug!("Now playing: {} - {}", track.artist, track.title);
+        let art = track.art.clone();
+        self.track = Some(track);
+
+        let cover = match load_art(&self.http_client, art.as_deref()?) {
+            Ok(cover) => cover,
+            Err(e) => {
+                warn!("Failed to load the album art: {}", e);
+                return None;
+            }
+        };
+        let mut frame = render(&cover, self.size, &self.settings);
+        if let Some(kelvin) = temperature::kelvin_now() {
+            temperature::tint(&mut frame, kelvin);
+        }
+        Some(frame)
+    }
+}
+
+impl FrameSource for Covers {
+    fn frame(&mut self, elapsed: Duration) -> Option<RgbaImage> {
+        // Not asked during a change, which would hold up its frames
+        if self.next.is_none() && elapsed >= self.next_poll {
+            self.next_poll = elapsed + Duration::from_secs(self.settings.poll_secs.max(1) as u64);
+            if let Some(frame) = self.poll() {
+                self.next = Some((frame, elapsed));
+            }
+        }
+
+        if !self.started {
+            self.started = true;
+            if let Some((frame, _)) = self.next.take() {
+                self.shown = frame;
+            }
+            return Some(self.shown.clone());
+        }
+
+        let transition = self.settings.transition;
+        let (next, since) = self.next.as_ref()?;
+        let progress = elapsed.saturating_sub(*since).as_secs_f32()
+            / (transition.duration_ms.max(1) as f32 / 1000.0);
+        if transition.effect == TransitionEffect::None || progress >= 1.0 {
+            let (next, _) = self.next.take()?;
+            self.shown = next;
+            return Some(self.shown.clone());
+        }
+        Some(effects::render_frame(
+            &self.shown,
+            next,
+            transition.effect,
+            effects::ease(progress),
+        ))
+    }
+}
+
+/// Open album art from a `file://` or web address or a path
+///
+/// Covers from the web are kept by address, as players hand out the same
+/// address for every track of an album.
+fn load_art(http_client: &HttpClient, art: &str) -> AppResult<DynamicImage> {
+    let path = if art.starts_with("http://") || art.starts_with("https://") {
+        let mut hasher = DefaultHasher::new();
+        art.hash(&mut hasher);
+        let path = media::art_dir().join(format!("{:016x}", hasher.finish()));
+        if !path.exists() {
+            http_client.download(PROVIDER, art, &path)?;
+        }
+        path
+    } else {
+        media::file_url_path(art).unwrap_or_else(|| PathBuf::from(art))
+    };
+    open(&path)
+}
+
+/// Open an image, whatever its extension says
+fn open(path: &Path) -> AppResult<DynamicImage> {
+    let bytes = std::fs::read(path)?;
+    image::load_from_memory(&bytes)
+        .map_err(|e| AppError::WallpaperError(format!("Failed to open {}: {}", path.display(), e)))
+}
+
+/// Draw a cover over a blurred, darkened copy of itself filling `size`
+pub fn render(
+    cover: &DynamicImage,
+    (width, height): (u32, u32),
+    settings: &NowPlayingConfig,
+) -> RgbaImage {
+    // Blurring a shrunken copy is far cheaper and looks the same once scaled up
+    let shrink = (settings.blur / 4).max(1);
+    let small = cover
+        .resize_to_fill(
+            (width / shrink).max(1),
+            (height / shrink).max(1),
+            FilterType::Triangle,
+        )
+        .to_rgba8();
+    let small = match settings.blur {
+        0 => small,
+        blur => imageops::blur(&small, (blur as f32 / shrink as f32).max(0.5)),
+    };
+    let mut frame = imageops::resize(&small, width, height, FilterType::Triangle);
+
+    let keep = 1.0 - settings.dim.clamp(0.0, 1.0);
+    for pixel in frame.pixels_mut() {
+        for channel in &mut pixel.0[..3] {
+            *channel = (*channel as f32 * keep) as u8;
+        }
+    }
+
+    if settings.show_cover {
+        let side = ((height as f32 * COVER_SIZE) as u32).clamp(1, width.max(1));
+        let sharp = cover
+            .resize_to_fill(side, side, FilterType::Lanczos3)
+            .to_rgba8();
+        imageops::overlay(
+            &mut frame,
+            &sharp,
+            ((width - side) / 2) as i64,
+            ((height - side) / 2) as i64,
+        );
+    }
+    frame
+}
+
+#[async_trait]
+impl super::Wallpaper for NowPlayingWallpaper {
+    fn get_type(&self) -> WallpaperType {
+        WallpaperType::NowPlaying
+    }
+
+    fn get_path(&self) -> Option<&Path> {
+        None
+    }
+
+    async fn start(&self) -> AppResult<()> {
+        debug!("Starting now-playing wallpaper");
+        self.player.start(
+            Box::new(Covers::new(self.settings.clone())),
+            self.settings.fps,
+        );
+        info!("Now-playing wallpaper started");
+        Ok(())
+    }
+
+    async fn stop(&self) -> AppResult<()> {
+        debug!("Stopping now-playing wallpaper");
+        self.player.stop();
+        info!("Now-playing wallpaper stopped");
+        Ok(())
+    }
+
+    async fn pause(&self) -> AppResult<()> {
+        self.player.pause();
+        info!("Now-playing wallpaper paused");
+        Ok(())
+    }
+
+    async fn resume(&self) -> AppResult<()> {
+        self.player.resume();
+        info!("Now-playing wallpaper resumed");
+        Ok(())
+    }
+
+    async fn capture_frame(&self, path: &Path) -> AppResult<()> {
+        self.player.capture_frame(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_cover_over_blurred_background() {
+        let cover = DynamicImage::ImageRgba8(RgbaImage::from_fn(40, 40, |x, _| {
+            if x < 20 {
+                Rgba([200, 0, 0, 255])
+            } else {
+                Rgba([0, 0, 200, 255])
+            }
+        }));
+        let settings = NowPlayingConfig {
+            dim: 0.5,
+            ..NowPlayingConfig::default()
+        };
+        let frame = render(&cover, (160, 90), &settings);
+        assert_eq!(frame.dimensions(), (160, 90));
+
+        // The sharp cover sits in the middle, the darkened background around it
+        let (left, right) = (frame.get_pixel(70, 45), frame.get_pixel(90, 45));
+        assert!(
+            left[0] >= 195 && left[2] <= 5,
+            "left half of the cover: {:?}",
+            left
+        );
+        assert!(
+            right[2] >= 195 && right[0] <= 5,
+            "right half of the cover: {:?}",
+            right
+        );
+        let edge = frame.get_pixel(0, 45);
+        assert!(
+            edge[0] <= 100 && edge[0] > 0,
+            "background is darkened: {:?}",
+            edge
+        );
+
+        let background = render(
+            &cover,
+            (160, 90),
+            &NowPlayingConfig {
+                show_cover: false,
+                dim: 0.0,
+                blur: 0,
+                ..settings
+            },
+        );
+        let edge = background.get_pixel(0, 45);
+        assert!(
+            edge[0] >= 195 && edge[2] <= 5,
+            "background is the cover: {:?}",
+            edge
+        );
+    }
+}
@@ -0,0 +1,478 @@
+use crate::core::config::{MouseReaction, ParticlesConfig};
+use crate::core::{AppResult, WallpaperType};
+use crate::platform::{cursor, display, WallpaperManager};
+use crate::wallpapers::frame_player::{self, FramePlayer, FrameSource};
+use crate::wallpapers::random::Rng;
+use async_trait::async_trait;
+use image::{Rgba, RgbaImage};
+use log::{debug, info};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Rate at which particles return to their drifting speed, per second
+const DRAG: f32 = 1.5;
+
+/// Mouse pull or push at full strength and no distance, in pixels per second squared
+const MOUSE_FORCE: f32 = 2400.0;
+
+/// Most particles drawn, keeping the lines between them affordable
+pub const MAX_PARTICLES: u32 = 600;
+
+/// Starting points for the particle settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParticlePreset {
+    /// Drifting dots joined by faint lines, scattering from the mouse
+    Constellation,
+
+    /// Warm glowing specks wandering slowly, drawn to the mouse
+    Fireflies,
+
+    /// White flakes falling and parting around the mouse
+    Snowfall,
+
+    /// Pale bubbles rising from the bottom
+    Bubbles,
+}
+
+impl ParticlePreset {
+    /// Every preset, in the order shown
+    pub const ALL: [ParticlePreset; 4] = [
+        ParticlePreset::Constellation,
+        ParticlePreset::Fireflies,
+        ParticlePreset::Snowfall,
+        ParticlePreset::Bubbles,
+    ];
+
+    /// Get the settings of this preset
+    pub fn settings(self) -> ParticlesConfig {
+        let colors = |colors: &[&str]| colors.iter().map(|color| color.to_string()).collect();
+        match self {
+            ParticlePreset::Constellation => ParticlesConfig::default(),
+            ParticlePreset::Fireflies => ParticlesConfig {
+                count: 80,
+                colors: colors(&["#ffd36e", "#ffb347", "#fff3b0"]),
+                background: "#0a1408".to_string(),
+                size: 2.5,
+                speed: 25.0,
+                gravity: 0.0,
+                mouse: MouseReaction::Attract,
+                mouse_strength: 0.6,
+                mouse_radius: 0.25,
+                link_distance: 0.0,
+                ..ParticlesConfig::default()
+            },
+            ParticlePreset::Snowfall => ParticlesConfig {
+                count: 300,
+                colors: colors(&["#ffffff", "#dfe9f5"]),
+                background: "#1b2838".to_string(),
+                size: 2.5,
+                speed: 20.0,
+                gravity: 60.0,
+                mouse: MouseReaction::Repel,
+                mouse_strength: 0.8,
+                mouse_radius: 0.1,
+                link_distance: 0.0,
+                ..ParticlesConfig::default()
+            },
+            ParticlePreset::Bubbles => ParticlesConfig {
+                count: 60,
+                colors: colors(&["#a0e9ff", "#cdf5fd", "#89cff3"]),
+                background: "#00337c".to_string(),
+                size: 7.0,
+                speed: 15.0,
+                gravity: -40.0,
+                mouse: MouseReaction::Repel,
+                mouse_strength: 1.2,
+                mouse_radius: 0.12,
+                link_distance: 0.0,
+                ..ParticlesConfig::default()
+            },
+        }
+    }
+}
+
+/// Read a hex color like "#8be9fd" or "8be9fd"
+pub fn parse_color(hex: &str) -> Option<Rgba<u8>> {
+    let hex = hex.trim().trim_start_matches('#');
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |range: std::ops::Range<usize>| u8::from_str_radix(hex.get(range)?, 16).ok();
+    Some(Rgba([channel(0..2)?, channel(2..4)?, channel(4..6)?, 255]))
+}
+
+/// Particle system wallpaper, drawn in-process
+pub struct ParticlesWallpaper {
+    /// Look and behavior of the particles
+    settings: ParticlesConfig,
+
+    /// Player showing the frames
+    player: FramePlayer,
+}
+
+impl ParticlesWallpaper {
+    /// Create a new particle wallpaper
+    pub fn new(wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>) -> Self {
+        Self {
+            settings: ParticlesConfig::default(),
+            player: FramePlayer::new(wallpaper_manager),
+        }
+    }
+
+    /// Set the look and behavior of the particles
+    pub fn with_settings(mut self, settings: ParticlesConfig) -> Self {
+        self.settings = settings;
+        self
+    }
+}
+
+/// One particle, in frame pixels
+#[derive(Debug, Clone, Copy)]
+struct Particle {
+    x: f32,
+    y: f32,
+
+    /// Velocity in pixels per second
+    vx: f32,
+    vy: f32,
+
+    /// Velocity the particle drifts at when left alone
+    drift: (f32, f32),
+
+    /// Index of its color
+    color: usize,
+}
+
+/// Particles moving over a background
+struct Particles {
+    /// Look and behavior
+    settings: ParticlesConfig,
+
+    /// Frame size
+    size: (u32, u32),
+
+    /// Frame pixels per pixel of a 1080 pixel high frame
+    scale: f32,
+
+    /// Background color
+    background: Rgba<u8>,
+
+    /// Colors particles are given
+    colors: Vec<Rgba<u8>>,
+
+    particles: Vec<Particle>,
+
+    /// Primary monitor's rectangle on the desktop, which the frame shows
+    monitor: Option<(i32, i32, u32, u32)>,
+
+    /// Time of the last frame
+    last: Option<Duration>,
+}
+
+impl Particles {
+    /// Scatter the particles over a frame
+    fn new(settings: ParticlesConfig, width: u32, height: u32) -> Self {
+        let mut rng = Rng::new();
+        let scale = height as f32 / 1080.0;
+        let mut colors: Vec<Rgba<u8>> = settings
+            .colors
+            .iter()
+            .filter_map(|color| parse_color(color))
+            .collect();
+        if colors.is_empty() {
+            colors.push(Rgba([255, 255, 255, 255]));
+        }
+        let particles = (0..settings.count.min(MAX_PARTICLES))
+            .map(|index| {
+                let angle = rng.between(0.0, std::f32::consts::TAU);
+                let speed = settings.speed * scale * rng.between(0.5, 1.0);
+                let drift = (angle.cos() * speed, angle.sin() * speed);
+                Particle {
+                    x: rng.between(0.0, width as f32),
+                    y: rng.between(0.0, height as f32),
+                    vx: drift.0,
+                    vy: drift.1,
+                    drift,
+                    color: index % colors.len(),
+                }
+            })
+            .collect();
+        let monitor = display::monitors()
+            .ok()
+            .and_then(|monitors| {
+                monitors
+                    .iter()
+                    .find(|m| m.primary)
+                    .or(monitors.first())
+                    .cloned()
+            })
+            .map(|m| (m.x, m.y, m.width.max(1), m.height.max(1)));
+        Self {
+            background: parse_color(&settings.background).unwrap_or(Rgba([0, 0, 0, 255])),
+            settings,
+            size: (width, height),
+            scale,
+            colors,
+            particles,
+            monitor,
+            last: None,
+        }
+    }
+
+    /// Get the cursor's position in the frame, None when it's off the primary monitor
+    fn cursor(&self) -> Option<(f32, f32)> {
+        let (x, y) = cursor::position()?;
+        let (left, top, width, height) = self.monitor?;
+        let (fx, fy) = (
+            (x - left) as f32 / width as f32,
+            (y - top) as f32 / height as f32,
+        );
+        ((0.0..1.0).contains(&fx) && (0.0..1.0).contains(&fy))
+            .then(|| (fx * self.size.0 as f32, fy * self.size.1 as f32))
+    }
+
+    /// Move the particles on by `seconds`, with the mouse at `cursor`
+    fn step(&mut self, seconds: f32, cursor: Option<(f32, f32)>) {
+        let (width, height) = (self.size.0 as f32, self.size.1 as f32);
+        let gravity = self.settings.gravity * self.scale;
+        let radius = self.settings.mouse_radius * height;
+        let force = match self.settings.mouse {
+            MouseReaction::None => 0.0,
+            MouseReaction::Attract => -MOUSE_FORCE,
+            MouseReaction::Repel => MOUSE_FORCE,
+        } * self.settings.mouse_strength
+            * self.scale;
+        let settle = (DRAG * seconds).min(1.0);
+
+        for particle in &mut self.particles {
+            // Pulled back towards the drift, so pushes and gravity level off
+            particle.vx += (particle.drift.0 - particle.vx) * settle;
+            particle.vy += (particle.drift.1 - particle.vy) * settle + gravity * seconds;
+
+            if let Some((cx, cy)) = cursor.filter(|_| force != 0.0 && radius > 0.0) {
+                let (dx, dy) = (particle.x - cx, particle.y - cy);
+                let distance = (dx * dx + dy * dy).sqrt();
+                if distance < radius && distance > 0.5 {
+                    let push = force * (1.0 - distance / radius) * seconds / distance;
+                    particle.vx += dx * push;
+                    particle.vy += dy * push;
+                }
+            }
+
+            // Leaving one edge enters at the opposite one
+            particle.x = (particle.x + particle.vx * seconds).rem_euclid(width);
+            particle.y = (particle.y + particle.vy * seconds).rem_euclid(height);
+        }
+    }
+
+    /// Draw the particles and the lines between them
+    fn draw(&self) -> RgbaImage {
+        let mut frame = RgbaImage::from_pixel(self.size.0, self.size.1, self.background);
+        let link = self.settings.link_distance * self.size.1 as f32;
+        if link > 0.0 {
+            for (index, a) in self.particles.iter().enumerate() {
+                for b in &self.particles[index + 1..] {
+                    let distance = ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt();
+                    if distance < link {
+                        line(
+                            &mut frame,
+                            (a.x, a.y),
+                            (b.x, b.y),
+                            self.colors[a.color],
+                            0.5 * (1.0 - distance / link),
+                        );
+                    }
+                }
+            }
+        }
+        let radius = (self.settings.size * self.scale).max(0.5);
+        for particle in &self.particles {
+            dot(
+                &mut frame,
+                (particle.x, particle.y),
+                radius,
+                self.colors[particle.color],
+            );
+        }
+        frame
+    }
+}
+
+impl FrameSource for Particles {
+    fn frame(&mut self, elapsed: Duration) -> Option<RgbaImage> {
+        // A long gap, such as a pause, shouldn't fling the particles across the frame
+        let seconds = self
+            .last
+            .map_or(0.0, |last| elapsed.saturating_sub(last).as_secs_f32())
+            .min(0.1);
+        self.last = Some(elapsed);
+        let cursor = self.cursor();
+        self.step(seconds, cursor);
+        Some(self.draw())
+    }
+}
+
+/// Mix `color` into a pixel by `alpha`, ignoring points off the frame
+pub(crate) fn blend(frame: &mut RgbaImage, x: i32, y: i32, color: Rgba<u8>, alpha: f32) {
+    if x < 0 || y < 0 || x >= frame.width() as i32 || y >= frame.height() as i32 {
+        return;
+    }
+    let pixel = frame.get_pixel_mut(x as u32, y as u32);
+    for channel in 0..3 {
+        pixel[channel] =
+            (pixel[channel] as f32 * (1.0 - alpha) + color[channel] as f32 * alpha).round() as u8;
+    }
+}
+
+/// Draw a soft-edged dot
+pub(crate) fn dot(frame: &mut RgbaImage, (x, y): (f32, f32), radius: f32, color: Rgba<u8>) {
+    let reach = radius.ceil() as i32 + 1;
+    for dy in -reach..=reach {
+        for dx in -reach..=reach {
+            let (px, py) = (x.round() as i32 + dx, y.round() as i32 + dy);
+            let distance = ((px as f32 - x).powi(2) + (py as f32 - y).powi(2)).sqrt();
+            let alpha = (radius + 0.5 - distance).clamp(0.0, 1.0);
+            if alpha > 0.0 {
+                blend(frame, px, py, color, alpha);
+            }
+        }
+    }
+}
+
+/// Draw a one pixel line
+fn line(frame: &mut RgbaImage, from: (f32, f32), to: (f32, f32), color: Rgba<u8>, alpha: f32) {
+    let steps = (to.0 - from.0)
+        .abs()
+        .max((to.1 - from.1).abs())
+        .ceil()
+        .max(1.0) as i32;
+    for step in 0..=steps {
+        let t = step as f32 / steps as f32;
+        let (x, y) = (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t);
+        blend(frame, x.round() as i32, y.round() as i32, color, alpha);
+    }
+}
+
+#[async_trait]
+impl super::Wallpaper for ParticlesWallpaper {
+    fn get_type(&self) -> WallpaperType {
+        WallpaperType::Particles
+    }
+
+    fn get_path(&self) -> Option<&Path> {
+        None
+    }
+
+    async fn start(&self) -> AppResult<()> {
+        debug!("Starting particle wallpaper");
+        let (width, height) = frame_player::frame_size();
+        self.player.start(
+            Box::new(Particles::new(self.settings.clone(), width, height)),
+            self.settings.fps,
+        );
+        info!("Particle wallpaper started");
+        Ok(())
+    }
+
+    async fn stop(&self) -> AppResult<()> {
+        debug!("Stopping particle wallpaper");
+        self.player.stop();
+        info!("Particle wallpaper stopped");
+        Ok(())
+    }
+
+    async fn pause(&self) -> AppResult<()> {
+        self.player.pause();
+        info!("Particle wallpaper paused");
+        Ok(())
+    }
+
+    async fn resume(&self) -> AppResult<()> {
+        self.player.resume();
+        info!("Particle wallpaper resumed");
+        Ok(())
+    }
+
+    async fn capture_frame(&self, path: &Path) -> AppResult<()> {
+        self.player.capture_frame(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn still(settings: ParticlesConfig) -> Particles {
+        let mut particles = Particles::new(
+            ParticlesConfig {
+                speed: 0.0,
+                ..settings
+            },
+            200,
+            100,
+        );
+        particles.monitor = None;
+        particles
+    }
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("#8be9fd"), Some(Rgba([0x8b, 0xe9, 0xfd, 255])));
+        assert_eq!(parse_color("FF0000"), Some(Rgba([255, 0, 0, 255])));
+        assert_eq!(parse_color("#fff"), None);
+        assert_eq!(parse_color("#gggggg"), None);
+    }
+
+    #[test]
+    fn test_gravity_and_mouse() {
+        let mut falling = still(ParticlesConfig {
+            count: 1,
+            gravity: 100.0,
+            ..ParticlesConfig::default()
+        });
+        falling.particles[0].y = 10.0;
+        falling.step(0.05, None);
+        assert!(falling.particles[0].vy > 0.0);
+
+        // Repelled away from the cursor, attracted towards it
+        let mut repelled = still(ParticlesConfig {
+            count: 1,
+            mouse: MouseReaction::Repel,
+            ..ParticlesConfig::default()
+        });
+        repelled.particles[0].x = 110.0;
+        repelled.particles[0].y = 50.0;
+        repelled.step(0.05, Some((100.0, 50.0)));
+        assert!(repelled.particles[0].vx > 0.0);
+
+        let mut attracted = still(ParticlesConfig {
+            count: 1,
+            mouse: MouseReaction::Attract,
+            ..ParticlesConfig::default()
+        });
+        attracted.particles[0].x = 110.0;
+        attracted.particles[0].y = 50.0;
+        attracted.step(0.05, Some((100.0, 50.0)));
+        assert!(attracted.particles[0].vx < 0.0);
+    }
+
+    #[test]
+    fn test_particles_wrap_and_draw() {
+        let mut particles = still(ParticlesConfig {
+            count: 1,
+            ..ParticlesConfig::default()
+        });
+        particles.particles[0].x = 199.0;
+        particles.particles[0].drift = (100.0, 0.0);
+        particles.particles[0].vx = 100.0;
+        particles.step(0.05, None);
+        assert!(particles.particles[0].x < 10.0);
+
+        let frame = particles.draw();
+        let background = parse_color(&ParticlesConfig::default().background).unwrap();
+        assert_eq!(frame.dimensions(), (200, 100));
+        assert!(frame.pixels().any(|pixel| *pixel != background));
+        assert_eq!(*frame.get_pixel(100, 99), background);
+    }
+}
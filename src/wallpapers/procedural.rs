@@ -0,0 +1,1169 @@
+//! Procedural wallpapers: patterns drawn from nothing but their settings
+//!
+//! Matrix rain, a starfield and plasma are drawn in-process, so they need no
+//! files at all. Motion is measured in seconds and sizes in pixels of a 1080
+//! pixel high frame, so the patterns look the same at any frame rate and size.
+//!
+//! The generative patterns, the Game of Life, reaction-diffusion and the flow
+//! field, run on a grid much coarser than the screen and are scaled up when
+//! drawn, which keeps them cheap enough to leave running. Given a seed, they
+//! grow the same way every time.
+use crate::core::config::{ColorScheme, PatternSettings, ProceduralConfig, ProceduralPattern};
+use crate::core::{AppResult, WallpaperType};
+use crate::platform::WallpaperManager;
+use crate::wallpapers::frame_player::{self, FramePlayer, FrameSource};
+use crate::wallpapers::particles::dot;
+use crate::wallpapers::random::Rng;
+use async_trait::async_trait;
+use image::imageops::{self, FilterType};
+use image::{Rgba, RgbaImage};
+use log::{debug, info};
+use std::f32::consts::TAU;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Columns of dots in a rain glyph
+const GLYPH_COLUMNS: u32 = 5;
+
+/// Rows of dots in a rain glyph
+const GLYPH_ROWS: u32 = 7;
+
+/// Size of a glyph's dots
+const GLYPH_DOT: f32 = 3.0;
+
+/// Seconds a glyph is shown before it changes
+const GLYPH_SECONDS: f32 = 0.8;
+
+/// Stars in the starfield at full density
+const MAX_STARS: f32 = 1500.0;
+
+/// Shrinking of the plasma before it's drawn; its waves are smooth enough to scale up
+const PLASMA_DOWNSCALE: u32 = 4;
+
+/// Longest step between two frames, so a stall doesn't make the pattern jump
+const MAX_STEP: f32 = 0.25;
+
+/// Size of a Game of Life cell
+const LIFE_CELL: f32 = 8.0;
+
+/// Generations per second of the Game of Life
+const LIFE_GENERATIONS: f32 = 8.0;
+
+/// Generations with the same number of living cells before the Game of Life starts over
+const LIFE_STALE: u32 = 120;
+
+/// Seconds a dead cell takes to fade into the background
+const LIFE_FADE: f32 = 1.5;
+
+/// Shrinking of the reaction-diffusion grid before it's drawn
+const DIFFUSION_DOWNSCALE: u32 = 8;
+
+/// Reaction-diffusion steps per second
+const DIFFUSION_STEPS: f32 = 200.0;
+
+/// Rate the first chemical is fed into the reaction-diffusion
+const DIFFUSION_FEED: f32 = 0.0545;
+
+/// Rate the second chemical is taken out of the reaction-diffusion
+const DIFFUSION_KILL: f32 = 0.062;
+
+/// Seconds between spots dropped into the reaction-diffusion, so it never settles
+const DIFFUSION_SPOT_SECONDS: f32 = 6.0;
+
+/// Shrinking of the flow field's trails before they're drawn
+const FLOW_DOWNSCALE: u32 = 2;
+
+/// Particles in the flow field per megapixel at full density
+const FLOW_PARTICLES: f32 = 2500.0;
+
+/// Cells of noise the flow field turns by across the screen's height
+const FLOW_CELLS: f32 = 3.0;
+
+/// Cells of noise before it repeats
+const FLOW_LATTICE: usize = 16;
+
+/// Share of the flow field's trails left after a second
+const FLOW_KEEP: f32 = 0.35;
+
+/// Get the colors of a scheme for a pattern: background, middle and brightest
+pub fn scheme_colors(scheme: ColorScheme, pattern: ProceduralPattern) -> [Rgba<u8>; 3] {
+    let rgb = |hex: u32| Rgba([(hex >> 16) as u8, (hex >> 8) as u8, hex as u8, 255]);
+    let [low, middle, high] = match (scheme, pattern) {
+        (ColorScheme::Classic, ProceduralPattern::MatrixRain) => [0x000000, 0x00a830, 0xd4ffd9],
+        (ColorScheme::Classic, ProceduralPattern::Starfield) => [0x02030a, 0x9bb0ff, 0xffffff],
+        (ColorScheme::Classic, ProceduralPattern::GameOfLife) => [0x0d1117, 0x1f6f8b, 0xe6f6ff],
+        (ColorScheme::Classic, ProceduralPattern::ReactionDiffusion) => {
+            [0x0b132b, 0x5bc0be, 0xf4f1de]
+        }
+        (ColorScheme::Classic, ProceduralPattern::FlowField) => [0x0a0a12, 0xe07a5f, 0xf2cc8f],
+        // The classic plasma cycles through every hue instead
+        (ColorScheme::Classic, ProceduralPattern::Plasma) | (ColorScheme::Mono, _) => {
+            [0x000000, 0x808080, 0xffffff]
+        }
+        (ColorScheme::Ocean, _) => [0x001f3f, 0x0074d9, 0x7fdbff],
+        (ColorScheme::Sunset, _) => [0x2d1b4e, 0xff5e5b, 0xffd166],
+        (ColorScheme::Neon, _) => [0x12002b, 0xff00c8, 0x00f0ff],
+    };
+    [rgb(low), rgb(middle), rgb(high)]
+}
+
+/// Get the color `t` (0.0 - 1.0) of the way from the first to the last of three colors
+pub fn gradient(colors: &[Rgba<u8>; 3], t: f32) -> Rgba<u8> {
+    let t = t.clamp(0.0, 1.0) * 2.0;
+    let (from, to, t) = if t < 1.0 {
+        (colors[0], colors[1], t)
+    } else {
+        (colors[1], colors[2], t - 1.0)
+    };
+    let mix = |channel: usize| {
+        (from[channel] as f32 + (to[channel] as f32 - from[channel] as f32) * t).round() as u8
+    };
+    Rgba([mix(0), mix(1), mix(2), 255])
+}
+
+/// Get the fully saturated color of a hue, wrapping around from 1.0 to 0.0
+fn hue(hue: f32) -> Rgba<u8> {
+    let h = hue.rem_euclid(1.0) * 6.0;
+    let x = 1.0 - (h % 2.0 - 1.0).abs();
+    let (r, g, b) = match h as u32 {
+        0 => (1.0, x, 0.0),
+        1 => (x, 1.0, 0.0),
+        2 => (0.0, 1.0, x),
+        3 => (0.0, x, 1.0),
+        4 => (x, 0.0, 1.0),
+        _ => (1.0, 0.0, x),
+    };
+    let channel = |value: f32| (value * 255.0).round() as u8;
+    Rgba([channel(r), channel(g), channel(b), 255])
+}
+
+/// Mix numbers into a well spread hash, for glyphs that don't need to be stored
+fn hash(a: u32, b: u32, c: u64) -> u64 {
+    let mut z = (((a as u64) << 40) ^ ((b as u64) << 20) ^ c).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Create the randomness of a pattern, the same every time for a seed
+fn pattern_rng(settings: &PatternSettings) -> Rng {
+    match settings.seed {
+        0 => Rng::new(),
+        seed => Rng::seeded(seed),
+    }
+}
+
+/// Get the seconds since the last frame, remembering this one
+fn seconds_since(last: &mut Option<Duration>, elapsed: Duration) -> f32 {
+    let seconds = last.map_or(0.0, |last| elapsed.saturating_sub(last).as_secs_f32());
+    *last = Some(elapsed);
+    seconds.min(MAX_STEP)
+}
+
+/// Create the frames of a pattern
+pub fn pattern_frames(
+    pattern: ProceduralPattern,
+    settings: &PatternSettings,
+    width: u32,
+    height: u32,
+) -> Box<dyn FrameSource> {
+    match pattern {
+        ProceduralPattern::MatrixRain => Box::new(MatrixRain::new(settings, width, height)),
+        ProceduralPattern::Starfield => Box::new(Starfield::new(settings, width, height)),
+        ProceduralPattern::Plasma => Box::new(Plasma::new(settings, width, height)),
+        ProceduralPattern::GameOfLife => Box::new(GameOfLife::new(settings, width, height)),
+        ProceduralPattern::ReactionDiffusion => {
+            Box::new(ReactionDiffusion::new(settings, width, height))
+        }
+        ProceduralPattern::FlowField => Box::new(FlowField::new(settings, width, height)),
+    }
+}
+
+/// Procedural wallpaper, drawn in-process
+pub struct ProceduralWallpaper {
+    /// Pattern drawn
+    pattern: ProceduralPattern,
+
+    /// Settings of every pattern
+    settings: ProceduralConfig,
+
+    /// Player showing the frames
+    player: FramePlayer,
+}
+
+impl ProceduralWallpaper {
+    /// Create a new procedural wallpaper
+    pub fn new(
+        pattern: ProceduralPattern,
+        wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>,
+    ) -> Self {
+        Self {
+            pattern,
+            settings: ProceduralConfig::default(),
+            player: FramePlayer::new(wallpaper_manager),
+        }
+    }
+
+    /// Set the settings of the patterns
+    pub fn with_settings(mut self, settings: ProceduralConfig) -> Self {
+        self.settings = settings;
+        self
+    }
+}
+
+/// Line of glyphs falling down a column, measured in cells
+#[derive(Debug, Clone, Copy)]
+struct Raindrop {
+    /// Row of the brightest glyph
+    head: f32,
+
+    /// Rows fallen per second
+    speed: f32,
+
+    /// Glyphs in the line
+    length: f32,
+}
+
+/// Columns of glyphs raining down the screen
+struct MatrixRain {
+    /// Frame size
+    width: u32,
+    height: u32,
+
+    /// Size of a glyph's dots in pixels
+    dot: u32,
+
+    /// Rows of glyphs on screen
+    rows: u32,
+
+    /// One drop per column
+    drops: Vec<Raindrop>,
+
+    /// Background, trail and head colors
+    colors: [Rgba<u8>; 3],
+
+    /// Speed and density settings
+    settings: PatternSettings,
+
+    /// Randomness of new drops
+    rng: Rng,
+
+    /// Time of the last frame
+    last: Option<Duration>,
+}
+
+impl MatrixRain {
+    fn new(settings: &PatternSettings, width: u32, height: u32) -> Self {
+        let dot = ((height as f32 / 1080.0 * GLYPH_DOT).round() as u32).max(1);
+        let columns = (width / ((GLYPH_COLUMNS + 1) * dot)).max(1);
+        let rows = (height / ((GLYPH_ROWS + 2) * dot)).max(1);
+        let mut rain = Self {
+            width,
+            height,
+            dot,
+            rows,
+            drops: Vec::with_capacity(columns as usize),
+            colors: scheme_colors(settings.scheme, ProceduralPattern::MatrixRain),
+            settings: settings.clone(),
+            rng: pattern_rng(settings),
+            last: None,
+        };
+        // Some drops are already on their way down
+        for _ in 0..columns {
+            let mut drop = rain.spawn();
+            drop.head = rain.rng.between(-(rows as f32), rows as f32);
+            rain.drops.push(drop);
+        }
+        rain
+    }
+
+    /// Create a drop above the top edge
+    fn spawn(&mut self) -> Raindrop {
+        // Sparser rain waits longer before falling again
+        let wait = (1.0 - self.settings.density.clamp(0.0, 1.0))
+            * self.rng.between(0.0, self.rows as f32 * 4.0);
+        Raindrop {
+            head: -wait,
+            speed: self.rng.between(8.0, 20.0),
+            length: self.rng.between(6.0, 24.0),
+        }
+    }
+
+    /// Move the drops on by `seconds`
+    fn step(&mut self, seconds: f32) {
+        for index in 0..self.drops.len() {
+            let mut drop = self.drops[index];
+            drop.head += drop.speed * self.settings.speed * seconds;
+            self.drops[index] = if drop.head - drop.length > self.rows as f32 {
+                self.spawn()
+            } else {
+                drop
+            };
+        }
+    }
+
+    /// Draw the rain, `time` seconds in
+    fn draw(&self, time: f32) -> RgbaImage {
+        let mut frame = RgbaImage::from_pixel(self.width, self.height, self.colors[0]);
+        let (cell_width, cell_height) =
+            ((GLYPH_COLUMNS + 1) * self.dot, (GLYPH_ROWS + 2) * self.dot);
+        for (column, drop) in self.drops.iter().enumerate() {
+            let column = column as u32;
+            for behind in 0..drop.length.ceil() as i32 {
+                let row = drop.head.floor() as i32 - behind;
+                if row < 0 || row >= self.rows as i32 {
+                    continue;
+                }
+                let row = row as u32;
+                // The head is brightest, the trail fades towards the background
+                let color = if behind == 0 {
+                    self.colors[2]
+                } else {
+                    gradient(&self.colors, 0.55 * (1.0 - behind as f32 / drop.length))
+                };
+
+                // Glyphs change now and then, each at its own moment
+                let phase = (hash(column, row, 0) % 1000) as f32 / 1000.0;
+                let bits = hash(column, row, (time / GLYPH_SECONDS + phase) as u64 + 1);
+                for dot_row in 0..GLYPH_ROWS {
+                    for dot_column in 0..GLYPH_COLUMNS {
+                        if (bits >> (dot_row * GLYPH_COLUMNS + dot_column)) & 1 == 0 {
+                            continue;
+                        }
+                        let x = column * cell_width + dot_column * self.dot;
+                        let y = row * cell_height + dot_row * self.dot;
+                        for py in y..(y + self.dot).min(self.height) {
+                            for px in x..(x + self.dot).min(self.width) {
+                                frame.put_pixel(px, py, color);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        frame
+    }
+}
+
+impl FrameSource for MatrixRain {
+    fn frame(&mut self, elapsed: Duration) -> Option<RgbaImage> {
+        let seconds = seconds_since(&mut self.last, elapsed);
+        self.step(seconds);
+        Some(self.draw(elapsed.as_secs_f32()))
+    }
+}
+
+/// Star flying towards the viewer
+#[derive(Debug, Clone, Copy)]
+struct Star {
+    /// Position across the view, from -1.0 to 1.0 horizontally
+    x: f32,
+    y: f32,
+
+    /// Distance, from 1.0 far away to 0.0 at the viewer
+    z: f32,
+}
+
+/// Flying through a field of stars
+struct Starfield {
+    /// Frame size
+    width: u32,
+    height: u32,
+
+    /// Stars in the field
+    stars: Vec<Star>,
+
+    /// Background, far and near star colors
+    colors: [Rgba<u8>; 3],
+
+    /// How fast the stars come closer
+    speed: f32,
+
+    /// Randomness of new stars
+    rng: Rng,
+
+    /// Time of the last frame
+    last: Option<Duration>,
+}
+
+impl Starfield {
+    fn new(settings: &PatternSettings, width: u32, height: u32) -> Self {
+        let count = (MAX_STARS * settings.density.clamp(0.02, 1.0)) as usize;
+        let mut field = Self {
+            width,
+            height,
+            stars: Vec::with_capacity(count),
+            colors: scheme_colors(settings.scheme, ProceduralPattern::Starfield),
+            speed: settings.speed,
+            rng: pattern_rng(settings),
+            last: None,
+        };
+        for _ in 0..count {
+            let mut star = field.spawn();
+            star.z = field.rng.between(0.05, 1.0);
+            field.stars.push(star);
+        }
+        field
+    }
+
+    /// Create a star far away
+    fn spawn(&mut self) -> Star {
+        let aspect = self.height as f32 / self.width.max(1) as f32;
+        Star {
+            x: self.rng.between(-1.0, 1.0),
+            y: self.rng.between(-aspect, aspect),
+            z: 1.0,
+        }
+    }
+
+    /// Get where a star is on the frame
+    fn project(&self, star: &Star) -> (f32, f32) {
+        let half = self.width as f32 / 2.0;
+        (
+            half + star.x / star.z * half,
+            self.height as f32 / 2.0 + star.y / star.z * half,
+        )
+    }
+
+    /// Move the stars on by `seconds`, replacing those that passed the viewer
+    fn step(&mut self, seconds: f32) {
+        for index in 0..self.stars.len() {
+            let mut star = self.stars[index];
+            star.z -= seconds * 0.3 * self.speed;
+            let (x, y) = if star.z > 0.02 {
+                self.project(&star)
+            } else {
+                (-1.0, -1.0)
+            };
+            let gone = x < 0.0 || y < 0.0 || x >= self.width as f32 || y >= self.height as f32;
+            self.stars[index] = if gone { self.spawn() } else { star };
+        }
+    }
+
+    /// Draw the stars, nearer ones bigger and brighter
+    fn draw(&self) -> RgbaImage {
+        let mut frame = RgbaImage::from_pixel(self.width, self.height, self.colors[0]);
+        let scale = self.height as f32 / 1080.0;
+        for star in &self.stars {
+            let near = 1.0 - star.z;
+            let radius = (0.4 + near * near * 2.5) * scale;
+            dot(
+                &mut frame,
+                self.project(star),
+                radius,
+                gradient(&self.colors, 0.2 + 0.8 * near),
+            );
+        }
+        frame
+    }
+}
+
+impl FrameSource for Starfield {
+    fn frame(&mut self, elapsed: Duration) -> Option<RgbaImage> {
+        let seconds = seconds_since(&mut self.last, elapsed);
+        self.step(seconds);
+        Some(self.draw())
+    }
+}
+
+/// Flowing waves of color
+struct Plasma {
+    /// Frame size
+    width: u32,
+    height: u32,
+
+    /// Colors of the scheme, or None for every hue
+    colors: Option<[Rgba<u8>; 3]>,
+
+    /// Speed and density settings
+    settings: PatternSettings,
+}
+
+impl Plasma {
+    fn new(settings: &PatternSettings, width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            colors: match settings.scheme {
+                ColorScheme::Classic => None,
+                scheme => Some(scheme_colors(scheme, ProceduralPattern::Plasma)),
+            },
+            settings: settings.clone(),
+        }
+    }
+
+    /// Draw the plasma, `time` seconds in
+    fn draw(&self, time: f32) -> RgbaImage {
+        let t = time * self.settings.speed;
+        let (width, height) = (
+            (self.width / PLASMA_DOWNSCALE).max(1),
+            (self.height / PLASMA_DOWNSCALE).max(1),
+        );
+
+        // Denser plasma fits more waves on the screen
+        let waves = 1.0 + self.settings.density.clamp(0.0, 1.0) * 4.0;
+        let k = waves * TAU / height as f32;
+        let (center_x, center_y) = (
+            width as f32 * k * (0.5 + 0.3 * (t * 0.37).sin()),
+            height as f32 * k * (0.5 + 0.3 * (t * 0.29).cos()),
+        );
+
+        let small = RgbaImage::from_fn(width, height, |x, y| {
+            let (u, v) = (x as f32 * k, y as f32 * k);
+            let value = (u + t).sin()
+                + ((v + t * 0.8) * 0.9).sin()
+                + ((u + v + t * 1.3) * 0.6).sin()
+                + (((u - center_x).powi(2) + (v - center_y).powi(2)).sqrt() + t * 1.1).sin();
+            let level = (value + 4.0) / 8.0;
+            match &self.colors {
+                None => hue(level + t * 0.05),
+                Some(colors) => {
+                    // Back and forth along the scheme, so it has no hard edge
+                    let along = (level * 2.0 + t * 0.05).rem_euclid(1.0);
+                    gradient(colors, 1.0 - (along * 2.0 - 1.0).abs())
+                }
+            }
+        });
+        imageops::resize(&small, self.width, self.height, FilterType::Triangle)
+    }
+}
+
+impl FrameSource for Plasma {
+    fn frame(&mut self, elapsed: Duration) -> Option<RgbaImage> {
+        Some(self.draw(elapsed.as_secs_f32()))
+    }
+}
+
+/// Conway's Game of Life on a grid wrapping around at the edges
+struct GameOfLife {
+    /// Frame size
+    width: u32,
+    height: u32,
+
+    /// Grid size in cells
+    columns: usize,
+    rows: usize,
+
+    /// Whether each cell is alive, row by row
+    cells: Vec<bool>,
+
+    /// How lit each cell is, 1.0 while alive and fading once it dies
+    glow: Vec<f32>,
+
+    /// Background, fading and living colors
+    colors: [Rgba<u8>; 3],
+
+    /// Speed and density settings
+    settings: PatternSettings,
+
+    /// Randomness of the living cells
+    rng: Rng,
+
+    /// Share of a generation still to run
+    pending: f32,
+
+    /// Living cells in the last generation
+    population: usize,
+
+    /// Generations the number of living cells stayed the same
+    stale: u32,
+
+    /// Time of the last frame
+    last: Option<Duration>,
+}
+
+impl GameOfLife {
+    fn new(settings: &PatternSettings, width: u32, height: u32) -> Self {
+        let cell = ((height as f32 / 1080.0 * LIFE_CELL).round() as u32).max(1);
+        let (columns, rows) = (
+            (width / cell).max(1) as usize,
+            (height / cell).max(1) as usize,
+        );
+        let mut life = Self {
+            width,
+            height,
+            columns,
+            rows,
+            cells: vec![false; columns * rows],
+            glow: vec![0.0; columns * rows],
+            colors: scheme_colors(settings.scheme, ProceduralPattern::GameOfLife),
+            settings: settings.clone(),
+            rng: pattern_rng(settings),
+            pending: 0.0,
+            population: 0,
+            stale: 0,
+            last: None,
+        };
+        life.scatter();
+        life
+    }
+
+    /// Bring cells to life at random, more of them the denser the pattern
+    fn scatter(&mut self) {
+        let share = 0.1 + 0.35 * self.settings.density.clamp(0.0, 1.0);
+        for cell in &mut self.cells {
+            *cell = self.rng.fraction() < share;
+        }
+        self.stale = 0;
+    }
+
+    /// Run one generation, starting over once the grid stopped changing
+    fn generation(&mut self) {
+        let (columns, rows) = (self.columns, self.rows);
+        let alive = |x: usize, y: usize| self.cells[y % rows * columns + x % columns] as u8;
+        let next: Vec<bool> = (0..rows * columns)
+            .map(|index| {
+                let (x, y) = (index % columns + columns, index / columns + rows);
+                let neighbours = alive(x - 1, y - 1)
+                    + alive(x, y - 1)
+                    + alive(x + 1, y - 1)
+                    + alive(x - 1, y)
+                    + alive(x + 1, y)
+                    + alive(x - 1, y + 1)
+                    + alive(x, y + 1)
+                    + alive(x + 1, y + 1);
+                neighbours == 3 || (neighbours == 2 && self.cells[index])
+            })
+            .collect();
+        self.cells = next;
+
+        // Still lifes, blinkers and lone gliders would go on forever
+        let population = self.cells.iter().filter(|cell| **cell).count();
+        self.stale = if population == self.population {
+            self.stale + 1
+        } else {
+            0
+        };
+        self.population = population;
+        if self.stale >= LIFE_STALE || population == 0 {
+            self.scatter();
+        }
+    }
+
+    /// Run the generations due in `seconds`, fading the cells that died
+    fn step(&mut self, seconds: f32) {
+        self.pending += seconds * LIFE_GENERATIONS * self.settings.speed.max(0.0);
+        while self.pending >= 1.0 {
+            self.generation();
+            self.pending -= 1.0;
+        }
+        for (glow, alive) in self.glow.iter_mut().zip(&self.cells) {
+            *glow = if *alive {
+                1.0
+            } else {
+                (*glow - seconds / LIFE_FADE).max(0.0)
+            };
+        }
+    }
+
+    /// Draw the cells, dead ones fading out
+    fn draw(&self) -> RgbaImage {
+        let small = RgbaImage::from_fn(self.columns as u32, self.rows as u32, |x, y| {
+            let glow = self.glow[y as usize * self.columns + x as usize];
+            gradient(&self.colors, if glow >= 1.0 { 1.0 } else { glow * 0.5 })
+        });
+        imageops::resize(&small, self.width, self.height, FilterType::Nearest)
+    }
+}
+
+impl FrameSource for GameOfLife {
+    fn frame(&mut self, elapsed: Duration) -> Option<RgbaImage> {
+        let seconds = seconds_since(&mut self.last, elapsed);
+        self.step(seconds);
+        Some(self.draw())
+    }
+}
+
+/// Gray-Scott reaction-diffusion, two chemicals spreading and reacting on a grid
+struct ReactionDiffusion {
+    /// Frame size
+    width: u32,
+    height: u32,
+
+    /// Grid size
+    columns: usize,
+    rows: usize,
+
+    /// Amount of the first chemical, which the second feeds on, row by row
+    a: Vec<f32>,
+
+    /// Amount of the second chemical, which is drawn, row by row
+    b: Vec<f32>,
+
+    /// Background, middle and brightest colors
+    colors: [Rgba<u8>; 3],
+
+    /// Speed and density settings
+    settings: PatternSettings,
+
+    /// Randomness of the spots
+    rng: Rng,
+
+    /// Share of a step still to run
+    pending: f32,
+
+    /// Seconds until the next spot is dropped
+    next_spot: f32,
+
+    /// Time of the last frame
+    last: Option<Duration>,
+}
+
+impl ReactionDiffusion {
+    fn new(settings: &PatternSettings, width: u32, height: u32) -> Self {
+        let (columns, rows) = (
+            (width / DIFFUSION_DOWNSCALE).max(1) as usize,
+            (height / DIFFUSION_DOWNSCALE).max(1) as usize,
+        );
+        let mut diffusion = Self {
+            width,
+            height,
+            columns,
+            rows,
+            a: vec![1.0; columns * rows],
+            b: vec![0.0; columns * rows],
+            colors: scheme_colors(settings.scheme, ProceduralPattern::ReactionDiffusion),
+            settings: settings.clone(),
+            rng: pattern_rng(settings),
+            pending: 0.0,
+            next_spot: DIFFUSION_SPOT_SECONDS,
+            last: None,
+        };
+        // Denser patterns start from more spots
+        let spots = 3 + (settings.density.clamp(0.0, 1.0) * 30.0) as usize;
+        for _ in 0..spots {
+            diffusion.spot(true);
+        }
+        diffusion
+    }
+
+    /// Drop a spot of the second chemical to grow from, or clear one to grow back into
+    fn spot(&mut self, seed: bool) {
+        let (x, y) = (
+            self.rng.between(0.0, self.columns as f32) as usize,
+            self.rng.between(0.0, self.rows as f32) as usize,
+        );
+        let radius = if seed { 3 } else { (self.rows / 6).max(3) };
+        for dy in 0..radius * 2 {
+            for dx in 0..radius * 2 {
+                let index = (y + dy) % self.rows * self.columns + (x + dx) % self.columns;
+                (self.a[index], self.b[index]) = if seed { (0.5, 1.0) } else { (1.0, 0.0) };
+            }
+        }
+    }
+
+    /// Run one step of the reaction
+    fn react(&mut self) {
+        let (columns, rows) = (self.columns, self.rows);
+        // Spreading towards the neighbours, the diagonal ones less
+        let spread = |values: &[f32], x: usize, y: usize| {
+            let at = |dx: usize, dy: usize| {
+                values[(y + dy - 1) % rows * columns + (x + dx - 1) % columns]
+            };
+            0.2 * (at(0, 1) + at(2, 1) + at(1, 0) + at(1, 2))
+                + 0.05 * (at(0, 0) + at(2, 0) + at(0, 2) + at(2, 2))
+                - at(1, 1)
+        };
+        let mut a = Vec::with_capacity(self.a.len());
+        let mut b = Vec::with_capacity(self.b.len());
+        for index in 0..columns * rows {
+            let (x, y) = (index % columns + columns, index / columns + rows);
+            let (old_a, old_b) = (self.a[index], self.b[index]);
+            let reaction = old_a * old_b * old_b;
+            a.push(
+                (old_a + spread(&self.a, x, y) - reaction + DIFFUSION_FEED * (1.0 - old_a))
+                    .clamp(0.0, 1.0),
+            );
+            b.push(
+                (old_b + 0.5 * spread(&self.b, x, y) + reaction
+                    - (DIFFUSION_KILL + DIFFUSION_FEED) * old_b)
+                    .clamp(0.0, 1.0),
+            );
+        }
+        self.a = a;
+        self.b = b;
+    }
+
+    /// Run the steps due in `seconds`, dropping a spot now and then
+    fn step(&mut self, seconds: f32) {
+        let seconds = seconds * self.settings.speed.max(0.0);
+        self.pending += seconds * DIFFUSION_STEPS;
+        while self.pending >= 1.0 {
+            self.react();
+            self.pending -= 1.0;
+        }
+        self.next_spot -= seconds;
+        if self.next_spot <= 0.0 {
+            self.next_spot = DIFFUSION_SPOT_SECONDS;
+            // Once everything died out, only seeds bring it back
+            let alive = self.b.iter().any(|b| *b > 0.05);
+            let seed = !alive || self.rng.fraction() < 0.5;
+            self.spot(seed);
+        }
+    }
+
+    /// Draw the second chemical
+    fn draw(&self) -> RgbaImage {
+        let small = RgbaImage::from_fn(self.columns as u32, self.rows as u32, |x, y| {
+            gradient(
+                &self.colors,
+                self.b[y as usize * self.columns + x as usize] * 3.0,
+            )
+        });
+        imageops::resize(&small, self.width, self.height, FilterType::Triangle)
+    }
+}
+
+impl FrameSource for ReactionDiffusion {
+    fn frame(&mut self, elapsed: Duration) -> Option<RgbaImage> {
+        let seconds = seconds_since(&mut self.last, elapsed);
+        self.step(seconds);
+        Some(self.draw())
+    }
+}
+
+/// Particle drifting along the flow field
+#[derive(Debug, Clone, Copy)]
+struct Drifter {
+    /// Position on the trails
+    x: f32,
+    y: f32,
+
+    /// Seconds left before starting again elsewhere
+    life: f32,
+}
+
+/// Particles drifting along a slowly turning field, leaving fading trails
+struct FlowField {
+    /// Frame size
+    width: u32,
+    height: u32,
+
+    /// Size of the trails
+    columns: usize,
+    rows: usize,
+
+    /// Brightness of the trails, from 0.0 to 1.0, row by row
+    trails: Vec<f32>,
+
+    /// Particles drifting
+    drifters: Vec<Drifter>,
+
+    /// Noise the field turns by, at the corners of its cells, row by row
+    noise: Vec<f32>,
+
+    /// Background, trail and brightest colors
+    colors: [Rgba<u8>; 3],
+
+    /// Speed and density settings
+    settings: PatternSettings,
+
+    /// Randomness of the particles and the field
+    rng: Rng,
+
+    /// How far the field has drifted, in its own seconds
+    time: f32,
+
+    /// Time of the last frame
+    last: Option<Duration>,
+}
+
+impl FlowField {
+    fn new(settings: &PatternSettings, width: u32, height: u32) -> Self {
+        let (columns, rows) = (
+            (width / FLOW_DOWNSCALE).max(1) as usize,
+            (height / FLOW_DOWNSCALE).max(1) as usize,
+        );
+        let mut rng = pattern_rng(settings);
+        let count = (FLOW_PARTICLES * width as f32 * height as f32 / 1_000_000.0
+            * settings.density.clamp(0.02, 1.0)) as usize;
+        let mut field = Self {
+            width,
+            height,
+            columns,
+            rows,
+            trails: vec![0.0; columns * rows],
+            drifters: Vec::with_capacity(count),
+            noise: (0..FLOW_LATTICE * FLOW_LATTICE)
+                .map(|_| rng.fraction())
+                .collect(),
+            colors: scheme_colors(settings.scheme, ProceduralPattern::FlowField),
+            settings: settings.clone(),
+            rng,
+            time: 0.0,
+            last: None,
+        };
+        for _ in 0..count {
+            let drifter = field.spawn();
+            field.drifters.push(drifter);
+        }
+        field
+    }
+
+    /// Create a particle somewhere on the screen
+    fn spawn(&mut self) -> Drifter {
+        Drifter {
+            x: self.rng.between(0.0, self.columns as f32),
+            y: self.rng.between(0.0, self.rows as f32),
+            life: self.rng.between(2.0, 8.0),
+        }
+    }
+
+    /// Get the noise at a point in cells, wrapping around both ways
+    fn noise_at(&self, u: f32, v: f32) -> f32 {
+        let corner = |column: usize, row: usize| {
+            self.noise[row % FLOW_LATTICE * FLOW_LATTICE + column % FLOW_LATTICE]
+        };
+        let (u, v) = (
+            u.rem_euclid(FLOW_LATTICE as f32),
+            v.rem_euclid(FLOW_LATTICE as f32),
+        );
+        let (column, row) = (u.floor() as usize, v.floor() as usize);
+        let (fx, fy) = (u.fract(), v.fract());
+        let (fx, fy) = (fx * fx * (3.0 - 2.0 * fx), fy * fy * (3.0 - 2.0 * fy));
+        let top = corner(column, row) * (1.0 - fx) + corner(column + 1, row) * fx;
+        let bottom = corner(column, row + 1) * (1.0 - fx) + corner(column + 1, row + 1) * fx;
+        top * (1.0 - fy) + bottom * fy
+    }
+
+    /// Get the direction the field flows in at a point on the trails
+    fn direction(&self, x: f32, y: f32) -> f32 {
+        let scale = FLOW_CELLS / self.rows as f32;
+        self.noise_at(x * scale + self.time * 0.05, y * scale - self.time * 0.03) * TAU * 2.0
+    }
+
+    /// Move the particles on by `seconds`, fading the trails they left
+    fn step(&mut self, seconds: f32) {
+        let seconds = seconds * self.settings.speed.max(0.0);
+        self.time += seconds;
+        let keep = FLOW_KEEP.powf(seconds);
+        for trail in &mut self.trails {
+            *trail *= keep;
+        }
+
+        // Trails are drawn a pixel at a time, so they have no gaps
+        let distance = 60.0 * self.rows as f32 / 540.0 * seconds;
+        let moves = distance.ceil() as usize;
+        for index in 0..self.drifters.len() {
+            let mut drifter = self.drifters[index];
+            for _ in 0..moves {
+                let angle = self.direction(drifter.x, drifter.y);
+                drifter.x += angle.cos() * distance / moves as f32;
+                drifter.y += angle.sin() * distance / moves as f32;
+                if drifter.x < 0.0
+                    || drifter.y < 0.0
+                    || drifter.x >= self.columns as f32
+                    || drifter.y >= self.rows as f32
+                {
+                    break;
+                }
+                let trail =
+                    &mut self.trails[drifter.y as usize * self.columns + drifter.x as usize];
+                *trail = (*trail + 0.3).min(1.0);
+            }
+            drifter.life -= seconds;
+            let gone = drifter.life <= 0.0
+                || drifter.x < 0.0
+                || drifter.y < 0.0
+                || drifter.x >= self.columns as f32
+                || drifter.y >= self.rows as f32;
+            self.drifters[index] = if gone { self.spawn() } else { drifter };
+        }
+    }
+
+    /// Draw the trails
+    fn draw(&self) -> RgbaImage {
+        let small = RgbaImage::from_fn(self.columns as u32, self.rows as u32, |x, y| {
+            gradient(
+                &self.colors,
+                self.trails[y as usize * self.columns + x as usize],
+            )
+        });
+        imageops::resize(&small, self.width, self.height, FilterType::Triangle)
+    }
+}
+
+impl FrameSource for FlowField {
+    fn frame(&mut self, elapsed: Duration) -> Option<RgbaImage> {
+        let seconds = seconds_since(&mut self.last, elapsed);
+        self.step(seconds);
+        Some(self.draw())
+    }
+}
+
+#[async_trait]
+impl super::Wallpaper for ProceduralWallpaper {
+    fn get_type(&self) -> WallpaperType {
+        WallpaperType::Procedural
+    }
+
+    fn get_path(&self) -> Option<&Path> {
+        None
+    }
+
+    async fn start(&self) -> AppResult<()> {
+        debug!("Starting {} wallpaper", self.pattern.name());
+        let (width, height) = frame_player::frame_size();
+        self.player.start(
+            pattern_frames(
+                self.pattern,
+                self.settings.settings(self.pattern),
+                width,
+                height,
+            ),
+            self.settings.fps,
+        );
+        info!("{} wallpaper started", self.pattern.name());
+        Ok(())
+    }
+
+    async fn stop(&self) -> AppResult<()> {
+        debug!("Stopping {} wallpaper", self.pattern.name());
+        self.player.stop();
+        info!("{} wallpaper stopped", self.pattern.name());
+        Ok(())
+    }
+
+    async fn pause(&self) -> AppResult<()> {
+        self.player.pause();
+        info!("{} wallpaper paused", self.pattern.name());
+        Ok(())
+    }
+
+    async fn resume(&self) -> AppResult<()> {
+        self.player.resume();
+        info!("{} wallpaper resumed", self.pattern.name());
+        Ok(())
+    }
+
+    async fn capture_frame(&self, path: &Path) -> AppResult<()> {
+        self.player.capture_frame(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pattern_ids() {
+        for pattern in ProceduralPattern::ALL {
+            assert_eq!(ProceduralPattern::from_id(pattern.id()), Some(pattern));
+        }
+        assert_eq!(ProceduralPattern::from_id("lava-lamp"), None);
+    }
+
+    #[test]
+    fn test_gradient() {
+        let colors = scheme_colors(ColorScheme::Mono, ProceduralPattern::Starfield);
+        assert_eq!(gradient(&colors, 0.0), Rgba([0, 0, 0, 255]));
+        assert_eq!(gradient(&colors, 0.5), Rgba([128, 128, 128, 255]));
+        assert_eq!(gradient(&colors, 2.0), Rgba([255, 255, 255, 255]));
+        assert_eq!(hue(0.0), Rgba([255, 0, 0, 255]));
+        assert_eq!(hue(1.0 / 3.0), Rgba([0, 255, 0, 255]));
+    }
+
+    #[test]
+    fn test_patterns_move() {
+        for pattern in ProceduralPattern::ALL {
+            let settings = PatternSettings {
+                density: 1.0,
+                ..PatternSettings::default()
+            };
+            let mut frames = pattern_frames(pattern, &settings, 160, 120);
+            let first = frames.frame(Duration::ZERO).unwrap();
+            let later = frames.frame(Duration::from_millis(200)).unwrap();
+            assert_eq!(first.dimensions(), (160, 120));
+            assert_ne!(first, later, "{} stood still", pattern.name());
+        }
+    }
+
+    #[test]
+    fn test_stars_come_closer() {
+        let settings = PatternSettings::default();
+        let mut field = Starfield::new(&settings, 320, 200);
+        let dense = Starfield::new(
+            &PatternSettings {
+                density: 1.0,
+                ..settings
+            },
+            320,
+            200,
+        );
+        assert!(dense.stars.len() > field.stars.len());
+
+        field.stars = vec![Star {
+            x: 0.1,
+            y: 0.1,
+            z: 0.8,
+        }];
+        field.step(0.1);
+        assert!(field.stars[0].z < 0.8);
+
+        // Stars passing the edge start again far away
+        field.stars = vec![Star {
+            x: 0.9,
+            y: 0.0,
+            z: 0.9,
+        }];
+        field.step(0.25);
+        assert_eq!(field.stars[0].z, 1.0);
+    }
+
+    #[test]
+    fn test_life_rules() {
+        let mut life = GameOfLife::new(&PatternSettings::default(), 8, 8);
+        assert_eq!((life.columns, life.rows), (8, 8));
+
+        // A blinker turns on its side and back
+        life.cells = vec![false; 64];
+        for x in 2..5 {
+            life.cells[3 * 8 + x] = true;
+        }
+        let before = life.cells.clone();
+        life.generation();
+        assert!(life.cells[2 * 8 + 3] && life.cells[4 * 8 + 3] && !life.cells[3 * 8 + 2]);
+        life.generation();
+        assert_eq!(life.cells, before);
+    }
+
+    #[test]
+    fn test_seeds_repeat() {
+        let settings = |seed| PatternSettings {
+            seed,
+            ..PatternSettings::default()
+        };
+        let grow = |seed| {
+            let mut frames =
+                pattern_frames(ProceduralPattern::FlowField, &settings(seed), 160, 120);
+            frames.frame(Duration::ZERO);
+            frames.frame(Duration::from_millis(200)).unwrap()
+        };
+        assert_eq!(grow(7), grow(7));
+        assert_ne!(grow(7), grow(8));
+        assert_eq!(
+            GameOfLife::new(&settings(7), 160, 120).cells,
+            GameOfLife::new(&settings(7), 160, 120).cells
+        );
+    }
+
+    #[test]
+    fn test_reaction_spreads() {
+        let mut diffusion = ReactionDiffusion::new(
+            &PatternSettings {
+                density: 0.0,
+                ..PatternSettings::default()
+            },
+            320,
+            240,
+        );
+        let seeded =
+            |diffusion: &ReactionDiffusion| diffusion.b.iter().filter(|b| **b > 0.1).count();
+        let before = seeded(&diffusion);
+        for _ in 0..400 {
+            diffusion.react();
+        }
+        assert!(seeded(&diffusion) > before);
+    }
+}
@@ -0,0 +1,58 @@
+//! Small random number generator for wallpapers drawn in-process
+//!
+//! A xorshift generator: fast, good enough for scattering particles, and
+//! repeatable when seeded, so a seed always grows the same pattern.
+
+/// Random number generator
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    /// Create a generator seeded from the system
+    pub fn new() -> Self {
+        let mut bytes = [0u8; 8];
+        let _ = getrandom::fill(&mut bytes);
+        Self::seeded(u64::from_le_bytes(bytes))
+    }
+
+    /// Create a generator giving the same numbers for the same seed
+    pub fn seeded(seed: u64) -> Self {
+        // Zero would only ever give zero
+        Self(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    /// Get the next number from 0.0 to 1.0
+    pub fn fraction(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Get a number between `low` and `high`
+    pub fn between(&mut self, low: f32, high: f32) -> f32 {
+        low + (high - low) * self.fraction()
+    }
+}
+
+impl Default for Rng {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seeded_repeats() {
+        let (mut a, mut b) = (Rng::seeded(42), Rng::seeded(42));
+        for _ in 0..100 {
+            let value = a.fraction();
+            assert_eq!(value, b.fraction());
+            assert!((0.0..1.0).contains(&value));
+        }
+        assert_ne!(Rng::seeded(1).fraction(), Rng::seeded(2).fraction());
+    }
+}
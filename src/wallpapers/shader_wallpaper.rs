@@ -1,32 +1,66 @@
-use crate::core::{AppError, AppResult, WallpaperType};
+use crate::core::span::SpanLayout;
+use crate::core::{AppResult, WallpaperType};
 use crate::platform::WallpaperManager;
-use log::{debug, error, info};
+use async_trait::async_trait;
+use log::{debug, info};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use async_trait::async_trait;
 
 /// Shader wallpaper
 pub struct ShaderWallpaper {
     /// Shader path
     path: PathBuf,
-    
+
     /// Platform-specific wallpaper manager
     wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>,
-    
+
     /// Whether the shader is active
     is_active: Arc<Mutex<bool>>,
+
+    /// Highest frame rate, without a limit when unset
+    max_fps: Option<u32>,
+
+    /// Whether to span all monitors as one canvas
+    span: bool,
+
+    /// Name of the only monitor the shader is shown on
+    monitor: Option<String>,
 }
 
 impl ShaderWallpaper {
     /// Create a new shader wallpaper
-    pub fn new<P: AsRef<Path>>(path: P, wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>) -> Self {
+    pub fn new<P: AsRef<Path>>(
+        path: P,
+        wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>,
+    ) -> Self {
         Self {
             path: path.as_ref().to_path_buf(),
             wallpaper_manager,
             is_active: Arc::new(Mutex::new(false)),
+            max_fps: None,
+            span: false,
+            monitor: None,
         }
     }
+
+    /// Limit the frame rate
+    pub fn with_max_fps(mut self, max_fps: Option<u32>) -> Self {
+        self.max_fps = max_fps;
+        self
+    }
+
+    /// Span all monitors as one canvas
+    pub fn with_span(mut self, span: bool) -> Self {
+        self.span = span;
+        self
+    }
+
+    /// Show the shader on one monitor only, named as the platform names it
+    pub fn with_monitor(mut self, monitor: Option<String>) -> Self {
+        self.monitor = monitor;
+        self
+    }
 }
 
 #[async_trait]
@@ -34,52 +68,83 @@ impl super::Wallpaper for ShaderWallpaper {
     fn get_type(&self) -> WallpaperType {
         WallpaperType::Shader
     }
-    
+
     fn get_path(&self) -> Option<&Path> {
         Some(&self.path)
     }
-    
+
     async fn start(&self) -> AppResult<()> {
         debug!("Starting shader wallpaper: {:?}", self.path);
-        
+
         // Set the wallpaper using the platform-specific manager
-        self.wallpaper_manager.set_shader_wallpaper(&self.path).await?;
-        
+        if let Some(monitor) = &self.monitor {
+            self.wallpaper_manager
+                .set_shader_wallpaper_on(&self.path, self.max_fps, monitor)
+                .await?;
+        } else {
+            match self.span.then(SpanLayout::detect).flatten() {
+                Some(layout) => {
+                    self.wallpaper_manager
+                        .set_shader_wallpaper_spanned(&self.path, self.max_fps, &layout)
+                        .await?
+                }
+                None => {
+                    self.wallpaper_manager
+                        .set_shader_wallpaper_limited(&self.path, self.max_fps)
+                        .await?
+                }
+            }
+        }
+
         // Update active state
         let mut is_active = self.is_active.lock().await;
         *is_active = true;
-        
+
         info!("Shader wallpaper started");
         Ok(())
     }
-    
+
     async fn stop(&self) -> AppResult<()> {
         debug!("Stopping shader wallpaper");
-        
+
         // Stop the wallpaper using the platform-specific manager
-        self.wallpaper_manager.stop_wallpaper().await?;
-        
+        match &self.monitor {
+            Some(monitor) => self.wallpaper_manager.stop_wallpaper_on(monitor).await?,
+            None => self.wallpaper_manager.stop_wallpaper().await?,
+        }
+
         // Update active state
         let mut is_active = self.is_active.lock().await;
         *is_active = false;
-        
+
         info!("Shader wallpaper stopped");
         Ok(())
     }
-    
+
     async fn pause(&self) -> AppResult<()> {
         debug!("Pausing shader wallpaper");
-        
-        // TODO: Implement shader pausing
-        error!("Shader pausing not implemented yet");
-        Err(AppError::WallpaperError("Shader pausing not implemented yet".to_string()))
+
+        // The platform manager owns the player process
+        self.wallpaper_manager.pause_wallpaper().await?;
+
+        info!("Shader wallpaper paused");
+        Ok(())
     }
-    
+
     async fn resume(&self) -> AppResult<()> {
         debug!("Resuming shader wallpaper");
-        
-        // TODO: Implement shader resuming
-        error!("Shader resuming not implemented yet");
-        Err(AppError::WallpaperError("Shader resuming not implemented yet".to_string()))
+
+        self.wallpaper_manager.resume_wallpaper().await?;
+
+        info!("Shader wallpaper resumed");
+        Ok(())
     }
-} 
\ No newline at end of file
+
+    async fn process_id(&self) -> Option<u32> {
+        let player = self.wallpaper_manager.player()?;
+        match &self.monitor {
+            Some(monitor) => player.pid_on(monitor),
+            None => player.pid(),
+        }
+    }
+}
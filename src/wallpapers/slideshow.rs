@@ -0,0 +1,371 @@
+//! Slideshow wallpapers: the images of a folder, one after another
+//!
+//! The images are drawn in-process and changed with the transition effects.
+//! The frame player pipes the frames of each change to the wallpaper window,
+//! so the backend is never asked to set a wallpaper while the slideshow runs.
+//! Between changes nothing is drawn and the window keeps the current image up,
+//! and the next image is prepared while the current one is shown.
+use crate::core::config::{PlacementConfig, SlideshowConfig, SlideshowOrder, TransitionEffect};
+use crate::core::{AppError, AppResult, WallpaperType};
+use crate::experiments::effects;
+use crate::platform::WallpaperManager;
+use crate::wallpapers::frame_player::{FramePlayer, FrameSource};
+use crate::wallpapers::random::Rng;
+use async_trait::async_trait;
+use image::RgbaImage;
+use log::{debug, info, warn};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Slideshow of a folder's images, drawn in-process
+pub struct SlideshowWallpaper {
+    /// Folder of the images
+    folder: PathBuf,
+
+    /// Timing, order and transition
+    settings: SlideshowConfig,
+
+    /// How the images are fitted
+    placement: PlacementConfig,
+
+    /// Player showing the frames
+    player: FramePlayer,
+}
+
+impl SlideshowWallpaper {
+    /// Create a new slideshow of the images in `folder`
+    pub fn new<P: AsRef<Path>>(
+        folder: P,
+        wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>,
+    ) -> Self {
+        Self {
+            folder: folder.as_ref().to_path_buf(),
+            settings: SlideshowConfig::default(),
+            placement: PlacementConfig::default(),
+            player: FramePlayer::new(wallpaper_manager),
+        }
+    }
+
+    /// Set the timing, order and transition
+    pub fn with_settings(mut self, settings: SlideshowConfig) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Set how the images are fitted
+    pub fn with_placement(mut self, placement: PlacementConfig) -> Self {
+        self.placement = placement;
+        self
+    }
+}
+
+/// List the images of a folder in the order they're shown
+pub fn list_images(folder: &Path, order: SlideshowOrder, rng: &mut Rng) -> AppResult<Vec<PathBuf>> {
+    let mut images: Vec<PathBuf> = fs::read_dir(folder)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && WallpaperType::Static.accepts(path))
+        .collect();
+    if images.is_empty() {
+        return Err(AppError::WallpaperError(format!(
+            "No images in {}",
+            folder.display()
+        )));
+    }
+
+    match order {
+        SlideshowOrder::Name => images.sort_by_key(|path| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().to_lowercase())
+        }),
+        SlideshowOrder::Modified => images.sort_by_key(|path| {
+            fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+        }),
+        SlideshowOrder::Shuffle => {
+            for index in (1..images.len()).rev() {
+                let other = (rng.fraction() * (index + 1) as f32) as usize;
+                images.swap(index, other.min(index));
+            }
+        }
+    }
+    Ok(images)
+}
+
+/// Frames of a slideshow
+struct Slides {
+    /// Folder of the images
+    folder: PathBuf,
+
+    /// Timing, order and transition
+    settings: SlideshowConfig,
+
+    /// How the images are fitted
+    placement: PlacementConfig,
+
+    /// Images of this round, in order
+    images: Vec<PathBuf>,
+
+    /// Position of the image shown in `images`
+    index: usize,
+
+    /// Image shown, drawn for the monitor
+    shown: RgbaImage,
+
+    /// Next image and its position, once prepared
+    next: Option<(usize, RgbaImage)>,
+
+    /// When the image shown was fully in place
+    shown_at: Duration,
+
+    /// Whether the first frame was drawn
+    started: bool,
+
+    /// Randomness of shuffled rounds
+    rng: Rng,
+}
+
+impl Slides {
+    /// Start with the first image of the folder that can be opened
+    fn new(
+        folder: &Path,
+        settings: SlideshowConfig,
+        placement: PlacementConfig,
+    ) -> AppResult<Self> {
+        let mut rng = Rng::new();
+        let images = list_images(folder, settings.order, &mut rng)?;
+        let (index, shown) = images
+            .iter()
+            .enumerate()
+            .find_map(|(index, path)| render(path, &placement).map(|image| (index, image)))
+            .ok_or_else(|| {
+                AppError::WallpaperError(format!(
+                    "None of the images in {} can be opened",
+                    folder.display()
+                ))
+            })?;
+        Ok(Self {
+            folder: folder.to_path_buf(),
+            settings,
+            placement,
+            images,
+            index,
+            shown,
+            next: None,
+            shown_at: Duration::ZERO,
+            started: false,
+            rng,
+        })
+    }
+
+    /// Prepare the image after the one shown, starting a new round after the last
+    ///
+    /// Images that can't be opened are skipped; false when none can be.
+    fn prepare_next(&mut self) -> bool {
+        for _ in 0..self.images.len() {
+            let mut index = self.index + 1;
+            if index >= self.images.len() {
+                // Images added or removed meanwhile show from the next round
+                let current = self.images.get(self.index).cloned();
+                if let Ok(mut images) =
+                    list_images(&self.folder, self.settings.order, &mut self.rng)
+                {
+                    // A new shuffle doesn't start with the image just shown
+                    if images.len() > 1 && images.first() == current.as_ref() {
+                        let last = images.len() - 1;
+                        images.swap(0, last);
+                    }
+                    self.images = images;
+                }
+                index = 0;
+            }
+            self.index = index;
+            if let Some(image) = render(&self.images[index], &self.placement) {
+                self.next = Some((index, image));
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// Draw an image for the primary monitor, or None when it can't be opened
+fn render(path: &Path, placement: &PlacementConfig) -> Option<RgbaImage> {
+    match effects::render_for_primary(path, placement) {
+        Ok(image) => Some(image),
+        Err(e) => {
+            warn!("Skipping slideshow image {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+impl FrameSource for Slides {
+    fn frame(&mut self, elapsed: Duration) -> Option<RgbaImage> {
+        if !self.started {
+            self.started = true;
+            return Some(self.shown.clone());
+        }
+
+        // Prepared while the image shown stays up, which leaves plenty of time
+        if self.next.is_none() && !self.prepare_next() {
+            self.shown_at = elapsed;
+            return None;
+        }
+
+        let interval = Duration::from_secs(self.settings.interval_secs.max(1) as u64);
+        let since = elapsed.saturating_sub(self.shown_at);
+        if since < interval {
+            return None;
+        }
+
+        let transition = self.settings.transition;
+        let progress =
+            (since - interval).as_secs_f32() / (transition.duration_ms.max(1) as f32 / 1000.0);
+        let (_, next) = self.next.as_ref()?;
+        if transition.effect == TransitionEffect::None || progress >= 1.0 {
+            let (index, next) = self.next.take()?;
+            debug!("Slideshow showing {}", self.images[index].display());
+            self.shown = next;
+            self.shown_at = elapsed;
+            return Some(self.shown.clone());
+        }
+        Some(effects::render_frame(
+            &self.shown,
+            next,
+            transition.effect,
+            effects::ease(progress),
+        ))
+    }
+}
+
+#[async_trait]
+impl super::Wallpaper for SlideshowWallpaper {
+    fn get_type(&self) -> WallpaperType {
+        WallpaperType::Slideshow
+    }
+
+    fn get_path(&self) -> Option<&Path> {
+        Some(&self.folder)
+    }
+
+    async fn start(&self) -> AppResult<()> {
+        debug!("Starting slideshow wallpaper: {:?}", self.folder);
+
+        let (folder, settings, placement) = (
+            self.folder.clone(),
+            self.settings.clone(),
+            self.placement.clone(),
+        );
+        let slides = tokio::task::spawn_blocking(move || Slides::new(&folder, settings, placement))
+            .await
+            .map_err(|e| {
+                AppError::WallpaperError(format!("Failed to prepare the slideshow: {}", e))
+            })??;
+        self.player.start(Box::new(slides), self.settings.fps);
+
+        info!("Slideshow wallpaper started");
+        Ok(())
+    }
+
+    async fn stop(&self) -> AppResult<()> {
+        debug!("Stopping slideshow wallpaper");
+        self.player.stop();
+        info!("Slideshow wallpaper stopped");
+        Ok(())
+    }
+
+    async fn pause(&self) -> AppResult<()> {
+        self.player.pause();
+        info!("Slideshow wallpaper paused");
+        Ok(())
+    }
+
+    async fn resume(&self) -> AppResult<()> {
+        self.player.resume();
+        info!("Slideshow wallpaper resumed");
+        Ok(())
+    }
+
+    async fn capture_frame(&self, path: &Path) -> AppResult<()> {
+        self.player.capture_frame(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::TransitionConfig;
+    use image::Rgba;
+
+    fn folder(names: &[&str]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for (index, name) in names.iter().enumerate() {
+            RgbaImage::from_pixel(8, 8, Rgba([index as u8 * 100, 0, 0, 255]))
+                .save(dir.path().join(name))
+                .unwrap();
+        }
+        fs::write(dir.path().join("notes.txt"), "not an image").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_list_images() {
+        let dir = folder(&["b.png", "A.png", "c.png"]);
+        let mut rng = Rng::seeded(1);
+        let names = |images: Vec<PathBuf>| -> Vec<String> {
+            images
+                .iter()
+                .map(|path| path.file_name().unwrap().to_string_lossy().to_string())
+                .collect()
+        };
+        assert_eq!(
+            names(list_images(dir.path(), SlideshowOrder::Name, &mut rng).unwrap()),
+            ["A.png", "b.png", "c.png"]
+        );
+
+        let mut shuffled =
+            names(list_images(dir.path(), SlideshowOrder::Shuffle, &mut rng).unwrap());
+        shuffled.sort();
+        assert_eq!(shuffled, ["A.png", "b.png", "c.png"]);
+
+        assert!(list_images(
+            tempfile::tempdir().unwrap().path(),
+            SlideshowOrder::Name,
+            &mut rng
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_slides_change_on_time() {
+        let dir = folder(&["a.png", "b.png"]);
+        let settings = SlideshowConfig {
+            interval_secs: 10,
+            transition: TransitionConfig {
+                effect: TransitionEffect::Crossfade,
+                duration_ms: 2000,
+            },
+            ..SlideshowConfig::default()
+        };
+        let mut slides = Slides::new(dir.path(), settings, PlacementConfig::default()).unwrap();
+        let red = |frame: &RgbaImage| frame.get_pixel(0, 0)[0];
+        let seconds = Duration::from_secs_f32;
+
+        // The first image, then nothing new until it's time to change
+        assert_eq!(red(&slides.frame(seconds(0.0)).unwrap()), 0);
+        assert!(slides.frame(seconds(5.0)).is_none());
+
+        // Halfway through the crossfade, then the next image alone
+        let fading = red(&slides.frame(seconds(11.0)).unwrap());
+        assert!(fading > 0 && fading < 100, "{}", fading);
+        assert_eq!(red(&slides.frame(seconds(12.5)).unwrap()), 100);
+        assert!(slides.frame(seconds(13.0)).is_none());
+
+        // After the last image the folder starts over
+        assert_eq!(red(&slides.frame(seconds(25.0)).unwrap()), 0);
+    }
+}
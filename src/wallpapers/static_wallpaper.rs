@@ -1,26 +1,80 @@
-use crate::core::{AppResult, WallpaperType};
+use crate::core::config::{PlacementConfig, TransitionConfig};
+use crate::core::{placement, AppResult, WallpaperType};
+use crate::experiments::effects;
 use crate::platform::WallpaperManager;
+use async_trait::async_trait;
 use log::{debug, info};
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
-use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
 
 /// Static wallpaper
 pub struct StaticWallpaper {
     /// Wallpaper path
     path: PathBuf,
-    
+
     /// Platform-specific wallpaper manager
     wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>,
+
+    /// How the image is fitted to the monitors
+    placement: PlacementConfig,
+
+    /// Monitors given their own image when last started
+    monitors: Mutex<Vec<String>>,
+
+    /// Whether the image is shown on one monitor only
+    single_monitor: bool,
+
+    /// Animation from the image shown before, with its path
+    transition: Option<(PathBuf, TransitionConfig)>,
 }
 
 impl StaticWallpaper {
     /// Create a new static wallpaper
-    pub fn new<P: AsRef<Path>>(path: P, wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>) -> Self {
+    pub fn new<P: AsRef<Path>>(
+        path: P,
+        wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>,
+    ) -> Self {
         Self {
             path: path.as_ref().to_path_buf(),
             wallpaper_manager,
+            placement: PlacementConfig::default(),
+            monitors: Mutex::new(Vec::new()),
+            single_monitor: false,
+            transition: None,
+        }
+    }
+
+    /// Set how the image is fitted to the monitors
+    pub fn with_placement(mut self, placement: PlacementConfig) -> Self {
+        self.placement = placement;
+        self
+    }
+
+    /// Animate the switch from the image at `from`, when the placement allows it
+    ///
+    /// Call after `with_placement` and `with_monitor`.
+    pub fn with_transition(mut self, from: Option<PathBuf>, transition: TransitionConfig) -> Self {
+        if !self.single_monitor && effects::applies(&self.placement, &transition) {
+            self.transition = from
+                .filter(|from| *from != self.path)
+                .map(|from| (from, transition));
         }
+        self
+    }
+
+    /// Show the image on one monitor only, given by its ID
+    ///
+    /// The other monitors show their own image from the placement, or this
+    /// one when they have none. Call after `with_placement`.
+    pub fn with_monitor(mut self, monitor: Option<String>) -> Self {
+        if let Some(monitor) = monitor {
+            self.placement
+                .monitor_images
+                .insert(monitor, self.path.display().to_string());
+            self.placement.span = false;
+            self.single_monitor = true;
+        }
+        self
     }
 }
 
@@ -29,38 +83,74 @@ impl super::Wallpaper for StaticWallpaper {
     fn get_type(&self) -> WallpaperType {
         WallpaperType::Static
     }
-    
+
     fn get_path(&self) -> Option<&Path> {
         Some(&self.path)
     }
-    
+
     async fn start(&self) -> AppResult<()> {
         debug!("Starting static wallpaper: {:?}", self.path);
-        
+
         // Set the wallpaper using the platform-specific manager
-        self.wallpaper_manager.set_static_wallpaper(&self.path).await?;
-        
+        let monitors = match &self.transition {
+            Some((from, transition)) => {
+                effects::transition(
+                    &self.wallpaper_manager,
+                    from,
+                    &self.path,
+                    &self.placement,
+                    transition,
+                )
+                .await?
+            }
+            None => {
+                placement::set_static_wallpaper(
+                    &*self.wallpaper_manager,
+                    &self.path,
+                    &self.placement,
+                )
+                .await?
+            }
+        };
+        *self.monitors.lock().unwrap() = monitors;
+
         info!("Static wallpaper started");
         Ok(())
     }
-    
+
     async fn stop(&self) -> AppResult<()> {
         debug!("Stopping static wallpaper");
-        
-        // Stop the wallpaper using the platform-specific manager
-        self.wallpaper_manager.stop_wallpaper().await?;
-        
+
+        // Clearing the desktop would also clear the other monitors
+        if !self.single_monitor {
+            self.wallpaper_manager.stop_wallpaper().await?;
+        }
+
         info!("Static wallpaper stopped");
         Ok(())
     }
-    
+
+    fn monitors(&self) -> Vec<String> {
+        self.monitors.lock().unwrap().clone()
+    }
+
+    async fn redraw(&self) -> AppResult<()> {
+        // Without the transition, which already played
+        let monitors =
+            placement::set_static_wallpaper(&*self.wallpaper_manager, &self.path, &self.placement)
+                .await?;
+        *self.monitors.lock().unwrap() = monitors;
+        debug!("Redrew static wallpaper: {:?}", self.path);
+        Ok(())
+    }
+
     async fn pause(&self) -> AppResult<()> {
         // Static wallpapers don't need to be paused
         Ok(())
     }
-    
+
     async fn resume(&self) -> AppResult<()> {
         // Static wallpapers don't need to be resumed
         Ok(())
     }
-} 
\ No newline at end of file
+}
@@ -1,15 +1,52 @@
-use crate::core::{AppError, AppResult, WallpaperType};
+use super::ReducedQuality;
+use crate::core::span::{self, SpanLayout, SpanRegion};
+use crate::core::{AppError, AppResult, ProcessHandle, WallpaperType};
 use crate::platform::WallpaperManager;
+use async_trait::async_trait;
+use image::RgbaImage;
 use log::{debug, error, info, warn};
+use std::io::{BufRead, BufReader, Write};
 use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
 use std::sync::Arc;
-use std::process::{Child, Command};
+use std::thread;
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
-use async_trait::async_trait;
 
 #[cfg(windows)]
 use crate::platform::windows::window_manager::WindowManager;
 
+/// Start of the names of MPV IPC endpoints, which tells wallpaper players apart
+pub const IPC_PREFIX: &str = "aether-desk-mpv-";
+
+/// Time to wait for MPV to save a captured frame
+const FRAME_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Time to wait for MPV to open its IPC endpoint or answer over it
+const IPC_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// MPV overlay slot the overlay is shown in
+const OVERLAY_ID: u32 = 0;
+
+/// Time between checks that spanned players still show the same moment
+const SYNC_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Largest difference between spanned players before they are realigned, in seconds
+const MAX_DRIFT: f64 = 0.1;
+
+/// Running MPV instance
+struct MpvPlayer {
+    /// MPV process handle
+    handle: ProcessHandle,
+
+    /// JSON IPC socket (a named pipe on Windows)
+    ipc_path: PathBuf,
+
+    /// Filters cutting out the monitor's part when spanning all monitors
+    crop: Option<String>,
+}
+
 /// Video wallpaper
 pub struct VideoWallpaper {
     /// Video path
@@ -21,32 +58,154 @@ pub struct VideoWallpaper {
     /// Whether the video is playing
     is_playing: Arc<Mutex<bool>>,
 
-    /// MPV process handle
-    mpv_process: Arc<Mutex<Option<Child>>>,
+    /// Whether playback is paused
+    is_paused: Arc<Mutex<bool>>,
+
+    /// MPV instances, one per monitor when spanning all monitors
+    players: Arc<Mutex<Vec<MpvPlayer>>>,
+
+    /// Highest frame rate, without a limit when unset
+    max_fps: Option<u32>,
+
+    /// Whether to span all monitors as one canvas
+    span: bool,
+
+    /// Name of the only monitor the video plays on
+    monitor: Option<String>,
+
+    /// Changed on each stop, ending the thread keeping spanned players in step
+    sync_generation: Arc<AtomicU64>,
+
+    /// Lowered quality, kept when MPV is restarted
+    reduced_quality: Arc<Mutex<Option<ReducedQuality>>>,
+
+    /// Overlays shown so far, alternating between two files
+    overlays_shown: AtomicU64,
 
     /// Window manager for desktop integration (Windows only)
     #[cfg(windows)]
     window_manager: Arc<Mutex<Option<WindowManager>>>,
+
+    /// Windows of the spanned players, one per monitor (Windows only)
+    #[cfg(windows)]
+    span_windows: Arc<Mutex<Vec<WindowManager>>>,
 }
 
 impl VideoWallpaper {
     /// Create a new video wallpaper
-    pub fn new<P: AsRef<Path>>(path: P, wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>) -> Self {
+    pub fn new<P: AsRef<Path>>(
+        path: P,
+        wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>,
+    ) -> Self {
         Self {
             path: path.as_ref().to_path_buf(),
             wallpaper_manager,
             is_playing: Arc::new(Mutex::new(false)),
-            mpv_process: Arc::new(Mutex::new(None)),
+            is_paused: Arc::new(Mutex::new(false)),
+            players: Arc::new(Mutex::new(Vec::new())),
+            max_fps: None,
+            span: false,
+            monitor: None,
+            sync_generation: Arc::new(AtomicU64::new(0)),
+            reduced_quality: Arc::new(Mutex::new(None)),
+            overlays_shown: AtomicU64::new(0),
             #[cfg(windows)]
             window_manager: Arc::new(Mutex::new(None)),
+            #[cfg(windows)]
+            span_windows: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Limit the frame rate
+    pub fn with_max_fps(mut self, max_fps: Option<u32>) -> Self {
+        self.max_fps = max_fps;
+        self
+    }
+
+    /// Span all monitors as one canvas, with an MPV window per monitor
+    pub fn with_span(mut self, span: bool) -> Self {
+        self.span = span;
+        self
+    }
+
+    /// Play the video on one monitor only, named as the platform names it
+    pub fn with_monitor(mut self, monitor: Option<String>) -> Self {
+        self.monitor = monitor;
+        self
+    }
+
+    /// Get an unused MPV IPC endpoint
+    fn new_ipc_path() -> PathBuf {
+        static NEXT: AtomicU32 = AtomicU32::new(0);
+        let name = format!(
+            "{}{}-{}",
+            IPC_PREFIX,
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        );
+
+        #[cfg(windows)]
+        {
+            PathBuf::from(format!(r"\\.\pipe\{}", name))
+        }
+
+        #[cfg(not(windows))]
+        {
+            dirs::runtime_dir()
+                .unwrap_or_else(std::env::temp_dir)
+                .join(format!("{}.sock", name))
+        }
+    }
+
+    /// Send a command to every MPV instance
+    async fn send_to_all(&self, command: serde_json::Value) -> AppResult<()> {
+        for player in self.players.lock().await.iter() {
+            send_command(&player.ipc_path, command.clone())?;
+        }
+        Ok(())
+    }
+
+    /// Get the MPV video filter chain of a player, cropping first when spanning
+    fn player_filters(
+        crop: Option<&str>,
+        max_fps: Option<u32>,
+        quality: Option<ReducedQuality>,
+    ) -> String {
+        let filters = Self::video_filters(max_fps, quality);
+        match crop {
+            Some(crop) if filters.is_empty() => crop.to_string(),
+            Some(crop) => format!("{},{}", crop, filters),
+            None => filters,
+        }
+    }
+
+    /// Get the MPV video filter chain for a frame rate limit and quality
+    ///
+    /// Full quality without a limit uses no filters.
+    fn video_filters(max_fps: Option<u32>, quality: Option<ReducedQuality>) -> String {
+        let fps = match (max_fps, quality.map(|quality| quality.max_fps)) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+
+        let mut filters = Vec::new();
+        if let Some(fps) = fps {
+            filters.push(format!("fps=fps={}", fps.max(1)));
+        }
+        if let Some(quality) = quality {
+            filters.push(format!(
+                "scale=w=trunc(iw*{}/2)*2:h=-2",
+                quality.scale.clamp(0.1, 1.0)
+            ));
         }
+        filters.join(",")
     }
-    
+
     /// Check if MPV is available on the system
     fn check_mpv_available() -> bool {
         // Try multiple possible MPV locations
         let mpv_commands = vec![
-            "mpv",                                    // Standard PATH
+            "mpv",                                   // Standard PATH
             "mpv.exe",                               // Windows with .exe
             "C:\\Program Files\\mpv\\mpv.exe",       // Common Windows install location
             "C:\\Program Files (x86)\\mpv\\mpv.exe", // 32-bit on 64-bit Windows
@@ -73,7 +232,7 @@ impl VideoWallpaper {
     }
 
     /// Get the MPV command path
-    fn get_mpv_command() -> Result<String, AppError> {
+    pub(crate) fn get_mpv_command() -> Result<String, AppError> {
         let mpv_commands = vec![
             "mpv",
             "mpv.exe",
@@ -93,34 +252,40 @@ impl VideoWallpaper {
             }
         }
 
-        Err(AppError::WallpaperError(
-            "MPV is not installed or not available. Please install MPV from https://mpv.io/".to_string()
-        ))
+        Err(AppError::missing_tool("mpv"))
     }
-    
+
     /// Start MPV with desktop integration
-    async fn start_mpv(&self) -> Result<Child, AppError> {
+    ///
+    /// With a screen, MPV plays on that monitor only. With a span layout too,
+    /// it shows the monitor's part of the canvas and starts paused, so all
+    /// the players can be started together.
+    async fn start_mpv(
+        &self,
+        screen: Option<&SpanRegion>,
+        span: Option<&SpanLayout>,
+    ) -> Result<MpvPlayer, AppError> {
         let mpv_command = Self::get_mpv_command()?;
 
         let mut cmd = Command::new(&mpv_command);
 
         // Basic MPV arguments for wallpaper mode (using most compatible options)
         cmd.args(&[
-            "--loop-file=inf",           // Loop the video infinitely
-            "--no-audio",                // Disable audio output
-            "--no-border",               // Remove window border
-            "--osd-level=0",             // Disable on-screen display
-            "--quiet",                   // Reduce log output
-            "--no-config",               // Don't load config files
+            "--loop-file=inf", // Loop the video infinitely
+            "--no-audio",      // Disable audio output
+            "--no-border",     // Remove window border
+            "--osd-level=0",   // Disable on-screen display
+            "--quiet",         // Reduce log output
+            "--no-config",     // Don't load config files
         ]);
 
         // Add optional arguments that might not be supported in all versions
         let optional_args = vec![
             "--no-input-default-bindings", // Disable input handling
-            "--no-input-cursor",         // Hide cursor
-            "--hwdec=auto",              // Enable hardware decoding if available
-            "--keepaspect=no",           // Don't maintain aspect ratio
-            "--no-terminal",             // Don't use terminal
+            "--no-input-cursor",           // Hide cursor
+            "--hwdec=auto",                // Enable hardware decoding if available
+            "--keepaspect=no",             // Don't maintain aspect ratio
+            "--no-terminal",               // Don't use terminal
         ];
 
         // Try to add optional arguments, but don't fail if they're not supported
@@ -128,90 +293,128 @@ impl VideoWallpaper {
             cmd.arg(arg);
         }
 
+        // Lets the wallpaper be paused without restarting the video
+        let ipc_path = Self::new_ipc_path();
+        cmd.arg(format!("--input-ipc-server={}", ipc_path.display()));
+
+        let crop = span
+            .zip(screen)
+            .map(|(layout, region)| span::video_crop_filter(layout, region));
+        let filters = Self::player_filters(
+            crop.as_deref(),
+            self.max_fps,
+            *self.reduced_quality.lock().await,
+        );
+        if !filters.is_empty() {
+            cmd.arg(format!("--vf={}", filters));
+        }
+        if span.is_some() {
+            cmd.arg("--pause");
+        }
+
         // Platform-specific window integration
         #[cfg(windows)]
         {
-            // Create a window manager and get the window handle
-            let mut wm_guard = self.window_manager.lock().await;
-            if wm_guard.is_none() {
-                match WindowManager::new().create_wallpaper_window() {
-                    Ok(window_hwnd) => {
-                        let hwnd_str = format!("{}", window_hwnd.0);
-
-                        // Use the window ID for MPV
-                        cmd.args(&[
-                            "--wid", &hwnd_str,      // Embed in our window
-                            "--no-keepaspect-window", // Don't maintain aspect ratio in window
-                        ]);
-
-                        *wm_guard = Some(WindowManager::new());
-                        debug!("Created wallpaper window with HWND: {}", hwnd_str);
-                    }
-                    Err(e) => {
-                        warn!("Failed to create wallpaper window: {}. Using fullscreen mode instead.", e);
-                        // Fallback to fullscreen mode
-                        cmd.args(&[
-                            "--fs",                  // Fullscreen
-                            "--no-keepaspect",       // Don't maintain aspect ratio
-                            "--ontop",               // Keep on top initially
-                        ]);
-                    }
-                }
+            if let Some(region) = screen {
+                // Each monitor's video plays in its own window, placed over that monitor
+                let mut window_manager = WindowManager::new();
+                let window_hwnd = window_manager.create_wallpaper_window()?;
+                window_manager.place_window(
+                    region.x as i32,
+                    region.y as i32,
+                    region.width as i32,
+                    region.height as i32,
+                )?;
+                let hwnd_str = format!("{}", window_hwnd.0);
+                cmd.args(&["--wid", &hwnd_str, "--no-keepaspect-window"]);
+                self.span_windows.lock().await.push(window_manager);
             } else {
-                // If window manager is already initialized, use the existing window
-                if let Some(ref existing_wm) = wm_guard.as_ref() {
-                    if let Some(window_hwnd) = existing_wm.get_window() {
-                        let hwnd_str = format!("{}", window_hwnd.0);
-                        cmd.args(&[
-                            "--wid", &hwnd_str,      // Embed in our window
-                            "--no-keepaspect-window", // Don't maintain aspect ratio in window
-                        ]);
-                        debug!("Using existing wallpaper window with HWND: {}", hwnd_str);
+                // Create a window manager and get the window handle
+                let mut wm_guard = self.window_manager.lock().await;
+                if wm_guard.is_none() {
+                    match WindowManager::new().create_wallpaper_window() {
+                        Ok(window_hwnd) => {
+                            let hwnd_str = format!("{}", window_hwnd.0);
+
+                            // Use the window ID for MPV
+                            cmd.args(&[
+                                "--wid",
+                                &hwnd_str,                // Embed in our window
+                                "--no-keepaspect-window", // Don't maintain aspect ratio in window
+                            ]);
+
+                            *wm_guard = Some(WindowManager::new());
+                            debug!("Created wallpaper window with HWND: {}", hwnd_str);
+                        }
+                        Err(e) => {
+                            warn!("Failed to create wallpaper window: {}. Using fullscreen mode instead.", e);
+                            // Fallback to fullscreen mode
+                            cmd.args(&[
+                                "--fs",            // Fullscreen
+                                "--no-keepaspect", // Don't maintain aspect ratio
+                                "--ontop",         // Keep on top initially
+                            ]);
+                        }
+                    }
+                } else {
+                    // If window manager is already initialized, use the existing window
+                    if let Some(ref existing_wm) = wm_guard.as_ref() {
+                        if let Some(window_hwnd) = existing_wm.get_window() {
+                            let hwnd_str = format!("{}", window_hwnd.0);
+                            cmd.args(&[
+                                "--wid",
+                                &hwnd_str,                // Embed in our window
+                                "--no-keepaspect-window", // Don't maintain aspect ratio in window
+                            ]);
+                            debug!("Using existing wallpaper window with HWND: {}", hwnd_str);
+                        } else {
+                            warn!("Existing window manager has no window, creating new one");
+                            match WindowManager::new().create_wallpaper_window() {
+                                Ok(window_hwnd) => {
+                                    let hwnd_str = format!("{}", window_hwnd.0);
+                                    cmd.args(&[
+                                        "--wid",
+                                        &hwnd_str,                // Embed in our window
+                                        "--no-keepaspect-window", // Don't maintain aspect ratio in window
+                                    ]);
+
+                                    *wm_guard = Some(WindowManager::new());
+                                    debug!("Created new wallpaper window with HWND: {}", hwnd_str);
+                                }
+                                Err(e) => {
+                                    warn!("Failed to create wallpaper window: {}. Using fullscreen mode instead.", e);
+                                    cmd.args(&[
+                                        "--fs",            // Fullscreen
+                                        "--no-keepaspect", // Don't maintain aspect ratio
+                                        "--ontop",         // Keep on top initially
+                                    ]);
+                                }
+                            }
+                        }
                     } else {
-                        warn!("Existing window manager has no window, creating new one");
+                        // Create new window manager
                         match WindowManager::new().create_wallpaper_window() {
                             Ok(window_hwnd) => {
                                 let hwnd_str = format!("{}", window_hwnd.0);
                                 cmd.args(&[
-                                    "--wid", &hwnd_str,      // Embed in our window
+                                    "--wid",
+                                    &hwnd_str,                // Embed in our window
                                     "--no-keepaspect-window", // Don't maintain aspect ratio in window
                                 ]);
 
                                 *wm_guard = Some(WindowManager::new());
-                                debug!("Created new wallpaper window with HWND: {}", hwnd_str);
+                                debug!("Created wallpaper window with HWND: {}", hwnd_str);
                             }
                             Err(e) => {
                                 warn!("Failed to create wallpaper window: {}. Using fullscreen mode instead.", e);
                                 cmd.args(&[
-                                    "--fs",                  // Fullscreen
-                                    "--no-keepaspect",       // Don't maintain aspect ratio
-                                    "--ontop",               // Keep on top initially
+                                    "--fs",            // Fullscreen
+                                    "--no-keepaspect", // Don't maintain aspect ratio
+                                    "--ontop",         // Keep on top initially
                                 ]);
                             }
                         }
                     }
-                } else {
-                    // Create new window manager
-                    match WindowManager::new().create_wallpaper_window() {
-                        Ok(window_hwnd) => {
-                            let hwnd_str = format!("{}", window_hwnd.0);
-                            cmd.args(&[
-                                "--wid", &hwnd_str,      // Embed in our window
-                                "--no-keepaspect-window", // Don't maintain aspect ratio in window
-                            ]);
-
-                            *wm_guard = Some(WindowManager::new());
-                            debug!("Created wallpaper window with HWND: {}", hwnd_str);
-                        }
-                        Err(e) => {
-                            warn!("Failed to create wallpaper window: {}. Using fullscreen mode instead.", e);
-                            cmd.args(&[
-                                "--fs",                  // Fullscreen
-                                "--no-keepaspect",       // Don't maintain aspect ratio
-                                "--ontop",               // Keep on top initially
-                            ]);
-                        }
-                    }
                 }
             }
         }
@@ -220,26 +423,157 @@ impl VideoWallpaper {
         {
             // On Linux, try to use fullscreen mode
             cmd.args(&[
-                "--fs",                  // Fullscreen
-                "--no-keepaspect",       // Don't maintain aspect ratio
+                "--fs",            // Fullscreen
+                "--no-keepaspect", // Don't maintain aspect ratio
             ]);
+            if let Some(region) = screen {
+                cmd.arg(format!("--fs-screen-name={}", region.monitor));
+            }
         }
 
         // Add the video file path
-        cmd.arg(self.path.to_str().ok_or_else(|| {
-            AppError::WallpaperError("Invalid video path".to_string())
-        })?);
+        cmd.arg(
+            self.path
+                .to_str()
+                .ok_or_else(|| AppError::WallpaperError("Invalid video path".to_string()))?,
+        );
 
         info!("Starting MPV with command: {:?}", cmd);
 
-        let child = cmd.spawn().map_err(|e| {
+        let handle = ProcessHandle::spawn(&mut cmd, "mpv", "video wallpaper").map_err(|e| {
             error!("Failed to start MPV process: {}", e);
-            AppError::WallpaperError(format!("Failed to start MPV: {}. Make sure MPV is installed and accessible.", e))
+            e
         })?;
 
-        info!("MPV process started successfully for video: {}", self.path.display());
-        Ok(child)
+        info!(
+            "MPV process started successfully for video: {}",
+            self.path.display()
+        );
+        Ok(MpvPlayer {
+            handle,
+            ipc_path,
+            crop,
+        })
+    }
+}
+
+/// Connect to an MPV IPC endpoint
+#[cfg(unix)]
+fn connect(ipc_path: &Path) -> std::io::Result<std::os::unix::net::UnixStream> {
+    let connection = std::os::unix::net::UnixStream::connect(ipc_path)?;
+    connection.set_read_timeout(Some(IPC_TIMEOUT))?;
+    Ok(connection)
+}
+
+/// Connect to an MPV IPC endpoint
+#[cfg(windows)]
+fn connect(ipc_path: &Path) -> std::io::Result<std::fs::File> {
+    std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(ipc_path)
+}
+
+/// Send a command to MPV over its JSON IPC
+fn send_command(ipc_path: &Path, command: serde_json::Value) -> AppResult<()> {
+    let mut line = serde_json::json!({ "command": command }).to_string();
+    line.push('\n');
+    connect(ipc_path)?.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// Read a property from MPV over its JSON IPC
+fn get_property(ipc_path: &Path, name: &str) -> AppResult<serde_json::Value> {
+    let mut line =
+        serde_json::json!({ "command": ["get_property", name], "request_id": 1 }).to_string();
+    line.push('\n');
+    let mut connection = connect(ipc_path)?;
+    connection.write_all(line.as_bytes())?;
+
+    // Events can arrive before the reply
+    for reply in BufReader::new(connection).lines() {
+        let reply: serde_json::Value = serde_json::from_str(&reply?)?;
+        if reply["request_id"] == 1 {
+            return match reply["error"].as_str() {
+                Some("success") => Ok(reply["data"].clone()),
+                error => Err(AppError::WallpaperError(format!(
+                    "MPV couldn't read {}: {}",
+                    name,
+                    error.unwrap_or("no reply")
+                ))),
+            };
+        }
     }
+    Err(AppError::WallpaperError(format!(
+        "MPV closed the connection before reading {}",
+        name
+    )))
+}
+
+/// Wait until MPV accepts IPC connections
+fn wait_for_ipc(ipc_path: &Path) -> bool {
+    let started = Instant::now();
+    while started.elapsed() < IPC_TIMEOUT {
+        if connect(ipc_path).is_ok() {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    false
+}
+
+/// Unpause spanned players together, then keep them showing the same moment
+///
+/// Each player loops the video on its own, so the others are moved to the
+/// first one's position whenever they drift apart, until `generation` changes.
+fn play_in_step(ipc_paths: Vec<PathBuf>, generation: Arc<AtomicU64>) {
+    let current = generation.load(Ordering::SeqCst);
+    thread::spawn(move || {
+        if !ipc_paths.iter().all(|path| wait_for_ipc(path)) {
+            warn!("Not every spanned MPV player opened its IPC endpoint; their timing may differ");
+        }
+        for path in &ipc_paths {
+            if let Err(e) = send_command(path, serde_json::json!(["set_property", "pause", false]))
+            {
+                warn!("Failed to start spanned MPV player: {}", e);
+            }
+        }
+
+        let Some((leader, followers)) = ipc_paths.split_first() else {
+            return;
+        };
+        loop {
+            thread::sleep(SYNC_INTERVAL);
+            if generation.load(Ordering::SeqCst) != current {
+                break;
+            }
+            let Some(position) = get_property(leader, "time-pos")
+                .ok()
+                .and_then(|value| value.as_f64())
+            else {
+                continue;
+            };
+            for follower in followers {
+                let Some(follower_position) = get_property(follower, "time-pos")
+                    .ok()
+                    .and_then(|value| value.as_f64())
+                else {
+                    continue;
+                };
+                if (follower_position - position).abs() > MAX_DRIFT {
+                    debug!(
+                        "Realigning spanned MPV player {:.2}s apart",
+                        follower_position - position
+                    );
+                    let _ = send_command(
+                        follower,
+                        serde_json::json!(["seek", position, "absolute+exact"]),
+                    );
+                }
+            }
+        }
+        debug!("Stopped keeping spanned MPV players in step");
+    });
 }
 
 #[async_trait]
@@ -247,11 +581,11 @@ impl super::Wallpaper for VideoWallpaper {
     fn get_type(&self) -> WallpaperType {
         WallpaperType::Video
     }
-    
+
     fn get_path(&self) -> Option<&Path> {
         Some(&self.path)
     }
-    
+
     async fn start(&self) -> AppResult<()> {
         debug!("Starting video wallpaper: {:?}", self.path);
 
@@ -266,14 +600,32 @@ impl super::Wallpaper for VideoWallpaper {
         // Stop any existing process
         self.stop().await?;
 
-        // Start MPV process
-        let child = self.start_mpv().await?;
+        // Start an MPV process, or one per monitor started together when spanning
+        let players = match (&self.monitor, self.span.then(SpanLayout::detect).flatten()) {
+            (Some(monitor), _) => vec![
+                self.start_mpv(Some(&span::monitor_region(monitor)?), None)
+                    .await?,
+            ],
+            (None, Some(layout)) => {
+                let mut players = Vec::new();
+                for region in &layout.regions {
+                    players.push(self.start_mpv(Some(region), Some(&layout)).await?);
+                }
+                play_in_step(
+                    players
+                        .iter()
+                        .map(|player| player.ipc_path.clone())
+                        .collect(),
+                    self.sync_generation.clone(),
+                );
+                info!("Spanning the video across {} monitors", players.len());
+                players
+            }
+            (None, None) => vec![self.start_mpv(None, None).await?],
+        };
 
-        // Store the process handle
-        {
-            let mut process = self.mpv_process.lock().await;
-            *process = Some(child);
-        }
+        // Store the process handles
+        *self.players.lock().await = players;
 
         // Update playing state
         {
@@ -281,37 +633,43 @@ impl super::Wallpaper for VideoWallpaper {
             *is_playing = true;
         }
 
-        // Notify the wallpaper manager that the video wallpaper has started
-        if let Err(e) = self.wallpaper_manager.set_video_wallpaper(&self.path).await {
-            warn!("Failed to notify wallpaper manager of video wallpaper: {}", e);
+        // Notify the wallpaper manager that the video wallpaper has started,
+        // unless it plays on one monitor beside other wallpapers
+        if self.monitor.is_none() {
+            if let Err(e) = self.wallpaper_manager.set_video_wallpaper(&self.path).await {
+                warn!(
+                    "Failed to notify wallpaper manager of video wallpaper: {}",
+                    e
+                );
+            }
         }
 
-        info!("Video wallpaper started successfully: {}", self.path.display());
+        info!(
+            "Video wallpaper started successfully: {}",
+            self.path.display()
+        );
         Ok(())
     }
-    
+
     async fn stop(&self) -> AppResult<()> {
         debug!("Stopping video wallpaper");
 
-        // Kill MPV process if running
-        {
-            let mut process = self.mpv_process.lock().await;
-            if let Some(mut child) = process.take() {
-                match child.kill() {
-                    Ok(_) => {
-                        debug!("MPV process terminated");
-                        // Wait for the process to actually exit
-                        let _ = child.wait();
-                    }
-                    Err(e) => {
-                        warn!("Failed to kill MPV process: {}", e);
-                        // Try to wait for it anyway
-                        let _ = child.wait();
-                    }
-                }
+        // Kill MPV processes if running
+        self.sync_generation.fetch_add(1, Ordering::SeqCst);
+        for player in self.players.lock().await.drain(..) {
+            // Waits for the process to actually exit
+            player.handle.kill();
+            debug!("MPV process terminated");
+
+            // MPV leaves its socket behind when it is killed
+            #[cfg(unix)]
+            {
+                let _ = std::fs::remove_file(&player.ipc_path);
             }
         }
 
+        *self.is_paused.lock().await = false;
+
         // Clean up window manager on Windows
         #[cfg(windows)]
         {
@@ -321,6 +679,11 @@ impl super::Wallpaper for VideoWallpaper {
                     warn!("Failed to hide wallpaper window: {}", e);
                 }
             }
+            for wm in self.span_windows.lock().await.drain(..) {
+                if let Err(e) = wm.hide_window() {
+                    warn!("Failed to hide wallpaper window: {}", e);
+                }
+            }
         }
 
         // Update playing state
@@ -329,46 +692,292 @@ impl super::Wallpaper for VideoWallpaper {
             *is_playing = false;
         }
 
-        // Notify the wallpaper manager that the video wallpaper has stopped
-        if let Err(e) = self.wallpaper_manager.stop_wallpaper().await {
-            warn!("Failed to notify wallpaper manager of stop: {}", e);
+        // Notify the wallpaper manager that the video wallpaper has stopped,
+        // unless that would clear the other monitors too
+        if self.monitor.is_none() {
+            if let Err(e) = self.wallpaper_manager.stop_wallpaper().await {
+                warn!("Failed to notify wallpaper manager of stop: {}", e);
+            }
         }
 
         info!("Video wallpaper stopped");
         Ok(())
     }
-    
+
     async fn pause(&self) -> AppResult<()> {
         debug!("Pausing video wallpaper");
-        
-        // For now, we'll implement pause by stopping the video
-        // A more sophisticated implementation would use MPV's IPC interface
-        {
-            let mut is_playing = self.is_playing.lock().await;
-            if *is_playing {
-                *is_playing = false;
-                info!("Video wallpaper paused (stopped)");
-            }
+
+        let mut is_paused = self.is_paused.lock().await;
+        if *is_paused || self.players.lock().await.is_empty() {
+            return Ok(());
         }
-        
+
+        self.send_to_all(serde_json::json!(["set_property", "pause", true]))
+            .await?;
+        *is_paused = true;
+        info!("Video wallpaper paused");
         Ok(())
     }
-    
+
     async fn resume(&self) -> AppResult<()> {
         debug!("Resuming video wallpaper");
-        
-        // For now, we'll implement resume by restarting the video
-        // A more sophisticated implementation would use MPV's IPC interface
-        let is_playing = {
-            let is_playing = self.is_playing.lock().await;
-            *is_playing
-        };
-        
-        if !is_playing {
+
+        // Restart the video if MPV isn't running
+        if self.players.lock().await.is_empty() {
             self.start().await?;
             info!("Video wallpaper resumed (restarted)");
+            return Ok(());
+        }
+
+        let mut is_paused = self.is_paused.lock().await;
+        if *is_paused {
+            self.send_to_all(serde_json::json!(["set_property", "pause", false]))
+                .await?;
+            *is_paused = false;
+            info!("Video wallpaper resumed");
         }
-        
         Ok(())
     }
-} 
\ No newline at end of file
+
+    async fn process_id(&self) -> Option<u32> {
+        self.players
+            .lock()
+            .await
+            .first()
+            .map(|player| player.handle.id())
+    }
+
+    async fn crashed(&self) -> Option<AppError> {
+        let mut players = self.players.lock().await;
+        let (status, tracked) = players.iter_mut().find_map(|player| {
+            player
+                .handle
+                .exited()
+                .map(|status| (status, player.handle.is_tracked()))
+        })?;
+
+        // Players killed over their resource limits were unregistered first
+        if !tracked {
+            return None;
+        }
+        // The other monitors' players are no use without it
+        self.sync_generation.fetch_add(1, Ordering::SeqCst);
+        players.clear();
+        *self.is_playing.lock().await = false;
+        warn!("MPV exited unexpectedly: {}", status);
+        Some(AppError::ProcessDied {
+            process: "mpv".to_string(),
+            code: status.code(),
+        })
+    }
+
+    async fn set_reduced_quality(&self, quality: Option<ReducedQuality>) -> AppResult<()> {
+        let mut reduced_quality = self.reduced_quality.lock().await;
+        if *reduced_quality == quality {
+            return Ok(());
+        }
+
+        for player in self.players.lock().await.iter() {
+            let filters = Self::player_filters(player.crop.as_deref(), self.max_fps, quality);
+            send_command(
+                &player.ipc_path,
+                serde_json::json!(["set_property", "vf", filters]),
+            )?;
+        }
+        *reduced_quality = quality;
+        info!(
+            "Video wallpaper quality {}",
+            if quality.is_some() {
+                "lowered"
+            } else {
+                "restored"
+            }
+        );
+        Ok(())
+    }
+
+    async fn capture_frame(&self, path: &Path) -> AppResult<()> {
+        debug!("Capturing video frame to {}", path.display());
+
+        // A spanned video is captured from the first monitor's player
+        let Some(ipc_path) = self
+            .players
+            .lock()
+            .await
+            .first()
+            .map(|player| player.ipc_path.clone())
+        else {
+            return Err(AppError::WallpaperError(
+                "Video wallpaper is not running".to_string(),
+            ));
+        };
+
+        let _ = std::fs::remove_file(path);
+        let target = path
+            .to_str()
+            .ok_or_else(|| AppError::WallpaperError("Invalid frame path".to_string()))?;
+        send_command(
+            &ipc_path,
+            serde_json::json!(["screenshot-to-file", target, "video"]),
+        )?;
+
+        // MPV writes the screenshot in the background
+        let started = Instant::now();
+        while started.elapsed() < FRAME_TIMEOUT {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            if std::fs::metadata(path).map_or(false, |metadata| metadata.len() > 0) {
+                // Give MPV time to finish writing the file
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                info!("Captured video frame to {}", path.display());
+                return Ok(());
+            }
+        }
+
+        Err(AppError::WallpaperError(
+            "MPV didn't save a frame in time".to_string(),
+        ))
+    }
+
+    async fn set_overlay(&self, overlay: Option<&RgbaImage>) -> AppResult<()> {
+        // MPV maps overlay files into memory, which it only does on Unix
+        if cfg!(windows) {
+            return Err(AppError::WallpaperError(
+                "MPV can't show overlays on Windows".to_string(),
+            ));
+        }
+        let Some(overlay) = overlay else {
+            return self
+                .send_to_all(serde_json::json!(["overlay-remove", OVERLAY_ID]))
+                .await;
+        };
+
+        // MPV reads the file it was last given until the next one is added, so
+        // the frame is written to the other of two files
+        let shown = self.overlays_shown.fetch_add(1, Ordering::Relaxed);
+        let path = std::env::temp_dir().join(format!(
+            "aether-desk-overlay-{}-{}.bgra",
+            std::process::id(),
+            shown % 2
+        ));
+        let bytes = overlay_bytes(overlay);
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+        file.write_all(&bytes)?;
+        file.set_len(bytes.len() as u64)?;
+
+        let target = path
+            .to_str()
+            .ok_or_else(|| AppError::WallpaperError("Invalid overlay path".to_string()))?;
+        let (width, height) = overlay.dimensions();
+        self.send_to_all(serde_json::json!([
+            "overlay-add",
+            OVERLAY_ID,
+            0,
+            0,
+            target,
+            0,
+            "bgra",
+            width,
+            height,
+            width * 4
+        ]))
+        .await
+    }
+}
+
+/// Get an image as the premultiplied BGRA bytes MPV overlays are made of
+fn overlay_bytes(image: &RgbaImage) -> Vec<u8> {
+    image
+        .pixels()
+        .flat_map(|pixel| {
+            let premultiply = |channel: u8| (channel as u16 * pixel[3] as u16 / 255) as u8;
+            [
+                premultiply(pixel[2]),
+                premultiply(pixel[1]),
+                premultiply(pixel[0]),
+                pixel[3],
+            ]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_player_filters_crop_first() {
+        let crop = "crop=w=1920:h=1080:x=1920:y=0";
+        assert_eq!(
+            VideoWallpaper::player_filters(None, Some(30), None),
+            "fps=fps=30"
+        );
+        assert_eq!(VideoWallpaper::player_filters(Some(crop), None, None), crop);
+        assert_eq!(
+            VideoWallpaper::player_filters(
+                Some(crop),
+                None,
+                Some(ReducedQuality {
+                    max_fps: 15,
+                    scale: 0.5
+                })
+            ),
+            "crop=w=1920:h=1080:x=1920:y=0,fps=fps=15,scale=w=trunc(iw*0.5/2)*2:h=-2"
+        );
+    }
+
+    #[test]
+    fn test_video_filters() {
+        assert_eq!(VideoWallpaper::video_filters(None, None), "");
+        assert_eq!(
+            VideoWallpaper::video_filters(
+                None,
+                Some(ReducedQuality {
+                    max_fps: 15,
+                    scale: 0.5
+                })
+            ),
+            "fps=fps=15,scale=w=trunc(iw*0.5/2)*2:h=-2"
+        );
+        assert_eq!(
+            VideoWallpaper::video_filters(
+                None,
+                Some(ReducedQuality {
+                    max_fps: 0,
+                    scale: 2.0
+                })
+            ),
+            "fps=fps=1,scale=w=trunc(iw*1/2)*2:h=-2"
+        );
+        assert_eq!(VideoWallpaper::video_filters(Some(30), None), "fps=fps=30");
+        // The lower of the limit and the lowered quality wins
+        assert_eq!(
+            VideoWallpaper::video_filters(
+                Some(10),
+                Some(ReducedQuality {
+                    max_fps: 15,
+                    scale: 0.5
+                })
+            ),
+            "fps=fps=10,scale=w=trunc(iw*0.5/2)*2:h=-2"
+        );
+    }
+
+    #[test]
+    fn test_overlay_bytes() {
+        let overlay = RgbaImage::from_fn(2, 1, |x, _| {
+            if x == 0 {
+                image::Rgba([255, 0, 0, 255])
+            } else {
+                image::Rgba([200, 100, 50, 128])
+            }
+        });
+        assert_eq!(
+            overlay_bytes(&overlay),
+            vec![0, 0, 255, 255, 25, 50, 100, 128]
+        );
+    }
+}
@@ -1,26 +1,29 @@
-use crate::core::{AppError, AppResult, WallpaperType};
+use crate::core::{AppResult, PlayerSlot, WallpaperType};
 use crate::platform::WallpaperManager;
-use log::{debug, error, info};
+use async_trait::async_trait;
+use log::{debug, info};
 use std::path::Path;
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use async_trait::async_trait;
 
 /// Web wallpaper
 pub struct WebWallpaper {
     /// Web URL
     url: String,
-    
+
     /// Platform-specific wallpaper manager
     wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>,
-    
+
     /// Whether the web wallpaper is active
     is_active: Arc<Mutex<bool>>,
 }
 
 impl WebWallpaper {
     /// Create a new web wallpaper
-    pub fn new<S: Into<String>>(url: S, wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>) -> Self {
+    pub fn new<S: Into<String>>(
+        url: S,
+        wallpaper_manager: Arc<dyn WallpaperManager + Send + Sync>,
+    ) -> Self {
         Self {
             url: url.into(),
             wallpaper_manager,
@@ -34,52 +37,59 @@ impl super::Wallpaper for WebWallpaper {
     fn get_type(&self) -> WallpaperType {
         WallpaperType::Web
     }
-    
+
     fn get_path(&self) -> Option<&Path> {
         None
     }
-    
+
     async fn start(&self) -> AppResult<()> {
         debug!("Starting web wallpaper: {}", self.url);
-        
+
         // Set the wallpaper using the platform-specific manager
         self.wallpaper_manager.set_web_wallpaper(&self.url).await?;
-        
+
         // Update active state
         let mut is_active = self.is_active.lock().await;
         *is_active = true;
-        
+
         info!("Web wallpaper started");
         Ok(())
     }
-    
+
     async fn stop(&self) -> AppResult<()> {
         debug!("Stopping web wallpaper");
-        
+
         // Stop the wallpaper using the platform-specific manager
         self.wallpaper_manager.stop_wallpaper().await?;
-        
+
         // Update active state
         let mut is_active = self.is_active.lock().await;
         *is_active = false;
-        
+
         info!("Web wallpaper stopped");
         Ok(())
     }
-    
+
     async fn pause(&self) -> AppResult<()> {
         debug!("Pausing web wallpaper");
-        
-        // TODO: Implement web wallpaper pausing
-        error!("Web wallpaper pausing not implemented yet");
-        Err(AppError::WallpaperError("Web wallpaper pausing not implemented yet".to_string()))
+
+        // The platform manager owns the player process
+        self.wallpaper_manager.pause_wallpaper().await?;
+
+        info!("Web wallpaper paused");
+        Ok(())
     }
-    
+
     async fn resume(&self) -> AppResult<()> {
         debug!("Resuming web wallpaper");
-        
-        // TODO: Implement web wallpaper resuming
-        error!("Web wallpaper resuming not implemented yet");
-        Err(AppError::WallpaperError("Web wallpaper resuming not implemented yet".to_string()))
+
+        self.wallpaper_manager.resume_wallpaper().await?;
+
+        info!("Web wallpaper resumed");
+        Ok(())
     }
-} 
\ No newline at end of file
+
+    async fn process_id(&self) -> Option<u32> {
+        self.wallpaper_manager.player().and_then(PlayerSlot::pid)
+    }
+}
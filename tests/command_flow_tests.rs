@@ -0,0 +1,139 @@
+//! Gallery and command queue tests against a daemon on the mock wallpaper manager
+mod common;
+
+use aether_desk::core::config::FitMode;
+use aether_desk::core::WallpaperType;
+use aether_desk::daemon::DaemonClient;
+use aether_desk::platform::mock::MockCall;
+use aether_desk::ui::commands::{Command, CommandEvent, CommandQueue, Intent};
+use aether_desk::ui::gallery::{GalleryItem, GalleryView};
+use eframe::egui;
+use serial_test::serial;
+
+/// Wait for the next finished command
+fn next_event(queue: &CommandQueue) -> CommandEvent {
+    let mut events = Vec::new();
+    common::wait_for("a command to finish", || {
+        events.extend(queue.poll());
+        !events.is_empty()
+    });
+    events.remove(0)
+}
+
+#[test]
+#[serial]
+fn test_gallery_apply_reaches_daemon() {
+    common::isolate();
+    let mock = common::daemon();
+    let queue = CommandQueue::new(DaemonClient::new());
+    let path = common::image_fixture("lake");
+
+    let mut gallery = GalleryView::new(queue.sender());
+    gallery.add_wallpaper(GalleryItem::from_path(path.clone(), WallpaperType::Static));
+    gallery.select_wallpaper(0);
+    gallery
+        .apply_selected_wallpaper(&egui::Context::default())
+        .unwrap();
+
+    let event = next_event(&queue);
+    assert!(event.result.is_ok(), "Apply failed: {:?}", event.result);
+    assert!(!queue.is_busy());
+    assert_eq!(
+        mock.last_call(),
+        Some(MockCall::Static(path.clone(), FitMode::Fill))
+    );
+
+    let status = DaemonClient::new().status().unwrap();
+    assert_eq!(
+        status
+            .current_wallpaper
+            .and_then(|wallpaper| wallpaper.path),
+        Some(path)
+    );
+    assert_eq!(status.backend, "Mock");
+}
+
+#[test]
+#[serial]
+fn test_failed_apply_reports_error() {
+    common::isolate();
+    let mock = common::daemon();
+    let queue = CommandQueue::new(DaemonClient::new());
+
+    mock.fail_with(Some("no display"));
+    queue.send(
+        Command::ApplyWallpaper {
+            wallpaper: common::static_wallpaper("desert"),
+            intent: Intent::Keep,
+        },
+        &egui::Context::default(),
+    );
+    let event = next_event(&queue);
+    mock.fail_with(None);
+
+    let error = event.result.unwrap_err();
+    assert!(
+        error.to_string().contains("no display"),
+        "Unexpected error: {}",
+        error
+    );
+}
+
+#[test]
+#[serial]
+fn test_commands_run_in_order() {
+    common::isolate();
+    let mock = common::daemon();
+    let queue = CommandQueue::new(DaemonClient::new());
+    let ctx = egui::Context::default();
+    let forest = common::static_wallpaper("forest");
+    let web = common::web_wallpaper("https://example.com/clock");
+
+    queue.send(
+        Command::ApplyWallpaper {
+            wallpaper: forest.clone(),
+            intent: Intent::Keep,
+        },
+        &ctx,
+    );
+    queue.send(
+        Command::ApplyWallpaper {
+            wallpaper: web,
+            intent: Intent::Keep,
+        },
+        &ctx,
+    );
+    queue.send(Command::UndoWallpaper, &ctx);
+    for _ in 0..3 {
+        let event = next_event(&queue);
+        assert!(
+            event.result.is_ok(),
+            "{:?} failed: {:?}",
+            event.command,
+            event.result
+        );
+    }
+    assert_eq!(
+        mock.last_call(),
+        Some(MockCall::Static(
+            forest.path.clone().unwrap(),
+            FitMode::Fill
+        ))
+    );
+    let current = DaemonClient::new()
+        .status()
+        .unwrap()
+        .current
+        .expect("No current wallpaper");
+    assert_eq!(current.wallpaper.path, forest.path);
+
+    queue.send(
+        Command::StopWallpaper {
+            intent: Intent::Keep,
+        },
+        &ctx,
+    );
+    assert!(next_event(&queue).result.is_ok());
+    assert_eq!(mock.last_call(), Some(MockCall::Stop));
+    assert!(DaemonClient::new().status().unwrap().current.is_none());
+}
@@ -0,0 +1,121 @@
+//! Shared setup for the integration tests
+//!
+//! Every test binary gets its own config and runtime directories, so tests
+//! never touch the user's configuration or talk to a running daemon. The app
+//! keeps process-wide state (history, current wallpaper, event log), so tests
+//! that change it are marked `#[serial]`.
+#![allow(dead_code)]
+
+use aether_desk::core::{Config, ScheduleItem, TriggerType, WallpaperInfo, WallpaperType};
+use aether_desk::daemon::{Daemon, DaemonClient};
+use aether_desk::platform::mock::MockWallpaperManager;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+/// Temporary home of the test binary
+fn home() -> &'static Path {
+    static HOME: OnceLock<TempDir> = OnceLock::new();
+    HOME.get_or_init(|| {
+        let home = TempDir::new().expect("Failed to create test directory");
+        std::env::set_var("XDG_CONFIG_HOME", home.path().join("config"));
+        std::env::set_var("XDG_RUNTIME_DIR", home.path().join("run"));
+        std::fs::create_dir_all(home.path().join("config")).unwrap();
+        std::fs::create_dir_all(home.path().join("run")).unwrap();
+        home
+    })
+    .path()
+}
+
+/// Point the config and runtime directories of the test binary at a temporary directory
+pub fn isolate() {
+    home();
+}
+
+/// Get the configuration directory of the test binary
+pub fn config_dir() -> PathBuf {
+    home();
+    Config::get_config_dir().expect("No config directory")
+}
+
+/// Write a small PNG and get its path
+pub fn image_fixture(name: &str) -> PathBuf {
+    let path = home().join(format!("{}.png", name));
+    if !path.exists() {
+        image::RgbaImage::from_pixel(8, 4, image::Rgba([40, 90, 160, 255]))
+            .save(&path)
+            .expect("Failed to write image fixture");
+    }
+    path
+}
+
+/// Write a small Shadertoy shader and get its path
+pub fn shader_fixture(name: &str) -> PathBuf {
+    let path = home().join(format!("{}.frag", name));
+    if !path.exists() {
+        std::fs::write(&path, "void mainImage(out vec4 fragColor, in vec2 fragCoord) {\n    fragColor = vec4(fragCoord / iResolution.xy, 0.5, 1.0);\n}\n")
+            .expect("Failed to write shader fixture");
+    }
+    path
+}
+
+/// Describe a static wallpaper from an image fixture
+pub fn static_wallpaper(name: &str) -> WallpaperInfo {
+    WallpaperInfo::new(WallpaperType::Static, Some(image_fixture(name)), None)
+}
+
+/// Describe a web wallpaper
+pub fn web_wallpaper(url: &str) -> WallpaperInfo {
+    WallpaperInfo::new(WallpaperType::Web, None, Some(url.to_string()))
+}
+
+/// Make a schedule item changing to `wallpaper` every hour
+pub fn hourly_item(wallpaper: WallpaperInfo) -> ScheduleItem {
+    ScheduleItem {
+        trigger: TriggerType::Interval(chrono::Duration::hours(1)),
+        wallpaper,
+        enabled: true,
+    }
+}
+
+/// Get the mock behind the daemon of the test binary, starting it first
+///
+/// The daemon runs until the test binary exits; stopping it would wait for
+/// the scheduler's next minute tick.
+pub fn daemon() -> Arc<MockWallpaperManager> {
+    static DAEMON: OnceLock<Arc<MockWallpaperManager>> = OnceLock::new();
+    DAEMON
+        .get_or_init(|| {
+            // Without a schedule the default one would change the wallpaper on its own
+            std::fs::write(config_dir().join("schedule.json"), "[]")
+                .expect("Failed to write empty schedule");
+
+            let mock = Arc::new(MockWallpaperManager::new());
+            let manager = mock.clone();
+            thread::spawn(move || {
+                if let Err(e) = Daemon::new(manager).run() {
+                    panic!("Test daemon failed: {}", e);
+                }
+            });
+
+            let client = DaemonClient::new();
+            let deadline = Instant::now() + Duration::from_secs(10);
+            while !client.is_running() {
+                assert!(Instant::now() < deadline, "Test daemon didn't start");
+                thread::sleep(Duration::from_millis(20));
+            }
+            mock
+        })
+        .clone()
+}
+
+/// Wait until `check` holds, failing the test after a few seconds
+pub fn wait_for(what: &str, mut check: impl FnMut() -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !check() {
+        assert!(Instant::now() < deadline, "Timed out waiting for {}", what);
+        thread::sleep(Duration::from_millis(10));
+    }
+}
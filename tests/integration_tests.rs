@@ -1,4 +1,4 @@
-use aether_desk::core::{Config, WallpaperType, AppResult};
+use aether_desk::core::{AppResult, Config, WallpaperType};
 use std::path::PathBuf;
 use tempfile::TempDir;
 
@@ -18,10 +18,17 @@ mod tests {
     fn test_config_serialization() {
         let config = Config::default();
         let json = serde_json::to_string(&config).expect("Failed to serialize config");
-        let deserialized: Config = serde_json::from_str(&json).expect("Failed to deserialize config");
-        
-        assert_eq!(config.wallpaper.wallpaper_type, deserialized.wallpaper.wallpaper_type);
-        assert_eq!(config.app.start_with_system, deserialized.app.start_with_system);
+        let deserialized: Config =
+            serde_json::from_str(&json).expect("Failed to deserialize config");
+
+        assert_eq!(
+            config.wallpaper.wallpaper_type,
+            deserialized.wallpaper.wallpaper_type
+        );
+        assert_eq!(
+            config.app.start_with_system,
+            deserialized.app.start_with_system
+        );
     }
 
     #[test]
@@ -35,8 +42,10 @@ mod tests {
         ];
 
         for wallpaper_type in types {
-            let json = serde_json::to_string(&wallpaper_type).expect("Failed to serialize wallpaper type");
-            let deserialized: WallpaperType = serde_json::from_str(&json).expect("Failed to deserialize wallpaper type");
+            let json =
+                serde_json::to_string(&wallpaper_type).expect("Failed to serialize wallpaper type");
+            let deserialized: WallpaperType =
+                serde_json::from_str(&json).expect("Failed to deserialize wallpaper type");
             assert_eq!(wallpaper_type, deserialized);
         }
     }
@@ -45,14 +54,14 @@ mod tests {
     async fn test_config_file_operations() {
         let temp_dir = TempDir::new().expect("Failed to create temp directory");
         let config_path = temp_dir.path().join("test_config.json");
-        
+
         let mut config = Config::default();
         config.app.start_with_system = true;
         config.wallpaper.wallpaper_type = WallpaperType::Video;
-        
+
         // This would need to be implemented in the Config struct
         // config.save_to_path(&config_path).expect("Failed to save config");
-        
+
         // let loaded_config = Config::load_from_path(&config_path).expect("Failed to load config");
         // assert_eq!(config.app.start_with_system, loaded_config.app.start_with_system);
         // assert_eq!(config.wallpaper.wallpaper_type, loaded_config.wallpaper.wallpaper_type);
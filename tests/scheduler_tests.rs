@@ -0,0 +1,411 @@
+//! Scheduler tests against the mock wallpaper manager
+mod common;
+
+use aether_desk::core::config::{FitMode, PlacementConfig, TransitionConfig, TransitionEffect};
+use aether_desk::core::{
+    AdjustmentStore, Adjustments, Config, CurrentWallpaperStore, WallpaperHistory, WallpaperInfo,
+    WallpaperScheduler, WallpaperType,
+};
+use aether_desk::platform::display;
+use aether_desk::platform::mock::{MockCall, MockWallpaperManager};
+use serial_test::serial;
+use std::sync::Arc;
+
+fn mock_scheduler() -> (Arc<MockWallpaperManager>, WallpaperScheduler) {
+    let mock = Arc::new(MockWallpaperManager::new());
+    let scheduler = WallpaperScheduler::new(mock.clone());
+    (mock, scheduler)
+}
+
+#[test]
+#[serial]
+fn test_apply_sets_wallpaper_and_state() {
+    common::isolate();
+    let (mock, scheduler) = mock_scheduler();
+    let wallpaper = common::static_wallpaper("forest");
+
+    scheduler.apply_now(&wallpaper).unwrap();
+    assert_eq!(
+        mock.calls(),
+        vec![MockCall::Static(
+            wallpaper.path.clone().unwrap(),
+            FitMode::Fill
+        )]
+    );
+
+    let state = CurrentWallpaperStore::shared()
+        .get()
+        .expect("No current wallpaper");
+    assert_eq!(state.wallpaper_type(), &WallpaperType::Static);
+    assert_eq!(state.wallpaper.path, wallpaper.path);
+    assert!(state.pid.is_none());
+    assert!(!scheduler.current_wallpaper().lock().unwrap().is_empty());
+}
+
+#[test]
+#[serial]
+fn test_apply_converts_images_the_backend_cannot_read() {
+    common::isolate();
+    let (mock, scheduler) = mock_scheduler();
+    let bitmap = common::image_fixture("dunes").with_extension("bmp");
+    image::open(common::image_fixture("dunes"))
+        .unwrap()
+        .save(&bitmap)
+        .unwrap();
+    let wallpaper = WallpaperInfo::new(WallpaperType::Static, Some(bitmap.clone()), None);
+
+    scheduler.apply_now(&wallpaper).unwrap();
+    let calls = mock.calls();
+    let [MockCall::Static(converted, FitMode::Fill)] = &calls[..] else {
+        panic!("Unexpected calls: {:?}", calls)
+    };
+    assert!(converted.starts_with(Config::get_converted_dir()));
+    assert_eq!(image::image_dimensions(converted).unwrap(), (8, 4));
+
+    // The wallpaper is still the original file
+    assert_eq!(
+        CurrentWallpaperStore::shared()
+            .get()
+            .unwrap()
+            .wallpaper
+            .path,
+        Some(bitmap)
+    );
+}
+
+#[test]
+#[serial]
+fn test_apply_shows_adjusted_copy() {
+    common::isolate();
+    let (mock, scheduler) = mock_scheduler();
+    let wallpaper = common::static_wallpaper("meadow");
+    let path = wallpaper.path.clone().unwrap();
+    AdjustmentStore::shared().set(
+        &path,
+        Adjustments {
+            brightness: -0.5,
+            ..Default::default()
+        },
+    );
+
+    let result = scheduler.apply_now(&wallpaper);
+    AdjustmentStore::shared().set(&path, Adjustments::default());
+    result.unwrap();
+    let calls = mock.calls();
+    let [MockCall::Static(shown, FitMode::Fill)] = &calls[..] else {
+        panic!("Unexpected calls: {:?}", calls)
+    };
+    assert!(shown.starts_with(Config::get_adjusted_dir()));
+    assert!(image::open(shown).unwrap().to_rgba8().get_pixel(0, 0)[2] < 160);
+}
+
+#[test]
+#[serial]
+fn test_replacing_wallpaper_stops_previous_one() {
+    common::isolate();
+    let (mock, scheduler) = mock_scheduler();
+    let web = common::web_wallpaper("https://example.com/clock");
+
+    scheduler
+        .apply_now(&common::static_wallpaper("forest"))
+        .unwrap();
+    mock.clear_calls();
+    scheduler.apply_now(&web).unwrap();
+    assert_eq!(
+        mock.calls(),
+        vec![
+            MockCall::Stop,
+            MockCall::Web("https://example.com/clock".to_string())
+        ]
+    );
+    assert_eq!(
+        CurrentWallpaperStore::shared()
+            .get()
+            .unwrap()
+            .source()
+            .as_deref(),
+        Some("https://example.com/clock")
+    );
+}
+
+#[test]
+#[serial]
+fn test_lock_screen_follows_static_wallpapers() {
+    common::isolate();
+    let (mock, scheduler) = mock_scheduler();
+    let forest = common::static_wallpaper("forest");
+    scheduler.set_lock_screen_sync(true);
+
+    scheduler.apply_now(&forest).unwrap();
+    let path = forest.path.clone().unwrap();
+    assert_eq!(
+        mock.calls(),
+        vec![
+            MockCall::Static(path.clone(), FitMode::Fill),
+            MockCall::LockScreen(path)
+        ]
+    );
+
+    // Animated wallpapers leave the lock screen alone
+    mock.clear_calls();
+    scheduler
+        .apply_now(&common::web_wallpaper("https://example.com/clock"))
+        .unwrap();
+    assert!(!mock
+        .calls()
+        .iter()
+        .any(|call| matches!(call, MockCall::LockScreen(_))));
+}
+
+#[test]
+#[serial]
+fn test_transition_shows_frames_before_new_wallpaper() {
+    common::isolate();
+    let (mock, scheduler) = mock_scheduler();
+    scheduler.set_transition(TransitionConfig {
+        effect: TransitionEffect::Crossfade,
+        duration_ms: 200,
+    });
+    scheduler
+        .apply_now(&common::static_wallpaper("forest"))
+        .unwrap();
+
+    mock.clear_calls();
+    let desert = common::static_wallpaper("desert");
+    scheduler.apply_now(&desert).unwrap();
+    let calls = mock.calls();
+    assert!(
+        matches!(&calls[..], [MockCall::Stop, MockCall::Static(frame, _), MockCall::Static(..)]
+        if frame.file_name().unwrap().to_string_lossy().starts_with("frame-")),
+        "Unexpected calls: {:?}",
+        calls
+    );
+    assert_eq!(
+        calls.last(),
+        Some(&MockCall::Static(desert.path.unwrap(), FitMode::Fill))
+    );
+}
+
+#[test]
+#[serial]
+fn test_failed_apply_keeps_state() {
+    common::isolate();
+    let (mock, scheduler) = mock_scheduler();
+    let forest = common::static_wallpaper("forest");
+    scheduler.apply_now(&forest).unwrap();
+
+    mock.fail_with(Some("no display"));
+    let error = scheduler
+        .apply_now(&common::static_wallpaper("desert"))
+        .unwrap_err();
+    assert!(
+        error.to_string().contains("no display"),
+        "Unexpected error: {}",
+        error
+    );
+    assert_eq!(CurrentWallpaperStore::shared().path(), forest.path);
+}
+
+#[test]
+#[serial]
+fn test_missing_path_is_rejected() {
+    common::isolate();
+    let (mock, scheduler) = mock_scheduler();
+    let mut wallpaper = common::static_wallpaper("forest");
+    wallpaper.path = None;
+
+    assert!(scheduler.apply_now(&wallpaper).is_err());
+    assert!(mock.calls().is_empty());
+}
+
+#[test]
+#[serial]
+fn test_stop_clears_state() {
+    common::isolate();
+    let (mock, scheduler) = mock_scheduler();
+    scheduler
+        .apply_now(&common::static_wallpaper("forest"))
+        .unwrap();
+
+    scheduler.stop_current().unwrap();
+    assert_eq!(mock.last_call(), Some(MockCall::Stop));
+    assert!(CurrentWallpaperStore::shared().get().is_none());
+    assert!(scheduler.current_wallpaper().lock().unwrap().is_empty());
+}
+
+/// Get the names of two monitors to put wallpapers on
+///
+/// Without monitor detection any names are taken; with fewer than two
+/// monitors connected there is nothing to test.
+fn two_monitors() -> Option<(String, String)> {
+    match display::monitors() {
+        Ok(monitors) if monitors.len() >= 2 => {
+            Some((monitors[0].id.clone(), monitors[1].id.clone()))
+        }
+        Ok(_) => None,
+        Err(_) => Some(("DP-1".to_string(), "DP-2".to_string())),
+    }
+}
+
+#[test]
+#[serial]
+fn test_wallpapers_per_monitor_run_side_by_side() {
+    common::isolate();
+    let Some((left, right)) = two_monitors() else {
+        return;
+    };
+    let (mock, scheduler) = mock_scheduler();
+    let waves = common::shader_fixture("waves");
+    let shader = WallpaperInfo::new(WallpaperType::Shader, Some(waves.clone()), None);
+
+    scheduler
+        .apply_now(&shader.clone().on_monitor(Some(left.clone())))
+        .unwrap();
+    scheduler
+        .apply_now(&shader.clone().on_monitor(Some(right.clone())))
+        .unwrap();
+    let calls = mock.calls();
+    assert!(
+        matches!(&calls[..], [MockCall::ShaderOn(path, None, _), MockCall::ShaderOn(_, None, _)] if *path == waves),
+        "Unexpected calls: {:?}",
+        calls
+    );
+    assert_eq!(CurrentWallpaperStore::shared().per_monitor().len(), 2);
+
+    // Replacing one monitor's wallpaper leaves the other running
+    mock.clear_calls();
+    scheduler
+        .apply_now(&shader.clone().on_monitor(Some(left.clone())))
+        .unwrap();
+    assert!(matches!(
+        &mock.calls()[..],
+        [MockCall::StopOn(_), MockCall::ShaderOn(..)]
+    ));
+
+    scheduler.stop_on(&right).unwrap();
+    assert_eq!(CurrentWallpaperStore::shared().per_monitor().len(), 1);
+
+    // A wallpaper on every monitor replaces them
+    mock.clear_calls();
+    scheduler
+        .apply_now(&common::web_wallpaper("https://example.com/clock"))
+        .unwrap();
+    assert!(matches!(
+        &mock.calls()[..],
+        [MockCall::StopOn(_), MockCall::Web(_)]
+    ));
+    assert!(CurrentWallpaperStore::shared().per_monitor().is_empty());
+}
+
+#[test]
+#[serial]
+fn test_mirroring_shows_wallpaper_on_every_monitor() {
+    common::isolate();
+    let (mock, scheduler) = mock_scheduler();
+    let mut placement = PlacementConfig {
+        mirror: true,
+        span: true,
+        ..PlacementConfig::default()
+    };
+    placement
+        .monitor_images
+        .insert("DP-1".to_string(), "desert.png".to_string());
+    scheduler.set_placement(placement);
+
+    // Neither the target monitor nor the monitors' own images apply
+    let forest = common::static_wallpaper("forest");
+    scheduler
+        .apply_now(&forest.clone().on_monitor(Some("DP-9".to_string())))
+        .unwrap();
+    assert_eq!(
+        mock.calls(),
+        vec![MockCall::Static(
+            forest.path.clone().unwrap(),
+            FitMode::Fill
+        )]
+    );
+    assert_eq!(
+        CurrentWallpaperStore::shared()
+            .get()
+            .unwrap()
+            .wallpaper
+            .monitor,
+        None
+    );
+}
+
+#[test]
+#[serial]
+fn test_web_wallpaper_is_not_shown_on_one_monitor() {
+    common::isolate();
+    let (mock, scheduler) = mock_scheduler();
+    let web =
+        common::web_wallpaper("https://example.com/clock").on_monitor(Some("DP-1".to_string()));
+
+    let error = scheduler.apply_now(&web).unwrap_err();
+    assert!(
+        error.to_string().contains("single monitor"),
+        "Unexpected error: {}",
+        error
+    );
+    assert!(mock.calls().is_empty());
+}
+
+#[test]
+#[serial]
+fn test_undo_and_redo_reapply_wallpapers() {
+    common::isolate();
+    let (mock, scheduler) = mock_scheduler();
+    let forest = common::static_wallpaper("forest");
+    let desert = common::static_wallpaper("desert");
+    scheduler.apply_now(&forest).unwrap();
+    scheduler.apply_now(&desert).unwrap();
+
+    let history = WallpaperHistory::shared();
+    let undone = history
+        .undo(|wallpaper| scheduler.apply_now(wallpaper))
+        .unwrap()
+        .unwrap();
+    assert_eq!(undone.path, forest.path);
+    assert_eq!(
+        mock.last_call(),
+        Some(MockCall::Static(
+            forest.path.clone().unwrap(),
+            FitMode::Fill
+        ))
+    );
+    assert_eq!(CurrentWallpaperStore::shared().path(), forest.path);
+
+    let redone = history
+        .redo(|wallpaper| scheduler.apply_now(wallpaper))
+        .unwrap()
+        .unwrap();
+    assert_eq!(redone.path, desert.path);
+    assert_eq!(CurrentWallpaperStore::shared().path(), desert.path);
+}
+
+#[test]
+#[serial]
+fn test_schedule_round_trip() {
+    common::isolate();
+    let (_, scheduler) = mock_scheduler();
+    let config = Config::default();
+    scheduler
+        .add_schedule_item(common::hourly_item(common::static_wallpaper("forest")))
+        .unwrap();
+    scheduler
+        .add_schedule_item(common::hourly_item(common::web_wallpaper(
+            "https://example.com/clock",
+        )))
+        .unwrap();
+    scheduler.save_schedule(&config).unwrap();
+    assert!(config.get_schedule_file().starts_with(common::config_dir()));
+
+    let (_, mut loaded) = mock_scheduler();
+    loaded.load_schedule(&config).unwrap();
+    assert_eq!(loaded.get_schedule_items().len(), 2);
+    assert_eq!(
+        loaded.get_schedule_items()[1].wallpaper.url.as_deref(),
+        Some("https://example.com/clock")
+    );
+}